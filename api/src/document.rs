@@ -0,0 +1,281 @@
+use crate::error::ApiError;
+
+use editor::application::{generate_uuid, set_uuid_seed, UUID_SEED};
+use editor::messages::frontend::utility_types::ExportBounds;
+use editor::messages::portfolio::document::utility_types::svg_export::SvgExportOptions;
+use editor::messages::portfolio::document::DocumentMessageHandler;
+use editor::messages::portfolio::utility_types::PersistentData;
+use editor::messages::prelude::*;
+use graphene::color::Color;
+use graphene::layers::layer_info::Layer;
+use graphene::layers::style::{Fill, PathStyle};
+use graphene::layers::text_layer::Font;
+use graphene::layers::vector::subpath::Subpath;
+use graphene::{LayerId, Operation};
+
+use glam::{DAffine2, DVec2};
+
+/// A document edited without the editor's interface, which is rendered on request instead of after every change.
+pub struct Document {
+	handler: DocumentMessageHandler,
+	persistent_data: PersistentData,
+	input: InputPreprocessorMessageHandler,
+	preferences: PreferencesMessageHandler,
+}
+
+impl Default for Document {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Document {
+	/// Creates an empty document on an infinite canvas.
+	pub fn new() -> Self {
+		seed_layer_ids();
+
+		let input = InputPreprocessorMessageHandler::default();
+		Self {
+			handler: DocumentMessageHandler::with_name("Untitled Document".into(), &input),
+			persistent_data: PersistentData::default(),
+			input,
+			preferences: PreferencesMessageHandler::default(),
+		}
+	}
+
+	/// Opens the content of a `.graphite` file.
+	pub fn open(content: &[u8]) -> Result<Self, ApiError> {
+		seed_layer_ids();
+
+		let handler = DocumentMessageHandler::with_name_and_content("Untitled Document".into(), content.to_vec()).map_err(|error| ApiError::InvalidDocument(error.to_string()))?;
+		Ok(Self { handler, ..Self::new() })
+	}
+
+	/// The content of the document as it's saved in a `.graphite` file.
+	pub fn save(&self) -> String {
+		self.handler.serialize_document()
+	}
+
+	/// The editor's handler of the document, for the parts of it not covered by this API.
+	pub fn handler(&self) -> &DocumentMessageHandler {
+		&self.handler
+	}
+
+	/// Loads a font for text layers, which is used for text whose font isn't loaded or embedded in the document if it's the default.
+	pub fn load_font(&mut self, font_family: &str, font_style: &str, data: Vec<u8>, is_default: bool) {
+		let font = Font::new(font_family.into(), font_style.into());
+		self.persistent_data.font_cache.insert(font, String::new(), data, is_default);
+	}
+
+	/// Applies an operation to the document, which can be undone.
+	pub fn apply(&mut self, operation: Operation) -> Result<(), ApiError> {
+		// The document only logs the errors of operations, so the layer they change is checked first
+		if let Some(path) = target_layer(&operation) {
+			self.handler.graphene_document.layer(path)?;
+		}
+
+		let mut responses = VecDeque::new();
+		self.handler.backup(&mut responses);
+		self.process(DocumentMessage::DispatchOperation(Box::new(operation)));
+
+		Ok(())
+	}
+
+	/// Reverts the last operation applied to the document.
+	pub fn undo(&mut self) {
+		let mut responses = VecDeque::new();
+		if self.handler.undo(&mut responses).is_err() {
+			warn!("There is nothing to undo");
+		}
+	}
+
+	/// Applies the last operation that was undone again.
+	pub fn redo(&mut self) {
+		let mut responses = VecDeque::new();
+		if self.handler.redo(&mut responses).is_err() {
+			warn!("There is nothing to redo");
+		}
+	}
+
+	/// Adds a rectangle with the given top left corner and size to the top of the document, returning the path of the new layer.
+	pub fn add_rectangle(&mut self, corner: DVec2, size: DVec2, fill: Color) -> Vec<LayerId> {
+		let path = vec![generate_uuid()];
+		self.add_layer(Operation::AddRect {
+			path: path.clone(),
+			insert_index: -1,
+			transform: DAffine2::from_scale_angle_translation(size, 0., corner).to_cols_array(),
+			style: PathStyle::new(None, Fill::solid(fill)),
+		});
+		path
+	}
+
+	/// Adds an ellipse fitting the rectangle with the given top left corner and size to the top of the document, returning the path of the new layer.
+	pub fn add_ellipse(&mut self, corner: DVec2, size: DVec2, fill: Color) -> Vec<LayerId> {
+		let path = vec![generate_uuid()];
+		self.add_layer(Operation::AddEllipse {
+			path: path.clone(),
+			insert_index: -1,
+			transform: DAffine2::from_scale_angle_translation(size, 0., corner).to_cols_array(),
+			style: PathStyle::new(None, Fill::solid(fill)),
+		});
+		path
+	}
+
+	/// Adds a shape with the given outline and style to the top of the document, returning the path of the new layer.
+	pub fn add_shape(&mut self, subpath: Subpath, style: PathStyle) -> Vec<LayerId> {
+		let path = vec![generate_uuid()];
+		self.add_layer(Operation::AddShape {
+			path: path.clone(),
+			insert_index: -1,
+			transform: DAffine2::IDENTITY.to_cols_array(),
+			style,
+			subpath,
+		});
+		path
+	}
+
+	/// Adds text with its top left corner at the given position to the top of the document, returning the path of the new layer.
+	/// The text is set in the given font if it was loaded with [Document::load_font], or otherwise in the default font.
+	pub fn add_text(&mut self, text: &str, font: &Font, size: f64, position: DVec2, fill: Color) -> Vec<LayerId> {
+		let path = vec![generate_uuid()];
+		self.add_layer(Operation::AddText {
+			path: path.clone(),
+			insert_index: -1,
+			transform: DAffine2::from_translation(position).to_cols_array(),
+			style: PathStyle::new(None, Fill::solid(fill)),
+			text: text.into(),
+			size,
+			font_name: font.font_family.clone(),
+			font_style: font.font_style.clone(),
+		});
+		path
+	}
+
+	/// Adds an empty folder to the top of the document, returning its path to add layers inside of it.
+	pub fn add_folder(&mut self) -> Vec<LayerId> {
+		let path = vec![generate_uuid()];
+		self.add_layer(Operation::CreateFolder { path: path.clone() });
+		path
+	}
+
+	/// Removes a layer, along with its contents if it's a folder.
+	pub fn delete_layer(&mut self, path: &[LayerId]) -> Result<(), ApiError> {
+		self.apply(Operation::DeleteLayer { path: path.to_vec() })
+	}
+
+	/// The layer at the given path, to read its name, transform, style, and other properties.
+	pub fn layer(&self, path: &[LayerId]) -> Result<&Layer, ApiError> {
+		Ok(self.handler.graphene_document.layer(path)?)
+	}
+
+	/// The paths of all layers in the document from the bottom to the top, with folders before their contents.
+	pub fn layers(&self) -> Vec<Vec<LayerId>> {
+		self.handler.all_layers_sorted().into_iter().map(|path| path.to_vec()).collect()
+	}
+
+	/// The bounding box of all artwork in the document, or `None` if it's empty.
+	pub fn bounds(&self) -> Option<[DVec2; 2]> {
+		self.handler.document_bounds(&self.persistent_data.font_cache)
+	}
+
+	/// Renders all of the artwork as the SVG file exported by the editor.
+	pub fn render_svg(&mut self) -> String {
+		self.render_svg_with(ExportBounds::AllArtwork, SvgExportOptions::default())
+	}
+
+	/// Renders an area of the document as an SVG file written with the given options.
+	pub fn render_svg_with(&mut self, bounds: ExportBounds, options: SvgExportOptions) -> String {
+		self.handler.export_svg_file(bounds, false, false, false, options, &self.persistent_data.font_cache)
+	}
+
+	/// Renders all of the artwork as a PNG file with the given number of pixels per document unit.
+	pub fn render_png(&mut self, scale: f64) -> Result<Vec<u8>, ApiError> {
+		// The renderer only understands sRGB colors, so wide-gamut colors are converted
+		let (svg, size) = self.handler.export_srgb_svg(ExportBounds::AllArtwork, false, false, &self.persistent_data);
+
+		let size = (size * scale).round().max(DVec2::ONE).as_u32();
+		let tree = usvg::Tree::from_str(&svg, &usvg::Options::default().to_ref()).map_err(|error| ApiError::Render(error.to_string()))?;
+		let mut pixmap = tiny_skia::Pixmap::new(size.x, size.y).ok_or_else(|| ApiError::Render("The rendered image is too large".into()))?;
+		resvg::render(&tree, usvg::FitTo::Size(size.x, size.y), tiny_skia::Transform::default(), pixmap.as_mut()).ok_or_else(|| ApiError::Render("The renderer failed".into()))?;
+
+		pixmap.encode_png().map_err(|error| ApiError::Render(error.to_string()))
+	}
+
+	fn add_layer(&mut self, operation: Operation) {
+		if let Err(error) = self.apply(operation) {
+			error!("The layer could not be added: {}", error);
+		}
+	}
+
+	/// Processes a message about the document, dropping its responses since there is no interface to update.
+	fn process(&mut self, message: DocumentMessage) {
+		let mut responses = VecDeque::new();
+		self.handler.process_message(message, (0, &self.input, &self.persistent_data, &self.preferences), &mut responses);
+	}
+}
+
+/// Seeds the random layer IDs of the thread from the time unless [set_seed](crate::set_seed) was called.
+fn seed_layer_ids() {
+	if UUID_SEED.with(|seed| seed.get().is_none()) {
+		let time = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.map(|duration| duration.as_nanos() as u64)
+			.unwrap_or_default();
+		set_uuid_seed(time);
+	}
+}
+
+/// The layer that the operation changes or creates, if it has one.
+fn target_layer(operation: &Operation) -> Option<&[LayerId]> {
+	match operation {
+		Operation::DeleteLayer { path }
+		| Operation::SetLayerTransform { path, .. }
+		| Operation::SetLayerName { path, .. }
+		| Operation::SetLayerFill { path, .. }
+		| Operation::SetLayerStroke { path, .. }
+		| Operation::SetLayerOpacity { path, .. }
+		| Operation::SetLayerVisibility { path, .. } => (!path.is_empty()).then_some(path),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::set_seed;
+
+	#[test]
+	fn add_layers_and_render() {
+		set_seed(0);
+		let mut document = Document::new();
+
+		let rectangle = document.add_rectangle(DVec2::new(10., 20.), DVec2::new(100., 50.), Color::BLACK);
+		let ellipse = document.add_ellipse(DVec2::ZERO, DVec2::splat(30.), Color::WHITE);
+		assert_eq!(document.layers(), vec![rectangle.clone(), ellipse]);
+
+		let [min, max] = document.bounds().unwrap();
+		assert!(min.abs_diff_eq(DVec2::ZERO, 1e-9) && max.abs_diff_eq(DVec2::new(110., 70.), 1e-9));
+		assert!(document.render_svg().contains("<path"));
+
+		document.delete_layer(&rectangle).unwrap();
+		assert_eq!(document.layers().len(), 1);
+		assert!(document.delete_layer(&rectangle).is_err());
+
+		document.undo();
+		assert_eq!(document.layers().len(), 2);
+	}
+
+	#[test]
+	fn save_and_open() {
+		set_seed(0);
+		let mut document = Document::new();
+		let folder = document.add_folder();
+		document.add_rectangle(DVec2::ZERO, DVec2::ONE, Color::BLACK);
+
+		let opened = Document::open(document.save().as_bytes()).unwrap();
+		assert_eq!(opened.layers(), document.layers());
+		assert!(opened.layers().contains(&folder));
+
+		assert!(matches!(Document::open(b"not a document"), Err(ApiError::InvalidDocument(_))));
+	}
+}
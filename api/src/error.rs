@@ -0,0 +1,32 @@
+use graphene::DocumentError;
+use graphene_std::error::NodeError;
+
+use thiserror::Error;
+
+/// The ways driving Graphite through the API can fail.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ApiError {
+	#[error("The document could not be opened: {0}")]
+	InvalidDocument(String),
+
+	#[error("The operation could not be applied: {0:?}")]
+	Document(DocumentError),
+
+	#[error("The document could not be rendered: {0}")]
+	Render(String),
+
+	#[error("The node graph is invalid: {0}")]
+	NodeGraph(NodeError),
+}
+
+impl From<DocumentError> for ApiError {
+	fn from(error: DocumentError) -> Self {
+		ApiError::Document(error)
+	}
+}
+
+impl From<NodeError> for ApiError {
+	fn from(error: NodeError) -> Self {
+		ApiError::NodeGraph(error)
+	}
+}
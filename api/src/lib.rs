@@ -0,0 +1,40 @@
+//! Drives Graphite as a library, without the editor's interface, for test harnesses, automation scripts, and servers.
+//!
+//! A [Document] is created or opened, edited by adding layers and applying [Operation]s, and rendered to SVG or PNG.
+//! Node networks are built and checked with a [NodeGraph].
+//!
+//! ```no_run
+//! use graphite_api::{Color, DVec2, Document};
+//!
+//! let mut document = Document::new();
+//! document.add_rectangle(DVec2::ZERO, DVec2::new(100., 50.), Color::BLACK);
+//! std::fs::write("rectangle.svg", document.render_svg()).unwrap();
+//! ```
+
+// `macro_use` puts the log macros (`error!`, `warn!`, `debug!`, `info!` and `trace!`) in scope for the crate
+#[macro_use]
+extern crate log;
+
+pub mod document;
+pub mod error;
+pub mod node_graph;
+
+pub use document::Document;
+pub use error::ApiError;
+pub use node_graph::NodeGraph;
+
+pub use editor::messages::frontend::utility_types::ExportBounds;
+pub use editor::messages::portfolio::document::utility_types::svg_export::SvgExportOptions;
+pub use glam::{DAffine2, DVec2};
+pub use graphene::color::Color;
+pub use graphene::layers::layer_info::Layer;
+pub use graphene::layers::style::{Fill, PathStyle, Stroke};
+pub use graphene::layers::text_layer::Font;
+pub use graphene::layers::vector::subpath::Subpath;
+pub use graphene::{LayerId, Operation};
+
+/// Makes the IDs given to new layers the same every time the program runs, which keeps the output of tests reproducible.
+/// It has to be called before the first document is created, or else the IDs are seeded from the time of that.
+pub fn set_seed(seed: u64) {
+	editor::application::set_uuid_seed(seed);
+}
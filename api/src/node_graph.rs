@@ -0,0 +1,164 @@
+use crate::error::ApiError;
+
+use graphene_std::document::{DocumentNode, DocumentNodeImplementation, IntoValue, NodeInput, NodeNetwork, ProtoNode};
+use graphene_std::error::NodeError;
+
+/// Identifies a node within its network.
+pub type NodeId = u64;
+
+/// A node network built node by node, which is checked and resolved into proto nodes the same way as networks built in the node graph.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeGraph {
+	network: NodeNetwork,
+	/// The ID given to the next node that is added
+	next_id: NodeId,
+}
+
+impl NodeGraph {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Edits an existing network, like one taken from [NodeGraph::into_network].
+	pub fn from_network(network: NodeNetwork) -> Self {
+		let next_id = network.nodes.keys().max().map_or(0, |id| id + 1);
+		Self { network, next_id }
+	}
+
+	pub fn network(&self) -> &NodeNetwork {
+		&self.network
+	}
+
+	pub fn into_network(self) -> NodeNetwork {
+		self.network
+	}
+
+	/// Adds a node implemented by the proto node with the given name, whose inputs all start out taking the input of the network until they're [connected](NodeGraph::connect).
+	pub fn add_node(&mut self, name: &str, input_count: usize) -> NodeId {
+		self.insert(DocumentNode {
+			name: name.into(),
+			inputs: vec![NodeInput::Network; input_count],
+			implementation: DocumentNodeImplementation::ProtoNode(ProtoNode::unresolved(name.into())),
+		})
+	}
+
+	/// Adds a node which outputs the given value.
+	pub fn add_value<T: IntoValue>(&mut self, value: T) -> NodeId {
+		self.insert(DocumentNode {
+			name: "Value".into(),
+			inputs: vec![NodeInput::Value(value.into_any())],
+			implementation: DocumentNodeImplementation::ProtoNode(ProtoNode::unresolved("value".into())),
+		})
+	}
+
+	/// Removes a node, leaving the inputs that were connected to it taking the input of the network instead.
+	pub fn remove_node(&mut self, node: NodeId) -> Result<(), ApiError> {
+		self.network.nodes.remove(&node).ok_or_else(|| missing_node(node))?;
+
+		for other in self.network.nodes.values_mut() {
+			for input in other.inputs.iter_mut().filter(|input| input.source() == Some(node)) {
+				*input = NodeInput::Network;
+			}
+		}
+		self.network.inputs.retain(|&input| input != node);
+
+		Ok(())
+	}
+
+	/// Feeds the output of one node into the input with the given index of another.
+	pub fn connect(&mut self, from: NodeId, to: NodeId, input_index: usize) -> Result<(), ApiError> {
+		if !self.network.nodes.contains_key(&from) {
+			return Err(missing_node(from));
+		}
+		let node = self.network.nodes.get_mut(&to).ok_or_else(|| missing_node(to))?;
+		let input = node
+			.inputs
+			.get_mut(input_index)
+			.ok_or_else(|| ApiError::NodeGraph(NodeError::new(to, format!("The node has no input with the index {}", input_index))))?;
+		*input = NodeInput::Node(from);
+
+		Ok(())
+	}
+
+	/// Makes the output of the given node the output of the network.
+	pub fn set_output(&mut self, node: NodeId) -> Result<(), ApiError> {
+		if !self.network.nodes.contains_key(&node) {
+			return Err(missing_node(node));
+		}
+		self.network.output = node;
+		Ok(())
+	}
+
+	/// Makes the given node receive the input of the network, in addition to the nodes already receiving it.
+	pub fn add_network_input(&mut self, node: NodeId) -> Result<(), ApiError> {
+		if !self.network.nodes.contains_key(&node) {
+			return Err(missing_node(node));
+		}
+		self.network.inputs.push(node);
+		Ok(())
+	}
+
+	/// The order the nodes are evaluated in, which fails if a node depends on its own output.
+	pub fn evaluation_order(&self) -> Result<Vec<NodeId>, ApiError> {
+		Ok(self.network.evaluation_levels()?.into_iter().flatten().collect())
+	}
+
+	/// Turns the network into proto nodes, ready to be compiled, which fails on the first node with an input that isn't connected.
+	pub fn resolve(&self) -> Result<NodeNetwork, ApiError> {
+		self.evaluation_order()?;
+
+		let mut network = self.network.clone();
+		network.resolve_proto_nodes()?;
+		Ok(network)
+	}
+
+	fn insert(&mut self, node: DocumentNode) -> NodeId {
+		let id = self.next_id;
+		self.next_id += 1;
+		self.network.nodes.insert(id, node);
+		id
+	}
+}
+
+fn missing_node(node: NodeId) -> ApiError {
+	ApiError::NodeGraph(NodeError::new(node, "The node graph has no node with this ID"))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn build_and_resolve() {
+		let mut graph = NodeGraph::new();
+		let value = graph.add_value(2_u32);
+		let add = graph.add_node("add", 2);
+		graph.add_network_input(add).unwrap();
+		graph.set_output(add).unwrap();
+
+		// The second input of the node isn't connected yet
+		assert!(matches!(graph.resolve(), Err(ApiError::NodeGraph(NodeError { input: Some(_), .. }))));
+
+		graph.connect(value, add, 1).unwrap();
+		assert_eq!(graph.evaluation_order().unwrap(), vec![value, add]);
+		assert!(graph.resolve().is_ok());
+
+		assert!(graph.connect(value, add, 2).is_err());
+		assert!(graph.connect(7, add, 1).is_err());
+	}
+
+	#[test]
+	fn cycles_and_removal() {
+		let mut graph = NodeGraph::new();
+		let first = graph.add_node("id", 1);
+		let second = graph.add_node("id", 1);
+		graph.connect(first, second, 0).unwrap();
+		graph.connect(second, first, 0).unwrap();
+		assert!(graph.evaluation_order().is_err());
+
+		graph.remove_node(second).unwrap();
+		assert_eq!(graph.network().nodes[&first].inputs, vec![NodeInput::Network]);
+		assert_eq!(graph.evaluation_order().unwrap(), vec![first]);
+		assert!(graph.remove_node(second).is_err());
+	}
+}
@@ -0,0 +1,291 @@
+//! Renders `.graphite` documents to SVG, PNG, JPEG, or WebP files without the editor frontend, for use in scripts, CI pipelines, and servers.
+
+use editor::messages::frontend::utility_types::ExportBounds;
+use editor::messages::portfolio::document::utility_types::color_profile::declare_svg_color_profile;
+use editor::messages::portfolio::document::utility_types::image_encoding::{ChromaSubsampling, RasterEncoding};
+use editor::messages::portfolio::document::utility_types::svg_export::{SvgExportOptions, SvgStyleOutput};
+use editor::messages::portfolio::document::DocumentMessageHandler;
+use editor::messages::portfolio::utility_types::PersistentData;
+use graphene::color::ColorSpace;
+use graphene::layers::text_layer::Font;
+
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: graphite-render <document.graphite> [options]
+
+Options:
+  -o, --output <path>    File to write, defaults to the document path with the extension of the format
+  -f, --format <format>  One of `svg`, `png`, `jpg`, or `webp`, defaults to the extension of the output path or `png`
+  -a, --artboard <index> Render only the artboard with the given index, counting from 0 in the order they were created
+  -s, --scale <factor>   Number of pixels per document unit of raster output, defaults to 1
+  -q, --quality <1-100>  Quality of JPEG and WebP output, defaults to 90 (WebP is lossless at 100)
+      --chroma-subsampling <444|420>
+                         Resolution of the colors of JPEG output relative to the brightness, defaults to 420
+      --font <path>      Font file used for text whose font is not embedded in the document
+      --precision <digits|full>
+                         Decimal places of the coordinates of SVG output, defaults to 3
+      --flatten-transforms
+                         Apply the transforms of groups to the path data of SVG output
+      --styles <attributes|inline|classes>
+                         How the styles of SVG output are written, defaults to `attributes`
+      --layer-ids        Give the elements of named layers in SVG output their names as IDs
+  -h, --help             Show this message";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+	Svg,
+	Png,
+	Jpeg,
+	Webp,
+}
+
+impl Format {
+	fn from_extension(extension: &str) -> Option<Self> {
+		match extension.to_lowercase().as_str() {
+			"svg" => Some(Format::Svg),
+			"png" => Some(Format::Png),
+			"jpg" | "jpeg" => Some(Format::Jpeg),
+			"webp" => Some(Format::Webp),
+			_ => None,
+		}
+	}
+
+	fn extension(&self) -> &'static str {
+		match self {
+			Format::Svg => "svg",
+			Format::Png => "png",
+			Format::Jpeg => "jpg",
+			Format::Webp => "webp",
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+	input: PathBuf,
+	output: PathBuf,
+	format: Format,
+	artboard: Option<usize>,
+	scale: f64,
+	quality: u8,
+	chroma_subsampling: ChromaSubsampling,
+	font: Option<PathBuf>,
+	svg: SvgExportOptions,
+}
+
+fn parse_arguments(mut arguments: impl Iterator<Item = String>) -> Result<Options, String> {
+	let (mut input, mut output, mut format, mut artboard, mut scale, mut font) = (None, None, None, None, 1., None);
+	let (mut quality, mut chroma_subsampling) = (90, ChromaSubsampling::Half);
+	let mut svg = SvgExportOptions::default();
+
+	while let Some(argument) = arguments.next() {
+		let mut value = |name: &str| arguments.next().ok_or(format!("Missing value for {}", name));
+		match argument.as_str() {
+			"-o" | "--output" => output = Some(PathBuf::from(value(&argument)?)),
+			"-f" | "--format" => {
+				let value = value(&argument)?;
+				format = Some(Format::from_extension(&value).ok_or(format!("Unknown format '{}'", value))?);
+			}
+			"-a" | "--artboard" => artboard = Some(value(&argument)?.parse().map_err(|_| "The artboard index must be a whole number".to_string())?),
+			"-s" | "--scale" => {
+				scale = value(&argument)?.parse::<f64>().map_err(|_| "The scale must be a number".to_string())?;
+				if !scale.is_finite() || scale <= 0. {
+					return Err("The scale must be a finite number greater than 0".into());
+				}
+			}
+			"-q" | "--quality" => {
+				quality = value(&argument)?.parse().map_err(|_| "The quality must be a whole number".to_string())?;
+				if !(1..=100).contains(&quality) {
+					return Err("The quality must be between 1 and 100".into());
+				}
+			}
+			"--chroma-subsampling" => {
+				chroma_subsampling = match value(&argument)?.as_str() {
+					"444" | "4:4:4" => ChromaSubsampling::Full,
+					"420" | "4:2:0" => ChromaSubsampling::Half,
+					other => return Err(format!("Unknown chroma subsampling '{}', expected `444` or `420`", other)),
+				}
+			}
+			"--font" => font = Some(PathBuf::from(value(&argument)?)),
+			"--precision" => {
+				svg.precision = match value(&argument)?.as_str() {
+					"full" => None,
+					digits => Some(digits.parse().map_err(|_| "The precision must be a whole number of digits or `full`".to_string())?),
+				}
+			}
+			"--flatten-transforms" => svg.flatten_transforms = true,
+			"--styles" => {
+				svg.style_output = match value(&argument)?.as_str() {
+					"attributes" => SvgStyleOutput::Attributes,
+					"inline" => SvgStyleOutput::InlineStyle,
+					"classes" => SvgStyleOutput::Classes,
+					other => return Err(format!("Unknown styles '{}', expected `attributes`, `inline`, or `classes`", other)),
+				}
+			}
+			"--layer-ids" => svg.layer_name_ids = true,
+			"-h" | "--help" => return Err(USAGE.into()),
+			flag if flag.starts_with('-') => return Err(format!("Unknown option '{}'\n\n{}", flag, USAGE)),
+			path if input.is_none() => input = Some(PathBuf::from(path)),
+			_ => return Err(format!("Only one document can be rendered at a time\n\n{}", USAGE)),
+		}
+	}
+
+	let input: PathBuf = input.ok_or_else(|| USAGE.to_string())?;
+	let format = format
+		.or_else(|| {
+			output
+				.as_ref()
+				.and_then(|output: &PathBuf| output.extension())
+				.and_then(|extension| Format::from_extension(&extension.to_string_lossy()))
+		})
+		.unwrap_or(Format::Png);
+	let output = output.unwrap_or_else(|| input.with_extension(format.extension()));
+
+	Ok(Options {
+		input,
+		output,
+		format,
+		artboard,
+		scale,
+		quality,
+		chroma_subsampling,
+		font,
+		svg,
+	})
+}
+
+fn run(options: &Options) -> Result<(), String> {
+	let content = std::fs::read(&options.input).map_err(|error| format!("Could not read {}: {}", options.input.display(), error))?;
+	let mut document = DocumentMessageHandler::deserialize_document(&content).map_err(|error| format!("Could not open {}: {:?}", options.input.display(), error))?;
+
+	let mut persistent_data = PersistentData::default();
+	if let Some(path) = &options.font {
+		let data = std::fs::read(path).map_err(|error| format!("Could not read the font {}: {}", path.display(), error))?;
+		let font = Font::new(path.to_string_lossy().into(), "Regular".into());
+		persistent_data.font_cache.insert(font, String::new(), data, true);
+	}
+
+	let bounds = match options.artboard {
+		Some(index) => {
+			let artboard_ids = &document.artboard_message_handler.artboard_ids;
+			let id = artboard_ids
+				.get(index)
+				.ok_or(format!("The document has {} artboards, there is no artboard with the index {}", artboard_ids.len(), index))?;
+			ExportBounds::Artboard(*id)
+		}
+		None => ExportBounds::AllArtwork,
+	};
+	let data = match options.format {
+		Format::Svg => {
+			let svg = document.export_svg_file(bounds, false, false, false, options.svg, &persistent_data.font_cache);
+			let svg = declare_svg_color_profile(svg, document.graphene_document.specified_color_space());
+			document.metadata.embed_in_svg(svg).into_bytes()
+		}
+		// The renderer only understands sRGB colors, so wide-gamut colors are converted for rasterized formats
+		Format::Png => {
+			let (svg, size) = document.export_srgb_svg(bounds, false, false, &persistent_data);
+			let png = rasterize(&svg, size * options.scale)?.encode_png().map_err(|error| format!("Could not encode the image: {}", error))?;
+			document.metadata.embed_in_png(png)
+		}
+		Format::Jpeg | Format::Webp => {
+			let (svg, size) = document.export_srgb_svg(bounds, false, false, &persistent_data);
+			let encoding = match options.format {
+				Format::Jpeg => RasterEncoding::Jpeg {
+					quality: options.quality,
+					chroma_subsampling: options.chroma_subsampling,
+				},
+				_ => RasterEncoding::Webp { quality: options.quality },
+			};
+			let pixmap = rasterize(&svg, size * options.scale)?;
+			// The encoders expect straight rather than premultiplied alpha
+			let pixels: Vec<u8> = pixmap
+				.pixels()
+				.iter()
+				.flat_map(|pixel| {
+					let color = pixel.demultiply();
+					[color.red(), color.green(), color.blue(), color.alpha()]
+				})
+				.collect();
+			encoding
+				.encode(&pixels, pixmap.width(), pixmap.height(), ColorSpace::Srgb)
+				.map_err(|error| format!("Could not encode the image: {}", error))?
+		}
+	};
+	std::fs::write(&options.output, data).map_err(|error| format!("Could not write {}: {}", options.output.display(), error))
+}
+
+fn rasterize(svg: &str, size: glam::DVec2) -> Result<tiny_skia::Pixmap, String> {
+	let size = size.round().max(glam::DVec2::ONE).as_u32();
+	let tree = usvg::Tree::from_str(svg, &usvg::Options::default().to_ref()).map_err(|error| format!("Could not render the document: {}", error))?;
+	let mut pixmap = tiny_skia::Pixmap::new(size.x, size.y).ok_or("The rendered image is too large")?;
+	resvg::render(&tree, usvg::FitTo::Size(size.x, size.y), tiny_skia::Transform::default(), pixmap.as_mut()).ok_or("Could not render the document")?;
+	Ok(pixmap)
+}
+
+fn main() {
+	let result = parse_arguments(std::env::args().skip(1)).and_then(|options| run(&options));
+	if let Err(message) = result {
+		eprintln!("{}", message);
+		std::process::exit(1);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn parse(arguments: &[&str]) -> Result<Options, String> {
+		parse_arguments(arguments.iter().map(|argument| argument.to_string()))
+	}
+
+	#[test]
+	fn output_format_from_extension() {
+		let options = parse(&["art.graphite", "-o", "out/art.svg", "--scale", "2"]).unwrap();
+		assert_eq!(options.format, Format::Svg);
+		assert_eq!(options.scale, 2.);
+
+		let options = parse(&["art.graphite", "--artboard", "1"]).unwrap();
+		assert_eq!(options.output, PathBuf::from("art.png"));
+		assert_eq!(options.artboard, Some(1));
+
+		assert!(parse(&["art.graphite", "--scale", "0"]).is_err());
+		assert!(parse(&["art.graphite", "other.graphite"]).is_err());
+	}
+
+	#[test]
+	fn lossy_format_options() {
+		let options = parse(&["art.graphite", "-o", "art.jpeg", "-q", "75", "--chroma-subsampling", "4:4:4"]).unwrap();
+		assert_eq!(options.format, Format::Jpeg);
+		assert_eq!(options.quality, 75);
+		assert_eq!(options.chroma_subsampling, ChromaSubsampling::Full);
+
+		let options = parse(&["art.graphite", "--format", "webp"]).unwrap();
+		assert_eq!(options.output, PathBuf::from("art.webp"));
+		assert_eq!(options.quality, 90);
+
+		assert!(parse(&["art.graphite", "--quality", "0"]).is_err());
+		assert!(parse(&["art.graphite", "--chroma-subsampling", "422"]).is_err());
+	}
+
+	#[test]
+	fn svg_options() {
+		let options = parse(&["art.graphite", "-o", "art.svg", "--precision", "1", "--flatten-transforms", "--styles", "classes", "--layer-ids"]).unwrap();
+		assert_eq!(options.svg.precision, Some(1));
+		assert!(options.svg.flatten_transforms);
+		assert_eq!(options.svg.style_output, SvgStyleOutput::Classes);
+		assert!(options.svg.layer_name_ids);
+
+		let options = parse(&["art.graphite", "-o", "art.svg", "--precision", "full"]).unwrap();
+		assert_eq!(options.svg.precision, None);
+		assert_eq!(
+			options.svg,
+			SvgExportOptions {
+				precision: None,
+				..SvgExportOptions::default()
+			}
+		);
+
+		assert!(parse(&["art.graphite", "--precision", "-1"]).is_err());
+		assert!(parse(&["art.graphite", "--styles", "css"]).is_err());
+	}
+}
@@ -17,6 +17,18 @@ pub const VIEWPORT_SCROLL_RATE: f64 = 0.6;
 
 pub const VIEWPORT_ROTATE_SNAP_INTERVAL: f64 = 15.;
 
+// Size in pixels of the tiles the GPU viewport renderer draws again when the layers overlapping them change
+pub const VIEWPORT_TILE_SIZE: f64 = 256.;
+
+// Size in viewport pixels that the pixels of the pixel preview must be enlarged to before the pixel grid is shown
+pub const PIXEL_PREVIEW_GRID_MIN_SCALE: f64 = 8.;
+
+// Isolation mode
+// Alpha of the black overlay dimming the viewport around the isolated folder
+pub const ISOLATION_DIM_ALPHA: u8 = 0x66;
+// Drawn below the overlays of the tools
+pub const ISOLATION_OVERLAY_Z_ORDER: i32 = -1;
+
 // Snapping axis
 pub const SNAP_AXIS_TOLERANCE: f64 = 3.;
 pub const SNAP_AXIS_OVERLAY_FADE_DISTANCE: f64 = 15.;
@@ -46,6 +58,7 @@ pub const SELECTION_DRAG_ANGLE: f64 = 90.;
 pub const PIVOT_OUTER_OUTLINE_THICKNESS: f64 = 1.;
 pub const PIVOT_OUTER: f64 = 9.;
 pub const PIVOT_INNER: f64 = 3.;
+pub const PIVOT_Z_ORDER: i32 = 1;
 
 // Transformation cage
 pub const BOUNDS_SELECT_THRESHOLD: f64 = 10.;
@@ -54,6 +67,8 @@ pub const BOUNDS_ROTATE_THRESHOLD: f64 = 20.;
 // Path tool
 pub const MANIPULATOR_GROUP_MARKER_SIZE: f64 = 5.;
 pub const SELECTION_THRESHOLD: f64 = 10.;
+// Distance in viewport pixels from an anchor to the widget for dragging the radius of its corner, while the corner isn't rounded
+pub const CORNER_RADIUS_WIDGET_OFFSET: f64 = 16.;
 
 // Pen tool
 pub const CREATE_CURVE_THRESHOLD: f64 = 5.;
@@ -61,6 +76,12 @@ pub const CREATE_CURVE_THRESHOLD: f64 = 5.;
 // Line tool
 pub const LINE_ROTATE_SNAP_ANGLE: f64 = 15.;
 
+// Artboard tool
+// Gap in pixels left between an artboard added from a size preset and the artboards already in the document
+pub const ARTBOARD_PRESET_GAP: f64 = 100.;
+// Size in viewport pixels of the squares of the checkerboard shown behind artboards with a transparent background
+pub const ARTBOARD_CHECKERBOARD_SIZE: f64 = 8.;
+
 // Scrollbars
 pub const SCROLLBAR_SPACING: f64 = 0.1;
 pub const ASYMPTOTIC_EFFECT: f64 = 0.5;
@@ -77,5 +98,9 @@ pub const DEFAULT_FONT_STYLE: &str = "Normal (400)";
 pub const GRAPHITE_DOCUMENT_VERSION: &str = "0.0.13"; // Remember to save a simple document and replace the test file `graphite-test-document.graphite`
 pub const DEFAULT_DOCUMENT_NAME: &str = "Untitled Document";
 pub const FILE_SAVE_SUFFIX: &str = ".graphite";
+// Number of replaced layer selections remembered to be reselected
+pub const SELECTION_HISTORY_LENGTH: usize = 16;
+// Size in pixels the layer thumbnails are rasterized at, which is twice the size the Layers panel shows them at so they stay sharp on high density displays
+pub const LAYER_THUMBNAIL_SIZE: (u32, u32) = (64, 40);
 
 pub const VIEWPORT_ZOOM_TO_FIT_PADDING_SCALE_FACTOR: f32 = 1.05;
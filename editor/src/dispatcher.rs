@@ -158,6 +158,11 @@ impl Dispatcher {
 				}
 				Preferences(message) => {
 					self.message_handlers.preferences_message_handler.process_message(message, (), &mut queue);
+
+					// Shortcuts are shown in the menu bar, so it's updated when they're rebound
+					if self.message_handlers.input_mapper_message_handler.set_keymap(&self.message_handlers.preferences_message_handler.keymap) {
+						queue.push_back(MenuBarMessage::SendLayout.into());
+					}
 				}
 				Tool(message) => {
 					if let Some(document) = self.message_handlers.portfolio_message_handler.active_document() {
@@ -537,6 +542,24 @@ mod test {
 		assert_eq!(all, non_selected.into_iter().chain(selected.into_iter()).collect::<Vec<_>>());
 	}
 
+	#[test]
+	/// - create rect, shape and ellipse
+	/// - save the document in the compressed format
+	/// - open the compressed document
+	/// - assert that it's the same as the original
+	fn open_compressed_document() {
+		let editor = create_editor_with_three_layers();
+
+		let document = editor.dispatcher.message_handlers.portfolio_message_handler.active_document().unwrap();
+		let compressed = document.serialize_document_compressed();
+		assert!(compressed.len() < document.serialize_document().len());
+
+		// The layer metadata is a hash map, so it's compared as one rather than by its serialization which depends on the iteration order
+		let opened = DocumentMessageHandler::deserialize_document(&compressed).unwrap();
+		assert_eq!(opened.layer_metadata, document.layer_metadata);
+		assert_eq!(serde_json::to_string(&opened.graphene_document).unwrap(), serde_json::to_string(&document.graphene_document).unwrap());
+	}
+
 	#[test]
 	/// If this test is failing take a look at `GRAPHITE_DOCUMENT_VERSION` in `editor/src/consts.rs`, it may need to be updated.
 	/// This test will fail when you make changes to the underlying serialization format for a document.
@@ -0,0 +1,1130 @@
+//! Reads and writes DXF, the drawing interchange format of CAD software, which laser cutters and CNC machines take their toolpaths from.
+//!
+//! Lines, circles, arcs, ellipses, polylines with bulges, and splines are imported as stroked paths, including those of blocks placed by inserts.
+//! Text, hatches, dimensions, and 3D entities are skipped, and only ASCII DXF files can be read.
+//!
+//! Exports hold the outlines of the shape and text layers in millimeters. Their curves are written either as polylines of circular arcs,
+//! which every CAD program reads and machines follow exactly, or as splines, which keep the curves exact.
+
+use crate::messages::portfolio::document::utility_types::error::EditorError;
+
+use graphene::color::{Color, ColorSpace};
+use graphene::layers::layer_info::{Layer, LayerDataType};
+use graphene::layers::style::{Fill, PathStyle};
+use graphene::layers::text_layer::FontCache;
+use graphene::layers::vector::subpath::Subpath;
+
+use bezier_rs::{ArcsOptions, Bezier};
+use glam::{DAffine2, DVec2, DVec3};
+use kurbo::{BezPath, PathEl, Point, Shape};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_PI_2, TAU};
+use std::fmt::Write;
+
+/// The number of document pixels (1/96 of an inch) in a millimeter, the unit of exports and of imported drawings which don't give theirs.
+const PIXELS_PER_MILLIMETER: f64 = 96. / 25.4;
+/// The signature of a binary DXF file, which AutoCAD can save instead of the usual ASCII ones.
+const BINARY_DXF_SIGNATURE: &[u8] = b"AutoCAD Binary DXF";
+/// Blocks inserted within blocks deeper than this are assumed to be a cycle.
+const MAX_INSERT_DEPTH: usize = 16;
+/// The most entities a drawing may draw, counting every copy placed by its inserts, before its import is aborted.
+/// Arrays of inserts nested in blocks multiply each other's copies, so the limit is shared by all of them rather than applying to each insert.
+const MAX_DRAWN_ENTITIES: usize = 1_000_000;
+/// How far, in millimeters, the arcs of an export may stray from the curves they approximate.
+const ARC_TOLERANCE: f64 = 0.01;
+/// The number of cubic curves each piece of a rational or high-degree spline is approximated by.
+const SPLINE_PIECE_SUBDIVISIONS: usize = 4;
+
+/// How the curves of a DXF export are written.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DxfCurves {
+	/// Curves are approximated by circular arcs, written as the bulges of polylines
+	#[default]
+	Arcs,
+	/// Curves are written exactly as cubic B-splines
+	Splines,
+}
+
+/// Identifies a DXF file by its contents, falling back to its MIME type.
+pub fn is_dxf(mime: &str, data: &[u8]) -> bool {
+	if data.starts_with(BINARY_DXF_SIGNATURE) {
+		return true;
+	}
+	// ASCII DXF files begin with their first section, possibly after comments, which are the only groups with code 999
+	let text = String::from_utf8_lossy(&data[..data.len().min(4096)]);
+	let mut lines = text.lines().map(str::trim);
+	while let (Some(code), Some(value)) = (lines.next(), lines.next()) {
+		match (code, value) {
+			("999", _) => continue,
+			("0", "SECTION") => return true,
+			_ => break,
+		}
+	}
+	matches!(mime, "image/vnd.dxf" | "image/x-dxf" | "application/dxf" | "application/x-dxf")
+}
+
+// Reading
+
+/// A group of a DXF file, which is a code telling what its value means followed by the value.
+type Group<'a> = (i32, &'a str);
+
+fn parse_groups(text: &str) -> Result<Vec<Group<'_>>, EditorError> {
+	let mut lines = text.lines();
+	let mut groups = Vec::new();
+	while let Some(code) = lines.next() {
+		let code = code.trim();
+		if code.is_empty() {
+			continue;
+		}
+		let code = code
+			.parse()
+			.map_err(|_| EditorError::Misc(format!("The file isn't a valid DXF file, since `{code}` isn't a group code")))?;
+		let value = lines.next().ok_or_else(|| EditorError::Misc("The DXF file ends in the middle of a group".into()))?;
+		groups.push((code, value.trim()));
+	}
+	Ok(groups)
+}
+
+/// An entity of a DXF file, or an entry of one of its tables, with the groups describing it in order.
+#[derive(Clone, Debug, Default)]
+struct Entity<'a> {
+	kind: &'a str,
+	groups: Vec<Group<'a>>,
+	/// The vertices of an old-style polyline, which follow it as entities of their own
+	vertices: Vec<Entity<'a>>,
+}
+
+impl<'a> Entity<'a> {
+	fn string(&self, code: i32) -> Option<&'a str> {
+		self.groups.iter().find(|(group_code, _)| *group_code == code).map(|&(_, value)| value)
+	}
+
+	fn float(&self, code: i32) -> Option<f64> {
+		self.string(code).and_then(|value| value.parse().ok()).filter(|value: &f64| value.is_finite())
+	}
+
+	fn int(&self, code: i32) -> Option<i64> {
+		self.string(code).and_then(|value| value.parse().ok())
+	}
+
+	fn floats(&self, code: i32) -> impl Iterator<Item = f64> + '_ {
+		self.groups
+			.iter()
+			.filter(move |(group_code, _)| *group_code == code)
+			.map(|(_, value)| value.parse().ok().filter(|value: &f64| value.is_finite()).unwrap_or_default())
+	}
+
+	/// A point given by the codes of its X and Y coordinates, which are the codes of other points plus 10 and 20.
+	fn point(&self, code: i32) -> DVec2 {
+		DVec2::new(self.float(code).unwrap_or_default(), self.float(code + 10).unwrap_or_default())
+	}
+
+	/// Every point given by the codes of X and Y coordinates, in order.
+	fn points(&self, code: i32) -> Vec<DVec2> {
+		self.floats(code).zip(self.floats(code + 10)).map(|(x, y)| DVec2::new(x, y)).collect()
+	}
+
+	/// Entities drawn in their own object coordinate system can be turned over by pointing their extrusion direction down, which mirrors them left to right.
+	fn object_transform(&self) -> DAffine2 {
+		match self.float(230) {
+			Some(z) if z < 0. => DAffine2::from_scale(DVec2::new(-1., 1.)),
+			_ => DAffine2::IDENTITY,
+		}
+	}
+}
+
+/// Splits the groups of a section into its entities, attaching the vertices of old-style polylines to them.
+fn parse_entities<'a>(groups: &[Group<'a>]) -> Vec<Entity<'a>> {
+	let mut entities: Vec<Entity> = Vec::new();
+	let mut in_polyline = false;
+	for &(code, value) in groups {
+		if code != 0 {
+			let entity = match entities.last_mut() {
+				Some(entity) => entity,
+				None => continue,
+			};
+			// Groups after a vertex belong to it
+			let entity = if in_polyline && !entity.vertices.is_empty() {
+				entity.vertices.last_mut().unwrap()
+			} else {
+				entity
+			};
+			entity.groups.push((code, value));
+			continue;
+		}
+
+		let entity = Entity { kind: value, ..Default::default() };
+		match value {
+			"VERTEX" if in_polyline => entities.last_mut().unwrap().vertices.push(entity),
+			"SEQEND" if in_polyline => in_polyline = false,
+			_ => {
+				in_polyline = value == "POLYLINE";
+				entities.push(entity);
+			}
+		}
+	}
+	entities
+}
+
+/// A layer of the drawing, whose line color and weight its entities use unless they give their own.
+#[derive(Clone, Copy, Debug)]
+struct DrawingLayer {
+	color: [u8; 3],
+	/// The line weight in hundredths of a millimeter, or a negative number for the default
+	line_weight: i64,
+	/// Layers which are turned off or frozen aren't drawn
+	visible: bool,
+}
+
+struct Block<'a> {
+	base_point: DVec2,
+	entities: Vec<Entity<'a>>,
+}
+
+/// The line color and weight an entity inherits when it's drawn by an insert of a block.
+#[derive(Clone, Copy, Debug)]
+struct InheritedStyle {
+	color: [u8; 3],
+	line_weight: i64,
+}
+
+/// A path of the drawing in its own units, along with its line style.
+struct DrawingPath {
+	path: BezPath,
+	color: [u8; 3],
+	line_weight: i64,
+}
+
+struct DxfReader<'a> {
+	layers: HashMap<&'a str, DrawingLayer>,
+	blocks: HashMap<&'a str, Block<'a>>,
+	paths: Vec<DrawingPath>,
+	/// How many more entities may be drawn, counting every copy placed by an insert
+	budget: usize,
+}
+
+/// Converts the line work of a DXF file into an SVG document fit to its extent, which is then imported like any other SVG.
+pub fn dxf_to_svg(data: &[u8]) -> Result<String, EditorError> {
+	if data.starts_with(BINARY_DXF_SIGNATURE) {
+		return Err(EditorError::Misc("Binary DXF files can't be imported. Save the drawing as an ASCII DXF file instead.".into()));
+	}
+	let text = String::from_utf8_lossy(data);
+	let groups = parse_groups(&text)?;
+
+	// The sections of the file by name, each with the groups between its start and end
+	let mut sections = HashMap::new();
+	let mut index = 0;
+	while index < groups.len() {
+		if groups[index] == (0, "SECTION") {
+			let name = groups.get(index + 1).map(|&(_, name)| name).unwrap_or_default();
+			let end = groups[index..].iter().position(|&group| group == (0, "ENDSEC")).map_or(groups.len(), |end| index + end);
+			sections.insert(name, &groups[(index + 2).min(end)..end]);
+			index = end;
+		}
+		index += 1;
+	}
+
+	let header = sections.get("HEADER").copied().unwrap_or_default();
+	let units = header
+		.iter()
+		.position(|&group| group == (9, "$INSUNITS"))
+		.and_then(|index| header.get(index + 1))
+		.and_then(|(_, value)| value.parse().ok());
+	let pixels_per_unit = pixels_per_unit(units.unwrap_or_default());
+
+	let mut reader = DxfReader {
+		layers: HashMap::new(),
+		blocks: HashMap::new(),
+		paths: Vec::new(),
+		budget: MAX_DRAWN_ENTITIES,
+	};
+	for entry in parse_entities(sections.get("TABLES").copied().unwrap_or_default()).iter().filter(|entry| entry.kind == "LAYER") {
+		let color = entry.int(62).unwrap_or(7);
+		let flags = entry.int(70).unwrap_or_default();
+		let layer = DrawingLayer {
+			color: true_color(entry).unwrap_or_else(|| aci_color(color.unsigned_abs().min(255) as u8)),
+			line_weight: entry.int(370).unwrap_or(-3),
+			// A negative color number turns the layer off, and the first flag freezes it
+			visible: color >= 0 && flags & 1 == 0,
+		};
+		reader.layers.insert(entry.string(2).unwrap_or_default(), layer);
+	}
+
+	let mut block: Option<(&str, Block)> = None;
+	for entity in parse_entities(sections.get("BLOCKS").copied().unwrap_or_default()) {
+		match entity.kind {
+			"BLOCK" => {
+				let base_point = entity.point(10);
+				block = Some((entity.string(2).unwrap_or_default(), Block { base_point, entities: Vec::new() }));
+			}
+			"ENDBLK" => {
+				if let Some((name, block)) = block.take() {
+					reader.blocks.insert(name, block);
+				}
+			}
+			_ => {
+				if let Some((_, block)) = &mut block {
+					block.entities.push(entity);
+				}
+			}
+		}
+	}
+
+	let default_style = InheritedStyle { color: [0; 3], line_weight: -3 };
+	for entity in parse_entities(sections.get("ENTITIES").copied().unwrap_or_default()) {
+		reader.draw_entity(&entity, DAffine2::IDENTITY, default_style, 0)?;
+	}
+	reader.into_svg(pixels_per_unit)
+}
+
+impl<'a> DxfReader<'a> {
+	fn draw_entity(&mut self, entity: &Entity<'a>, transform: DAffine2, inherited: InheritedStyle, depth: usize) -> Result<(), EditorError> {
+		self.spend(1)?;
+
+		let layer = self.layers.get(entity.string(8).unwrap_or("0")).copied();
+		if layer.is_some_and(|layer| !layer.visible) {
+			return Ok(());
+		}
+		// Invisible entities have their first flag of code 60 set
+		if entity.int(60).unwrap_or_default() & 1 == 1 {
+			return Ok(());
+		}
+
+		// Colors and line weights are given by the entity, by its layer, or by the insert of the block it's in
+		let color = match (true_color(entity), entity.int(62).unwrap_or(256)) {
+			(Some(color), _) => color,
+			(None, 0) => inherited.color,
+			(None, 256) => layer.map_or(inherited.color, |layer| layer.color),
+			(None, color) => aci_color(color.unsigned_abs().min(255) as u8),
+		};
+		let line_weight = match entity.int(370).unwrap_or(-1) {
+			-2 => inherited.line_weight,
+			-1 => layer.map_or(inherited.line_weight, |layer| layer.line_weight),
+			line_weight => line_weight,
+		};
+		let style = InheritedStyle { color, line_weight };
+
+		if entity.kind == "INSERT" {
+			return self.draw_insert(entity, transform, style, depth);
+		}
+
+		let mut path = BezPath::new();
+		match entity.kind {
+			"LINE" => {
+				path.move_to(to_point(entity.point(10)));
+				path.line_to(to_point(entity.point(11)));
+			}
+			"CIRCLE" => {
+				let (center, radius) = (entity.point(10), entity.float(40).unwrap_or_default());
+				let circle = DAffine2::from_scale_angle_translation(DVec2::splat(radius), 0., center);
+				elliptical_arc(&mut path, entity.object_transform() * circle, 0., TAU);
+				path.close_path();
+			}
+			"ARC" => {
+				let (center, radius) = (entity.point(10), entity.float(40).unwrap_or_default());
+				let (start_angle, end_angle) = (entity.float(50).unwrap_or_default().to_radians(), entity.float(51).unwrap_or_default().to_radians());
+				let circle = DAffine2::from_scale_angle_translation(DVec2::splat(radius), 0., center);
+				// Arcs run counterclockwise from their start angle to their end angle
+				let sweep = (end_angle - start_angle).rem_euclid(TAU);
+				let sweep = if sweep == 0. { TAU } else { sweep };
+				elliptical_arc(&mut path, entity.object_transform() * circle, start_angle, start_angle + sweep);
+			}
+			"ELLIPSE" => {
+				let (center, major_axis) = (entity.point(10), entity.point(11));
+				// The minor axis is a quarter turn counterclockwise from the major axis, unless the ellipse is turned over by its extrusion direction
+				let turn = if entity.float(230).is_some_and(|z| z < 0.) { -1. } else { 1. };
+				let minor_axis = major_axis.perp() * entity.float(40).unwrap_or(1.) * turn;
+				let ellipse = DAffine2::from_cols(major_axis, minor_axis, center);
+				let (start, end) = (entity.float(41).unwrap_or_default(), entity.float(42).unwrap_or(TAU));
+				let sweep = (end - start).rem_euclid(TAU);
+				let full = sweep.abs() < 1e-9;
+				elliptical_arc(&mut path, ellipse, start, start + if full { TAU } else { sweep });
+				if full {
+					path.close_path();
+				}
+			}
+			"LWPOLYLINE" => {
+				// Each vertex is followed by the bulge of the segment from it to the next vertex
+				let mut vertices: Vec<(DVec2, f64)> = Vec::new();
+				for &(code, value) in &entity.groups {
+					let value = value.parse().ok().filter(|value: &f64| value.is_finite()).unwrap_or_default();
+					match code {
+						10 => vertices.push((DVec2::new(value, 0.), 0.)),
+						20 => {
+							if let Some((vertex, _)) = vertices.last_mut() {
+								vertex.y = value;
+							}
+						}
+						42 => {
+							if let Some((_, bulge)) = vertices.last_mut() {
+								*bulge = value;
+							}
+						}
+						_ => {}
+					}
+				}
+				let closed = entity.int(70).unwrap_or_default() & 1 == 1;
+				polyline(&mut path, &vertices, closed, entity.object_transform());
+			}
+			"POLYLINE" => {
+				let flags = entity.int(70).unwrap_or_default();
+				// 3D polylines, polygon meshes, and polyface meshes are left out
+				if flags & (8 | 16 | 64) != 0 {
+					return Ok(());
+				}
+				let vertices: Vec<(DVec2, f64)> = entity
+					.vertices
+					.iter()
+					// Spline frame control points only shape the fit vertices which follow them
+					.filter(|vertex| vertex.int(70).unwrap_or_default() & 16 == 0)
+					.map(|vertex| (vertex.point(10), vertex.float(42).unwrap_or_default()))
+					.collect();
+				polyline(&mut path, &vertices, flags & 1 == 1, entity.object_transform());
+			}
+			"SPLINE" => spline(&mut path, entity),
+			_ => return Ok(()),
+		}
+
+		path.apply_affine(to_affine(transform));
+		if path.elements().len() > 1 {
+			self.paths.push(DrawingPath { path, color, line_weight });
+		}
+		Ok(())
+	}
+
+	/// Draws the entities of a block, for every copy an insert places of it.
+	fn draw_insert(&mut self, entity: &Entity<'a>, transform: DAffine2, style: InheritedStyle, depth: usize) -> Result<(), EditorError> {
+		if depth >= MAX_INSERT_DEPTH {
+			return Ok(());
+		}
+		let block = match entity.string(2).and_then(|name| self.blocks.get(name)) {
+			Some(block) => block,
+			None => return Ok(()),
+		};
+		let entities = block.entities.clone();
+		let base_point = block.base_point;
+
+		let scale = DVec2::new(entity.float(41).unwrap_or(1.), entity.float(42).unwrap_or(1.));
+		let rotation = entity.float(50).unwrap_or_default().to_radians();
+		let placement = entity.object_transform() * DAffine2::from_translation(entity.point(10)) * DAffine2::from_angle(rotation);
+		let block_transform = DAffine2::from_scale(scale) * DAffine2::from_translation(-base_point);
+
+		// Inserts can place a grid of copies, spaced along the rotated axes of the block
+		let (columns, rows) = (entity.int(70).unwrap_or(1).max(1), entity.int(71).unwrap_or(1).max(1));
+		let spacing = DVec2::new(entity.float(44).unwrap_or_default(), entity.float(45).unwrap_or_default());
+		// Every copy counts against the budget, so large arrays of empty blocks are caught too
+		self.spend(columns.saturating_mul(rows).try_into().unwrap_or(usize::MAX))?;
+		for row in 0..rows {
+			for column in 0..columns {
+				let offset = DAffine2::from_translation(DVec2::new(column as f64, row as f64) * spacing);
+				let copy_transform = transform * placement * offset * block_transform;
+				for block_entity in &entities {
+					self.draw_entity(block_entity, copy_transform, style, depth + 1)?;
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Counts drawing the given number of entities against the budget of the import, failing once it's used up.
+	fn spend(&mut self, entities: usize) -> Result<(), EditorError> {
+		self.budget = self
+			.budget
+			.checked_sub(entities)
+			.ok_or_else(|| EditorError::Misc(format!("The drawing places more than {MAX_DRAWN_ENTITIES} entities through its block inserts, so it can't be imported")))?;
+		Ok(())
+	}
+
+	/// Writes the paths as SVG elements, flipping the drawing so its Y axis points down.
+	fn into_svg(self, pixels_per_unit: f64) -> Result<String, EditorError> {
+		let bounds = self
+			.paths
+			.iter()
+			.map(|drawing_path| drawing_path.path.bounding_box())
+			.reduce(|a, b| a.union(b))
+			.ok_or_else(|| EditorError::Misc("The file has no lines or curves which can be imported".into()))?;
+		let to_document = DAffine2::from_scale(DVec2::new(pixels_per_unit, -pixels_per_unit)) * DAffine2::from_translation(DVec2::new(-bounds.x0, -bounds.y1));
+		let size = DVec2::new(bounds.width(), bounds.height()) * pixels_per_unit;
+
+		let mut svg = String::new();
+		for DrawingPath { path, color, line_weight } in self.paths {
+			let mut subpath = Subpath::from(path.into_iter());
+			subpath.apply_affine(to_document);
+			// Line weights are given in hundredths of a millimeter, and CAD software draws the default weight, which is usually hairline thin, a pixel wide
+			let width = if line_weight > 0 { line_weight as f64 / 100. * PIXELS_PER_MILLIMETER } else { 1. };
+			let [r, g, b] = color;
+			let _ = write!(
+				svg,
+				r##"<path d="{}" fill="none" stroke="#{r:02x}{g:02x}{b:02x}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"##,
+				subpath.to_svg(),
+				number(width)
+			);
+		}
+		Ok(format!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">{svg}</svg>"#,
+			number(size.x.max(1.)),
+			number(size.y.max(1.))
+		))
+	}
+}
+
+/// The size of a unit of the drawing in document pixels, by its `$INSUNITS` number. Unitless drawings are taken to be in millimeters, like most made for cutting.
+fn pixels_per_unit(units: i64) -> f64 {
+	let millimeters = match units {
+		1 => 25.4,
+		2 => 304.8,
+		3 => 1_609_344.,
+		5 => 10.,
+		6 => 1000.,
+		7 => 1_000_000.,
+		8 => 25.4e-6,
+		9 => 25.4e-3,
+		10 => 914.4,
+		13 => 1e-3,
+		14 => 100.,
+		_ => 1.,
+	};
+	millimeters * PIXELS_PER_MILLIMETER
+}
+
+/// The 24-bit color an entity or layer gives in addition to its color number.
+fn true_color(entity: &Entity) -> Option<[u8; 3]> {
+	let color = entity.int(420)?;
+	Some([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+}
+
+/// The sRGB color of an AutoCAD Color Index number. Color 7 is drawn black on light backgrounds and white on dark ones, so it's imported as black.
+fn aci_color(index: u8) -> [u8; 3] {
+	match index {
+		1 => [255, 0, 0],
+		2 => [255, 255, 0],
+		3 => [0, 255, 0],
+		4 => [0, 255, 255],
+		5 => [0, 0, 255],
+		6 => [255, 0, 255],
+		8 => [128, 128, 128],
+		9 => [192, 192, 192],
+		// Shades of gray from dark to white
+		250..=255 => {
+			let level = [51, 80, 105, 130, 190, 255][index as usize - 250];
+			[level; 3]
+		}
+		// The rest go around the color wheel in 24 hues 15° apart, with ten shades of each, alternating between full and half saturation at five brightness levels
+		10..=249 => {
+			let hue = ((index - 10) / 10) as f64 * 15.;
+			let shade = (index % 10) as usize;
+			let value = [255., 204., 153., 127., 76.][shade / 2];
+			let saturation = if shade % 2 == 0 { 1. } else { 0.5 };
+			let channel = |n: f64| {
+				let k = (n + hue / 60.) % 6.;
+				(value - value * saturation * k.min(4. - k).clamp(0., 1.)).round() as u8
+			};
+			[channel(5.), channel(3.), channel(1.)]
+		}
+		_ => [0; 3],
+	}
+}
+
+/// Adds an arc of the unit circle between two angles in radians, placed by `transform`, connecting it to the end of the path if there is one.
+fn elliptical_arc(path: &mut BezPath, transform: DAffine2, start_angle: f64, end_angle: f64) {
+	let point_at = |angle: f64| transform.transform_point2(DVec2::new(angle.cos(), angle.sin()));
+	let tangent_at = |angle: f64| transform.transform_vector2(DVec2::new(-angle.sin(), angle.cos()));
+
+	if path.elements().is_empty() {
+		path.move_to(to_point(point_at(start_angle)));
+	}
+	// Each quarter of a circle or less is closely approximated by one cubic Bézier curve
+	let segments = ((end_angle - start_angle).abs() / FRAC_PI_2).ceil().max(1.);
+	let sweep = (end_angle - start_angle) / segments;
+	let handle_length = 4. / 3. * (sweep / 4.).tan();
+	for segment in 0..segments as usize {
+		let (from, to) = (start_angle + sweep * segment as f64, start_angle + sweep * (segment + 1) as f64);
+		path.curve_to(
+			to_point(point_at(from) + tangent_at(from) * handle_length),
+			to_point(point_at(to) - tangent_at(to) * handle_length),
+			to_point(point_at(to)),
+		);
+	}
+}
+
+/// Adds a polyline whose vertices each give the bulge of the segment after them, which is the tangent of a quarter of the angle of the arc it bends into.
+/// Positive bulges bend counterclockwise, and a bulge of zero keeps the segment straight.
+fn polyline(path: &mut BezPath, vertices: &[(DVec2, f64)], closed: bool, transform: DAffine2) {
+	let (first, _) = match vertices.first() {
+		Some(&vertex) => vertex,
+		None => return,
+	};
+	path.move_to(to_point(transform.transform_point2(first)));
+
+	let segment_count = if closed { vertices.len() } else { vertices.len() - 1 };
+	for index in 0..segment_count {
+		let (from, bulge) = vertices[index];
+		let (to, _) = vertices[(index + 1) % vertices.len()];
+		if bulge == 0. || from == to {
+			path.line_to(to_point(transform.transform_point2(to)));
+			continue;
+		}
+
+		let sweep = 4. * bulge.atan();
+		let chord = to - from;
+		// The center is off the middle of the chord by the distance which makes the arc span the sweep
+		let center = (from + to) / 2. + chord.perp() / (2. * (sweep / 2.).tan());
+		let radius = center.distance(from);
+		let start_angle = (from - center).y.atan2((from - center).x);
+		let circle = transform * DAffine2::from_scale_angle_translation(DVec2::splat(radius), 0., center);
+		elliptical_arc(path, circle, start_angle, start_angle + sweep);
+	}
+	if closed {
+		path.close_path();
+	}
+}
+
+/// Adds a spline, from its control points and knots or, for splines defined only by the points they pass through, from those.
+fn spline(path: &mut BezPath, entity: &Entity) {
+	let degree = entity.int(71).unwrap_or(3).clamp(1, 32) as usize;
+	let control_points = entity.points(10);
+	let knots: Vec<f64> = entity.floats(40).collect();
+
+	if control_points.len() <= degree || knots.len() != control_points.len() + degree + 1 {
+		let fit_points = entity.points(11);
+		if fit_points.len() >= 2 {
+			path.extend(BezPath::from(&Subpath::new_spline(fit_points)));
+		}
+		return;
+	}
+
+	// Rational splines weigh each control point, and are worked on in homogeneous coordinates
+	let weights: Vec<f64> = entity.floats(41).collect();
+	let weights = if weights.len() == control_points.len() { weights } else { vec![1.; control_points.len()] };
+	let rational = weights.iter().any(|&weight| (weight - weights[0]).abs() > 1e-12);
+	let control_points = control_points.iter().zip(&weights).map(|(point, &weight)| (*point * weight).extend(weight)).collect();
+
+	for (index, piece) in b_spline_to_bezier(degree, control_points, knots).into_iter().enumerate() {
+		let projected = |point: DVec3| point.truncate() / point.z;
+		if index == 0 {
+			path.move_to(to_point(projected(piece[0])));
+		}
+		match (rational, piece.as_slice()) {
+			(false, &[_, end]) => path.line_to(to_point(projected(end))),
+			(false, &[_, control, end]) => path.quad_to(to_point(projected(control)), to_point(projected(end))),
+			(false, &[_, first, second, end]) => path.curve_to(to_point(projected(first)), to_point(projected(second)), to_point(projected(end))),
+			// Curves whose shape cubic Bézier curves can't represent are approximated by a few curves, matching their positions and tangents at their ends
+			_ => {
+				for subdivision in 0..SPLINE_PIECE_SUBDIVISIONS {
+					let (start, end) = (subdivision as f64 / SPLINE_PIECE_SUBDIVISIONS as f64, (subdivision + 1) as f64 / SPLINE_PIECE_SUBDIVISIONS as f64);
+					let ((start_point, start_tangent), (end_point, end_tangent)) = (evaluate_rational_bezier(&piece, start), evaluate_rational_bezier(&piece, end));
+					let length = (end - start) / 3.;
+					path.curve_to(to_point(start_point + start_tangent * length), to_point(end_point - end_tangent * length), to_point(end_point));
+				}
+			}
+		}
+	}
+	if entity.int(70).unwrap_or_default() & 1 == 1 {
+		path.close_path();
+	}
+}
+
+/// Splits a B-spline into the Bézier curves of its pieces between distinct knots, by inserting each knot until it's repeated as many times as the degree.
+/// Control points are in homogeneous coordinates, so rational splines are split exactly too.
+fn b_spline_to_bezier(degree: usize, mut control_points: Vec<DVec3>, mut knots: Vec<f64>) -> Vec<Vec<DVec3>> {
+	// The spline is only defined between the knots which have a whole degree's worth of knots before and after them
+	let (domain_start, domain_end) = (knots[degree], knots[knots.len() - degree - 1]);
+	if domain_start.is_nan() || domain_end.is_nan() || domain_start >= domain_end {
+		return Vec::new();
+	}
+
+	let mut distinct_knots: Vec<f64> = knots.iter().copied().filter(|&knot| (domain_start..=domain_end).contains(&knot)).collect();
+	distinct_knots.dedup();
+	for knot in distinct_knots {
+		loop {
+			let multiplicity = knots.iter().filter(|&&other| other == knot).count();
+			// The span containing the knot, which is the last one starting at or before it
+			let span = match knots.iter().rposition(|&other| other <= knot) {
+				Some(span) if span + 1 < knots.len() && multiplicity < degree => span,
+				_ => break,
+			};
+
+			// Boehm's algorithm inserts a knot by blending the control points around it, which leaves the shape of the curve unchanged
+			let mut inserted = Vec::with_capacity(control_points.len() + 1);
+			for index in 0..=control_points.len() {
+				let point = if index + degree <= span {
+					control_points[index]
+				} else if index > span - multiplicity {
+					control_points[index - 1]
+				} else {
+					let alpha = (knot - knots[index]) / (knots[index + degree] - knots[index]);
+					control_points[index - 1] * (1. - alpha) + control_points[index] * alpha
+				};
+				inserted.push(point);
+			}
+			control_points = inserted;
+			knots.insert(span + 1, knot);
+		}
+	}
+
+	// Every knot in the domain is now repeated at least as many times as the degree, so the control points of each span are its Bézier curve
+	(degree..knots.len() - degree - 1)
+		.filter(|&span| knots[span] < knots[span + 1] && knots[span] >= domain_start && knots[span + 1] <= domain_end)
+		.filter_map(|span| control_points.get(span - degree..=span).map(<[DVec3]>::to_vec))
+		.collect()
+}
+
+/// The position and derivative of a Bézier curve in homogeneous coordinates, projected into the plane.
+fn evaluate_rational_bezier(control_points: &[DVec3], t: f64) -> (DVec2, DVec2) {
+	// De Casteljau's algorithm, stopped one level early since the last two points give the derivative
+	let mut points = control_points.to_vec();
+	while points.len() > 2 {
+		points = points.windows(2).map(|pair| pair[0].lerp(pair[1], t)).collect();
+	}
+	let (point, derivative) = match points.as_slice() {
+		&[first, second] => (first.lerp(second, t), (second - first) * (control_points.len() - 1) as f64),
+		_ => (control_points[0], DVec3::ZERO),
+	};
+	let position = point.truncate() / point.z;
+	(position, (derivative.truncate() - position * derivative.z) / point.z)
+}
+
+// Writing
+
+/// Builds a DXF drawing of the shape and text layers within `bounds`, in millimeters with the bottom left corner of the bounds at the origin.
+pub fn dxf_document(root: &Layer, bounds: [DVec2; 2], curves: DxfCurves, font_cache: &FontCache, color_space: ColorSpace) -> String {
+	// Document space, where Y points down, is flipped and measured in millimeters from the bottom left corner of the bounds
+	let to_drawing = DAffine2::from_scale(DVec2::new(1., -1.) / PIXELS_PER_MILLIMETER) * DAffine2::from_translation(-DVec2::new(bounds[0].x, bounds[1].y));
+	let size = (bounds[1] - bounds[0]) / PIXELS_PER_MILLIMETER;
+	let mut writer = DxfWriter {
+		entities: String::new(),
+		curves,
+		font_cache,
+		color_space,
+		extent: size,
+	};
+	writer.write_layer(root, to_drawing);
+
+	let mut dxf = String::new();
+	let (width, height) = (number(size.x), number(size.y));
+	// The header gives the version, the units (4 is millimeters), and the extent of the drawing
+	for (code, value) in [
+		(0, "SECTION"),
+		(2, "HEADER"),
+		(9, "$ACADVER"),
+		(1, "AC1015"),
+		(9, "$INSUNITS"),
+		(70, "4"),
+		(9, "$MEASUREMENT"),
+		(70, "1"),
+		(9, "$EXTMIN"),
+		(10, "0"),
+		(20, "0"),
+		(30, "0"),
+		(9, "$EXTMAX"),
+		(10, width.as_str()),
+		(20, height.as_str()),
+		(30, "0"),
+		(0, "ENDSEC"),
+		(0, "SECTION"),
+		(2, "ENTITIES"),
+	] {
+		group(&mut dxf, code, value);
+	}
+	dxf.push_str(&writer.entities);
+	for (code, value) in [(0, "ENDSEC"), (0, "EOF")] {
+		group(&mut dxf, code, value);
+	}
+	dxf
+}
+
+struct DxfWriter<'a> {
+	entities: String,
+	curves: DxfCurves,
+	font_cache: &'a FontCache,
+	color_space: ColorSpace,
+	/// The size of the exported region of the document, whose layers are left out if they're entirely outside of it
+	extent: DVec2,
+}
+
+impl DxfWriter<'_> {
+	/// Writes a layer, given the transform from the space of its parent to the drawing.
+	fn write_layer(&mut self, layer: &Layer, transform: DAffine2) {
+		if !layer.visible {
+			return;
+		}
+		match &layer.data {
+			LayerDataType::Folder(folder) => {
+				for child in folder.layers() {
+					self.write_layer(child, transform * layer.transform);
+				}
+			}
+			LayerDataType::Shape(shape) => self.write_shape(&shape.shape.with_rounded_corners(), &shape.style, transform * layer.transform),
+			LayerDataType::Text(text) => self.write_shape(&text.to_subpath_nonmut(self.font_cache), &text.path_style, transform * layer.transform),
+			// DXF has no images
+			LayerDataType::Image(_) | LayerDataType::Imaginate(_) => {}
+		}
+	}
+
+	fn write_shape(&mut self, subpath: &Subpath, style: &PathStyle, transform: DAffine2) {
+		let mut subpath = subpath.clone();
+		subpath.apply_affine(transform);
+		let [min, max] = match subpath.bounding_box() {
+			Some(bounds) => bounds,
+			None => return,
+		};
+		if max.cmplt(DVec2::ZERO).any() || min.cmpgt(self.extent).any() {
+			return;
+		}
+
+		// Machines often pick their operation by color, so each path has the color it's drawn with
+		let color = match (style.stroke().and_then(|stroke| stroke.color()), style.fill()) {
+			(Some(color), _) => Some(color),
+			(None, Fill::Solid(color)) => Some(*color),
+			_ => None,
+		};
+		let color = color.map_or(7, |color| nearest_aci_color(color.convert_color_space(self.color_space, ColorSpace::Srgb)));
+
+		for (start, segments, closed) in contours(&BezPath::from(&subpath)) {
+			// Contours of straight lines are always polylines
+			if segments.iter().all(is_straight) || self.curves == DxfCurves::Arcs {
+				self.write_polyline(start, &segments, closed, color);
+			} else {
+				self.write_spline(start, &segments, color);
+			}
+		}
+	}
+
+	/// Writes a contour as a polyline, whose curves are approximated by arcs.
+	fn write_polyline(&mut self, start: DVec2, segments: &[Bezier], closed: bool, color: u8) {
+		let mut vertices = vec![(start, 0.)];
+		let options = ArcsOptions {
+			error: ARC_TOLERANCE,
+			..Default::default()
+		};
+		for bezier in segments {
+			if is_straight(bezier) {
+				vertices.push((bezier.end(), 0.));
+				continue;
+			}
+			for arc in bezier.arcs(options) {
+				let point_at = |angle: f64| arc.center + arc.radius * DVec2::new(angle.cos(), angle.sin());
+				let (start_point, end_point) = (point_at(arc.start_angle), point_at(arc.end_angle));
+				// The arcs run counterclockwise, so those the curve follows the other way are reversed
+				let sweep = (arc.end_angle - arc.start_angle).rem_euclid(TAU);
+				let current = vertices.last().unwrap().0;
+				let (from, to, sweep) = if current.distance(start_point) <= current.distance(end_point) {
+					(start_point, end_point, sweep)
+				} else {
+					(end_point, start_point, -sweep)
+				};
+				// Nearly straight stretches of the curve are left between the arcs, and become straight segments
+				if current.distance(from) > ARC_TOLERANCE / 100. {
+					vertices.push((from, 0.));
+				}
+				vertices.last_mut().unwrap().1 = (sweep / 4.).tan();
+				vertices.push((to, 0.));
+			}
+			let end = bezier.end();
+			if vertices.last().unwrap().0.distance(end) > ARC_TOLERANCE / 100. {
+				vertices.push((end, 0.));
+			}
+		}
+
+		if closed && vertices.len() > 1 && vertices[0].0.distance(vertices.last().unwrap().0) <= ARC_TOLERANCE / 100. {
+			vertices.pop();
+		}
+
+		self.entity("POLYLINE", color);
+		for (code, value) in [(66, 1.), (10, 0.), (20, 0.), (30, 0.), (70, if closed { 1. } else { 0. })] {
+			group(&mut self.entities, code, &number(value));
+		}
+		for (vertex, bulge) in vertices {
+			self.entity("VERTEX", color);
+			for (code, value) in [(10, vertex.x), (20, vertex.y), (30, 0.)] {
+				group(&mut self.entities, code, &number(value));
+			}
+			if bulge != 0. {
+				group(&mut self.entities, 42, &number(bulge));
+			}
+		}
+		self.entity("SEQEND", color);
+	}
+
+	/// Writes a contour as a cubic B-spline, whose knots are each repeated three times so its pieces are exactly the curves of the contour.
+	fn write_spline(&mut self, start: DVec2, segments: &[Bezier], color: u8) {
+		let mut control_points = vec![start];
+		for bezier in segments {
+			let (from, end) = (bezier.start(), bezier.end());
+			let (first, second) = match (bezier.handle_start(), bezier.handle_end()) {
+				(Some(first), Some(second)) => (first, second),
+				// Quadratic curves are raised to cubic ones of the same shape
+				(Some(control), None) => (from + (control - from) * (2. / 3.), end + (control - end) * (2. / 3.)),
+				_ => (from.lerp(end, 1. / 3.), from.lerp(end, 2. / 3.)),
+			};
+			control_points.extend([first, second, end]);
+		}
+
+		let pieces = segments.len();
+		let mut knots = vec![0.; 4];
+		knots.extend((1..pieces).flat_map(|piece| [piece as f64; 3]));
+		knots.extend([pieces as f64; 4]);
+
+		self.entity("SPLINE", color);
+		// The spline lies flat in the plane of the drawing
+		for (code, value) in [
+			(210, 0.),
+			(220, 0.),
+			(230, 1.),
+			(70, 8.),
+			(71, 3.),
+			(72, knots.len() as f64),
+			(73, control_points.len() as f64),
+			(74, 0.),
+		] {
+			group(&mut self.entities, code, &number(value));
+		}
+		for knot in knots {
+			group(&mut self.entities, 40, &number(knot));
+		}
+		for point in control_points {
+			for (code, value) in [(10, point.x), (20, point.y), (30, 0.)] {
+				group(&mut self.entities, code, &number(value));
+			}
+		}
+	}
+
+	fn entity(&mut self, kind: &str, color: u8) {
+		group(&mut self.entities, 0, kind);
+		group(&mut self.entities, 8, "0");
+		group(&mut self.entities, 62, &color.to_string());
+	}
+}
+
+/// Splits a path into its contours, each with its start, its segments, and whether it's closed.
+fn contours(path: &BezPath) -> Vec<(DVec2, Vec<Bezier>, bool)> {
+	let to_vec = |point: Point| DVec2::new(point.x, point.y);
+	let mut contours: Vec<(DVec2, Vec<Bezier>, bool)> = Vec::new();
+	let mut current = DVec2::ZERO;
+	for element in path.elements() {
+		if let PathEl::MoveTo(to) = element {
+			current = to_vec(*to);
+			contours.push((current, Vec::new(), false));
+			continue;
+		}
+		let (start, segments, closed) = match contours.last_mut() {
+			Some(contour) => contour,
+			None => continue,
+		};
+		let segment = match *element {
+			PathEl::LineTo(to) => Some(Bezier::from_linear_dvec2(current, to_vec(to))),
+			PathEl::QuadTo(control, to) => Some(Bezier::from_quadratic_dvec2(current, to_vec(control), to_vec(to))),
+			PathEl::CurveTo(first, second, to) => Some(Bezier::from_cubic_dvec2(current, to_vec(first), to_vec(second), to_vec(to))),
+			PathEl::ClosePath => {
+				*closed = true;
+				(current != *start).then(|| Bezier::from_linear_dvec2(current, *start))
+			}
+			PathEl::MoveTo(_) => None,
+		};
+		if let Some(segment) = segment {
+			current = segment.end();
+			segments.push(segment);
+		}
+	}
+	contours
+}
+
+fn is_straight(bezier: &Bezier) -> bool {
+	bezier.handle_start().is_none()
+}
+
+/// The AutoCAD Color Index number whose color is closest to the given one.
+fn nearest_aci_color(color: Color) -> u8 {
+	let target = [color.r(), color.g(), color.b()].map(|channel| channel.clamp(0., 1.) * 255.);
+	(1..=255)
+		.min_by(|&a, &b| {
+			let distance = |index: u8| aci_color(index).iter().zip(target).map(|(&channel, target)| (channel as f32 - target).powi(2)).sum::<f32>();
+			distance(a).total_cmp(&distance(b))
+		})
+		.unwrap_or(7)
+}
+
+fn group(dxf: &mut String, code: i32, value: &str) {
+	let _ = writeln!(dxf, "{code:>3}\n{value}");
+}
+
+fn to_point(point: DVec2) -> Point {
+	Point::new(point.x, point.y)
+}
+
+fn to_affine(transform: DAffine2) -> kurbo::Affine {
+	kurbo::Affine::new(transform.to_cols_array())
+}
+
+/// Formats a number with trailing zeros removed.
+fn number(value: f64) -> String {
+	let value = if value.is_finite() { value } else { 0. };
+	let formatted = format!("{value:.6}");
+	let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+	match formatted {
+		"-0" | "" => "0".to_string(),
+		_ => formatted.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use kurbo::ParamCurve;
+
+	/// Reads back the paths of the entities of a drawing, in its own units.
+	fn read_paths(dxf: &str) -> Vec<BezPath> {
+		let groups = parse_groups(dxf).unwrap();
+		let start = groups.iter().position(|&group| group == (2, "ENTITIES")).unwrap() + 1;
+		let end = groups.iter().rposition(|&group| group == (0, "ENDSEC")).unwrap();
+		let mut reader = DxfReader {
+			layers: HashMap::new(),
+			blocks: HashMap::new(),
+			paths: Vec::new(),
+			budget: MAX_DRAWN_ENTITIES,
+		};
+		for entity in parse_entities(&groups[start..end]) {
+			reader.draw_entity(&entity, DAffine2::IDENTITY, InheritedStyle { color: [0; 3], line_weight: -3 }, 0).unwrap();
+		}
+		reader.paths.into_iter().map(|drawing_path| drawing_path.path).collect()
+	}
+
+	/// Writes the outline of a subpath, given in millimeters, and reads it back.
+	fn round_trip(subpath: &Subpath, curves: DxfCurves) -> Vec<BezPath> {
+		let font_cache = FontCache::default();
+		let mut writer = DxfWriter {
+			entities: String::new(),
+			curves,
+			font_cache: &font_cache,
+			color_space: ColorSpace::Srgb,
+			extent: DVec2::splat(1000.),
+		};
+		writer.write_shape(subpath, &PathStyle::default(), DAffine2::IDENTITY);
+		read_paths(&format!("  0\nSECTION\n  2\nENTITIES\n{}  0\nENDSEC\n  0\nEOF\n", writer.entities))
+	}
+
+	/// Points spread along every segment of a path.
+	fn samples(path: &BezPath) -> Vec<DVec2> {
+		path.segments()
+			.flat_map(|segment| (0..=8).map(move |step| segment.eval(step as f64 / 8.)))
+			.map(|point| DVec2::new(point.x, point.y))
+			.collect()
+	}
+
+	fn end_points(path: &BezPath) -> (DVec2, DVec2) {
+		let points = samples(path);
+		(points[0], *points.last().unwrap())
+	}
+
+	#[test]
+	fn bulges_bend_counterclockwise() {
+		let mut path = BezPath::new();
+		// A bulge of 1 is a half circle
+		polyline(&mut path, &[(DVec2::ZERO, 1.), (DVec2::new(2., 0.), 0.)], false, DAffine2::IDENTITY);
+		for point in samples(&path) {
+			assert!((point.distance(DVec2::new(1., 0.)) - 1.).abs() < 1e-3, "{point}");
+			assert!(point.y <= 1e-9);
+		}
+		assert!(samples(&path).iter().any(|point| point.abs_diff_eq(DVec2::new(1., -1.), 1e-9)));
+
+		// A negative bulge bends the other way, and a bulge of zero is straight
+		let mut path = BezPath::new();
+		polyline(
+			&mut path,
+			&[(DVec2::ZERO, -(TAU / 16.).tan()), (DVec2::new(2., 0.), 0.), (DVec2::new(2., 2.), 0.)],
+			false,
+			DAffine2::IDENTITY,
+		);
+		assert!(samples(&path).iter().all(|point| point.y >= -1e-9));
+		assert!(matches!(path.elements().last(), Some(PathEl::LineTo(_))));
+	}
+
+	#[test]
+	fn arcs_run_counterclockwise() {
+		let groups = [(0, "ARC"), (10, "1"), (20, "1"), (40, "2"), (50, "0"), (51, "90")];
+		let paths = read_paths(&format!(
+			"  2\nENTITIES\n{}  0\nENDSEC\n",
+			groups.iter().map(|(code, value)| format!("{code:>3}\n{value}\n")).collect::<String>()
+		));
+		let (start, end) = end_points(&paths[0]);
+		assert!(start.abs_diff_eq(DVec2::new(3., 1.), 1e-9));
+		assert!(end.abs_diff_eq(DVec2::new(1., 3.), 1e-9));
+		for point in samples(&paths[0]) {
+			assert!((point.distance(DVec2::ONE) - 2.).abs() < 1e-3, "{point}");
+			assert!(point.x >= 1. - 1e-9 && point.y >= 1. - 1e-9);
+		}
+	}
+
+	#[test]
+	fn curves_round_trip_through_arcs() {
+		let circle = Subpath::new_ellipse(DVec2::splat(10.), DVec2::splat(30.));
+		let paths = round_trip(&circle, DxfCurves::Arcs);
+		assert_eq!(paths.len(), 1);
+		assert!(matches!(paths[0].elements().last(), Some(PathEl::ClosePath)));
+		// The arcs stray from the circle by no more than the tolerance, on top of the error of drawing them as cubic curves
+		for point in samples(&paths[0]) {
+			assert!((point.distance(DVec2::splat(20.)) - 10.).abs() < ARC_TOLERANCE * 2., "{point}");
+		}
+
+		let line = Subpath::new_poly_line(vec![DVec2::ZERO, DVec2::new(10., 0.), DVec2::new(10., 5.)]);
+		for curves in [DxfCurves::Arcs, DxfCurves::Splines] {
+			let paths = round_trip(&line, curves);
+			assert_eq!(paths[0].elements().iter().filter(|element| matches!(element, PathEl::LineTo(_))).count(), 2);
+			assert_eq!(end_points(&paths[0]), (DVec2::ZERO, DVec2::new(10., 5.)));
+		}
+	}
+
+	#[test]
+	fn curves_round_trip_through_splines() {
+		let mut subpath = Subpath::new_ellipse(DVec2::ZERO, DVec2::new(40., 20.));
+		subpath.apply_affine(DAffine2::from_translation(DVec2::new(5., 5.)));
+		let original = BezPath::from(&subpath);
+
+		let paths = round_trip(&subpath, DxfCurves::Splines);
+		assert_eq!(paths.len(), 1);
+		// The pieces of the spline are exactly the curves of the ellipse, up to the precision numbers are written with
+		let (original, read) = (samples(&original), samples(&paths[0]));
+		assert_eq!(original.len(), read.len());
+		for (original, read) in original.iter().zip(&read) {
+			assert!(original.abs_diff_eq(*read, 1e-5), "{original} {read}");
+		}
+	}
+
+	/// A drawing of a block holding a line and an insert of the given block, which is placed once.
+	fn nested_blocks(inner_insert: &str) -> String {
+		let block = format!("  0\nBLOCK\n  2\nA\n 10\n0\n 20\n0\n  0\nLINE\n 10\n0\n 20\n0\n 11\n1\n 21\n1\n  0\nINSERT\n  2\n{inner_insert}\n 70\n100\n 71\n100\n 44\n1\n 45\n1\n  0\nENDBLK\n");
+		format!("  0\nSECTION\n  2\nBLOCKS\n{block}  0\nENDSEC\n  0\nSECTION\n  2\nENTITIES\n  0\nINSERT\n  2\nA\n  0\nENDSEC\n  0\nEOF\n")
+	}
+
+	#[test]
+	fn self_nesting_array_blocks_use_up_the_budget() {
+		// Each level places a 100 by 100 array of the block, which would draw 10000^16 lines before reaching the depth limit
+		assert!(dxf_to_svg(nested_blocks("A").as_bytes()).is_err());
+
+		// Inserts of missing blocks place nothing, so only the line of the block is drawn
+		let svg = dxf_to_svg(nested_blocks("Missing").as_bytes()).unwrap();
+		assert_eq!(svg.matches("<path").count(), 1);
+	}
+
+	#[test]
+	fn splines_split_at_their_knots() {
+		let control_points = [DVec2::ZERO, DVec2::new(1., 2.), DVec2::new(3., 2.), DVec2::new(4., 0.), DVec2::new(6., 1.)].map(|point| point.extend(1.));
+
+		// A clamped spline with no knots inside its domain is a single Bézier curve of its control points
+		let pieces = b_spline_to_bezier(3, control_points[..4].to_vec(), vec![0., 0., 0., 0., 1., 1., 1., 1.]);
+		assert_eq!(pieces, [control_points[..4].to_vec()]);
+
+		// Each knot inside the domain splits it into another piece, and neighboring pieces meet
+		let pieces = b_spline_to_bezier(3, control_points.to_vec(), vec![0., 0., 0., 0., 0.5, 1., 1., 1., 1.]);
+		assert_eq!(pieces.len(), 2);
+		assert_eq!(pieces[0][0], control_points[0]);
+		assert!(pieces[0][3].abs_diff_eq(pieces[1][0], 1e-12));
+		assert_eq!(pieces[1][3], control_points[4]);
+
+		// Knots which leave the domain empty have no pieces
+		for knots in [vec![0.; 8], vec![0., 0., 0., f64::NAN, 1., 1., 1., 1.], vec![1., 1., 1., 1., 0., 0., 0., 0.]] {
+			assert!(b_spline_to_bezier(3, control_points[..4].to_vec(), knots).is_empty());
+		}
+	}
+}
@@ -0,0 +1,796 @@
+//! Reads the font files which users can load into the editor, converting the web font formats WOFF and WOFF2 back into the TrueType or OpenType font they compress.
+//!
+//! The tables of the font itself are read with `ttf-parser`, while the containers of the web fonts and the transformed tables of WOFF2 are read here.
+
+use crate::messages::portfolio::document::utility_types::error::EditorError;
+
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+
+/// Fonts larger than this once decompressed are assumed to be corrupt, rather than allocating whatever the file claims.
+const MAX_FONT_SIZE: usize = 256 * 1024 * 1024;
+/// The most tables a font can have while the binary search values of its table directory still fit in 16 bits.
+const MAX_TABLE_COUNT: u16 = 4095;
+
+/// The tags of the tables which WOFF2 files refer to by their index in this list.
+const WOFF2_KNOWN_TAGS: [&[u8; 4]; 63] = [
+	b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm", b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern", b"LTSH",
+	b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC", b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar", b"bdat", b"bloc",
+	b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty", b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat", b"Gloc", b"Feat", b"Sill",
+];
+
+// Flags of the points of simple glyphs in the `glyf` table
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+// Flags of the components of composite glyphs in the `glyf` table
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+fn corrupt() -> EditorError {
+	EditorError::Misc("The font file is corrupt".into())
+}
+
+/// Converts a font file into the TrueType or OpenType font it contains, decompressing WOFF and WOFF2 files.
+pub fn decode_font_file(data: &[u8]) -> Result<Vec<u8>, EditorError> {
+	match data.get(..4) {
+		Some(b"\x00\x01\x00\x00" | b"OTTO" | b"true" | b"ttcf") => Ok(data.to_vec()),
+		Some(b"wOFF") => decode_woff(data),
+		Some(b"wOF2") => decode_woff2(data),
+		_ => Err(EditorError::Misc("The file isn't a TrueType, OpenType, WOFF, or WOFF2 font".into())),
+	}
+}
+
+/// Reads big-endian numbers from a font file, failing on reads past its end.
+struct Reader<'a> {
+	data: &'a [u8],
+	position: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn new(data: &'a [u8]) -> Self {
+		Self { data, position: 0 }
+	}
+
+	fn bytes(&mut self, length: usize) -> Result<&'a [u8], EditorError> {
+		let bytes = self.data.get(self.position..self.position.checked_add(length).ok_or_else(corrupt)?).ok_or_else(corrupt)?;
+		self.position += length;
+		Ok(bytes)
+	}
+
+	fn u8(&mut self) -> Result<u8, EditorError> {
+		Ok(self.bytes(1)?[0])
+	}
+
+	fn u16(&mut self) -> Result<u16, EditorError> {
+		let bytes = self.bytes(2)?;
+		Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+	}
+
+	fn i16(&mut self) -> Result<i16, EditorError> {
+		Ok(self.u16()? as i16)
+	}
+
+	fn u32(&mut self) -> Result<u32, EditorError> {
+		let bytes = self.bytes(4)?;
+		Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+	}
+
+	/// Reads a variable-length number of up to 32 bits, stored seven bits to a byte.
+	fn uint_base_128(&mut self) -> Result<u32, EditorError> {
+		let mut value: u32 = 0;
+		for index in 0..5 {
+			let byte = self.u8()?;
+			// Leading zeros aren't allowed, and the value can't overflow
+			if (index == 0 && byte == 0x80) || value & 0xFE00_0000 != 0 {
+				return Err(corrupt());
+			}
+			value = (value << 7) | (byte & 0x7F) as u32;
+			if byte & 0x80 == 0 {
+				return Ok(value);
+			}
+		}
+		Err(corrupt())
+	}
+
+	/// Reads a variable-length number of up to 16 bits, which takes a single byte below 253.
+	fn uint_255_16(&mut self) -> Result<u16, EditorError> {
+		Ok(match self.u8()? {
+			253 => self.u16()?,
+			254 => self.u8()? as u16 + 253 * 2,
+			255 => self.u8()? as u16 + 253,
+			code => code as u16,
+		})
+	}
+}
+
+/// A table of a font, which is written into the font's table directory.
+struct FontTable {
+	tag: [u8; 4],
+	data: Vec<u8>,
+}
+
+/// The checksum of a table, which is the sum of its data as big-endian 32-bit numbers.
+fn table_checksum(data: &[u8]) -> u32 {
+	data.chunks(4).fold(0_u32, |sum, chunk| {
+		let mut word = [0; 4];
+		word[..chunk.len()].copy_from_slice(chunk);
+		sum.wrapping_add(u32::from_be_bytes(word))
+	})
+}
+
+/// Writes the tables into a font file with the given flavor, which is the version number of TrueType fonts or `OTTO` for fonts with CFF outlines.
+fn write_font(flavor: u32, mut tables: Vec<FontTable>) -> Vec<u8> {
+	tables.sort_by_key(|table| table.tag);
+
+	// The checksum adjustment in the `head` table is left out of its checksum, and set once the rest of the font is written
+	let head = tables.iter_mut().find(|table| &table.tag == b"head" && table.data.len() >= 12);
+	if let Some(head) = head {
+		head.data[8..12].fill(0);
+	}
+
+	// The table directory includes values to speed up binary searches through it, which only fit in 16 bits for up to `MAX_TABLE_COUNT` tables
+	let table_count = tables.len() as u16;
+	let entry_selector = 15 - table_count.max(1).leading_zeros() as u16;
+	let search_range = (1 << entry_selector) * 16;
+	let range_shift = table_count * 16 - search_range;
+
+	let mut font = Vec::new();
+	font.extend(flavor.to_be_bytes());
+	for value in [table_count, search_range, entry_selector, range_shift] {
+		font.extend(value.to_be_bytes());
+	}
+
+	let mut offset = 12 + tables.len() * 16;
+	for table in &tables {
+		font.extend(table.tag);
+		font.extend(table_checksum(&table.data).to_be_bytes());
+		font.extend((offset as u32).to_be_bytes());
+		font.extend((table.data.len() as u32).to_be_bytes());
+		offset += (table.data.len() + 3) & !3;
+	}
+
+	let mut head_offset = None;
+	for table in &tables {
+		if &table.tag == b"head" && table.data.len() >= 12 {
+			head_offset = Some(font.len());
+		}
+		font.extend(&table.data);
+		font.resize((font.len() + 3) & !3, 0);
+	}
+
+	// The checksum adjustment makes the checksum of the whole font come out to a magic number
+	if let Some(head_offset) = head_offset {
+		let adjustment = 0xB1B0_AFBA_u32.wrapping_sub(table_checksum(&font));
+		font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+	}
+
+	font
+}
+
+/// Decompresses a WOFF file, whose tables are each compressed separately with zlib.
+fn decode_woff(data: &[u8]) -> Result<Vec<u8>, EditorError> {
+	let mut reader = Reader::new(data);
+	reader.bytes(4)?;
+	let flavor = reader.u32()?;
+	reader.u32()?;
+	let table_count = reader.u16()?;
+	reader.bytes(2)?;
+	if table_count > MAX_TABLE_COUNT || reader.u32()? as usize > MAX_FONT_SIZE {
+		return Err(corrupt());
+	}
+	reader.bytes(24)?;
+
+	let mut tables = Vec::with_capacity(table_count as usize);
+	for _ in 0..table_count {
+		let tag = reader.bytes(4)?.try_into().map_err(|_| corrupt())?;
+		let offset = reader.u32()? as usize;
+		let compressed_length = reader.u32()? as usize;
+		let length = reader.u32()? as usize;
+		reader.u32()?;
+
+		let stored = data.get(offset..offset.checked_add(compressed_length).ok_or_else(corrupt)?).ok_or_else(corrupt)?;
+		// Tables which didn't get smaller when compressed are stored as they are
+		let table = match compressed_length < length {
+			true => {
+				let mut table = Vec::new();
+				ZlibDecoder::new(stored).take(length as u64).read_to_end(&mut table).map_err(|_| corrupt())?;
+				table
+			}
+			false => stored.to_vec(),
+		};
+		if table.len() != length {
+			return Err(corrupt());
+		}
+		tables.push(FontTable { tag, data: table });
+	}
+
+	Ok(write_font(flavor, tables))
+}
+
+/// An entry of the table directory of a WOFF2 file.
+struct Woff2Table {
+	tag: [u8; 4],
+	length: usize,
+	/// The length of the table in the decompressed data, which differs from its length in the font if it's transformed
+	stored_length: usize,
+	transformed: bool,
+}
+
+/// Decompresses a WOFF2 file, whose tables are compressed together with Brotli after the glyph outlines and metrics are transformed into a more compressible form.
+fn decode_woff2(data: &[u8]) -> Result<Vec<u8>, EditorError> {
+	let mut reader = Reader::new(data);
+	reader.bytes(4)?;
+	let flavor = reader.u32()?;
+	if &flavor.to_be_bytes() == b"ttcf" {
+		return Err(EditorError::Misc("Font collections in WOFF2 files aren't supported".into()));
+	}
+	reader.u32()?;
+	let table_count = reader.u16()?;
+	reader.bytes(2)?;
+	if table_count > MAX_TABLE_COUNT || reader.u32()? as usize > MAX_FONT_SIZE {
+		return Err(corrupt());
+	}
+	let compressed_length = reader.u32()? as usize;
+	reader.bytes(24)?;
+
+	let mut directory = Vec::with_capacity(table_count as usize);
+	for _ in 0..table_count {
+		let flags = reader.u8()?;
+		let tag = match flags & 0x3F {
+			63 => reader.bytes(4)?.try_into().map_err(|_| corrupt())?,
+			index => *WOFF2_KNOWN_TAGS[index as usize],
+		};
+		let length = reader.uint_base_128()? as usize;
+
+		// The glyph outlines and locations are transformed unless their transform version is 3, and other tables are transformed unless it's 0
+		let transform_version = flags >> 6;
+		let transformed = if &tag == b"glyf" || &tag == b"loca" { transform_version != 3 } else { transform_version != 0 };
+		let stored_length = if transformed { reader.uint_base_128()? as usize } else { length };
+		if length > MAX_FONT_SIZE || stored_length > MAX_FONT_SIZE {
+			return Err(corrupt());
+		}
+
+		directory.push(Woff2Table {
+			tag,
+			length,
+			stored_length,
+			transformed,
+		});
+	}
+
+	// Only as much is decompressed as the tables need, however much the compressed data expands to
+	let total_length = directory.iter().map(|table| table.stored_length).sum::<usize>();
+	if total_length > MAX_FONT_SIZE {
+		return Err(corrupt());
+	}
+	let compressed = data.get(reader.position..reader.position.checked_add(compressed_length).ok_or_else(corrupt)?).ok_or_else(corrupt)?;
+	let mut decompressed = Vec::new();
+	brotli_decompressor::Decompressor::new(compressed, 4096)
+		.take(total_length as u64)
+		.read_to_end(&mut decompressed)
+		.map_err(|_| corrupt())?;
+
+	// The tables follow each other in the decompressed data without any padding
+	let mut stored_tables = Vec::with_capacity(directory.len());
+	let mut offset = 0;
+	for table in &directory {
+		let end = offset + table.stored_length;
+		stored_tables.push(decompressed.get(offset..end).ok_or_else(corrupt)?);
+		offset = end;
+	}
+	let stored_table = |tag: &[u8; 4]| directory.iter().position(|table| &table.tag == tag).map(|index| stored_tables[index]);
+
+	let mut tables = Vec::with_capacity(directory.len());
+	let mut glyph_x_mins = None;
+	let mut reconstructed_loca = None;
+	for (table, stored) in directory.iter().zip(&stored_tables) {
+		if !table.transformed {
+			tables.push(FontTable {
+				tag: table.tag,
+				data: stored.to_vec(),
+			});
+			continue;
+		}
+
+		match &table.tag {
+			b"glyf" => {
+				let (glyf, loca, x_mins) = reconstruct_glyf(stored)?;
+				tables.push(FontTable { tag: table.tag, data: glyf });
+				reconstructed_loca = Some(loca);
+				glyph_x_mins = Some(x_mins);
+			}
+			// The glyph locations are rebuilt along with the glyph outlines, and the metrics once the left edges of the glyphs are known
+			b"loca" | b"hmtx" => {}
+			_ => return Err(EditorError::Misc("The font file uses a transform which isn't supported".into())),
+		}
+	}
+
+	if let Some(loca) = reconstructed_loca {
+		let table = directory.iter().find(|table| &table.tag == b"loca").ok_or_else(corrupt)?;
+		if loca.len() != table.length {
+			return Err(corrupt());
+		}
+		tables.push(FontTable { tag: *b"loca", data: loca });
+	}
+
+	// The side bearings left out of a transformed `hmtx` table are the left edges of the glyphs
+	if let Some(table) = directory.iter().find(|table| &table.tag == b"hmtx" && table.transformed) {
+		let x_mins = glyph_x_mins.as_deref().ok_or_else(corrupt)?;
+		let hhea = stored_table(b"hhea").and_then(ttf_parser::hhea::Table::parse).ok_or_else(corrupt)?;
+		let maxp = stored_table(b"maxp").and_then(ttf_parser::maxp::Table::parse).ok_or_else(corrupt)?;
+		if maxp.number_of_glyphs.get() as usize != x_mins.len() {
+			return Err(corrupt());
+		}
+		let hmtx = reconstruct_hmtx(stored_table(b"hmtx").ok_or_else(corrupt)?, hhea.number_of_metrics as usize, x_mins)?;
+		if hmtx.len() != table.length {
+			return Err(corrupt());
+		}
+		tables.push(FontTable { tag: *b"hmtx", data: hmtx });
+	}
+
+	Ok(write_font(flavor, tables))
+}
+
+/// The `glyf` and `loca` tables rebuilt from a WOFF2 file, and the left edge of each glyph.
+type ReconstructedGlyphs = (Vec<u8>, Vec<u8>, Vec<i16>);
+
+/// Rebuilds the `glyf` and `loca` tables from the transformed glyph data of a WOFF2 file, which splits the glyphs into separate streams of similar values.
+/// Also returns the left edge of each glyph, for rebuilding the metrics.
+fn reconstruct_glyf(data: &[u8]) -> Result<ReconstructedGlyphs, EditorError> {
+	let mut header = Reader::new(data);
+	header.u16()?;
+	let option_flags = header.u16()?;
+	let glyph_count = header.u16()? as usize;
+	let long_offsets = header.u16()? != 0;
+
+	let mut streams = Vec::with_capacity(7);
+	let mut offset: usize = 36;
+	for _ in 0..7 {
+		let length = header.u32()? as usize;
+		let end = offset.checked_add(length).ok_or_else(corrupt)?;
+		streams.push(Reader::new(data.get(offset..end).ok_or_else(corrupt)?));
+		offset = end;
+	}
+	let [mut contour_counts, mut point_counts, mut flags, mut glyph_stream, mut composites, mut bounding_boxes, mut instructions]: [Reader; 7] = streams.try_into().map_err(|_| corrupt())?;
+
+	let bitmap_length = glyph_count.div_ceil(32) * 4;
+	let bounding_box_bitmap = bounding_boxes.bytes(bitmap_length)?;
+	let overlap_bitmap = if option_flags & 1 != 0 {
+		Some(data.get(offset..offset + glyph_count.div_ceil(8)).ok_or_else(corrupt)?)
+	} else {
+		None
+	};
+	let bit = |bitmap: &[u8], index: usize| bitmap[index / 8] & (0x80 >> (index % 8)) != 0;
+
+	let mut glyf = Vec::new();
+	let mut glyph_offsets = Vec::with_capacity(glyph_count + 1);
+	let mut x_mins = Vec::with_capacity(glyph_count);
+
+	for glyph in 0..glyph_count {
+		glyph_offsets.push(glyf.len());
+		let contour_count = contour_counts.i16()?;
+		let has_bounding_box = bit(bounding_box_bitmap, glyph);
+
+		match contour_count {
+			0 => {
+				if has_bounding_box {
+					return Err(corrupt());
+				}
+				x_mins.push(0);
+			}
+			// Composite glyphs are copied as they are, always with an explicit bounding box
+			-1 => {
+				if !has_bounding_box {
+					return Err(corrupt());
+				}
+				let bounding_box = bounding_boxes.bytes(8)?;
+
+				let start = composites.position;
+				let mut has_instructions = false;
+				loop {
+					let component_flags = composites.u16()?;
+					has_instructions |= component_flags & WE_HAVE_INSTRUCTIONS != 0;
+					let mut length = 2 + if component_flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+					if component_flags & WE_HAVE_A_SCALE != 0 {
+						length += 2;
+					} else if component_flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+						length += 4;
+					} else if component_flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+						length += 8;
+					}
+					composites.bytes(length)?;
+					if component_flags & MORE_COMPONENTS == 0 {
+						break;
+					}
+				}
+
+				glyf.extend((-1_i16).to_be_bytes());
+				glyf.extend(bounding_box);
+				glyf.extend(&composites.data[start..composites.position]);
+				if has_instructions {
+					let instruction_length = glyph_stream.uint_255_16()?;
+					glyf.extend(instruction_length.to_be_bytes());
+					glyf.extend(instructions.bytes(instruction_length as usize)?);
+				}
+				x_mins.push(i16::from_be_bytes([bounding_box[0], bounding_box[1]]));
+			}
+			contour_count if contour_count > 0 => {
+				let mut end_points = Vec::with_capacity(contour_count as usize);
+				let mut point_count: usize = 0;
+				for _ in 0..contour_count {
+					point_count += point_counts.uint_255_16()? as usize;
+					end_points.push(point_count.checked_sub(1).ok_or_else(corrupt)?);
+				}
+				if point_count > u16::MAX as usize + 1 {
+					return Err(corrupt());
+				}
+
+				let mut points = Vec::with_capacity(point_count);
+				let (mut x, mut y) = (0_i32, 0_i32);
+				for _ in 0..point_count {
+					let flag = flags.u8()?;
+					let (dx, dy) = decode_triplet(flag & 0x7F, &mut glyph_stream)?;
+					x += dx;
+					y += dy;
+					points.push((x, y, flag & 0x80 == 0));
+				}
+				let instruction_length = glyph_stream.uint_255_16()?;
+
+				let bounding_box = if has_bounding_box {
+					[bounding_boxes.i16()?, bounding_boxes.i16()?, bounding_boxes.i16()?, bounding_boxes.i16()?]
+				} else {
+					let clamp = |value: i32| value.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+					[
+						clamp(points.iter().map(|point| point.0).min().unwrap_or_default()),
+						clamp(points.iter().map(|point| point.1).min().unwrap_or_default()),
+						clamp(points.iter().map(|point| point.0).max().unwrap_or_default()),
+						clamp(points.iter().map(|point| point.1).max().unwrap_or_default()),
+					]
+				};
+
+				glyf.extend(contour_count.to_be_bytes());
+				bounding_box.iter().for_each(|value| glyf.extend(value.to_be_bytes()));
+				end_points.iter().for_each(|&end_point| glyf.extend((end_point as u16).to_be_bytes()));
+				glyf.extend(instruction_length.to_be_bytes());
+				glyf.extend(instructions.bytes(instruction_length as usize)?);
+				let overlaps = overlap_bitmap.is_some_and(|bitmap| bit(bitmap, glyph));
+				write_simple_glyph_points(&mut glyf, &points, overlaps);
+				x_mins.push(bounding_box[0]);
+			}
+			_ => return Err(corrupt()),
+		}
+
+		// Glyphs are aligned to four bytes, which also keeps their offsets even for the short format of the `loca` table
+		glyf.resize((glyf.len() + 3) & !3, 0);
+	}
+	glyph_offsets.push(glyf.len());
+
+	let loca = if long_offsets {
+		glyph_offsets.iter().flat_map(|&offset| (offset as u32).to_be_bytes()).collect()
+	} else {
+		if glyf.len() > (u16::MAX as usize) * 2 {
+			return Err(corrupt());
+		}
+		glyph_offsets.iter().flat_map(|&offset| ((offset / 2) as u16).to_be_bytes()).collect()
+	};
+
+	Ok((glyf, loca, x_mins))
+}
+
+/// Reads the change in position to a point of a simple glyph, which is stored in a variable number of bytes chosen by the lower seven bits of its flag.
+fn decode_triplet(flag: u8, glyph_stream: &mut Reader) -> Result<(i32, i32), EditorError> {
+	let with_sign = |flag: u8, value: i32| if flag & 1 != 0 { value } else { -value };
+	let flag_value = flag as i32;
+
+	Ok(match flag {
+		0..=9 => {
+			let byte = glyph_stream.u8()? as i32;
+			(0, with_sign(flag, ((flag_value & 14) << 7) + byte))
+		}
+		10..=19 => {
+			let byte = glyph_stream.u8()? as i32;
+			(with_sign(flag, (((flag_value - 10) & 14) << 7) + byte), 0)
+		}
+		20..=83 => {
+			let base = flag_value - 20;
+			let byte = glyph_stream.u8()? as i32;
+			(with_sign(flag, 1 + (base & 0x30) + (byte >> 4)), with_sign(flag >> 1, 1 + ((base & 0x0C) << 2) + (byte & 0x0F)))
+		}
+		84..=119 => {
+			let base = flag_value - 84;
+			let bytes = glyph_stream.bytes(2)?;
+			(
+				with_sign(flag, 1 + ((base / 12) << 8) + bytes[0] as i32),
+				with_sign(flag >> 1, 1 + (((base % 12) >> 2) << 8) + bytes[1] as i32),
+			)
+		}
+		120..=123 => {
+			let bytes = glyph_stream.bytes(3)?;
+			(
+				with_sign(flag, ((bytes[0] as i32) << 4) + ((bytes[1] as i32) >> 4)),
+				with_sign(flag >> 1, (((bytes[1] as i32) & 0x0F) << 8) + bytes[2] as i32),
+			)
+		}
+		_ => {
+			let bytes = glyph_stream.bytes(4)?;
+			(
+				with_sign(flag, ((bytes[0] as i32) << 8) + bytes[1] as i32),
+				with_sign(flag >> 1, ((bytes[2] as i32) << 8) + bytes[3] as i32),
+			)
+		}
+	})
+}
+
+/// Writes the flags and coordinates of the points of a simple glyph, storing each coordinate as a change from the previous point in as few bytes as it fits in.
+fn write_simple_glyph_points(glyf: &mut Vec<u8>, points: &[(i32, i32, bool)], overlaps: bool) {
+	let mut point_flags = Vec::with_capacity(points.len());
+	let mut x_coordinates = Vec::new();
+	let mut y_coordinates = Vec::new();
+
+	let (mut previous_x, mut previous_y) = (0, 0);
+	for (index, &(x, y, on_curve)) in points.iter().enumerate() {
+		let mut flag = if on_curve { ON_CURVE_POINT } else { 0 };
+		if index == 0 && overlaps {
+			flag |= OVERLAP_SIMPLE;
+		}
+
+		for (delta, coordinates, short_flag, same_flag) in [
+			(x - previous_x, &mut x_coordinates, X_SHORT_VECTOR, X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR),
+			(y - previous_y, &mut y_coordinates, Y_SHORT_VECTOR, Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR),
+		] {
+			if delta == 0 {
+				flag |= same_flag;
+			} else if delta.abs() < 256 {
+				flag |= short_flag;
+				if delta > 0 {
+					flag |= same_flag;
+				}
+				coordinates.push(delta.unsigned_abs() as u8);
+			} else {
+				coordinates.extend((delta as i16).to_be_bytes());
+			}
+		}
+
+		point_flags.push(flag);
+		(previous_x, previous_y) = (x, y);
+	}
+
+	glyf.extend(point_flags);
+	glyf.extend(x_coordinates);
+	glyf.extend(y_coordinates);
+}
+
+/// Rebuilds the `hmtx` table from the transformed metrics of a WOFF2 file, which may leave out side bearings equal to the left edges of their glyphs.
+fn reconstruct_hmtx(data: &[u8], metric_count: usize, x_mins: &[i16]) -> Result<Vec<u8>, EditorError> {
+	let mut reader = Reader::new(data);
+	let flags = reader.u8()?;
+	let glyph_count = x_mins.len();
+	if metric_count == 0 || metric_count > glyph_count {
+		return Err(corrupt());
+	}
+
+	let advances = (0..metric_count).map(|_| reader.u16()).collect::<Result<Vec<_>, _>>()?;
+	let mut side_bearing = |glyph: usize, omitted: bool| if omitted { Ok(x_mins[glyph]) } else { reader.i16() };
+
+	let mut hmtx = Vec::with_capacity(metric_count * 4 + (glyph_count - metric_count) * 2);
+	for (glyph, advance) in advances.iter().enumerate() {
+		hmtx.extend(advance.to_be_bytes());
+		hmtx.extend(side_bearing(glyph, flags & 1 != 0)?.to_be_bytes());
+	}
+	for glyph in metric_count..glyph_count {
+		hmtx.extend(side_bearing(glyph, flags & 2 != 0)?.to_be_bytes());
+	}
+
+	Ok(hmtx)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use flate2::write::ZlibEncoder;
+	use flate2::Compression;
+	use std::io::Write;
+
+	const TRUETYPE: u32 = 0x0001_0000;
+
+	fn tables() -> Vec<FontTable> {
+		let mut head = vec![0; 54];
+		head[..4].copy_from_slice(&TRUETYPE.to_be_bytes());
+		head[12..16].copy_from_slice(&0x5F0F_3CF5_u32.to_be_bytes());
+		head[18..20].copy_from_slice(&1000_u16.to_be_bytes());
+		let mut hhea = vec![0; 36];
+		hhea[..4].copy_from_slice(&TRUETYPE.to_be_bytes());
+		hhea[34..36].copy_from_slice(&1_u16.to_be_bytes());
+		vec![
+			FontTable { tag: *b"head", data: head },
+			FontTable { tag: *b"hhea", data: hhea },
+			FontTable {
+				tag: *b"maxp",
+				data: vec![0, 0, 0x50, 0, 0, 3],
+			},
+			FontTable { tag: *b"Test", data: b"odd".to_vec() },
+		]
+	}
+
+	fn woff(tables: &[FontTable], table_count: u16) -> Vec<u8> {
+		let mut header = b"wOFF".to_vec();
+		header.extend(TRUETYPE.to_be_bytes());
+		header.extend(0_u32.to_be_bytes());
+		header.extend(table_count.to_be_bytes());
+		header.extend([0; 2]);
+		header.extend(1000_u32.to_be_bytes());
+		header.extend([0; 24]);
+
+		let mut directory = Vec::new();
+		let mut data = Vec::new();
+		let mut offset = 44 + tables.len() * 20;
+		for table in tables {
+			let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+			encoder.write_all(&table.data).unwrap();
+			let compressed = encoder.finish().unwrap();
+			let stored = if compressed.len() < table.data.len() { compressed } else { table.data.clone() };
+			directory.extend(table.tag);
+			directory.extend((offset as u32).to_be_bytes());
+			directory.extend((stored.len() as u32).to_be_bytes());
+			directory.extend((table.data.len() as u32).to_be_bytes());
+			directory.extend(table_checksum(&table.data).to_be_bytes());
+			offset += stored.len();
+			data.extend(stored);
+		}
+		[header, directory, data].concat()
+	}
+
+	fn uint_base_128(mut value: u32) -> Vec<u8> {
+		let mut bytes = vec![(value & 0x7F) as u8];
+		value >>= 7;
+		while value > 0 {
+			bytes.insert(0, (value & 0x7F) as u8 | 0x80);
+			value >>= 7;
+		}
+		bytes
+	}
+
+	/// Wraps data in a Brotli stream with one uncompressed meta-block, since there's no Brotli encoder to compress it with.
+	fn brotli_uncompressed(data: &[u8]) -> Vec<u8> {
+		assert!(!data.is_empty() && data.len() <= 1 << 16);
+		// A 16-bit window, a meta-block which isn't the last with a length given in 4 nibbles, and the flag marking it as uncompressed
+		let header = ((data.len() as u32 - 1) << 4) | (1 << 20);
+		let mut stream = header.to_le_bytes()[..3].to_vec();
+		stream.extend_from_slice(data);
+		// An empty last meta-block
+		stream.push(0b11);
+		stream
+	}
+
+	/// Builds a WOFF2 file of untransformed tables, whose directory gives the stored lengths and whose data is the given Brotli stream.
+	fn woff2(tables: &[(&[u8; 4], usize)], compressed: &[u8]) -> Vec<u8> {
+		let mut directory = Vec::new();
+		for &(tag, length) in tables {
+			match WOFF2_KNOWN_TAGS.iter().position(|known| *known == tag) {
+				Some(index) => directory.push(index as u8),
+				None => {
+					directory.push(63);
+					directory.extend(tag);
+				}
+			}
+			directory.extend(uint_base_128(length as u32));
+		}
+
+		let mut font = b"wOF2".to_vec();
+		font.extend(TRUETYPE.to_be_bytes());
+		font.extend(0_u32.to_be_bytes());
+		font.extend((tables.len() as u16).to_be_bytes());
+		font.extend([0; 2]);
+		font.extend(1000_u32.to_be_bytes());
+		font.extend((compressed.len() as u32).to_be_bytes());
+		font.extend([0; 24]);
+		[font, directory, compressed.to_vec()].concat()
+	}
+
+	fn woff2_of_tables(tables: &[FontTable]) -> Vec<u8> {
+		let directory = tables.iter().map(|table| (&table.tag, table.data.len())).collect::<Vec<_>>();
+		let data = tables.iter().flat_map(|table| table.data.iter().copied()).collect::<Vec<_>>();
+		woff2(&directory, &brotli_uncompressed(&data))
+	}
+
+	#[test]
+	fn table_directory_binary_search_values() {
+		let font = write_font(TRUETYPE, tables());
+		// The table count, the largest power of two tables times 16, its exponent, and the remaining tables times 16
+		assert_eq!(font[4..12], [0, 4, 0, 64, 0, 2, 0, 0]);
+		assert_eq!(table_checksum(&font), 0xB1B0_AFBA);
+
+		let many = (0..MAX_TABLE_COUNT)
+			.map(|index| FontTable {
+				tag: index.to_be_bytes().repeat(2).try_into().unwrap(),
+				data: Vec::new(),
+			})
+			.collect();
+		assert_eq!(write_font(TRUETYPE, many)[4..12], [0x0F, 0xFF, 0x80, 0, 0, 11, 0x7F, 0xF0]);
+	}
+
+	#[test]
+	fn truetype_is_unchanged() {
+		let font = write_font(TRUETYPE, tables());
+		assert_eq!(decode_font_file(&font).unwrap(), font);
+		assert!(decode_font_file(b"GIF89a").is_err());
+	}
+
+	#[test]
+	fn woff_decompresses_to_the_font() {
+		let tables = tables();
+		assert_eq!(decode_font_file(&woff(&tables, 4)).unwrap(), write_font(TRUETYPE, self::tables()));
+	}
+
+	#[test]
+	fn woff2_decompresses_to_the_font() {
+		let tables = tables();
+		assert_eq!(decode_font_file(&woff2_of_tables(&tables)).unwrap(), write_font(TRUETYPE, self::tables()));
+	}
+
+	#[test]
+	fn decompressed_fonts_can_be_read() {
+		for font in [woff(&tables(), 4), woff2_of_tables(&tables())] {
+			let font = decode_font_file(&font).unwrap();
+			let face = ttf_parser::Face::from_slice(&font, 0).unwrap();
+			assert_eq!((face.units_per_em(), face.number_of_glyphs()), (1000, 3));
+		}
+	}
+
+	#[test]
+	fn too_many_tables_are_rejected() {
+		assert!(decode_font_file(&woff(&tables(), MAX_TABLE_COUNT + 1)).is_err());
+		assert!(decode_font_file(&woff(&tables(), u16::MAX)).is_err());
+
+		let mut font = woff2_of_tables(&tables());
+		font[12..14].copy_from_slice(&u16::MAX.to_be_bytes());
+		assert!(decode_font_file(&font).is_err());
+	}
+
+	#[test]
+	fn woff_table_expanding_past_its_length_is_rejected() {
+		let mut font = woff(&tables(), 4);
+		// The compressed `head` table is the first, and claims to be a byte shorter than it decompresses to
+		let length_offset = 44 + 12;
+		font[length_offset..length_offset + 4].copy_from_slice(&53_u32.to_be_bytes());
+		assert!(decode_font_file(&font).is_err());
+	}
+
+	#[test]
+	fn woff2_decompresses_only_what_the_tables_need() {
+		let tables = tables();
+		let mut data = tables.iter().flat_map(|table| table.data.iter().copied()).collect::<Vec<_>>();
+		data.extend([0xAA; 1000]);
+		let directory = tables.iter().map(|table| (&table.tag, table.data.len())).collect::<Vec<_>>();
+		assert_eq!(decode_font_file(&woff2(&directory, &brotli_uncompressed(&data))).unwrap(), write_font(TRUETYPE, self::tables()));
+	}
+
+	#[test]
+	fn malformed_woff2_is_rejected() {
+		let data = [0; 64];
+		// The tables need more data than the stream decompresses to
+		assert!(decode_font_file(&woff2(&[(b"head", 54), (b"maxp", 6), (b"Test", 5)], &brotli_uncompressed(&data))).is_err());
+		// Tables larger than any font
+		assert!(decode_font_file(&woff2(&[(b"head", MAX_FONT_SIZE + 1)], &brotli_uncompressed(&data))).is_err());
+		assert!(decode_font_file(&woff2(&[(b"head", MAX_FONT_SIZE / 2), (b"maxp", MAX_FONT_SIZE / 2 + 1)], &brotli_uncompressed(&data))).is_err());
+		// A corrupt Brotli stream, and one cut short
+		assert!(decode_font_file(&woff2(&[(b"head", 54)], &[0xFF; 16])).is_err());
+		assert!(decode_font_file(&woff2(&[(b"head", 54)], &brotli_uncompressed(&[0; 54])[..20])).is_err());
+		// A file cut short in its table directory
+		assert!(decode_font_file(&woff2_of_tables(&tables())[..50]).is_err());
+		// A transformed `glyf` table with lengths of its streams which overflow
+		let mut glyf = vec![0; 36];
+		glyf[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+		glyf[12..16].copy_from_slice(&u32::MAX.to_be_bytes());
+		assert!(reconstruct_glyf(&glyf).is_err());
+	}
+}
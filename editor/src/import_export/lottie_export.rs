@@ -0,0 +1,718 @@
+//! Writes the artwork of a document as a Lottie animation, the JSON format played by the Lottie libraries on the web and on mobile platforms.
+//!
+//! Folders become null layers which the layers inside them are parented to, shape and text layers become shape layers, and images are embedded as image layers.
+//! The layers are sampled at each frame of the animation, and the properties whose values change from one frame to the next, like transforms, opacities, and paths, are keyframed.
+
+use crate::messages::portfolio::document::utility_types::animation_encoding::AnimationSettings;
+
+use graphene::color::{Color, ColorSpace};
+use graphene::layers::blend_mode::BlendMode;
+use graphene::layers::layer_info::{Layer, LayerDataType, LayerDataTypeDiscriminant};
+use graphene::layers::style::{Fill, Gradient, GradientType, LineCap, LineJoin, PathStyle, Stroke};
+use graphene::layers::text_layer::FontCache;
+use graphene::layers::vector::subpath::Subpath;
+
+use glam::{DAffine2, DVec2};
+use kurbo::{BezPath, PathEl};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// The version of the format the animations are written in, which follows the versions of the After Effects exporter the format comes from.
+const LOTTIE_VERSION: &str = "5.7.4";
+
+/// The types of Lottie layers, as numbered by the format.
+const SHAPE_LAYER: u8 = 4;
+const IMAGE_LAYER: u8 = 2;
+const NULL_LAYER: u8 = 3;
+
+pub struct LottieExportSettings<'a> {
+	pub font_cache: &'a FontCache,
+	/// The color space the colors of the document are specified in, which are converted to sRGB for the animation
+	pub color_space: ColorSpace,
+	/// The frame range and frame rate of the animation
+	pub animation: AnimationSettings,
+}
+
+/// Builds a Lottie animation showing the area of the document within `bounds`, where the layers of each root are drawn on top of those of the roots before it.
+/// `frames` gives the roots at each frame from the first frame of the animation on, and the last of them is held for the rest of the animation.
+pub fn lottie_animation(name: &str, frames: &[Vec<&Layer>], bounds: [DVec2; 2], settings: &LottieExportSettings) -> String {
+	let frame_range = settings.animation.frames();
+	let mut writer = LottieWriter {
+		settings,
+		in_point: *frame_range.start(),
+		out_point: frame_range.end() + 1,
+		layers: Vec::new(),
+		assets: Vec::new(),
+	};
+
+	let mut samples = Vec::new();
+	for roots in frames.iter().take(settings.animation.frame_count() as usize) {
+		for root in roots {
+			writer.write_layer(root, None, DAffine2::from_translation(-bounds[0]), 1.);
+		}
+		// Lottie lists layers from the top one down
+		writer.layers.reverse();
+		samples.push(std::mem::take(&mut writer.layers));
+	}
+	writer.layers = writer.merge_samples(&samples);
+
+	let size = (bounds[1] - bounds[0]).ceil().max(DVec2::ONE);
+	let animation = json!({
+		"v": LOTTIE_VERSION,
+		"nm": name,
+		"fr": settings.animation.frame_rate,
+		"ip": writer.in_point,
+		"op": writer.out_point,
+		"w": size.x as u32,
+		"h": size.y as u32,
+		"ddd": 0,
+		"assets": writer.assets,
+		"layers": writer.layers,
+	});
+	// We fully expect the serialization to succeed
+	serde_json::to_string(&animation).unwrap()
+}
+
+struct LottieWriter<'a> {
+	settings: &'a LottieExportSettings<'a>,
+	/// The first frame of the animation
+	in_point: u32,
+	/// The frame after the last one of the animation
+	out_point: u32,
+	/// The layers written so far, from the bottom one up
+	layers: Vec<Value>,
+	assets: Vec<Value>,
+}
+
+impl LottieWriter<'_> {
+	/// Merges the layers written for consecutive frames into layers whose changing properties are keyframed.
+	/// Layers can only be merged while their structure stays the same, so each stretch of frames with a different structure, like when a layer is hidden or a path gains a contour, gets layers of its own.
+	fn merge_samples(&self, samples: &[Vec<Value>]) -> Vec<Value> {
+		let structures = samples.iter().map(|layers| structure(&Value::Array(layers.clone()))).collect::<Vec<_>>();
+
+		let mut layers = Vec::new();
+		let mut start = 0;
+		while start < samples.len() {
+			let end = (start..samples.len()).find(|&index| structures[index] != structures[start]).unwrap_or(samples.len());
+			let in_point = self.in_point + start as u32;
+			let out_point = if end == samples.len() { self.out_point } else { self.in_point + end as u32 };
+
+			// Layers are referred to by their index, which continues on from the layers of the stretches before
+			let offset = layers.len();
+			for index in 0..samples[start].len() {
+				let frames = samples[start..end].iter().map(|layers| &layers[index]).collect::<Vec<_>>();
+				let mut layer = merge_frames(&frames, in_point);
+				if let Some(fields) = layer.as_object_mut() {
+					for key in ["ind", "parent"] {
+						if let Some(index) = fields.get(key).and_then(Value::as_u64) {
+							fields.insert(key.to_string(), (offset as u64 + index).into());
+						}
+					}
+					fields.insert("ip".to_string(), in_point.into());
+					fields.insert("op".to_string(), out_point.into());
+				}
+				layers.push(layer);
+			}
+			start = end;
+		}
+		layers
+	}
+
+	/// Writes a layer, given the index of the Lottie layer its parent is attached to and the transform from the space of its parent to that of the Lottie layer.
+	/// Null layers only pass their transforms on to the layers attached to them, so the opacity of folders is instead multiplied into the `opacity` of the layers inside them.
+	fn write_layer(&mut self, layer: &Layer, parent: Option<usize>, transform: DAffine2, opacity: f64) {
+		if !layer.visible || layer.opacity <= 0. {
+			return;
+		}
+		let opacity = opacity * layer.opacity;
+
+		match &layer.data {
+			LayerDataType::Folder(folder) => {
+				let transform = transform * layer.transform;
+				if !transform.is_finite() || transform.matrix2.determinant() == 0. {
+					return;
+				}
+				// A skewed folder can't be a null layer, so its transform is carried into the layers inside it instead
+				let (parent, transform) = match transform_property(transform, 1.) {
+					Some(transform_property) => (Some(self.add_layer(layer, NULL_LAYER, parent, transform_property, json!({}))), DAffine2::IDENTITY),
+					None => (parent, transform),
+				};
+				for child in folder.layers() {
+					self.write_layer(child, parent, transform, opacity);
+				}
+			}
+			LayerDataType::Shape(shape) => self.write_shape(layer, &shape.shape.with_rounded_corners(), &shape.style, parent, transform, opacity),
+			LayerDataType::Text(text) => self.write_shape(layer, &text.to_subpath_nonmut(self.settings.font_cache), &text.path_style, parent, transform, opacity),
+			LayerDataType::Image(image) => self.write_image(layer, &image.mime, &image.image_data, image.dimensions, parent, transform, opacity),
+			LayerDataType::Imaginate(imaginate) => {
+				if let Some(image_data) = &imaginate.image_data {
+					self.write_image(layer, &imaginate.mime, &image_data.image_data, imaginate.dimensions, parent, transform, opacity);
+				}
+			}
+		}
+	}
+
+	/// Adds a layer of the given type, returning the index it's referred to by.
+	fn add_layer(&mut self, layer: &Layer, layer_type: u8, parent: Option<usize>, transform_property: Value, contents: Value) -> usize {
+		let index = self.layers.len() + 1;
+		let name = layer.name.clone().unwrap_or_else(|| LayerDataTypeDiscriminant::from(&layer.data).to_string());
+		let mut lottie_layer = json!({
+			"ddd": 0,
+			"ind": index,
+			"ty": layer_type,
+			"nm": name,
+			"sr": 1,
+			"ks": transform_property,
+			"ao": 0,
+			"ip": self.in_point,
+			"op": self.out_point,
+			"st": 0,
+			"bm": blend_mode_index(layer.blend_mode),
+		});
+		if let (Some(fields), Value::Object(contents)) = (lottie_layer.as_object_mut(), contents) {
+			if let Some(parent) = parent {
+				fields.insert("parent".to_string(), parent.into());
+			}
+			fields.extend(contents);
+		}
+		self.layers.push(lottie_layer);
+		index
+	}
+
+	/// Writes a shape, whose path is in the layer's space but whose stroke is drawn in the space of its parent, matching the SVG renderer.
+	fn write_shape(&mut self, layer: &Layer, subpath: &Subpath, style: &PathStyle, parent: Option<usize>, transform: DAffine2, opacity: f64) {
+		// A stroke of varying width has no Lottie equivalent, so it's written as its outline filled with its color, in a layer above the fill
+		if let Some(stroke) = style.stroke().filter(|stroke| stroke.has_width_profile() && stroke.is_visible()) {
+			let mut fill_style = style.clone();
+			fill_style.clear_stroke();
+			self.write_shape(layer, subpath, &fill_style, parent, transform, opacity);
+			if let Some(outline) = subpath.transformed_stroke_outline(&stroke, layer.transform) {
+				self.write_shape(layer, &outline, &PathStyle::new(None, stroke.color().map_or(Fill::None, Fill::Solid)), parent, transform, opacity);
+			}
+			return;
+		}
+
+		let to_lottie_parent = transform * layer.transform;
+		let mut path = subpath.clone();
+		path.apply_affine(to_lottie_parent);
+		let mut items = path_shapes(&BezPath::from(&path));
+		if items.is_empty() {
+			return;
+		}
+
+		// Shapes drawn later in a group are drawn underneath, so the stroke comes before the fill to be drawn over it
+		if let Some((stroke, color)) = style.stroke().filter(|stroke| stroke.weight() > 0.).and_then(|stroke| stroke.color().map(|color| (stroke, color))) {
+			// The parts of the transform to the parent space which aren't carried by a null layer would otherwise leave the stroke unscaled
+			let stroke_scale = transform.matrix2.determinant().abs().sqrt();
+			items.push(self.stroke(&stroke, color, stroke_scale));
+		}
+		match style.fill() {
+			Fill::None => {}
+			Fill::Solid(color) => items.push(json!({
+				"ty": "fl",
+				"nm": "Fill",
+				"c": static_value(self.rgba(*color)),
+				"o": static_value(number(color.a() as f64 * 100.)),
+				"r": 1,
+			})),
+			Fill::Gradient(gradient) => {
+				if let Some(bounds) = subpath.bounding_box() {
+					items.push(self.gradient_fill(gradient, to_lottie_parent, bounds));
+				}
+			}
+		}
+		if items.iter().all(|item| item["ty"] == "sh") {
+			return;
+		}
+		items.push(json!({
+			"ty": "tr",
+			"nm": "Transform",
+			"a": static_value([0, 0]),
+			"p": static_value([0, 0]),
+			"s": static_value([100, 100]),
+			"r": static_value(0),
+			"o": static_value(100),
+		}));
+
+		let group = json!({ "ty": "gr", "nm": "Group", "it": items });
+		// The path is already in the space of the parent, so the layer itself isn't transformed
+		let transform_property = transform_property(DAffine2::IDENTITY, opacity).unwrap_or_default();
+		self.add_layer(layer, SHAPE_LAYER, parent, transform_property, json!({ "shapes": [group] }));
+	}
+
+	fn stroke(&self, stroke: &Stroke, color: Color, stroke_scale: f64) -> Value {
+		let line_cap = match stroke.line_cap() {
+			LineCap::Butt => 1,
+			LineCap::Round => 2,
+			LineCap::Square => 3,
+		};
+		let line_join = match stroke.line_join() {
+			LineJoin::Miter => 1,
+			LineJoin::Round => 2,
+			LineJoin::Bevel => 3,
+		};
+
+		// Dashes and gaps alternate, so like in SVG, an odd number of lengths is repeated to make an even number
+		let mut lengths = stroke.dash_pattern().to_vec();
+		if lengths.len() % 2 == 1 {
+			lengths.extend_from_within(..);
+		}
+		let mut dashes: Vec<Value> = lengths
+			.iter()
+			.enumerate()
+			.map(|(index, &length)| {
+				let (kind, name) = if index % 2 == 0 { ("d", "Dash") } else { ("g", "Gap") };
+				json!({ "n": kind, "nm": name, "v": static_value(number(length as f64 * stroke_scale)) })
+			})
+			.collect();
+		if !dashes.is_empty() {
+			dashes.push(json!({ "n": "o", "nm": "Offset", "v": static_value(number(stroke.dash_offset() * stroke_scale)) }));
+		}
+
+		json!({
+			"ty": "st",
+			"nm": "Stroke",
+			"c": static_value(self.rgba(color)),
+			"o": static_value(number(color.a() as f64 * 100.)),
+			"w": static_value(number(stroke.weight() * stroke_scale)),
+			"lc": line_cap,
+			"lj": line_join,
+			"ml": stroke.line_join_miter_limit(),
+			"d": dashes,
+		})
+	}
+
+	/// A gradient fill, whose positions are given relative to the bounding box of the layer's path.
+	fn gradient_fill(&self, gradient: &Gradient, to_lottie_parent: DAffine2, [min, max]: [DVec2; 2]) -> Value {
+		let to_lottie_parent = to_lottie_parent * DAffine2::from_scale_angle_translation(max - min, 0., min);
+		let (start, end) = (to_lottie_parent.transform_point2(gradient.start), to_lottie_parent.transform_point2(gradient.end));
+
+		let mut stops: Vec<(f64, Color)> = gradient.positions.iter().filter_map(|&(position, color)| color.map(|color| (position.clamp(0., 1.), color))).collect();
+		stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+		// The colors of the stops are followed by their opacities, which are left out when they're all opaque
+		let mut values: Vec<f64> = stops
+			.iter()
+			.flat_map(|&(position, color)| {
+				let color = color.convert_color_space(self.settings.color_space, ColorSpace::Srgb);
+				[position, color.r() as f64, color.g() as f64, color.b() as f64].map(|value| number(value.clamp(0., 1.)))
+			})
+			.collect();
+		if stops.iter().any(|(_, color)| color.a() < 1.) {
+			values.extend(stops.iter().flat_map(|&(position, color)| [number(position), number(color.a() as f64)]));
+		}
+
+		// Lottie has no angular or diamond gradients, which are drawn as radial ones, and always extends the colors at the ends of gradients
+		let gradient_type = match gradient.gradient_type {
+			GradientType::Linear => 1,
+			GradientType::Radial | GradientType::Angular | GradientType::Diamond => 2,
+		};
+		json!({
+			"ty": "gf",
+			"nm": "Gradient Fill",
+			"o": static_value(100),
+			"r": 1,
+			"t": gradient_type,
+			"s": static_value(point(start)),
+			"e": static_value(point(end)),
+			"h": static_value(0),
+			"a": static_value(0),
+			"g": { "p": stops.len(), "k": static_value(values) },
+		})
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	fn write_image(&mut self, layer: &Layer, mime: &str, image_data: &[u8], dimensions: DVec2, parent: Option<usize>, transform: DAffine2, opacity: f64) {
+		// Linked images whose files haven't been read yet have nothing to embed
+		if image_data.is_empty() || dimensions.x <= 0. || dimensions.y <= 0. {
+			return;
+		}
+		// Image layers have no path to carry a skew, so skewed images are left out
+		let transform_property = match transform_property(transform * layer.transform, opacity) {
+			Some(transform_property) => transform_property,
+			None => return,
+		};
+
+		// Images are sampled at every frame, but each is only embedded once
+		let data_url = format!("data:{mime};base64,{}", base64::encode(image_data));
+		let id = match self.assets.iter().find(|asset| asset["p"] == data_url && asset["w"] == dimensions.x && asset["h"] == dimensions.y) {
+			Some(asset) => asset["id"].clone(),
+			None => {
+				let id = Value::from(format!("image_{}", self.assets.len()));
+				self.assets.push(json!({
+					"id": id,
+					"w": dimensions.x,
+					"h": dimensions.y,
+					"u": "",
+					"p": data_url,
+					"e": 1,
+				}));
+				id
+			}
+		};
+		self.add_layer(layer, IMAGE_LAYER, parent, transform_property, json!({ "refId": id }));
+	}
+
+	/// The channels of a color as the sRGB color of a Lottie shape, whose opacity is given separately.
+	fn rgba(&self, color: Color) -> [f64; 4] {
+		let color = color.convert_color_space(self.settings.color_space, ColorSpace::Srgb);
+		let [r, g, b] = [color.r(), color.g(), color.b()].map(|channel| number(channel.clamp(0., 1.) as f64));
+		[r, g, b, 1.]
+	}
+}
+
+/// The transform of a Lottie layer, which positions, rotates, and scales it but can only be skewed around a single axis,
+/// so transforms which don't keep the axes of the layer perpendicular are left to be applied to its content instead.
+fn transform_property(transform: DAffine2, opacity: f64) -> Option<Value> {
+	let (x_axis, y_axis) = (transform.matrix2.x_axis, transform.matrix2.y_axis);
+	let scale_x = x_axis.length();
+	if !transform.is_finite() || scale_x == 0. || x_axis.dot(y_axis).abs() > 1e-6 * scale_x * y_axis.length() {
+		return None;
+	}
+	let scale_y = x_axis.perp_dot(y_axis) / scale_x;
+	let rotation = x_axis.y.atan2(x_axis.x).to_degrees();
+
+	Some(json!({
+		"a": static_value([0, 0]),
+		"p": static_value(point(transform.translation)),
+		"s": static_value([number(scale_x * 100.), number(scale_y * 100.)]),
+		"r": static_value(number(rotation)),
+		"o": static_value(number(opacity * 100.)),
+	}))
+}
+
+/// A property which keeps the same value for the whole animation.
+fn static_value(value: impl Serialize) -> Value {
+	json!({ "a": 0, "k": value })
+}
+
+fn is_static_value(value: &Value) -> bool {
+	value
+		.as_object()
+		.is_some_and(|fields| fields.len() == 2 && fields.get("a") == Some(&Value::from(0)) && fields.contains_key("k"))
+}
+
+/// Layers with the values of their properties left out, which is the same for the frames of a layer that can be merged.
+fn structure(value: &Value) -> Value {
+	match value {
+		_ if is_static_value(value) => Value::Null,
+		Value::Object(fields) => Value::Object(fields.iter().map(|(key, field)| (key.clone(), structure(field))).collect()),
+		Value::Array(items) => Value::Array(items.iter().map(structure).collect()),
+		_ => value.clone(),
+	}
+}
+
+/// Merges a layer, or a part of one, with the same structure in each of the consecutive frames starting at `first_frame`, keyframing the properties whose values change.
+fn merge_frames(frames: &[&Value], first_frame: u32) -> Value {
+	let parts = |part: &dyn Fn(&Value) -> &Value| frames.iter().map(|frame| part(frame)).collect::<Vec<_>>();
+	match frames[0] {
+		value if is_static_value(value) => {
+			let values = parts(&|frame| &frame["k"]);
+			if values.iter().all(|value| *value == values[0]) {
+				value.clone()
+			} else {
+				keyframed_value(&values, first_frame)
+			}
+		}
+		Value::Object(fields) => Value::Object(fields.keys().map(|key| (key.clone(), merge_frames(&parts(&|frame| &frame[key]), first_frame))).collect()),
+		Value::Array(items) => Value::Array((0..items.len()).map(|index| merge_frames(&parts(&|frame| &frame[index]), first_frame)).collect()),
+		value => value.clone(),
+	}
+}
+
+/// A property with a value at each of the consecutive frames starting at `first_frame`, keyframed on the frames where it changes.
+/// Values are interpolated linearly between keyframes, except for those which can't be, like paths with different numbers of vertices, which jump from one keyframe to the next.
+fn keyframed_value(values: &[&Value], first_frame: u32) -> Value {
+	fn interpolatable(a: &Value, b: &Value) -> bool {
+		match (a, b) {
+			(Value::Number(_), Value::Number(_)) => true,
+			(Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| interpolatable(a, b)),
+			(Value::Object(a), Value::Object(b)) => a.len() == b.len() && a.iter().all(|(key, a)| b.get(key).is_some_and(|b| interpolatable(a, b))),
+			_ => a == b,
+		}
+	}
+	let interpolated = values.iter().all(|value| interpolatable(value, values[0]));
+
+	let last = values.len() - 1;
+	let keyframes = values
+		.iter()
+		.enumerate()
+		// A frame is only a keyframe if the value changes on it or on the next frame
+		.filter(|&(index, value)| index == 0 || index == last || values[index - 1] != *value || values[index + 1] != *value)
+		.map(|(index, &value)| {
+			// The values of keyframes are always arrays, so single numbers and paths are wrapped in one
+			let start = if value.is_array() { value.clone() } else { json!([value]) };
+			let mut keyframe = json!({ "t": first_frame + index as u32, "s": start });
+			if index != last {
+				let easing = match interpolated {
+					true => json!({ "o": { "x": [0], "y": [0] }, "i": { "x": [1], "y": [1] } }),
+					false => json!({ "h": 1 }),
+				};
+				if let (Some(fields), Value::Object(easing)) = (keyframe.as_object_mut(), easing) {
+					fields.extend(easing);
+				}
+			}
+			keyframe
+		})
+		.collect::<Vec<_>>();
+
+	json!({ "a": 1, "k": keyframes })
+}
+
+/// The contours of a path as Lottie paths, whose tangents are given relative to the vertices they belong to.
+fn path_shapes(path: &BezPath) -> Vec<Value> {
+	#[derive(Default)]
+	struct Contour {
+		vertices: Vec<DVec2>,
+		in_tangents: Vec<DVec2>,
+		out_tangents: Vec<DVec2>,
+	}
+
+	impl Contour {
+		fn line_to(&mut self, to: DVec2) {
+			self.vertices.push(to);
+			self.in_tangents.push(DVec2::ZERO);
+			self.out_tangents.push(DVec2::ZERO);
+		}
+
+		fn curve_to(&mut self, first: DVec2, second: DVec2, to: DVec2) {
+			if let (Some(&from), Some(out_tangent)) = (self.vertices.last(), self.out_tangents.last_mut()) {
+				*out_tangent = first - from;
+			}
+			self.line_to(to);
+			if let Some(in_tangent) = self.in_tangents.last_mut() {
+				*in_tangent = second - to;
+			}
+		}
+
+		fn finish(&mut self, shapes: &mut Vec<Value>, closed: bool) {
+			// A closed contour's closing segment ends where it started, which Lottie connects to by itself
+			if closed && self.vertices.len() > 1 && self.vertices.first().unwrap().abs_diff_eq(*self.vertices.last().unwrap(), 1e-9) {
+				self.vertices.pop();
+				let in_tangent = self.in_tangents.pop().unwrap();
+				self.in_tangents[0] = in_tangent;
+				self.out_tangents.pop();
+			}
+			if !self.vertices.is_empty() {
+				let points = |vectors: &[DVec2]| vectors.iter().map(|&vector| point(vector)).collect::<Vec<_>>();
+				shapes.push(json!({
+					"ty": "sh",
+					"nm": "Path",
+					"ks": static_value(json!({
+						"i": points(&self.in_tangents),
+						"o": points(&self.out_tangents),
+						"v": points(&self.vertices),
+						"c": closed,
+					})),
+				}));
+			}
+			*self = Self::default();
+		}
+	}
+
+	let to_vec = |point: kurbo::Point| DVec2::new(point.x, point.y);
+	let mut shapes = Vec::new();
+	let mut contour = Contour::default();
+	let mut current = DVec2::ZERO;
+	for element in path.elements() {
+		match *element {
+			PathEl::MoveTo(to) => {
+				contour.finish(&mut shapes, false);
+				contour.line_to(to_vec(to));
+			}
+			PathEl::LineTo(to) => contour.line_to(to_vec(to)),
+			// Lottie only has cubic curves, which can represent quadratic ones exactly
+			PathEl::QuadTo(control, to) => {
+				let (control, to) = (to_vec(control), to_vec(to));
+				contour.curve_to(current + (control - current) * (2. / 3.), to + (control - to) * (2. / 3.), to);
+			}
+			PathEl::CurveTo(first, second, to) => contour.curve_to(to_vec(first), to_vec(second), to_vec(to)),
+			PathEl::ClosePath => contour.finish(&mut shapes, true),
+		}
+		current = contour.vertices.last().copied().unwrap_or(current);
+	}
+	contour.finish(&mut shapes, false);
+	shapes
+}
+
+fn point(point: DVec2) -> [f64; 2] {
+	[number(point.x), number(point.y)]
+}
+
+/// Rounds a number to a precision well beyond what's visible, which keeps the JSON small.
+fn number(value: f64) -> f64 {
+	let value = if value.is_finite() { value } else { 0. };
+	// Adding zero turns negative zero into zero
+	(value * 1000.).round() / 1000. + 0.
+}
+
+/// The number of a blend mode in Lottie, which has the same blend modes as After Effects.
+fn blend_mode_index(blend_mode: BlendMode) -> u8 {
+	match blend_mode {
+		BlendMode::Normal => 0,
+		BlendMode::Multiply => 1,
+		BlendMode::Screen => 2,
+		BlendMode::Overlay => 3,
+		BlendMode::Darken => 4,
+		BlendMode::Lighten => 5,
+		BlendMode::ColorDodge => 6,
+		BlendMode::ColorBurn => 7,
+		BlendMode::HardLight => 8,
+		BlendMode::SoftLight => 9,
+		BlendMode::Difference => 10,
+		BlendMode::Exclusion => 11,
+		BlendMode::Hue => 12,
+		BlendMode::Saturation => 13,
+		BlendMode::Color => 14,
+		BlendMode::Luminosity => 15,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene::layers::folder_layer::FolderLayer;
+	use graphene::layers::shape_layer::ShapeLayer;
+
+	fn shape(subpath: Subpath, opacity: f64) -> Layer {
+		let mut layer = Layer::new(
+			LayerDataType::Shape(ShapeLayer::new(subpath, PathStyle::new(None, Fill::Solid(Color::RED)))),
+			DAffine2::IDENTITY.to_cols_array(),
+		);
+		layer.opacity = opacity;
+		layer
+	}
+
+	fn square(size: f64) -> Subpath {
+		Subpath::new_rect(DVec2::ZERO, DVec2::splat(size))
+	}
+
+	fn folder(offset: DVec2, layers: Vec<Layer>) -> Layer {
+		let mut folder = FolderLayer::default();
+		for layer in layers {
+			folder.add_layer(layer, None, -1);
+		}
+		Layer::new(LayerDataType::Folder(folder), DAffine2::from_translation(offset).to_cols_array())
+	}
+
+	/// Exports the animation of 4 frames whose layers are given at each frame, returning its layers.
+	fn animation(frames: &[Layer]) -> Vec<Value> {
+		let font_cache = FontCache::default();
+		let settings = LottieExportSettings {
+			font_cache: &font_cache,
+			color_space: ColorSpace::Srgb,
+			animation: AnimationSettings {
+				first_frame: 10,
+				last_frame: 13,
+				frame_rate: 24.,
+				..Default::default()
+			},
+		};
+		let frames = frames.iter().map(|root| vec![root]).collect::<Vec<_>>();
+		let animation: Value = serde_json::from_str(&lottie_animation("Test", &frames, [DVec2::ZERO, DVec2::splat(100.)], &settings)).unwrap();
+		assert_eq!((animation["ip"].as_u64(), animation["op"].as_u64()), (Some(10), Some(14)));
+		animation["layers"].as_array().unwrap().clone()
+	}
+
+	fn keyframe_times(property: &Value) -> Vec<u64> {
+		assert_eq!(property["a"], 1, "{property}");
+		property["k"].as_array().unwrap().iter().map(|keyframe| keyframe["t"].as_u64().unwrap()).collect()
+	}
+
+	#[test]
+	fn unchanging_frames_are_static() {
+		let layers = animation(&[folder(DVec2::ZERO, vec![shape(square(10.), 1.)])]);
+		assert_eq!(layers.len(), 2);
+		assert_eq!(layers[0]["ty"], SHAPE_LAYER);
+		assert_eq!(layers[0]["parent"], 1);
+		assert_eq!(layers[1]["ty"], NULL_LAYER);
+		for layer in &layers {
+			assert_eq!((layer["ip"].as_u64(), layer["op"].as_u64()), (Some(10), Some(14)));
+			assert_eq!(layer["ks"]["p"]["a"], 0);
+			assert_eq!(layer["ks"]["o"]["a"], 0);
+		}
+		assert_eq!(layers[0]["shapes"][0]["it"][0]["ks"]["a"], 0);
+	}
+
+	#[test]
+	fn transforms_are_keyframed() {
+		let offsets = [0., 0., 10., 20.];
+		let layers = animation(&offsets.map(|offset| folder(DVec2::new(offset, 0.), vec![shape(square(10.), 1.)])));
+		assert_eq!(layers.len(), 2);
+
+		let position = &layers[1]["ks"]["p"];
+		// The keyframe of the second frame is the same as the first one, and there's nothing to interpolate between them
+		assert_eq!(keyframe_times(position), [10, 11, 12, 13]);
+		assert_eq!(position["k"][3]["s"], json!([20., 0.]));
+		assert_eq!(position["k"][0]["i"], json!({ "x": [1], "y": [1] }));
+		assert!(position["k"][3].get("i").is_none());
+		assert_eq!(layers[1]["ks"]["s"]["a"], 0);
+		// The shape inside the folder is moved along with it
+		assert_eq!(layers[0]["shapes"][0]["it"][0]["ks"]["a"], 0);
+	}
+
+	#[test]
+	fn redundant_keyframes_are_left_out() {
+		let offsets = [0., 5., 5., 5.];
+		let layers = animation(&offsets.map(|offset| folder(DVec2::new(offset, 0.), vec![shape(square(10.), 1.)])));
+		assert_eq!(keyframe_times(&layers[1]["ks"]["p"]), [10, 11, 13]);
+	}
+
+	#[test]
+	fn opacity_is_keyframed() {
+		let layers = animation(&[0.25, 0.5, 0.75, 1.].map(|opacity| folder(DVec2::ZERO, vec![shape(square(10.), opacity)])));
+		let opacity = &layers[0]["ks"]["o"];
+		assert_eq!(keyframe_times(opacity), [10, 11, 12, 13]);
+		assert_eq!(
+			opacity["k"].as_array().unwrap().iter().map(|keyframe| keyframe["s"][0].as_f64().unwrap()).collect::<Vec<_>>(),
+			[25., 50., 75., 100.]
+		);
+	}
+
+	#[test]
+	fn paths_are_morphed() {
+		let layers = animation(&[10., 20., 30., 30.].map(|size| shape(square(size), 1.)));
+		assert_eq!(layers.len(), 1);
+		let path = &layers[0]["shapes"][0]["it"][0]["ks"];
+		assert_eq!(keyframe_times(path), [10, 11, 12, 13]);
+		// The values of path keyframes are wrapped in an array
+		assert_eq!(path["k"][2]["s"][0]["v"][2], json!([30., 30.]));
+		assert!(path["k"][0].get("h").is_none());
+	}
+
+	#[test]
+	fn paths_with_different_vertices_are_held() {
+		let triangle = Subpath::new_poly_line(vec![DVec2::ZERO, DVec2::new(10., 0.), DVec2::new(0., 10.)]);
+		let layers = animation(&[shape(square(10.), 1.), shape(triangle, 1.)]);
+		assert_eq!(layers.len(), 1);
+		let path = &layers[0]["shapes"][0]["it"][0]["ks"];
+		assert_eq!(keyframe_times(path), [10, 11]);
+		assert_eq!(path["k"][0]["h"], 1);
+		// The last frame is held for the rest of the animation
+		assert_eq!(layers[0]["op"], 14);
+	}
+
+	#[test]
+	fn changing_structure_splits_layers() {
+		let mut hidden = shape(square(10.), 1.);
+		hidden.visible = false;
+		let frames = [
+			folder(DVec2::ZERO, vec![shape(square(10.), 1.)]),
+			folder(DVec2::ZERO, vec![shape(square(10.), 1.), hidden.clone()]),
+			folder(DVec2::ZERO, vec![shape(square(10.), 1.), shape(square(20.), 1.)]),
+			folder(DVec2::ZERO, vec![shape(square(10.), 1.), shape(square(30.), 1.)]),
+		];
+		let layers = animation(&frames);
+
+		let spans = layers
+			.iter()
+			.map(|layer| (layer["ind"].as_u64().unwrap(), layer["ip"].as_u64().unwrap(), layer["op"].as_u64().unwrap()))
+			.collect::<Vec<_>>();
+		assert_eq!(spans, [(2, 10, 12), (1, 10, 12), (5, 12, 14), (4, 12, 14), (3, 12, 14)]);
+		// Layers are attached to the null layer of their own stretch of frames
+		assert_eq!(layers[0]["parent"], 1);
+		assert_eq!(layers[2]["parent"], 3);
+		assert_eq!(layers[3]["parent"], 3);
+		assert_eq!(keyframe_times(&layers[2]["shapes"][0]["it"][0]["ks"]), [12, 13]);
+	}
+}
@@ -0,0 +1,9 @@
+//! Reads the files documents are imported from and writes the formats they're exported to, other than the raster images encoded in `utility_types`.
+
+pub mod dxf;
+pub mod font_file;
+pub mod lottie_export;
+pub mod palette_file;
+pub mod pdf_export;
+pub mod print_import;
+pub mod svg_import;
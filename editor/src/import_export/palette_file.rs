@@ -0,0 +1,340 @@
+//! Reads and writes the palette files that swatches are shared between apps with: Adobe Swatch Exchange (.ase) and GIMP palettes (.gpl).
+//!
+//! Palette colors are opaque sRGB colors. The CMYK, LAB, and grayscale swatches of Adobe Swatch Exchange files are converted to sRGB when read.
+
+use crate::messages::portfolio::document::utility_types::error::EditorError;
+
+use graphene::color::{Cmyk, Color, ColorSpace};
+use graphene::layers::style::ColorStyle;
+
+use serde::{Deserialize, Serialize};
+
+const ASE_SIGNATURE: &[u8] = b"ASEF";
+const GPL_SIGNATURE: &str = "GIMP Palette";
+
+const ASE_GROUP_START: u16 = 0xC001;
+const ASE_GROUP_END: u16 = 0xC002;
+const ASE_COLOR: u16 = 0x0001;
+/// Global swatches are linked to the artwork that uses them in Adobe apps, just like color styles
+const ASE_GLOBAL_COLOR: u16 = 0;
+
+/// The number of swatches in each row of the palette when shown in GIMP
+const GPL_COLUMNS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaletteFormat {
+	/// Adobe Swatch Exchange, read and written by Photoshop, Illustrator, InDesign, and Affinity apps
+	Ase,
+	/// GIMP palette, a text format also used by Inkscape, Krita, and Aseprite
+	Gpl,
+}
+
+impl PaletteFormat {
+	pub fn detect(data: &[u8]) -> Option<Self> {
+		if data.starts_with(ASE_SIGNATURE) {
+			return Some(Self::Ase);
+		}
+
+		let text = data.strip_prefix("\u{FEFF}".as_bytes()).unwrap_or(data);
+		text.starts_with(GPL_SIGNATURE.as_bytes()).then_some(Self::Gpl)
+	}
+
+	pub fn extension(self) -> &'static str {
+		match self {
+			Self::Ase => "ase",
+			Self::Gpl => "gpl",
+		}
+	}
+
+	pub fn mime(self) -> &'static str {
+		match self {
+			Self::Ase => "application/octet-stream",
+			Self::Gpl => "text/plain",
+		}
+	}
+}
+
+/// Reads the swatches of a palette file as sRGB color styles, in the order they appear in the file.
+pub fn read_palette(data: &[u8]) -> Result<Vec<ColorStyle>, EditorError> {
+	match PaletteFormat::detect(data) {
+		Some(PaletteFormat::Ase) => read_ase(data),
+		Some(PaletteFormat::Gpl) => read_gpl(data),
+		None => Err(EditorError::Misc("The file is not an Adobe Swatch Exchange or GIMP palette".into())),
+	}
+}
+
+/// Writes the sRGB colors of the color styles as a palette file. Palettes have no transparency, so the alpha of the colors is dropped.
+pub fn write_palette(format: PaletteFormat, name: &str, color_styles: &[ColorStyle]) -> Vec<u8> {
+	match format {
+		PaletteFormat::Ase => write_ase(name, color_styles),
+		PaletteFormat::Gpl => write_gpl(name, color_styles).into_bytes(),
+	}
+}
+
+/// Names the swatches that were saved without one by their hex color, as most apps show them.
+fn swatch_name(name: String, color: Color) -> String {
+	match name.trim() {
+		"" => format!("#{}", color.rgb_hex()),
+		trimmed => trimmed.to_string(),
+	}
+}
+
+/// Clamps a channel or ink value read from a palette file to the range of `0.0` to `1.0`, treating values which aren't finite as `0.0`.
+fn channel(channel: f32) -> f32 {
+	if channel.is_finite() {
+		channel.clamp(0., 1.)
+	} else {
+		0.
+	}
+}
+
+fn opaque_color(red: f32, green: f32, blue: f32) -> Color {
+	Color::from_rgbaf32(channel(red), channel(green), channel(blue), 1.).unwrap_or(Color::BLACK)
+}
+
+/// Converts a CIELAB color relative to the D50 white point, which Adobe apps use for their LAB colors, into sRGB.
+fn lab_to_srgb(lightness: f32, a: f32, b: f32) -> Color {
+	const WHITE_D50: [f32; 3] = [0.96422, 1., 0.82521];
+	// Converts D50 XYZ to linear sRGB, including the Bradford adaptation to sRGB's D65 white point
+	const XYZ_D50_TO_LINEAR_SRGB: [[f32; 3]; 3] = [[3.133856, -1.6168667, -0.4906146], [-0.9787684, 1.9161415, 0.033454], [0.0719453, -0.2289914, 1.4052427]];
+
+	let inverse = |t: f32| {
+		const DELTA: f32 = 6. / 29.;
+		if t > DELTA {
+			t.powi(3)
+		} else {
+			3. * DELTA * DELTA * (t - 4. / 29.)
+		}
+	};
+	let fy = (lightness + 16.) / 116.;
+	let xyz = [inverse(fy + a / 500.) * WHITE_D50[0], inverse(fy) * WHITE_D50[1], inverse(fy - b / 200.) * WHITE_D50[2]];
+	let [red, green, blue] = XYZ_D50_TO_LINEAR_SRGB.map(|row| row[0] * xyz[0] + row[1] * xyz[1] + row[2] * xyz[2]);
+
+	opaque_color(red, green, blue).convert_color_space(ColorSpace::LinearSrgb, ColorSpace::Srgb)
+}
+
+/// Reads the big-endian values of an Adobe Swatch Exchange file.
+struct AseReader<'a> {
+	data: &'a [u8],
+	position: usize,
+}
+
+impl<'a> AseReader<'a> {
+	fn bytes(&mut self, length: usize) -> Result<&'a [u8], EditorError> {
+		let bytes = self
+			.data
+			.get(self.position..self.position.saturating_add(length))
+			.ok_or_else(|| EditorError::Misc("The Adobe Swatch Exchange file ends unexpectedly".into()))?;
+		self.position += length;
+		Ok(bytes)
+	}
+
+	fn u16(&mut self) -> Result<u16, EditorError> {
+		self.bytes(2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+	}
+
+	fn u32(&mut self) -> Result<u32, EditorError> {
+		self.bytes(4).map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+	}
+
+	fn f32(&mut self) -> Result<f32, EditorError> {
+		self.bytes(4).map(|bytes| f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+	}
+
+	/// Reads a UTF-16 string, which is prefixed by its length in code units including the null terminator.
+	fn name(&mut self) -> Result<String, EditorError> {
+		let length = self.u16()? as usize;
+		let units = (0..length).map(|_| self.u16()).collect::<Result<Vec<_>, _>>()?;
+		let units = units.split(|&unit| unit == 0).next().unwrap_or_default();
+		Ok(String::from_utf16_lossy(units))
+	}
+}
+
+fn read_ase(data: &[u8]) -> Result<Vec<ColorStyle>, EditorError> {
+	let mut reader = AseReader { data, position: ASE_SIGNATURE.len() };
+	let major_version = reader.u16()?;
+	let _minor_version = reader.u16()?;
+	if major_version != 1 {
+		return Err(EditorError::Misc(format!("Version {major_version} Adobe Swatch Exchange files are not supported")));
+	}
+
+	let block_count = reader.u32()?;
+	let mut color_styles = Vec::new();
+	for _ in 0..block_count {
+		let block_type = reader.u16()?;
+		let length = reader.u32()? as usize;
+		let block = reader.bytes(length)?;
+
+		// Groups only organize the swatches, so their contents are read in order without them
+		if block_type != ASE_COLOR {
+			continue;
+		}
+
+		let mut block = AseReader { data: block, position: 0 };
+		let name = block.name()?;
+		let model = block.bytes(4)?;
+		let color = match model {
+			b"RGB " => opaque_color(block.f32()?, block.f32()?, block.f32()?),
+			b"CMYK" => {
+				let mut ink = || block.f32().map(channel);
+				Color::from_cmyk(Cmyk::new(ink()?, ink()?, ink()?, ink()?))
+			}
+			b"LAB " => lab_to_srgb(block.f32()? * 100., block.f32()?, block.f32()?),
+			b"Gray" => {
+				let gray = block.f32()?;
+				opaque_color(gray, gray, gray)
+			}
+			// Swatches in unknown color models are skipped rather than failing the whole palette
+			_ => continue,
+		};
+		color_styles.push(ColorStyle::new(swatch_name(name, color), color));
+	}
+
+	Ok(color_styles)
+}
+
+fn read_gpl(data: &[u8]) -> Result<Vec<ColorStyle>, EditorError> {
+	let text = String::from_utf8_lossy(data);
+
+	let mut color_styles = Vec::new();
+	for line in text.lines().skip(1).map(str::trim) {
+		if line.is_empty() || line.starts_with('#') || line.starts_with("Name:") || line.starts_with("Columns:") {
+			continue;
+		}
+
+		// Each swatch is its red, green, and blue values from 0 to 255, followed by its name
+		let mut rest = line;
+		let mut channel = || {
+			let (value, remainder) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+			rest = remainder.trim_start();
+			value.parse::<u8>().ok()
+		};
+		let (red, green, blue) = match (channel(), channel(), channel()) {
+			(Some(red), Some(green), Some(blue)) => (red, green, blue),
+			_ => return Err(EditorError::Misc(format!("The GIMP palette has an invalid swatch: \"{line}\""))),
+		};
+
+		let color = Color::from_rgb8(red, green, blue);
+		color_styles.push(ColorStyle::new(swatch_name(rest.to_string(), color), color));
+	}
+
+	Ok(color_styles)
+}
+
+fn ase_name(block: &mut Vec<u8>, name: &str) {
+	let units = name.encode_utf16().chain(std::iter::once(0)).collect::<Vec<_>>();
+	block.extend((units.len() as u16).to_be_bytes());
+	block.extend(units.into_iter().flat_map(u16::to_be_bytes));
+}
+
+fn ase_block(data: &mut Vec<u8>, block_type: u16, block: &[u8]) {
+	data.extend(block_type.to_be_bytes());
+	data.extend((block.len() as u32).to_be_bytes());
+	data.extend(block);
+}
+
+fn write_ase(name: &str, color_styles: &[ColorStyle]) -> Vec<u8> {
+	let mut data = ASE_SIGNATURE.to_vec();
+	data.extend(1_u16.to_be_bytes());
+	data.extend(0_u16.to_be_bytes());
+	data.extend((color_styles.len() as u32 + 2).to_be_bytes());
+
+	// The swatches are grouped under the palette's name, which Adobe apps show as a color group
+	let mut group = Vec::new();
+	ase_name(&mut group, name);
+	ase_block(&mut data, ASE_GROUP_START, &group);
+
+	for color_style in color_styles {
+		let mut block = Vec::new();
+		ase_name(&mut block, &color_style.name);
+		block.extend(b"RGB ");
+		let (red, green, blue, _) = color_style.color.components();
+		for channel in [red, green, blue] {
+			block.extend(channel.clamp(0., 1.).to_be_bytes());
+		}
+		block.extend(ASE_GLOBAL_COLOR.to_be_bytes());
+		ase_block(&mut data, ASE_COLOR, &block);
+	}
+
+	ase_block(&mut data, ASE_GROUP_END, &[]);
+	data
+}
+
+fn write_gpl(name: &str, color_styles: &[ColorStyle]) -> String {
+	// Line breaks in the name would start lines that are read as swatches
+	let name = name.replace(['\r', '\n'], " ");
+	let mut text = format!("{GPL_SIGNATURE}\nName: {name}\nColumns: {GPL_COLUMNS}\n#\n");
+
+	for color_style in color_styles {
+		let channel = |channel: f32| (channel.clamp(0., 1.) * 255.).round() as u8;
+		let (red, green, blue, _) = color_style.color.components();
+		let name = color_style.name.replace(['\r', '\n'], " ");
+		text.push_str(&format!("{:>3} {:>3} {:>3}\t{name}\n", channel(red), channel(green), channel(blue)));
+	}
+
+	text
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// Builds an Adobe Swatch Exchange file with a single swatch of the given color model and values.
+	fn ase(model: &[u8; 4], values: &[f32]) -> Vec<u8> {
+		let mut block = Vec::new();
+		ase_name(&mut block, "Swatch");
+		block.extend(model);
+		block.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+		block.extend(ASE_GLOBAL_COLOR.to_be_bytes());
+
+		let mut data = ASE_SIGNATURE.to_vec();
+		data.extend(1_u16.to_be_bytes());
+		data.extend(0_u16.to_be_bytes());
+		data.extend(1_u32.to_be_bytes());
+		ase_block(&mut data, ASE_COLOR, &block);
+		data
+	}
+
+	fn components(data: &[u8]) -> (f32, f32, f32, f32) {
+		let color_styles = read_palette(data).unwrap();
+		assert_eq!(color_styles.len(), 1);
+		color_styles[0].color.components()
+	}
+
+	#[test]
+	fn cmyk_swatches_are_converted() {
+		assert_eq!(components(&ase(b"CMYK", &[0., 0., 0., 0.])), (1., 1., 1., 1.));
+		assert_eq!(components(&ase(b"CMYK", &[1., 0., 1., 0.])), (0., 1., 0., 1.));
+		assert_eq!(components(&ase(b"CMYK", &[0., 0., 0., 1.])), (0., 0., 0., 1.));
+	}
+
+	#[test]
+	fn invalid_channels_are_clamped() {
+		let swatches: [(&[u8; 4], &[f32]); 5] = [
+			(b"RGB ", &[f32::NAN, f32::INFINITY, -1.]),
+			(b"CMYK", &[f32::NAN, f32::INFINITY, f32::NEG_INFINITY, f32::NAN]),
+			(b"CMYK", &[2., -1., 0.5, f32::NAN]),
+			(b"LAB ", &[f32::NAN, f32::INFINITY, 0.]),
+			(b"Gray", &[f32::NAN]),
+		];
+		for (model, values) in swatches {
+			let (red, green, blue, alpha) = components(&ase(model, values));
+			for channel in [red, green, blue, alpha] {
+				assert!((0. ..=1.).contains(&channel), "{model:?} {values:?}: {channel}");
+			}
+		}
+	}
+
+	#[test]
+	fn palettes_round_trip() {
+		let color_styles = vec![
+			ColorStyle::new("Red".into(), Color::from_rgb8(255, 0, 0)),
+			ColorStyle::new("Teal".into(), Color::from_rgb8(0, 128, 128)),
+		];
+		for format in [PaletteFormat::Ase, PaletteFormat::Gpl] {
+			let read = read_palette(&write_palette(format, "Palette", &color_styles)).unwrap();
+			let summary = |color_styles: &[ColorStyle]| color_styles.iter().map(|style| (style.name.clone(), style.color.rgb_hex())).collect::<Vec<_>>();
+			assert_eq!(summary(&read), summary(&color_styles), "{format:?}");
+		}
+	}
+}
@@ -0,0 +1,826 @@
+//! Writes the artwork of a document as a PDF file, with vector shapes, text outlines, gradients, and images kept as native PDF content.
+//!
+//! Content PDF can't represent, like gradients with varying transparency or images with an alpha channel, is either simplified or,
+//! when rasterizing unsupported content is enabled, sent to the frontend to be rasterized and embedded as an image once it comes back.
+
+use crate::messages::frontend::utility_types::FrontendPdfRaster;
+
+use graphene::color::{Color, ColorSpace};
+use graphene::layers::blend_mode::BlendMode;
+use graphene::layers::layer_info::{Layer, LayerDataType};
+use graphene::layers::style::{Fill, Gradient, GradientSpread, GradientType, LineCap, LineJoin, PathStyle};
+use graphene::layers::text_layer::FontCache;
+use graphene::layers::vector::subpath::Subpath;
+
+use glam::{DAffine2, DVec2};
+use kurbo::{BezPath, PathEl};
+use std::fmt::Write;
+
+/// The number of PDF points (1/72 of an inch) in a document pixel (1/96 of an inch).
+const POINTS_PER_PIXEL: f64 = 72. / 96.;
+/// The most times the colors of a repeating gradient are written out to cover a shape, past which its last colors are extended
+const MAX_GRADIENT_REPEATS: i64 = 256;
+/// The most pixels along either axis of rasterized content, which is the largest canvas browsers reliably support
+const MAX_RASTER_DIMENSION: f64 = 16384.;
+
+/// The options shared by every page of a PDF export.
+pub struct PdfExportSettings<'a> {
+	pub font_cache: &'a FontCache,
+	/// The color space the colors of the document are specified in, which are converted to sRGB for the PDF
+	pub color_space: ColorSpace,
+	/// Whether content PDF can't represent is rasterized, rather than simplified or left out
+	pub rasterize_unsupported: bool,
+	/// The number of raster pixels per document pixel for rasterized content
+	pub raster_scale: f64,
+}
+
+/// A PDF file being built, which may be waiting on the frontend to rasterize some of its content.
+#[derive(Clone, Debug, Default)]
+pub struct PdfExport {
+	pub file_name: String,
+	objects: PdfObjects,
+	/// The page tree object, reserved up front since every page references it as its parent
+	page_tree: usize,
+	pages: Vec<usize>,
+	pending_rasters: Vec<PendingRaster>,
+}
+
+#[derive(Clone, Debug)]
+struct PendingRaster {
+	/// The image object reserved for the raster
+	object: usize,
+	svg: String,
+	width: u32,
+	height: u32,
+}
+
+impl PdfExport {
+	pub fn new(file_name: String) -> Self {
+		let mut objects = PdfObjects::default();
+		let page_tree = objects.reserve();
+		Self {
+			file_name,
+			objects,
+			page_tree,
+			..Default::default()
+		}
+	}
+
+	/// Adds a page showing the area of the document within `bounds`, optionally filled with a background color like that of an artboard.
+	pub fn add_page(&mut self, root: &Layer, bounds: [DVec2; 2], background: Option<Color>, settings: &PdfExportSettings) {
+		let size = (bounds[1] - bounds[0]) * POINTS_PER_PIXEL;
+		// Maps document space, where y points down, to the points of the page, where y points up from its bottom left corner
+		let page_transform = DAffine2::from_cols_array(&[POINTS_PER_PIXEL, 0., 0., -POINTS_PER_PIXEL, -bounds[0].x * POINTS_PER_PIXEL, bounds[1].y * POINTS_PER_PIXEL]);
+
+		let mut page = PageWriter {
+			objects: &mut self.objects,
+			pending_rasters: &mut self.pending_rasters,
+			settings,
+			bounds,
+		};
+		let mut stream = ContentStream::new(page_transform.inverse());
+		let _ = writeln!(stream.content, "q {} cm", matrix(page_transform));
+		if let Some(color) = background.filter(|color| color.a() > 0.) {
+			stream.set_alpha(color.a() as f64, 1.);
+			let _ = writeln!(stream.content, "{} rg", page.rgb(color));
+			let _ = writeln!(
+				stream.content,
+				"{} {} {} {} re f",
+				number(bounds[0].x),
+				number(bounds[0].y),
+				number(bounds[1].x - bounds[0].x),
+				number(bounds[1].y - bounds[0].y)
+			);
+		}
+		page.write_layer(&mut stream, root, DAffine2::IDENTITY);
+		stream.content.push_str("Q\n");
+
+		let resources = stream.resources();
+		let contents = self.objects.add(stream_object("", stream.content.as_bytes()));
+		let page = self.objects.add(
+			format!(
+				"<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources {resources} /Contents {contents} 0 R >>",
+				self.page_tree,
+				number(size.x),
+				number(size.y)
+			)
+			.into_bytes(),
+		);
+		self.pages.push(page);
+	}
+
+	/// The content the frontend needs to rasterize before the PDF can be finished.
+	pub fn rasters(&self) -> Vec<FrontendPdfRaster> {
+		self.pending_rasters
+			.iter()
+			.map(|raster| FrontendPdfRaster {
+				svg: raster.svg.clone(),
+				width: raster.width,
+				height: raster.height,
+			})
+			.collect()
+	}
+
+	/// Writes the PDF file, given the RGBA pixels of every raster one after another, in the order of [PdfExport::rasters].
+	pub fn finish(mut self, raster_pixels: &[u8]) -> Vec<u8> {
+		let mut remaining_pixels = raster_pixels;
+		for raster in std::mem::take(&mut self.pending_rasters) {
+			let count = raster.width as usize * raster.height as usize;
+			let length = count * 4;
+			let pixels = match remaining_pixels.get(..length) {
+				Some(pixels) => pixels,
+				// Missing pixels are left transparent
+				None => &[],
+			};
+			remaining_pixels = remaining_pixels.get(length..).unwrap_or_default();
+
+			let pixel = |index: usize| pixels.get(index * 4..index * 4 + 4).unwrap_or(&[0; 4]);
+			let rgb = (0..count).flat_map(|index| pixel(index)[..3].to_vec()).collect::<Vec<_>>();
+			let alpha = (0..count).map(|index| pixel(index)[3]).collect::<Vec<_>>();
+
+			let dimensions = format!("/Type /XObject /Subtype /Image /Width {} /Height {} /BitsPerComponent 8", raster.width, raster.height);
+			let mask = self.objects.add(stream_object(&format!("{dimensions} /ColorSpace /DeviceGray"), &alpha));
+			self.objects.set(raster.object, stream_object(&format!("{dimensions} /ColorSpace /DeviceRGB /SMask {mask} 0 R"), &rgb));
+		}
+
+		let kids = self.pages.iter().map(|page| format!("{page} 0 R")).collect::<Vec<_>>().join(" ");
+		self.objects.set(self.page_tree, format!("<< /Type /Pages /Kids [{kids}] /Count {} >>", self.pages.len()).into_bytes());
+		let catalog = self.objects.add(format!("<< /Type /Catalog /Pages {} 0 R >>", self.page_tree).into_bytes());
+
+		self.objects.write(catalog)
+	}
+}
+
+/// The numbered objects of a PDF file, where object `n` is stored at index `n - 1`.
+#[derive(Clone, Debug, Default)]
+struct PdfObjects {
+	objects: Vec<Vec<u8>>,
+}
+
+impl PdfObjects {
+	/// Reserves the number of an object to be set later, for objects which need to be referenced before they are written.
+	fn reserve(&mut self) -> usize {
+		self.objects.push(Vec::new());
+		self.objects.len()
+	}
+
+	fn add(&mut self, object: Vec<u8>) -> usize {
+		self.objects.push(object);
+		self.objects.len()
+	}
+
+	fn set(&mut self, number: usize, object: Vec<u8>) {
+		self.objects[number - 1] = object;
+	}
+
+	fn write(self, catalog: usize) -> Vec<u8> {
+		// The comment of high bytes after the header marks the file as binary
+		let mut file = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec();
+		let mut offsets = Vec::with_capacity(self.objects.len());
+		for (index, object) in self.objects.iter().enumerate() {
+			offsets.push(file.len());
+			file.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+			file.extend_from_slice(object);
+			file.extend_from_slice(b"\nendobj\n");
+		}
+
+		let cross_reference = file.len();
+		let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.objects.len() + 1);
+		for offset in offsets {
+			let _ = writeln!(table, "{offset:010} 00000 n ");
+		}
+		let _ = writeln!(table, "trailer\n<< /Size {} /Root {catalog} 0 R >>\nstartxref\n{cross_reference}\n%%EOF", self.objects.len() + 1);
+		file.extend_from_slice(table.as_bytes());
+		file
+	}
+}
+
+fn stream_object(dictionary: &str, data: &[u8]) -> Vec<u8> {
+	let mut object = format!("<< {dictionary} /Length {} >>\nstream\n", data.len()).into_bytes();
+	object.extend_from_slice(data);
+	object.extend_from_slice(b"\nendstream");
+	object
+}
+
+/// The operators of a page or form XObject, along with the resources they use.
+struct ContentStream {
+	content: String,
+	/// Maps the pattern space of the stream, which is the default space of a page or the space a form is drawn in, to document space
+	pattern_space: DAffine2,
+	graphics_states: Vec<String>,
+	patterns: Vec<usize>,
+	x_objects: Vec<usize>,
+}
+
+impl ContentStream {
+	fn new(pattern_space: DAffine2) -> Self {
+		Self {
+			content: String::new(),
+			pattern_space,
+			graphics_states: Vec::new(),
+			patterns: Vec::new(),
+			x_objects: Vec::new(),
+		}
+	}
+
+	/// The resource name of a graphics state with the given parameters, reusing an identical one if it was already added.
+	fn graphics_state(&mut self, parameters: String) -> String {
+		let index = match self.graphics_states.iter().position(|existing| *existing == parameters) {
+			Some(index) => index,
+			None => {
+				self.graphics_states.push(parameters);
+				self.graphics_states.len() - 1
+			}
+		};
+		format!("GS{index}")
+	}
+
+	fn pattern(&mut self, object: usize) -> String {
+		self.patterns.push(object);
+		format!("P{}", self.patterns.len() - 1)
+	}
+
+	fn x_object(&mut self, object: usize) -> String {
+		self.x_objects.push(object);
+		format!("X{}", self.x_objects.len() - 1)
+	}
+
+	/// Sets the constant fill and stroke opacity, if either is translucent.
+	fn set_alpha(&mut self, fill_alpha: f64, stroke_alpha: f64) {
+		if fill_alpha < 1. || stroke_alpha < 1. {
+			let graphics_state = self.graphics_state(format!("/ca {} /CA {}", number(fill_alpha), number(stroke_alpha)));
+			let _ = writeln!(self.content, "/{graphics_state} gs");
+		}
+	}
+
+	fn resources(&self) -> String {
+		let graphics_states = self.graphics_states.iter().enumerate().map(|(index, parameters)| format!("/GS{index} << {parameters} >>"));
+		let patterns = self.patterns.iter().enumerate().map(|(index, object)| format!("/P{index} {object} 0 R"));
+		let x_objects = self.x_objects.iter().enumerate().map(|(index, object)| format!("/X{index} {object} 0 R"));
+		format!(
+			"<< /ExtGState << {} >> /Pattern << {} >> /XObject << {} >> >>",
+			graphics_states.collect::<Vec<_>>().join(" "),
+			patterns.collect::<Vec<_>>().join(" "),
+			x_objects.collect::<Vec<_>>().join(" ")
+		)
+	}
+}
+
+struct PageWriter<'a> {
+	objects: &'a mut PdfObjects,
+	pending_rasters: &'a mut Vec<PendingRaster>,
+	settings: &'a PdfExportSettings<'a>,
+	/// The area of the document shown on the page
+	bounds: [DVec2; 2],
+}
+
+impl PageWriter<'_> {
+	/// Writes a layer, given the transform from the current user space (the space of the layer's parent) to document space.
+	fn write_layer(&mut self, stream: &mut ContentStream, layer: &Layer, transform: DAffine2) {
+		if !layer.visible || layer.opacity <= 0. {
+			return;
+		}
+
+		if layer.opacity >= 1. && layer.blend_mode == BlendMode::Normal {
+			self.write_layer_contents(stream, layer, transform);
+			return;
+		}
+
+		// Opacity and blending apply to the layer as a whole, so it's drawn as a transparency group
+		let mut group = ContentStream::new(transform);
+		self.write_layer_contents(&mut group, layer, transform);
+		let bounding_box = match self.page_bounds_in(transform) {
+			Some([min, max]) => format!("[{} {} {} {}]", number(min.x), number(min.y), number(max.x), number(max.y)),
+			None => return,
+		};
+		let form = self.objects.add(stream_object(
+			&format!("/Type /XObject /Subtype /Form /BBox {bounding_box} /Group << /S /Transparency >> /Resources {}", group.resources()),
+			group.content.as_bytes(),
+		));
+
+		let opacity = number(layer.opacity);
+		let graphics_state = stream.graphics_state(format!("/ca {opacity} /CA {opacity} /BM /{}", blend_mode_name(layer.blend_mode)));
+		let x_object = stream.x_object(form);
+		let _ = writeln!(stream.content, "q /{graphics_state} gs /{x_object} Do Q");
+	}
+
+	fn write_layer_contents(&mut self, stream: &mut ContentStream, layer: &Layer, transform: DAffine2) {
+		match &layer.data {
+			LayerDataType::Folder(folder) => {
+				let transform = transform * layer.transform;
+				if !transform.is_finite() || transform.matrix2.determinant() == 0. {
+					return;
+				}
+				let _ = writeln!(stream.content, "q {} cm", matrix(layer.transform));
+				for child in folder.layers() {
+					self.write_layer(stream, child, transform);
+				}
+				stream.content.push_str("Q\n");
+			}
+			LayerDataType::Shape(shape) => self.write_shape(stream, layer, &shape.shape.with_rounded_corners(), &shape.style, transform),
+			LayerDataType::Text(text) => self.write_shape(stream, layer, &text.to_subpath_nonmut(self.settings.font_cache), &text.path_style, transform),
+			LayerDataType::Image(image) => self.write_image(stream, layer, &image.mime, &image.image_data, image.dimensions, transform),
+			LayerDataType::Imaginate(imaginate) => {
+				if let Some(image_data) = &imaginate.image_data {
+					self.write_image(stream, layer, &imaginate.mime, &image_data.image_data, imaginate.dimensions, transform);
+				}
+			}
+		}
+	}
+
+	/// Writes a shape, whose path is in the layer's space but whose stroke is drawn in the space of its parent, matching the SVG renderer.
+	fn write_shape(&mut self, stream: &mut ContentStream, layer: &Layer, subpath: &Subpath, style: &PathStyle, transform: DAffine2) {
+		// A stroke of varying width can't be written as a PDF stroke, so it's written as its outline filled with its color on top of the fill
+		if let Some(stroke) = style.stroke().filter(|stroke| stroke.has_width_profile() && stroke.is_visible()) {
+			let mut fill_style = style.clone();
+			fill_style.clear_stroke();
+			self.write_shape(stream, layer, subpath, &fill_style, transform);
+			if let Some(outline) = subpath.transformed_stroke_outline(&stroke, layer.transform) {
+				self.write_shape(stream, layer, &outline, &PathStyle::new(None, stroke.color().map_or(Fill::None, Fill::Solid)), transform);
+			}
+			return;
+		}
+
+		let mut subpath = subpath.clone();
+		subpath.apply_affine(layer.transform);
+
+		let fill = match style.fill() {
+			Fill::None => None,
+			Fill::Solid(color) => Some((format!("{} rg", self.rgb(*color)), color.a() as f64)),
+			Fill::Gradient(gradient) => {
+				let stops = gradient_stops(gradient);
+				let alpha = stops.first().map_or(1., |&(_, color)| color.a());
+				// PDF shadings can't vary in transparency without a soft mask, and have no angular or diamond type, which are otherwise drawn as radial gradients
+				let unsupported = stops.iter().any(|&(_, color)| color.a() != alpha) || !gradient.gradient_type.has_common_primitive();
+				if unsupported && self.settings.rasterize_unsupported {
+					self.write_raster(stream, layer, transform, style.stroke().map_or(0., |stroke| stroke.weight()));
+					return;
+				}
+				let bounds = layer_bounds(layer, self.settings.font_cache);
+				self.gradient_pattern(stream, gradient, &stops, layer.transform, bounds, transform)
+					.map(|pattern| (format!("/Pattern cs /{pattern} scn"), alpha as f64))
+			}
+		};
+		let stroke = style.stroke().filter(|stroke| stroke.weight() > 0.).and_then(|stroke| stroke.color().map(|color| (stroke, color)));
+		if fill.is_none() && stroke.is_none() {
+			return;
+		}
+
+		stream.content.push_str("q\n");
+		stream.set_alpha(fill.as_ref().map_or(1., |(_, alpha)| *alpha), stroke.as_ref().map_or(1., |(_, color)| color.a() as f64));
+		if let Some((operator, _)) = &fill {
+			let _ = writeln!(stream.content, "{operator}");
+		}
+		if let Some((stroke, color)) = &stroke {
+			let line_cap = match stroke.line_cap() {
+				LineCap::Butt => 0,
+				LineCap::Round => 1,
+				LineCap::Square => 2,
+			};
+			let line_join = match stroke.line_join() {
+				LineJoin::Miter => 0,
+				LineJoin::Round => 1,
+				LineJoin::Bevel => 2,
+			};
+			let dashes = stroke.dash_pattern().iter().map(|&length| number(length as f64)).collect::<Vec<_>>().join(" ");
+			let _ = writeln!(
+				stream.content,
+				"{} RG {} w {line_cap} J {line_join} j {} M [{dashes}] {} d",
+				self.rgb(*color),
+				number(stroke.weight()),
+				number(stroke.line_join_miter_limit() as f64),
+				number(stroke.dash_offset())
+			);
+		}
+
+		write_path(&mut stream.content, &BezPath::from(&subpath));
+		let operator = match (fill.is_some(), stroke.is_some()) {
+			(true, true) => "B",
+			(true, false) => "f",
+			_ => "S",
+		};
+		let _ = writeln!(stream.content, "{operator}\nQ");
+	}
+
+	/// Adds a shading pattern for a gradient fill, returning its resource name.
+	fn gradient_pattern(&mut self, stream: &mut ContentStream, gradient: &Gradient, stops: &[(f64, Color)], layer_transform: DAffine2, bounds: [DVec2; 2], transform: DAffine2) -> Option<String> {
+		// Gradient positions are given relative to the bounding box of the layer's path, and are placed in the space of its parent
+		let to_parent = layer_transform * DAffine2::from_scale_angle_translation(bounds[1] - bounds[0], 0., bounds[0]);
+		let (start, end) = (to_parent.transform_point2(gradient.start), to_parent.transform_point2(gradient.end));
+
+		// Shadings only extend the colors at their ends, so repeating gradients are stretched over the whole shape with their colors repeated along it
+		let (first, last) = match gradient.spread {
+			GradientSpread::Pad => (0, 1),
+			GradientSpread::Repeat | GradientSpread::Reflect => {
+				let mut parent_gradient = gradient.clone();
+				(parent_gradient.start, parent_gradient.end) = (start, end);
+				if !gradient.gradient_type.has_common_primitive() {
+					parent_gradient.gradient_type = GradientType::Radial;
+				}
+				let positions = [DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y].map(|corner| parent_gradient.position_at(to_parent.transform_point2(corner)));
+				let lowest = positions.iter().copied().fold(f64::INFINITY, f64::min);
+				let highest = positions.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+				let first = if gradient.gradient_type == GradientType::Linear {
+					lowest.floor().max(-(MAX_GRADIENT_REPEATS as f64)) as i64
+				} else {
+					0
+				};
+				let last = (highest.ceil().min((first + MAX_GRADIENT_REPEATS) as f64) as i64).max(first + 1);
+				(first, last)
+			}
+		};
+		let coordinates = match gradient.gradient_type {
+			GradientType::Linear => {
+				let (from, to) = (start + (end - start) * first as f64, start + (end - start) * last as f64);
+				[from.x, from.y, to.x, to.y].map(number).join(" ")
+			}
+			_ => [start.x, start.y, 0., start.x, start.y, start.distance(end) * last as f64].map(number).join(" "),
+		};
+		// Angular and diamond gradients are drawn as the radial gradient with the same start and end points
+		let shading_type = match gradient.gradient_type {
+			GradientType::Linear => 2,
+			_ => 3,
+		};
+
+		let interpolation = |start: Color, end: Color| format!("<< /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>", self.rgb(start), self.rgb(end));
+		let function = match stops {
+			[] => return None,
+			[(_, color)] => interpolation(*color, *color),
+			_ => {
+				let functions = stops.windows(2).map(|pair| interpolation(pair[0].1, pair[1].1)).collect::<Vec<_>>();
+				let bounds = stops[1..stops.len() - 1].iter().map(|&(position, _)| number(position)).collect::<Vec<_>>();
+				let encode = vec!["0 1"; functions.len()];
+				format!(
+					"<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds [{}] /Encode [{}] >>",
+					functions.join(" "),
+					bounds.join(" "),
+					encode.join(" ")
+				)
+			}
+		};
+
+		let function = match last - first {
+			1 => function,
+			_ => {
+				let functions = vec![function; (last - first) as usize];
+				let bounds = (first + 1..last).map(|bound| bound.to_string()).collect::<Vec<_>>();
+				let encode = (first..last)
+					.map(|repeat| if gradient.spread == GradientSpread::Reflect && repeat.rem_euclid(2) == 1 { "1 0" } else { "0 1" })
+					.collect::<Vec<_>>();
+				format!(
+					"<< /FunctionType 3 /Domain [{first} {last}] /Functions [{}] /Bounds [{}] /Encode [{}] >>",
+					functions.join(" "),
+					bounds.join(" "),
+					encode.join(" ")
+				)
+			}
+		};
+
+		let pattern_matrix = stream.pattern_space.inverse() * transform;
+		let pattern = self.objects.add(
+			format!(
+				"<< /Type /Pattern /PatternType 2 /Matrix [{}] /Shading << /ShadingType {shading_type} /ColorSpace /DeviceRGB /Coords [{coordinates}] /Domain [{first} {last}] /Function {function} /Extend [true true] >> >>",
+				matrix(pattern_matrix)
+			)
+			.into_bytes(),
+		);
+		Some(stream.pattern(pattern))
+	}
+
+	fn write_image(&mut self, stream: &mut ContentStream, layer: &Layer, mime: &str, image_data: &[u8], dimensions: DVec2, transform: DAffine2) {
+		let image = match mime {
+			"image/jpeg" => jpeg_image(image_data),
+			"image/png" => png_image(image_data),
+			_ => None,
+		};
+		let image = match image {
+			Some(image) => image,
+			None => {
+				if self.settings.rasterize_unsupported {
+					self.write_raster(stream, layer, transform, 0.);
+				}
+				return;
+			}
+		};
+
+		let object = self.objects.add(image);
+		let x_object = stream.x_object(object);
+		// Images fill the unit square with their first row at the top, so it's flipped to place the image's top left corner at the layer's origin
+		let placement = layer.transform * DAffine2::from_cols_array(&[dimensions.x, 0., 0., -dimensions.y, 0., dimensions.y]);
+		let _ = writeln!(stream.content, "q {} cm /{x_object} Do Q", matrix(placement));
+	}
+
+	/// Places an image of the layer, to be rasterized by the frontend, covering its bounding box within the page.
+	fn write_raster(&mut self, stream: &mut ContentStream, layer: &Layer, transform: DAffine2, stroke_width: f64) {
+		let to_document = transform * layer.transform;
+		let [min, max] = match layer.aabb_for_transform(to_document, self.settings.font_cache) {
+			Some([min, max]) => {
+				// Strokes extend past the bounding box of the path
+				let margin = DVec2::splat(stroke_width / 2. * transform.matrix2.determinant().abs().sqrt());
+				[(min - margin).max(self.bounds[0]), (max + margin).min(self.bounds[1])]
+			}
+			None => return,
+		};
+		let size = max - min;
+		if size.x <= 0. || size.y <= 0. || transform.matrix2.determinant() == 0. {
+			return;
+		}
+
+		let [width, height] = raster_dimensions(size, self.settings.raster_scale);
+		let svg = format!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="{} {} {} {}" preserveAspectRatio="none"><defs>{}</defs><g transform="matrix({})">{}</g></svg>"#,
+			min.x,
+			min.y,
+			size.x,
+			size.y,
+			layer.svg_defs_cache,
+			to_document.to_cols_array().map(|entry| entry.to_string()).join(","),
+			layer.thumbnail_cache
+		);
+
+		let object = self.objects.reserve();
+		self.pending_rasters.push(PendingRaster { object, svg, width, height });
+
+		let x_object = stream.x_object(object);
+		let placement = transform.inverse() * DAffine2::from_cols_array(&[size.x, 0., 0., -size.y, min.x, max.y]);
+		let _ = writeln!(stream.content, "q {} cm /{x_object} Do Q", matrix(placement));
+	}
+
+	/// The page's bounds in a user space, given the transform from that space to document space.
+	fn page_bounds_in(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
+		let inverse = transform.inverse();
+		if !inverse.is_finite() {
+			return None;
+		}
+		let [min, max] = self.bounds;
+		let corners = [min, DVec2::new(max.x, min.y), max, DVec2::new(min.x, max.y)].map(|corner| inverse.transform_point2(corner));
+		Some(corners.iter().fold([corners[0], corners[0]], |[min, max], &corner| [min.min(corner), max.max(corner)]))
+	}
+
+	/// The channels of a color as an sRGB PDF color operand.
+	fn rgb(&self, color: Color) -> String {
+		let color = color.convert_color_space(self.settings.color_space, ColorSpace::Srgb);
+		[color.r(), color.g(), color.b()].map(|channel| number(channel.clamp(0., 1.) as f64)).join(" ")
+	}
+}
+
+/// The bounding box of the path of a shape or text layer, in its own space, which its gradient positions are relative to.
+fn layer_bounds(layer: &Layer, font_cache: &FontCache) -> [DVec2; 2] {
+	let subpath = match &layer.data {
+		LayerDataType::Shape(shape) => Some(shape.shape.with_rounded_corners().into_owned()),
+		LayerDataType::Text(text) => Some(text.to_subpath_nonmut(font_cache)),
+		_ => None,
+	};
+	subpath.and_then(|subpath| subpath.bounding_box()).unwrap_or_default()
+}
+
+/// The stops of a gradient sorted by position, with stops added at its ends to extend the first and last colors.
+fn gradient_stops(gradient: &Gradient) -> Vec<(f64, Color)> {
+	let mut stops = gradient
+		.positions
+		.iter()
+		.filter_map(|&(position, color)| color.map(|color| (position.clamp(0., 1.), color)))
+		.collect::<Vec<_>>();
+	stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+	if let Some(&(_, color)) = stops.first().filter(|(position, _)| *position > 0.) {
+		stops.insert(0, (0., color));
+	}
+	if let Some(&(_, color)) = stops.last().filter(|(position, _)| *position < 1.) {
+		stops.push((1., color));
+	}
+	stops
+}
+
+fn write_path(content: &mut String, path: &BezPath) {
+	let point = |point: kurbo::Point| format!("{} {}", number(point.x), number(point.y));
+	let mut current = kurbo::Point::ZERO;
+	for element in path.elements() {
+		let _ = match *element {
+			PathEl::MoveTo(to) => writeln!(content, "{} m", point(to)),
+			PathEl::LineTo(to) => writeln!(content, "{} l", point(to)),
+			// PDF only has cubic curves, which can represent quadratic ones exactly
+			PathEl::QuadTo(control, to) => writeln!(
+				content,
+				"{} {} {} c",
+				point(current + (control - current) * (2. / 3.)),
+				point(to + (control - to) * (2. / 3.)),
+				point(to)
+			),
+			PathEl::CurveTo(first, second, to) => writeln!(content, "{} {} {} c", point(first), point(second), point(to)),
+			PathEl::ClosePath => writeln!(content, "h"),
+		};
+		current = match *element {
+			PathEl::MoveTo(to) | PathEl::LineTo(to) | PathEl::QuadTo(_, to) | PathEl::CurveTo(_, _, to) => to,
+			PathEl::ClosePath => current,
+		};
+	}
+}
+
+/// The size in pixels of rasterized content covering `size` in document pixels, scaled down to fit within [MAX_RASTER_DIMENSION] along both axes.
+fn raster_dimensions(size: DVec2, raster_scale: f64) -> [u32; 2] {
+	let raster_size = size * raster_scale;
+	let fit = MAX_RASTER_DIMENSION / raster_size.max_element();
+	let raster_size = if raster_size.is_finite() && fit.is_finite() { raster_size * fit.min(1.) } else { DVec2::ONE };
+	[raster_size.x, raster_size.y].map(|length| length.ceil().clamp(1., MAX_RASTER_DIMENSION) as u32)
+}
+
+/// Embeds a baseline or progressive JPEG file as is, since PDF can decode it.
+fn jpeg_image(data: &[u8]) -> Option<Vec<u8>> {
+	let mut position = 2;
+	while position + 9 < data.len() {
+		if data[position] != 0xFF {
+			return None;
+		}
+		let marker = data[position + 1];
+		let length = u16::from_be_bytes([data[position + 2], data[position + 3]]) as usize;
+		// The start of frame markers, except those for Huffman tables, arithmetic coding conditioning, and JPEG extensions
+		if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+			let height = u16::from_be_bytes([data[position + 5], data[position + 6]]);
+			let width = u16::from_be_bytes([data[position + 7], data[position + 8]]);
+			let color_space = match data[position + 9] {
+				1 => "/DeviceGray",
+				3 => "/DeviceRGB",
+				// CMYK JPEGs are usually saved inverted, as Photoshop does
+				4 => "/DeviceCMYK /Decode [1 0 1 0 1 0 1 0]",
+				_ => return None,
+			};
+			let dictionary = format!("/Type /XObject /Subtype /Image /Width {width} /Height {height} /ColorSpace {color_space} /BitsPerComponent 8 /Filter /DCTDecode");
+			return Some(stream_object(&dictionary, data));
+		}
+		position += 2 + length;
+	}
+	None
+}
+
+/// Embeds the compressed data of an opaque, non-interlaced PNG file, which PDF can decode using PNG predictors.
+/// PNGs with transparency can't be embedded this way, since PDF keeps the alpha channel in a separate image.
+fn png_image(data: &[u8]) -> Option<Vec<u8>> {
+	if !data.starts_with(b"\x89PNG\r\n\x1a\n") {
+		return None;
+	}
+
+	let mut header = None;
+	let mut palette = None;
+	let mut compressed = Vec::new();
+	let mut position = 8;
+	while position + 8 <= data.len() {
+		let length = u32::from_be_bytes(data[position..position + 4].try_into().ok()?) as usize;
+		let chunk_type = &data[position + 4..position + 8];
+		let chunk = data.get(position + 8..(position + 8).checked_add(length)?)?;
+		match chunk_type {
+			b"IHDR" if length >= 13 => header = Some(chunk),
+			b"PLTE" => palette = Some(chunk),
+			b"IDAT" => compressed.extend_from_slice(chunk),
+			b"tRNS" => return None,
+			b"IEND" => break,
+			_ => {}
+		}
+		// Skip the chunk's length, type, data, and checksum
+		position = position.checked_add(12 + length)?;
+	}
+
+	let header = header?;
+	let width = u32::from_be_bytes(header[0..4].try_into().ok()?);
+	let height = u32::from_be_bytes(header[4..8].try_into().ok()?);
+	let (bit_depth, color_type, interlaced) = (header[8], header[9], header[12] != 0);
+	if interlaced {
+		return None;
+	}
+	let (color_space, colors) = match color_type {
+		0 => ("/DeviceGray".to_string(), 1),
+		2 => ("/DeviceRGB".to_string(), 3),
+		3 => {
+			let palette = palette?;
+			// A palette holds between 1 and 256 colors of 3 bytes each
+			let entries = palette.len() / 3;
+			if palette.len() % 3 != 0 || !(1..=256).contains(&entries) {
+				return None;
+			}
+			let hex = palette.iter().map(|byte| format!("{byte:02X}")).collect::<String>();
+			(format!("[/Indexed /DeviceRGB {} <{hex}>]", entries - 1), 1)
+		}
+		// Grayscale and RGB with an alpha channel
+		_ => return None,
+	};
+
+	let dictionary = format!(
+		"/Type /XObject /Subtype /Image /Width {width} /Height {height} /ColorSpace {color_space} /BitsPerComponent {bit_depth} /Filter /FlateDecode /DecodeParms << /Predictor 15 /Colors {colors} /BitsPerComponent {bit_depth} /Columns {width} >>"
+	);
+	Some(stream_object(&dictionary, &compressed))
+}
+
+fn blend_mode_name(blend_mode: BlendMode) -> &'static str {
+	match blend_mode {
+		BlendMode::Normal => "Normal",
+		BlendMode::Multiply => "Multiply",
+		BlendMode::Darken => "Darken",
+		BlendMode::ColorBurn => "ColorBurn",
+		BlendMode::Screen => "Screen",
+		BlendMode::Lighten => "Lighten",
+		BlendMode::ColorDodge => "ColorDodge",
+		BlendMode::Overlay => "Overlay",
+		BlendMode::SoftLight => "SoftLight",
+		BlendMode::HardLight => "HardLight",
+		BlendMode::Difference => "Difference",
+		BlendMode::Exclusion => "Exclusion",
+		BlendMode::Hue => "Hue",
+		BlendMode::Saturation => "Saturation",
+		BlendMode::Color => "Color",
+		BlendMode::Luminosity => "Luminosity",
+	}
+}
+
+fn matrix(transform: DAffine2) -> String {
+	transform.to_cols_array().map(number).join(" ")
+}
+
+/// Formats a number for PDF, which doesn't allow exponents, with trailing zeros removed.
+fn number(value: f64) -> String {
+	let value = if value.is_finite() { value } else { 0. };
+	let formatted = format!("{value:.5}");
+	let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+	match formatted {
+		"-0" | "" => "0".to_string(),
+		_ => formatted.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// A PNG file with the given chunks after its signature, with zeroed checksums since they aren't read.
+	fn png(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+		let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+		for (chunk_type, chunk) in chunks {
+			data.extend_from_slice(&(chunk.len() as u32).to_be_bytes());
+			data.extend_from_slice(*chunk_type);
+			data.extend_from_slice(chunk);
+			data.extend_from_slice(&[0; 4]);
+		}
+		data
+	}
+
+	fn header(color_type: u8) -> Vec<u8> {
+		let mut header = Vec::new();
+		header.extend_from_slice(&2_u32.to_be_bytes());
+		header.extend_from_slice(&3_u32.to_be_bytes());
+		header.extend_from_slice(&[8, color_type, 0, 0, 0]);
+		header
+	}
+
+	#[test]
+	fn png_with_a_palette() {
+		let data = png(&[(b"IHDR", &header(3)), (b"PLTE", &[255, 0, 0, 0, 0, 255]), (b"IDAT", &[1, 2, 3]), (b"IEND", &[])]);
+		let object = String::from_utf8_lossy(&png_image(&data).unwrap()).into_owned();
+		assert!(object.contains("/Width 2 /Height 3"));
+		assert!(object.contains("[/Indexed /DeviceRGB 1 <FF00000000FF>]"));
+	}
+
+	#[test]
+	fn png_with_an_invalid_palette() {
+		for palette in [&[][..], &[1, 2], &[1, 2, 3, 4], &[0; 257 * 3]] {
+			let data = png(&[(b"IHDR", &header(3)), (b"PLTE", palette), (b"IDAT", &[1, 2, 3]), (b"IEND", &[])]);
+			assert_eq!(png_image(&data), None);
+		}
+		let data = png(&[(b"IHDR", &header(3)), (b"IDAT", &[1, 2, 3]), (b"IEND", &[])]);
+		assert_eq!(png_image(&data), None);
+	}
+
+	#[test]
+	fn png_with_a_truncated_chunk() {
+		let mut data = png(&[(b"IHDR", &header(2))]);
+		data.extend_from_slice(&u32::MAX.to_be_bytes());
+		data.extend_from_slice(b"IDAT");
+		assert_eq!(png_image(&data), None);
+		assert_eq!(png_image(b"\x89PNG\r\n\x1a\n"), None);
+	}
+
+	#[test]
+	fn rasters_fit_within_the_maximum_size() {
+		assert_eq!(raster_dimensions(DVec2::new(10.5, 20.), 2.), [21, 40]);
+		assert_eq!(raster_dimensions(DVec2::new(0.1, 0.1), 1.), [1, 1]);
+		assert_eq!(raster_dimensions(DVec2::new(1e6, 5e5), 1.), [16384, 8192]);
+		assert_eq!(raster_dimensions(DVec2::new(1e300, 1e300), 1e300), [1, 1]);
+		assert_eq!(raster_dimensions(DVec2::new(10., 10.), f64::NAN), [1, 1]);
+	}
+
+	#[test]
+	fn finish_with_missing_raster_pixels() {
+		let mut export = PdfExport::new("file".into());
+		for (width, height) in [(2, 1), (3, 2)] {
+			let object = export.objects.reserve();
+			let svg = String::new();
+			export.pending_rasters.push(PendingRaster { object, svg, width, height });
+		}
+		assert_eq!(export.rasters().len(), 2);
+
+		// Only the first raster and half of the second pixel come back, so the rest is left transparent
+		let pdf = export.finish(&[1, 2, 3, 4, 5, 6]);
+		let pdf = String::from_utf8_lossy(&pdf);
+		assert!(pdf.contains("/Width 2 /Height 1 /BitsPerComponent 8 /ColorSpace /DeviceRGB"));
+		assert!(pdf.contains("/Width 3 /Height 2 /BitsPerComponent 8 /ColorSpace /DeviceGray"));
+	}
+}
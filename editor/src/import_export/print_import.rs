@@ -0,0 +1,2499 @@
+//! Converts the vector artwork of PDF and EPS files into SVG markup, which is then imported as editable layers like any other SVG.
+//!
+//! Only the first page of a PDF is read. Paths, fills, strokes, dashes, transforms, opacity, and gray, RGB, and CMYK colors are supported,
+//! including inside form XObjects. Text, images, shadings, patterns, and clipping paths are skipped, and even-odd fills are imported as nonzero fills.
+//!
+//! EPS files are run through a small PostScript interpreter, which understands the stack, math, dictionary, control flow, path, painting,
+//! and graphics state operators used by the vector artwork of illustration software. Other operators are ignored.
+
+use crate::messages::portfolio::document::utility_types::error::EditorError;
+
+use glam::{DAffine2, DVec2};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::io::Read;
+use std::rc::Rc;
+
+/// The number of document pixels (1/96 of an inch) in a point (1/72 of an inch), the unit of PDF and PostScript user space.
+const PIXELS_PER_POINT: f64 = 96. / 72.;
+/// Nested form XObjects and PostScript procedures deeper than this are assumed to be a cycle.
+const MAX_NESTING_DEPTH: usize = 64;
+/// PostScript programs running more operators than this are assumed to never finish.
+const MAX_POSTSCRIPT_STEPS: usize = 10_000_000;
+/// PDF content streams, counting those of form XObjects each time they're drawn, running more operators than this are assumed to never finish.
+const MAX_PDF_OPERATORS: usize = 10_000_000;
+/// Decoded streams, and the decoded form XObjects drawn by a page altogether, larger than this are rejected so a small file can't expand to exhaust memory.
+const MAX_DECODED_SIZE: usize = 256 * 1024 * 1024;
+/// The most operands on the PostScript operand stack, and the most dictionaries on its dictionary stack.
+const MAX_POSTSCRIPT_STACK_DEPTH: usize = 65_535;
+/// The most array items and string bytes a PostScript program can allocate altogether.
+const MAX_POSTSCRIPT_ALLOCATION: usize = 16 * 1024 * 1024;
+/// The most graphics states which can be saved at once.
+const MAX_SAVED_STATES: usize = 256;
+/// Artwork whose SVG grows longer than this is rejected.
+const MAX_SVG_LENGTH: usize = 256 * 1024 * 1024;
+/// The signature of an EPS file with a binary header, which wraps the PostScript along with a preview image.
+const BINARY_EPS_SIGNATURE: [u8; 4] = [0xC5, 0xD0, 0xD3, 0xC6];
+/// The size of a US Letter page in points, used for EPS files which don't give their bounding box.
+const DEFAULT_PAGE_SIZE: DVec2 = glam::const_dvec2!([612., 792.]);
+
+/// The print formats whose vector artwork can be imported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrintFormat {
+	Pdf,
+	Eps,
+}
+
+impl PrintFormat {
+	/// Identifies a PDF or EPS file by its contents, falling back to its MIME type.
+	pub fn detect(mime: &str, data: &[u8]) -> Option<Self> {
+		// PDF readers accept the header anywhere in the first kilobyte
+		if find(&data[..data.len().min(1024)], b"%PDF-").is_some() {
+			return Some(Self::Pdf);
+		}
+		if data.starts_with(b"%!PS") || data.starts_with(&BINARY_EPS_SIGNATURE) {
+			return Some(Self::Eps);
+		}
+		match mime {
+			"application/pdf" => Some(Self::Pdf),
+			"application/postscript" | "application/eps" | "application/x-eps" | "image/eps" | "image/x-eps" => Some(Self::Eps),
+			_ => None,
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Pdf => "PDF",
+			Self::Eps => "EPS",
+		}
+	}
+
+	/// Converts the vector artwork of the file into an SVG document, sized to its page or bounding box.
+	pub fn to_svg(self, data: &[u8]) -> Result<String, EditorError> {
+		match self {
+			Self::Pdf => pdf_to_svg(data),
+			Self::Eps => eps_to_svg(data),
+		}
+	}
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	haystack.windows(needle.len()).rposition(|window| window == needle)
+}
+
+// Painting
+
+/// The paint and line style of a graphics state, shared by PDF and PostScript.
+#[derive(Clone, Debug)]
+struct GraphicsState {
+	/// The transform from user space to the page, whose origin is at its bottom left corner
+	ctm: DAffine2,
+	/// The colors of fills and strokes in sRGB, or `None` for paints which can't be imported, like patterns
+	fill: Option<[f64; 3]>,
+	stroke: Option<[f64; 3]>,
+	fill_color_space: ColorSpaceKind,
+	stroke_color_space: ColorSpaceKind,
+	fill_opacity: f64,
+	stroke_opacity: f64,
+	line_width: f64,
+	line_cap: u8,
+	line_join: u8,
+	miter_limit: f64,
+	dash_array: Vec<f64>,
+	dash_phase: f64,
+}
+
+impl Default for GraphicsState {
+	fn default() -> Self {
+		Self {
+			ctm: DAffine2::IDENTITY,
+			fill: Some([0.; 3]),
+			stroke: Some([0.; 3]),
+			fill_color_space: ColorSpaceKind::Gray,
+			stroke_color_space: ColorSpaceKind::Gray,
+			fill_opacity: 1.,
+			stroke_opacity: 1.,
+			line_width: 1.,
+			line_cap: 0,
+			line_join: 0,
+			miter_limit: 10.,
+			dash_array: Vec::new(),
+			dash_phase: 0.,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PathSegment {
+	Move(DVec2),
+	Line(DVec2),
+	Cubic(DVec2, DVec2, DVec2),
+	Close,
+}
+
+/// Builds paths and writes them as SVG elements as they're painted.
+struct Painter {
+	/// Flips the page so its origin is at its top left corner, as in SVG, and applies its rotation
+	page_transform: DAffine2,
+	state: GraphicsState,
+	/// The saved graphics states, along with the paths being built when they were saved, which PostScript restores along with them
+	saved_states: Vec<(GraphicsState, CurrentPath)>,
+	path: CurrentPath,
+	svg: String,
+}
+
+/// The path being built, in page space since points are transformed by the CTM as they're added.
+#[derive(Clone, Debug, Default)]
+struct CurrentPath {
+	segments: Vec<PathSegment>,
+	subpath_start: DVec2,
+	current_point: Option<DVec2>,
+}
+
+impl Painter {
+	fn new(page_transform: DAffine2) -> Self {
+		Self {
+			page_transform,
+			state: GraphicsState::default(),
+			saved_states: Vec::new(),
+			path: CurrentPath::default(),
+			svg: String::new(),
+		}
+	}
+
+	fn save(&mut self) -> Result<(), EditorError> {
+		if self.saved_states.len() >= MAX_SAVED_STATES {
+			return Err(EditorError::Misc(format!("The file saves more than {MAX_SAVED_STATES} graphics states at once")));
+		}
+		self.saved_states.push((self.state.clone(), self.path.clone()));
+		Ok(())
+	}
+
+	fn restore(&mut self) {
+		if let Some((state, path)) = self.saved_states.pop() {
+			self.state = state;
+			self.path = path;
+		}
+	}
+
+	fn concat(&mut self, transform: DAffine2) {
+		self.state.ctm = self.state.ctm * transform;
+	}
+
+	fn move_to(&mut self, point: DVec2) {
+		let point = self.state.ctm.transform_point2(point);
+		self.path.segments.push(PathSegment::Move(point));
+		self.path.subpath_start = point;
+		self.path.current_point = Some(point);
+	}
+
+	fn line_to(&mut self, point: DVec2) {
+		if self.path.current_point.is_none() {
+			return self.move_to(point);
+		}
+		let point = self.state.ctm.transform_point2(point);
+		self.path.segments.push(PathSegment::Line(point));
+		self.path.current_point = Some(point);
+	}
+
+	fn curve_to(&mut self, handle_start: DVec2, handle_end: DVec2, point: DVec2) {
+		if self.path.current_point.is_none() {
+			self.move_to(handle_start);
+		}
+		let ctm = self.state.ctm;
+		let point = ctm.transform_point2(point);
+		self.path.segments.push(PathSegment::Cubic(ctm.transform_point2(handle_start), ctm.transform_point2(handle_end), point));
+		self.path.current_point = Some(point);
+	}
+
+	fn close_path(&mut self) {
+		if self.path.current_point.is_some() {
+			self.path.segments.push(PathSegment::Close);
+			self.path.current_point = Some(self.path.subpath_start);
+		}
+	}
+
+	/// The current point in user space.
+	fn current_point(&self) -> Option<DVec2> {
+		let inverse = self.state.ctm.inverse();
+		self.path.current_point.map(|point| inverse.transform_point2(point)).filter(|point| point.is_finite())
+	}
+
+	fn rectangle(&mut self, corner: DVec2, size: DVec2) {
+		self.move_to(corner);
+		self.line_to(corner + DVec2::new(size.x, 0.));
+		self.line_to(corner + size);
+		self.line_to(corner + DVec2::new(0., size.y));
+		self.close_path();
+	}
+
+	/// Adds a circular arc between two angles in degrees, drawing a line to its start from the current point if there is one, as PostScript does.
+	fn arc(&mut self, center: DVec2, radius: f64, start_angle: f64, mut end_angle: f64, clockwise: bool) {
+		if clockwise {
+			while end_angle > start_angle {
+				end_angle -= 360.;
+			}
+		} else {
+			while end_angle < start_angle {
+				end_angle += 360.;
+			}
+		}
+		let point_at = |angle: f64| center + radius * DVec2::new(angle.to_radians().cos(), angle.to_radians().sin());
+
+		let start = point_at(start_angle);
+		if self.path.current_point.is_some() {
+			self.line_to(start);
+		} else {
+			self.move_to(start);
+		}
+
+		// Each quarter of a circle or less is closely approximated by one cubic Bézier curve
+		let segments = ((end_angle - start_angle).abs() / 90.).ceil().max(1.);
+		let sweep = (end_angle - start_angle) / segments;
+		let handle_length = radius * 4. / 3. * (sweep.to_radians() / 4.).tan();
+		for segment in 0..segments as usize {
+			let (from, to) = (start_angle + sweep * segment as f64, start_angle + sweep * (segment + 1) as f64);
+			let tangent = |angle: f64| DVec2::new(-angle.to_radians().sin(), angle.to_radians().cos());
+			let (from_point, to_point) = (point_at(from), point_at(to));
+			self.curve_to(from_point + tangent(from) * handle_length, to_point - tangent(to) * handle_length, to_point);
+		}
+	}
+
+	fn end_path(&mut self) {
+		self.path = CurrentPath::default();
+	}
+
+	/// Writes the current path as an SVG `<path>` element with the paints of the graphics state, then starts a new path.
+	fn paint(&mut self, fill: bool, stroke: bool) {
+		let fill = self.state.fill.filter(|_| fill);
+		let stroke = self.state.stroke.filter(|_| stroke);
+		let has_segments = self.path.segments.iter().any(|segment| !matches!(segment, PathSegment::Move(_)));
+		// Artwork which is too large is reported once the file has been read
+		if (fill.is_none() && stroke.is_none()) || !has_segments || self.svg.len() > MAX_SVG_LENGTH {
+			return self.end_path();
+		}
+
+		let mut data = String::new();
+		let point = |point: DVec2| {
+			let point = self.page_transform.transform_point2(point);
+			format!("{} {}", number(point.x), number(point.y))
+		};
+		for segment in &self.path.segments {
+			match *segment {
+				PathSegment::Move(position) => write!(data, "M{}", point(position)),
+				PathSegment::Line(position) => write!(data, "L{}", point(position)),
+				PathSegment::Cubic(handle_start, handle_end, position) => write!(data, "C{} {} {}", point(handle_start), point(handle_end), point(position)),
+				PathSegment::Close => write!(data, "Z"),
+			}
+			.unwrap();
+		}
+
+		let state = &self.state;
+		write!(self.svg, r#"<path d="{}" fill="{}""#, data, fill.map_or_else(|| "none".to_string(), hex_color)).unwrap();
+		if fill.is_some() && state.fill_opacity < 1. {
+			write!(self.svg, r#" fill-opacity="{}""#, number(state.fill_opacity)).unwrap();
+		}
+		if let Some(stroke) = stroke {
+			// Paths are written in page space, so the line style is scaled by the average scale of the CTM
+			let scale = state.ctm.matrix2.determinant().abs().sqrt();
+			// A width of zero asks for the thinnest line the device can draw, which is a pixel
+			let width = (state.line_width * scale).max(1. / PIXELS_PER_POINT);
+			let cap = ["butt", "round", "square"][state.line_cap.min(2) as usize];
+			let join = ["miter", "round", "bevel"][state.line_join.min(2) as usize];
+			write!(
+				self.svg,
+				r#" stroke="{}" stroke-width="{}" stroke-linecap="{}" stroke-linejoin="{}" stroke-miterlimit="{}""#,
+				hex_color(stroke),
+				number(width),
+				cap,
+				join,
+				number(state.miter_limit.max(1.))
+			)
+			.unwrap();
+			if state.stroke_opacity < 1. {
+				write!(self.svg, r#" stroke-opacity="{}""#, number(state.stroke_opacity)).unwrap();
+			}
+			if state.dash_array.iter().any(|&dash| dash > 0.) && state.dash_array.iter().all(|&dash| dash >= 0.) {
+				let dashes = state.dash_array.iter().map(|&dash| number(dash * scale)).collect::<Vec<_>>().join(" ");
+				write!(self.svg, r#" stroke-dasharray="{}" stroke-dashoffset="{}""#, dashes, number(state.dash_phase * scale)).unwrap();
+			}
+		}
+		self.svg.push_str("/>");
+
+		self.end_path();
+	}
+
+	/// Wraps the painted paths in an SVG document the size of the page, which is given in points.
+	fn into_svg(self, page_size: DVec2) -> Result<String, EditorError> {
+		if self.svg.is_empty() {
+			return Err(EditorError::Misc("The file has no vector artwork which can be imported".into()));
+		}
+		if self.svg.len() > MAX_SVG_LENGTH {
+			return Err(EditorError::Misc("The file has too much vector artwork to be imported".into()));
+		}
+		let size = page_size * PIXELS_PER_POINT;
+		Ok(format!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">{}</svg>"#,
+			number(size.x),
+			number(size.y),
+			number(page_size.x),
+			number(page_size.y),
+			self.svg
+		))
+	}
+}
+
+/// Formats a number with at most three decimal places, which is well below the precision of a point.
+fn number(value: f64) -> String {
+	let rounded = (value * 1000.).round() / 1000.;
+	if rounded == 0. {
+		"0".to_string()
+	} else {
+		rounded.to_string()
+	}
+}
+
+fn hex_color([red, green, blue]: [f64; 3]) -> String {
+	let channel = |value: f64| (value.clamp(0., 1.) * 255.).round() as u8;
+	format!("#{:02x}{:02x}{:02x}", channel(red), channel(green), channel(blue))
+}
+
+/// The families of color spaces, which determine how the components of a color are converted to sRGB.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorSpaceKind {
+	Gray,
+	Rgb,
+	Cmyk,
+	/// Spot colors, whose components are amounts of ink which are imported as shades of gray
+	Separation,
+	Pattern,
+	/// Color spaces which are guessed from their number of components
+	Other,
+}
+
+impl ColorSpaceKind {
+	fn color(&self, components: &[f64]) -> Option<[f64; 3]> {
+		let kind = match self {
+			Self::Other => match components.len() {
+				1 => Self::Gray,
+				3 => Self::Rgb,
+				4 => Self::Cmyk,
+				_ => return None,
+			},
+			kind => *kind,
+		};
+		match (kind, components) {
+			(Self::Gray, &[gray]) => Some([gray; 3]),
+			(Self::Rgb, &[red, green, blue]) => Some([red, green, blue]),
+			(Self::Cmyk, &[cyan, magenta, yellow, black]) => Some([(1. - cyan) * (1. - black), (1. - magenta) * (1. - black), (1. - yellow) * (1. - black)]),
+			(Self::Separation, inks) if !inks.is_empty() => Some([1. - inks.iter().sum::<f64>() / inks.len() as f64; 3]),
+			_ => None,
+		}
+	}
+}
+
+/// Converts a color in the HSB model used by PostScript to RGB.
+fn hsb_to_rgb(hue: f64, saturation: f64, brightness: f64) -> [f64; 3] {
+	let hue = hue.rem_euclid(1.) * 6.;
+	let channel = |offset: f64| {
+		let k = (offset + hue) % 6.;
+		brightness - brightness * saturation * k.min(4. - k).clamp(0., 1.)
+	};
+	[channel(5.), channel(3.), channel(1.)]
+}
+
+// Tokenizing
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+	Number(f64),
+	String(Vec<u8>),
+	/// A name written with a leading slash
+	Name(String),
+	/// A bare word, which is an operator in content streams and an executable name in PostScript
+	Keyword(String),
+	ArrayStart,
+	ArrayEnd,
+	DictionaryStart,
+	DictionaryEnd,
+	ProcedureStart,
+	ProcedureEnd,
+}
+
+/// Splits PDF objects and content streams, and PostScript programs, which share their syntax, into tokens.
+struct Lexer<'a> {
+	data: &'a [u8],
+	position: usize,
+}
+
+fn is_whitespace(byte: u8) -> bool {
+	matches!(byte, b' ' | b'\t' | b'\r' | b'\n' | b'\x0C' | b'\0')
+}
+
+fn is_delimiter(byte: u8) -> bool {
+	is_whitespace(byte) || b"()<>[]{}/%".contains(&byte)
+}
+
+impl<'a> Lexer<'a> {
+	fn new(data: &'a [u8], position: usize) -> Self {
+		Self { data, position }
+	}
+
+	fn peek(&self) -> Option<u8> {
+		self.data.get(self.position).copied()
+	}
+
+	fn skip_whitespace(&mut self) {
+		while let Some(byte) = self.peek() {
+			if is_whitespace(byte) {
+				self.position += 1;
+			} else if byte == b'%' {
+				while !matches!(self.peek(), None | Some(b'\r' | b'\n')) {
+					self.position += 1;
+				}
+			} else {
+				break;
+			}
+		}
+	}
+
+	fn next_token(&mut self) -> Result<Option<Token>, EditorError> {
+		self.skip_whitespace();
+		let byte = match self.peek() {
+			Some(byte) => byte,
+			None => return Ok(None),
+		};
+		self.position += 1;
+
+		let token = match byte {
+			b'[' => Token::ArrayStart,
+			b']' => Token::ArrayEnd,
+			b'{' => Token::ProcedureStart,
+			b'}' => Token::ProcedureEnd,
+			b'<' if self.peek() == Some(b'<') => {
+				self.position += 1;
+				Token::DictionaryStart
+			}
+			b'>' if self.peek() == Some(b'>') => {
+				self.position += 1;
+				Token::DictionaryEnd
+			}
+			b'<' => Token::String(self.hex_string()),
+			b'(' => Token::String(self.literal_string()?),
+			b'/' => {
+				let name = self.word();
+				Token::Name(decode_name(name))
+			}
+			b'>' | b')' => return Err(EditorError::Misc(format!("Unexpected '{}' in the file", byte as char))),
+			_ => {
+				self.position -= 1;
+				let word = self.word();
+				match parse_number(word) {
+					Some(number) => Token::Number(number),
+					None => Token::Keyword(String::from_utf8_lossy(word).into_owned()),
+				}
+			}
+		};
+		Ok(Some(token))
+	}
+
+	fn word(&mut self) -> &'a [u8] {
+		let start = self.position;
+		while self.peek().is_some_and(|byte| !is_delimiter(byte)) {
+			self.position += 1;
+		}
+		&self.data[start..self.position]
+	}
+
+	fn hex_string(&mut self) -> Vec<u8> {
+		let mut digits = Vec::new();
+		while let Some(byte) = self.peek() {
+			self.position += 1;
+			match byte {
+				b'>' => break,
+				_ => digits.extend((byte as char).to_digit(16).map(|digit| digit as u8)),
+			}
+		}
+		// An odd final digit is followed by an implied zero
+		digits.chunks(2).map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0)).collect()
+	}
+
+	fn literal_string(&mut self) -> Result<Vec<u8>, EditorError> {
+		let mut string = Vec::new();
+		let mut depth = 0;
+		loop {
+			let byte = self.peek().ok_or_else(|| EditorError::Misc("A string in the file is never closed".into()))?;
+			self.position += 1;
+			match byte {
+				b'(' => {
+					depth += 1;
+					string.push(byte);
+				}
+				b')' if depth == 0 => return Ok(string),
+				b')' => {
+					depth -= 1;
+					string.push(byte);
+				}
+				b'\\' => {
+					let escaped = match self.peek() {
+						Some(escaped) => escaped,
+						None => continue,
+					};
+					self.position += 1;
+					match escaped {
+						b'n' => string.push(b'\n'),
+						b'r' => string.push(b'\r'),
+						b't' => string.push(b'\t'),
+						b'b' => string.push(b'\x08'),
+						b'f' => string.push(b'\x0C'),
+						// A backslash at the end of a line continues the string on the next line
+						b'\r' => {
+							if self.peek() == Some(b'\n') {
+								self.position += 1;
+							}
+						}
+						b'\n' => {}
+						b'0'..=b'7' => {
+							let mut value = (escaped - b'0') as u32;
+							for _ in 0..2 {
+								match self.peek() {
+									Some(digit @ b'0'..=b'7') => {
+										value = value * 8 + (digit - b'0') as u32;
+										self.position += 1;
+									}
+									_ => break,
+								}
+							}
+							string.push(value as u8);
+						}
+						_ => string.push(escaped),
+					}
+				}
+				_ => string.push(byte),
+			}
+		}
+	}
+}
+
+/// Decodes the `#xx` hexadecimal escapes of a PDF name.
+fn decode_name(name: &[u8]) -> String {
+	let mut decoded = Vec::with_capacity(name.len());
+	let mut index = 0;
+	while index < name.len() {
+		let escaped = name.get(index + 1..index + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+		match (name[index], escaped) {
+			(b'#', Some(byte)) => {
+				decoded.push(byte);
+				index += 3;
+			}
+			(byte, _) => {
+				decoded.push(byte);
+				index += 1;
+			}
+		}
+	}
+	String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Parses a number, including the `base#digits` form of PostScript.
+fn parse_number(word: &[u8]) -> Option<f64> {
+	let word = std::str::from_utf8(word).ok()?;
+	if !word.starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-' || c == '.') {
+		return None;
+	}
+	if let Some((radix, digits)) = word.split_once('#') {
+		let radix = radix.parse().ok().filter(|radix| (2..=36).contains(radix))?;
+		return u32::from_str_radix(digits, radix).ok().map(f64::from);
+	}
+	// Some PDF writers emit malformed numbers like `--5` or `0.5.1`, which readers parse as far as they can
+	word.parse().ok().or_else(|| {
+		let trimmed = word.trim_start_matches('-');
+		let sign = if trimmed.len() < word.len() { -1. } else { 1. };
+		let end = trimmed.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(trimmed.len());
+		let number = &trimmed[..end];
+		let number = match number.match_indices('.').nth(1) {
+			Some((second_point, _)) => &number[..second_point],
+			None => number,
+		};
+		number.parse::<f64>().ok().map(|number| sign * number)
+	})
+}
+
+// PDF
+
+type PdfDictionary = HashMap<String, PdfObject>;
+
+#[derive(Clone, Debug, PartialEq)]
+enum PdfObject {
+	Null,
+	Bool(bool),
+	Number(f64),
+	String(Vec<u8>),
+	Name(String),
+	Array(Vec<PdfObject>),
+	Dictionary(PdfDictionary),
+	/// A stream's dictionary and its data before its filters are decoded
+	Stream(PdfDictionary, Vec<u8>),
+	/// A reference to an indirect object by its object number
+	Reference(u32),
+	/// An operator in a content stream
+	Operator(String),
+}
+
+impl PdfObject {
+	fn as_number(&self) -> Option<f64> {
+		match self {
+			Self::Number(number) => Some(*number),
+			_ => None,
+		}
+	}
+
+	fn as_name(&self) -> Option<&str> {
+		match self {
+			Self::Name(name) => Some(name),
+			_ => None,
+		}
+	}
+
+	fn as_dictionary(&self) -> Option<&PdfDictionary> {
+		match self {
+			Self::Dictionary(dictionary) | Self::Stream(dictionary, _) => Some(dictionary),
+			_ => None,
+		}
+	}
+}
+
+/// Parses PDF objects from tokens, for both the body of the file and content streams.
+struct PdfParser<'a> {
+	lexer: Lexer<'a>,
+	/// Whether `N G R` is read as a reference, which is only valid outside content streams
+	in_file_body: bool,
+}
+
+impl<'a> PdfParser<'a> {
+	fn new(data: &'a [u8], position: usize, in_file_body: bool) -> Self {
+		Self {
+			lexer: Lexer::new(data, position),
+			in_file_body,
+		}
+	}
+
+	fn next_object(&mut self) -> Result<Option<PdfObject>, EditorError> {
+		match self.lexer.next_token()? {
+			Some(token) => self.object_from(token, 0).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	fn expect_object(&mut self, depth: usize) -> Result<PdfObject, EditorError> {
+		let token = self.lexer.next_token()?.ok_or_else(|| EditorError::Misc("The file ends in the middle of an object".into()))?;
+		self.object_from(token, depth)
+	}
+
+	fn object_from(&mut self, token: Token, depth: usize) -> Result<PdfObject, EditorError> {
+		if depth > MAX_NESTING_DEPTH {
+			return Err(EditorError::Misc("The file nests arrays or dictionaries too deeply".into()));
+		}
+
+		let object = match token {
+			Token::Number(number) => match self.in_file_body && number >= 0. && number.fract() == 0. {
+				true => self.reference_after(number).unwrap_or(PdfObject::Number(number)),
+				false => PdfObject::Number(number),
+			},
+			Token::String(string) => PdfObject::String(string),
+			Token::Name(name) => PdfObject::Name(name),
+			Token::Keyword(keyword) => match keyword.as_str() {
+				"true" => PdfObject::Bool(true),
+				"false" => PdfObject::Bool(false),
+				"null" => PdfObject::Null,
+				_ => PdfObject::Operator(keyword),
+			},
+			Token::ArrayStart => {
+				let mut array = Vec::new();
+				loop {
+					match self.lexer.next_token()? {
+						Some(Token::ArrayEnd) => break,
+						Some(token) => array.push(self.object_from(token, depth + 1)?),
+						None => return Err(EditorError::Misc("An array in the file is never closed".into())),
+					}
+				}
+				PdfObject::Array(array)
+			}
+			Token::DictionaryStart => {
+				let mut dictionary = PdfDictionary::new();
+				loop {
+					match self.lexer.next_token()? {
+						Some(Token::DictionaryEnd) => break,
+						Some(Token::Name(key)) => {
+							let value = self.expect_object(depth + 1)?;
+							dictionary.insert(key, value);
+						}
+						// Some writers leave stray values in dictionaries, which readers skip
+						Some(_) => {}
+						None => return Err(EditorError::Misc("A dictionary in the file is never closed".into())),
+					}
+				}
+				match self.in_file_body {
+					true => self.stream_after(dictionary),
+					false => PdfObject::Dictionary(dictionary),
+				}
+			}
+			Token::ArrayEnd | Token::DictionaryEnd | Token::ProcedureStart | Token::ProcedureEnd => PdfObject::Null,
+		};
+		Ok(object)
+	}
+
+	/// Reads the rest of an `N G R` reference if the number is followed by one.
+	fn reference_after(&mut self, number: f64) -> Option<PdfObject> {
+		let start = self.lexer.position;
+		let is_reference = matches!(self.lexer.next_token(), Ok(Some(Token::Number(generation))) if generation >= 0. && generation.fract() == 0.)
+			&& matches!(self.lexer.next_token(), Ok(Some(Token::Keyword(keyword))) if keyword == "R");
+		if is_reference {
+			Some(PdfObject::Reference(number as u32))
+		} else {
+			self.lexer.position = start;
+			None
+		}
+	}
+
+	/// Reads the data of a stream if the dictionary is followed by one.
+	fn stream_after(&mut self, dictionary: PdfDictionary) -> PdfObject {
+		let data = self.lexer.data;
+		let mut start = self.lexer.position;
+		while data.get(start).is_some_and(|&byte| is_whitespace(byte)) {
+			start += 1;
+		}
+		if !data[start..].starts_with(b"stream") {
+			return PdfObject::Dictionary(dictionary);
+		}
+		start += b"stream".len();
+		if data[start..].starts_with(b"\r\n") {
+			start += 2;
+		} else if data.get(start).is_some_and(|&byte| byte == b'\n' || byte == b'\r') {
+			start += 1;
+		}
+
+		// The length is trusted if the stream ends where it says, since it may be a reference which can't be resolved yet, or simply wrong
+		let stated_end = dictionary
+			.get("Length")
+			.and_then(PdfObject::as_number)
+			.and_then(|length| start.checked_add(length as usize))
+			.filter(|&end| end >= start && end <= data.len())
+			.filter(|&end| {
+				let rest = &data[end..];
+				let whitespace = rest.iter().take_while(|&&byte| is_whitespace(byte)).count();
+				rest[whitespace..].starts_with(b"endstream")
+			});
+		let end = stated_end.unwrap_or_else(|| {
+			let end = find(&data[start..], b"endstream").map_or(data.len(), |offset| start + offset);
+			let line_break = if data[..end].ends_with(b"\r\n") {
+				2
+			} else {
+				data[..end].ends_with(b"\n") as usize + data[..end].ends_with(b"\r") as usize
+			};
+			(end - line_break).max(start)
+		});
+
+		self.lexer.position = find(&data[end..], b"endstream").map_or(data.len(), |offset| end + offset + b"endstream".len());
+		PdfObject::Stream(dictionary, data[start..end].to_vec())
+	}
+}
+
+/// The objects of a PDF file, found by scanning the file for them rather than reading its cross-reference table, which is often damaged.
+struct PdfDocument {
+	objects: HashMap<u32, PdfObject>,
+	/// The catalog, which is the root of the document's object tree
+	root: Option<u32>,
+}
+
+/// The page attributes which are inherited from the page tree.
+#[derive(Clone, Default)]
+struct InheritedPageAttributes {
+	resources: Option<PdfDictionary>,
+	media_box: Option<PdfObject>,
+	crop_box: Option<PdfObject>,
+	rotate: Option<f64>,
+}
+
+impl PdfDocument {
+	fn parse(data: &[u8]) -> Result<Self, EditorError> {
+		let mut objects = HashMap::new();
+
+		// Objects defined later, by incremental updates, replace those defined earlier
+		let mut position = 0;
+		while let Some(offset) = find(&data[position..], b"obj") {
+			let keyword = position + offset;
+			position = keyword + 3;
+			if data.get(keyword + 3).is_some_and(|&byte| !is_delimiter(byte)) {
+				continue;
+			}
+			let object_number = match object_number_before(data, keyword) {
+				Some(object_number) => object_number,
+				None => continue,
+			};
+
+			let mut parser = PdfParser::new(data, keyword + 3, true);
+			if let Ok(Some(object)) = parser.next_object() {
+				objects.insert(object_number, object);
+				position = position.max(parser.lexer.position);
+			}
+		}
+
+		let mut document = Self { objects, root: None };
+		document.expand_object_streams();
+		document.root = document.find_root(data);
+		Ok(document)
+	}
+
+	/// Adds the objects compressed into object streams, unless they're also defined directly.
+	/// The object streams share the limit on how much a single stream decodes to, so many of them can't add up to more.
+	fn expand_object_streams(&mut self) {
+		let object_streams = self
+			.objects
+			.values()
+			.filter(|object| matches!(object, PdfObject::Stream(dictionary, _) if dictionary.get("Type").and_then(PdfObject::as_name) == Some("ObjStm")))
+			.cloned()
+			.collect::<Vec<_>>();
+
+		let mut decoded = 0;
+		for object_stream in object_streams {
+			let (dictionary, data) = match &object_stream {
+				PdfObject::Stream(dictionary, data) => (dictionary, data),
+				_ => continue,
+			};
+			let data = match self.decode_stream(dictionary, data) {
+				Ok(data) => data,
+				Err(_) => continue,
+			};
+			decoded += data.len();
+			if decoded > MAX_DECODED_SIZE {
+				break;
+			}
+			let count = self.get(dictionary, "N").and_then(PdfObject::as_number).unwrap_or(0.) as usize;
+			let first = self.get(dictionary, "First").and_then(PdfObject::as_number).unwrap_or(0.) as usize;
+
+			let mut header = PdfParser::new(&data, 0, false);
+			let mut numbers = Vec::new();
+			while numbers.len() < count.saturating_mul(2) {
+				match header.next_object() {
+					Ok(Some(PdfObject::Number(number))) => numbers.push(number as usize),
+					_ => break,
+				}
+			}
+			for pair in numbers.chunks_exact(2) {
+				let mut parser = PdfParser::new(&data, first.saturating_add(pair[1]), true);
+				if let Ok(Some(object)) = parser.next_object() {
+					self.objects.entry(pair[0] as u32).or_insert(object);
+				}
+			}
+		}
+	}
+
+	/// Finds the catalog from the last trailer or cross-reference stream, or failing that, by its type.
+	fn find_root(&self, data: &[u8]) -> Option<u32> {
+		let trailer_root = rfind(data, b"trailer").and_then(|trailer| {
+			let mut parser = PdfParser::new(data, trailer + b"trailer".len(), true);
+			match parser.next_object() {
+				Ok(Some(PdfObject::Dictionary(dictionary))) => match dictionary.get("Root") {
+					Some(PdfObject::Reference(root)) => Some(*root),
+					_ => None,
+				},
+				_ => None,
+			}
+		});
+		let type_of = |object: &PdfObject| object.as_dictionary().and_then(|dictionary| dictionary.get("Type")).and_then(PdfObject::as_name).map(str::to_string);
+
+		trailer_root
+			.filter(|root| self.objects.contains_key(root))
+			.or_else(|| {
+				let mut cross_reference_streams = self.objects.iter().filter(|(_, object)| type_of(object).as_deref() == Some("XRef")).collect::<Vec<_>>();
+				cross_reference_streams.sort_by_key(|(number, _)| std::cmp::Reverse(**number));
+				cross_reference_streams.into_iter().find_map(|(_, object)| match object.as_dictionary()?.get("Root") {
+					Some(PdfObject::Reference(root)) => Some(*root),
+					_ => None,
+				})
+			})
+			.or_else(|| self.objects.iter().filter(|(_, object)| type_of(object).as_deref() == Some("Catalog")).map(|(number, _)| *number).min())
+	}
+
+	/// Follows references until reaching a direct object. Missing objects are null.
+	fn resolve<'b>(&'b self, mut object: &'b PdfObject) -> &'b PdfObject {
+		for _ in 0..MAX_NESTING_DEPTH {
+			match object {
+				PdfObject::Reference(number) => object = self.objects.get(number).unwrap_or(&PdfObject::Null),
+				_ => return object,
+			}
+		}
+		&PdfObject::Null
+	}
+
+	/// Looks up a key of a dictionary, following references.
+	fn get<'b>(&'b self, dictionary: &'b PdfDictionary, key: &str) -> Option<&'b PdfObject> {
+		dictionary.get(key).map(|object| self.resolve(object)).filter(|object| **object != PdfObject::Null)
+	}
+
+	fn numbers(&self, object: &PdfObject) -> Vec<f64> {
+		match self.resolve(object) {
+			PdfObject::Array(array) => array.iter().filter_map(|item| self.resolve(item).as_number()).collect(),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Decodes the data of a stream through each of its filters in turn.
+	fn decode_stream(&self, dictionary: &PdfDictionary, data: &[u8]) -> Result<Vec<u8>, EditorError> {
+		let as_list = |object: Option<&PdfObject>| match object {
+			Some(PdfObject::Array(array)) => array.iter().map(|item| self.resolve(item).clone()).collect(),
+			Some(object) => vec![object.clone()],
+			None => Vec::new(),
+		};
+		let filters = as_list(self.get(dictionary, "Filter"));
+		let parameters = as_list(self.get(dictionary, "DecodeParms"));
+
+		let mut data = data.to_vec();
+		for (index, filter) in filters.iter().enumerate() {
+			let parameters = parameters.get(index).and_then(PdfObject::as_dictionary);
+			let parameter = |key: &str, default: f64| parameters.and_then(|parameters| self.get(parameters, key)).and_then(PdfObject::as_number).unwrap_or(default);
+
+			data = match filter.as_name().unwrap_or_default() {
+				"FlateDecode" | "Fl" => {
+					let inflated = inflate(&data)?;
+					undo_predictor(
+						inflated,
+						parameter("Predictor", 1.),
+						parameter("Colors", 1.),
+						parameter("BitsPerComponent", 8.),
+						parameter("Columns", 1.),
+					)?
+				}
+				"LZWDecode" | "LZW" => {
+					let decoded = lzw_decode(&data, parameter("EarlyChange", 1.) != 0.)?;
+					undo_predictor(
+						decoded,
+						parameter("Predictor", 1.),
+						parameter("Colors", 1.),
+						parameter("BitsPerComponent", 8.),
+						parameter("Columns", 1.),
+					)?
+				}
+				"ASCIIHexDecode" | "AHx" => Lexer::new(&data, 0).hex_string(),
+				"ASCII85Decode" | "A85" => ascii85_decode(&data)?,
+				"RunLengthDecode" | "RL" => run_length_decode(&data)?,
+				filter => return Err(EditorError::Misc(format!("The file uses the {} filter, which isn't supported", filter))),
+			};
+		}
+		Ok(data)
+	}
+
+	fn first_page(&self) -> Result<(PdfDictionary, InheritedPageAttributes), EditorError> {
+		let catalog = self.root.and_then(|root| self.objects.get(&root)).and_then(PdfObject::as_dictionary);
+		let pages = catalog.and_then(|catalog| self.get(catalog, "Pages")).and_then(PdfObject::as_dictionary);
+
+		pages
+			.and_then(|pages| self.find_first_page(pages, InheritedPageAttributes::default(), 0, &mut HashSet::new()))
+			.or_else(|| {
+				// Without a page tree, fall back to the first object which looks like a page
+				let mut page_numbers = self
+					.objects
+					.iter()
+					.filter(|(_, object)| object.as_dictionary().and_then(|dictionary| dictionary.get("Type")).and_then(PdfObject::as_name) == Some("Page"))
+					.map(|(number, _)| *number)
+					.collect::<Vec<_>>();
+				page_numbers.sort_unstable();
+				let page = self.objects.get(page_numbers.first()?)?.as_dictionary()?;
+				Some((page.clone(), InheritedPageAttributes::default()))
+			})
+			.ok_or_else(|| EditorError::Misc("The PDF file has no pages".into()))
+	}
+
+	/// Searches the page tree depth first, going through each referenced node only once so trees which list their nodes many times or contain themselves are searched quickly.
+	fn find_first_page(&self, node: &PdfDictionary, inherited: InheritedPageAttributes, depth: usize, visited: &mut HashSet<u32>) -> Option<(PdfDictionary, InheritedPageAttributes)> {
+		if depth > MAX_NESTING_DEPTH {
+			return None;
+		}
+		let inherited = InheritedPageAttributes {
+			resources: self.get(node, "Resources").and_then(PdfObject::as_dictionary).cloned().or(inherited.resources),
+			media_box: self.get(node, "MediaBox").cloned().or(inherited.media_box),
+			crop_box: self.get(node, "CropBox").cloned().or(inherited.crop_box),
+			rotate: self.get(node, "Rotate").and_then(PdfObject::as_number).or(inherited.rotate),
+		};
+
+		match self.get(node, "Kids") {
+			Some(PdfObject::Array(kids)) if self.get(node, "Type").and_then(PdfObject::as_name) != Some("Page") => {
+				for kid in kids {
+					if let PdfObject::Reference(number) = kid {
+						if !visited.insert(*number) {
+							continue;
+						}
+					}
+					if let Some(page) = self.resolve(kid).as_dictionary().and_then(|kid| self.find_first_page(kid, inherited.clone(), depth + 1, visited)) {
+						return Some(page);
+					}
+				}
+				None
+			}
+			_ => Some((node.clone(), inherited)),
+		}
+	}
+
+	/// The decoded content of a page, which may be split across an array of streams.
+	fn page_contents(&self, page: &PdfDictionary) -> Result<Vec<u8>, EditorError> {
+		let streams = match self.get(page, "Contents") {
+			Some(PdfObject::Array(array)) => array.iter().map(|item| self.resolve(item)).collect(),
+			Some(stream) => vec![stream],
+			None => Vec::new(),
+		};
+
+		let mut contents = Vec::new();
+		for stream in streams {
+			if let PdfObject::Stream(dictionary, data) = stream {
+				contents.extend(self.decode_stream(dictionary, data)?);
+				// Operators may not be split across streams, so they're separated by whitespace
+				contents.push(b'\n');
+			}
+		}
+		Ok(contents)
+	}
+}
+
+/// Reads the `N G` before an `obj` keyword, returning the object number.
+fn object_number_before(data: &[u8], keyword: usize) -> Option<u32> {
+	let mut position = keyword;
+	let mut numbers = [0; 2];
+	for number in numbers.iter_mut().rev() {
+		let whitespace_end = position;
+		while position > 0 && is_whitespace(data[position - 1]) {
+			position -= 1;
+		}
+		let digits_end = position;
+		while position > 0 && data[position - 1].is_ascii_digit() {
+			position -= 1;
+		}
+		if digits_end == whitespace_end || digits_end == position {
+			return None;
+		}
+		*number = std::str::from_utf8(&data[position..digits_end]).ok()?.parse().ok()?;
+	}
+	if position > 0 && !is_delimiter(data[position - 1]) {
+		return None;
+	}
+	Some(numbers[0])
+}
+
+fn pdf_to_svg(data: &[u8]) -> Result<String, EditorError> {
+	if find(data, b"/Encrypt").is_some() {
+		return Err(EditorError::Misc("Encrypted PDF files can't be imported".into()));
+	}
+
+	let document = PdfDocument::parse(data)?;
+	let (page, inherited) = document.first_page()?;
+
+	let page_box = [&inherited.crop_box, &inherited.media_box]
+		.into_iter()
+		.flatten()
+		.map(|page_box| document.numbers(page_box))
+		.find(|page_box| page_box.len() == 4)
+		.unwrap_or_else(|| vec![0., 0., DEFAULT_PAGE_SIZE.x, DEFAULT_PAGE_SIZE.y]);
+	let (left, bottom, right, top) = (page_box[0].min(page_box[2]), page_box[1].min(page_box[3]), page_box[0].max(page_box[2]), page_box[1].max(page_box[3]));
+
+	// Pages are rotated clockwise when displayed by a multiple of 90 degrees
+	let rotation = (inherited.rotate.unwrap_or(0.) as i64).rem_euclid(360) / 90;
+	let page_transform = match rotation {
+		1 => [0., 1., 1., 0., -bottom, -left],
+		2 => [-1., 0., 0., 1., right, -bottom],
+		3 => [0., -1., -1., 0., top, right],
+		_ => [1., 0., 0., -1., -left, top],
+	};
+	let page_size = match rotation {
+		1 | 3 => DVec2::new(top - bottom, right - left),
+		_ => DVec2::new(right - left, top - bottom),
+	};
+
+	let resources = inherited.resources.unwrap_or_default();
+	let contents = document.page_contents(&page)?;
+
+	let mut interpreter = PdfContentInterpreter {
+		document: &document,
+		painter: Painter::new(DAffine2::from_cols_array(&page_transform)),
+		operators: 0,
+		decoded_forms: 0,
+	};
+	interpreter.run(&contents, &resources, 0)?;
+	interpreter.painter.into_svg(page_size)
+}
+
+/// Paints the paths of content streams.
+struct PdfContentInterpreter<'a> {
+	document: &'a PdfDocument,
+	painter: Painter,
+	operators: usize,
+	/// The decoded size of the form XObjects drawn so far
+	decoded_forms: usize,
+}
+
+impl<'a> PdfContentInterpreter<'a> {
+	fn run(&mut self, contents: &[u8], resources: &PdfDictionary, depth: usize) -> Result<(), EditorError> {
+		if depth > MAX_NESTING_DEPTH {
+			return Err(EditorError::Misc("The file nests form XObjects too deeply".into()));
+		}
+
+		let mut parser = PdfParser::new(contents, 0, false);
+		let mut operands = Vec::new();
+		while let Some(object) = parser.next_object()? {
+			match object {
+				PdfObject::Operator(operator) if operator == "BI" => {
+					skip_inline_image(&mut parser.lexer);
+					operands.clear();
+				}
+				PdfObject::Operator(operator) => {
+					self.operators += 1;
+					if self.operators > MAX_PDF_OPERATORS {
+						return Err(EditorError::Misc("The file draws too much to be imported".into()));
+					}
+					self.operator(&operator, &operands, resources, depth)?;
+					operands.clear();
+				}
+				operand => operands.push(operand),
+			}
+		}
+		Ok(())
+	}
+
+	/// Looks up a named resource, like a form XObject or a graphics state parameter dictionary.
+	fn resource(&self, resources: &PdfDictionary, category: &str, name: Option<&PdfObject>) -> Option<PdfObject> {
+		let category = self.document.get(resources, category)?.as_dictionary()?;
+		self.document.get(category, name?.as_name()?).cloned()
+	}
+
+	fn operator(&mut self, operator: &str, operands: &[PdfObject], resources: &PdfDictionary, depth: usize) -> Result<(), EditorError> {
+		let numbers = operands.iter().filter_map(PdfObject::as_number).collect::<Vec<_>>();
+		let painter = &mut self.painter;
+		let point = |x: f64, y: f64| DVec2::new(x, y);
+
+		match (operator, numbers.as_slice()) {
+			// Graphics state
+			("q", _) => painter.save()?,
+			("Q", _) => painter.restore(),
+			("cm", &[a, b, c, d, e, f]) => painter.concat(DAffine2::from_cols_array(&[a, b, c, d, e, f])),
+			("w", &[width]) => painter.state.line_width = width,
+			("J", &[cap]) => painter.state.line_cap = cap as u8,
+			("j", &[join]) => painter.state.line_join = join as u8,
+			("M", &[miter_limit]) => painter.state.miter_limit = miter_limit,
+			("d", _) => {
+				if let [dash_array, phase] = operands {
+					painter.state.dash_array = self.document.numbers(dash_array);
+					painter.state.dash_phase = phase.as_number().unwrap_or(0.);
+				}
+			}
+			("gs", _) => {
+				let parameters = self.resource(resources, "ExtGState", operands.first());
+				if let Some(parameters) = parameters.as_ref().and_then(PdfObject::as_dictionary) {
+					let parameter = |key: &str| self.document.get(parameters, key).and_then(PdfObject::as_number);
+					let state = &mut self.painter.state;
+					state.stroke_opacity = parameter("CA").unwrap_or(state.stroke_opacity);
+					state.fill_opacity = parameter("ca").unwrap_or(state.fill_opacity);
+					state.line_width = parameter("LW").unwrap_or(state.line_width);
+					state.line_cap = parameter("LC").map_or(state.line_cap, |cap| cap as u8);
+					state.line_join = parameter("LJ").map_or(state.line_join, |join| join as u8);
+					state.miter_limit = parameter("ML").unwrap_or(state.miter_limit);
+				}
+			}
+
+			// Path construction
+			("m", &[x, y]) => painter.move_to(point(x, y)),
+			("l", &[x, y]) => painter.line_to(point(x, y)),
+			("c", &[x1, y1, x2, y2, x3, y3]) => painter.curve_to(point(x1, y1), point(x2, y2), point(x3, y3)),
+			("v", &[x2, y2, x3, y3]) => {
+				let start = painter.current_point().unwrap_or_else(|| point(x2, y2));
+				painter.curve_to(start, point(x2, y2), point(x3, y3));
+			}
+			("y", &[x1, y1, x3, y3]) => painter.curve_to(point(x1, y1), point(x3, y3), point(x3, y3)),
+			("h", _) => painter.close_path(),
+			("re", &[x, y, width, height]) => painter.rectangle(point(x, y), point(width, height)),
+
+			// Path painting, where clipping paths are ignored and even-odd fills are imported as nonzero fills
+			("f" | "F" | "f*", _) => painter.paint(true, false),
+			("S", _) => painter.paint(false, true),
+			("s", _) => {
+				painter.close_path();
+				painter.paint(false, true);
+			}
+			("B" | "B*", _) => painter.paint(true, true),
+			("b" | "b*", _) => {
+				painter.close_path();
+				painter.paint(true, true);
+			}
+			("n", _) => painter.end_path(),
+
+			// Color
+			("g", &[gray]) => (painter.state.fill_color_space, painter.state.fill) = (ColorSpaceKind::Gray, Some([gray; 3])),
+			("G", &[gray]) => (painter.state.stroke_color_space, painter.state.stroke) = (ColorSpaceKind::Gray, Some([gray; 3])),
+			("rg", &[red, green, blue]) => (painter.state.fill_color_space, painter.state.fill) = (ColorSpaceKind::Rgb, Some([red, green, blue])),
+			("RG", &[red, green, blue]) => (painter.state.stroke_color_space, painter.state.stroke) = (ColorSpaceKind::Rgb, Some([red, green, blue])),
+			("k", components @ &[_, _, _, _]) => (painter.state.fill_color_space, painter.state.fill) = (ColorSpaceKind::Cmyk, ColorSpaceKind::Cmyk.color(components)),
+			("K", components @ &[_, _, _, _]) => (painter.state.stroke_color_space, painter.state.stroke) = (ColorSpaceKind::Cmyk, ColorSpaceKind::Cmyk.color(components)),
+			("cs" | "CS", _) => {
+				let kind = self.color_space(resources, operands.first());
+				// Setting a color space resets the color to its initial value, which is black except for patterns
+				let color = (kind != ColorSpaceKind::Pattern).then_some([0.; 3]);
+				let state = &mut self.painter.state;
+				match operator {
+					"cs" => (state.fill_color_space, state.fill) = (kind, color),
+					_ => (state.stroke_color_space, state.stroke) = (kind, color),
+				}
+			}
+			("sc" | "scn", components) => {
+				// A trailing name selects a pattern, which can't be imported
+				let pattern = matches!(operands.last(), Some(PdfObject::Name(_)));
+				painter.state.fill = painter.state.fill_color_space.color(components).filter(|_| !pattern);
+			}
+			("SC" | "SCN", components) => {
+				let pattern = matches!(operands.last(), Some(PdfObject::Name(_)));
+				painter.state.stroke = painter.state.stroke_color_space.color(components).filter(|_| !pattern);
+			}
+
+			// Form XObjects are drawn in place, while images are skipped
+			("Do", _) => {
+				let (dictionary, data) = match self.resource(resources, "XObject", operands.first()) {
+					Some(PdfObject::Stream(dictionary, data)) if dictionary.get("Subtype").and_then(PdfObject::as_name) == Some("Form") => (dictionary, data),
+					_ => return Ok(()),
+				};
+				let document = self.document;
+				let contents = document.decode_stream(&dictionary, &data)?;
+				self.decoded_forms += contents.len();
+				if self.decoded_forms > MAX_DECODED_SIZE {
+					return Err(decoded_too_large());
+				}
+				let form_resources = document.get(&dictionary, "Resources").and_then(PdfObject::as_dictionary).unwrap_or(resources);
+				let matrix: Option<[f64; 6]> = dictionary.get("Matrix").and_then(|matrix| document.numbers(matrix).try_into().ok());
+
+				self.painter.save()?;
+				if let Some(matrix) = matrix {
+					self.painter.concat(DAffine2::from_cols_array(&matrix));
+				}
+				self.run(&contents, form_resources, depth + 1)?;
+				self.painter.restore();
+			}
+
+			// Text, shadings, marked content, and anything else are ignored
+			_ => {}
+		}
+		Ok(())
+	}
+
+	fn color_space(&self, resources: &PdfDictionary, name: Option<&PdfObject>) -> ColorSpaceKind {
+		let kind_of_name = |name: &str| match name {
+			"DeviceGray" | "G" | "CalGray" => Some(ColorSpaceKind::Gray),
+			"DeviceRGB" | "RGB" | "CalRGB" => Some(ColorSpaceKind::Rgb),
+			"DeviceCMYK" | "CMYK" => Some(ColorSpaceKind::Cmyk),
+			"Pattern" => Some(ColorSpaceKind::Pattern),
+			"Separation" | "DeviceN" => Some(ColorSpaceKind::Separation),
+			_ => None,
+		};
+		if let Some(kind) = name.and_then(PdfObject::as_name).and_then(kind_of_name) {
+			return kind;
+		}
+
+		let document = self.document;
+		match self.resource(resources, "ColorSpace", name).as_ref() {
+			Some(PdfObject::Name(name)) => kind_of_name(name),
+			Some(PdfObject::Array(array)) => match array.first().map(|family| document.resolve(family)).and_then(PdfObject::as_name) {
+				Some("ICCBased") => {
+					let profile = array.get(1).map(|profile| document.resolve(profile)).and_then(PdfObject::as_dictionary);
+					match profile.and_then(|profile| document.get(profile, "N")).and_then(PdfObject::as_number).map(|components| components as u8) {
+						Some(1) => Some(ColorSpaceKind::Gray),
+						Some(4) => Some(ColorSpaceKind::Cmyk),
+						_ => Some(ColorSpaceKind::Rgb),
+					}
+				}
+				Some(family) => kind_of_name(family),
+				None => None,
+			},
+			_ => None,
+		}
+		.unwrap_or(ColorSpaceKind::Other)
+	}
+}
+
+/// Skips the data of an inline image, from after its `BI` operator to after its `EI` operator.
+fn skip_inline_image(lexer: &mut Lexer) {
+	let data = lexer.data;
+	let start = lexer.position;
+	let image_data = (start..data.len().saturating_sub(1))
+		.find(|&index| &data[index..index + 2] == b"ID" && index > 0 && is_whitespace(data[index - 1]) && data.get(index + 2).map_or(true, |&byte| is_whitespace(byte)));
+	let image_data = match image_data {
+		Some(image_data) => image_data + 3,
+		None => {
+			lexer.position = data.len();
+			return;
+		}
+	};
+	let end =
+		(image_data..data.len().saturating_sub(1)).find(|&index| &data[index..index + 2] == b"EI" && is_whitespace(data[index - 1]) && data.get(index + 2).map_or(true, |&byte| is_delimiter(byte)));
+	lexer.position = end.map_or(data.len(), |end| end + 2);
+}
+
+// Stream filters
+
+/// Reverses the PNG predictors which may be applied to data before it's compressed.
+fn undo_predictor(data: Vec<u8>, predictor: f64, colors: f64, bits_per_component: f64, columns: f64) -> Result<Vec<u8>, EditorError> {
+	if predictor < 10. {
+		return Ok(data);
+	}
+	let bytes_per_pixel = ((colors * bits_per_component / 8.).ceil() as usize).max(1);
+	// Rows are never longer than the data, however many columns the file claims
+	let row_length = ((colors * bits_per_component * columns / 8.).ceil() as usize).clamp(1, data.len().max(1));
+
+	let mut output = Vec::with_capacity(data.len());
+	let mut previous_row = vec![0; row_length];
+	for row in data.chunks(row_length + 1) {
+		let (filter, row) = (row[0], &row[1..]);
+		let mut decoded = vec![0; row_length];
+		for (index, &byte) in row.iter().enumerate() {
+			let left = if index >= bytes_per_pixel { decoded[index - bytes_per_pixel] } else { 0 };
+			let up = previous_row[index];
+			let up_left = if index >= bytes_per_pixel { previous_row[index - bytes_per_pixel] } else { 0 };
+			let prediction = match filter {
+				0 => 0,
+				1 => left,
+				2 => up,
+				3 => ((left as u16 + up as u16) / 2) as u8,
+				4 => {
+					let estimate = left as i16 + up as i16 - up_left as i16;
+					let distances = ((estimate - left as i16).abs(), (estimate - up as i16).abs(), (estimate - up_left as i16).abs());
+					match distances {
+						(left_distance, up_distance, up_left_distance) if left_distance <= up_distance && left_distance <= up_left_distance => left,
+						(_, up_distance, up_left_distance) if up_distance <= up_left_distance => up,
+						_ => up_left,
+					}
+				}
+				_ => return Err(EditorError::Misc("A compressed stream in the file is corrupt".into())),
+			};
+			decoded[index] = byte.wrapping_add(prediction);
+		}
+		output.extend_from_slice(&decoded[..row.len()]);
+		previous_row = decoded;
+	}
+	Ok(output)
+}
+
+fn decoded_too_large() -> EditorError {
+	EditorError::Misc(format!("A stream in the file decodes to more than {MAX_DECODED_SIZE} bytes"))
+}
+
+/// Decompresses data encoded with the `FlateDecode` filter, which is in the zlib format.
+fn inflate(data: &[u8]) -> Result<Vec<u8>, EditorError> {
+	let mut output = Vec::new();
+	let result = flate2::read::ZlibDecoder::new(data).take(MAX_DECODED_SIZE as u64 + 1).read_to_end(&mut output);
+	if output.len() > MAX_DECODED_SIZE {
+		return Err(decoded_too_large());
+	}
+	match result {
+		Ok(_) => Ok(output),
+		// Streams cut short are common, and everything decoded before the damage is kept
+		Err(_) if !output.is_empty() => Ok(output),
+		Err(_) => Err(EditorError::Misc("A compressed stream in the file is corrupt".into())),
+	}
+}
+
+/// Decompresses data encoded with the `LZWDecode` filter, whose codes start at 9 bits and grow to 12 bits.
+fn lzw_decode(data: &[u8], early_change: bool) -> Result<Vec<u8>, EditorError> {
+	const CLEAR: usize = 256;
+	const END: usize = 257;
+
+	let mut table: Vec<Vec<u8>> = (0..=255).map(|byte| vec![byte]).chain([Vec::new(), Vec::new()]).collect();
+	let mut output = Vec::new();
+	let mut previous: Option<Vec<u8>> = None;
+	let (mut width, mut buffer, mut buffered_bits, mut position) = (9, 0_u32, 0, 0);
+
+	loop {
+		while buffered_bits < width {
+			let byte = match data.get(position) {
+				Some(&byte) => byte,
+				None => return Ok(output),
+			};
+			buffer = (buffer << 8) | byte as u32;
+			buffered_bits += 8;
+			position += 1;
+		}
+		let code = ((buffer >> (buffered_bits - width)) & ((1 << width) - 1)) as usize;
+		buffered_bits -= width;
+
+		if code == CLEAR {
+			table.truncate(END + 1);
+			previous = None;
+			width = 9;
+			continue;
+		}
+		if code == END {
+			return Ok(output);
+		}
+
+		let entry = match (table.get(code), &previous) {
+			(Some(entry), _) => entry.clone(),
+			// The code being defined by this very step is the previous entry followed by its own first byte
+			(None, Some(previous)) if code == table.len() => [previous.as_slice(), &previous[..1]].concat(),
+			_ => return Ok(output),
+		};
+		if output.len() + entry.len() > MAX_DECODED_SIZE {
+			return Err(decoded_too_large());
+		}
+		output.extend_from_slice(&entry);
+		if let Some(previous) = previous {
+			if table.len() < 4096 {
+				table.push([previous.as_slice(), &entry[..1]].concat());
+			}
+		}
+		previous = Some(entry);
+
+		width = match table.len() + early_change as usize {
+			size if size >= 2048 => 12,
+			size if size >= 1024 => 11,
+			size if size >= 512 => 10,
+			_ => 9,
+		};
+	}
+}
+
+/// Decodes data encoded with the `ASCII85Decode` filter, where each group of five characters holds four bytes.
+fn ascii85_decode(data: &[u8]) -> Result<Vec<u8>, EditorError> {
+	let data = data.strip_prefix(b"<~").unwrap_or(data);
+	let mut output = Vec::new();
+	let mut group = Vec::with_capacity(5);
+
+	for &byte in data {
+		match byte {
+			b'~' => break,
+			_ if output.len() > MAX_DECODED_SIZE => return Err(decoded_too_large()),
+			b'z' if group.is_empty() => output.extend([0; 4]),
+			b'!'..=b'u' => {
+				group.push(byte - b'!');
+				if group.len() == 5 {
+					let value = group.iter().fold(0_u64, |value, &digit| value * 85 + digit as u64);
+					output.extend_from_slice(&(value as u32).to_be_bytes());
+					group.clear();
+				}
+			}
+			_ if is_whitespace(byte) => {}
+			_ => return Err(EditorError::Misc("An ASCII85 stream in the file is corrupt".into())),
+		}
+	}
+
+	// A final partial group is padded with the highest digit, then the bytes which the padding added are dropped
+	if group.len() > 1 {
+		let length = group.len() - 1;
+		group.resize(5, 84);
+		let value = group.iter().fold(0_u64, |value, &digit| value * 85 + digit as u64);
+		output.extend_from_slice(&(value as u32).to_be_bytes()[..length]);
+	}
+	Ok(output)
+}
+
+/// Decodes data encoded with the `RunLengthDecode` filter, made of runs of literal bytes and runs of one repeated byte.
+fn run_length_decode(data: &[u8]) -> Result<Vec<u8>, EditorError> {
+	let mut output = Vec::new();
+	let mut position = 0;
+	while let Some(&length) = data.get(position) {
+		if output.len() > MAX_DECODED_SIZE {
+			return Err(decoded_too_large());
+		}
+		position += 1;
+		match length {
+			0..=127 => {
+				let end = (position + length as usize + 1).min(data.len());
+				output.extend_from_slice(&data[position..end]);
+				position = end;
+			}
+			128 => break,
+			_ => {
+				if let Some(&byte) = data.get(position) {
+					output.extend(std::iter::repeat(byte).take(257 - length as usize));
+				}
+				position += 1;
+			}
+		}
+	}
+	Ok(output)
+}
+
+// EPS
+
+type PsDictionary = Rc<RefCell<HashMap<String, PsValue>>>;
+
+#[derive(Clone, Debug)]
+enum PsValue {
+	Number(f64),
+	Bool(bool),
+	String(Rc<Vec<u8>>),
+	/// A literal name, written with a leading slash
+	Name(String),
+	/// A name which is looked up in the dictionary stack and executed when it's encountered
+	Executable(String),
+	/// A built-in operator, bypassing the dictionary stack
+	Operator(String),
+	Array(Rc<RefCell<Vec<PsValue>>>),
+	Procedure(Rc<Vec<PsValue>>),
+	Dictionary(PsDictionary),
+	Mark,
+	Null,
+}
+
+impl PartialEq for PsValue {
+	fn eq(&self, other: &Self) -> bool {
+		let text = |value: &PsValue| match value {
+			PsValue::Name(name) | PsValue::Executable(name) | PsValue::Operator(name) => Some(name.as_bytes().to_vec()),
+			PsValue::String(string) => Some(string.to_vec()),
+			_ => None,
+		};
+		match (self, other) {
+			(Self::Number(a), Self::Number(b)) => a == b,
+			(Self::Bool(a), Self::Bool(b)) => a == b,
+			(Self::Array(a), Self::Array(b)) => Rc::ptr_eq(a, b),
+			(Self::Procedure(a), Self::Procedure(b)) => Rc::ptr_eq(a, b),
+			(Self::Dictionary(a), Self::Dictionary(b)) => Rc::ptr_eq(a, b),
+			(Self::Mark, Self::Mark) | (Self::Null, Self::Null) => true,
+			(a, b) => text(a).is_some() && text(a) == text(b),
+		}
+	}
+}
+
+/// How execution continues after a procedure returns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PsFlow {
+	Continue,
+	/// Leaves the innermost loop
+	Exit,
+	/// Leaves the innermost `stopped` context, or the whole program
+	Stop,
+}
+
+fn stack_underflow() -> EditorError {
+	EditorError::Misc("The EPS file uses PostScript which can't be imported".into())
+}
+
+/// Parses a PostScript program into values, gathering procedures into single values.
+fn parse_postscript(lexer: &mut Lexer, in_procedure: bool, depth: usize) -> Result<Vec<PsValue>, EditorError> {
+	if depth > MAX_NESTING_DEPTH {
+		return Err(EditorError::Misc("The EPS file nests procedures too deeply".into()));
+	}
+
+	let mut values = Vec::new();
+	loop {
+		let value = match lexer.next_token()? {
+			Some(Token::Number(number)) => PsValue::Number(number),
+			Some(Token::String(string)) => PsValue::String(Rc::new(string)),
+			Some(Token::Name(name)) => PsValue::Name(name),
+			Some(Token::Keyword(keyword)) => PsValue::Executable(keyword),
+			Some(Token::ArrayStart) => PsValue::Executable("[".into()),
+			Some(Token::ArrayEnd) => PsValue::Executable("]".into()),
+			Some(Token::DictionaryStart) => PsValue::Executable("<<".into()),
+			Some(Token::DictionaryEnd) => PsValue::Executable(">>".into()),
+			Some(Token::ProcedureStart) => PsValue::Procedure(Rc::new(parse_postscript(lexer, true, depth + 1)?)),
+			Some(Token::ProcedureEnd) if in_procedure => return Ok(values),
+			Some(Token::ProcedureEnd) => return Err(EditorError::Misc("The EPS file closes a procedure which was never opened".into())),
+			None if in_procedure => return Err(EditorError::Misc("A procedure in the EPS file is never closed".into())),
+			None => return Ok(values),
+		};
+		values.push(value);
+	}
+}
+
+fn eps_to_svg(data: &[u8]) -> Result<String, EditorError> {
+	// EPS files with a binary header point to where their PostScript is
+	let data = if data.starts_with(&BINARY_EPS_SIGNATURE) && data.len() >= 12 {
+		let start = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+		let length = u32::from_le_bytes([data[8], data[9], data[10], data[11]]) as usize;
+		data.get(start..start.saturating_add(length).min(data.len())).unwrap_or_default()
+	} else {
+		data
+	};
+
+	let bounding_box = |comment: &[u8]| {
+		let mut lines = data.split(|&byte| byte == b'\n' || byte == b'\r');
+		lines.find_map(|line| {
+			let numbers = std::str::from_utf8(line.strip_prefix(comment)?)
+				.ok()?
+				.split_whitespace()
+				.map(str::parse)
+				.collect::<Result<Vec<f64>, _>>()
+				.ok()?;
+			(numbers.len() == 4).then_some(numbers)
+		})
+	};
+	let (corner, size) = match bounding_box(b"%%HiResBoundingBox:").or_else(|| bounding_box(b"%%BoundingBox:")) {
+		Some(bounds) => (DVec2::new(bounds[0], bounds[1]), DVec2::new(bounds[2] - bounds[0], bounds[3] - bounds[1])),
+		None => (DVec2::ZERO, DEFAULT_PAGE_SIZE),
+	};
+	let page_transform = DAffine2::from_cols_array(&[1., 0., 0., -1., -corner.x, corner.y + size.y]);
+
+	let program = parse_postscript(&mut Lexer::new(data, 0), false, 0)?;
+	let mut interpreter = PostScriptInterpreter {
+		painter: Painter::new(page_transform),
+		operands: Vec::new(),
+		dictionaries: vec![Rc::new(RefCell::new(HashMap::new()))],
+		steps: 0,
+		allocated: 0,
+	};
+	interpreter.run_procedure(&program, 0)?;
+	interpreter.painter.into_svg(size)
+}
+
+/// Runs the subset of PostScript used by the vector artwork of EPS files.
+struct PostScriptInterpreter {
+	painter: Painter,
+	operands: Vec<PsValue>,
+	/// The dictionary stack, where names are defined and looked up, starting with the user dictionary
+	dictionaries: Vec<PsDictionary>,
+	steps: usize,
+	/// The number of array items and string bytes allocated so far
+	allocated: usize,
+}
+
+impl PostScriptInterpreter {
+	fn run_procedure(&mut self, procedure: &[PsValue], depth: usize) -> Result<PsFlow, EditorError> {
+		if depth > MAX_NESTING_DEPTH {
+			return Err(EditorError::Misc("The EPS file calls procedures too deeply".into()));
+		}
+		for value in procedure {
+			let flow = match value {
+				// Procedures inside procedures are pushed rather than run
+				PsValue::Procedure(_) => {
+					self.operands.push(value.clone());
+					PsFlow::Continue
+				}
+				value => self.execute(value.clone(), depth)?,
+			};
+			if flow != PsFlow::Continue {
+				return Ok(flow);
+			}
+		}
+		Ok(PsFlow::Continue)
+	}
+
+	fn step(&mut self) -> Result<(), EditorError> {
+		self.steps += 1;
+		if self.steps > MAX_POSTSCRIPT_STEPS {
+			return Err(EditorError::Misc("The EPS file runs for too long to be imported".into()));
+		}
+		// Every operator pushes a bounded number of values, except those which check the stack themselves
+		if self.operands.len() > MAX_POSTSCRIPT_STACK_DEPTH || self.dictionaries.len() > MAX_POSTSCRIPT_STACK_DEPTH {
+			return Err(EditorError::Misc("The EPS file overflows the PostScript stack".into()));
+		}
+		Ok(())
+	}
+
+	/// Accounts for the array items or string bytes about to be allocated.
+	fn allocate(&mut self, length: usize) -> Result<(), EditorError> {
+		self.allocated = self.allocated.saturating_add(length);
+		if self.allocated > MAX_POSTSCRIPT_ALLOCATION {
+			return Err(EditorError::Misc("The EPS file uses too much memory to be imported".into()));
+		}
+		Ok(())
+	}
+
+	/// Checks that the operand stack has room for `count` more operands.
+	fn reserve(&self, count: usize) -> Result<(), EditorError> {
+		match self.operands.len().checked_add(count) {
+			Some(length) if length <= MAX_POSTSCRIPT_STACK_DEPTH => Ok(()),
+			_ => Err(EditorError::Misc("The EPS file overflows the PostScript stack".into())),
+		}
+	}
+
+	fn execute(&mut self, value: PsValue, depth: usize) -> Result<PsFlow, EditorError> {
+		self.step()?;
+
+		match value {
+			PsValue::Executable(name) => match self.lookup(&name) {
+				Some(PsValue::Procedure(procedure)) => self.run_procedure(&procedure, depth + 1),
+				Some(PsValue::Operator(name)) => self.operator(&name, depth),
+				Some(value) => {
+					self.operands.push(value);
+					Ok(PsFlow::Continue)
+				}
+				None => self.operator(&name, depth),
+			},
+			PsValue::Operator(name) => self.operator(&name, depth),
+			PsValue::Procedure(procedure) => self.run_procedure(&procedure, depth + 1),
+			value => {
+				self.operands.push(value);
+				Ok(PsFlow::Continue)
+			}
+		}
+	}
+
+	fn lookup(&self, name: &str) -> Option<PsValue> {
+		self.dictionaries.iter().rev().find_map(|dictionary| dictionary.borrow().get(name).cloned())
+	}
+
+	fn pop(&mut self) -> Result<PsValue, EditorError> {
+		self.operands.pop().ok_or_else(stack_underflow)
+	}
+
+	fn pop_number(&mut self) -> Result<f64, EditorError> {
+		match self.pop()? {
+			PsValue::Number(number) => Ok(number),
+			_ => Err(stack_underflow()),
+		}
+	}
+
+	/// Pops numbers, returning them in the order they were pushed.
+	fn pop_numbers<const N: usize>(&mut self) -> Result<[f64; N], EditorError> {
+		let mut numbers = [0.; N];
+		for number in numbers.iter_mut().rev() {
+			*number = self.pop_number()?;
+		}
+		Ok(numbers)
+	}
+
+	fn pop_bool(&mut self) -> Result<bool, EditorError> {
+		match self.pop()? {
+			PsValue::Bool(value) => Ok(value),
+			_ => Err(stack_underflow()),
+		}
+	}
+
+	fn pop_procedure(&mut self) -> Result<Rc<Vec<PsValue>>, EditorError> {
+		match self.pop()? {
+			PsValue::Procedure(procedure) => Ok(procedure),
+			PsValue::Array(array) => {
+				self.allocate(array.borrow().len())?;
+				Ok(Rc::new(array.borrow().clone()))
+			}
+			_ => Err(stack_underflow()),
+		}
+	}
+
+	fn pop_key(&mut self) -> Result<String, EditorError> {
+		match self.pop()? {
+			PsValue::Name(name) | PsValue::Executable(name) | PsValue::Operator(name) => Ok(name),
+			PsValue::String(string) => Ok(String::from_utf8_lossy(&string).into_owned()),
+			_ => Err(stack_underflow()),
+		}
+	}
+
+	fn pop_matrix(&mut self) -> Result<DAffine2, EditorError> {
+		let numbers = match self.pop()? {
+			PsValue::Array(array) => array
+				.borrow()
+				.iter()
+				.filter_map(|value| if let PsValue::Number(number) = value { Some(*number) } else { None })
+				.collect::<Vec<_>>(),
+			_ => Vec::new(),
+		};
+		let matrix: [f64; 6] = numbers.try_into().map_err(|_| stack_underflow())?;
+		Ok(DAffine2::from_cols_array(&matrix))
+	}
+
+	fn push_number(&mut self, number: f64) {
+		self.operands.push(PsValue::Number(number));
+	}
+
+	fn matrix_value(matrix: DAffine2) -> PsValue {
+		PsValue::Array(Rc::new(RefCell::new(matrix.to_cols_array().into_iter().map(PsValue::Number).collect())))
+	}
+
+	/// The number of operands above the topmost mark.
+	fn count_to_mark(&self) -> Result<usize, EditorError> {
+		self.operands.iter().rev().position(|value| matches!(value, PsValue::Mark)).ok_or_else(stack_underflow)
+	}
+
+	/// Runs a procedure as the body of a loop, returning whether the loop should stop.
+	fn loop_body(&mut self, procedure: &[PsValue], depth: usize) -> Result<Option<PsFlow>, EditorError> {
+		// Counted separately from the procedure's operators, since empty loops would otherwise never end
+		self.step()?;
+		match self.run_procedure(procedure, depth + 1)? {
+			PsFlow::Continue => Ok(None),
+			PsFlow::Exit => Ok(Some(PsFlow::Continue)),
+			PsFlow::Stop => Ok(Some(PsFlow::Stop)),
+		}
+	}
+
+	fn operator(&mut self, name: &str, depth: usize) -> Result<PsFlow, EditorError> {
+		match name {
+			// Stack
+			"pop" => drop(self.pop()?),
+			"exch" => {
+				let (b, a) = (self.pop()?, self.pop()?);
+				self.operands.extend([b, a]);
+			}
+			"dup" => {
+				let top = self.operands.last().cloned().ok_or_else(stack_underflow)?;
+				self.operands.push(top);
+			}
+			"copy" => {
+				let count = self.pop_number()? as usize;
+				let start = self.operands.len().checked_sub(count).ok_or_else(stack_underflow)?;
+				self.reserve(count)?;
+				self.operands.extend_from_within(start..);
+			}
+			"index" => {
+				let index = self.pop_number()? as usize;
+				let value = self.operands.iter().rev().nth(index).cloned().ok_or_else(stack_underflow)?;
+				self.operands.push(value);
+			}
+			"roll" => {
+				let [count, shift] = self.pop_numbers()?;
+				let count = count as usize;
+				let start = self.operands.len().checked_sub(count).ok_or_else(stack_underflow)?;
+				if count > 0 {
+					self.operands[start..].rotate_right((shift as i64).rem_euclid(count as i64) as usize);
+				}
+			}
+			"clear" => self.operands.clear(),
+			"count" => self.push_number(self.operands.len() as f64),
+			"mark" | "[" | "<<" => self.operands.push(PsValue::Mark),
+			"cleartomark" => {
+				let count = self.count_to_mark()?;
+				self.operands.truncate(self.operands.len() - count - 1);
+			}
+			"counttomark" => {
+				let count = self.count_to_mark()?;
+				self.push_number(count as f64);
+			}
+			"]" => {
+				let count = self.count_to_mark()?;
+				let array = self.operands.split_off(self.operands.len() - count);
+				self.operands.pop();
+				self.operands.push(PsValue::Array(Rc::new(RefCell::new(array))));
+			}
+			">>" => {
+				let count = self.count_to_mark()?;
+				let entries = self.operands.split_off(self.operands.len() - count);
+				self.operands.pop();
+				let mut dictionary = HashMap::new();
+				for pair in entries.chunks_exact(2) {
+					if let PsValue::Name(key) | PsValue::Executable(key) = &pair[0] {
+						dictionary.insert(key.clone(), pair[1].clone());
+					}
+				}
+				self.operands.push(PsValue::Dictionary(Rc::new(RefCell::new(dictionary))));
+			}
+
+			// Arithmetic, where angles are in degrees
+			"add" | "sub" | "mul" | "div" | "idiv" | "mod" | "atan" | "exp" => {
+				let [a, b] = self.pop_numbers()?;
+				let result = match name {
+					"add" => a + b,
+					"sub" => a - b,
+					"mul" => a * b,
+					"div" => a / b,
+					"idiv" => (a / b).trunc(),
+					"mod" => a % b,
+					"atan" => a.atan2(b).to_degrees().rem_euclid(360.),
+					_ => a.powf(b),
+				};
+				self.push_number(if result.is_finite() { result } else { 0. });
+			}
+			"neg" | "abs" | "sqrt" | "sin" | "cos" | "ln" | "log" | "round" | "floor" | "ceiling" | "truncate" | "cvi" | "cvr" => {
+				let a = self.pop_number()?;
+				let result = match name {
+					"neg" => -a,
+					"abs" => a.abs(),
+					"sqrt" => a.sqrt(),
+					"sin" => a.to_radians().sin(),
+					"cos" => a.to_radians().cos(),
+					"ln" => a.ln(),
+					"log" => a.log10(),
+					// PostScript rounds halves up, even when they're negative
+					"round" => (a + 0.5).floor(),
+					"floor" => a.floor(),
+					"ceiling" => a.ceil(),
+					"truncate" | "cvi" => a.trunc(),
+					_ => a,
+				};
+				self.push_number(if result.is_finite() { result } else { 0. });
+			}
+
+			// Comparison and logic
+			"eq" | "ne" => {
+				let (b, a) = (self.pop()?, self.pop()?);
+				self.operands.push(PsValue::Bool((a == b) == (name == "eq")));
+			}
+			"gt" | "ge" | "lt" | "le" => {
+				let [a, b] = self.pop_numbers()?;
+				let result = match name {
+					"gt" => a > b,
+					"ge" => a >= b,
+					"lt" => a < b,
+					_ => a <= b,
+				};
+				self.operands.push(PsValue::Bool(result));
+			}
+			"and" | "or" | "xor" => {
+				let (b, a) = (self.pop()?, self.pop()?);
+				let result = match (a, b) {
+					(PsValue::Bool(a), PsValue::Bool(b)) => PsValue::Bool(match name {
+						"and" => a && b,
+						"or" => a || b,
+						_ => a != b,
+					}),
+					(PsValue::Number(a), PsValue::Number(b)) => PsValue::Number(match name {
+						"and" => (a as i64 & b as i64) as f64,
+						"or" => (a as i64 | b as i64) as f64,
+						_ => (a as i64 ^ b as i64) as f64,
+					}),
+					_ => return Err(stack_underflow()),
+				};
+				self.operands.push(result);
+			}
+			"not" => {
+				let result = match self.pop()? {
+					PsValue::Bool(value) => PsValue::Bool(!value),
+					PsValue::Number(number) => PsValue::Number(!(number as i64) as f64),
+					_ => return Err(stack_underflow()),
+				};
+				self.operands.push(result);
+			}
+			"true" => self.operands.push(PsValue::Bool(true)),
+			"false" => self.operands.push(PsValue::Bool(false)),
+			"null" => self.operands.push(PsValue::Null),
+
+			// Control flow
+			"exec" => {
+				let value = self.pop()?;
+				return self.execute(value, depth + 1);
+			}
+			"if" => {
+				let procedure = self.pop_procedure()?;
+				if self.pop_bool()? {
+					return self.run_procedure(&procedure, depth + 1);
+				}
+			}
+			"ifelse" => {
+				let (otherwise, then) = (self.pop_procedure()?, self.pop_procedure()?);
+				let procedure = if self.pop_bool()? { then } else { otherwise };
+				return self.run_procedure(&procedure, depth + 1);
+			}
+			"for" => {
+				let procedure = self.pop_procedure()?;
+				let [initial, increment, limit] = self.pop_numbers()?;
+				let mut control = initial;
+				while increment != 0. && ((increment > 0. && control <= limit) || (increment < 0. && control >= limit)) {
+					self.push_number(control);
+					if let Some(flow) = self.loop_body(&procedure, depth)? {
+						return Ok(flow);
+					}
+					control += increment;
+				}
+			}
+			"repeat" => {
+				let procedure = self.pop_procedure()?;
+				for _ in 0..self.pop_number()?.max(0.) as usize {
+					if let Some(flow) = self.loop_body(&procedure, depth)? {
+						return Ok(flow);
+					}
+				}
+			}
+			"loop" => {
+				let procedure = self.pop_procedure()?;
+				loop {
+					if let Some(flow) = self.loop_body(&procedure, depth)? {
+						return Ok(flow);
+					}
+				}
+			}
+			"forall" => {
+				let procedure = self.pop_procedure()?;
+				let collection = self.pop()?;
+				let length = match &collection {
+					PsValue::Array(array) => array.borrow().len(),
+					PsValue::Procedure(items) => items.len(),
+					PsValue::String(string) => string.len(),
+					PsValue::Dictionary(dictionary) => dictionary.borrow().len() * 2,
+					_ => return Err(stack_underflow()),
+				};
+				self.allocate(length)?;
+				let items = match collection {
+					PsValue::Array(array) => array.borrow().iter().map(|item| vec![item.clone()]).collect::<Vec<_>>(),
+					PsValue::Procedure(items) => items.iter().map(|item| vec![item.clone()]).collect(),
+					PsValue::String(string) => string.iter().map(|&byte| vec![PsValue::Number(byte as f64)]).collect(),
+					PsValue::Dictionary(dictionary) => dictionary.borrow().iter().map(|(key, value)| vec![PsValue::Name(key.clone()), value.clone()]).collect(),
+					_ => return Err(stack_underflow()),
+				};
+				for item in items {
+					self.operands.extend(item);
+					if let Some(flow) = self.loop_body(&procedure, depth)? {
+						return Ok(flow);
+					}
+				}
+			}
+			"exit" => return Ok(PsFlow::Exit),
+			"stop" => return Ok(PsFlow::Stop),
+			"stopped" => {
+				let value = self.pop()?;
+				let flow = self.execute(value, depth + 1)?;
+				self.operands.push(PsValue::Bool(flow == PsFlow::Stop));
+			}
+
+			// Dictionaries
+			"def" => {
+				let value = self.pop()?;
+				let key = self.pop_key()?;
+				self.dictionaries.last().unwrap().borrow_mut().insert(key, value);
+			}
+			"store" => {
+				let value = self.pop()?;
+				let key = self.pop_key()?;
+				let dictionary = self
+					.dictionaries
+					.iter()
+					.rev()
+					.find(|dictionary| dictionary.borrow().contains_key(&key))
+					.unwrap_or_else(|| self.dictionaries.last().unwrap());
+				dictionary.borrow_mut().insert(key, value);
+			}
+			"load" => {
+				let key = self.pop_key()?;
+				let value = self.lookup(&key).unwrap_or(PsValue::Operator(key));
+				self.operands.push(value);
+			}
+			"where" => {
+				let key = self.pop_key()?;
+				match self.dictionaries.iter().rev().find(|dictionary| dictionary.borrow().contains_key(&key)).cloned() {
+					Some(dictionary) => self.operands.extend([PsValue::Dictionary(dictionary), PsValue::Bool(true)]),
+					None => self.operands.push(PsValue::Bool(false)),
+				}
+			}
+			"known" => {
+				let key = self.pop_key()?;
+				let known = match self.pop()? {
+					PsValue::Dictionary(dictionary) => dictionary.borrow().contains_key(&key),
+					_ => false,
+				};
+				self.operands.push(PsValue::Bool(known));
+			}
+			"dict" => {
+				self.pop_number()?;
+				self.operands.push(PsValue::Dictionary(Rc::new(RefCell::new(HashMap::new()))));
+			}
+			"begin" => match self.pop()? {
+				PsValue::Dictionary(dictionary) => self.dictionaries.push(dictionary),
+				_ => return Err(stack_underflow()),
+			},
+			"end" if self.dictionaries.len() > 1 => drop(self.dictionaries.pop()),
+			"currentdict" => self.operands.push(PsValue::Dictionary(self.dictionaries.last().unwrap().clone())),
+			"userdict" | "globaldict" => self.operands.push(PsValue::Dictionary(self.dictionaries[0].clone())),
+			// Binding only speeds up lookups, so the procedure is left as it is
+			"bind" | "cvlit" | "readonly" | "executeonly" | "noaccess" => {}
+			"cvx" => {
+				let value = match self.pop()? {
+					PsValue::Array(array) => {
+						self.allocate(array.borrow().len())?;
+						PsValue::Procedure(Rc::new(array.borrow().clone()))
+					}
+					PsValue::Name(name) => PsValue::Executable(name),
+					value => value,
+				};
+				self.operands.push(value);
+			}
+			"cvn" => {
+				let key = self.pop_key()?;
+				self.operands.push(PsValue::Name(key));
+			}
+
+			// Arrays and strings
+			"array" => {
+				let length = self.pop_number()?.max(0.) as usize;
+				self.allocate(length)?;
+				self.operands.push(PsValue::Array(Rc::new(RefCell::new(vec![PsValue::Null; length]))));
+			}
+			"string" => {
+				let length = self.pop_number()?.max(0.) as usize;
+				self.allocate(length)?;
+				self.operands.push(PsValue::String(Rc::new(vec![0; length])));
+			}
+			"length" => {
+				let length = match self.pop()? {
+					PsValue::Array(array) => array.borrow().len(),
+					PsValue::Procedure(procedure) => procedure.len(),
+					PsValue::String(string) => string.len(),
+					PsValue::Dictionary(dictionary) => dictionary.borrow().len(),
+					PsValue::Name(name) => name.len(),
+					_ => return Err(stack_underflow()),
+				};
+				self.push_number(length as f64);
+			}
+			"get" => {
+				let key = self.pop()?;
+				let value = match (self.pop()?, key) {
+					(PsValue::Array(array), PsValue::Number(index)) => array.borrow().get(index as usize).cloned(),
+					(PsValue::Procedure(procedure), PsValue::Number(index)) => procedure.get(index as usize).cloned(),
+					(PsValue::String(string), PsValue::Number(index)) => string.get(index as usize).map(|&byte| PsValue::Number(byte as f64)),
+					(PsValue::Dictionary(dictionary), PsValue::Name(key) | PsValue::Executable(key)) => dictionary.borrow().get(&key).cloned(),
+					_ => None,
+				};
+				self.operands.push(value.ok_or_else(stack_underflow)?);
+			}
+			"put" => {
+				let value = self.pop()?;
+				let key = self.pop()?;
+				match (self.pop()?, key) {
+					(PsValue::Array(array), PsValue::Number(index)) => {
+						if let Some(item) = array.borrow_mut().get_mut(index as usize) {
+							*item = value;
+						}
+					}
+					(PsValue::Dictionary(dictionary), PsValue::Name(key) | PsValue::Executable(key)) => {
+						dictionary.borrow_mut().insert(key, value);
+					}
+					_ => {}
+				}
+			}
+			"aload" => {
+				let array = self.pop()?;
+				let items = match &array {
+					PsValue::Array(items) => items.borrow().clone(),
+					PsValue::Procedure(items) => items.to_vec(),
+					_ => return Err(stack_underflow()),
+				};
+				self.reserve(items.len() + 1)?;
+				self.operands.extend(items);
+				self.operands.push(array);
+			}
+
+			// Graphics state
+			"gsave" => self.painter.save()?,
+			"grestore" => self.painter.restore(),
+			"save" => {
+				self.painter.save()?;
+				self.operands.push(PsValue::Null);
+			}
+			"restore" => {
+				self.pop()?;
+				self.painter.restore();
+			}
+			"grestoreall" => {
+				if let Some((state, path)) = self.painter.saved_states.first().cloned() {
+					self.painter.saved_states.clear();
+					self.painter.state = state;
+					self.painter.path = path;
+				}
+			}
+			"setlinewidth" => self.painter.state.line_width = self.pop_number()?,
+			"setlinecap" => self.painter.state.line_cap = self.pop_number()? as u8,
+			"setlinejoin" => self.painter.state.line_join = self.pop_number()? as u8,
+			"setmiterlimit" => self.painter.state.miter_limit = self.pop_number()?,
+			"setdash" => {
+				let phase = self.pop_number()?;
+				let dashes = match self.pop()? {
+					PsValue::Array(array) => array
+						.borrow()
+						.iter()
+						.filter_map(|value| if let PsValue::Number(number) = value { Some(*number) } else { None })
+						.collect(),
+					_ => Vec::new(),
+				};
+				self.painter.state.dash_array = dashes;
+				self.painter.state.dash_phase = phase;
+			}
+			"setgray" => {
+				let gray = self.pop_number()?;
+				self.set_color(Some([gray; 3]));
+			}
+			"setrgbcolor" => {
+				let [red, green, blue] = self.pop_numbers()?;
+				self.set_color(Some([red, green, blue]));
+			}
+			"setcmykcolor" => {
+				let components = self.pop_numbers::<4>()?;
+				self.set_color(ColorSpaceKind::Cmyk.color(&components));
+			}
+			"sethsbcolor" => {
+				let [hue, saturation, brightness] = self.pop_numbers()?;
+				self.set_color(Some(hsb_to_rgb(hue, saturation, brightness)));
+			}
+			"setcolor" => {
+				let count = self.operands.iter().rev().take_while(|value| matches!(value, PsValue::Number(_))).count().min(4);
+				let components = self.operands.split_off(self.operands.len() - count);
+				let components = components
+					.iter()
+					.filter_map(|value| if let PsValue::Number(number) = value { Some(*number) } else { None })
+					.collect::<Vec<_>>();
+				self.set_color(ColorSpaceKind::Other.color(&components));
+			}
+			"setcolorspace" | "setfont" | "setflat" | "setoverprint" | "setstrokeadjust" | "setpagedevice" => drop(self.pop()?),
+
+			// Coordinate transformations
+			"translate" => {
+				let [x, y] = self.pop_numbers()?;
+				self.painter.concat(DAffine2::from_translation(DVec2::new(x, y)));
+			}
+			"scale" => {
+				let [x, y] = self.pop_numbers()?;
+				self.painter.concat(DAffine2::from_scale(DVec2::new(x, y)));
+			}
+			"rotate" => {
+				let angle = self.pop_number()?;
+				self.painter.concat(DAffine2::from_angle(angle.to_radians()));
+			}
+			"concat" => {
+				let matrix = self.pop_matrix()?;
+				self.painter.concat(matrix);
+			}
+			"matrix" => self.operands.push(Self::matrix_value(DAffine2::IDENTITY)),
+			"currentmatrix" => {
+				self.pop()?;
+				self.operands.push(Self::matrix_value(self.painter.state.ctm));
+			}
+			"setmatrix" => self.painter.state.ctm = self.pop_matrix()?,
+			"initmatrix" => self.painter.state.ctm = DAffine2::IDENTITY,
+
+			// Path construction
+			"newpath" => self.painter.end_path(),
+			"moveto" | "lineto" | "rmoveto" | "rlineto" => {
+				let [x, y] = self.pop_numbers()?;
+				let relative = name.starts_with('r');
+				let origin = if relative { self.painter.current_point().ok_or_else(stack_underflow)? } else { DVec2::ZERO };
+				let point = origin + DVec2::new(x, y);
+				if name.ends_with("moveto") {
+					self.painter.move_to(point);
+				} else {
+					self.painter.line_to(point);
+				}
+			}
+			"curveto" | "rcurveto" => {
+				let [x1, y1, x2, y2, x3, y3] = self.pop_numbers()?;
+				let origin = if name == "rcurveto" {
+					self.painter.current_point().ok_or_else(stack_underflow)?
+				} else {
+					DVec2::ZERO
+				};
+				self.painter.curve_to(origin + DVec2::new(x1, y1), origin + DVec2::new(x2, y2), origin + DVec2::new(x3, y3));
+			}
+			"arc" | "arcn" => {
+				let [x, y, radius, start_angle, end_angle] = self.pop_numbers()?;
+				self.painter.arc(DVec2::new(x, y), radius, start_angle, end_angle, name == "arcn");
+			}
+			"closepath" => self.painter.close_path(),
+			"currentpoint" => {
+				let point = self.painter.current_point().ok_or_else(stack_underflow)?;
+				self.operands.extend([PsValue::Number(point.x), PsValue::Number(point.y)]);
+			}
+
+			// Painting, where clipping paths are ignored and even-odd fills are imported as nonzero fills
+			"fill" | "eofill" => self.painter.paint(true, false),
+			"stroke" => self.painter.paint(false, true),
+			"rectfill" | "rectstroke" => {
+				let [x, y, width, height] = self.pop_numbers()?;
+				let current_path = std::mem::take(&mut self.painter.path);
+				self.painter.rectangle(DVec2::new(x, y), DVec2::new(width, height));
+				self.painter.paint(name == "rectfill", name == "rectstroke");
+				self.painter.path = current_path;
+			}
+
+			// Text and images can't be imported, but their operands are popped so the stack stays balanced
+			"findfont" => {
+				self.pop()?;
+				self.operands.push(PsValue::Dictionary(Rc::new(RefCell::new(HashMap::new()))));
+			}
+			"scalefont" | "makefont" | "show" => self.operands.truncate(self.operands.len().saturating_sub(1)),
+			"charpath" | "selectfont" | "kshow" | "xshow" | "yshow" | "xyshow" => self.operands.truncate(self.operands.len().saturating_sub(2)),
+			"ashow" => self.operands.truncate(self.operands.len().saturating_sub(3)),
+			"widthshow" => self.operands.truncate(self.operands.len().saturating_sub(4)),
+			"awidthshow" => self.operands.truncate(self.operands.len().saturating_sub(6)),
+			"stringwidth" => {
+				self.pop()?;
+				self.operands.extend([PsValue::Number(0.), PsValue::Number(0.)]);
+			}
+			"image" | "imagemask" => {
+				let count = if matches!(self.operands.last(), Some(PsValue::Dictionary(_))) { 1 } else { 5 };
+				self.operands.truncate(self.operands.len().saturating_sub(count));
+			}
+
+			// Clipping, page output, and anything else are ignored
+			_ => {}
+		}
+		Ok(PsFlow::Continue)
+	}
+
+	fn set_color(&mut self, color: Option<[f64; 3]>) {
+		// PostScript has a single current color, used by both fills and strokes
+		self.painter.state.fill = color;
+		self.painter.state.stroke = color;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use flate2::write::ZlibEncoder;
+	use flate2::Compression;
+	use std::io::Write as _;
+
+	fn zlib_compress(data: &[u8]) -> Vec<u8> {
+		let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+		encoder.write_all(data).unwrap();
+		encoder.finish().unwrap()
+	}
+
+	/// Builds a one page PDF file drawing the given content stream, which is described by the given extra stream dictionary entries.
+	fn pdf(entries: &str, contents: &[u8]) -> Vec<u8> {
+		let mut data = b"%PDF-1.4\n1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n".to_vec();
+		data.extend_from_slice(b"2 0 obj << /Type /Pages /Kids [3 0 R] /Count 1 >> endobj\n");
+		data.extend_from_slice(b"3 0 obj << /Type /Page /Parent 2 0 R /MediaBox [0 0 100 100] /Contents 4 0 R >> endobj\n");
+		data.extend_from_slice(format!("4 0 obj << /Length {} {entries} >> stream\n", contents.len()).as_bytes());
+		data.extend_from_slice(contents);
+		data.extend_from_slice(b"\nendstream endobj\ntrailer << /Root 1 0 R >>\n%%EOF\n");
+		data
+	}
+
+	fn eps(program: &str) -> Result<String, EditorError> {
+		PrintFormat::Eps.to_svg(format!("%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 100 100\n{program}\n").as_bytes())
+	}
+
+	fn error_message(result: Result<String, EditorError>) -> String {
+		match result {
+			Err(EditorError::Misc(message)) => message,
+			other => panic!("Expected an error, got {other:?}"),
+		}
+	}
+
+	#[test]
+	fn compressed_pdf_is_imported() {
+		let contents = zlib_compress(b"0 0 1 rg 10 10 50 50 re f");
+		let svg = PrintFormat::Pdf.to_svg(&pdf("/Filter /FlateDecode", &contents)).unwrap();
+		assert!(svg.contains("<path"));
+		assert!(svg.contains("#0000ff"));
+	}
+
+	#[test]
+	fn corrupt_compressed_pdf_is_rejected() {
+		for contents in [&b""[..], b"\x78", b"\x78\x00garbage", b"\x08\x1d\xff\xff\xff\xff"] {
+			assert!(PrintFormat::Pdf.to_svg(&pdf("/Filter /FlateDecode", contents)).is_err());
+		}
+	}
+
+	#[test]
+	fn hostile_stream_lengths_are_ignored() {
+		let contents = b"0 0 1 rg 10 10 50 50 re f";
+		let stated = format!("/Length {}", contents.len());
+		for length in ["100000000000000000000", "18446744073709551615", "-100000000000000000000", "1000"] {
+			let data = String::from_utf8(pdf("", contents)).unwrap().replace(&stated, &format!("/Length {length}"));
+			let svg = PrintFormat::Pdf.to_svg(data.as_bytes()).unwrap();
+			assert!(svg.contains("<path"), "{length}");
+		}
+	}
+
+	#[test]
+	fn predictor_with_huge_columns_is_bounded_by_the_data() {
+		let decoded = undo_predictor(vec![0, 1, 2, 3, 2, 1, 1, 1], 12., 1., 8., 1e18).unwrap();
+		assert_eq!(decoded, [1, 2, 3, 2, 1, 1, 1]);
+	}
+
+	#[test]
+	fn hostile_decoders_stop_at_the_end_of_their_data() {
+		// A run of 128 bytes, then a run length without a byte to repeat
+		assert_eq!(run_length_decode(&[129, 7, 129]).unwrap(), [7; 128]);
+		// A code which isn't in the table yet, and which the table can't grow to
+		assert!(lzw_decode(&[0xFF, 0xFF, 0xFF], true).unwrap().is_empty());
+		assert!(ascii85_decode(b"<~ab{~>").is_err());
+	}
+
+	#[test]
+	fn too_many_pdf_operators_are_rejected() {
+		let contents = "q Q ".repeat(MAX_PDF_OPERATORS / 2 + 1);
+		let message = error_message(PrintFormat::Pdf.to_svg(&pdf("", contents.as_bytes())));
+		assert!(message.contains("draws too much"), "{message}");
+	}
+
+	#[test]
+	fn unbalanced_saved_states_are_rejected() {
+		let contents = "q ".repeat(MAX_SAVED_STATES + 1);
+		let message = error_message(PrintFormat::Pdf.to_svg(&pdf("", contents.as_bytes())));
+		assert!(message.contains("graphics states"), "{message}");
+
+		let message = error_message(eps("{ gsave } loop"));
+		assert!(message.contains("graphics states"), "{message}");
+	}
+
+	#[test]
+	fn objects_in_object_streams_are_imported() {
+		let objects = b"<< /Type /Pages /Kids [3 0 R] /Count 1 >> << /Type /Page /Parent 2 0 R /MediaBox [0 0 100 100] /Contents 4 0 R >>";
+		let object_stream = |count: &str, first: &str| {
+			let header = b"2 0 3 42 ";
+			let mut data = b"%PDF-1.5\n1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n".to_vec();
+			data.extend_from_slice(format!("5 0 obj << /Type /ObjStm /N {count} /First {first} /Length {} >> stream\n", header.len() + objects.len()).as_bytes());
+			data.extend_from_slice(header);
+			data.extend_from_slice(objects);
+			data.extend_from_slice(b"\nendstream endobj\n4 0 obj << /Length 25 >> stream\n0 0 1 rg 10 10 50 50 re f\nendstream endobj\ntrailer << /Root 1 0 R >>\n%%EOF\n");
+			data
+		};
+
+		let svg = PrintFormat::Pdf.to_svg(&object_stream("2", "9")).unwrap();
+		assert!(svg.contains("<path"));
+		// Counts past the numbers in the header stop at the end of the header
+		assert!(PrintFormat::Pdf.to_svg(&object_stream("100000000000000000000000000000", "9")).unwrap().contains("<path"));
+		// Offsets past the end of the stream find nothing, leaving the page tree missing
+		assert!(PrintFormat::Pdf.to_svg(&object_stream("2", "100000000000000000000000000000")).is_err());
+	}
+
+	#[test]
+	fn self_referencing_page_trees_are_searched_once() {
+		let kids = "2 0 R ".repeat(100);
+		let data = format!("%PDF-1.4\n1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n2 0 obj << /Type /Pages /Kids [{kids}] /Count 1 >> endobj\ntrailer << /Root 1 0 R >>\n%%EOF\n");
+		let message = error_message(PrintFormat::Pdf.to_svg(data.as_bytes()));
+		assert!(message.contains("no pages"), "{message}");
+	}
+
+	#[test]
+	fn deep_nesting_is_rejected() {
+		let message = error_message(PrintFormat::Pdf.to_svg(&pdf("", "[".repeat(MAX_NESTING_DEPTH + 2).as_bytes())));
+		assert!(message.contains("too deeply"), "{message}");
+
+		// A form which draws itself
+		let data = String::from_utf8(pdf("/Subtype /Form /Resources << /XObject << /Self 4 0 R >> >>", b"/Self Do"))
+			.unwrap()
+			.replace("/Contents 4 0 R", "/Contents 4 0 R /Resources << /XObject << /Self 4 0 R >> >>");
+		let message = error_message(PrintFormat::Pdf.to_svg(data.as_bytes()));
+		assert!(message.contains("nests form XObjects too deeply"), "{message}");
+	}
+
+	#[test]
+	fn eps_is_imported() {
+		let svg = eps("newpath 10 10 moveto 90 10 lineto 50 90 lineto closepath 1 0 0 setrgbcolor fill").unwrap();
+		assert!(svg.contains("<path"));
+		assert!(svg.contains("#ff0000"));
+	}
+
+	#[test]
+	fn doubling_the_stack_is_rejected() {
+		let message = error_message(eps("1 { count copy } loop"));
+		assert!(message.contains("overflows the PostScript stack"), "{message}");
+
+		let message = error_message(eps("{ 1 } loop"));
+		assert!(message.contains("overflows the PostScript stack"), "{message}");
+
+		let message = error_message(eps("{ 1 dict begin } loop"));
+		assert!(message.contains("overflows the PostScript stack"), "{message}");
+
+		let message = error_message(eps("[ 0 1 70000 { } for ] aload"));
+		assert!(message.contains("overflows the PostScript stack"), "{message}");
+	}
+
+	#[test]
+	fn huge_allocations_are_rejected() {
+		for program in [
+			"1e15 array",
+			"1e15 string",
+			"{ 1000000 array pop } loop",
+			"{ 1000000 string pop } loop",
+			"[ 0 1 9999 { } for ] { dup cvx pop } loop",
+		] {
+			let message = error_message(eps(program));
+			assert!(message.contains("too much memory"), "{program}: {message}");
+		}
+	}
+
+	#[test]
+	fn malformed_postscript_is_rejected() {
+		assert!(eps("{ 1 2 add").is_err());
+		assert!(eps("1 2 add }").is_err());
+		assert!(eps(&"{".repeat(MAX_NESTING_DEPTH + 2)).is_err());
+		assert!(eps("/recurse { recurse } def recurse").is_err());
+		assert!(eps("pop").is_err());
+	}
+}
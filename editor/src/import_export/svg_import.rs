@@ -0,0 +1,809 @@
+//! Converts SVG files into native layers, so imported artwork can be edited like anything drawn in Graphite.
+//!
+//! Paths, basic shapes, groups, transforms, fills, strokes, and linear and radial gradients are supported.
+//! Other elements, like text, images, and `<use>` references, are skipped.
+
+use crate::application::generate_uuid;
+use crate::messages::portfolio::document::utility_types::clipboards::SVG_LAYER_DATA_ATTRIBUTE;
+use crate::messages::portfolio::document::utility_types::error::EditorError;
+
+use graphene::color::{Color, ColorSpace};
+use graphene::layers::style::{Fill, Gradient, GradientSpread, GradientType, LineCap, LineJoin, PathStyle, Stroke};
+use graphene::layers::vector::subpath::Subpath;
+use graphene::{LayerId, Operation};
+
+use glam::{DAffine2, DVec2};
+use std::collections::HashMap;
+
+/// Nested gradient references deeper than this are assumed to be a cycle.
+const MAX_GRADIENT_REFERENCE_DEPTH: usize = 16;
+/// Elements nested deeper than this are rejected, so a hostile file can't overflow the stack of the recursive parser and importer.
+const MAX_ELEMENT_DEPTH: usize = 256;
+
+/// Builds the operations creating the layers of an SVG file inside a new folder at `path`.
+///
+/// Also returns the transform from the SVG's user space to its outer size, which is set by its `viewBox`, for the caller to place the folder with.
+pub fn import_svg(svg: &str, path: Vec<LayerId>, color_space: ColorSpace) -> Result<(Vec<Operation>, DAffine2), EditorError> {
+	let root = XmlParser::new(svg).parse_document()?;
+	if root.name != "svg" {
+		return Err(EditorError::Misc("The file is not an SVG document".into()));
+	}
+
+	let mut gradients = HashMap::new();
+	collect_gradients(&root, &mut gradients);
+
+	let mut importer = SvgImporter {
+		gradients,
+		color_space,
+		operations: vec![Operation::CreateFolder { path: path.clone() }],
+	};
+	for child in &root.children {
+		importer.import_element(child, &path, &InheritedStyle::default());
+	}
+
+	Ok((importer.operations, view_box_transform(&root)))
+}
+
+/// The serialized layers embedded in an SVG copied from Graphite, if the SVG is one.
+pub fn embedded_layer_data(svg: &str) -> Option<String> {
+	let root = XmlParser::new(svg).parse_document().ok()?;
+	root.children
+		.iter()
+		.filter(|child| child.name == "metadata")
+		.find_map(|metadata| metadata.attributes.get(SVG_LAYER_DATA_ATTRIBUTE).cloned())
+}
+
+/// An element of an XML document, with its attributes and child elements. Text content is discarded.
+#[derive(Debug, Clone, PartialEq)]
+struct XmlElement {
+	name: String,
+	attributes: HashMap<String, String>,
+	children: Vec<XmlElement>,
+}
+
+impl XmlElement {
+	/// The value of a property from the inline `style` attribute, or otherwise from the presentation attribute of that name.
+	fn property(&self, name: &str) -> Option<&str> {
+		let from_style = self.attributes.get("style").and_then(|style| {
+			style
+				.split(';')
+				.filter_map(|declaration| declaration.split_once(':'))
+				.filter(|(property, _)| property.trim() == name)
+				.map(|(_, value)| value.trim())
+				.next_back()
+		});
+		from_style.or_else(|| self.attributes.get(name).map(|value| value.trim()))
+	}
+
+	fn length(&self, name: &str) -> f64 {
+		self.attributes.get(name).and_then(|value| parse_length(value)).unwrap_or_default()
+	}
+
+	/// The element referenced by `href`, which older files write as `xlink:href`.
+	fn href_id(&self) -> Option<&str> {
+		self.attributes.get("href").or_else(|| self.attributes.get("xlink:href")).and_then(|href| href.strip_prefix('#'))
+	}
+}
+
+/// A small XML parser which reads elements and attributes, skipping the prolog, comments, and character data.
+struct XmlParser<'a> {
+	source: &'a str,
+	position: usize,
+}
+
+impl<'a> XmlParser<'a> {
+	fn new(source: &'a str) -> Self {
+		Self { source, position: 0 }
+	}
+
+	fn rest(&self) -> &'a str {
+		&self.source[self.position..]
+	}
+
+	fn parse_document(&mut self) -> Result<XmlElement, EditorError> {
+		self.next_tag()?;
+		self.parse_element(0)
+	}
+
+	/// Moves past the next occurrence of `end`.
+	fn skip_past(&mut self, end: &str) -> Result<(), EditorError> {
+		match self.rest().find(end) {
+			Some(index) => {
+				self.position += index + end.len();
+				Ok(())
+			}
+			None => Err(EditorError::Misc("The SVG file ended unexpectedly".into())),
+		}
+	}
+
+	fn skip_whitespace(&mut self) {
+		self.position = self.source.len() - self.rest().trim_start().len();
+	}
+
+	fn parse_name(&mut self) -> &'a str {
+		let rest = self.rest();
+		let end = rest.find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '=')).unwrap_or(rest.len());
+		self.position += end;
+		&rest[..end]
+	}
+
+	/// Moves to the start of the next element or closing tag, skipping declarations, comments, and text.
+	fn next_tag(&mut self) -> Result<(), EditorError> {
+		loop {
+			self.skip_past("<")?;
+			self.position -= 1;
+
+			let rest = self.rest();
+			if rest.starts_with("<!--") {
+				self.skip_past("-->")?;
+			} else if rest.starts_with("<![CDATA[") {
+				self.skip_past("]]>")?;
+			} else if rest.starts_with("<?") {
+				self.skip_past("?>")?;
+			} else if rest.starts_with("<!") {
+				self.skip_past(">")?;
+			} else {
+				return Ok(());
+			}
+		}
+	}
+
+	fn parse_element(&mut self, depth: usize) -> Result<XmlElement, EditorError> {
+		if depth > MAX_ELEMENT_DEPTH {
+			return Err(EditorError::Misc(format!("The SVG file nests elements more than {MAX_ELEMENT_DEPTH} levels deep")));
+		}
+		if self.rest().starts_with("</") {
+			return Err(EditorError::Misc("The SVG file has a closing tag without an opening tag".into()));
+		}
+
+		// Skip the opening `<`
+		self.position += 1;
+		let name = self.parse_name().to_string();
+		let mut attributes = HashMap::new();
+
+		loop {
+			self.skip_whitespace();
+			let rest = self.rest();
+			if rest.starts_with("/>") {
+				self.position += 2;
+				return Ok(XmlElement {
+					name,
+					attributes,
+					children: Vec::new(),
+				});
+			}
+			if rest.starts_with('>') {
+				self.position += 1;
+				break;
+			}
+
+			let key = self.parse_name().to_string();
+			if key.is_empty() {
+				return Err(EditorError::Misc(format!("The SVG file has a malformed <{name}> tag")));
+			}
+			self.skip_whitespace();
+			if !self.rest().starts_with('=') {
+				attributes.insert(key, String::new());
+				continue;
+			}
+			self.position += 1;
+			self.skip_whitespace();
+
+			let quote = match self.rest().chars().next() {
+				Some(quote @ ('"' | '\'')) => quote,
+				_ => return Err(EditorError::Misc(format!("The value of the attribute \"{key}\" is missing its quotes"))),
+			};
+			self.position += 1;
+			let end = self.rest().find(quote).ok_or_else(|| EditorError::Misc("The SVG file ended unexpectedly".into()))?;
+			attributes.insert(key, decode_entities(&self.rest()[..end]));
+			self.position += end + 1;
+		}
+
+		let mut children = Vec::new();
+		loop {
+			self.next_tag()?;
+			if self.rest().starts_with("</") {
+				self.position += 2;
+				let closing_name = self.parse_name();
+				if closing_name != name {
+					return Err(EditorError::Misc(format!("The SVG file closes the <{name}> tag with </{closing_name}>")));
+				}
+				self.skip_whitespace();
+				if !self.rest().starts_with('>') {
+					return Err(EditorError::Misc(format!("The SVG file has a malformed </{name}> tag")));
+				}
+				self.position += 1;
+				return Ok(XmlElement { name, attributes, children });
+			}
+			children.push(self.parse_element(depth + 1)?);
+		}
+	}
+}
+
+fn decode_entities(value: &str) -> String {
+	value.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Paint {
+	None,
+	Color(Color),
+	/// A reference to a gradient by its ID
+	Gradient(String),
+}
+
+/// The properties that elements inherit from the groups containing them.
+#[derive(Debug, Clone)]
+struct InheritedStyle {
+	fill: Paint,
+	fill_opacity: f64,
+	stroke: Paint,
+	stroke_opacity: f64,
+	stroke_width: f64,
+	line_cap: LineCap,
+	line_join: LineJoin,
+	miter_limit: f64,
+	dash_array: String,
+	dash_offset: f64,
+}
+
+impl Default for InheritedStyle {
+	fn default() -> Self {
+		Self {
+			fill: Paint::Color(Color::BLACK),
+			fill_opacity: 1.,
+			stroke: Paint::None,
+			stroke_opacity: 1.,
+			stroke_width: 1.,
+			line_cap: LineCap::Butt,
+			line_join: LineJoin::Miter,
+			miter_limit: 4.,
+			dash_array: String::new(),
+			dash_offset: 0.,
+		}
+	}
+}
+
+impl InheritedStyle {
+	/// The style of an element, given the style inherited from its parent.
+	fn apply(&self, element: &XmlElement) -> Self {
+		let mut style = self.clone();
+		let number = |name: &str| element.property(name).and_then(parse_length);
+
+		if let Some(fill) = element.property("fill").and_then(|value| parse_paint(value, &self.fill)) {
+			style.fill = fill;
+		}
+		if let Some(stroke) = element.property("stroke").and_then(|value| parse_paint(value, &self.stroke)) {
+			style.stroke = stroke;
+		}
+		style.fill_opacity = number("fill-opacity").unwrap_or(style.fill_opacity);
+		style.stroke_opacity = number("stroke-opacity").unwrap_or(style.stroke_opacity);
+		style.stroke_width = number("stroke-width").unwrap_or(style.stroke_width);
+		style.miter_limit = number("stroke-miterlimit").unwrap_or(style.miter_limit);
+		style.dash_offset = number("stroke-dashoffset").unwrap_or(style.dash_offset);
+		style.line_cap = match element.property("stroke-linecap") {
+			Some("butt") => LineCap::Butt,
+			Some("round") => LineCap::Round,
+			Some("square") => LineCap::Square,
+			_ => style.line_cap,
+		};
+		style.line_join = match element.property("stroke-linejoin") {
+			Some("miter") => LineJoin::Miter,
+			Some("bevel") => LineJoin::Bevel,
+			Some("round") => LineJoin::Round,
+			_ => style.line_join,
+		};
+		match element.property("stroke-dasharray") {
+			Some("none") => style.dash_array.clear(),
+			Some(dash_array) => style.dash_array = dash_array.to_string(),
+			None => {}
+		}
+		style
+	}
+}
+
+/// A gradient definition with the attributes and stops it inherits through `href` references already merged in.
+struct ResolvedGradient {
+	gradient_type: GradientType,
+	attributes: HashMap<String, String>,
+	stops: Vec<(f64, Color)>,
+}
+
+struct SvgImporter {
+	/// The gradient elements of the document by their IDs
+	gradients: HashMap<String, XmlElement>,
+	/// The color space the colors of the document are specified in, which the sRGB colors of the SVG are converted to
+	color_space: ColorSpace,
+	operations: Vec<Operation>,
+}
+
+impl SvgImporter {
+	fn import_element(&mut self, element: &XmlElement, parent_path: &[LayerId], inherited_style: &InheritedStyle) {
+		if element.property("display") == Some("none") {
+			return;
+		}
+
+		let style = inherited_style.apply(element);
+		let transform = element.attributes.get("transform").map_or(DAffine2::IDENTITY, |transform| parse_transform(transform));
+		let path = [parent_path, &[generate_uuid()]].concat();
+
+		match element.name.as_str() {
+			"g" | "a" | "switch" | "svg" => {
+				let transform = match element.name.as_str() {
+					// Nested SVG elements establish a new viewport at their position
+					"svg" => transform * DAffine2::from_translation(DVec2::new(element.length("x"), element.length("y"))) * view_box_transform(element),
+					_ => transform,
+				};
+				self.operations.push(Operation::CreateFolder { path: path.clone() });
+				self.operations.push(Operation::SetLayerTransform {
+					path: path.clone(),
+					transform: transform.to_cols_array(),
+				});
+				for child in &element.children {
+					self.import_element(child, &path, &style);
+				}
+			}
+			_ => {
+				let subpath = match shape_subpath(element) {
+					Some(subpath) => subpath,
+					None => return,
+				};
+				let path_style = self.path_style(&style, &subpath);
+				self.operations.push(Operation::AddShape {
+					path: path.clone(),
+					insert_index: -1,
+					transform: transform.to_cols_array(),
+					style: path_style,
+					subpath,
+				});
+			}
+		}
+
+		if let Some(name) = element.attributes.get("id") {
+			self.operations.push(Operation::RenameLayer {
+				layer_path: path.clone(),
+				new_name: name.clone(),
+			});
+		}
+		if let Some(opacity) = element.property("opacity").and_then(parse_length) {
+			self.operations.push(Operation::SetLayerOpacity { path, opacity: opacity.clamp(0., 1.) });
+		}
+	}
+
+	fn path_style(&self, style: &InheritedStyle, subpath: &Subpath) -> PathStyle {
+		let fill = match &style.fill {
+			Paint::None => Fill::None,
+			Paint::Color(color) => Fill::Solid(self.color(*color, style.fill_opacity)),
+			Paint::Gradient(id) => self.gradient_fill(id, subpath, style.fill_opacity).unwrap_or(Fill::None),
+		};
+
+		let stroke_color = match &style.stroke {
+			Paint::None => None,
+			Paint::Color(color) => Some(*color),
+			// Strokes can't be gradients, so they take the color of the first stop instead
+			Paint::Gradient(id) => self.resolve_gradient(id, 0).and_then(|gradient| gradient.stops.first().map(|&(_, color)| color)),
+		};
+		let stroke = stroke_color.filter(|_| style.stroke_width > 0.).map(|color| {
+			let stroke = Stroke::new(self.color(color, style.stroke_opacity), style.stroke_width)
+				.with_line_cap(style.line_cap)
+				.with_line_join(style.line_join)
+				.with_line_join_miter_limit(style.miter_limit)
+				.with_dash_offset(style.dash_offset);
+			stroke.clone().with_dash_lengths(&style.dash_array).unwrap_or(stroke)
+		});
+
+		PathStyle::new(stroke, fill)
+	}
+
+	/// Converts an sRGB color from the SVG into the color space of the document, applying an opacity.
+	fn color(&self, color: Color, opacity: f64) -> Color {
+		let color = color.convert_color_space(ColorSpace::Srgb, self.color_space);
+		Color::from_rgbaf32(color.r(), color.g(), color.b(), color.a() * opacity as f32).unwrap_or(color)
+	}
+
+	fn resolve_gradient(&self, id: &str, depth: usize) -> Option<ResolvedGradient> {
+		let element = self.gradients.get(id)?;
+		let gradient_type = if element.name == "radialGradient" { GradientType::Radial } else { GradientType::Linear };
+		let stops = element
+			.children
+			.iter()
+			.filter(|child| child.name == "stop")
+			.map(|stop| {
+				let offset = stop.attributes.get("offset").and_then(|offset| parse_fraction(offset)).unwrap_or_default().clamp(0., 1.);
+				let color = stop.property("stop-color").and_then(parse_color).unwrap_or(Color::BLACK);
+				let opacity = stop.property("stop-opacity").and_then(parse_length).unwrap_or(1.);
+				(offset, Color::from_rgbaf32(color.r(), color.g(), color.b(), color.a() * opacity as f32).unwrap_or(color))
+			})
+			.collect::<Vec<_>>();
+
+		// A gradient inherits the attributes it doesn't set, and its stops if it has none, from the gradient it references
+		let inherited = element
+			.href_id()
+			.filter(|_| depth < MAX_GRADIENT_REFERENCE_DEPTH)
+			.and_then(|href| self.resolve_gradient(href, depth + 1));
+		Some(match inherited {
+			Some(inherited) => {
+				let mut attributes = inherited.attributes;
+				attributes.extend(element.attributes.clone());
+				ResolvedGradient {
+					gradient_type,
+					attributes,
+					stops: if stops.is_empty() { inherited.stops } else { stops },
+				}
+			}
+			None => ResolvedGradient {
+				gradient_type,
+				attributes: element.attributes.clone(),
+				stops,
+			},
+		})
+	}
+
+	fn gradient_fill(&self, id: &str, subpath: &Subpath, opacity: f64) -> Option<Fill> {
+		let gradient = self.resolve_gradient(id, 0)?;
+		let [min, max] = subpath.bounding_box()?;
+		let size = (max - min).max(DVec2::splat(f64::EPSILON));
+		let user_space = gradient.attributes.get("gradientUnits").map(String::as_str) == Some("userSpaceOnUse");
+
+		// Graphite places gradients relative to the bounding box of the shape, from (0, 0) at its top left to (1, 1) at its bottom right
+		let coordinate = |name: &str, default: &str, axis: usize| {
+			let value = gradient.attributes.get(name).map_or(default, String::as_str);
+			match (user_space, value.trim().ends_with('%')) {
+				(true, true) => parse_fraction(value).map(|fraction| min[axis] + fraction * size[axis]),
+				(true, false) => parse_length(value),
+				(false, _) => parse_fraction(value),
+			}
+			.unwrap_or_default()
+		};
+		let (start, end) = match gradient.gradient_type {
+			GradientType::Linear => (
+				DVec2::new(coordinate("x1", "0%", 0), coordinate("y1", "0%", 1)),
+				DVec2::new(coordinate("x2", "100%", 0), coordinate("y2", "0%", 1)),
+			),
+			// SVG only has linear and radial gradients, so this is the radial one
+			_ => {
+				let center = DVec2::new(coordinate("cx", "50%", 0), coordinate("cy", "50%", 1));
+				(center, center + DVec2::new(coordinate("r", "50%", 0) - if user_space { min.x } else { 0. }, 0.))
+			}
+		};
+
+		let gradient_transform = gradient.attributes.get("gradientTransform").map_or(DAffine2::IDENTITY, |transform| parse_transform(transform));
+		let to_bounds = match user_space {
+			true => DAffine2::from_scale(size.recip()) * DAffine2::from_translation(-min) * gradient_transform,
+			false => gradient_transform,
+		};
+
+		let stops = gradient.stops.iter().map(|&(offset, color)| (offset, self.color(color, opacity))).collect::<Vec<_>>();
+		match stops.as_slice() {
+			[] => None,
+			[(_, color)] => Some(Fill::Solid(*color)),
+			[(_, first), .., (_, last)] => {
+				let mut fill = Gradient::new(
+					to_bounds.transform_point2(start),
+					*first,
+					to_bounds.transform_point2(end),
+					*last,
+					DAffine2::IDENTITY,
+					generate_uuid(),
+					gradient.gradient_type,
+				);
+				fill.positions = stops.iter().map(|&(offset, color)| (offset, Some(color))).collect();
+				fill.spread = gradient
+					.attributes
+					.get("spreadMethod")
+					.and_then(|spread| GradientSpread::from_svg_name(spread.trim()))
+					.unwrap_or_default();
+				Some(Fill::Gradient(fill))
+			}
+		}
+	}
+}
+
+fn collect_gradients(element: &XmlElement, gradients: &mut HashMap<String, XmlElement>) {
+	for child in &element.children {
+		if matches!(child.name.as_str(), "linearGradient" | "radialGradient") {
+			if let Some(id) = child.attributes.get("id") {
+				gradients.insert(id.clone(), child.clone());
+			}
+		}
+		collect_gradients(child, gradients);
+	}
+}
+
+/// The outline of a path or basic shape element, or `None` for any other element.
+fn shape_subpath(element: &XmlElement) -> Option<Subpath> {
+	let length = |name: &str| element.length(name);
+	let subpath = match element.name.as_str() {
+		"path" => {
+			let path = kurbo::BezPath::from_svg(element.attributes.get("d")?).ok()?;
+			Subpath::from(path.iter())
+		}
+		"rect" => {
+			let (x, y, width, height) = (length("x"), length("y"), length("width"), length("height"));
+			let radius = match (element.attributes.contains_key("rx"), element.attributes.contains_key("ry")) {
+				(false, false) => 0.,
+				(true, false) => length("rx"),
+				(false, true) => length("ry"),
+				(true, true) => length("rx").min(length("ry")),
+			};
+			if radius > 0. {
+				Subpath::from_kurbo_shape(&kurbo::RoundedRect::new(x, y, x + width, y + height, radius.min(width.min(height) / 2.)))
+			} else {
+				Subpath::new_rect(DVec2::new(x, y), DVec2::new(x + width, y + height))
+			}
+		}
+		"circle" => {
+			let (center, radius) = (DVec2::new(length("cx"), length("cy")), DVec2::splat(length("r")));
+			Subpath::new_ellipse(center - radius, center + radius)
+		}
+		"ellipse" => {
+			let (center, radius) = (DVec2::new(length("cx"), length("cy")), DVec2::new(length("rx"), length("ry")));
+			Subpath::new_ellipse(center - radius, center + radius)
+		}
+		"line" => Subpath::new_line(DVec2::new(length("x1"), length("y1")), DVec2::new(length("x2"), length("y2"))),
+		"polyline" | "polygon" => {
+			let numbers = parse_numbers(element.attributes.get("points")?);
+			let points = numbers.chunks_exact(2).map(|point| DVec2::new(point[0], point[1]));
+			Subpath::from_points(points, element.name == "polygon")
+		}
+		_ => return None,
+	};
+	Some(subpath)
+}
+
+/// The transform from the user space of an `<svg>` element to its viewport, given by its `viewBox`, `width`, and `height`.
+fn view_box_transform(element: &XmlElement) -> DAffine2 {
+	let view_box = match element.attributes.get("viewBox").map(|view_box| parse_numbers(view_box)) {
+		Some(view_box) if view_box.len() == 4 => view_box,
+		_ => return DAffine2::IDENTITY,
+	};
+	let (origin, size) = (DVec2::new(view_box[0], view_box[1]), DVec2::new(view_box[2], view_box[3]));
+
+	let width = element.attributes.get("width").and_then(|width| parse_length(width)).unwrap_or(size.x);
+	let height = element.attributes.get("height").and_then(|height| parse_length(height)).unwrap_or(size.y);
+	let scale = DVec2::new(width, height) / size;
+	// The default `preserveAspectRatio` scales uniformly to fit and centers the view box
+	let scale = if scale.is_finite() { scale.min_element() } else { 1. };
+	let offset = (DVec2::new(width, height) - size * scale) / 2.;
+
+	DAffine2::from_translation(offset) * DAffine2::from_scale(DVec2::splat(scale)) * DAffine2::from_translation(-origin)
+}
+
+fn parse_transform(value: &str) -> DAffine2 {
+	value.split(')').fold(DAffine2::IDENTITY, |transform, function| {
+		let (name, arguments) = match function.split_once('(') {
+			Some(function) => function,
+			None => return transform,
+		};
+		let name = name.trim_matches(|c: char| c.is_whitespace() || c == ',');
+		let next = match (name, parse_numbers(arguments).as_slice()) {
+			("matrix", &[a, b, c, d, e, f]) => DAffine2::from_cols_array(&[a, b, c, d, e, f]),
+			("translate", &[x]) => DAffine2::from_translation(DVec2::new(x, 0.)),
+			("translate", &[x, y]) => DAffine2::from_translation(DVec2::new(x, y)),
+			("scale", &[scale]) => DAffine2::from_scale(DVec2::splat(scale)),
+			("scale", &[x, y]) => DAffine2::from_scale(DVec2::new(x, y)),
+			("rotate", &[angle]) => DAffine2::from_angle(angle.to_radians()),
+			("rotate", &[angle, x, y]) => DAffine2::from_translation(DVec2::new(x, y)) * DAffine2::from_angle(angle.to_radians()) * DAffine2::from_translation(-DVec2::new(x, y)),
+			("skewX", &[angle]) => DAffine2::from_cols_array(&[1., 0., angle.to_radians().tan(), 1., 0., 0.]),
+			("skewY", &[angle]) => DAffine2::from_cols_array(&[1., angle.to_radians().tan(), 0., 1., 0., 0.]),
+			_ => DAffine2::IDENTITY,
+		};
+		transform * next
+	})
+}
+
+/// Parses a list of numbers separated by whitespace or commas, or by nothing where a sign or a second decimal point starts the next number, like in `10-5` or `.5.5`.
+fn parse_numbers(value: &str) -> Vec<f64> {
+	let mut numbers = Vec::new();
+	let mut push = |number: &str| {
+		if let Ok(number) = number.parse() {
+			numbers.push(number);
+		}
+	};
+
+	let bytes = value.as_bytes();
+	let mut start = None;
+	let (mut has_point, mut has_exponent) = (false, false);
+	for (index, &byte) in bytes.iter().enumerate() {
+		let starts_number = match byte {
+			// A sign only belongs to the current number when it follows the exponent
+			b'+' | b'-' => !(index > 0 && matches!(bytes[index - 1], b'e' | b'E')),
+			b'.' => has_point || has_exponent,
+			b'0'..=b'9' | b'e' | b'E' => false,
+			_ => {
+				if let Some(start) = start.take() {
+					push(&value[start..index]);
+				}
+				continue;
+			}
+		};
+		match start {
+			Some(number_start) if starts_number => {
+				push(&value[number_start..index]);
+				start = Some(index);
+				(has_point, has_exponent) = (false, false);
+			}
+			Some(_) => {}
+			None => {
+				start = Some(index);
+				(has_point, has_exponent) = (false, false);
+			}
+		}
+		match byte {
+			b'.' => has_point = true,
+			b'e' | b'E' => has_exponent = true,
+			_ => {}
+		}
+	}
+	if let Some(start) = start {
+		push(&value[start..]);
+	}
+	numbers
+}
+
+/// Parses a length in pixels, converting absolute units. Percentages aren't supported since they depend on the viewport.
+fn parse_length(value: &str) -> Option<f64> {
+	let value = value.trim();
+	let units = [("px", 1.), ("pt", 4. / 3.), ("pc", 16.), ("mm", 96. / 25.4), ("cm", 96. / 2.54), ("in", 96.)];
+	let (number, scale) = units.iter().find_map(|&(unit, scale)| value.strip_suffix(unit).map(|number| (number, scale))).unwrap_or((value, 1.));
+	number.trim().parse::<f64>().ok().map(|number| number * scale)
+}
+
+/// Parses a number or a percentage as a fraction.
+fn parse_fraction(value: &str) -> Option<f64> {
+	match value.trim().strip_suffix('%') {
+		Some(percentage) => percentage.trim().parse::<f64>().ok().map(|percentage| percentage / 100.),
+		None => value.trim().parse().ok(),
+	}
+}
+
+fn parse_paint(value: &str, inherited: &Paint) -> Option<Paint> {
+	match value {
+		"none" | "transparent" => Some(Paint::None),
+		"inherit" => Some(inherited.clone()),
+		_ => match value.strip_prefix("url(") {
+			Some(reference) => {
+				let id = reference.split(')').next()?.trim().trim_matches(|c| c == '\'' || c == '"');
+				Some(Paint::Gradient(id.trim_start_matches('#').to_string()))
+			}
+			None => parse_color(value).map(Paint::Color),
+		},
+	}
+}
+
+/// Parses a CSS color in hex, `rgb()`, or `rgba()` notation, or one of the basic named colors.
+fn parse_color(value: &str) -> Option<Color> {
+	let value = value.trim().to_lowercase();
+
+	if let Some(hex) = value.strip_prefix('#').filter(|hex| hex.is_ascii()) {
+		return match hex.len() {
+			3 | 4 => {
+				let expanded = hex.chars().flat_map(|digit| [digit, digit]).collect::<String>();
+				Color::from_rgb_str(&expanded).or_else(|| Color::from_rgba_str(&expanded))
+			}
+			_ => Color::from_rgb_str(hex).or_else(|| Color::from_rgba_str(hex)),
+		};
+	}
+
+	if let Some(arguments) = value.strip_prefix("rgba(").or_else(|| value.strip_prefix("rgb(")) {
+		let channels = arguments
+			.trim_end_matches(')')
+			.split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+			.filter(|channel| !channel.is_empty())
+			.collect::<Vec<_>>();
+		let channel = |index: usize| -> Option<f32> {
+			let channel = channels.get(index)?;
+			match channel.strip_suffix('%') {
+				Some(percentage) => percentage.parse::<f32>().ok().map(|percentage| percentage / 100.),
+				None => channel.parse::<f32>().ok().map(|channel| channel / 255.),
+			}
+		};
+		let alpha = match channels.get(3) {
+			Some(alpha) => parse_fraction(alpha)? as f32,
+			None => 1.,
+		};
+		return Color::from_rgbaf32(channel(0)?.clamp(0., 1.), channel(1)?.clamp(0., 1.), channel(2)?.clamp(0., 1.), alpha.clamp(0., 1.));
+	}
+
+	let [red, green, blue] = match value.as_str() {
+		"black" | "currentcolor" => [0, 0, 0],
+		"white" => [255, 255, 255],
+		"red" => [255, 0, 0],
+		"lime" => [0, 255, 0],
+		"green" => [0, 128, 0],
+		"blue" => [0, 0, 255],
+		"yellow" => [255, 255, 0],
+		"cyan" | "aqua" => [0, 255, 255],
+		"magenta" | "fuchsia" => [255, 0, 255],
+		"gray" | "grey" => [128, 128, 128],
+		"silver" => [192, 192, 192],
+		"maroon" => [128, 0, 0],
+		"olive" => [128, 128, 0],
+		"purple" => [128, 0, 128],
+		"teal" => [0, 128, 128],
+		"navy" => [0, 0, 128],
+		"orange" => [255, 165, 0],
+		_ => return None,
+	};
+	Some(Color::from_rgb8(red, green, blue))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn parse(svg: &str) -> Result<XmlElement, EditorError> {
+		XmlParser::new(svg).parse_document()
+	}
+
+	#[test]
+	fn parse_nested_elements() {
+		let root = parse(r#"<?xml version="1.0"?><!-- comment --><svg width="10"><g id='a'><rect x="1"/></g>text</svg>"#).unwrap();
+		assert_eq!(root.name, "svg");
+		assert_eq!(root.attributes.get("width").map(String::as_str), Some("10"));
+		assert_eq!(root.children.len(), 1);
+		assert_eq!(root.children[0].attributes.get("id").map(String::as_str), Some("a"));
+		assert_eq!(root.children[0].children[0].name, "rect");
+	}
+
+	#[test]
+	fn reject_mismatched_closing_tags() {
+		assert!(parse("<svg><g></svg></g>").is_err());
+		assert!(parse("<svg><g></g></svg >").is_ok());
+		assert!(parse("<svg></svgx>").is_err());
+		assert!(parse("</svg>").is_err());
+	}
+
+	#[test]
+	fn reject_truncated_documents() {
+		assert!(parse("<svg><g>").is_err());
+		assert!(parse(r#"<svg width="10"#).is_err());
+		assert!(parse("<svg width=10></svg>").is_err());
+		assert!(parse("").is_err());
+	}
+
+	#[test]
+	fn reject_deeply_nested_elements() {
+		let depth = MAX_ELEMENT_DEPTH + 1;
+		let svg = format!("<svg>{}{}</svg>", "<g>".repeat(depth), "</g>".repeat(depth));
+		assert!(parse(&svg).is_err());
+
+		// Far deeper nesting than would fit on the stack without the limit
+		let depth = 1_000_000;
+		let svg = format!("<svg>{}", "<g>".repeat(depth));
+		assert!(parse(&svg).is_err());
+
+		let depth = MAX_ELEMENT_DEPTH;
+		let svg = format!("<svg>{}{}</svg>", "<g>".repeat(depth), "</g>".repeat(depth));
+		assert!(parse(&svg).is_ok());
+	}
+
+	#[test]
+	fn parse_number_lists() {
+		assert_eq!(parse_numbers("10,20 30\t40"), [10., 20., 30., 40.]);
+		assert_eq!(parse_numbers("10-5"), [10., -5.]);
+		assert_eq!(parse_numbers(".5.5"), [0.5, 0.5]);
+		assert_eq!(parse_numbers("1.5.5-.5"), [1.5, 0.5, -0.5]);
+		assert_eq!(parse_numbers("1e-2-3E+1"), [0.01, -30.]);
+		assert_eq!(parse_numbers("+1 , -2"), [1., -2.]);
+		assert!(parse_numbers("").is_empty());
+	}
+
+	#[test]
+	fn parse_transforms() {
+		let transform = parse_transform("translate(10-5) scale(2)");
+		assert_eq!(transform.transform_point2(DVec2::new(1., 1.)), DVec2::new(12., -3.));
+	}
+
+	#[test]
+	fn import_shapes_into_a_folder() {
+		crate::application::set_uuid_seed(0);
+		let svg = r#"<svg viewBox="0 0 10 10" width="20" height="20"><g><rect width="5" height="5"/><circle r="1"/></g><text>skipped</text></svg>"#;
+		let (operations, transform) = import_svg(svg, vec![1], ColorSpace::Srgb).unwrap();
+		assert!(matches!(&operations[0], Operation::CreateFolder { path } if path == &[1]));
+		assert_eq!(operations.iter().filter(|operation| matches!(operation, Operation::AddShape { .. })).count(), 2);
+		assert_eq!(transform.transform_point2(DVec2::new(10., 10.)), DVec2::new(20., 20.));
+
+		assert!(import_svg("<html></html>", vec![1], ColorSpace::Srgb).is_err());
+	}
+}
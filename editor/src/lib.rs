@@ -9,6 +9,7 @@ extern crate log;
 pub mod application;
 pub mod consts;
 pub mod dispatcher;
+pub mod import_export;
 pub mod messages;
 pub mod test_utils;
 pub mod utility_traits;
@@ -0,0 +1,18 @@
+use crate::messages::portfolio::document::utility_types::batch_export::ExportItemSettings;
+use crate::messages::prelude::*;
+
+use graphene::LayerId;
+
+use serde::{Deserialize, Serialize};
+
+#[impl_message(Message, DialogMessage, BatchExportDialog)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum BatchExportDialogMessage {
+	FileName(String),
+	ArtboardSettings { artboard: LayerId, settings: ExportItemSettings },
+	SliceName { index: usize, name: String },
+	SliceSettings { index: usize, settings: ExportItemSettings },
+	RemoveSlice(usize),
+
+	Submit,
+}
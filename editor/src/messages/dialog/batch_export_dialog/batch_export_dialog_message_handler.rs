@@ -0,0 +1,302 @@
+use crate::consts::FILE_SAVE_SUFFIX;
+use crate::messages::frontend::utility_types::FileType;
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::misc::LayoutTarget;
+use crate::messages::layout::utility_types::widgets::button_widgets::{IconButton, TextButton};
+use crate::messages::layout::utility_types::widgets::input_widgets::{CheckboxInput, DropdownEntryData, DropdownInput, NumberInput, TextInput};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::portfolio::document::utility_types::batch_export::{ExportItemSettings, ExportPreset, ExportSlice};
+use crate::messages::prelude::*;
+
+use graphene::LayerId;
+
+/// A dialog to choose which artboards and export slices are written by a batch export, and the presets each is exported with.
+#[derive(Debug, Clone, Default)]
+pub struct BatchExportDialogMessageHandler {
+	/// The name of the zip archive, without its extension
+	pub file_name: String,
+	/// The artboards in the order they were created, with their names and settings
+	pub artboards: Vec<(LayerId, String, ExportItemSettings)>,
+	pub slices: Vec<ExportSlice>,
+}
+
+impl MessageHandler<BatchExportDialogMessage, ()> for BatchExportDialogMessageHandler {
+	fn process_message(&mut self, message: BatchExportDialogMessage, _data: (), responses: &mut VecDeque<Message>) {
+		match message {
+			BatchExportDialogMessage::FileName(name) => self.file_name = name,
+			BatchExportDialogMessage::ArtboardSettings { artboard, settings } => {
+				if let Some((_, _, artboard_settings)) = self.artboards.iter_mut().find(|(id, _, _)| *id == artboard) {
+					*artboard_settings = settings;
+				}
+			}
+			BatchExportDialogMessage::SliceName { index, name } => {
+				if let Some(slice) = self.slices.get_mut(index) {
+					slice.name = name;
+				}
+			}
+			BatchExportDialogMessage::SliceSettings { index, settings } => {
+				if let Some(slice) = self.slices.get_mut(index) {
+					slice.settings = settings;
+				}
+			}
+			BatchExportDialogMessage::RemoveSlice(index) => {
+				if index < self.slices.len() {
+					self.slices.remove(index);
+				}
+			}
+
+			BatchExportDialogMessage::Submit => responses.push_front(
+				DocumentMessage::BatchExport {
+					zip_name: format!("{}.zip", self.file_name.trim_end_matches(FILE_SAVE_SUFFIX)),
+					artboard_settings: self.artboards.iter().map(|(id, _, settings)| (*id, settings.clone())).collect(),
+					slices: self.slices.clone(),
+				}
+				.into(),
+			),
+		}
+
+		self.register_properties(responses, LayoutTarget::DialogDetails);
+	}
+
+	advertise_actions! {BatchExportDialogUpdate;}
+}
+
+/// The rows of an artboard or export slice: its enabled checkbox, label, and a dropdown to add one of the common presets, then the format, scale, and file name pattern of each of its presets.
+/// Every widget sends the item's updated settings through the callback.
+fn item_settings_layout(label: WidgetHolder, remove: Option<WidgetHolder>, settings: &ExportItemSettings, update: impl Fn(ExportItemSettings) -> Message + Clone + 'static) -> Vec<LayoutGroup> {
+	let separator = || {
+		WidgetHolder::new(Widget::Separator(Separator {
+			separator_type: SeparatorType::Unrelated,
+			direction: SeparatorDirection::Horizontal,
+		}))
+	};
+	let with_presets = {
+		let settings = settings.clone();
+		move |presets: Vec<ExportPreset>| ExportItemSettings { presets, ..settings.clone() }
+	};
+
+	let enabled_update = update.clone();
+	let enabled_settings = settings.clone();
+	let add_preset_entries = vec![ExportPreset::common()
+		.into_iter()
+		.map(|(name, preset)| {
+			let (update, with_presets) = (update.clone(), with_presets.clone());
+			let mut presets = settings.presets.clone();
+			presets.push(preset);
+			DropdownEntryData {
+				label: name.into(),
+				on_update: WidgetCallback::new(move |_| update(with_presets(presets.clone()))),
+				..Default::default()
+			}
+		})
+		.collect()];
+	let mut header = vec![
+		WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+			checked: settings.enabled,
+			tooltip: "Include in the batch export".into(),
+			on_update: WidgetCallback::new(move |checkbox_input: &CheckboxInput| {
+				enabled_update(ExportItemSettings {
+					enabled: checkbox_input.checked,
+					..enabled_settings.clone()
+				})
+			}),
+			..Default::default()
+		})),
+		label,
+		separator(),
+		WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+			selected_index: None,
+			entries: add_preset_entries,
+			tooltip: "Add Export Preset".into(),
+			..Default::default()
+		})),
+	];
+	header.extend(remove);
+	let mut layout = vec![LayoutGroup::Row { widgets: header }];
+
+	let file_types = [(FileType::Png, "PNG"), (FileType::Jpg, "JPG"), (FileType::Webp, "WEBP"), (FileType::Svg, "SVG"), (FileType::Pdf, "PDF")];
+	for (preset_index, preset) in settings.presets.iter().enumerate() {
+		let with_preset = {
+			let (presets, with_presets) = (settings.presets.clone(), with_presets.clone());
+			move |preset: ExportPreset| {
+				let mut presets = presets.clone();
+				presets[preset_index] = preset;
+				with_presets(presets)
+			}
+		};
+
+		let index = file_types.iter().position(|&(file_type, _)| file_type == preset.file_type).unwrap_or_default();
+		let entries = vec![file_types
+			.into_iter()
+			.map(|(file_type, name)| {
+				let (update, with_preset, preset) = (update.clone(), with_preset.clone(), preset.clone());
+				DropdownEntryData {
+					label: name.into(),
+					on_update: WidgetCallback::new(move |_| update(with_preset(ExportPreset { file_type, ..preset.clone() }))),
+					..Default::default()
+				}
+			})
+			.collect()];
+
+		let (scale_update, scale_with_preset, scale_preset) = (update.clone(), with_preset.clone(), preset.clone());
+		let (pattern_update, pattern_with_preset, pattern_preset) = (update.clone(), with_preset, preset.clone());
+		let mut widgets = vec![
+			separator(),
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				selected_index: Some(index as u32),
+				entries,
+				tooltip: "PDFs in a batch export simplify the content PDF can't represent rather than rasterizing it".into(),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(preset.scale_factor),
+				label: "".into(),
+				unit: "×".into(),
+				min: Some(0.5),
+				max: Some(4.),
+				disabled: preset.file_type == FileType::Svg,
+				on_update: WidgetCallback::new(move |number_input: &NumberInput| {
+					scale_update(scale_with_preset(ExportPreset {
+						scale_factor: number_input.value.unwrap(),
+						..scale_preset.clone()
+					}))
+				}),
+				..NumberInput::default()
+			})),
+			WidgetHolder::new(Widget::TextInput(TextInput {
+				value: preset.file_name_pattern.clone(),
+				tooltip: "File name, where {name} is the name of the artboard or slice, {scale} the scale factor, and {ext} the extension of the format. Slashes sort files into folders.".into(),
+				min_width: 160,
+				on_update: WidgetCallback::new(move |text_input: &TextInput| {
+					pattern_update(pattern_with_preset(ExportPreset {
+						file_name_pattern: text_input.value.clone(),
+						..pattern_preset.clone()
+					}))
+				}),
+				..Default::default()
+			})),
+		];
+		if settings.presets.len() > 1 {
+			let (update, with_presets) = (update.clone(), with_presets.clone());
+			let mut presets = settings.presets.clone();
+			presets.remove(preset_index);
+			widgets.push(WidgetHolder::new(Widget::IconButton(IconButton {
+				icon: "Trash".into(),
+				tooltip: "Remove Export Preset".into(),
+				size: 24,
+				on_update: WidgetCallback::new(move |_| update(with_presets(presets.clone()))),
+				..Default::default()
+			})));
+		}
+		layout.push(LayoutGroup::Row { widgets });
+	}
+
+	layout
+}
+
+impl PropertyHolder for BatchExportDialogMessageHandler {
+	fn properties(&self) -> Layout {
+		let separator = || {
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			}))
+		};
+
+		let file_name = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "File Name".into(),
+				table_align: true,
+				..Default::default()
+			})),
+			separator(),
+			WidgetHolder::new(Widget::TextInput(TextInput {
+				value: self.file_name.clone(),
+				on_update: WidgetCallback::new(|text_input: &TextInput| BatchExportDialogMessage::FileName(text_input.value.clone()).into()),
+				..Default::default()
+			})),
+		];
+
+		let mut layout = vec![
+			LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "Batch Export".to_string(),
+					bold: true,
+					..Default::default()
+				}))],
+			},
+			LayoutGroup::Row { widgets: file_name },
+		];
+
+		for (artboard, name, settings) in &self.artboards {
+			let artboard = *artboard;
+			let label = WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: format!("Artboard: {name}"),
+				table_align: true,
+				..Default::default()
+			}));
+			layout.extend(item_settings_layout(label, None, settings, move |settings| {
+				BatchExportDialogMessage::ArtboardSettings { artboard, settings }.into()
+			}));
+		}
+
+		for (index, slice) in self.slices.iter().enumerate() {
+			let name = WidgetHolder::new(Widget::TextInput(TextInput {
+				value: slice.name.clone(),
+				tooltip: "Slice Name".into(),
+				on_update: WidgetCallback::new(move |text_input: &TextInput| {
+					BatchExportDialogMessage::SliceName {
+						index,
+						name: text_input.value.clone(),
+					}
+					.into()
+				}),
+				..Default::default()
+			}));
+			let remove = WidgetHolder::new(Widget::IconButton(IconButton {
+				icon: "Trash".into(),
+				tooltip: "Remove Export Slice".into(),
+				size: 24,
+				on_update: WidgetCallback::new(move |_| BatchExportDialogMessage::RemoveSlice(index).into()),
+				..Default::default()
+			}));
+			layout.extend(item_settings_layout(name, Some(remove), &slice.settings, move |settings| {
+				BatchExportDialogMessage::SliceSettings { index, settings }.into()
+			}));
+		}
+
+		if self.artboards.is_empty() && self.slices.is_empty() {
+			layout.push(LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "This document has no artboards or export slices. Slices can be added from the selection in the Layer menu.".into(),
+					multiline: true,
+					..Default::default()
+				}))],
+			});
+		}
+
+		let button_widgets = vec![
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Export All".to_string(),
+				min_width: 96,
+				emphasized: true,
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![BatchExportDialogMessage::Submit.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Cancel".to_string(),
+				min_width: 96,
+				on_update: WidgetCallback::new(|_| FrontendMessage::DisplayDialogDismiss.into()),
+				..Default::default()
+			})),
+		];
+		layout.push(LayoutGroup::Row { widgets: button_widgets });
+
+		Layout::WidgetLayout(WidgetLayout::new(layout))
+	}
+}
@@ -0,0 +1,7 @@
+mod batch_export_dialog_message;
+mod batch_export_dialog_message_handler;
+
+#[doc(inline)]
+pub use batch_export_dialog_message::{BatchExportDialogMessage, BatchExportDialogMessageDiscriminant};
+#[doc(inline)]
+pub use batch_export_dialog_message_handler::BatchExportDialogMessageHandler;
@@ -0,0 +1,11 @@
+use crate::messages::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+#[impl_message(Message, DialogMessage, CollaborationDialog)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum CollaborationDialogMessage {
+	Session(String),
+
+	Submit,
+}
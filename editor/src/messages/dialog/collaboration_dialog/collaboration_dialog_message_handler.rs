@@ -0,0 +1,91 @@
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::misc::LayoutTarget;
+use crate::messages::layout::utility_types::widgets::button_widgets::TextButton;
+use crate::messages::layout::utility_types::widgets::input_widgets::TextInput;
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::prelude::*;
+
+/// A dialog to join a collaboration session by the ID shared by the editor that started it.
+#[derive(Debug, Clone, Default)]
+pub struct CollaborationDialogMessageHandler {
+	pub session: String,
+	/// The name of the new document that the session's document is opened in
+	pub name: String,
+}
+
+impl MessageHandler<CollaborationDialogMessage, ()> for CollaborationDialogMessageHandler {
+	fn process_message(&mut self, message: CollaborationDialogMessage, _data: (), responses: &mut VecDeque<Message>) {
+		match message {
+			CollaborationDialogMessage::Session(session) => self.session = session.trim().to_string(),
+
+			CollaborationDialogMessage::Submit => {
+				if self.session.is_empty() {
+					return;
+				}
+
+				responses.push_back(PortfolioMessage::NewDocumentWithName { name: self.name.clone() }.into());
+				responses.push_back(DocumentMessage::JoinCollaboration { session: self.session.clone() }.into());
+			}
+		}
+
+		self.register_properties(responses, LayoutTarget::DialogDetails);
+	}
+
+	advertise_actions! {CollaborationDialogUpdate;}
+}
+
+impl PropertyHolder for CollaborationDialogMessageHandler {
+	fn properties(&self) -> Layout {
+		let title = vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+			value: "Join collaboration session".into(),
+			bold: true,
+			..Default::default()
+		}))];
+
+		let session = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Session ID".into(),
+				table_align: true,
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::TextInput(TextInput {
+				value: self.session.clone(),
+				tooltip: "The ID shown by the editor that started the session".into(),
+				on_update: WidgetCallback::new(|text_input: &TextInput| CollaborationDialogMessage::Session(text_input.value.clone()).into()),
+				..Default::default()
+			})),
+		];
+
+		let button_widgets = vec![
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Join".to_string(),
+				min_width: 96,
+				emphasized: true,
+				disabled: self.session.is_empty(),
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![CollaborationDialogMessage::Submit.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Cancel".to_string(),
+				min_width: 96,
+				on_update: WidgetCallback::new(|_| FrontendMessage::DisplayDialogDismiss.into()),
+				..Default::default()
+			})),
+		];
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![
+			LayoutGroup::Row { widgets: title },
+			LayoutGroup::Row { widgets: session },
+			LayoutGroup::Row { widgets: button_widgets },
+		]))
+	}
+}
@@ -0,0 +1,7 @@
+mod collaboration_dialog_message;
+mod collaboration_dialog_message_handler;
+
+#[doc(inline)]
+pub use collaboration_dialog_message::{CollaborationDialogMessage, CollaborationDialogMessageDiscriminant};
+#[doc(inline)]
+pub use collaboration_dialog_message_handler::CollaborationDialogMessageHandler;
@@ -9,19 +9,46 @@ pub enum DialogMessage {
 	// Sub-messages
 	#[remain::unsorted]
 	#[child]
+	BatchExportDialog(BatchExportDialogMessage),
+	#[remain::unsorted]
+	#[child]
+	CollaborationDialog(CollaborationDialogMessage),
+	#[remain::unsorted]
+	#[child]
+	DocumentPropertiesDialog(DocumentPropertiesDialogMessage),
+	#[remain::unsorted]
+	#[child]
 	ExportDialog(ExportDialogMessage),
 	#[remain::unsorted]
 	#[child]
+	KeyboardShortcutsDialog(KeyboardShortcutsDialogMessage),
+	#[remain::unsorted]
+	#[child]
 	NewDocumentDialog(NewDocumentDialogMessage),
 	#[remain::unsorted]
 	#[child]
+	OffsetPathDialog(OffsetPathDialogMessage),
+	#[remain::unsorted]
+	#[child]
 	PreferencesDialog(PreferencesDialogMessage),
+	#[remain::unsorted]
+	#[child]
+	SimplifyPathDialog(SimplifyPathDialogMessage),
+	#[remain::unsorted]
+	#[child]
+	SpriteSheetDialog(SpriteSheetDialogMessage),
+	#[remain::unsorted]
+	#[child]
+	TraceImageDialog(TraceImageDialogMessage),
 
 	// Messages
 	CloseAllDocumentsWithConfirmation,
 	CloseDialogAndThen {
 		followups: Vec<Message>,
 	},
+	DisplayCollaborationSession {
+		session: String,
+	},
 	DisplayDialogError {
 		title: String,
 		description: String,
@@ -30,10 +57,18 @@ pub enum DialogMessage {
 	RequestAboutGraphiteDialogWithLocalizedCommitDate {
 		localized_commit_date: String,
 	},
+	RequestBatchExportDialog,
 	RequestComingSoonDialog {
 		issue: Option<i32>,
 	},
+	RequestDocumentPropertiesDialog,
 	RequestExportDialog,
+	RequestJoinCollaborationDialog,
+	RequestKeyboardShortcutsDialog,
 	RequestNewDocumentDialog,
+	RequestOffsetPathDialog,
 	RequestPreferencesDialog,
+	RequestSimplifyPathDialog,
+	RequestSpriteSheetDialog,
+	RequestTraceImageDialog,
 }
@@ -1,13 +1,27 @@
-use super::simple_dialogs::{self, AboutGraphiteDialog, ComingSoonDialog};
+use super::simple_dialogs::{self, AboutGraphiteDialog, CollaborationSessionDialog, ComingSoonDialog};
+use crate::messages::frontend::utility_types::ExportBounds;
 use crate::messages::layout::utility_types::layout_widget::PropertyHolder;
 use crate::messages::layout::utility_types::misc::LayoutTarget;
 use crate::messages::prelude::*;
 
+use graphene::layers::vector::subpath::Subpath;
+use graphene::LayerId;
+
+use glam::DAffine2;
+
 #[derive(Debug, Default, Clone)]
 pub struct DialogMessageHandler {
+	batch_export_dialog: BatchExportDialogMessageHandler,
+	collaboration_dialog: CollaborationDialogMessageHandler,
+	document_properties_dialog: DocumentPropertiesDialogMessageHandler,
 	export_dialog: ExportDialogMessageHandler,
+	keyboard_shortcuts_dialog: KeyboardShortcutsDialogMessageHandler,
 	new_document_dialog: NewDocumentDialogMessageHandler,
+	offset_path_dialog: OffsetPathDialogMessageHandler,
 	preferences_dialog: PreferencesDialogMessageHandler,
+	simplify_path_dialog: SimplifyPathDialogMessageHandler,
+	sprite_sheet_dialog: SpriteSheetDialogMessageHandler,
+	trace_image_dialog: TraceImageDialogMessageHandler,
 }
 
 impl MessageHandler<DialogMessage, (&PortfolioMessageHandler, &PreferencesMessageHandler)> for DialogMessageHandler {
@@ -15,12 +29,28 @@ impl MessageHandler<DialogMessage, (&PortfolioMessageHandler, &PreferencesMessag
 	fn process_message(&mut self, message: DialogMessage, (portfolio, preferences): (&PortfolioMessageHandler, &PreferencesMessageHandler), responses: &mut VecDeque<Message>) {
 		#[remain::sorted]
 		match message {
+			#[remain::unsorted]
+			DialogMessage::BatchExportDialog(message) => self.batch_export_dialog.process_message(message, (), responses),
+			#[remain::unsorted]
+			DialogMessage::CollaborationDialog(message) => self.collaboration_dialog.process_message(message, (), responses),
+			#[remain::unsorted]
+			DialogMessage::DocumentPropertiesDialog(message) => self.document_properties_dialog.process_message(message, (), responses),
 			#[remain::unsorted]
 			DialogMessage::ExportDialog(message) => self.export_dialog.process_message(message, (), responses),
 			#[remain::unsorted]
+			DialogMessage::KeyboardShortcutsDialog(message) => self.keyboard_shortcuts_dialog.process_message(message, (), responses),
+			#[remain::unsorted]
 			DialogMessage::NewDocumentDialog(message) => self.new_document_dialog.process_message(message, (), responses),
 			#[remain::unsorted]
+			DialogMessage::OffsetPathDialog(message) => self.offset_path_dialog.process_message(message, (), responses),
+			#[remain::unsorted]
 			DialogMessage::PreferencesDialog(message) => self.preferences_dialog.process_message(message, preferences, responses),
+			#[remain::unsorted]
+			DialogMessage::SimplifyPathDialog(message) => self.simplify_path_dialog.process_message(message, (), responses),
+			#[remain::unsorted]
+			DialogMessage::SpriteSheetDialog(message) => self.sprite_sheet_dialog.process_message(message, (), responses),
+			#[remain::unsorted]
+			DialogMessage::TraceImageDialog(message) => self.trace_image_dialog.process_message(message, (), responses),
 
 			DialogMessage::CloseAllDocumentsWithConfirmation => {
 				let dialog = simple_dialogs::CloseAllDocumentsDialog;
@@ -33,6 +63,11 @@ impl MessageHandler<DialogMessage, (&PortfolioMessageHandler, &PreferencesMessag
 					responses.push_back(message);
 				}
 			}
+			DialogMessage::DisplayCollaborationSession { session } => {
+				let dialog = CollaborationSessionDialog { session };
+				dialog.register_properties(responses, LayoutTarget::DialogDetails);
+				responses.push_back(FrontendMessage::DisplayDialog { icon: "Copy".to_string() }.into());
+			}
 			DialogMessage::DisplayDialogError { title, description } => {
 				let dialog = simple_dialogs::ErrorDialog { title, description };
 				dialog.register_properties(responses, LayoutTarget::DialogDetails);
@@ -52,45 +87,78 @@ impl MessageHandler<DialogMessage, (&PortfolioMessageHandler, &PreferencesMessag
 				about_graphite.register_properties(responses, LayoutTarget::DialogDetails);
 				responses.push_back(FrontendMessage::DisplayDialog { icon: "GraphiteLogo".to_string() }.into());
 			}
+			DialogMessage::RequestBatchExportDialog => {
+				if let Some(document) = portfolio.active_document() {
+					let artboards = document
+						.artboard_names()
+						.into_iter()
+						.map(|(artboard, name)| (artboard, name, document.artboard_export_settings.get(&artboard).cloned().unwrap_or_default()))
+						.collect();
+
+					self.batch_export_dialog = BatchExportDialogMessageHandler {
+						file_name: document.name.clone(),
+						artboards,
+						slices: document.export_slices.clone(),
+					};
+					self.batch_export_dialog.register_properties(responses, LayoutTarget::DialogDetails);
+					responses.push_back(FrontendMessage::DisplayDialog { icon: "File".to_string() }.into());
+				}
+			}
 			DialogMessage::RequestComingSoonDialog { issue } => {
 				let coming_soon = ComingSoonDialog { issue };
 				coming_soon.register_properties(responses, LayoutTarget::DialogDetails);
 				responses.push_back(FrontendMessage::DisplayDialog { icon: "Warning".to_string() }.into());
 			}
+			DialogMessage::RequestDocumentPropertiesDialog => {
+				if let Some(document) = portfolio.active_document() {
+					self.document_properties_dialog = DocumentPropertiesDialogMessageHandler {
+						metadata: document.metadata.clone(),
+						keywords: document.metadata.keywords.join(", "),
+						transform_settings: document.transform_settings,
+						unit: document.unit,
+					};
+					self.document_properties_dialog.register_properties(responses, LayoutTarget::DialogDetails);
+					responses.push_back(FrontendMessage::DisplayDialog { icon: "File".to_string() }.into());
+				}
+			}
 			DialogMessage::RequestExportDialog => {
 				if let Some(document) = portfolio.active_document() {
-					let artboard_handler = &document.artboard_message_handler;
-					let mut index = 0;
-					let artboards = artboard_handler
-						.artboard_ids
-						.iter()
-						.rev()
-						.filter_map(|&artboard| artboard_handler.artboards_graphene_document.layer(&[artboard]).ok().map(|layer| (artboard, layer)))
-						.map(|(artboard, layer)| {
-							(
-								artboard,
-								format!(
-									"Artboard: {}",
-									layer.name.clone().unwrap_or_else(|| {
-										index += 1;
-										format!("Untitled {index}")
-									})
-								),
-							)
-						})
-						.collect();
+					let artboards = document.artboard_names().into_iter().map(|(artboard, name)| (artboard, format!("Artboard: {name}"))).collect();
+					let slices = document.export_slices.iter().map(|slice| format!("Slice: {}", slice.name)).collect();
 
 					self.export_dialog = ExportDialogMessageHandler {
 						file_name: document.name.clone(),
 						scale_factor: 1.,
+						unit: document.unit,
+						supersampling: 1,
+						quality: 90,
+						rasterize_unsupported: true,
 						artboards,
+						slices,
 						has_selection: document.selected_layers().next().is_some(),
+						color_space: document.graphene_document.specified_color_space(),
 						..Default::default()
 					};
 					self.export_dialog.register_properties(responses, LayoutTarget::DialogDetails);
 					responses.push_back(FrontendMessage::DisplayDialog { icon: "File".to_string() }.into());
 				}
 			}
+			DialogMessage::RequestJoinCollaborationDialog => {
+				self.collaboration_dialog = CollaborationDialogMessageHandler {
+					session: String::new(),
+					name: portfolio.generate_new_document_name(),
+				};
+				self.collaboration_dialog.register_properties(responses, LayoutTarget::DialogDetails);
+				responses.push_back(FrontendMessage::DisplayDialog { icon: "File".to_string() }.into());
+			}
+			DialogMessage::RequestKeyboardShortcutsDialog => {
+				self.keyboard_shortcuts_dialog = KeyboardShortcutsDialogMessageHandler {
+					keymap: preferences.keymap.clone(),
+					..Default::default()
+				};
+				self.keyboard_shortcuts_dialog.register_properties(responses, LayoutTarget::DialogDetails);
+				responses.push_back(FrontendMessage::DisplayDialog { icon: "Settings".to_string() }.into());
+			}
 			DialogMessage::RequestNewDocumentDialog => {
 				self.new_document_dialog = NewDocumentDialogMessageHandler {
 					name: portfolio.generate_new_document_name(),
@@ -100,18 +168,107 @@ impl MessageHandler<DialogMessage, (&PortfolioMessageHandler, &PreferencesMessag
 				self.new_document_dialog.register_properties(responses, LayoutTarget::DialogDetails);
 				responses.push_back(FrontendMessage::DisplayDialog { icon: "File".to_string() }.into());
 			}
+			DialogMessage::RequestOffsetPathDialog => {
+				if let Some(document) = portfolio.active_document() {
+					let shapes = selected_shapes(document);
+					if shapes.is_empty() {
+						return;
+					}
+
+					responses.push_back(DocumentMessage::StartTransaction.into());
+					self.offset_path_dialog = OffsetPathDialogMessageHandler {
+						distance: 10.,
+						shapes,
+						..Default::default()
+					};
+					self.offset_path_dialog.preview(responses);
+					self.offset_path_dialog.register_properties(responses, LayoutTarget::DialogDetails);
+					responses.push_back(FrontendMessage::DisplayDialog { icon: "VectorPathTool".to_string() }.into());
+				}
+			}
 			DialogMessage::RequestPreferencesDialog => {
 				self.preferences_dialog = PreferencesDialogMessageHandler {};
 				self.preferences_dialog.register_properties(responses, LayoutTarget::DialogDetails, preferences);
 				responses.push_back(FrontendMessage::DisplayDialog { icon: "Settings".to_string() }.into());
 			}
+			DialogMessage::RequestSimplifyPathDialog => {
+				if let Some(document) = portfolio.active_document() {
+					let shapes = selected_shapes(document);
+					if shapes.is_empty() {
+						return;
+					}
+
+					responses.push_back(DocumentMessage::StartTransaction.into());
+					self.simplify_path_dialog = SimplifyPathDialogMessageHandler {
+						tolerance: 1.,
+						shapes,
+						..Default::default()
+					};
+					self.simplify_path_dialog.preview(responses);
+					self.simplify_path_dialog.register_properties(responses, LayoutTarget::DialogDetails);
+					responses.push_back(FrontendMessage::DisplayDialog { icon: "VectorPathTool".to_string() }.into());
+				}
+			}
+			DialogMessage::RequestSpriteSheetDialog => {
+				if let Some(document) = portfolio.active_document() {
+					let artboards = document.artboard_names().into_iter().map(|(artboard, name)| (ExportBounds::Artboard(artboard), name, true));
+					let slices = document.export_slices.iter().enumerate().map(|(index, slice)| (ExportBounds::Slice(index), slice.name.clone(), true));
+
+					self.sprite_sheet_dialog = SpriteSheetDialogMessageHandler {
+						file_name: document.name.clone(),
+						sprites: artboards.chain(slices).collect(),
+						..Default::default()
+					};
+					self.sprite_sheet_dialog.register_properties(responses, LayoutTarget::DialogDetails);
+					responses.push_back(FrontendMessage::DisplayDialog { icon: "File".to_string() }.into());
+				}
+			}
+			DialogMessage::RequestTraceImageDialog => {
+				if let Some(document) = portfolio.active_document() {
+					// Linked images which couldn't be read have no pixels to trace
+					let selected_image = document.selected_layers_sorted().into_iter().find(|path| {
+						document
+							.graphene_document
+							.layer(path)
+							.and_then(|layer| layer.as_image())
+							.map_or(false, |image| !image.image_data.is_empty())
+					});
+					let layer_path = match selected_image {
+						Some(layer_path) => layer_path.to_vec(),
+						None => return,
+					};
+
+					self.trace_image_dialog = TraceImageDialogMessageHandler {
+						layer_path,
+						options: self.trace_image_dialog.options,
+					};
+					self.trace_image_dialog.register_properties(responses, LayoutTarget::DialogDetails);
+					responses.push_back(FrontendMessage::DisplayDialog { icon: "NodeImage".to_string() }.into());
+				}
+			}
 		}
 	}
 
 	advertise_actions!(DialogMessageDiscriminant;
 		CloseAllDocumentsWithConfirmation,
+		RequestDocumentPropertiesDialog,
 		RequestExportDialog,
+		RequestBatchExportDialog,
 		RequestNewDocumentDialog,
 		RequestPreferencesDialog,
+		RequestKeyboardShortcutsDialog,
+		RequestSpriteSheetDialog,
 	);
 }
+
+/// The selected vector layers of the document with their paths and the transforms from the layers to document space, to be edited by a dialog.
+fn selected_shapes(document: &DocumentMessageHandler) -> Vec<(Vec<LayerId>, Subpath, DAffine2)> {
+	document
+		.selected_layers()
+		.filter_map(|path| {
+			let subpath = document.graphene_document.layer(path).ok()?.as_subpath_copy()?;
+			let transform = document.graphene_document.root.transform.inverse() * document.graphene_document.multiply_transforms(path).ok()?;
+			Some((path.to_vec(), subpath, transform))
+		})
+		.collect()
+}
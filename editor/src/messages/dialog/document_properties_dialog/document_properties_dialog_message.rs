@@ -0,0 +1,23 @@
+use crate::messages::portfolio::document::utility_types::document_unit::DocumentUnit;
+use crate::messages::portfolio::document::utility_types::transform_settings::TransformOrigin;
+use crate::messages::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+#[impl_message(Message, DialogMessage, DocumentPropertiesDialog)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum DocumentPropertiesDialogMessage {
+	Title(String),
+	Author(String),
+	Description(String),
+	License(String),
+	Keywords(String),
+
+	Unit(DocumentUnit),
+	LockAspectRatio(bool),
+	TransformOrigin(TransformOrigin),
+	NudgeDistance(f64),
+	BigNudgeDistance(f64),
+
+	Submit,
+}
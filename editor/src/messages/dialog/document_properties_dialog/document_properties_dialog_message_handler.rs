@@ -0,0 +1,263 @@
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::misc::LayoutTarget;
+use crate::messages::layout::utility_types::widgets::button_widgets::TextButton;
+use crate::messages::layout::utility_types::widgets::input_widgets::{CheckboxInput, DropdownEntryData, DropdownInput, NumberInput, RadioEntryData, RadioInput, TextAreaInput, TextInput};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::portfolio::document::utility_types::document_metadata::DocumentMetadata;
+use crate::messages::portfolio::document::utility_types::document_unit::DocumentUnit;
+use crate::messages::portfolio::document::utility_types::transform_settings::{TransformOrigin, TransformSettings};
+use crate::messages::prelude::*;
+
+/// A dialog to edit the metadata of the active document, which is embedded in the files it's exported to, its unit, and the settings its layers are transformed with.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentPropertiesDialogMessageHandler {
+	pub metadata: DocumentMetadata,
+	/// The keywords as they're typed, separated by commas
+	pub keywords: String,
+	pub transform_settings: TransformSettings,
+	pub unit: DocumentUnit,
+}
+
+impl MessageHandler<DocumentPropertiesDialogMessage, ()> for DocumentPropertiesDialogMessageHandler {
+	fn process_message(&mut self, message: DocumentPropertiesDialogMessage, _data: (), responses: &mut VecDeque<Message>) {
+		match message {
+			DocumentPropertiesDialogMessage::Title(title) => self.metadata.title = title,
+			DocumentPropertiesDialogMessage::Author(author) => self.metadata.author = author,
+			DocumentPropertiesDialogMessage::Description(description) => self.metadata.description = description,
+			DocumentPropertiesDialogMessage::License(license) => self.metadata.license = license,
+			DocumentPropertiesDialogMessage::Keywords(keywords) => self.keywords = keywords,
+
+			DocumentPropertiesDialogMessage::Unit(unit) => self.unit = unit,
+			DocumentPropertiesDialogMessage::LockAspectRatio(lock_aspect_ratio) => self.transform_settings.lock_aspect_ratio = lock_aspect_ratio,
+			DocumentPropertiesDialogMessage::TransformOrigin(transform_origin) => self.transform_settings.transform_origin = transform_origin,
+			DocumentPropertiesDialogMessage::NudgeDistance(nudge_distance) => self.transform_settings.nudge_distance = nudge_distance,
+			DocumentPropertiesDialogMessage::BigNudgeDistance(big_nudge_distance) => self.transform_settings.big_nudge_distance = big_nudge_distance,
+
+			DocumentPropertiesDialogMessage::Submit => {
+				let metadata = DocumentMetadata {
+					title: self.metadata.title.trim().to_string(),
+					author: self.metadata.author.trim().to_string(),
+					description: self.metadata.description.trim().to_string(),
+					license: self.metadata.license.trim().to_string(),
+					keywords: DocumentMetadata::parse_keywords(&self.keywords),
+				};
+				responses.push_front(DocumentMessage::SetDocumentMetadata { metadata }.into());
+				responses.push_back(
+					DocumentMessage::SetTransformSettings {
+						transform_settings: self.transform_settings,
+					}
+					.into(),
+				);
+				responses.push_back(DocumentMessage::SetDocumentUnit { unit: self.unit }.into());
+			}
+		}
+
+		self.register_properties(responses, LayoutTarget::DialogDetails);
+	}
+
+	advertise_actions! {DocumentPropertiesDialogUpdate;}
+}
+
+impl PropertyHolder for DocumentPropertiesDialogMessageHandler {
+	fn properties(&self) -> Layout {
+		let separator = || {
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			}))
+		};
+		let label = |value: &str| {
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: value.into(),
+				table_align: true,
+				..Default::default()
+			}))
+		};
+
+		let title = vec![
+			label("Title"),
+			separator(),
+			WidgetHolder::new(Widget::TextInput(TextInput {
+				value: self.metadata.title.clone(),
+				on_update: WidgetCallback::new(|text_input: &TextInput| DocumentPropertiesDialogMessage::Title(text_input.value.clone()).into()),
+				..Default::default()
+			})),
+		];
+
+		let author = vec![
+			label("Author"),
+			separator(),
+			WidgetHolder::new(Widget::TextInput(TextInput {
+				value: self.metadata.author.clone(),
+				on_update: WidgetCallback::new(|text_input: &TextInput| DocumentPropertiesDialogMessage::Author(text_input.value.clone()).into()),
+				..Default::default()
+			})),
+		];
+
+		let description = vec![
+			label("Description"),
+			separator(),
+			WidgetHolder::new(Widget::TextAreaInput(TextAreaInput {
+				value: self.metadata.description.clone(),
+				on_update: WidgetCallback::new(|text_area_input: &TextAreaInput| DocumentPropertiesDialogMessage::Description(text_area_input.value.clone()).into()),
+				..Default::default()
+			})),
+		];
+
+		let license = vec![
+			label("License"),
+			separator(),
+			WidgetHolder::new(Widget::TextInput(TextInput {
+				value: self.metadata.license.clone(),
+				tooltip: "The name of the license the artwork is shared under, like \"CC BY 4.0\", or a link to it".into(),
+				on_update: WidgetCallback::new(|text_input: &TextInput| DocumentPropertiesDialogMessage::License(text_input.value.clone()).into()),
+				..Default::default()
+			})),
+		];
+
+		let keywords = vec![
+			label("Keywords"),
+			separator(),
+			WidgetHolder::new(Widget::TextInput(TextInput {
+				value: self.keywords.clone(),
+				tooltip: "Separated by commas".into(),
+				on_update: WidgetCallback::new(|text_input: &TextInput| DocumentPropertiesDialogMessage::Keywords(text_input.value.clone()).into()),
+				..Default::default()
+			})),
+		];
+
+		let note = vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+			value: "These properties are embedded in SVG and PNG exports of the document.".into(),
+			multiline: true,
+			..Default::default()
+		}))];
+
+		let unit_entries = vec![DocumentUnit::ALL
+			.into_iter()
+			.map(|unit| DropdownEntryData {
+				label: unit.name().into(),
+				on_update: WidgetCallback::new(move |_| DocumentPropertiesDialogMessage::Unit(unit).into()),
+				..Default::default()
+			})
+			.collect()];
+
+		let units = vec![
+			label("Units"),
+			separator(),
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				selected_index: DocumentUnit::ALL.iter().position(|&unit| unit == self.unit).map(|index| index as u32),
+				entries: unit_entries,
+				tooltip: "The unit that the rulers and the properties of layers measure lengths in, which can also be typed after any length like \"12mm\"".into(),
+				..Default::default()
+			})),
+		];
+
+		let transform_heading = vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+			value: "Transform".to_string(),
+			bold: true,
+			..Default::default()
+		}))];
+
+		let lock_aspect_ratio = vec![
+			label("Lock Aspect Ratio"),
+			separator(),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.transform_settings.lock_aspect_ratio,
+				tooltip: "Resizing and scaling keeps the proportions of the selection unless Shift is held".into(),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| DocumentPropertiesDialogMessage::LockAspectRatio(checkbox_input.checked).into()),
+				..Default::default()
+			})),
+		];
+
+		let transform_origin_entries = [
+			(TransformOrigin::OppositeEdge, "Opposite Edge", "Resizing keeps the opposite edge in place unless Alt is held"),
+			(TransformOrigin::Pivot, "Pivot", "Resizing keeps the pivot in place unless Alt is held"),
+		]
+		.into_iter()
+		.map(|(transform_origin, label, tooltip)| RadioEntryData {
+			label: label.into(),
+			tooltip: tooltip.into(),
+			on_update: WidgetCallback::new(move |_| DocumentPropertiesDialogMessage::TransformOrigin(transform_origin).into()),
+			..RadioEntryData::default()
+		})
+		.collect();
+
+		let transform_origin = vec![
+			label("Resize Origin"),
+			separator(),
+			WidgetHolder::new(Widget::RadioInput(RadioInput {
+				selected_index: self.transform_settings.transform_origin as u32,
+				entries: transform_origin_entries,
+			})),
+		];
+
+		let unit = self.unit;
+		let nudge_distances = vec![
+			label("Nudge Distance"),
+			separator(),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(unit.from_pixels(self.transform_settings.nudge_distance)),
+				label: "Arrow".into(),
+				unit: unit.number_input_unit(),
+				min: Some(0.),
+				on_update: WidgetCallback::new(move |number_input: &NumberInput| DocumentPropertiesDialogMessage::NudgeDistance(unit.to_pixels(number_input.value.unwrap())).into()),
+				..NumberInput::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Related,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(unit.from_pixels(self.transform_settings.big_nudge_distance)),
+				label: "Shift".into(),
+				unit: unit.number_input_unit(),
+				min: Some(0.),
+				on_update: WidgetCallback::new(move |number_input: &NumberInput| DocumentPropertiesDialogMessage::BigNudgeDistance(unit.to_pixels(number_input.value.unwrap())).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let button_widgets = vec![
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "OK".to_string(),
+				min_width: 96,
+				emphasized: true,
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![DocumentPropertiesDialogMessage::Submit.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Cancel".to_string(),
+				min_width: 96,
+				on_update: WidgetCallback::new(|_| FrontendMessage::DisplayDialogDismiss.into()),
+				..Default::default()
+			})),
+		];
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![
+			LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "Document Properties".to_string(),
+					bold: true,
+					..Default::default()
+				}))],
+			},
+			LayoutGroup::Row { widgets: title },
+			LayoutGroup::Row { widgets: author },
+			LayoutGroup::Row { widgets: description },
+			LayoutGroup::Row { widgets: license },
+			LayoutGroup::Row { widgets: keywords },
+			LayoutGroup::Row { widgets: note },
+			LayoutGroup::Row { widgets: units },
+			LayoutGroup::Row { widgets: transform_heading },
+			LayoutGroup::Row { widgets: lock_aspect_ratio },
+			LayoutGroup::Row { widgets: transform_origin },
+			LayoutGroup::Row { widgets: nudge_distances },
+			LayoutGroup::Row { widgets: button_widgets },
+		]))
+	}
+}
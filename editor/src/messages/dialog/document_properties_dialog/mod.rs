@@ -0,0 +1,7 @@
+mod document_properties_dialog_message;
+mod document_properties_dialog_message_handler;
+
+#[doc(inline)]
+pub use document_properties_dialog_message::{DocumentPropertiesDialogMessage, DocumentPropertiesDialogMessageDiscriminant};
+#[doc(inline)]
+pub use document_properties_dialog_message_handler::DocumentPropertiesDialogMessageHandler;
@@ -1,4 +1,8 @@
+use crate::import_export::dxf::DxfCurves;
 use crate::messages::frontend::utility_types::{ExportBounds, FileType};
+use crate::messages::portfolio::document::utility_types::animation_encoding::Dithering;
+use crate::messages::portfolio::document::utility_types::image_encoding::ChromaSubsampling;
+use crate::messages::portfolio::document::utility_types::svg_export::SvgStyleOutput;
 use crate::messages::prelude::*;
 
 use serde::{Deserialize, Serialize};
@@ -9,7 +13,25 @@ pub enum ExportDialogMessage {
 	FileName(String),
 	FileType(FileType),
 	ScaleFactor(f64),
+	Supersampling(u32),
+	Quality(u8),
+	ChromaSubsampling(ChromaSubsampling),
 	ExportBounds(ExportBounds),
+	RasterizeUnsupported(bool),
+	TransparentBackground(bool),
+	TrimToContent(bool),
+	ConvertToSrgb(bool),
+	FirstFrame(u32),
+	LastFrame(u32),
+	FrameRate(f64),
+	Plays(u32),
+	Colors(u16),
+	Dithering(Dithering),
+	DxfCurves(DxfCurves),
+	SvgPrecision(Option<u32>),
+	SvgFlattenTransforms(bool),
+	SvgStyleOutput(SvgStyleOutput),
+	SvgLayerNameIds(bool),
 
 	Submit,
 }
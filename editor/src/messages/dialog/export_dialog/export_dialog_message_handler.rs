@@ -1,11 +1,17 @@
+use crate::import_export::dxf::DxfCurves;
 use crate::messages::frontend::utility_types::{ExportBounds, FileType};
 use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
 use crate::messages::layout::utility_types::misc::LayoutTarget;
 use crate::messages::layout::utility_types::widgets::button_widgets::TextButton;
-use crate::messages::layout::utility_types::widgets::input_widgets::{DropdownEntryData, DropdownInput, NumberInput, RadioEntryData, RadioInput, TextInput};
+use crate::messages::layout::utility_types::widgets::input_widgets::{CheckboxInput, DropdownEntryData, DropdownInput, NumberInput, RadioEntryData, RadioInput, TextInput};
 use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::portfolio::document::utility_types::animation_encoding::{AnimationSettings, Dithering};
+use crate::messages::portfolio::document::utility_types::document_unit::{DocumentUnit, PIXELS_PER_INCH};
+use crate::messages::portfolio::document::utility_types::image_encoding::ChromaSubsampling;
+use crate::messages::portfolio::document::utility_types::svg_export::{SvgExportOptions, SvgStyleOutput};
 use crate::messages::prelude::*;
 
+use graphene::color::ColorSpace;
 use graphene::LayerId;
 
 /// A dialog to allow users to customize their file export.
@@ -14,8 +20,33 @@ pub struct ExportDialogMessageHandler {
 	pub file_name: String,
 	pub file_type: FileType,
 	pub scale_factor: f64,
+	/// The unit of the document, which shows the scale factor as a resolution in dots per inch when it measures a printed size
+	pub unit: DocumentUnit,
+	/// The number of samples taken along each axis for every exported pixel, which are averaged to smooth jagged edges
+	pub supersampling: u32,
+	/// The quality from 1 to 100 of JPEG and WebP exports, trading detail for a smaller file
+	pub quality: u8,
+	pub chroma_subsampling: ChromaSubsampling,
 	pub bounds: ExportBounds,
+	/// Whether the artboards and the background around them are left out, keeping only the artwork
+	pub transparent_background: bool,
+	/// Whether the exported region is shrunk to fit the artwork within it
+	pub trim_to_content: bool,
+	/// The color space the document's colors are specified in, which exported files are tagged with
+	pub color_space: ColorSpace,
+	/// Whether wide-gamut colors are converted to sRGB, for apps and viewers which ignore color profiles
+	pub convert_to_srgb: bool,
+	/// Whether a PDF export rasterizes the content PDF can't represent, rather than simplifying or leaving it out
+	pub rasterize_unsupported: bool,
+	/// The frame range, timing, and palette of GIF, APNG, and Lottie exports
+	pub animation: AnimationSettings,
+	/// Whether the curves of DXF exports are approximated by arcs or kept exact as splines
+	pub dxf_curves: DxfCurves,
+	/// How the markup of SVG exports is written for the code and tools consuming it
+	pub svg_options: SvgExportOptions,
 	pub artboards: HashMap<LayerId, String>,
+	/// The names of the document's export slices, in order
+	pub slices: Vec<String>,
 	pub has_selection: bool,
 }
 
@@ -25,14 +56,43 @@ impl MessageHandler<ExportDialogMessage, ()> for ExportDialogMessageHandler {
 			ExportDialogMessage::FileName(name) => self.file_name = name,
 			ExportDialogMessage::FileType(export_type) => self.file_type = export_type,
 			ExportDialogMessage::ScaleFactor(x) => self.scale_factor = x,
+			ExportDialogMessage::Supersampling(samples) => self.supersampling = samples,
+			ExportDialogMessage::Quality(quality) => self.quality = quality,
+			ExportDialogMessage::ChromaSubsampling(chroma_subsampling) => self.chroma_subsampling = chroma_subsampling,
 			ExportDialogMessage::ExportBounds(export_area) => self.bounds = export_area,
+			ExportDialogMessage::RasterizeUnsupported(rasterize_unsupported) => self.rasterize_unsupported = rasterize_unsupported,
+			ExportDialogMessage::TransparentBackground(transparent_background) => self.transparent_background = transparent_background,
+			ExportDialogMessage::TrimToContent(trim_to_content) => self.trim_to_content = trim_to_content,
+			ExportDialogMessage::ConvertToSrgb(convert_to_srgb) => self.convert_to_srgb = convert_to_srgb,
+			ExportDialogMessage::FirstFrame(frame) => self.animation.first_frame = frame,
+			ExportDialogMessage::LastFrame(frame) => self.animation.last_frame = frame,
+			ExportDialogMessage::FrameRate(frame_rate) => self.animation.frame_rate = frame_rate,
+			ExportDialogMessage::Plays(plays) => self.animation.plays = plays,
+			ExportDialogMessage::Colors(colors) => self.animation.colors = colors,
+			ExportDialogMessage::Dithering(dithering) => self.animation.dithering = dithering,
+			ExportDialogMessage::DxfCurves(dxf_curves) => self.dxf_curves = dxf_curves,
+			ExportDialogMessage::SvgPrecision(precision) => self.svg_options.precision = precision,
+			ExportDialogMessage::SvgFlattenTransforms(flatten_transforms) => self.svg_options.flatten_transforms = flatten_transforms,
+			ExportDialogMessage::SvgStyleOutput(style_output) => self.svg_options.style_output = style_output,
+			ExportDialogMessage::SvgLayerNameIds(layer_name_ids) => self.svg_options.layer_name_ids = layer_name_ids,
 
 			ExportDialogMessage::Submit => responses.push_front(
 				DocumentMessage::ExportDocument {
 					file_name: self.file_name.clone(),
 					file_type: self.file_type,
 					scale_factor: self.scale_factor,
+					supersampling: self.supersampling,
+					quality: self.quality,
+					chroma_subsampling: self.chroma_subsampling,
 					bounds: self.bounds,
+					// JPEGs can't be transparent
+					transparent_background: self.transparent_background && self.file_type != FileType::Jpg,
+					trim_to_content: self.trim_to_content,
+					rasterize_unsupported: self.rasterize_unsupported,
+					animation: self.animation,
+					dxf_curves: self.dxf_curves,
+					convert_to_srgb: self.convert_to_srgb,
+					svg_options: self.svg_options,
 				}
 				.into(),
 			),
@@ -63,14 +123,24 @@ impl PropertyHolder for ExportDialogMessageHandler {
 			})),
 		];
 
-		let entries = [(FileType::Png, "PNG"), (FileType::Jpg, "JPG"), (FileType::Svg, "SVG")]
-			.into_iter()
-			.map(|(val, name)| RadioEntryData {
-				label: name.into(),
-				on_update: WidgetCallback::new(move |_| ExportDialogMessage::FileType(val).into()),
-				..RadioEntryData::default()
-			})
-			.collect();
+		let entries = [
+			(FileType::Png, "PNG"),
+			(FileType::Jpg, "JPG"),
+			(FileType::Webp, "WEBP"),
+			(FileType::Svg, "SVG"),
+			(FileType::Pdf, "PDF"),
+			(FileType::Gif, "GIF"),
+			(FileType::Apng, "APNG"),
+			(FileType::Lottie, "Lottie"),
+			(FileType::Dxf, "DXF"),
+		]
+		.into_iter()
+		.map(|(val, name)| RadioEntryData {
+			label: name.into(),
+			on_update: WidgetCallback::new(move |_| ExportDialogMessage::FileType(val).into()),
+			..RadioEntryData::default()
+		})
+		.collect();
 
 		let export_type = vec![
 			WidgetHolder::new(Widget::TextLabel(TextLabel {
@@ -89,11 +159,17 @@ impl PropertyHolder for ExportDialogMessageHandler {
 		];
 
 		let artboards = self.artboards.iter().map(|(&val, name)| (ExportBounds::Artboard(val), name.to_string(), false));
+		// PDFs of all the artwork in a document with artboards have a page for each artboard
+		let all_artwork = match self.file_type == FileType::Pdf && !self.artboards.is_empty() {
+			true => "All Artboards (One Page Each)",
+			false => "All Artwork",
+		};
 		let mut export_area_options = vec![
-			(ExportBounds::AllArtwork, "All Artwork".to_string(), false),
+			(ExportBounds::AllArtwork, all_artwork.to_string(), false),
 			(ExportBounds::Selection, "Selection".to_string(), !self.has_selection),
 		];
 		export_area_options.extend(artboards);
+		export_area_options.extend(self.slices.iter().enumerate().map(|(index, name)| (ExportBounds::Slice(index), name.clone(), false)));
 		let index = export_area_options.iter().position(|(val, _, _)| val == &self.bounds).unwrap();
 		let entries = vec![export_area_options
 			.into_iter()
@@ -122,9 +198,84 @@ impl PropertyHolder for ExportDialogMessageHandler {
 			})),
 		];
 
+		let resolution_disabled = matches!(self.file_type, FileType::Svg | FileType::Lottie | FileType::Dxf);
+		let resolution_tooltip = match self.file_type {
+			FileType::Pdf => "The resolution of rasterized content, relative to the document's pixels".to_string(),
+			_ => String::new(),
+		};
+		let resolution_input = if self.unit.is_physical() {
+			// Documents measured in printed sizes are exported at a resolution in dots per inch, with each document pixel being 1/96 of an inch
+			NumberInput {
+				value: Some(self.scale_factor * PIXELS_PER_INCH),
+				label: "".into(),
+				unit: " DPI".into(),
+				is_integer: true,
+				min: Some(0.5 * PIXELS_PER_INCH),
+				max: Some(4. * PIXELS_PER_INCH),
+				disabled: resolution_disabled,
+				tooltip: resolution_tooltip,
+				on_update: WidgetCallback::new(|number_input: &NumberInput| ExportDialogMessage::ScaleFactor(number_input.value.unwrap() / PIXELS_PER_INCH).into()),
+				..NumberInput::default()
+			}
+		} else {
+			NumberInput {
+				value: Some(self.scale_factor),
+				label: "".into(),
+				unit: "×".into(),
+				min: Some(0.5),
+				max: Some(4.),
+				disabled: resolution_disabled,
+				tooltip: resolution_tooltip,
+				on_update: WidgetCallback::new(|number_input: &NumberInput| ExportDialogMessage::ScaleFactor(number_input.value.unwrap()).into()),
+				..NumberInput::default()
+			}
+		};
 		let resolution = vec![
 			WidgetHolder::new(Widget::TextLabel(TextLabel {
-				value: "Scale Factor".into(),
+				value: if self.unit.is_physical() { "Resolution" } else { "Scale Factor" }.into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(resolution_input)),
+		];
+
+		let supersampling_options = [(1, "Off"), (2, "2×2"), (4, "4×4")];
+		let index = supersampling_options.iter().position(|&(samples, _)| samples == self.supersampling).unwrap_or_default();
+		let entries = vec![supersampling_options
+			.into_iter()
+			.map(|(samples, name)| DropdownEntryData {
+				label: name.into(),
+				on_update: WidgetCallback::new(move |_| ExportDialogMessage::Supersampling(samples).into()),
+				..Default::default()
+			})
+			.collect()];
+
+		let supersampling = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Supersampling".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				selected_index: Some(index as u32),
+				entries,
+				disabled: matches!(self.file_type, FileType::Svg | FileType::Pdf | FileType::Lottie | FileType::Dxf),
+				tooltip: "Rasterizes at a higher resolution and averages the samples of each pixel, smoothing jagged edges".into(),
+				..Default::default()
+			})),
+		];
+
+		let quality = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Quality".into(),
 				table_align: true,
 				..TextLabel::default()
 			})),
@@ -133,16 +284,409 @@ impl PropertyHolder for ExportDialogMessageHandler {
 				direction: SeparatorDirection::Horizontal,
 			})),
 			WidgetHolder::new(Widget::NumberInput(NumberInput {
-				value: Some(self.scale_factor),
+				value: Some(self.quality as f64),
 				label: "".into(),
-				unit: " ".into(),
+				unit: "%".into(),
+				min: Some(1.),
+				max: Some(100.),
+				is_integer: true,
+				tooltip: match self.file_type {
+					FileType::Webp => "Lower qualities discard more detail, making for smaller files with more visible compression artifacts. At 100%, the export is lossless".into(),
+					_ => "Lower qualities discard more detail, making for smaller files with more visible compression artifacts".into(),
+				},
+				on_update: WidgetCallback::new(|number_input: &NumberInput| ExportDialogMessage::Quality(number_input.value.unwrap() as u8).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let chroma_subsampling_options = [(ChromaSubsampling::Full, "4:4:4 (Full Color Resolution)"), (ChromaSubsampling::Half, "4:2:0 (Half Color Resolution)")];
+		let index = chroma_subsampling_options
+			.iter()
+			.position(|&(chroma_subsampling, _)| chroma_subsampling == self.chroma_subsampling)
+			.unwrap_or_default();
+		let entries = vec![chroma_subsampling_options
+			.into_iter()
+			.map(|(chroma_subsampling, name)| DropdownEntryData {
+				label: name.into(),
+				on_update: WidgetCallback::new(move |_| ExportDialogMessage::ChromaSubsampling(chroma_subsampling).into()),
+				..Default::default()
+			})
+			.collect()];
+
+		let chroma_subsampling = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Chroma Subsampling".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				selected_index: Some(index as u32),
+				entries,
+				tooltip: "Stores the colors at a lower resolution than the brightness, which the eye rarely notices and makes for smaller files, but can blur the edges of saturated colors".into(),
+				..Default::default()
+			})),
+		];
+
+		let transparent_background = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Transparent Background".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.transparent_background,
+				tooltip: "Leaves out the artboards and the background around them, keeping only the artwork".into(),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| ExportDialogMessage::TransparentBackground(checkbox_input.checked).into()),
+				..Default::default()
+			})),
+		];
+
+		let trim_to_content = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Trim to Content".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.trim_to_content,
+				tooltip: "Shrinks the exported bounds to fit the artwork within them, removing the empty space around it".into(),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| ExportDialogMessage::TrimToContent(checkbox_input.checked).into()),
+				..Default::default()
+			})),
+		];
+
+		let convert_to_srgb = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Convert to sRGB".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.convert_to_srgb,
+				tooltip: format!(
+					"Converts the {} colors to sRGB instead of tagging the file with their color profile, for apps and viewers which ignore color profiles. Colors outside of sRGB are clipped",
+					self.color_space
+				),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| ExportDialogMessage::ConvertToSrgb(checkbox_input.checked).into()),
+				..Default::default()
+			})),
+		];
+
+		let rasterize_unsupported = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Rasterize Unsupported".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.rasterize_unsupported,
+				tooltip: "Embeds content PDF can't represent, like gradients with varying transparency and images with an alpha channel, as rasterized images instead of simplifying or leaving it out"
+					.into(),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| ExportDialogMessage::RasterizeUnsupported(checkbox_input.checked).into()),
+				..Default::default()
+			})),
+		];
+
+		let frames = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Frames".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.animation.first_frame as f64),
+				label: "First".into(),
 				min: Some(0.),
-				disabled: self.file_type == FileType::Svg,
-				on_update: WidgetCallback::new(|number_input: &NumberInput| ExportDialogMessage::ScaleFactor(number_input.value.unwrap()).into()),
+				is_integer: true,
+				on_update: WidgetCallback::new(|number_input: &NumberInput| ExportDialogMessage::FirstFrame(number_input.value.unwrap() as u32).into()),
+				..NumberInput::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Related,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.animation.last_frame as f64),
+				label: "Last".into(),
+				min: Some(self.animation.first_frame as f64),
+				is_integer: true,
+				on_update: WidgetCallback::new(|number_input: &NumberInput| ExportDialogMessage::LastFrame(number_input.value.unwrap() as u32).into()),
 				..NumberInput::default()
 			})),
 		];
 
+		let frame_rate = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Frame Rate".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.animation.frame_rate),
+				label: "".into(),
+				unit: " fps".into(),
+				min: Some(1.),
+				max: Some(120.),
+				tooltip: match self.file_type {
+					FileType::Gif => "GIFs are timed in hundredths of a second, and most viewers slow down frames shorter than two hundredths, so frame rates above 50 fps play back too slowly".into(),
+					_ => String::new(),
+				},
+				on_update: WidgetCallback::new(|number_input: &NumberInput| ExportDialogMessage::FrameRate(number_input.value.unwrap()).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let looping = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Loop Forever".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.animation.plays == 0,
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| ExportDialogMessage::Plays(if checkbox_input.checked { 0 } else { 1 }).into()),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.animation.plays.max(1) as f64),
+				label: "Plays".into(),
+				min: Some(1.),
+				max: Some(u16::MAX as f64),
+				is_integer: true,
+				disabled: self.animation.plays == 0,
+				tooltip: "The number of times the animation plays before stopping on its last frame".into(),
+				on_update: WidgetCallback::new(|number_input: &NumberInput| ExportDialogMessage::Plays(number_input.value.unwrap() as u32).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let colors = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Colors".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.animation.colors as f64),
+				label: "".into(),
+				min: Some(2.),
+				max: Some(256.),
+				is_integer: true,
+				tooltip: "The size of the palette shared by every frame, where fewer colors make for smaller files. One color is used for transparency if any pixels are transparent".into(),
+				on_update: WidgetCallback::new(|number_input: &NumberInput| ExportDialogMessage::Colors(number_input.value.unwrap() as u16).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let dithering_options = [(Dithering::None, "None"), (Dithering::Ordered, "Ordered"), (Dithering::ErrorDiffusion, "Error Diffusion")];
+		let index = dithering_options.iter().position(|&(dithering, _)| dithering == self.animation.dithering).unwrap_or_default();
+		let entries = vec![dithering_options
+			.into_iter()
+			.map(|(dithering, name)| DropdownEntryData {
+				label: name.into(),
+				on_update: WidgetCallback::new(move |_| ExportDialogMessage::Dithering(dithering).into()),
+				..Default::default()
+			})
+			.collect()];
+
+		let dithering = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Dithering".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				selected_index: Some(index as u32),
+				entries,
+				tooltip: "Mixes the palette's colors to approximate those it doesn't have. Ordered dithering keeps a steady pattern between frames, while error diffusion gives smoother gradients"
+					.into(),
+				..Default::default()
+			})),
+		];
+
+		let dxf_curves_entries = [
+			(
+				DxfCurves::Arcs,
+				"Arcs",
+				"Approximates curves with circular arcs, which every CAD program reads and machines follow exactly",
+			),
+			(DxfCurves::Splines, "Splines", "Keeps curves exact as splines, which some older CAD and machine software can't read"),
+		]
+		.into_iter()
+		.map(|(dxf_curves, label, tooltip)| RadioEntryData {
+			label: label.into(),
+			tooltip: tooltip.into(),
+			on_update: WidgetCallback::new(move |_| ExportDialogMessage::DxfCurves(dxf_curves).into()),
+			..RadioEntryData::default()
+		})
+		.collect();
+
+		let dxf_curves = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Curves".into(),
+				table_align: true,
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::RadioInput(RadioInput {
+				selected_index: self.dxf_curves as u32,
+				entries: dxf_curves_entries,
+			})),
+		];
+
+		let precision = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Round Numbers".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.svg_options.precision.is_some(),
+				tooltip: "Rounds coordinates and lengths to a number of decimal places, which makes for smaller and more readable files".into(),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| ExportDialogMessage::SvgPrecision(checkbox_input.checked.then_some(3)).into()),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.svg_options.precision.unwrap_or(3) as f64),
+				label: "Decimals".into(),
+				min: Some(0.),
+				max: Some(10.),
+				is_integer: true,
+				disabled: self.svg_options.precision.is_none(),
+				on_update: WidgetCallback::new(|number_input: &NumberInput| ExportDialogMessage::SvgPrecision(Some(number_input.value.unwrap() as u32)).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let flatten_transforms = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Flatten Transforms".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.svg_options.flatten_transforms,
+				tooltip: "Applies the transforms of groups to the path data of the shapes inside them, so every coordinate is where it's drawn. Images keep their transforms".into(),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| ExportDialogMessage::SvgFlattenTransforms(checkbox_input.checked).into()),
+				..Default::default()
+			})),
+		];
+
+		let style_output_entries = [
+			(
+				SvgStyleOutput::Attributes,
+				"Attributes",
+				"Writes the fill and stroke of each element as attributes, which CSS rules can override",
+			),
+			(SvgStyleOutput::InlineStyle, "Inline Style", "Writes the fill and stroke of each element in its style attribute"),
+			(
+				SvgStyleOutput::Classes,
+				"CSS Classes",
+				"Declares a CSS class for each distinct style in a stylesheet at the top of the file",
+			),
+		]
+		.into_iter()
+		.map(|(style_output, label, tooltip)| RadioEntryData {
+			label: label.into(),
+			tooltip: tooltip.into(),
+			on_update: WidgetCallback::new(move |_| ExportDialogMessage::SvgStyleOutput(style_output).into()),
+			..RadioEntryData::default()
+		})
+		.collect();
+
+		let style_output = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Styles".into(),
+				table_align: true,
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::RadioInput(RadioInput {
+				selected_index: self.svg_options.style_output as u32,
+				entries: style_output_entries,
+			})),
+		];
+
+		let layer_name_ids = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Layer Names as IDs".into(),
+				table_align: true,
+				..TextLabel::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.svg_options.layer_name_ids,
+				tooltip: "Gives the elements of named layers an ID made from the name, so code can find them. Repeated names are numbered to keep the IDs unique".into(),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| ExportDialogMessage::SvgLayerNameIds(checkbox_input.checked).into()),
+				..Default::default()
+			})),
+		];
+
 		let button_widgets = vec![
 			WidgetHolder::new(Widget::TextButton(TextButton {
 				label: "Export".to_string(),
@@ -164,7 +708,7 @@ impl PropertyHolder for ExportDialogMessageHandler {
 			})),
 		];
 
-		Layout::WidgetLayout(WidgetLayout::new(vec![
+		let mut layout = vec![
 			LayoutGroup::Row {
 				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
 					value: "Export".to_string(),
@@ -175,8 +719,53 @@ impl PropertyHolder for ExportDialogMessageHandler {
 			LayoutGroup::Row { widgets: file_name },
 			LayoutGroup::Row { widgets: export_type },
 			LayoutGroup::Row { widgets: resolution },
-			LayoutGroup::Row { widgets: export_area },
-			LayoutGroup::Row { widgets: button_widgets },
-		]))
+			LayoutGroup::Row { widgets: supersampling },
+		];
+		// Only JPEGs and WebPs are lossy
+		if matches!(self.file_type, FileType::Jpg | FileType::Webp) {
+			layout.push(LayoutGroup::Row { widgets: quality });
+		}
+		if self.file_type == FileType::Jpg {
+			layout.push(LayoutGroup::Row { widgets: chroma_subsampling });
+		}
+		layout.extend([LayoutGroup::Row { widgets: export_area }, LayoutGroup::Row { widgets: trim_to_content }]);
+		// JPEGs have no transparency, and DXF drawings have no background
+		if !matches!(self.file_type, FileType::Jpg | FileType::Dxf) {
+			layout.push(LayoutGroup::Row { widgets: transparent_background });
+		}
+		// Only documents with wide-gamut colors have colors to convert, and PDF, Lottie, and DXF exports handle the color space themselves
+		let in_color_space = matches!(self.file_type, FileType::Png | FileType::Jpg | FileType::Webp | FileType::Svg | FileType::Gif | FileType::Apng);
+		if self.color_space != ColorSpace::Srgb && in_color_space {
+			layout.push(LayoutGroup::Row { widgets: convert_to_srgb });
+		}
+		// Only PDFs have content which can't be represented natively
+		if self.file_type == FileType::Pdf {
+			layout.push(LayoutGroup::Row { widgets: rasterize_unsupported });
+		}
+		if matches!(self.file_type, FileType::Gif | FileType::Apng | FileType::Lottie) {
+			layout.extend([LayoutGroup::Row { widgets: frames }, LayoutGroup::Row { widgets: frame_rate }]);
+		}
+		// Lottie players choose for themselves whether to loop
+		if matches!(self.file_type, FileType::Gif | FileType::Apng) {
+			layout.push(LayoutGroup::Row { widgets: looping });
+		}
+		// Only GIFs are limited to a palette
+		if self.file_type == FileType::Gif {
+			layout.extend([LayoutGroup::Row { widgets: colors }, LayoutGroup::Row { widgets: dithering }]);
+		}
+		if self.file_type == FileType::Dxf {
+			layout.push(LayoutGroup::Row { widgets: dxf_curves });
+		}
+		if self.file_type == FileType::Svg {
+			layout.extend([
+				LayoutGroup::Row { widgets: precision },
+				LayoutGroup::Row { widgets: flatten_transforms },
+				LayoutGroup::Row { widgets: style_output },
+				LayoutGroup::Row { widgets: layer_name_ids },
+			]);
+		}
+		layout.push(LayoutGroup::Row { widgets: button_widgets });
+
+		Layout::WidgetLayout(WidgetLayout::new(layout))
 	}
 }
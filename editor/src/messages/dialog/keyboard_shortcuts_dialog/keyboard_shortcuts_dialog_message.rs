@@ -0,0 +1,18 @@
+use crate::messages::input_mapper::utility_types::keymap::KeymapPreset;
+use crate::messages::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+#[impl_message(Message, DialogMessage, KeyboardShortcutsDialog)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum KeyboardShortcutsDialogMessage {
+	Filter(String),
+	Preset(KeymapPreset),
+	Shortcuts { action: String, shortcuts: String },
+
+	ExportKeymap,
+	ImportKeymap,
+	LoadKeymapFile { content: String },
+
+	Submit,
+}
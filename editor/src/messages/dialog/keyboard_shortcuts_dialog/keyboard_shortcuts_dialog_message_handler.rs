@@ -0,0 +1,209 @@
+use crate::messages::input_mapper::utility_types::keymap::{bindable_actions, Keymap, KeymapPreset, Shortcut};
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::misc::LayoutTarget;
+use crate::messages::layout::utility_types::widgets::button_widgets::TextButton;
+use crate::messages::layout::utility_types::widgets::input_widgets::{DropdownEntryData, DropdownInput, TextInput};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::prelude::*;
+
+/// A dialog to rebind the actions triggered by keyboard shortcuts, which edits a copy of the keymap until it's confirmed.
+#[derive(Debug, Clone, Default)]
+pub struct KeyboardShortcutsDialogMessageHandler {
+	pub keymap: Keymap,
+	/// Only the actions with a label or shortcut containing this text are listed
+	pub filter: String,
+	/// The problem with the shortcuts or keymap file last given by the user
+	pub error: Option<String>,
+}
+
+const PRESETS: [KeymapPreset; 2] = [KeymapPreset::Graphite, KeymapPreset::Illustrator];
+
+impl MessageHandler<KeyboardShortcutsDialogMessage, ()> for KeyboardShortcutsDialogMessageHandler {
+	fn process_message(&mut self, message: KeyboardShortcutsDialogMessage, _data: (), responses: &mut VecDeque<Message>) {
+		self.error = None;
+
+		match message {
+			KeyboardShortcutsDialogMessage::Filter(filter) => self.filter = filter,
+			KeyboardShortcutsDialogMessage::Preset(preset) => self.keymap = preset.keymap(),
+			KeyboardShortcutsDialogMessage::Shortcuts { action, shortcuts } => match Shortcut::parse_list(&shortcuts) {
+				Ok(shortcuts) => {
+					if let Some(action) = bindable_actions().iter().find(|bindable| bindable.id == action) {
+						self.keymap.bind(action, shortcuts);
+					}
+				}
+				Err(name) => self.error = Some(format!("\"{}\" isn't the name of a key, so the shortcuts weren't changed", name)),
+			},
+
+			KeyboardShortcutsDialogMessage::ExportKeymap => {
+				let name = if self.keymap.name.is_empty() { "keymap" } else { self.keymap.name.as_str() };
+				responses.push_back(
+					FrontendMessage::TriggerFileDownload {
+						document: self.keymap.to_file(),
+						name: format!("{}.json", name),
+					}
+					.into(),
+				);
+			}
+			KeyboardShortcutsDialogMessage::ImportKeymap => responses.push_back(FrontendMessage::TriggerImportKeymap.into()),
+			KeyboardShortcutsDialogMessage::LoadKeymapFile { content } => match Keymap::from_file(&content) {
+				Ok(keymap) => self.keymap = keymap,
+				Err(error) => self.error = Some(format!("The keymap file couldn't be read: {}", error)),
+			},
+
+			KeyboardShortcutsDialogMessage::Submit => responses.push_front(PreferencesMessage::Keymap { keymap: self.keymap.clone() }.into()),
+		}
+
+		self.register_properties(responses, LayoutTarget::DialogDetails);
+	}
+
+	advertise_actions! {KeyboardShortcutsDialogUpdate;}
+}
+
+impl PropertyHolder for KeyboardShortcutsDialogMessageHandler {
+	fn properties(&self) -> Layout {
+		let separator = || {
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			}))
+		};
+		let label = |value: &str| {
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: value.into(),
+				table_align: true,
+				..Default::default()
+			}))
+		};
+
+		let preset_entries = PRESETS
+			.iter()
+			.map(|&preset| DropdownEntryData {
+				label: preset.name().into(),
+				on_update: WidgetCallback::new(move |_| KeyboardShortcutsDialogMessage::Preset(preset).into()),
+				..Default::default()
+			})
+			.collect();
+		let preset = vec![
+			label("Preset"),
+			separator(),
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				entries: vec![preset_entries],
+				selected_index: PRESETS.iter().position(|preset| preset.keymap().bindings == self.keymap.bindings).map(|index| index as u32),
+				tooltip: "The keymap the shortcuts start from, which is unselected once they're changed".into(),
+				..Default::default()
+			})),
+		];
+
+		let filter = vec![
+			label("Search"),
+			separator(),
+			WidgetHolder::new(Widget::TextInput(TextInput {
+				value: self.filter.clone(),
+				min_width: 200,
+				on_update: WidgetCallback::new(|text_input: &TextInput| KeyboardShortcutsDialogMessage::Filter(text_input.value.clone()).into()),
+				..Default::default()
+			})),
+		];
+
+		let actions = bindable_actions();
+		let filter_text = self.filter.to_lowercase();
+		let action_rows = actions.iter().filter_map(|action| {
+			let shortcuts = self.keymap.shortcuts(&action.id).unwrap_or(&action.default_shortcuts);
+			let shortcuts_text = Shortcut::format_list(shortcuts);
+			if !action.label.to_lowercase().contains(&filter_text) && !shortcuts_text.to_lowercase().contains(&filter_text) {
+				return None;
+			}
+
+			let conflicts = action.conflicts(shortcuts, &actions, &self.keymap);
+			let conflicts = conflicts.iter().map(|conflict| conflict.label.as_str()).collect::<Vec<_>>().join(", ");
+
+			let id = action.id.clone();
+			let mut widgets = vec![
+				label(&action.label),
+				separator(),
+				WidgetHolder::new(Widget::TextInput(TextInput {
+					value: shortcuts_text,
+					min_width: 160,
+					tooltip: "Separate alternative shortcuts with \"or\", like \"Del or Backspace\", or leave empty to unbind the action. Mouse chords are written like \"Space+LeftMouse\" or \"Alt+Scroll\".".into(),
+					on_update: WidgetCallback::new(move |text_input: &TextInput| {
+						KeyboardShortcutsDialogMessage::Shortcuts {
+							action: id.clone(),
+							shortcuts: text_input.value.clone(),
+						}
+						.into()
+					}),
+					..Default::default()
+				})),
+			];
+			if !conflicts.is_empty() {
+				widgets.push(separator());
+				widgets.push(WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: format!("Conflicts with {}", conflicts),
+					italic: true,
+					..Default::default()
+				})));
+			}
+
+			Some(LayoutGroup::Row { widgets })
+		});
+
+		let error = self.error.iter().map(|error| LayoutGroup::Row {
+			widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: error.clone(),
+				italic: true,
+				multiline: true,
+				..Default::default()
+			}))],
+		});
+
+		let button_widgets = vec![
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "OK".to_string(),
+				min_width: 96,
+				emphasized: true,
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![KeyboardShortcutsDialogMessage::Submit.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Cancel".to_string(),
+				min_width: 96,
+				on_update: WidgetCallback::new(|_| FrontendMessage::DisplayDialogDismiss.into()),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Import…".to_string(),
+				min_width: 96,
+				on_update: WidgetCallback::new(|_| KeyboardShortcutsDialogMessage::ImportKeymap.into()),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Export…".to_string(),
+				min_width: 96,
+				on_update: WidgetCallback::new(|_| KeyboardShortcutsDialogMessage::ExportKeymap.into()),
+				..Default::default()
+			})),
+		];
+
+		let title = LayoutGroup::Row {
+			widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Keyboard Shortcuts".to_string(),
+				bold: true,
+				..Default::default()
+			}))],
+		};
+
+		Layout::WidgetLayout(WidgetLayout::new(
+			[title, LayoutGroup::Row { widgets: preset }, LayoutGroup::Row { widgets: filter }]
+				.into_iter()
+				.chain(action_rows)
+				.chain(error)
+				.chain(std::iter::once(LayoutGroup::Row { widgets: button_widgets }))
+				.collect(),
+		))
+	}
+}
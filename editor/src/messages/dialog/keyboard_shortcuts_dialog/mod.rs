@@ -0,0 +1,7 @@
+mod keyboard_shortcuts_dialog_message;
+mod keyboard_shortcuts_dialog_message_handler;
+
+#[doc(inline)]
+pub use keyboard_shortcuts_dialog_message::{KeyboardShortcutsDialogMessage, KeyboardShortcutsDialogMessageDiscriminant};
+#[doc(inline)]
+pub use keyboard_shortcuts_dialog_message_handler::KeyboardShortcutsDialogMessageHandler;
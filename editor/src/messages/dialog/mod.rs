@@ -8,10 +8,18 @@
 mod dialog_message;
 mod dialog_message_handler;
 
+pub mod batch_export_dialog;
+pub mod collaboration_dialog;
+pub mod document_properties_dialog;
 pub mod export_dialog;
+pub mod keyboard_shortcuts_dialog;
 pub mod new_document_dialog;
+pub mod offset_path_dialog;
 pub mod preferences_dialog;
 pub mod simple_dialogs;
+pub mod simplify_path_dialog;
+pub mod sprite_sheet_dialog;
+pub mod trace_image_dialog;
 
 #[doc(inline)]
 pub use dialog_message::{DialogMessage, DialogMessageDiscriminant};
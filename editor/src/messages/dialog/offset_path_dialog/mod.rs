@@ -0,0 +1,7 @@
+mod offset_path_dialog_message;
+mod offset_path_dialog_message_handler;
+
+#[doc(inline)]
+pub use offset_path_dialog_message::{OffsetPathDialogMessage, OffsetPathDialogMessageDiscriminant};
+#[doc(inline)]
+pub use offset_path_dialog_message_handler::OffsetPathDialogMessageHandler;
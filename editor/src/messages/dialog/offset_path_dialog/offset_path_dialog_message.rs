@@ -0,0 +1,15 @@
+use crate::messages::prelude::*;
+
+use graphene::layers::style::LineJoin;
+
+use serde::{Deserialize, Serialize};
+
+#[impl_message(Message, DialogMessage, OffsetPathDialog)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum OffsetPathDialogMessage {
+	Distance(f64),
+	Join(LineJoin),
+
+	Cancel,
+	Submit,
+}
@@ -0,0 +1,162 @@
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::misc::LayoutTarget;
+use crate::messages::layout::utility_types::widgets::button_widgets::TextButton;
+use crate::messages::layout::utility_types::widgets::input_widgets::{NumberInput, RadioEntryData, RadioInput};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::prelude::*;
+
+use graphene::layers::style::LineJoin;
+use graphene::layers::vector::subpath::Subpath;
+use graphene::{LayerId, Operation};
+
+use glam::DAffine2;
+
+/// A dialog to grow or shrink the selected vector layers by offsetting their paths, which are previewed in the document while the dialog is open.
+/// Opening the dialog starts a transaction, so the offset paths are committed as a single undo step or reverted when canceled.
+#[derive(Debug, Clone)]
+pub struct OffsetPathDialogMessageHandler {
+	/// How far outwards, in document space, to offset the paths, which are inset when it's negative
+	pub distance: f64,
+	pub join: LineJoin,
+	/// The selected vector layers with their original paths and the transforms from the layers to document space
+	pub shapes: Vec<(Vec<LayerId>, Subpath, DAffine2)>,
+}
+
+impl Default for OffsetPathDialogMessageHandler {
+	fn default() -> Self {
+		Self {
+			distance: 0.,
+			join: LineJoin::Miter,
+			shapes: Vec::new(),
+		}
+	}
+}
+
+impl MessageHandler<OffsetPathDialogMessage, ()> for OffsetPathDialogMessageHandler {
+	fn process_message(&mut self, message: OffsetPathDialogMessage, _data: (), responses: &mut VecDeque<Message>) {
+		match message {
+			OffsetPathDialogMessage::Distance(distance) => {
+				self.distance = distance;
+				self.preview(responses);
+			}
+			OffsetPathDialogMessage::Join(join) => {
+				self.join = join;
+				self.preview(responses);
+			}
+
+			OffsetPathDialogMessage::Cancel => responses.push_back(DocumentMessage::AbortTransaction.into()),
+			OffsetPathDialogMessage::Submit => responses.push_back(DocumentMessage::CommitTransaction.into()),
+		}
+
+		self.register_properties(responses, LayoutTarget::DialogDetails);
+	}
+
+	advertise_actions! {OffsetPathDialogUpdate;}
+}
+
+impl OffsetPathDialogMessageHandler {
+	/// Replaces the paths of the layers with their originals offset by the current distance.
+	pub fn preview(&self, responses: &mut VecDeque<Message>) {
+		for (path, subpath, transform) in &self.shapes {
+			// Offset in document space so the distance doesn't depend on the scale of each layer
+			let mut subpath = subpath.clone();
+			subpath.apply_affine(*transform);
+			let mut offset = subpath.offset(self.distance, self.join.into());
+			offset.apply_affine(transform.inverse());
+
+			responses.push_back(Operation::SetShapePath { path: path.clone(), subpath: offset }.into());
+		}
+	}
+}
+
+impl PropertyHolder for OffsetPathDialogMessageHandler {
+	fn properties(&self) -> Layout {
+		let separator = || {
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			}))
+		};
+		let label = |value: &str| {
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: value.into(),
+				table_align: true,
+				..Default::default()
+			}))
+		};
+
+		let distance = vec![
+			label("Distance"),
+			separator(),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.distance),
+				unit: " px".into(),
+				tooltip: "How far to grow the shapes outwards, or to shrink them inwards when negative".into(),
+				on_update: WidgetCallback::new(|number_input: &NumberInput| OffsetPathDialogMessage::Distance(number_input.value.unwrap()).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let join_entries = [
+			(LineJoin::Miter, "Miter", "Extends the offset curves until they meet in a sharp corner"),
+			(LineJoin::Bevel, "Bevel", "Cuts corners off with a straight line"),
+			(LineJoin::Round, "Round", "Rounds corners off with a circular arc"),
+		]
+		.into_iter()
+		.map(|(join, label, tooltip)| RadioEntryData {
+			label: label.into(),
+			tooltip: tooltip.into(),
+			on_update: WidgetCallback::new(move |_| OffsetPathDialogMessage::Join(join).into()),
+			..RadioEntryData::default()
+		})
+		.collect();
+
+		let join = vec![
+			label("Corners"),
+			separator(),
+			WidgetHolder::new(Widget::RadioInput(RadioInput {
+				selected_index: self.join as u32,
+				entries: join_entries,
+			})),
+		];
+
+		let button_widgets = vec![
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "OK".to_string(),
+				min_width: 96,
+				emphasized: true,
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![OffsetPathDialogMessage::Submit.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Cancel".to_string(),
+				min_width: 96,
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![OffsetPathDialogMessage::Cancel.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+		];
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![
+			LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "Offset Path".to_string(),
+					bold: true,
+					..Default::default()
+				}))],
+			},
+			LayoutGroup::Row { widgets: distance },
+			LayoutGroup::Row { widgets: join },
+			LayoutGroup::Row { widgets: button_widgets },
+		]))
+	}
+}
@@ -1,7 +1,7 @@
 use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
 use crate::messages::layout::utility_types::misc::LayoutTarget;
 use crate::messages::layout::utility_types::widgets::button_widgets::TextButton;
-use crate::messages::layout::utility_types::widgets::input_widgets::{NumberInput, TextInput};
+use crate::messages::layout::utility_types::widgets::input_widgets::{CheckboxInput, NumberInput, TextInput};
 use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
 use crate::messages::prelude::*;
 
@@ -78,6 +78,66 @@ impl PreferencesDialogMessageHandler {
 			})),
 		];
 
+		let compress_documents = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Documents".into(),
+				min_width: 60,
+				italic: true,
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Compress Saved Files".into(),
+				table_align: true,
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: preferences.compress_documents,
+				tooltip: "Saves documents in a compressed binary format which is much smaller and faster to save and open, instead of readable JSON. Both formats can always be opened.".into(),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| PreferencesMessage::CompressDocuments { compress: checkbox_input.checked }.into()),
+				..Default::default()
+			})),
+		];
+
+		let keyboard_shortcuts = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Keyboard".into(),
+				min_width: 60,
+				italic: true,
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Shortcuts".into(),
+				table_align: true,
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Customize…".into(),
+				tooltip: format!(
+					"Rebind the actions triggered by keyboard shortcuts ({} changed)",
+					if preferences.keymap.bindings.is_empty() {
+						"none".to_string()
+					} else {
+						preferences.keymap.bindings.len().to_string()
+					}
+				),
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![DialogMessage::RequestKeyboardShortcutsDialog.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+		];
+
 		let button_widgets = vec![
 			WidgetHolder::new(Widget::TextButton(TextButton {
 				label: "Ok".to_string(),
@@ -109,6 +169,8 @@ impl PreferencesDialogMessageHandler {
 			},
 			LayoutGroup::Row { widgets: imaginate_server_hostname },
 			LayoutGroup::Row { widgets: imaginate_refresh_frequency },
+			LayoutGroup::Row { widgets: compress_documents },
+			LayoutGroup::Row { widgets: keyboard_shortcuts },
 			LayoutGroup::Row { widgets: button_widgets },
 		]))
 	}
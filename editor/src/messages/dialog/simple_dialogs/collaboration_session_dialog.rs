@@ -0,0 +1,56 @@
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::widgets::button_widgets::TextButton;
+use crate::messages::layout::utility_types::widgets::label_widgets::TextLabel;
+use crate::messages::prelude::*;
+
+/// A dialog showing the ID of a collaboration session, which is shared with the editors joining it.
+pub struct CollaborationSessionDialog {
+	pub session: String,
+}
+
+impl PropertyHolder for CollaborationSessionDialog {
+	fn properties(&self) -> Layout {
+		let session = self.session.clone();
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![
+			LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "Collaboration session".into(),
+					bold: true,
+					..Default::default()
+				}))],
+			},
+			LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "Other tabs join this document with File > Join Collaboration Session… and this ID:".into(),
+					multiline: true,
+					..Default::default()
+				}))],
+			},
+			LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: self.session.clone(),
+					bold: true,
+					..Default::default()
+				}))],
+			},
+			LayoutGroup::Row {
+				widgets: vec![
+					WidgetHolder::new(Widget::TextButton(TextButton {
+						label: "Copy ID".to_string(),
+						emphasized: true,
+						min_width: 96,
+						on_update: WidgetCallback::new(move |_| FrontendMessage::TriggerTextCopy { copy_text: session.clone() }.into()),
+						..Default::default()
+					})),
+					WidgetHolder::new(Widget::TextButton(TextButton {
+						label: "OK".to_string(),
+						min_width: 96,
+						on_update: WidgetCallback::new(|_| FrontendMessage::DisplayDialogDismiss.into()),
+						..Default::default()
+					})),
+				],
+			},
+		]))
+	}
+}
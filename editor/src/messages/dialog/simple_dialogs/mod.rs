@@ -1,11 +1,13 @@
 mod about_graphite_dialog;
 mod close_all_documents_dialog;
 mod close_document_dialog;
+mod collaboration_session_dialog;
 mod coming_soon_dialog;
 mod error_dialog;
 
 pub use about_graphite_dialog::AboutGraphiteDialog;
 pub use close_all_documents_dialog::CloseAllDocumentsDialog;
 pub use close_document_dialog::CloseDocumentDialog;
+pub use collaboration_session_dialog::CollaborationSessionDialog;
 pub use coming_soon_dialog::ComingSoonDialog;
 pub use error_dialog::ErrorDialog;
@@ -0,0 +1,7 @@
+mod simplify_path_dialog_message;
+mod simplify_path_dialog_message_handler;
+
+#[doc(inline)]
+pub use simplify_path_dialog_message::{SimplifyPathDialogMessage, SimplifyPathDialogMessageDiscriminant};
+#[doc(inline)]
+pub use simplify_path_dialog_message_handler::SimplifyPathDialogMessageHandler;
@@ -0,0 +1,12 @@
+use crate::messages::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+#[impl_message(Message, DialogMessage, SimplifyPathDialog)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum SimplifyPathDialogMessage {
+	Tolerance(f64),
+
+	Cancel,
+	Submit,
+}
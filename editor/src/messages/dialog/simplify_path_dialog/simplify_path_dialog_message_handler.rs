@@ -0,0 +1,137 @@
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::misc::LayoutTarget;
+use crate::messages::layout::utility_types::widgets::button_widgets::TextButton;
+use crate::messages::layout::utility_types::widgets::input_widgets::NumberInput;
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::prelude::*;
+
+use graphene::layers::vector::subpath::Subpath;
+use graphene::{LayerId, Operation};
+
+use glam::DAffine2;
+
+/// A dialog to reduce the number of anchors of the selected vector layers, which are previewed in the document while the dialog is open.
+/// Opening the dialog starts a transaction, so the simplified paths are committed as a single undo step or reverted when canceled.
+#[derive(Debug, Clone, Default)]
+pub struct SimplifyPathDialogMessageHandler {
+	/// The maximum distance, in document space, the simplified paths may deviate from the original ones
+	pub tolerance: f64,
+	/// The selected vector layers with their original paths and the transforms from the layers to document space
+	pub shapes: Vec<(Vec<LayerId>, Subpath, DAffine2)>,
+	/// The number of anchors left in the simplified paths at the current tolerance
+	pub simplified_anchor_count: usize,
+}
+
+impl MessageHandler<SimplifyPathDialogMessage, ()> for SimplifyPathDialogMessageHandler {
+	fn process_message(&mut self, message: SimplifyPathDialogMessage, _data: (), responses: &mut VecDeque<Message>) {
+		match message {
+			SimplifyPathDialogMessage::Tolerance(tolerance) => {
+				self.tolerance = tolerance;
+				self.preview(responses);
+			}
+
+			SimplifyPathDialogMessage::Cancel => responses.push_back(DocumentMessage::AbortTransaction.into()),
+			SimplifyPathDialogMessage::Submit => responses.push_back(DocumentMessage::CommitTransaction.into()),
+		}
+
+		self.register_properties(responses, LayoutTarget::DialogDetails);
+	}
+
+	advertise_actions! {SimplifyPathDialogUpdate;}
+}
+
+impl SimplifyPathDialogMessageHandler {
+	/// Replaces the paths of the layers with their originals simplified at the current tolerance.
+	pub fn preview(&mut self, responses: &mut VecDeque<Message>) {
+		self.simplified_anchor_count = 0;
+
+		for (path, subpath, transform) in &self.shapes {
+			// Simplify in document space so the tolerance doesn't depend on the scale of each layer
+			let mut subpath = subpath.clone();
+			subpath.apply_affine(*transform);
+			let mut simplified = subpath.simplify(self.tolerance);
+			simplified.apply_affine(transform.inverse());
+
+			self.simplified_anchor_count += simplified.anchor_count();
+			responses.push_back(
+				Operation::SetShapePath {
+					path: path.clone(),
+					subpath: simplified,
+				}
+				.into(),
+			);
+		}
+	}
+
+	fn original_anchor_count(&self) -> usize {
+		self.shapes.iter().map(|(_, subpath, _)| subpath.anchor_count()).sum()
+	}
+}
+
+impl PropertyHolder for SimplifyPathDialogMessageHandler {
+	fn properties(&self) -> Layout {
+		let tolerance = vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Tolerance".into(),
+				table_align: true,
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.tolerance),
+				unit: " px".into(),
+				min: Some(0.),
+				tooltip: "How far the simplified paths may deviate from the original ones. Higher tolerances remove more anchors".into(),
+				on_update: WidgetCallback::new(|number_input: &NumberInput| SimplifyPathDialogMessage::Tolerance(number_input.value.unwrap()).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let anchor_count = vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+			value: format!("Anchors: {} → {}", self.original_anchor_count(), self.simplified_anchor_count),
+			..Default::default()
+		}))];
+
+		let button_widgets = vec![
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "OK".to_string(),
+				min_width: 96,
+				emphasized: true,
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![SimplifyPathDialogMessage::Submit.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Cancel".to_string(),
+				min_width: 96,
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![SimplifyPathDialogMessage::Cancel.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+		];
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![
+			LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "Simplify Path".to_string(),
+					bold: true,
+					..Default::default()
+				}))],
+			},
+			LayoutGroup::Row { widgets: tolerance },
+			LayoutGroup::Row { widgets: anchor_count },
+			LayoutGroup::Row { widgets: button_widgets },
+		]))
+	}
+}
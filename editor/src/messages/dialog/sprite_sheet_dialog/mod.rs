@@ -0,0 +1,7 @@
+mod sprite_sheet_dialog_message;
+mod sprite_sheet_dialog_message_handler;
+
+#[doc(inline)]
+pub use sprite_sheet_dialog_message::{SpriteSheetDialogMessage, SpriteSheetDialogMessageDiscriminant};
+#[doc(inline)]
+pub use sprite_sheet_dialog_message_handler::SpriteSheetDialogMessageHandler;
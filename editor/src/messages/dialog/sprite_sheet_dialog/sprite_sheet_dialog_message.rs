@@ -0,0 +1,16 @@
+use crate::messages::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+#[impl_message(Message, DialogMessage, SpriteSheetDialog)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum SpriteSheetDialogMessage {
+	FileName(String),
+	ScaleFactor(f64),
+	Padding(u32),
+	PowerOfTwo(bool),
+	TransparentBackground(bool),
+	IncludeSprite { index: usize, included: bool },
+
+	Submit,
+}
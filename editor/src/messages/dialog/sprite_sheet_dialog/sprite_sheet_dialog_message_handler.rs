@@ -0,0 +1,199 @@
+use crate::consts::FILE_SAVE_SUFFIX;
+use crate::messages::frontend::utility_types::ExportBounds;
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::misc::LayoutTarget;
+use crate::messages::layout::utility_types::widgets::button_widgets::TextButton;
+use crate::messages::layout::utility_types::widgets::input_widgets::{CheckboxInput, NumberInput, TextInput};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::portfolio::document::utility_types::sprite_sheet::SpriteSheetSettings;
+use crate::messages::prelude::*;
+
+/// A dialog to choose which artboards and export slices are packed into a sprite sheet, and how.
+#[derive(Debug, Clone, Default)]
+pub struct SpriteSheetDialogMessageHandler {
+	/// The name of the sprite sheet and its atlas, without their extensions
+	pub file_name: String,
+	pub settings: SpriteSheetSettings,
+	/// The artboards and export slices which can become sprites, with their names and whether they're included
+	pub sprites: Vec<(ExportBounds, String, bool)>,
+}
+
+impl MessageHandler<SpriteSheetDialogMessage, ()> for SpriteSheetDialogMessageHandler {
+	fn process_message(&mut self, message: SpriteSheetDialogMessage, _data: (), responses: &mut VecDeque<Message>) {
+		match message {
+			SpriteSheetDialogMessage::FileName(name) => self.file_name = name,
+			SpriteSheetDialogMessage::ScaleFactor(scale_factor) => self.settings.scale_factor = scale_factor,
+			SpriteSheetDialogMessage::Padding(padding) => self.settings.padding = padding,
+			SpriteSheetDialogMessage::PowerOfTwo(power_of_two) => self.settings.power_of_two = power_of_two,
+			SpriteSheetDialogMessage::TransparentBackground(transparent_background) => self.settings.transparent_background = transparent_background,
+			SpriteSheetDialogMessage::IncludeSprite { index, included } => {
+				if let Some((_, _, sprite_included)) = self.sprites.get_mut(index) {
+					*sprite_included = included;
+				}
+			}
+
+			SpriteSheetDialogMessage::Submit => responses.push_front(
+				DocumentMessage::ExportSpriteSheet {
+					name: self.file_name.trim_end_matches(FILE_SAVE_SUFFIX).to_string(),
+					sprites: self.sprites.iter().filter(|(_, _, included)| *included).map(|(bounds, name, _)| (name.clone(), *bounds)).collect(),
+					settings: self.settings,
+				}
+				.into(),
+			),
+		}
+
+		self.register_properties(responses, LayoutTarget::DialogDetails);
+	}
+
+	advertise_actions! {SpriteSheetDialogUpdate;}
+}
+
+impl PropertyHolder for SpriteSheetDialogMessageHandler {
+	fn properties(&self) -> Layout {
+		let separator = || {
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			}))
+		};
+		let label = |value: &str| {
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: value.into(),
+				table_align: true,
+				..Default::default()
+			}))
+		};
+
+		let file_name = vec![
+			label("File Name"),
+			separator(),
+			WidgetHolder::new(Widget::TextInput(TextInput {
+				value: self.file_name.clone(),
+				on_update: WidgetCallback::new(|text_input: &TextInput| SpriteSheetDialogMessage::FileName(text_input.value.clone()).into()),
+				..Default::default()
+			})),
+		];
+
+		let scale_factor = vec![
+			label("Scale Factor"),
+			separator(),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.settings.scale_factor),
+				label: "".into(),
+				unit: "×".into(),
+				min: Some(0.25),
+				max: Some(4.),
+				on_update: WidgetCallback::new(|number_input: &NumberInput| SpriteSheetDialogMessage::ScaleFactor(number_input.value.unwrap()).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let padding = vec![
+			label("Padding"),
+			separator(),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.settings.padding as f64),
+				label: "".into(),
+				unit: " px".into(),
+				min: Some(0.),
+				max: Some(64.),
+				is_integer: true,
+				tooltip: "The space between sprites and around the edges of the sheet, which keeps texture filtering from bleeding neighboring sprites into each other".into(),
+				on_update: WidgetCallback::new(|number_input: &NumberInput| SpriteSheetDialogMessage::Padding(number_input.value.unwrap() as u32).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let power_of_two = vec![
+			label("Power of Two"),
+			separator(),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.settings.power_of_two,
+				tooltip: "Rounds the width and height of the sheet up to powers of two, which older GPUs and some engines require of textures".into(),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| SpriteSheetDialogMessage::PowerOfTwo(checkbox_input.checked).into()),
+				..Default::default()
+			})),
+		];
+
+		let transparent_background = vec![
+			label("Transparent Background"),
+			separator(),
+			WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+				checked: self.settings.transparent_background,
+				tooltip: "Leaves out the artboards, keeping only the artwork".into(),
+				on_update: WidgetCallback::new(|checkbox_input: &CheckboxInput| SpriteSheetDialogMessage::TransparentBackground(checkbox_input.checked).into()),
+				..Default::default()
+			})),
+		];
+
+		let mut layout = vec![
+			LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "Export Sprite Sheet".to_string(),
+					bold: true,
+					..Default::default()
+				}))],
+			},
+			LayoutGroup::Row { widgets: file_name },
+			LayoutGroup::Row { widgets: scale_factor },
+			LayoutGroup::Row { widgets: padding },
+			LayoutGroup::Row { widgets: power_of_two },
+			LayoutGroup::Row { widgets: transparent_background },
+		];
+
+		for (index, (_, name, included)) in self.sprites.iter().enumerate() {
+			layout.push(LayoutGroup::Row {
+				widgets: vec![
+					WidgetHolder::new(Widget::CheckboxInput(CheckboxInput {
+						checked: *included,
+						tooltip: "Include in the sprite sheet".into(),
+						on_update: WidgetCallback::new(move |checkbox_input: &CheckboxInput| {
+							SpriteSheetDialogMessage::IncludeSprite {
+								index,
+								included: checkbox_input.checked,
+							}
+							.into()
+						}),
+						..Default::default()
+					})),
+					label(name),
+				],
+			});
+		}
+
+		if self.sprites.is_empty() {
+			layout.push(LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "This document has no artboards or export slices to pack into a sprite sheet. Slices can be added from the selection in the Layer menu.".into(),
+					multiline: true,
+					..Default::default()
+				}))],
+			});
+		}
+
+		let button_widgets = vec![
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Export".to_string(),
+				min_width: 96,
+				emphasized: true,
+				disabled: !self.sprites.iter().any(|(_, _, included)| *included),
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![SpriteSheetDialogMessage::Submit.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Cancel".to_string(),
+				min_width: 96,
+				on_update: WidgetCallback::new(|_| FrontendMessage::DisplayDialogDismiss.into()),
+				..Default::default()
+			})),
+		];
+		layout.push(LayoutGroup::Row { widgets: button_widgets });
+
+		Layout::WidgetLayout(WidgetLayout::new(layout))
+	}
+}
@@ -0,0 +1,7 @@
+mod trace_image_dialog_message;
+mod trace_image_dialog_message_handler;
+
+#[doc(inline)]
+pub use trace_image_dialog_message::{TraceImageDialogMessage, TraceImageDialogMessageDiscriminant};
+#[doc(inline)]
+pub use trace_image_dialog_message_handler::TraceImageDialogMessageHandler;
@@ -0,0 +1,17 @@
+use crate::messages::prelude::*;
+
+use graphene::image_trace::TraceMode;
+
+use serde::{Deserialize, Serialize};
+
+#[impl_message(Message, DialogMessage, TraceImageDialog)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum TraceImageDialogMessage {
+	CornerSmoothing(f64),
+	Levels(f64),
+	Mode(TraceMode),
+	SpeckleSize(f64),
+	Threshold(f64),
+
+	Submit,
+}
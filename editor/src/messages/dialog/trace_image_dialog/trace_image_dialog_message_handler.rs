@@ -0,0 +1,173 @@
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::misc::LayoutTarget;
+use crate::messages::layout::utility_types::widgets::button_widgets::TextButton;
+use crate::messages::layout::utility_types::widgets::input_widgets::{NumberInput, RadioEntryData, RadioInput};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::prelude::*;
+
+use graphene::image_trace::{TraceMode, TraceOptions};
+use graphene::LayerId;
+
+/// A dialog with the settings for tracing the selected image layer into vector shapes.
+#[derive(Debug, Clone, Default)]
+pub struct TraceImageDialogMessageHandler {
+	pub layer_path: Vec<LayerId>,
+	pub options: TraceOptions,
+}
+
+impl MessageHandler<TraceImageDialogMessage, ()> for TraceImageDialogMessageHandler {
+	fn process_message(&mut self, message: TraceImageDialogMessage, _data: (), responses: &mut VecDeque<Message>) {
+		match message {
+			TraceImageDialogMessage::CornerSmoothing(corner_smoothing) => self.options.corner_smoothing = corner_smoothing,
+			TraceImageDialogMessage::Levels(levels) => self.options.levels = levels as u8,
+			TraceImageDialogMessage::Mode(mode) => self.options.mode = mode,
+			TraceImageDialogMessage::SpeckleSize(speckle_size) => self.options.speckle_size = speckle_size as usize,
+			TraceImageDialogMessage::Threshold(threshold) => self.options.threshold = threshold as u8,
+
+			TraceImageDialogMessage::Submit => responses.push_back(
+				DocumentMessage::TraceImage {
+					layer_path: self.layer_path.clone(),
+					options: self.options,
+				}
+				.into(),
+			),
+		}
+
+		self.register_properties(responses, LayoutTarget::DialogDetails);
+	}
+
+	advertise_actions! {TraceImageDialogUpdate;}
+}
+
+impl PropertyHolder for TraceImageDialogMessageHandler {
+	fn properties(&self) -> Layout {
+		let separator = || {
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			}))
+		};
+		let label = |value: &str| {
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: value.into(),
+				table_align: true,
+				..Default::default()
+			}))
+		};
+
+		let mode_entries = [
+			(TraceMode::Threshold, "Threshold", "Traces the dark parts of the image as a single black shape"),
+			(TraceMode::Posterize, "Posterize", "Reduces the image to a few colors and traces a shape for each of them"),
+		]
+		.into_iter()
+		.map(|(mode, label, tooltip)| RadioEntryData {
+			label: label.into(),
+			tooltip: tooltip.into(),
+			on_update: WidgetCallback::new(move |_| TraceImageDialogMessage::Mode(mode).into()),
+			..RadioEntryData::default()
+		})
+		.collect();
+
+		let mode = vec![
+			label("Mode"),
+			separator(),
+			WidgetHolder::new(Widget::RadioInput(RadioInput {
+				selected_index: self.options.mode as u32,
+				entries: mode_entries,
+			})),
+		];
+
+		let colors = match self.options.mode {
+			TraceMode::Threshold => vec![
+				label("Threshold"),
+				separator(),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					value: Some(self.options.threshold as f64),
+					min: Some(0.),
+					max: Some(255.),
+					is_integer: true,
+					tooltip: "The brightness, from 0 to 255, below which pixels are traced".into(),
+					on_update: WidgetCallback::new(|number_input: &NumberInput| TraceImageDialogMessage::Threshold(number_input.value.unwrap()).into()),
+					..NumberInput::default()
+				})),
+			],
+			TraceMode::Posterize => vec![
+				label("Levels"),
+				separator(),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					value: Some(self.options.levels as f64),
+					min: Some(2.),
+					max: Some(16.),
+					is_integer: true,
+					tooltip: "How many levels each color channel is reduced to. More levels trace more colors".into(),
+					on_update: WidgetCallback::new(|number_input: &NumberInput| TraceImageDialogMessage::Levels(number_input.value.unwrap()).into()),
+					..NumberInput::default()
+				})),
+			],
+		};
+
+		let speckle_size = vec![
+			label("Ignore Speckles"),
+			separator(),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.options.speckle_size as f64),
+				unit: " px".into(),
+				min: Some(0.),
+				is_integer: true,
+				tooltip: "Regions with fewer pixels than this are merged into their surroundings instead of being traced".into(),
+				on_update: WidgetCallback::new(|number_input: &NumberInput| TraceImageDialogMessage::SpeckleSize(number_input.value.unwrap()).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let corner_smoothing = vec![
+			label("Smooth Corners"),
+			separator(),
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
+				value: Some(self.options.corner_smoothing * 100.),
+				unit: "%".into(),
+				min: Some(0.),
+				max: Some(100.),
+				tooltip: "How much the staircase edges of the pixels are rounded into curves".into(),
+				on_update: WidgetCallback::new(|number_input: &NumberInput| TraceImageDialogMessage::CornerSmoothing(number_input.value.unwrap() / 100.).into()),
+				..NumberInput::default()
+			})),
+		];
+
+		let button_widgets = vec![
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "OK".to_string(),
+				min_width: 96,
+				emphasized: true,
+				on_update: WidgetCallback::new(|_| {
+					DialogMessage::CloseDialogAndThen {
+						followups: vec![TraceImageDialogMessage::Submit.into()],
+					}
+					.into()
+				}),
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Cancel".to_string(),
+				min_width: 96,
+				on_update: WidgetCallback::new(|_| FrontendMessage::DisplayDialogDismiss.into()),
+				..Default::default()
+			})),
+		];
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![
+			LayoutGroup::Row {
+				widgets: vec![WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "Trace Image".to_string(),
+					bold: true,
+					..Default::default()
+				}))],
+			},
+			LayoutGroup::Row { widgets: mode },
+			LayoutGroup::Row { widgets: colors },
+			LayoutGroup::Row { widgets: speckle_size },
+			LayoutGroup::Row { widgets: corner_smoothing },
+			LayoutGroup::Row { widgets: button_widgets },
+		]))
+	}
+}
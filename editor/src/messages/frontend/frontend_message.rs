@@ -1,14 +1,17 @@
-use super::utility_types::{FrontendDocumentDetails, FrontendImageData, MouseCursorIcon};
+use super::utility_types::{FrontendBatchRaster, FrontendDocumentDetails, FrontendDocumentView, FrontendImageData, FrontendLinkedImage, FrontendPdfRaster, MouseCursorIcon};
 use crate::messages::layout::utility_types::layout_widget::SubLayout;
 use crate::messages::layout::utility_types::misc::LayoutTarget;
 use crate::messages::layout::utility_types::widgets::menu_widgets::MenuBarEntry;
 use crate::messages::portfolio::document::utility_types::layer_panel::{LayerPanelEntry, RawBuffer};
+use crate::messages::portfolio::document::utility_types::misc::ArtworkSlice;
 use crate::messages::prelude::*;
 use crate::messages::tool::utility_types::HintData;
 
-use graphene::color::Color;
+use graphene::color::{CmykProfile, Color};
+use graphene::gpu_scene::GpuScene;
 use graphene::layers::imaginate_layer::{ImaginateBaseImage, ImaginateGenerationParameters};
 use graphene::layers::text_layer::Font;
+use graphene::operation_log::SessionMessage;
 use graphene::LayerId;
 
 use serde::{Deserialize, Serialize};
@@ -43,6 +46,40 @@ pub enum FrontendMessage {
 		#[serde(rename = "commitDate")]
 		commit_date: String,
 	},
+	/// Rasterizes the SVG document of every frame of an animated export and sends their pixels back to be encoded by the editor
+	TriggerAnimationRasterization {
+		frames: Vec<String>,
+		size: (f64, f64),
+		supersampling: u32,
+		#[serde(rename = "colorSpace")]
+		color_space: String,
+	},
+	TriggerBatchRasterization {
+		rasters: Vec<FrontendBatchRaster>,
+		#[serde(rename = "colorSpace")]
+		color_space: String,
+	},
+	TriggerBinaryFileDownload {
+		data: Vec<u8>,
+		name: String,
+		mime: String,
+	},
+	/// Forgets the documents recently opened in the desktop build
+	TriggerClearRecentDocuments,
+	/// Joins the collaboration session, whose messages are passed to the document with `ReceiveCollaborationMessage`
+	TriggerCollaborationJoin {
+		#[serde(rename = "documentId")]
+		document_id: u64,
+		session: String,
+	},
+	TriggerCollaborationLeave {
+		session: String,
+	},
+	/// Sends the message to the other editors in the collaboration session
+	TriggerCollaborationSend {
+		session: String,
+		message: SessionMessage,
+	},
 	TriggerFileDownload {
 		document: String,
 		name: String,
@@ -52,6 +89,12 @@ pub enum FrontendMessage {
 		#[serde(rename = "isDefault")]
 		is_default: bool,
 	},
+	/// Decodes an image and sends its pixels back to be traced by the editor
+	TriggerImageTrace {
+		mime: String,
+		#[serde(rename = "imageData")]
+		image_data: Vec<u8>,
+	},
 	TriggerImaginateCheckServerStatus {
 		hostname: String,
 	},
@@ -75,6 +118,22 @@ pub enum FrontendMessage {
 		hostname: String,
 	},
 	TriggerImport,
+	TriggerImportColorConversion {
+		mime: String,
+		#[serde(rename = "imageData")]
+		image_data: Vec<u8>,
+		#[serde(rename = "colorSpace")]
+		color_space: String,
+		mouse: Option<(f64, f64)>,
+	},
+	/// Picks a font file to load into the editor
+	TriggerImportFont,
+	/// Picks an image file to place in the document as a linked image, keeping a handle to the file to read it again
+	/// Picks a keymap file to load into the keyboard shortcuts dialog
+	TriggerImportKeymap,
+	TriggerImportLinked,
+	/// Picks an Adobe Swatch Exchange or GIMP palette file to import as color styles
+	TriggerImportPalette,
 	TriggerIndexedDbRemoveDocument {
 		#[serde(rename = "documentId")]
 		document_id: u64,
@@ -84,20 +143,65 @@ pub enum FrontendMessage {
 		details: FrontendDocumentDetails,
 		version: String,
 	},
+	/// Rasterizes the SVG thumbnail of a layer and sends it back as a PNG data URL to be shown in the Layers panel
+	TriggerLayerThumbnailRasterization {
+		#[serde(rename = "documentId")]
+		document_id: u64,
+		#[serde(rename = "layerPath")]
+		layer_path: Vec<LayerId>,
+		#[serde(rename = "contentHash")]
+		content_hash: u64,
+		svg: String,
+		width: u32,
+		height: u32,
+	},
 	TriggerLoadAutoSaveDocuments,
 	TriggerLoadPreferences,
 	TriggerOpenDocument,
+	/// Opens a document from its path in the desktop build
+	TriggerOpenRecentDocument {
+		path: String,
+	},
 	TriggerPaste,
-	TriggerRasterDownload {
+	TriggerPdfRasterization {
+		rasters: Vec<FrontendPdfRaster>,
+	},
+	/// Rasterizes an SVG document and sends its pixels back to be encoded by the editor
+	TriggerRasterEncoding {
 		svg: String,
-		name: String,
-		mime: String,
 		size: (f64, f64),
+		supersampling: u32,
+		#[serde(rename = "colorSpace")]
+		color_space: String,
+	},
+	/// Reads the files of linked images, sending back those which haven't been read yet or were modified since they were last read
+	TriggerReadLinkedImages {
+		#[serde(rename = "documentId")]
+		document_id: u64,
+		images: Vec<FrontendLinkedImage>,
+		/// Whether to ask for permission to read files the browser hasn't granted access to, which requires the user to have just clicked something
+		#[serde(rename = "requestPermission")]
+		request_permission: bool,
 	},
 	TriggerRefreshBoundsOfViewports,
+	/// Picks a new file for a linked image, or for an embedded image to become linked
+	TriggerRelinkImage {
+		#[serde(rename = "documentId")]
+		document_id: u64,
+		#[serde(rename = "layerPath")]
+		layer_path: Vec<LayerId>,
+	},
 	TriggerRevokeBlobUrl {
 		url: String,
 	},
+	/// Saves a document as a `.graphite` file, which is written back to the file it was opened from in the desktop build or otherwise downloaded
+	TriggerSaveDocument {
+		#[serde(rename = "documentId")]
+		document_id: u64,
+		name: String,
+		data: Vec<u8>,
+		mime: String,
+	},
 	TriggerSavePreferences {
 		preferences: PreferencesMessageHandler,
 	},
@@ -116,6 +220,14 @@ pub enum FrontendMessage {
 		#[serde(rename = "documentId")]
 		document_id: u64,
 	},
+	UpdateCmykProfile {
+		#[serde(rename = "cmykProfile")]
+		cmyk_profile: CmykProfile,
+	},
+	/// The fonts loaded from the user's font files, which are listed along with those of the font catalog
+	UpdateCustomFonts {
+		fonts: Vec<Font>,
+	},
 	UpdateDialogDetails {
 		#[serde(rename = "layoutTarget")]
 		layout_target: LayoutTarget,
@@ -127,6 +239,28 @@ pub enum FrontendMessage {
 	UpdateDocumentArtwork {
 		svg: String,
 	},
+	UpdateDocumentArtworkGpu {
+		/// The tessellated layers, or `None` if they are unchanged since the last update and only the view transform has to be applied
+		scene: Option<GpuScene>,
+		#[serde(rename = "viewTransform")]
+		view_transform: [f64; 6],
+		/// The tiles to draw again as x, y, width, and height in viewport pixels, or `None` to draw the whole viewport again
+		#[serde(rename = "dirtyTiles")]
+		dirty_tiles: Option<Vec<[f64; 4]>>,
+	},
+	UpdateDocumentArtworkPixels {
+		/// The visible artwork with one unit for every pixel of the preview
+		svg: String,
+		width: u32,
+		height: u32,
+		/// Places the pixels of the preview in the viewport
+		transform: [f64; 6],
+		/// Whether the pixels are enlarged enough to show the grid between them
+		grid: bool,
+	},
+	UpdateDocumentArtworkSlices {
+		slices: Vec<ArtworkSlice>,
+	},
 	UpdateDocumentBarLayout {
 		#[serde(rename = "layoutTarget")]
 		layout_target: LayoutTarget,
@@ -157,6 +291,10 @@ pub enum FrontendMessage {
 		size: (f64, f64),
 		multiplier: (f64, f64),
 	},
+	UpdateDocumentViews {
+		svg: String,
+		views: Vec<FrontendDocumentView>,
+	},
 	UpdateEyedropperSamplingState {
 		#[serde(rename = "mousePosition")]
 		mouse_position: Option<(f64, f64)>,
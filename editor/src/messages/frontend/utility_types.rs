@@ -9,6 +9,13 @@ pub struct FrontendDocumentDetails {
 	pub id: u64,
 }
 
+/// Another view of the active document, drawing the artwork rendered for the active view with the transform from its viewport to this view's.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct FrontendDocumentView {
+	pub transform: [f64; 6],
+	pub label: String,
+}
+
 #[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub struct FrontendImageData {
 	pub path: Vec<LayerId>,
@@ -17,6 +24,37 @@ pub struct FrontendImageData {
 	pub image_data: Vec<u8>,
 }
 
+/// An image layer linked to an external file, which the frontend reads through the file handle stored under its key.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct FrontendLinkedImage {
+	#[serde(rename = "layerPath")]
+	pub layer_path: Vec<LayerId>,
+	pub handle: String,
+	#[serde(rename = "fileName")]
+	pub file_name: String,
+	/// When the file was modified as of the last time it was read, or `None` if the image hasn't been read since the document was opened
+	#[serde(rename = "lastModified")]
+	pub last_modified: Option<f64>,
+}
+
+/// Content of a PDF export which the frontend rasterizes into RGBA pixels, since PDF can't represent it natively.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct FrontendPdfRaster {
+	pub svg: String,
+	pub width: u32,
+	pub height: u32,
+}
+
+/// A file of a batch export which the frontend rasterizes, then either encodes as a PNG or sends back as RGBA pixels for the editor to encode.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct FrontendBatchRaster {
+	pub svg: String,
+	pub width: u32,
+	pub height: u32,
+	#[serde(rename = "encodePng")]
+	pub encode_png: bool,
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub enum MouseCursorIcon {
 	#[default]
@@ -39,7 +77,16 @@ pub enum FileType {
 	#[default]
 	Png,
 	Jpg,
+	Webp,
 	Svg,
+	Pdf,
+	Gif,
+	/// An animated PNG, which is saved with the extension of a PNG
+	Apng,
+	/// A Lottie animation, the JSON format played by the Lottie libraries on the web and on mobile platforms
+	Lottie,
+	/// A CAD drawing of the outlines of the artwork, for laser cutters and CNC machines
+	Dxf,
 }
 
 impl FileType {
@@ -47,7 +94,26 @@ impl FileType {
 		match self {
 			FileType::Png => "image/png",
 			FileType::Jpg => "image/jpeg",
+			FileType::Webp => "image/webp",
 			FileType::Svg => "image/svg+xml",
+			FileType::Pdf => "application/pdf",
+			FileType::Gif => "image/gif",
+			FileType::Apng => "image/apng",
+			FileType::Lottie => "application/json",
+			FileType::Dxf => "image/vnd.dxf",
+		}
+	}
+
+	pub fn extension(self) -> &'static str {
+		match self {
+			FileType::Png | FileType::Apng => "png",
+			FileType::Jpg => "jpg",
+			FileType::Webp => "webp",
+			FileType::Svg => "svg",
+			FileType::Pdf => "pdf",
+			FileType::Gif => "gif",
+			FileType::Lottie => "json",
+			FileType::Dxf => "dxf",
 		}
 	}
 }
@@ -58,4 +124,6 @@ pub enum ExportBounds {
 	AllArtwork,
 	Selection,
 	Artboard(LayerId),
+	/// The export slice with the given index in the document's list of slices
+	Slice(usize),
 }
@@ -41,7 +41,7 @@ pub fn default_mapping() -> Mapping {
 		entry!(PointerMove; refresh_keys=[Shift, Control], action_dispatch=TransformLayerMessage::PointerMove { slow_key: Shift, snap_key: Control }),
 		//
 		// SelectToolMessage
-		entry!(PointerMove; refresh_keys=[Control, Shift, Alt], action_dispatch=SelectToolMessage::PointerMove { axis_align: Shift, snap_angle: Control, center: Alt, duplicate: Alt }),
+		entry!(PointerMove; refresh_keys=[Control, Shift, Alt], action_dispatch=SelectToolMessage::PointerMove { axis_align: Shift, snap_angle: Control, center: Alt, duplicate: Alt, toggle_marquee_mode: Alt }),
 		entry!(KeyDown(Lmb); action_dispatch=SelectToolMessage::DragStart { add_to_selection: Shift }),
 		entry!(KeyUp(Lmb); action_dispatch=SelectToolMessage::DragStop),
 		entry!(KeyDown(Enter); action_dispatch=SelectToolMessage::DragStop),
@@ -145,12 +145,46 @@ pub fn default_mapping() -> Mapping {
 		entry!(PointerMove; refresh_keys=[Alt, Shift, Control], action_dispatch=LineToolMessage::Redraw { center: Alt, lock_angle: Control, snap_angle: Shift }),
 		//
 		// PathToolMessage
+		entry!(KeyDown(Lmb); modifiers=[Alt], action_dispatch=PathToolMessage::CutPath),
 		entry!(KeyDown(Lmb); action_dispatch=PathToolMessage::DragStart { add_to_selection: Shift }),
 		entry!(PointerMove; refresh_keys=[Alt, Shift], action_dispatch=PathToolMessage::PointerMove { alt_mirror_angle: Alt, shift_mirror_distance: Shift }),
 		entry!(KeyDown(Delete); action_dispatch=PathToolMessage::Delete),
 		entry!(KeyDown(Backspace); action_dispatch=PathToolMessage::Delete),
 		entry!(KeyUp(Lmb); action_dispatch=PathToolMessage::DragStop),
 		entry!(DoubleClick; action_dispatch=PathToolMessage::InsertPoint),
+		entry!(KeyDown(KeyJ); modifiers=[Accel, Shift], action_dispatch=PathToolMessage::ClosePath),
+		entry!(KeyDown(KeyJ); modifiers=[Accel], action_dispatch=PathToolMessage::JoinEndpoints),
+		entry!(KeyDown(ArrowUp); modifiers=[Shift, ArrowLeft], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: -1., direction_y: -1., big_increment: true }),
+		entry!(KeyDown(ArrowUp); modifiers=[Shift, ArrowRight], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 1., direction_y: -1., big_increment: true }),
+		entry!(KeyDown(ArrowUp); modifiers=[Shift], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 0., direction_y: -1., big_increment: true }),
+		entry!(KeyDown(ArrowDown); modifiers=[Shift, ArrowLeft], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: -1., direction_y: 1., big_increment: true }),
+		entry!(KeyDown(ArrowDown); modifiers=[Shift, ArrowRight], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 1., direction_y: 1., big_increment: true }),
+		entry!(KeyDown(ArrowDown); modifiers=[Shift], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 0., direction_y: 1., big_increment: true }),
+		entry!(KeyDown(ArrowLeft); modifiers=[Shift, ArrowUp], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: -1., direction_y: -1., big_increment: true }),
+		entry!(KeyDown(ArrowLeft); modifiers=[Shift, ArrowDown], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: -1., direction_y: 1., big_increment: true }),
+		entry!(KeyDown(ArrowLeft); modifiers=[Shift], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: -1., direction_y: 0., big_increment: true }),
+		entry!(KeyDown(ArrowRight); modifiers=[Shift, ArrowUp], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 1., direction_y: -1., big_increment: true }),
+		entry!(KeyDown(ArrowRight); modifiers=[Shift, ArrowDown], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 1., direction_y: 1., big_increment: true }),
+		entry!(KeyDown(ArrowRight); modifiers=[Shift], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 1., direction_y: 0., big_increment: true }),
+		entry!(KeyDown(ArrowUp); modifiers=[ArrowLeft], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: -1., direction_y: -1., big_increment: false }),
+		entry!(KeyDown(ArrowUp); modifiers=[ArrowRight], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 1., direction_y: -1., big_increment: false }),
+		entry!(KeyDown(ArrowUp); action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 0., direction_y: -1., big_increment: false }),
+		entry!(KeyDown(ArrowDown); modifiers=[ArrowLeft], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: -1., direction_y: 1., big_increment: false }),
+		entry!(KeyDown(ArrowDown); modifiers=[ArrowRight], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 1., direction_y: 1., big_increment: false }),
+		entry!(KeyDown(ArrowDown); action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 0., direction_y: 1., big_increment: false }),
+		entry!(KeyDown(ArrowLeft); modifiers=[ArrowUp], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: -1., direction_y: -1., big_increment: false }),
+		entry!(KeyDown(ArrowLeft); modifiers=[ArrowDown], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: -1., direction_y: 1., big_increment: false }),
+		entry!(KeyDown(ArrowLeft); action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: -1., direction_y: 0., big_increment: false }),
+		entry!(KeyDown(ArrowRight); modifiers=[ArrowUp], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 1., direction_y: -1., big_increment: false }),
+		entry!(KeyDown(ArrowRight); modifiers=[ArrowDown], action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 1., direction_y: 1., big_increment: false }),
+		entry!(KeyDown(ArrowRight); action_dispatch=PathToolMessage::NudgeSelectedPoints { direction_x: 1., direction_y: 0., big_increment: false }),
+		//
+		// WidthToolMessage
+		entry!(KeyDown(Lmb); action_dispatch=WidthToolMessage::PointerDown),
+		entry!(PointerMove; refresh_keys=[Shift], action_dispatch=WidthToolMessage::PointerMove { lock_position: Shift }),
+		entry!(KeyUp(Lmb); action_dispatch=WidthToolMessage::PointerUp),
+		entry!(KeyDown(Delete); action_dispatch=WidthToolMessage::Delete),
+		entry!(KeyDown(Backspace); action_dispatch=WidthToolMessage::Delete),
 		//
 		// PenToolMessage
 		entry!(PointerMove; refresh_keys=[Shift, Control], action_dispatch=PenToolMessage::PointerMove { snap_angle: Control, break_handle: Shift }),
@@ -185,6 +219,7 @@ pub fn default_mapping() -> Mapping {
 		entry!(KeyDown(KeyF); action_dispatch=ToolMessage::ActivateToolFill),
 		entry!(KeyDown(KeyH); action_dispatch=ToolMessage::ActivateToolGradient),
 		entry!(KeyDown(KeyA); action_dispatch=ToolMessage::ActivateToolPath),
+		entry!(KeyDown(KeyW); modifiers=[Shift], action_dispatch=ToolMessage::ActivateToolWidth),
 		entry!(KeyDown(KeyP); action_dispatch=ToolMessage::ActivateToolPen),
 		entry!(KeyDown(KeyN); action_dispatch=ToolMessage::ActivateToolFreehand),
 		entry!(KeyDown(KeyL); action_dispatch=ToolMessage::ActivateToolLine),
@@ -201,13 +236,18 @@ pub fn default_mapping() -> Mapping {
 		entry!(KeyDown(KeyP); modifiers=[Alt], action_dispatch=DocumentMessage::DebugPrintDocument),
 		entry!(KeyDown(KeyZ); modifiers=[Accel, Shift], action_dispatch=DocumentMessage::Redo),
 		entry!(KeyDown(KeyZ); modifiers=[Accel], action_dispatch=DocumentMessage::Undo),
+		entry!(KeyDown(KeyY); modifiers=[Accel], action_dispatch=DocumentMessage::ToggleOutlineViewMode),
 		entry!(KeyDown(KeyA); modifiers=[Accel, Alt], action_dispatch=DocumentMessage::DeselectAllLayers),
+		entry!(KeyDown(KeyA); modifiers=[Accel, Shift], action_dispatch=DocumentMessage::SelectInverse),
 		entry!(KeyDown(KeyA); modifiers=[Accel], action_dispatch=DocumentMessage::SelectAllLayers),
 		entry!(KeyDown(KeyS); modifiers=[Accel], action_dispatch=DocumentMessage::SaveDocument),
+		entry!(KeyDown(KeyD); modifiers=[Accel, Shift], action_dispatch=DocumentMessage::ReselectPrevious),
 		entry!(KeyDown(KeyD); modifiers=[Accel], action_dispatch=DocumentMessage::DuplicateSelectedLayers),
 		entry!(KeyDown(KeyG); modifiers=[Accel], action_dispatch=DocumentMessage::GroupSelectedLayers),
 		entry!(KeyDown(KeyG); modifiers=[Accel, Shift], action_dispatch=DocumentMessage::UngroupSelectedLayers),
 		entry!(KeyDown(KeyN); modifiers=[Accel, Shift], action_dispatch=DocumentMessage::CreateEmptyFolder { container_path: vec![] }),
+		entry!(KeyDown(KeyL); modifiers=[Accel, Alt], action_dispatch=DocumentMessage::UnlockAllLayers),
+		entry!(KeyDown(KeyL); modifiers=[Accel], action_dispatch=DocumentMessage::LockSelectedLayers),
 		entry!(KeyDown(Digit0); modifiers=[Accel], action_dispatch=DocumentMessage::ZoomCanvasToFitAll),
 		entry!(KeyDown(Digit1); modifiers=[Accel], action_dispatch=DocumentMessage::ZoomCanvasTo100Percent),
 		entry!(KeyDown(Digit2); modifiers=[Accel], action_dispatch=DocumentMessage::ZoomCanvasTo200Percent),
@@ -256,6 +296,7 @@ pub fn default_mapping() -> Mapping {
 		entry!(KeyDown(Equal); modifiers=[Accel], action_dispatch=NavigationMessage::IncreaseCanvasZoom { center_on_mouse: false }),
 		entry!(KeyDown(Minus); modifiers=[Accel], action_dispatch=NavigationMessage::DecreaseCanvasZoom { center_on_mouse: false }),
 		entry!(WheelScroll; modifiers=[Control], action_dispatch=NavigationMessage::WheelCanvasZoom),
+		entry!(WheelScroll; modifiers=[Alt], action_dispatch=NavigationMessage::WheelCanvasZoom),
 		entry!(WheelScroll; modifiers=[Shift], action_dispatch=NavigationMessage::WheelCanvasTranslate { use_y_as_x: true }),
 		entry!(WheelScroll; action_dispatch=NavigationMessage::WheelCanvasTranslate { use_y_as_x: false }),
 		entry!(KeyDown(PageUp); modifiers=[Shift], action_dispatch=NavigationMessage::TranslateCanvasByViewportFraction { delta: DVec2::new(1., 0.) }),
@@ -265,6 +306,7 @@ pub fn default_mapping() -> Mapping {
 		//
 		// PortfolioMessage
 		entry!(KeyDown(KeyO); modifiers=[Accel], action_dispatch=PortfolioMessage::OpenDocument),
+		entry!(KeyDown(KeyI); modifiers=[Accel, Shift], action_dispatch=PortfolioMessage::ImportLinked),
 		entry!(KeyDown(KeyI); modifiers=[Accel], action_dispatch=PortfolioMessage::Import),
 		entry!(KeyDown(Tab); modifiers=[Control], action_dispatch=PortfolioMessage::NextDocument),
 		entry!(KeyDown(Tab); modifiers=[Control, Shift], action_dispatch=PortfolioMessage::PrevDocument),
@@ -275,6 +317,7 @@ pub fn default_mapping() -> Mapping {
 		//
 		// DialogMessage
 		entry!(KeyDown(KeyW); modifiers=[Accel, Alt], action_dispatch=DialogMessage::CloseAllDocumentsWithConfirmation),
+		entry!(KeyDown(KeyE); modifiers=[Accel, Shift], action_dispatch=DialogMessage::RequestBatchExportDialog),
 		entry!(KeyDown(KeyE); modifiers=[Accel], action_dispatch=DialogMessage::RequestExportDialog),
 		entry!(KeyDown(KeyN); modifiers=[Accel], action_dispatch=DialogMessage::RequestNewDocumentDialog),
 		entry!(KeyDown(Comma); modifiers=[Accel], action_dispatch=DialogMessage::RequestPreferencesDialog),
@@ -1,4 +1,5 @@
 use super::utility_types::input_keyboard::KeysGroup;
+use super::utility_types::keymap::Keymap;
 use super::utility_types::misc::Mapping;
 use crate::messages::input_mapper::utility_types::input_keyboard::{self, Key};
 use crate::messages::prelude::*;
@@ -8,6 +9,8 @@ use std::fmt::Write;
 #[derive(Debug, Default)]
 pub struct InputMapperMessageHandler {
 	mapping: Mapping,
+	/// The user's changes to the default shortcuts the mapping was built with
+	keymap: Keymap,
 }
 
 impl MessageHandler<InputMapperMessage, (&InputPreprocessorMessageHandler, ActionList)> for InputMapperMessageHandler {
@@ -20,6 +23,18 @@ impl MessageHandler<InputMapperMessage, (&InputPreprocessorMessageHandler, Actio
 }
 
 impl InputMapperMessageHandler {
+	/// Rebuilds the mapping from the defaults with the keymap's changes, returning whether the keymap differs from the one already in use.
+	pub fn set_keymap(&mut self, keymap: &Keymap) -> bool {
+		if self.keymap == *keymap {
+			return false;
+		}
+
+		self.keymap = keymap.clone();
+		self.mapping = Mapping::default();
+		self.keymap.apply(&mut self.mapping);
+		true
+	}
+
 	pub fn hints(&self, actions: ActionList) -> String {
 		let mut output = String::new();
 		let mut actions = actions
@@ -205,6 +205,8 @@ pub enum Key {
 	Lmb,
 	Rmb,
 	Mmb,
+	MouseBack,
+	MouseForward,
 
 	// This has to be the last element in the enum
 	NumKeys,
@@ -56,11 +56,83 @@ impl ScrollDelta {
 	}
 }
 
+/// The extra state reported by a pen or stylus, which is absent for mouse and touch input.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StylusState {
+	/// How firmly the tip is pressed, from 0 to 1
+	pub pressure: f64,
+	/// The angle between the pen and the surface's normal in the plane of the X axis, from -90° to 90° (positive tilts toward the right)
+	pub tilt_x: f64,
+	/// The angle between the pen and the surface's normal in the plane of the Y axis, from -90° to 90° (positive tilts toward the user)
+	pub tilt_y: f64,
+	pub barrel_button: bool,
+}
+
+impl Default for StylusState {
+	fn default() -> Self {
+		Self {
+			pressure: 0.5,
+			tilt_x: 0.,
+			tilt_y: 0.,
+			barrel_button: false,
+		}
+	}
+}
+
+impl StylusState {
+	/// Reads [pressure, tilt X, tilt Y, barrel button], with the barrel button given as 0 or 1.
+	pub fn from_slice(slice: &[f64]) -> Self {
+		Self {
+			pressure: slice[0].clamp(0., 1.),
+			tilt_x: slice[1].clamp(-90., 90.),
+			tilt_y: slice[2].clamp(-90., 90.),
+			barrel_button: slice[3] != 0.,
+		}
+	}
+
+	/// The direction the pen leans toward in the viewport, in radians, or `None` if it's held upright.
+	pub fn tilt_direction(&self) -> Option<f64> {
+		let tilt = DVec2::new(self.tilt_x.to_radians().tan(), self.tilt_y.to_radians().tan());
+		(tilt.length_squared() > f64::EPSILON).then(|| tilt.y.atan2(tilt.x))
+	}
+}
+
+/// The arrangement of the fingers touching the viewport in a multi-touch gesture, measured from the first two of them.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TouchGesture {
+	/// The point between the fingers
+	pub centroid: ViewportPosition,
+	/// The distance between the fingers
+	pub spread: f64,
+	/// The angle of the line from the first finger to the second, in radians
+	pub angle: f64,
+	/// How many fingers are touching, since the gesture restarts whenever one is added or lifted
+	pub touches: usize,
+}
+
+impl TouchGesture {
+	/// Returns `None` unless at least two fingers are touching at distinct positions.
+	pub fn from_touches(touches: &[ViewportPosition]) -> Option<Self> {
+		let (first, second) = match touches {
+			[first, second, ..] => (*first, *second),
+			_ => return None,
+		};
+		let offset = second - first;
+		(offset.length_squared() > f64::EPSILON).then(|| Self {
+			centroid: first.lerp(second, 0.5),
+			spread: offset.length(),
+			angle: offset.y.atan2(offset.x),
+			touches: touches.len(),
+		})
+	}
+}
+
 #[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct MouseState {
 	pub position: ViewportPosition,
 	pub mouse_keys: MouseKeys,
 	pub scroll_delta: ScrollDelta,
+	pub stylus: Option<StylusState>,
 }
 
 impl MouseState {
@@ -73,6 +145,7 @@ impl MouseState {
 			position: (x, y).into(),
 			mouse_keys: MouseKeys::default(),
 			scroll_delta: ScrollDelta::default(),
+			stylus: None,
 		}
 	}
 
@@ -83,6 +156,7 @@ impl MouseState {
 			position,
 			mouse_keys,
 			scroll_delta: ScrollDelta::default(),
+			stylus: None,
 		}
 	}
 }
@@ -92,6 +166,7 @@ pub struct EditorMouseState {
 	pub editor_position: EditorPosition,
 	pub mouse_keys: MouseKeys,
 	pub scroll_delta: ScrollDelta,
+	pub stylus: Option<StylusState>,
 }
 
 impl EditorMouseState {
@@ -104,6 +179,7 @@ impl EditorMouseState {
 			editor_position: (x, y).into(),
 			mouse_keys: MouseKeys::default(),
 			scroll_delta: ScrollDelta::default(),
+			stylus: None,
 		}
 	}
 
@@ -114,6 +190,7 @@ impl EditorMouseState {
 			editor_position,
 			mouse_keys,
 			scroll_delta: ScrollDelta::default(),
+			stylus: None,
 		}
 	}
 
@@ -122,6 +199,7 @@ impl EditorMouseState {
 			position: self.editor_position - active_viewport_bounds.top_left,
 			mouse_keys: self.mouse_keys,
 			scroll_delta: self.scroll_delta,
+			stylus: self.stylus,
 		}
 	}
 }
@@ -130,9 +208,11 @@ bitflags! {
 	#[derive(Default, Serialize, Deserialize)]
 	#[repr(transparent)]
 	pub struct MouseKeys: u8 {
-		const LEFT   = 0b0000_0001;
-		const RIGHT  = 0b0000_0010;
-		const MIDDLE = 0b0000_0100;
-		const NONE   = 0b0000_0000;
+		const LEFT    = 0b0000_0001;
+		const RIGHT   = 0b0000_0010;
+		const MIDDLE  = 0b0000_0100;
+		const BACK    = 0b0000_1000;
+		const FORWARD = 0b0001_0000;
+		const NONE    = 0b0000_0000;
 	}
 }
@@ -0,0 +1,593 @@
+//! Keymaps are the user's changes to the default keyboard shortcuts, which are saved with the preferences and exchanged as keymap files.
+//! Besides keys, shortcuts can be mouse buttons and the scroll wheel chorded with modifier keys, like `Space+LeftMouse` or `Alt+Scroll`.
+//!
+//! A binding names its action by an ID made of the path of the action's message and the values it carries,
+//! like `Portfolio.Document.Undo` or `Portfolio.Document.NudgeSelectedLayers { delta_x: 0.0, delta_y: -1.0 }`,
+//! so keymap files stay readable and keep working when unrelated shortcuts are added to the defaults.
+
+use super::input_keyboard::{Key, KeyStates, NUMBER_OF_KEYS};
+use super::misc::{KeyMappingEntries, Mapping, MappingEntry};
+use crate::messages::input_mapper::default_mapping::default_mapping;
+use crate::messages::prelude::*;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keymap {
+	pub name: String,
+	pub bindings: Vec<KeyBinding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+	/// The ID of the action, as given by [action_id]
+	pub action: String,
+	/// The shortcuts replacing all of the action's default shortcuts, where none leaves the action unbound
+	pub shortcuts: Vec<Shortcut>,
+}
+
+impl Keymap {
+	/// The shortcuts the keymap binds the action to, or `None` if it keeps the defaults.
+	pub fn shortcuts(&self, action: &str) -> Option<&[Shortcut]> {
+		self.bindings.iter().find(|binding| binding.action == action).map(|binding| binding.shortcuts.as_slice())
+	}
+
+	/// Binds the action to the shortcuts, dropping the binding instead if they're the same as the defaults.
+	pub fn bind(&mut self, action: &BindableAction, shortcuts: Vec<Shortcut>) {
+		self.bindings.retain(|binding| binding.action != action.id);
+		if shortcuts != action.default_shortcuts {
+			self.bindings.push(KeyBinding { action: action.id.clone(), shortcuts });
+		}
+	}
+
+	/// Replaces the default shortcuts of the bound actions in the mapping.
+	pub fn apply(&self, mapping: &mut Mapping) {
+		if self.bindings.is_empty() {
+			return;
+		}
+
+		let actions = bindable_actions();
+		for binding in &self.bindings {
+			let bindable = match actions.iter().find(|action| action.id == binding.action) {
+				Some(bindable) => bindable,
+				None => {
+					warn!("The keymap binds the unknown action `{}`", binding.action);
+					continue;
+				}
+			};
+			let action = &bindable.action;
+
+			// Plain clicks are how tools are used rather than shortcuts, so those mappings of the action are kept
+			for entries in mapping.key_down.iter_mut().chain(std::iter::once(&mut mapping.wheel_scroll)) {
+				entries.0.retain(|entry| entry.action != *action || Shortcut::from_entry(entry).is_none());
+			}
+			for shortcut in &binding.shortcuts {
+				let entry = MappingEntry {
+					action: action.clone(),
+					input: shortcut.input.clone(),
+					modifiers: shortcut.modifier_states(),
+				};
+				match shortcut.input {
+					InputMapperMessage::KeyDown(key) => mapping.key_down[key as usize].0.insert(0, entry),
+					_ => mapping.wheel_scroll.0.insert(0, entry),
+				}
+
+				// Releasing the mouse button of a drag ends it, whichever button and modifiers it was started with
+				if let (Some(release), Some(button)) = (&bindable.release, shortcut.mouse_button()) {
+					let release_entry = MappingEntry {
+						action: release.clone(),
+						input: InputMapperMessage::KeyUp(button),
+						modifiers: shortcut.modifier_states(),
+					};
+					let entries = &mut mapping.key_up[button as usize].0;
+					if !entries.iter().any(|entry| entry.action == release_entry.action && entry.modifiers == release_entry.modifiers) {
+						entries.insert(0, release_entry);
+					}
+				}
+			}
+		}
+
+		// Like the defaults, the entries requiring the most modifiers are matched first
+		let sort = |entries: &mut KeyMappingEntries| entries.0.sort_by_key(|entry| std::cmp::Reverse(entry.modifiers.ones()));
+		mapping.key_down.iter_mut().chain(mapping.key_up.iter_mut()).for_each(sort);
+		sort(&mut mapping.wheel_scroll);
+	}
+
+	pub fn to_file(&self) -> String {
+		serde_json::to_string_pretty(self).expect("Failed to serialize the keymap")
+	}
+
+	pub fn from_file(content: &str) -> Result<Self, String> {
+		serde_json::from_str(content).map_err(|error| error.to_string())
+	}
+}
+
+/// The keymaps that can be picked as a starting point for customizing the keyboard shortcuts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeymapPreset {
+	Graphite,
+	Illustrator,
+}
+
+impl KeymapPreset {
+	pub fn name(&self) -> &'static str {
+		match self {
+			KeymapPreset::Graphite => "Graphite",
+			KeymapPreset::Illustrator => "Illustrator",
+		}
+	}
+
+	pub fn keymap(&self) -> Keymap {
+		let bind = |action: Message, shortcuts: &str| KeyBinding {
+			action: action_id(&action),
+			shortcuts: Shortcut::parse_list(shortcuts).expect("Invalid shortcut in a keymap preset"),
+		};
+
+		let bindings = match self {
+			KeymapPreset::Graphite => Vec::new(),
+			KeymapPreset::Illustrator => vec![
+				bind(ToolMessage::ActivateToolGradient.into(), "G"),
+				bind(ToolMessage::ActivateToolEllipse.into(), "L"),
+				bind(ToolMessage::ActivateToolLine.into(), "\\"),
+				bind(ToolMessage::ActivateToolFill.into(), "K"),
+				bind(ToolMessage::ResetColors.into(), "D"),
+				bind(TransformLayerMessage::BeginGrab.into(), ""),
+				bind(DocumentMessage::DeselectAllLayers.into(), "Ctrl+Shift+A"),
+				bind(DocumentMessage::LockSelectedLayers.into(), "Ctrl+2"),
+				bind(DocumentMessage::UnlockAllLayers.into(), "Ctrl+Alt+2"),
+				bind(DocumentMessage::ZoomCanvasTo200Percent.into(), ""),
+			],
+		};
+
+		Keymap { name: self.name().into(), bindings }
+	}
+}
+
+/// A key or mouse button pressed, or the scroll wheel turned, while holding down the modifier keys, written like `Ctrl+Shift+G` (where `Ctrl` stands for the Command key on Mac).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcut {
+	/// Sorted in the order they're written in
+	pub modifiers: Vec<Key>,
+	/// Either a `KeyDown` or a `WheelScroll`
+	pub input: InputMapperMessage,
+}
+
+impl Shortcut {
+	pub fn new(mut modifiers: Vec<Key>, input: InputMapperMessage) -> Self {
+		modifiers.sort_by_key(|modifier| MODIFIER_ORDER.iter().position(|key| key == modifier).unwrap_or(MODIFIER_ORDER.len()));
+		modifiers.dedup();
+		Self { modifiers, input }
+	}
+
+	/// The shortcut of a mapping entry, unless it's a plain left or right click which tools rely on, or an input that isn't pressed like pointer movement.
+	fn from_entry(entry: &MappingEntry) -> Option<Self> {
+		let modifiers = || entry.modifiers.iter().map(key_from_index).collect();
+		match entry.input {
+			InputMapperMessage::KeyDown(Key::Lmb | Key::Rmb) if entry.modifiers.ones() == 0 => None,
+			InputMapperMessage::KeyDown(_) | InputMapperMessage::WheelScroll => Some(Self::new(modifiers(), entry.input.clone())),
+			_ => None,
+		}
+	}
+
+	fn mouse_button(&self) -> Option<Key> {
+		match self.input {
+			InputMapperMessage::KeyDown(key) if is_mouse_button(key) => Some(key),
+			_ => None,
+		}
+	}
+
+	fn modifier_states(&self) -> KeyStates {
+		let mut states = KeyStates::new();
+		for modifier in &self.modifiers {
+			states.set(*modifier as usize);
+		}
+		states
+	}
+
+	/// Reads the alternative shortcuts separated by "or", like `Del or Backspace`, returning the part that isn't a key if there is one.
+	pub fn parse_list(text: &str) -> Result<Vec<Self>, String> {
+		text.split_whitespace().filter(|part| !part.eq_ignore_ascii_case("or")).map(Self::from_str).collect()
+	}
+
+	pub fn format_list(shortcuts: &[Self]) -> String {
+		shortcuts.iter().map(Self::to_string).collect::<Vec<_>>().join(" or ")
+	}
+}
+
+impl fmt::Display for Shortcut {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for modifier in &self.modifiers {
+			write!(f, "{}+", key_name(*modifier))?;
+		}
+		match self.input {
+			InputMapperMessage::KeyDown(key) => write!(f, "{}", key_name(key)),
+			_ => write!(f, "{}", SCROLL_NAME),
+		}
+	}
+}
+
+impl FromStr for Shortcut {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		let mut keys = text
+			.split('+')
+			.map(|name| name.trim())
+			.filter(|name| !name.is_empty())
+			.filter(|name| !name.eq_ignore_ascii_case(SCROLL_NAME))
+			.map(|name| key_from_name(name).ok_or_else(|| name.to_string()))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let last = text.rsplit('+').next().unwrap_or_default().trim();
+		if last.eq_ignore_ascii_case(SCROLL_NAME) {
+			return Ok(Self::new(keys, InputMapperMessage::WheelScroll));
+		}
+
+		let key = keys.pop().ok_or_else(|| text.to_string())?;
+		Ok(Self::new(keys, InputMapperMessage::KeyDown(key)))
+	}
+}
+
+impl Serialize for Shortcut {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.to_string())
+	}
+}
+
+impl<'de> Deserialize<'de> for Shortcut {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let text = String::deserialize(deserializer)?;
+		text.parse().map_err(|name| serde::de::Error::custom(format!("`{}` isn't a key", name)))
+	}
+}
+
+/// An action that can be bound to shortcuts, which is any action one of the default shortcuts triggers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindableAction {
+	pub id: String,
+	pub label: String,
+	pub action: Message,
+	pub default_shortcuts: Vec<Shortcut>,
+	/// The action ending a drag begun by this action, which is sent when the mouse button of the shortcut is released
+	pub release: Option<Message>,
+}
+
+impl BindableAction {
+	/// Actions of different tools or of the modal layer transformation can share shortcuts since they're never available at the same time.
+	fn scope(&self) -> &str {
+		let path = self.id.split([' ', '(']).next().unwrap_or_default();
+		let segments = path.split('.').collect::<Vec<_>>();
+		match segments[..] {
+			["Tool", _, _] | ["Portfolio", "Document", "TransformLayer", _] => &path[..path.rfind('.').unwrap_or_default()],
+			_ => "",
+		}
+	}
+
+	/// The other actions in the same scope which share one of these shortcuts.
+	pub fn conflicts<'a>(&self, shortcuts: &[Shortcut], actions: &'a [BindableAction], keymap: &Keymap) -> Vec<&'a BindableAction> {
+		actions
+			.iter()
+			.filter(|other| other.id != self.id && other.scope() == self.scope())
+			.filter(|other| {
+				let other_shortcuts = keymap.shortcuts(&other.id).unwrap_or(&other.default_shortcuts);
+				other_shortcuts.iter().any(|shortcut| shortcuts.contains(shortcut))
+			})
+			.collect()
+	}
+}
+
+/// Every action triggered by one of the default shortcuts, sorted by label.
+pub fn bindable_actions() -> Vec<BindableAction> {
+	let mapping = default_mapping();
+
+	// Actions which also respond to the pointer only use keys to refresh modifier states, so they aren't shortcuts
+	let pointer_actions = [&mapping.pointer_move, &mapping.double_click]
+		.into_iter()
+		.chain(mapping.key_up.iter())
+		.flat_map(|entries| entries.0.iter().map(|entry| &entry.action))
+		.collect::<Vec<_>>();
+
+	let mut actions: Vec<BindableAction> = Vec::new();
+	for entry in mapping.key_down.iter().chain(std::iter::once(&mapping.wheel_scroll)).flat_map(|entries| entries.0.iter()) {
+		let shortcut = match Shortcut::from_entry(entry) {
+			Some(shortcut) if !pointer_actions.contains(&&entry.action) => shortcut,
+			_ => continue,
+		};
+
+		match actions.iter_mut().find(|action| action.action == entry.action) {
+			Some(action) => action.default_shortcuts.push(shortcut),
+			None => actions.push(BindableAction {
+				id: action_id(&entry.action),
+				label: action_label(&entry.action),
+				action: entry.action.clone(),
+				default_shortcuts: vec![shortcut],
+				release: None,
+			}),
+		}
+	}
+
+	for action in actions.iter_mut() {
+		action.release = action.default_shortcuts.iter().find_map(|shortcut| release_action(&mapping, &action.action, shortcut));
+	}
+
+	actions.sort_by(|a, b| a.label.cmp(&b.label));
+	actions
+}
+
+/// The action the default mapping sends when the mouse button of the shortcut is released, if it belongs with the action like the end of a canvas drag.
+fn release_action(mapping: &Mapping, action: &Message, shortcut: &Shortcut) -> Option<Message> {
+	let button = shortcut.mouse_button()?;
+	let modifiers = shortcut.modifier_states();
+	let parent = |message: &Message| {
+		let path = message.to_discriminant().local_name();
+		path[..path.rfind('.').unwrap_or_default()].to_string()
+	};
+
+	// The entries are sorted by the number of modifiers, so this finds the release entry most specific to the modifiers the shortcut is pressed with
+	mapping.key_up[button as usize]
+		.0
+		.iter()
+		.find(|entry| entry.modifiers.iter().all(|modifier| modifiers.get(modifier)) && parent(&entry.action) == parent(action))
+		.map(|entry| entry.action.clone())
+}
+
+/// The path of the action's message followed by the values it carries, like `Portfolio.Document.NudgeSelectedLayers { delta_x: 0.0, delta_y: -1.0 }`.
+pub fn action_id(action: &Message) -> String {
+	let path = action.to_discriminant().local_name();
+	let depth = path.matches('.').count();
+
+	// Skip past the enums the message is nested in, leaving its own variant and values
+	let debug = format!("{:?}", action);
+	let mut inner = debug.as_str();
+	for _ in 0..depth {
+		inner = inner.find('(').map_or(inner, |index| &inner[index + 1..]);
+	}
+	let inner = &inner[..inner.len().saturating_sub(depth)];
+	let variant = path.rsplit('.').next().unwrap_or_default();
+	let values = inner.strip_prefix(variant).unwrap_or_default();
+
+	format!("{}{}", path, values)
+}
+
+/// A readable name for the action, like "Document: Nudge Selected Layers { delta_x: 0.0, delta_y: -1.0 }".
+fn action_label(action: &Message) -> String {
+	let id = action_id(action);
+	let (path, values) = id.split_at(id.find([' ', '(']).unwrap_or(id.len()));
+	let segments = path.split('.').collect::<Vec<_>>();
+
+	let context = match segments[..] {
+		["Tool", tool, _] => format!("{} Tool", split_words(tool)),
+		[.., context, _] => split_words(context),
+		_ => String::new(),
+	};
+	let name = split_words(segments.last().copied().unwrap_or_default());
+
+	format!("{}: {}{}", context, name, values)
+}
+
+fn split_words(name: &str) -> String {
+	let mut words = String::new();
+	for (index, character) in name.char_indices() {
+		if index > 0 && character.is_uppercase() {
+			words.push(' ');
+		}
+		words.push(character);
+	}
+	words
+}
+
+const MODIFIER_ORDER: [Key; 6] = [Key::Accel, Key::Control, Key::Alt, Key::Shift, Key::Meta, Key::Command];
+
+/// The name of the scroll wheel in shortcuts, where it takes the place of the key
+const SCROLL_NAME: &str = "Scroll";
+
+fn is_mouse_button(key: Key) -> bool {
+	matches!(key, Key::Lmb | Key::Rmb | Key::Mmb | Key::MouseBack | Key::MouseForward)
+}
+
+fn key_from_index(index: usize) -> Key {
+	// TODO: Use a safe solution eventually
+	assert!(
+		index < NUMBER_OF_KEYS,
+		"Attempting to convert a Key with enum index {}, which is larger than the number of Key enums",
+		index
+	);
+	unsafe { std::mem::transmute_copy::<usize, Key>(&index) }
+}
+
+/// The name of the key in shortcuts, which unlike its label doesn't depend on the platform so keymap files can be shared.
+fn key_name(key: Key) -> String {
+	let name = match key {
+		Key::Accel => "Ctrl",
+		Key::Backquote => "`",
+		Key::Backslash => "\\",
+		Key::BracketLeft => "[",
+		Key::BracketRight => "]",
+		Key::Comma => ",",
+		Key::Equal => "=",
+		Key::Minus => "-",
+		Key::Period => ".",
+		Key::Quote => "'",
+		Key::Semicolon => ";",
+		Key::Slash => "/",
+		Key::Escape => "Esc",
+		Key::Delete => "Del",
+		Key::ArrowUp => "Up",
+		Key::ArrowDown => "Down",
+		Key::ArrowLeft => "Left",
+		Key::ArrowRight => "Right",
+		Key::Lmb => "LeftMouse",
+		Key::Rmb => "RightMouse",
+		Key::Mmb => "MiddleMouse",
+		Key::MouseBack => "BackMouse",
+		Key::MouseForward => "ForwardMouse",
+		_ => {
+			let name = format!("{:?}", key);
+			return match name.strip_prefix("Key").or_else(|| name.strip_prefix("Digit")) {
+				Some(character) if character.len() == 1 => character.to_string(),
+				_ => name,
+			};
+		}
+	};
+	name.to_string()
+}
+
+fn key_from_name(name: &str) -> Option<Key> {
+	let alias = match name.to_lowercase().as_str() {
+		"cmd" | "command" | "accel" => Some(Key::Accel),
+		"option" | "opt" => Some(Key::Alt),
+		"win" | "super" => Some(Key::Meta),
+		"escape" => Some(Key::Escape),
+		"delete" => Some(Key::Delete),
+		_ => None,
+	};
+
+	alias.or_else(|| {
+		(0..NUMBER_OF_KEYS)
+			.map(key_from_index)
+			.filter(|key| *key != Key::Unidentified)
+			.find(|key| key_name(*key).eq_ignore_ascii_case(name) || format!("{:?}", key).eq_ignore_ascii_case(name))
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn action(actions: &[BindableAction], message: impl Into<Message>) -> &BindableAction {
+		let id = action_id(&message.into());
+		actions.iter().find(|action| action.id == id).unwrap()
+	}
+
+	fn shortcuts(text: &str) -> Vec<Shortcut> {
+		Shortcut::parse_list(text).unwrap()
+	}
+
+	#[test]
+	fn shortcuts_are_written_with_their_modifiers_in_order() {
+		let shortcut: Shortcut = "Shift + Ctrl + g".parse().unwrap();
+		assert_eq!(shortcut, Shortcut::new(vec![Key::Accel, Key::Shift], InputMapperMessage::KeyDown(Key::KeyG)));
+		assert_eq!(shortcut.to_string(), "Ctrl+Shift+G");
+		assert_eq!("Cmd+Option+Escape".parse::<Shortcut>().unwrap().to_string(), "Ctrl+Alt+Esc");
+		assert_eq!(Shortcut::format_list(&shortcuts("Del or Backspace")), "Del or Backspace");
+
+		assert_eq!("Ctrl+Hyper+G".parse::<Shortcut>(), Err("Hyper".to_string()));
+		assert_eq!(Shortcut::parse_list(""), Ok(Vec::new()));
+	}
+
+	#[test]
+	fn keymap_files_round_trip() {
+		for preset in [KeymapPreset::Graphite, KeymapPreset::Illustrator] {
+			let keymap = preset.keymap();
+			assert_eq!(Keymap::from_file(&keymap.to_file()), Ok(keymap));
+		}
+
+		let file = r#"{ "name": "Mine", "bindings": [{ "action": "Portfolio.Document.Undo", "shortcuts": ["Ctrl+U", "F1"] }] }"#;
+		let keymap = Keymap::from_file(file).unwrap();
+		assert_eq!(keymap.shortcuts("Portfolio.Document.Undo"), Some(&shortcuts("Ctrl+U or F1")[..]));
+		assert_eq!(keymap.shortcuts("Portfolio.Document.Redo"), None);
+
+		let error = Keymap::from_file(r#"{ "name": "Mine", "bindings": [{ "action": "Portfolio.Document.Undo", "shortcuts": ["Ctrl+Hyper"] }] }"#).unwrap_err();
+		assert!(error.contains("`Hyper` isn't a key"), "{error}");
+		assert!(Keymap::from_file("{").is_err());
+	}
+
+	#[test]
+	fn binding_the_defaults_drops_the_binding() {
+		let actions = bindable_actions();
+		let undo = action(&actions, DocumentMessage::Undo);
+		assert_eq!(undo.default_shortcuts, shortcuts("Ctrl+Z"));
+
+		let mut keymap = Keymap::default();
+		keymap.bind(undo, shortcuts("Ctrl+U"));
+		keymap.bind(undo, shortcuts("F1"));
+		assert_eq!(
+			keymap.bindings,
+			[KeyBinding {
+				action: undo.id.clone(),
+				shortcuts: shortcuts("F1")
+			}]
+		);
+		keymap.bind(undo, undo.default_shortcuts.clone());
+		assert!(keymap.bindings.is_empty());
+	}
+
+	#[test]
+	fn conflicts_follow_the_keymap() {
+		let actions = bindable_actions();
+		let (undo, redo) = (action(&actions, DocumentMessage::Undo), action(&actions, DocumentMessage::Redo));
+		let mut keymap = Keymap::default();
+
+		let conflicts = undo.conflicts(&shortcuts("Ctrl+Shift+Z"), &actions, &keymap);
+		assert_eq!(conflicts, [redo]);
+		// An action doesn't conflict with its own shortcuts
+		assert!(undo.conflicts(&undo.default_shortcuts, &actions, &keymap).is_empty());
+
+		// Once the other action is bound elsewhere, its default shortcut is free
+		keymap.bind(redo, shortcuts("Ctrl+Alt+Shift+F9"));
+		assert!(undo.conflicts(&shortcuts("Ctrl+Shift+Z"), &actions, &keymap).is_empty());
+		assert_eq!(undo.conflicts(&shortcuts("Ctrl+Alt+Shift+F9"), &actions, &keymap), [redo]);
+	}
+
+	#[test]
+	fn tools_only_conflict_within_their_scope() {
+		let bindable = |id: &str, shortcut: &str| BindableAction {
+			id: id.to_string(),
+			label: id.to_string(),
+			action: Message::NoOp,
+			default_shortcuts: shortcuts(shortcut),
+			release: None,
+		};
+		let actions = [
+			bindable("Tool.Select.Abort", "Esc"),
+			bindable("Tool.Path.Abort", "Esc"),
+			bindable("Tool.Path.Delete", "Esc"),
+			bindable("Portfolio.Document.DeselectAllLayers", "Esc"),
+			bindable("Portfolio.Document.TransformLayer.CancelTransformOperation", "Esc"),
+		];
+		let keymap = Keymap::default();
+		let conflicts = |index: usize| {
+			actions[index]
+				.conflicts(&shortcuts("Esc"), &actions, &keymap)
+				.iter()
+				.map(|action| action.id.as_str())
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(conflicts(0), Vec::<&str>::new());
+		assert_eq!(conflicts(1), ["Tool.Path.Delete"]);
+		assert_eq!(conflicts(3), Vec::<&str>::new());
+		assert_eq!(conflicts(4), Vec::<&str>::new());
+	}
+
+	#[test]
+	fn applied_bindings_replace_the_defaults() {
+		let actions = bindable_actions();
+		let undo = action(&actions, DocumentMessage::Undo);
+		let mut keymap = Keymap::default();
+		keymap.bind(undo, shortcuts("Ctrl+U"));
+
+		let mut mapping = default_mapping();
+		keymap.apply(&mut mapping);
+		let entries = |key: Key| {
+			mapping.key_down[key as usize]
+				.0
+				.iter()
+				.filter(|entry| entry.action == undo.action)
+				.map(|entry| entry.modifiers.clone())
+				.collect::<Vec<_>>()
+		};
+		assert!(entries(Key::KeyZ).is_empty());
+		assert_eq!(entries(Key::KeyU), [shortcuts("Ctrl+U")[0].modifier_states()]);
+		// Redo is still bound by default
+		assert!(mapping.key_down[Key::KeyZ as usize].0.iter().any(|entry| entry.action == DocumentMessage::Redo.into()));
+
+		// Actions the editor doesn't have are skipped
+		let unknown = Keymap::from_file(r#"{ "name": "Mine", "bindings": [{ "action": "Portfolio.Document.Nonexistent", "shortcuts": ["Ctrl+U"] }] }"#).unwrap();
+		let mut mapping = default_mapping();
+		unknown.apply(&mut mapping);
+		assert!(mapping.key_down[Key::KeyU as usize].0.is_empty());
+	}
+}
@@ -1,4 +1,5 @@
 pub mod input_keyboard;
 pub mod input_mouse;
+pub mod keymap;
 pub mod macros;
 pub mod misc;
@@ -1,5 +1,5 @@
 use crate::messages::input_mapper::utility_types::input_keyboard::{Key, ModifierKeys};
-use crate::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, ViewportBounds};
+use crate::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, EditorPosition, ViewportBounds};
 use crate::messages::prelude::*;
 
 pub use graphene::DocumentResponse;
@@ -17,5 +17,6 @@ pub enum InputPreprocessorMessage {
 	PointerDown { editor_mouse_state: EditorMouseState, modifier_keys: ModifierKeys },
 	PointerMove { editor_mouse_state: EditorMouseState, modifier_keys: ModifierKeys },
 	PointerUp { editor_mouse_state: EditorMouseState, modifier_keys: ModifierKeys },
+	TouchUpdate { touches: Vec<EditorPosition> },
 	WheelScroll { editor_mouse_state: EditorMouseState, modifier_keys: ModifierKeys },
 }
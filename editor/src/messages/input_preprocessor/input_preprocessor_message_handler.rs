@@ -1,5 +1,5 @@
 use crate::messages::input_mapper::utility_types::input_keyboard::{Key, KeyStates, ModifierKeys};
-use crate::messages::input_mapper::utility_types::input_mouse::{MouseKeys, MouseState, ViewportBounds};
+use crate::messages::input_mapper::utility_types::input_mouse::{MouseKeys, MouseState, TouchGesture, ViewportBounds, ViewportPosition};
 use crate::messages::portfolio::utility_types::KeyboardPlatformLayout;
 use crate::messages::prelude::*;
 
@@ -12,6 +12,10 @@ pub struct InputPreprocessorMessageHandler {
 	pub keyboard: KeyStates,
 	pub mouse: MouseState,
 	pub viewport_bounds: ViewportBounds,
+	/// The canvases of the other views of the document, which are only drawn in and don't take input
+	pub view_bounds: Vec<ViewportBounds>,
+	/// The multi-touch gesture in progress, as of the last touch update
+	pub touch_gesture: Option<TouchGesture>,
 }
 
 impl MessageHandler<InputPreprocessorMessage, KeyboardPlatformLayout> for InputPreprocessorMessageHandler {
@@ -22,15 +26,14 @@ impl MessageHandler<InputPreprocessorMessage, KeyboardPlatformLayout> for InputP
 		#[remain::sorted]
 		match message {
 			InputPreprocessorMessage::BoundsOfViewports { bounds_of_viewports } => {
-				assert_eq!(bounds_of_viewports.len(), 1, "Only one viewport is currently supported");
-
-				for bounds in bounds_of_viewports {
+				// The first viewport is the canvas of the active view, which is followed by the canvases of the document's other views
+				let mut bounds_of_viewports = bounds_of_viewports.into_iter();
+				if let Some(bounds) = bounds_of_viewports.next() {
 					let new_size = bounds.size();
 					let existing_size = self.viewport_bounds.size();
 
 					let translation = (new_size - existing_size) / 2.;
 
-					// TODO: Extend this to multiple viewports instead of setting it to the value of this last loop iteration
 					self.viewport_bounds = bounds;
 
 					responses.push_back(
@@ -52,6 +55,12 @@ impl MessageHandler<InputPreprocessorMessage, KeyboardPlatformLayout> for InputP
 					);
 					responses.push_back(FrontendMessage::TriggerViewportResize.into());
 				}
+
+				let view_bounds = bounds_of_viewports.collect::<Vec<_>>();
+				if view_bounds != self.view_bounds {
+					self.view_bounds = view_bounds;
+					responses.push_back(DocumentMessage::RenderDocument.into());
+				}
 			}
 			InputPreprocessorMessage::DoubleClick { editor_mouse_state, modifier_keys } => {
 				self.update_states_of_modifier_keys(modifier_keys, keyboard_platform, responses);
@@ -98,6 +107,23 @@ impl MessageHandler<InputPreprocessorMessage, KeyboardPlatformLayout> for InputP
 
 				self.translate_mouse_event(mouse_state, false, responses);
 			}
+			InputPreprocessorMessage::TouchUpdate { touches } => {
+				let touches = touches.iter().map(|position| *position - self.viewport_bounds.top_left).collect::<Vec<_>>();
+				let gesture = TouchGesture::from_touches(&touches);
+
+				match (self.touch_gesture, gesture) {
+					// The first finger already started an interaction with the active tool, which a second finger turns into navigation instead
+					(None, Some(_)) => {
+						responses.push_back(BroadcastEvent::ToolAbort.into());
+						self.mouse.mouse_keys = MouseKeys::NONE;
+					}
+					(Some(from), Some(to)) if from.touches == to.touches => responses.push_back(NavigationMessage::TouchGesture { from, to }.into()),
+					(Some(_), None) => responses.push_back(NavigationMessage::TransformCanvasEnd.into()),
+					_ => {}
+				}
+
+				self.touch_gesture = gesture;
+			}
 			InputPreprocessorMessage::WheelScroll { editor_mouse_state, modifier_keys } => {
 				self.update_states_of_modifier_keys(modifier_keys, keyboard_platform, responses);
 
@@ -118,7 +144,14 @@ impl MessageHandler<InputPreprocessorMessage, KeyboardPlatformLayout> for InputP
 
 impl InputPreprocessorMessageHandler {
 	fn translate_mouse_event(&mut self, mut new_state: MouseState, allow_first_button_down: bool, responses: &mut VecDeque<Message>) {
-		for (bit_flag, key) in [(MouseKeys::LEFT, Key::Lmb), (MouseKeys::RIGHT, Key::Rmb), (MouseKeys::MIDDLE, Key::Mmb)] {
+		let buttons = [
+			(MouseKeys::LEFT, Key::Lmb),
+			(MouseKeys::RIGHT, Key::Rmb),
+			(MouseKeys::MIDDLE, Key::Mmb),
+			(MouseKeys::BACK, Key::MouseBack),
+			(MouseKeys::FORWARD, Key::MouseForward),
+		];
+		for (bit_flag, key) in buttons {
 			// Calculate the intersection between the two key states
 			let old_down = self.mouse.mouse_keys & bit_flag == bit_flag;
 			let new_down = new_state.mouse_keys & bit_flag == bit_flag;
@@ -135,6 +168,11 @@ impl InputPreprocessorMessageHandler {
 			}
 		}
 
+		// Once every button is released, the viewport no longer has to be drawn at the draft quality used while dragging
+		if self.mouse.mouse_keys != MouseKeys::NONE && new_state.mouse_keys == MouseKeys::NONE {
+			responses.push_back(DocumentMessage::EndInteractiveRender.into());
+		}
+
 		self.mouse = new_state;
 	}
 
@@ -177,15 +215,28 @@ impl InputPreprocessorMessageHandler {
 		// IPP bounds are relative to the entire application
 		[(0., 0.).into(), self.viewport_bounds.bottom_right - self.viewport_bounds.top_left]
 	}
+
+	/// The viewport position where pasted or dropped content is placed: at the given position relative to the entire application if there is one,
+	/// otherwise under the cursor if it's over the viewport, otherwise in the center of the viewport.
+	pub fn paste_position(&self, editor_position: Option<(f64, f64)>) -> ViewportPosition {
+		let viewport_size = self.viewport_bounds.size();
+		match editor_position {
+			Some(position) => DVec2::from(position) - self.viewport_bounds.top_left,
+			None if self.mouse.position.cmpge(DVec2::ZERO).all() && self.mouse.position.cmplt(viewport_size).all() => self.mouse.position,
+			None => viewport_size / 2.,
+		}
+	}
 }
 
 #[cfg(test)]
 mod test {
 	use crate::messages::input_mapper::utility_types::input_keyboard::{Key, ModifierKeys};
-	use crate::messages::input_mapper::utility_types::input_mouse::EditorMouseState;
+	use crate::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, MouseKeys, StylusState, ViewportBounds};
 	use crate::messages::portfolio::utility_types::KeyboardPlatformLayout;
 	use crate::messages::prelude::*;
 
+	use glam::DVec2;
+
 	#[test]
 	fn process_action_mouse_move_handle_modifier_keys() {
 		let mut input_preprocessor = InputPreprocessorMessageHandler::default();
@@ -234,6 +285,72 @@ mod test {
 		assert_eq!(responses.pop_front(), Some(InputMapperMessage::KeyDown(Key::Shift).into()));
 	}
 
+	#[test]
+	fn process_action_pointer_move_keeps_stylus_state() {
+		let mut input_preprocessor = InputPreprocessorMessageHandler::default();
+
+		let stylus = StylusState::from_slice(&[0.75, -30., 120., 1.]);
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(MouseKeys::LEFT.bits(), (10., 20.).into());
+		editor_mouse_state.stylus = Some(stylus);
+		let modifier_keys = ModifierKeys::empty();
+		let message = InputPreprocessorMessage::PointerMove { editor_mouse_state, modifier_keys };
+
+		let mut responses = VecDeque::new();
+
+		input_preprocessor.process_message(message, KeyboardPlatformLayout::Standard, &mut responses);
+
+		assert_eq!(input_preprocessor.mouse.stylus, Some(stylus));
+		assert_eq!(stylus.tilt_y, 90.);
+		assert!(stylus.barrel_button);
+	}
+
+	#[test]
+	fn process_action_side_mouse_buttons_are_keys() {
+		let mut input_preprocessor = InputPreprocessorMessageHandler::default();
+		let modifier_keys = ModifierKeys::empty();
+		let mut responses = VecDeque::new();
+
+		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(MouseKeys::BACK.bits(), (10., 20.).into());
+		input_preprocessor.process_message(
+			InputPreprocessorMessage::PointerDown { editor_mouse_state, modifier_keys },
+			KeyboardPlatformLayout::Standard,
+			&mut responses,
+		);
+		assert_eq!(responses.pop_front(), Some(InputMapperMessage::KeyDown(Key::MouseBack).into()));
+
+		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(MouseKeys::NONE.bits(), (10., 20.).into());
+		input_preprocessor.process_message(
+			InputPreprocessorMessage::PointerUp { editor_mouse_state, modifier_keys },
+			KeyboardPlatformLayout::Standard,
+			&mut responses,
+		);
+		assert_eq!(responses.pop_front(), Some(InputMapperMessage::KeyUp(Key::MouseBack).into()));
+	}
+
+	#[test]
+	fn process_action_touch_update_recognizes_gesture() {
+		let mut input_preprocessor = InputPreprocessorMessageHandler::default();
+		let mut responses = VecDeque::new();
+
+		let touches = vec![DVec2::new(100., 100.), DVec2::new(200., 100.)];
+		input_preprocessor.process_message(InputPreprocessorMessage::TouchUpdate { touches }, KeyboardPlatformLayout::Standard, &mut responses);
+		assert_eq!(responses.pop_front(), Some(BroadcastEvent::ToolAbort.into()));
+
+		let touches = vec![DVec2::new(100., 100.), DVec2::new(100., 300.)];
+		input_preprocessor.process_message(InputPreprocessorMessage::TouchUpdate { touches }, KeyboardPlatformLayout::Standard, &mut responses);
+		let (from, to) = match responses.pop_front() {
+			Some(Message::Portfolio(PortfolioMessage::Document(DocumentMessage::Navigation(NavigationMessage::TouchGesture { from, to })))) => (from, to),
+			other => panic!("Expected a touch gesture navigation message, got {:?}", other),
+		};
+		assert_eq!(to.spread / from.spread, 2.);
+		assert_eq!(to.angle - from.angle, std::f64::consts::FRAC_PI_2);
+		assert_eq!(to.centroid - from.centroid, DVec2::new(-50., 100.));
+
+		input_preprocessor.process_message(InputPreprocessorMessage::TouchUpdate { touches: vec![] }, KeyboardPlatformLayout::Standard, &mut responses);
+		assert_eq!(responses.pop_front(), Some(NavigationMessage::TransformCanvasEnd.into()));
+		assert_eq!(input_preprocessor.touch_gesture, None);
+	}
+
 	#[test]
 	fn process_action_key_down_handle_modifier_keys() {
 		let mut input_preprocessor = InputPreprocessorMessageHandler::default();
@@ -268,4 +385,20 @@ mod test {
 		assert!(responses.contains(&InputMapperMessage::KeyDown(Key::Control).into()));
 		assert!(responses.contains(&InputMapperMessage::KeyDown(Key::Control).into()));
 	}
+
+	#[test]
+	fn paste_position_falls_back_to_cursor_then_center() {
+		let mut input_preprocessor = InputPreprocessorMessageHandler {
+			viewport_bounds: ViewportBounds::from_slice(&[100., 50., 500., 450.]),
+			..Default::default()
+		};
+
+		assert_eq!(input_preprocessor.paste_position(Some((150., 60.))), DVec2::new(50., 10.));
+
+		input_preprocessor.mouse.position = DVec2::new(30., 40.);
+		assert_eq!(input_preprocessor.paste_position(None), DVec2::new(30., 40.));
+
+		input_preprocessor.mouse.position = DVec2::new(-10., 40.);
+		assert_eq!(input_preprocessor.paste_position(None), DVec2::new(200., 200.));
+	}
 }
@@ -1,8 +1,10 @@
 use crate::application::generate_uuid;
+use crate::consts::ARTBOARD_CHECKERBOARD_SIZE;
 use crate::messages::prelude::*;
 
 use graphene::color::Color;
 use graphene::document::Document as GrapheneDocument;
+use graphene::layers::layer_info::LayerDataType;
 use graphene::layers::style::{self, Fill, RenderData, ViewMode};
 use graphene::layers::text_layer::FontCache;
 use graphene::DocumentResponse;
@@ -11,6 +13,7 @@ use graphene::Operation as DocumentOperation;
 
 use glam::DAffine2;
 use serde::{Deserialize, Serialize};
+use std::fmt::Write;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ArtboardMessageHandler {
@@ -86,12 +89,8 @@ impl MessageHandler<ArtboardMessage, &FontCache> for ArtboardMessageHandler {
 					)
 				} else {
 					let render_data = RenderData::new(ViewMode::Normal, font_cache, None);
-					responses.push_back(
-						FrontendMessage::UpdateDocumentArtboards {
-							svg: self.artboards_graphene_document.render_root(render_data),
-						}
-						.into(),
-					);
+					let svg = self.transparent_artboards_checkerboard(font_cache) + &self.artboards_graphene_document.render_root(render_data);
+					responses.push_back(FrontendMessage::UpdateDocumentArtboards { svg }.into());
 				}
 			}
 			ResizeArtboard { artboard, position, mut size } => {
@@ -124,4 +123,33 @@ impl ArtboardMessageHandler {
 	pub fn is_infinite_canvas(&self) -> bool {
 		self.artboard_ids.is_empty()
 	}
+
+	/// Renders a checkerboard in viewport space behind the artboards with a transparent background, which have no fill.
+	fn transparent_artboards_checkerboard(&self, font_cache: &FontCache) -> String {
+		let document = &self.artboards_graphene_document;
+		let transparent = self.artboard_ids.iter().filter(|&&id| {
+			let layer = document.layer(&[id]);
+			matches!(layer.map(|layer| &layer.data), Ok(LayerDataType::Shape(shape)) if matches!(shape.style.fill(), Fill::None))
+		});
+
+		let mut rects = String::new();
+		for &id in transparent {
+			if let Ok(Some([min, max])) = document.viewport_bounding_box(&[id], font_cache) {
+				let size = max - min;
+				let _ = write!(
+					rects,
+					r#"<rect x="{}" y="{}" width="{}" height="{}" fill="url(#artboard-checkerboard)" />"#,
+					min.x, min.y, size.x, size.y
+				);
+			}
+		}
+		if rects.is_empty() {
+			return rects;
+		}
+
+		let (square, pattern) = (ARTBOARD_CHECKERBOARD_SIZE, ARTBOARD_CHECKERBOARD_SIZE * 2.);
+		format!(
+			r##"<defs><pattern id="artboard-checkerboard" width="{pattern}" height="{pattern}" patternUnits="userSpaceOnUse"><rect width="{pattern}" height="{pattern}" fill="#ffffff" /><rect width="{square}" height="{square}" fill="#cccccc" /><rect x="{square}" y="{square}" width="{square}" height="{square}" fill="#cccccc" /></pattern></defs>{rects}"##
+		)
+	}
 }
@@ -0,0 +1,23 @@
+use crate::import_export::palette_file::PaletteFormat;
+use crate::messages::prelude::*;
+
+use graphene::color::Color;
+use graphene::layers::style::ColorStyleId;
+
+use serde::{Deserialize, Serialize};
+
+#[remain::sorted]
+#[impl_message(Message, DocumentMessage, ColorStyles)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum ColorStylesMessage {
+	// Messages
+	Create { name: String, color: Color },
+	Delete { id: ColorStyleId },
+	ExportPalette { format: PaletteFormat },
+	ImportPalette,
+	LinkSelectedLayersFill { id: Option<ColorStyleId> },
+	LinkSelectedLayersStroke { id: Option<ColorStyleId> },
+	LoadPaletteFile { data: Vec<u8> },
+	Rename { id: ColorStyleId, name: String },
+	SetColor { id: ColorStyleId, color: Color },
+}
@@ -0,0 +1,148 @@
+use crate::application::generate_uuid;
+use crate::consts::FILE_SAVE_SUFFIX;
+use crate::import_export::palette_file::{read_palette, write_palette};
+use crate::messages::prelude::*;
+
+use graphene::color::ColorSpace;
+use graphene::document::Document as GrapheneDocument;
+use graphene::layers::style::ColorStyle;
+use graphene::{LayerId, Operation as DocumentOperation};
+
+/// Manages the document's color styles. Every edit is wrapped in a single transaction so it can be undone in one step,
+/// no matter how many layers are linked to the style.
+#[derive(Debug, Clone, Default)]
+pub struct ColorStylesMessageHandler {}
+
+impl MessageHandler<ColorStylesMessage, (&GrapheneDocument, &str, &[&[LayerId]])> for ColorStylesMessageHandler {
+	#[remain::check]
+	fn process_message(&mut self, message: ColorStylesMessage, (graphene_document, document_name, selected_layers): (&GrapheneDocument, &str, &[&[LayerId]]), responses: &mut VecDeque<Message>) {
+		use ColorStylesMessage::*;
+
+		#[remain::sorted]
+		match message {
+			Create { name, color } => {
+				let id = generate_uuid();
+				let color_style = ColorStyle::new(name, color);
+
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				responses.push_back(DocumentOperation::SetColorStyle { id, color_style }.into());
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			Delete { id } => {
+				if !graphene_document.color_styles.contains_key(&id) {
+					return;
+				}
+
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				responses.push_back(DocumentOperation::DeleteColorStyle { id }.into());
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			ExportPalette { format } => {
+				if graphene_document.color_styles.is_empty() {
+					responses.push_back(
+						DialogMessage::DisplayDialogError {
+							title: "Failed to export palette".into(),
+							description: "The document has no color styles to export as swatches.".into(),
+						}
+						.into(),
+					);
+					return;
+				}
+
+				// Palettes hold sRGB colors, and since color styles have no order of their own, they're listed by name
+				let color_space = graphene_document.specified_color_space();
+				let mut color_styles = graphene_document
+					.color_styles
+					.values()
+					.map(|color_style| ColorStyle::new(color_style.name.clone(), color_style.color.convert_color_space(color_space, ColorSpace::Srgb)))
+					.collect::<Vec<_>>();
+				color_styles.sort_by_key(|color_style| color_style.name.to_lowercase());
+
+				let palette_name = document_name.trim_end_matches(FILE_SAVE_SUFFIX);
+				let data = write_palette(format, palette_name, &color_styles);
+				let name = format!("{}.{}", palette_name, format.extension());
+				responses.push_back(
+					FrontendMessage::TriggerBinaryFileDownload {
+						data,
+						name,
+						mime: format.mime().into(),
+					}
+					.into(),
+				);
+			}
+			ImportPalette => {
+				responses.push_back(FrontendMessage::TriggerImportPalette.into());
+			}
+			LinkSelectedLayersFill { id } => {
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				for path in selected_layers.iter().filter(|path| graphene_document.layer(path).and_then(|layer| layer.style()).is_ok()) {
+					responses.push_back(DocumentOperation::SetLayerFillColorStyle { path: path.to_vec(), id }.into());
+				}
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			LinkSelectedLayersStroke { id } => {
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				for path in selected_layers.iter().filter(|path| graphene_document.layer(path).and_then(|layer| layer.style()).is_ok()) {
+					responses.push_back(DocumentOperation::SetLayerStrokeColorStyle { path: path.to_vec(), id }.into());
+				}
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			LoadPaletteFile { data } => {
+				let palette = match read_palette(&data) {
+					Ok(palette) => palette,
+					Err(error) => {
+						responses.push_back(
+							DialogMessage::DisplayDialogError {
+								title: "Failed to import palette".into(),
+								description: error.to_string(),
+							}
+							.into(),
+						);
+						return;
+					}
+				};
+
+				// Swatches which are already color styles are skipped, so importing a palette again doesn't duplicate them
+				let color_space = graphene_document.specified_color_space();
+				let color_styles = palette
+					.into_iter()
+					.map(|swatch| ColorStyle::new(swatch.name, swatch.color.convert_color_space(ColorSpace::Srgb, color_space)))
+					.filter(|color_style| !graphene_document.color_styles.values().any(|existing| *existing == *color_style))
+					.collect::<Vec<_>>();
+				if color_styles.is_empty() {
+					return;
+				}
+
+				// The whole palette is imported in one step of the undo history
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				for color_style in color_styles {
+					responses.push_back(DocumentOperation::SetColorStyle { id: generate_uuid(), color_style }.into());
+				}
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			Rename { id, name } => {
+				let color_style = match graphene_document.color_styles.get(&id) {
+					Some(color_style) if color_style.name != name => ColorStyle::new(name, color_style.color),
+					_ => return,
+				};
+
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				responses.push_back(DocumentOperation::SetColorStyle { id, color_style }.into());
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			SetColor { id, color } => {
+				let color_style = match graphene_document.color_styles.get(&id) {
+					Some(color_style) if color_style.color != color => ColorStyle::new(color_style.name.clone(), color),
+					_ => return,
+				};
+
+				// A single operation updates every linked layer
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				responses.push_back(DocumentOperation::SetColorStyle { id, color_style }.into());
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+		}
+	}
+
+	advertise_actions!(ColorStylesMessageDiscriminant;);
+}
@@ -0,0 +1,7 @@
+mod color_styles_message;
+mod color_styles_message_handler;
+
+#[doc(inline)]
+pub use color_styles_message::{ColorStylesMessage, ColorStylesMessageDiscriminant};
+#[doc(inline)]
+pub use color_styles_message_handler::ColorStylesMessageHandler;
@@ -1,11 +1,26 @@
+use crate::import_export::dxf::DxfCurves;
 use crate::messages::frontend::utility_types::{ExportBounds, FileType};
+use crate::messages::portfolio::document::utility_types::animation_encoding::AnimationSettings;
+use crate::messages::portfolio::document::utility_types::batch_export::{ExportItemSettings, ExportSlice};
+use crate::messages::portfolio::document::utility_types::document_metadata::DocumentMetadata;
+use crate::messages::portfolio::document::utility_types::document_unit::DocumentUnit;
+use crate::messages::portfolio::document::utility_types::image_encoding::ChromaSubsampling;
 use crate::messages::portfolio::document::utility_types::layer_panel::LayerMetadata;
-use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, FlipAxis};
+use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, FlipAxis, PixelDensity, PixelSnapping};
+use crate::messages::portfolio::document::utility_types::sprite_sheet::SpriteSheetSettings;
+use crate::messages::portfolio::document::utility_types::svg_export::SvgExportOptions;
+use crate::messages::portfolio::document::utility_types::transform_settings::TransformSettings;
 use crate::messages::prelude::*;
 
 use graphene::boolean_ops::BooleanOperation as BooleanOperationType;
+use graphene::color::{CmykProfile, ColorSpace};
+use graphene::gpu_scene::ViewportRenderer;
+use graphene::image_trace::TraceOptions;
 use graphene::layers::blend_mode::BlendMode;
-use graphene::layers::style::ViewMode;
+use graphene::layers::image_layer::ImageLink;
+use graphene::layers::layer_info::LayerLabel;
+use graphene::layers::style::{EffectQuality, ImageFilterQuality, ViewMode};
+use graphene::operation_log::{LoggedOperation, SessionMessage};
 use graphene::LayerId;
 use graphene::Operation as DocumentOperation;
 use serde::{Deserialize, Serialize};
@@ -22,6 +37,12 @@ pub enum DocumentMessage {
 	Artboard(ArtboardMessage),
 	#[remain::unsorted]
 	#[child]
+	ColorStyles(ColorStylesMessage),
+	#[remain::unsorted]
+	#[child]
+	LayersPanel(LayersPanelMessage),
+	#[remain::unsorted]
+	#[child]
 	Navigation(NavigationMessage),
 	#[remain::unsorted]
 	#[child]
@@ -32,17 +53,31 @@ pub enum DocumentMessage {
 	#[remain::unsorted]
 	#[child]
 	PropertiesPanel(PropertiesPanelMessage),
+	#[remain::unsorted]
+	#[child]
+	TextStyles(TextStylesMessage),
 
 	// Messages
 	AbortTransaction,
 	AddSelectedLayers {
 		additional_layers: Vec<Vec<LayerId>>,
 	},
+	/// Adds an export slice covering the bounds of the selected layers, which stays in place as the layers are edited
+	AddSelectionExportSlice,
 	AlignSelectedLayers {
 		axis: AlignAxis,
 		aggregate: AlignAggregate,
 	},
+	/// Stores the batch export settings of the artboards and the export slices, then exports every enabled one into a zip archive
+	BatchExport {
+		zip_name: String,
+		artboard_settings: Vec<(LayerId, ExportItemSettings)>,
+		slices: Vec<ExportSlice>,
+	},
 	BooleanOperation(BooleanOperationType),
+	CloseView {
+		view: usize,
+	},
 	CommitTransaction,
 	CreateEmptyFolder {
 		container_path: Vec<LayerId>,
@@ -61,12 +96,58 @@ pub enum DocumentMessage {
 	DocumentHistoryForward,
 	DocumentStructureChanged,
 	DuplicateSelectedLayers,
+	/// Saves the font files the user loaded in the document, for the fonts its text uses.
+	EmbedFonts,
+	EmbedSelectedImages,
+	/// Renders the document again at full quality if effects were left out while the user was interacting with the viewport.
+	EndInteractiveRender,
 	ExportDocument {
 		file_name: String,
 		file_type: FileType,
 		scale_factor: f64,
+		supersampling: u32,
+		quality: u8,
+		chroma_subsampling: ChromaSubsampling,
 		bounds: ExportBounds,
+		transparent_background: bool,
+		trim_to_content: bool,
+		rasterize_unsupported: bool,
+		animation: AnimationSettings,
+		dxf_curves: DxfCurves,
+		convert_to_srgb: bool,
+		svg_options: SvgExportOptions,
+	},
+	ExportSpriteSheet {
+		name: String,
+		sprites: Vec<(String, ExportBounds)>,
+		settings: SpriteSheetSettings,
+	},
+	/// Moves the position and size of the path data of the selected layers into their transforms, the reverse of flattening them.
+	ExtractSelectedTransforms,
+	FinishAnimationExport {
+		pixels: Vec<u8>,
+		width: u32,
+		height: u32,
+	},
+	FinishBatchExport {
+		data: Vec<u8>,
+		lengths: Vec<u32>,
+	},
+	FinishImageTrace {
+		pixels: Vec<u8>,
+		width: u32,
+		height: u32,
 	},
+	FinishPdfExport {
+		raster_pixels: Vec<u8>,
+	},
+	FinishRasterExport {
+		pixels: Vec<u8>,
+		width: u32,
+		height: u32,
+	},
+	/// Moves the transforms of the selected layers into their path data, which is what exporters and boolean operations work with.
+	FlattenSelectedTransforms,
 	FlipSelectedLayers {
 		flip_axis: FlipAxis,
 	},
@@ -77,9 +158,25 @@ pub enum DocumentMessage {
 	ImaginateClear,
 	ImaginateGenerate,
 	ImaginateTerminate,
+	ImportSvg {
+		svg: String,
+		mouse: Option<(f64, f64)>,
+	},
+	IsolateSelectedFolder,
+	/// Joins the collaboration session with the given ID, replacing the document with the one edited in the session once its state arrives
+	JoinCollaboration {
+		session: String,
+	},
 	LayerChanged {
 		affected_layer_path: Vec<LayerId>,
 	},
+	LockSelectedLayers,
+	/// Adds an export slice for each selected layer, which follows the bounds of the layer as it's edited
+	MarkSelectedLayersExportable,
+	/// Applies the operations made to the document by the other editors editing it together with this one.
+	MergeOperations {
+		operations: Vec<LoggedOperation>,
+	},
 	MoveSelectedLayersTo {
 		folder_path: Vec<LayerId>,
 		insert_index: isize,
@@ -89,21 +186,38 @@ pub enum DocumentMessage {
 		layer_path: Vec<LayerId>,
 		delta: (f64, f64),
 	},
+	NewView,
 	NudgeSelectedLayers {
 		delta_x: f64,
 		delta_y: f64,
 	},
+	OutlineSelectedStrokes,
 	PasteImage {
 		mime: String,
 		image_data: Vec<u8>,
 		mouse: Option<(f64, f64)>,
+		in_working_color_space: bool,
+	},
+	PasteLinkedImage {
+		mime: String,
+		image_data: Vec<u8>,
+		link: ImageLink,
+	},
+	/// Handles a message from another editor in the document's collaboration session.
+	ReceiveCollaborationMessage {
+		message: SessionMessage,
 	},
 	Redo,
+	RefreshLinkedImages {
+		request_permission: bool,
+	},
+	RelinkSelectedImage,
 	RenameLayer {
 		layer_path: Vec<LayerId>,
 		new_name: String,
 	},
 	RenderDocument,
+	ReselectPrevious,
 	RollbackTransaction,
 	SaveDocument,
 	SelectAllLayers,
@@ -114,20 +228,42 @@ pub enum DocumentMessage {
 	SelectedLayersReorder {
 		relative_index_offset: isize,
 	},
+	SelectInverse,
 	SelectLayer {
 		layer_path: Vec<LayerId>,
 		ctrl: bool,
 		shift: bool,
 	},
+	SelectLayersWithLabel {
+		label: LayerLabel,
+	},
 	SetBlendModeForSelectedLayers {
 		blend_mode: BlendMode,
 	},
+	SetCmykProfile {
+		cmyk_profile: CmykProfile,
+	},
+	SetColorSpace {
+		color_space: ColorSpace,
+	},
+	SetDocumentMetadata {
+		metadata: DocumentMetadata,
+	},
+	SetDocumentUnit {
+		unit: DocumentUnit,
+	},
 	SetImageBlobUrl {
 		layer_path: Vec<LayerId>,
 		blob_url: String,
 		resolution: (f64, f64),
 		document_id: u64,
 	},
+	SetIsolatedFolder {
+		folder_path: Option<Vec<LayerId>>,
+	},
+	SetLabelForSelectedLayers {
+		label: Option<LayerLabel>,
+	},
 	SetLayerExpansion {
 		layer_path: Vec<LayerId>,
 		set_expanded: bool,
@@ -136,12 +272,34 @@ pub enum DocumentMessage {
 		layer_path: Vec<LayerId>,
 		name: String,
 	},
+	SetLayerThumbnail {
+		layer_path: Vec<LayerId>,
+		content_hash: u64,
+		data_url: String,
+	},
 	SetOpacityForSelectedLayers {
 		opacity: f64,
 	},
 	SetOverlaysVisibility {
 		visible: bool,
 	},
+	SetPixelPreviewDensity {
+		density: PixelDensity,
+	},
+	SetPixelSnapping {
+		pixel_snapping: PixelSnapping,
+	},
+	/// Changes the given render quality settings of the document, leaving the others as they are.
+	SetRenderQuality {
+		anti_aliasing: Option<bool>,
+		image_filter: Option<ImageFilterQuality>,
+		interactive_effects: Option<EffectQuality>,
+	},
+	SetRulerOriginToSelection,
+	/// Marks the document as saved or unsaved, like when a save is cancelled after the document was sent to be saved
+	SetSaveState {
+		is_saved: bool,
+	},
 	SetSelectedLayers {
 		replacement_selected_layers: Vec<Vec<LayerId>>,
 	},
@@ -152,30 +310,63 @@ pub enum DocumentMessage {
 		path: Vec<LayerId>,
 		editable: bool,
 	},
+	SetTransformSettings {
+		transform_settings: TransformSettings,
+	},
 	SetViewMode {
 		view_mode: ViewMode,
 	},
+	SetViewportRenderer {
+		viewport_renderer: ViewportRenderer,
+	},
+	SetWideGamut {
+		wide_gamut: bool,
+	},
+	/// Moves the anchors of the selected shapes, including those in selected folders, onto the pixel grid.
+	SnapSelectedToPixelGrid,
+	/// Starts a collaboration session with a generated ID, which is shown to be shared with the other editors joining it
+	StartCollaboration,
 	StartTransaction,
+	/// Leaves the collaboration session, after which the document is only edited by this editor again
+	StopCollaboration,
+	SwapView {
+		view: usize,
+	},
 	ToggleLayerExpansion {
 		layer_path: Vec<LayerId>,
 	},
+	ToggleLayerLock {
+		layer_path: Vec<LayerId>,
+	},
 	ToggleLayerVisibility {
 		layer_path: Vec<LayerId>,
 	},
+	ToggleOutlineViewMode,
 	ToggleSelectedHandleMirroring {
 		layer_path: Vec<LayerId>,
 		toggle_distance: bool,
 		toggle_angle: bool,
 	},
+	TraceImage {
+		layer_path: Vec<LayerId>,
+		options: TraceOptions,
+	},
 	Undo,
 	UngroupLayers {
 		folder_path: Vec<LayerId>,
 	},
 	UngroupSelectedLayers,
+	UnlockAllLayers,
 	UpdateLayerMetadata {
 		layer_path: Vec<LayerId>,
 		layer_metadata: LayerMetadata,
 	},
+	UpdateLinkedImage {
+		layer_path: Vec<LayerId>,
+		mime: String,
+		image_data: Vec<u8>,
+		link: ImageLink,
+	},
 	ZoomCanvasTo100Percent,
 	ZoomCanvasTo200Percent,
 	ZoomCanvasToFitAll,
@@ -1,17 +1,39 @@
+use super::utility_types::animation_encoding::{AnimationFormat, PendingAnimationExport};
+use super::utility_types::batch_export::{ExportItemSettings, ExportSlice, ExportSliceArea, PendingBatchExport};
+use super::utility_types::collaboration::Collaboration;
+use super::utility_types::color_profile::declare_svg_color_profile;
+use super::utility_types::document_file::{decode_document, encode_compressed_document};
+use super::utility_types::document_metadata::DocumentMetadata;
+use super::utility_types::document_unit::DocumentUnit;
+use super::utility_types::document_view::DocumentView;
 use super::utility_types::error::EditorError;
-use super::utility_types::misc::DocumentRenderMode;
+use super::utility_types::image_encoding::{ChromaSubsampling, RasterEncoding};
+use super::utility_types::misc::{viewport_tiles, ArtworkUpdate, DocumentRenderMode, PixelDensity, PixelSnapping, SentArtworkSnapshots, SentGpuScene};
+use super::utility_types::sprite_sheet::{pack_sprites, sprite_sheet_atlas, sprite_sheet_svg, MAX_SPRITE_SHEET_SIZE};
+use super::utility_types::svg_export::{svg_document, SvgExportOptions, SvgExportSettings};
+use super::utility_types::transform_settings::TransformSettings;
 use crate::application::generate_uuid;
-use crate::consts::{ASYMPTOTIC_EFFECT, DEFAULT_DOCUMENT_NAME, FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION, SCALE_EFFECT, SCROLLBAR_SPACING, VIEWPORT_ZOOM_TO_FIT_PADDING_SCALE_FACTOR};
+use crate::consts::{
+	ASYMPTOTIC_EFFECT, DEFAULT_DOCUMENT_NAME, FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION, ISOLATION_DIM_ALPHA, ISOLATION_OVERLAY_Z_ORDER, LAYER_THUMBNAIL_SIZE, PIXEL_PREVIEW_GRID_MIN_SCALE,
+	SCALE_EFFECT, SCROLLBAR_SPACING, SELECTION_HISTORY_LENGTH, VIEWPORT_ZOOM_TO_FIT_PADDING_SCALE_FACTOR,
+};
+use crate::import_export::dxf::{dxf_document, dxf_to_svg, is_dxf, DxfCurves};
+use crate::import_export::lottie_export::{lottie_animation, LottieExportSettings};
+use crate::import_export::pdf_export::{PdfExport, PdfExportSettings};
+use crate::import_export::print_import::PrintFormat;
+use crate::import_export::svg_import::import_svg;
 use crate::messages::frontend::utility_types::ExportBounds;
-use crate::messages::frontend::utility_types::{FileType, FrontendImageData};
+use crate::messages::frontend::utility_types::{FileType, FrontendBatchRaster, FrontendDocumentView, FrontendImageData, FrontendLinkedImage};
+use crate::messages::input_mapper::utility_types::input_mouse::MouseKeys;
 use crate::messages::input_mapper::utility_types::macros::action_keys;
 use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
 use crate::messages::layout::utility_types::misc::LayoutTarget;
-use crate::messages::layout::utility_types::widgets::button_widgets::{IconButton, PopoverButton};
+use crate::messages::layout::utility_types::widgets::button_widgets::{IconButton, PopoverButton, TextButton};
 use crate::messages::layout::utility_types::widgets::input_widgets::{DropdownEntryData, DropdownInput, NumberInput, NumberInputIncrementBehavior, OptionalInput, RadioEntryData, RadioInput};
-use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::portfolio::document::overlays::utility_types::{Overlay, OverlayId, OverlayPrimitive, OverlayStyle};
 use crate::messages::portfolio::document::properties_panel::utility_types::PropertiesPanelMessageHandlerData;
-use crate::messages::portfolio::document::utility_types::clipboards::Clipboard;
+use crate::messages::portfolio::document::utility_types::clipboards::{svg_layer_data_metadata, Clipboard};
 use crate::messages::portfolio::document::utility_types::layer_panel::{LayerMetadata, LayerPanelEntry, RawBuffer};
 use crate::messages::portfolio::document::utility_types::misc::DocumentMode;
 use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, DocumentSave, FlipAxis};
@@ -19,15 +41,20 @@ use crate::messages::portfolio::document::utility_types::vectorize_layer_metadat
 use crate::messages::portfolio::utility_types::PersistentData;
 use crate::messages::prelude::*;
 
-use graphene::color::Color;
+use graphene::color::{Color, ColorSpace};
 use graphene::document::{pick_layer_safe_imaginate_resolution, Document as GrapheneDocument};
+use graphene::gpu_scene::ViewportRenderer;
+use graphene::image_trace::{trace_image, TraceOptions};
 use graphene::layers::blend_mode::BlendMode;
 use graphene::layers::folder_layer::FolderLayer;
+use graphene::layers::image_layer::ImageLink;
 use graphene::layers::imaginate_layer::{ImaginateBaseImage, ImaginateGenerationParameters, ImaginateStatus};
-use graphene::layers::layer_info::{LayerDataType, LayerDataTypeDiscriminant};
-use graphene::layers::style::{Fill, RenderData, ViewMode};
-use graphene::layers::text_layer::{Font, FontCache};
+use graphene::layers::layer_info::{Layer, LayerData, LayerDataType, LayerDataTypeDiscriminant};
+use graphene::layers::shape_layer::ShapeLayer;
+use graphene::layers::style::{Fill, PathStyle, RenderData, RenderQuality, ViewMode};
+use graphene::layers::text_layer::{EmbeddedFont, Font, FontCache};
 use graphene::layers::vector::subpath::Subpath;
+use graphene::operation_log::{MergeResult, OperationLog, SessionMessage};
 use graphene::{DocumentError, DocumentResponse, LayerId, Operation as DocumentOperation};
 
 use glam::{DAffine2, DVec2};
@@ -42,7 +69,12 @@ pub struct DocumentMessageHandler {
 
 	pub document_mode: DocumentMode,
 	pub view_mode: ViewMode,
+	#[serde(default)]
+	pub pixel_preview_density: PixelDensity,
 	pub snapping_enabled: bool,
+	/// Whether drawing and transforming rounds positions to whole or half pixels of the document
+	#[serde(default)]
+	pub pixel_snapping: PixelSnapping,
 	pub overlays_visible: bool,
 
 	#[serde(skip)]
@@ -55,12 +87,69 @@ pub struct DocumentMessageHandler {
 	layer_range_selection_reference: Vec<LayerId>,
 
 	navigation_handler: NavigationMessageHandler,
+	/// The other views of the document, besides the active one whose navigation state is kept by the navigation handler
+	#[serde(default)]
+	pub views: Vec<DocumentView>,
+	/// The collaboration session the document is edited in together with other editors, which logs the operations made by all of them so they converge to the same document
+	#[serde(skip)]
+	pub collaboration: Option<Collaboration>,
 	#[serde(skip)]
 	overlays_message_handler: OverlaysMessageHandler,
 	pub artboard_message_handler: ArtboardMessageHandler,
+	/// How each artboard is written by batch exports, with artboards missing from it using the default settings
+	#[serde(default)]
+	pub artboard_export_settings: HashMap<LayerId, ExportItemSettings>,
+	#[serde(default)]
+	pub export_slices: Vec<ExportSlice>,
+	/// The title, author, and other information embedded in exports of the document
+	#[serde(default)]
+	pub metadata: DocumentMetadata,
+	/// The aspect ratio locking, resize origin, and nudge distances that transforming the document's layers uses by default
+	#[serde(default)]
+	pub transform_settings: TransformSettings,
+	/// The unit that lengths are displayed in by the rulers and the properties of layers
+	#[serde(default)]
+	pub unit: DocumentUnit,
+	#[serde(skip)]
+	color_styles_message_handler: ColorStylesMessageHandler,
+	#[serde(skip)]
+	layers_panel_message_handler: LayersPanelMessageHandler,
 	#[serde(skip)]
 	transform_layer_handler: TransformLayerMessageHandler,
 	properties_panel_message_handler: PropertiesPanelMessageHandler,
+	#[serde(skip)]
+	text_styles_message_handler: TextStylesMessageHandler,
+	#[serde(skip)]
+	gpu_scene: Option<SentGpuScene>,
+	#[serde(skip)]
+	artwork_snapshots: SentArtworkSnapshots,
+	/// A PDF export waiting for the frontend to rasterize the content PDF can't represent
+	#[serde(skip)]
+	pending_pdf_export: Option<PdfExport>,
+	/// The file name and encoding of a JPEG or WebP export waiting for the frontend to rasterize the document
+	#[serde(skip)]
+	pending_raster_export: Option<(String, RasterEncoding, ColorSpace)>,
+	/// A batch export waiting for the frontend to rasterize its raster files
+	#[serde(skip)]
+	pending_batch_export: Option<PendingBatchExport>,
+	/// A GIF or APNG export waiting for the frontend to rasterize its frames
+	#[serde(skip)]
+	pending_animation_export: Option<PendingAnimationExport>,
+	/// The image layer and settings of a trace waiting for the frontend to decode the image
+	#[serde(skip)]
+	pending_image_trace: Option<(Vec<LayerId>, TraceOptions)>,
+	/// The rasterized thumbnails of the layers, with the hashes of the SVG thumbnails they were rasterized from
+	#[serde(skip)]
+	layer_thumbnails: HashMap<Vec<LayerId>, (u64, String)>,
+	/// The layers with thumbnails waiting for the frontend to rasterize them
+	#[serde(skip)]
+	pending_layer_thumbnails: HashSet<Vec<LayerId>>,
+	/// The overlay dimming the viewport around the isolated folder
+	#[serde(skip)]
+	isolation_overlay_id: OverlayId,
+	/// The layer selections which were replaced by other ones, from the oldest to the most recent, each sorted by layer path
+	#[serde(skip)]
+	selection_history: VecDeque<Vec<Vec<LayerId>>>,
 }
 
 impl Default for DocumentMessageHandler {
@@ -73,7 +162,9 @@ impl Default for DocumentMessageHandler {
 
 			document_mode: DocumentMode::DesignMode,
 			view_mode: ViewMode::default(),
+			pixel_preview_density: PixelDensity::default(),
 			snapping_enabled: true,
+			pixel_snapping: PixelSnapping::default(),
 			overlays_visible: true,
 
 			document_undo_history: Vec::new(),
@@ -83,10 +174,31 @@ impl Default for DocumentMessageHandler {
 			layer_range_selection_reference: Vec::new(),
 
 			navigation_handler: NavigationMessageHandler::default(),
+			views: Vec::new(),
+			collaboration: None,
 			overlays_message_handler: OverlaysMessageHandler::default(),
 			artboard_message_handler: ArtboardMessageHandler::default(),
+			artboard_export_settings: HashMap::new(),
+			export_slices: Vec::new(),
+			metadata: DocumentMetadata::default(),
+			transform_settings: TransformSettings::default(),
+			unit: DocumentUnit::default(),
+			color_styles_message_handler: ColorStylesMessageHandler::default(),
+			layers_panel_message_handler: LayersPanelMessageHandler::default(),
 			transform_layer_handler: TransformLayerMessageHandler::default(),
 			properties_panel_message_handler: PropertiesPanelMessageHandler::default(),
+			text_styles_message_handler: TextStylesMessageHandler::default(),
+			gpu_scene: None,
+			artwork_snapshots: SentArtworkSnapshots::default(),
+			pending_pdf_export: None,
+			pending_raster_export: None,
+			pending_batch_export: None,
+			pending_animation_export: None,
+			pending_image_trace: None,
+			layer_thumbnails: HashMap::new(),
+			pending_layer_thumbnails: HashSet::new(),
+			isolation_overlay_id: generate_uuid(),
+			selection_history: VecDeque::new(),
 		}
 	}
 }
@@ -105,7 +217,7 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 		match message {
 			// Sub-messages
 			#[remain::unsorted]
-			DispatchOperation(op) => match self.graphene_document.handle_operation(*op, &persistent_data.font_cache) {
+			DispatchOperation(op) => match self.apply_operation(*op, &persistent_data.font_cache, responses) {
 				Ok(Some(document_responses)) => {
 					for response in document_responses {
 						match &response {
@@ -142,6 +254,16 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 				self.artboard_message_handler.process_message(message, &persistent_data.font_cache, responses);
 			}
 			#[remain::unsorted]
+			ColorStyles(message) => {
+				let selected_layers = self.layer_metadata.iter().filter_map(|(path, data)| data.selected.then_some(path.as_slice())).collect::<Vec<_>>();
+				self.color_styles_message_handler
+					.process_message(message, (&self.graphene_document, &self.name, &selected_layers), responses);
+			}
+			#[remain::unsorted]
+			LayersPanel(message) => {
+				self.layers_panel_message_handler.process_message(message, (&self.graphene_document, &self.layer_metadata), responses);
+			}
+			#[remain::unsorted]
 			Navigation(message) => {
 				self.navigation_handler.process_message(message, (&self.graphene_document, ipp), responses);
 			}
@@ -152,8 +274,20 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 			}
 			#[remain::unsorted]
 			TransformLayer(message) => {
-				self.transform_layer_handler
-					.process_message(message, (&mut self.layer_metadata, &mut self.graphene_document, ipp, &persistent_data.font_cache), responses);
+				// Grabbing moves the selection by whole pixels of the document while pixel snapping is on
+				let pixel_snapping = if self.snapping_enabled { self.pixel_snapping } else { PixelSnapping::Off };
+				self.transform_layer_handler.process_message(
+					message,
+					(
+						&mut self.layer_metadata,
+						&mut self.graphene_document,
+						ipp,
+						&persistent_data.font_cache,
+						&self.transform_settings,
+						pixel_snapping,
+					),
+					responses,
+				);
 			}
 			#[remain::unsorted]
 			PropertiesPanel(message) => {
@@ -161,10 +295,17 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					artwork_document: &self.graphene_document,
 					artboard_document: &self.artboard_message_handler.artboards_graphene_document,
 					selected_layers: &mut self.layer_metadata.iter().filter_map(|(path, data)| data.selected.then_some(path.as_slice())),
+					ruler_origin: self.navigation_handler.ruler_origin,
+					unit: self.unit,
 				};
 				self.properties_panel_message_handler
 					.process_message(message, (persistent_data, properties_panel_message_handler_data), responses);
 			}
+			#[remain::unsorted]
+			TextStyles(message) => {
+				let selected_layers = self.layer_metadata.iter().filter_map(|(path, data)| data.selected.then_some(path.as_slice())).collect::<Vec<_>>();
+				self.text_styles_message_handler.process_message(message, (&self.graphene_document, &selected_layers), responses);
+			}
 
 			// Messages
 			AbortTransaction => {
@@ -182,6 +323,15 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 
 				self.update_layer_tree_options_bar_widgets(responses, &persistent_data.font_cache);
 			}
+			AddSelectionExportSlice => {
+				if let Some(bounds) = self.selected_visible_layers_document_bounding_box(&persistent_data.font_cache) {
+					self.export_slices.push(ExportSlice {
+						name: format!("Slice {}", self.export_slices.len() + 1),
+						area: ExportSliceArea::Rect(bounds),
+						settings: ExportItemSettings::default(),
+					});
+				}
+			}
 			AlignSelectedLayers { axis, aggregate } => {
 				self.backup(responses);
 				let (paths, boxes): (Vec<_>, Vec<_>) = self
@@ -219,6 +369,96 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					responses.push_back(BroadcastEvent::DocumentIsDirty.into());
 				}
 			}
+			BatchExport { zip_name, artboard_settings, slices } => {
+				self.artboard_export_settings = artboard_settings.into_iter().collect();
+				self.export_slices = slices;
+
+				let artboards = self
+					.artboard_names()
+					.into_iter()
+					.map(|(id, name)| (name, ExportBounds::Artboard(id), self.artboard_export_settings.get(&id).cloned().unwrap_or_default()));
+				let slices = self
+					.export_slices
+					.iter()
+					.enumerate()
+					.map(|(index, slice)| (slice.name.clone(), ExportBounds::Slice(index), slice.settings.clone()));
+				// Each item is exported once for every one of its presets
+				let items: Vec<_> = artboards
+					.chain(slices)
+					.filter(|(_, _, settings)| settings.enabled)
+					.flat_map(|(name, bounds, settings)| settings.presets.into_iter().map(move |preset| (name.clone(), bounds, preset)))
+					.collect();
+				if items.is_empty() {
+					responses.push_back(
+						DialogMessage::DisplayDialogError {
+							title: "Nothing to export".to_string(),
+							description: "Enable at least one artboard or export slice to include it in the batch export.".to_string(),
+						}
+						.into(),
+					);
+					return;
+				}
+
+				let mut export = PendingBatchExport::new(zip_name);
+				let mut rasters = Vec::new();
+				for (name, bounds, preset) in items {
+					let file_name = preset.file_name(&name);
+
+					if preset.file_type == FileType::Pdf {
+						// Content PDF can't represent is simplified, since rasterizing it would need another round trip to the frontend for each file
+						let pdf_settings = PdfExportSettings {
+							font_cache: &persistent_data.font_cache,
+							color_space: self.graphene_document.specified_color_space(),
+							rasterize_unsupported: false,
+							raster_scale: preset.scale_factor,
+						};
+						let pdf = self.export_pdf(name, bounds, false, false, &pdf_settings);
+						export.add_file(&file_name, pdf.finish(&[]));
+						continue;
+					}
+
+					let (svg, size) = self.export_svg(bounds, false, false, persistent_data);
+					// Slices of layers which have since been deleted have no bounds
+					if size.x <= 0. || size.y <= 0. {
+						continue;
+					}
+					let encoding = match preset.file_type {
+						FileType::Svg => {
+							let svg = declare_svg_color_profile(svg, self.graphene_document.specified_color_space());
+							export.add_file(&file_name, svg.into_bytes());
+							continue;
+						}
+						FileType::Jpg => Some(RasterEncoding::Jpeg {
+							quality: 90,
+							chroma_subsampling: ChromaSubsampling::default(),
+						}),
+						FileType::Webp => Some(RasterEncoding::Webp { quality: 90 }),
+						_ => None,
+					};
+					let size = (size * preset.scale_factor).round().max(DVec2::ONE).as_u32();
+					export.add_raster(&file_name, encoding, size.x, size.y);
+					rasters.push(FrontendBatchRaster {
+						svg,
+						width: size.x,
+						height: size.y,
+						encode_png: encoding.is_none(),
+					});
+				}
+
+				if export.has_rasters() {
+					let color_space = self.graphene_document.specified_color_space().canvas_color_space().to_string();
+					responses.push_back(FrontendMessage::TriggerBatchRasterization { rasters, color_space }.into());
+				} else {
+					responses.push_back(
+						FinishBatchExport {
+							data: Vec::new(),
+							lengths: Vec::new(),
+						}
+						.into(),
+					);
+				}
+				self.pending_batch_export = Some(export);
+			}
 			BooleanOperation(op) => {
 				// Convert Vec<&[LayerId]> to Vec<Vec<&LayerId>> because Vec<&[LayerId]> does not implement several traits (Debug, Serialize, Deserialize, ...) required by DocumentOperation enum
 				responses.push_back(StartTransaction.into());
@@ -231,6 +471,12 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 				);
 				responses.push_back(CommitTransaction.into());
 			}
+			CloseView { view } => {
+				if view < self.views.len() {
+					self.views.remove(view);
+					responses.push_back(RenderDocument.into());
+				}
+			}
 			CommitTransaction => (),
 			CreateEmptyFolder { mut container_path } => {
 				let id = generate_uuid();
@@ -300,6 +546,18 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 			DocumentHistoryBackward => self.undo(responses).unwrap_or_else(|e| warn!("{}", e)),
 			DocumentHistoryForward => self.redo(responses).unwrap_or_else(|e| warn!("{}", e)),
 			DocumentStructureChanged => {
+				// Forget the thumbnails of deleted layers
+				let document = &self.graphene_document;
+				self.layer_thumbnails.retain(|path, _| document.layer(path).is_ok());
+				self.pending_layer_thumbnails.retain(|path| document.layer(path).is_ok());
+
+				// Leave isolation once the isolated folder is gone
+				if let Some(folder_path) = self.graphene_document.isolated_folder.clone() {
+					if self.graphene_document.folder(&folder_path).is_err() {
+						responses.push_back(SetIsolatedFolder { folder_path: None }.into());
+					}
+				}
+
 				let data_buffer: RawBuffer = self.serialize_root().into();
 				responses.push_back(FrontendMessage::UpdateDocumentLayerTreeStructure { data_buffer }.into())
 			}
@@ -309,45 +567,334 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					responses.push_back(DocumentOperation::DuplicateLayer { path: path.to_vec() }.into());
 				}
 			}
+			EmbedFonts => {
+				// Only fonts loaded from the user's files are embedded, since fonts from the font catalog can be downloaded again
+				let font_cache = &persistent_data.font_cache;
+				let fonts: Vec<EmbeddedFont> = self
+					.graphene_document
+					.fonts()
+					.into_iter()
+					.filter(|&font| font_cache.is_custom(font) && !self.graphene_document.embedded_fonts.iter().any(|embedded_font| &embedded_font.font == font))
+					.filter_map(|font| {
+						let data = font_cache.get(font)?.clone();
+						Some(EmbeddedFont { font: font.clone(), data })
+					})
+					.collect();
+
+				if !fonts.is_empty() {
+					self.backup(responses);
+					responses.push_back(DocumentOperation::EmbedFonts { fonts }.into());
+					responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+				}
+			}
+			EmbedSelectedImages => {
+				self.backup(responses);
+				// Images which couldn't be read from their file have nothing to embed, so they stay linked
+				for image in self.linked_images(true).into_iter().filter(|image| image.last_modified.is_some()) {
+					responses.push_back(DocumentOperation::SetImageLink { path: image.layer_path, link: None }.into());
+				}
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
+			EndInteractiveRender => {
+				if self.graphene_document.has_draft_effects() {
+					responses.push_back(DocumentMessage::RenderDocument.into());
+				}
+			}
 			ExportDocument {
 				file_name,
 				file_type,
 				scale_factor,
+				supersampling,
+				quality,
+				chroma_subsampling,
 				bounds,
+				transparent_background,
+				trim_to_content,
+				rasterize_unsupported,
+				animation,
+				dxf_curves,
+				convert_to_srgb,
+				svg_options,
 			} => {
-				let old_transforms = self.remove_document_transform();
-
-				// Calculate the bounding box of the region to be exported
-				let bounds = match bounds {
-					ExportBounds::AllArtwork => self.all_layer_bounds(&persistent_data.font_cache),
-					ExportBounds::Selection => self.selected_visible_layers_bounding_box(&persistent_data.font_cache),
-					ExportBounds::Artboard(id) => self
-						.artboard_message_handler
-						.artboards_graphene_document
-						.layer(&[id])
-						.ok()
-						.and_then(|layer| layer.aabb(&persistent_data.font_cache)),
-				}
-				.unwrap_or_default();
-				let size = bounds[1] - bounds[0];
-				let transform = (DAffine2::from_translation(bounds[0]) * DAffine2::from_scale(size)).inverse();
-
-				let document = self.render_document(size, transform, persistent_data, DocumentRenderMode::Root);
-				self.restore_document_transform(old_transforms);
-
-				let file_suffix = &format!(".{file_type:?}").to_lowercase();
+				let file_suffix = &format!(".{}", file_type.extension());
 				let name = match file_name.ends_with(FILE_SAVE_SUFFIX) {
 					true => file_name.replace(FILE_SAVE_SUFFIX, file_suffix),
 					false => file_name + file_suffix,
 				};
 
+				if file_type == FileType::Lottie {
+					let settings = LottieExportSettings {
+						font_cache: &persistent_data.font_cache,
+						color_space: self.graphene_document.specified_color_space(),
+						animation,
+					};
+					let document = self.export_lottie(name.trim_end_matches(file_suffix), bounds, transparent_background, trim_to_content, &settings);
+					responses.push_back(FrontendMessage::TriggerFileDownload { document, name }.into());
+					return;
+				}
+
+				if file_type == FileType::Dxf {
+					let document = self.export_dxf(bounds, trim_to_content, dxf_curves, &persistent_data.font_cache);
+					responses.push_back(FrontendMessage::TriggerFileDownload { document, name }.into());
+					return;
+				}
+
+				if file_type == FileType::Pdf {
+					let settings = PdfExportSettings {
+						font_cache: &persistent_data.font_cache,
+						color_space: self.graphene_document.specified_color_space(),
+						rasterize_unsupported,
+						raster_scale: scale_factor,
+					};
+					let export = self.export_pdf(name, bounds, transparent_background, trim_to_content, &settings);
+					let rasters = export.rasters();
+					self.pending_pdf_export = Some(export);
+					if rasters.is_empty() {
+						responses.push_back(FinishPdfExport { raster_pixels: Vec::new() }.into());
+					} else {
+						responses.push_back(FrontendMessage::TriggerPdfRasterization { rasters }.into());
+					}
+					return;
+				}
+
+				// Converting wide-gamut colors to sRGB is for apps and viewers which ignore color profiles, so the file is exported as sRGB like them
+				let export_color_space = if convert_to_srgb { ColorSpace::Srgb } else { self.graphene_document.specified_color_space() };
+				let raster_color_space = export_color_space.raster_color_space();
+
 				if file_type == FileType::Svg {
+					let document = self.export_svg_file(bounds, transparent_background, trim_to_content, convert_to_srgb, svg_options, &persistent_data.font_cache);
+					let document = self.metadata.embed_in_svg(declare_svg_color_profile(document, export_color_space));
 					responses.push_back(FrontendMessage::TriggerFileDownload { document, name }.into());
-				} else {
-					let mime = file_type.to_mime().to_string();
-					let size = (size * scale_factor).into();
-					responses.push_back(FrontendMessage::TriggerRasterDownload { svg: document, name, mime, size }.into());
+					return;
+				}
+
+				let (document, size) = self.export_svg(bounds, transparent_background, trim_to_content, persistent_data);
+				let size = (size * scale_factor).into();
+				let color_space = raster_color_space.canvas_color_space().to_string();
+
+				let animation_format = match file_type {
+					FileType::Gif => Some(AnimationFormat::Gif),
+					FileType::Apng => Some(AnimationFormat::Apng),
+					_ => None,
+				};
+				if let Some(format) = animation_format {
+					// Layers can't be animated yet, so every frame of the range looks the same and is merged into a single frame shown for the whole range
+					let frames = vec![document];
+					self.pending_animation_export = Some(PendingAnimationExport {
+						file_name: name,
+						format,
+						settings: animation,
+						lengths: vec![animation.frame_count()],
+						color_space: raster_color_space,
+					});
+					responses.push_back(
+						FrontendMessage::TriggerAnimationRasterization {
+							frames,
+							size,
+							supersampling,
+							color_space,
+						}
+						.into(),
+					);
+					return;
+				}
+
+				let encoding = match file_type {
+					FileType::Jpg => RasterEncoding::Jpeg { quality, chroma_subsampling },
+					FileType::Webp => RasterEncoding::Webp { quality },
+					_ => RasterEncoding::Png,
+				};
+				// The browser's own encoders don't offer these options (or don't exist for WebP in some browsers) and don't reliably tag the color space, so the frontend only rasterizes
+				self.pending_raster_export = Some((name, encoding, raster_color_space));
+				responses.push_back(
+					FrontendMessage::TriggerRasterEncoding {
+						svg: document,
+						size,
+						supersampling,
+						color_space,
+					}
+					.into(),
+				);
+			}
+			ExportSpriteSheet { name, sprites, settings } => {
+				let (names, sprites): (Vec<_>, Vec<_>) = sprites
+					.into_iter()
+					.map(|(name, bounds)| (name, self.export_svg(bounds, settings.transparent_background, false, persistent_data)))
+					// Slices of layers which have since been deleted have no bounds
+					.filter(|(_, (_, size))| size.x > 0. && size.y > 0.)
+					.unzip();
+				if sprites.is_empty() {
+					responses.push_back(
+						DialogMessage::DisplayDialogError {
+							title: "Nothing to export".to_string(),
+							description: "None of the included artboards or export slices have any area to pack into the sprite sheet.".to_string(),
+						}
+						.into(),
+					);
+					return;
+				}
+
+				let sizes: Vec<_> = sprites.iter().map(|(_, size)| (*size * settings.scale_factor).round().max(DVec2::ONE).as_u32()).collect();
+				let (positions, sheet_size) = pack_sprites(&sizes, settings.padding, settings.power_of_two);
+				if sheet_size.max_element() > MAX_SPRITE_SHEET_SIZE {
+					responses.push_back(
+						DialogMessage::DisplayDialogError {
+							title: "Sprite sheet too large".to_string(),
+							description: format!(
+								"The sprites need a {}×{} sheet, but sprite sheets can be at most {MAX_SPRITE_SHEET_SIZE} pixels wide and tall. Try a smaller scale factor or fewer sprites.",
+								sheet_size.x, sheet_size.y
+							),
+						}
+						.into(),
+					);
+					return;
+				}
+
+				// The sheet and its atlas are downloaded together in a zip archive
+				let image_name = format!("{name}.png");
+				let svg = sprite_sheet_svg(&sprites, &positions, &sizes, sheet_size);
+				let atlas = sprite_sheet_atlas(&names, &positions, &sizes, sheet_size, &image_name, settings.scale_factor);
+				let mut export = PendingBatchExport::new(format!("{name}.zip"));
+				export.add_raster(&image_name, None, sheet_size.x, sheet_size.y);
+				export.add_file(&format!("{name}.json"), atlas.into_bytes());
+				self.pending_batch_export = Some(export);
+
+				let rasters = vec![FrontendBatchRaster {
+					svg,
+					width: sheet_size.x,
+					height: sheet_size.y,
+					encode_png: true,
+				}];
+				let color_space = self.graphene_document.specified_color_space().canvas_color_space().to_string();
+				responses.push_back(FrontendMessage::TriggerBatchRasterization { rasters, color_space }.into());
+			}
+			ExtractSelectedTransforms => {
+				self.backup(responses);
+				for path in self.selected_layers_without_children() {
+					responses.push_back(DocumentOperation::ExtractLayerTransform { path: path.to_vec() }.into());
+				}
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
+			FinishAnimationExport { pixels, width, height } => {
+				if let Some(export) = self.pending_animation_export.take() {
+					match export.finish(&pixels, width, height) {
+						Ok(data) => {
+							let name = export.file_name;
+							let mime = export.format.to_mime().to_string();
+							responses.push_back(FrontendMessage::TriggerBinaryFileDownload { data, name, mime }.into());
+						}
+						Err(error) => responses.push_back(
+							DialogMessage::DisplayDialogError {
+								title: "Failed to export".to_string(),
+								description: error.to_string(),
+							}
+							.into(),
+						),
+					}
+				}
+			}
+			FinishBatchExport { data, lengths } => {
+				if let Some(export) = self.pending_batch_export.take() {
+					let name = export.zip_name.clone();
+					let color_space = self.graphene_document.specified_color_space().raster_color_space();
+					match export.finish(&data, &lengths, color_space) {
+						Ok(data) => {
+							let mime = "application/zip".to_string();
+							responses.push_back(FrontendMessage::TriggerBinaryFileDownload { data, name, mime }.into());
+						}
+						Err(error) => responses.push_back(
+							DialogMessage::DisplayDialogError {
+								title: "Failed to export".to_string(),
+								description: error.to_string(),
+							}
+							.into(),
+						),
+					}
+				}
+			}
+			FinishImageTrace { pixels, width, height } => {
+				let (layer_path, options) = match self.pending_image_trace.take() {
+					Some(pending) => pending,
+					None => return,
+				};
+				let layer = match self.graphene_document.layer(&layer_path) {
+					Ok(layer) => layer,
+					Err(_) => return,
+				};
+				let image = match layer.as_image() {
+					Ok(image) => image,
+					Err(_) => return,
+				};
+
+				// The shapes are traced in pixels, which are scaled onto the bounds of the image layer
+				let scale = DAffine2::from_scale(image.dimensions / DVec2::new(width as f64, height as f64));
+				let mut folder = FolderLayer::default();
+				for shape in trace_image(&pixels, width, height, &options) {
+					let style = PathStyle::new(None, Fill::Solid(shape.color));
+					folder.add_layer(Layer::new(LayerDataType::Shape(ShapeLayer::new(shape.path, style)), scale.to_cols_array()), None, -1);
+				}
+				if folder.layers().is_empty() {
+					return;
+				}
+				let mut traced = Layer::new(LayerDataType::Folder(folder), layer.transform.to_cols_array());
+				traced.name = Some("Traced Image".to_string());
+
+				// Place the traced shapes directly above the image, which is hidden rather than deleted so it can be traced again
+				let (folder_path, layer_id) = layer_path.split_at(layer_path.len() - 1);
+				let insert_index = self.graphene_document.folder(folder_path).ok().and_then(|folder| folder.position_of_layer(layer_id[0]).ok());
+				let mut destination_path = folder_path.to_vec();
+				destination_path.push(generate_uuid());
+
+				self.backup(responses);
+				responses.push_back(
+					DocumentOperation::InsertLayer {
+						layer: Box::new(traced),
+						destination_path: destination_path.clone(),
+						insert_index: insert_index.map_or(-1, |index| index as isize + 1),
+					}
+					.into(),
+				);
+				responses.push_back(DocumentOperation::SetLayerVisibility { path: layer_path, visible: false }.into());
+				responses.push_back(
+					DocumentMessage::SetSelectedLayers {
+						replacement_selected_layers: vec![destination_path],
+					}
+					.into(),
+				);
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
+			FinishPdfExport { raster_pixels } => {
+				if let Some(export) = self.pending_pdf_export.take() {
+					let name = export.file_name.clone();
+					let data = export.finish(&raster_pixels);
+					let mime = FileType::Pdf.to_mime().to_string();
+					responses.push_back(FrontendMessage::TriggerBinaryFileDownload { data, name, mime }.into());
+				}
+			}
+			FinishRasterExport { pixels, width, height } => {
+				if let Some((name, encoding, color_space)) = self.pending_raster_export.take() {
+					match encoding.encode(&pixels, width, height, color_space) {
+						Ok(data) => {
+							let data = self.metadata.embed_in_png(data);
+							let mime = encoding.to_mime().to_string();
+							responses.push_back(FrontendMessage::TriggerBinaryFileDownload { data, name, mime }.into());
+						}
+						Err(error) => responses.push_back(
+							DialogMessage::DisplayDialogError {
+								title: "Failed to export".to_string(),
+								description: error.to_string(),
+							}
+							.into(),
+						),
+					}
+				}
+			}
+			FlattenSelectedTransforms => {
+				self.backup(responses);
+				for path in self.selected_layers_without_children() {
+					responses.push_back(DocumentOperation::FlattenLayerTransform { path: path.to_vec() }.into());
 				}
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
 			}
 			FlipSelectedLayers { flip_axis } => {
 				self.backup(responses);
@@ -465,13 +1012,139 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					responses.push_back(FrontendMessage::TriggerImaginateTerminate { document_id, layer_path, hostname }.into());
 				}
 			}
+			ImportSvg { svg, mouse } => {
+				let path = vec![generate_uuid()];
+				let (operations, view_box_transform) = match import_svg(&svg, path.clone(), self.graphene_document.specified_color_space()) {
+					Ok(imported) => imported,
+					Err(error) => {
+						let description = error.to_string();
+						responses.push_back(
+							DialogMessage::DisplayDialogError {
+								title: "Failed to import SVG".into(),
+								description,
+							}
+							.into(),
+						);
+						return;
+					}
+				};
+
+				self.backup(responses);
+				responses.extend(operations.into_iter().map(Message::from));
+				responses.push_back(
+					DocumentMessage::SetSelectedLayers {
+						replacement_selected_layers: vec![path.clone()],
+					}
+					.into(),
+				);
+
+				// Place the top left corner of the SVG's viewport at the mouse, keeping the scale its viewBox gives it
+				let transform = (DAffine2::from_translation(ipp.paste_position(mouse)) * view_box_transform).to_cols_array();
+				responses.push_back(DocumentOperation::SetLayerTransformInViewport { path, transform }.into());
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
+			IsolateSelectedFolder => {
+				let selected_folder = self
+					.selected_layers_sorted()
+					.into_iter()
+					.find(|path| self.graphene_document.folder(path).is_ok())
+					.map(|path| path.to_vec());
+				if let Some(folder_path) = selected_folder {
+					responses.push_back(SetIsolatedFolder { folder_path: Some(folder_path) }.into());
+				}
+			}
+			JoinCollaboration { session } => {
+				if self.collaboration.is_some() {
+					warn!("Can't join a collaboration session with a document that's already being edited together");
+					return;
+				}
+
+				let collaboration = Collaboration::join(session.clone(), generate_uuid());
+				responses.push_back(FrontendMessage::TriggerCollaborationJoin { document_id, session }.into());
+				responses.push_back(collaboration.send(SessionMessage::Join {
+					replica: collaboration.log.replica(),
+					version: collaboration.log.version(),
+				}));
+				self.collaboration = Some(collaboration);
+			}
 			LayerChanged { affected_layer_path } => {
 				if let Ok(layer_entry) = self.layer_panel_entry(affected_layer_path.clone(), &persistent_data.font_cache) {
+					self.request_layer_thumbnail(&layer_entry, document_id, responses);
 					responses.push_back(FrontendMessage::UpdateDocumentLayerDetails { data: layer_entry }.into());
 				}
 				responses.push_back(PropertiesPanelMessage::CheckSelectedWasUpdated { path: affected_layer_path }.into());
 				self.update_layer_tree_options_bar_widgets(responses, &persistent_data.font_cache);
 			}
+			LockSelectedLayers => {
+				self.backup(responses);
+				let paths = self.selected_layers().map(|path| path.to_vec()).collect::<Vec<_>>();
+				for path in paths {
+					responses.push_back(DocumentOperation::SetLayerLocked { path, locked: true }.into());
+				}
+				responses.push_back(DeselectAllLayers.into());
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
+			MarkSelectedLayersExportable => {
+				let paths = self.selected_layers_sorted().into_iter().map(|path| path.to_vec()).collect::<Vec<_>>();
+				for path in paths {
+					if self.export_slices.iter().any(|slice| slice.area == ExportSliceArea::Layer(path.clone())) {
+						continue;
+					}
+					let name = self.graphene_document.layer(&path).ok().and_then(|layer| layer.name.clone());
+					self.export_slices.push(ExportSlice {
+						name: name.unwrap_or_else(|| format!("Slice {}", self.export_slices.len() + 1)),
+						area: ExportSliceArea::Layer(path),
+						settings: ExportItemSettings::default(),
+					});
+				}
+			}
+			MergeOperations { operations } => {
+				let collaboration = match &mut self.collaboration {
+					Some(collaboration) => collaboration,
+					None => {
+						warn!("Operations of other editors were received for a document that isn't being edited together");
+						return;
+					}
+				};
+
+				let (mut merged, mut replay) = (false, false);
+				for logged in operations {
+					let change = logged.change.clone();
+					match collaboration.log.merge(&self.graphene_document, logged) {
+						MergeResult::Duplicate => continue,
+						MergeResult::Appended if !replay => {
+							if let Err(e) = change.apply(&mut self.graphene_document, &persistent_data.font_cache) {
+								debug!("Merged operation had no effect: {:?}", e);
+							}
+						}
+						// Once the document has to be replayed, the operations after it are replayed along with it
+						MergeResult::Appended | MergeResult::Inserted => replay = true,
+					}
+					merged = true;
+				}
+				if replay {
+					if let Some(document) = collaboration.log.replay(&self.graphene_document, &persistent_data.font_cache) {
+						self.graphene_document = document;
+					}
+				}
+				if merged {
+					// The other editors can fold the operations this one has seen into their logs once it acknowledges them
+					let (replica, counter) = (collaboration.log.replica(), collaboration.log.clock());
+					responses.push_back(collaboration.send(SessionMessage::Acknowledge { replica, counter }));
+				}
+				collaboration.log.compact(&persistent_data.font_cache);
+
+				// Layers created and deleted by the merged operations gain and lose their metadata
+				let layer_paths = self.layer_paths_in_document();
+				self.layer_metadata.retain(|path, _| path.is_empty() || layer_paths.contains(path));
+				for path in layer_paths {
+					self.layer_metadata.entry(path).or_insert_with(|| LayerMetadata::new(false));
+				}
+
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+				responses.push_back(RenderDocument.into());
+				responses.push_back(FolderChanged { affected_folder_path: vec![] }.into());
+			}
 			MoveSelectedLayersTo {
 				folder_path,
 				insert_index,
@@ -503,7 +1176,14 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					responses.push_back(DocumentOperation::MoveSelectedManipulatorPoints { layer_path, delta }.into());
 				}
 			}
+			NewView => {
+				// The new view starts out looking where the active view does
+				self.views.push(self.navigation_handler.view());
+				responses.push_back(RenderDocument.into());
+			}
 			NudgeSelectedLayers { delta_x, delta_y } => {
+				let (delta_x, delta_y) = (self.transform_settings.nudge(delta_x), self.transform_settings.nudge(delta_y));
+
 				self.backup(responses);
 				for path in self.selected_layers().map(|path| path.to_vec()) {
 					let operation = DocumentOperation::TransformLayerInViewport {
@@ -514,71 +1194,284 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 				}
 				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
 			}
-			PasteImage { mime, image_data, mouse } => {
-				let path = vec![generate_uuid()];
-				responses.push_back(
-					DocumentOperation::AddImage {
-						path: path.clone(),
-						transform: DAffine2::ZERO.to_cols_array(),
-						insert_index: -1,
-						image_data: image_data.clone(),
-						mime: mime.clone(),
-					}
-					.into(),
-				);
-				responses.push_back(
-					FrontendMessage::UpdateImageData {
-						document_id,
-						image_data: vec![FrontendImageData { path: path.clone(), image_data, mime }],
-					}
-					.into(),
-				);
-				responses.push_back(
-					DocumentMessage::SetSelectedLayers {
-						replacement_selected_layers: vec![path.clone()],
+			OutlineSelectedStrokes => {
+				let mut operations = Vec::new();
+				for path in self.selected_layers() {
+					let layer = match self.graphene_document.layer(path) {
+						Ok(layer) => layer,
+						Err(_) => continue,
+					};
+					let (shape, stroke) = match &layer.data {
+						LayerDataType::Shape(shape) => match shape.style.stroke() {
+							Some(stroke) if stroke.is_visible() => (shape, stroke),
+							_ => continue,
+						},
+						_ => continue,
+					};
+					// The stroke is drawn in the space of the layer's parent, so it's outlined there
+					let subpath = match shape.shape.transformed_stroke_outline(&stroke, layer.transform) {
+						Some(subpath) => subpath,
+						None => continue,
+					};
+
+					// Keep the fill on a copy of the layer beneath the outlined stroke
+					if *shape.style.fill() != Fill::None {
+						let mut fill_layer = layer.clone();
+						if let LayerDataType::Shape(fill_shape) = &mut fill_layer.data {
+							fill_shape.style.clear_stroke();
+						}
+						let (folder_path, layer_id) = path.split_at(path.len() - 1);
+						let insert_index = self.graphene_document.folder(folder_path).ok().and_then(|folder| folder.position_of_layer(layer_id[0]).ok());
+						let mut destination_path = folder_path.to_vec();
+						destination_path.push(generate_uuid());
+						operations.push(DocumentOperation::InsertLayer {
+							layer: Box::new(fill_layer),
+							destination_path,
+							insert_index: insert_index.map_or(-1, |index| index as isize),
+						});
 					}
-					.into(),
-				);
 
-				let mouse = mouse.map_or(ipp.viewport_bounds.center(), |pos| pos.into());
-				let transform = DAffine2::from_translation(mouse - ipp.viewport_bounds.top_left).to_cols_array();
-				responses.push_back(DocumentOperation::SetLayerTransformInViewport { path, transform }.into());
-			}
-			Redo => {
-				responses.push_back(SelectToolMessage::Abort.into());
-				responses.push_back(DocumentHistoryForward.into());
-				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
-				responses.push_back(RenderDocument.into());
-				responses.push_back(FolderChanged { affected_folder_path: vec![] }.into());
+					operations.push(DocumentOperation::SetShapePath { path: path.to_vec(), subpath });
+					operations.push(DocumentOperation::SetLayerStyle {
+						path: path.to_vec(),
+						style: PathStyle::new(None, stroke.color().map_or(Fill::None, Fill::Solid)),
+					});
+				}
+
+				if !operations.is_empty() {
+					self.backup(responses);
+					responses.extend(operations.into_iter().map(Message::from));
+					responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+				}
+			}
+			PasteImage {
+				mime,
+				image_data,
+				mouse,
+				in_working_color_space,
+			} => {
+				// SVGs are imported as editable layers instead of being embedded as an image
+				if mime == "image/svg+xml" {
+					let svg = String::from_utf8_lossy(&image_data).into_owned();
+					responses.push_back(ImportSvg { svg, mouse }.into());
+					return;
+				}
+
+				// So is the vector artwork of PDF and EPS files, by way of SVG
+				if let Some(format) = PrintFormat::detect(&mime, &image_data) {
+					match format.to_svg(&image_data) {
+						Ok(svg) => responses.push_back(ImportSvg { svg, mouse }.into()),
+						Err(error) => responses.push_back(
+							DialogMessage::DisplayDialogError {
+								title: format!("Failed to import {}", format.name()),
+								description: error.to_string(),
+							}
+							.into(),
+						),
+					}
+					return;
+				}
+
+				// And the outlines of DXF drawings
+				if is_dxf(&mime, &image_data) {
+					match dxf_to_svg(&image_data) {
+						Ok(svg) => responses.push_back(ImportSvg { svg, mouse }.into()),
+						Err(error) => responses.push_back(
+							DialogMessage::DisplayDialogError {
+								title: "Failed to import DXF".into(),
+								description: error.to_string(),
+							}
+							.into(),
+						),
+					}
+					return;
+				}
+
+				// Have the frontend convert the image into the working color space before it's added to the document
+				let color_space = self.graphene_document.color_space.canvas_color_space();
+				if !in_working_color_space && color_space != ColorSpace::Srgb.canvas_color_space() {
+					let color_space = color_space.to_string();
+					responses.push_back(FrontendMessage::TriggerImportColorConversion { mime, image_data, color_space, mouse }.into());
+					return;
+				}
+
+				Self::add_image_layer(mime, image_data, None, ipp.paste_position(mouse), document_id, responses);
+			}
+			PasteLinkedImage { mime, image_data, link } => {
+				// Linked images aren't converted into the working color space, since they're read again from their file each time the document is opened
+				self.backup(responses);
+				Self::add_image_layer(mime, image_data, Some(link), ipp.paste_position(None), document_id, responses);
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
+			ReceiveCollaborationMessage { message } => {
+				let collaboration = match &mut self.collaboration {
+					Some(collaboration) => collaboration,
+					None => {
+						warn!("A collaboration message was received for a document that isn't being edited together");
+						return;
+					}
+				};
+
+				// The message can arrive for a document that isn't the active one
+				let merge = |document_id, operations| -> Message {
+					PortfolioMessage::DocumentPassMessage {
+						document_id,
+						message: MergeOperations { operations },
+					}
+					.into()
+				};
+				match message {
+					// Any member that has joined replies, and the joining editor ignores the states after the first through the log
+					SessionMessage::Join { replica, version } => {
+						if collaboration.pending.is_none() {
+							// Nothing is compacted until the joining editor acknowledges the operations in the state it's sent
+							collaboration.log.acknowledge(replica, 0);
+							let state = collaboration.log.state(&self.graphene_document, &version);
+							responses.push_back(collaboration.send(SessionMessage::State { to: replica, state: Box::new(state) }));
+						}
+					}
+					SessionMessage::State { to, state } if to == collaboration.log.replica() => match collaboration.pending.take() {
+						Some(pending) => {
+							collaboration.log = OperationLog::joined(to, *state);
+							if let Some(document) = collaboration.log.replay(&self.graphene_document, &persistent_data.font_cache) {
+								self.graphene_document = document;
+							}
+							let (replica, counter) = (collaboration.log.replica(), collaboration.log.clock());
+							responses.push_back(collaboration.send(SessionMessage::Acknowledge { replica, counter }));
+							responses.push_back(merge(document_id, pending));
+						}
+						None => responses.push_back(merge(document_id, state.operations)),
+					},
+					SessionMessage::State { .. } => (),
+					SessionMessage::Operations(operations) => match &mut collaboration.pending {
+						Some(pending) => pending.extend(operations),
+						None => responses.push_back(merge(document_id, operations)),
+					},
+					SessionMessage::Acknowledge { replica, counter } => {
+						collaboration.log.acknowledge(replica, counter);
+						collaboration.log.compact(&persistent_data.font_cache);
+					}
+					SessionMessage::Leave { replica } => {
+						collaboration.log.forget(replica);
+						collaboration.log.compact(&persistent_data.font_cache);
+					}
+				}
+			}
+			Redo => {
+				responses.push_back(SelectToolMessage::Abort.into());
+				responses.push_back(DocumentHistoryForward.into());
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+				responses.push_back(RenderDocument.into());
+				responses.push_back(FolderChanged { affected_folder_path: vec![] }.into());
+			}
+			RefreshLinkedImages { request_permission } => {
+				let images = self.linked_images(false);
+				if !images.is_empty() {
+					responses.push_back(
+						FrontendMessage::TriggerReadLinkedImages {
+							document_id,
+							images,
+							request_permission,
+						}
+						.into(),
+					);
+				}
+			}
+			RelinkSelectedImage => {
+				let selected_image = self
+					.selected_layers_sorted()
+					.into_iter()
+					.find(|path| self.graphene_document.layer(path).is_ok_and(|layer| layer.as_image().is_ok()));
+				if let Some(layer_path) = selected_image {
+					let layer_path = layer_path.to_vec();
+					responses.push_back(FrontendMessage::TriggerRelinkImage { document_id, layer_path }.into());
+				}
 			}
 			RenameLayer { layer_path, new_name } => responses.push_back(DocumentOperation::RenameLayer { layer_path, new_name }.into()),
 			RenderDocument => {
-				let render_data = RenderData::new(self.view_mode, &persistent_data.font_cache, Some(ipp.document_bounds()));
-				responses.push_back(
-					FrontendMessage::UpdateDocumentArtwork {
-						svg: self.graphene_document.render_root(render_data),
+				let render_data = RenderData {
+					// Effects may be drawn at draft quality while a pointer button is held to drag in the viewport
+					interactive: ipp.mouse.mouse_keys != MouseKeys::NONE,
+					..RenderData::new(self.view_mode, &persistent_data.font_cache, Some(ipp.document_bounds()))
+				};
+				// Text is edited in an input placed in the SVG, so the SVG renderer is used while editing it
+				let editing_text = self.graphene_document.root.iter().any(|layer| matches!(&layer.data, LayerDataType::Text(text) if text.editable));
+				let pixel_preview = match self.view_mode {
+					ViewMode::Pixels if !editing_text => self.render_pixel_preview(ipp.viewport_bounds.size(), &persistent_data.font_cache),
+					_ => None,
+				};
+				if let Some(pixel_preview) = pixel_preview {
+					self.invalidate_sent_artwork();
+					responses.push_back(pixel_preview.into());
+				} else if self.graphene_document.viewport_renderer == ViewportRenderer::Gpu && !editing_text {
+					let view_transform = self.graphene_document.root.transform;
+					// Panning and rotating only move the tessellated geometry, so it is only built again when the document or the zoom changes
+					let key = (self.graphene_document.current_state_identifier(), self.view_mode, view_transform.matrix2.determinant().to_bits());
+					let (scene, dirty_tiles) = match self.gpu_scene.take() {
+						Some(previous) if previous.key == key => {
+							// Unchanged geometry only has to be drawn again if the view moved
+							let dirty_tiles = (previous.view_transform == view_transform).then(Vec::new);
+							self.gpu_scene = Some(SentGpuScene { view_transform, ..previous });
+							(None, dirty_tiles)
+						}
+						previous => {
+							let scene = self.graphene_document.render_gpu_scene(render_data);
+							// While the view stays the same, only the tiles overlapping the layers which changed are drawn again
+							let dirty_tiles = previous
+								.filter(|previous| previous.view_transform == view_transform)
+								.and_then(|previous| scene.changed_regions(&previous.scene))
+								.map(|regions| viewport_tiles(&regions, view_transform, ipp.viewport_bounds.size()));
+							self.gpu_scene = Some(SentGpuScene {
+								key,
+								view_transform,
+								scene: scene.clone(),
+							});
+							(Some(scene), dirty_tiles)
+						}
+					};
+					responses.push_back(
+						FrontendMessage::UpdateDocumentArtworkGpu {
+							scene,
+							view_transform: view_transform.to_cols_array(),
+							dirty_tiles,
+						}
+						.into(),
+					);
+				} else {
+					self.gpu_scene = None;
+					// Runs of layers left unchanged while others are edited, like when dragging a shape, are drawn from raster snapshots the frontend reuses
+					if editing_text {
+						self.artwork_snapshots = SentArtworkSnapshots::default();
+						let svg = self.graphene_document.render_root(render_data);
+						responses.push_back(FrontendMessage::UpdateDocumentArtwork { svg }.into());
+					} else {
+						match self.artwork_snapshots.slice(self.graphene_document.render_root_layers(render_data)) {
+							ArtworkUpdate::AllChanged { svg } => responses.push_back(FrontendMessage::UpdateDocumentArtwork { svg }.into()),
+							ArtworkUpdate::Slices(slices) => responses.push_back(FrontendMessage::UpdateDocumentArtworkSlices { slices }.into()),
+							ArtworkUpdate::Unchanged => {}
+						}
 					}
-					.into(),
-				);
+				}
 				responses.push_back(ArtboardMessage::RenderArtboards.into());
+				self.update_isolation_overlay(ipp, &persistent_data.font_cache, responses);
+				self.send_views(render_data, ipp, responses);
 
 				let document_transform_scale = self.navigation_handler.snapped_scale();
 				let scale = 0.5 + ASYMPTOTIC_EFFECT + document_transform_scale * SCALE_EFFECT;
 				let viewport_size = ipp.viewport_bounds.size();
 				let viewport_mid = ipp.viewport_bounds.center();
 				let [bounds1, bounds2] = self.document_bounds(&persistent_data.font_cache).unwrap_or([viewport_mid; 2]);
-				let bounds1 = bounds1.min(viewport_mid) - viewport_size * scale;
-				let bounds2 = bounds2.max(viewport_mid) + viewport_size * scale;
+				let ruler_origin = self.graphene_document.root.transform.transform_point2(self.navigation_handler.ruler_origin);
+				// The scrollable area always reaches the ruler origin, even when it is far outside the artwork
+				let bounds1 = bounds1.min(viewport_mid).min(ruler_origin) - viewport_size * scale;
+				let bounds2 = bounds2.max(viewport_mid).max(ruler_origin) + viewport_size * scale;
 				let bounds_length = (bounds2 - bounds1) * (1. + SCROLLBAR_SPACING);
 				let scrollbar_position = DVec2::splat(0.5) - (bounds1.lerp(bounds2, 0.5) - viewport_mid) / (bounds_length - viewport_size);
 				let scrollbar_multiplier = bounds_length - viewport_size;
 				let scrollbar_size = viewport_size / bounds_length;
 
-				let log = document_transform_scale.log2();
-				let ruler_interval = if log < 0. { 100. * 2_f64.powf(-log.ceil()) } else { 100. / 2_f64.powf(log.ceil()) };
-				let ruler_spacing = ruler_interval * document_transform_scale;
-
-				let ruler_origin = self.graphene_document.root.transform.transform_point2(DVec2::ZERO);
+				let ruler_interval = self.unit.ruler_interval(document_transform_scale);
+				let ruler_spacing = self.unit.to_pixels(ruler_interval) * document_transform_scale;
 
 				responses.push_back(
 					FrontendMessage::UpdateDocumentScrollbars {
@@ -598,6 +1491,28 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					.into(),
 				);
 			}
+			ReselectPrevious => {
+				let mut current = self.selected_layers().map(|path| path.to_vec()).collect::<Vec<_>>();
+				current.sort();
+
+				// Skip over the remembered selections of layers which have all been deleted or locked since
+				while let Some(previous) = self.selection_history.pop_back() {
+					let previous = previous
+						.into_iter()
+						.filter(|path| self.graphene_document.layer(path).is_ok() && !self.graphene_document.is_locked(path))
+						.collect::<Vec<_>>();
+					if previous.is_empty() || previous == current {
+						continue;
+					}
+
+					// The replaced selection goes to the other end of the history, so reselecting repeatedly cycles through all of it
+					if !current.is_empty() {
+						self.selection_history.push_front(current);
+					}
+					self.replace_selected_layers(previous, responses);
+					break;
+				}
+			}
 			RollbackTransaction => {
 				self.rollback(responses).unwrap_or_else(|e| warn!("{}", e));
 				responses.extend([RenderDocument.into(), DocumentStructureChanged.into()]);
@@ -612,16 +1527,23 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					true => self.name.clone(),
 					false => self.name.clone() + FILE_SAVE_SUFFIX,
 				};
+				let (data, mime) = if preferences.compress_documents {
+					(self.serialize_document_compressed(), "application/octet-stream")
+				} else {
+					(self.serialize_document().into_bytes(), "application/json")
+				};
 				responses.push_back(
-					FrontendMessage::TriggerFileDownload {
-						document: self.serialize_document(),
+					FrontendMessage::TriggerSaveDocument {
+						document_id,
 						name,
+						data,
+						mime: mime.into(),
 					}
 					.into(),
-				)
+				);
 			}
 			SelectAllLayers => {
-				let all = self.all_layers().map(|path| path.to_vec()).collect();
+				let all = self.all_layers().filter(|path| !self.graphene_document.is_locked(path)).map(|path| path.to_vec()).collect();
 				responses.push_front(SetSelectedLayers { replacement_selected_layers: all }.into());
 			}
 			SelectedLayersLower => {
@@ -639,6 +1561,17 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 			SelectedLayersReorder { relative_index_offset } => {
 				self.selected_layers_reorder(relative_index_offset, responses);
 			}
+			SelectInverse => {
+				let selected = self.selected_layers().map(|path| path.to_vec()).collect::<Vec<_>>();
+				// Besides the selected layers, the folders containing them and the layers inside of them are left out
+				let inverse = self
+					.all_layers()
+					.filter(|path| !self.graphene_document.is_locked(path))
+					.filter(|path| !selected.iter().any(|selected| selected.starts_with(path) || path.starts_with(selected)))
+					.map(|path| path.to_vec())
+					.collect();
+				responses.push_front(SetSelectedLayers { replacement_selected_layers: inverse }.into());
+			}
 			SelectLayer { layer_path, ctrl, shift } => {
 				let mut paths = vec![];
 				let last_selection_exists = !self.layer_range_selection_reference.is_empty();
@@ -682,12 +1615,38 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					}
 				}
 			}
+			SelectLayersWithLabel { label } => {
+				let labeled = self
+					.all_layers()
+					.filter(|path| !self.graphene_document.is_locked(path))
+					.filter(|path| self.graphene_document.layer(path).is_ok_and(|layer| layer.label == Some(label)))
+					.map(|path| path.to_vec())
+					.collect();
+				responses.push_front(SetSelectedLayers { replacement_selected_layers: labeled }.into());
+			}
 			SetBlendModeForSelectedLayers { blend_mode } => {
 				self.backup(responses);
 				for path in self.selected_layers() {
 					responses.push_back(DocumentOperation::SetLayerBlendMode { path: path.to_vec(), blend_mode }.into());
 				}
 			}
+			SetCmykProfile { cmyk_profile } => {
+				self.backup(responses);
+				responses.push_back(DocumentOperation::SetCmykProfile { cmyk_profile }.into());
+				responses.push_back(FrontendMessage::UpdateCmykProfile { cmyk_profile }.into());
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
+			SetColorSpace { color_space } => {
+				self.backup(responses);
+				responses.push_back(DocumentOperation::SetColorSpace { color_space }.into());
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
+			SetDocumentMetadata { metadata } => self.metadata = metadata,
+			SetDocumentUnit { unit } => {
+				self.unit = unit;
+				responses.push_back(RenderDocument.into());
+				responses.push_back(PropertiesPanelMessage::ResendActiveProperties.into());
+			}
 			SetImageBlobUrl {
 				layer_path,
 				blob_url,
@@ -715,6 +1674,32 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					.into(),
 				);
 			}
+			SetIsolatedFolder { folder_path } => {
+				// Isolating the root folder is the same as leaving isolation
+				let folder_path = folder_path.filter(|path| !path.is_empty() && self.graphene_document.folder(path).is_ok());
+				if self.graphene_document.isolated_folder == folder_path {
+					return;
+				}
+				self.graphene_document.isolated_folder = folder_path;
+
+				// Only the layers inside the isolated folder can stay selected
+				let replacement_selected_layers = self
+					.selected_layers()
+					.filter(|path| !self.graphene_document.is_outside_isolation(path))
+					.map(|path| path.to_vec())
+					.collect();
+				responses.push_back(SetSelectedLayers { replacement_selected_layers }.into());
+				responses.push_back(BroadcastEvent::ToolAbort.into());
+				self.update_document_widgets(responses);
+				responses.push_back(RenderDocument.into());
+			}
+			SetLabelForSelectedLayers { label } => {
+				self.backup(responses);
+				for path in self.selected_layers() {
+					responses.push_back(DocumentOperation::SetLayerLabel { path: path.to_vec(), label }.into());
+				}
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
 			SetLayerExpansion { layer_path, set_expanded } => {
 				self.layer_metadata_mut(&layer_path).expanded = set_expanded;
 				responses.push_back(DocumentStructureChanged.into());
@@ -729,6 +1714,19 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					}
 				}
 			}
+			SetLayerThumbnail { layer_path, content_hash, data_url } => {
+				self.pending_layer_thumbnails.remove(&layer_path);
+				if self.graphene_document.layer(&layer_path).is_err() {
+					return;
+				}
+				self.layer_thumbnails.insert(layer_path.clone(), (content_hash, data_url));
+
+				if let Ok(layer_entry) = self.layer_panel_entry(layer_path, &persistent_data.font_cache) {
+					// The layer may have changed again while its thumbnail was being rasterized
+					self.request_layer_thumbnail(&layer_entry, document_id, responses);
+					responses.push_back(FrontendMessage::UpdateDocumentLayerDetails { data: layer_entry }.into());
+				}
+			}
 			SetOpacityForSelectedLayers { opacity } => {
 				self.backup(responses);
 				let opacity = opacity.clamp(0., 1.);
@@ -741,18 +1739,49 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 				self.overlays_visible = visible;
 				responses.push_back(OverlaysMessage::Rerender.into());
 			}
+			SetPixelPreviewDensity { density } => {
+				self.pixel_preview_density = density;
+				responses.push_back(DocumentMessage::RenderDocument.into());
+				responses.push_back(PortfolioMessage::UpdateDocumentWidgets.into());
+			}
+			SetPixelSnapping { pixel_snapping } => {
+				self.pixel_snapping = pixel_snapping;
+				responses.push_back(PortfolioMessage::UpdateDocumentWidgets.into());
+			}
+			SetRenderQuality {
+				anti_aliasing,
+				image_filter,
+				interactive_effects,
+			} => {
+				let current = self.graphene_document.render_quality;
+				let render_quality = RenderQuality {
+					anti_aliasing: anti_aliasing.unwrap_or(current.anti_aliasing),
+					image_filter: image_filter.unwrap_or(current.image_filter),
+					interactive_effects: interactive_effects.unwrap_or(current.interactive_effects),
+				};
+				if render_quality != current {
+					self.backup(responses);
+					responses.push_back(DocumentOperation::SetRenderQuality { render_quality }.into());
+					responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+				}
+			}
+			SetRulerOriginToSelection => {
+				if let Some([top_left, _]) = self.selected_visible_layers_document_bounding_box(&persistent_data.font_cache) {
+					responses.push_back(NavigationMessage::SetRulerOrigin { origin: top_left }.into());
+				}
+			}
+			SetSaveState { is_saved } => {
+				self.set_save_state(is_saved);
+				responses.push_back(PortfolioMessage::UpdateOpenDocumentsList.into());
+			}
 			SetSelectedLayers { replacement_selected_layers } => {
-				let selected = self.layer_metadata.iter_mut().filter(|(_, layer_metadata)| layer_metadata.selected);
-				selected.for_each(|(path, layer_metadata)| {
-					layer_metadata.selected = false;
-					responses.push_back(LayerChanged { affected_layer_path: path.clone() }.into())
-				});
-
-				let additional_layers = replacement_selected_layers;
-				responses.push_front(AddSelectedLayers { additional_layers }.into());
+				self.remember_selection(&replacement_selected_layers);
+				self.replace_selected_layers(replacement_selected_layers, responses);
 			}
 			SetSnapping { snap } => {
 				self.snapping_enabled = snap;
+				// The pixel snapping dropdown is disabled along with snapping
+				responses.push_back(PortfolioMessage::UpdateDocumentWidgets.into());
 			}
 			SetTextboxEditability { path, editable } => {
 				let text = self.graphene_document.layer(&path).unwrap().as_text().unwrap();
@@ -772,20 +1801,110 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					responses.push_back(FrontendMessage::DisplayRemoveEditableTextbox.into());
 				}
 			}
+			SetTransformSettings { transform_settings } => self.transform_settings = transform_settings,
 			SetViewMode { view_mode } => {
 				self.view_mode = view_mode;
 				responses.push_front(DocumentMessage::DirtyRenderDocument.into());
+				responses.push_back(PortfolioMessage::UpdateDocumentWidgets.into());
+			}
+			SetViewportRenderer { viewport_renderer } => {
+				responses.push_back(DocumentOperation::SetViewportRenderer { viewport_renderer }.into());
+				responses.push_back(PortfolioMessage::UpdateDocumentWidgets.into());
+			}
+			SetWideGamut { wide_gamut } => {
+				self.backup(responses);
+				responses.push_back(DocumentOperation::SetWideGamut { wide_gamut }.into());
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
+			SnapSelectedToPixelGrid => {
+				self.backup(responses);
+
+				// Without a pixel snapping mode chosen, the anchors are snapped to the corners of pixels
+				let pixel_snapping = if self.pixel_snapping == PixelSnapping::Off {
+					PixelSnapping::WholePixels
+				} else {
+					self.pixel_snapping
+				};
+				let viewport_to_document = self.graphene_document.root.transform.inverse();
+				let selected_layers = self.selected_layers().collect::<Vec<_>>();
+				for path in self.all_layers().filter(|path| selected_layers.iter().any(|selected| path.starts_with(selected))) {
+					if let Ok(Layer {
+						data: LayerDataType::Shape(shape), ..
+					}) = self.graphene_document.layer(path)
+					{
+						let layer_to_document = viewport_to_document * self.graphene_document.multiply_transforms(path).unwrap_or_default();
+						let subpath = pixel_snapping.snap_subpath(&shape.shape, layer_to_document);
+						responses.push_back(DocumentOperation::SetShapePath { path: path.to_vec(), subpath }.into());
+					}
+				}
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
+			StartCollaboration => {
+				let session = match &self.collaboration {
+					Some(collaboration) => collaboration.session.clone(),
+					None => {
+						let session = format!("{:016x}", generate_uuid());
+						self.collaboration = Some(Collaboration::host(session.clone(), generate_uuid()));
+						responses.push_back(
+							FrontendMessage::TriggerCollaborationJoin {
+								document_id,
+								session: session.clone(),
+							}
+							.into(),
+						);
+						session
+					}
+				};
+				responses.push_back(DialogMessage::DisplayCollaborationSession { session }.into());
 			}
 			StartTransaction => self.backup(responses),
+			StopCollaboration => {
+				if let Some(collaboration) = self.collaboration.take() {
+					collaboration.leave(responses);
+				}
+			}
+			SwapView { view } => {
+				if let Some(other) = self.views.get_mut(view) {
+					let active = self.navigation_handler.view();
+					self.navigation_handler.set_view(*other, &ipp.viewport_bounds, responses);
+					*other = active;
+				}
+			}
 			ToggleLayerExpansion { layer_path } => {
 				self.layer_metadata_mut(&layer_path).expanded ^= true;
 				responses.push_back(DocumentStructureChanged.into());
 				responses.push_back(LayerChanged { affected_layer_path: layer_path }.into())
 			}
+			ToggleLayerLock { layer_path } => {
+				if let Ok(layer) = self.graphene_document.layer(&layer_path) {
+					let locked = !layer.locked;
+					self.backup(responses);
+					responses.push_back(DocumentOperation::SetLayerLocked { path: layer_path.clone(), locked }.into());
+
+					// Locking a layer also deselects it and any of its selected children
+					if locked {
+						let remaining = self.selected_layers().filter(|path| !path.starts_with(&layer_path)).map(|path| path.to_vec()).collect();
+						responses.push_back(
+							SetSelectedLayers {
+								replacement_selected_layers: remaining,
+							}
+							.into(),
+						);
+					}
+					responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+				}
+			}
 			ToggleLayerVisibility { layer_path } => {
 				responses.push_back(DocumentOperation::ToggleLayerVisibility { path: layer_path }.into());
 				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
 			}
+			ToggleOutlineViewMode => {
+				let view_mode = match self.view_mode {
+					ViewMode::Outline => ViewMode::Normal,
+					_ => ViewMode::Outline,
+				};
+				responses.push_back(SetViewMode { view_mode }.into());
+			}
 			ToggleSelectedHandleMirroring {
 				layer_path,
 				toggle_distance,
@@ -800,6 +1919,21 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					.into(),
 				);
 			}
+			TraceImage { layer_path, options } => {
+				let image = match self.graphene_document.layer(&layer_path).and_then(|layer| layer.as_image()) {
+					Ok(image) => image,
+					Err(_) => return,
+				};
+				// The editor can't decode images, so the frontend sends back the pixels to trace
+				responses.push_back(
+					FrontendMessage::TriggerImageTrace {
+						mime: image.mime.clone(),
+						image_data: image.image_data.clone(),
+					}
+					.into(),
+				);
+				self.pending_image_trace = Some((layer_path, options));
+			}
 			Undo => {
 				responses.push_back(BroadcastEvent::ToolAbort.into());
 				responses.push_back(DocumentHistoryBackward.into());
@@ -840,9 +1974,50 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 				}
 				responses.push_back(DocumentMessage::CommitTransaction.into());
 			}
+			UnlockAllLayers => {
+				self.backup(responses);
+				let locked_paths = self
+					.all_layers()
+					.filter(|path| self.graphene_document.layer(path).is_ok_and(|layer| layer.locked))
+					.map(|path| path.to_vec());
+				for path in locked_paths.collect::<Vec<_>>() {
+					responses.push_back(DocumentOperation::SetLayerLocked { path, locked: false }.into());
+				}
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
 			UpdateLayerMetadata { layer_path, layer_metadata } => {
 				self.layer_metadata.insert(layer_path, layer_metadata);
 			}
+			UpdateLinkedImage { layer_path, mime, image_data, link } => {
+				// The layer may have been deleted while its file was being read
+				if self.graphene_document.layer(&layer_path).map_or(true, |layer| layer.as_image().is_err()) {
+					return;
+				}
+
+				responses.push_back(
+					DocumentOperation::SetImageData {
+						path: layer_path.clone(),
+						mime: mime.clone(),
+						image_data: image_data.clone(),
+					}
+					.into(),
+				);
+				responses.push_back(
+					DocumentOperation::SetImageLink {
+						path: layer_path.clone(),
+						link: Some(link),
+					}
+					.into(),
+				);
+				responses.push_back(
+					FrontendMessage::UpdateImageData {
+						document_id,
+						image_data: vec![FrontendImageData { path: layer_path, image_data, mime }],
+					}
+					.into(),
+				);
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+			}
 			ZoomCanvasTo100Percent => {
 				responses.push_front(NavigationMessage::SetCanvasZoom { zoom_factor: 1. }.into());
 			}
@@ -870,15 +2045,28 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 			Redo,
 			SelectAllLayers,
 			DeselectAllLayers,
+			SelectInverse,
+			ReselectPrevious,
 			RenderDocument,
 			ExportDocument,
+			BatchExport,
+			ExportSpriteSheet,
 			SaveDocument,
 			SetSnapping,
 			DebugPrintDocument,
 			ZoomCanvasToFitAll,
 			ZoomCanvasTo100Percent,
 			ZoomCanvasTo200Percent,
+			NewView,
+			CloseView,
+			SwapView,
 			CreateEmptyFolder,
+			UnlockAllLayers,
+			ToggleOutlineViewMode,
+			RefreshLinkedImages,
+			EmbedFonts,
+			StartCollaboration,
+			StopCollaboration,
 		);
 
 		if self.layer_metadata.values().any(|data| data.selected) {
@@ -892,6 +2080,15 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 				SelectedLayersRaiseToFront,
 				GroupSelectedLayers,
 				UngroupSelectedLayers,
+				LockSelectedLayers,
+				AddSelectionExportSlice,
+				MarkSelectedLayersExportable,
+				RelinkSelectedImage,
+				EmbedSelectedImages,
+				OutlineSelectedStrokes,
+				FlattenSelectedTransforms,
+				ExtractSelectedTransforms,
+				SnapSelectedToPixelGrid,
 			);
 			common.extend(select);
 		}
@@ -899,88 +2096,423 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 		common.extend(self.transform_layer_handler.actions());
 		common
 	}
-}
+}
+
+impl DocumentMessageHandler {
+	pub fn call_imaginate(&mut self, document_id: u64, preferences: &PreferencesMessageHandler, persistent_data: &PersistentData) -> Option<Message> {
+		let layer_path = {
+			let mut selected_imaginate_layers = self.selected_layers_with_type(LayerDataTypeDiscriminant::Imaginate);
+
+			// Get what is hopefully the only selected Imaginate layer
+			match selected_imaginate_layers.next() {
+				// Continue only if there are no additional Imaginate layers also selected
+				Some(layer_path) if selected_imaginate_layers.next().is_none() => layer_path.to_owned(),
+				_ => return None,
+			}
+		};
+
+		// Prepare the Imaginate parameters and base image
+
+		let transform = self.graphene_document.root.transform.inverse() * self.graphene_document.multiply_transforms(&layer_path).unwrap();
+		let layer = self.graphene_document.layer(&layer_path).unwrap();
+		let imaginate_layer = layer.as_imaginate().unwrap();
+
+		let parameters = ImaginateGenerationParameters {
+			seed: imaginate_layer.seed,
+			samples: imaginate_layer.samples,
+			sampling_method: imaginate_layer.sampling_method.api_value().to_string(),
+			denoising_strength: imaginate_layer.use_img2img.then_some(imaginate_layer.denoising_strength),
+			cfg_scale: imaginate_layer.cfg_scale,
+			prompt: imaginate_layer.prompt.clone(),
+			negative_prompt: imaginate_layer.negative_prompt.clone(),
+			resolution: pick_layer_safe_imaginate_resolution(layer, &persistent_data.font_cache),
+			restore_faces: imaginate_layer.restore_faces,
+			tiling: imaginate_layer.tiling,
+		};
+		let base_image = if imaginate_layer.use_img2img {
+			// Calculate the size of the region to be exported
+			let size = transform.transform_point2(DVec2::ONE) - transform.transform_point2(DVec2::ZERO);
+
+			let old_transforms = self.remove_document_transform();
+			let svg = self.render_document(size, transform.inverse(), persistent_data, DocumentRenderMode::OnlyBelowLayerInFolder(&layer_path), false);
+			self.restore_document_transform(old_transforms);
+
+			Some(ImaginateBaseImage { svg, size })
+		} else {
+			None
+		};
+
+		Some(
+			FrontendMessage::TriggerImaginateGenerate {
+				parameters,
+				base_image,
+				hostname: preferences.imaginate_server_hostname.clone(),
+				refresh_frequency: preferences.imaginate_refresh_frequency,
+				document_id,
+				layer_path,
+			}
+			.into(),
+		)
+	}
+
+	/// Remove the artwork and artboard pan/tilt/zoom to render it without the user's viewport navigation, and save it to be restored at the end
+	fn remove_document_transform(&mut self) -> [DAffine2; 2] {
+		let old_artwork_transform = self.graphene_document.root.transform;
+		self.graphene_document.root.transform = DAffine2::IDENTITY;
+		GrapheneDocument::mark_children_as_dirty(&mut self.graphene_document.root);
+
+		let old_artboard_transform = self.artboard_message_handler.artboards_graphene_document.root.transform;
+		self.artboard_message_handler.artboards_graphene_document.root.transform = DAffine2::IDENTITY;
+		GrapheneDocument::mark_children_as_dirty(&mut self.artboard_message_handler.artboards_graphene_document.root);
+
+		[old_artwork_transform, old_artboard_transform]
+	}
+
+	/// Transform the artwork and artboard back to their original scales
+	fn restore_document_transform(&mut self, [old_artwork_transform, old_artboard_transform]: [DAffine2; 2]) {
+		self.graphene_document.root.transform = old_artwork_transform;
+		GrapheneDocument::mark_children_as_dirty(&mut self.graphene_document.root);
+
+		self.artboard_message_handler.artboards_graphene_document.root.transform = old_artboard_transform;
+		GrapheneDocument::mark_children_as_dirty(&mut self.artboard_message_handler.artboards_graphene_document.root);
+	}
+
+	/// Renders the visible part of the artwork into the pixels of the document at the pixel preview density, for the frontend to rasterize and show enlarged without smoothing.
+	/// Returns `None` when zoomed out so far that the pixels are smaller than the viewport's, where the preview would look no different from the normal view.
+	/// Sends the artwork for the other views of the document, which show the SVG rendered for the active view transformed to where each of them looks.
+	fn send_views(&mut self, render_data: RenderData, ipp: &InputPreprocessorMessageHandler, responses: &mut VecDeque<Message>) {
+		if self.views.is_empty() {
+			responses.push_back(
+				FrontendMessage::UpdateDocumentViews {
+					svg: String::new(),
+					views: Vec::new(),
+				}
+				.into(),
+			);
+			return;
+		}
+
+		let active_view_to_document = self.graphene_document.root.transform.inverse();
+		let views = self
+			.views
+			.iter()
+			.enumerate()
+			.map(|(index, view)| {
+				// Views whose canvas hasn't been measured yet are assumed to be the size of the active view
+				let viewport_size = ipp.view_bounds.get(index).unwrap_or(&ipp.viewport_bounds).size();
+				FrontendDocumentView {
+					transform: (view.transform(viewport_size) * active_view_to_document).to_cols_array(),
+					label: view.label(),
+				}
+			})
+			.collect();
+		let svg = self.graphene_document.render_root(render_data);
+
+		responses.push_back(FrontendMessage::UpdateDocumentViews { svg, views }.into());
+	}
+
+	fn render_pixel_preview(&mut self, viewport_size: DVec2, font_cache: &FontCache) -> Option<FrontendMessage> {
+		let view_transform = self.graphene_document.root.transform;
+		let density = self.pixel_preview_density.factor();
+		let pixel_scale = view_transform.matrix2.determinant().abs().sqrt() / density;
+		if pixel_scale.is_nan() || pixel_scale < 1. {
+			return None;
+		}
+
+		// The preview covers the pixels of the document which are at least partially visible
+		let viewport_to_pixels = DAffine2::from_scale(DVec2::splat(density)) * view_transform.inverse();
+		let corners = [DVec2::ZERO, DVec2::new(viewport_size.x, 0.), viewport_size, DVec2::new(0., viewport_size.y)].map(|corner| viewport_to_pixels.transform_point2(corner));
+		let min = corners.into_iter().reduce(DVec2::min)?.floor();
+		let max = corners.into_iter().reduce(DVec2::max)?.ceil();
+		let size = max - min;
+		if !size.is_finite() || size.x < 1. || size.y < 1. {
+			return None;
+		}
+
+		// The artwork is rendered with one unit per preview pixel, so the browser rasterizes it exactly at the preview density
+		let pixels_transform = DAffine2::from_translation(-min) * DAffine2::from_scale(DVec2::splat(density));
+		self.graphene_document.root.transform = pixels_transform;
+		GrapheneDocument::mark_children_as_dirty(&mut self.graphene_document.root);
+		let artwork = self.graphene_document.render_root(RenderData::new(ViewMode::Pixels, font_cache, Some([DVec2::ZERO, size])));
+		self.graphene_document.root.transform = view_transform;
+		GrapheneDocument::mark_children_as_dirty(&mut self.graphene_document.root);
+
+		let svg = format!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{}" height="{}">{}</svg>"#,
+			size.x, size.y, artwork
+		);
+		Some(FrontendMessage::UpdateDocumentArtworkPixels {
+			svg,
+			width: size.x as u32,
+			height: size.y as u32,
+			transform: (view_transform * pixels_transform.inverse()).to_cols_array(),
+			grid: pixel_scale >= PIXEL_PREVIEW_GRID_MIN_SCALE,
+		})
+	}
+
+	/// Render the given region of the document into an SVG, returning it along with its size in document units.
+	pub fn export_svg(&mut self, bounds: ExportBounds, transparent_background: bool, trim_to_content: bool, persistent_data: &PersistentData) -> (String, DVec2) {
+		let old_transforms = self.remove_document_transform();
+
+		let bounds = self.export_bounds(bounds, trim_to_content, &persistent_data.font_cache);
+		let size = bounds[1] - bounds[0];
+		let transform = (DAffine2::from_translation(bounds[0]) * DAffine2::from_scale(size)).inverse();
+
+		let document = self.render_document(size, transform, persistent_data, DocumentRenderMode::Root, transparent_background);
+		self.restore_document_transform(old_transforms);
+
+		(document, size)
+	}
+
+	/// Renders the artwork within the export bounds as an SVG like [Self::export_svg], but with wide-gamut colors converted to sRGB hex colors.
+	pub fn export_srgb_svg(&mut self, bounds: ExportBounds, transparent_background: bool, trim_to_content: bool, persistent_data: &PersistentData) -> (String, DVec2) {
+		self.with_srgb_colors(&persistent_data.font_cache, |document| {
+			document.export_svg(bounds, transparent_background, trim_to_content, persistent_data)
+		})
+	}
+
+	/// Runs the export with the wide-gamut colors of the document converted to sRGB, leaving the document itself untouched.
+	fn with_srgb_colors<T>(&mut self, font_cache: &FontCache, export: impl FnOnce(&mut Self) -> T) -> T {
+		if !self.graphene_document.wide_gamut {
+			return export(self);
+		}
+
+		// A copy of the document with wide-gamut colors turned off has its colors converted to sRGB and is exported in place of the document
+		let mut srgb_document = self.graphene_document.clone();
+		if srgb_document.handle_operation(DocumentOperation::SetWideGamut { wide_gamut: false }, font_cache).is_err() {
+			return export(self);
+		}
+		let wide_gamut_document = std::mem::replace(&mut self.graphene_document, srgb_document);
+		let result = export(self);
+		self.graphene_document = wide_gamut_document;
+
+		result
+	}
+
+	/// Builds a PDF of the artwork within the export bounds. When exporting all the artwork of a document with artboards, each artboard becomes a page.
+	pub fn export_pdf(&mut self, file_name: String, bounds: ExportBounds, transparent_background: bool, trim_to_content: bool, settings: &PdfExportSettings) -> PdfExport {
+		let old_transforms = self.remove_document_transform();
+		let font_cache = settings.font_cache;
+
+		// Rasterized layers are drawn from their cached SVG renders, so those are brought up to date first
+		self.graphene_document.render_root(RenderData::new(ViewMode::Normal, font_cache, None));
+
+		let artboards = &self.artboard_message_handler;
+		let pages = match bounds {
+			ExportBounds::AllArtwork if !artboards.artboard_ids.is_empty() => artboards
+				.artboard_ids
+				.iter()
+				.rev()
+				.filter_map(|&id| artboards.artboards_graphene_document.layer(&[id]).ok())
+				.filter_map(|artboard| {
+					let background = match artboard.style().map(|style| style.fill()) {
+						Ok(Fill::Solid(color)) if !transparent_background => Some(*color),
+						_ => None,
+					};
+					let bounds = artboard.aabb(font_cache)?;
+					let bounds = if trim_to_content { self.trim_to_content(bounds, font_cache) } else { bounds };
+					Some((bounds, background))
+				})
+				.collect(),
+			_ => vec![(self.export_bounds(bounds, trim_to_content, font_cache), None)],
+		};
+
+		let mut export = PdfExport::new(file_name);
+		for (bounds, background) in pages {
+			export.add_page(&self.graphene_document.root, bounds, background, settings);
+		}
+
+		self.restore_document_transform(old_transforms);
+		export
+	}
 
-impl DocumentMessageHandler {
-	pub fn call_imaginate(&mut self, document_id: u64, preferences: &PreferencesMessageHandler, persistent_data: &PersistentData) -> Option<Message> {
-		let layer_path = {
-			let mut selected_imaginate_layers = self.selected_layers_with_type(LayerDataTypeDiscriminant::Imaginate);
+	/// Writes the artwork within the export bounds as a standalone SVG file, drawn over the artboards unless the background is transparent.
+	/// Unlike [Self::export_svg], which renders what the viewport shows, the markup is written for the people and code which read it, as chosen by the options.
+	pub fn export_svg_file(&mut self, bounds: ExportBounds, transparent_background: bool, trim_to_content: bool, convert_to_srgb: bool, options: SvgExportOptions, font_cache: &FontCache) -> String {
+		if convert_to_srgb {
+			return self.with_srgb_colors(font_cache, |document| {
+				document.export_svg_file(bounds, transparent_background, trim_to_content, false, options, font_cache)
+			});
+		}
+		let old_transforms = self.remove_document_transform();
+
+		let bounds = self.export_bounds(bounds, trim_to_content, font_cache);
+		let document = &self.graphene_document;
+		let settings = SvgExportSettings {
+			font_cache,
+			wide_gamut_color_space: document.wide_gamut.then_some(document.color_space),
+			color_space: document.color_space,
+			render_quality: document.render_quality,
+			options,
+		};
 
-			// Get what is hopefully the only selected Imaginate layer
-			match selected_imaginate_layers.next() {
-				// Continue only if there are no additional Imaginate layers also selected
-				Some(layer_path) if selected_imaginate_layers.next().is_none() => layer_path.to_owned(),
-				_ => return None,
+		// Like the rendered export, the artboards are drawn over a background which is white for documents without artboards
+		let artboards = &self.artboard_message_handler;
+		let mut roots = Vec::new();
+		let background = match transparent_background {
+			true => None,
+			false => {
+				roots.push(&artboards.artboards_graphene_document.root);
+				// The background is a fixed sRGB color, so it's written in the color space of the document's colors
+				let outside_artboards = if artboards.artboard_ids.is_empty() { Color::WHITE } else { Color::from_rgb8(0x22, 0x22, 0x22) };
+				Some(outside_artboards.convert_color_space(ColorSpace::Srgb, document.specified_color_space()))
 			}
 		};
+		roots.push(&document.root);
+		let svg = svg_document(&roots, bounds, background, &settings);
 
-		// Prepare the Imaginate parameters and base image
+		self.restore_document_transform(old_transforms);
+		svg
+	}
 
-		let transform = self.graphene_document.root.transform.inverse() * self.graphene_document.multiply_transforms(&layer_path).unwrap();
-		let layer = self.graphene_document.layer(&layer_path).unwrap();
-		let imaginate_layer = layer.as_imaginate().unwrap();
+	/// Builds a Lottie animation of the artwork within the export bounds, drawn over the artboards unless the background is transparent.
+	pub fn export_lottie(&mut self, name: &str, bounds: ExportBounds, transparent_background: bool, trim_to_content: bool, settings: &LottieExportSettings) -> String {
+		let old_transforms = self.remove_document_transform();
 
-		let parameters = ImaginateGenerationParameters {
-			seed: imaginate_layer.seed,
-			samples: imaginate_layer.samples,
-			sampling_method: imaginate_layer.sampling_method.api_value().to_string(),
-			denoising_strength: imaginate_layer.use_img2img.then_some(imaginate_layer.denoising_strength),
-			cfg_scale: imaginate_layer.cfg_scale,
-			prompt: imaginate_layer.prompt.clone(),
-			negative_prompt: imaginate_layer.negative_prompt.clone(),
-			resolution: pick_layer_safe_imaginate_resolution(layer, &persistent_data.font_cache),
-			restore_faces: imaginate_layer.restore_faces,
-			tiling: imaginate_layer.tiling,
-		};
-		let base_image = if imaginate_layer.use_img2img {
-			// Calculate the size of the region to be exported
-			let size = transform.transform_point2(DVec2::ONE) - transform.transform_point2(DVec2::ZERO);
+		let bounds = self.export_bounds(bounds, trim_to_content, settings.font_cache);
+		let mut roots = Vec::new();
+		if !transparent_background {
+			roots.push(&self.artboard_message_handler.artboards_graphene_document.root);
+		}
+		roots.push(&self.graphene_document.root);
+		// The layers of a document don't change over time, so the frame they're in now is held for the whole animation
+		let animation = lottie_animation(name, &[roots], bounds, settings);
 
-			let old_transforms = self.remove_document_transform();
-			let svg = self.render_document(size, transform.inverse(), persistent_data, DocumentRenderMode::OnlyBelowLayerInFolder(&layer_path));
-			self.restore_document_transform(old_transforms);
+		self.restore_document_transform(old_transforms);
+		animation
+	}
 
-			Some(ImaginateBaseImage { svg, size })
-		} else {
-			None
-		};
+	/// Builds a DXF drawing of the outlines of the artwork within the export bounds, in millimeters.
+	pub fn export_dxf(&mut self, bounds: ExportBounds, trim_to_content: bool, curves: DxfCurves, font_cache: &FontCache) -> String {
+		let old_transforms = self.remove_document_transform();
 
-		Some(
-			FrontendMessage::TriggerImaginateGenerate {
-				parameters,
-				base_image,
-				hostname: preferences.imaginate_server_hostname.clone(),
-				refresh_frequency: preferences.imaginate_refresh_frequency,
+		let bounds = self.export_bounds(bounds, trim_to_content, font_cache);
+		let document = dxf_document(&self.graphene_document.root, bounds, curves, font_cache, self.graphene_document.specified_color_space());
+
+		self.restore_document_transform(old_transforms);
+		document
+	}
+
+	/// Renders the selected layers by themselves as a standalone SVG fit to their bounds, for copying into other apps.
+	/// The serialized layers are embedded in the SVG's metadata so pasting it back into Graphite recreates them exactly.
+	pub fn selected_layers_svg(&self, layer_data: &str, font_cache: &FontCache) -> Option<String> {
+		let render_data = RenderData::new(ViewMode::Normal, font_cache, None);
+		let document_transform_inverse = self.graphene_document.root.transform.inverse();
+
+		let mut svg_defs = String::new();
+		let mut artwork = String::new();
+		let mut bounds: Option<[DVec2; 2]> = None;
+		for path in self.selected_layers_without_children() {
+			// Each layer is rendered with the transforms of its parent folders, in document space
+			let (mut layer, transform) = match (self.graphene_document.layer(path), self.graphene_document.multiply_transforms(path)) {
+				(Ok(layer), Ok(transform)) if layer.visible => (layer.clone(), transform),
+				_ => continue,
+			};
+			layer.transform = document_transform_inverse * transform;
+			if let Some([min, max]) = layer.aabb(font_cache) {
+				bounds = Some(bounds.map_or([min, max], |[combined_min, combined_max]| [combined_min.min(min), combined_max.max(max)]));
+			}
+			artwork.push_str(layer.render(&mut Vec::new(), &mut svg_defs, render_data));
+		}
+
+		let [min, max] = bounds?;
+		let size = max - min;
+		Some(format!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="{} {} {} {}" width="{}" height="{}">{}<defs>{}</defs>{}</svg>"#,
+			min.x,
+			min.y,
+			size.x,
+			size.y,
+			size.x,
+			size.y,
+			svg_layer_data_metadata(layer_data),
+			svg_defs,
+			artwork
+		))
+	}
+
+	/// Adds an image layer at a viewport position and selects it, sending its image to the frontend to make a blob URL for it.
+	fn add_image_layer(mime: String, image_data: Vec<u8>, link: Option<ImageLink>, position: DVec2, document_id: u64, responses: &mut VecDeque<Message>) {
+		let path = vec![generate_uuid()];
+		responses.push_back(
+			DocumentOperation::AddImage {
+				path: path.clone(),
+				transform: DAffine2::ZERO.to_cols_array(),
+				insert_index: -1,
+				image_data: image_data.clone(),
+				mime: mime.clone(),
+			}
+			.into(),
+		);
+		if link.is_some() {
+			responses.push_back(DocumentOperation::SetImageLink { path: path.clone(), link }.into());
+		}
+		responses.push_back(
+			FrontendMessage::UpdateImageData {
 				document_id,
-				layer_path,
+				image_data: vec![FrontendImageData { path: path.clone(), image_data, mime }],
 			}
 			.into(),
-		)
+		);
+		responses.push_back(
+			DocumentMessage::SetSelectedLayers {
+				replacement_selected_layers: vec![path.clone()],
+			}
+			.into(),
+		);
+
+		let transform = DAffine2::from_translation(position).to_cols_array();
+		responses.push_back(DocumentOperation::SetLayerTransformInViewport { path, transform }.into());
 	}
 
-	/// Remove the artwork and artboard pan/tilt/zoom to render it without the user's viewport navigation, and save it to be restored at the end
-	fn remove_document_transform(&mut self) -> [DAffine2; 2] {
-		let old_artwork_transform = self.graphene_document.root.transform;
-		self.graphene_document.root.transform = DAffine2::IDENTITY;
-		GrapheneDocument::mark_children_as_dirty(&mut self.graphene_document.root);
+	/// The linked images in the document, or only the selected ones, for the frontend to read from their files.
+	/// Images which haven't been read since the document was opened are always read, while the others are only read again if their file was modified.
+	fn linked_images(&self, only_selected: bool) -> Vec<FrontendLinkedImage> {
+		self.all_layers()
+			.filter(|path| !only_selected || self.selected_layers_contains(path))
+			.filter_map(|path| {
+				let image = self.graphene_document.layer(path).ok()?.as_image().ok()?;
+				let link = image.link.as_ref()?;
+				Some(FrontendLinkedImage {
+					layer_path: path.to_vec(),
+					handle: link.handle.clone(),
+					file_name: link.file_name.clone(),
+					last_modified: (!image.image_data.is_empty()).then_some(link.last_modified),
+				})
+			})
+			.collect()
+	}
 
-		let old_artboard_transform = self.artboard_message_handler.artboards_graphene_document.root.transform;
-		self.artboard_message_handler.artboards_graphene_document.root.transform = DAffine2::IDENTITY;
-		GrapheneDocument::mark_children_as_dirty(&mut self.artboard_message_handler.artboards_graphene_document.root);
+	/// Calculate the bounding box of the region to be exported, which expects the document transform to have been removed.
+	fn export_bounds(&self, bounds: ExportBounds, trim_to_content: bool, font_cache: &FontCache) -> [DVec2; 2] {
+		let bounds = match bounds {
+			ExportBounds::AllArtwork => self.all_layer_bounds(font_cache),
+			ExportBounds::Selection => self.selected_visible_layers_bounding_box(font_cache),
+			ExportBounds::Artboard(id) => self.artboard_message_handler.artboards_graphene_document.layer(&[id]).ok().and_then(|layer| layer.aabb(font_cache)),
+			ExportBounds::Slice(index) => self.export_slices.get(index).and_then(|slice| match &slice.area {
+				ExportSliceArea::Rect(bounds) => Some(*bounds),
+				ExportSliceArea::Layer(path) => self.graphene_document.viewport_bounding_box(path, font_cache).ok().flatten(),
+			}),
+		}
+		.unwrap_or_default();
 
-		[old_artwork_transform, old_artboard_transform]
+		// The artwork and selection bounds already fit their content, but an artboard may have empty space around its artwork
+		if trim_to_content {
+			self.trim_to_content(bounds, font_cache)
+		} else {
+			bounds
+		}
 	}
 
-	/// Transform the artwork and artboard back to their original scales
-	fn restore_document_transform(&mut self, [old_artwork_transform, old_artboard_transform]: [DAffine2; 2]) {
-		self.graphene_document.root.transform = old_artwork_transform;
-		GrapheneDocument::mark_children_as_dirty(&mut self.graphene_document.root);
-
-		self.artboard_message_handler.artboards_graphene_document.root.transform = old_artboard_transform;
-		GrapheneDocument::mark_children_as_dirty(&mut self.artboard_message_handler.artboards_graphene_document.root);
+	/// Shrinks a region of the document to the bounds of the artwork within it, or leaves it unchanged if it contains no artwork.
+	fn trim_to_content(&self, [min, max]: [DVec2; 2], font_cache: &FontCache) -> [DVec2; 2] {
+		match self.all_layer_bounds(font_cache) {
+			Some([content_min, content_max]) if content_min.cmplt(max).all() && content_max.cmpgt(min).all() => [min.max(content_min), max.min(content_max)],
+			_ => [min, max],
+		}
 	}
 
-	pub fn render_document(&mut self, size: DVec2, transform: DAffine2, persistent_data: &PersistentData, render_mode: DocumentRenderMode) -> String {
+	pub fn render_document(&mut self, size: DVec2, transform: DAffine2, persistent_data: &PersistentData, render_mode: DocumentRenderMode, transparent_background: bool) -> String {
 		// Render the document SVG code
 
 		let render_data = RenderData::new(ViewMode::Normal, &persistent_data.font_cache, None);
@@ -989,9 +2521,14 @@ impl DocumentMessageHandler {
 			DocumentRenderMode::Root => self.graphene_document.render_root(render_data),
 			DocumentRenderMode::OnlyBelowLayerInFolder(below_layer_path) => self.graphene_document.render_layers_below(below_layer_path, render_data).unwrap(),
 		};
-		let artboards = self.artboard_message_handler.artboards_graphene_document.render_root(render_data);
-		let outside_artboards_color = if self.artboard_message_handler.artboard_ids.is_empty() { "#ffffff" } else { "#222222" };
-		let outside_artboards = format!(r#"<rect x="0" y="0" width="100%" height="100%" fill="{}" />"#, outside_artboards_color);
+		// A transparent background leaves out the artboards and the color surrounding them, keeping only the artwork
+		let (artboards, outside_artboards) = if transparent_background {
+			(String::new(), String::new())
+		} else {
+			let artboards = self.artboard_message_handler.artboards_graphene_document.render_root(render_data);
+			let outside_artboards_color = if self.artboard_message_handler.artboard_ids.is_empty() { "#ffffff" } else { "#222222" };
+			(artboards, format!(r#"<rect x="0" y="0" width="100%" height="100%" fill="{}" />"#, outside_artboards_color))
+		};
 		let matrix = transform
 			.to_cols_array()
 			.iter()
@@ -1011,8 +2548,14 @@ impl DocumentMessageHandler {
 		val.unwrap()
 	}
 
-	pub fn deserialize_document(serialized_content: &str) -> Result<Self, DocumentError> {
-		let deserialized_result: Result<Self, DocumentError> = serde_json::from_str(serialized_content).map_err(|e| DocumentError::InvalidFile(e.to_string()));
+	/// Serializes the document in the compressed binary format, which is smaller and faster to save and load than JSON.
+	pub fn serialize_document_compressed(&self) -> Vec<u8> {
+		encode_compressed_document(self)
+	}
+
+	/// Deserializes a document saved as either JSON or in the compressed binary format.
+	pub fn deserialize_document(serialized_content: &[u8]) -> Result<Self, DocumentError> {
+		let deserialized_result: Result<Self, DocumentError> = decode_document(serialized_content).map_err(DocumentError::InvalidFile);
 		match deserialized_result {
 			Ok(document) => {
 				if document.version == GRAPHITE_DOCUMENT_VERSION {
@@ -1033,7 +2576,7 @@ impl DocumentMessageHandler {
 		document
 	}
 
-	pub fn with_name_and_content(name: String, serialized_content: String) -> Result<Self, EditorError> {
+	pub fn with_name_and_content(name: String, serialized_content: Vec<u8>) -> Result<Self, EditorError> {
 		match Self::deserialize_document(&serialized_content) {
 			Ok(mut document) => {
 				document.name = name;
@@ -1054,6 +2597,10 @@ impl DocumentMessageHandler {
 	fn select_layer(&mut self, path: &[LayerId], font_cache: &FontCache) -> Option<Message> {
 		println!("Select_layer fail: {:?}", self.all_layers_sorted());
 
+		if self.graphene_document.is_locked(path) {
+			return None;
+		}
+
 		if let Some(layer) = self.layer_metadata.get_mut(path) {
 			layer.selected = true;
 			let data = self.layer_panel_entry(path.to_vec(), font_cache).ok()?;
@@ -1069,6 +2616,18 @@ impl DocumentMessageHandler {
 		self.graphene_document.combined_viewport_bounding_box(paths, font_cache)
 	}
 
+	/// The bounding box of the selected layers in document space, which is unaffected by panning, zooming, and tilting the viewport.
+	pub fn selected_visible_layers_document_bounding_box(&self, font_cache: &FontCache) -> Option<[DVec2; 2]> {
+		let viewport_to_document = self.graphene_document.root.transform.inverse();
+		self.selected_visible_layers()
+			.filter_map(|path| {
+				let layer = self.graphene_document.layer(path).ok()?;
+				let transform = self.graphene_document.multiply_transforms(path).ok()?;
+				layer.data.bounding_box(viewport_to_document * transform, font_cache)
+			})
+			.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])])
+	}
+
 	pub fn artboard_bounding_box_and_transform(&self, path: &[LayerId], font_cache: &FontCache) -> Option<([DVec2; 2], DAffine2)> {
 		self.artboard_message_handler.artboards_graphene_document.bounding_box_and_transform(path, font_cache).unwrap_or(None)
 	}
@@ -1097,6 +2656,11 @@ impl DocumentMessageHandler {
 		self.sort_layers(unique_layers.iter().copied())
 	}
 
+	/// The topmost retained overlay which can be interacted with at the viewport position.
+	pub fn overlay_at(&self, position: DVec2) -> Option<OverlayId> {
+		self.overlays_message_handler.overlay_at(position)
+	}
+
 	pub fn selected_layers_contains(&self, path: &[LayerId]) -> bool {
 		self.layer_metadata.get(path).map(|layer| layer.selected).unwrap_or(false)
 	}
@@ -1158,6 +2722,10 @@ impl DocumentMessageHandler {
 	fn serialize_structure(&self, folder: &FolderLayer, structure: &mut Vec<u64>, data: &mut Vec<LayerId>, path: &mut Vec<LayerId>) {
 		let mut space = 0;
 		for (id, layer) in folder.layer_ids.iter().zip(folder.layers()).rev() {
+			if !self.layers_panel_message_handler.filter.shows(layer) {
+				continue;
+			}
+
 			data.push(*id);
 			space += 1;
 			if let LayerDataType::Folder(ref folder) = layer.data {
@@ -1241,6 +2809,32 @@ impl DocumentMessageHandler {
 		layers_with_indices.into_iter().map(|(path, _)| path).collect()
 	}
 
+	/// Applies an operation made by this editor to the Graphene document, sending it to the other editors if the document is being edited together with them.
+	fn apply_operation(&mut self, operation: DocumentOperation, font_cache: &FontCache, responses: &mut VecDeque<Message>) -> Result<Option<Vec<DocumentResponse>>, DocumentError> {
+		if let Some(collaboration) = &mut self.collaboration {
+			if let Some(logged) = collaboration.log.record(&self.graphene_document, &operation) {
+				collaboration.log.compact(font_cache);
+				responses.push_back(collaboration.send(SessionMessage::Operations(vec![logged])));
+			}
+		}
+		self.graphene_document.handle_operation(operation, font_cache)
+	}
+
+	/// Returns the paths to all layers in the Graphene document, which are the same as those with layer metadata unless the document was changed by operations of other editors
+	fn layer_paths_in_document(&self) -> Vec<Vec<LayerId>> {
+		let mut paths = Vec::new();
+		let mut folders = vec![vec![]];
+		while let Some(folder) = folders.pop() {
+			for path in self.graphene_document.folder_children_paths(&folder) {
+				if self.graphene_document.is_folder(&path) {
+					folders.push(path.clone());
+				}
+				paths.push(path);
+			}
+		}
+		paths
+	}
+
 	/// Returns the paths to all layers in order
 	pub fn all_layers_sorted(&self) -> Vec<&[LayerId]> {
 		self.sort_layers(self.all_layers())
@@ -1302,6 +2896,7 @@ impl DocumentMessageHandler {
 				}
 
 				let document = std::mem::replace(&mut self.graphene_document, document);
+				self.send_snapshot(&document, responses);
 				let layer_metadata = std::mem::replace(&mut self.layer_metadata, layer_metadata);
 				self.document_redo_history.push((document, layer_metadata));
 
@@ -1332,6 +2927,7 @@ impl DocumentMessageHandler {
 				}
 
 				let document = std::mem::replace(&mut self.graphene_document, document);
+				self.send_snapshot(&document, responses);
 				let layer_metadata = std::mem::replace(&mut self.layer_metadata, layer_metadata);
 				self.document_undo_history.push((document, layer_metadata));
 
@@ -1345,6 +2941,14 @@ impl DocumentMessageHandler {
 		}
 	}
 
+	/// Sends the document, which replaced the previous one wholesale like by undoing, to the other editors editing it together with this one.
+	fn send_snapshot(&mut self, previous: &GrapheneDocument, responses: &mut VecDeque<Message>) {
+		if let Some(collaboration) = &mut self.collaboration {
+			let logged = collaboration.log.record_snapshot(previous, &self.graphene_document);
+			responses.push_back(collaboration.send(SessionMessage::Operations(vec![logged])));
+		}
+	}
+
 	pub fn current_identifier(&self) -> u64 {
 		// We can use the last state of the document to serve as the identifier to compare against
 		// This is useful since when the document is empty the identifier will be 0
@@ -1373,10 +2977,92 @@ impl DocumentMessageHandler {
 			.get_mut(&path)
 			.ok_or_else(|| EditorError::Document(format!("Could not get layer metadata for {:?}", path)))?;
 		let layer = self.graphene_document.layer(&path)?;
-		let entry = LayerPanelEntry::new(&data, self.graphene_document.multiply_transforms(&path)?, layer, path, font_cache);
+		// Leave out the viewport transform so the thumbnail, and therefore its hash, doesn't change while navigating
+		let transform = self.graphene_document.generate_transform_across_scope(&path, Some(self.graphene_document.root.transform.inverse()))?;
+		let mut entry = LayerPanelEntry::new(&data, transform, layer, path, font_cache);
+		entry.thumbnail_raster = self.layer_thumbnails.get(&entry.path).map(|(_, data_url)| data_url.clone());
 		Ok(entry)
 	}
 
+	/// Deselects all layers, then selects the given ones.
+	fn replace_selected_layers(&mut self, replacement_selected_layers: Vec<Vec<LayerId>>, responses: &mut VecDeque<Message>) {
+		let selected = self.layer_metadata.iter_mut().filter(|(_, layer_metadata)| layer_metadata.selected);
+		selected.for_each(|(path, layer_metadata)| {
+			layer_metadata.selected = false;
+			responses.push_back(DocumentMessage::LayerChanged { affected_layer_path: path.clone() }.into())
+		});
+
+		let additional_layers = replacement_selected_layers;
+		responses.push_front(DocumentMessage::AddSelectedLayers { additional_layers }.into());
+	}
+
+	/// Remembers the current selection when it's about to be replaced by a different one, so it can be reselected later.
+	fn remember_selection(&mut self, replacement_selected_layers: &[Vec<LayerId>]) {
+		let mut current = self.selected_layers().map(|path| path.to_vec()).collect::<Vec<_>>();
+		current.sort();
+		let mut replacement = replacement_selected_layers.to_vec();
+		replacement.sort();
+		if current.is_empty() || current == replacement || self.selection_history.back() == Some(&current) {
+			return;
+		}
+
+		if self.selection_history.len() >= SELECTION_HISTORY_LENGTH {
+			self.selection_history.pop_front();
+		}
+		self.selection_history.push_back(current);
+	}
+
+	/// Dims the viewport around the bounds of the isolated folder with an overlay, or removes the overlay when no folder is isolated.
+	fn update_isolation_overlay(&self, ipp: &InputPreprocessorMessageHandler, font_cache: &FontCache, responses: &mut VecDeque<Message>) {
+		let id = self.isolation_overlay_id;
+		let folder_path = match &self.graphene_document.isolated_folder {
+			Some(folder_path) => folder_path,
+			None => {
+				responses.push_back(DocumentMessage::Overlays(OverlaysMessage::RemoveOverlay { id }).into());
+				return;
+			}
+		};
+
+		let size = ipp.viewport_bounds.size();
+		let mut d = format!("M0,0 L{},0 L{},{} L0,{} Z", size.x, size.x, size.y, size.y);
+		// The bounds are wound the other way around than the viewport, which cuts them out of the nonzero fill
+		if let Ok(Some([min, max])) = self.graphene_document.viewport_bounding_box(folder_path, font_cache) {
+			d.push_str(&format!(" M{},{} L{},{} L{},{} L{},{} Z", min.x, min.y, min.x, max.y, max.x, max.y, max.x, min.y));
+		}
+
+		let style = OverlayStyle::new(Some(Color::from_rgba8(0, 0, 0, ISOLATION_DIM_ALPHA)), None, 0.);
+		let overlay = Overlay::new(OverlayPrimitive::Path { d }, style, ISOLATION_OVERLAY_Z_ORDER);
+		responses.push_back(DocumentMessage::Overlays(OverlaysMessage::SetOverlay { id, overlay }).into());
+	}
+
+	/// Asks the frontend to rasterize the thumbnail of the Layers panel entry, unless its current content was already rasterized or the layer is waiting for a rasterized thumbnail.
+	/// Until the new thumbnail arrives, the entry keeps showing the previous one.
+	pub fn request_layer_thumbnail(&mut self, entry: &LayerPanelEntry, document_id: u64, responses: &mut VecDeque<Message>) {
+		if entry.thumbnail.is_empty() || self.pending_layer_thumbnails.contains(&entry.path) {
+			return;
+		}
+		let content_hash = entry.thumbnail_hash();
+		if self.layer_thumbnails.get(&entry.path).map_or(false, |(hash, _)| *hash == content_hash) {
+			return;
+		}
+
+		self.pending_layer_thumbnails.insert(entry.path.clone());
+		let (width, height) = LAYER_THUMBNAIL_SIZE;
+		// The SVG thumbnail only has a viewBox, so it's given a size to be drawn at
+		let svg = entry.thumbnail.replacen("<svg ", &format!(r#"<svg width="{}" height="{}" "#, width, height), 1);
+		responses.push_back(
+			FrontendMessage::TriggerLayerThumbnailRasterization {
+				document_id,
+				layer_path: entry.path.clone(),
+				content_hash,
+				svg,
+				width,
+				height,
+			}
+			.into(),
+		);
+	}
+
 	/// Returns a list of `LayerPanelEntry`s intended for display purposes. These don't contain
 	/// any actual data, but rather attributes such as visibility and names of the layers.
 	pub fn layer_panel(&mut self, path: &[LayerId], font_cache: &FontCache) -> Result<Vec<LayerPanelEntry>, EditorError> {
@@ -1394,7 +3080,9 @@ impl DocumentMessageHandler {
 			.ok()?;
 		let layer = self.graphene_document.layer(path).ok()?;
 
-		Some(LayerPanelEntry::new(layer_metadata, transform, layer, path.to_vec(), font_cache))
+		let mut entry = LayerPanelEntry::new(layer_metadata, transform, layer, path.to_vec(), font_cache);
+		entry.thumbnail_raster = self.layer_thumbnails.get(path).map(|(_, data_url)| data_url.clone());
+		Some(entry)
 	}
 
 	/// When working with an insert index, deleting the layers may cause the insert index to point to a different location (if the layer being deleted was located before the insert index).
@@ -1408,6 +3096,25 @@ impl DocumentMessageHandler {
 		Ok(insert_index - layer_ids_above.iter().filter(|layer_id| layers.iter().any(|x| *x == [path, &[**layer_id]].concat())).count() as isize)
 	}
 
+	/// The names of the artboards in the order they were created, with unnamed artboards numbered.
+	pub fn artboard_names(&self) -> Vec<(LayerId, String)> {
+		let artboard_handler = &self.artboard_message_handler;
+		let mut index = 0;
+		artboard_handler
+			.artboard_ids
+			.iter()
+			.rev()
+			.filter_map(|&artboard| artboard_handler.artboards_graphene_document.layer(&[artboard]).ok().map(|layer| (artboard, layer)))
+			.map(|(artboard, layer)| {
+				let name = layer.name.clone().unwrap_or_else(|| {
+					index += 1;
+					format!("Untitled {index}")
+				});
+				(artboard, name)
+			})
+			.collect()
+	}
+
 	/// Calculates the bounding box of all layers in the document
 	pub fn all_layer_bounds(&self, font_cache: &FontCache) -> Option<[DVec2; 2]> {
 		self.graphene_document.viewport_bounding_box(&[], font_cache).ok().flatten()
@@ -1434,23 +3141,32 @@ impl DocumentMessageHandler {
 
 	/// Loads layer resources such as creating the blob URLs for the images and loading all of the fonts in the document
 	pub fn load_layer_resources(&self, responses: &mut VecDeque<Message>, root: &LayerDataType, mut path: Vec<LayerId>, document_id: u64) {
-		fn walk_layers(data: &LayerDataType, path: &mut Vec<LayerId>, image_data: &mut Vec<FrontendImageData>, fonts: &mut HashSet<Font>) {
+		fn walk_layers(data: &LayerDataType, path: &mut Vec<LayerId>, image_data: &mut Vec<FrontendImageData>, linked_images: &mut Vec<FrontendLinkedImage>, fonts: &mut HashSet<Font>) {
 			match data {
 				LayerDataType::Folder(folder) => {
 					for (id, layer) in folder.layer_ids.iter().zip(folder.layers().iter()) {
 						path.push(*id);
-						walk_layers(&layer.data, path, image_data, fonts);
+						walk_layers(&layer.data, path, image_data, linked_images, fonts);
 						path.pop();
 					}
 				}
 				LayerDataType::Text(text) => {
 					fonts.insert(text.font.clone());
 				}
-				LayerDataType::Image(image) => image_data.push(FrontendImageData {
-					path: path.clone(),
-					image_data: image.image_data.clone(),
-					mime: image.mime.clone(),
-				}),
+				// Linked images saved without their pixels are read from their file instead
+				LayerDataType::Image(image) => match &image.link {
+					Some(link) if image.image_data.is_empty() => linked_images.push(FrontendLinkedImage {
+						layer_path: path.clone(),
+						handle: link.handle.clone(),
+						file_name: link.file_name.clone(),
+						last_modified: None,
+					}),
+					_ => image_data.push(FrontendImageData {
+						path: path.clone(),
+						image_data: image.image_data.clone(),
+						mime: image.mime.clone(),
+					}),
+				},
 				LayerDataType::Imaginate(imaginate) => {
 					if let Some(data) = &imaginate.image_data {
 						image_data.push(FrontendImageData {
@@ -1465,16 +3181,34 @@ impl DocumentMessageHandler {
 		}
 
 		let mut image_data = Vec::new();
+		let mut linked_images = Vec::new();
 		let mut fonts = HashSet::new();
-		walk_layers(root, &mut path, &mut image_data, &mut fonts);
+		walk_layers(root, &mut path, &mut image_data, &mut linked_images, &mut fonts);
 		if !image_data.is_empty() {
 			responses.push_front(FrontendMessage::UpdateImageData { document_id, image_data }.into());
 		}
+		if !linked_images.is_empty() {
+			responses.push_back(
+				FrontendMessage::TriggerReadLinkedImages {
+					document_id,
+					images: linked_images,
+					request_permission: false,
+				}
+				.into(),
+			);
+		}
+		// Fonts which are embedded in the document or were already loaded aren't downloaded
 		for font in fonts {
-			responses.push_front(FrontendMessage::TriggerFontLoad { font, is_default: false }.into());
+			responses.push_front(PortfolioMessage::LoadFont { font, is_default: false }.into());
 		}
 	}
 
+	/// Makes the next render send the whole artwork to the frontend, instead of only what changed since this document was last rendered.
+	pub fn invalidate_sent_artwork(&mut self) {
+		self.gpu_scene = None;
+		self.artwork_snapshots = SentArtworkSnapshots::default();
+	}
+
 	pub fn update_document_widgets(&self, responses: &mut VecDeque<Message>) {
 		let mut widgets = vec![
 			WidgetHolder::new(Widget::OptionalInput(OptionalInput {
@@ -1489,6 +3223,28 @@ impl DocumentMessageHandler {
 				text: "The contents of this popover menu are coming soon".into(),
 				..Default::default()
 			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Related,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				entries: vec![[
+					(PixelSnapping::Off, "Pixel Snapping Off"),
+					(PixelSnapping::WholePixels, "Whole Pixels"),
+					(PixelSnapping::HalfPixels, "Half Pixels"),
+				]
+				.into_iter()
+				.map(|(pixel_snapping, label)| DropdownEntryData {
+					label: label.into(),
+					on_update: WidgetCallback::new(move |_| DocumentMessage::SetPixelSnapping { pixel_snapping }.into()),
+					..DropdownEntryData::default()
+				})
+				.collect()],
+				selected_index: Some(self.pixel_snapping as u32),
+				disabled: !self.snapping_enabled,
+				tooltip: "Pixel Snapping: rounds positions while drawing and transforming to the corners (whole pixels) or centers (half pixels) of the document's pixels".into(),
+				..DropdownInput::default()
+			})),
 			WidgetHolder::new(Widget::Separator(Separator {
 				separator_type: SeparatorType::Unrelated,
 				direction: SeparatorDirection::Horizontal,
@@ -1526,7 +3282,11 @@ impl DocumentMessageHandler {
 				direction: SeparatorDirection::Horizontal,
 			})),
 			WidgetHolder::new(Widget::RadioInput(RadioInput {
-				selected_index: if self.view_mode == ViewMode::Normal { 0 } else { 1 },
+				selected_index: match self.view_mode {
+					ViewMode::Normal => 0,
+					ViewMode::Outline => 1,
+					ViewMode::Pixels => 2,
+				},
 				entries: vec![
 					RadioEntryData {
 						value: "normal".into(),
@@ -1546,7 +3306,7 @@ impl DocumentMessageHandler {
 						value: "pixels".into(),
 						icon: "ViewModePixels".into(),
 						tooltip: "View Mode: Pixels".into(),
-						on_update: WidgetCallback::new(|_| DialogMessage::RequestComingSoonDialog { issue: Some(320) }.into()),
+						on_update: WidgetCallback::new(|_| DocumentMessage::SetViewMode { view_mode: ViewMode::Pixels }.into()),
 						..RadioEntryData::default()
 					},
 				],
@@ -1556,6 +3316,64 @@ impl DocumentMessageHandler {
 				text: "The contents of this popover menu are coming soon".into(),
 				..Default::default()
 			})),
+		];
+		if self.view_mode == ViewMode::Pixels {
+			widgets.extend([
+				WidgetHolder::new(Widget::Separator(Separator {
+					separator_type: SeparatorType::Related,
+					direction: SeparatorDirection::Horizontal,
+				})),
+				WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+					entries: vec![vec![
+						DropdownEntryData {
+							label: "1x".into(),
+							on_update: WidgetCallback::new(|_| DocumentMessage::SetPixelPreviewDensity { density: PixelDensity::Standard }.into()),
+							..DropdownEntryData::default()
+						},
+						DropdownEntryData {
+							label: "2x".into(),
+							on_update: WidgetCallback::new(|_| DocumentMessage::SetPixelPreviewDensity { density: PixelDensity::High }.into()),
+							..DropdownEntryData::default()
+						},
+					]],
+					selected_index: Some(if self.pixel_preview_density == PixelDensity::High { 1 } else { 0 }),
+					tooltip: "Pixel Density: the number of preview pixels for every document pixel".into(),
+					..DropdownInput::default()
+				})),
+			]);
+		}
+		widgets.extend([
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Related,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				entries: vec![vec![
+					DropdownEntryData {
+						label: "SVG".into(),
+						on_update: WidgetCallback::new(|_| {
+							DocumentMessage::SetViewportRenderer {
+								viewport_renderer: ViewportRenderer::Svg,
+							}
+							.into()
+						}),
+						..DropdownEntryData::default()
+					},
+					DropdownEntryData {
+						label: "GPU".into(),
+						on_update: WidgetCallback::new(|_| {
+							DocumentMessage::SetViewportRenderer {
+								viewport_renderer: ViewportRenderer::Gpu,
+							}
+							.into()
+						}),
+						..DropdownEntryData::default()
+					},
+				]],
+				selected_index: Some(if self.graphene_document.viewport_renderer == ViewportRenderer::Gpu { 1 } else { 0 }),
+				tooltip: "Viewport Renderer: the GPU renderer keeps pan and zoom interactive in very complex documents".into(),
+				..DropdownInput::default()
+			})),
 			WidgetHolder::new(Widget::Separator(Separator {
 				separator_type: SeparatorType::Section,
 				direction: SeparatorDirection::Horizontal,
@@ -1604,7 +3422,7 @@ impl DocumentMessageHandler {
 				increment_callback_increase: WidgetCallback::new(|_| NavigationMessage::IncreaseCanvasZoom { center_on_mouse: false }.into()),
 				..NumberInput::default()
 			})),
-		];
+		]);
 		let rotation_value = self.navigation_handler.snapped_angle() / (std::f64::consts::PI / 180.);
 		if rotation_value.abs() > 0.00001 {
 			widgets.extend([
@@ -1639,39 +3457,67 @@ impl DocumentMessageHandler {
 		]);
 		let document_bar_layout = WidgetLayout::new(vec![LayoutGroup::Row { widgets }]);
 
-		let document_mode_layout = WidgetLayout::new(vec![LayoutGroup::Row {
-			widgets: vec![
-				WidgetHolder::new(Widget::DropdownInput(DropdownInput {
-					entries: vec![vec![
-						DropdownEntryData {
-							label: DocumentMode::DesignMode.to_string(),
-							icon: DocumentMode::DesignMode.icon_name(),
-							..DropdownEntryData::default()
-						},
-						DropdownEntryData {
-							label: DocumentMode::SelectMode.to_string(),
-							icon: DocumentMode::SelectMode.icon_name(),
-							on_update: WidgetCallback::new(|_| DialogMessage::RequestComingSoonDialog { issue: Some(330) }.into()),
-							..DropdownEntryData::default()
-						},
-						DropdownEntryData {
-							label: DocumentMode::GuideMode.to_string(),
-							icon: DocumentMode::GuideMode.icon_name(),
-							on_update: WidgetCallback::new(|_| DialogMessage::RequestComingSoonDialog { issue: Some(331) }.into()),
-							..DropdownEntryData::default()
-						},
-					]],
-					selected_index: Some(self.document_mode as u32),
-					draw_icon: true,
-					interactive: false, // TODO: set to true when dialogs are not spawned
+		let mut document_mode_widgets = vec![
+			WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+				entries: vec![vec![
+					DropdownEntryData {
+						label: DocumentMode::DesignMode.to_string(),
+						icon: DocumentMode::DesignMode.icon_name(),
+						..DropdownEntryData::default()
+					},
+					DropdownEntryData {
+						label: DocumentMode::SelectMode.to_string(),
+						icon: DocumentMode::SelectMode.icon_name(),
+						on_update: WidgetCallback::new(|_| DialogMessage::RequestComingSoonDialog { issue: Some(330) }.into()),
+						..DropdownEntryData::default()
+					},
+					DropdownEntryData {
+						label: DocumentMode::GuideMode.to_string(),
+						icon: DocumentMode::GuideMode.icon_name(),
+						on_update: WidgetCallback::new(|_| DialogMessage::RequestComingSoonDialog { issue: Some(331) }.into()),
+						..DropdownEntryData::default()
+					},
+				]],
+				selected_index: Some(self.document_mode as u32),
+				draw_icon: true,
+				interactive: false, // TODO: set to true when dialogs are not spawned
+				..Default::default()
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Section,
+				direction: SeparatorDirection::Horizontal,
+			})),
+		];
+		// While a folder is isolated, a breadcrumb of the folders leading to it leaves isolation or isolates one of the folders containing it
+		if let Some(isolated_folder) = &self.graphene_document.isolated_folder {
+			document_mode_widgets.push(WidgetHolder::new(Widget::TextButton(TextButton {
+				label: "Document".into(),
+				tooltip: "Exit Isolation".into(),
+				on_update: WidgetCallback::new(|_| DocumentMessage::SetIsolatedFolder { folder_path: None }.into()),
+				..Default::default()
+			})));
+			for depth in 1..=isolated_folder.len() {
+				let folder_path = isolated_folder[..depth].to_vec();
+				let name = self.graphene_document.layer(&folder_path).ok().and_then(|layer| layer.name.clone()).unwrap_or_else(|| "Folder".into());
+				document_mode_widgets.push(WidgetHolder::new(Widget::TextLabel(TextLabel {
+					value: "›".into(),
 					..Default::default()
-				})),
-				WidgetHolder::new(Widget::Separator(Separator {
-					separator_type: SeparatorType::Section,
-					direction: SeparatorDirection::Horizontal,
-				})),
-			],
-		}]);
+				})));
+				document_mode_widgets.push(WidgetHolder::new(Widget::TextButton(TextButton {
+					label: name,
+					emphasized: depth == isolated_folder.len(),
+					tooltip: "Isolate This Folder".into(),
+					on_update: WidgetCallback::new(move |_| {
+						DocumentMessage::SetIsolatedFolder {
+							folder_path: Some(folder_path.clone()),
+						}
+						.into()
+					}),
+					..Default::default()
+				})));
+			}
+		}
+		let document_mode_layout = WidgetLayout::new(vec![LayoutGroup::Row { widgets: document_mode_widgets }]);
 
 		responses.push_back(
 			LayoutMessage::SendLayout {
@@ -1809,56 +3655,23 @@ impl DocumentMessageHandler {
 	pub fn selected_layers_reorder(&mut self, relative_index_offset: isize, responses: &mut VecDeque<Message>) {
 		self.backup(responses);
 
-		let all_layer_paths = self.all_layers_sorted();
-		let selected_layers = self.selected_layers_sorted();
-
-		let first_or_last_selected_layer = match relative_index_offset.signum() {
-			-1 => selected_layers.first(),
-			1 => selected_layers.last(),
-			_ => panic!("selected_layers_reorder() must be given a non-zero value"),
-		};
-
-		if let Some(pivot_layer) = first_or_last_selected_layer {
-			let sibling_layer_paths: Vec<_> = all_layer_paths
-				.iter()
-				.filter(|layer| {
-					// Check if this is a sibling of the pivot layer
-					// TODO: Break this out into a reusable function `fn are_layers_siblings(layer_a, layer_b) -> bool`
-					let containing_folder_path = &pivot_layer[0..pivot_layer.len() - 1];
-					layer.starts_with(containing_folder_path) && pivot_layer.len() == layer.len()
-				})
-				.collect();
-
-			// TODO: Break this out into a reusable function: `fn layer_index_in_containing_folder(layer_path) -> usize`
-			let pivot_index_among_siblings = sibling_layer_paths.iter().position(|path| *path == pivot_layer);
-
-			if let Some(pivot_index) = pivot_index_among_siblings {
-				let max = sibling_layer_paths.len() as i64 - 1;
-				let insert_index = (pivot_index as i64 + relative_index_offset as i64).clamp(0, max) as usize;
-
-				let existing_layer_to_insert_beside = sibling_layer_paths.get(insert_index);
-
-				// TODO: Break this block out into a call to a message called `MoveSelectedLayersNextToLayer { neighbor_path, above_or_below }`
-				if let Some(neighbor_path) = existing_layer_to_insert_beside {
-					let (neighbor_id, folder_path) = neighbor_path.split_last().expect("Can't move the root folder");
-
-					if let Some(folder) = self.graphene_document.layer(folder_path).ok().and_then(|layer| layer.as_folder().ok()) {
-						let neighbor_layer_index = folder.layer_ids.iter().position(|id| id == neighbor_id).unwrap() as isize;
-
-						// If moving down, insert below this layer. If moving up, insert above this layer.
-						let insert_index = if relative_index_offset < 0 { neighbor_layer_index } else { neighbor_layer_index + 1 };
+		// Group the selected layers by their containing folder, so each layer is only reordered among its siblings
+		let mut selected_by_folder: HashMap<Vec<LayerId>, Vec<LayerId>> = HashMap::new();
+		for path in self.selected_layers() {
+			if let Some((layer_id, folder_path)) = path.split_last() {
+				selected_by_folder.entry(folder_path.to_vec()).or_default().push(*layer_id);
+			}
+		}
 
-						responses.push_back(
-							DocumentMessage::MoveSelectedLayersTo {
-								folder_path: folder_path.to_vec(),
-								insert_index,
-								reverse_index: false,
-							}
-							.into(),
-						);
-					}
+		for (folder_path, layer_ids) in selected_by_folder {
+			responses.push_back(
+				DocumentOperation::ReorderLayers {
+					folder_path,
+					layer_ids,
+					relative_index_offset,
 				}
-			}
+				.into(),
+			);
 		}
 	}
 }
@@ -0,0 +1,11 @@
+use crate::messages::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+#[remain::sorted]
+#[impl_message(Message, DocumentMessage, LayersPanel)]
+#[derive(PartialEq, Eq, Clone, Debug, Hash, Serialize, Deserialize)]
+pub enum LayersPanelMessage {
+	// Messages
+	SetFilter(String),
+}
@@ -0,0 +1,49 @@
+use crate::messages::portfolio::document::utility_types::layer_panel::{LayerFilter, LayerMetadata};
+use crate::messages::prelude::*;
+
+use graphene::document::Document as GrapheneDocument;
+use graphene::LayerId;
+
+#[derive(Debug, Clone, Default)]
+pub struct LayersPanelMessageHandler {
+	pub filter: LayerFilter,
+}
+
+impl MessageHandler<LayersPanelMessage, (&GrapheneDocument, &HashMap<Vec<LayerId>, LayerMetadata>)> for LayersPanelMessageHandler {
+	#[remain::check]
+	fn process_message(&mut self, message: LayersPanelMessage, (graphene_document, layer_metadata): (&GrapheneDocument, &HashMap<Vec<LayerId>, LayerMetadata>), responses: &mut VecDeque<Message>) {
+		use LayersPanelMessage::*;
+
+		#[remain::sorted]
+		match message {
+			SetFilter(query) => {
+				self.filter = LayerFilter::from_query(&query);
+
+				if self.filter.is_active() {
+					// Expand every folder containing a match so the matches are visible in the tree
+					let ancestors = layer_metadata
+						.keys()
+						.filter(|path| graphene_document.layer(path).is_ok_and(|layer| self.filter.matches(layer)))
+						.flat_map(|path| (1..path.len()).map(move |length| &path[..length]))
+						.collect::<HashSet<_>>();
+
+					for layer_path in ancestors {
+						if layer_metadata.get(layer_path).is_some_and(|metadata| !metadata.expanded) {
+							responses.push_back(
+								DocumentMessage::SetLayerExpansion {
+									layer_path: layer_path.to_vec(),
+									set_expanded: true,
+								}
+								.into(),
+							);
+						}
+					}
+				}
+
+				responses.push_back(DocumentMessage::DocumentStructureChanged.into());
+			}
+		}
+	}
+
+	advertise_actions!(LayersPanelMessageDiscriminant;);
+}
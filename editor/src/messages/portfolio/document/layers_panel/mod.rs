@@ -0,0 +1,7 @@
+mod layers_panel_message;
+mod layers_panel_message_handler;
+
+#[doc(inline)]
+pub use layers_panel_message::{LayersPanelMessage, LayersPanelMessageDiscriminant};
+#[doc(inline)]
+pub use layers_panel_message_handler::LayersPanelMessageHandler;
@@ -2,9 +2,12 @@ mod document_message;
 mod document_message_handler;
 
 pub mod artboard;
+pub mod color_styles;
+pub mod layers_panel;
 pub mod navigation;
 pub mod overlays;
 pub mod properties_panel;
+pub mod text_styles;
 pub mod transform_layer;
 pub mod utility_types;
 
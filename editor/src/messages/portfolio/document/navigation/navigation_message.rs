@@ -1,4 +1,5 @@
 use crate::messages::input_mapper::utility_types::input_keyboard::Key;
+use crate::messages::input_mapper::utility_types::input_mouse::TouchGesture;
 use crate::messages::prelude::*;
 
 use glam::DVec2;
@@ -33,6 +34,13 @@ pub enum NavigationMessage {
 	SetCanvasZoom {
 		zoom_factor: f64,
 	},
+	SetRulerOrigin {
+		origin: DVec2,
+	},
+	TouchGesture {
+		from: TouchGesture,
+		to: TouchGesture,
+	},
 	TransformCanvasEnd,
 	TranslateCanvas {
 		delta: DVec2,
@@ -2,6 +2,7 @@ use crate::consts::{VIEWPORT_ROTATE_SNAP_INTERVAL, VIEWPORT_SCROLL_RATE, VIEWPOR
 use crate::messages::frontend::utility_types::MouseCursorIcon;
 use crate::messages::input_mapper::utility_types::input_keyboard::{Key, KeysGroup};
 use crate::messages::input_mapper::utility_types::input_mouse::{ViewportBounds, ViewportPosition};
+use crate::messages::portfolio::document::utility_types::document_view::DocumentView;
 use crate::messages::prelude::*;
 use crate::messages::tool::utility_types::{HintData, HintGroup, HintInfo};
 
@@ -26,6 +27,10 @@ pub struct NavigationMessageHandler {
 	snap_zoom: bool,
 
 	mouse_position: ViewportPosition,
+
+	/// The document space position where the rulers and the coordinates of layers in the properties panel measure from.
+	#[serde(default)]
+	pub ruler_origin: DVec2,
 }
 
 impl Default for NavigationMessageHandler {
@@ -44,6 +49,8 @@ impl Default for NavigationMessageHandler {
 			snap_zoom: false,
 
 			mouse_position: ViewportPosition::default(),
+
+			ruler_origin: DVec2::ZERO,
 		}
 	}
 }
@@ -188,6 +195,27 @@ impl MessageHandler<NavigationMessage, (&Document, &InputPreprocessorMessageHand
 				responses.push_back(PortfolioMessage::UpdateDocumentWidgets.into());
 				self.create_document_transform(&ipp.viewport_bounds, responses);
 			}
+			SetRulerOrigin { origin } => {
+				self.ruler_origin = origin;
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+				responses.push_back(PropertiesPanelMessage::ResendActiveProperties.into());
+			}
+			TouchGesture { from, to } => {
+				let half_viewport = ipp.viewport_bounds.size() / 2.;
+				let document_position = |viewport_position: DVec2, zoom: f64, tilt: f64, pan: DVec2| DAffine2::from_angle(-tilt).transform_vector2((viewport_position - half_viewport) / zoom) - pan;
+
+				// The document point under the fingers when the gesture moved stays under them as they pinch, twist, and drag
+				let anchor = document_position(from.centroid, self.zoom, self.tilt, self.pan);
+
+				self.zoom = (self.zoom * to.spread / from.spread).clamp(VIEWPORT_ZOOM_SCALE_MIN, VIEWPORT_ZOOM_SCALE_MAX);
+				self.tilt += to.angle - from.angle;
+				self.pan = document_position(to.centroid, self.zoom, self.tilt, DVec2::ZERO) - anchor;
+
+				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+				responses.push_back(DocumentMessage::DirtyRenderDocumentInOutlineView.into());
+				responses.push_back(PortfolioMessage::UpdateDocumentWidgets.into());
+				self.create_document_transform(&ipp.viewport_bounds, responses);
+			}
 			TransformCanvasEnd => {
 				self.tilt = self.snapped_angle();
 				self.zoom = self.snapped_scale();
@@ -315,6 +343,29 @@ impl NavigationMessageHandler {
 		scale_transform * offset_transform * angle_transform * translation_transform
 	}
 
+	/// Where the view currently looks, with the angle and zoom snapped like they're shown.
+	pub fn view(&self) -> DocumentView {
+		DocumentView {
+			pan: self.pan,
+			tilt: self.snapped_angle(),
+			zoom: self.snapped_scale(),
+		}
+	}
+
+	/// Looks where another view of the document was looking, keeping the ruler origin since it belongs to the document.
+	pub fn set_view(&mut self, view: DocumentView, viewport_bounds: &ViewportBounds, responses: &mut VecDeque<Message>) {
+		self.pan = view.pan;
+		self.tilt = view.tilt;
+		self.zoom = view.zoom;
+		self.snap_tilt = false;
+		self.snap_zoom = false;
+
+		self.create_document_transform(viewport_bounds, responses);
+		responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+		responses.push_back(DocumentMessage::DirtyRenderDocumentInOutlineView.into());
+		responses.push_back(PortfolioMessage::UpdateDocumentWidgets.into());
+	}
+
 	fn create_document_transform(&self, viewport_bounds: &ViewportBounds, responses: &mut VecDeque<Message>) {
 		let half_viewport = viewport_bounds.size() / 2.;
 		let scaled_half_viewport = half_viewport / self.snapped_scale();
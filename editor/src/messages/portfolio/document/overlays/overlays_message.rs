@@ -1,3 +1,4 @@
+use super::utility_types::{Overlay, OverlayId};
 use crate::messages::prelude::*;
 
 use graphene::Operation as DocumentOperation;
@@ -14,7 +15,14 @@ pub enum OverlaysMessage {
 
 	// Messages
 	ClearAllOverlays,
+	RemoveOverlay {
+		id: OverlayId,
+	},
 	Rerender,
+	SetOverlay {
+		id: OverlayId,
+		overlay: Overlay,
+	},
 }
 
 impl From<DocumentOperation> for OverlaysMessage {
@@ -1,12 +1,18 @@
+use super::utility_types::{Overlay, OverlayId};
 use crate::messages::prelude::*;
 
 use graphene::document::Document as GrapheneDocument;
 use graphene::layers::style::{RenderData, ViewMode};
 use graphene::layers::text_layer::FontCache;
 
+use glam::DVec2;
+use std::collections::BTreeMap;
+
 #[derive(Debug, Clone, Default)]
 pub struct OverlaysMessageHandler {
 	pub overlays_graphene_document: GrapheneDocument,
+	/// Overlays declared by tools, which persist until they are replaced or removed
+	retained_overlays: BTreeMap<OverlayId, Overlay>,
 }
 
 impl MessageHandler<OverlaysMessage, (bool, &FontCache, &InputPreprocessorMessageHandler)> for OverlaysMessageHandler {
@@ -24,7 +30,16 @@ impl MessageHandler<OverlaysMessage, (bool, &FontCache, &InputPreprocessorMessag
 			},
 
 			// Messages
-			ClearAllOverlays => todo!(),
+			ClearAllOverlays => {
+				self.overlays_graphene_document = GrapheneDocument::default();
+				self.retained_overlays.clear();
+				responses.push_back(OverlaysMessage::Rerender.into());
+			}
+			RemoveOverlay { id } => {
+				if self.retained_overlays.remove(&id).is_some() {
+					responses.push_back(OverlaysMessage::Rerender.into());
+				}
+			}
 			Rerender =>
 			// Render overlays
 			{
@@ -32,7 +47,11 @@ impl MessageHandler<OverlaysMessage, (bool, &FontCache, &InputPreprocessorMessag
 					FrontendMessage::UpdateDocumentOverlays {
 						svg: if overlays_visible {
 							let render_data = RenderData::new(ViewMode::Normal, font_cache, Some(ipp.document_bounds()));
-							self.overlays_graphene_document.render_root(render_data)
+							let mut svg = self.overlays_graphene_document.render_root(render_data);
+							for overlay in self.overlays_by_z_order() {
+								overlay.render(&mut svg);
+							}
+							svg
 						} else {
 							String::from("")
 						},
@@ -40,6 +59,12 @@ impl MessageHandler<OverlaysMessage, (bool, &FontCache, &InputPreprocessorMessag
 					.into(),
 				)
 			}
+			SetOverlay { id, overlay } => {
+				if self.retained_overlays.get(&id) != Some(&overlay) {
+					self.retained_overlays.insert(id, overlay);
+					responses.push_back(OverlaysMessage::Rerender.into());
+				}
+			}
 		}
 	}
 
@@ -49,3 +74,19 @@ impl MessageHandler<OverlaysMessage, (bool, &FontCache, &InputPreprocessorMessag
 		)
 	}
 }
+
+impl OverlaysMessageHandler {
+	/// The retained overlays from the bottom to the top, with ties kept in the order of their IDs.
+	fn overlays_by_z_order(&self) -> Vec<&Overlay> {
+		let mut overlays = self.retained_overlays.values().collect::<Vec<_>>();
+		overlays.sort_by_key(|overlay| overlay.z_order);
+		overlays
+	}
+
+	/// The topmost retained overlay with a hit region containing the viewport position, which pointer events at that position should be routed to.
+	pub fn overlay_at(&self, position: DVec2) -> Option<OverlayId> {
+		let mut overlays = self.retained_overlays.iter().filter(|(_, overlay)| overlay.is_hit(position)).collect::<Vec<_>>();
+		overlays.sort_by_key(|(_, overlay)| overlay.z_order);
+		overlays.last().map(|(&id, _)| id)
+	}
+}
@@ -0,0 +1,127 @@
+use graphene::color::Color;
+
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Identifies a retained overlay, chosen by the tool which owns it (usually from `generate_uuid()`).
+pub type OverlayId = u64;
+
+/// A persistent on-canvas overlay, kept by the overlays message handler until it is replaced or removed, rather than rebuilt by its tool on every frame.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Overlay {
+	pub primitive: OverlayPrimitive,
+	pub style: OverlayStyle,
+	/// Overlays with a higher z-order are drawn above (and hit tested before) those with a lower one
+	pub z_order: i32,
+	/// The region of the viewport where the pointer is over this overlay, or `None` if it can't be interacted with
+	pub hit_region: Option<HitRegion>,
+}
+
+impl Overlay {
+	pub fn new(primitive: OverlayPrimitive, style: OverlayStyle, z_order: i32) -> Self {
+		Self {
+			primitive,
+			style,
+			z_order,
+			hit_region: None,
+		}
+	}
+
+	pub fn with_hit_region(mut self, hit_region: HitRegion) -> Self {
+		self.hit_region = Some(hit_region);
+		self
+	}
+
+	/// Answers if the viewport position is within the hit region of the overlay.
+	pub fn is_hit(&self, position: DVec2) -> bool {
+		self.hit_region.as_ref().is_some_and(|hit_region| hit_region.contains(position))
+	}
+
+	/// Appends the SVG element drawing the overlay in viewport space.
+	pub fn render(&self, svg: &mut String) {
+		let style = self.style.render();
+		let _ = match &self.primitive {
+			OverlayPrimitive::Circle { center, diameter } => write!(svg, r#"<circle cx="{}" cy="{}" r="{}"{style} />"#, center.x, center.y, diameter / 2.),
+			OverlayPrimitive::Handle { center, size } => write!(svg, r#"<rect x="{}" y="{}" width="{size}" height="{size}"{style} />"#, center.x - size / 2., center.y - size / 2.),
+			OverlayPrimitive::Rect { min, max } => write!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}"{style} />"#, min.x, min.y, max.x - min.x, max.y - min.y),
+			OverlayPrimitive::Line { start, end } => write!(svg, r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{style} />"#, start.x, start.y, end.x, end.y),
+			OverlayPrimitive::Path { d } => write!(svg, r#"<path d="{d}"{style} />"#),
+			OverlayPrimitive::Label { position, text } => {
+				let text = text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+				write!(svg, r#"<text x="{}" y="{}"{style}>{text}</text>"#, position.x, position.y)
+			}
+		};
+	}
+}
+
+/// The shape drawn by an overlay, in viewport coordinates.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum OverlayPrimitive {
+	Circle {
+		center: DVec2,
+		diameter: f64,
+	},
+	/// A square of the given side length centered on a point, such as an anchor or gradient stop
+	Handle {
+		center: DVec2,
+		size: f64,
+	},
+	Rect {
+		min: DVec2,
+		max: DVec2,
+	},
+	Line {
+		start: DVec2,
+		end: DVec2,
+	},
+	/// An outline given by its SVG path data
+	Path {
+		d: String,
+	},
+	/// Text with its baseline starting at the position
+	Label {
+		position: DVec2,
+		text: String,
+	},
+}
+
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct OverlayStyle {
+	pub fill: Option<Color>,
+	pub stroke: Option<Color>,
+	pub stroke_width: f64,
+}
+
+impl OverlayStyle {
+	pub fn new(fill: Option<Color>, stroke: Option<Color>, stroke_width: f64) -> Self {
+		Self { fill, stroke, stroke_width }
+	}
+
+	fn render(&self) -> String {
+		let mut attributes = match self.fill {
+			Some(fill) => format!(r##" fill="#{}""##, fill.rgba_hex()),
+			None => String::from(r#" fill="none""#),
+		};
+		if let Some(stroke) = self.stroke {
+			let _ = write!(attributes, r##" stroke="#{}" stroke-width="{}""##, stroke.rgba_hex(), self.stroke_width);
+		}
+		attributes
+	}
+}
+
+/// The area of the viewport where pointer events are routed to an overlay.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum HitRegion {
+	Circle { center: DVec2, radius: f64 },
+	Rect { min: DVec2, max: DVec2 },
+}
+
+impl HitRegion {
+	pub fn contains(&self, position: DVec2) -> bool {
+		match self {
+			HitRegion::Circle { center, radius } => position.distance_squared(*center) < radius * radius,
+			HitRegion::Rect { min, max } => position.cmpge(*min).all() && position.cmple(*max).all(),
+		}
+	}
+}
@@ -10,6 +10,7 @@ use crate::messages::prelude::*;
 
 use graphene::{LayerId, Operation};
 
+use glam::DVec2;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -24,6 +25,8 @@ impl<'a> MessageHandler<PropertiesPanelMessage, (&PersistentData, PropertiesPane
 			artwork_document,
 			artboard_document,
 			selected_layers,
+			ruler_origin,
+			unit,
 		} = data;
 		let get_document = |document_selector: TargetDocument| match document_selector {
 			TargetDocument::Artboard => artboard_document,
@@ -107,7 +110,9 @@ impl<'a> MessageHandler<PropertiesPanelMessage, (&PersistentData, PropertiesPane
 				let position: Option<glam::DVec2> = new_position.into();
 				let pivot = position.unwrap().into();
 
+				responses.push_back(DocumentMessage::StartTransaction.into());
 				responses.push_back(Operation::SetPivot { layer_path, pivot }.into());
+				responses.push_back(DocumentMessage::CommitTransaction.into());
 			}
 			CheckSelectedWasUpdated { path } => {
 				if self.matches_selected(&path) {
@@ -136,9 +141,11 @@ impl<'a> MessageHandler<PropertiesPanelMessage, (&PersistentData, PropertiesPane
 			ResendActiveProperties => {
 				if let Some((path, target_document)) = self.active_selection.clone() {
 					let layer = get_document(target_document).layer(&path).unwrap();
+					// Layers inside folders are located relative to their folder, so only those directly in the document are measured from the ruler origin
+					let origin = if path.len() == 1 { ruler_origin } else { DVec2::ZERO };
 					match target_document {
-						TargetDocument::Artboard => register_artboard_layer_properties(layer, responses, persistent_data),
-						TargetDocument::Artwork => register_artwork_layer_properties(layer, responses, persistent_data),
+						TargetDocument::Artboard => register_artboard_layer_properties(layer, origin, unit, responses, persistent_data),
+						TargetDocument::Artwork => register_artwork_layer_properties(layer, origin, unit, responses, persistent_data),
 					}
 				}
 			}
@@ -7,6 +7,7 @@ use crate::messages::layout::utility_types::widgets::input_widgets::{
 	CheckboxInput, ColorInput, DropdownEntryData, DropdownInput, FontInput, NumberInput, RadioEntryData, RadioInput, TextAreaInput, TextInput,
 };
 use crate::messages::layout::utility_types::widgets::label_widgets::{IconLabel, Separator, SeparatorDirection, SeparatorType, TextLabel};
+use crate::messages::portfolio::document::utility_types::document_unit::DocumentUnit;
 use crate::messages::portfolio::utility_types::{ImaginateServerStatus, PersistentData};
 use crate::messages::prelude::*;
 
@@ -14,7 +15,7 @@ use graphene::color::Color;
 use graphene::document::pick_layer_safe_imaginate_resolution;
 use graphene::layers::imaginate_layer::{ImaginateLayer, ImaginateSamplingMethod, ImaginateStatus};
 use graphene::layers::layer_info::{Layer, LayerDataType, LayerDataTypeDiscriminant};
-use graphene::layers::style::{Fill, Gradient, GradientType, LineCap, LineJoin, Stroke};
+use graphene::layers::style::{Fill, Gradient, GradientSpread, GradientType, LineCap, LineJoin, Stroke};
 use graphene::layers::text_layer::{FontCache, TextLayer};
 
 use glam::{DAffine2, DVec2};
@@ -39,7 +40,7 @@ pub fn apply_transform_operation(layer: &Layer, transform_op: TransformOp, value
 	transformation(layer.transform, value / scale).to_cols_array()
 }
 
-pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDeque<Message>, persistent_data: &PersistentData) {
+pub fn register_artboard_layer_properties(layer: &Layer, origin: DVec2, unit: DocumentUnit, responses: &mut VecDeque<Message>, persistent_data: &PersistentData) {
 	let options_bar = vec![LayoutGroup::Row {
 		widgets: vec![
 			WidgetHolder::new(Widget::IconLabel(IconLabel {
@@ -81,12 +82,11 @@ pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDequ
 		} else {
 			panic!("Artboards can only be shapes")
 		};
-		let color = if let Fill::Solid(color) = shape.style.fill() {
-			color
-		} else {
-			panic!("Artboard must have a solid fill")
-		};
+		// Artboards without a fill have a transparent background
+		let color = if let Fill::Solid(color) = shape.style.fill() { Some(color.rgba_hex()) } else { None };
 		let pivot = layer.transform.transform_vector2(layer.layerspace_pivot(&persistent_data.font_cache));
+		// The location is shown at the pivot, measured from the ruler origin
+		let location_offset = pivot - origin;
 
 		vec![LayoutGroup::Section {
 			name: "Artboard".into(),
@@ -102,12 +102,12 @@ pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDequ
 							direction: SeparatorDirection::Horizontal,
 						})),
 						WidgetHolder::new(Widget::NumberInput(NumberInput {
-							value: Some(layer.transform.x() + pivot.x),
+							value: Some(unit.from_pixels(layer.transform.x() + location_offset.x)),
 							label: "X".into(),
-							unit: " px".into(),
+							unit: unit.number_input_unit(),
 							on_update: WidgetCallback::new(move |number_input: &NumberInput| {
 								PropertiesPanelMessage::ModifyTransform {
-									value: number_input.value.unwrap() - pivot.x,
+									value: unit.to_pixels(number_input.value.unwrap()) - location_offset.x,
 									transform_op: TransformOp::X,
 								}
 								.into()
@@ -119,12 +119,12 @@ pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDequ
 							direction: SeparatorDirection::Horizontal,
 						})),
 						WidgetHolder::new(Widget::NumberInput(NumberInput {
-							value: Some(layer.transform.y() + pivot.y),
+							value: Some(unit.from_pixels(layer.transform.y() + location_offset.y)),
 							label: "Y".into(),
-							unit: " px".into(),
+							unit: unit.number_input_unit(),
 							on_update: WidgetCallback::new(move |number_input: &NumberInput| {
 								PropertiesPanelMessage::ModifyTransform {
-									value: number_input.value.unwrap() - pivot.y,
+									value: unit.to_pixels(number_input.value.unwrap()) - location_offset.y,
 									transform_op: TransformOp::Y,
 								}
 								.into()
@@ -144,14 +144,15 @@ pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDequ
 							direction: SeparatorDirection::Horizontal,
 						})),
 						WidgetHolder::new(Widget::NumberInput(NumberInput {
-							value: Some(layer.bounding_transform(&persistent_data.font_cache).scale_x()),
+							value: Some(unit.from_pixels(layer.bounding_transform(&persistent_data.font_cache).scale_x())),
 							label: "W".into(),
-							unit: " px".into(),
-							is_integer: true,
-							min: Some(1.),
-							on_update: WidgetCallback::new(|number_input: &NumberInput| {
+							unit: unit.number_input_unit(),
+							// Only pixel sizes are kept whole, since a whole number of millimeters or inches is rarely a whole number of pixels
+							is_integer: unit == DocumentUnit::Pixels,
+							min: Some(unit.from_pixels(1.)),
+							on_update: WidgetCallback::new(move |number_input: &NumberInput| {
 								PropertiesPanelMessage::ModifyTransform {
-									value: number_input.value.unwrap(),
+									value: unit.to_pixels(number_input.value.unwrap()),
 									transform_op: TransformOp::Width,
 								}
 								.into()
@@ -163,14 +164,14 @@ pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDequ
 							direction: SeparatorDirection::Horizontal,
 						})),
 						WidgetHolder::new(Widget::NumberInput(NumberInput {
-							value: Some(layer.bounding_transform(&persistent_data.font_cache).scale_y()),
+							value: Some(unit.from_pixels(layer.bounding_transform(&persistent_data.font_cache).scale_y())),
 							label: "H".into(),
-							unit: " px".into(),
-							is_integer: true,
-							min: Some(1.),
-							on_update: WidgetCallback::new(|number_input: &NumberInput| {
+							unit: unit.number_input_unit(),
+							is_integer: unit == DocumentUnit::Pixels,
+							min: Some(unit.from_pixels(1.)),
+							on_update: WidgetCallback::new(move |number_input: &NumberInput| {
 								PropertiesPanelMessage::ModifyTransform {
-									value: number_input.value.unwrap(),
+									value: unit.to_pixels(number_input.value.unwrap()),
 									transform_op: TransformOp::Height,
 								}
 								.into()
@@ -190,7 +191,7 @@ pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDequ
 							direction: SeparatorDirection::Horizontal,
 						})),
 						WidgetHolder::new(Widget::ColorInput(ColorInput {
-							value: Some(color.rgba_hex()),
+							value: color,
 							on_update: WidgetCallback::new(|text_input: &ColorInput| {
 								if let Some(value) = &text_input.value {
 									if let Some(color) = Color::from_rgba_str(value).or_else(|| Color::from_rgb_str(value)) {
@@ -203,7 +204,7 @@ pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDequ
 									PropertiesPanelMessage::ModifyFill { fill: Fill::None }.into()
 								}
 							}),
-							no_transparency: true,
+							no_transparency: false,
 							..Default::default()
 						})),
 					],
@@ -228,7 +229,7 @@ pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDequ
 	);
 }
 
-pub fn register_artwork_layer_properties(layer: &Layer, responses: &mut VecDeque<Message>, persistent_data: &PersistentData) {
+pub fn register_artwork_layer_properties(layer: &Layer, origin: DVec2, unit: DocumentUnit, responses: &mut VecDeque<Message>, persistent_data: &PersistentData) {
 	let options_bar = vec![LayoutGroup::Row {
 		widgets: vec![
 			match &layer.data {
@@ -286,30 +287,36 @@ pub fn register_artwork_layer_properties(layer: &Layer, responses: &mut VecDeque
 		LayerDataType::Shape(shape) => {
 			if let Some(fill_layout) = node_section_fill(shape.style.fill()) {
 				vec![
-					node_section_transform(layer, persistent_data),
+					node_section_transform(layer, origin, unit, persistent_data),
 					fill_layout,
 					node_section_stroke(&shape.style.stroke().unwrap_or_default()),
 				]
 			} else {
-				vec![node_section_transform(layer, persistent_data), node_section_stroke(&shape.style.stroke().unwrap_or_default())]
+				vec![
+					node_section_transform(layer, origin, unit, persistent_data),
+					node_section_stroke(&shape.style.stroke().unwrap_or_default()),
+				]
 			}
 		}
 		LayerDataType::Text(text) => {
 			vec![
-				node_section_transform(layer, persistent_data),
+				node_section_transform(layer, origin, unit, persistent_data),
 				node_section_font(text),
 				node_section_fill(text.path_style.fill()).expect("Text should have fill"),
 				node_section_stroke(&text.path_style.stroke().unwrap_or_default()),
 			]
 		}
 		LayerDataType::Image(_) => {
-			vec![node_section_transform(layer, persistent_data)]
+			vec![node_section_transform(layer, origin, unit, persistent_data)]
 		}
 		LayerDataType::Imaginate(imaginate) => {
-			vec![node_section_transform(layer, persistent_data), node_section_imaginate(imaginate, layer, persistent_data, responses)]
+			vec![
+				node_section_transform(layer, origin, unit, persistent_data),
+				node_section_imaginate(imaginate, layer, persistent_data, responses),
+			]
 		}
 		LayerDataType::Folder(_) => {
-			vec![node_section_transform(layer, persistent_data)]
+			vec![node_section_transform(layer, origin, unit, persistent_data)]
 		}
 	};
 
@@ -329,8 +336,10 @@ pub fn register_artwork_layer_properties(layer: &Layer, responses: &mut VecDeque
 	);
 }
 
-fn node_section_transform(layer: &Layer, persistent_data: &PersistentData) -> LayoutGroup {
+fn node_section_transform(layer: &Layer, origin: DVec2, unit: DocumentUnit, persistent_data: &PersistentData) -> LayoutGroup {
 	let pivot = layer.transform.transform_vector2(layer.layerspace_pivot(&persistent_data.font_cache));
+	// The location is shown at the pivot, measured from the ruler origin
+	let location_offset = pivot - origin;
 	LayoutGroup::Section {
 		name: "Transform".into(),
 		layout: vec![
@@ -353,12 +362,12 @@ fn node_section_transform(layer: &Layer, persistent_data: &PersistentData) -> La
 						direction: SeparatorDirection::Horizontal,
 					})),
 					WidgetHolder::new(Widget::NumberInput(NumberInput {
-						value: Some(layer.transform.x() + pivot.x),
+						value: Some(unit.from_pixels(layer.transform.x() + location_offset.x)),
 						label: "X".into(),
-						unit: " px".into(),
+						unit: unit.number_input_unit(),
 						on_update: WidgetCallback::new(move |number_input: &NumberInput| {
 							PropertiesPanelMessage::ModifyTransform {
-								value: number_input.value.unwrap() - pivot.x,
+								value: unit.to_pixels(number_input.value.unwrap()) - location_offset.x,
 								transform_op: TransformOp::X,
 							}
 							.into()
@@ -370,12 +379,12 @@ fn node_section_transform(layer: &Layer, persistent_data: &PersistentData) -> La
 						direction: SeparatorDirection::Horizontal,
 					})),
 					WidgetHolder::new(Widget::NumberInput(NumberInput {
-						value: Some(layer.transform.y() + pivot.y),
+						value: Some(unit.from_pixels(layer.transform.y() + location_offset.y)),
 						label: "Y".into(),
-						unit: " px".into(),
+						unit: unit.number_input_unit(),
 						on_update: WidgetCallback::new(move |number_input: &NumberInput| {
 							PropertiesPanelMessage::ModifyTransform {
-								value: number_input.value.unwrap() - pivot.y,
+								value: unit.to_pixels(number_input.value.unwrap()) - location_offset.y,
 								transform_op: TransformOp::Y,
 							}
 							.into()
@@ -462,12 +471,12 @@ fn node_section_transform(layer: &Layer, persistent_data: &PersistentData) -> La
 						direction: SeparatorDirection::Horizontal,
 					})),
 					WidgetHolder::new(Widget::NumberInput(NumberInput {
-						value: Some(layer.bounding_transform(&persistent_data.font_cache).scale_x()),
+						value: Some(unit.from_pixels(layer.bounding_transform(&persistent_data.font_cache).scale_x())),
 						label: "W".into(),
-						unit: " px".into(),
-						on_update: WidgetCallback::new(|number_input: &NumberInput| {
+						unit: unit.number_input_unit(),
+						on_update: WidgetCallback::new(move |number_input: &NumberInput| {
 							PropertiesPanelMessage::ModifyTransform {
-								value: number_input.value.unwrap(),
+								value: unit.to_pixels(number_input.value.unwrap()),
 								transform_op: TransformOp::Width,
 							}
 							.into()
@@ -479,12 +488,12 @@ fn node_section_transform(layer: &Layer, persistent_data: &PersistentData) -> La
 						direction: SeparatorDirection::Horizontal,
 					})),
 					WidgetHolder::new(Widget::NumberInput(NumberInput {
-						value: Some(layer.bounding_transform(&persistent_data.font_cache).scale_y()),
+						value: Some(unit.from_pixels(layer.bounding_transform(&persistent_data.font_cache).scale_y())),
 						label: "H".into(),
-						unit: " px".into(),
-						on_update: WidgetCallback::new(|number_input: &NumberInput| {
+						unit: unit.number_input_unit(),
+						on_update: WidgetCallback::new(move |number_input: &NumberInput| {
 							PropertiesPanelMessage::ModifyTransform {
-								value: number_input.value.unwrap(),
+								value: unit.to_pixels(number_input.value.unwrap()),
 								transform_op: TransformOp::Height,
 							}
 							.into()
@@ -1121,14 +1130,26 @@ fn node_section_font(layer: &TextLayer) -> LayoutGroup {
 }
 
 fn node_gradient_type(gradient: &Gradient) -> LayoutGroup {
-	let selected_index = match gradient.gradient_type {
-		GradientType::Linear => 0,
-		GradientType::Radial => 1,
-	};
-	let mut cloned_gradient_linear = gradient.clone();
-	cloned_gradient_linear.gradient_type = GradientType::Linear;
-	let mut cloned_gradient_radial = gradient.clone();
-	cloned_gradient_radial.gradient_type = GradientType::Radial;
+	let selected_index = GradientType::ALL.iter().position(|&gradient_type| gradient_type == gradient.gradient_type).unwrap_or_default() as u32;
+	let entries = GradientType::ALL
+		.into_iter()
+		.map(|gradient_type| {
+			let mut new_gradient = gradient.clone();
+			new_gradient.gradient_type = gradient_type;
+			RadioEntryData {
+				value: gradient_type.name().to_lowercase(),
+				label: gradient_type.name().into(),
+				tooltip: format!("{} Gradient", gradient_type.name()),
+				on_update: WidgetCallback::new(move |_| {
+					PropertiesPanelMessage::ModifyFill {
+						fill: Fill::Gradient(new_gradient.clone()),
+					}
+					.into()
+				}),
+				..RadioEntryData::default()
+			}
+		})
+		.collect();
 	LayoutGroup::Row {
 		widgets: vec![
 			WidgetHolder::new(Widget::TextLabel(TextLabel {
@@ -1139,35 +1160,47 @@ fn node_gradient_type(gradient: &Gradient) -> LayoutGroup {
 				separator_type: SeparatorType::Unrelated,
 				direction: SeparatorDirection::Horizontal,
 			})),
-			WidgetHolder::new(Widget::RadioInput(RadioInput {
-				selected_index,
-				entries: vec![
-					RadioEntryData {
-						value: "linear".into(),
-						label: "Linear".into(),
-						tooltip: "Linear Gradient".into(),
-						on_update: WidgetCallback::new(move |_| {
-							PropertiesPanelMessage::ModifyFill {
-								fill: Fill::Gradient(cloned_gradient_linear.clone()),
-							}
-							.into()
-						}),
-						..RadioEntryData::default()
-					},
-					RadioEntryData {
-						value: "radial".into(),
-						label: "Radial".into(),
-						tooltip: "Radial Gradient".into(),
-						on_update: WidgetCallback::new(move |_| {
-							PropertiesPanelMessage::ModifyFill {
-								fill: Fill::Gradient(cloned_gradient_radial.clone()),
-							}
-							.into()
-						}),
-						..RadioEntryData::default()
-					},
-				],
+			WidgetHolder::new(Widget::RadioInput(RadioInput { selected_index, entries })),
+		],
+	}
+}
+
+fn node_gradient_spread(gradient: &Gradient) -> LayoutGroup {
+	let selected_index = GradientSpread::ALL.iter().position(|&spread| spread == gradient.spread).unwrap_or_default() as u32;
+	let entries = GradientSpread::ALL
+		.into_iter()
+		.map(|spread| {
+			let mut new_gradient = gradient.clone();
+			new_gradient.spread = spread;
+			RadioEntryData {
+				value: spread.svg_name().into(),
+				label: spread.name().into(),
+				tooltip: match spread {
+					GradientSpread::Pad => "Extend the colors at the ends of the gradient".into(),
+					GradientSpread::Repeat => "Repeat the colors of the gradient past its end".into(),
+					GradientSpread::Reflect => "Repeat the colors of the gradient past its end, alternating their direction".into(),
+				},
+				on_update: WidgetCallback::new(move |_| {
+					PropertiesPanelMessage::ModifyFill {
+						fill: Fill::Gradient(new_gradient.clone()),
+					}
+					.into()
+				}),
+				..RadioEntryData::default()
+			}
+		})
+		.collect();
+	LayoutGroup::Row {
+		widgets: vec![
+			WidgetHolder::new(Widget::TextLabel(TextLabel {
+				value: "Spread".into(),
+				..TextLabel::default()
 			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				separator_type: SeparatorType::Unrelated,
+				direction: SeparatorDirection::Horizontal,
+			})),
+			WidgetHolder::new(Widget::RadioInput(RadioInput { selected_index, entries })),
 		],
 	}
 }
@@ -1243,7 +1276,12 @@ fn node_section_fill(fill: &Fill) -> Option<LayoutGroup> {
 		}),
 		Fill::Gradient(gradient) => Some(LayoutGroup::Section {
 			name: "Fill".into(),
-			layout: vec![node_gradient_type(gradient), node_gradient_color(gradient, "0%", 0), node_gradient_color(gradient, "100%", 1)],
+			layout: vec![
+				node_gradient_type(gradient),
+				node_gradient_spread(gradient),
+				node_gradient_color(gradient, "0%", 0),
+				node_gradient_color(gradient, "100%", 1),
+			],
 		}),
 	}
 }
@@ -1346,8 +1384,7 @@ fn node_section_stroke(stroke: &Stroke) -> LayoutGroup {
 					})),
 					WidgetHolder::new(Widget::NumberInput(NumberInput {
 						value: Some(stroke.dash_offset() as f64),
-						is_integer: true,
-						min: Some(0.),
+						is_integer: false,
 						unit: " px".into(),
 						on_update: WidgetCallback::new(move |number_input: &NumberInput| {
 							PropertiesPanelMessage::ModifyStroke {
@@ -1453,7 +1490,6 @@ fn node_section_stroke(stroke: &Stroke) -> LayoutGroup {
 					})),
 				],
 			},
-			// TODO: Gray out this row when Line Join isn't set to Miter
 			LayoutGroup::Row {
 				widgets: vec![
 					WidgetHolder::new(Widget::TextLabel(TextLabel {
@@ -1466,9 +1502,11 @@ fn node_section_stroke(stroke: &Stroke) -> LayoutGroup {
 					})),
 					WidgetHolder::new(Widget::NumberInput(NumberInput {
 						value: Some(stroke.line_join_miter_limit() as f64),
-						is_integer: true,
-						min: Some(0.),
+						is_integer: false,
+						min: Some(1.),
 						unit: "".into(),
+						tooltip: "How far a miter may reach past its corner, as a multiple of half the weight, before it's beveled instead".into(),
+						disabled: stroke.line_join() != LineJoin::Miter,
 						on_update: WidgetCallback::new(move |number_input: &NumberInput| {
 							PropertiesPanelMessage::ModifyStroke {
 								stroke: internal_stroke5.clone().with_line_join_miter_limit(number_input.value.unwrap()),
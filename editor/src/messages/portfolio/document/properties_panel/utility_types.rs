@@ -1,12 +1,18 @@
+use crate::messages::portfolio::document::utility_types::document_unit::DocumentUnit;
+
 use graphene::document::Document as GrapheneDocument;
 use graphene::LayerId;
 
+use glam::DVec2;
 use serde::{Deserialize, Serialize};
 
 pub struct PropertiesPanelMessageHandlerData<'a> {
 	pub artwork_document: &'a GrapheneDocument,
 	pub artboard_document: &'a GrapheneDocument,
 	pub selected_layers: &'a mut dyn Iterator<Item = &'a [LayerId]>,
+	/// The document space position the locations of layers directly in the document are measured from
+	pub ruler_origin: DVec2,
+	pub unit: DocumentUnit,
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
@@ -0,0 +1,7 @@
+mod text_styles_message;
+mod text_styles_message_handler;
+
+#[doc(inline)]
+pub use text_styles_message::{TextStylesMessage, TextStylesMessageDiscriminant};
+#[doc(inline)]
+pub use text_styles_message_handler::TextStylesMessageHandler;
@@ -0,0 +1,18 @@
+use crate::messages::prelude::*;
+
+use graphene::layers::text_layer::{Font, TextStyleId, Typesetting};
+
+use serde::{Deserialize, Serialize};
+
+#[remain::sorted]
+#[impl_message(Message, DocumentMessage, TextStyles)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum TextStylesMessage {
+	// Messages
+	Create { name: String, font: Font, size: f64, typesetting: Typesetting },
+	CreateFromSelectedLayer { name: String },
+	Delete { id: TextStyleId },
+	LinkSelectedLayers { id: Option<TextStyleId> },
+	Rename { id: TextStyleId, name: String },
+	Update { id: TextStyleId, font: Font, size: f64, typesetting: Typesetting },
+}
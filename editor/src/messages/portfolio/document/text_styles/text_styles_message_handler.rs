@@ -0,0 +1,108 @@
+use crate::application::generate_uuid;
+use crate::messages::prelude::*;
+
+use graphene::document::Document as GrapheneDocument;
+use graphene::layers::text_layer::TextStyle;
+use graphene::{LayerId, Operation as DocumentOperation};
+
+/// Manages the document's character and paragraph styles. Every edit is wrapped in a single transaction so it can be undone in one step,
+/// no matter how many text layers are laid out again because of it.
+#[derive(Debug, Clone, Default)]
+pub struct TextStylesMessageHandler {}
+
+impl MessageHandler<TextStylesMessage, (&GrapheneDocument, &[&[LayerId]])> for TextStylesMessageHandler {
+	#[remain::check]
+	fn process_message(&mut self, message: TextStylesMessage, (graphene_document, selected_layers): (&GrapheneDocument, &[&[LayerId]]), responses: &mut VecDeque<Message>) {
+		use TextStylesMessage::*;
+
+		#[remain::sorted]
+		match message {
+			Create { name, font, size, typesetting } => {
+				let text_style = TextStyle { name, font, size, typesetting };
+
+				responses.push_back(
+					PortfolioMessage::LoadFont {
+						font: text_style.font.clone(),
+						is_default: false,
+					}
+					.into(),
+				);
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				responses.push_back(DocumentOperation::SetTextStyle { id: generate_uuid(), text_style }.into());
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			CreateFromSelectedLayer { name } => {
+				let mut selected_text_layers = selected_layers.iter().filter_map(|path| Some((path, graphene_document.layer(path).ok()?.as_text().ok()?)));
+				let (path, text) = match selected_text_layers.next() {
+					Some(selected_text_layer) => selected_text_layer,
+					None => return,
+				};
+
+				let id = generate_uuid();
+				let text_style = TextStyle {
+					name,
+					font: text.font.clone(),
+					size: text.size,
+					typesetting: text.typesetting,
+				};
+
+				// Link the layer the style was created from, so it follows any later edits to the style
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				responses.push_back(DocumentOperation::SetTextStyle { id, text_style }.into());
+				responses.push_back(DocumentOperation::SetLayerTextStyle { path: path.to_vec(), id: Some(id) }.into());
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			Delete { id } => {
+				if !graphene_document.text_styles.contains_key(&id) {
+					return;
+				}
+
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				responses.push_back(DocumentOperation::DeleteTextStyle { id }.into());
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			LinkSelectedLayers { id } => {
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				for path in selected_layers.iter().filter(|path| graphene_document.layer(path).and_then(|layer| layer.as_text()).is_ok()) {
+					responses.push_back(DocumentOperation::SetLayerTextStyle { path: path.to_vec(), id }.into());
+				}
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			Rename { id, name } => {
+				let text_style = match graphene_document.text_styles.get(&id) {
+					Some(text_style) if text_style.name != name => TextStyle { name, ..text_style.clone() },
+					_ => return,
+				};
+
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				responses.push_back(DocumentOperation::SetTextStyle { id, text_style }.into());
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+			Update { id, font, size, typesetting } => {
+				let text_style = match graphene_document.text_styles.get(&id) {
+					Some(text_style) => TextStyle {
+						name: text_style.name.clone(),
+						font,
+						size,
+						typesetting,
+					},
+					None => return,
+				};
+
+				// A single operation lays out every linked text layer again
+				responses.push_back(
+					PortfolioMessage::LoadFont {
+						font: text_style.font.clone(),
+						is_default: false,
+					}
+					.into(),
+				);
+				responses.push_back(DocumentMessage::StartTransaction.into());
+				responses.push_back(DocumentOperation::SetTextStyle { id, text_style }.into());
+				responses.push_back(DocumentMessage::CommitTransaction.into());
+			}
+		}
+	}
+
+	advertise_actions!(TextStylesMessageDiscriminant;);
+}
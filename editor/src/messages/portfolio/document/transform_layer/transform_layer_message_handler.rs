@@ -1,6 +1,8 @@
 use crate::consts::SLOWING_DIVISOR;
 use crate::messages::input_mapper::utility_types::input_mouse::ViewportPosition;
 use crate::messages::portfolio::document::utility_types::layer_panel::LayerMetadata;
+use crate::messages::portfolio::document::utility_types::misc::PixelSnapping;
+use crate::messages::portfolio::document::utility_types::transform_settings::TransformSettings;
 use crate::messages::portfolio::document::utility_types::transformation::{Axis, OriginalTransforms, Selected, TransformOperation, Typing};
 use crate::messages::prelude::*;
 
@@ -25,10 +27,17 @@ pub struct TransformLayerMessageHandler {
 	pivot: DVec2,
 }
 
-type TransformData<'a> = (&'a mut HashMap<Vec<LayerId>, LayerMetadata>, &'a mut Document, &'a InputPreprocessorMessageHandler, &'a FontCache);
+type TransformData<'a> = (
+	&'a mut HashMap<Vec<LayerId>, LayerMetadata>,
+	&'a mut Document,
+	&'a InputPreprocessorMessageHandler,
+	&'a FontCache,
+	&'a TransformSettings,
+	PixelSnapping,
+);
 impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformLayerMessageHandler {
 	#[remain::check]
-	fn process_message(&mut self, message: TransformLayerMessage, (layer_metadata, document, ipp, font_cache): TransformData, responses: &mut VecDeque<Message>) {
+	fn process_message(&mut self, message: TransformLayerMessage, (layer_metadata, document, ipp, font_cache, transform_settings, pixel_snapping): TransformData, responses: &mut VecDeque<Message>) {
 		use TransformLayerMessage::*;
 
 		let selected_layers = layer_metadata.iter().filter_map(|(layer_path, data)| data.selected.then(|| layer_path)).collect::<Vec<_>>();
@@ -46,6 +55,9 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 			*start_mouse = ipp.mouse.position;
 		};
 
+		// Scaling along a single axis would break the aspect ratio the document locks
+		let axes_locked = transform_settings.lock_aspect_ratio && matches!(self.transform_operation, TransformOperation::Scaling(_));
+
 		#[remain::sorted]
 		match message {
 			ApplyTransformOperation => {
@@ -101,7 +113,7 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 				begin_operation(self.transform_operation, &mut self.typing, &mut self.mouse_position, &mut self.start_mouse);
 
 				self.transform_operation = TransformOperation::Scaling(Default::default());
-				self.transform_operation.apply_transform_operation(&mut selected, self.snap);
+				self.transform_operation.apply_transform_operation(&mut selected, self.snap, pixel_snapping);
 
 				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
 			}
@@ -115,15 +127,23 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 
 				responses.push_back(BroadcastEvent::DocumentIsDirty.into());
 			}
-			ConstrainX => self.transform_operation.constrain_axis(Axis::X, &mut selected, self.snap),
-			ConstrainY => self.transform_operation.constrain_axis(Axis::Y, &mut selected, self.snap),
+			ConstrainX => {
+				if !axes_locked {
+					self.transform_operation.constrain_axis(Axis::X, &mut selected, self.snap, pixel_snapping)
+				}
+			}
+			ConstrainY => {
+				if !axes_locked {
+					self.transform_operation.constrain_axis(Axis::Y, &mut selected, self.snap, pixel_snapping)
+				}
+			}
 			PointerMove { slow_key, snap_key } => {
 				self.slow = ipp.keyboard.get(slow_key as usize);
 
 				let new_snap = ipp.keyboard.get(snap_key as usize);
 				if new_snap != self.snap {
 					self.snap = new_snap;
-					self.transform_operation.apply_transform_operation(&mut selected, self.snap);
+					self.transform_operation.apply_transform_operation(&mut selected, self.snap, pixel_snapping);
 				}
 
 				if self.typing.digits.is_empty() {
@@ -134,7 +154,7 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 						TransformOperation::Grabbing(translation) => {
 							let change = if self.slow { delta_pos / SLOWING_DIVISOR } else { delta_pos };
 							self.transform_operation = TransformOperation::Grabbing(translation.increment_amount(change));
-							self.transform_operation.apply_transform_operation(&mut selected, self.snap);
+							self.transform_operation.apply_transform_operation(&mut selected, self.snap, pixel_snapping);
 						}
 						TransformOperation::Rotating(rotation) => {
 							let selected_pivot = selected.mean_average_of_pivots(font_cache);
@@ -147,7 +167,7 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 
 							let change = if self.slow { angle / SLOWING_DIVISOR } else { angle };
 							self.transform_operation = TransformOperation::Rotating(rotation.increment_amount(change));
-							self.transform_operation.apply_transform_operation(&mut selected, self.snap);
+							self.transform_operation.apply_transform_operation(&mut selected, self.snap, pixel_snapping);
 						}
 						TransformOperation::Scaling(scale) => {
 							let change = {
@@ -160,16 +180,16 @@ impl<'a> MessageHandler<TransformLayerMessage, TransformData<'a>> for TransformL
 
 							let change = if self.slow { change / SLOWING_DIVISOR } else { change };
 							self.transform_operation = TransformOperation::Scaling(scale.increment_amount(change));
-							self.transform_operation.apply_transform_operation(&mut selected, self.snap);
+							self.transform_operation.apply_transform_operation(&mut selected, self.snap, pixel_snapping);
 						}
 					};
 				}
 				self.mouse_position = ipp.mouse.position;
 			}
-			TypeBackspace => self.transform_operation.handle_typed(self.typing.type_backspace(), &mut selected, self.snap),
-			TypeDecimalPoint => self.transform_operation.handle_typed(self.typing.type_decimal_point(), &mut selected, self.snap),
-			TypeDigit { digit } => self.transform_operation.handle_typed(self.typing.type_number(digit), &mut selected, self.snap),
-			TypeNegate => self.transform_operation.handle_typed(self.typing.type_negate(), &mut selected, self.snap),
+			TypeBackspace => self.transform_operation.handle_typed(self.typing.type_backspace(), &mut selected, self.snap, pixel_snapping),
+			TypeDecimalPoint => self.transform_operation.handle_typed(self.typing.type_decimal_point(), &mut selected, self.snap, pixel_snapping),
+			TypeDigit { digit } => self.transform_operation.handle_typed(self.typing.type_number(digit), &mut selected, self.snap, pixel_snapping),
+			TypeNegate => self.transform_operation.handle_typed(self.typing.type_negate(), &mut selected, self.snap, pixel_snapping),
 		}
 	}
 
@@ -0,0 +1,506 @@
+//! Encodes rasterized frames as animated GIF and APNG files.
+//!
+//! Like the still image encoders, both take tightly packed, non-premultiplied 8-bit RGBA pixels in rows from top to bottom.
+
+use super::color_profile::tag_image;
+use super::error::EditorError;
+use super::image_encoding::check_dimensions;
+
+use graphene::color::ColorSpace;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+/// How the colors of a GIF which aren't in its palette are approximated.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Dithering {
+	/// Each pixel takes the closest color of the palette, which leaves visible bands in gradients
+	None,
+	/// A fixed pattern of offsets mixes nearby palette colors, which stays in place from frame to frame instead of shimmering
+	Ordered,
+	/// Floyd–Steinberg error diffusion spreads the difference between each pixel and its palette color to the pixels after it, for the smoothest gradients
+	#[default]
+	ErrorDiffusion,
+}
+
+/// The frame range, timing, and palette of an animated export.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnimationSettings {
+	pub first_frame: u32,
+	pub last_frame: u32,
+	pub frame_rate: f64,
+	/// The number of times the animation plays, or 0 to loop it forever
+	pub plays: u32,
+	/// The most colors in the palette of a GIF, from 2 to 256
+	pub colors: u16,
+	pub dithering: Dithering,
+}
+
+impl Default for AnimationSettings {
+	fn default() -> Self {
+		Self {
+			first_frame: 0,
+			last_frame: 47,
+			frame_rate: 24.,
+			plays: 0,
+			colors: 256,
+			dithering: Dithering::default(),
+		}
+	}
+}
+
+impl AnimationSettings {
+	pub fn frames(&self) -> RangeInclusive<u32> {
+		self.first_frame..=self.last_frame.max(self.first_frame)
+	}
+
+	pub fn frame_count(&self) -> u32 {
+		self.frames().count() as u32
+	}
+}
+
+/// An animated format, which the editor encodes from the pixels of the frames rasterized by the frontend.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AnimationFormat {
+	Gif,
+	Apng,
+}
+
+impl AnimationFormat {
+	pub fn encode(self, frames: &[AnimationFrame], width: u32, height: u32, settings: &AnimationSettings) -> Result<Vec<u8>, EditorError> {
+		match self {
+			AnimationFormat::Gif => encode_gif(frames, width, height, settings),
+			AnimationFormat::Apng => encode_apng(frames, width, height, settings),
+		}
+	}
+
+	pub fn to_mime(self) -> &'static str {
+		match self {
+			AnimationFormat::Gif => "image/gif",
+			AnimationFormat::Apng => "image/apng",
+		}
+	}
+}
+
+/// The pixels of a frame, which is shown for a number of frames of the animation since consecutive frames that look the same are merged into one.
+#[derive(Clone, Copy, Debug)]
+pub struct AnimationFrame<'a> {
+	pub rgba: &'a [u8],
+	pub length: u32,
+}
+
+/// An animated export waiting for the frontend to rasterize its frames.
+#[derive(Clone, Debug)]
+pub struct PendingAnimationExport {
+	pub file_name: String,
+	pub format: AnimationFormat,
+	pub settings: AnimationSettings,
+	/// The number of frames of the animation each rasterized frame is shown for
+	pub lengths: Vec<u32>,
+	/// The color space the frames are rasterized in, which APNGs are tagged with
+	pub color_space: ColorSpace,
+}
+
+impl PendingAnimationExport {
+	/// Encodes the rasterized frames, which are sent back as the pixels of one frame after another.
+	pub fn finish(&self, pixels: &[u8], width: u32, height: u32) -> Result<Vec<u8>, EditorError> {
+		let frame_size = width as usize * height as usize * 4;
+		if frame_size == 0 || pixels.len() != frame_size * self.lengths.len() {
+			return Err(EditorError::Misc("The rasterized frames don't match the frames of the export".into()));
+		}
+
+		let frames: Vec<_> = pixels.chunks_exact(frame_size).zip(&self.lengths).map(|(rgba, &length)| AnimationFrame { rgba, length }).collect();
+		let animation = self.format.encode(&frames, width, height, &self.settings)?;
+		Ok(tag_image(animation, self.color_space))
+	}
+}
+
+/// The duration of each frame in the given units of time, rounded so the rounding errors don't add up over the course of the animation.
+fn frame_delays(frames: &[AnimationFrame], frame_rate: f64, units_per_second: f64) -> Vec<u32> {
+	let mut elapsed_frames = 0;
+	let mut elapsed_units = 0;
+	frames
+		.iter()
+		.map(|frame| {
+			elapsed_frames += frame.length;
+			let end = (elapsed_frames as f64 / frame_rate * units_per_second).round() as u32;
+			let delay = end - elapsed_units;
+			elapsed_units = end;
+			delay
+		})
+		.collect()
+}
+
+fn check_frames(frames: &[AnimationFrame], width: u32, height: u32, max: u32, format: &str) -> Result<(), EditorError> {
+	if frames.is_empty() {
+		return Err(EditorError::Misc(format!("A {format} needs at least one frame")));
+	}
+	frames.iter().try_for_each(|frame| check_dimensions(frame.rgba, width, height, max, format))
+}
+
+// GIF
+
+/// The thresholds of a 4×4 Bayer matrix, used by ordered dithering.
+const BAYER_MATRIX: [u8; 16] = [0, 8, 2, 10, 12, 4, 14, 6, 3, 11, 1, 9, 15, 7, 13, 5];
+
+fn gif_error(error: gif::EncodingError) -> EditorError {
+	EditorError::Misc(format!("Failed to encode the GIF: {error}"))
+}
+
+/// Encodes an animated GIF. Every frame shares a palette chosen from the colors of all of them, so colors don't flicker between frames,
+/// and pixels which are less than half opaque become transparent since the format has no partial transparency.
+pub fn encode_gif(frames: &[AnimationFrame], width: u32, height: u32, settings: &AnimationSettings) -> Result<Vec<u8>, EditorError> {
+	check_frames(frames, width, height, u16::MAX as u32, "GIF")?;
+
+	let transparent = frames.iter().any(|frame| frame.rgba.chunks_exact(4).any(|pixel| pixel[3] < 128));
+	let palette = median_cut(frames, settings.colors.clamp(2, 256) as usize - transparent as usize);
+	let transparent_index = palette.len() as u8;
+	// The transparent color takes the entry after the palette
+	let global_palette = palette.iter().flatten().copied().chain(transparent.then_some([0; 3]).into_iter().flatten()).collect::<Vec<_>>();
+
+	let mut gif = Vec::new();
+	{
+		let mut encoder = gif::Encoder::new(&mut gif, width as u16, height as u16, &global_palette).map_err(gif_error)?;
+
+		// Looping is given as the number of times to repeat after the first play
+		if settings.plays != 1 {
+			let repeat = match settings.plays {
+				0 => gif::Repeat::Infinite,
+				plays => gif::Repeat::Finite((plays - 1).min(u16::MAX as u32) as u16),
+			};
+			encoder.set_repeat(repeat).map_err(gif_error)?;
+		}
+
+		let mut lookup = PaletteLookup::new(&palette);
+		for (frame, delay) in frames.iter().zip(frame_delays(frames, settings.frame_rate, 100.)) {
+			let indices = palette_indices(frame.rgba, width as usize, &mut lookup, settings.dithering, transparent_index);
+			let frame = gif::Frame {
+				width: width as u16,
+				height: height as u16,
+				delay: delay.min(u16::MAX as u32) as u16,
+				// Frames with transparency restore the background when they're replaced, so the next frame doesn't show through them
+				dispose: if transparent { gif::DisposalMethod::Background } else { gif::DisposalMethod::Keep },
+				transparent: transparent.then_some(transparent_index),
+				buffer: indices.into(),
+				..Default::default()
+			};
+			encoder.write_frame(&frame).map_err(gif_error)?;
+		}
+		// The trailer is written once the encoder is dropped
+	}
+
+	Ok(gif)
+}
+
+/// Picks a palette for the opaque colors of every frame with the median cut algorithm, which repeatedly splits the group of colors spanning the widest range
+/// in two halves of equal population, and then averages the colors of each group.
+fn median_cut(frames: &[AnimationFrame], max_colors: usize) -> Vec<[u8; 3]> {
+	// Colors are counted at 5 bits per channel, while keeping the sum of the full colors to average them
+	let mut histogram: HashMap<u16, (u64, [u64; 3])> = HashMap::new();
+	for pixel in frames.iter().flat_map(|frame| frame.rgba.chunks_exact(4)).filter(|pixel| pixel[3] >= 128) {
+		let key = ((pixel[0] as u16 >> 3) << 10) | ((pixel[1] as u16 >> 3) << 5) | (pixel[2] as u16 >> 3);
+		let (count, sum) = histogram.entry(key).or_default();
+		*count += 1;
+		for channel in 0..3 {
+			sum[channel] += pixel[channel] as u64;
+		}
+	}
+	let average = |colors: &[([u8; 3], u64)]| {
+		let count = colors.iter().map(|&(_, count)| count).sum::<u64>().max(1);
+		[0, 1, 2].map(|channel| ((colors.iter().map(|&(color, count)| color[channel] as u64 * count).sum::<u64>() + count / 2) / count) as u8)
+	};
+	let colors: Vec<_> = histogram.into_values().map(|(count, sum)| (sum.map(|channel| ((channel + count / 2) / count) as u8), count)).collect();
+	if colors.is_empty() {
+		return vec![[0; 3]];
+	}
+
+	let mut groups = vec![colors];
+	while groups.len() < max_colors {
+		let widest = groups
+			.iter()
+			.enumerate()
+			.filter(|(_, group)| group.len() > 1)
+			.flat_map(|(index, group)| {
+				(0..3).map(move |channel| {
+					let (min, max) = group.iter().fold((u8::MAX, 0), |(min, max), (color, _)| (min.min(color[channel]), max.max(color[channel])));
+					(max - min, index, channel)
+				})
+			})
+			.max();
+		let (index, channel) = match widest {
+			Some((_, index, channel)) => (index, channel),
+			None => break,
+		};
+
+		let group = &mut groups[index];
+		group.sort_unstable_by_key(|(color, _)| color[channel]);
+		let half = group.iter().map(|&(_, count)| count).sum::<u64>() / 2;
+		let mut population = 0;
+		let split = group
+			.iter()
+			.position(|&(_, count)| {
+				population += count;
+				population > half
+			})
+			.unwrap_or_default()
+			.clamp(1, group.len() - 1);
+		let second_half = group.split_off(split);
+		groups.push(second_half);
+	}
+
+	groups.iter().map(|group| average(group)).collect()
+}
+
+/// Finds the closest palette color, remembering the answer for colors which differ only in the last two bits of each channel.
+struct PaletteLookup<'a> {
+	palette: &'a [[u8; 3]],
+	cache: Vec<u8>,
+	cached: Vec<bool>,
+}
+
+impl<'a> PaletteLookup<'a> {
+	fn new(palette: &'a [[u8; 3]]) -> Self {
+		Self {
+			palette,
+			cache: vec![0; 1 << 18],
+			cached: vec![false; 1 << 18],
+		}
+	}
+
+	fn closest(&mut self, color: [u8; 3]) -> u8 {
+		let key = ((color[0] as usize >> 2) << 12) | ((color[1] as usize >> 2) << 6) | (color[2] as usize >> 2);
+		if !self.cached[key] {
+			// Measure from the center of the colors sharing the key, so the answer doesn't depend on which of them came first
+			let center = color.map(|channel| ((channel & !3) | 2) as i32);
+			let distance = |palette_color: &[u8; 3]| (0..3).map(|channel| (palette_color[channel] as i32 - center[channel]).pow(2)).sum::<i32>();
+			self.cache[key] = (0..self.palette.len()).min_by_key(|&index| distance(&self.palette[index])).unwrap_or_default() as u8;
+			self.cached[key] = true;
+		}
+		self.cache[key]
+	}
+}
+
+/// Replaces the pixels of a frame with the index of their palette color, dithering them if asked to.
+fn palette_indices(rgba: &[u8], width: usize, lookup: &mut PaletteLookup, dithering: Dithering, transparent_index: u8) -> Vec<u8> {
+	// Ordered dithering offsets the colors by up to about the distance between neighboring palette colors
+	let spread = 255. / (lookup.palette.len() as f32).cbrt();
+	// The error diffused to the current and the next row, with a pixel of padding at either end
+	let mut errors = vec![[0_f32; 3]; width + 2];
+	let mut next_errors = vec![[0_f32; 3]; width + 2];
+
+	let mut indices = Vec::with_capacity(rgba.len() / 4);
+	for (y, row) in rgba.chunks_exact(width * 4).enumerate() {
+		for (x, pixel) in row.chunks_exact(4).enumerate() {
+			if pixel[3] < 128 {
+				indices.push(transparent_index);
+				continue;
+			}
+
+			let offset = match dithering {
+				Dithering::None => [0.; 3],
+				Dithering::Ordered => [(BAYER_MATRIX[(y % 4) * 4 + x % 4] as f32 + 0.5) / 16. - 0.5; 3].map(|threshold| threshold * spread),
+				Dithering::ErrorDiffusion => errors[x + 1],
+			};
+			let color = [0, 1, 2].map(|channel| (pixel[channel] as f32 + offset[channel]).clamp(0., 255.));
+			let index = lookup.closest(color.map(|channel| channel.round() as u8));
+			indices.push(index);
+
+			if dithering == Dithering::ErrorDiffusion {
+				let palette_color = lookup.palette[index as usize];
+				let error = [0, 1, 2].map(|channel| color[channel] - palette_color[channel] as f32);
+				for channel in 0..3 {
+					errors[x + 2][channel] += error[channel] * 7. / 16.;
+					next_errors[x][channel] += error[channel] * 3. / 16.;
+					next_errors[x + 1][channel] += error[channel] * 5. / 16.;
+					next_errors[x + 2][channel] += error[channel] / 16.;
+				}
+			}
+		}
+
+		std::mem::swap(&mut errors, &mut next_errors);
+		next_errors.fill([0.; 3]);
+	}
+	indices
+}
+
+// APNG
+
+fn png_error(error: png::EncodingError) -> EditorError {
+	EditorError::Misc(format!("Failed to encode the PNG: {error}"))
+}
+
+/// Starts a PNG of 8-bit RGBA pixels, filtering each row with the filter which leaves the smallest differences.
+fn png_encoder(png: &mut Vec<u8>, width: u32, height: u32) -> png::Encoder<&mut Vec<u8>> {
+	let mut encoder = png::Encoder::new(png, width, height);
+	encoder.set_color(png::ColorType::Rgba);
+	encoder.set_depth(png::BitDepth::Eight);
+	encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive);
+	encoder
+}
+
+/// Encodes an animated PNG, where every frame covers the whole image and replaces the one before it.
+/// Browsers and viewers which don't support APNG show the first frame as a still image.
+pub fn encode_apng(frames: &[AnimationFrame], width: u32, height: u32, settings: &AnimationSettings) -> Result<Vec<u8>, EditorError> {
+	check_frames(frames, width, height, u16::MAX as u32, "APNG")?;
+
+	let mut png = Vec::new();
+	let mut encoder = png_encoder(&mut png, width, height);
+	encoder.set_animated(frames.len() as u32, settings.plays).map_err(png_error)?;
+	let mut writer = encoder.write_header().map_err(png_error)?;
+	// Nothing is disposed of and each frame is drawn over the canvas without blending, since every frame covers all of it
+	for (frame, delay) in frames.iter().zip(frame_delays(frames, settings.frame_rate, 1000.)) {
+		writer.set_frame_delay(delay.min(u16::MAX as u32) as u16, 1000).map_err(png_error)?;
+		writer.write_image_data(frame.rgba).map_err(png_error)?;
+	}
+	writer.finish().map_err(png_error)?;
+
+	Ok(png)
+}
+
+/// Encodes a still PNG, which is an APNG without the animation chunks.
+pub fn encode_png(rgba: &[u8], width: u32, height: u32) -> Result<Vec<u8>, EditorError> {
+	check_dimensions(rgba, width, height, u16::MAX as u32, "PNG")?;
+
+	let mut png = Vec::new();
+	let mut writer = png_encoder(&mut png, width, height).write_header().map_err(png_error)?;
+	writer.write_image_data(rgba).map_err(png_error)?;
+	writer.finish().map_err(png_error)?;
+
+	Ok(png)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use image::AnimationDecoder;
+
+	const WIDTH: u32 = 24;
+	const HEIGHT: u32 = 16;
+
+	/// A frame with a moving bar of a few flat colors over a transparent background.
+	fn frame_pixels(offset: u32) -> Vec<u8> {
+		let colors = [[255, 0, 0, 255], [0, 128, 255, 255], [250, 250, 250, 255]];
+		(0..HEIGHT)
+			.flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+			.flat_map(|(x, y)| match (x + offset) % 12 < 6 {
+				true => colors[(y / 6) as usize],
+				false => [0, 0, 0, 0],
+			})
+			.collect()
+	}
+
+	fn settings(plays: u32) -> AnimationSettings {
+		AnimationSettings {
+			frame_rate: 10.,
+			plays,
+			..Default::default()
+		}
+	}
+
+	fn decoded_frames(frames: Vec<image::Frame>) -> Vec<(u32, Vec<u8>)> {
+		frames
+			.into_iter()
+			.map(|frame| {
+				let (numerator, denominator) = frame.delay().numer_denom_ms();
+				(numerator / denominator, frame.into_buffer().into_raw())
+			})
+			.collect()
+	}
+
+	#[test]
+	fn frame_delays_do_not_drift() {
+		let rgba = [0; 4];
+		let frames = [1, 1, 1, 2].map(|length| AnimationFrame { rgba: &rgba, length });
+		assert_eq!(frame_delays(&frames, 30., 100.), [3, 4, 3, 7]);
+		assert_eq!(frame_delays(&frames, 30., 100.).iter().sum::<u32>(), 17);
+	}
+
+	#[test]
+	fn gif_round_trip() {
+		let pixels = [frame_pixels(0), frame_pixels(3)];
+		let frames = [AnimationFrame { rgba: &pixels[0], length: 1 }, AnimationFrame { rgba: &pixels[1], length: 2 }];
+		let gif = encode_gif(&frames, WIDTH, HEIGHT, &settings(0)).unwrap();
+		assert!(gif.starts_with(b"GIF89a") && gif.ends_with(&[0x3b]));
+		assert!(gif.windows(11).any(|window| window == b"NETSCAPE2.0"));
+
+		let decoder = image::codecs::gif::GifDecoder::new(gif.as_slice()).unwrap();
+		let decoded = decoded_frames(decoder.into_frames().collect_frames().unwrap());
+		// The palette has room for every color, so the frames come back exactly
+		assert_eq!(decoded, [(100, pixels[0].clone()), (200, pixels[1].clone())]);
+	}
+
+	#[test]
+	fn gif_plays_once_without_looping() {
+		let pixels = frame_pixels(0);
+		let gif = encode_gif(&[AnimationFrame { rgba: &pixels, length: 1 }], WIDTH, HEIGHT, &settings(1)).unwrap();
+		assert!(!gif.windows(11).any(|window| window == b"NETSCAPE2.0"));
+	}
+
+	#[test]
+	fn gif_with_a_reduced_palette() {
+		let pixels = (0..WIDTH * HEIGHT).flat_map(|index| [(index % 256) as u8, (index * 7 % 256) as u8, 100, 255]).collect::<Vec<_>>();
+		for dithering in [Dithering::None, Dithering::Ordered, Dithering::ErrorDiffusion] {
+			let settings = AnimationSettings { colors: 4, dithering, ..settings(0) };
+			let gif = encode_gif(&[AnimationFrame { rgba: &pixels, length: 1 }], WIDTH, HEIGHT, &settings).unwrap();
+
+			let decoder = image::codecs::gif::GifDecoder::new(gif.as_slice()).unwrap();
+			let (_, decoded) = decoded_frames(decoder.into_frames().collect_frames().unwrap()).remove(0);
+			let mut colors = decoded.chunks_exact(4).collect::<Vec<_>>();
+			colors.sort_unstable();
+			colors.dedup();
+			assert!(colors.len() <= 4, "{} colors with {dithering:?} dithering", colors.len());
+		}
+	}
+
+	#[test]
+	fn gif_with_many_distinct_runs() {
+		// Enough distinct runs of 200 colors, which are far enough apart to each get their own palette color, to fill the 4096 codes of the LZW table several times
+		let pixels = (0..512 * 64_u32)
+			.flat_map(|index| {
+				let color = (index.wrapping_mul(2654435761) >> 16) % 200;
+				[(color % 8 * 32) as u8, (color / 8 % 8 * 32) as u8, (color / 64 * 32) as u8, 255]
+			})
+			.collect::<Vec<_>>();
+		let settings = AnimationSettings {
+			dithering: Dithering::None,
+			..settings(0)
+		};
+		let gif = encode_gif(&[AnimationFrame { rgba: &pixels, length: 1 }], 512, 64, &settings).unwrap();
+
+		let decoder = image::codecs::gif::GifDecoder::new(gif.as_slice()).unwrap();
+		let (_, decoded) = decoded_frames(decoder.into_frames().collect_frames().unwrap()).remove(0);
+		assert!(decoded == pixels);
+	}
+
+	#[test]
+	fn apng_round_trip() {
+		let pixels = [frame_pixels(0), frame_pixels(5), frame_pixels(9)];
+		let frames = pixels.iter().map(|rgba| AnimationFrame { rgba, length: 1 }).collect::<Vec<_>>();
+		let apng = encode_apng(&frames, WIDTH, HEIGHT, &settings(0)).unwrap();
+
+		let decoder = image::codecs::png::PngDecoder::new(apng.as_slice()).unwrap();
+		let decoded = decoded_frames(decoder.apng().into_frames().collect_frames().unwrap());
+		assert_eq!(decoded, pixels.iter().map(|pixels| (100, pixels.clone())).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn png_round_trip() {
+		// Noise compresses poorly, while the flat areas are made of long copies
+		let mut pixels = frame_pixels(0);
+		pixels.extend((0..WIDTH * HEIGHT * 4).map(|index| (index.wrapping_mul(2654435761) >> 13) as u8));
+		let png = encode_png(&pixels, WIDTH, HEIGHT * 2).unwrap();
+
+		let decoded = image::load_from_memory_with_format(&png, image::ImageFormat::Png).unwrap().to_rgba8();
+		assert_eq!(decoded.dimensions(), (WIDTH, HEIGHT * 2));
+		assert_eq!(decoded.into_raw(), pixels);
+	}
+
+	#[test]
+	fn reject_missing_or_mismatched_frames() {
+		assert!(encode_gif(&[], WIDTH, HEIGHT, &settings(0)).is_err());
+		assert!(encode_apng(&[], WIDTH, HEIGHT, &settings(0)).is_err());
+		let pixels = frame_pixels(0);
+		assert!(encode_apng(&[AnimationFrame { rgba: &pixels, length: 1 }], WIDTH + 1, HEIGHT, &settings(0)).is_err());
+	}
+}
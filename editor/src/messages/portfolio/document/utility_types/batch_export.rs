@@ -0,0 +1,250 @@
+//! Export slices, which mark regions of a document to be exported by themselves, and the batch export of every artboard and slice into a single zip archive.
+
+use super::color_profile::tag_image;
+use super::error::EditorError;
+use super::image_encoding::RasterEncoding;
+use crate::messages::frontend::utility_types::FileType;
+
+use graphene::color::ColorSpace;
+use graphene::LayerId;
+
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Write};
+use zip::result::ZipError;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// How an artboard or export slice is written by a batch export, as a file for each of its presets.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportItemSettings {
+	/// Whether the item is included in batch exports
+	pub enabled: bool,
+	pub presets: Vec<ExportPreset>,
+}
+
+impl Default for ExportItemSettings {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			presets: vec![ExportPreset::default()],
+		}
+	}
+}
+
+/// One of the files an artboard or export slice is exported as, like a PNG at twice the resolution for high density displays.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportPreset {
+	pub file_type: FileType,
+	pub scale_factor: f64,
+	/// The path of the file within the exported archive, where `{name}` is replaced by the name of the item, `{scale}` by the scale factor, and `{ext}` by the extension of the format
+	pub file_name_pattern: String,
+}
+
+impl Default for ExportPreset {
+	fn default() -> Self {
+		Self::new(FileType::Png, 1.)
+	}
+}
+
+impl ExportPreset {
+	pub const DEFAULT_FILE_NAME_PATTERN: &'static str = "{name}@{scale}x.{ext}";
+
+	pub fn new(file_type: FileType, scale_factor: f64) -> Self {
+		Self {
+			file_type,
+			scale_factor,
+			file_name_pattern: Self::DEFAULT_FILE_NAME_PATTERN.to_string(),
+		}
+	}
+
+	/// The presets commonly used for icons and interface assets, offered when adding a preset.
+	pub fn common() -> [(&'static str, Self); 4] {
+		[
+			("@1x PNG", Self::new(FileType::Png, 1.)),
+			("@2x PNG", Self::new(FileType::Png, 2.)),
+			("@3x PNG", Self::new(FileType::Png, 3.)),
+			(
+				"SVG",
+				Self {
+					file_name_pattern: "{name}.{ext}".to_string(),
+					..Self::new(FileType::Svg, 1.)
+				},
+			),
+		]
+	}
+
+	pub fn extension(&self) -> &'static str {
+		self.file_type.extension()
+	}
+
+	/// Fills in the file name pattern for an item. Characters which aren't allowed in file names are replaced in the item's name,
+	/// while slashes in the pattern itself sort the files into folders.
+	pub fn file_name(&self, name: &str) -> String {
+		let name: String = name.chars().map(|character| if "/\\:*?\"<>|".contains(character) { '-' } else { character }).collect();
+		let path = self
+			.file_name_pattern
+			.replace("{name}", &name)
+			.replace("{scale}", &self.scale_factor.to_string())
+			.replace("{ext}", self.extension())
+			.replace('\\', "/");
+		let path = path.split('/').filter(|folder| !folder.is_empty() && *folder != "." && *folder != "..").collect::<Vec<_>>().join("/");
+		match path.is_empty() {
+			true => format!("{name}.{}", self.extension()),
+			false => path,
+		}
+	}
+}
+
+/// The region of the document covered by an export slice.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ExportSliceArea {
+	/// A fixed rectangle in document space
+	Rect([DVec2; 2]),
+	/// The bounds of a layer, which follow it as it's edited
+	Layer(Vec<LayerId>),
+}
+
+/// A named region of the document which is exported by itself, alongside the artboards, in batch exports.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportSlice {
+	pub name: String,
+	pub area: ExportSliceArea,
+	pub settings: ExportItemSettings,
+}
+
+/// A raster file of a batch export, which the frontend rasterizes and either encodes as a PNG itself or sends back as pixels to be encoded by the editor.
+#[derive(Clone, Debug)]
+struct PendingBatchRaster {
+	/// The index of the file the raster fills in
+	file: usize,
+	encoding: Option<RasterEncoding>,
+	width: u32,
+	height: u32,
+}
+
+/// A batch export being collected into a zip archive, which may be waiting on the frontend to rasterize some of its files.
+#[derive(Clone, Debug, Default)]
+pub struct PendingBatchExport {
+	pub zip_name: String,
+	files: Vec<(String, Vec<u8>)>,
+	rasters: Vec<PendingBatchRaster>,
+}
+
+impl PendingBatchExport {
+	pub fn new(zip_name: String) -> Self {
+		Self { zip_name, ..Default::default() }
+	}
+
+	/// Adds a file, numbering its name if another file in the archive already has it.
+	pub fn add_file(&mut self, path: &str, data: Vec<u8>) -> usize {
+		let (stem, extension) = match path.rfind('.') {
+			Some(dot) if dot > path.rfind('/').map_or(0, |slash| slash + 1) => path.split_at(dot),
+			_ => (path, ""),
+		};
+		let mut file_name = path.to_string();
+		let mut copy = 1;
+		while self.files.iter().any(|(existing, _)| existing.eq_ignore_ascii_case(&file_name)) {
+			copy += 1;
+			file_name = format!("{stem} {copy}{extension}");
+		}
+		self.files.push((file_name, data));
+		self.files.len() - 1
+	}
+
+	/// Adds a file whose contents arrive once the frontend has rasterized it, as a PNG if there's no encoding or otherwise as pixels for the editor to encode.
+	pub fn add_raster(&mut self, path: &str, encoding: Option<RasterEncoding>, width: u32, height: u32) {
+		let file = self.add_file(path, Vec::new());
+		self.rasters.push(PendingBatchRaster { file, encoding, width, height });
+	}
+
+	pub fn has_rasters(&self) -> bool {
+		!self.rasters.is_empty()
+	}
+
+	/// Fills in the rasters with the data sent back by the frontend, one raster after another with the given lengths, and writes the zip archive.
+	/// The rasters are tagged with the color space they were rasterized in.
+	pub fn finish(mut self, data: &[u8], lengths: &[u32], color_space: ColorSpace) -> Result<Vec<u8>, EditorError> {
+		if lengths.len() != self.rasters.len() || lengths.iter().map(|&length| length as usize).sum::<usize>() != data.len() {
+			return Err(EditorError::Misc("The rasterized files don't match the files of the export".into()));
+		}
+
+		let mut offset = 0;
+		for (raster, &length) in self.rasters.iter().zip(lengths) {
+			let raster_data = &data[offset..offset + length as usize];
+			offset += length as usize;
+			self.files[raster.file].1 = match raster.encoding {
+				Some(encoding) => encoding.encode(raster_data, raster.width, raster.height, color_space)?,
+				None => tag_image(raster_data.to_vec(), color_space),
+			};
+		}
+
+		write_zip(&self.files)
+	}
+}
+
+/// Writes a zip archive of the files without compressing them, since the formats being exported are already compressed (or compress poorly with deflate).
+/// The files are dated at the earliest time zip archives can represent, 1980-01-01 00:00, so the same document always exports the same archive.
+pub fn write_zip(files: &[(String, Vec<u8>)]) -> Result<Vec<u8>, EditorError> {
+	let zip_error = |error: ZipError| EditorError::Misc(format!("Failed to write the zip archive: {error}"));
+	let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+
+	let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+	for (name, data) in files {
+		archive.start_file(name.as_str(), options).map_err(zip_error)?;
+		archive.write_all(data).map_err(|error| zip_error(error.into()))?;
+	}
+	Ok(archive.finish().map_err(zip_error)?.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// Reads the files of a zip archive back, checking they were stored without compression.
+	fn read_zip(archive: Vec<u8>) -> Vec<(String, Vec<u8>)> {
+		let mut archive = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+		(0..archive.len())
+			.map(|index| {
+				let mut file = archive.by_index(index).unwrap();
+				assert_eq!(file.compression(), CompressionMethod::Stored, "files are stored without compression");
+				let mut data = Vec::new();
+				std::io::Read::read_to_end(&mut file, &mut data).unwrap();
+				(file.name().to_string(), data)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn zip_can_be_read_back() {
+		let files = vec![
+			("artwork.svg".to_string(), b"<svg></svg>".to_vec()),
+			("folder/empty.png".to_string(), Vec::new()),
+			("Ünïcödé.jpg".to_string(), (0..=255).collect()),
+		];
+		assert_eq!(read_zip(write_zip(&files).unwrap()), files);
+		assert!(read_zip(write_zip(&[]).unwrap()).is_empty());
+	}
+
+	#[test]
+	fn numbered_file_names() {
+		let mut export = PendingBatchExport::new("export.zip".into());
+		export.add_file("art.png", vec![1]);
+		export.add_file("ART.png", vec![2]);
+		export.add_file("art.png", vec![3]);
+		export.add_file("folder.v2/art", vec![4]);
+		export.add_file("folder.v2/art", vec![5]);
+
+		let names = read_zip(export.finish(&[], &[], ColorSpace::Srgb).unwrap()).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+		assert_eq!(names, ["art.png", "ART 2.png", "art 3.png", "folder.v2/art", "folder.v2/art 2"]);
+	}
+
+	#[test]
+	fn finish_with_mismatched_rasters() {
+		let mut export = PendingBatchExport::new("export.zip".into());
+		export.add_raster("art.png", None, 1, 1);
+		assert!(export.clone().finish(&[1, 2, 3], &[], ColorSpace::Srgb).is_err());
+		assert!(export.clone().finish(&[1, 2, 3], &[2], ColorSpace::Srgb).is_err());
+		assert!(export.finish(&[1, 2, 3], &[3], ColorSpace::Srgb).is_ok());
+	}
+}
@@ -16,6 +16,15 @@ pub enum Clipboard {
 
 pub const INTERNAL_CLIPBOARD_COUNT: u8 = Clipboard::_InternalClipboardCount as u8;
 
+/// The attribute of the `<metadata>` element in SVGs copied to the device clipboard which holds the serialized layers, so pasting the SVG back into Graphite recreates them exactly.
+pub const SVG_LAYER_DATA_ATTRIBUTE: &str = "data-graphite-layers";
+
+/// The `<metadata>` element embedding the serialized layers in a copied SVG.
+pub fn svg_layer_data_metadata(data: &str) -> String {
+	let escaped = data.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+	format!(r#"<metadata {SVG_LAYER_DATA_ATTRIBUTE}="{escaped}" />"#)
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CopyBufferEntry {
 	pub layer: Layer,
@@ -0,0 +1,48 @@
+use crate::messages::prelude::*;
+
+use graphene::operation_log::{LoggedOperation, OperationLog, SessionMessage};
+
+/// A document being edited together with the other editors in a collaboration session.
+#[derive(Debug, Clone)]
+pub struct Collaboration {
+	/// The generated ID that the other editors join the session with
+	pub session: String,
+	pub log: OperationLog,
+	/// The operations received while joining the session, which are merged once the state of the session arrives, or `None` once it has
+	pub pending: Option<Vec<LoggedOperation>>,
+}
+
+impl Collaboration {
+	/// Starts a new session with this editor as its only member.
+	pub fn host(session: String, replica: u64) -> Self {
+		Self {
+			session,
+			log: OperationLog::new(replica),
+			pending: None,
+		}
+	}
+
+	/// Joins an existing session, waiting for the state sent by one of its members.
+	pub fn join(session: String, replica: u64) -> Self {
+		Self {
+			session,
+			log: OperationLog::new(replica),
+			pending: Some(Vec::new()),
+		}
+	}
+
+	/// Sends the message to the other editors in the session.
+	pub fn send(&self, message: SessionMessage) -> Message {
+		FrontendMessage::TriggerCollaborationSend {
+			session: self.session.clone(),
+			message,
+		}
+		.into()
+	}
+
+	/// Leaves the session, after which the other editors stop waiting for this one before compacting their logs.
+	pub fn leave(&self, responses: &mut VecDeque<Message>) {
+		responses.push_back(self.send(SessionMessage::Leave { replica: self.log.replica() }));
+		responses.push_back(FrontendMessage::TriggerCollaborationLeave { session: self.session.clone() }.into());
+	}
+}
@@ -0,0 +1,325 @@
+//! Describes the color space of exported files, so apps and viewers show wide-gamut colors as they look in the editor instead of assuming sRGB.
+//!
+//! Raster images are tagged with ICC profiles (or with the `sRGB` chunk of PNGs, which needs no profile), and SVGs declare the profile of their
+//! wide-gamut colors with a `color-profile` element. The profiles are built from each color space's primaries and transfer function.
+
+use graphene::color::ColorSpace;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::Write;
+
+pub const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// The PNG chunks which describe the color space of the image, which are replaced when it's tagged.
+const PNG_COLOR_CHUNKS: [&[u8; 4]; 5] = [b"sRGB", b"iCCP", b"gAMA", b"cHRM", b"cICP"];
+
+/// The name of the JPEG marker segments holding an ICC profile, after which the profile may be split over several segments.
+const JPEG_ICC_SIGNATURE: &[u8] = b"ICC_PROFILE\0";
+
+/// The white point of the profile connection space, D50, in XYZ.
+const PCS_WHITE: [f64; 3] = [0.9642, 1., 0.8249];
+/// The Bradford chromatic adaptation from the D65 white point of the supported color spaces to the D50 white point of the profile connection space.
+const D65_TO_D50: [[f64; 3]; 3] = [[1.047886, 0.0229188, -0.0502161], [0.0295818, 0.9904835, -0.0170787], [-0.0092519, 0.0150726, 0.7516781]];
+
+/// The XYZ coordinates of the red, green, and blue primaries, adapted to the D50 white point.
+fn colorants(color_space: ColorSpace) -> [[f64; 3]; 3] {
+	match color_space {
+		ColorSpace::Srgb | ColorSpace::LinearSrgb => [[0.436041, 0.222485, 0.01392], [0.385113, 0.716905, 0.097067], [0.143046, 0.06061, 0.713913]],
+		ColorSpace::DisplayP3 => [[0.515119, 0.241189, -0.00105], [0.291978, 0.692244, 0.041879], [0.157103, 0.066567, 0.784071]],
+		ColorSpace::Rec2020 => [[0.67348, 0.279043, -0.001933], [0.165671, 0.675344, 0.029983], [0.125049, 0.045613, 0.796851]],
+	}
+}
+
+/// The transfer function as the parameters of an ICC parametric curve, which is `(a × X + b)^g` above `d` and `c × X` below it.
+fn transfer_function(color_space: ColorSpace) -> (u16, Vec<f64>) {
+	match color_space {
+		ColorSpace::Srgb | ColorSpace::DisplayP3 => (3, vec![2.4, 1. / 1.055, 0.055 / 1.055, 1. / 12.92, 0.04045]),
+		ColorSpace::Rec2020 => {
+			const ALPHA: f64 = 1.09929682680944;
+			const BETA: f64 = 0.018053968510807;
+			(3, vec![1. / 0.45, 1. / ALPHA, (ALPHA - 1.) / ALPHA, 1. / 4.5, BETA * 4.5])
+		}
+		ColorSpace::LinearSrgb => (0, vec![1.]),
+	}
+}
+
+fn s15_fixed16(data: &mut Vec<u8>, value: f64) {
+	data.extend(((value * 65536.).round() as i32).to_be_bytes());
+}
+
+fn xyz_tag(xyz: [f64; 3]) -> Vec<u8> {
+	let mut tag = b"XYZ \0\0\0\0".to_vec();
+	for value in xyz {
+		s15_fixed16(&mut tag, value);
+	}
+	tag
+}
+
+/// A text tag of an ICC version 4 profile, with a single English string.
+fn text_tag(text: &str) -> Vec<u8> {
+	let text = text.encode_utf16().flat_map(u16::to_be_bytes).collect::<Vec<_>>();
+	let mut tag = b"mluc\0\0\0\0".to_vec();
+	for value in [1, 12] {
+		tag.extend(u32::to_be_bytes(value));
+	}
+	tag.extend(b"enUS");
+	tag.extend((text.len() as u32).to_be_bytes());
+	tag.extend(28_u32.to_be_bytes());
+	tag.extend(text);
+	tag
+}
+
+/// Builds an ICC version 4 display profile of the color space, as a matrix of its primaries with a parametric curve for its transfer function.
+pub fn icc_profile(color_space: ColorSpace) -> Vec<u8> {
+	let [red, green, blue] = colorants(color_space);
+
+	let mut chromatic_adaptation = b"sf32\0\0\0\0".to_vec();
+	for value in D65_TO_D50.iter().flatten() {
+		s15_fixed16(&mut chromatic_adaptation, *value);
+	}
+
+	let (function_type, parameters) = transfer_function(color_space);
+	let mut curve = b"para\0\0\0\0".to_vec();
+	curve.extend(function_type.to_be_bytes());
+	curve.extend([0, 0]);
+	for parameter in parameters {
+		s15_fixed16(&mut curve, parameter);
+	}
+
+	// The three channels share a single curve, which the tag table points to three times
+	let tags: [(&[u8; 4], usize); 10] = [
+		(b"desc", 0),
+		(b"cprt", 1),
+		(b"wtpt", 2),
+		(b"chad", 3),
+		(b"rXYZ", 4),
+		(b"gXYZ", 5),
+		(b"bXYZ", 6),
+		(b"rTRC", 7),
+		(b"gTRC", 7),
+		(b"bTRC", 7),
+	];
+	let tag_data = [
+		text_tag(&color_space.to_string()),
+		text_tag("No copyright, use freely"),
+		xyz_tag(PCS_WHITE),
+		chromatic_adaptation,
+		xyz_tag(red),
+		xyz_tag(green),
+		xyz_tag(blue),
+		curve,
+	];
+
+	// Lay out the tag data after the header and the tag table, with each tag starting on a multiple of four bytes
+	let mut offset = 128 + 4 + tags.len() * 12;
+	let mut offsets = Vec::new();
+	let mut data = Vec::new();
+	for tag in &tag_data {
+		offsets.push(offset);
+		data.extend(tag);
+		offset += tag.len();
+		while offset % 4 != 0 {
+			data.push(0);
+			offset += 1;
+		}
+	}
+
+	let mut profile = Vec::with_capacity(offset);
+	profile.extend((offset as u32).to_be_bytes());
+	profile.extend([0; 4]);
+	// Version 4.3 of a display (monitor) profile of RGB colors, connecting through XYZ
+	profile.extend([4, 0x30, 0, 0]);
+	profile.extend(b"mntrRGB XYZ ");
+	// The creation date, 2022-01-01 00:00:00, is fixed so the same document always exports the same file
+	for value in [2022_u16, 1, 1, 0, 0, 0] {
+		profile.extend(value.to_be_bytes());
+	}
+	profile.extend(b"acsp");
+	profile.extend([0; 24]);
+	// The perceptual rendering intent
+	profile.extend([0; 4]);
+	for value in PCS_WHITE {
+		s15_fixed16(&mut profile, value);
+	}
+	profile.extend([0; 4 + 16 + 28]);
+
+	profile.extend((tags.len() as u32).to_be_bytes());
+	for (signature, index) in tags {
+		profile.extend(signature);
+		profile.extend((offsets[index] as u32).to_be_bytes());
+		profile.extend((tag_data[index].len() as u32).to_be_bytes());
+	}
+	profile.extend(data);
+	profile
+}
+
+/// Appends a chunk to a PNG, followed by the CRC-32 checksum of its type and data.
+pub fn write_png_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+	png.extend((data.len() as u32).to_be_bytes());
+	let start = png.len();
+	png.extend(chunk_type);
+	png.extend(data);
+	let crc = crc32fast::hash(&png[start..]);
+	png.extend(crc.to_be_bytes());
+}
+
+/// Tags an encoded PNG, JPEG, or WebP image as having its pixels in the given color space, replacing any color space it was tagged with.
+/// Since apps and viewers assume untagged JPEG and WebP images are sRGB, only PNGs are tagged as sRGB.
+pub fn tag_image(image: Vec<u8>, color_space: ColorSpace) -> Vec<u8> {
+	if image.starts_with(PNG_SIGNATURE) {
+		tag_png(&image, color_space)
+	} else if color_space == ColorSpace::Srgb {
+		image
+	} else if image.starts_with(&[0xFF, 0xD8]) {
+		tag_jpeg(&image, color_space)
+	} else if image.starts_with(b"RIFF") && image.get(8..12) == Some(b"WEBP".as_slice()) {
+		tag_webp(&image, color_space)
+	} else {
+		image
+	}
+}
+
+/// Splits the chunks of a PNG after its signature into their types and data.
+pub fn png_chunks(png: &[u8]) -> Vec<([u8; 4], &[u8])> {
+	let mut chunks = Vec::new();
+	let mut position = PNG_SIGNATURE.len();
+	while let Some(length) = png.get(position..position + 4) {
+		let length = u32::from_be_bytes([length[0], length[1], length[2], length[3]]) as usize;
+		let (chunk_type, data) = match (png.get(position + 4..position + 8), png.get(position + 8..position + 8 + length)) {
+			(Some(chunk_type), Some(data)) => ([chunk_type[0], chunk_type[1], chunk_type[2], chunk_type[3]], data),
+			_ => break,
+		};
+		chunks.push((chunk_type, data));
+		position += 12 + length;
+	}
+	chunks
+}
+
+fn tag_png(png: &[u8], color_space: ColorSpace) -> Vec<u8> {
+	let mut tagged = PNG_SIGNATURE.to_vec();
+	for (chunk_type, data) in png_chunks(png) {
+		if PNG_COLOR_CHUNKS.contains(&&chunk_type) {
+			continue;
+		}
+		write_png_chunk(&mut tagged, &chunk_type, data);
+
+		// The color space must be given before the image data, so it follows the header
+		if &chunk_type == b"IHDR" {
+			if color_space == ColorSpace::Srgb {
+				// The perceptual rendering intent
+				write_png_chunk(&mut tagged, b"sRGB", &[0]);
+			} else {
+				// The profile's name, followed by the compression method (zlib) and the compressed profile
+				let mut data = color_space.to_string().into_bytes();
+				data.extend([0, 0]);
+				let mut encoder = ZlibEncoder::new(data, Compression::best());
+				encoder.write_all(&icc_profile(color_space)).expect("Failed to compress the ICC profile");
+				write_png_chunk(&mut tagged, b"iCCP", &encoder.finish().expect("Failed to compress the ICC profile"));
+			}
+		}
+	}
+	tagged
+}
+
+fn tag_jpeg(jpeg: &[u8], color_space: ColorSpace) -> Vec<u8> {
+	let profile = icc_profile(color_space);
+
+	// The profile is stored in an APP2 segment, which is numbered as the first of one since the profile is small enough to fit
+	let mut segment = vec![0xFF, 0xE2];
+	segment.extend(((2 + JPEG_ICC_SIGNATURE.len() + 2 + profile.len()) as u16).to_be_bytes());
+	segment.extend(JPEG_ICC_SIGNATURE);
+	segment.extend([1, 1]);
+	segment.extend(profile);
+
+	// The segment goes after the start of image marker and the JFIF header, if there is one
+	let mut position = 2;
+	if jpeg.get(2..4) == Some([0xFF, 0xE0].as_slice()) {
+		if let Some(length) = jpeg.get(4..6) {
+			position += 2 + u16::from_be_bytes([length[0], length[1]]) as usize;
+		}
+	}
+	let position = position.min(jpeg.len());
+
+	[&jpeg[..position], segment.as_slice(), &jpeg[position..]].concat()
+}
+
+fn tag_webp(webp: &[u8], color_space: ColorSpace) -> Vec<u8> {
+	// Split the chunks after the RIFF header into their FourCC and data, which is padded to an even length
+	let mut chunks = Vec::new();
+	let mut position = 12;
+	while let (Some(fourcc), Some(length)) = (webp.get(position..position + 4), webp.get(position + 4..position + 8)) {
+		let length = u32::from_le_bytes([length[0], length[1], length[2], length[3]]) as usize;
+		let data = match webp.get(position + 8..position + 8 + length) {
+			Some(data) => data,
+			None => break,
+		};
+		chunks.push((fourcc, data));
+		position += 8 + length + length % 2;
+	}
+
+	// The extended format header, flagging that the image has a profile and whether it has transparency, is needed to hold a profile
+	let extended_header = |width: u32, height: u32, has_alpha: bool| {
+		let mut header = vec![0x20 | if has_alpha { 0x10 } else { 0 }, 0, 0, 0];
+		header.extend(&(width - 1).to_le_bytes()[..3]);
+		header.extend(&(height - 1).to_le_bytes()[..3]);
+		header
+	};
+	let (extended, image_chunks) = match chunks.first() {
+		// Extended WebPs, like lossy ones with transparency, already have the header, which only needs the profile flag
+		Some((b"VP8X", data)) if data.len() >= 10 => ([&[data[0] | 0x20][..], &data[1..]].concat(), &chunks[1..]),
+		// Simple lossless WebPs store the size and transparency in the header of the image
+		Some((b"VP8L", data)) if data.len() >= 5 && data[0] == 0x2F => {
+			let bits = u32::from_le_bytes([data[1], data[2], data[3], data[4]]);
+			(extended_header((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1, (bits >> 28) & 1 == 1), &chunks[..])
+		}
+		// Simple lossy WebPs have no transparency, and store the size in the header of their key frame
+		Some((b"VP8 ", data)) if data.len() >= 10 && data[3..6] == [0x9D, 0x01, 0x2A] => {
+			let dimension = |offset: usize| (u16::from_le_bytes([data[offset], data[offset + 1]]) & 0x3FFF) as u32;
+			(extended_header(dimension(6), dimension(8), false), &chunks[..])
+		}
+		_ => return webp.to_vec(),
+	};
+
+	let mut tagged_chunks = Vec::new();
+	let mut write_chunk = |fourcc: &[u8], data: &[u8]| {
+		tagged_chunks.extend(fourcc);
+		tagged_chunks.extend((data.len() as u32).to_le_bytes());
+		tagged_chunks.extend(data);
+		if data.len() % 2 == 1 {
+			tagged_chunks.push(0);
+		}
+	};
+
+	// The profile must directly follow the extended format header
+	write_chunk(b"VP8X", &extended);
+	write_chunk(b"ICCP", &icc_profile(color_space));
+	for (fourcc, data) in image_chunks.iter().filter(|(fourcc, _)| *fourcc != b"ICCP") {
+		write_chunk(fourcc, data);
+	}
+
+	let mut tagged = b"RIFF".to_vec();
+	tagged.extend((tagged_chunks.len() as u32 + 4).to_le_bytes());
+	tagged.extend(b"WEBP");
+	tagged.extend(tagged_chunks);
+	tagged
+}
+
+/// Declares the profile of the wide-gamut colors of an SVG, by adding a `color-profile` element with the embedded ICC profile after its opening tag.
+/// SVGs are sRGB unless stated otherwise, so nothing is added for sRGB.
+pub fn declare_svg_color_profile(svg: String, color_space: ColorSpace) -> String {
+	let position = match svg.find('>') {
+		Some(position) if color_space != ColorSpace::Srgb => position + 1,
+		_ => return svg,
+	};
+
+	let profile = base64::encode(icc_profile(color_space));
+	let element = format!(
+		r#"<defs><color-profile xmlns:xlink="http://www.w3.org/1999/xlink" name="{}" rendering-intent="perceptual" xlink:href="data:application/vnd.iccprofile;base64,{}" /></defs>"#,
+		color_space.css_name(),
+		profile
+	);
+
+	let mut svg = svg;
+	svg.insert_str(position, &element);
+	svg
+}
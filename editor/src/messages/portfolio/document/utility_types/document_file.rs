@@ -0,0 +1,50 @@
+//! Documents are saved either as JSON, which is readable and diffs well, or in a compressed binary format which is many times smaller and faster to save and load.
+//!
+//! A compressed document is the MessagePack encoding of the document, compressed with Zstandard, after a signature which tells it apart from JSON when it's opened.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::Read;
+
+/// Begins every compressed document, followed by the version of its binary encoding. JSON documents begin with `{`, so they can't be mistaken for one.
+const COMPRESSED_DOCUMENT_SIGNATURE: &[u8] = b"GRAPHITE";
+const COMPRESSED_DOCUMENT_ENCODING_VERSION: u8 = 1;
+/// Decompression stops past this many bytes, so a small hostile file can't expand to exhaust memory.
+const MAX_DECOMPRESSED_DOCUMENT_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Encodes a document in the compressed binary format.
+pub fn encode_compressed_document<T: Serialize>(document: &T) -> Vec<u8> {
+	// Fields are encoded with their names, like JSON, so documents can be read by versions of Graphite whose fields are in a different order.
+	// We fully expect the serialization to succeed.
+	let encoded = rmp_serde::to_vec_named(document).unwrap();
+
+	let mut file = COMPRESSED_DOCUMENT_SIGNATURE.to_vec();
+	file.push(COMPRESSED_DOCUMENT_ENCODING_VERSION);
+	file.extend(ruzstd::encoding::compress_to_vec(encoded.as_slice(), ruzstd::encoding::CompressionLevel::Fastest));
+	file
+}
+
+/// Decodes a document saved in either format, telling which by the signature of compressed documents.
+pub fn decode_document<T: DeserializeOwned>(file: &[u8]) -> Result<T, String> {
+	match file.strip_prefix(COMPRESSED_DOCUMENT_SIGNATURE) {
+		Some([version, compressed @ ..]) => {
+			if *version != COMPRESSED_DOCUMENT_ENCODING_VERSION {
+				return Err("The document was compressed by a newer version of Graphite".to_string());
+			}
+
+			let decoder = ruzstd::decoding::StreamingDecoder::new(compressed).map_err(|error| error.to_string())?;
+			let mut encoded = Vec::new();
+			decoder.take(MAX_DECOMPRESSED_DOCUMENT_SIZE + 1).read_to_end(&mut encoded).map_err(|error| error.to_string())?;
+			if encoded.len() as u64 > MAX_DECOMPRESSED_DOCUMENT_SIZE {
+				return Err("The compressed document is too large to open".to_string());
+			}
+
+			rmp_serde::from_slice(&encoded).map_err(|error| error.to_string())
+		}
+		Some([]) => Err("The compressed document is empty".to_string()),
+		None => {
+			let json = std::str::from_utf8(file).map_err(|_| "The document is neither JSON nor a compressed document".to_string())?;
+			serde_json::from_str(json).map_err(|error| error.to_string())
+		}
+	}
+}
@@ -0,0 +1,138 @@
+//! The title, author, and other information describing a document, which is saved with it and embedded in the files it's exported to.
+//!
+//! SVGs carry the metadata as Dublin Core properties in an RDF `<metadata>` element, the form Inkscape and stock image sites read,
+//! and PNGs carry it in text chunks with the keywords defined by the PNG specification.
+
+use super::color_profile::{png_chunks, write_png_chunk, PNG_SIGNATURE};
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentMetadata {
+	pub title: String,
+	pub author: String,
+	pub description: String,
+	/// The license the artwork is shared under, either as its name or as a link to it
+	pub license: String,
+	pub keywords: Vec<String>,
+}
+
+impl DocumentMetadata {
+	pub fn is_empty(&self) -> bool {
+		self.title.is_empty() && self.author.is_empty() && self.description.is_empty() && self.license.is_empty() && self.keywords.is_empty()
+	}
+
+	/// Splits a comma-separated list of keywords, leaving out empty ones.
+	pub fn parse_keywords(keywords: &str) -> Vec<String> {
+		keywords.split(',').map(str::trim).filter(|keyword| !keyword.is_empty()).map(String::from).collect()
+	}
+
+	/// The license as a link, if it was given as one rather than by name.
+	fn license_url(&self) -> Option<&str> {
+		let license = self.license.trim();
+		(license.starts_with("https://") || license.starts_with("http://")).then_some(license)
+	}
+
+	/// Adds the metadata to an SVG as a `<title>` element and an RDF `<metadata>` element after its opening tag, where viewers and search engines look for them.
+	pub fn embed_in_svg(&self, svg: String) -> String {
+		let position = match svg.find('>') {
+			Some(position) if !self.is_empty() => position + 1,
+			_ => return svg,
+		};
+
+		let mut element = String::new();
+		if !self.title.is_empty() {
+			let _ = write!(element, "<title>{}</title>", escape_xml(&self.title));
+		}
+		element.push_str(r#"<metadata><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:cc="http://creativecommons.org/ns#">"#);
+		element.push_str(r#"<cc:Work rdf:about=""><dc:format>image/svg+xml</dc:format><dc:type rdf:resource="http://purl.org/dc/dcmitype/StillImage" />"#);
+		if !self.title.is_empty() {
+			let _ = write!(element, "<dc:title>{}</dc:title>", escape_xml(&self.title));
+		}
+		if !self.author.is_empty() {
+			let _ = write!(element, "<dc:creator><cc:Agent><dc:title>{}</dc:title></cc:Agent></dc:creator>", escape_xml(&self.author));
+		}
+		if !self.description.is_empty() {
+			let _ = write!(element, "<dc:description>{}</dc:description>", escape_xml(&self.description));
+		}
+		match self.license_url() {
+			Some(url) => {
+				let _ = write!(element, r#"<cc:license rdf:resource="{}" />"#, escape_xml(url));
+			}
+			None if !self.license.is_empty() => {
+				let _ = write!(element, "<dc:rights><cc:Agent><dc:title>{}</dc:title></cc:Agent></dc:rights>", escape_xml(&self.license));
+			}
+			None => {}
+		}
+		if !self.keywords.is_empty() {
+			element.push_str("<dc:subject><rdf:Bag>");
+			for keyword in &self.keywords {
+				let _ = write!(element, "<rdf:li>{}</rdf:li>", escape_xml(keyword));
+			}
+			element.push_str("</rdf:Bag></dc:subject>");
+		}
+		element.push_str("</cc:Work></rdf:RDF></metadata>");
+
+		let mut svg = svg;
+		svg.insert_str(position, &element);
+		svg
+	}
+
+	/// Adds the metadata to an encoded PNG as text chunks before its image data, replacing the text chunks it already had with the same keywords.
+	pub fn embed_in_png(&self, png: Vec<u8>) -> Vec<u8> {
+		if self.is_empty() || !png.starts_with(PNG_SIGNATURE) {
+			return png;
+		}
+
+		let keywords = self.keywords.join(", ");
+		let texts = [
+			("Title", self.title.as_str()),
+			("Author", self.author.as_str()),
+			("Description", self.description.as_str()),
+			("Copyright", self.license.as_str()),
+			("Keywords", keywords.as_str()),
+			("Software", "Graphite"),
+		];
+		let texts = texts.into_iter().filter(|(_, text)| !text.is_empty()).collect::<Vec<_>>();
+		let replaced = |data: &[u8]| texts.iter().any(|(keyword, _)| data.starts_with(keyword.as_bytes()) && data.get(keyword.len()) == Some(&0));
+
+		let mut tagged = PNG_SIGNATURE.to_vec();
+		let mut written = false;
+		for (chunk_type, data) in png_chunks(&png) {
+			if &chunk_type == b"IDAT" && !written {
+				for (keyword, text) in &texts {
+					write_text_chunk(&mut tagged, keyword, text);
+				}
+				written = true;
+			}
+			if matches!(&chunk_type, b"tEXt" | b"iTXt" | b"zTXt") && replaced(data) {
+				continue;
+			}
+			write_png_chunk(&mut tagged, &chunk_type, data);
+		}
+		tagged
+	}
+}
+
+/// Writes a `tEXt` chunk, or an `iTXt` chunk for text with characters outside of the Latin-1 encoding `tEXt` chunks are limited to.
+fn write_text_chunk(png: &mut Vec<u8>, keyword: &str, text: &str) {
+	let mut data = keyword.as_bytes().to_vec();
+	data.push(0);
+	match text.chars().map(|character| u8::try_from(character as u32).ok()).collect::<Option<Vec<u8>>>() {
+		Some(latin1) => {
+			data.extend(latin1);
+			write_png_chunk(png, b"tEXt", &data);
+		}
+		None => {
+			// Uncompressed, with no language tag or translated keyword
+			data.extend([0, 0, 0, 0]);
+			data.extend(text.as_bytes());
+			write_png_chunk(png, b"iTXt", &data);
+		}
+	}
+}
+
+fn escape_xml(text: &str) -> String {
+	text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
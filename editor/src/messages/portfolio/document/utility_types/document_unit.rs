@@ -0,0 +1,89 @@
+//! The unit a document's lengths are displayed and typed in, which is saved with the document but never changes its artwork.
+//! Lengths are always stored in pixels, with the CSS convention of 96 pixels to the inch.
+
+use serde::{Deserialize, Serialize};
+
+/// The number of document pixels in an inch, which physical units and export resolutions are converted with
+pub const PIXELS_PER_INCH: f64 = 96.;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DocumentUnit {
+	#[default]
+	Pixels,
+	Millimeters,
+	Centimeters,
+	Inches,
+	Points,
+}
+
+impl DocumentUnit {
+	pub const ALL: [DocumentUnit; 5] = [DocumentUnit::Pixels, DocumentUnit::Millimeters, DocumentUnit::Centimeters, DocumentUnit::Inches, DocumentUnit::Points];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			DocumentUnit::Pixels => "Pixels",
+			DocumentUnit::Millimeters => "Millimeters",
+			DocumentUnit::Centimeters => "Centimeters",
+			DocumentUnit::Inches => "Inches",
+			DocumentUnit::Points => "Points",
+		}
+	}
+
+	/// The abbreviation typed after values in this unit, like `mm`.
+	pub fn abbreviation(&self) -> &'static str {
+		match self {
+			DocumentUnit::Pixels => "px",
+			DocumentUnit::Millimeters => "mm",
+			DocumentUnit::Centimeters => "cm",
+			DocumentUnit::Inches => "in",
+			DocumentUnit::Points => "pt",
+		}
+	}
+
+	/// The unit shown after the values of number inputs, with the leading space they're written with.
+	pub fn number_input_unit(&self) -> String {
+		format!(" {}", self.abbreviation())
+	}
+
+	pub fn pixels_per_unit(&self) -> f64 {
+		match self {
+			DocumentUnit::Pixels => 1.,
+			DocumentUnit::Millimeters => PIXELS_PER_INCH / 25.4,
+			DocumentUnit::Centimeters => PIXELS_PER_INCH / 2.54,
+			DocumentUnit::Inches => PIXELS_PER_INCH,
+			DocumentUnit::Points => PIXELS_PER_INCH / 72.,
+		}
+	}
+
+	pub fn from_pixels(&self, pixels: f64) -> f64 {
+		pixels / self.pixels_per_unit()
+	}
+
+	pub fn to_pixels(&self, value: f64) -> f64 {
+		value * self.pixels_per_unit()
+	}
+
+	/// Whether the unit measures a printed size, so export resolutions are better described in dots per inch than as a scale factor.
+	pub fn is_physical(&self) -> bool {
+		!matches!(self, DocumentUnit::Pixels)
+	}
+
+	/// The distance in this unit between the numbered marks of the rulers, given the zoom of the viewport.
+	///
+	/// Pixels are divided by powers of two so the marks land on whole pixels, while the other units use the 1, 2, 5 sequence of a physical ruler.
+	pub fn ruler_interval(&self, document_scale: f64) -> f64 {
+		if *self == DocumentUnit::Pixels {
+			let log = document_scale.log2();
+			return if log < 0. { 100. * 2_f64.powf(-log.ceil()) } else { 100. / 2_f64.powf(log.ceil()) };
+		}
+
+		// The smallest interval which keeps the numbered marks at least as far apart on screen as the closest marks of the pixel ruler
+		let minimum = 50. / (self.pixels_per_unit() * document_scale);
+		let magnitude = 10_f64.powf(minimum.log10().floor());
+		[1., 2., 5., 10.]
+			.into_iter()
+			.map(|step| step * magnitude)
+			.find(|&interval| interval >= minimum)
+			.unwrap_or(10. * magnitude)
+	}
+}
@@ -0,0 +1,36 @@
+use glam::{DAffine2, DVec2};
+use serde::{Deserialize, Serialize};
+
+/// Where a view of the document looks, which is the navigation state belonging to each view rather than to the document shared by all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DocumentView {
+	pub pan: DVec2,
+	/// In radians
+	pub tilt: f64,
+	pub zoom: f64,
+}
+
+impl Default for DocumentView {
+	fn default() -> Self {
+		Self { pan: DVec2::ZERO, tilt: 0., zoom: 1. }
+	}
+}
+
+impl DocumentView {
+	/// The transform from document space to the space of a viewport of the given size, with the panned point of the document in its center.
+	pub fn transform(&self, viewport_size: DVec2) -> DAffine2 {
+		let half_viewport = viewport_size / 2. / self.zoom;
+		DAffine2::from_scale(DVec2::splat(self.zoom)) * DAffine2::from_translation(half_viewport) * DAffine2::from_angle(self.tilt) * DAffine2::from_translation(self.pan)
+	}
+
+	/// How the view is described next to it, like "150% · 45°".
+	pub fn label(&self) -> String {
+		let percent = self.zoom * 100.;
+		let degrees = self.tilt.to_degrees().rem_euclid(360.);
+		if degrees.abs() < 1e-6 {
+			format!("{}%", (percent * 100.).round() / 100.)
+		} else {
+			format!("{}% · {}°", (percent * 100.).round() / 100., (degrees * 100.).round() / 100.)
+		}
+	}
+}
@@ -0,0 +1,213 @@
+//! Encodes rasterized artwork as PNG, JPEG, and WebP files, so exports in these formats come out the same from the browser and from the headless renderer.
+//!
+//! The encoders take tightly packed, non-premultiplied 8-bit RGBA pixels in rows from top to bottom.
+
+use super::animation_encoding::encode_png;
+use super::color_profile::tag_image;
+use super::error::EditorError;
+
+use graphene::color::ColorSpace;
+
+use image::codecs::webp::{WebPEncoder, WebPQuality};
+use jpeg_encoder::{ColorType, Encoder, SamplingFactor};
+use serde::{Deserialize, Serialize};
+
+/// The resolution of the color information of a JPEG, relative to its brightness.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ChromaSubsampling {
+	/// 4:4:4, keeping the color of every pixel
+	Full,
+	/// 4:2:0, keeping one color for each square of four pixels, which the eye rarely notices and makes for smaller files
+	#[default]
+	Half,
+}
+
+/// A raster format which is encoded by the editor, along with its encoding options.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RasterEncoding {
+	Png,
+	Jpeg {
+		quality: u8,
+		chroma_subsampling: ChromaSubsampling,
+	},
+	/// Lossy with a quality from 1 to 99, or lossless at 100
+	Webp {
+		quality: u8,
+	},
+}
+
+impl RasterEncoding {
+	/// Encodes the pixels and tags the file with the color space they're in.
+	pub fn encode(self, rgba: &[u8], width: u32, height: u32, color_space: ColorSpace) -> Result<Vec<u8>, EditorError> {
+		let image = match self {
+			RasterEncoding::Png => encode_png(rgba, width, height),
+			RasterEncoding::Jpeg { quality, chroma_subsampling } => encode_jpeg(rgba, width, height, quality, chroma_subsampling),
+			RasterEncoding::Webp { quality } => encode_webp(rgba, width, height, quality),
+		}?;
+		Ok(tag_image(image, color_space))
+	}
+
+	pub fn to_mime(self) -> &'static str {
+		match self {
+			RasterEncoding::Png => "image/png",
+			RasterEncoding::Jpeg { .. } => "image/jpeg",
+			RasterEncoding::Webp { .. } => "image/webp",
+		}
+	}
+}
+
+pub fn check_dimensions(rgba: &[u8], width: u32, height: u32, max: u32, format: &str) -> Result<(), EditorError> {
+	if width == 0 || height == 0 || width > max || height > max {
+		return Err(EditorError::Misc(format!(
+			"A {format} must be between 1 and {max} pixels wide and tall, but the image is {width}×{height}"
+		)));
+	}
+	if rgba.len() != width as usize * height as usize * 4 {
+		return Err(EditorError::Misc(format!(
+			"Expected {} bytes of RGBA pixels for a {width}×{height} image, but got {}",
+			width as usize * height as usize * 4,
+			rgba.len()
+		)));
+	}
+	Ok(())
+}
+
+/// Encodes a baseline JPEG with a quality from 1 to 100, compositing any transparency over white since JPEGs can't store it.
+pub fn encode_jpeg(rgba: &[u8], width: u32, height: u32, quality: u8, chroma_subsampling: ChromaSubsampling) -> Result<Vec<u8>, EditorError> {
+	check_dimensions(rgba, width, height, u16::MAX as u32, "JPEG")?;
+
+	let rgb = rgba
+		.chunks_exact(4)
+		.flat_map(|pixel| {
+			let alpha = pixel[3] as u32;
+			[0, 1, 2].map(|channel| ((pixel[channel] as u32 * alpha + 255 * (255 - alpha) + 127) / 255) as u8)
+		})
+		.collect::<Vec<_>>();
+
+	let mut jpeg = Vec::new();
+	let mut encoder = Encoder::new(&mut jpeg, quality.clamp(1, 100));
+	encoder.set_sampling_factor(match chroma_subsampling {
+		ChromaSubsampling::Full => SamplingFactor::R_4_4_4,
+		ChromaSubsampling::Half => SamplingFactor::R_4_2_0,
+	});
+	encoder
+		.encode(&rgb, width as u16, height as u16, ColorType::Rgb)
+		.map_err(|error| EditorError::Misc(format!("Failed to encode the JPEG: {error}")))?;
+	Ok(jpeg)
+}
+
+/// Encodes a WebP, which is lossy with a quality from 1 to 99 and lossless at 100.
+pub fn encode_webp(rgba: &[u8], width: u32, height: u32, quality: u8) -> Result<Vec<u8>, EditorError> {
+	check_dimensions(rgba, width, height, (1 << 14) - 1, "WebP")?;
+
+	let quality = match quality.clamp(1, 100) {
+		100 => WebPQuality::lossless(),
+		quality => WebPQuality::lossy(quality),
+	};
+	let mut webp = Vec::new();
+	WebPEncoder::new_with_quality(&mut webp, quality)
+		.encode(rgba, width, height, image::ColorType::Rgba8)
+		.map_err(|error| EditorError::Misc(format!("Failed to encode the WebP: {error}")))?;
+	Ok(webp)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// An image with a gradient, flat areas for the encoders to find repeats in, and varying transparency.
+	fn test_image(width: u32, height: u32) -> Vec<u8> {
+		(0..height)
+			.flat_map(|y| {
+				(0..width).flat_map(move |x| match x < width / 2 {
+					true => [(x * 255 / width) as u8, (y * 255 / height) as u8, 128, (255 - y) as u8],
+					false => [200, 30, 60, 255],
+				})
+			})
+			.collect()
+	}
+
+	fn decode(data: &[u8], format: image::ImageFormat) -> image::RgbaImage {
+		image::load_from_memory_with_format(data, format).expect("the encoded image can't be decoded").to_rgba8()
+	}
+
+	#[test]
+	fn webp_at_full_quality_is_lossless() {
+		for (width, height) in [(1, 1), (37, 23), (300, 2)] {
+			let rgba = test_image(width, height);
+			let webp = encode_webp(&rgba, width, height, 100).unwrap();
+			assert_eq!(&webp[0..4], b"RIFF");
+			assert_eq!(u32::from_le_bytes(webp[4..8].try_into().unwrap()) as usize, webp.len() - 8);
+
+			let decoded = decode(&webp, image::ImageFormat::WebP);
+			assert_eq!(decoded.dimensions(), (width, height));
+			assert_eq!(decoded.into_raw(), rgba);
+		}
+	}
+
+	#[test]
+	fn webp_of_repeated_pixels_is_small() {
+		let rgba = [10, 20, 30, 255].repeat(256 * 256);
+		let webp = encode_webp(&rgba, 256, 256, 100).unwrap();
+		assert!(webp.len() < 1024, "{} bytes", webp.len());
+		assert_eq!(decode(&webp, image::ImageFormat::WebP).into_raw(), rgba);
+	}
+
+	#[test]
+	fn webp_quality_trades_size_for_detail() {
+		let (width, height) = (64, 64);
+		let rgba = test_image(width, height);
+		let low = encode_webp(&rgba, width, height, 10).unwrap();
+		let high = encode_webp(&rgba, width, height, 90).unwrap();
+		assert!(low.len() < high.len());
+
+		let decoded = decode(&high, image::ImageFormat::WebP);
+		assert_eq!(decoded.dimensions(), (width, height));
+		let error = decoded.into_raw().iter().zip(&rgba).map(|(&a, &b)| (a as f64 - b as f64).abs()).sum::<f64>() / rgba.len() as f64;
+		assert!(error < 8., "average error of {error}");
+	}
+
+	#[test]
+	fn tagged_webp_can_be_decoded() {
+		let (width, height) = (16, 8);
+		let rgba = test_image(width, height);
+		for quality in [80, 100] {
+			let webp = RasterEncoding::Webp { quality }.encode(&rgba, width, height, ColorSpace::DisplayP3).unwrap();
+			assert_eq!(&webp[12..16], b"VP8X");
+			assert!(webp.windows(4).any(|window| window == b"ICCP"));
+			assert_eq!(decode(&webp, image::ImageFormat::WebP).dimensions(), (width, height));
+		}
+	}
+
+	#[test]
+	fn jpeg_is_close_to_the_original() {
+		let (width, height) = (40, 24);
+		let rgba = test_image(width, height).chunks_exact(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255]).collect::<Vec<_>>();
+		for chroma_subsampling in [ChromaSubsampling::Full, ChromaSubsampling::Half] {
+			let jpeg = encode_jpeg(&rgba, width, height, 95, chroma_subsampling).unwrap();
+			let decoded = decode(&jpeg, image::ImageFormat::Jpeg);
+			assert_eq!(decoded.dimensions(), (width, height));
+
+			let error = decoded.into_raw().iter().zip(&rgba).map(|(&a, &b)| (a as f64 - b as f64).abs()).sum::<f64>() / rgba.len() as f64;
+			assert!(error < 8., "average error of {error} with {chroma_subsampling:?} chroma subsampling");
+		}
+	}
+
+	#[test]
+	fn jpeg_quality_trades_size_for_detail() {
+		let (width, height) = (64, 64);
+		let rgba = test_image(width, height);
+		let low = encode_jpeg(&rgba, width, height, 10, ChromaSubsampling::Half).unwrap();
+		let high = encode_jpeg(&rgba, width, height, 100, ChromaSubsampling::Half).unwrap();
+		assert!(low.len() < high.len());
+		decode(&low, image::ImageFormat::Jpeg);
+	}
+
+	#[test]
+	fn reject_mismatched_dimensions() {
+		assert!(encode_webp(&[0; 4], 0, 1, 100).is_err());
+		assert!(encode_webp(&[0; 8], 1, 1, 100).is_err());
+		assert!(encode_webp(&[0; 4], 1 << 14, 1, 90).is_err());
+		assert!(encode_jpeg(&[0; 12], 2, 2, 90, ChromaSubsampling::Half).is_err());
+	}
+}
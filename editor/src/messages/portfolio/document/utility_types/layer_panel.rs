@@ -1,4 +1,4 @@
-use graphene::layers::layer_info::{Layer, LayerData, LayerDataTypeDiscriminant};
+use graphene::layers::layer_info::{Layer, LayerData, LayerDataTypeDiscriminant, LayerLabel};
 use graphene::layers::style::{RenderData, ViewMode};
 use graphene::layers::text_layer::FontCache;
 use graphene::LayerId;
@@ -6,6 +6,8 @@ use graphene::LayerId;
 use glam::{DAffine2, DVec2};
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 pub struct RawBuffer(Vec<u8>);
@@ -44,17 +46,80 @@ impl LayerMetadata {
 	}
 }
 
+/// Narrows down the layers shown in the Layers panel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LayerFilter {
+	/// Lowercase text that layer names must contain.
+	pub name: String,
+	/// The layer types to show, or all types if empty.
+	pub layer_types: Vec<LayerDataTypeDiscriminant>,
+	/// The color labels to show, or all layers regardless of label if empty.
+	pub labels: Vec<LayerLabel>,
+}
+
+impl LayerFilter {
+	/// Parses a search query. The words `type:vector`, `type:raster`, and `type:text` restrict the layer types,
+	/// words like `label:red` restrict the color labels, and the remaining words must appear in the layer name.
+	pub fn from_query(query: &str) -> Self {
+		let mut filter = Self::default();
+		let mut name_words = Vec::new();
+
+		for word in query.split_whitespace() {
+			let word = word.to_lowercase();
+			match word.as_str() {
+				"type:vector" => filter.layer_types.push(LayerDataTypeDiscriminant::Shape),
+				"type:raster" => filter.layer_types.extend([LayerDataTypeDiscriminant::Image, LayerDataTypeDiscriminant::Imaginate]),
+				"type:text" => filter.layer_types.push(LayerDataTypeDiscriminant::Text),
+				_ => match word
+					.strip_prefix("label:")
+					.and_then(|name| LayerLabel::ALL.into_iter().find(|label| label.to_string().to_lowercase() == name))
+				{
+					Some(label) => filter.labels.push(label),
+					None => name_words.push(word),
+				},
+			}
+		}
+		filter.name = name_words.join(" ");
+
+		filter
+	}
+
+	/// Whether the filter hides any layers.
+	pub fn is_active(&self) -> bool {
+		!self.name.is_empty() || !self.layer_types.is_empty() || !self.labels.is_empty()
+	}
+
+	/// Whether the layer itself matches the filter.
+	pub fn matches(&self, layer: &Layer) -> bool {
+		let name_matches = self.name.is_empty() || layer.name.as_ref().is_some_and(|name| name.to_lowercase().contains(&self.name));
+		let type_matches = self.layer_types.is_empty() || self.layer_types.contains(&(&layer.data).into());
+		let label_matches = self.labels.is_empty() || layer.label.is_some_and(|label| self.labels.contains(&label));
+
+		name_matches && type_matches && label_matches
+	}
+
+	/// Whether the layer should be listed, because it or any layer inside it matches the filter.
+	pub fn shows(&self, layer: &Layer) -> bool {
+		!self.is_active() || layer.iter().any(|layer| self.matches(layer))
+	}
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct LayerPanelEntry {
 	pub name: String,
 	pub tooltip: String,
 	pub visible: bool,
+	pub locked: bool,
+	pub label: Option<LayerLabel>,
 	#[serde(rename = "layerType")]
 	pub layer_type: LayerDataTypeDiscriminant,
 	#[serde(rename = "layerMetadata")]
 	pub layer_metadata: LayerMetadata,
 	pub path: Vec<LayerId>,
 	pub thumbnail: String,
+	/// The thumbnail rasterized into a PNG data URL, which is shown instead of the SVG thumbnail once the frontend has rasterized it
+	#[serde(rename = "thumbnailRaster")]
+	pub thumbnail_raster: Option<String>,
 }
 
 impl LayerPanelEntry {
@@ -94,10 +159,20 @@ impl LayerPanelEntry {
 			name,
 			tooltip,
 			visible: layer.visible,
+			locked: layer.locked,
+			label: layer.label,
 			layer_type: (&layer.data).into(),
 			layer_metadata: *layer_metadata,
 			path,
 			thumbnail,
+			thumbnail_raster: None,
 		}
 	}
+
+	/// A hash of the SVG thumbnail, which tells if a rasterized thumbnail is still up to date with the content of the layer.
+	pub fn thumbnail_hash(&self) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		self.thumbnail.hash(&mut hasher);
+		hasher.finish()
+	}
 }
@@ -1,11 +1,22 @@
 pub use super::layer_panel::{LayerMetadata, LayerPanelEntry};
 
-use graphene::document::Document as GrapheneDocument;
+use crate::consts::VIEWPORT_TILE_SIZE;
+
+use graphene::document::{Document as GrapheneDocument, RenderedRootLayer};
+use graphene::gpu_scene::GpuScene;
+use graphene::layers::blend_mode::BlendMode;
+use graphene::layers::style::ViewMode;
+use graphene::layers::vector::consts::ManipulatorType;
+use graphene::layers::vector::subpath::Subpath;
 use graphene::LayerId;
 
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 pub type DocumentSave = (GrapheneDocument, HashMap<Vec<LayerId>, LayerMetadata>);
 
@@ -62,7 +73,310 @@ impl DocumentMode {
 	}
 }
 
+/// The number of preview pixels for every document pixel in the Pixels view mode, to check how artwork rasterizes on standard and high density displays.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum PixelDensity {
+	#[default]
+	Standard,
+	High,
+}
+
+impl PixelDensity {
+	pub fn factor(&self) -> f64 {
+		match self {
+			PixelDensity::Standard => 1.,
+			PixelDensity::High => 2.,
+		}
+	}
+}
+
+/// How positions are rounded to the pixel grid of the document while drawing and transforming, so artwork stays crisp when rasterized at its actual size.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum PixelSnapping {
+	#[default]
+	Off,
+	/// Positions are rounded to the corners of pixels, where the edges of filled shapes are crisp
+	WholePixels,
+	/// Positions are rounded to the centers of pixels, at half pixel coordinates where 1 px strokes are crisp
+	HalfPixels,
+}
+
+impl PixelSnapping {
+	/// Rounds a document space position to the pixel grid.
+	pub fn snap_point(&self, point: DVec2) -> DVec2 {
+		match self {
+			PixelSnapping::Off => point,
+			PixelSnapping::WholePixels => point.round(),
+			PixelSnapping::HalfPixels => (point - 0.5).round() + 0.5,
+		}
+	}
+
+	/// Rounds a viewport space position to the pixel grid of the document, which is shown in the viewport with the given transform.
+	pub fn snap_viewport_point(&self, point: DVec2, document_to_viewport: DAffine2) -> DVec2 {
+		if *self == PixelSnapping::Off {
+			return point;
+		}
+		document_to_viewport.transform_point2(self.snap_point(document_to_viewport.inverse().transform_point2(point)))
+	}
+
+	/// Rounds a viewport space movement to whole pixels of the document, so geometry on the pixel grid stays on it.
+	pub fn snap_viewport_delta(&self, delta: DVec2, document_to_viewport: DAffine2) -> DVec2 {
+		if *self == PixelSnapping::Off {
+			return delta;
+		}
+		document_to_viewport.transform_vector2(document_to_viewport.inverse().transform_vector2(delta).round())
+	}
+
+	/// Moves the anchors of a path onto the pixel grid, taking their handles along so the curves between them keep their shape.
+	pub fn snap_subpath(&self, subpath: &Subpath, layer_to_document: DAffine2) -> Subpath {
+		let mut subpath = subpath.clone();
+		if layer_to_document.matrix2.determinant() == 0. {
+			return subpath;
+		}
+		let document_to_layer = layer_to_document.inverse();
+
+		for manipulator_group in subpath.manipulator_groups_mut().iter_mut() {
+			let anchor = match &manipulator_group.points[ManipulatorType::Anchor] {
+				Some(anchor) => anchor.position,
+				None => continue,
+			};
+			let offset = document_to_layer.transform_point2(self.snap_point(layer_to_document.transform_point2(anchor))) - anchor;
+			for point in manipulator_group.points.iter_mut().flatten() {
+				point.position += offset;
+			}
+		}
+
+		subpath
+	}
+}
+
+/// The GPU scene last sent to the frontend, used to send only the view transform or the changed tiles on the next render.
+#[derive(Clone, Debug)]
+pub struct SentGpuScene {
+	/// The document state, view mode, and zoom the scene was tessellated for
+	pub key: (u64, ViewMode, u64),
+	pub view_transform: DAffine2,
+	pub scene: GpuScene,
+}
+
+/// The tiles of the viewport overlapping the given document space regions, as x, y, width, and height in viewport pixels.
+pub fn viewport_tiles(regions: &[[DVec2; 2]], view_transform: DAffine2, viewport_size: DVec2) -> Vec<[f64; 4]> {
+	let tile_count = (viewport_size / VIEWPORT_TILE_SIZE).ceil();
+	let mut tiles = BTreeSet::new();
+	for &[min, max] in regions {
+		let corners = [min, DVec2::new(max.x, min.y), max, DVec2::new(min.x, max.y)].map(|corner| view_transform.transform_point2(corner));
+		// Anti-aliasing reaches a pixel past the edges of the geometry
+		let (viewport_min, viewport_max) = (corners.into_iter().reduce(DVec2::min).unwrap() - 1., corners.into_iter().reduce(DVec2::max).unwrap() + 1.);
+		let first = (viewport_min / VIEWPORT_TILE_SIZE).floor().max(DVec2::ZERO);
+		let last = (viewport_max / VIEWPORT_TILE_SIZE).floor().min(tile_count - 1.);
+		// Regions outside of the viewport end before they start
+		if !first.is_finite() || !last.is_finite() || last.x < first.x || last.y < first.y {
+			continue;
+		}
+		for y in first.y as u32..=last.y as u32 {
+			for x in first.x as u32..=last.x as u32 {
+				tiles.insert((y, x));
+			}
+		}
+	}
+	tiles
+		.into_iter()
+		.map(|(y, x)| [x as f64 * VIEWPORT_TILE_SIZE, y as f64 * VIEWPORT_TILE_SIZE, VIEWPORT_TILE_SIZE, VIEWPORT_TILE_SIZE])
+		.collect()
+}
+
+/// A part of the document artwork sent to the frontend, which stacks the slices in order.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub enum ArtworkSlice {
+	/// Layers which changed since the last render, drawn from their SVG
+	Svg { svg: String },
+	/// A run of unchanged layers which the frontend rasterizes once and reuses, with the SVG only sent the first time its key is used
+	Snapshot { key: String, svg: Option<String> },
+}
+
+/// What the frontend needs to update the document artwork, found by comparing the layers of the root folder with those of the last render.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum ArtworkUpdate {
+	/// Every layer changed, so the artwork is sent as a single SVG joined from the already rendered layers
+	AllChanged { svg: String },
+	/// Only some layers changed, so the unchanged runs of layers are drawn from raster snapshots
+	Slices(Vec<ArtworkSlice>),
+	/// No layer changed and the frontend already shows the artwork from the last render
+	Unchanged,
+}
+
+/// The layers and raster snapshots of the last render, used to find which runs of layers are unchanged on the next render.
+#[derive(Clone, Debug, Default)]
+pub struct SentArtworkSnapshots {
+	/// The content hashes of the layers in the root folder
+	layer_hashes: HashSet<u64>,
+	/// The content hashes of the layers drawn from their SVG instead of a snapshot
+	live_hashes: HashSet<u64>,
+	/// The keys of the snapshots the frontend holds
+	snapshot_keys: HashSet<u64>,
+}
+
+impl SentArtworkSnapshots {
+	/// Splits the layers of the root folder into slices, drawing the runs of layers which are unchanged since the last render from raster snapshots.
+	pub fn slice(&mut self, layers: Vec<RenderedRootLayer>) -> ArtworkUpdate {
+		let previous = std::mem::replace(
+			self,
+			Self {
+				layer_hashes: layers.iter().map(|layer| layer.content_hash).collect(),
+				..Default::default()
+			},
+		);
+
+		// If nothing changed, such as when the pointer stops during a drag, the layers drawn live last time stay live so the same snapshots are reused
+		let nothing_changed = layers.iter().all(|layer| previous.layer_hashes.contains(&layer.content_hash));
+		let mut live_below = false;
+		let live = layers
+			.iter()
+			.map(|layer| {
+				let changed = match nothing_changed {
+					true => previous.live_hashes.contains(&layer.content_hash),
+					false => !previous.layer_hashes.contains(&layer.content_hash),
+				};
+				// Layers blending with the layers beneath them can't be composited separately from the live layers they blend with
+				let live = changed || (live_below && layer.blend_mode != BlendMode::Normal);
+				live_below |= live;
+				live
+			})
+			.collect::<Vec<_>>();
+		if live.iter().all(|&live| live) {
+			return ArtworkUpdate::AllChanged {
+				svg: layers.iter().map(|layer| layer.svg.as_str()).collect(),
+			};
+		}
+		if !live.iter().any(|&live| live) {
+			return ArtworkUpdate::Unchanged;
+		}
+
+		let mut runs: Vec<(bool, Vec<RenderedRootLayer>)> = Vec::new();
+		for (layer, live) in layers.into_iter().zip(live) {
+			match runs.last_mut() {
+				Some((run_live, run)) if *run_live == live => run.push(layer),
+				_ => runs.push((live, vec![layer])),
+			}
+		}
+
+		let slices = runs
+			.into_iter()
+			.map(|(live, run)| {
+				let svg = run.iter().map(|layer| layer.svg.as_str()).collect::<String>();
+				if live {
+					self.live_hashes.extend(run.iter().map(|layer| layer.content_hash));
+					return ArtworkSlice::Svg { svg };
+				}
+
+				let mut hasher = DefaultHasher::new();
+				run.iter().for_each(|layer| layer.content_hash.hash(&mut hasher));
+				let key = hasher.finish();
+				self.snapshot_keys.insert(key);
+				ArtworkSlice::Snapshot {
+					// Sent as a string since JavaScript numbers can't hold every 64 bit integer
+					key: format!("{key:016x}"),
+					svg: (!previous.snapshot_keys.contains(&key)).then_some(svg),
+				}
+			})
+			.collect();
+		ArtworkUpdate::Slices(slices)
+	}
+}
+
 pub enum DocumentRenderMode<'a> {
 	Root,
 	OnlyBelowLayerInFolder(&'a [LayerId]),
 }
+
+#[cfg(test)]
+mod test {
+	use super::{ArtworkSlice, ArtworkUpdate, SentArtworkSnapshots};
+
+	use graphene::document::RenderedRootLayer;
+	use graphene::layers::blend_mode::BlendMode;
+
+	fn layer(content_hash: u64) -> RenderedRootLayer {
+		layer_blended(content_hash, BlendMode::Normal)
+	}
+
+	fn layer_blended(content_hash: u64, blend_mode: BlendMode) -> RenderedRootLayer {
+		RenderedRootLayer {
+			svg: format!("<g id=\"{content_hash}\"/>"),
+			content_hash,
+			blend_mode,
+		}
+	}
+
+	#[test]
+	fn all_changed_joins_the_rendered_layers() {
+		let mut snapshots = SentArtworkSnapshots::default();
+		let update = snapshots.slice(vec![layer(1), layer(2)]);
+		assert_eq!(
+			update,
+			ArtworkUpdate::AllChanged {
+				svg: "<g id=\"1\"/><g id=\"2\"/>".to_string()
+			}
+		);
+
+		let update = snapshots.slice(vec![layer(3), layer(4)]);
+		assert_eq!(
+			update,
+			ArtworkUpdate::AllChanged {
+				svg: "<g id=\"3\"/><g id=\"4\"/>".to_string()
+			}
+		);
+	}
+
+	#[test]
+	fn nothing_changed_after_sending_the_whole_artwork() {
+		let mut snapshots = SentArtworkSnapshots::default();
+		assert!(matches!(snapshots.slice(vec![layer(1), layer(2)]), ArtworkUpdate::AllChanged { .. }));
+		assert_eq!(snapshots.slice(vec![layer(1), layer(2)]), ArtworkUpdate::Unchanged);
+		assert_eq!(snapshots.slice(vec![layer(1), layer(2)]), ArtworkUpdate::Unchanged);
+	}
+
+	#[test]
+	fn empty_document_is_always_sent() {
+		let mut snapshots = SentArtworkSnapshots::default();
+		assert_eq!(snapshots.slice(Vec::new()), ArtworkUpdate::AllChanged { svg: String::new() });
+		assert_eq!(snapshots.slice(Vec::new()), ArtworkUpdate::AllChanged { svg: String::new() });
+	}
+
+	#[test]
+	fn unchanged_layers_are_drawn_from_snapshots() {
+		let mut snapshots = SentArtworkSnapshots::default();
+		snapshots.slice(vec![layer(1), layer(2), layer(3)]);
+
+		let update = snapshots.slice(vec![layer(1), layer(4), layer(3)]);
+		let ArtworkUpdate::Slices(slices) = update else { panic!("expected slices, got {update:?}") };
+		assert_eq!(slices.len(), 3);
+		assert!(matches!(&slices[0], ArtworkSlice::Snapshot { svg: Some(svg), .. } if svg == "<g id=\"1\"/>"));
+		assert_eq!(slices[1], ArtworkSlice::Svg { svg: "<g id=\"4\"/>".to_string() });
+		assert!(matches!(&slices[2], ArtworkSlice::Snapshot { svg: Some(svg), .. } if svg == "<g id=\"3\"/>"));
+
+		// When the pointer stops during a drag, the same layer stays live and the snapshots are reused without their SVG
+		let update = snapshots.slice(vec![layer(1), layer(4), layer(3)]);
+		let ArtworkUpdate::Slices(slices) = update else { panic!("expected slices, got {update:?}") };
+		assert!(matches!(&slices[0], ArtworkSlice::Snapshot { svg: None, .. }));
+		assert_eq!(slices[1], ArtworkSlice::Svg { svg: "<g id=\"4\"/>".to_string() });
+		assert!(matches!(&slices[2], ArtworkSlice::Snapshot { svg: None, .. }));
+	}
+
+	#[test]
+	fn blended_layers_above_changes_are_drawn_live() {
+		let mut snapshots = SentArtworkSnapshots::default();
+		snapshots.slice(vec![layer(1), layer(2), layer_blended(3, BlendMode::Multiply)]);
+
+		let update = snapshots.slice(vec![layer(1), layer(4), layer_blended(3, BlendMode::Multiply)]);
+		let ArtworkUpdate::Slices(slices) = update else { panic!("expected slices, got {update:?}") };
+		assert_eq!(slices.len(), 2);
+		assert!(matches!(&slices[0], ArtworkSlice::Snapshot { .. }));
+		assert_eq!(
+			slices[1],
+			ArtworkSlice::Svg {
+				svg: "<g id=\"4\"/><g id=\"3\"/>".to_string()
+			}
+		);
+	}
+}
@@ -1,6 +1,18 @@
+pub mod animation_encoding;
+pub mod batch_export;
 pub mod clipboards;
+pub mod collaboration;
+pub mod color_profile;
+pub mod document_file;
+pub mod document_metadata;
+pub mod document_unit;
+pub mod document_view;
 pub mod error;
+pub mod image_encoding;
 pub mod layer_panel;
 pub mod misc;
+pub mod sprite_sheet;
+pub mod svg_export;
+pub mod transform_settings;
 pub mod transformation;
 pub mod vectorize_layer_metadata;
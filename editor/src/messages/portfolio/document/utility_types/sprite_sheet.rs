@@ -0,0 +1,173 @@
+//! Sprite sheets, which pack artboards and export slices into a single image along with a JSON atlas of where each of them is, as loaded by game engines.
+
+use glam::{DVec2, UVec2};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The largest sprite sheet, in pixels along either side, since browsers can't rasterize larger canvases.
+pub const MAX_SPRITE_SHEET_SIZE: u32 = 1 << 14;
+
+/// How the sprites are scaled and packed into the sheet.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpriteSheetSettings {
+	pub scale_factor: f64,
+	/// The space in pixels between sprites and around the edges of the sheet, which keeps texture filtering from bleeding neighboring sprites into each other
+	pub padding: u32,
+	/// Whether the width and height of the sheet are rounded up to powers of two, which older GPUs and some engines require of textures
+	pub power_of_two: bool,
+	/// Whether the artboards are left out, keeping only the artwork
+	pub transparent_background: bool,
+}
+
+impl Default for SpriteSheetSettings {
+	fn default() -> Self {
+		Self {
+			scale_factor: 1.,
+			padding: 2,
+			power_of_two: false,
+			transparent_background: true,
+		}
+	}
+}
+
+/// Places sprites of the given sizes into rows, tallest first, trying a few sheet widths to find the one which wastes the least space.
+/// Returns the top left corner of each sprite and the size of the sheet.
+pub fn pack_sprites(sizes: &[UVec2], padding: u32, power_of_two: bool) -> (Vec<UVec2>, UVec2) {
+	let mut order: Vec<usize> = (0..sizes.len()).collect();
+	order.sort_by_key(|&index| std::cmp::Reverse((sizes[index].y, sizes[index].x)));
+
+	let pack = |max_width: u32| {
+		let mut positions = vec![UVec2::ZERO; sizes.len()];
+		let mut cursor = UVec2::splat(padding);
+		let mut row_height = 0;
+		let mut width = 0;
+		for &index in &order {
+			let size = sizes[index];
+			if cursor.x > padding && cursor.x + size.x + padding > max_width {
+				cursor = UVec2::new(padding, cursor.y + row_height + padding);
+				row_height = 0;
+			}
+			positions[index] = cursor;
+			cursor.x += size.x + padding;
+			row_height = row_height.max(size.y);
+			width = width.max(cursor.x);
+		}
+		let mut sheet_size = UVec2::new(width.max(1), cursor.y + row_height + padding);
+		if power_of_two {
+			sheet_size = UVec2::new(sheet_size.x.next_power_of_two(), sheet_size.y.next_power_of_two());
+		}
+		(positions, sheet_size)
+	};
+
+	let widest = sizes.iter().map(|size| size.x).max().unwrap_or_default() + 2 * padding;
+	let widths: Vec<u32> = if power_of_two {
+		std::iter::successors(Some(widest.next_power_of_two()), |&width| (width < MAX_SPRITE_SHEET_SIZE).then_some(width * 2)).collect()
+	} else {
+		let area: u64 = sizes.iter().map(|size| (size.x + padding) as u64 * (size.y + padding) as u64).sum();
+		[1., 1.1, 1.25, 1.5, 2.]
+			.iter()
+			.map(|factor| ((area as f64).sqrt() * factor).ceil() as u32)
+			.chain([widest])
+			.map(|width| width.max(widest))
+			.collect()
+	};
+
+	// Prefer the smallest sheet, and the squarest among sheets of the same size
+	widths
+		.into_iter()
+		.map(pack)
+		.min_by_key(|(_, size)| (size.x as u64 * size.y as u64, size.max_element()))
+		.unwrap_or_else(|| (Vec::new(), UVec2::ONE))
+}
+
+/// Combines the SVG documents of the sprites, along with their sizes in document space, into a single SVG of the sheet.
+/// Each is stretched to fill its place in the sheet, which is its scaled size rounded to whole pixels.
+pub fn sprite_sheet_svg(sprites: &[(String, DVec2)], positions: &[UVec2], sizes: &[UVec2], sheet_size: UVec2) -> String {
+	let mut svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#, sheet_size.x, sheet_size.y);
+	for (((sprite, document_size), position), size) in sprites.iter().zip(positions).zip(sizes) {
+		let scale = size.as_f64() / *document_size;
+		svg.push_str(&format!(r#"<g transform="translate({} {}) scale({} {})">{}</g>"#, position.x, position.y, scale.x, scale.y, sprite));
+	}
+	svg.push_str("</svg>");
+	svg
+}
+
+#[derive(Serialize)]
+struct AtlasRect {
+	x: u32,
+	y: u32,
+	w: u32,
+	h: u32,
+}
+
+#[derive(Serialize)]
+struct AtlasSize {
+	w: u32,
+	h: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AtlasFrame {
+	frame: AtlasRect,
+	rotated: bool,
+	trimmed: bool,
+	sprite_source_size: AtlasRect,
+	source_size: AtlasSize,
+}
+
+#[derive(Serialize)]
+struct AtlasMeta {
+	app: &'static str,
+	image: String,
+	format: &'static str,
+	size: AtlasSize,
+	scale: String,
+}
+
+#[derive(Serialize)]
+struct Atlas {
+	frames: BTreeMap<String, AtlasFrame>,
+	meta: AtlasMeta,
+}
+
+/// Describes where each sprite is in the sheet as a JSON atlas, in the hash format first written by TexturePacker which most game engines can load.
+/// Sprites sharing a name are numbered so each has its own entry.
+pub fn sprite_sheet_atlas(names: &[String], positions: &[UVec2], sizes: &[UVec2], sheet_size: UVec2, image: &str, scale_factor: f64) -> String {
+	let mut frames = BTreeMap::new();
+	for ((name, position), size) in names.iter().zip(positions).zip(sizes) {
+		let mut unique_name = name.clone();
+		let mut copy = 1;
+		while frames.contains_key(&unique_name) {
+			copy += 1;
+			unique_name = format!("{name} {copy}");
+		}
+
+		let frame = AtlasFrame {
+			frame: AtlasRect {
+				x: position.x,
+				y: position.y,
+				w: size.x,
+				h: size.y,
+			},
+			rotated: false,
+			trimmed: false,
+			sprite_source_size: AtlasRect { x: 0, y: 0, w: size.x, h: size.y },
+			source_size: AtlasSize { w: size.x, h: size.y },
+		};
+		frames.insert(unique_name, frame);
+	}
+
+	let atlas = Atlas {
+		frames,
+		meta: AtlasMeta {
+			app: "Graphite",
+			image: image.to_string(),
+			format: "RGBA8888",
+			size: AtlasSize { w: sheet_size.x, h: sheet_size.y },
+			scale: scale_factor.to_string(),
+		},
+	};
+	// We fully expect the serialization to succeed
+	serde_json::to_string_pretty(&atlas).unwrap()
+}
@@ -0,0 +1,510 @@
+//! Writes the artwork of a document as a standalone SVG file meant to be read and edited by people and code, rather than the SVG rendered for the viewport.
+//!
+//! Folders become groups, shape and text layers become paths, and images are embedded as data URLs. The [SvgExportOptions] control how the markup is written,
+//! from the precision of its numbers to whether the styles are given by attributes or CSS classes.
+
+use graphene::color::{Color, ColorSpace};
+use graphene::layers::blend_mode::BlendMode;
+use graphene::layers::layer_info::{Layer, LayerDataType};
+use graphene::layers::style::{Fill, Gradient, GradientSpread, GradientType, LineCap, LineJoin, PathStyle, RenderQuality, Stroke};
+use graphene::layers::text_layer::FontCache;
+use graphene::layers::vector::subpath::Subpath;
+
+use glam::{DAffine2, DVec2};
+use kurbo::{BezPath, PathEl};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// The miter limit SVG uses when the `stroke-miterlimit` property is left out.
+const DEFAULT_MITER_LIMIT: f64 = 4.;
+
+/// How the fill, stroke, opacity, and blend mode of each element are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SvgStyleOutput {
+	/// Presentation attributes like `fill="#FF0000"`, which are the easiest to read and override with CSS
+	#[default]
+	Attributes,
+	/// A `style` attribute on each element, which takes precedence over CSS rules
+	InlineStyle,
+	/// A CSS class for each distinct style, declared once in a `<style>` element, which keeps files with many similar shapes small
+	Classes,
+}
+
+/// The options for how an SVG export is written, which matter to the code and tools consuming the SVG rather than to how it looks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SvgExportOptions {
+	/// The number of digits kept after the decimal point of coordinates and lengths, or `None` to write them at full precision
+	pub precision: Option<u32>,
+	/// Whether the transforms of groups are applied to the path data of the shapes inside them, leaving no `transform` attributes on groups or paths
+	pub flatten_transforms: bool,
+	pub style_output: SvgStyleOutput,
+	/// Whether layer names become the `id` of their elements, so they can be found with `getElementById` or styled with CSS
+	pub layer_name_ids: bool,
+}
+
+impl Default for SvgExportOptions {
+	fn default() -> Self {
+		Self {
+			precision: Some(3),
+			flatten_transforms: false,
+			style_output: SvgStyleOutput::default(),
+			layer_name_ids: false,
+		}
+	}
+}
+
+pub struct SvgExportSettings<'a> {
+	pub font_cache: &'a FontCache,
+	/// The color space that colors are written in as CSS `color()` values, or `None` to write them as sRGB hex colors
+	pub wide_gamut_color_space: Option<ColorSpace>,
+	/// The color space that the artwork is composited in
+	pub color_space: ColorSpace,
+	pub render_quality: RenderQuality,
+	pub options: SvgExportOptions,
+}
+
+/// Builds an SVG showing the area of the document within `bounds`, where the layers of each root are drawn on top of those of the roots before it, filled by the `background` color if any.
+pub fn svg_document(roots: &[&Layer], bounds: [DVec2; 2], background: Option<Color>, settings: &SvgExportSettings) -> String {
+	let mut writer = SvgWriter {
+		settings,
+		body: String::new(),
+		defs: String::new(),
+		classes: Vec::new(),
+		ids: HashSet::new(),
+		gradients: 0,
+	};
+	let size = bounds[1] - bounds[0];
+
+	if let Some(background) = background {
+		let mut declarations = vec![("fill", background.rgb_css(settings.wide_gamut_color_space))];
+		if let Some(opacity) = writer.opacity(background.a() as f64) {
+			declarations.push(("fill-opacity", opacity));
+		}
+		let rect = format!(
+			r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+			writer.number(bounds[0].x),
+			writer.number(bounds[0].y),
+			writer.number(size.x),
+			writer.number(size.y),
+			writer.style(&declarations)
+		);
+		writer.body.push_str(&rect);
+		writer.body.push('\n');
+	}
+	for root in roots {
+		// The roots themselves are the document and its artboards, which have no name or style of their own and whose transforms are removed for exporting
+		if let LayerDataType::Folder(folder) = &root.data {
+			for layer in folder.layers() {
+				writer.write_layer(layer, DAffine2::IDENTITY);
+			}
+		}
+	}
+
+	// The color space and quality settings of the document are inherited by every element
+	let mut attributes = String::new();
+	if settings.color_space != ColorSpace::default() {
+		let _ = write!(attributes, r#" color-interpolation="{}""#, settings.color_space.color_interpolation());
+	}
+	attributes.push_str(&settings.render_quality.render());
+
+	let mut svg = format!(
+		r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}"{}>"#,
+		writer.number(size.x),
+		writer.number(size.y),
+		writer.number(bounds[0].x),
+		writer.number(bounds[0].y),
+		writer.number(size.x),
+		writer.number(size.y),
+		attributes
+	);
+	svg.push('\n');
+	if !writer.classes.is_empty() {
+		svg.push_str("<style>\n");
+		for (index, declarations) in writer.classes.iter().enumerate() {
+			let _ = writeln!(svg, ".style-{} {{ {} }}", index + 1, declarations);
+		}
+		svg.push_str("</style>\n");
+	}
+	if !writer.defs.is_empty() {
+		let _ = writeln!(svg, "<defs>\n{}</defs>", writer.defs);
+	}
+	svg.push_str(&writer.body);
+	svg.push_str("</svg>\n");
+	svg
+}
+
+struct SvgWriter<'a> {
+	settings: &'a SvgExportSettings<'a>,
+	body: String,
+	defs: String,
+	/// The declarations of each CSS class, in the order they were first used
+	classes: Vec<String>,
+	/// The element IDs given out so far, which must be unique within the file
+	ids: HashSet<String>,
+	gradients: usize,
+}
+
+impl SvgWriter<'_> {
+	/// Writes a layer, given the transform from the space of its parent to that of the element it's written in, which is only carried down when flattening transforms.
+	fn write_layer(&mut self, layer: &Layer, transform: DAffine2) {
+		if !layer.visible {
+			return;
+		}
+		let flatten = self.settings.options.flatten_transforms;
+
+		let mut declarations = Vec::new();
+		if let Some(opacity) = self.opacity(layer.opacity) {
+			declarations.push(("opacity", opacity));
+		}
+		if layer.blend_mode != BlendMode::Normal {
+			declarations.push(("mix-blend-mode", layer.blend_mode.to_svg_style_name().to_string()));
+		}
+
+		match &layer.data {
+			LayerDataType::Folder(folder) => {
+				let (transform_attribute, transform) = match flatten {
+					true => (String::new(), transform * layer.transform),
+					false => (self.transform_attribute(layer.transform), DAffine2::IDENTITY),
+				};
+				let group = format!("<g{}{}{}>", self.id(layer), transform_attribute, self.style(&declarations));
+				self.body.push_str(&group);
+				self.body.push('\n');
+				for child in folder.layers() {
+					self.write_layer(child, transform);
+				}
+				self.body.push_str("</g>\n");
+			}
+			LayerDataType::Shape(shape) => self.write_path(layer, &shape.shape.with_rounded_corners(), &shape.style, transform, declarations),
+			LayerDataType::Text(text) => self.write_path(layer, &text.to_subpath_nonmut(self.settings.font_cache), &text.path_style, transform, declarations),
+			LayerDataType::Image(image) => self.write_image(layer, &image.mime, &image.image_data, image.dimensions, transform, declarations),
+			LayerDataType::Imaginate(imaginate) => {
+				if let Some(image_data) = &imaginate.image_data {
+					self.write_image(layer, &imaginate.mime, &image_data.image_data, imaginate.dimensions, transform, declarations);
+				}
+			}
+		}
+	}
+
+	/// Writes a path, whose geometry is in the layer's space but whose stroke is drawn in the space of its parent, matching the SVG renderer.
+	/// The layer's own transform is therefore always applied to the path data, and only the transforms of its parents can leave the stroke width to be scaled.
+	fn write_path(&mut self, layer: &Layer, subpath: &Subpath, style: &PathStyle, transform: DAffine2, declarations: Vec<(&'static str, String)>) {
+		// A stroke of varying width can't be written as an SVG stroke, so it's written as its outline filled with its color, grouped with the fill beneath it
+		if let Some(stroke) = style.stroke().filter(|stroke| stroke.has_width_profile() && stroke.is_visible()) {
+			let group = format!("<g{}{}>", self.id(layer), self.style(&declarations));
+			self.body.push_str(&group);
+			self.body.push('\n');
+			if style.fill().is_some() {
+				let mut fill_style = style.clone();
+				fill_style.clear_stroke();
+				self.write_path_element(layer, subpath, &fill_style, transform, String::new(), Vec::new());
+			}
+			if let Some(outline) = subpath.transformed_stroke_outline(&stroke, layer.transform) {
+				let outline_style = PathStyle::new(None, stroke.color().map_or(Fill::None, Fill::Solid));
+				self.write_path_element(layer, &outline, &outline_style, transform, String::new(), Vec::new());
+			}
+			self.body.push_str("</g>\n");
+			return;
+		}
+
+		let id = self.id(layer);
+		self.write_path_element(layer, subpath, style, transform, id, declarations);
+	}
+
+	fn write_path_element(&mut self, layer: &Layer, subpath: &Subpath, style: &PathStyle, transform: DAffine2, id: String, declarations: Vec<(&'static str, String)>) {
+		let to_output = transform * layer.transform;
+		if !to_output.is_finite() {
+			return;
+		}
+		let mut path = subpath.clone();
+		path.apply_affine(to_output);
+		let path_data = self.path_data(&BezPath::from(&path));
+		if path_data.is_empty() {
+			return;
+		}
+
+		let mut path_declarations = self.fill(style.fill(), subpath, to_output);
+		if let Some(stroke) = style.stroke() {
+			path_declarations.extend(self.stroke(&stroke, transform));
+		}
+		path_declarations.extend(declarations);
+
+		let element = format!(r#"<path{} d="{}"{}/>"#, id, path_data, self.style(&path_declarations));
+		self.body.push_str(&element);
+		self.body.push('\n');
+	}
+
+	fn write_image(&mut self, layer: &Layer, mime: &str, image_data: &[u8], dimensions: DVec2, transform: DAffine2, declarations: Vec<(&'static str, String)>) {
+		// Linked images whose files haven't been read yet have nothing to embed
+		if image_data.is_empty() || dimensions.x <= 0. || dimensions.y <= 0. {
+			return;
+		}
+		let to_output = transform * layer.transform;
+		if !to_output.is_finite() {
+			return;
+		}
+
+		// Images have no path data, so they keep a transform even when flattening
+		let element = format!(
+			r#"<image{} width="{}" height="{}"{}{} href="data:{};base64,{}"/>"#,
+			self.id(layer),
+			self.number(dimensions.x),
+			self.number(dimensions.y),
+			self.transform_attribute(to_output),
+			self.style(&declarations),
+			mime,
+			base64::encode(image_data)
+		);
+		self.body.push_str(&element);
+		self.body.push('\n');
+	}
+
+	/// The declarations of a fill, adding the definition of its gradient if it has one.
+	fn fill(&mut self, fill: &Fill, subpath: &Subpath, to_output: DAffine2) -> Vec<(&'static str, String)> {
+		let color_space = self.settings.wide_gamut_color_space;
+		match fill {
+			Fill::None => vec![("fill", "none".to_string())],
+			Fill::Solid(color) => {
+				let mut declarations = vec![("fill", color.rgb_css(color_space))];
+				if let Some(opacity) = self.opacity(color.a() as f64) {
+					declarations.push(("fill-opacity", opacity));
+				}
+				declarations
+			}
+			Fill::Gradient(gradient) => match subpath.bounding_box() {
+				Some(bounds) => {
+					let id = self.gradient(gradient, to_output, bounds);
+					vec![("fill", format!("url(#{id})"))]
+				}
+				None => vec![("fill", "none".to_string())],
+			},
+		}
+	}
+
+	/// Writes the definition of a gradient, whose positions are given relative to the bounding box of the layer's path, and returns its ID.
+	fn gradient(&mut self, gradient: &Gradient, to_output: DAffine2, [min, max]: [DVec2; 2]) -> String {
+		self.gradients += 1;
+		let id = format!("gradient-{}", self.gradients);
+
+		let to_output = to_output * DAffine2::from_scale_angle_translation(max - min, 0., min);
+		let (start, end) = (to_output.transform_point2(gradient.start), to_output.transform_point2(gradient.end));
+		let stops = gradient
+			.positions
+			.iter()
+			.filter_map(|&(position, color)| color.map(|color| (position, color)))
+			.map(|(position, color)| {
+				let opacity = self.opacity(color.a() as f64).map(|opacity| format!(r#" stop-opacity="{opacity}""#)).unwrap_or_default();
+				format!(
+					r#"<stop offset="{}" stop-color="{}"{}/>"#,
+					self.number(position),
+					color.rgb_css(self.settings.wide_gamut_color_space),
+					opacity
+				)
+			})
+			.collect::<String>();
+
+		let spread = match gradient.spread {
+			GradientSpread::Pad => String::new(),
+			spread => format!(r#" spreadMethod="{}""#, spread.svg_name()),
+		};
+
+		let definition = match gradient.gradient_type {
+			GradientType::Linear => format!(
+				r#"<linearGradient id="{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}"{}>{}</linearGradient>"#,
+				id,
+				self.number(start.x),
+				self.number(start.y),
+				self.number(end.x),
+				self.number(end.y),
+				spread,
+				stops
+			),
+			GradientType::Radial => format!(
+				r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}"{}>{}</radialGradient>"#,
+				id,
+				self.number(start.x),
+				self.number(start.y),
+				self.number(start.distance(end)),
+				spread,
+				stops
+			),
+			// Without an element for these gradients, they're approximated by polygons in a pattern covering the bounding box of the path
+			GradientType::Angular | GradientType::Diamond => {
+				let corners = [DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y].map(|corner| to_output.transform_point2(corner));
+				let area = [corners.into_iter().reduce(DVec2::min).unwrap(), corners.into_iter().reduce(DVec2::max).unwrap()];
+				let mut output_gradient = gradient.clone();
+				output_gradient.start = start;
+				output_gradient.end = end;
+				let polygons = output_gradient
+					.fallback_polygons(area)
+					.into_iter()
+					.map(|(polygon, color)| {
+						let points = polygon.iter().map(|point| format!("{},{}", self.number(point.x), self.number(point.y))).collect::<Vec<_>>().join(" ");
+						let opacity = self.opacity(color.a() as f64).map(|opacity| format!(r#" fill-opacity="{opacity}""#)).unwrap_or_default();
+						format!(r#"<polygon points="{}" fill="{}"{}/>"#, points, color.rgb_css(self.settings.wide_gamut_color_space), opacity)
+					})
+					.collect::<String>();
+				format!(
+					r#"<pattern id="{}" patternUnits="userSpaceOnUse" x="{}" y="{}" width="{}" height="{}" shape-rendering="crispEdges">{}</pattern>"#,
+					id,
+					self.number(area[0].x),
+					self.number(area[0].y),
+					self.number(area[1].x - area[0].x),
+					self.number(area[1].y - area[0].y),
+					polygons
+				)
+			}
+		};
+		self.defs.push_str(&definition);
+		self.defs.push('\n');
+		id
+	}
+
+	/// The declarations of a stroke, whose width and dashes are scaled by the part of the transform to the output space which isn't carried by a group.
+	fn stroke(&self, stroke: &Stroke, transform: DAffine2) -> Vec<(&'static str, String)> {
+		let color = match stroke.color() {
+			Some(color) if stroke.weight() > 0. => color,
+			_ => return Vec::new(),
+		};
+		let scale = transform.matrix2.determinant().abs().sqrt();
+
+		let mut declarations = vec![("stroke", color.rgb_css(self.settings.wide_gamut_color_space))];
+		if let Some(opacity) = self.opacity(color.a() as f64) {
+			declarations.push(("stroke-opacity", opacity));
+		}
+		declarations.push(("stroke-width", self.number(stroke.weight() * scale)));
+		// A pattern of only zero lengths draws a solid line, which is left out like in the renderer
+		if stroke.dash_pattern().iter().any(|&length| length > 0.) {
+			let lengths = stroke.dash_pattern().iter().map(|&length| self.number(length as f64 * scale)).collect::<Vec<_>>();
+			declarations.push(("stroke-dasharray", lengths.join(" ")));
+			if stroke.dash_offset() != 0. {
+				declarations.push(("stroke-dashoffset", self.number(stroke.dash_offset() * scale)));
+			}
+		}
+		if stroke.line_cap() != LineCap::Butt {
+			declarations.push(("stroke-linecap", stroke.line_cap().to_string()));
+		}
+		if stroke.line_join() != LineJoin::Miter {
+			declarations.push(("stroke-linejoin", stroke.line_join().to_string()));
+		} else if stroke.line_join_miter_limit() as f64 != DEFAULT_MITER_LIMIT {
+			declarations.push(("stroke-miterlimit", self.number(stroke.line_join_miter_limit() as f64)));
+		}
+		declarations
+	}
+
+	/// The attributes which give an element its styling, in the form chosen by the options.
+	fn style(&mut self, declarations: &[(&'static str, String)]) -> String {
+		if declarations.is_empty() {
+			return String::new();
+		}
+		let css = || declarations.iter().map(|(property, value)| format!("{property}: {value};")).collect::<Vec<_>>().join(" ");
+
+		match self.settings.options.style_output {
+			SvgStyleOutput::Attributes => {
+				// Blend modes are only a CSS property, with no presentation attribute of their own
+				let (attributes, properties): (Vec<_>, Vec<_>) = declarations.iter().partition(|(property, _)| *property != "mix-blend-mode");
+				let mut style = attributes.iter().map(|(property, value)| format!(r#" {property}="{value}""#)).collect::<String>();
+				if let Some((property, value)) = properties.first() {
+					let _ = write!(style, r#" style="{property}: {value};""#);
+				}
+				style
+			}
+			SvgStyleOutput::InlineStyle => format!(r#" style="{}""#, css()),
+			SvgStyleOutput::Classes => {
+				let css = css();
+				let index = match self.classes.iter().position(|declarations| *declarations == css) {
+					Some(index) => index,
+					None => {
+						self.classes.push(css);
+						self.classes.len() - 1
+					}
+				};
+				format!(r#" class="style-{}""#, index + 1)
+			}
+		}
+	}
+
+	/// The `id` attribute made from the layer's name, if the options ask for it and the layer has a name.
+	/// Characters which can't be used in CSS selectors are replaced by hyphens, and names used more than once are numbered to keep the IDs unique.
+	fn id(&mut self, layer: &Layer) -> String {
+		if !self.settings.options.layer_name_ids {
+			return String::new();
+		}
+		let name = match layer.name.as_deref().map(str::trim) {
+			Some(name) if !name.is_empty() => name,
+			_ => return String::new(),
+		};
+
+		let mut id = String::new();
+		for character in name.chars() {
+			if character.is_ascii_alphanumeric() || character == '_' || character == '-' {
+				id.push(character);
+			} else if !id.ends_with('-') {
+				id.push('-');
+			}
+		}
+		let id = id.trim_matches('-');
+		// IDs which don't start with a letter or underscore aren't valid XML names
+		let id = match id.chars().next() {
+			Some(first) if first.is_ascii_alphabetic() || first == '_' => id.to_string(),
+			_ => format!("layer-{id}").trim_end_matches('-').to_string(),
+		};
+
+		let mut unique_id = id.clone();
+		let mut count = 1;
+		while self.ids.contains(&unique_id) {
+			count += 1;
+			unique_id = format!("{id}-{count}");
+		}
+		self.ids.insert(unique_id.clone());
+		format!(r#" id="{unique_id}""#)
+	}
+
+	fn transform_attribute(&self, transform: DAffine2) -> String {
+		if transform == DAffine2::IDENTITY {
+			return String::new();
+		}
+		let [a, b, c, d, e, f] = transform.to_cols_array();
+		// The linear part of the matrix is kept at full precision, since rounding it distorts shapes far from the origin
+		let linear = [a, b, c, d].map(|value| value.to_string()).join(" ");
+		format!(r#" transform="matrix({} {} {})""#, linear, self.number(e), self.number(f))
+	}
+
+	fn path_data(&self, path: &BezPath) -> String {
+		let mut data = String::new();
+		let point = |point: kurbo::Point| format!("{},{}", self.number(point.x), self.number(point.y));
+		for element in path.elements() {
+			match *element {
+				PathEl::MoveTo(to) => data.push_str(&format!("M{}", point(to))),
+				PathEl::LineTo(to) => data.push_str(&format!("L{}", point(to))),
+				PathEl::QuadTo(control, to) => data.push_str(&format!("Q{} {}", point(control), point(to))),
+				PathEl::CurveTo(first, second, to) => data.push_str(&format!("C{} {} {}", point(first), point(second), point(to))),
+				PathEl::ClosePath => data.push('Z'),
+			}
+		}
+		data
+	}
+
+	/// An opacity to write, which is left out when the element is opaque.
+	fn opacity(&self, opacity: f64) -> Option<String> {
+		let opacity = self.number(opacity.clamp(0., 1.));
+		(opacity != "1").then_some(opacity)
+	}
+
+	/// A number at the precision given by the options, without trailing zeros.
+	fn number(&self, value: f64) -> String {
+		let precision = match self.settings.options.precision {
+			Some(precision) => precision as usize,
+			None => return value.to_string(),
+		};
+		let rounded = format!("{value:.precision$}");
+		let rounded = match rounded.contains('.') {
+			true => rounded.trim_end_matches('0').trim_end_matches('.'),
+			false => rounded.as_str(),
+		};
+		match rounded {
+			"-0" => "0".to_string(),
+			rounded => rounded.to_string(),
+		}
+	}
+}
@@ -0,0 +1,59 @@
+//! How the transforms of a document's layers are constrained by default, saved with the document so everyone editing it gets the same behavior.
+
+use crate::consts::{BIG_NUDGE_AMOUNT, NUDGE_AMOUNT};
+
+use serde::{Deserialize, Serialize};
+
+/// The point that resizing the selection with its bounding box scales around, unless the center key is held to use the other one.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TransformOrigin {
+	/// The edge or corner opposite the one being dragged stays in place
+	#[default]
+	OppositeEdge,
+	/// The pivot of the selection stays in place
+	Pivot,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformSettings {
+	/// Whether resizing and scaling keeps the aspect ratio of the selection, unless the constrain key is held to unlock it
+	pub lock_aspect_ratio: bool,
+	pub transform_origin: TransformOrigin,
+	/// The distance in pixels that the arrow keys move the selection by
+	pub nudge_distance: f64,
+	/// The distance in pixels that the arrow keys move the selection by while Shift is held
+	pub big_nudge_distance: f64,
+}
+
+impl Default for TransformSettings {
+	fn default() -> Self {
+		Self {
+			lock_aspect_ratio: false,
+			transform_origin: TransformOrigin::OppositeEdge,
+			nudge_distance: NUDGE_AMOUNT,
+			big_nudge_distance: BIG_NUDGE_AMOUNT,
+		}
+	}
+}
+
+impl TransformSettings {
+	/// Whether resizing keeps the aspect ratio, given whether the constrain key is held.
+	pub fn constrain_aspect_ratio(&self, constrain_key: bool) -> bool {
+		self.lock_aspect_ratio != constrain_key
+	}
+
+	/// Whether resizing scales around the pivot, given whether the center key is held.
+	pub fn resize_around_pivot(&self, center_key: bool) -> bool {
+		(self.transform_origin == TransformOrigin::Pivot) != center_key
+	}
+
+	/// Converts a nudge from the keyboard shortcuts, which are given in the default distances, into the distances of the document.
+	pub fn nudge(&self, delta: f64) -> f64 {
+		if delta.abs() >= BIG_NUDGE_AMOUNT {
+			delta / BIG_NUDGE_AMOUNT * self.big_nudge_distance
+		} else {
+			delta / NUDGE_AMOUNT * self.nudge_distance
+		}
+	}
+}
@@ -1,4 +1,5 @@
 use crate::consts::{ROTATE_SNAP_ANGLE, SCALE_SNAP_INTERVAL};
+use crate::messages::portfolio::document::utility_types::misc::PixelSnapping;
 use crate::messages::prelude::*;
 
 use graphene::document::Document;
@@ -152,10 +153,13 @@ impl Default for TransformOperation {
 }
 
 impl TransformOperation {
-	pub fn apply_transform_operation(&self, selected: &mut Selected, snapping: bool) {
+	pub fn apply_transform_operation(&self, selected: &mut Selected, snapping: bool, pixel_snapping: PixelSnapping) {
 		if self != &TransformOperation::None {
 			let transformation = match self {
-				TransformOperation::Grabbing(translation) => DAffine2::from_translation(translation.to_dvec()),
+				TransformOperation::Grabbing(translation) => {
+					let document_to_viewport = selected.document.root.transform;
+					DAffine2::from_translation(pixel_snapping.snap_viewport_delta(translation.to_dvec(), document_to_viewport))
+				}
 				TransformOperation::Rotating(rotation) => DAffine2::from_angle(rotation.to_f64(snapping)),
 				TransformOperation::Scaling(scale) => DAffine2::from_scale(scale.to_dvec(snapping)),
 				TransformOperation::None => unreachable!(),
@@ -165,7 +169,7 @@ impl TransformOperation {
 		}
 	}
 
-	pub fn constrain_axis(&mut self, axis: Axis, selected: &mut Selected, snapping: bool) {
+	pub fn constrain_axis(&mut self, axis: Axis, selected: &mut Selected, snapping: bool, pixel_snapping: PixelSnapping) {
 		match self {
 			TransformOperation::None => (),
 			TransformOperation::Grabbing(translation) => translation.constraint.set_or_toggle(axis),
@@ -173,10 +177,10 @@ impl TransformOperation {
 			TransformOperation::Scaling(scale) => scale.constraint.set_or_toggle(axis),
 		};
 
-		self.apply_transform_operation(selected, snapping);
+		self.apply_transform_operation(selected, snapping, pixel_snapping);
 	}
 
-	pub fn handle_typed(&mut self, typed: Option<f64>, selected: &mut Selected, snapping: bool) {
+	pub fn handle_typed(&mut self, typed: Option<f64>, selected: &mut Selected, snapping: bool, pixel_snapping: PixelSnapping) {
 		match self {
 			TransformOperation::None => (),
 			TransformOperation::Grabbing(translation) => translation.typed_distance = typed,
@@ -184,7 +188,7 @@ impl TransformOperation {
 			TransformOperation::Scaling(scale) => scale.typed_factor = typed,
 		};
 
-		self.apply_transform_operation(selected, snapping);
+		self.apply_transform_operation(selected, snapping, pixel_snapping);
 	}
 }
 
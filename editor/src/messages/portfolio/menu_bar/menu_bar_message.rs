@@ -8,4 +8,8 @@ use serde::{Deserialize, Serialize};
 pub enum MenuBarMessage {
 	// Messages
 	SendLayout,
+	/// Lists the documents recently opened in the desktop build, most recent first, under File > Open Recent
+	SetRecentDocuments {
+		paths: Vec<String>,
+	},
 }
@@ -1,3 +1,4 @@
+use crate::import_export::palette_file::PaletteFormat;
 use crate::messages::input_mapper::utility_types::macros::action_keys;
 use crate::messages::layout::utility_types::layout_widget::{Layout, PropertyHolder};
 use crate::messages::layout::utility_types::misc::LayoutTarget;
@@ -5,8 +6,15 @@ use crate::messages::layout::utility_types::widgets::menu_widgets::{MenuBarEntry
 use crate::messages::portfolio::document::utility_types::clipboards::Clipboard;
 use crate::messages::prelude::*;
 
+use graphene::layers::style::{EffectQuality, ImageFilterQuality};
+
+use glam::DVec2;
+
 #[derive(Debug, Clone, Default)]
-pub struct MenuBarMessageHandler {}
+pub struct MenuBarMessageHandler {
+	/// The paths of the documents recently opened in the desktop build, which is empty in the web build
+	recent_documents: Vec<String>,
+}
 
 impl MessageHandler<MenuBarMessage, ()> for MenuBarMessageHandler {
 	#[remain::check]
@@ -16,6 +24,10 @@ impl MessageHandler<MenuBarMessage, ()> for MenuBarMessageHandler {
 		#[remain::sorted]
 		match message {
 			SendLayout => self.register_properties(responses, LayoutTarget::MenuBar),
+			SetRecentDocuments { paths } => {
+				self.recent_documents = paths;
+				self.register_properties(responses, LayoutTarget::MenuBar);
+			}
 		}
 	}
 
@@ -24,6 +36,39 @@ impl MessageHandler<MenuBarMessage, ()> for MenuBarMessageHandler {
 	}
 }
 
+impl MenuBarMessageHandler {
+	/// The Open Recent submenu, which is left out when no documents were recently opened
+	fn open_recent_entry(&self) -> Option<MenuBarEntry> {
+		if self.recent_documents.is_empty() {
+			return None;
+		}
+
+		let documents = self
+			.recent_documents
+			.iter()
+			.map(|path| {
+				let document_path = path.clone();
+				MenuBarEntry {
+					label: path.clone(),
+					action: MenuBarEntry::create_action(move |_| PortfolioMessage::OpenRecentDocument { path: document_path.clone() }.into()),
+					..MenuBarEntry::default()
+				}
+			})
+			.collect();
+		let clear = MenuBarEntry {
+			label: "Clear Recent".into(),
+			action: MenuBarEntry::create_action(|_| PortfolioMessage::ClearRecentDocuments.into()),
+			..MenuBarEntry::default()
+		};
+
+		Some(MenuBarEntry {
+			label: "Open Recent".into(),
+			children: MenuBarEntryChildren(vec![documents, vec![clear]]),
+			..MenuBarEntry::default()
+		})
+	}
+}
+
 impl PropertyHolder for MenuBarMessageHandler {
 	fn properties(&self) -> Layout {
 		Layout::MenuLayout(MenuLayout::new(vec![
@@ -49,7 +94,10 @@ impl PropertyHolder for MenuBarMessageHandler {
 							action: MenuBarEntry::create_action(|_| PortfolioMessage::OpenDocument.into()),
 							..MenuBarEntry::default()
 						},
-					],
+					]
+					.into_iter()
+					.chain(self.open_recent_entry())
+					.collect(),
 					vec![
 						MenuBarEntry {
 							label: "Close".into(),
@@ -70,6 +118,23 @@ impl PropertyHolder for MenuBarMessageHandler {
 						action: MenuBarEntry::create_action(|_| DocumentMessage::SaveDocument.into()),
 						..MenuBarEntry::default()
 					}],
+					vec![
+						MenuBarEntry {
+							label: "Start Collaboration Session…".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::StartCollaboration.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Join Collaboration Session…".into(),
+							action: MenuBarEntry::create_action(|_| DialogMessage::RequestJoinCollaborationDialog.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Stop Collaborating".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::StopCollaboration.into()),
+							..MenuBarEntry::default()
+						},
+					],
 					vec![
 						MenuBarEntry {
 							label: "Import…".into(),
@@ -77,20 +142,51 @@ impl PropertyHolder for MenuBarMessageHandler {
 							action: MenuBarEntry::create_action(|_| PortfolioMessage::Import.into()),
 							..MenuBarEntry::default()
 						},
+						MenuBarEntry {
+							label: "Import Linked…".into(),
+							shortcut: action_keys!(PortfolioMessageDiscriminant::ImportLinked),
+							action: MenuBarEntry::create_action(|_| PortfolioMessage::ImportLinked.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Load Font…".into(),
+							action: MenuBarEntry::create_action(|_| PortfolioMessage::ImportFont.into()),
+							..MenuBarEntry::default()
+						},
 						MenuBarEntry {
 							label: "Export…".into(),
 							shortcut: action_keys!(DialogMessageDiscriminant::RequestExportDialog),
 							action: MenuBarEntry::create_action(|_| DialogMessage::RequestExportDialog.into()),
 							..MenuBarEntry::default()
 						},
+						MenuBarEntry {
+							label: "Batch Export…".into(),
+							shortcut: action_keys!(DialogMessageDiscriminant::RequestBatchExportDialog),
+							action: MenuBarEntry::create_action(|_| DialogMessage::RequestBatchExportDialog.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Export Sprite Sheet…".into(),
+							shortcut: action_keys!(DialogMessageDiscriminant::RequestSpriteSheetDialog),
+							action: MenuBarEntry::create_action(|_| DialogMessage::RequestSpriteSheetDialog.into()),
+							..MenuBarEntry::default()
+						},
+					],
+					vec![
+						MenuBarEntry {
+							label: "Preferences…".into(),
+							icon: Some("Settings".into()),
+							shortcut: action_keys!(DialogMessageDiscriminant::RequestPreferencesDialog),
+							action: MenuBarEntry::create_action(|_| DialogMessage::RequestPreferencesDialog.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Keyboard Shortcuts…".into(),
+							shortcut: action_keys!(DialogMessageDiscriminant::RequestKeyboardShortcutsDialog),
+							action: MenuBarEntry::create_action(|_| DialogMessage::RequestKeyboardShortcutsDialog.into()),
+							..MenuBarEntry::default()
+						},
 					],
-					vec![MenuBarEntry {
-						label: "Preferences…".into(),
-						icon: Some("Settings".into()),
-						shortcut: action_keys!(DialogMessageDiscriminant::RequestPreferencesDialog),
-						action: MenuBarEntry::create_action(|_| DialogMessage::RequestPreferencesDialog.into()),
-						..MenuBarEntry::default()
-					}],
 				]),
 			),
 			MenuBarEntry::new_root(
@@ -150,6 +246,18 @@ impl PropertyHolder for MenuBarMessageHandler {
 							action: MenuBarEntry::create_action(|_| DocumentMessage::DeselectAllLayers.into()),
 							..MenuBarEntry::default()
 						},
+						MenuBarEntry {
+							label: "Select Inverse".into(),
+							shortcut: action_keys!(DocumentMessageDiscriminant::SelectInverse),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::SelectInverse.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Reselect Previous".into(),
+							shortcut: action_keys!(DocumentMessageDiscriminant::ReselectPrevious),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::ReselectPrevious.into()),
+							..MenuBarEntry::default()
+						},
 					],
 					vec![MenuBarEntry {
 						label: "Delete Selected".into(),
@@ -178,6 +286,40 @@ impl PropertyHolder for MenuBarMessageHandler {
 							..MenuBarEntry::default()
 						},
 					],
+					vec![
+						MenuBarEntry {
+							label: "Simplify Path…".into(),
+							action: MenuBarEntry::create_action(|_| DialogMessage::RequestSimplifyPathDialog.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Offset Path…".into(),
+							action: MenuBarEntry::create_action(|_| DialogMessage::RequestOffsetPathDialog.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Outline Stroke".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::OutlineSelectedStrokes.into()),
+							..MenuBarEntry::default()
+						},
+					],
+					vec![
+						MenuBarEntry {
+							label: "Flatten Transform".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::FlattenSelectedTransforms.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Extract Transform".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::ExtractSelectedTransforms.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Snap to Pixel Grid".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::SnapSelectedToPixelGrid.into()),
+							..MenuBarEntry::default()
+						},
+					],
 					vec![MenuBarEntry {
 						label: "Order".into(),
 						action: MenuBarEntry::no_action(),
@@ -209,15 +351,117 @@ impl PropertyHolder for MenuBarMessageHandler {
 						]]),
 						..MenuBarEntry::default()
 					}],
+					vec![
+						MenuBarEntry {
+							label: "Isolate Folder".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::IsolateSelectedFolder.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Exit Isolation".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::SetIsolatedFolder { folder_path: None }.into()),
+							..MenuBarEntry::default()
+						},
+					],
+					vec![
+						MenuBarEntry {
+							label: "Add Export Slice from Selection".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::AddSelectionExportSlice.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Mark Selected as Exportable".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::MarkSelectedLayersExportable.into()),
+							..MenuBarEntry::default()
+						},
+					],
+					vec![
+						MenuBarEntry {
+							label: "Refresh Linked Images".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::RefreshLinkedImages { request_permission: true }.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Relink Image…".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::RelinkSelectedImage.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Embed Linked Images".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::EmbedSelectedImages.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Trace Image…".into(),
+							action: MenuBarEntry::create_action(|_| DialogMessage::RequestTraceImageDialog.into()),
+							..MenuBarEntry::default()
+						},
+					],
 				]),
 			),
 			MenuBarEntry::new_root(
 				"Document".into(),
-				MenuBarEntryChildren(vec![vec![MenuBarEntry {
-					label: "Clear Artboards".into(),
-					action: MenuBarEntry::create_action(|_| ArtboardMessage::ClearArtboards.into()),
-					..MenuBarEntry::default()
-				}]]),
+				MenuBarEntryChildren(vec![
+					vec![MenuBarEntry {
+						label: "Document Properties…".into(),
+						shortcut: action_keys!(DialogMessageDiscriminant::RequestDocumentPropertiesDialog),
+						action: MenuBarEntry::create_action(|_| DialogMessage::RequestDocumentPropertiesDialog.into()),
+						..MenuBarEntry::default()
+					}],
+					vec![MenuBarEntry {
+						label: "Clear Artboards".into(),
+						action: MenuBarEntry::create_action(|_| ArtboardMessage::ClearArtboards.into()),
+						..MenuBarEntry::default()
+					}],
+					vec![MenuBarEntry {
+						label: "Embed Fonts".into(),
+						action: MenuBarEntry::create_action(|_| DocumentMessage::EmbedFonts.into()),
+						..MenuBarEntry::default()
+					}],
+					vec![
+						MenuBarEntry {
+							label: "Import Palette…".into(),
+							action: MenuBarEntry::create_action(|_| ColorStylesMessage::ImportPalette.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Export Palette".into(),
+							action: MenuBarEntry::no_action(),
+							children: MenuBarEntryChildren(vec![vec![
+								MenuBarEntry {
+									label: "Adobe Swatch Exchange (.ase)".into(),
+									action: MenuBarEntry::create_action(|_| ColorStylesMessage::ExportPalette { format: PaletteFormat::Ase }.into()),
+									..MenuBarEntry::default()
+								},
+								MenuBarEntry {
+									label: "GIMP Palette (.gpl)".into(),
+									action: MenuBarEntry::create_action(|_| ColorStylesMessage::ExportPalette { format: PaletteFormat::Gpl }.into()),
+									..MenuBarEntry::default()
+								},
+							]]),
+							..MenuBarEntry::default()
+						},
+					],
+					vec![MenuBarEntry {
+						label: "Render Quality".into(),
+						action: MenuBarEntry::no_action(),
+						children: MenuBarEntryChildren(vec![
+							vec![
+								render_quality_entry("Anti-aliasing On", Some(true), None, None),
+								render_quality_entry("Anti-aliasing Off", Some(false), None, None),
+							],
+							vec![
+								render_quality_entry("Smooth Image Resampling", None, Some(ImageFilterQuality::Smooth), None),
+								render_quality_entry("Nearest Neighbor Image Resampling", None, Some(ImageFilterQuality::Nearest), None),
+							],
+							vec![
+								render_quality_entry("Full Effects While Dragging", None, None, Some(EffectQuality::Full)),
+								render_quality_entry("Draft Effects While Dragging", None, None, Some(EffectQuality::Draft)),
+							],
+						]),
+						..MenuBarEntry::default()
+					}],
+				]),
 			),
 			MenuBarEntry::new_root(
 				"View".into(),
@@ -242,6 +486,29 @@ impl PropertyHolder for MenuBarMessageHandler {
 							..MenuBarEntry::default()
 						},
 					],
+					vec![MenuBarEntry {
+						label: "New View".into(),
+						action: MenuBarEntry::create_action(|_| DocumentMessage::NewView.into()),
+						..MenuBarEntry::default()
+					}],
+					vec![
+						MenuBarEntry {
+							label: "Set Ruler Origin to Selection".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::SetRulerOriginToSelection.into()),
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Reset Ruler Origin".into(),
+							action: MenuBarEntry::create_action(|_| NavigationMessage::SetRulerOrigin { origin: DVec2::ZERO }.into()),
+							..MenuBarEntry::default()
+						},
+					],
+					vec![MenuBarEntry {
+						label: "Outline Mode".into(),
+						shortcut: action_keys!(DocumentMessageDiscriminant::ToggleOutlineViewMode),
+						action: MenuBarEntry::create_action(|_| DocumentMessage::ToggleOutlineViewMode.into()),
+						..MenuBarEntry::default()
+					}],
 					vec![MenuBarEntry {
 						label: "Node Graph (In Development)".into(),
 						action: MenuBarEntry::create_action(|_| WorkspaceMessage::NodeGraphToggleVisibility.into()),
@@ -331,3 +598,18 @@ impl PropertyHolder for MenuBarMessageHandler {
 		]))
 	}
 }
+
+fn render_quality_entry(label: &str, anti_aliasing: Option<bool>, image_filter: Option<ImageFilterQuality>, interactive_effects: Option<EffectQuality>) -> MenuBarEntry {
+	MenuBarEntry {
+		label: label.into(),
+		action: MenuBarEntry::create_action(move |_| {
+			DocumentMessage::SetRenderQuality {
+				anti_aliasing,
+				image_filter,
+				interactive_effects,
+			}
+			.into()
+		}),
+		..MenuBarEntry::default()
+	}
+}
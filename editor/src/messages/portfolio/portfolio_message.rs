@@ -29,6 +29,8 @@ pub enum PortfolioMessage {
 	AutoSaveDocument {
 		document_id: u64,
 	},
+	/// Forgets the documents recently opened in the desktop build
+	ClearRecentDocuments,
 	CloseActiveDocumentWithConfirmation,
 	CloseAllDocuments,
 	CloseDocument {
@@ -73,6 +75,9 @@ pub enum PortfolioMessage {
 		status: ImaginateServerStatus,
 	},
 	Import,
+	/// Picks a font file to load, so their fonts can be used by text alongside those of the font catalog
+	ImportFont,
+	ImportLinked,
 	LoadDocumentResources {
 		document_id: u64,
 	},
@@ -80,6 +85,10 @@ pub enum PortfolioMessage {
 		font: Font,
 		is_default: bool,
 	},
+	/// Loads a TrueType, OpenType, WOFF, or WOFF2 font file the user picked
+	LoadFontFile {
+		data: Vec<u8>,
+	},
 	NewDocumentWithName {
 		name: String,
 	},
@@ -87,13 +96,16 @@ pub enum PortfolioMessage {
 	OpenDocument,
 	OpenDocumentFile {
 		document_name: String,
-		document_serialized_content: String,
+		document_serialized_content: Vec<u8>,
 	},
 	OpenDocumentFileWithId {
 		document_id: u64,
 		document_name: String,
 		document_is_saved: bool,
-		document_serialized_content: String,
+		document_serialized_content: Vec<u8>,
+	},
+	OpenRecentDocument {
+		path: String,
 	},
 	// TODO: Paste message is unused, delete it?
 	Paste {
@@ -107,7 +119,15 @@ pub enum PortfolioMessage {
 	PasteSerializedData {
 		data: String,
 	},
+	PasteSvg {
+		svg: String,
+	},
 	PrevDocument,
+	/// Replaces an open document with the content of its file, after the file was changed by another app
+	ReloadDocumentFile {
+		document_id: u64,
+		document_serialized_content: Vec<u8>,
+	},
 	SelectDocument {
 		document_id: u64,
 	},
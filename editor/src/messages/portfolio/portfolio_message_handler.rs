@@ -1,11 +1,14 @@
 use super::utility_types::PersistentData;
 use crate::application::generate_uuid;
 use crate::consts::{DEFAULT_DOCUMENT_NAME, GRAPHITE_DOCUMENT_VERSION};
+use crate::import_export::font_file::decode_font_file;
+use crate::import_export::svg_import::embedded_layer_data;
 use crate::messages::dialog::simple_dialogs;
 use crate::messages::frontend::utility_types::FrontendDocumentDetails;
 use crate::messages::layout::utility_types::layout_widget::PropertyHolder;
 use crate::messages::layout::utility_types::misc::LayoutTarget;
 use crate::messages::portfolio::document::utility_types::clipboards::{Clipboard, CopyBufferEntry, INTERNAL_CLIPBOARD_COUNT};
+use crate::messages::portfolio::document::utility_types::error::EditorError;
 use crate::messages::portfolio::utility_types::ImaginateServerStatus;
 use crate::messages::prelude::*;
 
@@ -67,6 +70,10 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 					.into(),
 				)
 			}
+			PortfolioMessage::ClearRecentDocuments => {
+				responses.push_back(MenuBarMessage::SetRecentDocuments { paths: Vec::new() }.into());
+				responses.push_back(FrontendMessage::TriggerClearRecentDocuments.into());
+			}
 			PortfolioMessage::CloseActiveDocumentWithConfirmation => {
 				if let Some(document_id) = self.active_document_id {
 					responses.push_back(PortfolioMessage::CloseDocumentWithConfirmation { document_id }.into());
@@ -82,13 +89,18 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 				for document_id in &self.document_ids {
 					responses.push_back(FrontendMessage::TriggerIndexedDbRemoveDocument { document_id: *document_id }.into());
 				}
+				for collaboration in self.documents.values().filter_map(|document| document.collaboration.as_ref()) {
+					collaboration.leave(responses);
+				}
 
 				responses.push_back(PortfolioMessage::DestroyAllDocuments.into());
 				responses.push_back(PortfolioMessage::UpdateOpenDocumentsList.into());
 			}
 			PortfolioMessage::CloseDocument { document_id } => {
 				let document_index = self.document_index(document_id);
-				self.documents.remove(&document_id);
+				if let Some(collaboration) = self.documents.remove(&document_id).and_then(|document| document.collaboration) {
+					collaboration.leave(responses);
+				}
 				self.document_ids.remove(document_index);
 
 				if self.document_ids.is_empty() {
@@ -158,9 +170,13 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 					if clipboard == Clipboard::Device {
 						let mut buffer = Vec::new();
 						copy_val(&mut buffer);
-						let mut copy_text = String::from("graphite/layer: ");
-						copy_text += &serde_json::to_string(&buffer).expect("Could not serialize paste");
+						let layer_data = serde_json::to_string(&buffer).expect("Could not serialize paste");
 
+						// The layers are copied as SVG markup other apps can paste, falling back to just the layer data when nothing visible is selected
+						let copy_text = match active_document.selected_layers_svg(&layer_data, &self.persistent_data.font_cache) {
+							Some(svg) => svg,
+							None => format!("graphite/layer: {layer_data}"),
+						};
 						responses.push_back(FrontendMessage::TriggerTextCopy { copy_text }.into());
 					} else {
 						let copy_buffer = &mut self.copy_buffer;
@@ -241,7 +257,28 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 					responses.push_back(FrontendMessage::TriggerImport.into());
 				}
 			}
+			PortfolioMessage::ImportFont => {
+				responses.push_back(FrontendMessage::TriggerImportFont.into());
+			}
+			PortfolioMessage::ImportLinked => {
+				if self.active_document().is_some() {
+					responses.push_back(FrontendMessage::TriggerImportLinked.into());
+				}
+			}
 			PortfolioMessage::LoadDocumentResources { document_id } => {
+				if let Some(document) = self.documents.get(&document_id) {
+					let embedded_fonts = &document.graphene_document.embedded_fonts;
+					for embedded_font in embedded_fonts {
+						if !self.persistent_data.font_cache.loaded_font(&embedded_font.font) {
+							self.persistent_data.font_cache.insert_custom(embedded_font.font.clone(), embedded_font.data.clone());
+						}
+					}
+					if !embedded_fonts.is_empty() {
+						let fonts = self.persistent_data.font_cache.custom_fonts().to_vec();
+						responses.push_back(FrontendMessage::UpdateCustomFonts { fonts }.into());
+					}
+				}
+
 				if let Some(document) = self.document_mut(document_id) {
 					document.load_layer_resources(responses, &document.graphene_document.root.data, Vec::new(), document_id);
 				}
@@ -251,6 +288,33 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 					responses.push_front(FrontendMessage::TriggerFontLoad { font, is_default }.into());
 				}
 			}
+			PortfolioMessage::LoadFontFile { data } => {
+				let font = decode_font_file(&data).and_then(|data| {
+					let font = Font::from_font_file(&data).ok_or_else(|| EditorError::Misc("The font file has no usable font in it".into()))?;
+					Ok((font, data))
+				});
+
+				match font {
+					Ok((font, data)) => {
+						self.persistent_data.font_cache.insert_custom(font, data);
+						let fonts = self.persistent_data.font_cache.custom_fonts().to_vec();
+						responses.push_back(FrontendMessage::UpdateCustomFonts { fonts }.into());
+
+						// Text with characters the new font has may have been drawn by a fallback font, or not at all
+						if let Some(document) = self.active_document_mut() {
+							document.graphene_document.mark_all_layers_of_type_as_dirty(LayerDataTypeDiscriminant::Text);
+							responses.push_back(DocumentMessage::RenderDocument.into());
+						}
+					}
+					Err(error) => responses.push_back(
+						DialogMessage::DisplayDialogError {
+							title: "Failed to load font".into(),
+							description: error.to_string(),
+						}
+						.into(),
+					),
+				}
+			}
 			PortfolioMessage::NewDocumentWithName { name } => {
 				let new_document = DocumentMessageHandler::with_name(name, ipp);
 				let document_id = generate_uuid();
@@ -310,6 +374,9 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 				}
 			}
 			// TODO: Paste message is unused, delete it?
+			PortfolioMessage::OpenRecentDocument { path } => {
+				responses.push_back(FrontendMessage::TriggerOpenRecentDocument { path }.into());
+			}
 			PortfolioMessage::Paste { clipboard } => {
 				let shallowest_common_folder = self.active_document().map(|document| {
 					document
@@ -406,6 +473,13 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 					}
 				}
 			}
+			PortfolioMessage::PasteSvg { svg } => {
+				// SVGs copied from Graphite recreate their layers exactly, while others are imported as editable layers
+				match embedded_layer_data(&svg) {
+					Some(data) => responses.push_back(PortfolioMessage::PasteSerializedData { data }.into()),
+					None => responses.push_back(DocumentMessage::ImportSvg { svg, mouse: None }.into()),
+				}
+			}
 			PortfolioMessage::PrevDocument => {
 				if let Some(active_document_id) = self.active_document_id {
 					let len = self.document_ids.len();
@@ -415,6 +489,34 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 					responses.push_back(PortfolioMessage::SelectDocument { document_id: prev_id }.into());
 				}
 			}
+			PortfolioMessage::ReloadDocumentFile {
+				document_id,
+				document_serialized_content,
+			} => {
+				if let Some(name) = self.documents.get(&document_id).map(|document| document.name.clone()) {
+					match DocumentMessageHandler::with_name_and_content(name, document_serialized_content) {
+						Ok(mut document) => {
+							document.set_save_state(true);
+							self.documents.insert(document_id, document);
+							responses.push_back(PortfolioMessage::LoadDocumentResources { document_id }.into());
+
+							// Selecting the document again sends all of its layers and artwork to the frontend
+							if self.active_document_id == Some(document_id) {
+								responses.push_back(PortfolioMessage::SelectDocument { document_id }.into());
+							} else {
+								responses.push_back(PortfolioMessage::UpdateOpenDocumentsList.into());
+							}
+						}
+						Err(e) => responses.push_back(
+							DialogMessage::DisplayDialogError {
+								title: "Failed to reload document".to_string(),
+								description: e.to_string(),
+							}
+							.into(),
+						),
+					}
+				}
+			}
 			PortfolioMessage::SelectDocument { document_id } => {
 				if let Some(document) = self.active_document() {
 					if !document.is_saved() {
@@ -432,10 +534,17 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 					responses.push_back(BroadcastEvent::ToolAbort.into());
 				}
 
+				// The frontend holds the artwork of the previously active document, so the newly active one has to send all of its artwork again
+				if let Some(document) = self.documents.get_mut(&document_id) {
+					document.invalidate_sent_artwork();
+				}
+
 				// TODO: Remove this message in favor of having tools have specific data per document instance
 				responses.push_back(PortfolioMessage::SetActiveDocument { document_id }.into());
 				responses.push_back(PortfolioMessage::UpdateOpenDocumentsList.into());
 				responses.push_back(FrontendMessage::UpdateActiveDocument { document_id }.into());
+				let cmyk_profile = self.documents.get(&document_id).unwrap().graphene_document.cmyk_profile;
+				responses.push_back(FrontendMessage::UpdateCmykProfile { cmyk_profile }.into());
 				responses.push_back(DocumentMessage::RenderDocument.into());
 				responses.push_back(DocumentMessage::DocumentStructureChanged.into());
 				for layer in self.documents.get(&document_id).unwrap().layer_metadata.keys() {
@@ -489,6 +598,8 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 			CloseActiveDocumentWithConfirmation,
 			CloseAllDocuments,
 			Import,
+			ImportFont,
+			ImportLinked,
 			NextDocument,
 			OpenDocument,
 			Paste,
@@ -554,17 +665,18 @@ impl PortfolioMessageHandler {
 	}
 
 	// TODO Fix how this doesn't preserve tab order upon loading new document from *File > Load*
-	fn load_document(&mut self, new_document: DocumentMessageHandler, document_id: u64, responses: &mut VecDeque<Message>) {
+	fn load_document(&mut self, mut new_document: DocumentMessageHandler, document_id: u64, responses: &mut VecDeque<Message>) {
 		self.document_ids.push(document_id);
 
-		responses.extend(
-			new_document
-				.layer_metadata
-				.keys()
-				.filter_map(|path| new_document.layer_panel_entry_from_path(path, &self.persistent_data.font_cache))
-				.map(|entry| FrontendMessage::UpdateDocumentLayerDetails { data: entry }.into())
-				.collect::<Vec<_>>(),
-		);
+		let entries = new_document
+			.layer_metadata
+			.keys()
+			.filter_map(|path| new_document.layer_panel_entry_from_path(path, &self.persistent_data.font_cache))
+			.collect::<Vec<_>>();
+		for entry in entries {
+			new_document.request_layer_thumbnail(&entry, document_id, responses);
+			responses.push_back(FrontendMessage::UpdateDocumentLayerDetails { data: entry }.into());
+		}
 		new_document.update_layer_tree_options_bar_widgets(responses, &self.persistent_data.font_cache);
 
 		self.documents.insert(document_id, new_document);
@@ -1,3 +1,4 @@
+use crate::messages::input_mapper::utility_types::keymap::Keymap;
 use crate::messages::prelude::*;
 
 use serde::{Deserialize, Serialize};
@@ -8,6 +9,9 @@ pub enum PreferencesMessage {
 	Load { preferences: String },
 	ResetToDefaults,
 
+	CompressDocuments { compress: bool },
+	Keymap { keymap: Keymap },
+
 	ImaginateRefreshFrequency { seconds: f64 },
 	ImaginateServerHostname { hostname: String },
 }
@@ -1,3 +1,4 @@
+use crate::messages::input_mapper::utility_types::keymap::Keymap;
 use crate::messages::prelude::*;
 
 use serde::{Deserialize, Serialize};
@@ -6,6 +7,12 @@ use serde::{Deserialize, Serialize};
 pub struct PreferencesMessageHandler {
 	pub imaginate_server_hostname: String,
 	pub imaginate_refresh_frequency: f64,
+	/// Whether documents are saved in the compressed binary format instead of JSON
+	#[serde(default)]
+	pub compress_documents: bool,
+	/// The user's changes to the default keyboard shortcuts
+	#[serde(default)]
+	pub keymap: Keymap,
 }
 
 impl Default for PreferencesMessageHandler {
@@ -13,6 +20,8 @@ impl Default for PreferencesMessageHandler {
 		Self {
 			imaginate_server_hostname: "http://localhost:7860/".into(),
 			imaginate_refresh_frequency: 1.,
+			compress_documents: false,
+			keymap: Keymap::default(),
 		}
 	}
 }
@@ -36,6 +45,9 @@ impl MessageHandler<PreferencesMessage, ()> for PreferencesMessageHandler {
 				*self = Self::default()
 			}
 
+			PreferencesMessage::CompressDocuments { compress } => self.compress_documents = compress,
+			PreferencesMessage::Keymap { keymap } => self.keymap = keymap,
+
 			PreferencesMessage::ImaginateRefreshFrequency { seconds } => {
 				self.imaginate_refresh_frequency = seconds;
 				responses.push_back(PortfolioMessage::ImaginateCheckServerStatus.into());
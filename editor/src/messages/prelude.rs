@@ -4,9 +4,17 @@ pub use crate::utility_traits::{ActionList, AsMessage, MessageHandler, ToDiscrim
 // Message, MessageDiscriminant, MessageHandler
 pub use crate::messages::broadcast::{BroadcastMessage, BroadcastMessageDiscriminant, BroadcastMessageHandler};
 pub use crate::messages::debug::{DebugMessage, DebugMessageDiscriminant, DebugMessageHandler};
+pub use crate::messages::dialog::batch_export_dialog::{BatchExportDialogMessage, BatchExportDialogMessageDiscriminant, BatchExportDialogMessageHandler};
+pub use crate::messages::dialog::collaboration_dialog::{CollaborationDialogMessage, CollaborationDialogMessageDiscriminant, CollaborationDialogMessageHandler};
+pub use crate::messages::dialog::document_properties_dialog::{DocumentPropertiesDialogMessage, DocumentPropertiesDialogMessageDiscriminant, DocumentPropertiesDialogMessageHandler};
 pub use crate::messages::dialog::export_dialog::{ExportDialogMessage, ExportDialogMessageDiscriminant, ExportDialogMessageHandler};
+pub use crate::messages::dialog::keyboard_shortcuts_dialog::{KeyboardShortcutsDialogMessage, KeyboardShortcutsDialogMessageDiscriminant, KeyboardShortcutsDialogMessageHandler};
 pub use crate::messages::dialog::new_document_dialog::{NewDocumentDialogMessage, NewDocumentDialogMessageDiscriminant, NewDocumentDialogMessageHandler};
+pub use crate::messages::dialog::offset_path_dialog::{OffsetPathDialogMessage, OffsetPathDialogMessageDiscriminant, OffsetPathDialogMessageHandler};
 pub use crate::messages::dialog::preferences_dialog::{PreferencesDialogMessage, PreferencesDialogMessageDiscriminant, PreferencesDialogMessageHandler};
+pub use crate::messages::dialog::simplify_path_dialog::{SimplifyPathDialogMessage, SimplifyPathDialogMessageDiscriminant, SimplifyPathDialogMessageHandler};
+pub use crate::messages::dialog::sprite_sheet_dialog::{SpriteSheetDialogMessage, SpriteSheetDialogMessageDiscriminant, SpriteSheetDialogMessageHandler};
+pub use crate::messages::dialog::trace_image_dialog::{TraceImageDialogMessage, TraceImageDialogMessageDiscriminant, TraceImageDialogMessageHandler};
 pub use crate::messages::dialog::{DialogMessage, DialogMessageDiscriminant, DialogMessageHandler};
 pub use crate::messages::frontend::{FrontendMessage, FrontendMessageDiscriminant};
 pub use crate::messages::globals::{GlobalsMessage, GlobalsMessageDiscriminant, GlobalsMessageHandler};
@@ -14,9 +22,12 @@ pub use crate::messages::input_mapper::{InputMapperMessage, InputMapperMessageDi
 pub use crate::messages::input_preprocessor::{InputPreprocessorMessage, InputPreprocessorMessageDiscriminant, InputPreprocessorMessageHandler};
 pub use crate::messages::layout::{LayoutMessage, LayoutMessageDiscriminant, LayoutMessageHandler};
 pub use crate::messages::portfolio::document::artboard::{ArtboardMessage, ArtboardMessageDiscriminant, ArtboardMessageHandler};
+pub use crate::messages::portfolio::document::color_styles::{ColorStylesMessage, ColorStylesMessageDiscriminant, ColorStylesMessageHandler};
+pub use crate::messages::portfolio::document::layers_panel::{LayersPanelMessage, LayersPanelMessageDiscriminant, LayersPanelMessageHandler};
 pub use crate::messages::portfolio::document::navigation::{NavigationMessage, NavigationMessageDiscriminant, NavigationMessageHandler};
 pub use crate::messages::portfolio::document::overlays::{OverlaysMessage, OverlaysMessageDiscriminant, OverlaysMessageHandler};
 pub use crate::messages::portfolio::document::properties_panel::{PropertiesPanelMessage, PropertiesPanelMessageDiscriminant, PropertiesPanelMessageHandler};
+pub use crate::messages::portfolio::document::text_styles::{TextStylesMessage, TextStylesMessageDiscriminant, TextStylesMessageHandler};
 pub use crate::messages::portfolio::document::transform_layer::{TransformLayerMessage, TransformLayerMessageDiscriminant, TransformLayerMessageHandler};
 pub use crate::messages::portfolio::document::{DocumentMessage, DocumentMessageDiscriminant, DocumentMessageHandler};
 pub use crate::messages::portfolio::menu_bar::{MenuBarMessage, MenuBarMessageDiscriminant, MenuBarMessageHandler};
@@ -44,6 +55,7 @@ pub use crate::messages::tool::tool_messages::select_tool::{SelectToolMessage, S
 pub use crate::messages::tool::tool_messages::shape_tool::{ShapeToolMessage, ShapeToolMessageDiscriminant};
 pub use crate::messages::tool::tool_messages::spline_tool::{SplineToolMessage, SplineToolMessageDiscriminant};
 pub use crate::messages::tool::tool_messages::text_tool::{TextToolMessage, TextToolMessageDiscriminant};
+pub use crate::messages::tool::tool_messages::width_tool::{WidthToolMessage, WidthToolMessageDiscriminant};
 
 // Helper
 pub use crate::messages::globals::global_variables::*;
@@ -4,4 +4,5 @@ pub mod pivot;
 pub mod resize;
 pub mod shape_editor;
 pub mod snapping;
+pub mod stylus;
 pub mod transformation_cage;
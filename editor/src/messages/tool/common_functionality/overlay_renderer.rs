@@ -2,6 +2,7 @@ use crate::application::generate_uuid;
 use crate::consts::VIEWPORT_GRID_ROUNDING_BIAS;
 use crate::consts::{COLOR_ACCENT, MANIPULATOR_GROUP_MARKER_SIZE, PATH_OUTLINE_WEIGHT};
 use crate::messages::prelude::*;
+use crate::messages::tool::common_functionality::shape_editor::CornerRadiusWidget;
 
 use graphene::color::Color;
 use graphene::document::Document;
@@ -24,6 +25,7 @@ const POINT_STROKE_WEIGHT: f64 = 2.;
 pub struct OverlayRenderer {
 	shape_overlay_cache: HashMap<LayerId, Vec<LayerId>>,
 	manipulator_group_overlay_cache: HashMap<(LayerId, ManipulatorId), ManipulatorGroupOverlays>,
+	corner_radius_overlays: Vec<Vec<LayerId>>,
 }
 
 impl OverlayRenderer {
@@ -31,6 +33,7 @@ impl OverlayRenderer {
 		OverlayRenderer {
 			manipulator_group_overlay_cache: HashMap::new(),
 			shape_overlay_cache: HashMap::new(),
+			corner_radius_overlays: Vec::new(),
 		}
 	}
 
@@ -127,6 +130,30 @@ impl OverlayRenderer {
 		}
 	}
 
+	/// Places a marker on each of the corner radius widgets, creating or removing markers so there is one per widget.
+	pub fn render_corner_radius_overlays(&mut self, widgets: &[CornerRadiusWidget], responses: &mut VecDeque<Message>) {
+		while self.corner_radius_overlays.len() > widgets.len() {
+			if let Some(overlay_path) = self.corner_radius_overlays.pop() {
+				responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path: overlay_path }.into()).into());
+			}
+		}
+		while self.corner_radius_overlays.len() < widgets.len() {
+			self.corner_radius_overlays.push(Self::create_handle_overlay(responses));
+		}
+
+		for (overlay_path, widget) in self.corner_radius_overlays.iter().zip(widgets) {
+			let scale = DVec2::splat(MANIPULATOR_GROUP_MARKER_SIZE);
+			let translation = (widget.position() - (scale / 2.) + VIEWPORT_GRID_ROUNDING_BIAS).round();
+			let transform = DAffine2::from_scale_angle_translation(scale, 0., translation).to_cols_array();
+			responses.push_back(Self::overlay_transform_message(overlay_path.clone(), transform));
+		}
+	}
+
+	/// Removes the markers of the corner radius widgets.
+	pub fn clear_corner_radius_overlays(&mut self, responses: &mut VecDeque<Message>) {
+		self.render_corner_radius_overlays(&[], responses);
+	}
+
 	/// Create the kurbo shape that matches the selected viewport shape.
 	fn create_shape_outline_overlay(&self, subpath: Subpath, responses: &mut VecDeque<Message>) -> Vec<LayerId> {
 		let layer_path = vec![generate_uuid()];
@@ -34,7 +34,7 @@ impl PathOutline {
 		// TODO Purge this area of BezPath and Kurbo
 		// Get the bezpath from the shape or text
 		let subpath = match &document_layer.data {
-			LayerDataType::Shape(layer_shape) => Some(layer_shape.shape.clone()),
+			LayerDataType::Shape(layer_shape) => Some(layer_shape.shape.with_rounded_corners().into_owned()),
 			LayerDataType::Text(text) => Some(text.to_subpath_nonmut(font_cache)),
 			_ => document_layer.aabb_for_transform(DAffine2::IDENTITY, font_cache).map(|[p1, p2]| Subpath::new_rect(p1, p2)),
 		}?;
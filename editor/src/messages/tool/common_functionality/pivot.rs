@@ -1,11 +1,12 @@
 //! Handler for the pivot overlay visible on the selected layer(s) whilst using the Select tool which controls the center of rotation/scale and origin of the layer.
 
 use crate::application::generate_uuid;
-use crate::consts::{COLOR_ACCENT, PIVOT_INNER, PIVOT_OUTER, PIVOT_OUTER_OUTLINE_THICKNESS};
+use crate::consts::{COLOR_ACCENT, PIVOT_INNER, PIVOT_OUTER, PIVOT_OUTER_OUTLINE_THICKNESS, PIVOT_Z_ORDER};
 use crate::messages::layout::utility_types::widgets::assist_widgets::PivotPosition;
+use crate::messages::portfolio::document::overlays::utility_types::{HitRegion, Overlay, OverlayId, OverlayPrimitive, OverlayStyle};
 use crate::messages::prelude::*;
 
-use graphene::layers::style;
+use graphene::color::Color;
 use graphene::layers::text_layer::FontCache;
 use graphene::{LayerId, Operation};
 
@@ -20,8 +21,8 @@ pub struct Pivot {
 	transform_from_normalized: DAffine2,
 	/// The viewspace pivot position (if applicable)
 	pivot: Option<DVec2>,
-	/// A reference to the previous overlays so we can destroy them
-	pivot_overlay_circles: Option<[Vec<LayerId>; 2]>,
+	/// The retained overlays drawing the outer and inner circles of the pivot, the outer one also receiving the pointer
+	overlay_ids: [OverlayId; 2],
 	/// The old pivot position in the GUI, used to reduce refreshes of the document bar
 	old_pivot_position: PivotPosition,
 }
@@ -32,7 +33,7 @@ impl Default for Pivot {
 			normalized_pivot: DVec2::splat(0.5),
 			transform_from_normalized: Default::default(),
 			pivot: Default::default(),
-			pivot_overlay_circles: Default::default(),
+			overlay_ids: [generate_uuid(), generate_uuid()],
 			old_pivot_position: PivotPosition::Center,
 		}
 	}
@@ -84,56 +85,35 @@ impl Pivot {
 	}
 
 	pub fn clear_overlays(&mut self, responses: &mut VecDeque<Message>) {
-		if let Some(overlays) = self.pivot_overlay_circles.take() {
-			for path in overlays {
-				responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
-			}
+		for id in self.overlay_ids {
+			responses.push_back(DocumentMessage::Overlays(OverlaysMessage::RemoveOverlay { id }).into());
 		}
 	}
 
 	fn redraw_pivot(&mut self, responses: &mut VecDeque<Message>) {
-		self.clear_overlays(responses);
-
 		let pivot = match self.pivot {
 			Some(pivot) => pivot,
-			None => return,
+			None => {
+				self.clear_overlays(responses);
+				return;
+			}
 		};
 
-		let layer_paths = [vec![generate_uuid()], vec![generate_uuid()]];
-		responses.push_back(
-			DocumentMessage::Overlays(
-				Operation::AddEllipse {
-					path: layer_paths[0].clone(),
-					transform: DAffine2::IDENTITY.to_cols_array(),
-					style: style::PathStyle::new(Some(style::Stroke::new(COLOR_ACCENT, PIVOT_OUTER_OUTLINE_THICKNESS)), style::Fill::Solid(graphene::color::Color::WHITE)),
-					insert_index: -1,
-				}
-				.into(),
-			)
-			.into(),
-		);
-		responses.push_back(
-			DocumentMessage::Overlays(
-				Operation::AddEllipse {
-					path: layer_paths[1].clone(),
-					transform: DAffine2::IDENTITY.to_cols_array(),
-					style: style::PathStyle::new(None, style::Fill::Solid(COLOR_ACCENT)),
-					insert_index: -1,
-				}
-				.into(),
-			)
-			.into(),
-		);
-
-		self.pivot_overlay_circles = Some(layer_paths.clone());
-		let [outer, inner] = layer_paths;
-
-		let pivot_diameter_without_outline = PIVOT_OUTER - PIVOT_OUTER_OUTLINE_THICKNESS;
-		let transform = DAffine2::from_scale_angle_translation(DVec2::splat(pivot_diameter_without_outline), 0., pivot - DVec2::splat(pivot_diameter_without_outline / 2.)).to_cols_array();
-		responses.push_back(DocumentMessage::Overlays(Operation::TransformLayerInViewport { path: outer, transform }.into()).into());
-
-		let transform = DAffine2::from_scale_angle_translation(DVec2::splat(PIVOT_INNER), 0., pivot - DVec2::splat(PIVOT_INNER / 2.)).to_cols_array();
-		responses.push_back(DocumentMessage::Overlays(Operation::TransformLayerInViewport { path: inner, transform }.into()).into());
+		let [outer, inner] = self.overlay_ids;
+		let outer_style = OverlayStyle::new(Some(Color::WHITE), Some(COLOR_ACCENT), PIVOT_OUTER_OUTLINE_THICKNESS);
+		let outer_circle = OverlayPrimitive::Circle {
+			center: pivot,
+			diameter: PIVOT_OUTER - PIVOT_OUTER_OUTLINE_THICKNESS,
+		};
+		let overlay = Overlay::new(outer_circle, outer_style, PIVOT_Z_ORDER).with_hit_region(HitRegion::Circle {
+			center: pivot,
+			radius: PIVOT_OUTER / 2.,
+		});
+		responses.push_back(DocumentMessage::Overlays(OverlaysMessage::SetOverlay { id: outer, overlay }).into());
+
+		let inner_circle = OverlayPrimitive::Circle { center: pivot, diameter: PIVOT_INNER };
+		let overlay = Overlay::new(inner_circle, OverlayStyle::new(Some(COLOR_ACCENT), None, 0.), PIVOT_Z_ORDER);
+		responses.push_back(DocumentMessage::Overlays(OverlaysMessage::SetOverlay { id: inner, overlay }).into());
 	}
 
 	pub fn update_pivot(&mut self, document: &DocumentMessageHandler, font_cache: &FontCache, responses: &mut VecDeque<Message>) {
@@ -173,8 +153,14 @@ impl Pivot {
 		self.set_viewport_position(self.transform_from_normalized.transform_point2(position), document, font_cache, responses);
 	}
 
-	/// Answers if the pointer is currently positioned over the pivot.
-	pub fn is_over(&self, mouse: DVec2) -> bool {
-		self.pivot.filter(|&pivot| mouse.distance_squared(pivot) < (PIVOT_OUTER / 2.).powi(2)).is_some()
+	/// The viewport positions of the corners, edge centers, and center of the selection, which the pivot snaps to while it's dragged.
+	pub fn preset_viewport_positions(&self) -> impl Iterator<Item = DVec2> + '_ {
+		let normalized_positions = [0., 0.5, 1.].into_iter().flat_map(|y| [0., 0.5, 1.].into_iter().map(move |x| DVec2::new(x, y)));
+		normalized_positions.map(|position| self.transform_from_normalized.transform_point2(position))
+	}
+
+	/// Answers if the pointer is currently positioned over the pivot, and not over an overlay drawn above it.
+	pub fn is_over(&self, document: &DocumentMessageHandler, mouse: DVec2) -> bool {
+		self.pivot.is_some() && document.overlay_at(mouse) == Some(self.overlay_ids[0])
 	}
 }
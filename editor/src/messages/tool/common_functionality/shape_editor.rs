@@ -1,5 +1,9 @@
+use crate::application::generate_uuid;
+use crate::consts::CORNER_RADIUS_WIDGET_OFFSET;
 use crate::messages::prelude::*;
 
+use graphene::intersection::Quad;
+use graphene::layers::layer_info::LayerDataType;
 use graphene::layers::vector::consts::ManipulatorType;
 use graphene::layers::vector::manipulator_group::ManipulatorGroup;
 use graphene::layers::vector::manipulator_point::ManipulatorPoint;
@@ -9,6 +13,31 @@ use graphene::{LayerId, Operation};
 use glam::DVec2;
 use graphene::document::Document;
 
+/// A handle on the inside of the corner at a selected anchor, which is dragged along the bisector of the corner to change its radius.
+/// All positions and directions are in viewport space.
+#[derive(Clone, Debug)]
+pub struct CornerRadiusWidget {
+	pub layer_path: Vec<LayerId>,
+	pub anchor: DVec2,
+	/// The unit vector halving the corner, pointing into it
+	pub bisector: DVec2,
+	/// How far the middle of the rounding arc moves away from the anchor, in viewport pixels, per unit of radius
+	pub arc_distance_per_radius: f64,
+	pub radius: f64,
+}
+
+impl CornerRadiusWidget {
+	/// The position of the widget, which sits a fixed distance beyond the middle of the rounding arc.
+	pub fn position(&self) -> DVec2 {
+		self.anchor + self.bisector * (CORNER_RADIUS_WIDGET_OFFSET + self.radius * self.arc_distance_per_radius)
+	}
+
+	/// The radius which puts the widget closest to the position.
+	pub fn radius_at(&self, position: DVec2) -> f64 {
+		((position - self.anchor).dot(self.bisector) - CORNER_RADIUS_WIDGET_OFFSET).max(0.) / self.arc_distance_per_radius
+	}
+}
+
 /// ShapeEditor is the container for all of the layer paths that are represented as [Subpath]s and provides
 /// functionality required to query and create the [Subpath] / [ManipulatorGroup]s / [ManipulatorPoint]s.
 ///
@@ -126,6 +155,37 @@ impl ShapeEditor {
 		None
 	}
 
+	/// Select the anchors within the viewport space quad, across all the shapes, in place of the current selection unless adding to it.
+	pub fn select_anchors_in_quad(&self, document: &Document, quad: Quad, add_to_selection: bool, responses: &mut VecDeque<Message>) {
+		if !add_to_selection {
+			responses.push_back(DocumentMessage::DeselectAllManipulatorPoints.into());
+		}
+
+		for layer_path in &self.selected_layers {
+			let (shape, viewspace) = match (self.shape(document, layer_path), document.generate_transform_relative_to_viewport(layer_path)) {
+				(Some(shape), Ok(viewspace)) => (shape, viewspace),
+				_ => continue,
+			};
+
+			let point_ids = shape
+				.manipulator_groups()
+				.enumerate()
+				.filter(|(_id, manipulator_group)| {
+					manipulator_group.points[ManipulatorType::Anchor]
+						.as_ref()
+						.filter(|anchor| anchor.editor_state.can_be_selected)
+						.map_or(false, |anchor| quad.contains(viewspace.transform_point2(anchor.position)))
+				})
+				.map(|(id, _manipulator_group)| (*id, ManipulatorType::Anchor))
+				.collect::<Vec<_>>();
+
+			if !point_ids.is_empty() {
+				let layer_path = layer_path.clone();
+				responses.push_back(Operation::SelectManipulatorPoints { layer_path, point_ids, add: true }.into());
+			}
+		}
+	}
+
 	/// A wrapper for `find_nearest_point_indices()` and returns a [ManipulatorPoint].
 	pub fn find_nearest_point<'a>(&'a self, document: &'a Document, mouse_position: DVec2, select_threshold: f64) -> Option<&'a ManipulatorPoint> {
 		let (shape_layer_path, manipulator_group_id, manipulator_point_index) = self.find_nearest_point_indices(document, mouse_position, select_threshold)?;
@@ -205,6 +265,57 @@ impl ShapeEditor {
 		}
 	}
 
+	/// Set the radius the corners at the selected anchors are rounded off with.
+	pub fn set_selected_corner_radius(&self, radius: f64, responses: &mut VecDeque<Message>) {
+		for layer_path in &self.selected_layers {
+			responses.push_back(
+				Operation::SetSelectedCornerRadius {
+					layer_path: layer_path.clone(),
+					radius,
+				}
+				.into(),
+			);
+		}
+	}
+
+	/// The widgets for dragging the radius of the corners at the selected anchors, leaving out anchors where the path is nearly straight.
+	pub fn corner_radius_widgets(&self, document: &Document) -> Vec<CornerRadiusWidget> {
+		let mut widgets = Vec::new();
+
+		for layer_path in &self.selected_layers {
+			let (shape, transform) = match (self.shape(document, layer_path), document.generate_transform_relative_to_viewport(layer_path)) {
+				(Some(shape), Ok(transform)) => (shape, transform),
+				_ => continue,
+			};
+			// Radii are measured in layer space, so they grow with the (average) scale of the layer
+			let scale = transform.matrix2.determinant().abs().sqrt();
+
+			for (&id, manipulator_group) in shape.manipulator_groups().enumerate().filter(|(_, group)| group.is_anchor_selected()) {
+				let (anchor, directions) = match (&manipulator_group.points[ManipulatorType::Anchor], shape.corner_directions(id)) {
+					(Some(anchor), Some(directions)) => (anchor, directions),
+					_ => continue,
+				};
+				let [incoming, outgoing] = directions.map(|direction| transform.transform_vector2(direction).normalize_or_zero());
+				let bisector = (incoming + outgoing).normalize_or_zero();
+				// The sine of half the angle of the corner
+				let half_angle_sine = (incoming - outgoing).length() / 2.;
+				if bisector == DVec2::ZERO || half_angle_sine <= f64::EPSILON || half_angle_sine > 0.995 {
+					continue;
+				}
+
+				widgets.push(CornerRadiusWidget {
+					layer_path: layer_path.clone(),
+					anchor: transform.transform_point2(anchor.position),
+					bisector,
+					arc_distance_per_radius: (1. / half_angle_sine - 1.) * scale,
+					radius: manipulator_group.corner_radius,
+				});
+			}
+		}
+
+		widgets
+	}
+
 	/// Deselect all manipulators from the shapes that the manipulation handler has created.
 	pub fn deselect_all_points(&self, responses: &mut VecDeque<Message>) {
 		responses.push_back(DocumentMessage::DeselectAllManipulatorPoints.into());
@@ -326,6 +437,179 @@ impl ShapeEditor {
 		}
 	}
 
+	/// Cuts the path at the anchor or segment under the position (which can be activated by Alt clicking with the Path tool).
+	/// The path keeps the first piece, while any other open pieces become layers of their own right above it.
+	/// Returns whether a path was cut.
+	pub fn cut(&self, document: &Document, position: glam::DVec2, tolerance: f64, responses: &mut VecDeque<Message>) -> bool {
+		for layer_path in &self.selected_layers {
+			let shape = match self.shape(document, layer_path) {
+				Some(shape) => shape,
+				None => continue,
+			};
+
+			let pieces = match self.closest_anchor(document, layer_path, position, tolerance) {
+				Some(id) => shape.cut_at_anchor(id),
+				None => self
+					.closest_segment(document, layer_path, position, tolerance)
+					.and_then(|(bezier_id, t)| shape.cut_at_segment(&bezier_id, t)),
+			};
+			let mut pieces = match pieces {
+				Some(pieces) => pieces.into_iter(),
+				None => continue,
+			};
+
+			responses.push_back(DocumentMessage::StartTransaction.into());
+			if let Some(subpath) = pieces.next() {
+				responses.push_back(Operation::SetShapePath { path: layer_path.clone(), subpath }.into());
+			}
+
+			let (folder_path, layer_id) = layer_path.split_at(layer_path.len() - 1);
+			let insert_index = document
+				.folder(folder_path)
+				.and_then(|folder| folder.position_of_layer(layer_id[0]))
+				.map_or(-1, |index| index as isize + 1);
+			let mut new_layer_paths = Vec::new();
+			for subpath in pieces {
+				let mut layer = match document.layer(layer_path) {
+					Ok(layer) => layer.clone(),
+					Err(_) => break,
+				};
+				if let LayerDataType::Shape(shape_layer) = &mut layer.data {
+					shape_layer.shape = subpath;
+				}
+
+				let destination_path = [folder_path, &[generate_uuid()]].concat();
+				new_layer_paths.push(destination_path.clone());
+				responses.push_back(
+					Operation::InsertLayer {
+						layer: Box::new(layer),
+						destination_path,
+						insert_index,
+					}
+					.into(),
+				);
+			}
+			if !new_layer_paths.is_empty() {
+				responses.push_back(DocumentMessage::AddSelectedLayers { additional_layers: new_layer_paths }.into());
+			}
+			responses.push_back(DocumentMessage::DeselectAllManipulatorPoints.into());
+
+			return true;
+		}
+
+		false
+	}
+
+	/// Joins the two selected endpoints of open paths into one path (which can be activated with the Path tool), or closes the path if they are the ends of the same one.
+	/// When they are on different layers, the path of the second one is joined onto the first layer, which is deleted unless it has other closed paths left.
+	/// Returns whether the endpoints were joined.
+	pub fn join_selected_endpoints(&self, document: &Document, average: bool, responses: &mut VecDeque<Message>) -> bool {
+		let endpoints = self.selected_endpoints(document);
+		let ((layer_path, id), (other_layer_path, other_id)) = match endpoints.as_slice() {
+			[first, second] => (*first, *second),
+			_ => return false,
+		};
+		if layer_path == other_layer_path {
+			return self.close_selected_paths(document, average, responses);
+		}
+
+		let (shape, other_shape) = match (self.shape(document, layer_path), self.shape(document, other_layer_path)) {
+			(Some(shape), Some(other_shape)) => (shape, other_shape),
+			_ => return false,
+		};
+		let transform = match (document.multiply_transforms(layer_path), document.multiply_transforms(other_layer_path)) {
+			(Ok(transform), Ok(other_transform)) => transform.inverse() * other_transform,
+			_ => return false,
+		};
+
+		// Join in the space of the first layer
+		let mut other_shape = other_shape.clone();
+		other_shape.apply_affine(transform);
+		let (joined, mut rest) = match shape.join_open_contours(id, &other_shape, other_id, average) {
+			Some(result) => result,
+			None => return false,
+		};
+
+		responses.push_back(DocumentMessage::StartTransaction.into());
+		responses.push_back(
+			Operation::SetShapePath {
+				path: layer_path.to_vec(),
+				subpath: joined,
+			}
+			.into(),
+		);
+		if rest.manipulator_groups().is_empty() {
+			responses.push_back(
+				DocumentMessage::DeleteLayer {
+					layer_path: other_layer_path.to_vec(),
+				}
+				.into(),
+			);
+		} else {
+			rest.apply_affine(transform.inverse());
+			responses.push_back(
+				Operation::SetShapePath {
+					path: other_layer_path.to_vec(),
+					subpath: rest,
+				}
+				.into(),
+			);
+		}
+		responses.push_back(DocumentMessage::DeselectAllManipulatorPoints.into());
+
+		true
+	}
+
+	/// Closes the open path of each layer with a selected point (which can be activated with the Path tool).
+	/// Returns whether any path was closed.
+	pub fn close_selected_paths(&self, document: &Document, average: bool, responses: &mut VecDeque<Message>) -> bool {
+		let closed_paths = self
+			.selected_layers
+			.iter()
+			.filter_map(|layer_path| Some((layer_path, self.shape(document, layer_path)?)))
+			.filter(|(_, shape)| shape.selected_manipulator_groups_any_points().next().is_some())
+			.filter_map(|(layer_path, shape)| Some((layer_path.clone(), shape.close_open_contour(average)?)))
+			.collect::<Vec<_>>();
+		if closed_paths.is_empty() {
+			return false;
+		}
+
+		responses.push_back(DocumentMessage::StartTransaction.into());
+		for (path, subpath) in closed_paths {
+			responses.push_back(Operation::SetShapePath { path, subpath }.into());
+		}
+		responses.push_back(DocumentMessage::DeselectAllManipulatorPoints.into());
+
+		true
+	}
+
+	/// The selected anchors which are endpoints of the open paths in the shapes, as `(layer path, ManipulatorGroup ID)`.
+	fn selected_endpoints<'a>(&'a self, document: &'a Document) -> Vec<(&'a [LayerId], u64)> {
+		let shapes = self.selected_layers.iter().filter_map(|layer_path| Some((layer_path.as_slice(), self.shape(document, layer_path)?)));
+
+		shapes
+			.flat_map(|(layer_path, shape)| {
+				let endpoints = shape.open_endpoints().into_iter().flatten();
+				let selected = endpoints.filter(move |&id| shape.manipulator_groups().by_id(id).map_or(false, |group| group.is_anchor_selected()));
+				selected.map(move |id| (layer_path, id))
+			})
+			.collect()
+	}
+
+	/// Find the ID of the [ManipulatorGroup] with the anchor closest to the position in the layer, within the tolerance.
+	fn closest_anchor(&self, document: &Document, layer_path: &[LayerId], position: glam::DVec2, tolerance: f64) -> Option<u64> {
+		let viewspace = document.generate_transform_relative_to_viewport(layer_path).ok()?;
+		let anchors = self.shape(document, layer_path)?.manipulator_groups().enumerate().filter_map(|(&id, manipulator_group)| {
+			let anchor = manipulator_group.points[ManipulatorType::Anchor].as_ref()?;
+			Some((id, viewspace.transform_point2(anchor.position).distance_squared(position)))
+		});
+
+		anchors
+			.filter(|&(_, distance_squared)| distance_squared < tolerance * tolerance)
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))
+			.map(|(id, _)| id)
+	}
+
 	/// Handles the flipping between sharp corner and smooth (which can be activated by double clicking on an anchor with the Path tool).
 	pub fn flip_sharp(&self, document: &Document, position: glam::DVec2, tolerance: f64, responses: &mut VecDeque<Message>) -> bool {
 		let mut process_layer = |layer_path| {
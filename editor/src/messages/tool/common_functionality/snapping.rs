@@ -3,6 +3,7 @@ use crate::consts::{
 	COLOR_ACCENT, SNAP_AXIS_OVERLAY_FADE_DISTANCE, SNAP_AXIS_TOLERANCE, SNAP_AXIS_UNSNAPPED_OPACITY, SNAP_POINT_OVERLAY_FADE_FAR, SNAP_POINT_OVERLAY_FADE_NEAR, SNAP_POINT_SIZE, SNAP_POINT_TOLERANCE,
 	SNAP_POINT_UNSNAPPED_OPACITY,
 };
+use crate::messages::portfolio::document::utility_types::misc::PixelSnapping;
 use crate::messages::prelude::*;
 
 use graphene::layers::layer_info::{Layer, LayerDataType};
@@ -281,18 +282,25 @@ impl SnapManager {
 
 	/// Finds the closest snap from an array of layers to the specified snap targets in viewport coords.
 	/// Returns 0 for each axis that there is no snap less than the snap tolerance.
+	///
+	/// With pixel snapping, the top left corner of the anchors is then rounded onto the pixel grid.
 	pub fn snap_layers(&mut self, responses: &mut VecDeque<Message>, document_message_handler: &DocumentMessageHandler, snap_anchors: Vec<DVec2>, mouse_delta: DVec2) -> DVec2 {
 		if document_message_handler.snapping_enabled {
-			self.calculate_snap(snap_anchors.iter().map(move |&snap| mouse_delta + snap), responses)
+			let snap = self.calculate_snap(snap_anchors.iter().map(move |&snap| mouse_delta + snap), responses);
+			snap + pixel_snap_anchors(document_message_handler, &snap_anchors, mouse_delta + snap)
 		} else {
 			DVec2::ZERO
 		}
 	}
 
 	/// Handles snapping of a viewport position, returning another viewport position.
+	///
+	/// With pixel snapping, the position is then rounded onto the pixel grid.
 	pub fn snap_position(&mut self, responses: &mut VecDeque<Message>, document_message_handler: &DocumentMessageHandler, position_viewport: DVec2) -> DVec2 {
 		if document_message_handler.snapping_enabled {
-			self.calculate_snap([position_viewport].into_iter(), responses) + position_viewport
+			let position_viewport = self.calculate_snap([position_viewport].into_iter(), responses) + position_viewport;
+			let document_to_viewport = document_message_handler.graphene_document.root.transform;
+			document_message_handler.pixel_snapping.snap_viewport_point(position_viewport, document_to_viewport)
 		} else {
 			position_viewport
 		}
@@ -306,6 +314,21 @@ impl SnapManager {
 	}
 }
 
+/// The movement which puts the top left corner of the anchors, once they're moved by `delta`, onto the pixel grid of the document.
+fn pixel_snap_anchors(document_message_handler: &DocumentMessageHandler, anchors: &[DVec2], delta: DVec2) -> DVec2 {
+	let pixel_snapping = document_message_handler.pixel_snapping;
+	if pixel_snapping == PixelSnapping::Off {
+		return DVec2::ZERO;
+	}
+
+	let document_to_viewport = document_message_handler.graphene_document.root.transform;
+	let viewport_to_document = document_to_viewport.inverse();
+	let corner = anchors.iter().map(|&anchor| viewport_to_document.transform_point2(anchor + delta)).reduce(DVec2::min);
+	corner
+		.map(|corner| document_to_viewport.transform_vector2(pixel_snapping.snap_point(corner) - corner))
+		.unwrap_or_default()
+}
+
 /// Converts a bounding box into a set of points for snapping
 ///
 /// Puts a point in the middle of each edge (top, bottom, left, right)
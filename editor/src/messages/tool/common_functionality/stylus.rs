@@ -0,0 +1,39 @@
+use crate::messages::input_mapper::utility_types::input_mouse::StylusState;
+
+use serde::{Deserialize, Serialize};
+
+/// The property of a drawn stroke that the stylus pressure controls.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Hash, Serialize, Deserialize)]
+pub enum PressureTarget {
+	None,
+	#[default]
+	Size,
+	Opacity,
+}
+
+/// How the pressure applied to the stylus is translated before it's used, so the pen can be tuned to how heavy-handed the artist is.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Hash, Serialize, Deserialize)]
+pub enum ResponseCurve {
+	/// Light pressure already has a strong effect
+	Soft,
+	#[default]
+	Linear,
+	/// Only firm pressure has a strong effect
+	Firm,
+}
+
+impl ResponseCurve {
+	pub fn apply(&self, pressure: f64) -> f64 {
+		let pressure = pressure.clamp(0., 1.);
+		match self {
+			ResponseCurve::Soft => pressure.sqrt(),
+			ResponseCurve::Linear => pressure,
+			ResponseCurve::Firm => pressure * pressure,
+		}
+	}
+
+	/// The factor a stroke property is scaled by for the given stylus state, which is 1 for mouse and touch input since they don't report pressure.
+	pub fn factor(&self, stylus: Option<StylusState>) -> f64 {
+		stylus.map_or(1., |stylus| self.apply(stylus.pressure))
+	}
+}
@@ -34,6 +34,9 @@ pub enum ToolMessage {
 	Path(PathToolMessage),
 	#[remain::unsorted]
 	#[child]
+	Width(WidthToolMessage),
+	#[remain::unsorted]
+	#[child]
 	Pen(PenToolMessage),
 	#[remain::unsorted]
 	#[child]
@@ -98,6 +101,8 @@ pub enum ToolMessage {
 	#[remain::unsorted]
 	ActivateToolPath,
 	#[remain::unsorted]
+	ActivateToolWidth,
+	#[remain::unsorted]
 	ActivateToolPen,
 	#[remain::unsorted]
 	ActivateToolFreehand,
@@ -42,6 +42,8 @@ impl MessageHandler<ToolMessage, (&DocumentMessageHandler, u64, &InputPreprocess
 			#[remain::unsorted]
 			ToolMessage::ActivateToolPath => responses.push_front(ToolMessage::ActivateTool { tool_type: ToolType::Path }.into()),
 			#[remain::unsorted]
+			ToolMessage::ActivateToolWidth => responses.push_front(ToolMessage::ActivateTool { tool_type: ToolType::Width }.into()),
+			#[remain::unsorted]
 			ToolMessage::ActivateToolPen => responses.push_front(ToolMessage::ActivateTool { tool_type: ToolType::Pen }.into()),
 			#[remain::unsorted]
 			ToolMessage::ActivateToolFreehand => responses.push_front(ToolMessage::ActivateTool { tool_type: ToolType::Freehand }.into()),
@@ -207,6 +209,7 @@ impl MessageHandler<ToolMessage, (&DocumentMessageHandler, u64, &InputPreprocess
 			ActivateToolGradient,
 
 			ActivateToolPath,
+			ActivateToolWidth,
 			ActivateToolPen,
 			ActivateToolFreehand,
 			ActivateToolSpline,
@@ -1,9 +1,13 @@
 use crate::application::generate_uuid;
-use crate::consts::SELECTION_TOLERANCE;
+use crate::consts::{ARTBOARD_PRESET_GAP, SELECTION_TOLERANCE};
 use crate::messages::frontend::utility_types::MouseCursorIcon;
 use crate::messages::input_mapper::utility_types::input_keyboard::{Key, KeysGroup, MouseMotion};
-use crate::messages::layout::utility_types::layout_widget::PropertyHolder;
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::misc::LayoutTarget;
+use crate::messages::layout::utility_types::widgets::input_widgets::{DropdownEntryData, DropdownInput, NumberInput, RadioEntryData, RadioInput};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType};
 use crate::messages::portfolio::document::utility_types::misc::TargetDocument;
+use crate::messages::portfolio::document::utility_types::transformation::{OriginalTransforms, Selected};
 use crate::messages::prelude::*;
 use crate::messages::tool::common_functionality::snapping::SnapManager;
 use crate::messages::tool::common_functionality::transformation_cage::*;
@@ -11,15 +15,61 @@ use crate::messages::tool::utility_types::{EventToMessageMap, Fsm, ToolActionHan
 use crate::messages::tool::utility_types::{HintData, HintGroup, HintInfo};
 
 use graphene::intersection::Quad;
+use graphene::layers::text_layer::FontCache;
 use graphene::LayerId;
 
-use glam::{DVec2, Vec2Swizzles};
+use glam::{DAffine2, DVec2, Vec2Swizzles};
 use serde::{Deserialize, Serialize};
 
+/// Common artboard sizes in pixels, grouped into screens, paper at 96 DPI, and social media images.
+const ARTBOARD_PRESETS: [&[(&str, f64, f64)]; 3] = [
+	&[
+		("Desktop", 1920., 1080.),
+		("Laptop", 1366., 768.),
+		("Tablet", 768., 1024.),
+		("iPhone", 390., 844.),
+		("Android", 360., 800.),
+		("Watch", 184., 224.),
+	],
+	&[
+		("A3", 1123., 1587.),
+		("A4", 794., 1123.),
+		("A5", 559., 794.),
+		("Letter", 816., 1056.),
+		("Legal", 816., 1344.),
+		("Tabloid", 1056., 1632.),
+	],
+	&[
+		("Square Post", 1080., 1080.),
+		("Portrait Post", 1080., 1350.),
+		("Story", 1080., 1920.),
+		("Video Thumbnail", 1280., 720.),
+		("Banner", 1500., 500.),
+	],
+];
+
 #[derive(Default)]
 pub struct ArtboardTool {
 	fsm_state: ArtboardToolFsmState,
 	data: ArtboardToolData,
+	options: ArtboardOptions,
+}
+
+#[derive(Default)]
+pub struct ArtboardOptions {
+	content_behavior: ArtboardContentBehavior,
+}
+
+/// What happens to the artwork on an artboard when the artboard is moved or resized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArtboardContentBehavior {
+	/// The artwork stays where it is
+	#[default]
+	Stay,
+	/// The artwork keeps its position relative to the top left corner of the artboard
+	Move,
+	/// The artwork is stretched along with the artboard
+	Scale,
 }
 
 #[remain::sorted]
@@ -44,6 +94,17 @@ pub enum ArtboardToolMessage {
 		center: Key,
 	},
 	PointerUp,
+	ResizeSelected {
+		width: f64,
+		height: f64,
+	},
+	UpdateOptions(ArtboardOptionsUpdate),
+}
+
+#[remain::sorted]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum ArtboardOptionsUpdate {
+	ContentBehavior(ArtboardContentBehavior),
 }
 
 impl ToolMetadata for ArtboardTool {
@@ -70,12 +131,27 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for ArtboardTool
 			return;
 		}
 
-		let new_state = self.fsm_state.transition(message, &mut self.data, data, &(), responses);
+		if let ToolMessage::Artboard(ArtboardToolMessage::UpdateOptions(action)) = message {
+			match action {
+				ArtboardOptionsUpdate::ContentBehavior(content_behavior) => self.options.content_behavior = content_behavior,
+			}
+			self.register_properties(responses, LayoutTarget::ToolOptions);
+			return;
+		}
+
+		let selected_size = self.data.selected_size;
+
+		let new_state = self.fsm_state.transition(message, &mut self.data, data, &self.options, responses);
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
 			self.fsm_state.update_hints(responses);
 		}
+
+		// Show the size of the newly selected or resized artboard
+		if self.data.selected_size != selected_size {
+			self.register_properties(responses, LayoutTarget::ToolOptions);
+		}
 	}
 
 	advertise_actions!(ArtboardToolMessageDiscriminant;
@@ -88,7 +164,115 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for ArtboardTool
 	);
 }
 
-impl PropertyHolder for ArtboardTool {}
+impl PropertyHolder for ArtboardTool {
+	fn properties(&self) -> Layout {
+		let size = self.data.selected_size;
+
+		let mut index = 0;
+		let mut selected_index = None;
+		let presets = ARTBOARD_PRESETS
+			.iter()
+			.map(|group| {
+				group
+					.iter()
+					.map(|&(name, width, height)| {
+						if size == Some(DVec2::new(width, height)) {
+							selected_index = Some(index);
+						}
+						index += 1;
+
+						DropdownEntryData {
+							label: format!("{name} ({width}×{height})"),
+							on_update: WidgetCallback::new(move |_| ArtboardToolMessage::ResizeSelected { width, height }.into()),
+							..DropdownEntryData::default()
+						}
+					})
+					.collect()
+			})
+			.collect();
+
+		let content_behavior_entries = [
+			(ArtboardContentBehavior::Stay, "Stay", "Moving or resizing an artboard leaves the artwork on it where it is"),
+			(
+				ArtboardContentBehavior::Move,
+				"Move",
+				"Moving or resizing an artboard moves the artwork on it along with its top left corner",
+			),
+			(ArtboardContentBehavior::Scale, "Scale", "Resizing an artboard stretches the artwork on it to fit"),
+		]
+		.into_iter()
+		.map(|(content_behavior, label, tooltip)| RadioEntryData {
+			label: label.into(),
+			tooltip: tooltip.into(),
+			on_update: WidgetCallback::new(move |_| ArtboardToolMessage::UpdateOptions(ArtboardOptionsUpdate::ContentBehavior(content_behavior)).into()),
+			..RadioEntryData::default()
+		})
+		.collect();
+
+		let [width, height] = size.map_or([None, None], |size| [Some(size.x), Some(size.y)]);
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row {
+			widgets: vec![
+				WidgetHolder::new(Widget::DropdownInput(DropdownInput {
+					entries: presets,
+					selected_index,
+					tooltip: "Resizes the selected artboard to a common size, or adds an artboard of that size if none is selected".into(),
+					..DropdownInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Related,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					value: width,
+					label: "W".into(),
+					unit: " px".into(),
+					is_integer: true,
+					min: Some(1.),
+					disabled: size.is_none(),
+					on_update: WidgetCallback::new(move |number_input: &NumberInput| {
+						let height = height.unwrap_or(1.);
+						ArtboardToolMessage::ResizeSelected {
+							width: number_input.value.unwrap(),
+							height,
+						}
+						.into()
+					}),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Related,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					value: height,
+					label: "H".into(),
+					unit: " px".into(),
+					is_integer: true,
+					min: Some(1.),
+					disabled: size.is_none(),
+					on_update: WidgetCallback::new(move |number_input: &NumberInput| {
+						let width = width.unwrap_or(1.);
+						ArtboardToolMessage::ResizeSelected {
+							width,
+							height: number_input.value.unwrap(),
+						}
+						.into()
+					}),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Section,
+				})),
+				WidgetHolder::new(Widget::RadioInput(RadioInput {
+					selected_index: self.options.content_behavior as u32,
+					entries: content_behavior_entries,
+				})),
+			],
+		}]))
+	}
+}
 
 impl ToolTransition for ArtboardTool {
 	fn event_to_message_map(&self) -> EventToMessageMap {
@@ -122,18 +306,97 @@ struct ArtboardToolData {
 	cursor: MouseCursorIcon,
 	drag_start: DVec2,
 	drag_current: DVec2,
+	/// The size of the selected artboard, shown in the tool options
+	selected_size: Option<DVec2>,
+	/// The layers on the artboard being moved or resized, which are repositioned along with it
+	content: Vec<Vec<LayerId>>,
+	content_original_transforms: OriginalTransforms,
+	artboard_original_transform: DAffine2,
+}
+
+impl ArtboardToolData {
+	/// Finds the top level layers centered on the selected artboard, to be repositioned along with it as it's moved or resized.
+	fn start_content_reposition(&mut self, content_behavior: ArtboardContentBehavior, document: &DocumentMessageHandler, font_cache: &FontCache, responses: &mut VecDeque<Message>) {
+		self.content.clear();
+		self.content_original_transforms.clear();
+
+		let artboard = match self.selected_artboard {
+			Some(artboard) if content_behavior != ArtboardContentBehavior::Stay => artboard,
+			_ => return,
+		};
+		let artboards = &document.artboard_message_handler.artboards_graphene_document;
+		let (artboard_bounds, artboard_transform) = match (artboards.viewport_bounding_box(&[artboard], font_cache), artboards.layer(&[artboard])) {
+			(Ok(Some(bounds)), Ok(layer)) => (bounds, layer.transform),
+			_ => return,
+		};
+		let layer_ids = match document.graphene_document.root.as_folder() {
+			Ok(folder) => folder.layer_ids.clone(),
+			Err(_) => return,
+		};
+
+		self.content = layer_ids
+			.into_iter()
+			.map(|id| vec![id])
+			.filter(|path| !document.graphene_document.is_locked(path))
+			.filter(|path| match document.graphene_document.viewport_bounding_box(path, font_cache) {
+				Ok(Some([min, max])) => {
+					let center = (min + max) / 2.;
+					center.cmpge(artboard_bounds[0]).all() && center.cmple(artboard_bounds[1]).all()
+				}
+				_ => false,
+			})
+			.collect();
+		self.artboard_original_transform = artboard_transform;
+
+		if !self.content.is_empty() {
+			responses.push_back(DocumentMessage::StartTransaction.into());
+		}
+	}
+
+	/// Moves or scales the layers on the artboard from where they were when it started being moved or resized, to follow its new position and size.
+	fn reposition_content(&mut self, content_behavior: ArtboardContentBehavior, position: DVec2, size: DVec2, document: &DocumentMessageHandler, responses: &mut VecDeque<Message>) {
+		if self.content.is_empty() {
+			return;
+		}
+
+		let old = self.artboard_original_transform;
+		let new = DAffine2::from_scale_angle_translation(size, 0., position);
+		let top_left = |transform: DAffine2| transform.transform_point2(DVec2::ZERO).min(transform.transform_point2(DVec2::ONE));
+		let delta = match content_behavior {
+			ArtboardContentBehavior::Stay => return,
+			ArtboardContentBehavior::Move => DAffine2::from_translation(top_left(new) - top_left(old)),
+			ArtboardContentBehavior::Scale => new * old.inverse(),
+		};
+
+		// The layers are transformed in viewport space
+		let root = document.graphene_document.root.transform;
+		let delta = root * delta * root.inverse();
+
+		let content = self.content.iter().collect::<Vec<_>>();
+		let mut pivot = DVec2::ZERO;
+		let mut selected = Selected::new(&mut self.content_original_transforms, &mut pivot, &content, responses, &document.graphene_document);
+		selected.update_transforms(delta);
+	}
+
+	fn finish_content_reposition(&mut self, responses: &mut VecDeque<Message>) {
+		if !self.content.is_empty() {
+			responses.push_back(DocumentMessage::CommitTransaction.into());
+		}
+		self.content.clear();
+		self.content_original_transforms.clear();
+	}
 }
 
 impl Fsm for ArtboardToolFsmState {
 	type ToolData = ArtboardToolData;
-	type ToolOptions = ();
+	type ToolOptions = ArtboardOptions;
 
 	fn transition(
 		self,
 		event: ToolMessage,
 		tool_data: &mut Self::ToolData,
 		(document, _document_id, _global_tool_data, input, font_cache): ToolActionHandlerData,
-		_tool_options: &Self::ToolOptions,
+		tool_options: &Self::ToolOptions,
 		responses: &mut VecDeque<Message>,
 	) -> Self {
 		if let ToolMessage::Artboard(event) = event {
@@ -146,8 +409,13 @@ impl Fsm for ArtboardToolFsmState {
 							.unwrap_or(None),
 						tool_data.bounding_box_overlays.take(),
 					) {
-						(None, Some(bounding_box_overlays)) => bounding_box_overlays.delete(responses),
+						(None, Some(bounding_box_overlays)) => {
+							bounding_box_overlays.delete(responses);
+							tool_data.selected_size = None;
+						}
 						(Some((bounds, transform)), paths) => {
+							tool_data.selected_size = Some((bounds[1] - bounds[0]).abs().round());
+
 							let mut bounding_box_overlays = paths.unwrap_or_else(|| BoundingBoxOverlays::new(responses));
 
 							bounding_box_overlays.bounds = bounds;
@@ -204,6 +472,8 @@ impl Fsm for ArtboardToolFsmState {
 							bounds.center_of_transformation = pivot;
 						}
 
+						tool_data.start_content_reposition(tool_options.content_behavior, document, font_cache, responses);
+
 						ArtboardToolFsmState::ResizingBounds
 					} else {
 						let tolerance = DVec2::splat(SELECTION_TOLERANCE);
@@ -227,6 +497,8 @@ impl Fsm for ArtboardToolFsmState {
 								.into(),
 							);
 
+							tool_data.start_content_reposition(tool_options.content_behavior, document, font_cache, responses);
+
 							ArtboardToolFsmState::Dragging
 						} else {
 							tool_data.selected_artboard = None;
@@ -247,14 +519,16 @@ impl Fsm for ArtboardToolFsmState {
 							let snapped_mouse_position = tool_data.snap_manager.snap_position(responses, document, mouse_position);
 
 							let (position, size) = movement.new_size(snapped_mouse_position, bounds.transform, from_center, bounds.center_of_transformation, constrain_square);
+							let (position, size) = (position.round(), size.round());
 							responses.push_back(
 								ArtboardMessage::ResizeArtboard {
 									artboard: tool_data.selected_artboard.unwrap(),
-									position: position.round().into(),
-									size: size.round().into(),
+									position: position.into(),
+									size: size.into(),
 								}
 								.into(),
 							);
+							tool_data.reposition_content(tool_options.content_behavior, position, size, document, responses);
 
 							responses.push_back(BroadcastEvent::DocumentIsDirty.into());
 						}
@@ -271,18 +545,20 @@ impl Fsm for ArtboardToolFsmState {
 						let snap = bounds.evaluate_transform_handle_positions().into_iter().collect();
 						let closest_move = tool_data.snap_manager.snap_layers(responses, document, snap, mouse_delta);
 
-						let size = bounds.bounds[1] - bounds.bounds[0];
+						let size = (bounds.bounds[1] - bounds.bounds[0]).round();
 
 						let position = bounds.bounds[0] + bounds.transform.inverse().transform_vector2(mouse_position - tool_data.drag_current + closest_move);
+						let position = position.round();
 
 						responses.push_back(
 							ArtboardMessage::ResizeArtboard {
 								artboard: tool_data.selected_artboard.unwrap(),
-								position: position.round().into(),
-								size: size.round().into(),
+								position: position.into(),
+								size: size.into(),
 							}
 							.into(),
 						);
+						tool_data.reposition_content(tool_options.content_behavior, position, size, document, responses);
 
 						responses.push_back(BroadcastEvent::DocumentIsDirty.into());
 
@@ -363,6 +639,7 @@ impl Fsm for ArtboardToolFsmState {
 				}
 				(ArtboardToolFsmState::ResizingBounds, ArtboardToolMessage::PointerUp) => {
 					tool_data.snap_manager.cleanup(responses);
+					tool_data.finish_content_reposition(responses);
 
 					if let Some(bounds) = &mut tool_data.bounding_box_overlays {
 						bounds.original_transforms.clear();
@@ -383,6 +660,7 @@ impl Fsm for ArtboardToolFsmState {
 				}
 				(ArtboardToolFsmState::Dragging, ArtboardToolMessage::PointerUp) => {
 					tool_data.snap_manager.cleanup(responses);
+					tool_data.finish_content_reposition(responses);
 
 					if let Some(bounds) = &mut tool_data.bounding_box_overlays {
 						bounds.original_transforms.clear();
@@ -398,6 +676,7 @@ impl Fsm for ArtboardToolFsmState {
 					ArtboardToolFsmState::Ready
 				}
 				(_, ArtboardToolMessage::NudgeSelected { delta_x, delta_y }) => {
+					let (delta_x, delta_y) = (document.transform_settings.nudge(delta_x), document.transform_settings.nudge(delta_y));
 					if let Some(bounds) = &mut tool_data.bounding_box_overlays {
 						responses.push_back(
 							ArtboardMessage::ResizeArtboard {
@@ -411,10 +690,62 @@ impl Fsm for ArtboardToolFsmState {
 
 					ArtboardToolFsmState::Ready
 				}
+				(ArtboardToolFsmState::Ready, ArtboardToolMessage::ResizeSelected { width, height }) => {
+					let size = DVec2::new(width, height).round().max(DVec2::ONE);
+
+					if let (Some(artboard), Some(bounds)) = (tool_data.selected_artboard, &tool_data.bounding_box_overlays) {
+						// The artboard keeps its top left corner in place
+						let position = bounds.bounds[0].min(bounds.bounds[1]);
+
+						tool_data.start_content_reposition(tool_options.content_behavior, document, font_cache, responses);
+						responses.push_back(
+							ArtboardMessage::ResizeArtboard {
+								artboard,
+								position: position.into(),
+								size: size.into(),
+							}
+							.into(),
+						);
+						tool_data.reposition_content(tool_options.content_behavior, position, size, document, responses);
+						tool_data.finish_content_reposition(responses);
+					} else {
+						// The new artboard is lined up to the right of the others
+						let artboards = &document.artboard_message_handler;
+						let position = artboards
+							.artboard_ids
+							.iter()
+							.filter_map(|&id| artboards.artboards_graphene_document.layer(&[id]).ok()?.aabb(font_cache))
+							.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])])
+							.map_or(DVec2::ZERO, |[min, max]| DVec2::new(max.x + ARTBOARD_PRESET_GAP, min.y));
+
+						let id = generate_uuid();
+						tool_data.selected_artboard = Some(id);
+						responses.push_back(
+							ArtboardMessage::AddArtboard {
+								id: Some(id),
+								position: position.round().into(),
+								size: size.into(),
+							}
+							.into(),
+						);
+						responses.push_back(
+							PropertiesPanelMessage::SetActiveLayers {
+								paths: vec![vec![id]],
+								document: TargetDocument::Artboard,
+							}
+							.into(),
+						);
+					}
+
+					responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+
+					ArtboardToolFsmState::Ready
+				}
 				(_, ArtboardToolMessage::Abort) => {
 					if let Some(bounding_box_overlays) = tool_data.bounding_box_overlays.take() {
 						bounding_box_overlays.delete(responses);
 					}
+					tool_data.selected_size = None;
 
 					// Register properties when switching back to other tools
 					responses.push_back(
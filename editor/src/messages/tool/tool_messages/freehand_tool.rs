@@ -1,11 +1,15 @@
 use crate::messages::frontend::utility_types::MouseCursorIcon;
 use crate::messages::input_mapper::utility_types::input_keyboard::MouseMotion;
 use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
-use crate::messages::layout::utility_types::widgets::input_widgets::NumberInput;
+use crate::messages::layout::utility_types::misc::LayoutTarget;
+use crate::messages::layout::utility_types::widgets::input_widgets::{NumberInput, RadioEntryData, RadioInput};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType};
 use crate::messages::prelude::*;
+use crate::messages::tool::common_functionality::stylus::{PressureTarget, ResponseCurve};
 use crate::messages::tool::utility_types::{DocumentToolData, EventToMessageMap, Fsm, ToolActionHandlerData, ToolMetadata, ToolTransition, ToolType};
 use crate::messages::tool::utility_types::{HintData, HintGroup, HintInfo};
 
+use graphene::color::Color;
 use graphene::layers::style;
 use graphene::LayerId;
 use graphene::Operation;
@@ -22,11 +26,17 @@ pub struct FreehandTool {
 
 pub struct FreehandOptions {
 	line_weight: f64,
+	pressure_target: PressureTarget,
+	response_curve: ResponseCurve,
 }
 
 impl Default for FreehandOptions {
 	fn default() -> Self {
-		Self { line_weight: 5. }
+		Self {
+			line_weight: 5.,
+			pressure_target: PressureTarget::default(),
+			response_curve: ResponseCurve::default(),
+		}
 	}
 }
 
@@ -49,6 +59,8 @@ pub enum FreehandToolMessage {
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum FreehandToolMessageOptionsUpdate {
 	LineWeight(f64),
+	PressureTarget(PressureTarget),
+	ResponseCurve(ResponseCurve),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -71,8 +83,23 @@ impl ToolMetadata for FreehandTool {
 
 impl PropertyHolder for FreehandTool {
 	fn properties(&self) -> Layout {
-		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row {
-			widgets: vec![WidgetHolder::new(Widget::NumberInput(NumberInput {
+		let pressure_entries = [
+			(PressureTarget::None, "Off", "Ignore Pen Pressure"),
+			(PressureTarget::Size, "Size", "Pen Pressure Controls Weight"),
+			(PressureTarget::Opacity, "Opacity", "Pen Pressure Controls Opacity"),
+		]
+		.into_iter()
+		.map(|(target, label, tooltip)| RadioEntryData {
+			value: label.to_lowercase(),
+			label: label.into(),
+			tooltip: tooltip.into(),
+			on_update: WidgetCallback::new(move |_| FreehandToolMessage::UpdateOptions(FreehandToolMessageOptionsUpdate::PressureTarget(target)).into()),
+			..RadioEntryData::default()
+		})
+		.collect();
+
+		let mut widgets = vec![
+			WidgetHolder::new(Widget::NumberInput(NumberInput {
 				unit: " px".into(),
 				label: "Weight".into(),
 				value: Some(self.options.line_weight as f64),
@@ -80,8 +107,44 @@ impl PropertyHolder for FreehandTool {
 				min: Some(1.),
 				on_update: WidgetCallback::new(|number_input: &NumberInput| FreehandToolMessage::UpdateOptions(FreehandToolMessageOptionsUpdate::LineWeight(number_input.value.unwrap())).into()),
 				..NumberInput::default()
-			}))],
-		}]))
+			})),
+			WidgetHolder::new(Widget::Separator(Separator {
+				direction: SeparatorDirection::Horizontal,
+				separator_type: SeparatorType::Section,
+			})),
+			WidgetHolder::new(Widget::RadioInput(RadioInput {
+				selected_index: self.options.pressure_target as u32,
+				entries: pressure_entries,
+			})),
+		];
+
+		if self.options.pressure_target != PressureTarget::None {
+			let curve_entries = [
+				(ResponseCurve::Soft, "Soft", "Light Pen Pressure Has a Strong Effect"),
+				(ResponseCurve::Linear, "Linear", "Pen Pressure Has a Proportional Effect"),
+				(ResponseCurve::Firm, "Firm", "Only Firm Pen Pressure Has a Strong Effect"),
+			]
+			.into_iter()
+			.map(|(curve, label, tooltip)| RadioEntryData {
+				value: label.to_lowercase(),
+				label: label.into(),
+				tooltip: tooltip.into(),
+				on_update: WidgetCallback::new(move |_| FreehandToolMessage::UpdateOptions(FreehandToolMessageOptionsUpdate::ResponseCurve(curve)).into()),
+				..RadioEntryData::default()
+			})
+			.collect();
+
+			widgets.push(WidgetHolder::new(Widget::Separator(Separator {
+				direction: SeparatorDirection::Horizontal,
+				separator_type: SeparatorType::Related,
+			})));
+			widgets.push(WidgetHolder::new(Widget::RadioInput(RadioInput {
+				selected_index: self.options.response_curve as u32,
+				entries: curve_entries,
+			})));
+		}
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row { widgets }]))
 	}
 }
 
@@ -100,7 +163,10 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for FreehandTool
 		if let ToolMessage::Freehand(FreehandToolMessage::UpdateOptions(action)) = message {
 			match action {
 				FreehandToolMessageOptionsUpdate::LineWeight(line_weight) => self.options.line_weight = line_weight,
+				FreehandToolMessageOptionsUpdate::PressureTarget(pressure_target) => self.options.pressure_target = pressure_target,
+				FreehandToolMessageOptionsUpdate::ResponseCurve(response_curve) => self.options.response_curve = response_curve,
 			}
+			self.register_properties(responses, LayoutTarget::ToolOptions);
 			return;
 		}
 
@@ -149,10 +215,23 @@ impl Default for FreehandToolFsmState {
 #[derive(Clone, Debug, Default)]
 struct FreehandToolData {
 	points: Vec<DVec2>,
+	/// The factor from the response curve for the stylus pressure at each point
+	pressures: Vec<f64>,
 	weight: f64,
+	pressure_target: PressureTarget,
 	path: Option<Vec<LayerId>>,
 }
 
+impl FreehandToolData {
+	/// Since a polyline has one weight and color, the pressure across the whole stroke is averaged
+	fn pressure_factor(&self) -> f64 {
+		if self.pressures.is_empty() {
+			return 1.;
+		}
+		self.pressures.iter().sum::<f64>() / self.pressures.len() as f64
+	}
+}
+
 impl Fsm for FreehandToolFsmState {
 	type ToolData = FreehandToolData;
 	type ToolOptions = FreehandOptions;
@@ -180,8 +259,10 @@ impl Fsm for FreehandToolFsmState {
 					let pos = transform.inverse().transform_point2(input.mouse.position);
 
 					tool_data.points.push(pos);
+					tool_data.pressures.push(tool_options.response_curve.factor(input.mouse.stylus));
 
 					tool_data.weight = tool_options.line_weight;
+					tool_data.pressure_target = tool_options.pressure_target;
 
 					responses.push_back(add_polyline(tool_data, global_tool_data));
 
@@ -192,6 +273,7 @@ impl Fsm for FreehandToolFsmState {
 
 					if tool_data.points.last() != Some(&pos) {
 						tool_data.points.push(pos);
+						tool_data.pressures.push(tool_options.response_curve.factor(input.mouse.stylus));
 					}
 
 					responses.push_back(remove_preview(tool_data));
@@ -210,6 +292,7 @@ impl Fsm for FreehandToolFsmState {
 
 					tool_data.path = None;
 					tool_data.points.clear();
+					tool_data.pressures.clear();
 
 					Ready
 				}
@@ -247,12 +330,22 @@ fn remove_preview(data: &FreehandToolData) -> Message {
 fn add_polyline(data: &FreehandToolData, tool_data: &DocumentToolData) -> Message {
 	let points: Vec<(f64, f64)> = data.points.iter().map(|p| (p.x, p.y)).collect();
 
+	let (mut color, mut weight) = (tool_data.primary_color, data.weight);
+	match data.pressure_target {
+		PressureTarget::None => {}
+		PressureTarget::Size => weight *= data.pressure_factor(),
+		PressureTarget::Opacity => {
+			let (red, green, blue, alpha) = color.components();
+			color = Color::from_rgbaf32(red, green, blue, alpha * data.pressure_factor() as f32).unwrap_or(color);
+		}
+	}
+
 	Operation::AddPolyline {
 		path: data.path.clone().unwrap(),
 		insert_index: -1,
 		transform: DAffine2::IDENTITY.to_cols_array(),
 		points,
-		style: style::PathStyle::new(Some(style::Stroke::new(tool_data.primary_color, data.weight)), style::Fill::None),
+		style: style::PathStyle::new(Some(style::Stroke::new(color, weight)), style::Fill::None),
 	}
 	.into()
 }
@@ -4,6 +4,7 @@ use crate::messages::frontend::utility_types::MouseCursorIcon;
 use crate::messages::input_mapper::utility_types::input_keyboard::{Key, KeysGroup, MouseMotion};
 use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
 use crate::messages::layout::utility_types::widgets::input_widgets::{RadioEntryData, RadioInput};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType};
 use crate::messages::prelude::*;
 use crate::messages::tool::common_functionality::snapping::SnapManager;
 use crate::messages::tool::utility_types::{EventToMessageMap, Fsm, ToolActionHandlerData, ToolMetadata, ToolTransition, ToolType};
@@ -12,7 +13,7 @@ use crate::messages::tool::utility_types::{HintData, HintGroup, HintInfo};
 use graphene::color::Color;
 use graphene::intersection::Quad;
 use graphene::layers::layer_info::Layer;
-use graphene::layers::style::{Fill, Gradient, GradientType, PathStyle, Stroke};
+use graphene::layers::style::{Fill, Gradient, GradientSpread, GradientType, PathStyle, Stroke};
 use graphene::LayerId;
 use graphene::Operation;
 
@@ -29,11 +30,15 @@ pub struct GradientTool {
 
 pub struct GradientOptions {
 	gradient_type: GradientType,
+	spread: GradientSpread,
 }
 
 impl Default for GradientOptions {
 	fn default() -> Self {
-		Self { gradient_type: GradientType::Linear }
+		Self {
+			gradient_type: GradientType::Linear,
+			spread: GradientSpread::Pad,
+		}
 	}
 }
 
@@ -59,6 +64,7 @@ pub enum GradientToolMessage {
 #[remain::sorted]
 #[derive(PartialEq, Eq, Clone, Debug, Hash, Serialize, Deserialize)]
 pub enum GradientOptionsUpdate {
+	Spread(GradientSpread),
 	Type(GradientType),
 }
 
@@ -87,6 +93,7 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for GradientTool
 		}
 		if let ToolMessage::Gradient(GradientToolMessage::UpdateOptions(action)) = message {
 			match action {
+				GradientOptionsUpdate::Spread(spread) => self.options.spread = spread,
 				GradientOptionsUpdate::Type(gradient_type) => self.options.gradient_type = gradient_type,
 			}
 			return;
@@ -110,26 +117,42 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for GradientTool
 
 impl PropertyHolder for GradientTool {
 	fn properties(&self) -> Layout {
+		let type_entries = GradientType::ALL
+			.into_iter()
+			.map(|gradient_type| RadioEntryData {
+				value: gradient_type.name().to_lowercase(),
+				label: gradient_type.name().into(),
+				tooltip: format!("{} Gradient", gradient_type.name()),
+				on_update: WidgetCallback::new(move |_| GradientToolMessage::UpdateOptions(GradientOptionsUpdate::Type(gradient_type)).into()),
+				..RadioEntryData::default()
+			})
+			.collect();
+		let spread_entries = GradientSpread::ALL
+			.into_iter()
+			.map(|spread| RadioEntryData {
+				value: spread.svg_name().into(),
+				label: spread.name().into(),
+				tooltip: format!("{} Spread", spread.name()),
+				on_update: WidgetCallback::new(move |_| GradientToolMessage::UpdateOptions(GradientOptionsUpdate::Spread(spread)).into()),
+				..RadioEntryData::default()
+			})
+			.collect();
+
 		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row {
-			widgets: vec![WidgetHolder::new(Widget::RadioInput(RadioInput {
-				selected_index: if self.options.gradient_type == GradientType::Radial { 1 } else { 0 },
-				entries: vec![
-					RadioEntryData {
-						value: "linear".into(),
-						label: "Linear".into(),
-						tooltip: "Linear Gradient".into(),
-						on_update: WidgetCallback::new(move |_| GradientToolMessage::UpdateOptions(GradientOptionsUpdate::Type(GradientType::Linear)).into()),
-						..RadioEntryData::default()
-					},
-					RadioEntryData {
-						value: "radial".into(),
-						label: "Radial".into(),
-						tooltip: "Radial Gradient".into(),
-						on_update: WidgetCallback::new(move |_| GradientToolMessage::UpdateOptions(GradientOptionsUpdate::Type(GradientType::Radial)).into()),
-						..RadioEntryData::default()
-					},
-				],
-			}))],
+			widgets: vec![
+				WidgetHolder::new(Widget::RadioInput(RadioInput {
+					selected_index: GradientType::ALL.iter().position(|&gradient_type| gradient_type == self.options.gradient_type).unwrap_or_default() as u32,
+					entries: type_entries,
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Section,
+				})),
+				WidgetHolder::new(Widget::RadioInput(RadioInput {
+					selected_index: GradientSpread::ALL.iter().position(|&spread| spread == self.options.spread).unwrap_or_default() as u32,
+					entries: spread_entries,
+				})),
+			],
 		}]))
 	}
 }
@@ -407,7 +430,7 @@ impl Fsm for GradientToolFsmState {
 
 							let layer = document.graphene_document.layer(&intersection).unwrap();
 
-							let gradient = Gradient::new(
+							let mut gradient = Gradient::new(
 								DVec2::ZERO,
 								global_tool_data.secondary_color,
 								DVec2::ONE,
@@ -416,6 +439,7 @@ impl Fsm for GradientToolFsmState {
 								generate_uuid(),
 								tool_options.gradient_type,
 							);
+							gradient.spread = tool_options.spread;
 							let mut selected_gradient = SelectedGradient::new(gradient, &intersection, layer, document, font_cache).with_gradient_start(input.mouse.position);
 							selected_gradient.update_gradient(input.mouse.position, responses, false, tool_options.gradient_type);
 
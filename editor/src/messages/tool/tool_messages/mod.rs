@@ -14,3 +14,4 @@ pub mod select_tool;
 pub mod shape_tool;
 pub mod spline_tool;
 pub mod text_tool;
+pub mod width_tool;
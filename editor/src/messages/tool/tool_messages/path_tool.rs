@@ -1,29 +1,61 @@
-use crate::consts::{SELECTION_THRESHOLD, SELECTION_TOLERANCE};
+use crate::consts::{BIG_NUDGE_AMOUNT, CORNER_RADIUS_WIDGET_OFFSET, DRAG_THRESHOLD, NUDGE_AMOUNT, SELECTION_THRESHOLD, SELECTION_TOLERANCE};
 use crate::messages::frontend::utility_types::MouseCursorIcon;
 use crate::messages::input_mapper::utility_types::input_keyboard::{Key, KeysGroup, MouseMotion};
-use crate::messages::layout::utility_types::layout_widget::PropertyHolder;
+use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup, PropertyHolder, Widget, WidgetCallback, WidgetHolder, WidgetLayout};
+use crate::messages::layout::utility_types::widgets::input_widgets::{NumberInput, RadioEntryData, RadioInput};
+use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType};
 use crate::messages::prelude::*;
 use crate::messages::tool::common_functionality::overlay_renderer::OverlayRenderer;
-use crate::messages::tool::common_functionality::shape_editor::ShapeEditor;
+use crate::messages::tool::common_functionality::shape_editor::{CornerRadiusWidget, ShapeEditor};
 use crate::messages::tool::common_functionality::snapping::SnapManager;
+use crate::messages::tool::common_functionality::transformation_cage::{add_bounding_box, transform_from_box};
 use crate::messages::tool::utility_types::{EventToMessageMap, Fsm, ToolActionHandlerData, ToolMetadata, ToolTransition, ToolType};
 use crate::messages::tool::utility_types::{HintData, HintGroup, HintInfo};
 
 use graphene::intersection::Quad;
 use graphene::layers::vector::consts::ManipulatorType;
+use graphene::{LayerId, Operation};
 
-use glam::DVec2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
 
 #[derive(Default)]
 pub struct PathTool {
 	fsm_state: PathToolFsmState,
 	tool_data: PathToolData,
+	options: PathOptions,
+}
+
+pub struct PathOptions {
+	nudge_increment: f64,
+	big_nudge_increment: f64,
+	join_mode: JoinMode,
+	corner_radius: f64,
+}
+
+impl Default for PathOptions {
+	fn default() -> Self {
+		Self {
+			nudge_increment: NUDGE_AMOUNT,
+			big_nudge_increment: BIG_NUDGE_AMOUNT,
+			join_mode: JoinMode::Connect,
+			corner_radius: 0.,
+		}
+	}
+}
+
+/// How the endpoints of open paths are joined, or the two ends of one path when closing it.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+pub enum JoinMode {
+	/// Connect the endpoints with a straight segment.
+	Connect,
+	/// Merge the endpoints into one anchor at their midpoint, such as when they coincide.
+	Average,
 }
 
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Path)]
-#[derive(PartialEq, Eq, Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum PathToolMessage {
 	// Standard messages
 	#[remain::unsorted]
@@ -34,16 +66,37 @@ pub enum PathToolMessage {
 	SelectionChanged,
 
 	// Tool-specific messages
+	ClosePath,
+	CutPath,
 	Delete,
 	DragStart {
 		add_to_selection: Key,
 	},
 	DragStop,
 	InsertPoint,
+	JoinEndpoints,
+	NudgeSelectedPoints {
+		direction_x: f64,
+		direction_y: f64,
+		big_increment: bool,
+	},
 	PointerMove {
 		alt_mirror_angle: Key,
 		shift_mirror_distance: Key,
 	},
+	SetCornerRadius {
+		radius: f64,
+	},
+	UpdateOptions(PathOptionsUpdate),
+}
+
+#[remain::sorted]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum PathOptionsUpdate {
+	BigNudgeIncrement(f64),
+	CornerRadius(f64),
+	JoinMode(JoinMode),
+	NudgeIncrement(f64),
 }
 
 impl ToolMetadata for PathTool {
@@ -58,7 +111,75 @@ impl ToolMetadata for PathTool {
 	}
 }
 
-impl PropertyHolder for PathTool {}
+impl PropertyHolder for PathTool {
+	fn properties(&self) -> Layout {
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row {
+			widgets: vec![
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Nudge".into(),
+					tooltip: "Distance the arrow keys move the selected points".into(),
+					value: Some(self.options.nudge_increment),
+					is_integer: false,
+					min: Some(0.),
+					on_update: WidgetCallback::new(|number_input: &NumberInput| PathToolMessage::UpdateOptions(PathOptionsUpdate::NudgeIncrement(number_input.value.unwrap())).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Related,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Big Nudge".into(),
+					tooltip: "Distance the arrow keys move the selected points while Shift is held".into(),
+					value: Some(self.options.big_nudge_increment),
+					is_integer: false,
+					min: Some(0.),
+					on_update: WidgetCallback::new(|number_input: &NumberInput| PathToolMessage::UpdateOptions(PathOptionsUpdate::BigNudgeIncrement(number_input.value.unwrap())).into()),
+					..NumberInput::default()
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Section,
+				})),
+				WidgetHolder::new(Widget::RadioInput(RadioInput {
+					selected_index: if self.options.join_mode == JoinMode::Average { 1 } else { 0 },
+					entries: vec![
+						RadioEntryData {
+							value: "connect".into(),
+							label: "Connect".into(),
+							tooltip: "Join Endpoints with a Straight Segment".into(),
+							on_update: WidgetCallback::new(move |_| PathToolMessage::UpdateOptions(PathOptionsUpdate::JoinMode(JoinMode::Connect)).into()),
+							..RadioEntryData::default()
+						},
+						RadioEntryData {
+							value: "average".into(),
+							label: "Average".into(),
+							tooltip: "Join Endpoints by Merging Them at Their Midpoint".into(),
+							on_update: WidgetCallback::new(move |_| PathToolMessage::UpdateOptions(PathOptionsUpdate::JoinMode(JoinMode::Average)).into()),
+							..RadioEntryData::default()
+						},
+					],
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Section,
+				})),
+				WidgetHolder::new(Widget::NumberInput(NumberInput {
+					unit: " px".into(),
+					label: "Corner Radius".into(),
+					tooltip: "Radius the corners at the selected anchors are rounded off with, without moving the anchors".into(),
+					value: Some(self.options.corner_radius),
+					is_integer: false,
+					min: Some(0.),
+					on_update: WidgetCallback::new(|number_input: &NumberInput| PathToolMessage::UpdateOptions(PathOptionsUpdate::CornerRadius(number_input.value.unwrap())).into()),
+					..NumberInput::default()
+				})),
+			],
+		}]))
+	}
+}
 
 impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for PathTool {
 	fn process_message(&mut self, message: ToolMessage, tool_data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
@@ -72,7 +193,20 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for PathTool {
 			return;
 		}
 
-		let new_state = self.fsm_state.transition(message, &mut self.tool_data, tool_data, &(), responses);
+		if let ToolMessage::Path(PathToolMessage::UpdateOptions(action)) = message {
+			match action {
+				PathOptionsUpdate::BigNudgeIncrement(big_nudge_increment) => self.options.big_nudge_increment = big_nudge_increment,
+				PathOptionsUpdate::CornerRadius(radius) => {
+					self.options.corner_radius = radius;
+					responses.push_back(PathToolMessage::SetCornerRadius { radius }.into());
+				}
+				PathOptionsUpdate::JoinMode(join_mode) => self.options.join_mode = join_mode,
+				PathOptionsUpdate::NudgeIncrement(nudge_increment) => self.options.nudge_increment = nudge_increment,
+			}
+			return;
+		}
+
+		let new_state = self.fsm_state.transition(message, &mut self.tool_data, tool_data, &self.options, responses);
 
 		if self.fsm_state != new_state {
 			self.fsm_state = new_state;
@@ -88,8 +222,12 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for PathTool {
 		match self.fsm_state {
 			Ready => actions!(PathToolMessageDiscriminant;
 				InsertPoint,
+				CutPath,
 				DragStart,
 				Delete,
+				NudgeSelectedPoints,
+				JoinEndpoints,
+				ClosePath,
 			),
 			Dragging => actions!(PathToolMessageDiscriminant;
 				InsertPoint,
@@ -97,6 +235,16 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for PathTool {
 				PointerMove,
 				Delete,
 			),
+			DrawingBox => actions!(PathToolMessageDiscriminant;
+				DragStop,
+				PointerMove,
+				Abort,
+			),
+			DraggingCornerRadius => actions!(PathToolMessageDiscriminant;
+				DragStop,
+				PointerMove,
+				Abort,
+			),
 		}
 	}
 }
@@ -115,6 +263,8 @@ impl ToolTransition for PathTool {
 enum PathToolFsmState {
 	Ready,
 	Dragging,
+	DrawingBox,
+	DraggingCornerRadius,
 }
 
 impl Default for PathToolFsmState {
@@ -132,18 +282,24 @@ struct PathToolData {
 	drag_start_pos: DVec2,
 	alt_debounce: bool,
 	shift_debounce: bool,
+
+	drag_box_overlay_layer: Option<Vec<LayerId>>,
+	drag_current_pos: DVec2,
+	add_to_selection: bool,
+
+	corner_radius_widget: Option<CornerRadiusWidget>,
 }
 
 impl Fsm for PathToolFsmState {
 	type ToolData = PathToolData;
-	type ToolOptions = ();
+	type ToolOptions = PathOptions;
 
 	fn transition(
 		self,
 		event: ToolMessage,
 		tool_data: &mut Self::ToolData,
 		(document, _document_id, _global_tool_data, input, font_cache): ToolActionHandlerData,
-		_tool_options: &Self::ToolOptions,
+		tool_options: &Self::ToolOptions,
 		responses: &mut VecDeque<Message>,
 	) -> Self {
 		if let ToolMessage::Path(event) = event {
@@ -161,6 +317,8 @@ impl Fsm for PathToolFsmState {
 					for layer_path in tool_data.shape_editor.selected_layers() {
 						tool_data.overlay_renderer.render_subpath_overlays(&document.graphene_document, layer_path.to_vec(), responses);
 					}
+					let widgets = tool_data.shape_editor.corner_radius_widgets(&document.graphene_document);
+					tool_data.overlay_renderer.render_corner_radius_overlays(&widgets, responses);
 
 					// This can happen in any state (which is why we return self)
 					self
@@ -171,6 +329,8 @@ impl Fsm for PathToolFsmState {
 					for layer_path in document.selected_visible_layers() {
 						tool_data.overlay_renderer.render_subpath_overlays(&document.graphene_document, layer_path.to_vec(), responses);
 					}
+					let widgets = tool_data.shape_editor.corner_radius_widgets(&document.graphene_document);
+					tool_data.overlay_renderer.render_corner_radius_overlays(&widgets, responses);
 
 					self
 				}
@@ -178,6 +338,21 @@ impl Fsm for PathToolFsmState {
 				(_, PathToolMessage::DragStart { add_to_selection }) => {
 					let toggle_add_to_selection = input.keyboard.get(add_to_selection as usize);
 
+					// Grab the corner radius widget under the mouse, which takes precedence over the points since it only shows up beside selected anchors
+					let corner_radius_widget = tool_data
+						.shape_editor
+						.corner_radius_widgets(&document.graphene_document)
+						.into_iter()
+						.map(|widget| (widget.position().distance_squared(input.mouse.position), widget))
+						.filter(|&(distance_squared, _)| distance_squared < (CORNER_RADIUS_WIDGET_OFFSET / 2.).powi(2))
+						.min_by(|(a, _), (b, _)| a.total_cmp(b))
+						.map(|(_, widget)| widget);
+					if let Some(widget) = corner_radius_widget {
+						responses.push_back(DocumentMessage::StartTransaction.into());
+						tool_data.corner_radius_widget = Some(widget);
+						return PathToolFsmState::DraggingCornerRadius;
+					}
+
 					// Select the first point within the threshold (in pixels)
 					if let Some((mut new_selected, offset)) =
 						tool_data
@@ -225,14 +400,56 @@ impl Fsm for PathToolFsmState {
 									.into(),
 								);
 							}
-						} else {
-							// Clear the previous selection if we didn't find anything
-							if !input.keyboard.get(toggle_add_to_selection as usize) {
-								responses.push_back(DocumentMessage::DeselectAllLayers.into());
-							}
+							PathToolFsmState::Ready
 						}
-						PathToolFsmState::Ready
+						// Otherwise start a box selection of the points in the selected layers
+						else {
+							tool_data.drag_start_pos = input.mouse.position;
+							tool_data.drag_current_pos = input.mouse.position;
+							tool_data.add_to_selection = toggle_add_to_selection;
+							tool_data.drag_box_overlay_layer = Some(add_bounding_box(responses));
+							PathToolFsmState::DrawingBox
+						}
+					}
+				}
+				(PathToolFsmState::DrawingBox, PathToolMessage::PointerMove { .. }) => {
+					tool_data.drag_current_pos = input.mouse.position;
+
+					if let Some(path) = tool_data.drag_box_overlay_layer.clone() {
+						let transform = transform_from_box(tool_data.drag_start_pos, tool_data.drag_current_pos, DAffine2::IDENTITY).to_cols_array();
+						responses.push_back(DocumentMessage::Overlays(Operation::SetLayerTransformInViewport { path, transform }.into()).into());
 					}
+
+					PathToolFsmState::DrawingBox
+				}
+				(PathToolFsmState::DrawingBox, PathToolMessage::DragStop) => {
+					if let Some(path) = tool_data.drag_box_overlay_layer.take() {
+						responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
+					}
+
+					if tool_data.drag_start_pos.distance(tool_data.drag_current_pos) < DRAG_THRESHOLD {
+						// Clear the previous selection if we clicked on nothing
+						if !tool_data.add_to_selection {
+							responses.push_back(DocumentMessage::DeselectAllLayers.into());
+						}
+					} else {
+						let quad = Quad::from_box([tool_data.drag_start_pos, tool_data.drag_current_pos]);
+						tool_data.shape_editor.select_anchors_in_quad(&document.graphene_document, quad, tool_data.add_to_selection, responses);
+					}
+
+					PathToolFsmState::Ready
+				}
+				(PathToolFsmState::DraggingCornerRadius, PathToolMessage::PointerMove { .. }) => {
+					if let Some(widget) = &tool_data.corner_radius_widget {
+						tool_data.shape_editor.set_selected_corner_radius(widget.radius_at(input.mouse.position), responses);
+					}
+
+					PathToolFsmState::DraggingCornerRadius
+				}
+				(PathToolFsmState::DraggingCornerRadius, PathToolMessage::DragStop) => {
+					tool_data.corner_radius_widget = None;
+
+					PathToolFsmState::Ready
 				}
 				// Dragging
 				(
@@ -281,6 +498,42 @@ impl Fsm for PathToolFsmState {
 					}
 					PathToolFsmState::Ready
 				}
+				(
+					_,
+					PathToolMessage::NudgeSelectedPoints {
+						direction_x,
+						direction_y,
+						big_increment,
+					},
+				) => {
+					let increment = if big_increment { tool_options.big_nudge_increment } else { tool_options.nudge_increment };
+					tool_data.shape_editor.move_selected_points(DVec2::new(direction_x, direction_y) * increment, responses);
+
+					self
+				}
+				(_, PathToolMessage::JoinEndpoints) => {
+					let average = tool_options.join_mode == JoinMode::Average;
+					tool_data.shape_editor.join_selected_endpoints(&document.graphene_document, average, responses);
+
+					self
+				}
+				(_, PathToolMessage::ClosePath) => {
+					let average = tool_options.join_mode == JoinMode::Average;
+					tool_data.shape_editor.close_selected_paths(&document.graphene_document, average, responses);
+
+					self
+				}
+				(_, PathToolMessage::CutPath) => {
+					tool_data.shape_editor.cut(&document.graphene_document, input.mouse.position, SELECTION_TOLERANCE, responses);
+
+					self
+				}
+				(_, PathToolMessage::SetCornerRadius { radius }) => {
+					responses.push_back(DocumentMessage::StartTransaction.into());
+					tool_data.shape_editor.set_selected_corner_radius(radius, responses);
+
+					self
+				}
 				(_, PathToolMessage::InsertPoint) => {
 					// First we try and flip the sharpness (if they have clicked on an anchor)
 					if !tool_data.shape_editor.flip_sharp(&document.graphene_document, input.mouse.position, SELECTION_TOLERANCE, responses) {
@@ -291,10 +544,16 @@ impl Fsm for PathToolFsmState {
 					self
 				}
 				(_, PathToolMessage::Abort) => {
+					if let Some(path) = tool_data.drag_box_overlay_layer.take() {
+						responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
+					}
+
 					// TODO Tell overlay manager to remove the overlays
 					for layer_path in document.all_layers() {
 						tool_data.overlay_renderer.clear_subpath_overlays(&document.graphene_document, layer_path.to_vec(), responses);
 					}
+					tool_data.overlay_renderer.clear_corner_radius_overlays(responses);
+					tool_data.corner_radius_widget = None;
 					PathToolFsmState::Ready
 				}
 				(
@@ -304,6 +563,7 @@ impl Fsm for PathToolFsmState {
 						shift_mirror_distance: _,
 					},
 				) => self,
+				(_, PathToolMessage::UpdateOptions(_)) => self,
 			}
 		} else {
 			self
@@ -336,6 +596,45 @@ impl Fsm for PathToolFsmState {
 					label: String::from("Drag Selected"),
 					plus: false,
 				}]),
+				HintGroup(vec![
+					HintInfo {
+						key_groups: vec![KeysGroup(vec![Key::Alt])],
+						key_groups_mac: None,
+						mouse: Some(MouseMotion::Lmb),
+						label: String::from("Cut Path"),
+						plus: false,
+					},
+					HintInfo {
+						key_groups: vec![KeysGroup(vec![Key::Control, Key::KeyJ])],
+						key_groups_mac: Some(vec![KeysGroup(vec![Key::Command, Key::KeyJ])]),
+						mouse: None,
+						label: String::from("Join Endpoints"),
+						plus: false,
+					},
+					HintInfo {
+						key_groups: vec![KeysGroup(vec![Key::Control, Key::Shift, Key::KeyJ])],
+						key_groups_mac: Some(vec![KeysGroup(vec![Key::Command, Key::Shift, Key::KeyJ])]),
+						mouse: None,
+						label: String::from("Close Path"),
+						plus: false,
+					},
+				]),
+				HintGroup(vec![
+					HintInfo {
+						key_groups: vec![],
+						key_groups_mac: None,
+						mouse: Some(MouseMotion::LmbDrag),
+						label: String::from("Select Area"),
+						plus: false,
+					},
+					HintInfo {
+						key_groups: vec![KeysGroup(vec![Key::Shift])],
+						key_groups_mac: None,
+						mouse: None,
+						label: String::from("Extend Selection"),
+						plus: true,
+					},
+				]),
 				HintGroup(vec![
 					HintInfo {
 						key_groups: vec![
@@ -346,7 +645,7 @@ impl Fsm for PathToolFsmState {
 						],
 						key_groups_mac: None,
 						mouse: None,
-						label: String::from("Nudge Selected (coming soon)"),
+						label: String::from("Nudge Selected"),
 						plus: false,
 					},
 					HintInfo {
@@ -397,6 +696,8 @@ impl Fsm for PathToolFsmState {
 					plus: false,
 				},
 			])]),
+			PathToolFsmState::DrawingBox => HintData(vec![]),
+			PathToolFsmState::DraggingCornerRadius => HintData(vec![]),
 		};
 
 		responses.push_back(FrontendMessage::UpdateInputHints { hint_data }.into());
@@ -7,6 +7,7 @@ use crate::messages::layout::utility_types::layout_widget::{Layout, LayoutGroup,
 use crate::messages::layout::utility_types::misc::LayoutTarget;
 use crate::messages::layout::utility_types::widgets::assist_widgets::{PivotAssist, PivotPosition};
 use crate::messages::layout::utility_types::widgets::button_widgets::{IconButton, PopoverButton};
+use crate::messages::layout::utility_types::widgets::input_widgets::{RadioEntryData, RadioInput};
 use crate::messages::layout::utility_types::widgets::label_widgets::{Separator, SeparatorDirection, SeparatorType};
 use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, FlipAxis};
 use crate::messages::portfolio::document::utility_types::transformation::Selected;
@@ -32,6 +33,36 @@ use serde::{Deserialize, Serialize};
 pub struct SelectTool {
 	fsm_state: SelectToolFsmState,
 	tool_data: SelectToolData,
+	options: SelectOptions,
+}
+
+#[derive(Default)]
+pub struct SelectOptions {
+	marquee_mode: MarqueeMode,
+}
+
+/// Which layers are selected by dragging a box around them.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+pub enum MarqueeMode {
+	/// Layers touched by the box, even partly.
+	Touching,
+	/// Only layers lying entirely within the box.
+	Enclosed,
+}
+
+impl Default for MarqueeMode {
+	fn default() -> Self {
+		MarqueeMode::Touching
+	}
+}
+
+impl MarqueeMode {
+	fn toggled(self) -> Self {
+		match self {
+			MarqueeMode::Touching => MarqueeMode::Enclosed,
+			MarqueeMode::Enclosed => MarqueeMode::Touching,
+		}
+	}
 }
 
 #[remain::sorted]
@@ -63,10 +94,18 @@ pub enum SelectToolMessage {
 		snap_angle: Key,
 		center: Key,
 		duplicate: Key,
+		toggle_marquee_mode: Key,
 	},
 	SetPivot {
 		position: PivotPosition,
 	},
+	UpdateOptions(SelectOptionsUpdate),
+}
+
+#[remain::sorted]
+#[derive(PartialEq, Eq, Clone, Debug, Hash, Serialize, Deserialize)]
+pub enum SelectOptionsUpdate {
+	MarqueeMode(MarqueeMode),
 }
 
 impl ToolMetadata for SelectTool {
@@ -85,6 +124,29 @@ impl PropertyHolder for SelectTool {
 	fn properties(&self) -> Layout {
 		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row {
 			widgets: vec![
+				WidgetHolder::new(Widget::RadioInput(RadioInput {
+					selected_index: if self.options.marquee_mode == MarqueeMode::Enclosed { 1 } else { 0 },
+					entries: vec![
+						RadioEntryData {
+							value: "touching".into(),
+							label: "Touching".into(),
+							tooltip: "Box Select Layers Touching the Box".into(),
+							on_update: WidgetCallback::new(move |_| SelectToolMessage::UpdateOptions(SelectOptionsUpdate::MarqueeMode(MarqueeMode::Touching)).into()),
+							..RadioEntryData::default()
+						},
+						RadioEntryData {
+							value: "enclosed".into(),
+							label: "Enclosed".into(),
+							tooltip: "Box Select Only Layers Entirely Inside the Box".into(),
+							on_update: WidgetCallback::new(move |_| SelectToolMessage::UpdateOptions(SelectOptionsUpdate::MarqueeMode(MarqueeMode::Enclosed)).into()),
+							..RadioEntryData::default()
+						},
+					],
+				})),
+				WidgetHolder::new(Widget::Separator(Separator {
+					direction: SeparatorDirection::Horizontal,
+					separator_type: SeparatorType::Section,
+				})),
 				WidgetHolder::new(Widget::IconButton(IconButton {
 					icon: "AlignLeft".into(),
 					tooltip: "Align Left".into(),
@@ -277,7 +339,15 @@ impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for SelectTool {
 			return;
 		}
 
-		let new_state = self.fsm_state.transition(message, &mut self.tool_data, tool_data, &(), responses);
+		if let ToolMessage::Select(SelectToolMessage::UpdateOptions(action)) = message {
+			match action {
+				SelectOptionsUpdate::MarqueeMode(marquee_mode) => self.options.marquee_mode = marquee_mode,
+			}
+			self.register_properties(responses, LayoutTarget::ToolOptions);
+			return;
+		}
+
+		let new_state = self.fsm_state.transition(message, &mut self.tool_data, tool_data, &self.options, responses);
 
 		if self.tool_data.pivot.should_refresh_pivot_position() {
 			// Notify the frontend about the updated pivot position (a bit ugly to do it here not in the fsm but that doesn't have SelectTool)
@@ -348,6 +418,8 @@ struct SelectToolData {
 	snap_manager: SnapManager,
 	cursor: MouseCursorIcon,
 	pivot: Pivot,
+	/// Whether the box selection uses the other marquee mode than the one chosen in the tool options, while its modifier key is held.
+	marquee_mode_toggled: bool,
 }
 
 impl SelectToolData {
@@ -368,14 +440,14 @@ impl SelectToolData {
 
 impl Fsm for SelectToolFsmState {
 	type ToolData = SelectToolData;
-	type ToolOptions = ();
+	type ToolOptions = SelectOptions;
 
 	fn transition(
 		self,
 		event: ToolMessage,
 		tool_data: &mut Self::ToolData,
 		(document, document_id, _global_tool_data, input, font_cache): ToolActionHandlerData,
-		_tool_options: &Self::ToolOptions,
+		tool_options: &Self::ToolOptions,
 		responses: &mut VecDeque<Message>,
 	) -> Self {
 		use SelectToolFsmState::*;
@@ -464,9 +536,12 @@ impl Fsm for SelectToolFsmState {
 					// If the user clicks on a layer that is in their current selection, go into the dragging mode.
 					// If the user clicks on new shape, make that layer their new selection.
 					// Otherwise enter the box select mode
-					let state = if tool_data.pivot.is_over(input.mouse.position) {
+					let state = if tool_data.pivot.is_over(document, input.mouse.position) {
+						responses.push_back(DocumentMessage::StartTransaction.into());
+
 						tool_data.snap_manager.start_snap(document, document.bounding_boxes(None, None, font_cache), true, true);
 						tool_data.snap_manager.add_all_document_handles(document, &[], &[], &[]);
+						tool_data.snap_manager.add_snap_points(document, tool_data.pivot.preset_viewport_positions());
 
 						DraggingPivot
 					} else if let Some(selected_edges) = dragging_bounds {
@@ -528,6 +603,7 @@ impl Fsm for SelectToolFsmState {
 							Dragging
 						} else {
 							tool_data.drag_box_overlay_layer = Some(add_bounding_box(responses));
+							tool_data.marquee_mode_toggled = false;
 							DrawingBox
 						}
 					};
@@ -574,7 +650,9 @@ impl Fsm for SelectToolFsmState {
 				(ResizingBounds, PointerMove { axis_align, center, .. }) => {
 					if let Some(bounds) = &mut tool_data.bounding_box_overlays {
 						if let Some(movement) = &mut bounds.selected_edges {
-							let (center, axis_align) = (input.keyboard.get(center as usize), input.keyboard.get(axis_align as usize));
+							let settings = &document.transform_settings;
+							let center = settings.resize_around_pivot(input.keyboard.get(center as usize));
+							let axis_align = settings.constrain_aspect_ratio(input.keyboard.get(axis_align as usize));
 
 							let mouse_position = input.mouse.position;
 
@@ -624,8 +702,9 @@ impl Fsm for SelectToolFsmState {
 
 					DraggingPivot
 				}
-				(DrawingBox, PointerMove { .. }) => {
+				(DrawingBox, PointerMove { toggle_marquee_mode, .. }) => {
 					tool_data.drag_current = input.mouse.position;
+					tool_data.marquee_mode_toggled = input.keyboard.get(toggle_marquee_mode as usize);
 
 					responses.push_front(
 						DocumentMessage::Overlays(
@@ -643,7 +722,7 @@ impl Fsm for SelectToolFsmState {
 					let mut cursor = tool_data.bounding_box_overlays.as_ref().map_or(MouseCursorIcon::Default, |bounds| bounds.get_cursor(input, true));
 
 					// Dragging the pivot overrules the other operations
-					if tool_data.pivot.is_over(input.mouse.position) {
+					if tool_data.pivot.is_over(document, input.mouse.position) {
 						cursor = MouseCursorIcon::Move;
 					}
 
@@ -687,18 +766,28 @@ impl Fsm for SelectToolFsmState {
 					Ready
 				}
 				(DraggingPivot, DragStop) => {
+					// Clicking the pivot without moving it shouldn't add a step to the undo history
+					let response = match input.mouse.position.distance(tool_data.drag_start) < 10. * f64::EPSILON {
+						true => DocumentMessage::AbortTransaction,
+						false => DocumentMessage::CommitTransaction,
+					};
 					tool_data.snap_manager.cleanup(responses);
+					responses.push_back(response.into());
 
 					Ready
 				}
 				(DrawingBox, DragStop) => {
 					let quad = tool_data.selection_quad();
-					responses.push_front(
-						DocumentMessage::AddSelectedLayers {
-							additional_layers: document.graphene_document.intersects_quad_root(quad, font_cache),
-						}
-						.into(),
-					);
+					let marquee_mode = match tool_data.marquee_mode_toggled {
+						true => tool_options.marquee_mode.toggled(),
+						false => tool_options.marquee_mode,
+					};
+					let additional_layers = match marquee_mode {
+						MarqueeMode::Touching => document.graphene_document.intersects_quad_root(quad, font_cache),
+						MarqueeMode::Enclosed => document.graphene_document.enclosed_by_quad_root(quad, font_cache),
+					};
+
+					responses.push_front(DocumentMessage::AddSelectedLayers { additional_layers }.into());
 					responses.push_front(
 						DocumentMessage::Overlays(
 							Operation::DeleteLayer {
@@ -719,6 +808,12 @@ impl Fsm for SelectToolFsmState {
 
 					Ready
 				}
+				(DraggingPivot, Abort) => {
+					tool_data.snap_manager.cleanup(responses);
+					responses.push_back(DocumentMessage::AbortTransaction.into());
+
+					Ready
+				}
 				(_, Abort) => {
 					if let Some(path) = tool_data.drag_box_overlay_layer.take() {
 						responses.push_front(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into())
@@ -762,7 +857,9 @@ impl Fsm for SelectToolFsmState {
 				}
 				(_, SetPivot { position }) => {
 					let pos: Option<DVec2> = position.into();
+					responses.push_back(DocumentMessage::StartTransaction.into());
 					tool_data.pivot.set_normalized_position(pos.unwrap(), document, font_cache, responses);
+					responses.push_back(DocumentMessage::CommitTransaction.into());
 
 					self
 				}
@@ -899,7 +996,13 @@ impl Fsm for SelectToolFsmState {
 					plus: false,
 				},
 			])]),
-			SelectToolFsmState::DrawingBox => HintData(vec![]),
+			SelectToolFsmState::DrawingBox => HintData(vec![HintGroup(vec![HintInfo {
+				key_groups: vec![KeysGroup(vec![Key::Alt])],
+				key_groups_mac: None,
+				mouse: None,
+				label: String::from("Switch Touching/Enclosed"),
+				plus: false,
+			}])]),
 			SelectToolFsmState::ResizingBounds => HintData(vec![]),
 			SelectToolFsmState::RotatingBounds => HintData(vec![HintGroup(vec![HintInfo {
 				key_groups: vec![KeysGroup(vec![Key::Control])],
@@ -434,7 +434,7 @@ impl Fsm for TextToolFsmState {
 				(Editing, UpdateBounds { new_text }) => {
 					resize_overlays(&mut tool_data.overlays, responses, 1);
 					let text = document.graphene_document.layer(&tool_data.path).unwrap().as_text().unwrap();
-					let quad = text.bounding_box(&new_text, text.load_face(font_cache));
+					let quad = text.bounding_box(&new_text, &text.load_faces(font_cache));
 
 					let transformed_quad = document.graphene_document.multiply_transforms(&tool_data.path).unwrap() * quad;
 					let bounds = transformed_quad.bounding_box();
@@ -0,0 +1,447 @@
+use crate::application::generate_uuid;
+use crate::consts::{COLOR_ACCENT, MANIPULATOR_GROUP_MARKER_SIZE, SELECTION_TOLERANCE};
+use crate::messages::frontend::utility_types::MouseCursorIcon;
+use crate::messages::input_mapper::utility_types::input_keyboard::{Key, KeysGroup, MouseMotion};
+use crate::messages::layout::utility_types::layout_widget::PropertyHolder;
+use crate::messages::prelude::*;
+use crate::messages::tool::utility_types::{EventToMessageMap, Fsm, ToolActionHandlerData, ToolMetadata, ToolTransition, ToolType};
+use crate::messages::tool::utility_types::{HintData, HintGroup, HintInfo};
+
+use graphene::color::Color;
+use graphene::intersection::Quad;
+use graphene::layers::layer_info::LayerDataType;
+use graphene::layers::style::{Fill, PathStyle, Stroke, WidthPoint};
+use graphene::layers::vector::subpath::Subpath;
+use graphene::LayerId;
+use graphene::Operation;
+
+use glam::{DAffine2, DVec2};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default)]
+pub struct WidthTool {
+	fsm_state: WidthToolFsmState,
+	data: WidthToolData,
+}
+
+#[remain::sorted]
+#[impl_message(Message, ToolMessage, Width)]
+#[derive(PartialEq, Eq, Clone, Debug, Hash, Serialize, Deserialize)]
+pub enum WidthToolMessage {
+	// Standard messages
+	#[remain::unsorted]
+	Abort,
+	#[remain::unsorted]
+	DocumentIsDirty,
+
+	// Tool-specific messages
+	Delete,
+	PointerDown,
+	PointerMove {
+		lock_position: Key,
+	},
+	PointerUp,
+}
+
+impl ToolMetadata for WidthTool {
+	fn icon_name(&self) -> String {
+		"VectorWidthTool".into()
+	}
+	fn tooltip(&self) -> String {
+		"Width Tool".into()
+	}
+	fn tool_type(&self) -> crate::messages::tool::utility_types::ToolType {
+		ToolType::Width
+	}
+}
+
+impl PropertyHolder for WidthTool {}
+
+impl<'a> MessageHandler<ToolMessage, ToolActionHandlerData<'a>> for WidthTool {
+	fn process_message(&mut self, message: ToolMessage, data: ToolActionHandlerData<'a>, responses: &mut VecDeque<Message>) {
+		if message == ToolMessage::UpdateHints {
+			self.fsm_state.update_hints(responses);
+			return;
+		}
+
+		if message == ToolMessage::UpdateCursor {
+			self.fsm_state.update_cursor(responses);
+			return;
+		}
+
+		let new_state = self.fsm_state.transition(message, &mut self.data, data, &(), responses);
+
+		if self.fsm_state != new_state {
+			self.fsm_state = new_state;
+			self.fsm_state.update_hints(responses);
+		}
+	}
+
+	advertise_actions!(WidthToolMessageDiscriminant;
+		PointerDown,
+		PointerUp,
+		PointerMove,
+		Delete,
+		Abort,
+	);
+}
+
+impl ToolTransition for WidthTool {
+	fn event_to_message_map(&self) -> EventToMessageMap {
+		EventToMessageMap {
+			document_dirty: Some(WidthToolMessage::DocumentIsDirty.into()),
+			tool_abort: Some(WidthToolMessage::Abort.into()),
+			selection_changed: None,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WidthToolFsmState {
+	Ready,
+	Dragging,
+}
+
+impl Default for WidthToolFsmState {
+	fn default() -> Self {
+		WidthToolFsmState::Ready
+	}
+}
+
+/// A shape layer with a visible stroke, whose width profile is edited by the tool
+#[derive(Clone, Debug)]
+struct WidthTarget {
+	layer_path: Vec<LayerId>,
+	stroke: Stroke,
+	/// The path of the shape in the space of its parent, which is where its stroke is drawn
+	subpath: Subpath,
+	/// The transform from the space of the stroke to the viewport
+	to_viewport: DAffine2,
+}
+
+impl WidthTarget {
+	fn new(layer_path: &[LayerId], document: &DocumentMessageHandler) -> Option<Self> {
+		let layer = document.graphene_document.layer(layer_path).ok()?;
+		let shape = match &layer.data {
+			LayerDataType::Shape(shape) => shape,
+			_ => return None,
+		};
+		let stroke = shape.style.stroke().filter(Stroke::is_visible)?;
+
+		let to_viewport = document.graphene_document.multiply_transforms(layer_path).ok()? * layer.transform.inverse();
+		if !to_viewport.is_finite() {
+			return None;
+		}
+
+		let mut subpath = shape.shape.clone();
+		subpath.apply_affine(layer.transform);
+
+		Some(Self {
+			layer_path: layer_path.to_vec(),
+			stroke,
+			subpath,
+			to_viewport,
+		})
+	}
+
+	/// The index of each point of the width profile, together with the viewport positions of the ends of its line across the stroke on every contour.
+	fn handles(&self) -> Vec<(usize, [DVec2; 2])> {
+		let mut handles = Vec::new();
+		for contour_index in 0..self.subpath.contour_count() {
+			for (index, point) in self.stroke.width_profile().iter().enumerate() {
+				if let Some((position, normal)) = self.subpath.contour_point_at(contour_index, point.position) {
+					let offset = normal * self.stroke.width_at(point.position) / 2.;
+					handles.push((index, [position + offset, position - offset].map(|end| self.to_viewport.transform_point2(end))));
+				}
+			}
+		}
+		handles
+	}
+
+	/// Replaces the width profile of the stroke, returning the index `point` ends up at once the profile is sorted by position.
+	fn set_width_profile(&mut self, width_profile: Vec<WidthPoint>, point: WidthPoint, responses: &mut VecDeque<Message>) -> usize {
+		self.stroke = self.stroke.clone().with_width_profile(width_profile);
+		responses.push_back(
+			Operation::SetLayerStroke {
+				path: self.layer_path.clone(),
+				stroke: self.stroke.clone(),
+			}
+			.into(),
+		);
+
+		self.stroke.width_profile().iter().position(|&other| other == point).unwrap_or_default()
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+struct WidthToolData {
+	targets: Vec<WidthTarget>,
+	overlays: Vec<Vec<LayerId>>,
+	/// The layer and width profile index of the point last clicked, which is the one removed by deleting
+	selected_point: Option<(Vec<LayerId>, usize)>,
+	/// The stroke and width profile index of the point being dragged
+	dragging: Option<(WidthTarget, usize)>,
+}
+
+impl WidthToolData {
+	fn delete_overlays(&mut self, responses: &mut VecDeque<Message>) {
+		for path in self.overlays.drain(..) {
+			responses.push_back(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()).into());
+		}
+	}
+
+	fn generate_overlays(&mut self, responses: &mut VecDeque<Message>) {
+		for target in &self.targets {
+			for (index, [start, end]) in target.handles() {
+				let selected = self.selected_point.as_ref().map_or(false, |(path, selected)| *path == target.layer_path && *selected == index);
+
+				self.overlays.push(generate_overlay_line(start, end, responses));
+				self.overlays.push(generate_overlay_handle(start, selected, responses));
+				self.overlays.push(generate_overlay_handle(end, selected, responses));
+			}
+		}
+	}
+
+	/// The stroke and width profile index of the point with a handle under the mouse.
+	fn point_under_mouse(&self, mouse: DVec2) -> Option<(WidthTarget, usize)> {
+		let tolerance = MANIPULATOR_GROUP_MARKER_SIZE.powi(2);
+
+		self.targets.iter().find_map(|target| {
+			target
+				.handles()
+				.into_iter()
+				.find(|(_, ends)| ends.iter().any(|end| end.distance_squared(mouse) < tolerance))
+				.map(|(index, _)| (target.clone(), index))
+		})
+	}
+
+	/// The stroke under the mouse, together with a new point of its width profile at the closest position on its path, with the width the stroke already has there.
+	fn stroke_under_mouse(&self, mouse: DVec2) -> Option<(WidthTarget, WidthPoint)> {
+		self.targets.iter().find_map(|target| {
+			let (_, position, closest) = target.subpath.project_onto_contours(target.to_viewport.inverse().transform_point2(mouse))?;
+			let width = target.stroke.width_at(position);
+
+			let viewport_scale = target.to_viewport.matrix2.determinant().abs().sqrt();
+			let distance = target.to_viewport.transform_point2(closest).distance(mouse);
+			if distance > width / 2. * viewport_scale + SELECTION_TOLERANCE {
+				return None;
+			}
+
+			Some((target.clone(), WidthPoint::new(position, width / target.stroke.weight())))
+		})
+	}
+}
+
+fn generate_overlay_handle(translation: DVec2, selected: bool, responses: &mut VecDeque<Message>) -> Vec<LayerId> {
+	let path = vec![generate_uuid()];
+
+	let size = DVec2::splat(MANIPULATOR_GROUP_MARKER_SIZE);
+
+	let fill = if selected { Fill::solid(COLOR_ACCENT) } else { Fill::solid(Color::WHITE) };
+
+	let operation = Operation::AddEllipse {
+		path: path.clone(),
+		transform: DAffine2::from_scale_angle_translation(size, 0., translation - size / 2.).to_cols_array(),
+		style: PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), fill),
+		insert_index: -1,
+	};
+	responses.push_back(DocumentMessage::Overlays(operation.into()).into());
+
+	path
+}
+
+fn generate_overlay_line(start: DVec2, end: DVec2, responses: &mut VecDeque<Message>) -> Vec<LayerId> {
+	let path = vec![generate_uuid()];
+
+	let line_vector = end - start;
+	let scale = DVec2::splat(line_vector.length());
+	let angle = -line_vector.angle_between(DVec2::X);
+	let transform = DAffine2::from_scale_angle_translation(scale, angle, start).to_cols_array();
+
+	let operation = Operation::AddLine {
+		path: path.clone(),
+		transform,
+		style: PathStyle::new(Some(Stroke::new(COLOR_ACCENT, 1.0)), Fill::None),
+		insert_index: -1,
+	};
+	responses.push_back(DocumentMessage::Overlays(operation.into()).into());
+
+	path
+}
+
+impl Fsm for WidthToolFsmState {
+	type ToolData = WidthToolData;
+	type ToolOptions = ();
+
+	fn transition(
+		self,
+		event: ToolMessage,
+		tool_data: &mut Self::ToolData,
+		(document, _document_id, _global_tool_data, input, font_cache): ToolActionHandlerData,
+		_tool_options: &Self::ToolOptions,
+		responses: &mut VecDeque<Message>,
+	) -> Self {
+		if let ToolMessage::Width(event) = event {
+			match (self, event) {
+				(_, WidthToolMessage::DocumentIsDirty) => {
+					tool_data.delete_overlays(responses);
+
+					tool_data.targets = document.selected_visible_layers().filter_map(|path| WidthTarget::new(path, document)).collect();
+					tool_data.generate_overlays(responses);
+
+					self
+				}
+				(WidthToolFsmState::Ready, WidthToolMessage::PointerDown) => {
+					let mouse = input.mouse.position;
+
+					if let Some((target, index)) = tool_data.point_under_mouse(mouse) {
+						responses.push_back(DocumentMessage::StartTransaction.into());
+
+						tool_data.selected_point = Some((target.layer_path.clone(), index));
+						tool_data.dragging = Some((target, index));
+					} else if let Some((mut target, point)) = tool_data.stroke_under_mouse(mouse) {
+						responses.push_back(DocumentMessage::StartTransaction.into());
+
+						let mut width_profile = target.stroke.width_profile().to_vec();
+						width_profile.push(point);
+						let index = target.set_width_profile(width_profile, point, responses);
+
+						tool_data.selected_point = Some((target.layer_path.clone(), index));
+						tool_data.dragging = Some((target, index));
+					} else {
+						tool_data.selected_point = None;
+
+						let tolerance = DVec2::splat(SELECTION_TOLERANCE);
+						let quad = Quad::from_box([mouse - tolerance, mouse + tolerance]);
+						if let Some(intersection) = document.graphene_document.intersects_quad_root(quad, font_cache).pop() {
+							if !document.selected_layers_contains(&intersection) {
+								responses.push_back(
+									DocumentMessage::SetSelectedLayers {
+										replacement_selected_layers: vec![intersection],
+									}
+									.into(),
+								);
+							}
+						}
+					}
+
+					responses.push_back(BroadcastEvent::DocumentIsDirty.into());
+
+					if tool_data.dragging.is_some() {
+						WidthToolFsmState::Dragging
+					} else {
+						WidthToolFsmState::Ready
+					}
+				}
+				(WidthToolFsmState::Dragging, WidthToolMessage::PointerMove { lock_position }) => {
+					if let Some((target, index)) = &mut tool_data.dragging {
+						let mouse = target.to_viewport.inverse().transform_point2(input.mouse.position);
+						let mut width_profile = target.stroke.width_profile().to_vec();
+						let mut point = width_profile[*index];
+
+						// The distance from the path to the mouse is half the width of the stroke at the point
+						let half_width = if input.keyboard.get(lock_position as usize) {
+							target
+								.subpath
+								.project_onto_contours(mouse)
+								.and_then(|(contour_index, _, _)| target.subpath.contour_point_at(contour_index, point.position))
+								.map(|(position, normal)| normal.dot(mouse - position).abs())
+						} else {
+							target.subpath.project_onto_contours(mouse).map(|(_, position, closest)| {
+								point.position = position;
+								closest.distance(mouse)
+							})
+						};
+
+						if let Some(half_width) = half_width {
+							point.scale = 2. * half_width / target.stroke.weight();
+							width_profile[*index] = point;
+							*index = target.set_width_profile(width_profile, point, responses);
+
+							tool_data.selected_point = Some((target.layer_path.clone(), *index));
+						}
+					}
+
+					WidthToolFsmState::Dragging
+				}
+				(WidthToolFsmState::Dragging, WidthToolMessage::PointerUp) => {
+					responses.push_back(DocumentMessage::CommitTransaction.into());
+					tool_data.dragging = None;
+
+					WidthToolFsmState::Ready
+				}
+				(WidthToolFsmState::Ready, WidthToolMessage::Delete) => {
+					if let Some((layer_path, index)) = tool_data.selected_point.take() {
+						if let Some(mut target) = WidthTarget::new(&layer_path, document) {
+							let mut width_profile = target.stroke.width_profile().to_vec();
+							if index < width_profile.len() {
+								let point = width_profile.remove(index);
+
+								responses.push_back(DocumentMessage::StartTransaction.into());
+								target.set_width_profile(width_profile, point, responses);
+								responses.push_back(DocumentMessage::CommitTransaction.into());
+							}
+						}
+					}
+
+					WidthToolFsmState::Ready
+				}
+				(_, WidthToolMessage::Abort) => {
+					if self == WidthToolFsmState::Dragging {
+						responses.push_back(DocumentMessage::AbortTransaction.into());
+					}
+					tool_data.dragging = None;
+					tool_data.delete_overlays(responses);
+
+					WidthToolFsmState::Ready
+				}
+				_ => self,
+			}
+		} else {
+			self
+		}
+	}
+
+	fn update_hints(&self, responses: &mut VecDeque<Message>) {
+		let hint_data = match self {
+			WidthToolFsmState::Ready => HintData(vec![
+				HintGroup(vec![
+					HintInfo {
+						key_groups: vec![],
+						key_groups_mac: None,
+						mouse: Some(MouseMotion::LmbDrag),
+						label: String::from("Adjust Width"),
+						plus: false,
+					},
+					HintInfo {
+						key_groups: vec![KeysGroup(vec![Key::Shift])],
+						key_groups_mac: None,
+						mouse: None,
+						label: String::from("Lock Position"),
+						plus: true,
+					},
+				]),
+				HintGroup(vec![HintInfo {
+					key_groups: vec![KeysGroup(vec![Key::Backspace])],
+					key_groups_mac: None,
+					mouse: None,
+					label: String::from("Delete Selected Point"),
+					plus: false,
+				}]),
+			]),
+			WidthToolFsmState::Dragging => HintData(vec![HintGroup(vec![HintInfo {
+				key_groups: vec![KeysGroup(vec![Key::Shift])],
+				key_groups_mac: None,
+				mouse: None,
+				label: String::from("Lock Position"),
+				plus: false,
+			}])]),
+		};
+
+		responses.push_back(FrontendMessage::UpdateInputHints { hint_data }.into());
+	}
+
+	fn update_cursor(&self, responses: &mut VecDeque<Message>) {
+		responses.push_back(FrontendMessage::UpdateMouseCursor { cursor: MouseCursorIcon::Default }.into());
+	}
+}
@@ -263,6 +263,7 @@ pub enum ToolType {
 
 	// Vector tool group
 	Path,
+	Width,
 	Pen,
 	Freehand,
 	Spline,
@@ -302,6 +303,7 @@ fn list_tools_in_groups() -> Vec<Vec<ToolAvailability>> {
 		vec![
 			// Vector tool group
 			ToolAvailability::Available(Box::new(path_tool::PathTool::default())),
+			ToolAvailability::Available(Box::new(width_tool::WidthTool::default())),
 			ToolAvailability::Available(Box::new(pen_tool::PenTool::default())),
 			ToolAvailability::Available(Box::new(freehand_tool::FreehandTool::default())),
 			ToolAvailability::Available(Box::new(spline_tool::SplineTool::default())),
@@ -366,6 +368,7 @@ pub fn tool_message_to_tool_type(tool_message: &ToolMessage) -> ToolType {
 
 		// Vector tool group
 		ToolMessage::Path(_) => ToolType::Path,
+		ToolMessage::Width(_) => ToolType::Width,
 		ToolMessage::Pen(_) => ToolType::Pen,
 		ToolMessage::Freehand(_) => ToolType::Freehand,
 		ToolMessage::Spline(_) => ToolType::Spline,
@@ -402,6 +405,7 @@ pub fn tool_type_to_activate_tool_message(tool_type: ToolType) -> ToolMessageDis
 
 		// Vector tool group
 		ToolType::Path => ToolMessageDiscriminant::ActivateToolPath,
+		ToolType::Width => ToolMessageDiscriminant::ActivateToolWidth,
 		ToolType::Pen => ToolMessageDiscriminant::ActivateToolPen,
 		ToolType::Freehand => ToolMessageDiscriminant::ActivateToolFreehand,
 		ToolType::Spline => ToolMessageDiscriminant::ActivateToolSpline,
@@ -467,6 +471,7 @@ mod tool_crash_on_layer_delete_tests {
 	#[test_case(ToolType::Ellipse; "while using ellipse tool")]
 	#[test_case(ToolType::Shape; "while using shape tool")]
 	#[test_case(ToolType::Path; "while using path tool")]
+	#[test_case(ToolType::Width; "while using width tool")]
 	fn should_not_crash_when_layer_is_deleted(tool: ToolType) {
 		set_uuid_seed(0);
 		let mut test_editor = Editor::new();
@@ -0,0 +1,413 @@
+//! The desktop build of Graphite, which runs the web frontend in a native window.
+//!
+//! The frontend calls the commands defined here to open and save documents with the system's file dialogs instead of the browser's uploads and downloads.
+//! The recently opened documents are kept in the app's config directory, and `.graphite` files opened from the file manager are passed in as arguments.
+//! Open documents and linked images are watched, so the frontend can offer to reload them when they're changed by other apps.
+//!
+//! The webview is never trusted with arbitrary paths: documents are only read from and written to paths the user picked in a dialog, launched the app with, or opened before,
+//! and linked files are only read if they're images.
+
+// Keeps a console window from opening alongside the app in release builds on Windows
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::api::dialog::blocking::FileDialogBuilder;
+use tauri::{AppHandle, Manager, State, WindowEvent};
+
+const FILE_EXTENSION: &str = "graphite";
+const RECENT_DOCUMENTS_FILE: &str = "recent-documents.json";
+const MAX_RECENT_DOCUMENTS: usize = 10;
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "webp", "bmp", "svg"];
+
+/// A document read from disk, ready to be opened by the editor.
+#[derive(Serialize)]
+struct OpenedDocument {
+	path: String,
+	name: String,
+	content: Vec<u8>,
+}
+
+/// An image file read from disk, to be placed as a linked image whose handle is its path.
+#[derive(Serialize)]
+struct LinkedFile {
+	path: String,
+	name: String,
+	mime: String,
+	content: Vec<u8>,
+	/// When the file was last modified, in milliseconds since the Unix epoch like the `lastModified` of files in the browser
+	#[serde(rename = "lastModified")]
+	last_modified: f64,
+}
+
+/// Sent to the frontend when a watched file is changed or removed by another app.
+#[derive(Clone, Serialize)]
+struct FileChange {
+	path: String,
+	removed: bool,
+}
+
+/// The paths of recently opened and saved documents, most recent first, which are kept in a file to be listed again after a restart.
+struct RecentDocuments {
+	file: Option<PathBuf>,
+	paths: Mutex<Vec<String>>,
+}
+
+impl RecentDocuments {
+	fn load(config_dir: Option<PathBuf>) -> Self {
+		let file = config_dir.map(|dir| dir.join(RECENT_DOCUMENTS_FILE));
+		let paths = file
+			.as_ref()
+			.and_then(|file| std::fs::read_to_string(file).ok())
+			.and_then(|json| serde_json::from_str(&json).ok())
+			.unwrap_or_default();
+
+		Self { file, paths: Mutex::new(paths) }
+	}
+
+	fn paths(&self) -> Vec<String> {
+		self.paths.lock().unwrap().clone()
+	}
+
+	/// Moves the path to the top of the list, dropping the oldest path if the list is full.
+	fn add(&self, path: &str) {
+		let mut paths = self.paths.lock().unwrap();
+		paths.retain(|recent| recent != path);
+		paths.insert(0, path.to_string());
+		paths.truncate(MAX_RECENT_DOCUMENTS);
+		self.save(&paths);
+	}
+
+	fn remove(&self, path: &str) {
+		let mut paths = self.paths.lock().unwrap();
+		paths.retain(|recent| recent != path);
+		self.save(&paths);
+	}
+
+	fn clear(&self) {
+		let mut paths = self.paths.lock().unwrap();
+		paths.clear();
+		self.save(&paths);
+	}
+
+	fn save(&self, paths: &[String]) {
+		let file = match &self.file {
+			Some(file) => file,
+			None => return,
+		};
+
+		let result = file
+			.parent()
+			.map_or(Ok(()), std::fs::create_dir_all)
+			.and_then(|_| std::fs::write(file, serde_json::to_string(paths).unwrap_or_default()));
+		if let Err(error) = result {
+			log::warn!("The list of recent documents could not be saved to {}: {}", file.display(), error);
+		}
+	}
+}
+
+/// The document paths the user has chosen, through the open and save dialogs, the file manager, or the recent documents, which are the only ones the frontend may read or write.
+struct GrantedPaths(Mutex<HashSet<PathBuf>>);
+
+impl GrantedPaths {
+	fn new(paths: impl IntoIterator<Item = String>) -> Self {
+		Self(Mutex::new(paths.into_iter().map(PathBuf::from).collect()))
+	}
+
+	fn grant(&self, path: &str) {
+		self.0.lock().unwrap().insert(PathBuf::from(path));
+	}
+
+	/// Fails unless the path is a document the user has chosen.
+	fn check(&self, path: &str) -> Result<(), String> {
+		let path = Path::new(path);
+		if is_document(path) && self.0.lock().unwrap().contains(path) {
+			Ok(())
+		} else {
+			Err(format!("{} wasn't opened or saved by the user", path.display()))
+		}
+	}
+}
+
+/// Watches the open documents and linked images, telling the frontend when they're changed by other apps.
+/// The folders containing the files are watched instead of the files themselves, since many apps save by replacing the file with a new one.
+struct FileWatcher {
+	watcher: Mutex<Option<RecommendedWatcher>>,
+	/// The watched files by their path, with the path as it was given by the frontend
+	files: Arc<Mutex<HashMap<PathBuf, String>>>,
+	folders: Mutex<HashSet<PathBuf>>,
+	/// The modification times of the files last written by the app, or `None` while they're being written, whose changes aren't sent to the frontend
+	own_writes: Arc<Mutex<HashMap<PathBuf, Option<SystemTime>>>>,
+}
+
+impl FileWatcher {
+	fn new(app: AppHandle) -> Self {
+		let files: Arc<Mutex<HashMap<PathBuf, String>>> = Arc::default();
+		let own_writes: Arc<Mutex<HashMap<PathBuf, Option<SystemTime>>>> = Arc::default();
+
+		let (watched_files, written_files) = (files.clone(), own_writes.clone());
+		let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+			let event = match result {
+				Ok(event) if !event.kind.is_access() => event,
+				Ok(_) => return,
+				Err(error) => {
+					log::warn!("Watching files failed: {}", error);
+					return;
+				}
+			};
+
+			for changed in event.paths {
+				let path = match watched_files.lock().unwrap().get(&changed) {
+					Some(path) => path.clone(),
+					None => continue,
+				};
+				let modified = std::fs::metadata(&changed).and_then(|metadata| metadata.modified()).ok();
+				match written_files.lock().unwrap().get(&changed) {
+					Some(None) => continue,
+					Some(written) if modified.is_some() && *written == modified => continue,
+					_ => {}
+				}
+
+				let change = FileChange { path, removed: modified.is_none() };
+				if let Err(error) = app.emit_all("file-changed", change) {
+					log::error!("The frontend could not be told about a changed file: {}", error);
+				}
+			}
+		});
+		if let Err(error) = &watcher {
+			log::error!("Files can't be watched for changes: {}", error);
+		}
+
+		Self {
+			watcher: Mutex::new(watcher.ok()),
+			files,
+			folders: Mutex::default(),
+			own_writes,
+		}
+	}
+
+	/// Replaces the watched files with the given ones.
+	fn watch(&self, paths: Vec<String>) {
+		let files: HashMap<PathBuf, String> = paths.into_iter().map(|path| (PathBuf::from(&path), path)).collect();
+		let folders: HashSet<PathBuf> = files.keys().filter_map(|file| file.parent()).map(Path::to_path_buf).collect();
+
+		let mut watcher = self.watcher.lock().unwrap();
+		if let Some(watcher) = watcher.as_mut() {
+			let mut watched_folders = self.folders.lock().unwrap();
+			for folder in watched_folders.difference(&folders) {
+				let _ = watcher.unwatch(folder);
+			}
+			for folder in folders.difference(&watched_folders) {
+				if let Err(error) = watcher.watch(folder, RecursiveMode::NonRecursive) {
+					log::warn!("{} can't be watched for changes: {}", folder.display(), error);
+				}
+			}
+			*watched_folders = folders;
+		}
+
+		*self.files.lock().unwrap() = files;
+	}
+
+	/// Writes the file, remembering that the app wrote it so the change isn't mistaken for one made by another app.
+	fn write(&self, path: &Path, data: Vec<u8>) -> std::io::Result<()> {
+		self.own_writes.lock().unwrap().insert(path.to_path_buf(), None);
+		let result = std::fs::write(path, data);
+
+		let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+		self.own_writes.lock().unwrap().insert(path.to_path_buf(), modified);
+		result
+	}
+}
+
+/// The documents the app was launched with, which the frontend opens once it's ready.
+/// On Windows and Linux, opening `.graphite` files with Graphite from the file manager passes their paths as arguments.
+struct LaunchDocuments(Mutex<Vec<String>>);
+
+fn is_document(path: &Path) -> bool {
+	path.extension().map_or(false, |extension| extension == FILE_EXTENSION)
+}
+
+fn image_mime(path: &Path) -> Option<String> {
+	let extension = path.extension()?.to_string_lossy().to_lowercase();
+	match extension.as_str() {
+		"jpg" | "jpeg" => Some("image/jpeg".into()),
+		"svg" => Some("image/svg+xml".into()),
+		"png" | "gif" | "webp" | "bmp" => Some(format!("image/{}", extension)),
+		_ => None,
+	}
+}
+
+/// Reads the image at the path, refusing files that aren't images so documents can't link to other files to read them.
+fn read_linked_file(path: &str) -> Option<LinkedFile> {
+	let path = Path::new(path);
+	let mime = image_mime(path)?;
+	if !std::fs::metadata(path).ok()?.is_file() {
+		return None;
+	}
+	let content = std::fs::read(path).ok()?;
+	let last_modified = std::fs::metadata(path)
+		.and_then(|metadata| metadata.modified())
+		.ok()
+		.and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+		.map_or(0., |duration| duration.as_millis() as f64);
+
+	Some(LinkedFile {
+		path: path.to_string_lossy().into_owned(),
+		name: path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+		mime,
+		content,
+		last_modified,
+	})
+}
+
+fn read(path: &str) -> Result<OpenedDocument, String> {
+	let content = std::fs::read(path).map_err(|error| error.to_string())?;
+	let name = Path::new(path).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| path.to_string());
+
+	Ok(OpenedDocument {
+		path: path.to_string(),
+		name,
+		content,
+	})
+}
+
+/// Shows the open dialog and reads the picked document, or returns `None` if the dialog is dismissed.
+// The command is async so the dialog blocks a worker thread instead of the main thread, which runs the window's event loop
+#[tauri::command]
+async fn open_document_dialog(recent_documents: State<'_, RecentDocuments>, granted_paths: State<'_, GrantedPaths>) -> Result<Option<OpenedDocument>, String> {
+	let path = match FileDialogBuilder::new().add_filter("Graphite Document", &[FILE_EXTENSION]).pick_file() {
+		Some(path) => path.to_string_lossy().into_owned(),
+		None => return Ok(None),
+	};
+
+	granted_paths.grant(&path);
+	let document = read(&path)?;
+	recent_documents.add(&path);
+	Ok(Some(document))
+}
+
+/// Reads the document at the given path, which is removed from the recent documents if it can't be read.
+/// Only documents the user has chosen can be read.
+#[tauri::command]
+fn read_document(path: String, recent_documents: State<RecentDocuments>, granted_paths: State<GrantedPaths>) -> Result<OpenedDocument, String> {
+	granted_paths.check(&path)?;
+
+	match read(&path) {
+		Ok(document) => {
+			recent_documents.add(&path);
+			Ok(document)
+		}
+		Err(error) => {
+			recent_documents.remove(&path);
+			Err(error)
+		}
+	}
+}
+
+/// Shows the save dialog with the given file name filled in, returning the picked path or `None` if the dialog is dismissed.
+#[tauri::command]
+async fn save_document_dialog(name: String, granted_paths: State<'_, GrantedPaths>) -> Result<Option<String>, String> {
+	let path = match FileDialogBuilder::new().add_filter("Graphite Document", &[FILE_EXTENSION]).set_file_name(&name).save_file() {
+		Some(path) => path,
+		None => return Ok(None),
+	};
+
+	// Some dialogs don't add the extension of the filter to the name that's typed in
+	let path = if is_document(&path) { path } else { path.with_extension(FILE_EXTENSION) };
+	let path = path.to_string_lossy().into_owned();
+	granted_paths.grant(&path);
+	Ok(Some(path))
+}
+
+/// Writes the document to the given path, which must have been picked in the save dialog or be a document the user has opened.
+#[tauri::command]
+fn write_document(path: String, data: Vec<u8>, recent_documents: State<RecentDocuments>, granted_paths: State<GrantedPaths>, file_watcher: State<FileWatcher>) -> Result<(), String> {
+	granted_paths.check(&path)?;
+
+	file_watcher.write(Path::new(&path), data).map_err(|error| error.to_string())?;
+	recent_documents.add(&path);
+	Ok(())
+}
+
+/// Shows the open dialog for an image to link, returning it or `None` if the dialog is dismissed.
+#[tauri::command]
+async fn pick_image_dialog() -> Option<LinkedFile> {
+	let path = FileDialogBuilder::new().add_filter("Images", &IMAGE_EXTENSIONS).pick_file()?;
+	read_linked_file(&path.to_string_lossy())
+}
+
+/// Reads the file of a linked image, or returns `None` if it was moved or deleted or isn't an image.
+#[tauri::command]
+fn read_image(path: String) -> Option<LinkedFile> {
+	read_linked_file(&path)
+}
+
+/// Watches the given documents and linked images for changes by other apps, instead of the files that were watched before.
+#[tauri::command]
+fn watch_files(paths: Vec<String>, file_watcher: State<FileWatcher>) {
+	file_watcher.watch(paths);
+}
+
+#[tauri::command]
+fn recent_documents(recent_documents: State<RecentDocuments>) -> Vec<String> {
+	recent_documents.paths()
+}
+
+#[tauri::command]
+fn clear_recent_documents(recent_documents: State<RecentDocuments>) {
+	recent_documents.clear();
+}
+
+/// Takes the paths of the documents the app was launched with, so they're only opened once even if the frontend reloads.
+#[tauri::command]
+fn launch_documents(launch_documents: State<LaunchDocuments>) -> Vec<String> {
+	std::mem::take(&mut *launch_documents.0.lock().unwrap())
+}
+
+/// Closes the app once the frontend has checked for unsaved changes.
+#[tauri::command]
+fn quit(app: AppHandle) {
+	app.exit(0);
+}
+
+fn main() {
+	let launch_documents: Vec<String> = std::env::args().skip(1).filter(|argument| is_document(Path::new(argument))).collect();
+
+	tauri::Builder::default()
+		.manage(LaunchDocuments(Mutex::new(launch_documents.clone())))
+		.setup(move |app| {
+			let recent_documents = RecentDocuments::load(app.path_resolver().app_config_dir());
+			app.manage(GrantedPaths::new(launch_documents.into_iter().chain(recent_documents.paths())));
+			app.manage(recent_documents);
+			app.manage(FileWatcher::new(app.handle()));
+			Ok(())
+		})
+		.on_window_event(|event| {
+			// The frontend knows which documents have unsaved changes, so it decides whether to ask before quitting
+			if let WindowEvent::CloseRequested { api, .. } = event.event() {
+				api.prevent_close();
+				if let Err(error) = event.window().emit("close-requested", ()) {
+					log::error!("The window could not be asked to close: {}", error);
+				}
+			}
+		})
+		.invoke_handler(tauri::generate_handler![
+			open_document_dialog,
+			read_document,
+			save_document_dialog,
+			write_document,
+			pick_image_dialog,
+			read_image,
+			watch_files,
+			recent_documents,
+			clear_recent_documents,
+			launch_documents,
+			quit
+		])
+		.run(tauri::generate_context!())
+		.expect("The Graphite desktop app failed to start");
+}
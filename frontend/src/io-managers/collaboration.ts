@@ -0,0 +1,35 @@
+import { type Editor } from "@/wasm-communication/editor";
+import { TriggerCollaborationJoin, TriggerCollaborationLeave, TriggerCollaborationSend } from "@/wasm-communication/messages";
+
+const COLLABORATION_CHANNEL_PREFIX = "graphite-collaboration:";
+
+export function createCollaborationManager(editor: Editor): () => void {
+	// The channel of each session a document in this tab is edited in, keyed by the session ID
+	const channels = new Map<string, BroadcastChannel>();
+
+	const leave = (session: string): void => {
+		channels.get(session)?.close();
+		channels.delete(session);
+	};
+
+	// Subscribe to process backend events
+	editor.subscriptions.subscribeJsMessage(TriggerCollaborationJoin, (triggerCollaborationJoin) => {
+		const { documentId, session } = triggerCollaborationJoin;
+		leave(session);
+
+		const channel = new BroadcastChannel(`${COLLABORATION_CHANNEL_PREFIX}${session}`);
+		channel.addEventListener("message", (e: MessageEvent<unknown>) => editor.instance.receiveCollaborationMessage(documentId, e.data));
+		channels.set(session, channel);
+	});
+
+	editor.subscriptions.subscribeJsMessage(TriggerCollaborationLeave, (triggerCollaborationLeave) => {
+		leave(triggerCollaborationLeave.session);
+	});
+
+	editor.subscriptions.subscribeJsMessage(TriggerCollaborationSend, (triggerCollaborationSend) => {
+		channels.get(triggerCollaborationSend.session)?.postMessage(triggerCollaborationSend.message);
+	});
+
+	// Return the destructor
+	return () => Array.from(channels.keys()).forEach(leave);
+}
@@ -0,0 +1,45 @@
+import { type PortfolioState } from "@/state-providers/portfolio";
+import { askToQuitWithUnsavedDocuments, isDesktop, launchDocuments, onCloseRequested, onFileChanged, quit, recentDocuments } from "@/utility-functions/desktop";
+import { type Editor } from "@/wasm-communication/editor";
+
+// Apps often save a file in several steps, so changes are only handled once the file hasn't changed for this long
+const FILE_CHANGE_SETTLE_MILLISECONDS = 300;
+
+export async function createDesktopManager(editor: Editor, portfolio: PortfolioState): Promise<() => void> {
+	if (!isDesktop()) return () => undefined;
+
+	// Ask before closing the window if any document has unsaved changes
+	const stopListening = await onCloseRequested(async () => {
+		const unsavedDocuments = portfolio.state.documents.filter((document) => !document.isSaved);
+		unsavedDocuments.forEach((document) => editor.instance.triggerAutoSave(document.id));
+
+		if (unsavedDocuments.length === 0 || editor.instance.hasCrashed() || (await askToQuitWithUnsavedDocuments())) await quit();
+	});
+
+	// Offer to reload documents and read linked images again when their files are changed by other apps
+	const pendingChanges = new Map<string, ReturnType<typeof setTimeout>>();
+	const stopWatching = await onFileChanged((path, removed) => {
+		clearTimeout(pendingChanges.get(path));
+		pendingChanges.set(
+			path,
+			setTimeout(() => {
+				pendingChanges.delete(path);
+				portfolio.desktopFileChanged(path, removed);
+			}, FILE_CHANGE_SETTLE_MILLISECONDS)
+		);
+	});
+
+	editor.instance.updateRecentDocuments(await recentDocuments());
+
+	// Open the documents Graphite was launched with from the file manager, one at a time to keep their order
+	await (await launchDocuments()).reduce(async (previous, path) => {
+		await previous;
+		await portfolio.openDesktopDocument(path);
+	}, Promise.resolve());
+
+	return () => {
+		stopListening();
+		stopWatching();
+		pendingChanges.forEach((timeout) => clearTimeout(timeout));
+	};
+}
@@ -1,6 +1,8 @@
 import { type DialogState } from "@/state-providers/dialog";
 import { type FullscreenState } from "@/state-providers/fullscreen";
 import { type PortfolioState } from "@/state-providers/portfolio";
+import { isDesktop } from "@/utility-functions/desktop";
+import { isImportableFile } from "@/utility-functions/files";
 import { makeKeyboardModifiersBitfield, textInputCleanup, getLocalizedScanCode } from "@/utility-functions/keyboard-entry";
 import { platformIsMac } from "@/utility-functions/platform";
 import { stripIndents } from "@/utility-functions/strip-indents";
@@ -18,6 +20,9 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 	app?.focus();
 
 	let viewportPointerInteractionOngoing = false;
+	// Fingers touching the canvas by pointer ID, which turn into a navigation gesture once there are two of them until they're all lifted
+	const canvasTouches = new Map<number, { x: number; y: number }>();
+	let touchGestureOngoing = false;
 	let textInput = undefined as undefined | HTMLDivElement;
 	let canvasFocused = true;
 
@@ -38,11 +43,16 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 		{ target: window, eventName: "pointermove", action: (e: PointerEvent): void => onPointerMove(e) },
 		{ target: window, eventName: "pointerdown", action: (e: PointerEvent): void => onPointerDown(e) },
 		{ target: window, eventName: "pointerup", action: (e: PointerEvent): void => onPointerUp(e) },
+		{ target: window, eventName: "pointercancel", action: (e: PointerEvent): void => onPointerUp(e) },
 		{ target: window, eventName: "dblclick", action: (e: PointerEvent): void => onDoubleClick(e) },
 		{ target: window, eventName: "mousedown", action: (e: MouseEvent): void => onMouseDown(e) },
+		{ target: window, eventName: "mouseup", action: (e: MouseEvent): void => onMouseUp(e) },
 		{ target: window, eventName: "wheel", action: (e: WheelEvent): void => onWheelScroll(e), options: { passive: false } },
 		{ target: window, eventName: "modifyinputfield", action: (e: CustomEvent): void => onModifyInputField(e) },
 		{ target: window.document.body, eventName: "paste", action: (e: ClipboardEvent): void => onPaste(e) },
+		{ target: window, eventName: "focus", action: (): void => editor.instance.refreshLinkedImages() },
+		{ target: window, eventName: "dragover", action: (e: DragEvent): void => preventFileDrop(e) },
+		{ target: window, eventName: "drop", action: (e: DragEvent): void => preventFileDrop(e) },
 		{
 			target: app as EventListenerTarget,
 			eventName: "blur",
@@ -129,6 +139,8 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 
 	// While any pointer button is already down, additional button down events are not reported, but they are sent as `pointermove` events and these are handled in the backend
 	function onPointerMove(e: PointerEvent): void {
+		if (updateTouchGesture(e)) return;
+
 		if (!e.buttons) viewportPointerInteractionOngoing = false;
 
 		// Don't redirect pointer movement to the backend if there's no ongoing interaction and it's over a floating menu on top of the canvas
@@ -146,7 +158,7 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 		}
 
 		const modifiers = makeKeyboardModifiersBitfield(e);
-		editor.instance.onMouseMove(e.clientX, e.clientY, e.buttons, modifiers);
+		editor.instance.onMouseMove(e.clientX, e.clientY, e.buttons, modifiers, makeStylusState(e));
 	}
 
 	function onPointerDown(e: PointerEvent): void {
@@ -166,21 +178,60 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 			else viewportPointerInteractionOngoing = isTargetingCanvas instanceof Element;
 		}
 
+		if (e.pointerType === "touch" && isTargetingCanvas instanceof Element) canvasTouches.set(e.pointerId, { x: e.clientX, y: e.clientY });
+		if (updateTouchGesture(e)) return;
+
 		if (viewportPointerInteractionOngoing) {
 			const modifiers = makeKeyboardModifiersBitfield(e);
-			editor.instance.onMouseDown(e.clientX, e.clientY, e.buttons, modifiers);
+			editor.instance.onMouseDown(e.clientX, e.clientY, e.buttons, modifiers, makeStylusState(e));
 		}
 	}
 
 	function onPointerUp(e: PointerEvent): void {
+		const wasTouchGesture = touchGestureOngoing && canvasTouches.has(e.pointerId);
+		canvasTouches.delete(e.pointerId);
+		if (wasTouchGesture) {
+			sendTouches();
+			if (canvasTouches.size === 0) touchGestureOngoing = false;
+			return;
+		}
+
 		if (!e.buttons) viewportPointerInteractionOngoing = false;
 
 		if (!textInput) {
 			const modifiers = makeKeyboardModifiersBitfield(e);
-			editor.instance.onMouseUp(e.clientX, e.clientY, e.buttons, modifiers);
+			editor.instance.onMouseUp(e.clientX, e.clientY, e.buttons, modifiers, makeStylusState(e));
 		}
 	}
 
+	// Touch gestures
+
+	// Returns true if the event belongs to a multi-touch gesture, which is sent to the backend in place of the individual pointer events
+	function updateTouchGesture(e: PointerEvent): boolean {
+		if (e.pointerType !== "touch" || !canvasTouches.has(e.pointerId)) return false;
+
+		canvasTouches.set(e.pointerId, { x: e.clientX, y: e.clientY });
+		if (canvasTouches.size >= 2) touchGestureOngoing = true;
+		if (!touchGestureOngoing) return false;
+
+		sendTouches();
+		return true;
+	}
+
+	function sendTouches(): void {
+		const touches = Array.from(canvasTouches.values()).flatMap(({ x, y }) => [x, y]);
+		editor.instance.onTouchUpdate(new Float64Array(touches));
+	}
+
+	// Pen input carries its pressure, tilt, and barrel button state, which mouse and touch input leave out
+	function makeStylusState(e: PointerEvent): Float64Array | undefined {
+		if (e.pointerType !== "pen") return undefined;
+
+		// The barrel button is reported like the secondary mouse button
+		const barrelButton = (e.buttons & 2) !== 0 ? 1 : 0;
+		return new Float64Array([e.pressure, e.tiltX, e.tiltY, barrelButton]);
+	}
+
 	function onDoubleClick(e: PointerEvent): void {
 		if (!e.buttons) viewportPointerInteractionOngoing = false;
 
@@ -198,6 +249,12 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 		if (e.button === 1) e.preventDefault();
 	}
 
+	function onMouseUp(e: MouseEvent): void {
+		// Block the browser from navigating back or forward when the side mouse buttons are released over the canvas, since they can be bound to editor actions
+		const isTargetingCanvas = e.target instanceof Element && e.target.closest("[data-canvas]");
+		if ((e.button === 3 || e.button === 4) && isTargetingCanvas) e.preventDefault();
+	}
+
 	function onWheelScroll(e: WheelEvent): void {
 		const { target } = e;
 		const isTargetingCanvas = target instanceof Element && target.closest("[data-canvas]");
@@ -224,7 +281,8 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 	// Window events
 
 	function onWindowResize(container: HTMLElement): void {
-		const viewports = Array.from(container.querySelectorAll("[data-canvas]"));
+		// The canvas of the active view comes first, followed by those of the document's other views
+		const viewports = [...Array.from(container.querySelectorAll("[data-canvas]")), ...Array.from(container.querySelectorAll("[data-view-canvas]"))];
 		const boundsOfViewports = viewports.map((canvas) => {
 			const bounds = canvas.getBoundingClientRect();
 			return [bounds.left, bounds.top, bounds.right, bounds.bottom];
@@ -246,6 +304,9 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 		// Skip the message during development, since it's annoying when testing
 		if (editor.instance.inDevelopmentMode()) return;
 
+		// Skip the message in the desktop build, which asks before its window is closed instead
+		if (isDesktop()) return;
+
 		const allDocumentsSaved = document.state.documents.reduce((acc, doc) => acc && doc.isSaved, true);
 		if (!allDocumentsSaved) {
 			e.returnValue = "Unsaved work will be lost if the web browser tab is closed. Close anyway?";
@@ -253,6 +314,24 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 		}
 	}
 
+	// Files dropped outside the canvas would otherwise be opened by the browser in place of the editor
+	function preventFileDrop(e: DragEvent): void {
+		// The canvas has already accepted the drop
+		if (e.defaultPrevented || !e.dataTransfer?.types.includes("Files")) return;
+
+		e.preventDefault();
+		if (e.dataTransfer) e.dataTransfer.dropEffect = "none";
+	}
+
+	// Pass layer data or SVG markup from the clipboard to the editor, which copies layers as SVG markup embedding their data
+	function pasteText(text: string): void {
+		if (text.startsWith("graphite/layer: ")) {
+			editor.instance.pasteSerializedData(text.substring(16, text.length));
+		} else if (text.trimStart().startsWith("<") && /<svg[\s>]/.test(text)) {
+			editor.instance.pasteSvg(text);
+		}
+	}
+
 	function onPaste(e: ClipboardEvent): void {
 		const dataTransfer = e.clipboardData;
 		if (!dataTransfer || targetIsTextField(e.target || undefined)) return;
@@ -260,15 +339,11 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 
 		Array.from(dataTransfer.items).forEach((item) => {
 			if (item.type === "text/plain") {
-				item.getAsString((text) => {
-					if (text.startsWith("graphite/layer: ")) {
-						editor.instance.pasteSerializedData(text.substring(16, text.length));
-					}
-				});
+				item.getAsString((text) => pasteText(text));
 			}
 
 			const file = item.getAsFile();
-			if (file?.type.startsWith("image")) {
+			if (file && isImportableFile(file)) {
 				file.arrayBuffer().then((buffer): void => {
 					const u8Array = new Uint8Array(buffer);
 
@@ -302,11 +377,7 @@ export function createInputManager(editor: Editor, container: HTMLElement, dialo
 					const blob = await item.getType("text/plain");
 					const reader = new FileReader();
 					reader.onload = (): void => {
-						const text = reader.result as string;
-
-						if (text.startsWith("graphite/layer: ")) {
-							editor.instance.pasteSerializedData(text.substring(16, text.length));
-						}
+						pasteText(reader.result as string);
 					};
 					reader.readAsText(blob);
 				}
@@ -1,11 +1,14 @@
 import { reactive } from "vue";
 
 import { type Editor } from "@/wasm-communication/editor";
-import { TriggerFontLoad } from "@/wasm-communication/messages";
+import { type Font, TriggerFontLoad, UpdateCustomFonts } from "@/wasm-communication/messages";
 
 // eslint-disable-next-line @typescript-eslint/explicit-function-return-type
 export function createFontsState(editor: Editor) {
-	const state = reactive({});
+	const state = reactive({
+		// Fonts loaded from the user's font files, which are already in the editor so they're never downloaded
+		customFonts: [] as Font[],
+	});
 
 	function createURL(font: string): URL {
 		const url = new URL("https://fonts.googleapis.com/css2");
@@ -16,10 +19,16 @@ export function createFontsState(editor: Editor) {
 	}
 
 	async function fontNames(): Promise<{ name: string; url: URL | undefined }[]> {
-		return (await fontList).map((font) => ({ name: font.family, url: createURL(font.family) }));
+		const customFamilies = [...new Set(state.customFonts.map((font) => font.fontFamily))];
+		const customNames = customFamilies.map((family) => ({ name: family, url: undefined }));
+		const catalogNames = (await fontList).filter((font) => !customFamilies.includes(font.family)).map((font) => ({ name: font.family, url: createURL(font.family) }));
+		return [...customNames, ...catalogNames];
 	}
 
 	async function getFontStyles(fontFamily: string): Promise<{ name: string; url: URL | undefined }[]> {
+		const customStyles = state.customFonts.filter((font) => font.fontFamily === fontFamily);
+		if (customStyles.length > 0) return customStyles.map((font) => ({ name: font.fontStyle, url: undefined }));
+
 		const font = (await fontList).find((value) => value.family === fontFamily);
 		return font?.variants.map((variant) => ({ name: variant, url: undefined })) || [];
 	}
@@ -47,6 +56,9 @@ export function createFontsState(editor: Editor) {
 	}
 
 	// Subscribe to process backend events
+	editor.subscriptions.subscribeJsMessage(UpdateCustomFonts, (updateCustomFonts) => {
+		state.customFonts = updateCustomFonts.fonts;
+	});
 	editor.subscriptions.subscribeJsMessage(TriggerFontLoad, async (triggerFontLoad) => {
 		const url = await getFontFileUrl(triggerFontLoad.font.fontFamily, triggerFontLoad.font.fontStyle);
 		if (url) {
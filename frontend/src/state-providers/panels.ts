@@ -9,11 +9,15 @@ import {
 	TriggerViewportResize,
 	UpdateDocumentArtboards,
 	UpdateDocumentArtwork,
+	UpdateDocumentArtworkGpu,
+	UpdateDocumentArtworkPixels,
+	UpdateDocumentArtworkSlices,
 	UpdateDocumentBarLayout,
 	UpdateDocumentModeLayout,
 	UpdateDocumentOverlays,
 	UpdateDocumentRulers,
 	UpdateDocumentScrollbars,
+	UpdateDocumentViews,
 	UpdateEyedropperSamplingState,
 	UpdateMouseCursor,
 	UpdateToolOptionsLayout,
@@ -41,6 +45,20 @@ export function createPanelsState(editor: Editor) {
 			await nextTick();
 			state.documentPanel.updateDocumentArtwork(updateDocumentArtwork.svg);
 		});
+		editor.subscriptions.subscribeJsMessage(UpdateDocumentArtworkGpu, async (updateDocumentArtworkGpu) => {
+			await nextTick();
+			const { scene, viewTransform, dirtyTiles } = updateDocumentArtworkGpu;
+			state.documentPanel.updateDocumentArtworkGpu(scene, viewTransform, dirtyTiles);
+		});
+		editor.subscriptions.subscribeJsMessage(UpdateDocumentArtworkPixels, async (updateDocumentArtworkPixels) => {
+			await nextTick();
+			const { svg, width, height, transform, grid } = updateDocumentArtworkPixels;
+			state.documentPanel.updateDocumentArtworkPixels(svg, width, height, transform, grid);
+		});
+		editor.subscriptions.subscribeJsMessage(UpdateDocumentArtworkSlices, async (updateDocumentArtworkSlices) => {
+			await nextTick();
+			state.documentPanel.updateDocumentArtworkSlices(updateDocumentArtworkSlices.slices);
+		});
 		editor.subscriptions.subscribeJsMessage(UpdateDocumentOverlays, async (updateDocumentOverlays) => {
 			await nextTick();
 			state.documentPanel.updateDocumentOverlays(updateDocumentOverlays.svg);
@@ -71,6 +89,11 @@ export function createPanelsState(editor: Editor) {
 			const { origin, spacing, interval } = updateDocumentRulers;
 			state.documentPanel.updateDocumentRulers(origin, spacing, interval);
 		});
+		editor.subscriptions.subscribeJsMessage(UpdateDocumentViews, async (updateDocumentViews) => {
+			await nextTick();
+			const { svg, views } = updateDocumentViews;
+			state.documentPanel.updateDocumentViews(svg, views);
+		});
 
 		// Update mouse cursor icon
 		editor.subscriptions.subscribeJsMessage(UpdateMouseCursor, async (updateMouseCursor) => {
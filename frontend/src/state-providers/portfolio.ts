@@ -1,20 +1,40 @@
 /* eslint-disable max-classes-per-file */
 import { reactive, readonly } from "vue";
 
-import { downloadFileText, downloadFileBlob, upload } from "@/utility-functions/files";
+import { askToReloadDocument, clearRecentDocuments, isDesktop, openDocumentDialog, readDocument, recentDocuments, saveDocument, watchFiles } from "@/utility-functions/desktop";
+import { FONT_FILE_TYPES, IMPORTABLE_FILE_TYPES, KEYMAP_FILE_TYPES, PALETTE_FILE_TYPES, downloadFileText, downloadFileBlob, upload } from "@/utility-functions/files";
 import { imaginateGenerate, imaginateCheckConnection, imaginateTerminate } from "@/utility-functions/imaginate";
-import { rasterizeSVG } from "@/utility-functions/rasterization";
+import { linkedFilesSupported, pickLinkedImage, readLinkedFile } from "@/utility-functions/linked-files";
+import { convertImageColorSpace, decodeImagePixels, rasterizeSVG, rasterizeSVGCanvas, rasterizeSVGSupersampled } from "@/utility-functions/rasterization";
 import { type Editor } from "@/wasm-communication/editor";
 import {
 	type FrontendDocumentDetails,
+	type CmykProfile,
+	TriggerAnimationRasterization,
+	TriggerBatchRasterization,
+	TriggerBinaryFileDownload,
+	TriggerClearRecentDocuments,
 	TriggerFileDownload,
 	TriggerImport,
+	TriggerImportFont,
+	TriggerImportKeymap,
+	TriggerImportLinked,
+	TriggerImportPalette,
 	TriggerOpenDocument,
-	TriggerRasterDownload,
+	TriggerOpenRecentDocument,
+	TriggerRasterEncoding,
+	TriggerPdfRasterization,
+	TriggerImportColorConversion,
+	TriggerImageTrace,
+	TriggerLayerThumbnailRasterization,
 	TriggerImaginateGenerate,
 	TriggerImaginateTerminate,
 	TriggerImaginateCheckServerStatus,
+	TriggerReadLinkedImages,
+	TriggerRelinkImage,
+	TriggerSaveDocument,
 	UpdateActiveDocument,
+	UpdateCmykProfile,
 	UpdateOpenDocumentsList,
 	UpdateImageData,
 	TriggerRevokeBlobUrl,
@@ -26,40 +46,295 @@ export function createPortfolioState(editor: Editor) {
 		unsaved: false,
 		documents: [] as FrontendDocumentDetails[],
 		activeDocumentIndex: 0,
+		cmykProfile: { totalInkLimit: 3, blackGeneration: 1 } as CmykProfile,
 	});
 
+	// In the desktop build, the files that documents were opened from or saved to, which they're saved to again without showing the save dialog
+	const documentPaths = new Map<bigint, string>();
+	// In the desktop build, the paths of the linked images of each document, which are watched for changes along with the files of the documents
+	const documentLinkedImages = new Map<bigint, Set<string>>();
+
+	function updateWatchedFiles(): void {
+		const linkedImages = Array.from(documentLinkedImages.values()).flatMap((paths) => Array.from(paths));
+		watchFiles(Array.from(new Set([...documentPaths.values(), ...linkedImages])));
+	}
+
+	function watchLinkedImages(documentId: bigint, paths: string[]): void {
+		if (!isDesktop()) return;
+
+		const watched = documentLinkedImages.get(documentId) || new Set<string>();
+		if (paths.every((path) => watched.has(path))) return;
+
+		paths.forEach((path) => watched.add(path));
+		documentLinkedImages.set(documentId, watched);
+		updateWatchedFiles();
+	}
+
+	// Handles a watched file being changed by another app in the desktop build, offering to reload the documents saved to it and reading the linked images from it again
+	async function desktopFileChanged(path: string, removed: boolean): Promise<void> {
+		documentLinkedImages.forEach((paths, documentId) => {
+			// Removed images are listed in a dialog suggesting to relink them
+			if (paths.has(path)) editor.instance.refreshDocumentLinkedImages(documentId, removed);
+		});
+
+		const changedDocuments = Array.from(documentPaths.entries()).filter(([, documentPath]) => documentPath === path);
+		await changedDocuments.reduce(async (previous, [documentId]) => {
+			await previous;
+
+			const details = state.documents.find((document) => document.id === documentId);
+			if (!details) return;
+
+			// A document whose file was removed is saved to a newly picked file next time
+			if (removed) {
+				documentPaths.delete(documentId);
+				updateWatchedFiles();
+				return;
+			}
+
+			if (await askToReloadDocument(details.name, details.isSaved)) {
+				const { content } = await readDocument(path);
+				editor.instance.reloadDocumentFile(documentId, content);
+			} else {
+				// The document no longer matches its file
+				editor.instance.markDocumentUnsaved(documentId);
+			}
+		}, Promise.resolve());
+	}
+
+	// Opens a document in the desktop build from the given path, or from the one picked in the open dialog
+	async function openDesktopDocument(path?: string): Promise<void> {
+		try {
+			const opened = path ? await readDocument(path) : await openDocumentDialog();
+			if (opened) {
+				const previousDocument = state.documents[state.activeDocumentIndex]?.id;
+				editor.instance.openDocumentFile(opened.name, opened.content);
+
+				// The opened document becomes the active one, unless it couldn't be loaded
+				const openedDocument = state.documents[state.activeDocumentIndex]?.id;
+				if (openedDocument !== undefined && openedDocument !== previousDocument) {
+					documentPaths.set(openedDocument, opened.path);
+					updateWatchedFiles();
+				}
+			}
+		} catch (error) {
+			const reason = path ? `${path} couldn't be read, so it was removed from the recent documents.` : "The file couldn't be read.";
+			editor.instance.errorDialog("Document couldn't be opened", `${reason}\n\n${error}`);
+		}
+
+		editor.instance.updateRecentDocuments(await recentDocuments());
+	}
+
 	// Set up message subscriptions on creation
 	editor.subscriptions.subscribeJsMessage(UpdateOpenDocumentsList, (updateOpenDocumentList) => {
 		state.documents = updateOpenDocumentList.openDocuments;
+
+		// Stop watching the files of closed documents
+		const closedDocuments = [...documentPaths.keys(), ...documentLinkedImages.keys()].filter((id) => !state.documents.some((document) => document.id === id));
+		if (closedDocuments.length > 0) {
+			closedDocuments.forEach((id) => {
+				documentPaths.delete(id);
+				documentLinkedImages.delete(id);
+			});
+			updateWatchedFiles();
+		}
 	});
 	editor.subscriptions.subscribeJsMessage(UpdateActiveDocument, (updateActiveDocument) => {
 		// Assume we receive a correct document id
 		const activeId = state.documents.findIndex((doc) => doc.id === updateActiveDocument.documentId);
 		state.activeDocumentIndex = activeId;
 	});
+	editor.subscriptions.subscribeJsMessage(UpdateCmykProfile, (updateCmykProfile) => {
+		state.cmykProfile = updateCmykProfile.cmykProfile;
+	});
 	editor.subscriptions.subscribeJsMessage(TriggerOpenDocument, async () => {
+		if (isDesktop()) {
+			await openDesktopDocument();
+			return;
+		}
+
 		const extension = editor.instance.fileSaveSuffix();
-		const data = await upload(extension, "text");
+		const data = await upload(extension, "data");
 		editor.instance.openDocumentFile(data.filename, data.content);
 	});
+	editor.subscriptions.subscribeJsMessage(TriggerOpenRecentDocument, async (triggerOpenRecentDocument) => {
+		await openDesktopDocument(triggerOpenRecentDocument.path);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerClearRecentDocuments, async () => {
+		await clearRecentDocuments();
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerSaveDocument, async (triggerSaveDocument) => {
+		const { documentId, name, data, mime } = triggerSaveDocument;
+
+		if (!isDesktop()) {
+			downloadFileBlob(name, new Blob([new Uint8Array(data)], { type: mime }));
+			return;
+		}
+
+		try {
+			const path = await saveDocument(documentPaths.get(documentId), name, new Uint8Array(data));
+			if (path) {
+				documentPaths.set(documentId, path);
+				updateWatchedFiles();
+			} else {
+				editor.instance.markDocumentUnsaved(documentId);
+			}
+		} catch (error) {
+			editor.instance.markDocumentUnsaved(documentId);
+			editor.instance.errorDialog("Document couldn't be saved", `${error}`);
+		}
+
+		editor.instance.updateRecentDocuments(await recentDocuments());
+	});
 	editor.subscriptions.subscribeJsMessage(TriggerImport, async () => {
-		const data = await upload("image/*", "data");
+		const data = await upload(IMPORTABLE_FILE_TYPES, "data");
 		editor.instance.pasteImage(data.type, Uint8Array.from(data.content));
 	});
+	editor.subscriptions.subscribeJsMessage(TriggerImportFont, async () => {
+		const data = await upload(FONT_FILE_TYPES, "data");
+		editor.instance.loadFontFile(data.content);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerImportPalette, async () => {
+		const data = await upload(PALETTE_FILE_TYPES, "data");
+		editor.instance.loadPaletteFile(data.content);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerImportKeymap, async () => {
+		const data = await upload(KEYMAP_FILE_TYPES, "text");
+		editor.instance.loadKeymapFile(data.content);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerImportLinked, async () => {
+		if (!linkedFilesSupported()) {
+			editor.instance.errorDialog("Linked images are unsupported", "This browser can't keep access to files. Use Import instead to embed the image in the document.");
+			return;
+		}
+
+		const linked = await pickLinkedImage();
+		if (!linked) return;
+
+		const { handle, file } = linked;
+		const data = new Uint8Array(await file.arrayBuffer());
+		editor.instance.pasteLinkedImage(file.type, data, handle, file.name, file.lastModified);
+
+		const activeDocument = state.documents[state.activeDocumentIndex];
+		if (activeDocument) watchLinkedImages(activeDocument.id, [handle]);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerReadLinkedImages, async (triggerReadLinkedImages) => {
+		const { documentId, images, requestPermission } = triggerReadLinkedImages;
+		watchLinkedImages(documentId, images.map((image) => image.handle));
+
+		// The files are read one at a time since each may show a permission prompt
+		const unreadable: string[] = [];
+		await images.reduce(async (previous, image) => {
+			await previous;
+
+			const file = await readLinkedFile(image.handle, requestPermission);
+			if (!file) {
+				unreadable.push(image.fileName);
+			} else if (file.lastModified !== image.lastModified) {
+				const data = new Uint8Array(await file.arrayBuffer());
+				editor.instance.updateLinkedImage(documentId, image.layerPath, file.type, data, image.handle, file.name, file.lastModified);
+			}
+		}, Promise.resolve());
+
+		if (requestPermission && unreadable.length > 0) {
+			const files = unreadable.join("\n");
+			editor.instance.errorDialog("Linked images couldn't be read", `These files were moved, deleted, or linked in another browser. Use Relink Image to pick them again.\n\n${files}`);
+		}
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerRelinkImage, async (triggerRelinkImage) => {
+		const { documentId, layerPath } = triggerRelinkImage;
+
+		const linked = await pickLinkedImage();
+		if (!linked) return;
+
+		const { handle, file } = linked;
+		const data = new Uint8Array(await file.arrayBuffer());
+		editor.instance.updateLinkedImage(documentId, layerPath, file.type, data, handle, file.name, file.lastModified);
+		watchLinkedImages(documentId, [handle]);
+	});
 	editor.subscriptions.subscribeJsMessage(TriggerFileDownload, (triggerFileDownload) => {
 		downloadFileText(triggerFileDownload.name, triggerFileDownload.document);
 	});
-	editor.subscriptions.subscribeJsMessage(TriggerRasterDownload, async (triggerRasterDownload) => {
-		const { svg, name, mime, size } = triggerRasterDownload;
+	editor.subscriptions.subscribeJsMessage(TriggerRasterEncoding, async (triggerRasterEncoding) => {
+		const { svg, size, supersampling, colorSpace } = triggerRasterEncoding;
 
-		// Fill the canvas with white if it'll be a JPEG (which does not support transparency and defaults to black)
-		const backgroundColor = mime.endsWith("jpeg") ? "white" : undefined;
+		// Rasterize the SVG and send its pixels back to the editor, which encodes them into the image file and then has it downloaded
+		const canvas = await rasterizeSVGSupersampled(svg, size.x, size.y, undefined, colorSpace, supersampling);
+		const context = canvas.getContext("2d", { willReadFrequently: true, colorSpace });
+		const pixels = context?.getImageData(0, 0, canvas.width, canvas.height, { colorSpace }).data || new Uint8ClampedArray(canvas.width * canvas.height * 4);
+		editor.instance.finishRasterExport(new Uint8Array(pixels.buffer), canvas.width, canvas.height);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerBinaryFileDownload, (triggerBinaryFileDownload) => {
+		const { data, name, mime } = triggerBinaryFileDownload;
+
+		downloadFileBlob(name, new Blob([new Uint8Array(data)], { type: mime }));
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerPdfRasterization, async (triggerPdfRasterization) => {
+		// Rasterize the content the PDF can't represent and send back the pixels of every raster, one after another
+		const pixels = await Promise.all(
+			triggerPdfRasterization.rasters.map(async ({ svg, width, height }) => {
+				const canvas = await rasterizeSVGCanvas(svg, width, height);
+				const context = canvas.getContext("2d", { willReadFrequently: true });
+				return context?.getImageData(0, 0, width, height).data || new Uint8ClampedArray(width * height * 4);
+			})
+		);
+
+		const rasterPixels = new Uint8Array(pixels.reduce((length, raster) => length + raster.length, 0));
+		pixels.reduce((offset, raster) => {
+			rasterPixels.set(raster, offset);
+			return offset + raster.length;
+		}, 0);
+		editor.instance.finishPdfExport(rasterPixels);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerBatchRasterization, async (triggerBatchRasterization) => {
+		const { rasters, colorSpace } = triggerBatchRasterization;
+
+		// Rasterize each file, encoding PNGs here and sending back the pixels of the formats the editor encodes itself
+		const files = await Promise.all(
+			rasters.map(async ({ svg, width, height, encodePng }) => {
+				const canvas = await rasterizeSVGCanvas(svg, width, height, undefined, colorSpace);
+				if (encodePng) {
+					const blob = await new Promise<Blob | null>((resolve) => canvas.toBlob(resolve, "image/png"));
+					return new Uint8Array((await blob?.arrayBuffer()) || new ArrayBuffer(0));
+				}
+				const context = canvas.getContext("2d", { willReadFrequently: true, colorSpace });
+				return new Uint8Array(context?.getImageData(0, 0, width, height, { colorSpace }).data.buffer || new ArrayBuffer(width * height * 4));
+			})
+		);
+
+		const data = new Uint8Array(files.reduce((length, file) => length + file.length, 0));
+		files.reduce((offset, file) => {
+			data.set(file, offset);
+			return offset + file.length;
+		}, 0);
+		editor.instance.finishBatchExport(data, new Uint32Array(files.map((file) => file.length)));
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerAnimationRasterization, async (triggerAnimationRasterization) => {
+		const { frames, size, supersampling, colorSpace } = triggerAnimationRasterization;
 
-		// Rasterize the SVG to an image file
-		const blob = await rasterizeSVG(svg, size.x, size.y, mime, backgroundColor);
+		// Rasterize every frame and send back their pixels one after another, which the editor encodes into the animated image file and then has it downloaded
+		const canvases = await Promise.all(frames.map((svg) => rasterizeSVGSupersampled(svg, size.x, size.y, undefined, colorSpace, supersampling)));
+		const { width, height } = canvases[0];
+		const pixels = new Uint8Array(width * height * 4 * canvases.length);
+		canvases.forEach((canvas, index) => {
+			const context = canvas.getContext("2d", { willReadFrequently: true, colorSpace });
+			const frame = context?.getImageData(0, 0, width, height, { colorSpace }).data;
+			if (frame) pixels.set(frame, index * width * height * 4);
+		});
+		editor.instance.finishAnimationExport(pixels, width, height);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerImportColorConversion, async (triggerImportColorConversion) => {
+		const { mime, imageData, colorSpace, mouse } = triggerImportColorConversion;
 
-		// Have the browser download the file to the user's disk
-		downloadFileBlob(name, blob);
+		// Convert the image into the document's working color space before adding it to the document
+		const converted = await convertImageColorSpace(mime, imageData, colorSpace);
+		editor.instance.pasteConvertedImage("image/png", converted, mouse?.x, mouse?.y);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerImageTrace, async (triggerImageTrace) => {
+		const { mime, imageData } = triggerImageTrace;
+
+		// The editor can't decode images itself, so it's sent the pixels to trace
+		const { data, width, height } = await decodeImagePixels(mime, imageData);
+		editor.instance.finishImageTrace(new Uint8Array(data.buffer), width, height);
 	});
 	editor.subscriptions.subscribeJsMessage(TriggerImaginateCheckServerStatus, async (triggerImaginateCheckServerStatus) => {
 		const { hostname } = triggerImaginateCheckServerStatus;
@@ -99,12 +374,20 @@ export function createPortfolioState(editor: Editor) {
 			editor.instance.setImageBlobURL(updateImageData.documentId, element.path, blobURL, image.width, image.height);
 		});
 	});
+	editor.subscriptions.subscribeJsMessage(TriggerLayerThumbnailRasterization, async (triggerLayerThumbnailRasterization) => {
+		const { documentId, layerPath, contentHash, svg, width, height } = triggerLayerThumbnailRasterization;
+
+		const canvas = await rasterizeSVGCanvas(svg, width, height);
+		editor.instance.setLayerThumbnail(documentId, layerPath, contentHash, canvas.toDataURL("image/png"));
+	});
 	editor.subscriptions.subscribeJsMessage(TriggerRevokeBlobUrl, async (triggerRevokeBlobUrl) => {
 		URL.revokeObjectURL(triggerRevokeBlobUrl.url);
 	});
 
 	return {
 		state: readonly(state) as typeof state,
+		openDesktopDocument,
+		desktopFileChanged,
 	};
 }
 export type PortfolioState = ReturnType<typeof createPortfolioState>;
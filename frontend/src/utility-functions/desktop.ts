@@ -0,0 +1,108 @@
+// The desktop build runs the editor in a Tauri window, whose commands (in `frontend/src-tauri`) open and save documents with the system's file dialogs.
+// Tauri also watches the files of open documents and linked images, whose handles are their paths in the desktop build, to tell when other apps change them.
+// Tauri's API is reached through the `__TAURI__` global, which is added to the page since `withGlobalTauri` is enabled in `tauri.conf.json`.
+
+export type DesktopDocument = { path: string; name: string; content: Uint8Array };
+
+type TauriApi = {
+	tauri: { invoke<T>(command: string, args?: Record<string, unknown>): Promise<T> };
+	event: { listen<T>(event: string, handler: (event: { payload: T }) => void): Promise<() => void> };
+	dialog: { ask(message: string, options: { title: string; type: "warning" }): Promise<boolean> };
+};
+type TauriWindow = Window & { __TAURI__?: TauriApi };
+
+// Documents are sent between the frontend and Tauri as JSON, so their bytes are arrays of numbers
+type OpenedDocument = { path: string; name: string; content: number[] };
+type LinkedFile = { path: string; name: string; mime: string; content: number[]; lastModified: number };
+
+function tauri(): TauriApi {
+	const api = (window as TauriWindow).__TAURI__;
+	if (!api) throw new Error("The desktop API was used outside of the desktop build");
+	return api;
+}
+
+function fromOpenedDocument({ path, name, content }: OpenedDocument): DesktopDocument {
+	return { path, name, content: Uint8Array.from(content) };
+}
+
+function fromLinkedFile({ name, mime, content, lastModified }: LinkedFile): File {
+	return new File([Uint8Array.from(content)], name, { type: mime, lastModified });
+}
+
+export function isDesktop(): boolean {
+	return "__TAURI__" in window;
+}
+
+// Shows the open dialog and reads the picked document, or resolves to undefined if the dialog is dismissed
+export async function openDocumentDialog(): Promise<DesktopDocument | undefined> {
+	const document = await tauri().tauri.invoke<OpenedDocument | null>("open_document_dialog");
+	return document ? fromOpenedDocument(document) : undefined;
+}
+
+// Reads the document at the given path, which rejects with the reason if it was moved or deleted
+export async function readDocument(path: string): Promise<DesktopDocument> {
+	return fromOpenedDocument(await tauri().tauri.invoke<OpenedDocument>("read_document", { path }));
+}
+
+// Writes the document to the given path, or to a path picked in the save dialog if it wasn't saved before, resolving to the path or undefined if the dialog is dismissed
+export async function saveDocument(path: string | undefined, name: string, data: Uint8Array): Promise<string | undefined> {
+	const savePath = path || (await tauri().tauri.invoke<string | null>("save_document_dialog", { name }));
+	if (!savePath) return undefined;
+
+	await tauri().tauri.invoke("write_document", { path: savePath, data: Array.from(data) });
+	return savePath;
+}
+
+// Shows the open dialog for an image to link, resolving to its path and file or undefined if the dialog is dismissed
+export async function pickImageDialog(): Promise<{ path: string; file: File } | undefined> {
+	const image = await tauri().tauri.invoke<LinkedFile | null>("pick_image_dialog");
+	return image ? { path: image.path, file: fromLinkedFile(image) } : undefined;
+}
+
+// Reads the file of a linked image, or resolves to undefined if it was moved or deleted
+export async function readImage(path: string): Promise<File | undefined> {
+	const image = await tauri().tauri.invoke<LinkedFile | null>("read_image", { path });
+	return image ? fromLinkedFile(image) : undefined;
+}
+
+// Watches the given files for changes by other apps, in place of the files watched before
+export async function watchFiles(paths: string[]): Promise<void> {
+	await tauri().tauri.invoke("watch_files", { paths });
+}
+
+// Calls the handler when a watched file is changed or removed by another app, resolving to a function that stops listening
+export async function onFileChanged(handler: (path: string, removed: boolean) => void): Promise<() => void> {
+	return tauri().event.listen<{ path: string; removed: boolean }>("file-changed", ({ payload }) => handler(payload.path, payload.removed));
+}
+
+export async function askToReloadDocument(name: string, isSaved: boolean): Promise<boolean> {
+	const warning = isSaved ? "" : " Its unsaved changes will be lost.";
+	return tauri().dialog.ask(`${name} was changed by another app. Reload it?${warning}`, { title: "Graphite", type: "warning" });
+}
+
+// The paths of the recently opened and saved documents, most recent first
+export async function recentDocuments(): Promise<string[]> {
+	return tauri().tauri.invoke<string[]>("recent_documents");
+}
+
+export async function clearRecentDocuments(): Promise<void> {
+	await tauri().tauri.invoke("clear_recent_documents");
+}
+
+// The paths of the documents Graphite was opened with from the file manager, which are only given once
+export async function launchDocuments(): Promise<string[]> {
+	return tauri().tauri.invoke<string[]>("launch_documents");
+}
+
+// Calls the handler when the window's close button is clicked, instead of closing it, resolving to a function that stops listening
+export async function onCloseRequested(handler: () => void): Promise<() => void> {
+	return tauri().event.listen("close-requested", handler);
+}
+
+export async function askToQuitWithUnsavedDocuments(): Promise<boolean> {
+	return tauri().dialog.ask("Unsaved work will be lost if Graphite is closed. Close anyway?", { title: "Graphite", type: "warning" });
+}
+
+export async function quit(): Promise<void> {
+	await tauri().tauri.invoke("quit");
+}
@@ -1,3 +1,20 @@
+// PDF, EPS, and DXF files are imported along with images, since the editor converts their vector artwork into layers
+export const IMPORTABLE_FILE_TYPES = "image/*,.pdf,.eps,.ps,.dxf,application/pdf,application/postscript";
+const VECTOR_FILE_EXTENSIONS = [".pdf", ".eps", ".ps", ".dxf"];
+
+// The editor converts WOFF and WOFF2 web fonts back into the TrueType or OpenType fonts they compress
+export const FONT_FILE_TYPES = ".ttf,.otf,.ttc,.woff,.woff2";
+
+// Adobe Swatch Exchange and GIMP palettes, which are imported as color styles
+export const PALETTE_FILE_TYPES = ".ase,.gpl";
+export const KEYMAP_FILE_TYPES = ".json";
+
+// Some systems give EPS and DXF files no MIME type, so the file extension is checked as well
+export function isImportableFile(file: File): boolean {
+	const name = file.name.toLowerCase();
+	return file.type.startsWith("image") || VECTOR_FILE_EXTENSIONS.some((extension) => name.endsWith(extension));
+}
+
 export function downloadFileURL(filename: string, url: string): void {
 	const element = document.createElement("a");
 
@@ -16,7 +33,10 @@ export function downloadFileBlob(filename: string, blob: Blob): void {
 }
 
 export function downloadFileText(filename: string, text: string): void {
-	const type = filename.endsWith(".svg") ? "image/svg+xml;charset=utf-8" : "text/plain;charset=utf-8";
+	let type = "text/plain;charset=utf-8";
+	if (filename.endsWith(".svg")) type = "image/svg+xml;charset=utf-8";
+	if (filename.endsWith(".json")) type = "application/json;charset=utf-8";
+	if (filename.endsWith(".dxf")) type = "image/vnd.dxf";
 
 	const blob = new Blob([text], { type });
 	downloadFileBlob(filename, blob);
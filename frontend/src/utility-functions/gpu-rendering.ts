@@ -0,0 +1,298 @@
+import { type GpuScene } from "@/wasm-communication/messages";
+
+type Affine = [number, number, number, number, number, number];
+
+// Minimum x, minimum y, maximum x, and maximum y
+type Bounds = [number, number, number, number];
+
+// X, y, width, and height in viewport pixels
+type Tile = [number, number, number, number];
+
+type UploadedItem = { bounds: Bounds } & (
+	| { kind: "shape"; buffer: WebGLBuffer; vertexCount: number; color: [number, number, number, number] }
+	| { kind: "image"; blobUrl: string; transform: Affine; opacity: number }
+);
+
+const SHAPE_VERTEX_SHADER = `#version 300 es
+in vec2 a_position;
+uniform mat3 u_view;
+void main() {
+	gl_Position = vec4((u_view * vec3(a_position, 1.)).xy, 0., 1.);
+}`;
+
+const SHAPE_FRAGMENT_SHADER = `#version 300 es
+precision mediump float;
+uniform vec4 u_color;
+out vec4 out_color;
+void main() {
+	out_color = vec4(u_color.rgb * u_color.a, u_color.a);
+}`;
+
+const IMAGE_VERTEX_SHADER = `#version 300 es
+in vec2 a_position;
+uniform mat3 u_view;
+uniform mat3 u_transform;
+out vec2 v_uv;
+void main() {
+	v_uv = a_position;
+	gl_Position = vec4((u_view * u_transform * vec3(a_position, 1.)).xy, 0., 1.);
+}`;
+
+const IMAGE_FRAGMENT_SHADER = `#version 300 es
+precision mediump float;
+uniform sampler2D u_image;
+uniform float u_opacity;
+in vec2 v_uv;
+out vec4 out_color;
+void main() {
+	out_color = texture(u_image, v_uv) * u_opacity;
+}`;
+
+// Converts the columns of an affine transform into a column-major 3x3 matrix
+function affineToMat3([a, b, c, d, e, f]: Affine): Float32Array {
+	return new Float32Array([a, b, 0, c, d, 0, e, f, 1]);
+}
+
+function transformBounds([a, b, c, d, e, f]: Affine, [minX, minY, maxX, maxY]: Bounds): Bounds {
+	const corners = [
+		[minX, minY],
+		[maxX, minY],
+		[maxX, maxY],
+		[minX, maxY],
+	].map(([x, y]) => [a * x + c * y + e, b * x + d * y + f]);
+	const xs = corners.map(([x]) => x);
+	const ys = corners.map(([, y]) => y);
+	return [Math.min(...xs), Math.min(...ys), Math.max(...xs), Math.max(...ys)];
+}
+
+function compileProgram(gl: WebGL2RenderingContext, vertexSource: string, fragmentSource: string): WebGLProgram {
+	const program = gl.createProgram();
+	if (!program) throw new Error("Can't create a WebGL program for the GPU viewport renderer");
+
+	[
+		[gl.VERTEX_SHADER, vertexSource],
+		[gl.FRAGMENT_SHADER, fragmentSource],
+	].forEach(([type, source]) => {
+		const shader = gl.createShader(type as number);
+		if (!shader) throw new Error("Can't create a WebGL shader for the GPU viewport renderer");
+		gl.shaderSource(shader, source as string);
+		gl.compileShader(shader);
+		if (!gl.getShaderParameter(shader, gl.COMPILE_STATUS)) throw new Error(`GPU viewport shader failed to compile: ${gl.getShaderInfoLog(shader)}`);
+		gl.attachShader(program, shader);
+	});
+
+	gl.bindAttribLocation(program, 0, "a_position");
+	gl.linkProgram(program);
+	if (!gl.getProgramParameter(program, gl.LINK_STATUS)) throw new Error(`GPU viewport program failed to link: ${gl.getProgramInfoLog(program)}`);
+	return program;
+}
+
+// Draws the tessellated document with WebGL2, filling every shape with the stencil-and-cover technique.
+// The geometry stays uploaded between updates, so panning the viewport only changes the view transform.
+export class GpuRenderer {
+	readonly canvas: HTMLCanvasElement;
+
+	private readonly gl: WebGL2RenderingContext;
+
+	private readonly shapeProgram: WebGLProgram;
+
+	private readonly imageProgram: WebGLProgram;
+
+	private readonly unitSquare: WebGLBuffer;
+
+	private readonly textures = new Map<string, WebGLTexture>();
+
+	private items: UploadedItem[] = [];
+
+	private viewTransform: Affine = [1, 0, 0, 1, 0, 0];
+
+	// Until the whole viewport has been drawn once, there are no tiles to keep
+	private drawn = false;
+
+	// Returns undefined if the browser doesn't support WebGL2, in which case the SVG renderer has to be used
+	static create(canvas: HTMLCanvasElement): GpuRenderer | undefined {
+		// The drawing buffer is preserved so the Eyedropper tool can sample it
+		const gl = canvas.getContext("webgl2", { stencil: true, antialias: true, premultipliedAlpha: true, preserveDrawingBuffer: true });
+		return gl ? new GpuRenderer(canvas, gl) : undefined;
+	}
+
+	private constructor(canvas: HTMLCanvasElement, gl: WebGL2RenderingContext) {
+		this.canvas = canvas;
+		this.gl = gl;
+		this.shapeProgram = compileProgram(gl, SHAPE_VERTEX_SHADER, SHAPE_FRAGMENT_SHADER);
+		this.imageProgram = compileProgram(gl, IMAGE_VERTEX_SHADER, IMAGE_FRAGMENT_SHADER);
+
+		const unitSquare = gl.createBuffer();
+		if (!unitSquare) throw new Error("Can't create a WebGL buffer for the GPU viewport renderer");
+		this.unitSquare = unitSquare;
+		gl.bindBuffer(gl.ARRAY_BUFFER, unitSquare);
+		gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([0, 0, 1, 0, 1, 1, 0, 0, 1, 1, 0, 1]), gl.STATIC_DRAW);
+	}
+
+	// Replaces the uploaded geometry with a new scene, or keeps the current one if the scene is undefined, then draws it with the view transform.
+	// Only the dirty tiles are drawn again if they're given, keeping the rest of the previously drawn viewport.
+	update(scene: GpuScene | undefined, viewTransform: Affine, dirtyTiles: Tile[] | undefined): void {
+		if (scene) this.upload(scene);
+		this.viewTransform = viewTransform;
+		this.render(this.drawn ? dirtyTiles : undefined);
+	}
+
+	private upload(scene: GpuScene): void {
+		const { gl } = this;
+		this.items.forEach((item) => {
+			if (item.kind === "shape") gl.deleteBuffer(item.buffer);
+		});
+
+		const usedBlobUrls = new Set<string>();
+		this.items = scene.items.flatMap((item): UploadedItem[] => {
+			if ("Image" in item) {
+				const { blobUrl, transform, opacity } = item.Image;
+				usedBlobUrls.add(blobUrl);
+				this.loadTexture(blobUrl);
+				return [{ kind: "image", blobUrl, transform, opacity, bounds: transformBounds(transform, [0, 0, 1, 1]) }];
+			}
+
+			// The cover quad spanning the bounds is stored after the triangles
+			const { triangles, bounds, color } = item.Shape;
+			const [minX, minY, maxX, maxY] = bounds;
+			const vertices = new Float32Array(triangles.length + 12);
+			vertices.set(triangles);
+			vertices.set([minX, minY, maxX, minY, maxX, maxY, minX, minY, maxX, maxY, minX, maxY], triangles.length);
+
+			const buffer = gl.createBuffer();
+			if (!buffer) return [];
+			gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+			gl.bufferData(gl.ARRAY_BUFFER, vertices, gl.STATIC_DRAW);
+			return [{ kind: "shape", buffer, vertexCount: triangles.length / 2, color, bounds }];
+		});
+
+		// Free the textures of images which are no longer in the document
+		[...this.textures.keys()].forEach((blobUrl) => {
+			if (usedBlobUrls.has(blobUrl)) return;
+			gl.deleteTexture(this.textures.get(blobUrl) || null);
+			this.textures.delete(blobUrl);
+		});
+	}
+
+	private loadTexture(blobUrl: string): void {
+		if (this.textures.has(blobUrl)) return;
+
+		const { gl } = this;
+		const texture = gl.createTexture();
+		if (!texture) return;
+		this.textures.set(blobUrl, texture);
+
+		const image = new Image();
+		image.onload = (): void => {
+			if (this.textures.get(blobUrl) !== texture) return;
+
+			gl.bindTexture(gl.TEXTURE_2D, texture);
+			gl.pixelStorei(gl.UNPACK_PREMULTIPLY_ALPHA_WEBGL, true);
+			gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, gl.RGBA, gl.UNSIGNED_BYTE, image);
+			gl.generateMipmap(gl.TEXTURE_2D);
+			gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, gl.LINEAR_MIPMAP_LINEAR);
+			gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_S, gl.CLAMP_TO_EDGE);
+			gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_T, gl.CLAMP_TO_EDGE);
+			this.render();
+		};
+		image.src = blobUrl;
+	}
+
+	render(dirtyTiles?: Tile[]): void {
+		const { gl, canvas } = this;
+		const dpiFactor = window.devicePixelRatio;
+		const width = canvas.clientWidth;
+		const height = canvas.clientHeight;
+		let tiles = dirtyTiles;
+		if (canvas.width !== Math.round(width * dpiFactor) || canvas.height !== Math.round(height * dpiFactor)) {
+			canvas.width = Math.round(width * dpiFactor);
+			canvas.height = Math.round(height * dpiFactor);
+			// Resizing clears the canvas
+			tiles = undefined;
+		}
+		if (tiles?.length === 0) return;
+
+		gl.viewport(0, 0, canvas.width, canvas.height);
+		gl.clearColor(0, 0, 0, 0);
+		gl.clearStencil(0);
+		gl.enable(gl.BLEND);
+		gl.blendFunc(gl.ONE, gl.ONE_MINUS_SRC_ALPHA);
+		gl.enableVertexAttribArray(0);
+
+		// Maps document space to the viewport in CSS pixels, then to clip space
+		const [a, b, c, d, e, f] = this.viewTransform;
+		const [sx, sy] = [2 / width, -2 / height];
+		const view = affineToMat3([a * sx, b * sy, c * sx, d * sy, e * sx - 1, f * sy + 1]);
+
+		// Each tile is cleared and drawn again with only the items overlapping it, while the scissor test keeps the pixels of the other tiles
+		if (tiles) gl.enable(gl.SCISSOR_TEST);
+		const itemBounds = tiles ? this.items.map((item) => transformBounds(this.viewTransform, item.bounds)) : [];
+		(tiles || [[0, 0, width, height] as Tile]).forEach(([x, y, tileWidth, tileHeight]) => {
+			if (tiles) gl.scissor(Math.floor(x * dpiFactor), Math.floor(canvas.height - (y + tileHeight) * dpiFactor), Math.ceil(tileWidth * dpiFactor), Math.ceil(tileHeight * dpiFactor));
+			gl.clear(gl.COLOR_BUFFER_BIT | gl.STENCIL_BUFFER_BIT);
+
+			this.items.forEach((item, index) => {
+				if (tiles) {
+					const [minX, minY, maxX, maxY] = itemBounds[index];
+					if (maxX < x || minX > x + tileWidth || maxY < y || minY > y + tileHeight) return;
+				}
+				this.drawItem(item, view);
+			});
+		});
+		gl.disable(gl.SCISSOR_TEST);
+
+		this.drawn = true;
+	}
+
+	private drawItem(item: UploadedItem, view: Float32Array): void {
+		const { gl } = this;
+
+		if (item.kind === "shape") {
+			gl.useProgram(this.shapeProgram);
+			gl.uniformMatrix3fv(gl.getUniformLocation(this.shapeProgram, "u_view"), false, view);
+			gl.uniform4fv(gl.getUniformLocation(this.shapeProgram, "u_color"), item.color);
+			gl.bindBuffer(gl.ARRAY_BUFFER, item.buffer);
+			gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+
+			// Stencil: count the winding of the triangles covering each pixel
+			gl.enable(gl.STENCIL_TEST);
+			gl.colorMask(false, false, false, false);
+			gl.stencilFunc(gl.ALWAYS, 0, 0xff);
+			gl.stencilOpSeparate(gl.FRONT, gl.KEEP, gl.KEEP, gl.INCR_WRAP);
+			gl.stencilOpSeparate(gl.BACK, gl.KEEP, gl.KEEP, gl.DECR_WRAP);
+			gl.drawArrays(gl.TRIANGLES, 0, item.vertexCount);
+
+			// Cover: draw the color where the winding is not zero, resetting the stencil for the next shape
+			gl.colorMask(true, true, true, true);
+			gl.stencilFunc(gl.NOTEQUAL, 0, 0xff);
+			gl.stencilOp(gl.KEEP, gl.KEEP, gl.ZERO);
+			gl.drawArrays(gl.TRIANGLES, item.vertexCount, 6);
+			gl.disable(gl.STENCIL_TEST);
+		} else {
+			const texture = this.textures.get(item.blobUrl);
+			if (!texture) return;
+
+			gl.useProgram(this.imageProgram);
+			gl.uniformMatrix3fv(gl.getUniformLocation(this.imageProgram, "u_view"), false, view);
+			gl.uniformMatrix3fv(gl.getUniformLocation(this.imageProgram, "u_transform"), false, affineToMat3(item.transform));
+			gl.uniform1f(gl.getUniformLocation(this.imageProgram, "u_opacity"), item.opacity);
+			gl.bindTexture(gl.TEXTURE_2D, texture);
+			gl.bindBuffer(gl.ARRAY_BUFFER, this.unitSquare);
+			gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+			gl.drawArrays(gl.TRIANGLES, 0, 6);
+		}
+	}
+
+	destroy(): void {
+		const { gl } = this;
+		this.items.forEach((item) => {
+			if (item.kind === "shape") gl.deleteBuffer(item.buffer);
+		});
+		this.textures.forEach((texture) => gl.deleteTexture(texture));
+		gl.deleteBuffer(this.unitSquare);
+		gl.deleteProgram(this.shapeProgram);
+		gl.deleteProgram(this.imageProgram);
+		this.items = [];
+		this.textures.clear();
+	}
+}
@@ -109,6 +109,8 @@ import NodeOutput from "@/../assets/icon-16px-solid/node-output.svg";
 import NodeShape from "@/../assets/icon-16px-solid/node-shape.svg";
 import NodeText from "@/../assets/icon-16px-solid/node-text.svg";
 import NodeTransform from "@/../assets/icon-16px-solid/node-transform.svg";
+import PadlockLocked from "@/../assets/icon-16px-solid/padlock-locked.svg";
+import PadlockUnlocked from "@/../assets/icon-16px-solid/padlock-unlocked.svg";
 import Paste from "@/../assets/icon-16px-solid/paste.svg";
 import Random from "@/../assets/icon-16px-solid/random.svg";
 import Regenerate from "@/../assets/icon-16px-solid/regenerate.svg";
@@ -164,6 +166,8 @@ const SOLID_16PX = {
 	NodeShape: { component: NodeShape, size: 16 },
 	NodeText: { component: NodeText, size: 16 },
 	NodeTransform: { component: NodeTransform, size: 16 },
+	PadlockLocked: { component: PadlockLocked, size: 16 },
+	PadlockUnlocked: { component: PadlockUnlocked, size: 16 },
 	Paste: { component: Paste, size: 16 },
 	Random: { component: Random, size: 16 },
 	Regenerate: { component: Regenerate, size: 16 },
@@ -231,6 +235,7 @@ import VectorRectangleTool from "@/../assets/icon-24px-two-tone/vector-rectangle
 import VectorShapeTool from "@/../assets/icon-24px-two-tone/vector-shape-tool.svg";
 import VectorSplineTool from "@/../assets/icon-24px-two-tone/vector-spline-tool.svg";
 import VectorTextTool from "@/../assets/icon-24px-two-tone/vector-text-tool.svg";
+import VectorWidthTool from "@/../assets/icon-24px-two-tone/vector-width-tool.svg";
 
 const TWO_TONE_24PX = {
 	GeneralArtboardTool: { component: GeneralArtboardTool, size: 24 },
@@ -255,6 +260,7 @@ const TWO_TONE_24PX = {
 	VectorShapeTool: { component: VectorShapeTool, size: 24 },
 	VectorSplineTool: { component: VectorSplineTool, size: 24 },
 	VectorTextTool: { component: VectorTextTool, size: 24 },
+	VectorWidthTool: { component: VectorWidthTool, size: 24 },
 } as const;
 
 // All icons
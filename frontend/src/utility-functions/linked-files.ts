@@ -0,0 +1,102 @@
+// Linked images keep a handle to the file they were placed from, so the file can be read again instead of saving its pixels in the document.
+// The handles are stored in IndexedDB under a key saved in the document, which lets them outlive the page, but only in this browser.
+// File handles come from the File System Access API, which is currently only supported by Chromium-based browsers.
+// The desktop build reads the files itself instead, so the handles of its linked images are the paths of their files.
+
+import { isDesktop, pickImageDialog, readImage } from "@/utility-functions/desktop";
+
+const LINKED_FILES_INDEXED_DB_NAME = "graphite-linked-files";
+const LINKED_FILES_INDEXED_DB_VERSION = 1;
+const LINKED_FILES_STORE = "file-handles";
+
+const IMAGE_FILE_TYPES = { description: "Images", accept: { "image/*": [".png", ".jpg", ".jpeg", ".gif", ".webp", ".bmp", ".svg"] } };
+
+type FilePermissionDescriptor = { mode: "read" };
+type LinkedFileHandle = FileSystemFileHandle & {
+	queryPermission(descriptor: FilePermissionDescriptor): Promise<PermissionState>;
+	requestPermission(descriptor: FilePermissionDescriptor): Promise<PermissionState>;
+};
+type FilePickerOptions = { types: typeof IMAGE_FILE_TYPES[]; multiple: boolean };
+type FilePickerWindow = Window & { showOpenFilePicker?: (options: FilePickerOptions) => Promise<LinkedFileHandle[]> };
+
+export type LinkedFile = { handle: string; file: File };
+
+export function linkedFilesSupported(): boolean {
+	return isDesktop() || "showOpenFilePicker" in window;
+}
+
+// Asks the user to pick an image file and stores its handle under a new key, or resolves to undefined if the picker is dismissed
+export async function pickLinkedImage(): Promise<LinkedFile | undefined> {
+	if (isDesktop()) {
+		const image = await pickImageDialog();
+		return image && { handle: image.path, file: image.file };
+	}
+
+	const { showOpenFilePicker } = window as FilePickerWindow;
+	if (!showOpenFilePicker) return undefined;
+
+	let handles: LinkedFileHandle[];
+	try {
+		handles = await showOpenFilePicker({ types: [IMAGE_FILE_TYPES], multiple: false });
+	} catch {
+		return undefined;
+	}
+
+	const key = Array.from(crypto.getRandomValues(new Uint8Array(16)), (byte) => byte.toString(16).padStart(2, "0")).join("");
+	await storeHandle(key, handles[0]);
+
+	return { handle: key, file: await handles[0].getFile() };
+}
+
+// Reads the file of a linked image, or resolves to undefined if its handle isn't stored in this browser, the browser hasn't been granted access to it, or it was moved or deleted
+// Asking for permission shows a prompt, which browsers only allow right after the user has clicked something
+export async function readLinkedFile(key: string, requestPermission: boolean): Promise<File | undefined> {
+	if (isDesktop()) return readImage(key);
+
+	const handle = await loadHandle(key).catch(() => undefined);
+	if (!handle) return undefined;
+
+	const descriptor: FilePermissionDescriptor = { mode: "read" };
+	let permission = await handle.queryPermission(descriptor);
+	if (permission === "prompt" && requestPermission) permission = await handle.requestPermission(descriptor);
+	if (permission !== "granted") return undefined;
+
+	try {
+		return await handle.getFile();
+	} catch {
+		return undefined;
+	}
+}
+
+function openDatabase(): Promise<IDBDatabase> {
+	return new Promise<IDBDatabase>((resolve, reject) => {
+		const dbOpenRequest = indexedDB.open(LINKED_FILES_INDEXED_DB_NAME, LINKED_FILES_INDEXED_DB_VERSION);
+
+		dbOpenRequest.onupgradeneeded = (): void => {
+			dbOpenRequest.result.createObjectStore(LINKED_FILES_STORE);
+		};
+		dbOpenRequest.onsuccess = (): void => resolve(dbOpenRequest.result);
+		dbOpenRequest.onerror = (): void => reject(dbOpenRequest.error);
+	});
+}
+
+async function storeHandle(key: string, handle: LinkedFileHandle): Promise<void> {
+	const db = await openDatabase();
+
+	return new Promise<void>((resolve, reject) => {
+		const transaction = db.transaction(LINKED_FILES_STORE, "readwrite");
+		transaction.objectStore(LINKED_FILES_STORE).put(handle, key);
+		transaction.oncomplete = (): void => resolve();
+		transaction.onerror = (): void => reject(transaction.error);
+	});
+}
+
+async function loadHandle(key: string): Promise<LinkedFileHandle | undefined> {
+	const db = await openDatabase();
+
+	return new Promise<LinkedFileHandle | undefined>((resolve, reject) => {
+		const request = db.transaction(LINKED_FILES_STORE, "readonly").objectStore(LINKED_FILES_STORE).get(key);
+		request.onsuccess = (): void => resolve(request.result);
+		request.onerror = (): void => reject(request.error);
+	});
+}
@@ -1,7 +1,7 @@
 import { replaceBlobURLsWithBase64 } from "@/utility-functions/files";
 
 // Rasterize the string of an SVG document at a given width and height and turn it into the blob data of an image file matching the given MIME type
-export async function rasterizeSVGCanvas(svg: string, width: number, height: number, backgroundColor?: string): Promise<HTMLCanvasElement> {
+export async function rasterizeSVGCanvas(svg: string, width: number, height: number, backgroundColor?: string, colorSpace?: PredefinedColorSpace): Promise<HTMLCanvasElement> {
 	let promiseResolve: (value: HTMLCanvasElement | PromiseLike<HTMLCanvasElement>) => void | undefined;
 	const promise = new Promise<HTMLCanvasElement>((resolve) => {
 		promiseResolve = resolve;
@@ -12,7 +12,7 @@ export async function rasterizeSVGCanvas(svg: string, width: number, height: num
 	const canvas = document.createElement("canvas");
 	canvas.width = width;
 	canvas.height = height;
-	const context = canvas.getContext("2d", { willReadFrequently: true });
+	const context = canvas.getContext("2d", { willReadFrequently: true, colorSpace });
 	if (!context) throw new Error("Can't create 2D context from canvas during SVG rasterization");
 
 	// Apply a background fill color if one is given
@@ -42,7 +42,25 @@ export async function rasterizeSVGCanvas(svg: string, width: number, height: num
 	return promise;
 }
 
-export async function rasterizeSVG(svg: string, width: number, height: number, mime: string, backgroundColor?: string): Promise<Blob> {
+// Rasterizing with supersampling renders the SVG at that many times the width and height, then averages each square of samples into one pixel to smooth jagged edges
+export async function rasterizeSVGSupersampled(
+	svg: string,
+	width: number,
+	height: number,
+	backgroundColor?: string,
+	colorSpace?: PredefinedColorSpace,
+	supersampling = 1
+): Promise<HTMLCanvasElement> {
+	const outputWidth = Math.max(Math.round(width), 1);
+	const outputHeight = Math.max(Math.round(height), 1);
+	if (supersampling <= 1) return rasterizeSVGCanvas(svg, outputWidth, outputHeight, backgroundColor, colorSpace);
+
+	const canvas = await rasterizeSVGCanvas(svg, outputWidth * supersampling, outputHeight * supersampling, backgroundColor, colorSpace);
+	return downsampleCanvas(canvas, supersampling, colorSpace);
+}
+
+// Rasterize the string of an SVG document, optionally with supersampling, and turn it into the blob data of an image file matching the given MIME type
+export async function rasterizeSVG(svg: string, width: number, height: number, mime: string, backgroundColor?: string, colorSpace?: PredefinedColorSpace, supersampling = 1): Promise<Blob> {
 	let promiseResolve: (value: Blob | PromiseLike<Blob>) => void | undefined;
 	let promiseReject: () => void | undefined;
 	const promise = new Promise<Blob>((resolve, reject) => {
@@ -50,7 +68,7 @@ export async function rasterizeSVG(svg: string, width: number, height: number, m
 		promiseReject = reject;
 	});
 
-	rasterizeSVGCanvas(svg, width, height, backgroundColor).then((canvas) => {
+	rasterizeSVGSupersampled(svg, width, height, backgroundColor, colorSpace, supersampling).then((canvas) => {
 		// Convert the canvas to an image of the correct MIME type
 		canvas.toBlob((blob) => {
 			if (blob !== null) promiseResolve(blob);
@@ -60,3 +78,81 @@ export async function rasterizeSVG(svg: string, width: number, height: number, m
 
 	return promise;
 }
+
+// Redraw an encoded image in the given color space, letting the browser convert it from its embedded color profile, and re-encode it as a PNG tagged with that color space
+export async function convertImageColorSpace(mime: string, imageData: Uint8Array, colorSpace: PredefinedColorSpace): Promise<Uint8Array> {
+	const image = await createImageBitmap(new Blob([imageData], { type: mime }));
+
+	const canvas = document.createElement("canvas");
+	canvas.width = image.width;
+	canvas.height = image.height;
+	const context = canvas.getContext("2d", { colorSpace });
+	if (!context) throw new Error("Can't create 2D context from canvas during image color space conversion");
+	context.drawImage(image, 0, 0);
+	image.close();
+
+	const blob = await new Promise<Blob | null>((resolve) => canvas.toBlob(resolve, "image/png"));
+	if (!blob) throw new Error("Can't encode the image after color space conversion");
+
+	return new Uint8Array(await blob.arrayBuffer());
+}
+
+// Decode an encoded image into its RGBA pixels
+export async function decodeImagePixels(mime: string, imageData: Uint8Array): Promise<ImageData> {
+	const image = await createImageBitmap(new Blob([imageData], { type: mime }));
+
+	const canvas = document.createElement("canvas");
+	canvas.width = image.width;
+	canvas.height = image.height;
+	const context = canvas.getContext("2d", { willReadFrequently: true });
+	if (!context) throw new Error("Can't create 2D context from canvas during image decoding");
+	context.drawImage(image, 0, 0);
+	image.close();
+
+	return context.getImageData(0, 0, canvas.width, canvas.height);
+}
+
+// Shrink a canvas by an integer factor with a box filter, averaging the colors of each square of pixels weighted by their alpha so transparent pixels don't darken the edges
+export function downsampleCanvas(source: HTMLCanvasElement, factor: number, colorSpace?: PredefinedColorSpace): HTMLCanvasElement {
+	const sourceContext = source.getContext("2d", { willReadFrequently: true, colorSpace });
+	if (!sourceContext) throw new Error("Can't create 2D context from canvas during downsampling");
+	const { data } = sourceContext.getImageData(0, 0, source.width, source.height, { colorSpace });
+
+	const canvas = document.createElement("canvas");
+	canvas.width = Math.floor(source.width / factor);
+	canvas.height = Math.floor(source.height / factor);
+	const context = canvas.getContext("2d", { colorSpace });
+	if (!context) throw new Error("Can't create 2D context from canvas during downsampling");
+	const output = context.createImageData(canvas.width, canvas.height, { colorSpace });
+
+	const samples = factor * factor;
+	for (let y = 0; y < canvas.height; y += 1) {
+		for (let x = 0; x < canvas.width; x += 1) {
+			let red = 0;
+			let green = 0;
+			let blue = 0;
+			let alpha = 0;
+			for (let sampleY = y * factor; sampleY < (y + 1) * factor; sampleY += 1) {
+				for (let sampleX = x * factor; sampleX < (x + 1) * factor; sampleX += 1) {
+					const index = (sampleY * source.width + sampleX) * 4;
+					const sampleAlpha = data[index + 3];
+					red += data[index] * sampleAlpha;
+					green += data[index + 1] * sampleAlpha;
+					blue += data[index + 2] * sampleAlpha;
+					alpha += sampleAlpha;
+				}
+			}
+
+			const index = (y * canvas.width + x) * 4;
+			if (alpha > 0) {
+				output.data[index] = Math.round(red / alpha);
+				output.data[index + 1] = Math.round(green / alpha);
+				output.data[index + 2] = Math.round(blue / alpha);
+			}
+			output.data[index + 3] = Math.round(alpha / samples);
+		}
+	}
+
+	context.putImageData(output, 0, 0);
+	return canvas;
+}
@@ -190,6 +190,45 @@ export class Color {
 
 		return { h, s, v, a };
 	}
+
+	// Mirrors `Color::to_cmyk()` in Rust, with each ink amount ranging from 0 to 1
+	toCMYK(profile: CmykProfile): CMYK {
+		const [r, g, b] = [this.red, this.green, this.blue].map((channel) => Math.min(Math.max(channel, 0), 1));
+
+		const k = (1 - Math.max(r, g, b)) * Math.min(Math.max(profile.blackGeneration, 0), 1);
+		if (k >= 1) return { c: 0, m: 0, y: 0, k: 1 };
+		const separate = (channel: number): number => Math.min(Math.max((1 - channel - k) / (1 - k), 0), 1);
+
+		return { c: separate(r), m: separate(g), y: separate(b), k };
+	}
+
+	// Mirrors `Color::from_cmyk()` in Rust
+	static fromCMYK(cmyk: CMYK, alpha: number): Color {
+		const print = (ink: number): number => (1 - ink) * (1 - cmyk.k);
+
+		return new Color(print(cmyk.c), print(cmyk.m), print(cmyk.y), alpha);
+	}
+
+	// Mirrors `CmykProfile::is_out_of_gamut()` in Rust
+	isOutOfCmykGamut(profile: CmykProfile): boolean {
+		const hdr = this.red > 1 || this.green > 1 || this.blue > 1;
+		const { c, m, y, k } = this.toCMYK(profile);
+
+		return hdr || c + m + y + k > profile.totalInkLimit + Number.EPSILON;
+	}
+}
+
+export type CMYK = { c: number; m: number; y: number; k: number };
+
+export class CmykProfile {
+	readonly totalInkLimit!: number;
+
+	readonly blackGeneration!: number;
+}
+
+export class UpdateCmykProfile extends JsMessage {
+	@Type(() => CmykProfile)
+	readonly cmykProfile!: CmykProfile;
 }
 
 export class UpdateActiveDocument extends JsMessage {
@@ -212,6 +251,53 @@ export class UpdateDocumentArtwork extends JsMessage {
 	readonly svg!: string;
 }
 
+export type GpuShape = {
+	triangles: number[];
+	bounds: [number, number, number, number];
+	color: [number, number, number, number];
+};
+
+export type GpuImage = {
+	transform: [number, number, number, number, number, number];
+	blobUrl: string;
+	opacity: number;
+};
+
+export type GpuSceneItem = { Shape: GpuShape } | { Image: GpuImage };
+
+export type GpuScene = { items: GpuSceneItem[] };
+
+export class UpdateDocumentArtworkGpu extends JsMessage {
+	// Undefined if the scene is unchanged since the last update and only the view transform has changed
+	readonly scene!: GpuScene | undefined;
+
+	readonly viewTransform!: [number, number, number, number, number, number];
+
+	// The tiles to draw again as x, y, width, and height in viewport pixels, or undefined to draw the whole viewport again
+	readonly dirtyTiles!: [number, number, number, number][] | undefined;
+}
+
+export class UpdateDocumentArtworkPixels extends JsMessage {
+	// The visible artwork with one unit for every pixel of the preview
+	readonly svg!: string;
+
+	readonly width!: number;
+
+	readonly height!: number;
+
+	// Places the pixels of the preview in the viewport
+	readonly transform!: [number, number, number, number, number, number];
+
+	readonly grid!: boolean;
+}
+
+// A run of unchanged layers is drawn from a raster snapshot, with its SVG only given the first time its key is used
+export type ArtworkSlice = { Svg: { svg: string } } | { Snapshot: { key: string; svg: string | undefined } };
+
+export class UpdateDocumentArtworkSlices extends JsMessage {
+	readonly slices!: ArtworkSlice[];
+}
+
 export class UpdateDocumentOverlays extends JsMessage {
 	readonly svg!: string;
 }
@@ -236,6 +322,18 @@ export class UpdateDocumentScrollbars extends JsMessage {
 	readonly multiplier!: XY;
 }
 
+// Another view of the document, drawing the artwork of the active view with this transform
+export type FrontendDocumentView = {
+	transform: [number, number, number, number, number, number];
+	label: string;
+};
+
+export class UpdateDocumentViews extends JsMessage {
+	readonly svg!: string;
+
+	readonly views!: FrontendDocumentView[];
+}
+
 export class UpdateDocumentRulers extends JsMessage {
 	@TupleToVec2
 	readonly origin!: XY;
@@ -283,25 +381,136 @@ export class TriggerFileDownload extends JsMessage {
 	readonly name!: string;
 }
 
+export class FrontendBatchRaster {
+	readonly svg!: string;
+
+	readonly width!: number;
+
+	readonly height!: number;
+
+	readonly encodePng!: boolean;
+}
+
+export class TriggerAnimationRasterization extends JsMessage {
+	readonly frames!: string[];
+
+	@TupleToVec2
+	readonly size!: XY;
+
+	readonly supersampling!: number;
+
+	readonly colorSpace!: PredefinedColorSpace;
+}
+
+export class TriggerBatchRasterization extends JsMessage {
+	@Type(() => FrontendBatchRaster)
+	readonly rasters!: FrontendBatchRaster[];
+
+	readonly colorSpace!: PredefinedColorSpace;
+}
+
+export class TriggerBinaryFileDownload extends JsMessage {
+	readonly data!: Uint8Array;
+
+	readonly name!: string;
+
+	readonly mime!: string;
+}
+
+export class TriggerLayerThumbnailRasterization extends JsMessage {
+	readonly documentId!: bigint;
+
+	readonly layerPath!: BigUint64Array;
+
+	readonly contentHash!: bigint;
+
+	readonly svg!: string;
+
+	readonly width!: number;
+
+	readonly height!: number;
+}
+
 export class TriggerLoadAutoSaveDocuments extends JsMessage {}
 
 export class TriggerLoadPreferences extends JsMessage {}
 
 export class TriggerOpenDocument extends JsMessage {}
 
+export class TriggerOpenRecentDocument extends JsMessage {
+	readonly path!: string;
+}
+
+export class TriggerClearRecentDocuments extends JsMessage {}
+
+export class TriggerCollaborationJoin extends JsMessage {
+	readonly documentId!: bigint;
+
+	readonly session!: string;
+}
+
+export class TriggerCollaborationLeave extends JsMessage {
+	readonly session!: string;
+}
+
+export class TriggerCollaborationSend extends JsMessage {
+	readonly session!: string;
+
+	// The message, which is only passed between editors so its contents aren't needed by the frontend
+	readonly message!: unknown;
+}
+
 export class TriggerImport extends JsMessage {}
 
+export class TriggerImportColorConversion extends JsMessage {
+	readonly mime!: string;
+
+	readonly imageData!: Uint8Array;
+
+	readonly colorSpace!: PredefinedColorSpace;
+
+	@TupleToVec2
+	readonly mouse!: XY | undefined;
+}
+
+export class TriggerImportFont extends JsMessage {}
+
+export class TriggerImportKeymap extends JsMessage {}
+
+export class TriggerImportLinked extends JsMessage {}
+
+export class TriggerImportPalette extends JsMessage {}
+
 export class TriggerPaste extends JsMessage {}
 
-export class TriggerRasterDownload extends JsMessage {
+export class FrontendPdfRaster {
 	readonly svg!: string;
 
-	readonly name!: string;
+	readonly width!: number;
 
-	readonly mime!: string;
+	readonly height!: number;
+}
+
+export class TriggerPdfRasterization extends JsMessage {
+	@Type(() => FrontendPdfRaster)
+	readonly rasters!: FrontendPdfRaster[];
+}
+
+export class TriggerRasterEncoding extends JsMessage {
+	readonly svg!: string;
 
 	@TupleToVec2
 	readonly size!: XY;
+
+	readonly supersampling!: number;
+
+	readonly colorSpace!: PredefinedColorSpace;
+}
+
+export class TriggerImageTrace extends JsMessage {
+	readonly mime!: string;
+
+	readonly imageData!: Uint8Array;
 }
 
 export class TriggerImaginateCheckServerStatus extends JsMessage {
@@ -361,12 +570,47 @@ export class TriggerImaginateTerminate extends JsMessage {
 	readonly hostname!: string;
 }
 
+export class FrontendLinkedImage {
+	readonly layerPath!: BigUint64Array;
+
+	readonly handle!: string;
+
+	readonly fileName!: string;
+
+	readonly lastModified!: number | undefined;
+}
+
+export class TriggerReadLinkedImages extends JsMessage {
+	readonly documentId!: bigint;
+
+	@Type(() => FrontendLinkedImage)
+	readonly images!: FrontendLinkedImage[];
+
+	readonly requestPermission!: boolean;
+}
+
 export class TriggerRefreshBoundsOfViewports extends JsMessage {}
 
+export class TriggerRelinkImage extends JsMessage {
+	readonly documentId!: bigint;
+
+	readonly layerPath!: BigUint64Array;
+}
+
 export class TriggerRevokeBlobUrl extends JsMessage {
 	readonly url!: string;
 }
 
+export class TriggerSaveDocument extends JsMessage {
+	readonly documentId!: bigint;
+
+	readonly name!: string;
+
+	readonly data!: Uint8Array;
+
+	readonly mime!: string;
+}
+
 export class TriggerSavePreferences extends JsMessage {
 	readonly preferences!: Record<string, unknown>;
 }
@@ -471,6 +715,10 @@ export class LayerPanelEntry {
 
 	visible!: boolean;
 
+	locked!: boolean;
+
+	label!: LayerLabel | undefined;
+
 	layerType!: LayerType;
 
 	@Transform(({ value }: { value: bigint[] }) => new BigUint64Array(value))
@@ -480,6 +728,8 @@ export class LayerPanelEntry {
 	layerMetadata!: LayerMetadata;
 
 	thumbnail!: string;
+
+	thumbnailRaster!: string | undefined;
 }
 
 export class LayerMetadata {
@@ -490,6 +740,8 @@ export class LayerMetadata {
 
 export type LayerType = "Imaginate" | "Folder" | "Image" | "Shape" | "Text";
 
+export type LayerLabel = "Red" | "Orange" | "Yellow" | "Green" | "Blue" | "Violet" | "Gray";
+
 export type LayerTypeData = {
 	name: string;
 	icon: IconName;
@@ -530,6 +782,11 @@ export class TriggerFontLoad extends JsMessage {
 	isDefault!: boolean;
 }
 
+export class UpdateCustomFonts extends JsMessage {
+	@Type(() => Font)
+	readonly fonts!: Font[];
+}
+
 export class TriggerVisitLink extends JsMessage {
 	url!: string;
 }
@@ -1045,30 +1302,54 @@ export const messageMakers: Record<string, MessageMaker> = {
 	DisplayEditableTextbox,
 	DisplayRemoveEditableTextbox,
 	TriggerAboutGraphiteLocalizedCommitDate,
+	TriggerAnimationRasterization,
+	TriggerBatchRasterization,
+	TriggerBinaryFileDownload,
+	TriggerClearRecentDocuments,
+	TriggerCollaborationJoin,
+	TriggerCollaborationLeave,
+	TriggerCollaborationSend,
+	TriggerImageTrace,
 	TriggerImaginateCheckServerStatus,
 	TriggerImaginateGenerate,
 	TriggerImaginateTerminate,
 	TriggerFileDownload,
 	TriggerFontLoad,
 	TriggerImport,
+	TriggerImportColorConversion,
+	TriggerImportFont,
+	TriggerImportKeymap,
+	TriggerImportLinked,
+	TriggerImportPalette,
 	TriggerIndexedDbRemoveDocument,
 	TriggerIndexedDbWriteDocument,
+	TriggerLayerThumbnailRasterization,
 	TriggerLoadAutoSaveDocuments,
 	TriggerLoadPreferences,
 	TriggerOpenDocument,
+	TriggerOpenRecentDocument,
 	TriggerPaste,
-	TriggerRasterDownload,
+	TriggerPdfRasterization,
+	TriggerRasterEncoding,
+	TriggerReadLinkedImages,
 	TriggerRefreshBoundsOfViewports,
+	TriggerRelinkImage,
 	TriggerRevokeBlobUrl,
+	TriggerSaveDocument,
 	TriggerSavePreferences,
 	TriggerTextCommit,
 	TriggerTextCopy,
 	TriggerViewportResize,
 	TriggerVisitLink,
 	UpdateActiveDocument,
+	UpdateCmykProfile,
+	UpdateCustomFonts,
 	UpdateDialogDetails,
 	UpdateDocumentArtboards,
 	UpdateDocumentArtwork,
+	UpdateDocumentArtworkGpu,
+	UpdateDocumentArtworkPixels,
+	UpdateDocumentArtworkSlices,
 	UpdateDocumentBarLayout,
 	UpdateDocumentLayerDetails,
 	UpdateDocumentLayerTreeStructure: newUpdateDocumentLayerTreeStructure,
@@ -1077,6 +1358,7 @@ export const messageMakers: Record<string, MessageMaker> = {
 	UpdateDocumentRulers,
 	UpdateEyedropperSamplingState,
 	UpdateDocumentScrollbars,
+	UpdateDocumentViews,
 	UpdateImageData,
 	UpdateInputHints,
 	UpdateLayerTreeOptionsLayout,
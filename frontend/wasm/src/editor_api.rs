@@ -9,10 +9,12 @@ use editor::application::generate_uuid;
 use editor::application::Editor;
 use editor::consts::{FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION};
 use editor::messages::input_mapper::utility_types::input_keyboard::ModifierKeys;
-use editor::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, ScrollDelta, ViewportBounds};
+use editor::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, ScrollDelta, StylusState, ViewportBounds};
 use editor::messages::portfolio::utility_types::{ImaginateServerStatus, Platform};
 use editor::messages::prelude::*;
-use graphene::color::Color;
+use graphene::color::{CmykProfile, Color};
+use graphene::gpu_scene::ViewportRenderer;
+use graphene::layers::image_layer::ImageLink;
 use graphene::layers::imaginate_layer::ImaginateStatus;
 use graphene::LayerId;
 
@@ -194,7 +196,7 @@ impl JsEditorHandle {
 	}
 
 	#[wasm_bindgen(js_name = openDocumentFile)]
-	pub fn open_document_file(&self, document_name: String, document_serialized_content: String) {
+	pub fn open_document_file(&self, document_name: String, document_serialized_content: Vec<u8>) {
 		let message = PortfolioMessage::OpenDocumentFile {
 			document_name,
 			document_serialized_content,
@@ -208,6 +210,41 @@ impl JsEditorHandle {
 			document_id,
 			document_name,
 			document_is_saved,
+			document_serialized_content: document_serialized_content.into_bytes(),
+		};
+		self.dispatch(message);
+	}
+
+	/// Handles a message from another editor in the document's collaboration session, given as it was sent by `TriggerCollaborationSend`
+	#[wasm_bindgen(js_name = receiveCollaborationMessage)]
+	pub fn receive_collaboration_message(&self, document_id: u64, message: JsValue) -> Result<(), JsValue> {
+		let message = from_value(message).map_err(|_| Error::new("Invalid message from another editor"))?;
+		let message = DocumentMessage::ReceiveCollaborationMessage { message };
+		self.dispatch(PortfolioMessage::DocumentPassMessage { document_id, message });
+		Ok(())
+	}
+
+	/// Lists the documents recently opened in the desktop build, given as an array of paths with the most recent first
+	#[wasm_bindgen(js_name = updateRecentDocuments)]
+	pub fn update_recent_documents(&self, paths: JsValue) -> Result<(), JsValue> {
+		let paths = from_value(paths).map_err(|_| Error::new("Invalid list of recent documents"))?;
+		let message = MenuBarMessage::SetRecentDocuments { paths };
+		self.dispatch(message);
+		Ok(())
+	}
+
+	/// Marks a document as unsaved again after saving it failed or was cancelled
+	#[wasm_bindgen(js_name = markDocumentUnsaved)]
+	pub fn mark_document_unsaved(&self, document_id: u64) {
+		let message = DocumentMessage::SetSaveState { is_saved: false };
+		self.dispatch(PortfolioMessage::DocumentPassMessage { document_id, message });
+	}
+
+	/// Replaces an open document with the content of its file, after the file was changed by another app
+	#[wasm_bindgen(js_name = reloadDocumentFile)]
+	pub fn reload_document_file(&self, document_id: u64, document_serialized_content: Vec<u8>) {
+		let message = PortfolioMessage::ReloadDocumentFile {
+			document_id,
 			document_serialized_content,
 		};
 		self.dispatch(message);
@@ -242,9 +279,11 @@ impl JsEditorHandle {
 	}
 
 	/// Mouse movement within the screenspace bounds of the viewport
+	/// The stylus state is given for pen input as [pressure, tilt X, tilt Y, barrel button]
 	#[wasm_bindgen(js_name = onMouseMove)]
-	pub fn on_mouse_move(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_move(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8, stylus: Option<Box<[f64]>>) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.stylus = stylus.as_deref().map(StylusState::from_slice);
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -265,9 +304,11 @@ impl JsEditorHandle {
 	}
 
 	/// A mouse button depressed within screenspace the bounds of the viewport
+	/// The stylus state is given for pen input as [pressure, tilt X, tilt Y, barrel button]
 	#[wasm_bindgen(js_name = onMouseDown)]
-	pub fn on_mouse_down(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_down(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8, stylus: Option<Box<[f64]>>) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.stylus = stylus.as_deref().map(StylusState::from_slice);
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -276,9 +317,11 @@ impl JsEditorHandle {
 	}
 
 	/// A mouse button released
+	/// The stylus state is given for pen input as [pressure, tilt X, tilt Y, barrel button]
 	#[wasm_bindgen(js_name = onMouseUp)]
-	pub fn on_mouse_up(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_up(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8, stylus: Option<Box<[f64]>>) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.stylus = stylus.as_deref().map(StylusState::from_slice);
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -286,6 +329,15 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// The fingers touching the viewport changed, given as the [x, y] position of each
+	#[wasm_bindgen(js_name = onTouchUpdate)]
+	pub fn on_touch_update(&self, touches: &[f64]) {
+		let touches = touches.chunks(2).map(|touch| (touch[0], touch[1]).into()).collect();
+
+		let message = InputPreprocessorMessage::TouchUpdate { touches };
+		self.dispatch(message);
+	}
+
 	/// Mouse double clicked
 	#[wasm_bindgen(js_name = onDoubleClick)]
 	pub fn on_double_click(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
@@ -344,6 +396,27 @@ impl JsEditorHandle {
 		Ok(())
 	}
 
+	/// A font file was picked by the user to load
+	#[wasm_bindgen(js_name = loadFontFile)]
+	pub fn load_font_file(&self, data: Vec<u8>) {
+		let message = PortfolioMessage::LoadFontFile { data };
+		self.dispatch(message);
+	}
+
+	/// A palette file was picked by the user to import as color styles
+	#[wasm_bindgen(js_name = loadPaletteFile)]
+	pub fn load_palette_file(&self, data: Vec<u8>) {
+		let message = ColorStylesMessage::LoadPaletteFile { data };
+		self.dispatch(message);
+	}
+
+	/// A keymap file was picked by the user to load into the keyboard shortcuts dialog
+	#[wasm_bindgen(js_name = loadKeymapFile)]
+	pub fn load_keymap_file(&self, content: String) {
+		let message = KeyboardShortcutsDialogMessage::LoadKeymapFile { content };
+		self.dispatch(message);
+	}
+
 	/// A text box was changed
 	#[wasm_bindgen(js_name = updateBounds)]
 	pub fn update_bounds(&self, new_text: String) -> Result<(), JsValue> {
@@ -388,6 +461,13 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Paste SVG markup from the clipboard, recreating the layers of SVGs copied from Graphite or otherwise importing it as editable layers
+	#[wasm_bindgen(js_name = pasteSvg)]
+	pub fn paste_svg(&self, svg: String) {
+		let message = PortfolioMessage::PasteSvg { svg };
+		self.dispatch(message);
+	}
+
 	/// Modify the layer selection based on the layer which is clicked while holding down the <kbd>Ctrl</kbd> and/or <kbd>Shift</kbd> modifier keys used for range selection behavior
 	#[wasm_bindgen(js_name = selectLayer)]
 	pub fn select_layer(&self, layer_path: Vec<LayerId>, ctrl: bool, shift: bool) {
@@ -420,6 +500,20 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Makes another view of the document the active one, in exchange for the active view
+	#[wasm_bindgen(js_name = swapView)]
+	pub fn swap_view(&self, view: usize) {
+		let message = DocumentMessage::SwapView { view };
+		self.dispatch(message);
+	}
+
+	/// Closes another view of the document
+	#[wasm_bindgen(js_name = closeView)]
+	pub fn close_view(&self, view: usize) {
+		let message = DocumentMessage::CloseView { view };
+		self.dispatch(message);
+	}
+
 	/// Translates document (in viewport coords)
 	#[wasm_bindgen(js_name = translateCanvas)]
 	pub fn translate_canvas(&self, delta_x: f64, delta_y: f64) {
@@ -447,6 +541,13 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Sends the thumbnail of a layer rasterized by JS, along with the hash of the SVG thumbnail it was rasterized from, to the respective document
+	#[wasm_bindgen(js_name = setLayerThumbnail)]
+	pub fn set_layer_thumbnail(&self, document_id: u64, layer_path: Vec<LayerId>, content_hash: u64, data_url: String) {
+		let message = DocumentMessage::SetLayerThumbnail { layer_path, content_hash, data_url };
+		self.dispatch(PortfolioMessage::DocumentPassMessage { document_id, message });
+	}
+
 	/// Sends the blob URL generated by JS to the Imaginate layer in the respective document
 	#[wasm_bindgen(js_name = setImaginateImageData)]
 	pub fn set_imaginate_image_data(&self, document_id: u64, layer_path: Vec<LayerId>, image_data: Vec<u8>) {
@@ -506,10 +607,128 @@ impl JsEditorHandle {
 	#[wasm_bindgen(js_name = pasteImage)]
 	pub fn paste_image(&self, mime: String, image_data: Vec<u8>, mouse_x: Option<f64>, mouse_y: Option<f64>) {
 		let mouse = mouse_x.and_then(|x| mouse_y.map(|y| (x, y)));
-		let message = DocumentMessage::PasteImage { mime, image_data, mouse };
+		let message = DocumentMessage::PasteImage {
+			mime,
+			image_data,
+			mouse,
+			in_working_color_space: false,
+		};
 		self.dispatch(message);
 	}
 
+	/// Place an image file as a linked image, which is read again from the file the handle key refers to instead of being saved in the document
+	#[wasm_bindgen(js_name = pasteLinkedImage)]
+	pub fn paste_linked_image(&self, mime: String, image_data: Vec<u8>, handle: String, file_name: String, last_modified: f64) {
+		let link = ImageLink { handle, file_name, last_modified };
+		let message = DocumentMessage::PasteLinkedImage { mime, image_data, link };
+		self.dispatch(message);
+	}
+
+	/// Replace the image of a linked image layer with the contents of its file, which may be a different file when it's relinked
+	#[wasm_bindgen(js_name = updateLinkedImage)]
+	pub fn update_linked_image(&self, document_id: u64, layer_path: Vec<LayerId>, mime: String, image_data: Vec<u8>, handle: String, file_name: String, last_modified: f64) {
+		let link = ImageLink { handle, file_name, last_modified };
+		let message = DocumentMessage::UpdateLinkedImage { layer_path, mime, image_data, link };
+		self.dispatch(PortfolioMessage::DocumentPassMessage { document_id, message });
+	}
+
+	/// Read the files of the active document's linked images again if they were modified, without asking for permission to read them
+	#[wasm_bindgen(js_name = refreshLinkedImages)]
+	pub fn refresh_linked_images(&self) {
+		let message = DocumentMessage::RefreshLinkedImages { request_permission: false };
+		self.dispatch(message);
+	}
+
+	/// Read the files of a document's linked images again if they were modified, after one of them was changed by another app
+	/// Images whose files were removed are listed in an error dialog suggesting to relink them if `report_unreadable` is true
+	#[wasm_bindgen(js_name = refreshDocumentLinkedImages)]
+	pub fn refresh_document_linked_images(&self, document_id: u64, report_unreadable: bool) {
+		let message = DocumentMessage::RefreshLinkedImages {
+			request_permission: report_unreadable,
+		};
+		self.dispatch(PortfolioMessage::DocumentPassMessage { document_id, message });
+	}
+
+	/// Finishes a PDF export with the RGBA pixels of the content the editor asked to be rasterized, one raster after another
+	#[wasm_bindgen(js_name = finishPdfExport)]
+	pub fn finish_pdf_export(&self, raster_pixels: Vec<u8>) {
+		let message = DocumentMessage::FinishPdfExport { raster_pixels };
+		self.dispatch(message);
+	}
+
+	/// Finishes a GIF or APNG export with the RGBA pixels of every rasterized frame, one after another, which the editor then encodes
+	#[wasm_bindgen(js_name = finishAnimationExport)]
+	pub fn finish_animation_export(&self, pixels: Vec<u8>, width: u32, height: u32) {
+		let message = DocumentMessage::FinishAnimationExport { pixels, width, height };
+		self.dispatch(message);
+	}
+
+	/// Finishes a batch export with the data of each file the editor asked to be rasterized, one after another with the given lengths
+	#[wasm_bindgen(js_name = finishBatchExport)]
+	pub fn finish_batch_export(&self, data: Vec<u8>, lengths: Vec<u32>) {
+		let message = DocumentMessage::FinishBatchExport { data, lengths };
+		self.dispatch(message);
+	}
+
+	/// Finishes tracing an image with its decoded RGBA pixels
+	#[wasm_bindgen(js_name = finishImageTrace)]
+	pub fn finish_image_trace(&self, pixels: Vec<u8>, width: u32, height: u32) {
+		let message = DocumentMessage::FinishImageTrace { pixels, width, height };
+		self.dispatch(message);
+	}
+
+	/// Finishes a JPEG or WebP export with the RGBA pixels of the rasterized document, which the editor then encodes
+	#[wasm_bindgen(js_name = finishRasterExport)]
+	pub fn finish_raster_export(&self, pixels: Vec<u8>, width: u32, height: u32) {
+		let message = DocumentMessage::FinishRasterExport { pixels, width, height };
+		self.dispatch(message);
+	}
+
+	/// Pastes an image that has already been converted into the document's working color space
+	#[wasm_bindgen(js_name = pasteConvertedImage)]
+	pub fn paste_converted_image(&self, mime: String, image_data: Vec<u8>, mouse_x: Option<f64>, mouse_y: Option<f64>) {
+		let mouse = mouse_x.and_then(|x| mouse_y.map(|y| (x, y)));
+		let message = DocumentMessage::PasteImage {
+			mime,
+			image_data,
+			mouse,
+			in_working_color_space: true,
+		};
+		self.dispatch(message);
+	}
+
+	/// Toggle whether the active document's colors are rendered in its wide-gamut working color space instead of being clamped to sRGB
+	#[wasm_bindgen(js_name = setDocumentWideGamut)]
+	pub fn set_document_wide_gamut(&self, wide_gamut: bool) {
+		let message = DocumentMessage::SetWideGamut { wide_gamut };
+		self.dispatch(message);
+	}
+
+	/// Switch the active document between the GPU and SVG viewport renderers, such as when the browser doesn't support the GPU renderer
+	#[wasm_bindgen(js_name = setDocumentGpuRendering)]
+	pub fn set_document_gpu_rendering(&self, gpu: bool) {
+		let viewport_renderer = if gpu { ViewportRenderer::Gpu } else { ViewportRenderer::Svg };
+		let message = DocumentMessage::SetViewportRenderer { viewport_renderer };
+		self.dispatch(message);
+	}
+
+	/// Set the profile used to separate the active document's colors into CMYK inks
+	#[wasm_bindgen(js_name = setCmykProfile)]
+	pub fn set_cmyk_profile(&self, total_ink_limit: f32, black_generation: f32) {
+		let cmyk_profile = CmykProfile { total_ink_limit, black_generation };
+		let message = DocumentMessage::SetCmykProfile { cmyk_profile };
+		self.dispatch(message);
+	}
+
+	/// Set the working color space of the active document
+	#[wasm_bindgen(js_name = setDocumentColorSpace)]
+	pub fn set_document_color_space(&self, color_space: JsValue) -> Result<(), JsValue> {
+		let color_space = from_value(color_space).map_err(|_| Error::new("Invalid color space"))?;
+		let message = DocumentMessage::SetColorSpace { color_space };
+		self.dispatch(message);
+		Ok(())
+	}
+
 	/// Toggle visibility of a layer from the layer list
 	#[wasm_bindgen(js_name = toggleLayerVisibility)]
 	pub fn toggle_layer_visibility(&self, layer_path: Vec<LayerId>) {
@@ -517,12 +736,44 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Toggle the lock of a layer from the layer list
+	#[wasm_bindgen(js_name = toggleLayerLock)]
+	pub fn toggle_layer_lock(&self, layer_path: Vec<LayerId>) {
+		let message = DocumentMessage::ToggleLayerLock { layer_path };
+		self.dispatch(message);
+	}
+
 	/// Toggle expansions state of a layer from the layer list
 	#[wasm_bindgen(js_name = toggleLayerExpansion)]
 	pub fn toggle_layer_expansion(&self, layer_path: Vec<LayerId>) {
 		let message = DocumentMessage::ToggleLayerExpansion { layer_path };
 		self.dispatch(message);
 	}
+
+	/// Filter the layers shown in the layer list by a search query
+	#[wasm_bindgen(js_name = setLayerFilter)]
+	pub fn set_layer_filter(&self, query: String) {
+		let message = LayersPanelMessage::SetFilter(query);
+		self.dispatch(message);
+	}
+
+	/// Set the color label of the selected layers, or clear it if `label` is undefined
+	#[wasm_bindgen(js_name = setLabelForSelectedLayers)]
+	pub fn set_label_for_selected_layers(&self, label: JsValue) -> Result<(), JsValue> {
+		let label = from_value(label).map_err(|_| Error::new("Invalid layer label"))?;
+		let message = DocumentMessage::SetLabelForSelectedLayers { label };
+		self.dispatch(message);
+		Ok(())
+	}
+
+	/// Select every layer tagged with the given color label
+	#[wasm_bindgen(js_name = selectLayersWithLabel)]
+	pub fn select_layers_with_label(&self, label: JsValue) -> Result<(), JsValue> {
+		let label = from_value(label).map_err(|_| Error::new("Invalid layer label"))?;
+		let message = DocumentMessage::SelectLayersWithLabel { label };
+		self.dispatch(message);
+		Ok(())
+	}
 }
 
 // Needed to make JsEditorHandle functions pub to Rust.
@@ -1,4 +1,207 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The working color space that a document's colors are specified and composited in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ColorSpace {
+	#[default]
+	Srgb,
+	DisplayP3,
+	Rec2020,
+	LinearSrgb,
+}
+
+impl ColorSpace {
+	pub const ALL: [ColorSpace; 4] = [ColorSpace::Srgb, ColorSpace::DisplayP3, ColorSpace::Rec2020, ColorSpace::LinearSrgb];
+
+	/// The name of the color space as used by CSS.
+	pub fn css_name(&self) -> &'static str {
+		match self {
+			ColorSpace::Srgb => "srgb",
+			ColorSpace::DisplayP3 => "display-p3",
+			ColorSpace::Rec2020 => "rec2020",
+			ColorSpace::LinearSrgb => "srgb-linear",
+		}
+	}
+
+	/// The color space that raster images are imported and exported in, since canvases only support sRGB and Display P3.
+	/// Exported image files are tagged with the ICC profile of this color space.
+	pub fn raster_color_space(&self) -> ColorSpace {
+		match self {
+			ColorSpace::DisplayP3 | ColorSpace::Rec2020 => ColorSpace::DisplayP3,
+			ColorSpace::Srgb | ColorSpace::LinearSrgb => ColorSpace::Srgb,
+		}
+	}
+
+	/// The CSS name of the [raster color space](ColorSpace::raster_color_space), used for the canvases that import and export raster images.
+	pub fn canvas_color_space(&self) -> &'static str {
+		self.raster_color_space().css_name()
+	}
+
+	/// The SVG `color-interpolation` mode used to composite gradients and layers in this color space.
+	pub fn color_interpolation(&self) -> &'static str {
+		match self {
+			ColorSpace::LinearSrgb => "linearRGB",
+			ColorSpace::Srgb | ColorSpace::DisplayP3 | ColorSpace::Rec2020 => "sRGB",
+		}
+	}
+
+	/// Whether the gamut is wider than sRGB's, so its colors can't all be written as sRGB hex colors.
+	pub fn is_wide_gamut(&self) -> bool {
+		matches!(self, ColorSpace::DisplayP3 | ColorSpace::Rec2020)
+	}
+
+	/// Decodes a channel value into a value proportional to light intensity.
+	fn decode(&self, channel: f32) -> f32 {
+		match self {
+			ColorSpace::Srgb | ColorSpace::DisplayP3 => srgb_to_linear(channel),
+			ColorSpace::Rec2020 => rec2020_to_linear(channel),
+			ColorSpace::LinearSrgb => channel,
+		}
+	}
+
+	/// Encodes a value proportional to light intensity with the color space's transfer function.
+	fn encode(&self, channel: f32) -> f32 {
+		match self {
+			ColorSpace::Srgb | ColorSpace::DisplayP3 => linear_to_srgb(channel),
+			ColorSpace::Rec2020 => linear_to_rec2020(channel),
+			ColorSpace::LinearSrgb => channel,
+		}
+	}
+
+	/// The matrices converting linear light channels from this color space's primaries to sRGB's primaries and back, or `None` if it shares sRGB's primaries.
+	fn primaries_matrices(&self) -> Option<(&'static Matrix3, &'static Matrix3)> {
+		match self {
+			ColorSpace::DisplayP3 => Some((&LINEAR_P3_TO_LINEAR_SRGB, &LINEAR_SRGB_TO_LINEAR_P3)),
+			ColorSpace::Rec2020 => Some((&LINEAR_REC2020_TO_LINEAR_SRGB, &LINEAR_SRGB_TO_LINEAR_REC2020)),
+			ColorSpace::Srgb | ColorSpace::LinearSrgb => None,
+		}
+	}
+}
+
+impl fmt::Display for ColorSpace {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ColorSpace::Srgb => write!(f, "sRGB"),
+			ColorSpace::DisplayP3 => write!(f, "Display P3"),
+			ColorSpace::Rec2020 => write!(f, "Rec. 2020"),
+			ColorSpace::LinearSrgb => write!(f, "Linear sRGB"),
+		}
+	}
+}
+
+/// A row-major 3×3 matrix converting between the channels of two color spaces.
+type Matrix3 = [[f32; 3]; 3];
+
+/// Converts linear light Display P3 channels to linear light sRGB channels.
+const LINEAR_P3_TO_LINEAR_SRGB: Matrix3 = [[1.224_940_1, -0.224_940_4, 0.], [-0.042_056_9, 1.042_057_1, 0.], [-0.019_637_6, -0.078_636_1, 1.098_273_5]];
+/// Converts linear light sRGB channels to linear light Display P3 channels.
+const LINEAR_SRGB_TO_LINEAR_P3: Matrix3 = [[0.822_462_1, 0.177_538, 0.], [0.033_194_1, 0.966_805_8, 0.], [0.017_082_7, 0.072_397_4, 0.910_519_9]];
+/// Converts linear light Rec. 2020 channels to linear light sRGB channels.
+const LINEAR_REC2020_TO_LINEAR_SRGB: Matrix3 = [
+	[1.660_491, -0.587_641_1, -0.072_849_9],
+	[-0.124_550_5, 1.132_9, -0.008_349_4],
+	[-0.018_150_8, -0.100_578_9, 1.118_729_7],
+];
+/// Converts linear light sRGB channels to linear light Rec. 2020 channels.
+const LINEAR_SRGB_TO_LINEAR_REC2020: Matrix3 = [[0.627_403_9, 0.329_283, 0.043_313_1], [0.069_097_3, 0.919_540_4, 0.011_362_3], [0.016_391_4, 0.088_013_3, 0.895_595_3]];
+
+fn srgb_to_linear(channel: f32) -> f32 {
+	if channel <= 0.04045 {
+		channel / 12.92
+	} else {
+		((channel + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+fn linear_to_srgb(channel: f32) -> f32 {
+	if channel <= 0.0031308 {
+		channel * 12.92
+	} else {
+		1.055 * channel.powf(1. / 2.4) - 0.055
+	}
+}
+
+const REC2020_ALPHA: f32 = 1.099_296_8;
+const REC2020_BETA: f32 = 0.018_053_968;
+
+fn rec2020_to_linear(channel: f32) -> f32 {
+	if channel < REC2020_BETA * 4.5 {
+		channel / 4.5
+	} else {
+		((channel + REC2020_ALPHA - 1.) / REC2020_ALPHA).powf(1. / 0.45)
+	}
+}
+
+fn linear_to_rec2020(channel: f32) -> f32 {
+	if channel < REC2020_BETA {
+		channel * 4.5
+	} else {
+		REC2020_ALPHA * channel.powf(0.45) - (REC2020_ALPHA - 1.)
+	}
+}
+
+fn multiply_matrix(matrix: &Matrix3, [r, g, b]: [f32; 3]) -> [f32; 3] {
+	matrix.map(|row| row[0] * r + row[1] * g + row[2] * b)
+}
+
+/// The parameters used to separate colors into CMYK ink values for print.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CmykProfile {
+	/// The maximum total coverage of all four inks, from `0.0` to `4.0` (400%).
+	#[serde(rename = "totalInkLimit")]
+	pub total_ink_limit: f32,
+	/// How much of the shared gray component of the cyan, magenta, and yellow inks is replaced by black ink, from `0.0` to `1.0`.
+	#[serde(rename = "blackGeneration")]
+	pub black_generation: f32,
+}
+
+impl Default for CmykProfile {
+	fn default() -> Self {
+		Self {
+			total_ink_limit: 3.,
+			black_generation: 1.,
+		}
+	}
+}
+
+impl CmykProfile {
+	/// Whether the color can't be printed with this profile, because it is brighter than paper white or needs more ink than the total ink limit.
+	///
+	/// # Examples
+	/// ```
+	/// use graphite_graphene::color::{CmykProfile, Color};
+	/// let profile = CmykProfile { total_ink_limit: 2.5, black_generation: 0. };
+	/// assert!(!profile.is_out_of_gamut(Color::RED));
+	/// assert!(profile.is_out_of_gamut(Color::BLACK));
+	/// ```
+	pub fn is_out_of_gamut(&self, color: Color) -> bool {
+		let (red, green, blue, _) = color.components();
+		let hdr = red > 1. || green > 1. || blue > 1.;
+
+		hdr || color.to_cmyk(self).total_ink() > self.total_ink_limit + f32::EPSILON
+	}
+}
+
+/// A color as amounts of cyan, magenta, yellow, and black (key) ink, each from `0.0` (none) to `1.0` (full coverage).
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Cmyk {
+	pub cyan: f32,
+	pub magenta: f32,
+	pub yellow: f32,
+	pub key: f32,
+}
+
+impl Cmyk {
+	pub fn new(cyan: f32, magenta: f32, yellow: f32, key: f32) -> Self {
+		Self { cyan, magenta, yellow, key }
+	}
+
+	/// The total coverage of all four inks, from `0.0` to `4.0`.
+	pub fn total_ink(&self) -> f32 {
+		self.cyan + self.magenta + self.yellow + self.key
+	}
+}
 
 /// Structure that represents a color.
 /// Internally alpha is stored as `f32` that ranges from `0.0` (transparent) to `1.0` (opaque).
@@ -163,6 +366,31 @@ impl Color {
 		format!("{:02X?}{:02X?}{:02X?}", (self.r() * 255.) as u8, (self.g() * 255.) as u8, (self.b() * 255.) as u8,)
 	}
 
+	/// Return the RGB channels as a CSS color. With no color space, this is a clamped sRGB hex color.
+	/// Otherwise, the channels are given in that color space by a `color()` value, keeping values outside the `0.0` to `1.0` range for extended range (HDR) colors.
+	///
+	/// # Examples
+	/// ```
+	/// use graphite_graphene::color::{Color, ColorSpace};
+	/// let color = Color::from_rgbaf32(1.5, 0.25, 0., 0.5).unwrap();
+	/// assert_eq!(color.rgb_css(None), "#FF3F00");
+	/// assert_eq!(color.rgb_css(Some(ColorSpace::DisplayP3)), "color(display-p3 1.5 0.25 0)");
+	/// ```
+	pub fn rgb_css(&self, color_space: Option<ColorSpace>) -> String {
+		match color_space {
+			Some(color_space) => format!("color({} {} {} {})", color_space.css_name(), self.red, self.green, self.blue),
+			None => format!("#{}", self.rgb_hex()),
+		}
+	}
+
+	/// Return the RGBA channels as a CSS color, like [Color::rgb_css] but including the alpha.
+	pub fn rgba_css(&self, color_space: Option<ColorSpace>) -> String {
+		match color_space {
+			Some(color_space) => format!("color({} {} {} {} / {})", color_space.css_name(), self.red, self.green, self.blue, self.alpha),
+			None => format!("#{}", self.rgba_hex()),
+		}
+	}
+
 	/// Creates a color from a 8-character RGBA hex string (without a # prefix).
 	///
 	/// # Examples
@@ -199,4 +427,78 @@ impl Color {
 
 		Some(Color::from_rgb8(r, g, b))
 	}
+
+	/// Converts the color's RGB channels from one [ColorSpace] to another, keeping the alpha unchanged.
+	/// Colors outside the gamut of the target color space are clipped to its boundary.
+	///
+	/// # Examples
+	/// ```
+	/// use graphite_graphene::color::{Color, ColorSpace};
+	/// let color = Color::from_unsafe(0.5, 0.5, 0.5);
+	/// let linear = color.convert_color_space(ColorSpace::Srgb, ColorSpace::LinearSrgb);
+	/// assert!((linear.r() - 0.214).abs() < 0.001);
+	///
+	/// let p3 = Color::RED.convert_color_space(ColorSpace::Srgb, ColorSpace::DisplayP3);
+	/// assert!(p3.r() < 1. && p3.g() > 0.);
+	/// ```
+	pub fn convert_color_space(&self, from: ColorSpace, to: ColorSpace) -> Color {
+		if from == to {
+			return *self;
+		}
+
+		// Decode the channels into linear light values in the source gamut
+		let mut channels = [self.red, self.green, self.blue].map(|channel| from.decode(channel));
+
+		// Convert between the primaries of the two color spaces by way of sRGB's primaries
+		if let Some((to_srgb, _)) = from.primaries_matrices() {
+			channels = multiply_matrix(to_srgb, channels);
+		}
+		if let Some((_, from_srgb)) = to.primaries_matrices() {
+			channels = multiply_matrix(from_srgb, channels);
+		}
+		channels = channels.map(|channel| channel.max(0.));
+
+		// Encode the linear light values with the target's transfer function
+		channels = channels.map(|channel| to.encode(channel));
+
+		let [red, green, blue] = channels;
+		Color { red, green, blue, alpha: self.alpha }
+	}
+
+	/// Separates the color into CMYK ink amounts using the given [CmykProfile], ignoring the alpha.
+	///
+	/// # Examples
+	/// ```
+	/// use graphite_graphene::color::{Cmyk, CmykProfile, Color};
+	/// let profile = CmykProfile::default();
+	/// assert_eq!(Color::RED.to_cmyk(&profile), Cmyk::new(0., 1., 1., 0.));
+	/// assert_eq!(Color::BLACK.to_cmyk(&profile), Cmyk::new(0., 0., 0., 1.));
+	/// ```
+	pub fn to_cmyk(&self, profile: &CmykProfile) -> Cmyk {
+		let [red, green, blue] = [self.red, self.green, self.blue].map(|channel| channel.clamp(0., 1.));
+
+		// Replace the shared gray component of the inks with black ink according to the profile
+		let key = (1. - red.max(green).max(blue)) * profile.black_generation.clamp(0., 1.);
+		if key >= 1. {
+			return Cmyk::new(0., 0., 0., 1.);
+		}
+		let separate = |channel: f32| ((1. - channel - key) / (1. - key)).clamp(0., 1.);
+
+		Cmyk::new(separate(red), separate(green), separate(blue), key)
+	}
+
+	/// Return an opaque `Color` that results from printing the given CMYK ink amounts.
+	///
+	/// # Examples
+	/// ```
+	/// use graphite_graphene::color::{Cmyk, Color};
+	/// let color = Color::from_cmyk(Cmyk::new(0., 1., 1., 0.5));
+	/// assert_eq!(color.components(), (0.5, 0., 0., 1.));
+	/// ```
+	pub fn from_cmyk(cmyk: Cmyk) -> Color {
+		let Cmyk { cyan, magenta, yellow, key } = cmyk;
+		let print = |ink: f32| (1. - ink.clamp(0., 1.)) * (1. - key.clamp(0., 1.));
+
+		Color::from_unsafe(print(cyan), print(magenta), print(yellow))
+	}
 }
@@ -1,12 +1,15 @@
 use crate::boolean_ops::composite_boolean_operation;
+use crate::color::{CmykProfile, ColorSpace};
+use crate::gpu_scene::{self, GpuScene, ViewportRenderer};
 use crate::intersection::Quad;
+use crate::layers::blend_mode::BlendMode;
 use crate::layers::folder_layer::FolderLayer;
 use crate::layers::image_layer::ImageLayer;
 use crate::layers::imaginate_layer::{ImaginateImageData, ImaginateLayer, ImaginateStatus};
 use crate::layers::layer_info::{Layer, LayerData, LayerDataType, LayerDataTypeDiscriminant};
 use crate::layers::shape_layer::ShapeLayer;
-use crate::layers::style::RenderData;
-use crate::layers::text_layer::{Font, FontCache, TextLayer};
+use crate::layers::style::{ColorStyle, ColorStyleId, RenderData, RenderQuality};
+use crate::layers::text_layer::{EmbeddedFont, Font, FontCache, TextLayer, TextStyle, TextStyleId};
 use crate::layers::vector::subpath::Subpath;
 use crate::{DocumentError, DocumentResponse, Operation};
 
@@ -15,16 +18,57 @@ use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::cmp::max;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 
 /// A number that identifies a layer.
 /// This does not technically need to be unique globally, only within a folder.
 pub type LayerId = u64;
 
+/// A layer directly inside the root folder, rendered on its own by [Document::render_root_layers].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedRootLayer {
+	/// The SVG of the layer placed by the root transform, including the definitions it uses.
+	pub svg: String,
+	/// A hash of the SVG, which changes whenever the layer or the view changes.
+	pub content_hash: u64,
+	pub blend_mode: BlendMode,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Document {
 	/// The root layer, usually a [FolderLayer](layers::folder_layer::FolderLayer) that contains all other [Layers](layers::layer_info::Layer).
 	pub root: Layer,
+	/// The color styles that layers can link their fill and stroke colors to.
+	#[serde(default)]
+	pub color_styles: HashMap<ColorStyleId, ColorStyle>,
+	/// The text styles that text layers can link their font, size, and typesetting to.
+	#[serde(default)]
+	pub text_styles: HashMap<TextStyleId, TextStyle>,
+	/// The font files saved in the document, which are loaded when it's opened.
+	#[serde(default)]
+	pub embedded_fonts: Vec<EmbeddedFont>,
+	/// The working color space that the document is composited in and that imported and exported raster images are converted to.
+	#[serde(default)]
+	pub color_space: ColorSpace,
+	/// Whether colors are specified and rendered in the working color space, keeping wide-gamut and extended range (HDR) colors, instead of being clamped to sRGB.
+	#[serde(default)]
+	pub wide_gamut: bool,
+	/// The profile used to separate the document's colors into CMYK inks for print.
+	#[serde(default)]
+	pub cmyk_profile: CmykProfile,
+	/// The renderer used to draw the document in the viewport.
+	#[serde(default)]
+	pub viewport_renderer: ViewportRenderer,
+	/// The anti-aliasing and resampling quality used in the viewport and in exports.
+	#[serde(default)]
+	pub render_quality: RenderQuality,
+	/// Whether the layer caches were last rendered with draft quality effects, so they are rendered again once the interaction ends.
+	#[serde(skip)]
+	drafted_effects: bool,
+	/// The folder that editing is scoped to while it's isolated. Layers outside of it act as locked and are left out of hit testing.
+	#[serde(skip)]
+	pub isolated_folder: Option<Vec<LayerId>>,
 	/// The state_identifier serves to provide a way to uniquely identify a particular state that the document is in.
 	/// This identifier is not a hash and is not guaranteed to be equal for equivalent documents.
 	#[serde(skip)]
@@ -35,6 +79,16 @@ impl Default for Document {
 	fn default() -> Self {
 		Self {
 			root: Layer::new(LayerDataType::Folder(FolderLayer::default()), DAffine2::IDENTITY.to_cols_array()),
+			color_styles: HashMap::new(),
+			text_styles: HashMap::new(),
+			embedded_fonts: Vec::new(),
+			color_space: ColorSpace::default(),
+			wide_gamut: false,
+			cmyk_profile: CmykProfile::default(),
+			viewport_renderer: ViewportRenderer::default(),
+			render_quality: RenderQuality::default(),
+			drafted_effects: false,
+			isolated_folder: None,
 			state_identifier: DefaultHasher::new(),
 		}
 	}
@@ -43,21 +97,90 @@ impl Default for Document {
 impl Document {
 	/// Wrapper around render, that returns the whole document as a Response.
 	pub fn render_root(&mut self, render_data: RenderData) -> String {
+		let render_data = self.document_render_data(render_data);
+
 		// Render and append to the defs section
 		let mut svg_defs = String::from("<defs>");
 		self.root.render(&mut vec![], &mut svg_defs, render_data);
 		svg_defs.push_str("</defs>");
 
-		// Append the cached rendered SVG
-		svg_defs.push_str(&self.root.cache);
+		// Append the cached rendered SVG, composited in the document's working color space and with the document's render quality
+		let attributes = self.artwork_attributes();
+		if attributes.is_empty() {
+			svg_defs.push_str(&self.root.cache);
+		} else {
+			svg_defs.push_str(&format!(r#"<g{}>{}</g>"#, attributes, self.root.cache));
+		}
 
 		svg_defs
 	}
 
+	/// Applies the color and quality settings of the document to the render data, marking every layer to be rendered again when effects switch between draft and full quality.
+	fn document_render_data<'a>(&mut self, render_data: RenderData<'a>) -> RenderData<'a> {
+		let render_data = RenderData {
+			wide_gamut_color_space: self.wide_gamut.then_some(self.color_space),
+			quality: self.render_quality,
+			..render_data
+		};
+		if render_data.draft_effects() != self.drafted_effects {
+			self.drafted_effects = render_data.draft_effects();
+			Self::mark_children_as_dirty(&mut self.root);
+		}
+		render_data
+	}
+
+	/// The attributes of the group containing the rendered artwork, which set its compositing color space and render quality.
+	fn artwork_attributes(&self) -> String {
+		let mut attributes = String::new();
+		if self.color_space != ColorSpace::default() {
+			attributes.push_str(&format!(r#" color-interpolation="{}""#, self.color_space.color_interpolation()));
+		}
+		attributes.push_str(&self.render_quality.render());
+		attributes
+	}
+
+	/// Whether the last render left out effects because the user was interacting with the viewport.
+	pub fn has_draft_effects(&self) -> bool {
+		self.drafted_effects
+	}
+
+	/// Renders each layer directly inside the root folder on its own, so runs of unchanged layers can be composited once and reused.
+	/// Stacking the SVGs in order draws the same artwork as [Document::render_root].
+	pub fn render_root_layers(&mut self, render_data: RenderData) -> Vec<RenderedRootLayer> {
+		let render_data = self.document_render_data(render_data);
+		let root_transform = self.root.transform.to_cols_array().map(|entry| entry.to_string()).join(",");
+		let attributes = self.artwork_attributes();
+
+		let folder = match &mut self.root.data {
+			LayerDataType::Folder(folder) => folder,
+			_ => return Vec::new(),
+		};
+		let mut transforms = vec![self.root.transform];
+		folder
+			.layers
+			.iter_mut()
+			.map(|layer| {
+				let mut svg_defs = String::new();
+				let cache = layer.render(&mut transforms, &mut svg_defs, render_data);
+				let svg = format!(r#"<defs>{}</defs><g transform="matrix({})"{}>{}</g>"#, svg_defs, root_transform, attributes, cache);
+
+				let mut hasher = DefaultHasher::new();
+				svg.hash(&mut hasher);
+				RenderedRootLayer {
+					svg,
+					content_hash: hasher.finish(),
+					blend_mode: layer.blend_mode,
+				}
+			})
+			.collect()
+	}
+
 	/// Renders everything below the given layer contained within its parent folder.
 	pub fn render_layers_below(&mut self, below_layer_path: &[LayerId], render_data: RenderData) -> Option<String> {
 		// Split the path into the layer ID and its parent folder
 		let (layer_id_to_render_below, parent_folder_path) = below_layer_path.split_last()?;
+		let render_data = self.document_render_data(render_data);
+		let attributes = self.artwork_attributes();
 
 		// Note: it is bad practice to directly clone and modify the Graphene document structure, this is a temporary hack until this whole system is replaced by the node graph
 		let mut temp_subset_folder = self.layer_mut(parent_folder_path).ok()?.clone();
@@ -73,7 +196,7 @@ impl Document {
 			svg_defs.push_str("</defs>");
 
 			// Append the cached rendered SVG
-			svg_defs.push_str(&temp_subset_folder.cache);
+			svg_defs.push_str(&format!(r#"<g{}>{}</g>"#, attributes, temp_subset_folder.cache));
 
 			Some(svg_defs)
 		} else {
@@ -81,6 +204,11 @@ impl Document {
 		}
 	}
 
+	/// Tessellates the visible layers for the GPU viewport renderer, instead of rendering them to SVG like [Document::render_root].
+	pub fn render_gpu_scene(&self, render_data: RenderData) -> GpuScene {
+		gpu_scene::tessellate(&self.root, render_data)
+	}
+
 	pub fn current_state_identifier(&self) -> u64 {
 		self.state_identifier.finish()
 	}
@@ -94,9 +222,18 @@ impl Document {
 	pub fn intersects_quad_root(&self, quad: Quad, font_cache: &FontCache) -> Vec<Vec<LayerId>> {
 		let mut intersections = Vec::new();
 		self.intersects_quad(quad, &mut vec![], &mut intersections, font_cache);
+		intersections.retain(|path| !self.is_outside_isolation(path));
 		intersections
 	}
 
+	/// Returns the paths to all layers under the root path which lie entirely within the provided `quad`.
+	pub fn enclosed_by_quad_root(&self, quad: Quad, font_cache: &FontCache) -> Vec<Vec<LayerId>> {
+		let mut enclosed = Vec::new();
+		self.root.enclosed_by_quad(quad, &mut vec![], &mut enclosed, font_cache);
+		enclosed.retain(|path| !self.is_outside_isolation(path));
+		enclosed
+	}
+
 	/// Returns a reference to the requested folder. Fails if the path does not exist,
 	/// or if the requested layer is not of type folder.
 	pub fn folder(&self, path: impl AsRef<[LayerId]>) -> Result<&FolderLayer, DocumentError> {
@@ -259,6 +396,41 @@ impl Document {
 		return self.folder(path.as_ref()).is_ok();
 	}
 
+	/// The color space that the document's colors are specified in, which is sRGB unless wide gamut is enabled.
+	pub fn specified_color_space(&self) -> ColorSpace {
+		if self.wide_gamut {
+			self.color_space
+		} else {
+			ColorSpace::Srgb
+		}
+	}
+
+	/// Converts the colors of every layer and color style from one color space to another and marks the layers as dirty.
+	fn convert_colors(&mut self, from: ColorSpace, to: ColorSpace) -> Result<(), DocumentError> {
+		if from == to {
+			return Ok(());
+		}
+
+		for color_style in self.color_styles.values_mut() {
+			color_style.color = color_style.color.convert_color_space(from, to);
+		}
+		for path in self.layers_matching(|layer| layer.style().is_ok()) {
+			self.layer_mut(&path)?.style_mut()?.map_colors(|color| color.convert_color_space(from, to));
+			self.mark_as_dirty(&path)?;
+		}
+		Ok(())
+	}
+
+	/// Checks whether the layer at the given path, or any of the folders containing it, is locked.
+	pub fn is_locked(&self, path: &[LayerId]) -> bool {
+		self.is_outside_isolation(path) || (0..=path.len()).any(|depth| self.layer(&path[..depth]).map_or(false, |layer| layer.locked))
+	}
+
+	/// Answers if a folder is isolated and the layer isn't inside of it, which includes the isolated folder itself and the folders containing it.
+	pub fn is_outside_isolation(&self, path: &[LayerId]) -> bool {
+		self.isolated_folder.as_ref().map_or(false, |folder| path.len() <= folder.len() || !path.starts_with(folder))
+	}
+
 	// Determines which layer is closer to the root, if path_a return true, if path_b return false
 	// Answers the question: Is A closer to the root than B?
 	pub fn layer_closer_to_root(&self, path_a: &[u64], path_b: &[u64]) -> bool {
@@ -384,6 +556,52 @@ impl Document {
 		Ok(())
 	}
 
+	/// Returns the paths to all non-folder layers that satisfy the predicate.
+	fn layers_matching(&self, predicate: impl Fn(&Layer) -> bool) -> Vec<Vec<LayerId>> {
+		fn collect(layer: &Layer, predicate: &impl Fn(&Layer) -> bool, path: &mut Vec<LayerId>, paths: &mut Vec<Vec<LayerId>>) {
+			if let LayerDataType::Folder(folder) = &layer.data {
+				for (layer_id, layer) in folder.layer_ids.iter().zip(folder.layers()) {
+					path.push(*layer_id);
+					collect(layer, predicate, path, paths);
+					path.pop();
+				}
+			} else if predicate(layer) {
+				paths.push(path.clone());
+			}
+		}
+
+		let mut paths = Vec::new();
+		collect(&self.root, &predicate, &mut vec![], &mut paths);
+		paths
+	}
+
+	/// Returns the paths to all layers with a fill or stroke linked to the given color style.
+	pub fn layers_with_color_style(&self, id: ColorStyleId) -> Vec<Vec<LayerId>> {
+		self.layers_matching(|layer| layer.style().map_or(false, |style| style.fill_color_style() == Some(id) || style.stroke_color_style() == Some(id)))
+	}
+
+	/// Returns the paths to all text layers linked to the given text style.
+	pub fn layers_with_text_style(&self, id: TextStyleId) -> Vec<Vec<LayerId>> {
+		self.layers_matching(|layer| layer.as_text().map_or(false, |text| text.text_style == Some(id)))
+	}
+
+	/// The fonts used by text layers and text styles, each listed once.
+	pub fn fonts(&self) -> Vec<&Font> {
+		let layer_fonts = self
+			.layers_matching(|layer| layer.as_text().is_ok())
+			.into_iter()
+			.filter_map(|path| Some(&self.layer(&path).ok()?.as_text().ok()?.font));
+		let style_fonts = self.text_styles.values().map(|text_style| &text_style.font);
+
+		let mut fonts = Vec::new();
+		for font in layer_fonts.chain(style_fonts) {
+			if !fonts.contains(&font) {
+				fonts.push(font);
+			}
+		}
+		fonts
+	}
+
 	pub fn viewport_bounding_box(&self, path: &[LayerId], font_cache: &FontCache) -> Result<Option<[DVec2; 2]>, DocumentError> {
 		let layer = self.layer(path)?;
 		let transform = self.multiply_transforms(path)?;
@@ -447,6 +665,14 @@ impl Document {
 		Ok(())
 	}
 
+	/// Marks every layer in the [Document] as dirty.
+	pub fn mark_all_as_dirty(&mut self) -> Result<(), DocumentError> {
+		for path in self.layers_matching(|_| true) {
+			self.mark_as_dirty(&path)?;
+		}
+		Ok(())
+	}
+
 	/// Marks all decendants of the specified [Layer] of a specific [LayerDataType] as dirty
 	fn mark_layers_of_type_as_dirty(root: &mut Layer, data_type: LayerDataTypeDiscriminant) -> bool {
 		if let LayerDataType::Folder(folder) = &mut root.data {
@@ -681,6 +907,24 @@ impl Document {
 				self.set_layer(&path, Layer::new(LayerDataType::Shape(ShapeLayer::spline(points, style)), transform), insert_index)?;
 				Some([vec![DocumentChanged, CreatedLayer { path: path.clone() }], update_thumbnails_upstream(&path)].concat())
 			}
+			Operation::DeleteColorStyle { id } => {
+				self.color_styles.remove(&id).ok_or(DocumentError::ColorStyleNotFound(id))?;
+
+				// The linked layers keep their current colors, so nothing needs to be rerendered
+				for path in self.layers_with_color_style(id) {
+					self.layer_mut(&path)?.style_mut()?.unlink_color_style(id);
+				}
+				Some(vec![DocumentChanged])
+			}
+			Operation::DeleteTextStyle { id } => {
+				self.text_styles.remove(&id).ok_or(DocumentError::TextStyleNotFound(id))?;
+
+				// The linked layers keep their current settings, so nothing needs to be rerendered
+				for path in self.layers_with_text_style(id) {
+					self.layer_mut(&path)?.as_text_mut()?.text_style = None;
+				}
+				Some(vec![DocumentChanged])
+			}
 			Operation::DeleteLayer { path } => {
 				fn aggregate_deletions(folder: &FolderLayer, path: &mut Vec<LayerId>, responses: &mut Vec<DocumentResponse>) {
 					for (id, layer) in folder.layer_ids.iter().zip(folder.layers()) {
@@ -761,9 +1005,14 @@ impl Document {
 				let layer_mut = current_folder.as_folder_mut()?.layer_mut(id).ok_or_else(|| DocumentError::LayerNotFound(folder_path.into()))?;
 				let text = layer_mut.as_text_mut()?;
 
-				text.font = Font::new(font_family, font_style);
+				let font = Font::new(font_family, font_style);
+				// Manually changing the font or size breaks the link to the text style
+				if text.font != font || text.size != size {
+					text.text_style = None;
+				}
+				text.font = font;
 				text.size = size;
-				text.cached_path = Some(text.generate_path(text.load_face(font_cache)));
+				text.cached_path = Some(text.generate_path(&text.load_faces(font_cache)));
 				self.mark_as_dirty(&path)?;
 				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(&path)].concat())
 			}
@@ -807,6 +1056,17 @@ impl Document {
 				self.mark_as_dirty(&layer_path)?;
 				Some([vec![DocumentChanged, LayerChanged { path: layer_path.clone() }], update_thumbnails_upstream(&layer_path)].concat())
 			}
+			Operation::SetImageData { path, mime, image_data } => {
+				let image = self.layer_mut(&path)?.as_image_mut()?;
+				image.mime = mime;
+				image.image_data = image_data;
+				self.mark_as_dirty(&path)?;
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(&path)].concat())
+			}
+			Operation::SetImageLink { path, link } => {
+				self.layer_mut(&path)?.as_image_mut()?.link = link;
+				Some(vec![LayerChanged { path }])
+			}
 			Operation::ImaginateSetImageData { layer_path, image_data } => {
 				let layer = self.layer_mut(&layer_path).expect("Setting Imaginate image data for invalid layer");
 				if let LayerDataType::Imaginate(imaginate) = &mut layer.data {
@@ -1094,6 +1354,25 @@ impl Document {
 				layer.visible = visible;
 				Some([vec![DocumentChanged], update_thumbnails_upstream(&path)].concat())
 			}
+			Operation::ReorderLayers {
+				folder_path,
+				layer_ids,
+				relative_index_offset,
+			} => {
+				self.folder_mut(&folder_path)?.reorder_layers(&layer_ids, relative_index_offset);
+				self.mark_as_dirty(&folder_path)?;
+				Some([vec![DocumentChanged, FolderChanged { path: folder_path.clone() }], update_thumbnails_upstream(&folder_path)].concat())
+			}
+			Operation::SetLayerLabel { path, label } => {
+				let layer = self.layer_mut(&path)?;
+				layer.label = label;
+				Some(vec![LayerChanged { path }])
+			}
+			Operation::SetLayerLocked { path, locked } => {
+				let layer = self.layer_mut(&path)?;
+				layer.locked = locked;
+				Some(vec![LayerChanged { path }])
+			}
 			Operation::SetLayerName { path, name } => {
 				self.mark_as_dirty(&path)?;
 				let mut layer = self.layer_mut(&path)?;
@@ -1101,6 +1380,96 @@ impl Document {
 
 				Some(vec![LayerChanged { path }])
 			}
+			Operation::SetCmykProfile { cmyk_profile } => {
+				self.cmyk_profile = cmyk_profile;
+				Some(vec![DocumentChanged])
+			}
+			Operation::SetColorSpace { color_space } => {
+				let old_color_space = self.specified_color_space();
+				self.color_space = color_space;
+				self.convert_colors(old_color_space, self.specified_color_space())?;
+				self.mark_all_as_dirty()?;
+				Some(vec![DocumentChanged])
+			}
+			Operation::SetRenderQuality { render_quality } => {
+				self.render_quality = render_quality;
+				Some(vec![DocumentChanged])
+			}
+			Operation::SetViewportRenderer { viewport_renderer } => {
+				self.viewport_renderer = viewport_renderer;
+				Some(vec![DocumentChanged])
+			}
+			Operation::SetWideGamut { wide_gamut } => {
+				let old_color_space = self.specified_color_space();
+				self.wide_gamut = wide_gamut;
+				self.convert_colors(old_color_space, self.specified_color_space())?;
+
+				// Every layer is rendered again to switch between hex and wide-gamut colors
+				self.mark_all_as_dirty()?;
+				Some(vec![DocumentChanged])
+			}
+			Operation::SetColorStyle { id, color_style } => {
+				let color = color_style.color;
+				self.color_styles.insert(id, color_style);
+
+				let mut responses = vec![DocumentChanged];
+				for path in self.layers_with_color_style(id) {
+					self.layer_mut(&path)?.style_mut()?.apply_color_style(id, color);
+					self.mark_as_dirty(&path)?;
+					responses.push(LayerChanged { path: path.clone() });
+					responses.extend(update_thumbnails_upstream(&path));
+				}
+				Some(responses)
+			}
+			Operation::SetLayerFillColorStyle { path, id } => {
+				let color_style = match id {
+					Some(id) => Some((id, self.color_styles.get(&id).ok_or(DocumentError::ColorStyleNotFound(id))?.color)),
+					None => None,
+				};
+				self.layer_mut(&path)?.style_mut()?.link_fill_color_style(color_style);
+				self.mark_as_dirty(&path)?;
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(&path)].concat())
+			}
+			Operation::SetLayerStrokeColorStyle { path, id } => {
+				let color_style = match id {
+					Some(id) => Some((id, self.color_styles.get(&id).ok_or(DocumentError::ColorStyleNotFound(id))?.color)),
+					None => None,
+				};
+				self.layer_mut(&path)?.style_mut()?.link_stroke_color_style(color_style);
+				self.mark_as_dirty(&path)?;
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(&path)].concat())
+			}
+			Operation::SetTextStyle { id, text_style } => {
+				let mut responses = vec![DocumentChanged];
+				for path in self.layers_with_text_style(id) {
+					self.layer_mut(&path)?.as_text_mut()?.apply_text_style(id, &text_style, font_cache);
+					self.mark_as_dirty(&path)?;
+					responses.push(LayerChanged { path: path.clone() });
+					responses.extend(update_thumbnails_upstream(&path));
+				}
+				self.text_styles.insert(id, text_style);
+				Some(responses)
+			}
+			Operation::EmbedFonts { fonts } => {
+				for font in fonts {
+					self.embedded_fonts.retain(|embedded_font| embedded_font.font != font.font);
+					self.embedded_fonts.push(font);
+				}
+				Some(vec![DocumentChanged])
+			}
+			Operation::SetLayerTextStyle { path, id } => {
+				let text_style = match id {
+					Some(id) => Some(self.text_styles.get(&id).ok_or(DocumentError::TextStyleNotFound(id))?.clone()),
+					None => None,
+				};
+				let text = self.layer_mut(&path)?.as_text_mut()?;
+				match (id, text_style) {
+					(Some(id), Some(text_style)) => text.apply_text_style(id, &text_style, font_cache),
+					_ => text.text_style = None,
+				}
+				self.mark_as_dirty(&path)?;
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(&path)].concat())
+			}
 			Operation::SetLayerBlendMode { path, blend_mode } => {
 				self.mark_as_dirty(&path)?;
 				self.layer_mut(&path)?.blend_mode = blend_mode;
@@ -1227,6 +1596,30 @@ impl Document {
 				// This does nothing visually so we don't need to send any messages
 				None
 			}
+			Operation::SetSelectedCornerRadius { layer_path, radius } => {
+				let layer = self.layer_mut(&layer_path)?;
+				if let Some(shape) = layer.as_subpath_mut() {
+					for manipulator_group in shape.selected_manipulator_groups_mut() {
+						manipulator_group.corner_radius = radius.max(0.);
+					}
+				}
+				self.mark_as_dirty(&layer_path)?;
+				Some([vec![DocumentChanged, LayerChanged { path: layer_path.clone() }], update_thumbnails_upstream(&layer_path)].concat())
+			}
+			Operation::FlattenLayerTransform { path } => {
+				let layer = self.layer_mut(&path)?;
+				layer.flatten_transform();
+				Self::mark_children_as_dirty(layer);
+				self.mark_as_dirty(&path)?;
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(&path)].concat())
+			}
+			Operation::ExtractLayerTransform { path } => {
+				let layer = self.layer_mut(&path)?;
+				layer.extract_transform();
+				Self::mark_children_as_dirty(layer);
+				self.mark_as_dirty(&path)?;
+				Some([vec![DocumentChanged, LayerChanged { path: path.clone() }], update_thumbnails_upstream(&path)].concat())
+			}
 		};
 		Ok(responses)
 	}
@@ -1270,3 +1663,65 @@ pub fn pick_safe_imaginate_resolution((width, height): (f64, f64)) -> (u64, u64)
 		scale_factor -= 0.1;
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::color::Color;
+	use crate::layers::style::{Fill, PathStyle};
+	use crate::operation_log::OperationLog;
+
+	fn fill(document: &Document, id: LayerId) -> Fill {
+		document.layer(&[id]).unwrap().style().unwrap().fill().clone()
+	}
+
+	#[test]
+	fn color_style_updates_every_linked_layer_in_one_step() {
+		let font_cache = FontCache::default();
+		let mut document = Document::default();
+		let apply = |document: &mut Document, operation| document.handle_operation(operation, &font_cache).unwrap();
+
+		apply(
+			&mut document,
+			Operation::SetColorStyle {
+				id: 1,
+				color_style: ColorStyle::new("Accent".into(), Color::RED),
+			},
+		);
+		for id in [10, 20] {
+			apply(
+				&mut document,
+				Operation::AddRect {
+					path: vec![id],
+					insert_index: -1,
+					transform: DAffine2::IDENTITY.to_cols_array(),
+					style: PathStyle::default(),
+				},
+			);
+			apply(&mut document, Operation::SetLayerFillColorStyle { path: vec![id], id: Some(1) });
+		}
+		assert_eq!(document.layers_with_color_style(1).len(), 2);
+
+		// Like the editor, keep the document from before the operation to undo it
+		let before = document.clone();
+		let mut log = OperationLog::new(1);
+		let operation = Operation::SetColorStyle {
+			id: 1,
+			color_style: ColorStyle::new("Accent".into(), Color::BLUE),
+		};
+		log.record(&document, &operation);
+		let responses = apply(&mut document, operation).unwrap();
+
+		assert_eq!(log.operations().len(), 1);
+		for id in [10, 20] {
+			assert_eq!(fill(&document, id), Fill::Solid(Color::BLUE));
+			assert!(responses.contains(&DocumentResponse::LayerChanged { path: vec![id] }));
+		}
+
+		// A single undo reverts both layers
+		log.record_snapshot(&document, &before).change.apply(&mut document, &font_cache).unwrap();
+		for id in [10, 20] {
+			assert_eq!(fill(&document, id), Fill::Solid(Color::RED));
+		}
+	}
+}
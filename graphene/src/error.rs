@@ -1,5 +1,7 @@
 use super::LayerId;
 use crate::boolean_ops::BooleanOperationError;
+use crate::layers::style::ColorStyleId;
+use crate::layers::text_layer::TextStyleId;
 
 /// A set of different errors that can occur when using Graphene.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -13,6 +15,8 @@ pub enum DocumentError {
 	NotText,
 	NotAnImage,
 	NotAnImaginate,
+	ColorStyleNotFound(ColorStyleId),
+	TextStyleNotFound(TextStyleId),
 	InvalidFile(String),
 }
 
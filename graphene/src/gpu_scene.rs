@@ -0,0 +1,558 @@
+use crate::color::Color;
+use crate::consts::{LAYER_OUTLINE_STROKE_COLOR, LAYER_OUTLINE_STROKE_WEIGHT};
+use crate::layers::layer_info::{Layer, LayerDataType};
+use crate::layers::style::{LineCap, LineJoin, PathStyle, RenderData, Stroke, ViewMode};
+use crate::layers::vector::subpath::Subpath;
+use crate::LayerId;
+
+use glam::{DAffine2, DMat2, DVec2};
+use kurbo::{BezPath, PathEl};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::ops::Range;
+
+/// The maximum distance in viewport pixels between a curve and the line segments it is flattened into.
+const FLATTEN_TOLERANCE: f64 = 0.2;
+/// The number of triangles approximating a half circle in round joins and caps.
+const ROUND_SEGMENTS: f64 = 8.;
+
+/// The renderer used to draw a document in the viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ViewportRenderer {
+	/// Builds an SVG DOM of the document, which supports every feature but becomes slow with tens of thousands of path segments.
+	#[default]
+	Svg,
+	/// Draws the tessellated layers on the GPU, keeping pan and zoom interactive on very complex documents.
+	Gpu,
+}
+
+/// A filled area drawn with the stencil-and-cover technique.
+///
+/// The triangles are drawn into the stencil buffer, incrementing it for front facing and decrementing it for back facing triangles,
+/// then the bounds are covered with the color wherever the stencil is not zero. This fills overlapping and self intersecting paths with the nonzero rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuShape {
+	/// The x and y coordinates of the triangle corners in document space, six numbers per triangle.
+	pub triangles: Vec<f32>,
+	/// The bounding box of the triangles in document space, as minimum x, minimum y, maximum x, and maximum y.
+	pub bounds: [f32; 4],
+	/// The sRGB color with straight alpha, including the opacity of the layer and its folders.
+	pub color: [f32; 4],
+}
+
+/// An image layer drawn as a textured quad.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuImage {
+	/// The transform from the unit square to the image in document space.
+	pub transform: [f64; 6],
+	#[serde(rename = "blobUrl")]
+	pub blob_url: String,
+	pub opacity: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GpuSceneItem {
+	Shape(GpuShape),
+	Image(GpuImage),
+}
+
+impl GpuSceneItem {
+	/// The bounding box of the item in document space.
+	pub fn bounds(&self) -> [DVec2; 2] {
+		match self {
+			GpuSceneItem::Shape(shape) => [DVec2::new(shape.bounds[0] as f64, shape.bounds[1] as f64), DVec2::new(shape.bounds[2] as f64, shape.bounds[3] as f64)],
+			GpuSceneItem::Image(image) => {
+				let transform = DAffine2::from_cols_array(&image.transform);
+				let corners = [DVec2::ZERO, DVec2::X, DVec2::Y, DVec2::ONE].map(|corner| transform.transform_point2(corner));
+				[corners.into_iter().reduce(DVec2::min).unwrap(), corners.into_iter().reduce(DVec2::max).unwrap()]
+			}
+		}
+	}
+}
+
+/// The visible layers of a document tessellated for the GPU viewport renderer, in drawing order from back to front.
+///
+/// The geometry is in document space so panning the viewport only changes the view transform, but curves are flattened for the current zoom level.
+/// Blend modes are not supported yet, and gradient fills are drawn with the color of their first stop.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GpuScene {
+	pub items: Vec<GpuSceneItem>,
+	/// The range of items tessellated from each layer, used to find the regions which changed between two scenes.
+	#[serde(skip)]
+	pub layers: Vec<(Vec<LayerId>, Range<usize>)>,
+}
+
+impl GpuScene {
+	/// The regions in document space which have to be drawn again to update the previous scene to this one, covering the old and new items of every changed layer.
+	///
+	/// Returns `None` if the layers were reordered, in which case the whole scene has to be drawn again.
+	pub fn changed_regions(&self, previous: &GpuScene) -> Option<Vec<[DVec2; 2]>> {
+		fn layer_items(scene: &GpuScene) -> HashMap<&[LayerId], &[GpuSceneItem]> {
+			scene.layers.iter().map(|(path, range)| (path.as_slice(), &scene.items[range.clone()])).collect()
+		}
+		let (current_items, previous_items) = (layer_items(self), layer_items(previous));
+
+		// Layers drawn in a different order overlap differently, which isn't worth tracking per region
+		let current_order = self.layers.iter().map(|(path, _)| path.as_slice()).filter(|path| previous_items.contains_key(path));
+		let previous_order = previous.layers.iter().map(|(path, _)| path.as_slice()).filter(|path| current_items.contains_key(path));
+		if !current_order.eq(previous_order) {
+			return None;
+		}
+
+		let mut regions = Vec::new();
+		for (items, other_items) in [(&current_items, &previous_items), (&previous_items, &current_items)] {
+			for (path, items) in items {
+				if other_items.get(path) != Some(items) {
+					regions.extend(items.iter().map(GpuSceneItem::bounds));
+				}
+			}
+		}
+		Some(regions)
+	}
+}
+
+/// A sequence of connected points, closed back to its first point if `closed` is set.
+struct Polyline {
+	points: Vec<DVec2>,
+	closed: bool,
+}
+
+/// Tessellates the layers below the root layer, whose transform maps document space to the viewport.
+pub fn tessellate(root: &Layer, render_data: RenderData) -> GpuScene {
+	let mut tessellator = Tessellator {
+		render_data,
+		view_inverse: root.transform.inverse(),
+		scene: GpuScene::default(),
+	};
+	if tessellator.view_inverse.is_finite() {
+		tessellator.add_layer(root, &mut Vec::new(), &mut Vec::new(), 1.);
+	}
+	tessellator.scene
+}
+
+struct Tessellator<'a> {
+	render_data: RenderData<'a>,
+	view_inverse: DAffine2,
+	scene: GpuScene,
+}
+
+impl<'a> Tessellator<'a> {
+	fn add_layer(&mut self, layer: &Layer, path: &mut Vec<LayerId>, transforms: &mut Vec<DAffine2>, opacity: f64) {
+		if !layer.visible {
+			return;
+		}
+
+		transforms.push(layer.transform);
+		let view_mode = self.render_data.view_mode;
+		// Outline mode shows every path without blending, including the ones which are transparent
+		let opacity = if view_mode == ViewMode::Outline { 1. } else { opacity * layer.opacity };
+		// Like in the SVG renderer, the transforms of all containing layers map the layer's geometry to the viewport
+		let viewport_transform = transforms.iter().fold(DAffine2::IDENTITY, |a, b| a * *b);
+		let first_item = self.scene.items.len();
+		match &layer.data {
+			LayerDataType::Folder(folder) => {
+				for (&id, child) in folder.layer_ids.iter().zip(folder.layers()) {
+					path.push(id);
+					self.add_layer(child, path, transforms, opacity);
+					path.pop();
+				}
+			}
+			LayerDataType::Shape(shape) => self.add_path(&shape.shape.with_rounded_corners(), &shape.style, viewport_transform, shape.transform(transforms, view_mode), opacity),
+			// Text which is being edited is rendered by the text input instead
+			LayerDataType::Text(text) if !text.editable => {
+				let subpath = text.to_subpath_nonmut(self.render_data.font_cache);
+				self.add_path(&subpath, &text.path_style, viewport_transform, text.transform(transforms, view_mode), opacity);
+			}
+			LayerDataType::Text(_) => {}
+			LayerDataType::Image(image) => self.add_image(image.blob_url.as_ref(), image.dimensions, viewport_transform, opacity),
+			// Generated images fill the unit square, which the layer transform sizes
+			LayerDataType::Imaginate(imaginate) => self.add_image(imaginate.blob_url.as_ref(), DVec2::ONE, viewport_transform, opacity),
+		}
+		if !matches!(layer.data, LayerDataType::Folder(_)) {
+			self.scene.layers.push((path.clone(), first_item..self.scene.items.len()));
+		}
+		transforms.pop();
+	}
+
+	/// Tessellates the fill and stroke of a path, where `style_transform` is the part of the layer transforms which the stroke weight is applied after, as in the SVG renderer.
+	fn add_path(&mut self, subpath: &Subpath, style: &PathStyle, viewport_transform: DAffine2, style_transform: DAffine2, opacity: f64) {
+		let style_to_viewport = viewport_transform * style_transform.inverse();
+		let style_to_document = self.view_inverse * style_to_viewport;
+		if !style_to_document.is_finite() {
+			return;
+		}
+
+		let mut subpath = subpath.clone();
+		subpath.apply_affine(style_transform);
+		let viewport_scale = style_to_viewport.matrix2.determinant().abs().sqrt().max(f64::EPSILON);
+		let polylines = flatten(&(&subpath).into(), FLATTEN_TOLERANCE / viewport_scale);
+
+		let outline = self.render_data.view_mode == ViewMode::Outline;
+		if !outline && style.fill().is_some() {
+			self.push_shape(&fill_triangles(&polylines), style_to_document, style.fill().color(), opacity);
+		}
+
+		let stroke = if outline {
+			Some(Stroke::new(LAYER_OUTLINE_STROKE_COLOR, LAYER_OUTLINE_STROKE_WEIGHT))
+		} else {
+			style.stroke()
+		};
+		if let Some((stroke, color)) = stroke.and_then(|stroke| stroke.color().map(|color| (stroke, color))).filter(|(stroke, _)| stroke.weight() > 0.) {
+			// A stroke of varying width is filled in from its outline, which encloses it with the nonzero fill rule
+			if stroke.has_width_profile() {
+				let outline = flatten(&(&subpath.stroke_outline(&stroke)).into(), FLATTEN_TOLERANCE / viewport_scale);
+				self.push_shape(&fill_triangles(&outline), style_to_document, color, opacity);
+				return;
+			}
+
+			let mut triangles = Vec::new();
+			for polyline in polylines {
+				for dash in dash(polyline, stroke.dash_pattern(), stroke.dash_offset()) {
+					stroke_polyline(&dash, &stroke, &mut triangles);
+				}
+			}
+			// Overlapping stroke triangles must not cancel out, so they are all given the same winding
+			for [a, b, c] in &mut triangles {
+				if (*b - *a).perp_dot(*c - *a) < 0. {
+					std::mem::swap(b, c);
+				}
+			}
+			self.push_shape(&triangles, style_to_document, color, opacity);
+		}
+	}
+
+	fn add_image(&mut self, blob_url: Option<&String>, dimensions: DVec2, viewport_transform: DAffine2, opacity: f64) {
+		// Only the bounds of the image are drawn in outline mode
+		if self.render_data.view_mode == ViewMode::Outline {
+			let bounds = Subpath::new_rect(DVec2::ZERO, dimensions);
+			self.add_path(&bounds, &PathStyle::default(), viewport_transform, viewport_transform, opacity);
+			return;
+		}
+
+		if let Some(blob_url) = blob_url {
+			let transform = self.view_inverse * viewport_transform * DAffine2::from_scale(dimensions);
+			self.scene.items.push(GpuSceneItem::Image(GpuImage {
+				transform: transform.to_cols_array(),
+				blob_url: blob_url.clone(),
+				opacity: opacity as f32,
+			}));
+		}
+	}
+
+	fn push_shape(&mut self, triangles: &[[DVec2; 3]], to_document: DAffine2, color: Color, opacity: f64) {
+		let alpha = color.a() * opacity as f32;
+		if triangles.is_empty() || alpha <= 0. {
+			return;
+		}
+
+		let (mut min, mut max) = (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY));
+		let mut vertices = Vec::with_capacity(triangles.len() * 6);
+		for point in triangles.iter().flatten() {
+			let point = to_document.transform_point2(*point);
+			min = min.min(point);
+			max = max.max(point);
+			vertices.extend([point.x as f32, point.y as f32]);
+		}
+		self.scene.items.push(GpuSceneItem::Shape(GpuShape {
+			triangles: vertices,
+			bounds: [min.x as f32, min.y as f32, max.x as f32, max.y as f32],
+			color: [color.r(), color.g(), color.b(), alpha],
+		}));
+	}
+}
+
+/// Approximates the curves of the path with line segments, splitting it into one polyline per subpath.
+/// Fills in the polylines with fans of triangles from their first points, whose overlapping coverage follows the nonzero fill rule.
+fn fill_triangles(polylines: &[Polyline]) -> Vec<[DVec2; 3]> {
+	let mut triangles = Vec::new();
+	for polyline in polylines.iter().filter(|polyline| polyline.points.len() > 2) {
+		// The signed coverage of a fan from the first point equals the winding number of the polygon, even if it is concave or self intersecting
+		let first = polyline.points[0];
+		triangles.extend(polyline.points[1..].windows(2).map(|pair| [first, pair[0], pair[1]]));
+	}
+	triangles
+}
+
+fn flatten(path: &BezPath, tolerance: f64) -> Vec<Polyline> {
+	let mut polylines: Vec<Polyline> = Vec::new();
+	let point = |point: kurbo::Point| DVec2::new(point.x, point.y);
+	path.flatten(tolerance, |element| match element {
+		PathEl::MoveTo(start) => polylines.push(Polyline {
+			points: vec![point(start)],
+			closed: false,
+		}),
+		PathEl::LineTo(end) => {
+			if let Some(polyline) = polylines.last_mut() {
+				polyline.points.push(point(end));
+			}
+		}
+		PathEl::ClosePath => {
+			if let Some(polyline) = polylines.last_mut() {
+				polyline.closed = true;
+			}
+		}
+		_ => {}
+	});
+	polylines
+}
+
+/// Splits a polyline into its dashes, alternating between the dash and gap lengths of the pattern starting at the offset.
+fn dash(polyline: Polyline, pattern: &[f32], offset: f64) -> Vec<Polyline> {
+	let mut pattern = pattern.iter().map(|&length| length as f64).collect::<Vec<_>>();
+	if pattern.iter().sum::<f64>() <= 0. || pattern.iter().any(|&length| length < 0.) || polyline.points.len() < 2 {
+		return vec![polyline];
+	}
+	// Like in SVG, a pattern with an odd number of lengths is repeated to get an even number
+	if pattern.len() % 2 == 1 {
+		pattern.extend(pattern.clone());
+	}
+
+	let mut points = polyline.points;
+	if polyline.closed {
+		points.push(points[0]);
+	}
+
+	let mut index = 0;
+	let mut remaining = pattern[0];
+	let mut skipped = offset.rem_euclid(pattern.iter().sum());
+	while skipped >= remaining {
+		skipped -= remaining;
+		index = (index + 1) % pattern.len();
+		remaining = pattern[index];
+	}
+	remaining -= skipped;
+
+	let mut dashes = Vec::new();
+	let mut current = (index % 2 == 0).then(|| vec![points[0]]);
+	for segment in points.windows(2) {
+		let (mut start, end) = (segment[0], segment[1]);
+		let mut length = start.distance(end);
+		while length > remaining {
+			let split = start.lerp(end, remaining / length);
+			match current.take() {
+				Some(mut points) => {
+					points.push(split);
+					dashes.push(Polyline { points, closed: false });
+				}
+				None => current = Some(vec![split]),
+			}
+			length -= remaining;
+			start = split;
+			index = (index + 1) % pattern.len();
+			remaining = pattern[index];
+		}
+		remaining -= length;
+		if let Some(points) = &mut current {
+			points.push(end);
+		}
+	}
+	dashes.extend(current.map(|points| Polyline { points, closed: false }));
+	dashes
+}
+
+/// Adds the triangles covering the stroke of a polyline, made of a quad for every segment along with the joins between them and the caps at the ends.
+fn stroke_polyline(polyline: &Polyline, stroke: &Stroke, triangles: &mut Vec<[DVec2; 3]>) {
+	let half_width = stroke.weight() / 2.;
+	let mut points = polyline.points.clone();
+	points.dedup_by(|a, b| a.distance_squared(*b) < 1e-12);
+	if polyline.closed && points.len() > 2 && points[0].distance_squared(points[points.len() - 1]) < 1e-12 {
+		points.pop();
+	}
+	if points.len() < 2 {
+		return;
+	}
+
+	let closed = polyline.closed && points.len() > 2;
+	let segment_count = if closed { points.len() } else { points.len() - 1 };
+	let direction = |index: usize| (points[(index + 1) % points.len()] - points[index]).normalize();
+
+	for index in 0..segment_count {
+		let (start, end) = (points[index], points[(index + 1) % points.len()]);
+		let normal = direction(index).perp() * half_width;
+		triangles.push([start + normal, end + normal, end - normal]);
+		triangles.push([start + normal, end - normal, start - normal]);
+	}
+
+	let joins = if closed { 0..segment_count } else { 1..segment_count };
+	for index in joins {
+		let previous = (index + segment_count - 1) % segment_count;
+		join(points[index], direction(previous), direction(index), stroke, half_width, triangles);
+	}
+
+	if !closed {
+		cap(points[0], -direction(0), stroke.line_cap(), half_width, triangles);
+		cap(points[points.len() - 1], direction(segment_count - 1), stroke.line_cap(), half_width, triangles);
+	}
+}
+
+/// Fills the gap on the outer side of the turn between two segments meeting at a point.
+fn join(point: DVec2, incoming: DVec2, outgoing: DVec2, stroke: &Stroke, half_width: f64, triangles: &mut Vec<[DVec2; 3]>) {
+	let turn = incoming.perp_dot(outgoing);
+	if turn.abs() < 1e-9 && incoming.dot(outgoing) > 0. {
+		return;
+	}
+
+	let side = if turn > 0. { -1. } else { 1. };
+	let (from, to) = (incoming.perp() * half_width * side, outgoing.perp() * half_width * side);
+	match stroke.line_join() {
+		LineJoin::Round => arc(point, from, from.angle_between(to), triangles),
+		LineJoin::Miter => {
+			let bisector = (from + to).normalize_or_zero();
+			let cos_half_angle = bisector.dot(from) / half_width;
+			// The ratio of the miter length to the stroke weight is the inverse of the cosine of half the angle between the normals
+			if cos_half_angle > 0. && 1. / cos_half_angle <= stroke.line_join_miter_limit() as f64 {
+				let tip = point + bisector * (half_width / cos_half_angle);
+				triangles.push([point, point + from, tip]);
+				triangles.push([point, tip, point + to]);
+			} else {
+				triangles.push([point, point + from, point + to]);
+			}
+		}
+		LineJoin::Bevel => triangles.push([point, point + from, point + to]),
+	}
+}
+
+/// Extends the stroke past an end point in the outward direction.
+fn cap(point: DVec2, direction: DVec2, line_cap: LineCap, half_width: f64, triangles: &mut Vec<[DVec2; 3]>) {
+	let normal = direction.perp() * half_width;
+	match line_cap {
+		LineCap::Butt => {}
+		LineCap::Square => {
+			let extension = direction * half_width;
+			triangles.push([point + normal, point + normal + extension, point - normal + extension]);
+			triangles.push([point + normal, point - normal + extension, point - normal]);
+		}
+		LineCap::Round => arc(point, normal, -PI, triangles),
+	}
+}
+
+/// Adds a fan of triangles covering the circular sector starting at `center + from` and rotating by `angle`.
+fn arc(center: DVec2, from: DVec2, angle: f64, triangles: &mut Vec<[DVec2; 3]>) {
+	let steps = (angle.abs() / PI * ROUND_SEGMENTS).ceil().max(1.) as usize;
+	let corner = |step: usize| center + DMat2::from_angle(angle * step as f64 / steps as f64) * from;
+	triangles.extend((0..steps).map(|step| [center, corner(step), corner(step + 1)]));
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn polyline(points: &[(f64, f64)], closed: bool) -> Polyline {
+		Polyline {
+			points: points.iter().map(|&(x, y)| DVec2::new(x, y)).collect(),
+			closed,
+		}
+	}
+
+	fn signed_area([a, b, c]: &[DVec2; 3]) -> f64 {
+		(*b - *a).perp_dot(*c - *a) / 2.
+	}
+
+	/// The sum of the signs of the triangles covering a point, which is the winding number of the filled path around it.
+	fn winding_at(triangles: &[[DVec2; 3]], point: DVec2) -> i32 {
+		let contains = |[a, b, c]: &[DVec2; 3]| {
+			let sides = [(*b - *a).perp_dot(point - *a), (*c - *b).perp_dot(point - *b), (*a - *c).perp_dot(point - *c)];
+			sides.iter().all(|&side| side > 0.) || sides.iter().all(|&side| side < 0.)
+		};
+		triangles.iter().filter(|triangle| contains(triangle)).map(|triangle| signed_area(triangle).signum() as i32).sum()
+	}
+
+	/// Whether a point is covered by any of the triangles of a stroke, whose triangles are given the same winding before they are drawn.
+	fn covers(triangles: &[[DVec2; 3]], point: DVec2) -> bool {
+		let positive = triangles.iter().map(|&[a, b, c]| if signed_area(&[a, b, c]) < 0. { [a, c, b] } else { [a, b, c] }).collect::<Vec<_>>();
+		winding_at(&positive, point) > 0
+	}
+
+	#[test]
+	fn fill_follows_the_winding_of_the_path() {
+		let square = [(0., 0.), (10., 0.), (10., 10.), (0., 10.)];
+		let triangles = fill_triangles(&[polyline(&square, true)]);
+		assert_eq!(triangles.iter().map(signed_area).sum::<f64>(), 100.);
+		assert_eq!(winding_at(&triangles, DVec2::new(3., 6.)), 1);
+		assert_eq!(winding_at(&triangles, DVec2::new(13., 6.)), 0);
+
+		// Reversing the direction reverses the winding
+		let reversed = square.iter().rev().copied().collect::<Vec<_>>();
+		let triangles = fill_triangles(&[polyline(&reversed, true)]);
+		assert_eq!(triangles.iter().map(signed_area).sum::<f64>(), -100.);
+		assert_eq!(winding_at(&triangles, DVec2::new(3., 6.)), -1);
+	}
+
+	#[test]
+	fn fill_of_concave_and_overlapping_paths() {
+		// An L shape, whose fan from the first point covers the notch positively and negatively
+		let l_shape = [(0., 0.), (10., 0.), (10., 4.), (4., 4.), (4., 10.), (0., 10.)];
+		let triangles = fill_triangles(&[polyline(&l_shape, true)]);
+		assert_eq!(triangles.iter().map(signed_area).sum::<f64>(), 64.);
+		assert_eq!(winding_at(&triangles, DVec2::new(7., 7.)), 0);
+		assert_eq!(winding_at(&triangles, DVec2::new(2., 7.)), 1);
+
+		// Two overlapping squares in the same direction cover their overlap twice
+		let first = polyline(&[(0., 0.), (10., 0.), (10., 10.), (0., 10.)], true);
+		let second = polyline(&[(5., 5.), (15., 5.), (15., 15.), (5., 15.)], true);
+		let triangles = fill_triangles(&[first, second]);
+		assert_eq!(winding_at(&triangles, DVec2::new(7., 8.)), 2);
+		assert_eq!(winding_at(&triangles, DVec2::new(2., 3.)), 1);
+	}
+
+	#[test]
+	fn degenerate_paths_have_no_triangles() {
+		assert!(fill_triangles(&[polyline(&[(0., 0.), (10., 0.)], true)]).is_empty());
+		assert!(fill_triangles(&[polyline(&[(5., 5.)], false)]).is_empty());
+
+		let stroke = Stroke::new(Color::BLACK, 2.).with_line_cap(LineCap::Round);
+		let mut triangles = Vec::new();
+		stroke_polyline(&polyline(&[(5., 5.), (5., 5.), (5., 5.)], false), &stroke, &mut triangles);
+		stroke_polyline(&polyline(&[], true), &stroke, &mut triangles);
+		assert!(triangles.is_empty());
+
+		// An empty or zero length dash pattern leaves the polyline solid
+		assert_eq!(dash(polyline(&[(0., 0.), (10., 0.)], false), &[], 0.).len(), 1);
+		assert_eq!(dash(polyline(&[(0., 0.), (10., 0.)], false), &[0., 0.], 0.).len(), 1);
+	}
+
+	#[test]
+	fn stroke_of_a_line() {
+		let total_area = |stroke: &Stroke, line: &Polyline| {
+			let mut triangles = Vec::new();
+			stroke_polyline(line, stroke, &mut triangles);
+			triangles.iter().map(|triangle| signed_area(triangle).abs()).sum::<f64>()
+		};
+		let line = polyline(&[(0., 0.), (10., 0.)], false);
+		let stroke = Stroke::new(Color::BLACK, 2.);
+		assert!((total_area(&stroke, &line) - 20.).abs() < 1e-9);
+		assert!((total_area(&stroke.clone().with_line_cap(LineCap::Square), &line) - 24.).abs() < 1e-9);
+		// Round caps add a circle of the stroke weight, approximated by a polygon inside it
+		let round = total_area(&stroke.clone().with_line_cap(LineCap::Round), &line);
+		assert!(round > 20. + PI * 0.95 && round < 20. + PI);
+
+		// A closed square has no caps, and its miter joins fill in the outer corners exactly
+		let square = polyline(&[(0., 0.), (10., 0.), (10., 10.), (0., 10.)], true);
+		let miter = stroke.clone().with_line_join(LineJoin::Miter).with_line_cap(LineCap::Square);
+		let mut triangles = Vec::new();
+		stroke_polyline(&square, &miter, &mut triangles);
+		assert!(covers(&triangles, DVec2::new(-0.9, -0.8)));
+		assert!(covers(&triangles, DVec2::new(5., 0.5)));
+		assert!(!covers(&triangles, DVec2::new(5., 5.)));
+
+		// A bevel only cuts the corner off
+		let bevel = stroke.with_line_join(LineJoin::Bevel);
+		let mut triangles = Vec::new();
+		stroke_polyline(&square, &bevel, &mut triangles);
+		assert!(!covers(&triangles, DVec2::new(-0.9, -0.8)));
+		assert!(covers(&triangles, DVec2::new(-0.2, -0.2)));
+	}
+
+	#[test]
+	fn dashes_split_the_polyline() {
+		let dashes = dash(polyline(&[(0., 0.), (10., 0.)], false), &[3., 2.], 0.);
+		let spans = dashes.iter().map(|dash| (dash.points[0].x, dash.points.last().unwrap().x)).collect::<Vec<_>>();
+		assert_eq!(spans, [(0., 3.), (5., 8.)]);
+
+		// The offset shifts the pattern along the line
+		let dashes = dash(polyline(&[(0., 0.), (10., 0.)], false), &[3., 2.], 4.);
+		let spans = dashes.iter().map(|dash| (dash.points[0].x, dash.points.last().unwrap().x)).collect::<Vec<_>>();
+		assert_eq!(spans, [(1., 4.), (6., 9.)]);
+	}
+}
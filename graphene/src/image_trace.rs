@@ -0,0 +1,361 @@
+use crate::color::Color;
+use crate::layers::vector::manipulator_group::ManipulatorGroup;
+use crate::layers::vector::subpath::Subpath;
+
+use glam::{DVec2, IVec2};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The label of the pixels which aren't traced, being transparent or lighter than the threshold.
+const UNTRACED: u32 = u32::MAX;
+/// Corners of the traced outlines are kept sharp where both of their sides span at least this many pixels.
+const CORNER_SIDE_LENGTH: i32 = 2;
+/// How far, in pixels, the traced outlines may deviate from the smoothed pixel edges when their anchors are reduced.
+const SIMPLIFY_TOLERANCE: f64 = 0.5;
+
+/// How the pixels of an image are split into the regions which are traced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TraceMode {
+	/// Traces the pixels darker than the threshold as a single black shape.
+	Threshold,
+	/// Rounds the color channels of the pixels to a number of levels, tracing a shape for each resulting color.
+	Posterize,
+}
+
+/// The settings for tracing an image with [trace_image].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TraceOptions {
+	pub mode: TraceMode,
+	/// The luminance, from 0 to 255, below which pixels are traced in [TraceMode::Threshold]
+	pub threshold: u8,
+	/// The number of levels, from 2 to 16, each color channel is rounded to in [TraceMode::Posterize]
+	pub levels: u8,
+	/// Regions with fewer pixels than this are merged into the region surrounding them
+	pub speckle_size: usize,
+	/// How much the corners between pixels are rounded off, from 0 for straight lines to 1 for smooth curves
+	pub corner_smoothing: f64,
+}
+
+impl Default for TraceOptions {
+	fn default() -> Self {
+		Self {
+			mode: TraceMode::Threshold,
+			threshold: 128,
+			levels: 4,
+			speckle_size: 8,
+			corner_smoothing: 1.,
+		}
+	}
+}
+
+/// A region of a traced image, with the outline of its pixels and the color to fill it with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracedShape {
+	pub color: Color,
+	/// The outline in pixel coordinates, with the origin at the top left corner of the image
+	pub path: Subpath,
+}
+
+/// Trace an image, given as 8-bit RGBA pixels row by row, into shapes outlining its regions of similar color.
+/// The shapes are ordered from the largest to the smallest region, so stacking them in that order keeps the smaller details on top.
+pub fn trace_image(pixels: &[u8], width: u32, height: u32, options: &TraceOptions) -> Vec<TracedShape> {
+	let (width, height) = (width as usize, height as usize);
+	if width == 0 || height == 0 || pixels.len() < width * height * 4 {
+		return Vec::new();
+	}
+
+	let mut labels = label_pixels(pixels, width * height, options);
+	remove_speckles(&mut labels, width, height, options.speckle_size);
+
+	// The number of pixels of each region and the sum of their color channels
+	let mut regions: HashMap<u32, (usize, [u64; 3])> = HashMap::new();
+	for (index, &label) in labels.iter().enumerate().filter(|&(_, &label)| label != UNTRACED) {
+		let (count, sum) = regions.entry(label).or_default();
+		*count += 1;
+		for (channel, total) in sum.iter_mut().enumerate() {
+			*total += pixels[index * 4 + channel] as u64;
+		}
+	}
+
+	let mut regions: Vec<_> = regions.into_iter().collect();
+	regions.sort_by_key(|&(label, (count, _))| (std::cmp::Reverse(count), label));
+
+	regions
+		.into_iter()
+		.map(|(label, (count, sum))| {
+			let color = match options.mode {
+				TraceMode::Threshold => Color::BLACK,
+				TraceMode::Posterize => {
+					let [red, green, blue] = sum.map(|channel| (channel / count as u64) as u8);
+					Color::from_rgba8(red, green, blue, 255)
+				}
+			};
+			let path = trace_region(&labels, width, height, label, options.corner_smoothing);
+			TracedShape { color, path }
+		})
+		.collect()
+}
+
+/// Label each pixel with the region it belongs to, before pixels of the same label are split into separate regions by connectivity.
+fn label_pixels(pixels: &[u8], pixel_count: usize, options: &TraceOptions) -> Vec<u32> {
+	let levels = options.levels.clamp(2, 16) as u32;
+
+	pixels
+		.chunks_exact(4)
+		.take(pixel_count)
+		.map(|pixel| {
+			if pixel[3] < 128 {
+				return UNTRACED;
+			}
+			match options.mode {
+				TraceMode::Threshold => {
+					let luminance = 0.2126 * pixel[0] as f64 + 0.7152 * pixel[1] as f64 + 0.0722 * pixel[2] as f64;
+					if luminance < options.threshold as f64 {
+						0
+					} else {
+						UNTRACED
+					}
+				}
+				TraceMode::Posterize => {
+					let level = |channel: u8| (channel as u32 * (levels - 1) + 127) / 255;
+					(level(pixel[0]) * levels + level(pixel[1])) * levels + level(pixel[2])
+				}
+			}
+		})
+		.collect()
+}
+
+/// Relabel the connected areas of pixels smaller than `speckle_size` with the label most common around them, including untraced areas such as small holes.
+fn remove_speckles(labels: &mut [u32], width: usize, height: usize, speckle_size: usize) {
+	if speckle_size <= 1 {
+		return;
+	}
+
+	let neighbors = |index: usize| {
+		let (x, y) = (index % width, index / width);
+		[
+			(x > 0).then(|| index - 1),
+			(x + 1 < width).then(|| index + 1),
+			(y > 0).then(|| index - width),
+			(y + 1 < height).then(|| index + width),
+		]
+		.into_iter()
+		.flatten()
+	};
+
+	let mut visited = vec![false; labels.len()];
+	let mut area = Vec::new();
+	for start in 0..labels.len() {
+		if visited[start] {
+			continue;
+		}
+
+		// Flood fill the connected area of pixels with the same label
+		let label = labels[start];
+		area.clear();
+		area.push(start);
+		visited[start] = true;
+		let mut next = 0;
+		while next < area.len() {
+			for neighbor in neighbors(area[next]) {
+				if !visited[neighbor] && labels[neighbor] == label {
+					visited[neighbor] = true;
+					area.push(neighbor);
+				}
+			}
+			next += 1;
+		}
+		if area.len() >= speckle_size {
+			continue;
+		}
+
+		let mut surrounding_labels: HashMap<u32, usize> = HashMap::new();
+		for &index in &area {
+			for neighbor in neighbors(index).filter(|&neighbor| labels[neighbor] != label) {
+				*surrounding_labels.entry(labels[neighbor]).or_default() += 1;
+			}
+		}
+		if let Some((surrounding_label, _)) = surrounding_labels.into_iter().max_by_key(|&(surrounding_label, count)| (count, std::cmp::Reverse(surrounding_label))) {
+			for &index in &area {
+				labels[index] = surrounding_label;
+			}
+		}
+	}
+}
+
+/// Outline the pixels with the given label as closed contours, which wind clockwise around the region and counter-clockwise around its holes.
+fn trace_region(labels: &[u32], width: usize, height: usize, label: u32, corner_smoothing: f64) -> Subpath {
+	let inside = |x: i32, y: i32| x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height && labels[y as usize * width + x as usize] == label;
+
+	// The edges between the pixels of the region and those outside of it, directed to keep the region on their right
+	let mut edges = Vec::new();
+	for y in 0..height as i32 {
+		for x in 0..width as i32 {
+			if !inside(x, y) {
+				continue;
+			}
+			if !inside(x, y - 1) {
+				edges.push((IVec2::new(x, y), IVec2::new(x + 1, y)));
+			}
+			if !inside(x + 1, y) {
+				edges.push((IVec2::new(x + 1, y), IVec2::new(x + 1, y + 1)));
+			}
+			if !inside(x, y + 1) {
+				edges.push((IVec2::new(x + 1, y + 1), IVec2::new(x, y + 1)));
+			}
+			if !inside(x - 1, y) {
+				edges.push((IVec2::new(x, y + 1), IVec2::new(x, y)));
+			}
+		}
+	}
+
+	let mut outgoing: HashMap<IVec2, Vec<usize>> = HashMap::new();
+	for (index, (start, _)) in edges.iter().enumerate() {
+		outgoing.entry(*start).or_default().push(index);
+	}
+
+	let mut path = Subpath::new();
+	let mut used = vec![false; edges.len()];
+	for first in 0..edges.len() {
+		if used[first] {
+			continue;
+		}
+
+		// Follow the edges around until returning to the start, turning right where diagonally touching pixels leave a choice, which keeps them apart
+		let mut corners = Vec::new();
+		let mut current = first;
+		loop {
+			used[current] = true;
+			let (start, end) = edges[current];
+			corners.push(start);
+			if end == edges[first].0 {
+				break;
+			}
+
+			let direction = end - start;
+			let next = outgoing.get(&end).and_then(|candidates| {
+				candidates
+					.iter()
+					.copied()
+					.filter(|&candidate| !used[candidate])
+					.max_by_key(|&candidate| direction.perp_dot(edges[candidate].1 - end))
+			});
+			match next {
+				Some(next) => current = next,
+				None => break,
+			}
+		}
+
+		let groups = smooth_contour(&remove_collinear(corners), corner_smoothing);
+		path.manipulator_groups_mut().push_range(groups);
+		path.manipulator_groups_mut().push(ManipulatorGroup::closed());
+	}
+
+	path.simplify(SIMPLIFY_TOLERANCE)
+}
+
+/// Remove the points of a closed polygon which lie on a straight line between their neighbors.
+fn remove_collinear(points: Vec<IVec2>) -> Vec<IVec2> {
+	let len = points.len();
+	(0..len)
+		.filter(|&index| {
+			let (previous, point, next) = (points[(index + len - 1) % len], points[index], points[(index + 1) % len]);
+			(point - previous).perp_dot(next - point) != 0
+		})
+		.map(|index| points[index])
+		.collect()
+}
+
+/// Turn the corners of a polygon running along pixel edges into anchors at the middle of each side, smoothly connected with handles scaled by `corner_smoothing`.
+/// The staircases of pixels along diagonals and curves are smoothed over this way, while the corners between longer sides are kept sharp.
+fn smooth_contour(corners: &[IVec2], corner_smoothing: f64) -> Vec<ManipulatorGroup> {
+	let len = corners.len();
+	let side_length = |index: usize| {
+		let side = corners[(index + 1) % len] - corners[index];
+		side.x.abs() + side.y.abs()
+	};
+
+	// Each anchor, and whether it's a sharp corner
+	let mut anchors = Vec::new();
+	for index in 0..len {
+		let (start, end) = (corners[index].as_f64(), corners[(index + 1) % len].as_f64());
+		anchors.push(((start + end) / 2., false));
+		if side_length(index) >= CORNER_SIDE_LENGTH && side_length((index + 1) % len) >= CORNER_SIDE_LENGTH {
+			anchors.push((end, true));
+		}
+	}
+
+	let len = anchors.len();
+	(0..len)
+		.map(|index| {
+			let (anchor, sharp) = anchors[index];
+			if sharp || corner_smoothing <= 0. {
+				return ManipulatorGroup::new_with_anchor(anchor);
+			}
+
+			// Catmull-Rom style handles along the direction between the neighboring anchors
+			let (previous, next) = (anchors[(index + len - 1) % len].0, anchors[(index + 1) % len].0);
+			let handle: DVec2 = (next - previous) * corner_smoothing.min(1.) / 6.;
+			ManipulatorGroup::new_with_handles(anchor, Some(anchor - handle), Some(anchor + handle))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// An image of the given size, black where `dark` is true and white elsewhere.
+	fn image(width: usize, height: usize, dark: impl Fn(usize, usize) -> bool) -> Vec<u8> {
+		(0..width * height)
+			.flat_map(|index| {
+				let value = if dark(index % width, index / width) { 0 } else { 255 };
+				[value, value, value, 255]
+			})
+			.collect()
+	}
+
+	#[test]
+	fn trace_square_keeps_corners() {
+		let pixels = image(10, 10, |x, y| (2..8).contains(&x) && (2..8).contains(&y));
+		let options = TraceOptions {
+			speckle_size: 0,
+			..Default::default()
+		};
+
+		let shapes = trace_image(&pixels, 10, 10, &options);
+		assert_eq!(shapes.len(), 1);
+		assert_eq!(shapes[0].color, Color::BLACK);
+		assert_eq!(shapes[0].path.bounding_box(), Some([DVec2::splat(2.), DVec2::splat(8.)]));
+		assert_eq!(shapes[0].path.anchor_count(), 4);
+	}
+
+	#[test]
+	fn trace_removes_speckles() {
+		// A square with a hole in it and a stray pixel outside of it, both smaller than the speckle size
+		let pixels = image(12, 12, |x, y| (x == 10 && y == 10) || ((2..8).contains(&x) && (2..8).contains(&y) && !(x == 4 && y == 4)));
+
+		let shapes = trace_image(&pixels, 12, 12, &TraceOptions::default());
+		assert_eq!(shapes.len(), 1);
+		assert_eq!(shapes[0].path.bounding_box(), Some([DVec2::splat(2.), DVec2::splat(8.)]));
+
+		let contours = shapes[0].path.manipulator_groups().iter().filter(|group| group.is_close()).count();
+		assert_eq!(contours, 1);
+	}
+
+	#[test]
+	fn posterize_splits_colors() {
+		let pixels: Vec<u8> = (0..4 * 4).flat_map(|index| if index % 4 < 2 { [255, 0, 0, 255] } else { [0, 0, 255, 255] }).collect();
+		let options = TraceOptions {
+			mode: TraceMode::Posterize,
+			speckle_size: 0,
+			..Default::default()
+		};
+
+		let shapes = trace_image(&pixels, 4, 4, &options);
+		assert_eq!(shapes.len(), 2);
+		let colors: Vec<_> = shapes.iter().map(|shape| shape.color).collect();
+		assert!(colors.contains(&Color::from_rgba8(255, 0, 0, 255)));
+		assert!(colors.contains(&Color::from_rgba8(0, 0, 255, 255)));
+	}
+}
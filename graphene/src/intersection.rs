@@ -2,6 +2,7 @@ use crate::boolean_ops::{split_path_seg, subdivide_path_seg};
 use crate::consts::{F64LOOSE, F64PRECISE};
 use crate::layers::vector::subpath::Subpath;
 
+use bezier_rs::Bezier;
 use glam::{DAffine2, DMat2, DVec2};
 use kurbo::{BezPath, CubicBez, Line, ParamCurve, ParamCurveDeriv, ParamCurveExtrema, PathSeg, Point, QuadBez, Rect, Shape, Vec2};
 use std::collections::VecDeque;
@@ -56,6 +57,17 @@ impl Quad {
 	pub fn center(&self) -> DVec2 {
 		self.0.iter().sum::<DVec2>() / 4.
 	}
+
+	/// Get all the edges in the quad as linear [Bezier]s.
+	pub fn bezier_lines(&self) -> [Bezier; 4] {
+		[0, 1, 2, 3].map(|index| Bezier::from_linear_dvec2(self.0[index], self.0[(index + 1) % 4]))
+	}
+
+	/// Checks if the point is inside the quad or on its edges, which assumes the quad is convex like the transformed boxes it's made from.
+	pub fn contains(&self, point: DVec2) -> bool {
+		let sides = [0, 1, 2, 3].map(|index| (self.0[(index + 1) % 4] - self.0[index]).perp_dot(point - self.0[index]));
+		sides.iter().all(|&side| side >= 0.) || sides.iter().all(|&side| side <= 0.)
+	}
 }
 
 impl Mul<Quad> for DAffine2 {
@@ -100,6 +112,16 @@ pub fn intersect_quad_bez_path(quad: Quad, shape: &BezPath, filled: bool) -> boo
 	get_arbitrary_point_on_path(&shape).map(|shape_point| quad.path().contains(shape_point)).unwrap_or_default()
 }
 
+/// Return `true` if `shape` lies entirely within `quad`.
+/// This is the case if each segment of `shape` starts inside `quad` and none of them cross its edges, which is exact for curves unlike comparing bounding boxes.
+/// The fill of an open shape is also within `quad` then, since `quad` is convex.
+pub fn quad_encloses_subpath(quad: Quad, shape: &Subpath) -> bool {
+	let edges = quad.bezier_lines();
+	let mut segments = shape.bezier_iter().map(|segment| segment.internal).peekable();
+
+	segments.peek().is_some() && segments.all(|segment| quad.contains(segment.start()) && edges.iter().all(|edge| segment.intersections(edge, None).is_empty()))
+}
+
 /// Returns a point on `path`.
 /// This function will usually return the first point from the path's first segment, but callers should not rely on this behavior.
 pub fn get_arbitrary_point_on_path(path: &BezPath) -> Option<Point> {
@@ -1160,4 +1182,19 @@ mod tests {
 		use std::env::current_dir;
 		println!("{:?}", current_dir());
 	}
+
+	#[test]
+	fn quad_encloses_subpath_only_when_entirely_inside() {
+		let quad = Quad::from_box([DVec2::ZERO, DVec2::splat(100.)]);
+
+		assert!(quad.contains(DVec2::new(50., 50.)));
+		assert!(quad.contains(DVec2::new(0., 100.)));
+		assert!(!quad.contains(DVec2::new(-1., 50.)));
+
+		assert!(quad_encloses_subpath(quad, &Subpath::new_rect(DVec2::splat(10.), DVec2::splat(90.))));
+		assert!(quad_encloses_subpath(quad, &Subpath::new_ellipse(DVec2::splat(10.), DVec2::splat(90.))));
+		assert!(!quad_encloses_subpath(quad, &Subpath::new_rect(DVec2::splat(50.), DVec2::splat(150.))));
+		assert!(!quad_encloses_subpath(quad, &Subpath::new_rect(DVec2::splat(-10.), DVec2::splat(110.))));
+		assert!(!quad_encloses_subpath(quad, &Subpath::default()));
+	}
 }
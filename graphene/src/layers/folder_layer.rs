@@ -36,6 +36,14 @@ impl LayerData for FolderLayer {
 		}
 	}
 
+	fn enclosed_by_quad(&self, quad: Quad, path: &mut Vec<LayerId>, enclosed: &mut Vec<Vec<LayerId>>, font_cache: &FontCache) {
+		for (layer, layer_id) in self.layers().iter().zip(&self.layer_ids) {
+			path.push(*layer_id);
+			layer.enclosed_by_quad(quad, path, enclosed, font_cache);
+			path.pop();
+		}
+	}
+
 	fn bounding_box(&self, transform: glam::DAffine2, font_cache: &FontCache) -> Option<[DVec2; 2]> {
 		self.layers
 			.iter()
@@ -120,6 +128,54 @@ impl FolderLayer {
 		Ok(())
 	}
 
+	/// Moves the layers with the given IDs up (positive offset) or down (negative offset) the stacking order by `relative_index_offset` steps.
+	/// The layers keep their order relative to each other, and a layer stops once it runs into the end of the folder or another moving layer that has stopped.
+	///
+	/// # Example
+	/// ```
+	/// # use graphite_graphene::layers::folder_layer::FolderLayer;
+	/// let mut folder = FolderLayer::default();
+	/// for id in 0..5 {
+	///     folder.add_layer(FolderLayer::default().into(), Some(id), -1);
+	/// }
+	///
+	/// folder.reorder_layers(&[1, 3], 1);
+	/// assert_eq!(folder.list_layers(), &[0, 2, 1, 4, 3]);
+	///
+	/// folder.reorder_layers(&[1, 3], isize::MIN);
+	/// assert_eq!(folder.list_layers(), &[1, 3, 0, 2, 4]);
+	/// ```
+	pub fn reorder_layers(&mut self, ids: &[LayerId], relative_index_offset: isize) {
+		let len = self.layer_ids.len();
+		let steps = relative_index_offset.unsigned_abs().min(len);
+
+		for _ in 0..steps {
+			let mut moved = false;
+
+			if relative_index_offset > 0 {
+				for index in (0..len.saturating_sub(1)).rev() {
+					if ids.contains(&self.layer_ids[index]) && !ids.contains(&self.layer_ids[index + 1]) {
+						self.layer_ids.swap(index, index + 1);
+						self.layers.swap(index, index + 1);
+						moved = true;
+					}
+				}
+			} else {
+				for index in 1..len {
+					if ids.contains(&self.layer_ids[index]) && !ids.contains(&self.layer_ids[index - 1]) {
+						self.layer_ids.swap(index, index - 1);
+						self.layers.swap(index, index - 1);
+						moved = true;
+					}
+				}
+			}
+
+			if !moved {
+				break;
+			}
+		}
+	}
+
 	/// Returns a list of [LayerId]s in the folder.
 	pub fn list_layers(&self) -> &[LayerId] {
 		self.layer_ids.as_slice()
@@ -1,20 +1,36 @@
 use super::base64_serde;
 use super::layer_info::LayerData;
-use super::style::{RenderData, ViewMode};
-use crate::intersection::{intersect_quad_bez_path, Quad};
+use super::style::{outline_attributes, RenderData, ViewMode};
+use super::vector::subpath::Subpath;
+use crate::intersection::{intersect_quad_bez_path, quad_encloses_subpath, Quad};
 use crate::layers::text_layer::FontCache;
 use crate::LayerId;
 
 use glam::{DAffine2, DMat2, DVec2};
 use kurbo::{Affine, BezPath, Shape as KurboShape};
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 use std::fmt::Write;
 
-#[derive(Clone, PartialEq, Deserialize, Serialize)]
+/// A reference to the external file an image layer was placed from, which is read again to refresh the image instead of saving its pixels in the document.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ImageLink {
+	/// The key the frontend stores the handle of the file under
+	pub handle: String,
+	/// The name of the file, shown while it can't be read
+	pub file_name: String,
+	/// When the file was last modified, in milliseconds since the Unix epoch, to detect changes to it
+	pub last_modified: f64,
+}
+
+#[derive(Clone, PartialEq, Deserialize)]
 pub struct ImageLayer {
 	pub mime: String,
-	#[serde(serialize_with = "base64_serde::as_base64", deserialize_with = "base64_serde::from_base64")]
+	/// The encoded image, which is empty for linked images until their file has been read
+	#[serde(default, deserialize_with = "base64_serde::from_base64")]
 	pub image_data: Vec<u8>,
+	#[serde(default)]
+	pub link: Option<ImageLink>,
 	// TODO: Have the browser dispose of this blob URL when this is dropped (like when the layer is deleted)
 	#[serde(skip)]
 	pub blob_url: Option<String>,
@@ -38,20 +54,27 @@ impl LayerData for ImageLayer {
 		});
 		let _ = svg.write_str(r#")">"#);
 
-		let svg_transform = transform
-			.to_cols_array()
-			.iter()
-			.enumerate()
-			.map(|(i, entry)| entry.to_string() + if i == 5 { "" } else { "," })
-			.collect::<String>();
-		let _ = write!(
-			svg,
-			r#"<image width="{}" height="{}" transform="matrix({})" href="{}"/>"#,
-			self.dimensions.x,
-			self.dimensions.y,
-			svg_transform,
-			self.blob_url.as_ref().unwrap_or(&String::new())
-		);
+		if render_data.view_mode == ViewMode::Outline {
+			// Only the bounds of the image are drawn in outline mode
+			let mut bounds = Subpath::new_rect(DVec2::ZERO, self.dimensions);
+			bounds.apply_affine(transform);
+			let _ = write!(svg, r#"<path d="{}"{}/>"#, bounds.to_svg(), outline_attributes());
+		} else {
+			let svg_transform = transform
+				.to_cols_array()
+				.iter()
+				.enumerate()
+				.map(|(i, entry)| entry.to_string() + if i == 5 { "" } else { "," })
+				.collect::<String>();
+			let _ = write!(
+				svg,
+				r#"<image width="{}" height="{}" transform="matrix({})" href="{}"/>"#,
+				self.dimensions.x,
+				self.dimensions.y,
+				svg_transform,
+				self.blob_url.as_ref().unwrap_or(&String::new())
+			);
+		}
 		let _ = svg.write_str("</g>");
 	}
 
@@ -72,6 +95,25 @@ impl LayerData for ImageLayer {
 			intersections.push(path.clone());
 		}
 	}
+
+	fn enclosed_by_quad(&self, quad: Quad, path: &mut Vec<LayerId>, enclosed: &mut Vec<Vec<LayerId>>, _font_cache: &FontCache) {
+		if quad_encloses_subpath(quad, &Subpath::new_rect(DVec2::ZERO, self.dimensions)) {
+			enclosed.push(path.clone());
+		}
+	}
+}
+
+impl Serialize for ImageLayer {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		// Linked images are read again from their file, so their pixels aren't saved to keep the document small
+		let image_data: &[u8] = if self.link.is_some() { &[] } else { &self.image_data };
+
+		let mut image = serializer.serialize_struct("ImageLayer", 3)?;
+		image.serialize_field("mime", &self.mime)?;
+		image.serialize_field("image_data", &base64::encode(image_data))?;
+		image.serialize_field("link", &self.link)?;
+		image.end()
+	}
 }
 
 impl ImageLayer {
@@ -79,6 +121,7 @@ impl ImageLayer {
 		Self {
 			mime,
 			image_data,
+			link: None,
 			blob_url: None,
 			dimensions: DVec2::ONE,
 		}
@@ -102,6 +145,7 @@ impl std::fmt::Debug for ImageLayer {
 		f.debug_struct("ImageLayer")
 			.field("mime", &self.mime)
 			.field("image_data", &"...")
+			.field("link", &self.link)
 			.field("blob_url", &self.blob_url)
 			.field("dimensions", &self.dimensions)
 			.finish()
@@ -1,7 +1,8 @@
 use super::base64_serde;
 use super::layer_info::LayerData;
-use super::style::{RenderData, ViewMode};
-use crate::intersection::{intersect_quad_bez_path, Quad};
+use super::style::{outline_attributes, RenderData, ViewMode};
+use super::vector::subpath::Subpath;
+use crate::intersection::{intersect_quad_bez_path, quad_encloses_subpath, Quad};
 use crate::layers::text_layer::FontCache;
 use crate::LayerId;
 
@@ -201,6 +202,14 @@ impl LayerData for ImaginateLayer {
 		});
 		let _ = svg.write_str(r#")">"#);
 
+		if render_data.view_mode == ViewMode::Outline {
+			// Only the bounds of the image are drawn in outline mode
+			let mut bounds = Subpath::new_rect(DVec2::ZERO, DVec2::ONE);
+			bounds.apply_affine(transform);
+			let _ = write!(svg, r#"<path d="{}"{}/></g>"#, bounds.to_svg(), outline_attributes());
+			return;
+		}
+
 		if let Some(blob_url) = &self.blob_url {
 			let _ = write!(
 				svg,
@@ -246,6 +255,12 @@ impl LayerData for ImaginateLayer {
 			intersections.push(path.clone());
 		}
 	}
+
+	fn enclosed_by_quad(&self, quad: Quad, path: &mut Vec<LayerId>, enclosed: &mut Vec<Vec<LayerId>>, _font_cache: &FontCache) {
+		if quad_encloses_subpath(quad, &Subpath::new_rect(DVec2::ZERO, DVec2::ONE)) {
+			enclosed.push(path.clone());
+		}
+	}
 }
 
 impl ImaginateLayer {
@@ -3,7 +3,7 @@ use super::folder_layer::FolderLayer;
 use super::image_layer::ImageLayer;
 use super::imaginate_layer::ImaginateLayer;
 use super::shape_layer::ShapeLayer;
-use super::style::{PathStyle, RenderData};
+use super::style::{PathStyle, RenderData, ViewMode};
 use super::text_layer::TextLayer;
 use super::vector::subpath::Subpath;
 use crate::intersection::Quad;
@@ -88,6 +88,44 @@ impl From<&LayerDataType> for LayerDataTypeDiscriminant {
 	}
 }
 
+/// A color label used to tag and organize layers.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum LayerLabel {
+	Red,
+	Orange,
+	Yellow,
+	Green,
+	Blue,
+	Violet,
+	Gray,
+}
+
+impl LayerLabel {
+	pub const ALL: [LayerLabel; 7] = [
+		LayerLabel::Red,
+		LayerLabel::Orange,
+		LayerLabel::Yellow,
+		LayerLabel::Green,
+		LayerLabel::Blue,
+		LayerLabel::Violet,
+		LayerLabel::Gray,
+	];
+}
+
+impl fmt::Display for LayerLabel {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			LayerLabel::Red => write!(f, "Red"),
+			LayerLabel::Orange => write!(f, "Orange"),
+			LayerLabel::Yellow => write!(f, "Yellow"),
+			LayerLabel::Green => write!(f, "Green"),
+			LayerLabel::Blue => write!(f, "Blue"),
+			LayerLabel::Violet => write!(f, "Violet"),
+			LayerLabel::Gray => write!(f, "Gray"),
+		}
+	}
+}
+
 /// Defines shared behavior for every layer type.
 pub trait LayerData {
 	/// Render the layer as an SVG tag to a given string.
@@ -139,6 +177,9 @@ pub trait LayerData {
 	/// ```
 	fn intersects_quad(&self, quad: Quad, path: &mut Vec<LayerId>, intersections: &mut Vec<Vec<LayerId>>, font_cache: &FontCache);
 
+	/// Determine the layers within this layer that lie entirely within a given quad, unlike [LayerData::intersects_quad] which also finds those only touching it.
+	fn enclosed_by_quad(&self, quad: Quad, path: &mut Vec<LayerId>, enclosed: &mut Vec<Vec<LayerId>>, font_cache: &FontCache);
+
 	// TODO: this doctest fails because 0 != 1e-32, maybe assert difference < epsilon?
 	/// Calculate the bounding box for the layer's contents after applying a given transform.
 	/// # Example
@@ -169,6 +210,10 @@ impl LayerData for LayerDataType {
 		self.inner().intersects_quad(quad, path, intersections, font_cache)
 	}
 
+	fn enclosed_by_quad(&self, quad: Quad, path: &mut Vec<LayerId>, enclosed: &mut Vec<Vec<LayerId>>, font_cache: &FontCache) {
+		self.inner().enclosed_by_quad(quad, path, enclosed, font_cache)
+	}
+
 	fn bounding_box(&self, transform: glam::DAffine2, font_cache: &FontCache) -> Option<[DVec2; 2]> {
 		self.inner().bounding_box(transform, font_cache)
 	}
@@ -191,6 +236,12 @@ fn return_true() -> bool {
 pub struct Layer {
 	/// Whether the layer is currently visible or hidden.
 	pub visible: bool,
+	/// Whether the layer is locked, protecting it from being selected or edited.
+	#[serde(default)]
+	pub locked: bool,
+	/// The color label used to organize the layer, if any.
+	#[serde(default)]
+	pub label: Option<LayerLabel>,
 	/// The user-given name of the layer.
 	pub name: Option<String>,
 	/// The type of layer, such as folder or shape.
@@ -223,6 +274,8 @@ impl Layer {
 	pub fn new(data: LayerDataType, transform: [f64; 6]) -> Self {
 		Self {
 			visible: true,
+			locked: false,
+			label: None,
 			name: None,
 			data,
 			transform: glam::DAffine2::from_cols_array(&transform),
@@ -303,13 +356,20 @@ impl Layer {
 			self.transform.to_cols_array().iter().enumerate().for_each(|(i, f)| {
 				let _ = self.cache.write_str(&(f.to_string() + if i == 5 { "" } else { "," }));
 			});
-			let _ = write!(
-				self.cache,
-				r#")" style="mix-blend-mode: {}; opacity: {}">{}</g>"#,
-				self.blend_mode.to_svg_style_name(),
-				self.opacity,
-				self.thumbnail_cache.as_str()
-			);
+			// Outline mode shows every path without blending, including the ones which are transparent
+			if render_data.view_mode == ViewMode::Outline {
+				let _ = write!(self.cache, r#")">{}</g>"#, self.thumbnail_cache.as_str());
+			} else if render_data.draft_effects() {
+				let _ = write!(self.cache, r#")" style="opacity: {}">{}</g>"#, self.opacity, self.thumbnail_cache.as_str());
+			} else {
+				let _ = write!(
+					self.cache,
+					r#")" style="mix-blend-mode: {}; opacity: {}">{}</g>"#,
+					self.blend_mode.to_svg_style_name(),
+					self.opacity,
+					self.thumbnail_cache.as_str()
+				);
+			}
 
 			self.cache_dirty = false;
 		}
@@ -320,7 +380,7 @@ impl Layer {
 	}
 
 	pub fn intersects_quad(&self, quad: Quad, path: &mut Vec<LayerId>, intersections: &mut Vec<Vec<LayerId>>, font_cache: &FontCache) {
-		if !self.visible {
+		if !self.visible || self.locked {
 			return;
 		}
 
@@ -328,6 +388,15 @@ impl Layer {
 		self.data.intersects_quad(transformed_quad, path, intersections, font_cache)
 	}
 
+	pub fn enclosed_by_quad(&self, quad: Quad, path: &mut Vec<LayerId>, enclosed: &mut Vec<Vec<LayerId>>, font_cache: &FontCache) {
+		if !self.visible || self.locked {
+			return;
+		}
+
+		let transformed_quad = self.transform.inverse() * quad;
+		self.data.enclosed_by_quad(transformed_quad, path, enclosed, font_cache)
+	}
+
 	/// Compute the bounding box of the layer after applying a transform to it.
 	///
 	/// # Example
@@ -401,6 +470,63 @@ impl Layer {
 		}
 	}
 
+	/// Moves the transform of the layer into the points of its path, leaving the layer with no transform of its own.
+	/// Folders pass their transform on to the layers inside of them, which are flattened in turn, while text and images keep their transforms since they have no path to move them into.
+	pub fn flatten_transform(&mut self) {
+		let transform = self.transform;
+		let scale = transform.matrix2.determinant().abs().sqrt();
+		match &mut self.data {
+			LayerDataType::Shape(shape) => {
+				shape.transform_path(transform);
+				if shape.stroke_in_layer_space() {
+					shape.scale_stroke_weight(scale);
+				}
+			}
+			LayerDataType::Folder(folder) => {
+				for layer in folder.layers_mut() {
+					layer.transform = transform * layer.transform;
+					if let LayerDataType::Shape(shape) = &mut layer.data {
+						if shape.stroke_in_parent_space() {
+							shape.scale_stroke_weight(scale);
+						}
+					}
+					layer.flatten_transform();
+				}
+			}
+			_ => return,
+		}
+		self.transform = DAffine2::IDENTITY;
+	}
+
+	/// Moves the position and size of the path into the transform of the layer, leaving the path within the unit square like that of the shapes drawn with the tools.
+	/// This is the reverse of [Layer::flatten_transform], except that any rotation or skew stays in the path. Folders extract the transforms of the layers inside of them.
+	pub fn extract_transform(&mut self) {
+		match &mut self.data {
+			LayerDataType::Shape(shape) => {
+				let [min, max] = match shape.shape.bounding_box() {
+					Some(bounds) => bounds,
+					None => return,
+				};
+				// Paths without any width or height, like horizontal lines, keep their size along that axis
+				let size = max - min;
+				let size = DVec2::new(if size.x > f64::EPSILON { size.x } else { 1. }, if size.y > f64::EPSILON { size.y } else { 1. });
+				let extracted = DAffine2::from_scale_angle_translation(size, 0., min);
+
+				shape.transform_path(extracted.inverse());
+				if shape.stroke_in_layer_space() {
+					shape.scale_stroke_weight(1. / extracted.matrix2.determinant().abs().sqrt());
+				}
+				self.transform = self.transform * extracted;
+			}
+			LayerDataType::Folder(folder) => {
+				for layer in folder.layers_mut() {
+					layer.extract_transform();
+				}
+			}
+			_ => {}
+		}
+	}
+
 	pub fn as_subpath_mut(&mut self) -> Option<&mut Subpath> {
 		match &mut self.data {
 			LayerDataType::Shape(s) => Some(&mut s.shape),
@@ -492,6 +618,8 @@ impl Clone for Layer {
 	fn clone(&self) -> Self {
 		Self {
 			visible: self.visible,
+			locked: self.locked,
+			label: self.label,
 			name: self.name.clone(),
 			data: self.data.clone(),
 			transform: self.transform,
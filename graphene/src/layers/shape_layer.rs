@@ -1,7 +1,7 @@
 use super::layer_info::LayerData;
-use super::style::{self, PathStyle, RenderData, ViewMode};
+use super::style::{self, Fill, PathStyle, RenderData, Stroke, ViewMode};
 use super::vector::subpath::Subpath;
-use crate::intersection::{intersect_quad_bez_path, Quad};
+use crate::intersection::{intersect_quad_bez_path, quad_encloses_subpath, Quad};
 use crate::layers::text_layer::FontCache;
 use crate::LayerId;
 
@@ -27,7 +27,7 @@ pub struct ShapeLayer {
 
 impl LayerData for ShapeLayer {
 	fn render(&mut self, svg: &mut String, svg_defs: &mut String, transforms: &mut Vec<DAffine2>, render_data: RenderData) {
-		let mut subpath = self.shape.clone();
+		let mut subpath = self.shape.with_rounded_corners().into_owned();
 
 		let layer_bounds = subpath.bounding_box().unwrap_or_default();
 
@@ -46,17 +46,36 @@ impl LayerData for ShapeLayer {
 			let _ = svg.write_str(&(entry.to_string() + if i == 5 { "" } else { "," }));
 		});
 		let _ = svg.write_str(r#")">"#);
+
+		// A stroke of varying width can't be drawn as an SVG stroke, so its outline is filled in with its color on top of the fill instead
+		let variable_stroke = self
+			.style
+			.stroke()
+			.filter(|stroke| stroke.has_width_profile() && stroke.is_visible() && render_data.view_mode != ViewMode::Outline);
+		let mut style = self.style.clone();
+		if variable_stroke.is_some() {
+			style.clear_stroke();
+		}
 		let _ = write!(
 			svg,
 			r#"<path d="{}" {} />"#,
 			subpath.to_svg(),
-			self.style.render(render_data.view_mode, svg_defs, transform, layer_bounds, transformed_bounds)
+			style.render(render_data, svg_defs, transform, layer_bounds, transformed_bounds)
 		);
+		if let Some(stroke) = variable_stroke {
+			let outline_style = PathStyle::new(None, stroke.color().map_or(Fill::None, Fill::Solid));
+			let _ = write!(
+				svg,
+				r#"<path d="{}" {} />"#,
+				subpath.stroke_outline(&stroke).to_svg(),
+				outline_style.render(render_data, svg_defs, transform, layer_bounds, transformed_bounds)
+			);
+		}
 		let _ = svg.write_str("</g>");
 	}
 
 	fn bounding_box(&self, transform: glam::DAffine2, _font_cache: &FontCache) -> Option<[DVec2; 2]> {
-		let mut subpath = self.shape.clone();
+		let mut subpath = self.shape.with_rounded_corners().into_owned();
 		if transform.matrix2 == DMat2::ZERO {
 			return None;
 		}
@@ -67,10 +86,30 @@ impl LayerData for ShapeLayer {
 
 	fn intersects_quad(&self, quad: Quad, path: &mut Vec<LayerId>, intersections: &mut Vec<Vec<LayerId>>, _font_cache: &FontCache) {
 		let filled = self.style.fill().is_some() || self.shape.manipulator_groups().last().filter(|manipulator_group| manipulator_group.is_close()).is_some();
-		if intersect_quad_bez_path(quad, &(&self.shape).into(), filled) {
+		let subpath = self.shape.with_rounded_corners();
+
+		let intersects = match self.style.stroke().filter(Stroke::is_visible) {
+			// A stroke is hit where it's drawn, leaving out the gaps between its dashes but taking in its width, caps, and joins
+			Some(stroke) => {
+				let stroke_in_reach = subpath.bounding_box().map_or(false, |[min, max]| {
+					let [quad_min, quad_max] = quad.bounding_box();
+					let reach = DVec2::splat(stroke.max_reach());
+					(min - reach).cmple(quad_max).all() && (max + reach).cmpge(quad_min).all()
+				});
+				(filled && intersect_quad_bez_path(quad, &subpath.as_ref().into(), true)) || (stroke_in_reach && intersect_quad_bez_path(quad, &(&subpath.stroke_outline(&stroke)).into(), true))
+			}
+			None => intersect_quad_bez_path(quad, &subpath.as_ref().into(), filled),
+		};
+		if intersects {
 			intersections.push(path.clone());
 		}
 	}
+
+	fn enclosed_by_quad(&self, quad: Quad, path: &mut Vec<LayerId>, enclosed: &mut Vec<Vec<LayerId>>, _font_cache: &FontCache) {
+		if quad_encloses_subpath(quad, &self.shape.with_rounded_corners()) {
+			enclosed.push(path.clone());
+		}
+	}
 }
 
 impl ShapeLayer {
@@ -152,6 +191,48 @@ impl ShapeLayer {
 		}
 	}
 
+	/// Applies a transform to the points of the path, keeping the gradient fill and the rounded corners the same relative to the shape.
+	pub fn transform_path(&mut self, transform: DAffine2) {
+		let old_bounds = self.shape.with_rounded_corners().bounding_box();
+
+		self.shape.apply_affine(transform);
+		let scale = transform.matrix2.determinant().abs().sqrt();
+		for manipulator_group in self.shape.manipulator_groups_mut().iter_mut() {
+			manipulator_group.corner_radius *= scale;
+		}
+
+		// Gradients are positioned relative to the bounds of the path, so they are moved to where the transform takes them within the new bounds
+		let new_bounds = self.shape.with_rounded_corners().bounding_box();
+		if let (Fill::Gradient(gradient), Some(old_bounds), Some(new_bounds)) = (self.style.fill(), old_bounds, new_bounds) {
+			let bounds_transform = |[min, max]: [DVec2; 2]| DAffine2::from_scale_angle_translation(max - min, 0., min);
+			let remap = bounds_transform(new_bounds).inverse() * transform * bounds_transform(old_bounds);
+			if remap.is_finite() {
+				let mut gradient = gradient.clone();
+				gradient.start = remap.transform_point2(gradient.start);
+				gradient.end = remap.transform_point2(gradient.end);
+				self.style.set_fill(Fill::Gradient(gradient));
+			}
+		}
+	}
+
+	/// Scales the weight of the stroke, to keep it looking the same when the space it's drawn in is scaled by the inverse.
+	pub fn scale_stroke_weight(&mut self, scale: f64) {
+		if let Some(stroke) = self.style.stroke() {
+			let weight = stroke.weight() * scale;
+			self.style.set_stroke(stroke.with_weight(weight));
+		}
+	}
+
+	/// Whether the stroke is drawn in the space of the layer itself, so it's scaled by the transform of the layer.
+	pub fn stroke_in_layer_space(&self) -> bool {
+		self.render_index == 0
+	}
+
+	/// Whether the stroke is drawn in the space of the parent folder, so it's scaled by the transform of the folder but not by that of the layer.
+	pub fn stroke_in_parent_space(&self) -> bool {
+		self.render_index == 1
+	}
+
 	/// Create a polygonal line that visits each provided point.
 	pub fn poly_line(points: Vec<impl Into<glam::DVec2>>, style: PathStyle) -> Self {
 		Self {
@@ -1,7 +1,7 @@
 //! Contains stylistic options for SVG elements.
 
 use super::text_layer::FontCache;
-use crate::color::Color;
+use crate::color::{Color, ColorSpace};
 use crate::consts::{LAYER_OUTLINE_STROKE_COLOR, LAYER_OUTLINE_STROKE_WEIGHT};
 
 use glam::{DAffine2, DVec2};
@@ -43,6 +43,11 @@ pub struct RenderData<'a> {
 	pub view_mode: ViewMode,
 	pub font_cache: &'a FontCache,
 	pub culling_bounds: Option<[DVec2; 2]>,
+	/// The color space that colors are written in as CSS `color()` values to render them in wide gamut, or `None` to clamp them to sRGB hex colors.
+	pub wide_gamut_color_space: Option<ColorSpace>,
+	pub quality: RenderQuality,
+	/// Whether the render is shown while the user is dragging in the viewport, when [RenderQuality::interactive_effects] applies.
+	pub interactive: bool,
 }
 
 impl<'a> RenderData<'a> {
@@ -51,14 +56,78 @@ impl<'a> RenderData<'a> {
 			view_mode,
 			font_cache,
 			culling_bounds,
+			wide_gamut_color_space: None,
+			quality: RenderQuality::default(),
+			interactive: false,
 		}
 	}
+
+	/// Whether effects like blend modes are left out to keep the render fast.
+	pub fn draft_effects(&self) -> bool {
+		self.interactive && self.quality.interactive_effects == EffectQuality::Draft
+	}
+}
+
+/// The quality settings of a document, respected by both the viewport and exports, which trade fidelity for rendering speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RenderQuality {
+	/// Whether the edges of shapes and text are smoothed, instead of aligned to whole pixels
+	pub anti_aliasing: bool,
+	pub image_filter: ImageFilterQuality,
+	/// The quality of effects while the user is dragging in the viewport, which is always full quality once the interaction ends and in exports
+	pub interactive_effects: EffectQuality,
+}
+
+impl Default for RenderQuality {
+	fn default() -> Self {
+		Self {
+			anti_aliasing: true,
+			image_filter: ImageFilterQuality::default(),
+			interactive_effects: EffectQuality::default(),
+		}
+	}
+}
+
+impl RenderQuality {
+	/// The attributes of the group containing the artwork, which are inherited by every element in it.
+	pub fn render(&self) -> String {
+		let mut attributes = String::new();
+		if !self.anti_aliasing {
+			attributes.push_str(r#" shape-rendering="crispEdges" text-rendering="optimizeSpeed""#);
+		}
+		if self.image_filter == ImageFilterQuality::Nearest {
+			attributes.push_str(r#" image-rendering="pixelated""#);
+		}
+		attributes
+	}
+}
+
+/// How raster images are resampled when drawn at a different size than their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ImageFilterQuality {
+	/// Uses the nearest pixel, which is fastest and keeps pixel art sharp
+	Nearest,
+	/// Interpolates between pixels
+	#[default]
+	Smooth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum EffectQuality {
+	#[default]
+	Full,
+	/// Leaves out blend modes, which are slow to composite
+	Draft,
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
 pub enum GradientType {
 	Linear,
 	Radial,
+	/// Sweeps around the start point, beginning in the direction of the end point (also known as a conic gradient)
+	Angular,
+	/// Spreads out from the start point in squares, with a corner of the square reaching the end color at the end point
+	Diamond,
 }
 
 impl Default for GradientType {
@@ -67,6 +136,71 @@ impl Default for GradientType {
 	}
 }
 
+impl GradientType {
+	pub const ALL: [GradientType; 4] = [GradientType::Linear, GradientType::Radial, GradientType::Angular, GradientType::Diamond];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			GradientType::Linear => "Linear",
+			GradientType::Radial => "Radial",
+			GradientType::Angular => "Angular",
+			GradientType::Diamond => "Diamond",
+		}
+	}
+
+	/// Whether the gradient is drawn by the linear and radial gradient primitives that formats like SVG and PDF share,
+	/// which the other types are approximated with [fallback polygons](Gradient::fallback_polygons) for.
+	pub fn has_common_primitive(&self) -> bool {
+		matches!(self, GradientType::Linear | GradientType::Radial)
+	}
+}
+
+/// How a gradient continues past its end, and before its start for linear gradients.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Hash, Serialize, Deserialize)]
+pub enum GradientSpread {
+	/// Extends the colors at the ends
+	#[default]
+	Pad,
+	/// Starts over from the first color
+	Repeat,
+	/// Goes back and forth between the colors at the ends
+	Reflect,
+}
+
+impl GradientSpread {
+	pub const ALL: [GradientSpread; 3] = [GradientSpread::Pad, GradientSpread::Repeat, GradientSpread::Reflect];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			GradientSpread::Pad => "Pad",
+			GradientSpread::Repeat => "Repeat",
+			GradientSpread::Reflect => "Reflect",
+		}
+	}
+
+	/// The value of the `spreadMethod` attribute of SVG gradients.
+	pub fn svg_name(&self) -> &'static str {
+		match self {
+			GradientSpread::Pad => "pad",
+			GradientSpread::Repeat => "repeat",
+			GradientSpread::Reflect => "reflect",
+		}
+	}
+
+	pub fn from_svg_name(name: &str) -> Option<Self> {
+		GradientSpread::ALL.into_iter().find(|spread| spread.svg_name() == name)
+	}
+
+	/// Maps a position along a gradient, which may lie past its ends, to the position from 0 to 1 whose color it takes.
+	pub fn apply(&self, position: f64) -> f64 {
+		match self {
+			GradientSpread::Pad => position.clamp(0., 1.),
+			GradientSpread::Repeat => position.rem_euclid(1.),
+			GradientSpread::Reflect => 1. - (position.rem_euclid(2.) - 1.).abs(),
+		}
+	}
+}
+
 /// A gradient fill.
 ///
 /// Contains the start and end points, along with the colors at varying points along the length.
@@ -79,6 +213,8 @@ pub struct Gradient {
 	pub positions: Vec<(f64, Option<Color>)>,
 	uuid: u64,
 	pub gradient_type: GradientType,
+	#[serde(default)]
+	pub spread: GradientSpread,
 }
 
 impl Gradient {
@@ -91,11 +227,136 @@ impl Gradient {
 			transform,
 			uuid,
 			gradient_type,
+			spread: GradientSpread::default(),
 		}
 	}
 
+	/// Evaluate the color at a position along the gradient, from 0 at the start to 1 at the end, interpolating between the neighboring stops.
+	pub fn evaluate(&self, position: f64) -> Color {
+		let mut stops = self.positions.iter().filter_map(|&(position, color)| color.map(|color| (position, color))).collect::<Vec<_>>();
+		stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+		match stops.iter().position(|&(stop, _)| stop >= position) {
+			Some(0) => stops[0].1,
+			Some(index) => {
+				let ((start_position, start), (end_position, end)) = (stops[index - 1], stops[index]);
+				let t = ((position - start_position) / (end_position - start_position)) as f32;
+				let lerp = |a: f32, b: f32| a + (b - a) * t;
+				Color::from_rgbaf32(lerp(start.r(), end.r()), lerp(start.g(), end.g()), lerp(start.b(), end.b()), lerp(start.a(), end.a())).unwrap_or(start)
+			}
+			None => stops.last().map_or(Color::BLACK, |&(_, color)| color),
+		}
+	}
+
+	/// The position along the gradient whose color is shown at `point`, which is given in the same space as the start and end points.
+	/// Positions past the ends of the gradient are left for its [spread](GradientSpread::apply) to map back to the gradient.
+	pub fn position_at(&self, point: DVec2) -> f64 {
+		let axis = self.end - self.start;
+		let offset = point - self.start;
+		let length_squared = axis.length_squared();
+		if length_squared == 0. {
+			return 0.;
+		}
+
+		match self.gradient_type {
+			GradientType::Linear => offset.dot(axis) / length_squared,
+			GradientType::Radial => offset.length() / length_squared.sqrt(),
+			GradientType::Angular if offset == DVec2::ZERO => 0.,
+			GradientType::Angular => axis.angle_between(offset).rem_euclid(std::f64::consts::TAU) / std::f64::consts::TAU,
+			GradientType::Diamond => (offset.dot(axis).abs() + offset.perp_dot(axis).abs()) / length_squared,
+		}
+	}
+
+	/// The color shown at `point`, which is given in the same space as the start and end points.
+	pub fn color_at(&self, point: DVec2) -> Color {
+		self.evaluate(self.spread.apply(self.position_at(point)))
+	}
+
+	/// Approximates the gradient with solid-colored polygons covering the box `area`, given in the same space as the start and end points,
+	/// for formats without a primitive for the [gradient type](GradientType::has_common_primitive) or [spread](GradientSpread).
+	/// The polygons don't overlap, so colors with transparency aren't blended with each other.
+	pub fn fallback_polygons(&self, [min, max]: [DVec2; 2]) -> Vec<(Vec<DVec2>, Color)> {
+		const STEPS_PER_GRADIENT: usize = 128;
+		const MAX_STEPS: usize = 4096;
+		const CIRCLE_SEGMENTS: usize = 64;
+
+		let axis = self.end - self.start;
+		let corners = [min, DVec2::new(max.x, min.y), max, DVec2::new(min.x, max.y)];
+		if axis == DVec2::ZERO {
+			return vec![(corners.to_vec(), self.evaluate(0.))];
+		}
+		let color = |position: f64| self.evaluate(self.spread.apply(position));
+		// How far past the start point the area reaches, in multiples of the length of the gradient
+		let reach = corners.iter().map(|corner| corner.distance(self.start)).fold(0., f64::max) * 2. / axis.length();
+
+		if self.gradient_type == GradientType::Angular {
+			let step = std::f64::consts::TAU / STEPS_PER_GRADIENT as f64;
+			let direction = |angle: f64| self.start + (axis * angle.cos() + axis.perp() * angle.sin()) * reach;
+
+			return (0..STEPS_PER_GRADIENT)
+				.map(|index| {
+					let angle = index as f64 * step;
+					let wedge = vec![self.start, direction(angle), direction(angle + step)];
+					(wedge, color((index as f64 + 0.5) / STEPS_PER_GRADIENT as f64))
+				})
+				.collect();
+		}
+
+		// The positions bounding the bands of a single color, from the lowest to the highest position found in the area with a little margin to cover its edges,
+		// where the rings of the gradient types other than linear start from the start point
+		let positions = corners.map(|corner| self.position_at(corner));
+		let (lowest, highest) = (positions.iter().copied().fold(f64::INFINITY, f64::min), positions.iter().copied().fold(0., f64::max));
+		let margin = (highest - lowest).abs() * 0.001;
+		let low = if self.gradient_type == GradientType::Linear { lowest - margin } else { 0. };
+		let high = highest + margin;
+
+		let mut levels = match self.spread {
+			GradientSpread::Pad => {
+				let inside = (0..=STEPS_PER_GRADIENT)
+					.map(|index| index as f64 / STEPS_PER_GRADIENT as f64)
+					.filter(|&level| low < level && level < high);
+				std::iter::once(low).chain(inside).chain(std::iter::once(high)).collect::<Vec<_>>()
+			}
+			GradientSpread::Repeat | GradientSpread::Reflect => {
+				let steps = (((high - low) * STEPS_PER_GRADIENT as f64).ceil() as usize).clamp(1, MAX_STEPS);
+				(0..=steps).map(|index| low + (high - low) * index as f64 / steps as f64).collect()
+			}
+		};
+		levels.dedup();
+		let bands = levels.windows(2).map(|band| (band[0], band[1], color((band[0] + band[1]) / 2.)));
+
+		// Linear gradients are drawn in strips across the axis, and the others in rings split into a trapezoid along each side of their outline
+		let outline = match self.gradient_type {
+			GradientType::Linear => {
+				let across = axis.perp() * reach;
+				let strip = |(inner, outer, color): (f64, f64, Color)| {
+					let points = [axis * inner + across, axis * outer + across, axis * outer - across, axis * inner - across];
+					(points.map(|offset| self.start + offset).to_vec(), color)
+				};
+				return bands.map(strip).collect();
+			}
+			GradientType::Diamond => vec![axis, axis.perp(), -axis, -axis.perp()],
+			_ => {
+				// The polygon surrounds the circle so the rings cover it
+				let step = std::f64::consts::TAU / CIRCLE_SEGMENTS as f64;
+				let radius = 1. / (step / 2.).cos();
+				(0..CIRCLE_SEGMENTS).map(|index| DAffine2::from_angle(index as f64 * step).transform_vector2(axis) * radius).collect()
+			}
+		};
+		bands
+			.flat_map(|(inner, outer, color)| {
+				let outline = &outline;
+				(0..outline.len()).map(move |side| {
+					let (first, second) = (outline[side], outline[(side + 1) % outline.len()]);
+					let points = [first * outer, second * outer, second * inner, first * inner];
+					(points.map(|offset| self.start + offset).to_vec(), color)
+				})
+			})
+			.collect()
+	}
+
 	/// Adds the gradient def with the uuid specified
-	fn render_defs(&self, svg_defs: &mut String, multiplied_transform: DAffine2, bounds: [DVec2; 2], transformed_bounds: [DVec2; 2]) {
+	fn render_defs(&self, svg_defs: &mut String, multiplied_transform: DAffine2, bounds: [DVec2; 2], transformed_bounds: [DVec2; 2], color_space: Option<ColorSpace>) {
 		let bound_transform = DAffine2::from_scale_angle_translation(bounds[1] - bounds[0], 0., bounds[0]);
 		let transformed_bound_transform = DAffine2::from_scale_angle_translation(transformed_bounds[1] - transformed_bounds[0], 0., transformed_bounds[0]);
 		let updated_transform = multiplied_transform * bound_transform;
@@ -104,7 +365,7 @@ impl Gradient {
 			.positions
 			.iter()
 			.filter_map(|(pos, color)| color.map(|color| (pos, color)))
-			.map(|(position, color)| format!(r##"<stop offset="{}" stop-color="#{}" />"##, position, color.rgba_hex()))
+			.map(|(position, color)| format!(r#"<stop offset="{}" stop-color="{}" />"#, position, color.rgba_css(color_space)))
 			.collect::<String>();
 
 		let mod_gradient = transformed_bound_transform.inverse();
@@ -120,20 +381,48 @@ impl Gradient {
 			.map(|(i, entry)| entry.to_string() + if i == 5 { "" } else { "," })
 			.collect::<String>();
 
+		let spread = match self.spread {
+			GradientSpread::Pad => String::new(),
+			spread => format!(r#" spreadMethod="{}""#, spread.svg_name()),
+		};
+
 		match self.gradient_type {
 			GradientType::Linear => {
 				let _ = write!(
 					svg_defs,
-					r#"<linearGradient id="{}" x1="{}" x2="{}" y1="{}" y2="{}" gradientTransform="matrix({})">{}</linearGradient>"#,
-					self.uuid, start.x, end.x, start.y, end.y, transform, positions
+					r#"<linearGradient id="{}" x1="{}" x2="{}" y1="{}" y2="{}" gradientTransform="matrix({})"{}>{}</linearGradient>"#,
+					self.uuid, start.x, end.x, start.y, end.y, transform, spread, positions
 				);
 			}
 			GradientType::Radial => {
 				let radius = (f64::powi(start.x - end.x, 2) + f64::powi(start.y - end.y, 2)).sqrt();
 				let _ = write!(
 					svg_defs,
-					r#"<radialGradient id="{}" cx="{}" cy="{}" r="{}" gradientTransform="matrix({})">{}</radialGradient>"#,
-					self.uuid, start.x, start.y, radius, transform, positions
+					r#"<radialGradient id="{}" cx="{}" cy="{}" r="{}" gradientTransform="matrix({})"{}>{}</radialGradient>"#,
+					self.uuid, start.x, start.y, radius, transform, spread, positions
+				);
+			}
+			GradientType::Angular | GradientType::Diamond => {
+				// SVG has no element for these gradients, so they're approximated by polygons in a pattern covering the shape
+				let gradient = Gradient { start, end, ..self.clone() };
+				let polygons = gradient
+					.fallback_polygons(transformed_bounds)
+					.into_iter()
+					.map(|(polygon, color)| {
+						let points = polygon.iter().map(|point| format!("{},{}", point.x, point.y)).collect::<Vec<_>>().join(" ");
+						format!(r#"<polygon points="{}" fill="{}"{} />"#, points, color.rgb_css(color_space), format_opacity("fill", color.a()))
+					})
+					.collect::<String>();
+				let [min, max] = transformed_bounds;
+				let _ = write!(
+					svg_defs,
+					r#"<pattern id="{}" patternUnits="userSpaceOnUse" x="{}" y="{}" width="{}" height="{}" shape-rendering="crispEdges">{}</pattern>"#,
+					self.uuid,
+					min.x,
+					min.y,
+					max.x - min.x,
+					max.y - min.y,
+					polygons
 				);
 			}
 		}
@@ -142,7 +431,7 @@ impl Gradient {
 
 /// Describes the fill of a layer.
 ///
-/// Can be None, a solid [Color], a linear [Gradient], a radial [Gradient] or potentially some sort of image or pattern in the future
+/// Can be None, a solid [Color], a linear, radial, angular, or diamond [Gradient], or potentially some sort of image or pattern in the future
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Fill {
@@ -174,12 +463,12 @@ impl Fill {
 	}
 
 	/// Renders the fill, adding necessary defs.
-	pub fn render(&self, svg_defs: &mut String, multiplied_transform: DAffine2, bounds: [DVec2; 2], transformed_bounds: [DVec2; 2]) -> String {
+	pub fn render(&self, svg_defs: &mut String, multiplied_transform: DAffine2, bounds: [DVec2; 2], transformed_bounds: [DVec2; 2], color_space: Option<ColorSpace>) -> String {
 		match self {
 			Self::None => r#" fill="none""#.to_string(),
-			Self::Solid(color) => format!(r##" fill="#{}"{}"##, color.rgb_hex(), format_opacity("fill", color.a())),
+			Self::Solid(color) => format!(r#" fill="{}"{}"#, color.rgb_css(color_space), format_opacity("fill", color.a())),
 			Self::Gradient(gradient) => {
-				gradient.render_defs(svg_defs, multiplied_transform, bounds, transformed_bounds);
+				gradient.render_defs(svg_defs, multiplied_transform, bounds, transformed_bounds, color_space);
 				format!(r##" fill="url('#{}')""##, gradient.uuid)
 			}
 		}
@@ -200,6 +489,16 @@ pub enum LineCap {
 	Square,
 }
 
+impl From<LineCap> for bezier_rs::Cap {
+	fn from(line_cap: LineCap) -> Self {
+		match line_cap {
+			LineCap::Butt => bezier_rs::Cap::Butt,
+			LineCap::Round => bezier_rs::Cap::Round,
+			LineCap::Square => bezier_rs::Cap::Square,
+		}
+	}
+}
+
 impl Display for LineCap {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
@@ -218,6 +517,16 @@ pub enum LineJoin {
 	Round,
 }
 
+impl From<LineJoin> for bezier_rs::Join {
+	fn from(line_join: LineJoin) -> Self {
+		match line_join {
+			LineJoin::Miter => bezier_rs::Join::Miter,
+			LineJoin::Bevel => bezier_rs::Join::Bevel,
+			LineJoin::Round => bezier_rs::Join::Round,
+		}
+	}
+}
+
 impl Display for LineJoin {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
@@ -228,6 +537,21 @@ impl Display for LineJoin {
 	}
 }
 
+/// A point of the [width profile](Stroke::width_profile) of a stroke, which scales the weight of the stroke at a position along its path.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WidthPoint {
+	/// The fraction of the length of the path travelled to reach the point, from 0 at its start to 1 at its end
+	pub position: f64,
+	/// The factor the weight of the stroke is scaled by at the point
+	pub scale: f64,
+}
+
+impl WidthPoint {
+	pub fn new(position: f64, scale: f64) -> Self {
+		Self { position, scale }
+	}
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Stroke {
@@ -240,6 +564,9 @@ pub struct Stroke {
 	line_cap: LineCap,
 	line_join: LineJoin,
 	line_join_miter_limit: f64,
+	/// The points the width of the stroke varies between along each contour of its path, sorted by position
+	#[serde(default)]
+	width_profile: Vec<WidthPoint>,
 }
 
 impl Stroke {
@@ -281,12 +608,62 @@ impl Stroke {
 		self.line_join_miter_limit as f32
 	}
 
+	pub fn line_cap(&self) -> LineCap {
+		self.line_cap
+	}
+
+	pub fn line_join(&self) -> LineJoin {
+		self.line_join
+	}
+
+	/// Whether the stroke draws anything, which takes a color and some width.
+	pub fn is_visible(&self) -> bool {
+		self.color.is_some() && self.weight > 0.
+	}
+
+	/// How far the outline of the stroke can reach from the path it's drawn along, at the tip of a miter or the corner of a square cap.
+	pub fn max_reach(&self) -> f64 {
+		let miter_reach = if self.line_join == LineJoin::Miter { self.line_join_miter_limit } else { 1. };
+		let max_scale = self.width_profile.iter().map(|point| point.scale).fold(1_f64, f64::max);
+		self.weight * max_scale / 2. * miter_reach.max(std::f64::consts::SQRT_2)
+	}
+
+	/// The points the width of the stroke varies between along each contour of its path, which has a constant width if there are none.
+	/// Strokes of varying width can't be drawn as SVG strokes, so they are drawn as their [outline](crate::layers::vector::subpath::Subpath::stroke_outline) instead.
+	pub fn width_profile(&self) -> &[WidthPoint] {
+		&self.width_profile
+	}
+
+	pub fn has_width_profile(&self) -> bool {
+		!self.width_profile.is_empty()
+	}
+
+	/// The width of the stroke at the given fraction of the length of a contour, interpolating linearly between the points of its width profile.
+	pub fn width_at(&self, position: f64) -> f64 {
+		let profile = &self.width_profile;
+		let scale = match profile.iter().position(|point| point.position > position) {
+			None => profile.last().map_or(1., |point| point.scale),
+			Some(0) => profile[0].scale,
+			Some(index) => {
+				let (previous, next) = (profile[index - 1], profile[index]);
+				let factor = (position - previous.position) / (next.position - previous.position);
+				previous.scale + (next.scale - previous.scale) * factor
+			}
+		};
+		self.weight * scale
+	}
+
+	/// The lengths of the alternating dashes and gaps, which draw a solid line if they are all zero.
+	pub fn dash_pattern(&self) -> &[f32] {
+		&self.dash_lengths
+	}
+
 	/// Provide the SVG attributes for the stroke.
-	pub fn render(&self) -> String {
+	pub fn render(&self, color_space: Option<ColorSpace>) -> String {
 		if let Some(color) = self.color {
 			format!(
-				r##" stroke="#{}"{} stroke-width="{}" stroke-dasharray="{}" stroke-dashoffset="{}" stroke-linecap="{}" stroke-linejoin="{}" stroke-miterlimit="{}" "##,
-				color.rgb_hex(),
+				r#" stroke="{}"{} stroke-width="{}" stroke-dasharray="{}" stroke-dashoffset="{}" stroke-linecap="{}" stroke-linejoin="{}" stroke-miterlimit="{}" "#,
+				color.rgb_css(color_space),
 				format_opacity("stroke", color.a()),
 				self.weight,
 				self.dash_lengths(),
@@ -317,6 +694,7 @@ impl Stroke {
 		self
 	}
 
+	/// Sets the dash and gap lengths from a list separated by commas or spaces, which can't be negative.
 	pub fn with_dash_lengths(mut self, dash_lengths: &str) -> Option<Self> {
 		dash_lengths
 			.split(&[',', ' '])
@@ -324,6 +702,7 @@ impl Stroke {
 			.map(str::parse::<f32>)
 			.collect::<Result<Vec<_>, _>>()
 			.ok()
+			.filter(|lengths| lengths.iter().all(|length| length.is_finite() && *length >= 0.))
 			.map(|lengths| {
 				self.dash_lengths = lengths;
 				self
@@ -349,6 +728,12 @@ impl Stroke {
 		self.line_join_miter_limit = limit;
 		self
 	}
+
+	pub fn with_width_profile(mut self, mut width_profile: Vec<WidthPoint>) -> Self {
+		width_profile.sort_by(|a, b| a.position.total_cmp(&b.position));
+		self.width_profile = width_profile;
+		self
+	}
 }
 
 // Having an alpha of 1 to start with leads to a better experience with the properties panel
@@ -362,20 +747,50 @@ impl Default for Stroke {
 			line_cap: LineCap::Butt,
 			line_join: LineJoin::Miter,
 			line_join_miter_limit: 4.,
+			width_profile: Vec::new(),
 		}
 	}
 }
 
+/// A number that identifies a [ColorStyle] within a document.
+pub type ColorStyleId = u64;
+
+/// A named color shared across the document.
+///
+/// Layers link their fill or stroke color to a style by its [ColorStyleId], so editing the style updates every layer using it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorStyle {
+	pub name: String,
+	pub color: Color,
+}
+
+impl ColorStyle {
+	pub fn new(name: String, color: Color) -> Self {
+		Self { name, color }
+	}
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct PathStyle {
 	stroke: Option<Stroke>,
 	fill: Fill,
+	/// The [ColorStyle] the solid fill color is linked to, if any.
+	#[serde(default)]
+	fill_color_style: Option<ColorStyleId>,
+	/// The [ColorStyle] the stroke color is linked to, if any.
+	#[serde(default)]
+	stroke_color_style: Option<ColorStyleId>,
 }
 
 impl PathStyle {
 	pub fn new(stroke: Option<Stroke>, fill: Fill) -> Self {
-		Self { stroke, fill }
+		Self {
+			stroke,
+			fill,
+			fill_color_style: None,
+			stroke_color_style: None,
+		}
 	}
 
 	/// Get the current path's [Fill].
@@ -424,6 +839,10 @@ impl PathStyle {
 	/// assert_eq!(*style.fill(), fill);
 	/// ```
 	pub fn set_fill(&mut self, fill: Fill) {
+		// Manually picking a different fill breaks the link to its color style
+		if self.fill != fill {
+			self.fill_color_style = None;
+		}
 		self.fill = fill;
 	}
 
@@ -443,6 +862,10 @@ impl PathStyle {
 	/// assert_eq!(style.stroke(), Some(stroke));
 	/// ```
 	pub fn set_stroke(&mut self, stroke: Stroke) {
+		// Manually picking a different stroke color breaks the link to its color style
+		if self.stroke.as_ref().map(|old_stroke| old_stroke.color) != Some(stroke.color) {
+			self.stroke_color_style = None;
+		}
 		self.stroke = Some(stroke);
 	}
 
@@ -462,6 +885,7 @@ impl PathStyle {
 	/// ```
 	pub fn clear_fill(&mut self) {
 		self.fill = Fill::None;
+		self.fill_color_style = None;
 	}
 
 	/// Set the path's stroke to None.
@@ -480,19 +904,106 @@ impl PathStyle {
 	/// ```
 	pub fn clear_stroke(&mut self) {
 		self.stroke = None;
+		self.stroke_color_style = None;
 	}
 
-	pub fn render(&self, view_mode: ViewMode, svg_defs: &mut String, multiplied_transform: DAffine2, bounds: [DVec2; 2], transformed_bounds: [DVec2; 2]) -> String {
-		let fill_attribute = match (view_mode, &self.fill) {
-			(ViewMode::Outline, _) => Fill::None.render(svg_defs, multiplied_transform, bounds, transformed_bounds),
-			(_, fill) => fill.render(svg_defs, multiplied_transform, bounds, transformed_bounds),
-		};
-		let stroke_attribute = match (view_mode, &self.stroke) {
-			(ViewMode::Outline, _) => Stroke::new(LAYER_OUTLINE_STROKE_COLOR, LAYER_OUTLINE_STROKE_WEIGHT).render(),
-			(_, Some(stroke)) => stroke.render(),
-			(_, None) => String::new(),
-		};
+	/// Get the [ColorStyleId] that the fill is linked to.
+	pub fn fill_color_style(&self) -> Option<ColorStyleId> {
+		self.fill_color_style
+	}
+
+	/// Get the [ColorStyleId] that the stroke is linked to.
+	pub fn stroke_color_style(&self) -> Option<ColorStyleId> {
+		self.stroke_color_style
+	}
+
+	/// Link the fill to a [ColorStyle], replacing the fill with a solid fill of the style's color. Passing `None` unlinks it and keeps the current fill.
+	pub fn link_fill_color_style(&mut self, color_style: Option<(ColorStyleId, Color)>) {
+		self.fill_color_style = color_style.map(|(id, color)| {
+			self.fill = Fill::Solid(color);
+			id
+		});
+	}
+
+	/// Link the stroke color to a [ColorStyle], adding a default stroke if there is none. Passing `None` unlinks it and keeps the current stroke.
+	pub fn link_stroke_color_style(&mut self, color_style: Option<(ColorStyleId, Color)>) {
+		self.stroke_color_style = color_style.map(|(id, color)| {
+			self.stroke.get_or_insert_with(Stroke::default).color = Some(color);
+			id
+		});
+	}
+
+	/// Update the fill and stroke colors linked to the given [ColorStyle]. Returns whether anything was linked to it.
+	///
+	/// # Example
+	/// ```
+	/// # use graphite_graphene::layers::style::{Fill, PathStyle};
+	/// # use graphite_graphene::color::Color;
+	/// let mut style = PathStyle::default();
+	/// style.link_fill_color_style(Some((42, Color::RED)));
+	///
+	/// assert_eq!(*style.fill(), Fill::Solid(Color::RED));
+	///
+	/// assert!(style.apply_color_style(42, Color::BLUE));
+	/// assert!(!style.apply_color_style(7, Color::GREEN));
+	///
+	/// assert_eq!(*style.fill(), Fill::Solid(Color::BLUE));
+	/// ```
+	pub fn apply_color_style(&mut self, id: ColorStyleId, color: Color) -> bool {
+		let mut linked = false;
+		if self.fill_color_style == Some(id) {
+			self.fill = Fill::Solid(color);
+			linked = true;
+		}
+		if self.stroke_color_style == Some(id) {
+			if let Some(stroke) = &mut self.stroke {
+				stroke.color = Some(color);
+			}
+			linked = true;
+		}
+		linked
+	}
+
+	/// Remove any links to the given [ColorStyle], keeping the current colors. Returns whether anything was linked to it.
+	pub fn unlink_color_style(&mut self, id: ColorStyleId) -> bool {
+		let mut linked = false;
+		if self.fill_color_style == Some(id) {
+			self.fill_color_style = None;
+			linked = true;
+		}
+		if self.stroke_color_style == Some(id) {
+			self.stroke_color_style = None;
+			linked = true;
+		}
+		linked
+	}
+
+	/// Replace every fill, gradient, and stroke color with the result of the given function.
+	pub fn map_colors(&mut self, map: impl Fn(Color) -> Color) {
+		match &mut self.fill {
+			Fill::None => {}
+			Fill::Solid(color) => *color = map(*color),
+			Fill::Gradient(gradient) => gradient.positions.iter_mut().filter_map(|(_, color)| color.as_mut()).for_each(|color| *color = map(*color)),
+		}
+		if let Some(color) = self.stroke.as_mut().and_then(|stroke| stroke.color.as_mut()) {
+			*color = map(*color);
+		}
+	}
+
+	pub fn render(&self, render_data: RenderData, svg_defs: &mut String, multiplied_transform: DAffine2, bounds: [DVec2; 2], transformed_bounds: [DVec2; 2]) -> String {
+		if render_data.view_mode == ViewMode::Outline {
+			return outline_attributes();
+		}
+
+		let color_space = render_data.wide_gamut_color_space;
+		let fill_attribute = self.fill.render(svg_defs, multiplied_transform, bounds, transformed_bounds, color_space);
+		let stroke_attribute = self.stroke.as_ref().map(|stroke| stroke.render(color_space)).unwrap_or_default();
 
 		format!("{}{}", fill_attribute, stroke_attribute)
 	}
 }
+
+/// The attributes of every path drawn in [ViewMode::Outline], which shows only the geometry with a thin line regardless of its style.
+pub fn outline_attributes() -> String {
+	format!(r#" fill="none"{}"#, Stroke::new(LAYER_OUTLINE_STROKE_COLOR, LAYER_OUTLINE_STROKE_WEIGHT).render(None))
+}
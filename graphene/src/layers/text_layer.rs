@@ -1,9 +1,9 @@
 use super::layer_info::LayerData;
 use super::style::{PathStyle, RenderData, ViewMode};
 use super::vector::subpath::Subpath;
-use crate::intersection::{intersect_quad_bez_path, Quad};
+use crate::intersection::{intersect_quad_bez_path, quad_encloses_subpath, Quad};
 use crate::LayerId;
-pub use font_cache::{Font, FontCache};
+pub use font_cache::{EmbeddedFont, Font, FontCache};
 
 use glam::{DAffine2, DMat2, DVec2};
 use rustybuzz::Face;
@@ -13,6 +13,61 @@ use std::fmt::Write;
 mod font_cache;
 mod to_path;
 
+/// The horizontal alignment of each line within a [TextLayer].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub enum TextAlignment {
+	#[default]
+	Left,
+	Center,
+	Right,
+}
+
+impl TextAlignment {
+	/// The fraction of the unused width of a line that is placed before it.
+	pub fn factor(&self) -> f64 {
+		match self {
+			TextAlignment::Left => 0.,
+			TextAlignment::Center => 0.5,
+			TextAlignment::Right => 1.,
+		}
+	}
+}
+
+/// Paragraph settings controlling how the lines of a [TextLayer] are spaced and aligned.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Typesetting {
+	/// The distance between lines as a multiple of the font size.
+	pub leading: f64,
+	/// Extra space added after each character, in thousandths of an em.
+	pub tracking: f64,
+	pub alignment: TextAlignment,
+}
+
+impl Default for Typesetting {
+	fn default() -> Self {
+		Self {
+			leading: 1.,
+			tracking: 0.,
+			alignment: TextAlignment::Left,
+		}
+	}
+}
+
+/// A number that identifies a [TextStyle] within a document.
+pub type TextStyleId = u64;
+
+/// A named set of character and paragraph settings shared across the document.
+///
+/// Text layers link to a style by its [TextStyleId], so editing the style lays out every text layer using it again.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TextStyle {
+	pub name: String,
+	pub font: Font,
+	/// Font size in pixels.
+	pub size: f64,
+	pub typesetting: Typesetting,
+}
+
 /// A line, or multiple lines, of text drawn in the document.
 /// Like [ShapeLayers](super::shape_layer::ShapeLayer), [TextLayer] are rendered as
 /// [`<path>`s](https://developer.mozilla.org/en-US/docs/Web/SVG/Element/path).
@@ -26,6 +81,11 @@ pub struct TextLayer {
 	pub size: f64,
 	pub line_width: Option<f64>,
 	pub font: Font,
+	#[serde(default)]
+	pub typesetting: Typesetting,
+	/// The [TextStyle] this layer's font, size, and typesetting are linked to, if any.
+	#[serde(default)]
+	pub text_style: Option<TextStyleId>,
 	#[serde(skip)]
 	pub editable: bool,
 	#[serde(skip)]
@@ -66,9 +126,9 @@ impl LayerData for TextLayer {
 				font.map(|_| r#" style="font-family: local-font;""#).unwrap_or_default()
 			);
 		} else {
-			let buzz_face = self.load_face(render_data.font_cache);
+			let buzz_faces = self.load_faces(render_data.font_cache);
 
-			let mut path = self.to_subpath(buzz_face);
+			let mut path = self.to_subpath(&buzz_faces);
 
 			let bounds = path.bounding_box().unwrap_or_default();
 
@@ -80,34 +140,43 @@ impl LayerData for TextLayer {
 				svg,
 				r#"<path d="{}" {} />"#,
 				path.to_svg(),
-				self.path_style.render(render_data.view_mode, svg_defs, transform, bounds, transformed_bounds)
+				self.path_style.render(render_data, svg_defs, transform, bounds, transformed_bounds)
 			);
 		}
 		let _ = svg.write_str("</g>");
 	}
 
 	fn bounding_box(&self, transform: glam::DAffine2, font_cache: &FontCache) -> Option<[DVec2; 2]> {
-		let buzz_face = Some(self.load_face(font_cache)?);
+		let buzz_faces = self.load_faces(font_cache);
 
-		if transform.matrix2 == DMat2::ZERO {
+		if buzz_faces.is_empty() || transform.matrix2 == DMat2::ZERO {
 			return None;
 		}
 
-		Some((transform * self.bounding_box(&self.text, buzz_face)).bounding_box())
+		Some((transform * self.bounding_box(&self.text, &buzz_faces)).bounding_box())
 	}
 
 	fn intersects_quad(&self, quad: Quad, path: &mut Vec<LayerId>, intersections: &mut Vec<Vec<LayerId>>, font_cache: &FontCache) {
-		let buzz_face = self.load_face(font_cache);
+		let buzz_faces = self.load_faces(font_cache);
 
-		if intersect_quad_bez_path(quad, &self.bounding_box(&self.text, buzz_face).path(), true) {
+		if intersect_quad_bez_path(quad, &self.bounding_box(&self.text, &buzz_faces).path(), true) {
 			intersections.push(path.clone());
 		}
 	}
+
+	fn enclosed_by_quad(&self, quad: Quad, path: &mut Vec<LayerId>, enclosed: &mut Vec<Vec<LayerId>>, font_cache: &FontCache) {
+		let buzz_faces = self.load_faces(font_cache);
+
+		if quad_encloses_subpath(quad, &self.bounding_box(&self.text, &buzz_faces).subpath()) {
+			enclosed.push(path.clone());
+		}
+	}
 }
 
 impl TextLayer {
-	pub fn load_face<'a>(&self, font_cache: &'a FontCache) -> Option<Face<'a>> {
-		font_cache.get(&self.font).map(|data| rustybuzz::Face::from_slice(data, 0).expect("Loading font failed"))
+	/// Loads the layer's font followed by the fonts which are used for characters it doesn't have, so they don't render as missing glyph boxes.
+	pub fn load_faces<'a>(&self, font_cache: &'a FontCache) -> Vec<Face<'a>> {
+		font_cache.get_with_fallbacks(&self.font).into_iter().filter_map(|data| rustybuzz::Face::from_slice(data, 0)).collect()
 	}
 
 	pub fn transform(&self, transforms: &[DAffine2], mode: ViewMode) -> DAffine2 {
@@ -125,20 +194,22 @@ impl TextLayer {
 			size,
 			line_width: None,
 			font,
+			typesetting: Typesetting::default(),
+			text_style: None,
 			editable: false,
 			cached_path: None,
 		};
 
-		new.cached_path = Some(new.generate_path(new.load_face(font_cache)));
+		new.cached_path = Some(new.generate_path(&new.load_faces(font_cache)));
 
 		new
 	}
 
 	/// Converts to a [Subpath], populating the cache if necessary.
 	#[inline]
-	pub fn to_subpath(&mut self, buzz_face: Option<Face>) -> Subpath {
+	pub fn to_subpath(&mut self, buzz_faces: &[Face]) -> Subpath {
 		if self.cached_path.as_ref().filter(|subpath| !subpath.manipulator_groups().is_empty()).is_none() {
-			let path = self.generate_path(buzz_face);
+			let path = self.generate_path(buzz_faces);
 			self.cached_path = Some(path.clone());
 			return path;
 		}
@@ -148,29 +219,38 @@ impl TextLayer {
 	/// Converts to a [Subpath], without populating the cache.
 	#[inline]
 	pub fn to_subpath_nonmut(&self, font_cache: &FontCache) -> Subpath {
-		let buzz_face = self.load_face(font_cache);
+		let buzz_faces = self.load_faces(font_cache);
 
 		self.cached_path
 			.clone()
 			.filter(|subpath| !subpath.manipulator_groups().is_empty())
-			.unwrap_or_else(|| self.generate_path(buzz_face))
+			.unwrap_or_else(|| self.generate_path(&buzz_faces))
 	}
 
 	#[inline]
-	pub fn generate_path(&self, buzz_face: Option<Face>) -> Subpath {
-		to_path::to_path(&self.text, buzz_face, self.size, self.line_width)
+	pub fn generate_path(&self, buzz_faces: &[Face]) -> Subpath {
+		to_path::to_path(&self.text, buzz_faces, self.size, self.line_width, self.typesetting)
 	}
 
 	#[inline]
-	pub fn bounding_box(&self, text: &str, buzz_face: Option<Face>) -> Quad {
-		let far = to_path::bounding_box(text, buzz_face, self.size, self.line_width);
+	pub fn bounding_box(&self, text: &str, buzz_faces: &[Face]) -> Quad {
+		let far = to_path::bounding_box(text, buzz_faces, self.size, self.line_width, self.typesetting);
 		Quad::from_box([DVec2::ZERO, far])
 	}
 
 	pub fn update_text(&mut self, text: String, font_cache: &FontCache) {
-		let buzz_face = self.load_face(font_cache);
+		let buzz_faces = self.load_faces(font_cache);
 
 		self.text = text;
-		self.cached_path = Some(self.generate_path(buzz_face));
+		self.cached_path = Some(self.generate_path(&buzz_faces));
+	}
+
+	/// Links the layer to a [TextStyle], taking on its font, size, and typesetting and laying out the text again.
+	pub fn apply_text_style(&mut self, id: TextStyleId, text_style: &TextStyle, font_cache: &FontCache) {
+		self.text_style = Some(id);
+		self.font = text_style.font.clone();
+		self.size = text_style.size;
+		self.typesetting = text_style.typesetting;
+		self.cached_path = Some(self.generate_path(&self.load_faces(font_cache)));
 	}
 }
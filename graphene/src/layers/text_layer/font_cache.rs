@@ -13,6 +13,54 @@ impl Font {
 	pub fn new(font_family: String, font_style: String) -> Self {
 		Self { font_family, font_style }
 	}
+
+	/// Reads the family and style of a TrueType or OpenType font file, naming the style by its weight like the fonts of the font catalog.
+	pub fn from_font_file(data: &[u8]) -> Option<Self> {
+		let face = ttf_parser::Face::from_slice(data, 0).ok()?;
+
+		// The typographic names group all the weights of a family together, while the legacy names split families with more than four styles
+		let name = |ids: [u16; 2]| {
+			ids.iter().find_map(|&id| {
+				face.names()
+					.into_iter()
+					.filter(|name| name.name_id == id && name.is_unicode())
+					.find_map(|name| name.to_string())
+					.filter(|name| !name.trim().is_empty())
+			})
+		};
+		let font_family = name([ttf_parser::name_id::TYPOGRAPHIC_FAMILY, ttf_parser::name_id::FAMILY])?;
+
+		let weight = face.weight().to_number();
+		// From https://developer.mozilla.org/en-US/docs/Web/CSS/font-weight#common_weight_name_mapping
+		const WEIGHT_NAMES: [(u16, &str); 10] = [
+			(100, "Thin"),
+			(200, "Extra Light"),
+			(300, "Light"),
+			(400, "Normal"),
+			(500, "Medium"),
+			(600, "Semi Bold"),
+			(700, "Bold"),
+			(800, "Extra Bold"),
+			(900, "Black"),
+			(950, "Extra Black"),
+		];
+		let weight_name = WEIGHT_NAMES
+			.iter()
+			.min_by_key(|(named_weight, _)| named_weight.abs_diff(weight))
+			.map(|(_, name)| *name)
+			.unwrap_or("Normal");
+		let italic = if face.is_italic() || face.is_oblique() { " Italic" } else { "" };
+
+		Some(Self::new(font_family, format!("{weight_name}{italic} ({weight})")))
+	}
+}
+
+/// A font file the user provided, saved in the document so its text renders the same wherever the document is opened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddedFont {
+	pub font: Font,
+	/// The TrueType or OpenType font file
+	pub data: Vec<u8>,
 }
 
 /// A cache of all loaded font data and preview urls along with the default font (send from `init_app` in `editor_api.rs`)
@@ -24,6 +72,12 @@ pub struct FontCache {
 	preview_urls: HashMap<Font, String>,
 	/// The default font (used as a fallback)
 	default_font: Option<Font>,
+	/// Every loaded font in the order it was loaded, which is the order they're tried in for characters missing from a text layer's font
+	#[serde(default)]
+	load_order: Vec<Font>,
+	/// The fonts loaded from files the user provided, rather than from the font catalog
+	#[serde(default)]
+	custom_fonts: Vec<Font>,
 }
 impl FontCache {
 	/// Returns the font family name if the font is cached, otherwise returns the default font family name if that is cached
@@ -40,6 +94,18 @@ impl FontCache {
 		self.resolve_font(font).and_then(|font| self.font_file_data.get(font))
 	}
 
+	/// The bytes of the font followed by the fonts tried for characters it doesn't have: the default font, then the other fonts in the order they were loaded.
+	/// Text in a font which hasn't loaded yet is shaped with the fallbacks alone.
+	pub fn get_with_fallbacks<'a>(&'a self, font: &Font) -> Vec<&'a [u8]> {
+		let mut fonts: Vec<&Font> = self.resolve_font(font).into_iter().collect();
+		for fallback in self.default_font.iter().chain(&self.load_order) {
+			if !fonts.contains(&fallback) && self.loaded_font(fallback) {
+				fonts.push(fallback);
+			}
+		}
+		fonts.into_iter().filter_map(|font| self.font_file_data.get(font)).map(|data| data.as_slice()).collect()
+	}
+
 	/// Check if the font is already loaded
 	pub fn loaded_font(&self, font: &Font) -> bool {
 		self.font_file_data.contains_key(font)
@@ -50,10 +116,33 @@ impl FontCache {
 		if is_default {
 			self.default_font = Some(font.clone());
 		}
+		if !self.load_order.contains(&font) {
+			self.load_order.push(font.clone());
+		}
 		self.font_file_data.insert(font.clone(), data);
 		self.preview_urls.insert(font, perview_url);
 	}
 
+	/// Insert a font from a file the user provided, previewing it from a data URL of the file since it isn't hosted anywhere
+	pub fn insert_custom(&mut self, font: Font, data: Vec<u8>) {
+		let mime = if data.starts_with(b"OTTO") { "font/otf" } else { "font/ttf" };
+		let preview_url = format!("data:{};base64,{}", mime, base64::encode(&data));
+		if !self.custom_fonts.contains(&font) {
+			self.custom_fonts.push(font.clone());
+		}
+		self.insert(font, preview_url, data, false);
+	}
+
+	/// The fonts loaded from files the user provided, in the order they were loaded
+	pub fn custom_fonts(&self) -> &[Font] {
+		&self.custom_fonts
+	}
+
+	/// Checks if the font was loaded from a file the user provided
+	pub fn is_custom(&self, font: &Font) -> bool {
+		self.custom_fonts.contains(font)
+	}
+
 	/// Checks if the font cache has a default font
 	pub fn has_default(&self) -> bool {
 		self.default_font.is_some()
@@ -1,3 +1,4 @@
+use super::Typesetting;
 use crate::layers::vector::consts::ManipulatorType;
 use crate::layers::vector::manipulator_group::ManipulatorGroup;
 use crate::layers::vector::manipulator_point::ManipulatorPoint;
@@ -10,14 +11,14 @@ use ttf_parser::{GlyphId, OutlineBuilder};
 struct Builder {
 	path: Subpath,
 	pos: DVec2,
-	offset: DVec2,
-	ascender: f64,
+	/// The distance from the top of the line to the baseline, in pixels
+	baseline: f64,
 	scale: f64,
 }
 
 impl Builder {
 	fn point(&self, x: f32, y: f32) -> DVec2 {
-		self.pos + self.offset + DVec2::new(x as f64, self.ascender - y as f64) * self.scale
+		self.pos + DVec2::new(x as f64 * self.scale, self.baseline - y as f64 * self.scale)
 	}
 }
 
@@ -53,25 +54,87 @@ impl OutlineBuilder for Builder {
 	}
 }
 
-fn font_properties(buzz_face: &rustybuzz::Face, font_size: f64) -> (f64, f64, UnicodeBuffer) {
-	let scale = (buzz_face.units_per_em() as f64).recip() * font_size;
-	let line_height = font_size;
-	let buffer = UnicodeBuffer::new();
-	(scale, line_height, buffer)
+/// A glyph placed at its position relative to the start of its line.
+struct PlacedGlyph {
+	/// The index of the face the glyph is from, among the font of the text and its fallbacks
+	face: usize,
+	id: GlyphId,
+	position: DVec2,
 }
 
-fn push_str(buffer: &mut UnicodeBuffer, word: &str, trailing_space: bool) {
-	buffer.push_str(word);
+/// A visual line of text, which is either a whole line of the source text or the part of one remaining after wrapping.
+#[derive(Default)]
+struct Line {
+	glyphs: Vec<PlacedGlyph>,
+	width: f64,
+}
+
+/// The size of a font unit of each face in pixels.
+fn face_scales(buzz_faces: &[rustybuzz::Face], font_size: f64) -> Vec<f64> {
+	buzz_faces.iter().map(|face| (face.units_per_em() as f64).recip() * font_size).collect()
+}
+
+fn font_properties(font_size: f64, typesetting: Typesetting) -> (f64, f64) {
+	let line_height = font_size * typesetting.leading;
+	// Tracking is measured in thousandths of an em
+	let tracking = typesetting.tracking / 1000. * font_size;
+	(line_height, tracking)
+}
+
+/// Splits the text into runs which are each shaped with a single face, so characters missing from the font are drawn by the first fallback which has them instead of as missing glyph boxes.
+/// Characters stay with the font of the text whenever it has them, and with the face of the run they're in otherwise, so spaces and punctuation don't split runs.
+fn font_runs<'a>(text: &'a str, buzz_faces: &[rustybuzz::Face]) -> Vec<(usize, &'a str)> {
+	let mut runs = Vec::new();
+	let mut run_start = 0;
+	let mut run_face = None;
 
-	if trailing_space {
-		buffer.push_str(" ");
+	for (index, character) in text.char_indices() {
+		if character.is_whitespace() || character.is_control() {
+			continue;
+		}
+
+		let has_glyph = |face: usize| buzz_faces[face].glyph_index(character).is_some();
+		let face = match run_face {
+			_ if has_glyph(0) => 0,
+			Some(face) if has_glyph(face) => face,
+			_ => (0..buzz_faces.len()).find(|&face| has_glyph(face)).unwrap_or_else(|| run_face.unwrap_or(0)),
+		};
+
+		match run_face {
+			Some(previous_face) if previous_face != face => {
+				runs.push((previous_face, &text[run_start..index]));
+				run_start = index;
+			}
+			_ => {}
+		}
+		run_face = Some(face);
 	}
+	runs.push((run_face.unwrap_or(0), &text[run_start..]));
+
+	runs
+}
+
+/// Shapes each run of the text with its face.
+fn shape(text: &str, buzz_faces: &[rustybuzz::Face]) -> Vec<(usize, GlyphBuffer)> {
+	font_runs(text, buzz_faces)
+		.into_iter()
+		.map(|(face, run)| {
+			let mut buffer = UnicodeBuffer::new();
+			buffer.push_str(run);
+			(face, rustybuzz::shape(&buzz_faces[face], &[], buffer))
+		})
+		.collect()
 }
 
-fn wrap_word(line_width: Option<f64>, glyph_buffer: &GlyphBuffer, scale: f64, x_pos: f64) -> bool {
+fn wrap_word(line_width: Option<f64>, glyph_buffers: &[(usize, GlyphBuffer)], scales: &[f64], tracking: f64, x_pos: f64) -> bool {
 	if let Some(line_width) = line_width {
-		let word_length: i32 = glyph_buffer.glyph_positions().iter().map(|pos| pos.x_advance).sum();
-		let scaled_word_length = word_length as f64 * scale;
+		let scaled_word_length: f64 = glyph_buffers
+			.iter()
+			.map(|(face, glyph_buffer)| {
+				let word_length: i32 = glyph_buffer.glyph_positions().iter().map(|pos| pos.x_advance).sum();
+				word_length as f64 * scales[*face] + glyph_buffer.len() as f64 * tracking
+			})
+			.sum();
 
 		if scaled_word_length + x_pos > line_width {
 			return true;
@@ -80,88 +143,99 @@ fn wrap_word(line_width: Option<f64>, glyph_buffer: &GlyphBuffer, scale: f64, x_
 	false
 }
 
-pub fn to_path(str: &str, buzz_face: Option<rustybuzz::Face>, font_size: f64, line_width: Option<f64>) -> Subpath {
-	let buzz_face = match buzz_face {
-		Some(face) => face,
-		// Show blank layer if font has not loaded
-		None => return Subpath::default(),
-	};
+fn break_line(lines: &mut Vec<Line>, line: &mut Line, pos: &mut DVec2, line_height: f64) {
+	line.width = pos.x;
+	lines.push(std::mem::take(line));
+	*pos = DVec2::new(0., pos.y + line_height);
+}
 
-	let (scale, line_height, mut buffer) = font_properties(&buzz_face, font_size);
+/// Shapes the text and breaks it into lines, aligned according to the [Typesetting].
+fn layout(str: &str, buzz_faces: &[rustybuzz::Face], font_size: f64, line_width: Option<f64>, typesetting: Typesetting) -> (Vec<Line>, f64) {
+	let (line_height, tracking) = font_properties(font_size, typesetting);
+	let scales = face_scales(buzz_faces, font_size);
 
-	let mut builder = Builder {
-		path: Subpath::new(),
-		pos: DVec2::ZERO,
-		offset: DVec2::ZERO,
-		ascender: (buzz_face.ascender() as f64 / buzz_face.height() as f64) * font_size / scale,
-		scale,
-	};
+	let mut lines = Vec::new();
+	let mut line = Line::default();
+	let mut pos = DVec2::ZERO;
 
-	for line in str.split('\n') {
-		let length = line.split(' ').count();
-		for (index, word) in line.split(' ').enumerate() {
-			push_str(&mut buffer, word, index != length - 1);
-			let glyph_buffer = rustybuzz::shape(&buzz_face, &[], buffer);
+	for text_line in str.split('\n') {
+		let length = text_line.split(' ').count();
+		for (index, word) in text_line.split(' ').enumerate() {
+			let word = if index != length - 1 { format!("{word} ") } else { word.to_string() };
+			let glyph_buffers = shape(&word, buzz_faces);
 
-			if wrap_word(line_width, &glyph_buffer, scale, builder.pos.x) {
-				builder.pos = DVec2::new(0., builder.pos.y + line_height);
+			if wrap_word(line_width, &glyph_buffers, &scales, tracking, pos.x) {
+				break_line(&mut lines, &mut line, &mut pos, line_height);
 			}
 
-			for (glyph_position, glyph_info) in glyph_buffer.glyph_positions().iter().zip(glyph_buffer.glyph_infos()) {
-				if let Some(line_width) = line_width {
-					if builder.pos.x + (glyph_position.x_advance as f64 * builder.scale) >= line_width {
-						builder.pos = DVec2::new(0., builder.pos.y + line_height);
+			for (face, glyph_buffer) in &glyph_buffers {
+				let scale = scales[*face];
+				for (glyph_position, glyph_info) in glyph_buffer.glyph_positions().iter().zip(glyph_buffer.glyph_infos()) {
+					let advance = DVec2::new(glyph_position.x_advance as f64, glyph_position.y_advance as f64) * scale + DVec2::new(tracking, 0.);
+					if let Some(line_width) = line_width {
+						if pos.x + advance.x >= line_width {
+							break_line(&mut lines, &mut line, &mut pos, line_height);
+						}
 					}
+					let offset = DVec2::new(glyph_position.x_offset as f64, glyph_position.y_offset as f64) * scale;
+					line.glyphs.push(PlacedGlyph {
+						face: *face,
+						id: GlyphId(glyph_info.glyph_id as u16),
+						position: pos + offset,
+					});
+					pos += advance;
 				}
-				builder.offset = DVec2::new(glyph_position.x_offset as f64, glyph_position.y_offset as f64) * builder.scale;
-				buzz_face.outline_glyph(GlyphId(glyph_info.glyph_id as u16), &mut builder);
-				builder.pos += DVec2::new(glyph_position.x_advance as f64, glyph_position.y_advance as f64) * builder.scale;
 			}
-
-			buffer = glyph_buffer.clear();
 		}
-		builder.pos = DVec2::new(0., builder.pos.y + line_height);
+		break_line(&mut lines, &mut line, &mut pos, line_height);
 	}
-	builder.path
+
+	// Shift each line within the text box (or within the widest line, if the text does not wrap) to align it
+	let box_width = line_width.unwrap_or_else(|| lines.iter().map(|line| line.width).fold(0., f64::max));
+	for line in &mut lines {
+		let shift = (box_width - line.width).max(0.) * typesetting.alignment.factor();
+		line.glyphs.iter_mut().for_each(|glyph| glyph.position.x += shift);
+		line.width += shift;
+	}
+
+	(lines, line_height)
 }
 
-pub fn bounding_box(str: &str, buzz_face: Option<rustybuzz::Face>, font_size: f64, line_width: Option<f64>) -> DVec2 {
-	let buzz_face = match buzz_face {
+/// Converts the text to a path, using the first face for the characters it has and the following faces as fallbacks for the rest.
+pub fn to_path(str: &str, buzz_faces: &[rustybuzz::Face], font_size: f64, line_width: Option<f64>, typesetting: Typesetting) -> Subpath {
+	let primary_face = match buzz_faces.first() {
 		Some(face) => face,
 		// Show blank layer if font has not loaded
-		None => return DVec2::ZERO,
+		None => return Subpath::default(),
 	};
 
-	let (scale, line_height, mut buffer) = font_properties(&buzz_face, font_size);
-
-	let mut pos = DVec2::ZERO;
-	let mut bounds = DVec2::ZERO;
-
-	for line in str.split('\n') {
-		let length = line.split(' ').count();
-		for (index, word) in line.split(' ').enumerate() {
-			push_str(&mut buffer, word, index != length - 1);
-
-			let glyph_buffer = rustybuzz::shape(&buzz_face, &[], buffer);
+	let (lines, _) = layout(str, buzz_faces, font_size, line_width, typesetting);
+	let scales = face_scales(buzz_faces, font_size);
 
-			if wrap_word(line_width, &glyph_buffer, scale, pos.x) {
-				pos = DVec2::new(0., pos.y + line_height);
-			}
+	// Every line is placed on the baseline of the font of the text, whichever faces its glyphs are from
+	let mut builder = Builder {
+		path: Subpath::new(),
+		pos: DVec2::ZERO,
+		baseline: (primary_face.ascender() as f64 / primary_face.height() as f64) * font_size,
+		scale: scales[0],
+	};
 
-			for glyph_position in glyph_buffer.glyph_positions() {
-				if let Some(line_width) = line_width {
-					if pos.x + (glyph_position.x_advance as f64 * scale) >= line_width {
-						pos = DVec2::new(0., pos.y + line_height);
-					}
-				}
-				pos += DVec2::new(glyph_position.x_advance as f64, glyph_position.y_advance as f64) * scale;
-			}
-			bounds = bounds.max(pos + DVec2::new(0., line_height));
+	for glyph in lines.iter().flat_map(|line| &line.glyphs) {
+		builder.pos = glyph.position;
+		builder.scale = scales[glyph.face];
+		buzz_faces[glyph.face].outline_glyph(glyph.id, &mut builder);
+	}
+	builder.path
+}
 
-			buffer = glyph_buffer.clear();
-		}
-		pos = DVec2::new(0., pos.y + line_height);
+pub fn bounding_box(str: &str, buzz_faces: &[rustybuzz::Face], font_size: f64, line_width: Option<f64>, typesetting: Typesetting) -> DVec2 {
+	if buzz_faces.is_empty() {
+		// Show blank layer if font has not loaded
+		return DVec2::ZERO;
 	}
 
-	bounds
+	let (lines, line_height) = layout(str, buzz_faces, font_size, line_width, typesetting);
+	let width = lines.iter().map(|line| line.width).fold(0., f64::max);
+
+	DVec2::new(width, lines.len() as f64 * line_height)
 }
@@ -21,6 +21,10 @@ pub struct ManipulatorGroup {
 	/// Editable points for the anchor and handles.
 	pub points: [Option<ManipulatorPoint>; 3],
 
+	/// The radius of the arc the corner at the anchor is rounded off with when the path is drawn, which leaves the anchor itself in place.
+	#[serde(default)]
+	pub corner_radius: f64,
+
 	#[serde(skip)]
 	// TODO: Remove this from Graphene, editor state should be stored in the frontend if possible.
 	/// The editor state of the anchor and handles.
@@ -33,6 +37,7 @@ impl ManipulatorGroup {
 		Self {
 			// An anchor and 2x None's which represent non-existent handles
 			points: [Some(ManipulatorPoint::new(anchor_pos, ManipulatorType::Anchor)), None, None],
+			corner_radius: 0.,
 			editor_state: ManipulatorGroupEditorState::default(),
 		}
 	}
@@ -58,6 +63,7 @@ impl ManipulatorGroup {
 				],
 				(None, None) => [Some(ManipulatorPoint::new(anchor_pos, ManipulatorType::Anchor)), None, None],
 			},
+			corner_radius: 0.,
 			editor_state: ManipulatorGroupEditorState::default(),
 		}
 	}
@@ -68,6 +74,7 @@ impl ManipulatorGroup {
 		Self {
 			// An anchor (the first element) being `None` indicates a ClosePath (i.e. a path end command)
 			points: [None, None, None],
+			corner_radius: 0.,
 			editor_state: ManipulatorGroupEditorState::default(),
 		}
 	}
@@ -278,6 +285,48 @@ impl ManipulatorGroup {
 		}
 	}
 
+	/// Set the position of a handle, or remove it if the position is `None`.
+	pub fn set_handle(&mut self, handle: ManipulatorType, position: Option<DVec2>) {
+		match position {
+			Some(position) => self.set_point_position(handle as usize, position),
+			None => self.points[handle] = None,
+		}
+	}
+
+	/// Returns a copy of this [ManipulatorGroup] without the given handle, such as for the ends of a path cut at its anchor.
+	pub fn without_handle(&self, handle: ManipulatorType) -> Self {
+		let mut manipulator_group = self.clone();
+		manipulator_group.points[handle] = None;
+		manipulator_group
+	}
+
+	/// Returns a copy of this [ManipulatorGroup] with its handles swapped, for a path running in the other direction.
+	pub fn reversed(&self) -> Self {
+		let mut manipulator_group = self.clone();
+		manipulator_group.points.swap(ManipulatorType::InHandle as usize, ManipulatorType::OutHandle as usize);
+		for manipulator_type in [ManipulatorType::InHandle, ManipulatorType::OutHandle] {
+			if let Some(point) = &mut manipulator_group.points[manipulator_type] {
+				point.manipulator_type = manipulator_type;
+			}
+		}
+		manipulator_group
+	}
+
+	/// Merges the anchor at the end of one path with the anchor at the start of the next into one at their midpoint, which keeps the in handle of `end` and the out handle of `start`.
+	pub fn merged(end: &Self, start: &Self) -> Self {
+		let anchor = |group: &Self| group.points[ManipulatorType::Anchor].as_ref().map_or(DVec2::ZERO, |point| point.position);
+		let handle = |group: &Self, handle: ManipulatorType| group.points[handle].as_ref().map(|point| point.position);
+
+		let midpoint = anchor(end).lerp(anchor(start), 0.5);
+		let in_handle = handle(end, ManipulatorType::InHandle).map(|position| position - anchor(end) + midpoint);
+		let out_handle = handle(start, ManipulatorType::OutHandle).map(|position| position - anchor(start) + midpoint);
+
+		let mut manipulator_group = Self::new_with_handles(midpoint, in_handle, out_handle);
+		manipulator_group.corner_radius = start.corner_radius;
+		manipulator_group.editor_state = start.editor_state.clone();
+		manipulator_group
+	}
+
 	/// Apply an affine transformation the points
 	pub fn transform(&mut self, transform: &DAffine2) {
 		for point in self.points_mut() {
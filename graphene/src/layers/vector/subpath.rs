@@ -3,10 +3,12 @@ use super::manipulator_group::ManipulatorGroup;
 use super::manipulator_point::ManipulatorPoint;
 use crate::layers::id_vec::IdBackedVec;
 use crate::layers::layer_info::{Layer, LayerDataType};
+use crate::layers::style::Stroke;
 
 use glam::{DAffine2, DVec2};
 use kurbo::{BezPath, PathEl, Shape};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
 /// [Subpath] represents a single vector path, containing many [ManipulatorGroups].
 /// For each closed shape we keep a [Subpath] which contains the [ManipulatorGroup]s (handles and anchors) that define that shape.
@@ -207,6 +209,330 @@ impl Subpath {
 		}
 	}
 
+	// ** CUTTING AND JOINING **
+
+	/// Cut the [Subpath] at the anchor of the [ManipulatorGroup] with the given ID, keeping the handles on both sides of the cut.
+	/// A closed contour is opened up to start and end at that anchor, while an open contour is split into two there.
+	/// Only the last contour of a [Subpath] can be open, so any other open contours resulting from the cut are returned as their own [Subpath]s after the first.
+	/// Returns `None` if there is no such anchor or it's already an end of an open contour.
+	pub fn cut_at_anchor(&self, id: u64) -> Option<Vec<Subpath>> {
+		let mut contours = self.contours();
+		let (contour_index, group_index) = contours
+			.iter()
+			.enumerate()
+			.find_map(|(contour_index, (ids, _, _))| Some((contour_index, ids.iter().position(|&other| other == id)?)))?;
+		let (_, groups, closed) = contours.remove(contour_index);
+
+		let (cut_start, cut_end) = (
+			groups[group_index].without_handle(ManipulatorType::InHandle),
+			groups[group_index].without_handle(ManipulatorType::OutHandle),
+		);
+		let mut open_contours = if closed {
+			// Start the opened contour at the cut, wrapping around to end there again
+			let opened = std::iter::once(cut_start)
+				.chain(groups[group_index + 1..].iter().cloned())
+				.chain(groups[..group_index].iter().cloned())
+				.chain(std::iter::once(cut_end))
+				.collect::<Vec<_>>();
+			vec![opened]
+		} else {
+			if group_index == 0 || group_index == groups.len() - 1 {
+				return None;
+			}
+			let before = groups[..group_index].iter().cloned().chain(std::iter::once(cut_end)).collect::<Vec<_>>();
+			let after = std::iter::once(cut_start).chain(groups[group_index + 1..].iter().cloned()).collect::<Vec<_>>();
+			vec![before, after]
+		};
+
+		// Keep the remaining closed contours together with one open contour, which has to come last
+		let (closed_contours, other_open_contours): (Vec<_>, Vec<_>) = contours.into_iter().map(|(_, groups, closed)| (groups, closed)).partition(|(_, closed)| *closed);
+		open_contours.extend(other_open_contours.into_iter().map(|(groups, _)| groups));
+		let mut open_contours = open_contours.into_iter();
+
+		let first = closed_contours.into_iter().chain(open_contours.next().map(|groups| (groups, false)));
+		let mut subpaths = vec![Self::from_contours(first)];
+		subpaths.extend(open_contours.map(|groups| Self::from_contours([(groups, false)])));
+		Some(subpaths)
+	}
+
+	/// Cut the [Subpath] at the point `t` along the given segment, like [Subpath::cut_at_anchor] does at an anchor.
+	pub fn cut_at_segment(&self, segment: &BezierId, t: f64) -> Option<Vec<Subpath>> {
+		let [first, second] = segment.internal.split(t);
+		let mut subpath = self.clone();
+		let manipulator_groups = subpath.manipulator_groups_mut();
+
+		// Move the handles of the split segment over to the two halves
+		manipulator_groups.by_id_mut(segment.start)?.set_handle(ManipulatorType::OutHandle, first.handle_start());
+		manipulator_groups.by_id_mut(segment.end)?.set_handle(ManipulatorType::InHandle, second.handle_end());
+
+		// Insert the anchor at the cut right after the start of the segment, which is before the closing marker for the segment closing a contour
+		let new_group = ManipulatorGroup::new_with_handles(first.end(), first.handle_end(), second.handle_start());
+		let insert_index = manipulator_groups.index_from_id(segment.start)? + 1;
+		let next_id = manipulator_groups.enumerate().nth(insert_index).map(|(&id, _)| id);
+		let cut_id = match next_id {
+			Some(next_id) => manipulator_groups.insert(new_group, next_id),
+			None => manipulator_groups.push_end(new_group),
+		}?;
+
+		subpath.cut_at_anchor(cut_id)
+	}
+
+	/// The IDs of the [ManipulatorGroup]s at the start and end of the open contour, which is always the last one, if there is one with at least two anchors.
+	pub fn open_endpoints(&self) -> Option<[u64; 2]> {
+		let (ids, _, closed) = self.contours().pop()?;
+		(!closed && ids.len() >= 2).then(|| [ids[0], ids[ids.len() - 1]])
+	}
+
+	/// Close the open contour of the [Subpath] with a straight segment between its endpoints or, if `average` is set, by merging them into one anchor at their midpoint.
+	/// Returns `None` if there is no open contour to close.
+	pub fn close_open_contour(&self, average: bool) -> Option<Subpath> {
+		let mut contours = self.contours();
+		let (_, mut groups, closed) = contours.pop()?;
+		if closed || groups.len() < if average { 3 } else { 2 } {
+			return None;
+		}
+
+		let end = groups.pop()?;
+		if average {
+			groups[0] = ManipulatorGroup::merged(&end, &groups[0]);
+		} else {
+			groups[0] = groups[0].without_handle(ManipulatorType::InHandle);
+			groups.push(end.without_handle(ManipulatorType::OutHandle));
+		}
+
+		let contours = contours.into_iter().map(|(_, groups, closed)| (groups, closed));
+		Some(Self::from_contours(contours.chain(std::iter::once((groups, true)))))
+	}
+
+	/// Join the open contour of this [Subpath] to the open contour of `other`, which has to be in the same coordinate space, at their endpoints with the given IDs.
+	/// The endpoints are connected by a straight segment or, if `average` is set, merged into one anchor at their midpoint.
+	/// Returns the joined [Subpath], which keeps the closed contours of this one, and the closed contours left over from `other`.
+	pub fn join_open_contours(&self, id: u64, other: &Subpath, other_id: u64, average: bool) -> Option<(Subpath, Subpath)> {
+		let (mut contours, mut other_contours) = (self.contours(), other.contours());
+		let (ids, groups, closed) = contours.pop()?;
+		let (other_ids, other_groups, other_closed) = other_contours.pop()?;
+		if closed || other_closed {
+			return None;
+		}
+
+		// Orient both contours so they continue from one into the other at the joined endpoints
+		let reverse = |groups: Vec<ManipulatorGroup>| groups.iter().rev().map(ManipulatorGroup::reversed).collect::<Vec<_>>();
+		let mut groups = match (ids.first() == Some(&id), ids.last() == Some(&id)) {
+			(_, true) => groups,
+			(true, false) => reverse(groups),
+			(false, false) => return None,
+		};
+		let other_groups = match (other_ids.first() == Some(&other_id), other_ids.last() == Some(&other_id)) {
+			(true, _) => other_groups,
+			(false, true) => reverse(other_groups),
+			(false, false) => return None,
+		};
+
+		let mut other_groups = other_groups.into_iter();
+		let (end, start) = (groups.pop()?, other_groups.next()?);
+		if average {
+			groups.push(ManipulatorGroup::merged(&end, &start));
+		} else {
+			groups.push(end.without_handle(ManipulatorType::OutHandle));
+			groups.push(start.without_handle(ManipulatorType::InHandle));
+		}
+		groups.extend(other_groups);
+
+		let contours = contours.into_iter().map(|(_, groups, closed)| (groups, closed));
+		let joined = Self::from_contours(contours.chain(std::iter::once((groups, false))));
+		let rest = Self::from_contours(other_contours.into_iter().map(|(_, groups, closed)| (groups, closed)));
+		Some((joined, rest))
+	}
+
+	/// Reduce the number of anchors of every contour with [bezier_rs::Subpath::simplify], removing those whose neighboring segments can be merged
+	/// while deviating from the original shape by at most `tolerance`. The endpoints of an open contour are always kept.
+	pub fn simplify(&self, tolerance: f64) -> Subpath {
+		Self::from_bezier_contours(self.bezier_contours().iter().map(|contour| contour.simplify(tolerance)))
+	}
+
+	/// Offset the contours of the [Subpath] outwards by `distance`, or inwards if it's negative, connecting the offset curves around corners with the given join.
+	/// The loops left where the offset curves overlap are removed, as are contours inset until they vanish.
+	pub fn offset(&self, distance: f64, join: bezier_rs::Join) -> Subpath {
+		let contours = self.bezier_contours();
+
+		// Offset along the direction the largest contour winds in, so holes winding the other way shrink as the shape grows
+		let largest_area = contours.iter().map(|contour| contour.signed_area()).max_by(|a, b| a.abs().total_cmp(&b.abs())).unwrap_or_default();
+		// Positive distances offset to the left of the curves, which is the inside of counter-clockwise contours
+		let distance = if largest_area > 0. { -distance } else { distance };
+
+		let offset_contours = contours.iter().flat_map(|contour| contour.clean_offset(distance, join)).filter(|contour| !contour.is_empty());
+		Self::from_bezier_contours(offset_contours)
+	}
+
+	/// The outline of a stroke of the given `width` along every contour of the [Subpath], as closed contours which enclose the stroke with the nonzero fill rule.
+	/// Miters reaching further than `miter_limit` times half the `width` from their corner are beveled.
+	pub fn outline(&self, width: f64, join: bezier_rs::Join, miter_limit: f64, cap: bezier_rs::Cap) -> Subpath {
+		Self::from_bezier_contours(self.bezier_contours().iter().flat_map(|contour| contour.outline(width, join, miter_limit, cap)))
+	}
+
+	/// The outline of the [Stroke] as it's drawn along the [Subpath], with rounded corners and split into its dashes, enclosing each dash with the caps and joins of the stroke.
+	/// A stroke with a [width profile](Stroke::width_profile) instead varies in width along each contour, which isn't split into dashes.
+	pub fn stroke_outline(&self, stroke: &Stroke) -> Subpath {
+		let dash_lengths: Vec<f64> = stroke.dash_pattern().iter().map(|&length| length as f64).collect();
+		let (join, cap): (bezier_rs::Join, bezier_rs::Cap) = (stroke.line_join().into(), stroke.line_cap().into());
+		let miter_limit = stroke.line_join_miter_limit() as f64;
+
+		if stroke.has_width_profile() {
+			let contours = self.with_rounded_corners().bezier_contours();
+			return Self::from_bezier_contours(
+				contours
+					.iter()
+					.flat_map(|contour| contour.variable_outline(|position| stroke.width_at(position), join, miter_limit, cap)),
+			);
+		}
+
+		let dashes = self
+			.with_rounded_corners()
+			.bezier_contours()
+			.into_iter()
+			.flat_map(|contour| contour.dash(&dash_lengths, stroke.dash_offset()));
+		Self::from_bezier_contours(dashes.flat_map(|dash| dash.outline(stroke.weight(), join, miter_limit, cap)))
+	}
+
+	/// The [stroke outline](Subpath::stroke_outline) of the [Subpath] drawn in the space `transform` takes it to, like the stroke of a layer drawn in the space of its parent,
+	/// brought back into the space of the [Subpath]. Returns `None` if the `transform` can't be inverted.
+	pub fn transformed_stroke_outline(&self, stroke: &Stroke, transform: DAffine2) -> Option<Subpath> {
+		let inverse = transform.inverse();
+		if !inverse.is_finite() {
+			return None;
+		}
+		let mut subpath = self.clone();
+		subpath.apply_affine(transform);
+		let mut outline = subpath.stroke_outline(stroke);
+		outline.apply_affine(inverse);
+		Some(outline)
+	}
+
+	/// The number of contours the [Subpath] is made of, which are separated by [close markers](ManipulatorGroup::is_close).
+	pub fn contour_count(&self) -> usize {
+		self.contours().len()
+	}
+
+	/// The point at the given fraction of the length of the contour at `contour_index`, measured with rounded corners like a [width profile](Stroke::width_profile),
+	/// along with the unit normal of the contour there.
+	pub fn contour_point_at(&self, contour_index: usize, position: f64) -> Option<(DVec2, DVec2)> {
+		self.with_rounded_corners().bezier_contours().get(contour_index)?.evaluate_length_fraction(position)
+	}
+
+	/// The index of the contour closest to `point`, the fraction of its length travelled to reach its closest point to `point`, and that closest point.
+	pub fn project_onto_contours(&self, point: DVec2) -> Option<(usize, f64, DVec2)> {
+		self.with_rounded_corners()
+			.bezier_contours()
+			.iter()
+			.enumerate()
+			.filter_map(|(index, contour)| contour.project_length_fraction(point).map(|(position, closest)| (index, position, closest)))
+			.min_by(|a, b| a.2.distance_squared(point).total_cmp(&b.2.distance_squared(point)))
+	}
+
+	/// Whether any anchor of the [Subpath] has a [corner radius](ManipulatorGroup::corner_radius).
+	pub fn has_rounded_corners(&self) -> bool {
+		self.manipulator_groups().iter().any(|group| !group.is_close() && group.corner_radius > 0.)
+	}
+
+	/// The [Subpath] as it's drawn, with the corners at anchors with a [corner radius](ManipulatorGroup::corner_radius) rounded off by [bezier_rs::Subpath::round_corners].
+	/// The path is only copied if any of its corners are rounded.
+	pub fn with_rounded_corners(&self) -> Cow<Subpath> {
+		if !self.has_rounded_corners() {
+			return Cow::Borrowed(self);
+		}
+
+		let contours = self.contours().into_iter().zip(self.bezier_contours()).map(|((_, groups, _), contour)| {
+			let radii: Vec<f64> = groups.iter().map(|group| group.corner_radius).collect();
+			contour.round_corners(&radii)
+		});
+		Cow::Owned(Self::from_bezier_contours(contours))
+	}
+
+	/// The unit vectors pointing from the anchor of the [ManipulatorGroup] with the given ID along the curves on either side of it,
+	/// or `None` if it's the end of an open contour, so there's no corner to round.
+	pub fn corner_directions(&self, id: u64) -> Option<[DVec2; 2]> {
+		self.contours()
+			.into_iter()
+			.zip(self.bezier_contours())
+			.find_map(|((ids, _, _), contour)| contour.corner_directions(ids.iter().position(|&contour_id| contour_id == id)?))
+	}
+
+	/// The number of anchors in all contours of the [Subpath].
+	pub fn anchor_count(&self) -> usize {
+		self.manipulator_groups().iter().filter(|group| !group.is_close()).count()
+	}
+
+	/// The contours of the [Subpath], each as the IDs and [ManipulatorGroup]s of its anchors and whether it is closed.
+	fn contours(&self) -> Vec<(Vec<u64>, Vec<ManipulatorGroup>, bool)> {
+		let mut contours = Vec::new();
+		let (mut ids, mut groups) = (Vec::new(), Vec::new());
+
+		for (&id, manipulator_group) in self.manipulator_groups().enumerate() {
+			if manipulator_group.is_close() {
+				contours.push((std::mem::take(&mut ids), std::mem::take(&mut groups), true));
+			} else {
+				ids.push(id);
+				groups.push(manipulator_group.clone());
+			}
+		}
+		if !groups.is_empty() {
+			contours.push((ids, groups, false));
+		}
+
+		contours.retain(|(_, groups, _)| !groups.is_empty());
+		contours
+	}
+
+	/// The contours of the [Subpath] as [bezier_rs::Subpath]s.
+	fn bezier_contours(&self) -> Vec<bezier_rs::Subpath> {
+		let position = |group: &ManipulatorGroup, manipulator_type: ManipulatorType| group.points[manipulator_type].as_ref().map(|point| point.position);
+
+		self.contours()
+			.into_iter()
+			.map(|(_, groups, closed)| {
+				let manipulator_groups: Vec<_> = groups
+					.iter()
+					.filter_map(|group| {
+						Some(bezier_rs::ManipulatorGroup {
+							anchor: position(group, ManipulatorType::Anchor)?,
+							in_handle: position(group, ManipulatorType::InHandle),
+							out_handle: position(group, ManipulatorType::OutHandle),
+						})
+					})
+					.collect();
+				let closed = closed && manipulator_groups.len() > 1;
+				bezier_rs::Subpath::new(manipulator_groups, closed)
+			})
+			.collect()
+	}
+
+	/// Construct a [Subpath] from contours given as [bezier_rs::Subpath]s, moving any open ones to the end.
+	fn from_bezier_contours(contours: impl IntoIterator<Item = bezier_rs::Subpath>) -> Self {
+		let mut contours: Vec<_> = contours.into_iter().collect();
+		contours.sort_by_key(|contour| !contour.closed());
+
+		Self::from_contours(contours.into_iter().map(|contour| {
+			let groups = contour
+				.manipulator_groups()
+				.iter()
+				.map(|group| ManipulatorGroup::new_with_handles(group.anchor, group.in_handle, group.out_handle))
+				.collect();
+			(groups, contour.closed())
+		}))
+	}
+
+	/// Construct a [Subpath] from contours given as their [ManipulatorGroup]s and whether they are closed.
+	fn from_contours(contours: impl IntoIterator<Item = (Vec<ManipulatorGroup>, bool)>) -> Self {
+		let mut subpath = Subpath::new();
+		for (groups, closed) in contours {
+			subpath.0.push_range(groups);
+			if closed {
+				subpath.0.push_end(ManipulatorGroup::closed());
+			}
+		}
+		subpath
+	}
+
 	// ** SELECTION OF POINTS **
 
 	/// Set a single point to a chosen selection state by providing `(manipulator group ID, manipulator type)`.
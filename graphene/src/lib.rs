@@ -10,10 +10,16 @@ pub mod consts;
 pub mod document;
 /// Defines errors that can occur when using Graphene.
 pub mod error;
+/// Tessellation of documents for the GPU viewport renderer.
+pub mod gpu_scene;
+/// Tracing of raster images into vector shapes.
+pub mod image_trace;
 /// Utilities for computing intersections.
 pub mod intersection;
 pub mod layers;
 pub mod operation;
+/// An ordered log of the operations made by every editor changing a shared document, for collaborating on it.
+pub mod operation_log;
 pub mod response;
 
 pub use document::LayerId;
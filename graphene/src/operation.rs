@@ -1,8 +1,12 @@
 use crate::boolean_ops::BooleanOperation as BooleanOperationType;
+use crate::color::{CmykProfile, ColorSpace};
+use crate::gpu_scene::ViewportRenderer;
 use crate::layers::blend_mode::BlendMode;
+use crate::layers::image_layer::ImageLink;
 use crate::layers::imaginate_layer::{ImaginateSamplingMethod, ImaginateStatus};
-use crate::layers::layer_info::Layer;
-use crate::layers::style::{self, Stroke};
+use crate::layers::layer_info::{Layer, LayerLabel};
+use crate::layers::style::{self, ColorStyle, ColorStyleId, RenderQuality, Stroke};
+use crate::layers::text_layer::{EmbeddedFont, TextStyle, TextStyleId};
 use crate::layers::vector::consts::ManipulatorType;
 use crate::layers::vector::manipulator_group::ManipulatorGroup;
 use crate::layers::vector::subpath::Subpath;
@@ -64,6 +68,18 @@ pub enum Operation {
 		blob_url: String,
 		resolution: (f64, f64),
 	},
+	/// Replaces the encoded image of an Image layer, like when the file of a linked image has changed.
+	/// **Be sure to send the new image to the frontend together with this, so it can make a blob URL for it.**
+	SetImageData {
+		path: Vec<LayerId>,
+		mime: String,
+		image_data: Vec<u8>,
+	},
+	/// Links an Image layer to an external file, or embeds the image in the document when the link is `None`.
+	SetImageLink {
+		path: Vec<LayerId>,
+		link: Option<ImageLink>,
+	},
 	/// Clears the image to leave the Imaginate layer un-rendered.
 	/// **Be sure to call `FrontendMessage::TriggerRevokeBlobUrl` together with this.**
 	ImaginateClear {
@@ -166,9 +182,15 @@ pub enum Operation {
 		operation: BooleanOperationType,
 		selected: Vec<Vec<LayerId>>,
 	},
+	DeleteColorStyle {
+		id: ColorStyleId,
+	},
 	DeleteLayer {
 		path: Vec<LayerId>,
 	},
+	DeleteTextStyle {
+		id: TextStyleId,
+	},
 	DeleteSelectedManipulatorPoints {
 		layer_paths: Vec<Vec<LayerId>>,
 	},
@@ -280,10 +302,68 @@ pub enum Operation {
 		path: Vec<LayerId>,
 		visible: bool,
 	},
+	/// Moves the given layers of a folder up or down its stacking order, keeping their order relative to each other.
+	ReorderLayers {
+		folder_path: Vec<LayerId>,
+		layer_ids: Vec<LayerId>,
+		relative_index_offset: isize,
+	},
+	SetWideGamut {
+		wide_gamut: bool,
+	},
+	SetViewportRenderer {
+		viewport_renderer: ViewportRenderer,
+	},
+	SetRenderQuality {
+		render_quality: RenderQuality,
+	},
+	SetLayerLabel {
+		path: Vec<LayerId>,
+		label: Option<LayerLabel>,
+	},
+	SetLayerLocked {
+		path: Vec<LayerId>,
+		locked: bool,
+	},
 	SetLayerName {
 		path: Vec<LayerId>,
 		name: String,
 	},
+	SetCmykProfile {
+		cmyk_profile: CmykProfile,
+	},
+	SetColorSpace {
+		color_space: ColorSpace,
+	},
+	/// Creates or replaces a color style, updating the colors of every layer linked to it.
+	SetColorStyle {
+		id: ColorStyleId,
+		color_style: ColorStyle,
+	},
+	/// Links the fill of a layer to a color style, or unlinks it when `id` is `None`.
+	SetLayerFillColorStyle {
+		path: Vec<LayerId>,
+		id: Option<ColorStyleId>,
+	},
+	/// Links the stroke of a layer to a color style, or unlinks it when `id` is `None`.
+	SetLayerStrokeColorStyle {
+		path: Vec<LayerId>,
+		id: Option<ColorStyleId>,
+	},
+	/// Links a text layer to a text style, or unlinks it when `id` is `None`.
+	SetLayerTextStyle {
+		path: Vec<LayerId>,
+		id: Option<TextStyleId>,
+	},
+	/// Creates or replaces a text style, laying out every text layer linked to it again.
+	SetTextStyle {
+		id: TextStyleId,
+		text_style: TextStyle,
+	},
+	/// Saves font files in the document, replacing any already embedded for the same fonts.
+	EmbedFonts {
+		fonts: Vec<EmbeddedFont>,
+	},
 	SetLayerBlendMode {
 		path: Vec<LayerId>,
 		blend_mode: BlendMode,
@@ -315,6 +395,19 @@ pub enum Operation {
 		toggle_distance: bool,
 		toggle_angle: bool,
 	},
+	/// Sets the radius the corners at the selected anchors of the layer are rounded off with.
+	SetSelectedCornerRadius {
+		layer_path: Vec<LayerId>,
+		radius: f64,
+	},
+	/// Moves the transform of the layer into its path data, or into that of the layers inside of it for folders.
+	FlattenLayerTransform {
+		path: Vec<LayerId>,
+	},
+	/// Moves the position and size of the path data of the layer, or of the layers inside of it for folders, into their transforms.
+	ExtractLayerTransform {
+		path: Vec<LayerId>,
+	},
 }
 
 impl Operation {
@@ -0,0 +1,474 @@
+use crate::document::Document;
+use crate::layers::text_layer::FontCache;
+use crate::operation::Operation;
+use crate::DocumentError;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Identifies one of the editors making changes to its own copy of a shared document.
+pub type ReplicaId = u64;
+
+/// A Lamport timestamp, which orders the operations of every replica the same way for all of them: first by the counter, then by the replica to break ties.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct OperationId {
+	/// One more than the highest counter the replica had seen when it made the operation
+	pub counter: u64,
+	pub replica: ReplicaId,
+}
+
+/// A change made to the document by one of the replicas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Change {
+	Operation(Operation),
+	/// Replaces the whole document, like undoing and redoing do, which discards the effect of the operations ordered before it
+	Snapshot(Box<Document>),
+}
+
+impl Change {
+	/// Applies the change to the document, keeping how it's viewed by this replica.
+	pub fn apply(&self, document: &mut Document, font_cache: &FontCache) -> Result<(), DocumentError> {
+		match self {
+			Change::Operation(operation) => document.handle_operation(operation.clone(), font_cache).map(|_| ()),
+			Change::Snapshot(snapshot) => {
+				let view = (document.root.transform, document.viewport_renderer, document.render_quality);
+				*document = snapshot.as_ref().clone();
+				(document.root.transform, document.viewport_renderer, document.render_quality) = view;
+				Ok(())
+			}
+		}
+	}
+}
+
+/// A change made by one of the replicas, as it's sent to the others.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedOperation {
+	pub id: OperationId,
+	pub change: Change,
+}
+
+/// Every operation has its own ID, so operations with the same ID are the same operation.
+impl PartialEq for LoggedOperation {
+	fn eq(&self, other: &Self) -> bool {
+		self.id == other.id
+	}
+}
+
+/// What a replica in a collaboration session sends to a replica joining it, which the joining replica's log starts from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogState {
+	/// The document with the operations folded into it, before the operations of the log
+	pub base: Box<Document>,
+	/// The last operation folded into the base document
+	pub horizon: Option<OperationId>,
+	/// The version of the sending replica, including the operations folded into the base document
+	pub version: HashMap<ReplicaId, u64>,
+	/// The operations after the base document that the joining replica hasn't seen
+	pub operations: Vec<LoggedOperation>,
+}
+
+/// The base document results from the operations folded into it, so states with the same operations are the same.
+impl PartialEq for LogState {
+	fn eq(&self, other: &Self) -> bool {
+		self.horizon == other.horizon && self.version == other.version && self.operations == other.operations
+	}
+}
+
+/// What the replicas in a collaboration session send each other.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SessionMessage {
+	/// Asks the replicas in the session for their state, which is sent by a replica joining it
+	Join {
+		replica: ReplicaId,
+		version: HashMap<ReplicaId, u64>,
+	},
+	/// Answers a replica that joined the session
+	State {
+		to: ReplicaId,
+		state: Box<LogState>,
+	},
+	Operations(Vec<LoggedOperation>),
+	/// Tells the other replicas that every operation the replica makes from now on has a higher counter, so they can [compact](OperationLog::compact) their logs
+	Acknowledge {
+		replica: ReplicaId,
+		counter: u64,
+	},
+	/// Tells the other replicas that the replica left, so they stop waiting for its operations before compacting their logs
+	Leave {
+		replica: ReplicaId,
+	},
+}
+
+/// How merging an operation from another replica changed the log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeResult {
+	/// The operation was already in the log.
+	Duplicate,
+	/// The operation comes after every other one in the log, so it can be applied to the document directly.
+	Appended,
+	/// The operation happened concurrently with others that are already applied, so the document has to be [replayed](OperationLog::replay).
+	Inserted,
+}
+
+/// The changes made to a document by every replica editing it, which results in the same document for all of them once they've exchanged their changes.
+///
+/// Changes are kept in the total order of their [OperationId]s, so replaying the log onto the base document applies them identically no matter the order they arrived in.
+/// Layers are addressed by their random IDs and anchors by the IDs their subpaths hand out in order, so both are the same for every replica that applies the same operations.
+/// An operation whose target was removed by a concurrent operation ordered before it fails to apply and has no effect.
+///
+/// The log is [compacted](OperationLog::compact) as the replicas acknowledge each other's operations, so it only holds those that a concurrent operation could still be ordered before.
+#[derive(Debug, Clone, Default)]
+pub struct OperationLog {
+	replica: ReplicaId,
+	/// The highest counter of any operation seen by this replica
+	clock: u64,
+	/// The highest counter of the operations seen or acknowledged by each replica, including this one
+	seen: HashMap<ReplicaId, u64>,
+	/// The document before the first operation of the log, which is taken when that operation is recorded or merged
+	base: Option<Document>,
+	/// The last operation folded into the base document
+	horizon: Option<OperationId>,
+	/// Sorted by their IDs
+	operations: Vec<LoggedOperation>,
+}
+
+impl OperationLog {
+	pub fn new(replica: ReplicaId) -> Self {
+		Self { replica, ..Default::default() }
+	}
+
+	/// Starts the log of a replica joining a session from the state sent by one of the replicas already in it.
+	pub fn joined(replica: ReplicaId, state: LogState) -> Self {
+		Self {
+			replica,
+			clock: state.version.values().copied().max().unwrap_or_default(),
+			seen: state.version,
+			base: Some(*state.base),
+			horizon: state.horizon,
+			operations: state.operations,
+		}
+	}
+
+	pub fn replica(&self) -> ReplicaId {
+		self.replica
+	}
+
+	/// The highest counter of any operation seen by this replica, which those it makes next are higher than.
+	pub fn clock(&self) -> u64 {
+		self.clock
+	}
+
+	pub fn operations(&self) -> &[LoggedOperation] {
+		&self.operations
+	}
+
+	/// Records an operation that this replica is about to apply to the document, returning it as it's sent to the other replicas.
+	/// Nothing is recorded for operations that only change how this replica views the document, like panning the canvas.
+	pub fn record(&mut self, document: &Document, operation: &Operation) -> Option<LoggedOperation> {
+		if changes_only_view(operation) {
+			return None;
+		}
+
+		Some(self.log(document, Change::Operation(operation.clone())))
+	}
+
+	/// Records that this replica replaced the previous document with the snapshot, like by undoing, returning it as it's sent to the other replicas.
+	pub fn record_snapshot(&mut self, previous: &Document, snapshot: &Document) -> LoggedOperation {
+		self.log(previous, Change::Snapshot(Box::new(snapshot.clone())))
+	}
+
+	fn log(&mut self, document: &Document, change: Change) -> LoggedOperation {
+		self.base.get_or_insert_with(|| document.clone());
+		// The counter is higher than that of any operation seen so far, so the operation belongs at the end of the log
+		self.clock += 1;
+		let logged = LoggedOperation {
+			id: OperationId {
+				counter: self.clock,
+				replica: self.replica,
+			},
+			change,
+		};
+		self.seen.insert(self.replica, self.clock);
+		self.operations.push(logged.clone());
+
+		logged
+	}
+
+	/// Adds an operation made by another replica to the log, in its place among the operations made concurrently with it.
+	pub fn merge(&mut self, document: &Document, logged: LoggedOperation) -> MergeResult {
+		self.clock = self.clock.max(logged.id.counter);
+		let seen = self.seen.entry(logged.id.replica).or_insert(0);
+		let already_seen = logged.id.counter <= *seen;
+		*seen = (*seen).max(logged.id.counter);
+
+		if already_seen && self.horizon.is_some_and(|horizon| logged.id <= horizon) {
+			return MergeResult::Duplicate;
+		}
+
+		match self.operations.binary_search_by_key(&logged.id, |operation| operation.id) {
+			Ok(_) => MergeResult::Duplicate,
+			Err(index) => {
+				self.base.get_or_insert_with(|| document.clone());
+				let appended = index == self.operations.len();
+				self.operations.insert(index, logged);
+
+				if appended {
+					MergeResult::Appended
+				} else {
+					MergeResult::Inserted
+				}
+			}
+		}
+	}
+
+	/// Applies the changes of the log in order to the base document, keeping how the given current document is viewed by this replica.
+	/// Returns `None` if the log is empty, in which case the current document is already up to date.
+	pub fn replay(&self, current: &Document, font_cache: &FontCache) -> Option<Document> {
+		let mut document = self.base.clone()?;
+		for logged in &self.operations {
+			// Failing means an earlier operation removed or changed the target, which takes precedence
+			if let Err(error) = logged.change.apply(&mut document, font_cache) {
+				debug!("Skipped replaying the operation {:?}: {:?}", logged.id, error);
+			}
+		}
+
+		document.root.transform = current.root.transform;
+		document.viewport_renderer = current.viewport_renderer;
+		document.render_quality = current.render_quality;
+
+		Some(document)
+	}
+
+	/// The highest counter of the operations seen from each replica, which another replica compares against to find the operations it's missing.
+	/// This relies on each replica's operations arriving in the order they were made.
+	pub fn version(&self) -> HashMap<ReplicaId, u64> {
+		self.seen.clone()
+	}
+
+	/// The operations in the log that are newer than those in the given version of another replica's log.
+	/// Operations already folded into the base document aren't included.
+	pub fn operations_after<'a>(&'a self, version: &'a HashMap<ReplicaId, u64>) -> impl Iterator<Item = &'a LoggedOperation> + 'a {
+		self.operations.iter().filter(|logged| logged.id.counter > version.get(&logged.id.replica).copied().unwrap_or(0))
+	}
+
+	/// The state that a replica joining the session with the given version starts from, made of the base document and the operations after it that the replica is missing.
+	pub fn state(&self, current: &Document, version: &HashMap<ReplicaId, u64>) -> LogState {
+		LogState {
+			// Without a base document the log is empty, so the current document is the base
+			base: Box::new(self.base.clone().unwrap_or_else(|| current.clone())),
+			horizon: self.horizon,
+			version: self.version(),
+			operations: self.operations_after(version).cloned().collect(),
+		}
+	}
+
+	/// Notes that every operation the replica makes from now on has a higher counter than the given one.
+	/// This relies on the replica's acknowledgement arriving after the operations it made before it.
+	pub fn acknowledge(&mut self, replica: ReplicaId, counter: u64) {
+		self.clock = self.clock.max(counter);
+		let seen = self.seen.entry(replica).or_insert(0);
+		*seen = (*seen).max(counter);
+	}
+
+	/// Stops waiting for the operations of a replica that left the session before compacting the log.
+	pub fn forget(&mut self, replica: ReplicaId) {
+		if replica != self.replica {
+			self.seen.remove(&replica);
+		}
+	}
+
+	/// Folds the operations that nothing can be ordered before anymore into the base document.
+	/// The counters of each replica only grow, so once every other replica has made or acknowledged an operation with a higher counter, no operation ordered before it can still arrive.
+	/// Until another replica has been seen, nothing is folded, since it could still send operations ordered before any of them.
+	pub fn compact(&mut self, font_cache: &FontCache) {
+		let mut others = self.seen.iter().filter(|&(&replica, _)| replica != self.replica).map(|(_, &counter)| counter).peekable();
+		if others.peek().is_none() {
+			return;
+		}
+		let stable = others.min().unwrap_or_default();
+		let count = self.operations.partition_point(|logged| logged.id.counter < stable);
+
+		let base = match &mut self.base {
+			Some(base) if count > 0 => base,
+			_ => return,
+		};
+		for logged in self.operations.drain(..count) {
+			if let Err(error) = logged.change.apply(base, font_cache) {
+				debug!("Skipped compacting the operation {:?}: {:?}", logged.id, error);
+			}
+			self.horizon = Some(logged.id);
+		}
+	}
+}
+
+/// Whether the operation changes how a replica views the document rather than the document itself.
+fn changes_only_view(operation: &Operation) -> bool {
+	match operation {
+		Operation::SetLayerTransform { path, .. } => path.is_empty(),
+		Operation::SetViewportRenderer { .. } | Operation::SetRenderQuality { .. } => true,
+		_ => false,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::layers::style::PathStyle;
+	use crate::LayerId;
+
+	use glam::DAffine2;
+
+	fn add_rect(id: LayerId) -> Operation {
+		Operation::AddRect {
+			path: vec![id],
+			insert_index: -1,
+			transform: DAffine2::IDENTITY.to_cols_array(),
+			style: PathStyle::default(),
+		}
+	}
+
+	/// Applies an operation locally, like an editor does, returning it as it's sent to the other replicas.
+	fn apply(log: &mut OperationLog, document: &mut Document, operation: Operation) -> LoggedOperation {
+		let logged = log.record(document, &operation).unwrap();
+		let _ = document.handle_operation(operation, &FontCache::default());
+		logged
+	}
+
+	/// Receives an operation from another replica, like an editor does.
+	fn receive(log: &mut OperationLog, document: &mut Document, logged: LoggedOperation) {
+		let change = logged.change.clone();
+		match log.merge(document, logged) {
+			MergeResult::Duplicate => (),
+			MergeResult::Appended => {
+				let _ = change.apply(document, &FontCache::default());
+			}
+			MergeResult::Inserted => *document = log.replay(document, &FontCache::default()).unwrap(),
+		}
+	}
+
+	fn layer_ids(document: &Document) -> Vec<LayerId> {
+		document.folder(&[]).unwrap().layer_ids.clone()
+	}
+
+	#[test]
+	fn concurrent_operations_converge() {
+		let (mut log_a, mut document_a) = (OperationLog::new(1), Document::default());
+		let (mut log_b, mut document_b) = (OperationLog::new(2), Document::default());
+
+		let from_a = apply(&mut log_a, &mut document_a, add_rect(10));
+		let from_b = apply(&mut log_b, &mut document_b, add_rect(20));
+
+		receive(&mut log_a, &mut document_a, from_b);
+		receive(&mut log_b, &mut document_b, from_a);
+
+		assert_eq!(layer_ids(&document_a), vec![10, 20]);
+		assert_eq!(layer_ids(&document_a), layer_ids(&document_b));
+		assert_eq!(log_a.operations(), log_b.operations());
+	}
+
+	#[test]
+	fn operation_on_concurrently_deleted_layer_has_no_effect() {
+		let (mut log_a, mut document_a) = (OperationLog::new(1), Document::default());
+		let (mut log_b, mut document_b) = (OperationLog::new(2), Document::default());
+
+		let created = apply(&mut log_a, &mut document_a, add_rect(10));
+		receive(&mut log_b, &mut document_b, created);
+
+		let deleted = apply(&mut log_a, &mut document_a, Operation::DeleteLayer { path: vec![10] });
+		let renamed = apply(
+			&mut log_b,
+			&mut document_b,
+			Operation::SetLayerName {
+				path: vec![10],
+				name: "Renamed".into(),
+			},
+		);
+
+		receive(&mut log_a, &mut document_a, renamed);
+		receive(&mut log_b, &mut document_b, deleted);
+
+		assert!(document_a.layer(&[10]).is_err());
+		assert!(document_b.layer(&[10]).is_err());
+	}
+
+	#[test]
+	fn version_finds_missing_operations() {
+		let (mut log_a, mut document_a) = (OperationLog::new(1), Document::default());
+		let mut log_b = OperationLog::new(2);
+
+		let first = apply(&mut log_a, &mut document_a, add_rect(10));
+		apply(&mut log_a, &mut document_a, add_rect(20));
+		assert_eq!(log_b.merge(&Document::default(), first.clone()), MergeResult::Appended);
+		assert_eq!(log_b.merge(&Document::default(), first), MergeResult::Duplicate);
+
+		let missing = log_a.operations_after(&log_b.version()).cloned().collect::<Vec<_>>();
+		assert_eq!(missing, log_a.operations()[1..].to_vec());
+		assert!(log_a.record(&document_a, &Operation::SetLayerTransform { path: vec![], transform: [0.; 6] }).is_none());
+	}
+
+	#[test]
+	fn snapshots_converge_with_concurrent_operations() {
+		let (mut log_a, mut document_a) = (OperationLog::new(1), Document::default());
+		let (mut log_b, mut document_b) = (OperationLog::new(2), Document::default());
+
+		let created = apply(&mut log_a, &mut document_a, add_rect(10));
+		receive(&mut log_b, &mut document_b, created);
+
+		// The first replica undoes creating the layer while the second one concurrently creates another
+		let previous = std::mem::take(&mut document_a);
+		let undone = log_a.record_snapshot(&previous, &document_a);
+		let from_b = apply(&mut log_b, &mut document_b, add_rect(20));
+
+		receive(&mut log_a, &mut document_a, from_b);
+		receive(&mut log_b, &mut document_b, undone);
+
+		// The snapshot is ordered first, so the layer created after it remains
+		assert_eq!(layer_ids(&document_a), vec![20]);
+		assert_eq!(layer_ids(&document_a), layer_ids(&document_b));
+	}
+
+	#[test]
+	fn joining_replica_starts_from_state() {
+		let (mut log_a, mut document_a) = (OperationLog::new(1), Document::default());
+		let (mut log_b, mut document_b) = (OperationLog::new(2), Document::default());
+
+		receive(&mut log_b, &mut document_b, apply(&mut log_a, &mut document_a, add_rect(10)));
+		receive(&mut log_a, &mut document_a, apply(&mut log_b, &mut document_b, add_rect(20)));
+		log_a.compact(&FontCache::default());
+
+		let log_c = OperationLog::joined(3, log_a.state(&document_a, &HashMap::new()));
+		let document_c = log_c.replay(&Document::default(), &FontCache::default()).unwrap();
+
+		assert_eq!(layer_ids(&document_c), layer_ids(&document_a));
+		assert_eq!(log_c.version(), log_a.version());
+		assert!(log_c.clock() >= log_a.clock());
+	}
+
+	#[test]
+	fn only_acknowledged_operations_are_compacted() {
+		let (mut log_a, mut document_a) = (OperationLog::new(1), Document::default());
+		let (mut log_b, mut document_b) = (OperationLog::new(2), Document::default());
+
+		for id in 0..10 {
+			let logged = apply(&mut log_a, &mut document_a, add_rect(id));
+			receive(&mut log_b, &mut document_b, logged);
+			log_a.compact(&FontCache::default());
+		}
+		// Nothing has been seen from the other replica, which could still send operations ordered before any of them
+		assert_eq!(log_a.operations().len(), 10);
+
+		log_a.acknowledge(log_b.replica(), log_b.clock());
+		log_a.compact(&FontCache::default());
+		assert_eq!(log_a.operations().len(), 1);
+
+		// Operations from the other replica still converge, and folded ones aren't merged twice
+		let from_b = apply(&mut log_b, &mut document_b, add_rect(1000));
+		receive(&mut log_a, &mut document_a, from_b.clone());
+		receive(&mut log_a, &mut document_a, from_b);
+		assert_eq!(log_a.merge(&document_a, log_b.operations()[0].clone()), MergeResult::Duplicate);
+
+		let replayed = log_a.replay(&document_a, &FontCache::default()).unwrap();
+		assert_eq!(layer_ids(&replayed), layer_ids(&document_a));
+		assert_eq!(layer_ids(&document_a), layer_ids(&document_b));
+	}
+}
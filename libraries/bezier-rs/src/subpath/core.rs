@@ -40,6 +40,16 @@ impl Subpath {
 		self.manipulator_groups.len()
 	}
 
+	/// Returns the [ManipulatorGroup]s contained within the `Subpath`.
+	pub fn manipulator_groups(&self) -> &[ManipulatorGroup] {
+		&self.manipulator_groups
+	}
+
+	/// Returns true if the `Subpath` connects its last [ManipulatorGroup] back to the first one.
+	pub fn closed(&self) -> bool {
+		self.closed
+	}
+
 	/// Returns an iterator of the [Bezier]s along the `Subpath`.
 	pub fn iter(&self) -> SubpathIter {
 		SubpathIter { sub_path: self, index: 0 }
@@ -1,5 +1,10 @@
 use super::*;
 
+use glam::DVec2;
+
+/// The number of straight lines each `Bezier` is approximated by when measuring the fraction of the length of a `Subpath` travelled to reach a point.
+pub(super) const LENGTH_FRACTION_STEPS: usize = 16;
+
 /// Functionality relating to looking up properties of the `Subpath` or points along the `Subpath`.
 impl Subpath {
 	/// Return the sum of the approximation of the length of each `Bezier` curve along the `Subpath`.
@@ -7,6 +12,73 @@ impl Subpath {
 	pub fn length(&self, num_subdivisions: Option<usize>) -> f64 {
 		self.iter().fold(0., |accumulator, bezier| accumulator + bezier.length(num_subdivisions))
 	}
+
+	/// Return an approximation of the area enclosed by the `Subpath`, treating it as closed, from a polygon through points along each `Bezier` curve.
+	/// The area is positive when the `Subpath` is traversed in a counter-clockwise direction in a y-up coordinate system, and negative otherwise.
+	pub fn signed_area(&self) -> f64 {
+		let mut points = self.iter().flat_map(|bezier| {
+			let mut lookup_table = bezier.compute_lookup_table(Some(8));
+			lookup_table.pop();
+			lookup_table
+		});
+		let first = match points.next() {
+			Some(point) => point,
+			None => return 0.,
+		};
+
+		let (last, double_area) = points.fold((first, 0.), |(previous, double_area), point| (point, double_area + previous.perp_dot(point)));
+		(double_area + last.perp_dot(first)) / 2.
+	}
+
+	/// Returns the points of the polyline approximating the `Subpath` which fractions of its length are measured along, with the length travelled to reach each of them.
+	fn length_polyline(&self) -> (Vec<DVec2>, Vec<f64>) {
+		let points: Vec<DVec2> = self.iter().flat_map(|bezier| bezier.compute_lookup_table(Some(LENGTH_FRACTION_STEPS))).collect();
+		let mut travelled = 0.;
+		let lengths = points
+			.iter()
+			.enumerate()
+			.map(|(index, point)| {
+				if index > 0 {
+					travelled += points[index - 1].distance(*point);
+				}
+				travelled
+			})
+			.collect();
+		(points, lengths)
+	}
+
+	/// Returns the point reached after travelling the given `fraction` of the length of the `Subpath`, along with the unit normal of the `Subpath` there.
+	/// Lengths are measured along straight lines through points spaced evenly along each `Bezier`. Returns `None` if the `Subpath` has no length.
+	pub fn evaluate_length_fraction(&self, fraction: f64) -> Option<(DVec2, DVec2)> {
+		let (points, lengths) = self.length_polyline();
+		let total_length = lengths.last().copied().filter(|&length| length > 0.)?;
+		let target = fraction.clamp(0., 1.) * total_length;
+
+		let segments: Vec<usize> = (0..points.len() - 1).filter(|&index| lengths[index + 1] > lengths[index]).collect();
+		let index = segments.iter().copied().find(|&index| lengths[index + 1] >= target).or_else(|| segments.last().copied())?;
+		let (start, end) = (points[index], points[index + 1]);
+		let point = start.lerp(end, ((target - lengths[index]) / (lengths[index + 1] - lengths[index])).clamp(0., 1.));
+		Some((point, (end - start).normalize().perp()))
+	}
+
+	/// Returns the fraction of the length of the `Subpath` travelled to reach its closest point to `point`, along with that closest point.
+	/// Lengths are measured like in [Subpath::evaluate_length_fraction]. Returns `None` if the `Subpath` has no length.
+	pub fn project_length_fraction(&self, point: DVec2) -> Option<(f64, DVec2)> {
+		let (points, lengths) = self.length_polyline();
+		let total_length = lengths.last().copied().filter(|&length| length > 0.)?;
+
+		(0..points.len() - 1)
+			.filter(|&index| lengths[index + 1] > lengths[index])
+			.map(|index| {
+				let (start, end) = (points[index], points[index + 1]);
+				let t = ((point - start).dot(end - start) / start.distance_squared(end)).clamp(0., 1.);
+				let closest = start.lerp(end, t);
+				let travelled = lengths[index] + t * (lengths[index + 1] - lengths[index]);
+				(closest.distance_squared(point), travelled / total_length, closest)
+			})
+			.min_by(|a, b| a.0.total_cmp(&b.0))
+			.map(|(_, fraction, closest)| (fraction, closest))
+	}
 }
 
 #[cfg(test)]
@@ -92,4 +164,34 @@ mod tests {
 		subpath.closed = true;
 		assert_eq!(subpath.length(None), linear_bezier.length(None) + quadratic_bezier.length(None) + cubic_bezier.length(None));
 	}
+
+	#[test]
+	fn length_fraction_round_trip() {
+		let subpath = Subpath::from_beziers(&[Bezier::from_linear_coordinates(0., 0., 10., 0.), Bezier::from_linear_coordinates(10., 0., 10., 30.)], false);
+
+		let (point, normal) = subpath.evaluate_length_fraction(0.5).unwrap();
+		assert!(point.abs_diff_eq(DVec2::new(10., 10.), 1e-6));
+		assert!(normal.abs_diff_eq(DVec2::new(-1., 0.), 1e-6));
+
+		let (fraction, closest) = subpath.project_length_fraction(DVec2::new(15., 10.)).unwrap();
+		assert!((fraction - 0.5).abs() < 1e-6);
+		assert!(closest.abs_diff_eq(point, 1e-6));
+	}
+
+	#[test]
+	fn signed_area_square() {
+		let corners = [DVec2::new(0., 0.), DVec2::new(10., 0.), DVec2::new(10., 10.), DVec2::new(0., 10.)];
+		let manipulator_groups: Vec<_> = corners
+			.into_iter()
+			.map(|anchor| ManipulatorGroup {
+				anchor,
+				in_handle: None,
+				out_handle: None,
+			})
+			.collect();
+
+		assert!((Subpath::new(manipulator_groups.clone(), true).signed_area() - 100.).abs() < 1e-6);
+		let reversed = manipulator_groups.into_iter().rev().collect();
+		assert!((Subpath::new(reversed, true).signed_area() + 100.).abs() < 1e-6);
+	}
 }
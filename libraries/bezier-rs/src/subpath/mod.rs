@@ -1,6 +1,7 @@
 mod core;
 mod lookup;
 mod structs;
+mod transform;
 pub use structs::*;
 
 use crate::Bezier;
@@ -8,6 +9,7 @@ use crate::Bezier;
 use std::ops::{Index, IndexMut};
 
 /// Structure used to represent a path composed of [Bezier] curves.
+#[derive(Clone, Debug, PartialEq)]
 pub struct Subpath {
 	manipulator_groups: Vec<ManipulatorGroup>,
 	closed: bool,
@@ -1,8 +1,31 @@
 use glam::DVec2;
 
 /// Structure used to represent a single anchor with up to two optional associated handles along a `Subpath`
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct ManipulatorGroup {
 	pub anchor: DVec2,
 	pub in_handle: Option<DVec2>,
 	pub out_handle: Option<DVec2>,
 }
+
+/// Enum to represent the shape used to close off the ends of the outline of an open `Subpath`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Cap {
+	/// End the outline with a straight line across the end of the `Subpath`.
+	Butt,
+	/// Extend the outline past the end of the `Subpath` by half its width, ending it with a straight line.
+	Square,
+	/// End the outline with a semicircle around the end of the `Subpath`.
+	Round,
+}
+
+/// Enum to represent the shape used to connect consecutive curves of an offset `Subpath`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Join {
+	/// Connect the curves with a straight line.
+	Bevel,
+	/// Extend the curves along their tangents until they meet, falling back to a bevel if the tangents are parallel.
+	Miter,
+	/// Connect the curves with a circular arc around the original corner.
+	Round,
+}
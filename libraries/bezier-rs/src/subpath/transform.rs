@@ -0,0 +1,777 @@
+use super::lookup::LENGTH_FRACTION_STEPS;
+use super::*;
+use crate::consts::MAX_ABSOLUTE_DIFFERENCE;
+use crate::utils::{do_rectangles_overlap, line_intersection};
+use crate::ProjectionOptions;
+
+use glam::DVec2;
+
+/// Returns the handles of the `Bezier` as a cubic segment, elevating the degree of quadratic segments.
+fn cubic_handles(bezier: &Bezier) -> [Option<DVec2>; 2] {
+	match (bezier.handle_start(), bezier.handle_end()) {
+		(Some(handle), None) => [Some(bezier.start() + (handle - bezier.start()) * (2. / 3.)), Some(bezier.end() + (handle - bezier.end()) * (2. / 3.))],
+		(handle_start, handle_end) => [handle_start, handle_end],
+	}
+}
+
+/// Returns the `Bezier` segment between two adjacent [ManipulatorGroup]s.
+fn segment(start: &ManipulatorGroup, end: &ManipulatorGroup) -> Bezier {
+	match (start.out_handle, end.in_handle) {
+		(Some(handle1), Some(handle2)) => Bezier::from_cubic_dvec2(start.anchor, handle1, handle2, end.anchor),
+		(Some(handle), None) | (None, Some(handle)) => Bezier::from_quadratic_dvec2(start.anchor, handle, end.anchor),
+		(None, None) => Bezier::from_linear_dvec2(start.anchor, end.anchor),
+	}
+}
+
+/// Returns the curves connecting the end of an offset curve at `start` with the start of the next offset curve at `end`, around the original `corner`.
+/// A miter reaching further than `miter_limit` times the `distance` from the corner is replaced by a bevel, like the `stroke-miterlimit` of SVG.
+fn join_curves(start: DVec2, start_tangent: DVec2, end: DVec2, end_tangent: DVec2, corner: DVec2, distance: f64, join: Join, miter_limit: f64) -> Vec<Bezier> {
+	if start.abs_diff_eq(end, MAX_ABSOLUTE_DIFFERENCE) {
+		return Vec::new();
+	}
+
+	// Offset curves overlap on the inner side of a corner, where they are simply connected with a straight line
+	let turn = start_tangent.perp_dot(end_tangent);
+	let outer_corner = turn * distance < 0.;
+	match join {
+		Join::Miter if outer_corner && turn.abs() > MAX_ABSOLUTE_DIFFERENCE => {
+			let miter = line_intersection(start, start_tangent, end, end_tangent);
+			if miter.distance(corner) <= distance.abs() * miter_limit {
+				vec![Bezier::from_linear_dvec2(start, miter), Bezier::from_linear_dvec2(miter, end)]
+			} else {
+				vec![Bezier::from_linear_dvec2(start, end)]
+			}
+		}
+		Join::Round if outer_corner => {
+			// Approximate the circular arc with a single cubic segment
+			let angle = (start - corner).angle_between(end - corner).abs();
+			let handle_length = distance.abs() * 4. / 3. * (angle / 4.).tan();
+			vec![Bezier::from_cubic_dvec2(start, start + start_tangent * handle_length, end - end_tangent * handle_length, end)]
+		}
+		_ => vec![Bezier::from_linear_dvec2(start, end)],
+	}
+}
+
+/// Returns the curves closing off the outline of an open subpath from `start` on one side of its end to `end` on the other side,
+/// where `direction` is the unit tangent pointing away from the subpath at its end.
+fn cap_curves(start: DVec2, end: DVec2, direction: DVec2, cap: Cap) -> Vec<Bezier> {
+	let radius = start.distance(end) / 2.;
+	match cap {
+		Cap::Butt => vec![Bezier::from_linear_dvec2(start, end)],
+		Cap::Square => {
+			let extension = direction * radius;
+			vec![
+				Bezier::from_linear_dvec2(start, start + extension),
+				Bezier::from_linear_dvec2(start + extension, end + extension),
+				Bezier::from_linear_dvec2(end + extension, end),
+			]
+		}
+		Cap::Round => {
+			// Approximate the semicircle with two quarter circles, each as a cubic segment
+			let handle_length = radius * 4. / 3. * (std::f64::consts::PI / 8.).tan();
+			let across = (end - start).normalize_or_zero();
+			let middle = (start + end) / 2. + direction * radius;
+			vec![
+				Bezier::from_cubic_dvec2(start, start + direction * handle_length, middle - across * handle_length, middle),
+				Bezier::from_cubic_dvec2(middle, middle + across * handle_length, end + direction * handle_length, end),
+			]
+		}
+	}
+}
+
+/// Returns the unit direction the `Bezier` leaves its start in, skipping handles which coincide with the start, or zero if it's a single point.
+fn start_direction(bezier: &Bezier) -> DVec2 {
+	let start = bezier.start();
+	bezier
+		.get_points()
+		.skip(1)
+		.map(|point| point - start)
+		.find(|direction| direction.length() > MAX_ABSOLUTE_DIFFERENCE)
+		.map_or(DVec2::ZERO, DVec2::normalize)
+}
+
+/// Returns the unit direction the `Bezier` arrives at its end in, skipping handles which coincide with the end, or zero if it's a single point.
+fn end_direction(bezier: &Bezier) -> DVec2 {
+	let end = bezier.end();
+	let points: Vec<DVec2> = bezier.get_points().collect();
+	points
+		.into_iter()
+		.rev()
+		.skip(1)
+		.map(|point| end - point)
+		.find(|direction| direction.length() > MAX_ABSOLUTE_DIFFERENCE)
+		.map_or(DVec2::ZERO, DVec2::normalize)
+}
+
+/// Returns the `t` value at which the length of the `Bezier` from its start reaches `distance`, measured along a polyline approximating the curve.
+fn parametric_at_distance(bezier: &Bezier, distance: f64) -> f64 {
+	const STEPS: usize = 64;
+
+	let points = bezier.compute_lookup_table(Some(STEPS));
+	let mut travelled = 0.;
+	for (index, pair) in points.windows(2).enumerate() {
+		let step = pair[0].distance(pair[1]);
+		if travelled + step >= distance {
+			let fraction = if step > 0. { (distance - travelled) / step } else { 0. };
+			return (index as f64 + fraction) / STEPS as f64;
+		}
+		travelled += step;
+	}
+	1.
+}
+
+/// Returns whether the `Bezier` has collapsed into a single point.
+fn is_degenerate(bezier: &Bezier) -> bool {
+	let [min, max] = bezier.bounding_box();
+	min.abs_diff_eq(max, MAX_ABSOLUTE_DIFFERENCE)
+}
+
+/// Returns the first intersection between two of the curves of a closed loop, as the index and `t` value on each of the two curves.
+/// The anchors shared by neighboring curves aren't counted as intersections.
+fn first_intersection(beziers: &[Bezier]) -> Option<(usize, f64, usize, f64)> {
+	// Intersections are found with a smaller error than shared anchors are distinguished by, so they aren't mistaken for each other
+	const ERROR: f64 = MAX_ABSOLUTE_DIFFERENCE;
+	const SHARED_ANCHOR_DISTANCE: f64 = 10. * ERROR;
+
+	let len = beziers.len();
+	for index in 0..len {
+		for other_index in index + 1..len {
+			let (bezier, other) = (&beziers[index], &beziers[other_index]);
+			if !do_rectangles_overlap(bezier.bounding_box(), other.bounding_box()) {
+				continue;
+			}
+
+			let mut shared_anchors = Vec::new();
+			if other_index == index + 1 {
+				shared_anchors.push(bezier.end());
+			}
+			if index == 0 && other_index == len - 1 {
+				shared_anchors.push(bezier.start());
+			}
+
+			for t in bezier.intersections(other, Some(ERROR)) {
+				let point = bezier.evaluate(t);
+				if shared_anchors.iter().any(|anchor| anchor.distance(point) < SHARED_ANCHOR_DISTANCE) {
+					continue;
+				}
+				return Some((index, t, other_index, other.project(point, ProjectionOptions::default())));
+			}
+		}
+	}
+	None
+}
+
+/// Functionality that transforms a `Subpath`, such as offset and simplify.
+impl Subpath {
+	/// Create a `Subpath` from a list of consecutive `Bezier` curves.
+	/// Curves which don't start at the end of the previous curve are connected with a straight line.
+	pub fn from_beziers(beziers: &[Bezier], closed: bool) -> Self {
+		let mut manipulator_groups: Vec<ManipulatorGroup> = Vec::new();
+		for bezier in beziers {
+			let [handle_start, handle_end] = cubic_handles(bezier);
+			match manipulator_groups.last_mut() {
+				Some(last) if last.anchor.abs_diff_eq(bezier.start(), MAX_ABSOLUTE_DIFFERENCE) => last.out_handle = handle_start,
+				_ => manipulator_groups.push(ManipulatorGroup {
+					anchor: bezier.start(),
+					in_handle: None,
+					out_handle: handle_start,
+				}),
+			}
+			manipulator_groups.push(ManipulatorGroup {
+				anchor: bezier.end(),
+				in_handle: handle_end,
+				out_handle: None,
+			});
+		}
+
+		// The closing segment is implied by a closed `Subpath`, so the final anchor is merged with the first one
+		if closed
+			&& manipulator_groups.len() > 2
+			&& manipulator_groups[0]
+				.anchor
+				.abs_diff_eq(manipulator_groups[manipulator_groups.len() - 1].anchor, MAX_ABSOLUTE_DIFFERENCE)
+		{
+			let last = manipulator_groups.pop().unwrap();
+			manipulator_groups[0].in_handle = last.in_handle;
+		}
+
+		let closed = closed && manipulator_groups.len() > 1;
+		Subpath::new(manipulator_groups, closed)
+	}
+
+	/// Returns a `Subpath` offset by the provided `distance` along the normal of each curve, with gaps at the corners filled using the given [Join].
+	/// A positive distance offsets to the side of the normals, which is the left side when the `Subpath` is traversed in a counter-clockwise direction in a y-up coordinate system.
+	pub fn offset(&self, distance: f64, join: Join) -> Subpath {
+		self.offset_with_miter_limit(distance, join, f64::INFINITY)
+	}
+
+	/// Returns the [offset](Subpath::offset) of the `Subpath`, replacing the miters reaching further than `miter_limit` times the `distance` from their corner by bevels.
+	pub fn offset_with_miter_limit(&self, distance: f64, join: Join, miter_limit: f64) -> Subpath {
+		let offset_curves: Vec<(DVec2, Vec<Bezier>)> = self.iter().map(|bezier| (bezier.start(), bezier.offset(distance))).filter(|(_, curves)| !curves.is_empty()).collect();
+
+		let mut beziers: Vec<Bezier> = Vec::new();
+		for (corner, curves) in &offset_curves {
+			if let Some(previous) = beziers.last().copied() {
+				beziers.extend(join_curves(
+					previous.end(),
+					previous.tangent(1.),
+					curves[0].start(),
+					curves[0].tangent(0.),
+					*corner,
+					distance,
+					join,
+					miter_limit,
+				));
+			}
+			beziers.extend(curves);
+		}
+		if let (true, Some(last), Some((corner, curves))) = (self.closed, beziers.last().copied(), offset_curves.first()) {
+			beziers.extend(join_curves(
+				last.end(),
+				last.tangent(1.),
+				curves[0].start(),
+				curves[0].tangent(0.),
+				*corner,
+				distance,
+				join,
+				miter_limit,
+			));
+		}
+
+		Subpath::from_beziers(&beziers, self.closed)
+	}
+
+	/// Returns the indices of the curves arriving at and leaving the [ManipulatorGroup] at `index`, if it has a curve on both sides.
+	fn corner_curves(&self, index: usize) -> Option<(usize, usize)> {
+		let len = self.len();
+		if index >= len || len < 2 {
+			return None;
+		}
+		match (self.closed, index) {
+			(true, _) => Some(((index + len - 1) % len, index)),
+			(false, index) if index > 0 && index < len - 1 => Some((index - 1, index)),
+			_ => None,
+		}
+	}
+
+	/// Returns the unit vectors pointing from the anchor of the [ManipulatorGroup] at `index` along the curves arriving at and leaving it,
+	/// if it has a curve on both sides. The corner at the anchor lies between the two directions.
+	pub fn corner_directions(&self, index: usize) -> Option<[DVec2; 2]> {
+		let (incoming, outgoing) = self.corner_curves(index)?;
+		let beziers: Vec<Bezier> = self.iter().collect();
+		let directions = [-end_direction(&beziers[incoming]), start_direction(&beziers[outgoing])];
+		if directions.contains(&DVec2::ZERO) {
+			return None;
+		}
+		Some(directions)
+	}
+
+	/// Returns the `Subpath` with the corners at its anchors rounded off by circular arcs with the given `radii`, one per [ManipulatorGroup].
+	/// The arcs are made smaller where they would take up more than half of either neighboring curve. Anchors without a curve on both sides,
+	/// or whose curves already meet smoothly, are left as they are.
+	pub fn round_corners(&self, radii: &[f64]) -> Subpath {
+		// A turn this small counts as the curves meeting smoothly
+		const MIN_TURN: f64 = 1e-3;
+
+		let beziers: Vec<Bezier> = self.iter().collect();
+		// The range of each curve left between the arcs at its ends
+		let mut ranges = vec![(0., 1.); beziers.len()];
+		// The arc replacing the corner at each anchor
+		let mut arcs: Vec<Option<Bezier>> = vec![None; self.len()];
+
+		for (index, &radius) in radii.iter().enumerate().filter(|&(_, &radius)| radius > 0.) {
+			let (incoming, outgoing) = match self.corner_curves(index) {
+				Some(curves) => curves,
+				None => continue,
+			};
+			let (incoming_bezier, outgoing_bezier) = (&beziers[incoming], &beziers[outgoing]);
+			let (incoming_direction, outgoing_direction) = (end_direction(incoming_bezier), start_direction(outgoing_bezier));
+			if incoming_direction == DVec2::ZERO || outgoing_direction == DVec2::ZERO {
+				continue;
+			}
+			let turn = incoming_direction.angle_between(outgoing_direction).abs();
+			if turn < MIN_TURN || turn > std::f64::consts::PI - MIN_TURN {
+				continue;
+			}
+
+			// The distance from the corner to where the arc touches each curve, limited to half of the curves
+			let (incoming_length, outgoing_length) = (incoming_bezier.length(None), outgoing_bezier.length(None));
+			let tangent_length = (radius * (turn / 2.).tan()).min(incoming_length / 2.).min(outgoing_length / 2.);
+			let radius = tangent_length / (turn / 2.).tan();
+
+			let start_t = parametric_at_distance(incoming_bezier, incoming_length - tangent_length);
+			let end_t = parametric_at_distance(outgoing_bezier, tangent_length);
+			ranges[incoming].1 = start_t;
+			ranges[outgoing].0 = end_t;
+
+			let (start, end) = (incoming_bezier.evaluate(start_t), outgoing_bezier.evaluate(end_t));
+			let start_tangent = Some(incoming_bezier.tangent(start_t)).filter(|tangent| tangent.is_finite()).unwrap_or(incoming_direction);
+			let end_tangent = Some(outgoing_bezier.tangent(end_t)).filter(|tangent| tangent.is_finite()).unwrap_or(outgoing_direction);
+			// Approximate the circular arc with a single cubic segment
+			let handle_length = radius * 4. / 3. * (turn / 4.).tan();
+			arcs[index] = Some(Bezier::from_cubic_dvec2(start, start + start_tangent * handle_length, end - end_tangent * handle_length, end));
+		}
+
+		let len = self.len();
+		let mut rounded = Vec::new();
+		for (index, bezier) in beziers.iter().enumerate() {
+			let (start_t, end_t) = ranges[index];
+			// Curves taken up entirely by the arcs at their ends are left out
+			let trimmed = bezier.trim(start_t, end_t);
+			if !is_degenerate(&trimmed) {
+				rounded.push(trimmed);
+			}
+			rounded.extend(arcs[(index + 1) % len]);
+		}
+		Subpath::from_beziers(&rounded, self.closed)
+	}
+
+	/// Returns the `Subpath` traversed in the opposite direction.
+	pub fn reverse(&self) -> Subpath {
+		let manipulator_groups = self
+			.manipulator_groups
+			.iter()
+			.rev()
+			.map(|group| ManipulatorGroup {
+				anchor: group.anchor,
+				in_handle: group.out_handle,
+				out_handle: group.in_handle,
+			})
+			.collect();
+		Subpath::new(manipulator_groups, self.closed)
+	}
+
+	/// Returns the outline of a stroke of the given `width` along the `Subpath` as closed `Subpath`s, with corners connected using the given [Join].
+	/// Miters reaching further than `miter_limit` times half the `width` from their corner are replaced by bevels.
+	/// A closed `Subpath` has an outline on each side, winding in opposite directions so the area between them is filled by the nonzero fill rule.
+	/// An open `Subpath` has a single outline around both sides, which is closed off around its ends with the given [Cap].
+	pub fn outline(&self, width: f64, join: Join, miter_limit: f64, cap: Cap) -> Vec<Subpath> {
+		let distance = width / 2.;
+		if self.closed {
+			let mut outlines = self.clean_offset_with_miter_limit(distance, join, miter_limit);
+			outlines.extend(self.reverse().clean_offset_with_miter_limit(distance, join, miter_limit));
+			return outlines;
+		}
+
+		let (left, right) = (
+			self.offset_with_miter_limit(distance, join, miter_limit),
+			self.reverse().offset_with_miter_limit(distance, join, miter_limit),
+		);
+		self.cap_outline(&left, &right, cap)
+	}
+
+	/// Returns the offset of the `Subpath` along the normals of its curves by a `distance` which varies with the fraction of the length of the `Subpath` travelled.
+	/// The curves are approximated by straight lines between points spaced evenly along them, which are measured like in [Subpath::evaluate_length_fraction].
+	/// The gaps at corners are filled using the given [Join], with miters reaching further than `miter_limit` times the local distance from their corner beveled.
+	pub fn variable_offset(&self, distance: impl Fn(f64) -> f64, join: Join, miter_limit: f64) -> Subpath {
+		let lookup_tables: Vec<(Bezier, Vec<DVec2>)> = self
+			.iter()
+			.filter(|bezier| !is_degenerate(bezier))
+			.map(|bezier| (bezier, bezier.compute_lookup_table(Some(LENGTH_FRACTION_STEPS))))
+			.collect();
+		let total_length: f64 = lookup_tables.iter().flat_map(|(_, points)| points.windows(2).map(|pair| pair[0].distance(pair[1]))).sum();
+		if total_length <= MAX_ABSOLUTE_DIFFERENCE {
+			return Subpath::from_beziers(&[], false);
+		}
+
+		let mut beziers: Vec<Bezier> = Vec::new();
+		let mut travelled = 0.;
+		for (bezier, points) in &lookup_tables {
+			if let Some(previous) = beziers.last().copied() {
+				let start = bezier.start() + start_direction(bezier).perp() * distance(travelled / total_length);
+				beziers.extend(join_curves(
+					previous.end(),
+					previous.tangent(1.),
+					start,
+					start_direction(bezier),
+					bezier.start(),
+					distance(travelled / total_length),
+					join,
+					miter_limit,
+				));
+			}
+
+			let last = points.len() - 1;
+			let mut offset_points = Vec::with_capacity(points.len());
+			for (index, &point) in points.iter().enumerate() {
+				if index > 0 {
+					travelled += points[index - 1].distance(point);
+				}
+				let direction = match index {
+					0 => start_direction(bezier),
+					_ if index == last => end_direction(bezier),
+					_ => (points[index + 1] - points[index - 1]).normalize_or_zero(),
+				};
+				offset_points.push(point + direction.perp() * distance(travelled / total_length));
+			}
+			beziers.extend(offset_points.windows(2).map(|pair| Bezier::from_linear_dvec2(pair[0], pair[1])).filter(|bezier| !is_degenerate(bezier)));
+		}
+		if let (true, Some(last), Some((first, _))) = (self.closed, beziers.last().copied(), lookup_tables.first()) {
+			let start = first.start() + start_direction(first).perp() * distance(0.);
+			beziers.extend(join_curves(last.end(), last.tangent(1.), start, start_direction(first), first.start(), distance(0.), join, miter_limit));
+		}
+
+		Subpath::from_beziers(&beziers, self.closed)
+	}
+
+	/// Returns the outline of a stroke along the `Subpath` as closed `Subpath`s, whose `width` varies with the fraction of the length of the `Subpath` travelled.
+	/// The outline is made from the [variable offset](Subpath::variable_offset) on each side, with the ends of an open `Subpath` closed off with the given [Cap].
+	/// Unlike with [Subpath::outline], the loops where the offset curves overlap around the inner side of corners are kept, since the nonzero fill rule fills them in with the rest of the stroke.
+	pub fn variable_outline(&self, width: impl Fn(f64) -> f64, join: Join, miter_limit: f64, cap: Cap) -> Vec<Subpath> {
+		let left = self.variable_offset(|fraction| width(fraction) / 2., join, miter_limit);
+		let right = self.variable_offset(|fraction| -width(fraction) / 2., join, miter_limit).reverse();
+		if self.closed {
+			return [left, right].into_iter().filter(|side| !side.is_empty()).collect();
+		}
+		self.cap_outline(&left, &right, cap)
+	}
+
+	/// Returns the outline of an open `Subpath` from the offsets along its `left` and `right` sides, the latter running backwards from the end of the `Subpath`,
+	/// closed off around the ends of the `Subpath` with the given [Cap].
+	fn cap_outline(&self, left: &Subpath, right: &Subpath, cap: Cap) -> Vec<Subpath> {
+		let (first, last) = match (self.iter().next(), self.iter().last()) {
+			(Some(first), Some(last)) => (first, last),
+			_ => return Vec::new(),
+		};
+		let (left_start, left_end, right_start, right_end) = match (
+			left.manipulator_groups.first(),
+			left.manipulator_groups.last(),
+			right.manipulator_groups.first(),
+			right.manipulator_groups.last(),
+		) {
+			(Some(left_start), Some(left_end), Some(right_start), Some(right_end)) => (left_start.anchor, left_end.anchor, right_start.anchor, right_end.anchor),
+			_ => return Vec::new(),
+		};
+
+		let mut beziers: Vec<Bezier> = left.iter().collect();
+		beziers.extend(cap_curves(left_end, right_start, last.tangent(1.), cap));
+		beziers.extend(right.iter());
+		beziers.extend(cap_curves(right_end, left_start, -first.tangent(0.), cap));
+		vec![Subpath::from_beziers(&beziers, true)]
+	}
+
+	/// Returns the dashes of the `Subpath` as open `Subpath`s, alternating between the dash and gap `lengths` of the pattern, which starts `offset` along it.
+	/// Like in SVG, a pattern with an odd number of lengths is repeated to get an even number, and a closed `Subpath` starts and ends a dash at its first anchor.
+	/// The `Subpath` is returned as is if the pattern has no length or any length is negative.
+	pub fn dash(&self, lengths: &[f64], offset: f64) -> Vec<Subpath> {
+		// Lengths are measured with as many steps as [parametric_at_distance] uses, so dashes line up with where they're split
+		const STEPS: usize = 64;
+
+		let mut pattern = lengths.to_vec();
+		if pattern.iter().sum::<f64>() <= 0. || pattern.iter().any(|&length| length < 0. || !length.is_finite()) || !offset.is_finite() {
+			return vec![self.clone()];
+		}
+		if pattern.len() % 2 == 1 {
+			pattern.extend(lengths);
+		}
+
+		let mut index = 0;
+		let mut remaining = pattern[0];
+		let mut skipped = offset.rem_euclid(pattern.iter().sum());
+		while skipped >= remaining {
+			skipped -= remaining;
+			index = (index + 1) % pattern.len();
+			remaining = pattern[index];
+		}
+		remaining -= skipped;
+
+		let mut dashes = Vec::new();
+		let mut current: Vec<Bezier> = Vec::new();
+		for bezier in self.iter() {
+			let length = bezier.length(Some(STEPS));
+			let mut start = 0.;
+			while length - start > remaining {
+				let end = start + remaining;
+				if index % 2 == 0 {
+					current.push(bezier.trim(parametric_at_distance(&bezier, start), parametric_at_distance(&bezier, end)));
+					dashes.push(Subpath::from_beziers(&current, false));
+					current.clear();
+				}
+				start = end;
+				index = (index + 1) % pattern.len();
+				remaining = pattern[index];
+			}
+			remaining -= length - start;
+			if index % 2 == 0 {
+				current.push(bezier.trim(parametric_at_distance(&bezier, start), 1.));
+			}
+		}
+		if !current.is_empty() {
+			dashes.push(Subpath::from_beziers(&current, false));
+		}
+		dashes
+	}
+
+	/// Returns a `Subpath` with fewer [ManipulatorGroup]s, removing anchors whenever the two curves meeting at it can be replaced by a single curve
+	/// which deviates from the original shape by at most `tolerance`. The handles of the remaining curves are scaled to span the merged curves.
+	pub fn simplify(&self, tolerance: f64) -> Subpath {
+		let mut manipulator_groups = self.manipulator_groups.clone();
+		let minimum_len = if self.closed { 3 } else { 2 };
+
+		// The endpoints of an open subpath are always kept
+		let mut index = if self.closed { 0 } else { 1 };
+		while manipulator_groups.len() > minimum_len && index < manipulator_groups.len() - if self.closed { 0 } else { 1 } {
+			let len = manipulator_groups.len();
+			let (previous, current, next) = (&manipulator_groups[(index + len - 1) % len], &manipulator_groups[index], &manipulator_groups[(index + 1) % len]);
+			let original = [segment(previous, current), segment(current, next)];
+
+			let [length1, length2] = original.map(|bezier| bezier.length(None));
+			let total_length = length1 + length2;
+			let merged_start = ManipulatorGroup {
+				out_handle: previous
+					.out_handle
+					.map(|handle| previous.anchor + (handle - previous.anchor) * (total_length / length1.max(f64::EPSILON))),
+				..*previous
+			};
+			let merged_end = ManipulatorGroup {
+				in_handle: next.in_handle.map(|handle| next.anchor + (handle - next.anchor) * (total_length / length2.max(f64::EPSILON))),
+				..*next
+			};
+			let merged = segment(&merged_start, &merged_end);
+
+			let within_tolerance = original.iter().all(|bezier| {
+				bezier.compute_lookup_table(Some(8)).into_iter().all(|point| {
+					let closest = merged.evaluate(merged.project(point, ProjectionOptions::default()));
+					closest.distance(point) <= tolerance
+				})
+			});
+			if within_tolerance {
+				let previous_index = (index + len - 1) % len;
+				let next_index = (index + 1) % len;
+				manipulator_groups[previous_index] = merged_start;
+				manipulator_groups[next_index] = merged_end;
+				manipulator_groups.remove(index);
+			} else {
+				index += 1;
+			}
+		}
+
+		Subpath::new(manipulator_groups, self.closed)
+	}
+
+	/// Returns the [offset](Subpath::offset) of the `Subpath` with the loops left where the offset curves overlap removed.
+	/// A closed offset is cut apart at its self-intersections, keeping only the loops which wind in the same direction as this `Subpath`
+	/// and reach the full `distance` away from it. The loops around the inner side of corners are removed this way, along with the whole offset
+	/// of a `Subpath` inset until it turns inside out. The offset of an open `Subpath` is returned as is.
+	pub fn clean_offset(&self, distance: f64, join: Join) -> Vec<Subpath> {
+		self.clean_offset_with_miter_limit(distance, join, f64::INFINITY)
+	}
+
+	/// Returns the [clean offset](Subpath::clean_offset) of the `Subpath`, replacing the miters reaching further than `miter_limit` times the `distance` from their corner by bevels.
+	pub fn clean_offset_with_miter_limit(&self, distance: f64, join: Join, miter_limit: f64) -> Vec<Subpath> {
+		let offset = self.offset_with_miter_limit(distance, join, miter_limit);
+		if !self.closed {
+			return vec![offset];
+		}
+
+		let counter_clockwise = self.signed_area() > 0.;
+		// The ends of the curves of an overlapping loop can be the full distance away where they intersect, so only their middles are checked,
+		// with some leeway for the approximation of offset curves
+		let reaches_distance = |subpath: &Subpath| {
+			subpath.iter().map(|bezier| bezier.evaluate(0.5)).any(|point| {
+				let closest_distance = self
+					.iter()
+					.map(|bezier| bezier.evaluate(bezier.project(point, ProjectionOptions::default())).distance(point))
+					.fold(f64::INFINITY, f64::min);
+				closest_distance >= distance.abs() * 0.99
+			})
+		};
+
+		let mut loops = Vec::new();
+		let mut pending = vec![offset.iter().filter(|bezier| !is_degenerate(bezier)).collect::<Vec<_>>()];
+		while let Some(beziers) = pending.pop() {
+			match first_intersection(&beziers) {
+				Some((index, t, other_index, other_t)) => {
+					// Split into the loop running from the intersection along the curves in between back to it, and the loop formed by the remaining curves
+					let [start, end] = beziers[index].split(t);
+					let [other_start, other_end] = beziers[other_index].split(other_t);
+					let inner = std::iter::once(end).chain(beziers[index + 1..other_index].iter().copied()).chain(std::iter::once(other_start));
+					let outer = std::iter::once(other_end)
+						.chain(beziers[other_index + 1..].iter().copied())
+						.chain(beziers[..index].iter().copied())
+						.chain(std::iter::once(start));
+					pending.push(inner.filter(|bezier| !is_degenerate(bezier)).collect());
+					pending.push(outer.filter(|bezier| !is_degenerate(bezier)).collect());
+				}
+				None => {
+					let subpath = Subpath::from_beziers(&beziers, true);
+					let area = subpath.signed_area();
+					if area.abs() > MAX_ABSOLUTE_DIFFERENCE && (area > 0.) == counter_clockwise && reaches_distance(&subpath) {
+						loops.push(subpath);
+					}
+				}
+			}
+		}
+		loops
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn square() -> Subpath {
+		let corners = [DVec2::new(0., 0.), DVec2::new(10., 0.), DVec2::new(10., 10.), DVec2::new(0., 10.)];
+		let manipulator_groups = corners
+			.into_iter()
+			.map(|anchor| ManipulatorGroup {
+				anchor,
+				in_handle: None,
+				out_handle: None,
+			})
+			.collect();
+		Subpath::new(manipulator_groups, true)
+	}
+
+	#[test]
+	fn offset_miter() {
+		let offset = square().offset(-1., Join::Miter).simplify(MAX_ABSOLUTE_DIFFERENCE);
+		assert_eq!(offset.len(), 4);
+		for corner in [DVec2::new(-1., -1.), DVec2::new(11., -1.), DVec2::new(11., 11.), DVec2::new(-1., 11.)] {
+			assert!(offset.manipulator_groups.iter().any(|group| group.anchor.abs_diff_eq(corner, MAX_ABSOLUTE_DIFFERENCE)));
+		}
+	}
+
+	#[test]
+	fn offset_bevel() {
+		// Each corner is cut off by a straight line, doubling the number of anchors
+		assert_eq!(square().offset(-1., Join::Bevel).len(), 8);
+	}
+
+	#[test]
+	fn clean_offset_inset() {
+		// Insetting a square past its center turns it inside out
+		assert!(square().clean_offset(6., Join::Miter).is_empty());
+
+		let inset = square().clean_offset(1., Join::Miter);
+		assert_eq!(inset.len(), 1);
+		assert!((inset[0].signed_area() - 64.).abs() < 1e-3);
+	}
+
+	#[test]
+	fn clean_offset_at_inner_corner() {
+		// An L shape, whose inner corner makes the curves of a bevelled outset overlap
+		let corners = [(0., 0.), (10., 0.), (10., 5.), (5., 5.), (5., 10.), (0., 10.)];
+		let manipulator_groups = corners
+			.into_iter()
+			.map(|(x, y)| ManipulatorGroup {
+				anchor: DVec2::new(x, y),
+				in_handle: None,
+				out_handle: None,
+			})
+			.collect();
+		let l_shape = Subpath::new(manipulator_groups, true);
+
+		let outset = l_shape.clean_offset(-1., Join::Bevel);
+		assert_eq!(outset.len(), 1);
+		assert!(outset[0].signed_area() > l_shape.signed_area());
+	}
+
+	#[test]
+	fn outline_open_line() {
+		let line = Subpath::from_bezier(Bezier::from_linear_coordinates(0., 0., 10., 0.));
+
+		let butt = line.outline(2., Join::Miter, 4., Cap::Butt);
+		assert_eq!(butt.len(), 1);
+		assert!((butt[0].signed_area().abs() - 20.).abs() < 1e-3);
+
+		let square = line.outline(2., Join::Miter, 4., Cap::Square);
+		assert!((square[0].signed_area().abs() - 24.).abs() < 1e-3);
+	}
+
+	#[test]
+	fn outline_miter_limit() {
+		// The miter of a right angle reaches a factor of sqrt(2) further than the offset distance
+		let corner = Subpath::from_beziers(&[Bezier::from_linear_coordinates(0., 0., 10., 0.), Bezier::from_linear_coordinates(10., 0., 10., 10.)], false);
+		let mitered = corner.outline(2., Join::Miter, 2., Cap::Butt);
+		let beveled = corner.outline(2., Join::Miter, 1.2, Cap::Butt);
+		assert!(mitered[0].iter().any(|bezier| bezier.end().abs_diff_eq(DVec2::new(11., -1.), MAX_ABSOLUTE_DIFFERENCE)));
+		assert!(!beveled[0].iter().any(|bezier| bezier.end().abs_diff_eq(DVec2::new(11., -1.), MAX_ABSOLUTE_DIFFERENCE)));
+		assert_eq!(beveled[0].len() + 1, mitered[0].len());
+	}
+
+	#[test]
+	fn variable_outline_taper() {
+		let line = Subpath::from_bezier(Bezier::from_linear_coordinates(0., 0., 10., 0.));
+
+		// Tapering from a width of 2 down to a point leaves a triangle
+		let taper = line.variable_outline(|fraction| 2. * (1. - fraction), Join::Miter, 4., Cap::Butt);
+		assert_eq!(taper.len(), 1);
+		assert!((taper[0].signed_area().abs() - 10.).abs() < 1e-3);
+
+		// A constant width matches the outline
+		let constant = line.variable_outline(|_| 2., Join::Miter, 4., Cap::Butt);
+		assert!((constant[0].signed_area().abs() - 20.).abs() < 1e-3);
+	}
+
+	#[test]
+	fn dash_line() {
+		let line = Subpath::from_beziers(&[Bezier::from_linear_coordinates(0., 0., 10., 0.)], false);
+		let dashes = line.dash(&[3., 1.], 1.);
+		let ends: Vec<_> = dashes.iter().map(|dash| (dash.iter().next().unwrap().start().x, dash.iter().last().unwrap().end().x)).collect();
+		let expected = [(0., 2.), (3., 6.), (7., 10.)];
+		assert_eq!(ends.len(), expected.len());
+		assert!(ends
+			.iter()
+			.zip(expected)
+			.all(|(&(start, end), (expected_start, expected_end))| (start - expected_start).abs() < 1e-3 && (end - expected_end).abs() < 1e-3));
+
+		// A pattern without length draws a solid line
+		assert_eq!(line.dash(&[0., 0.], 0.), vec![line.clone()]);
+	}
+
+	#[test]
+	fn dash_across_anchors() {
+		// A single length is used for both the dashes and the gaps, with the first dash running around the corner
+		let dashes = square().dash(&[12.], 0.);
+		assert_eq!(dashes.len(), 2);
+		assert_eq!(dashes[0].len(), 3);
+		assert!(!dashes[0].closed());
+	}
+
+	#[test]
+	fn outline_closed_square() {
+		let outlines = square().outline(2., Join::Miter, 4., Cap::Butt);
+		assert_eq!(outlines.len(), 2);
+
+		// The outlines wind in opposite directions, so the sum of their areas is the area of the stroke between them
+		let areas: Vec<f64> = outlines.iter().map(Subpath::signed_area).collect();
+		assert!(areas[0] * areas[1] < 0.);
+		assert!(((areas[0] + areas[1]).abs() - 80.).abs() < 1e-3);
+	}
+
+	#[test]
+	fn round_corners_square() {
+		let rounded = square().round_corners(&[2.; 4]);
+		assert_eq!(rounded.len(), 8);
+		// Each corner loses the area between a 2x2 square and a quarter circle
+		let expected_area = 100. - 4. * (4. - std::f64::consts::PI);
+		assert!((rounded.signed_area() - expected_area).abs() < 0.2);
+
+		// The arcs are limited to half of each side, turning the square into a circle
+		let circle = square().round_corners(&[20.; 4]);
+		assert!((circle.signed_area() - 25. * std::f64::consts::PI).abs() < 0.5);
+
+		// An anchor without a radius keeps its corner
+		let one_corner = square().round_corners(&[2., 0., 0., 0.]);
+		assert_eq!(one_corner.len(), 5);
+		assert!(!one_corner.manipulator_groups.iter().any(|group| group.anchor.abs_diff_eq(DVec2::ZERO, 1e-6)));
+		assert!(one_corner.manipulator_groups.iter().any(|group| group.anchor.abs_diff_eq(DVec2::new(10., 0.), 1e-6)));
+	}
+
+	#[test]
+	fn simplify_keeps_corners() {
+		let subpath = Subpath::from_beziers(
+			&[
+				Bezier::from_linear_coordinates(0., 0., 5., 0.),
+				Bezier::from_linear_coordinates(5., 0., 10., 0.),
+				Bezier::from_linear_coordinates(10., 0., 10., 10.),
+			],
+			false,
+		);
+		assert_eq!(subpath.len(), 4);
+		let simplified = subpath.simplify(0.1);
+		assert_eq!(simplified.len(), 3);
+		assert_eq!(simplified[1].anchor, DVec2::new(10., 0.));
+	}
+}
@@ -13,6 +13,8 @@ pub mod generic;
 pub mod ops;
 pub mod raster;
 pub mod structural;
+#[cfg(feature = "text")]
+pub mod text;
 pub mod value;
 
 pub trait Node<T> {
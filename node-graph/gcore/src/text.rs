@@ -0,0 +1,152 @@
+use alloc::vec::Vec;
+use bezier_rs::{ManipulatorGroup, Subpath};
+use glam::DVec2;
+use rustybuzz::{Face, UnicodeBuffer};
+use ttf_parser::{GlyphId, OutlineBuilder};
+
+use crate::Node;
+
+/// Collects the outlines of glyphs as subpaths, placing them at the current pen position.
+struct Builder {
+	subpaths: Vec<Subpath>,
+	manipulator_groups: Vec<ManipulatorGroup>,
+	position: DVec2,
+	/// Distance from the baseline to the top of the line in font units, used to flip the y-up glyph coordinates
+	ascender: f64,
+	scale: f64,
+}
+
+impl Builder {
+	fn point(&self, x: f32, y: f32) -> DVec2 {
+		self.position + DVec2::new(x as f64, self.ascender - y as f64) * self.scale
+	}
+
+	fn finish_contour(&mut self, closed: bool) {
+		let mut manipulator_groups = core::mem::take(&mut self.manipulator_groups);
+		// Glyph contours usually end on their starting point, which is implied by closing the subpath
+		if closed && manipulator_groups.len() > 2 && manipulator_groups[0].anchor == manipulator_groups[manipulator_groups.len() - 1].anchor {
+			let last = manipulator_groups.pop().unwrap();
+			manipulator_groups[0].in_handle = last.in_handle;
+		}
+		if manipulator_groups.len() > 1 {
+			self.subpaths.push(Subpath::new(manipulator_groups, closed));
+		}
+	}
+
+	fn push_anchor(&mut self, anchor: DVec2, in_handle: Option<DVec2>) {
+		self.manipulator_groups.push(ManipulatorGroup { anchor, in_handle, out_handle: None });
+	}
+}
+
+impl OutlineBuilder for Builder {
+	fn move_to(&mut self, x: f32, y: f32) {
+		self.finish_contour(false);
+		self.push_anchor(self.point(x, y), None);
+	}
+
+	fn line_to(&mut self, x: f32, y: f32) {
+		self.push_anchor(self.point(x, y), None);
+	}
+
+	fn quad_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+		let [handle, anchor] = [self.point(x1, y1), self.point(x2, y2)];
+		if let Some(last) = self.manipulator_groups.last_mut() {
+			last.out_handle = Some(handle);
+		}
+		self.push_anchor(anchor, None);
+	}
+
+	fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32) {
+		let [handle1, handle2, anchor] = [self.point(x1, y1), self.point(x2, y2), self.point(x3, y3)];
+		if let Some(last) = self.manipulator_groups.last_mut() {
+			last.out_handle = Some(handle1);
+		}
+		self.push_anchor(anchor, Some(handle2));
+	}
+
+	fn close(&mut self) {
+		self.finish_contour(true);
+	}
+}
+
+/// Lays out a string with a font and produces the outlines of the shaped glyphs as vector data, one subpath per glyph contour.
+///
+/// Lines are broken at newlines and, if a line width is given, between words which would exceed it.
+/// The first line starts at the origin, with the top of its glyphs at y = 0.
+pub struct TextNode<'a> {
+	/// The contents of a TrueType or OpenType font file
+	pub font_data: &'a [u8],
+	/// Font size in pixels
+	pub font_size: f64,
+	pub line_width: Option<f64>,
+}
+
+impl<'a> TextNode<'a> {
+	pub const fn new(font_data: &'a [u8], font_size: f64, line_width: Option<f64>) -> Self {
+		Self { font_data, font_size, line_width }
+	}
+}
+
+impl<'n, 'a, 's> Node<&'s str> for &'n TextNode<'a> {
+	type Output = Vec<Subpath>;
+	fn eval(self, text: &'s str) -> Self::Output {
+		// A font which can't be parsed produces no geometry, like a font which has not been loaded yet
+		let face = match Face::from_slice(self.font_data, 0) {
+			Some(face) => face,
+			None => return Vec::new(),
+		};
+		let scale = self.font_size / face.units_per_em() as f64;
+		let line_height = (face.ascender() as f64 - face.descender() as f64 + face.line_gap() as f64) * scale;
+
+		let mut builder = Builder {
+			subpaths: Vec::new(),
+			manipulator_groups: Vec::new(),
+			position: DVec2::ZERO,
+			ascender: face.ascender() as f64,
+			scale,
+		};
+		let mut pen = DVec2::ZERO;
+		let mut buffer = UnicodeBuffer::new();
+
+		for line in text.split('\n') {
+			let word_count = line.split(' ').count();
+			for (index, word) in line.split(' ').enumerate() {
+				buffer.push_str(word);
+				if index != word_count - 1 {
+					buffer.push_str(" ");
+				}
+				let glyphs = rustybuzz::shape(&face, &[], buffer);
+
+				let word_width = glyphs.glyph_positions().iter().map(|position| position.x_advance as f64).sum::<f64>() * scale;
+				if let Some(line_width) = self.line_width {
+					if pen.x > 0. && pen.x + word_width > line_width {
+						pen = DVec2::new(0., pen.y + line_height);
+					}
+				}
+
+				for (position, info) in glyphs.glyph_positions().iter().zip(glyphs.glyph_infos()) {
+					builder.position = pen + DVec2::new(position.x_offset as f64, -position.y_offset as f64) * scale;
+					face.outline_glyph(GlyphId(info.glyph_id as u16), &mut builder);
+					builder.finish_contour(false);
+					pen += DVec2::new(position.x_advance as f64, -position.y_advance as f64) * scale;
+				}
+
+				buffer = glyphs.clear();
+			}
+			pen = DVec2::new(0., pen.y + line_height);
+		}
+
+		builder.subpaths
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn text_without_font() {
+		let node = TextNode::new(&[], 24., None);
+		assert!((&node).eval("Graphite").is_empty());
+	}
+}
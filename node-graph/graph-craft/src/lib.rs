@@ -1,3 +1,7 @@
+pub mod node_registry;
+pub mod plugin;
+pub mod repeat;
+
 #[cfg(test)]
 mod tests {
 
@@ -111,6 +115,7 @@ mod tests {
 			]
 			.into_iter()
 			.collect(),
+			..Default::default()
 		};
 	}
 }
@@ -0,0 +1,419 @@
+//! The proto nodes that networks are built from, and the evaluation of networks using them with the [`NetworkExecutor`].
+
+use crate::plugin::{AbiValue, PluginError, PluginRegistry};
+use crate::repeat::{RepeatCollectNode, RepeatNode};
+
+use graphene::layers::vector::subpath::Subpath;
+use graphene_core::generic::FnNode;
+use graphene_core::raster::GrayscaleNode;
+use graphene_core::value::ValueNode;
+use graphene_core::Node;
+use graphene_std::adjustments::{Levels, LevelsNode};
+use graphene_std::document::{NodeNetwork, Value};
+use graphene_std::error::NodeResult;
+use graphene_std::executor::{proto_node_fn, Lambda, NetworkExecutor, ProtoNodeFn};
+use graphene_std::raster::{Image, ParallelMapImageNode};
+use graphene_std::script::{ScriptNode, ScriptValue};
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// A value passed between the nodes of a network evaluated with a [`NodeRegistry`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeValue {
+	F64(f64),
+	U32(u32),
+	Bool(bool),
+	String(String),
+	Bytes(Vec<u8>),
+	List(Vec<NodeValue>),
+	Image(Image),
+	Vector(Subpath),
+	/// The error of a node, which is passed on by the nodes using its output instead of evaluating them
+	Error(String),
+}
+
+impl NodeValue {
+	/// Convert the value of a value node, returning `None` for values of unsupported types.
+	pub fn from_value(value: &Value) -> Option<Self> {
+		let value = value.as_ref().as_dyn_any();
+		dyn_any::downcast_ref::<f64>(value)
+			.map(|&number| NodeValue::F64(number))
+			.or_else(|| dyn_any::downcast_ref::<u32>(value).map(|&number| NodeValue::U32(number)))
+			.or_else(|| dyn_any::downcast_ref::<bool>(value).map(|&boolean| NodeValue::Bool(boolean)))
+			.or_else(|| dyn_any::downcast_ref::<String>(value).map(|string| NodeValue::String(string.clone())))
+			.or_else(|| dyn_any::downcast_ref::<Vec<String>>(value).map(|strings| NodeValue::List(strings.iter().cloned().map(NodeValue::String).collect())))
+	}
+}
+
+impl From<AbiValue> for NodeValue {
+	fn from(value: AbiValue) -> Self {
+		match value {
+			AbiValue::F64(number) => NodeValue::F64(number),
+			AbiValue::U32(number) => NodeValue::U32(number),
+			AbiValue::Bool(boolean) => NodeValue::Bool(boolean),
+			AbiValue::String(string) => NodeValue::String(string),
+			AbiValue::Bytes(bytes) => NodeValue::Bytes(bytes),
+		}
+	}
+}
+
+impl NodeValue {
+	/// Convert the value to be passed to a plugin, returning `None` for values which plugins can not receive.
+	fn to_abi_value(&self) -> Option<AbiValue> {
+		match self {
+			NodeValue::F64(number) => Some(AbiValue::F64(*number)),
+			NodeValue::U32(number) => Some(AbiValue::U32(*number)),
+			NodeValue::Bool(boolean) => Some(AbiValue::Bool(*boolean)),
+			NodeValue::String(string) => Some(AbiValue::String(string.clone())),
+			NodeValue::Bytes(bytes) => Some(AbiValue::Bytes(bytes.clone())),
+			NodeValue::List(_) | NodeValue::Image(_) | NodeValue::Vector(_) | NodeValue::Error(_) => None,
+		}
+	}
+}
+
+/// Take the value out of the output of a node, cloning it only if the output is shared.
+fn take(value: Arc<NodeValue>) -> NodeValue {
+	Arc::try_unwrap(value).unwrap_or_else(|value| (*value).clone())
+}
+
+fn invalid_inputs(node: &str) -> NodeValue {
+	NodeValue::Error(format!("The {} node was given invalid inputs", node))
+}
+
+/// Wrap the implementation of a node so it passes on the first error among its inputs instead of being evaluated.
+fn node(implementation: impl Fn(&[Arc<NodeValue>], Option<Lambda<NodeValue>>) -> NodeValue + Send + Sync + 'static) -> ProtoNodeFn<NodeValue> {
+	proto_node_fn(move |inputs, lambda| match inputs.iter().find(|input| matches!(***input, NodeValue::Error(_))) {
+		Some(error) => (**error).clone(),
+		None => implementation(inputs, lambda),
+	})
+}
+
+/// Evaluates the lambda `count` times with a list of the index and the output of the previous iteration, starting with the input.
+fn repeat(inputs: &[Arc<NodeValue>], lambda: Option<Lambda<NodeValue>>) -> NodeValue {
+	match (lambda, inputs) {
+		(Some(lambda), [input, count]) => match **count {
+			NodeValue::U32(count) => {
+				let iteration = FnNode::new(|(index, previous): (u32, NodeValue)| match previous {
+					NodeValue::Error(_) => previous,
+					previous => take(lambda(NodeValue::List(vec![NodeValue::U32(index), previous]))),
+				});
+				RepeatNode::new(&iteration, ValueNode(count)).eval((**input).clone())
+			}
+			_ => invalid_inputs("repeat"),
+		},
+		_ => invalid_inputs("repeat"),
+	}
+}
+
+/// Evaluates the lambda `count` times with a list of the index and the input, collecting the outputs into a list.
+fn repeat_collect(inputs: &[Arc<NodeValue>], lambda: Option<Lambda<NodeValue>>) -> NodeValue {
+	match (lambda, inputs) {
+		(Some(lambda), [input, count]) => match **count {
+			NodeValue::U32(count) => {
+				let iteration = FnNode::new(|(index, input): (u32, NodeValue)| take(lambda(NodeValue::List(vec![NodeValue::U32(index), input]))));
+				let outputs = RepeatCollectNode::new(&iteration, ValueNode(count)).eval((**input).clone());
+				match outputs.iter().find(|output| matches!(output, NodeValue::Error(_))) {
+					Some(error) => error.clone(),
+					None => NodeValue::List(outputs),
+				}
+			}
+			_ => invalid_inputs("repeat_collect"),
+		},
+		_ => invalid_inputs("repeat_collect"),
+	}
+}
+
+/// Converts the colors of an image to gray, with the pixels split between threads.
+fn grayscale(inputs: &[Arc<NodeValue>], _: Option<Lambda<NodeValue>>) -> NodeValue {
+	match inputs {
+		[image] => match &**image {
+			NodeValue::Image(image) => NodeValue::Image((&ParallelMapImageNode::new(GrayscaleNode)).eval(image.clone())),
+			_ => invalid_inputs("grayscale"),
+		},
+		_ => invalid_inputs("grayscale"),
+	}
+}
+
+/// Remaps the tonal range of an image given its input black and white points, gamma, and output black and white points, with the pixels split between threads.
+fn levels(inputs: &[Arc<NodeValue>], _: Option<Lambda<NodeValue>>) -> NodeValue {
+	let number = |input: &NodeValue| match *input {
+		NodeValue::F64(number) => Some(number as f32),
+		_ => None,
+	};
+	match inputs {
+		[image, levels @ ..] if levels.len() == 5 => match (&**image, levels.iter().map(|level| number(level)).collect::<Option<Vec<_>>>()) {
+			(NodeValue::Image(image), Some(levels)) => {
+				let levels = LevelsNode {
+					master: Levels {
+						input_black: levels[0],
+						input_white: levels[1],
+						gamma: levels[2],
+						output_black: levels[3],
+						output_white: levels[4],
+					},
+					..Default::default()
+				};
+				NodeValue::Image((&ParallelMapImageNode::new(&levels)).eval(image.clone()))
+			}
+			_ => invalid_inputs("levels"),
+		},
+		_ => invalid_inputs("levels"),
+	}
+}
+
+/// Runs a script given its source, a list of the names its other inputs are exposed to it with, and those inputs.
+fn script(inputs: &[Arc<NodeValue>], _: Option<Lambda<NodeValue>>) -> NodeValue {
+	let (source, names, values) = match inputs {
+		[source, names, values @ ..] => match (&**source, &**names) {
+			(NodeValue::String(source), NodeValue::List(names)) => (source, names, values),
+			_ => return invalid_inputs("script"),
+		},
+		_ => return invalid_inputs("script"),
+	};
+	let names = names.iter().map(|name| match name {
+		NodeValue::String(name) => Some(name.clone()),
+		_ => None,
+	});
+	let values = values.iter().map(|value| match &**value {
+		NodeValue::F64(number) => Some(ScriptValue::Number(*number)),
+		NodeValue::U32(number) => Some(ScriptValue::Number(*number as f64)),
+		NodeValue::Vector(path) => Some(ScriptValue::Vector(path.clone())),
+		NodeValue::Image(image) => Some(ScriptValue::Raster(image.clone())),
+		_ => None,
+	});
+	let (names, values) = match (names.collect::<Option<Vec<_>>>(), values.collect::<Option<Vec<_>>>()) {
+		(Some(names), Some(values)) => (names, values),
+		_ => return NodeValue::Error("Scripts can only be given numbers, paths, and images under names given as strings".into()),
+	};
+
+	match ScriptNode::new(source, names).and_then(|node| (&node).eval(values.as_slice())) {
+		Ok(ScriptValue::Number(number)) => NodeValue::F64(number),
+		Ok(ScriptValue::Vector(path)) => NodeValue::Vector(path),
+		Ok(ScriptValue::Raster(image)) => NodeValue::Image(image),
+		Err(error) => NodeValue::Error(error.to_string()),
+	}
+}
+
+/// Evaluates a node of a loaded plugin with the outputs of its inputs.
+fn plugin_node(plugins: &RwLock<PluginRegistry>, name: &str, inputs: &[Arc<NodeValue>]) -> NodeValue {
+	let inputs = match inputs.iter().map(|input| input.to_abi_value()).collect::<Option<Vec<_>>>() {
+		Some(inputs) => inputs,
+		None => return NodeValue::Error(format!("The plugin node '{}' can only be given numbers, booleans, strings, and bytes", name)),
+	};
+	match plugins.read().expect("plugin registry lock poisoned").eval(name, &inputs) {
+		Ok(output) => output.into(),
+		Err(error) => NodeValue::Error(error.to_string()),
+	}
+}
+
+/// The proto nodes available to networks, by the name their proto nodes refer to them with.
+///
+/// Nodes provided by plugins are looked up after the registered nodes, so a plugin can not replace a registered node.
+pub struct NodeRegistry {
+	nodes: HashMap<String, ProtoNodeFn<NodeValue>>,
+	plugins: Arc<RwLock<PluginRegistry>>,
+}
+
+impl Default for NodeRegistry {
+	fn default() -> Self {
+		let mut registry = Self {
+			nodes: HashMap::new(),
+			plugins: Arc::default(),
+		};
+		registry.register("repeat", node(repeat));
+		registry.register("repeat_collect", node(repeat_collect));
+		registry.register("grayscale", node(grayscale));
+		registry.register("levels", node(levels));
+		registry.register("script", node(script));
+		registry
+	}
+}
+
+impl NodeRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Register a proto node, replacing any node registered under the same name.
+	pub fn register(&mut self, name: impl Into<String>, implementation: ProtoNodeFn<NodeValue>) {
+		self.nodes.insert(name.into(), implementation);
+	}
+
+	/// Load a plugin module and return the names of its nodes, which can be used by the networks prepared for evaluation afterwards.
+	pub fn load_plugin(&mut self, bytes: &[u8]) -> Result<Vec<String>, PluginError> {
+		self.plugins.write().expect("plugin registry lock poisoned").load(bytes)
+	}
+
+	pub fn lookup(&self, name: &str) -> Option<ProtoNodeFn<NodeValue>> {
+		if let Some(implementation) = self.nodes.get(name) {
+			return Some(implementation.clone());
+		}
+		self.plugins.read().expect("plugin registry lock poisoned").signature(name)?;
+
+		let (plugins, name) = (self.plugins.clone(), name.to_string());
+		Some(node(move |inputs, _| plugin_node(&plugins, &name, inputs)))
+	}
+
+	/// Prepare a flattened network with resolved proto nodes for evaluation.
+	pub fn executor(&self, network: &NodeNetwork) -> NodeResult<NetworkExecutor<NodeValue>> {
+		NetworkExecutor::new(network, |name| self.lookup(name), NodeValue::from_value)
+	}
+
+	/// Prepare the changed network for evaluation, keeping the cached outputs of its unchanged nodes.
+	pub fn update(&self, executor: &mut NetworkExecutor<NodeValue>, network: &NodeNetwork) -> NodeResult<()> {
+		executor.update(network, |name| self.lookup(name), NodeValue::from_value)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene_core::raster::color::Color;
+	use graphene_std::document::{ConstructionArgs, DocumentNode, DocumentNodeImplementation, IntoValue, ProtoNode, ProtoNodeInput};
+	use graphene_std::raster::MapImageNode;
+
+	fn proto_node(name: &str, input: ProtoNodeInput, construction_args: ConstructionArgs) -> DocumentNode {
+		DocumentNode {
+			name: name.into(),
+			inputs: vec![],
+			implementation: DocumentNodeImplementation::ProtoNode(ProtoNode {
+				name: name.into(),
+				input,
+				construction_args,
+			}),
+		}
+	}
+
+	/// Repeats a step which doubles the previous output and adds the index, starting with the input of the network.
+	fn repeat_network(repeat: &str) -> NodeNetwork {
+		NodeNetwork {
+			inputs: vec![0, 2],
+			output: 3,
+			nodes: [
+				(0, proto_node("id", ProtoNodeInput::Network, ConstructionArgs::None)),
+				(1, proto_node("value", ProtoNodeInput::None, ConstructionArgs::Value(3_u32.into_any()))),
+				(2, proto_node("step", ProtoNodeInput::Network, ConstructionArgs::None)),
+				(3, proto_node(repeat, ProtoNodeInput::Lambda(2), ConstructionArgs::Nodes(vec![0, 1]))),
+			]
+			.into_iter()
+			.collect(),
+			..Default::default()
+		}
+	}
+
+	fn registry() -> NodeRegistry {
+		let mut registry = NodeRegistry::new();
+		registry.register(
+			"step",
+			node(|inputs, _| match &*inputs[0] {
+				NodeValue::List(list) => match list.as_slice() {
+					[NodeValue::U32(index), NodeValue::F64(previous)] => NodeValue::F64(previous * 2. + *index as f64),
+					_ => invalid_inputs("step"),
+				},
+				_ => invalid_inputs("step"),
+			}),
+		);
+		registry
+	}
+
+	#[test]
+	fn repeat_lambda() {
+		let registry = registry();
+		let executor = registry.executor(&repeat_network("repeat")).unwrap();
+		assert_eq!(*executor.execute(NodeValue::F64(1.)), NodeValue::F64(12.));
+
+		let executor = registry.executor(&repeat_network("repeat_collect")).unwrap();
+		assert_eq!(
+			*executor.execute(NodeValue::F64(10.)),
+			NodeValue::List(vec![NodeValue::F64(20.), NodeValue::F64(21.), NodeValue::F64(22.)])
+		);
+	}
+
+	#[test]
+	fn map_image() {
+		let network = NodeNetwork {
+			inputs: vec![0],
+			output: 3,
+			nodes: [
+				(0, proto_node("grayscale", ProtoNodeInput::Network, ConstructionArgs::None)),
+				(1, proto_node("value", ProtoNodeInput::None, ConstructionArgs::Value(0_f64.into_any()))),
+				(2, proto_node("value", ProtoNodeInput::None, ConstructionArgs::Value(1_f64.into_any()))),
+				(3, proto_node("levels", ProtoNodeInput::Node(0), ConstructionArgs::Nodes(vec![1, 2, 2, 2, 1]))),
+			]
+			.into_iter()
+			.collect(),
+			..Default::default()
+		};
+
+		// Grayscale followed by inverting levels
+		let image = Image::new(3, 1, vec![Color::from_rgbaf32(1., 0.5, 0., 1.).unwrap(), Color::BLACK, Color::WHITE]);
+		let inverted = LevelsNode {
+			master: Levels {
+				output_black: 1.,
+				output_white: 0.,
+				..Default::default()
+			},
+			..Default::default()
+		};
+		let expected = (&MapImageNode::new(&inverted)).eval((&MapImageNode::new(GrayscaleNode)).eval(image.clone()));
+
+		let executor = NodeRegistry::new().executor(&network).unwrap();
+		assert_eq!(*executor.execute(NodeValue::Image(image)), NodeValue::Image(expected));
+	}
+
+	#[test]
+	fn evaluate_plugin_node() {
+		let network = NodeNetwork {
+			inputs: vec![0],
+			output: 0,
+			nodes: [(0, proto_node("Double", ProtoNodeInput::Network, ConstructionArgs::None))].into_iter().collect(),
+			..Default::default()
+		};
+
+		let mut registry = NodeRegistry::new();
+		assert_eq!(registry.executor(&network).err().and_then(|error| error.node()), Some(0));
+
+		let module = wat::parse_str(crate::plugin::test::DOUBLE_PLUGIN).unwrap();
+		assert_eq!(registry.load_plugin(&module), Ok(vec!["Double".to_string()]));
+		let executor = registry.executor(&network).unwrap();
+		assert_eq!(*executor.execute(NodeValue::F64(21.)), NodeValue::F64(42.));
+		assert!(matches!(*executor.execute(NodeValue::U32(21)), NodeValue::Error(_)));
+		assert!(matches!(*executor.execute(NodeValue::List(Vec::new())), NodeValue::Error(_)));
+	}
+
+	#[test]
+	fn run_script() {
+		let value = |value: Value| proto_node("value", ProtoNodeInput::None, ConstructionArgs::Value(value));
+		let network = NodeNetwork {
+			inputs: vec![4],
+			output: 3,
+			nodes: [
+				(0, value("radius * 2 + offset".to_string().into_any())),
+				(1, value(vec!["radius".to_string(), "offset".to_string()].into_any())),
+				(2, value(1_f64.into_any())),
+				(3, proto_node("script", ProtoNodeInput::None, ConstructionArgs::Nodes(vec![0, 1, 4, 2]))),
+				(4, proto_node("id", ProtoNodeInput::Network, ConstructionArgs::None)),
+			]
+			.into_iter()
+			.collect(),
+			..Default::default()
+		};
+
+		let executor = NodeRegistry::new().executor(&network).unwrap();
+		assert_eq!(*executor.execute(NodeValue::U32(3)), NodeValue::F64(7.));
+		assert!(matches!(*executor.execute(NodeValue::Bool(true)), NodeValue::Error(_)));
+	}
+
+	#[test]
+	fn errors_are_passed_on() {
+		let registry = registry();
+		let executor = registry.executor(&repeat_network("repeat")).unwrap();
+		assert_eq!(*executor.execute(NodeValue::Bool(true)), invalid_inputs("step"));
+
+		let mut network = repeat_network("repeat");
+		network.nodes.insert(1, proto_node("value", ProtoNodeInput::None, ConstructionArgs::Value(3_f64.into_any())));
+		let executor = registry.executor(&network).unwrap();
+		assert_eq!(*executor.execute(NodeValue::F64(1.)), invalid_inputs("repeat"));
+	}
+}
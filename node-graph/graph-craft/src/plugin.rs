@@ -0,0 +1,411 @@
+//! Loading of third party nodes compiled to WebAssembly, which are executed in a sandbox without access to the host.
+//!
+//! # Plugin ABI
+//!
+//! A plugin is a wasm module exporting its linear memory as `memory` and the following functions:
+//! - `graphite_manifest() -> i64` returns the location of the manifest, a UTF-8 string describing the nodes of the plugin.
+//! - `graphite_alloc(len: i32) -> i32` reserves `len` bytes of memory for the inputs of a node and returns their location.
+//! - One function per node with the signature `(ptr: i32, len: i32) -> i64`, which receives its encoded inputs and returns the location of its encoded output.
+//!
+//! Locations are returned as a pointer in the upper 32 bits and a length in the lower 32 bits of an `i64`.
+//!
+//! The manifest starts with the line `abi <version>`, followed by one line per node in the format
+//! `node <name> <export> <input types> -> <output type>`, where the input types are separated by commas or are `-` if the node has no inputs.
+//!
+//! Values are encoded as a tag byte followed by their payload, see [`AbiValue`].
+
+use graphene_core::Node;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+/// Version of the plugin ABI, increased whenever a change would break existing plugins.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Amount of fuel, roughly the number of executed instructions, a plugin may use for a single evaluation before it is aborted.
+const FUEL_PER_EVALUATION: u64 = 100_000_000;
+
+/// Largest size in bytes the linear memory of a plugin may grow to.
+const MAX_PLUGIN_MEMORY: usize = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbiType {
+	F64,
+	U32,
+	Bool,
+	String,
+	Bytes,
+}
+
+impl AbiType {
+	fn parse(name: &str) -> Option<Self> {
+		match name {
+			"f64" => Some(AbiType::F64),
+			"u32" => Some(AbiType::U32),
+			"bool" => Some(AbiType::Bool),
+			"string" => Some(AbiType::String),
+			"bytes" => Some(AbiType::Bytes),
+			_ => None,
+		}
+	}
+
+	fn tag(self) -> u8 {
+		self as u8
+	}
+}
+
+/// A value passed between the node graph and a plugin.
+///
+/// Encoded as the tag of its [`AbiType`] (`0` to `4` in declaration order), followed by the little endian value for numbers,
+/// a single byte for booleans, or a `u32` length followed by the content for strings and bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AbiValue {
+	F64(f64),
+	U32(u32),
+	Bool(bool),
+	String(String),
+	Bytes(Vec<u8>),
+}
+
+impl AbiValue {
+	pub fn abi_type(&self) -> AbiType {
+		match self {
+			AbiValue::F64(_) => AbiType::F64,
+			AbiValue::U32(_) => AbiType::U32,
+			AbiValue::Bool(_) => AbiType::Bool,
+			AbiValue::String(_) => AbiType::String,
+			AbiValue::Bytes(_) => AbiType::Bytes,
+		}
+	}
+
+	pub fn encode(&self, buffer: &mut Vec<u8>) {
+		buffer.push(self.abi_type().tag());
+		match self {
+			AbiValue::F64(value) => buffer.extend(value.to_le_bytes()),
+			AbiValue::U32(value) => buffer.extend(value.to_le_bytes()),
+			AbiValue::Bool(value) => buffer.push(*value as u8),
+			AbiValue::String(value) => {
+				buffer.extend((value.len() as u32).to_le_bytes());
+				buffer.extend(value.as_bytes());
+			}
+			AbiValue::Bytes(value) => {
+				buffer.extend((value.len() as u32).to_le_bytes());
+				buffer.extend(value);
+			}
+		}
+	}
+
+	/// Decode a value from the start of the buffer, returning it along with the number of bytes it used.
+	pub fn decode(buffer: &[u8]) -> Option<(Self, usize)> {
+		// The length of strings and bytes is controlled by the plugin, so it may overflow on 32-bit targets
+		let bytes = |start: usize, len: usize| buffer.get(start..start.checked_add(len)?);
+		let length_prefixed = || {
+			let len = u32::from_le_bytes(bytes(1, 4)?.try_into().ok()?) as usize;
+			Some((bytes(5, len)?, 5usize.checked_add(len)?))
+		};
+		match *buffer.first()? {
+			0 => Some((AbiValue::F64(f64::from_le_bytes(bytes(1, 8)?.try_into().ok()?)), 9)),
+			1 => Some((AbiValue::U32(u32::from_le_bytes(bytes(1, 4)?.try_into().ok()?)), 5)),
+			2 => Some((AbiValue::Bool(*bytes(1, 1)?.first()? != 0), 2)),
+			3 => length_prefixed().and_then(|(content, len)| Some((AbiValue::String(String::from_utf8(content.to_vec()).ok()?), len))),
+			4 => length_prefixed().map(|(content, len)| (AbiValue::Bytes(content.to_vec()), len)),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginNodeSignature {
+	pub name: String,
+	/// Name of the function exported by the plugin which implements the node
+	pub export: String,
+	pub inputs: Vec<AbiType>,
+	pub output: AbiType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginError {
+	/// The module is not valid WebAssembly, or does not export the functions required by the ABI
+	InvalidModule(String),
+	InvalidManifest(String),
+	IncompatibleAbi {
+		version: u32,
+	},
+	/// A node with the same name was already registered by another plugin
+	DuplicateNode(String),
+	UnknownNode(String),
+	InputMismatch {
+		node: String,
+		expected: Vec<AbiType>,
+		found: Vec<AbiType>,
+	},
+	/// The plugin trapped, ran out of fuel, or returned an output which could not be decoded
+	Execution(String),
+}
+
+impl Display for PluginError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PluginError::InvalidModule(error) => write!(f, "The plugin is not a valid module: {}", error),
+			PluginError::InvalidManifest(error) => write!(f, "The plugin manifest is invalid: {}", error),
+			PluginError::IncompatibleAbi { version } => write!(f, "The plugin uses ABI version {} but version {} is required", version, PLUGIN_ABI_VERSION),
+			PluginError::DuplicateNode(name) => write!(f, "A node named '{}' is already registered", name),
+			PluginError::UnknownNode(name) => write!(f, "No plugin provides a node named '{}'", name),
+			PluginError::InputMismatch { node, expected, found } => write!(f, "The node '{}' expects the inputs {:?} but was given {:?}", node, expected, found),
+			PluginError::Execution(error) => write!(f, "The plugin failed: {}", error),
+		}
+	}
+}
+
+fn parse_manifest(manifest: &str) -> Result<Vec<PluginNodeSignature>, PluginError> {
+	let invalid = |line: &str| PluginError::InvalidManifest(format!("Could not parse the line '{}'", line));
+	let mut lines = manifest.lines().map(str::trim).filter(|line| !line.is_empty());
+
+	let header = lines.next().ok_or_else(|| PluginError::InvalidManifest("The manifest is empty".into()))?;
+	let version = header.strip_prefix("abi ").and_then(|version| version.trim().parse().ok()).ok_or_else(|| invalid(header))?;
+	if version != PLUGIN_ABI_VERSION {
+		return Err(PluginError::IncompatibleAbi { version });
+	}
+
+	lines
+		.map(|line| {
+			let words = line.split_whitespace().collect::<Vec<_>>();
+			match words.as_slice() {
+				["node", name, export, inputs, "->", output] => Ok(PluginNodeSignature {
+					name: name.to_string(),
+					export: export.to_string(),
+					inputs: match *inputs {
+						"-" => Vec::new(),
+						inputs => inputs.split(',').map(AbiType::parse).collect::<Option<_>>().ok_or_else(|| invalid(line))?,
+					},
+					output: AbiType::parse(output).ok_or_else(|| invalid(line))?,
+				}),
+				_ => Err(invalid(line)),
+			}
+		})
+		.collect()
+}
+
+fn split_location(location: i64) -> (usize, usize) {
+	((location as u64 >> 32) as usize, (location as u64 & u32::MAX as u64) as usize)
+}
+
+/// An instantiated plugin module. It has no imports, so its only way to interact with the host is through the values passed to its nodes.
+struct Plugin {
+	store: Mutex<wasmi::Store<wasmi::StoreLimits>>,
+	instance: wasmi::Instance,
+	memory: wasmi::Memory,
+}
+
+impl Plugin {
+	fn load(bytes: &[u8]) -> Result<(Self, Vec<PluginNodeSignature>), PluginError> {
+		let invalid = |error: &dyn Display| PluginError::InvalidModule(error.to_string());
+
+		let mut config = wasmi::Config::default();
+		config.consume_fuel(true);
+		let engine = wasmi::Engine::new(&config);
+		let module = wasmi::Module::new(&engine, bytes).map_err(|error| invalid(&error))?;
+		let limits = wasmi::StoreLimitsBuilder::new().memory_size(MAX_PLUGIN_MEMORY).instances(1).build();
+		let mut store = wasmi::Store::new(&engine, limits);
+		store.limiter(|limits| limits);
+		let instance = wasmi::Linker::new(&engine)
+			.instantiate(&mut store, &module)
+			.and_then(|instance| instance.start(&mut store))
+			.map_err(|error| invalid(&error))?;
+		let memory = instance
+			.get_export(&store, "memory")
+			.and_then(wasmi::Extern::into_memory)
+			.ok_or_else(|| PluginError::InvalidModule("The plugin does not export its memory".into()))?;
+
+		let plugin = Self {
+			store: Mutex::new(store),
+			instance,
+			memory,
+		};
+		let manifest = plugin.call(|store| {
+			let manifest = plugin.instance.get_typed_func::<(), i64>(&*store, "graphite_manifest").map_err(|error| invalid(&error))?;
+			manifest.call(store, ()).map_err(|error| PluginError::Execution(error.to_string()))
+		})?;
+		let manifest = String::from_utf8(plugin.read(manifest)?).map_err(|_| PluginError::InvalidManifest("The manifest is not valid UTF-8".into()))?;
+		let signatures = parse_manifest(&manifest)?;
+		Ok((plugin, signatures))
+	}
+
+	/// Run a function with a fresh amount of fuel, replacing whatever was left over from the previous call.
+	fn call<T>(&self, f: impl FnOnce(&mut wasmi::Store<wasmi::StoreLimits>) -> Result<T, PluginError>) -> Result<T, PluginError> {
+		let mut store = self.store.lock().expect("plugin store mutex poisoned");
+		store.set_fuel(FUEL_PER_EVALUATION).map_err(|error| PluginError::Execution(error.to_string()))?;
+		f(&mut store)
+	}
+
+	/// Copy a location out of the memory of the plugin, which must lie within the memory rather than being trusted to size the copy.
+	fn read(&self, location: i64) -> Result<Vec<u8>, PluginError> {
+		let (pointer, len) = split_location(location);
+		let store = self.store.lock().expect("plugin store mutex poisoned");
+		let data = self.memory.data(&*store);
+		let bytes = pointer.checked_add(len).and_then(|end| data.get(pointer..end));
+		bytes
+			.map(<[u8]>::to_vec)
+			.ok_or_else(|| PluginError::Execution("The plugin returned a location outside of its memory".into()))
+	}
+
+	fn eval(&self, signature: &PluginNodeSignature, inputs: &[AbiValue]) -> Result<AbiValue, PluginError> {
+		let mut encoded = Vec::new();
+		inputs.iter().for_each(|input| input.encode(&mut encoded));
+		let execution_error = |error: &dyn Display| PluginError::Execution(error.to_string());
+
+		let output = self.call(|store| {
+			let alloc = self
+				.instance
+				.get_typed_func::<i32, i32>(&*store, "graphite_alloc")
+				.map_err(|error| PluginError::InvalidModule(error.to_string()))?;
+			let node = self
+				.instance
+				.get_typed_func::<(i32, i32), i64>(&*store, &signature.export)
+				.map_err(|error| PluginError::InvalidModule(error.to_string()))?;
+
+			let pointer = alloc.call(&mut *store, encoded.len() as i32).map_err(|error| execution_error(&error))?;
+			self.memory.write(&mut *store, pointer as u32 as usize, &encoded).map_err(|error| execution_error(&error))?;
+			node.call(&mut *store, (pointer, encoded.len() as i32)).map_err(|error| execution_error(&error))
+		})?;
+
+		let output = self.read(output)?;
+		match AbiValue::decode(&output) {
+			Some((value, _)) if value.abi_type() == signature.output => Ok(value),
+			_ => Err(PluginError::Execution(format!("The node '{}' returned an invalid output", signature.name))),
+		}
+	}
+}
+
+/// The nodes provided by all loaded plugins, by name.
+#[derive(Default)]
+pub struct PluginRegistry {
+	plugins: Vec<Plugin>,
+	nodes: HashMap<String, (usize, PluginNodeSignature)>,
+}
+
+impl PluginRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Load a plugin module and register its nodes, returning their names.
+	pub fn load(&mut self, bytes: &[u8]) -> Result<Vec<String>, PluginError> {
+		let (plugin, signatures) = Plugin::load(bytes)?;
+		if let Some(duplicate) = signatures.iter().find(|signature| self.nodes.contains_key(&signature.name)) {
+			return Err(PluginError::DuplicateNode(duplicate.name.clone()));
+		}
+
+		let index = self.plugins.len();
+		self.plugins.push(plugin);
+		Ok(signatures
+			.into_iter()
+			.map(|signature| {
+				let name = signature.name.clone();
+				self.nodes.insert(name.clone(), (index, signature));
+				name
+			})
+			.collect())
+	}
+
+	pub fn signature(&self, node: &str) -> Option<&PluginNodeSignature> {
+		self.nodes.get(node).map(|(_, signature)| signature)
+	}
+
+	/// The signatures of all registered nodes, to be listed in the node catalog.
+	pub fn signatures(&self) -> impl Iterator<Item = &PluginNodeSignature> {
+		self.nodes.values().map(|(_, signature)| signature)
+	}
+
+	pub fn eval(&self, node: &str, inputs: &[AbiValue]) -> Result<AbiValue, PluginError> {
+		let (index, signature) = self.nodes.get(node).ok_or_else(|| PluginError::UnknownNode(node.into()))?;
+		let found = inputs.iter().map(AbiValue::abi_type).collect::<Vec<_>>();
+		if found != signature.inputs {
+			return Err(PluginError::InputMismatch {
+				node: node.into(),
+				expected: signature.inputs.clone(),
+				found,
+			});
+		}
+		self.plugins[*index].eval(signature, inputs)
+	}
+}
+
+/// Evaluates a node provided by a plugin.
+pub struct PluginNode<'a> {
+	pub registry: &'a PluginRegistry,
+	pub name: String,
+}
+
+impl<'a> PluginNode<'a> {
+	pub fn new(registry: &'a PluginRegistry, name: String) -> Self {
+		Self { registry, name }
+	}
+}
+
+impl<'n, 'a> Node<&'n [AbiValue]> for &'n PluginNode<'a> {
+	type Output = Result<AbiValue, PluginError>;
+	fn eval(self, inputs: &'n [AbiValue]) -> Self::Output {
+		self.registry.eval(&self.name, inputs)
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+	use super::*;
+
+	pub(crate) const DOUBLE_PLUGIN: &str = r#"(module
+		(memory (export "memory") 1)
+		(global $next (mut i32) (i32.const 1024))
+		(data (i32.const 0) "abi 1\nnode Double double f64 -> f64\n")
+		(func (export "graphite_manifest") (result i64)
+			(i64.const 36))
+		(func (export "graphite_alloc") (param $len i32) (result i32)
+			(local $pointer i32)
+			(local.set $pointer (global.get $next))
+			(global.set $next (i32.add (global.get $next) (local.get $len)))
+			(local.get $pointer))
+		(func (export "double") (param $pointer i32) (param $len i32) (result i64)
+			(i32.store8 (i32.const 512) (i32.const 0))
+			(f64.store (i32.const 513) (f64.mul (f64.load (i32.add (local.get $pointer) (i32.const 1))) (f64.const 2)))
+			(i64.or (i64.shl (i64.const 512) (i64.const 32)) (i64.const 9)))
+	)"#;
+
+	#[test]
+	fn evaluate_plugin_node() {
+		let mut registry = PluginRegistry::new();
+		let module = wat::parse_str(DOUBLE_PLUGIN).unwrap();
+		assert_eq!(registry.load(&module), Ok(vec!["Double".to_string()]));
+		assert_eq!(registry.load(&module), Err(PluginError::DuplicateNode("Double".into())));
+
+		let node = PluginNode::new(&registry, "Double".into());
+		assert_eq!((&node).eval(&[AbiValue::F64(21.)]), Ok(AbiValue::F64(42.)));
+		assert!(matches!((&node).eval(&[AbiValue::U32(21)]), Err(PluginError::InputMismatch { .. })));
+	}
+
+	#[test]
+	fn encode_values() {
+		for value in [
+			AbiValue::F64(1.5),
+			AbiValue::U32(7),
+			AbiValue::Bool(true),
+			AbiValue::String("Graphite".into()),
+			AbiValue::Bytes(vec![1, 2, 3]),
+		] {
+			let mut buffer = Vec::new();
+			value.encode(&mut buffer);
+			assert_eq!(AbiValue::decode(&buffer), Some((value, buffer.len())));
+		}
+	}
+
+	#[test]
+	fn decode_hostile_lengths() {
+		for tag in [3, 4] {
+			let mut buffer = vec![tag];
+			buffer.extend(u32::MAX.to_le_bytes());
+			buffer.extend(b"short");
+			assert_eq!(AbiValue::decode(&buffer), None);
+		}
+		assert_eq!(AbiValue::decode(&[0, 1, 2]), None);
+	}
+}
@@ -0,0 +1,72 @@
+use graphene_core::Node;
+
+/// Evaluates the iteration node `count` times, passing in the index of the iteration together with the output of the previous one.
+/// The input of the node is used as the value for the first iteration and the output of the last iteration is returned.
+pub struct RepeatNode<Iteration, Count>(pub Iteration, pub Count);
+
+impl<Iteration, Count, T> Node<T> for RepeatNode<Iteration, Count>
+where
+	Iteration: Node<(u32, T), Output = T> + Copy,
+	Count: Node<(), Output = u32>,
+{
+	type Output = T;
+	fn eval(self, input: T) -> Self::Output {
+		let count = self.1.eval(());
+		(0..count).fold(input, |previous, index| self.0.eval((index, previous)))
+	}
+}
+impl<'n, Iteration, Count, T> Node<T> for &'n RepeatNode<Iteration, Count>
+where
+	Iteration: Node<(u32, T), Output = T> + Copy,
+	Count: Node<(), Output = u32> + Copy,
+{
+	type Output = T;
+	fn eval(self, input: T) -> Self::Output {
+		let count = self.1.eval(());
+		(0..count).fold(input, |previous, index| self.0.eval((index, previous)))
+	}
+}
+
+impl<Iteration, Count> RepeatNode<Iteration, Count> {
+	pub const fn new(iteration: Iteration, count: Count) -> Self {
+		Self(iteration, count)
+	}
+}
+
+/// Evaluates the iteration node `count` times with the index of the iteration and a copy of the input, collecting all outputs.
+pub struct RepeatCollectNode<Iteration, Count>(pub Iteration, pub Count);
+
+impl<Iteration, Count, T: Clone, O> Node<T> for RepeatCollectNode<Iteration, Count>
+where
+	Iteration: Node<(u32, T), Output = O> + Copy,
+	Count: Node<(), Output = u32>,
+{
+	type Output = Vec<O>;
+	fn eval(self, input: T) -> Self::Output {
+		let count = self.1.eval(());
+		(0..count).map(|index| self.0.eval((index, input.clone()))).collect()
+	}
+}
+
+impl<Iteration, Count> RepeatCollectNode<Iteration, Count> {
+	pub const fn new(iteration: Iteration, count: Count) -> Self {
+		Self(iteration, count)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene_core::generic::FnNode;
+	use graphene_core::value::ValueNode;
+
+	#[test]
+	fn repeat_node() {
+		let step = FnNode::new(|(index, previous): (u32, u32)| previous * 2 + index);
+		let repeat = RepeatNode::new(&step, ValueNode(3u32));
+		assert_eq!(repeat.eval(1), 12);
+
+		let collect = RepeatCollectNode::new(&step, ValueNode(3u32));
+		assert_eq!(collect.eval(10), [20, 21, 22]);
+	}
+}
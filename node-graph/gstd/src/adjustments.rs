@@ -0,0 +1,181 @@
+use bezier_rs::Bezier;
+use graphene::layers::style::Gradient;
+use graphene_core::raster::color::Color;
+use graphene_core::Node;
+
+fn srgb_to_linear(channel: f32) -> f32 {
+	if channel <= 0.04045 {
+		channel / 12.92
+	} else {
+		((channel + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+fn linear_to_srgb(channel: f32) -> f32 {
+	if channel <= 0.0031308 {
+		channel * 12.92
+	} else {
+		1.055 * channel.powf(1. / 2.4) - 0.055
+	}
+}
+
+/// Applies a mapping to each color channel of an sRGB encoded color in linear light.
+fn map_linear(color: Color, map: impl Fn(usize, f32) -> f32) -> Color {
+	let [r, g, b] = [color.r(), color.g(), color.b()];
+	let channel = |index: usize, value: f32| linear_to_srgb(map(index, srgb_to_linear(value)).clamp(0., 1.));
+	Color::from_rgbaf32_unchecked(channel(0, r), channel(1, g), channel(2, b), color.a())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Levels {
+	pub input_black: f32,
+	pub input_white: f32,
+	pub gamma: f32,
+	pub output_black: f32,
+	pub output_white: f32,
+}
+
+impl Default for Levels {
+	fn default() -> Self {
+		Self {
+			input_black: 0.,
+			input_white: 1.,
+			gamma: 1.,
+			output_black: 0.,
+			output_white: 1.,
+		}
+	}
+}
+
+impl Levels {
+	pub fn apply(&self, value: f32) -> f32 {
+		let range = (self.input_white - self.input_black).max(f32::EPSILON);
+		let normalized = ((value - self.input_black) / range).clamp(0., 1.);
+		let corrected = normalized.powf(self.gamma.max(f32::EPSILON).recip());
+		self.output_black + corrected * (self.output_white - self.output_black)
+	}
+}
+
+/// Remaps the tonal range of each channel with its own levels, followed by the levels shared by all channels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LevelsNode {
+	pub master: Levels,
+	/// Levels of the red, green, and blue channels
+	pub channels: [Levels; 3],
+}
+
+impl<'n> Node<Color> for &'n LevelsNode {
+	type Output = Color;
+	fn eval(self, color: Color) -> Color {
+		map_linear(color, |index, value| self.master.apply(self.channels[index].apply(value)))
+	}
+}
+
+/// Maps each color channel through a curve, which is evaluated once into a lookup table on construction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurvesNode {
+	lut: Vec<f32>,
+}
+
+impl CurvesNode {
+	pub const LUT_SIZE: usize = 1024;
+
+	/// Create the node from bezier segments spanning the unit square, with the input along the x axis and the output along the y axis.
+	/// The segments are expected to be ordered and monotonic in x.
+	pub fn new(curve: &[Bezier]) -> Self {
+		let mut points = curve
+			.iter()
+			.flat_map(|segment| segment.compute_lookup_table(Some(64)))
+			.map(|point| (point.x as f32, point.y as f32))
+			.collect::<Vec<_>>();
+		points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+		let lut = (0..Self::LUT_SIZE)
+			.map(|index| {
+				let x = index as f32 / (Self::LUT_SIZE - 1) as f32;
+				let y = match points.iter().position(|point| point.0 >= x) {
+					Some(0) => points[0].1,
+					Some(next) => {
+						let (start, end) = (points[next - 1], points[next]);
+						let t = if end.0 > start.0 { (x - start.0) / (end.0 - start.0) } else { 0. };
+						start.1 + (end.1 - start.1) * t
+					}
+					None => points.last().map_or(x, |point| point.1),
+				};
+				y.clamp(0., 1.)
+			})
+			.collect();
+		Self { lut }
+	}
+
+	pub fn apply(&self, value: f32) -> f32 {
+		let position = value.clamp(0., 1.) * (self.lut.len() - 1) as f32;
+		let index = (position as usize).min(self.lut.len() - 2);
+		let t = position - index as f32;
+		self.lut[index] + (self.lut[index + 1] - self.lut[index]) * t
+	}
+}
+
+impl<'n> Node<Color> for &'n CurvesNode {
+	type Output = Color;
+	fn eval(self, color: Color) -> Color {
+		map_linear(color, |_, value| self.apply(value))
+	}
+}
+
+/// Replaces each color with the color of a gradient at the position given by its luminance, with black at the start and white at the end of the gradient.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientMapNode {
+	pub gradient: Gradient,
+	/// Reverse the direction of the gradient
+	pub reverse: bool,
+}
+
+impl GradientMapNode {
+	pub fn new(gradient: Gradient) -> Self {
+		Self { gradient, reverse: false }
+	}
+}
+
+impl<'n> Node<Color> for &'n GradientMapNode {
+	type Output = Color;
+	fn eval(self, color: Color) -> Color {
+		let linear_luminance = 0.2126 * srgb_to_linear(color.r()) + 0.7152 * srgb_to_linear(color.g()) + 0.0722 * srgb_to_linear(color.b());
+		let luminance = linear_to_srgb(linear_luminance.clamp(0., 1.)) as f64;
+		let mapped = self.gradient.evaluate(if self.reverse { 1. - luminance } else { luminance });
+		Color::from_rgbaf32_unchecked(mapped.r(), mapped.g(), mapped.b(), mapped.a() * color.a())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn identity_adjustments() {
+		let color = Color::from_rgbaf32_unchecked(0.2, 0.5, 0.8, 0.5);
+		let approximately_equal = |a: Color, b: Color| (a.r() - b.r()).abs() < 1e-3 && (a.g() - b.g()).abs() < 1e-3 && (a.b() - b.b()).abs() < 1e-3 && a.a() == b.a();
+
+		assert!(approximately_equal((&LevelsNode::default()).eval(color), color));
+
+		let diagonal = CurvesNode::new(&[Bezier::from_cubic_coordinates(0., 0., 0.25, 0.25, 0.75, 0.75, 1., 1.)]);
+		assert!(approximately_equal((&diagonal).eval(color), color));
+
+		let inverted = CurvesNode::new(&[Bezier::from_linear_coordinates(0., 1., 1., 0.)]);
+		assert!((inverted.apply(0.25) - 0.75).abs() < 1e-3);
+	}
+
+	#[test]
+	fn gradient_map() {
+		use glam::{DAffine2, DVec2};
+		use graphene::color::Color as DocumentColor;
+		use graphene::layers::style::GradientType;
+
+		let gradient = Gradient::new(DVec2::ZERO, DocumentColor::RED, DVec2::X, DocumentColor::BLUE, DAffine2::IDENTITY, 0, GradientType::Linear);
+		let map = GradientMapNode::new(gradient);
+		let black = Color::from_rgbaf32_unchecked(0., 0., 0., 0.5);
+		assert_eq!((&map).eval(black), Color::from_rgbaf32_unchecked(1., 0., 0., 0.5));
+		let mapped_gray = (&map).eval(Color::from_rgbaf32_unchecked(0.5, 0.5, 0.5, 1.));
+		assert!((mapped_gray.r() - 0.5).abs() < 1e-3 && (mapped_gray.b() - 0.5).abs() < 1e-3);
+	}
+}
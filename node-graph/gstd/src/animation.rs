@@ -0,0 +1,82 @@
+use graphene_core::Node;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The point in time the document is evaluated at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationTime {
+	pub seconds: f64,
+	pub frame: u32,
+}
+
+/// Shared playback position which the editor updates during playback and scrubbing, read by every [`TimeNode`] of the graph.
+#[derive(Debug)]
+pub struct AnimationClock {
+	seconds: AtomicU64,
+	pub frame_rate: f64,
+}
+
+impl Default for AnimationClock {
+	fn default() -> Self {
+		Self::new(60.)
+	}
+}
+
+impl AnimationClock {
+	pub fn new(frame_rate: f64) -> Self {
+		Self {
+			seconds: AtomicU64::new(0_f64.to_bits()),
+			frame_rate,
+		}
+	}
+
+	pub fn set_seconds(&self, seconds: f64) {
+		self.seconds.store(seconds.max(0.).to_bits(), Ordering::Relaxed);
+	}
+
+	pub fn set_frame(&self, frame: u32) {
+		self.set_seconds(frame as f64 / self.frame_rate);
+	}
+
+	pub fn time(&self) -> AnimationTime {
+		let seconds = f64::from_bits(self.seconds.load(Ordering::Relaxed));
+		// Round to avoid landing on the previous frame because of floating point error
+		let frame = (seconds * self.frame_rate + 1e-6).floor() as u32;
+		AnimationTime { seconds, frame }
+	}
+}
+
+/// Outputs the current time of the [`AnimationClock`], so any parameter wired to it animates.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeNode<'a>(pub &'a AnimationClock);
+
+impl<'n, 'a> Node<()> for &'n TimeNode<'a> {
+	type Output = AnimationTime;
+	fn eval(self, _: ()) -> Self::Output {
+		self.0.time()
+	}
+}
+
+impl<'a> Node<()> for TimeNode<'a> {
+	type Output = AnimationTime;
+	fn eval(self, _: ()) -> Self::Output {
+		self.0.time()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn time_follows_clock() {
+		let clock = AnimationClock::new(24.);
+		let time = TimeNode(&clock);
+		assert_eq!(time.eval(()), AnimationTime { seconds: 0., frame: 0 });
+
+		clock.set_frame(36);
+		assert_eq!(time.eval(()), AnimationTime { seconds: 1.5, frame: 36 });
+
+		clock.set_seconds(0.1);
+		assert_eq!(time.eval(()).frame, 2);
+	}
+}
@@ -0,0 +1,337 @@
+use crate::document::{generate_uuid, DefinitionId, DocumentNode, DocumentNodeImplementation, IntoValue, NodeDefinition, NodeId, NodeInput, NodeNetwork, ProtoNode, Value};
+use dyn_any::downcast_ref;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// Version of the asset file format, increased whenever a change would prevent older versions of Graphite from reading new files.
+pub const ASSET_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetError {
+	/// The value of an input can't be stored in an asset file
+	UnsupportedValue {
+		node: NodeId,
+		type_name: &'static str,
+	},
+	/// The file was written by a newer version of Graphite
+	IncompatibleVersion {
+		format_version: u32,
+	},
+	UnknownDefinition(DefinitionId),
+	Parse(String),
+}
+
+impl Display for AssetError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			AssetError::UnsupportedValue { node, type_name } => write!(f, "Node {} has an input of type {} which can't be exported", node, type_name),
+			AssetError::IncompatibleVersion { format_version } => write!(
+				f,
+				"The asset uses format version {} but only versions up to {} are supported, try updating Graphite",
+				format_version, ASSET_FORMAT_VERSION
+			),
+			AssetError::UnknownDefinition(id) => write!(f, "The network contains no definition with the id {}", id),
+			AssetError::Parse(error) => write!(f, "The asset file could not be read: {}", error),
+		}
+	}
+}
+
+/// The values which can be stored as the inputs of nodes in an asset file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum SerializedValue {
+	Bool(bool),
+	U32(u32),
+	U64(u64),
+	I32(i32),
+	I64(i64),
+	F32(f32),
+	F64(f64),
+	String(String),
+}
+
+impl SerializedValue {
+	fn from_value(value: &Value) -> Option<Self> {
+		let value = value.as_ref().as_dyn_any();
+		downcast_ref::<bool>(value)
+			.map(|&value| Self::Bool(value))
+			.or_else(|| downcast_ref::<u32>(value).map(|&value| Self::U32(value)))
+			.or_else(|| downcast_ref::<u64>(value).map(|&value| Self::U64(value)))
+			.or_else(|| downcast_ref::<i32>(value).map(|&value| Self::I32(value)))
+			.or_else(|| downcast_ref::<i64>(value).map(|&value| Self::I64(value)))
+			.or_else(|| downcast_ref::<f32>(value).map(|&value| Self::F32(value)))
+			.or_else(|| downcast_ref::<f64>(value).map(|&value| Self::F64(value)))
+			.or_else(|| downcast_ref::<String>(value).map(|value| Self::String(value.clone())))
+	}
+
+	fn into_value(self) -> Value {
+		match self {
+			Self::Bool(value) => value.into_any(),
+			Self::U32(value) => value.into_any(),
+			Self::U64(value) => value.into_any(),
+			Self::I32(value) => value.into_any(),
+			Self::I64(value) => value.into_any(),
+			Self::F32(value) => value.into_any(),
+			Self::F64(value) => value.into_any(),
+			Self::String(value) => value.into_any(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum SerializedInput {
+	Node(NodeId),
+	Value(SerializedValue),
+	Network,
+	Lambda(NodeId),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum SerializedImplementation {
+	Network(SerializedNetwork),
+	/// Proto nodes are stored by name and resolved again after importing
+	ProtoNode(String),
+	Definition(DefinitionId),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SerializedNode {
+	name: String,
+	inputs: Vec<SerializedInput>,
+	implementation: SerializedImplementation,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SerializedNetwork {
+	inputs: Vec<NodeId>,
+	output: NodeId,
+	nodes: Vec<(NodeId, SerializedNode)>,
+	definitions: Vec<(DefinitionId, SerializedDefinition)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SerializedDefinition {
+	name: String,
+	input_names: Vec<String>,
+	network: SerializedNetwork,
+}
+
+/// A node definition stored in a standalone file, so it can be shared between documents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeAsset {
+	/// Version of the file format, used to refuse files which this version of Graphite can't read
+	pub format_version: u32,
+	/// Version of the asset itself, chosen by its author
+	pub version: String,
+	pub description: String,
+	definition: SerializedDefinition,
+}
+
+impl NodeAsset {
+	/// Create an asset from a definition of the network, along with all definitions it depends on.
+	pub fn export(network: &NodeNetwork, definition: DefinitionId, version: String, description: String) -> Result<Self, AssetError> {
+		let definition = network.definitions.get(&definition).ok_or(AssetError::UnknownDefinition(definition))?;
+		let mut serialized = serialize_definition(definition)?;
+
+		// Definitions used by the exported one are stored in the root network, so they are included in the asset
+		let mut dependencies = Vec::new();
+		collect_definitions(&definition.network, &mut dependencies);
+		while let Some(id) = dependencies.pop() {
+			if serialized.network.definitions.iter().any(|(existing, _)| *existing == id) {
+				continue;
+			}
+			let dependency = network.definitions.get(&id).ok_or(AssetError::UnknownDefinition(id))?;
+			collect_definitions(&dependency.network, &mut dependencies);
+			serialized.network.definitions.push((id, serialize_definition(dependency)?));
+		}
+
+		Ok(Self {
+			format_version: ASSET_FORMAT_VERSION,
+			version,
+			description,
+			definition: serialized,
+		})
+	}
+
+	pub fn name(&self) -> &str {
+		&self.definition.name
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("Failed to serialize the node asset")
+	}
+
+	pub fn from_json(json: &str) -> Result<Self, AssetError> {
+		let asset: Self = serde_json::from_str(json).map_err(|error| AssetError::Parse(error.to_string()))?;
+		if asset.format_version > ASSET_FORMAT_VERSION {
+			return Err(AssetError::IncompatibleVersion { format_version: asset.format_version });
+		}
+		Ok(asset)
+	}
+
+	/// Add the definition to the network, returning its new id. All definitions of the asset are given new ids so they don't collide with existing ones.
+	pub fn import(&self, network: &mut NodeNetwork) -> DefinitionId {
+		let mut id_map = HashMap::new();
+		let id = generate_uuid();
+		let definition = deserialize_definition(&self.definition, &mut id_map);
+		network.definitions.insert(id, definition);
+		id
+	}
+}
+
+fn collect_definitions(network: &NodeNetwork, definitions: &mut Vec<DefinitionId>) {
+	for node in network.nodes.values() {
+		match &node.implementation {
+			DocumentNodeImplementation::Definition(id) => definitions.push(*id),
+			DocumentNodeImplementation::Network(network) => collect_definitions(network, definitions),
+			DocumentNodeImplementation::ProtoNode(_) => {}
+		}
+	}
+}
+
+fn serialize_definition(definition: &NodeDefinition) -> Result<SerializedDefinition, AssetError> {
+	Ok(SerializedDefinition {
+		name: definition.name.clone(),
+		input_names: definition.input_names.clone(),
+		network: serialize_network(&definition.network)?,
+	})
+}
+
+fn serialize_network(network: &NodeNetwork) -> Result<SerializedNetwork, AssetError> {
+	let mut nodes = network.nodes.iter().map(|(&id, node)| Ok((id, serialize_node(id, node)?))).collect::<Result<Vec<_>, AssetError>>()?;
+	// Sort by id so exporting the same network always produces the same file
+	nodes.sort_by_key(|(id, _)| *id);
+
+	let mut definitions = network
+		.definitions
+		.iter()
+		.map(|(&id, definition)| Ok((id, serialize_definition(definition)?)))
+		.collect::<Result<Vec<_>, AssetError>>()?;
+	definitions.sort_by_key(|(id, _)| *id);
+
+	Ok(SerializedNetwork {
+		inputs: network.inputs.clone(),
+		output: network.output,
+		nodes,
+		definitions,
+	})
+}
+
+fn serialize_node(id: NodeId, node: &DocumentNode) -> Result<SerializedNode, AssetError> {
+	let inputs = node
+		.inputs
+		.iter()
+		.map(|input| match input {
+			NodeInput::Node(source) => Ok(SerializedInput::Node(*source)),
+			NodeInput::Value(value) => SerializedValue::from_value(value).map(SerializedInput::Value).ok_or(AssetError::UnsupportedValue {
+				node: id,
+				type_name: value.type_name(),
+			}),
+			NodeInput::Network => Ok(SerializedInput::Network),
+			NodeInput::Lambda(source) => Ok(SerializedInput::Lambda(*source)),
+		})
+		.collect::<Result<_, _>>()?;
+	let implementation = match &node.implementation {
+		DocumentNodeImplementation::Network(network) => SerializedImplementation::Network(serialize_network(network)?),
+		DocumentNodeImplementation::ProtoNode(proto_node) => SerializedImplementation::ProtoNode(proto_node.name.clone()),
+		DocumentNodeImplementation::Definition(definition) => SerializedImplementation::Definition(*definition),
+	};
+	Ok(SerializedNode {
+		name: node.name.clone(),
+		inputs,
+		implementation,
+	})
+}
+
+fn deserialize_definition(definition: &SerializedDefinition, id_map: &mut HashMap<DefinitionId, DefinitionId>) -> NodeDefinition {
+	NodeDefinition {
+		name: definition.name.clone(),
+		input_names: definition.input_names.clone(),
+		network: deserialize_network(&definition.network, id_map),
+	}
+}
+
+fn deserialize_network(network: &SerializedNetwork, id_map: &mut HashMap<DefinitionId, DefinitionId>) -> NodeNetwork {
+	// Assign the new ids first, as the definitions can reference each other
+	for (id, _) in &network.definitions {
+		id_map.insert(*id, generate_uuid());
+	}
+	let definitions = network.definitions.iter().map(|(id, definition)| (id_map[id], deserialize_definition(definition, id_map))).collect();
+
+	let nodes = network
+		.nodes
+		.iter()
+		.map(|(id, node)| {
+			let inputs = node
+				.inputs
+				.iter()
+				.map(|input| match input {
+					SerializedInput::Node(source) => NodeInput::Node(*source),
+					SerializedInput::Value(value) => NodeInput::Value(value.clone().into_value()),
+					SerializedInput::Network => NodeInput::Network,
+					SerializedInput::Lambda(source) => NodeInput::Lambda(*source),
+				})
+				.collect();
+			let implementation = match &node.implementation {
+				SerializedImplementation::Network(network) => DocumentNodeImplementation::Network(deserialize_network(network, id_map)),
+				SerializedImplementation::ProtoNode(name) => DocumentNodeImplementation::ProtoNode(ProtoNode::unresolved(name.clone())),
+				SerializedImplementation::Definition(definition) => DocumentNodeImplementation::Definition(*id_map.get(definition).unwrap_or(definition)),
+			};
+			let node = DocumentNode {
+				name: node.name.clone(),
+				inputs,
+				implementation,
+			};
+			(*id, node)
+		})
+		.collect();
+
+	NodeNetwork {
+		inputs: network.inputs.clone(),
+		output: network.output,
+		nodes,
+		definitions,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn export_and_import_definition() {
+		let mut inner = NodeNetwork {
+			inputs: vec![0],
+			output: 0,
+			..Default::default()
+		};
+		inner.nodes.insert(
+			0,
+			DocumentNode {
+				name: "Add".into(),
+				inputs: vec![NodeInput::Network, NodeInput::Value(2_u32.into_any())],
+				implementation: DocumentNodeImplementation::ProtoNode(ProtoNode::unresolved("graphene_core::ops::AddNode".into())),
+			},
+		);
+		let mut network = NodeNetwork::default();
+		network.definitions.insert(
+			7,
+			NodeDefinition {
+				name: "Add Two".into(),
+				input_names: vec!["Value".into()],
+				network: inner,
+			},
+		);
+
+		let json = NodeAsset::export(&network, 7, "1.0.0".into(), "Adds two to the input".into()).unwrap().to_json();
+		let asset = NodeAsset::from_json(&json).unwrap();
+		assert_eq!(asset.name(), "Add Two");
+
+		let mut other = NodeNetwork::default();
+		let id = asset.import(&mut other);
+		assert_eq!(other.definitions[&id], network.definitions[&7]);
+
+		let newer = json.replacen(&format!("\"format_version\": {}", ASSET_FORMAT_VERSION), "\"format_version\": 1000", 1);
+		assert_eq!(NodeAsset::from_json(&newer), Err(AssetError::IncompatibleVersion { format_version: 1000 }));
+	}
+}
@@ -0,0 +1,62 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A source of monotonic time for nodes which measure how long an evaluation takes.
+///
+/// The time is taken through this trait instead of `std::time::Instant` directly, which panics on wasm, so that tests can use a [`ManualClock`].
+pub trait Clock {
+	/// Time elapsed since an arbitrary point which stays fixed for the lifetime of the clock.
+	fn now(&self) -> Duration;
+}
+
+/// The monotonic clock of the system, read through `performance.now()` on wasm.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+	start: web_time::Instant,
+}
+
+impl Default for SystemClock {
+	fn default() -> Self {
+		Self { start: web_time::Instant::now() }
+	}
+}
+
+impl Clock for SystemClock {
+	fn now(&self) -> Duration {
+		self.start.elapsed()
+	}
+}
+
+/// A clock which only advances when told to, for deterministic timings.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+	now: Mutex<Duration>,
+}
+
+impl ManualClock {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn advance(&self, time: Duration) {
+		*self.now.lock().expect("clock mutex poisoned") += time;
+	}
+}
+
+impl Clock for ManualClock {
+	fn now(&self) -> Duration {
+		*self.now.lock().expect("clock mutex poisoned")
+	}
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+	fn now(&self) -> Duration {
+		(**self).now()
+	}
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+	fn now(&self) -> Duration {
+		(**self).now()
+	}
+}
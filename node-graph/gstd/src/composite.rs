@@ -0,0 +1,242 @@
+use crate::raster::Image;
+use crate::vector::to_bezier_subpaths;
+use glam::{DAffine2, DVec2};
+use graphene::layers::blend_mode::BlendMode;
+use graphene::layers::vector::subpath::Subpath;
+use graphene_core::raster::color::Color;
+use graphene_core::Node;
+
+fn luminosity([r, g, b]: [f32; 3]) -> f32 {
+	0.3 * r + 0.59 * g + 0.11 * b
+}
+
+fn clip_color(color: [f32; 3]) -> [f32; 3] {
+	let luminosity = luminosity(color);
+	let (min, max) = (color[0].min(color[1]).min(color[2]), color[0].max(color[1]).max(color[2]));
+	color.map(|channel| {
+		let mut channel = channel;
+		if min < 0. {
+			channel = luminosity + (channel - luminosity) * luminosity / (luminosity - min);
+		}
+		if max > 1. {
+			channel = luminosity + (channel - luminosity) * (1. - luminosity) / (max - luminosity);
+		}
+		channel
+	})
+}
+
+fn set_luminosity(color: [f32; 3], target: f32) -> [f32; 3] {
+	let difference = target - luminosity(color);
+	clip_color(color.map(|channel| channel + difference))
+}
+
+fn saturation(color: [f32; 3]) -> f32 {
+	color[0].max(color[1]).max(color[2]) - color[0].min(color[1]).min(color[2])
+}
+
+fn set_saturation(color: [f32; 3], target: f32) -> [f32; 3] {
+	let (min, max) = (color[0].min(color[1]).min(color[2]), color[0].max(color[1]).max(color[2]));
+	if max > min {
+		color.map(|channel| (channel - min) * target / (max - min))
+	} else {
+		[0.; 3]
+	}
+}
+
+/// Blend the color of the source with the color of the backdrop, as defined by the [compositing specification](https://www.w3.org/TR/compositing-1/#blending).
+pub fn blend(mode: BlendMode, backdrop: [f32; 3], source: [f32; 3]) -> [f32; 3] {
+	let separable = |function: fn(f32, f32) -> f32| [0, 1, 2].map(|index| function(backdrop[index], source[index]));
+	match mode {
+		BlendMode::Normal => source,
+		BlendMode::Multiply => separable(|b, s| b * s),
+		BlendMode::Darken => separable(f32::min),
+		BlendMode::ColorBurn => separable(|b, s| match (b, s) {
+			_ if b >= 1. => 1.,
+			_ if s <= 0. => 0.,
+			_ => 1. - ((1. - b) / s).min(1.),
+		}),
+		BlendMode::Screen => separable(|b, s| b + s - b * s),
+		BlendMode::Lighten => separable(f32::max),
+		BlendMode::ColorDodge => separable(|b, s| match (b, s) {
+			_ if b <= 0. => 0.,
+			_ if s >= 1. => 1.,
+			_ => (b / (1. - s)).min(1.),
+		}),
+		BlendMode::Overlay => separable(|b, s| hard_light(s, b)),
+		BlendMode::SoftLight => separable(|b, s| {
+			if s <= 0.5 {
+				b - (1. - 2. * s) * b * (1. - b)
+			} else {
+				let d = if b <= 0.25 { ((16. * b - 12.) * b + 4.) * b } else { b.sqrt() };
+				b + (2. * s - 1.) * (d - b)
+			}
+		}),
+		BlendMode::HardLight => separable(hard_light),
+		BlendMode::Difference => separable(|b, s| (b - s).abs()),
+		BlendMode::Exclusion => separable(|b, s| b + s - 2. * b * s),
+		BlendMode::Hue => set_luminosity(set_saturation(source, saturation(backdrop)), luminosity(backdrop)),
+		BlendMode::Saturation => set_luminosity(set_saturation(backdrop, saturation(source)), luminosity(backdrop)),
+		BlendMode::Color => set_luminosity(source, luminosity(backdrop)),
+		BlendMode::Luminosity => set_luminosity(backdrop, luminosity(source)),
+	}
+}
+
+fn hard_light(backdrop: f32, source: f32) -> f32 {
+	if source <= 0.5 {
+		backdrop * 2. * source
+	} else {
+		let source = 2. * source - 1.;
+		backdrop + source - backdrop * source
+	}
+}
+
+/// Composite the source over the backdrop with the given blend mode, where the source alpha has already been multiplied by the mask.
+fn composite(mode: BlendMode, backdrop: Color, source: Color, source_alpha: f32) -> Color {
+	let (backdrop_alpha, backdrop_color) = (backdrop.a(), [backdrop.r(), backdrop.g(), backdrop.b()]);
+	let source_color = [source.r(), source.g(), source.b()];
+	let blended = blend(mode, backdrop_color, source_color);
+
+	let alpha = source_alpha + backdrop_alpha * (1. - source_alpha);
+	if alpha <= 0. {
+		return Color::from_rgbaf32_unchecked(0., 0., 0., 0.);
+	}
+	let [r, g, b] = [0, 1, 2].map(|index| {
+		// Where the backdrop is transparent the source color is used as is
+		let source = (1. - backdrop_alpha) * source_color[index] + backdrop_alpha * blended[index];
+		let premultiplied = source_alpha * source + backdrop_alpha * backdrop_color[index] * (1. - source_alpha);
+		(premultiplied / alpha).clamp(0., 1.)
+	});
+	Color::from_rgbaf32_unchecked(r, g, b, alpha)
+}
+
+/// Blends a foreground image onto a background image where a grayscale mask is white, taking the input `(foreground, background, mask)`.
+///
+/// The result has the size of the background. The foreground and the mask are aligned with its top left corner,
+/// and pixels outside of them are treated as transparent and black respectively.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompositeNode {
+	pub blend_mode: BlendMode,
+	pub opacity: f32,
+}
+
+impl CompositeNode {
+	pub const fn new(blend_mode: BlendMode, opacity: f32) -> Self {
+		Self { blend_mode, opacity }
+	}
+}
+
+impl<'n> Node<(Image, Image, Image)> for &'n CompositeNode {
+	type Output = Image;
+	fn eval(self, (foreground, background, mask): (Image, Image, Image)) -> Self::Output {
+		let pixel = |image: &Image, x: u32, y: u32| (x < image.width && y < image.height).then(|| image.data[(y * image.width + x) as usize]);
+
+		let data = (0..background.height)
+			.flat_map(|y| (0..background.width).map(move |x| (x, y)))
+			.map(|(x, y)| {
+				let backdrop = background.data[(y * background.width + x) as usize];
+				let source = match pixel(&foreground, x, y) {
+					Some(source) => source,
+					None => return backdrop,
+				};
+				let coverage = pixel(&mask, x, y).map_or(0., |mask| luminosity([mask.r(), mask.g(), mask.b()]) * mask.a());
+				composite(self.blend_mode, backdrop, source, source.a() * coverage * self.opacity)
+			})
+			.collect();
+		Image::new(background.width, background.height, data)
+	}
+}
+
+/// Renders the fill of a path into a grayscale mask, so vector shapes can be used as the mask of a [`CompositeNode`].
+///
+/// Uses the nonzero fill rule with 4x4 samples per pixel for antialiasing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RasterizeMaskNode {
+	/// Transformation from the path to the pixels of the mask
+	pub transform: DAffine2,
+	pub width: u32,
+	pub height: u32,
+}
+
+impl RasterizeMaskNode {
+	pub const fn new(transform: DAffine2, width: u32, height: u32) -> Self {
+		Self { transform, width, height }
+	}
+}
+
+impl<'n> Node<Subpath> for &'n RasterizeMaskNode {
+	type Output = Image;
+	fn eval(self, path: Subpath) -> Self::Output {
+		const SAMPLES: u32 = 4;
+
+		// Every contour is filled as if it was closed
+		let polygons = to_bezier_subpaths(&path)
+			.iter()
+			.map(|subpath| {
+				let mut points = subpath
+					.iter()
+					.flat_map(|bezier| bezier.compute_lookup_table(Some(16)))
+					.map(|point| self.transform.transform_point2(point))
+					.collect::<Vec<_>>();
+				points.dedup();
+				points
+			})
+			.filter(|points| points.len() > 2)
+			.collect::<Vec<_>>();
+
+		let winding_number = |point: DVec2| {
+			let mut winding = 0;
+			for polygon in &polygons {
+				for (index, &start) in polygon.iter().enumerate() {
+					let end = polygon[(index + 1) % polygon.len()];
+					let side = (end - start).perp_dot(point - start);
+					if start.y <= point.y && end.y > point.y && side > 0. {
+						winding += 1;
+					} else if start.y > point.y && end.y <= point.y && side < 0. {
+						winding -= 1;
+					}
+				}
+			}
+			winding
+		};
+
+		let data = (0..self.height)
+			.flat_map(|y| (0..self.width).map(move |x| (x, y)))
+			.map(|(x, y)| {
+				let covered = (0..SAMPLES * SAMPLES)
+					.filter(|sample| {
+						let offset = DVec2::new((sample % SAMPLES) as f64 + 0.5, (sample / SAMPLES) as f64 + 0.5) / SAMPLES as f64;
+						winding_number(DVec2::new(x as f64, y as f64) + offset) != 0
+					})
+					.count();
+				let value = covered as f32 / (SAMPLES * SAMPLES) as f32;
+				Color::from_rgbaf32_unchecked(value, value, value, 1.)
+			})
+			.collect();
+		Image::new(self.width, self.height, data)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn masked_composite() {
+		let image = |color: Color| Image::new(4, 4, vec![color; 16]);
+		let red = Color::from_rgbaf32_unchecked(1., 0., 0., 1.);
+		let blue = Color::from_rgbaf32_unchecked(0., 0., 1., 1.);
+
+		// Mask the left half of the image
+		let path = Subpath::new_rect(DVec2::new(0., 0.), DVec2::new(2., 4.));
+		let mask = (&RasterizeMaskNode::new(DAffine2::IDENTITY, 4, 4)).eval(path);
+		assert_eq!(mask.data[0].r(), 1.);
+		assert_eq!(mask.data[3].r(), 0.);
+
+		let composited = (&CompositeNode::new(BlendMode::Normal, 1.)).eval((image(red), image(blue), mask.clone()));
+		assert_eq!(composited.data[0], red);
+		assert_eq!(composited.data[3], blue);
+
+		let screen = (&CompositeNode::new(BlendMode::Screen, 1.)).eval((image(red), image(blue), mask));
+		assert_eq!(screen.data[0], Color::from_rgbaf32_unchecked(1., 0., 1., 1.));
+	}
+}
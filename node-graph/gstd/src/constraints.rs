@@ -0,0 +1,143 @@
+use crate::expression::{ExpressionError, ExpressionNode};
+use graphene_core::Node;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstraintError {
+	Expression(ExpressionError),
+	/// The expression would make the property depend on itself, through the listed chain of properties
+	Cycle(Vec<String>),
+}
+
+impl Display for ConstraintError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ConstraintError::Expression(error) => write!(f, "{}", error),
+			ConstraintError::Cycle(chain) => write!(f, "The expression creates a circular dependency: {}", chain.join(" -> ")),
+		}
+	}
+}
+
+impl From<ExpressionError> for ConstraintError {
+	fn from(error: ExpressionError) -> Self {
+		ConstraintError::Expression(error)
+	}
+}
+
+/// Numeric properties of layers and nodes which are driven by expressions referencing other properties, such as `rect.width = circle.radius * 2`.
+///
+/// Properties are identified by name, by convention the name of the layer or node followed by a dot and the name of the property.
+/// Constraints are kept free of cycles, so a change to any property can be propagated by evaluating its dependents in topological order.
+#[derive(Debug, Clone, Default)]
+pub struct PropertyConstraints {
+	expressions: HashMap<String, ExpressionNode>,
+}
+
+impl PropertyConstraints {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Drive the target property by the expression, replacing any previous expression of the property.
+	pub fn set(&mut self, target: &str, source: &str) -> Result<(), ConstraintError> {
+		let expression = ExpressionNode::new(source)?;
+		if let Some(chain) = expression.inputs().iter().find_map(|input| self.path(input, target)) {
+			return Err(ConstraintError::Cycle(std::iter::once(target.to_string()).chain(chain).collect()));
+		}
+		self.expressions.insert(target.into(), expression);
+		Ok(())
+	}
+
+	pub fn remove(&mut self, target: &str) -> Option<ExpressionNode> {
+		self.expressions.remove(target)
+	}
+
+	pub fn expression(&self, target: &str) -> Option<&ExpressionNode> {
+		self.expressions.get(target)
+	}
+
+	/// Whether the value of the property is computed, in which case it should not be edited directly.
+	pub fn is_constrained(&self, property: &str) -> bool {
+		self.expressions.contains_key(property)
+	}
+
+	/// Chain of dependencies leading from one property to another, if the first depends on the second.
+	fn path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+		if from == to {
+			return Some(vec![from.into()]);
+		}
+		let expression = self.expressions.get(from)?;
+		expression.inputs().iter().find_map(|input| self.path(input, to)).map(|mut chain| {
+			chain.insert(0, from.into());
+			chain
+		})
+	}
+
+	/// All properties which directly or indirectly depend on one of the changed properties, ordered so every property comes after the properties it depends on.
+	pub fn affected(&self, changed: &[String]) -> Vec<String> {
+		let mut affected = HashSet::new();
+		let mut stack = changed.to_vec();
+		while let Some(property) = stack.pop() {
+			for (target, expression) in &self.expressions {
+				if expression.inputs().contains(&property) && affected.insert(target.clone()) {
+					stack.push(target.clone());
+				}
+			}
+		}
+
+		let mut ordered = Vec::new();
+		let mut visited = HashSet::new();
+		let mut targets = affected.iter().collect::<Vec<_>>();
+		targets.sort();
+		for target in targets {
+			self.visit(target, &affected, &mut visited, &mut ordered);
+		}
+		ordered
+	}
+
+	fn visit(&self, property: &str, affected: &HashSet<String>, visited: &mut HashSet<String>, ordered: &mut Vec<String>) {
+		if !affected.contains(property) || !visited.insert(property.into()) {
+			return;
+		}
+		for input in self.expressions[property].inputs() {
+			self.visit(input, affected, visited, ordered);
+		}
+		ordered.push(property.into());
+	}
+
+	/// Re-evaluate the properties affected by a change and store their new values, returning the names of the updated properties.
+	///
+	/// Properties without a value are treated as 0.
+	pub fn update(&self, values: &mut HashMap<String, f64>, changed: &[String]) -> Vec<String> {
+		let affected = self.affected(changed);
+		for property in &affected {
+			let expression = &self.expressions[property];
+			let inputs = expression.inputs().iter().map(|input| values.get(input).copied().unwrap_or_default()).collect::<Vec<_>>();
+			values.insert(property.clone(), expression.eval(&inputs[..]));
+		}
+		affected
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn propagate_constraints() {
+		let mut constraints = PropertyConstraints::new();
+		constraints.set("rect.width", "circle.radius * 2").unwrap();
+		constraints.set("rect.height", "rect.width / 2 + circle.radius").unwrap();
+
+		let mut values = HashMap::from([("circle.radius".to_string(), 10.)]);
+		assert_eq!(constraints.update(&mut values, &["circle.radius".into()]), ["rect.width", "rect.height"]);
+		assert_eq!(values["rect.height"], 20.);
+
+		assert_eq!(
+			constraints.set("circle.radius", "rect.height"),
+			Err(ConstraintError::Cycle(vec!["circle.radius".into(), "rect.height".into(), "rect.width".into(), "circle.radius".into()]))
+		);
+		assert!(!constraints.is_constrained("circle.radius"));
+	}
+}
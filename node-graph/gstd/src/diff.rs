@@ -0,0 +1,135 @@
+use crate::document::{NodeId, NodeInput, NodeNetwork};
+use serde::{Deserialize, Serialize};
+
+/// Identifies a wire by the node input it connects to, which can only be fed by a single wire.
+/// It stays the same as long as the input exists, even when the wire is reconnected to another source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct WireId {
+	pub node: NodeId,
+	pub input: usize,
+}
+
+/// The source feeding an input, in the form displayed by the frontend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WireSource {
+	Node(NodeId),
+	/// A constant value, described by its debug representation
+	Value(String),
+	Network,
+	/// The node itself is passed rather than its output
+	Lambda(NodeId),
+}
+
+impl From<&NodeInput> for WireSource {
+	fn from(input: &NodeInput) -> Self {
+		match input {
+			NodeInput::Node(id) => WireSource::Node(*id),
+			NodeInput::Value(value) => WireSource::Value(format!("{:?}", value)),
+			NodeInput::Network => WireSource::Network,
+			NodeInput::Lambda(id) => WireSource::Lambda(*id),
+		}
+	}
+}
+
+/// A single change to a network, sent to the frontend instead of the whole network so it only has to update what changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphChange {
+	AddNode { id: NodeId, name: String },
+	RemoveNode { id: NodeId },
+	RenameNode { id: NodeId, name: String },
+	SetWire { wire: WireId, source: WireSource },
+	RemoveWire { wire: WireId },
+	SetOutput { id: NodeId },
+}
+
+/// The changes which turn the old network into the new one, in an order which can be applied one after another.
+///
+/// Removals come first, and wires are only set after the nodes they connect have been added.
+pub fn diff_networks(old: &NodeNetwork, new: &NodeNetwork) -> Vec<GraphChange> {
+	let mut changes = Vec::new();
+	let sorted_ids = |network: &NodeNetwork| {
+		let mut ids = network.nodes.keys().copied().collect::<Vec<_>>();
+		ids.sort_unstable();
+		ids
+	};
+	let (old_ids, new_ids) = (sorted_ids(old), sorted_ids(new));
+
+	for &id in &old_ids {
+		let old_node = &old.nodes[&id];
+		match new.nodes.get(&id) {
+			None => changes.push(GraphChange::RemoveNode { id }),
+			Some(new_node) => changes.extend((new_node.inputs.len()..old_node.inputs.len()).map(|input| GraphChange::RemoveWire { wire: WireId { node: id, input } })),
+		}
+	}
+
+	for &id in &new_ids {
+		let new_node = &new.nodes[&id];
+		match old.nodes.get(&id) {
+			None => changes.push(GraphChange::AddNode { id, name: new_node.name.clone() }),
+			Some(old_node) if old_node.name != new_node.name => changes.push(GraphChange::RenameNode { id, name: new_node.name.clone() }),
+			Some(_) => {}
+		}
+	}
+
+	for &id in &new_ids {
+		let old_inputs = old.nodes.get(&id).map_or(&[][..], |node| &node.inputs[..]);
+		for (input, source) in new.nodes[&id].inputs.iter().enumerate() {
+			if old_inputs.get(input) != Some(source) {
+				changes.push(GraphChange::SetWire {
+					wire: WireId { node: id, input },
+					source: source.into(),
+				});
+			}
+		}
+	}
+
+	if old.output != new.output || old.nodes.is_empty() {
+		changes.push(GraphChange::SetOutput { id: new.output });
+	}
+	changes
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::document::{DocumentNode, DocumentNodeImplementation, IntoValue, ProtoNode};
+
+	fn node(name: &str, inputs: Vec<NodeInput>) -> DocumentNode {
+		DocumentNode {
+			name: name.into(),
+			inputs,
+			implementation: DocumentNodeImplementation::ProtoNode(ProtoNode::unresolved(name.into())),
+		}
+	}
+
+	#[test]
+	fn diff_changed_network() {
+		let mut old = NodeNetwork { output: 1, ..Default::default() };
+		old.nodes.insert(0, node("value", vec![NodeInput::Value(1_u32.into_any())]));
+		old.nodes.insert(1, node("add", vec![NodeInput::Node(0), NodeInput::Node(0)]));
+		old.nodes.insert(2, node("unused", vec![]));
+
+		let mut new = old.clone();
+		new.nodes.remove(&2);
+		new.nodes.insert(3, node("value", vec![NodeInput::Value(2_u32.into_any())]));
+		new.nodes.get_mut(&1).unwrap().inputs = vec![NodeInput::Node(3)];
+
+		assert_eq!(
+			diff_networks(&old, &new),
+			[
+				GraphChange::RemoveWire { wire: WireId { node: 1, input: 1 } },
+				GraphChange::RemoveNode { id: 2 },
+				GraphChange::AddNode { id: 3, name: "value".into() },
+				GraphChange::SetWire {
+					wire: WireId { node: 1, input: 0 },
+					source: WireSource::Node(3)
+				},
+				GraphChange::SetWire {
+					wire: WireId { node: 3, input: 0 },
+					source: WireSource::Value("2".into())
+				},
+			]
+		);
+		assert!(diff_networks(&new, &new).is_empty());
+	}
+}
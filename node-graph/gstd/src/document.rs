@@ -1,14 +1,16 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::sync::Mutex;
 
+use crate::error::{NodeError, NodeResult};
 use dyn_any::{DynAny, StaticType};
 use rand_chacha::{
 	rand_core::{RngCore, SeedableRng},
 	ChaCha20Rng,
 };
 
-type NodeId = u64;
+pub(crate) type NodeId = u64;
+pub(crate) type DefinitionId = u64;
 static RNG: Mutex<Option<ChaCha20Rng>> = Mutex::new(None);
 
 pub fn generate_uuid() -> u64 {
@@ -35,7 +37,7 @@ fn merge_ids(a: u64, b: u64) -> u64 {
 	hasher.finish()
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DocumentNode {
 	pub name: String,
 	pub inputs: Vec<NodeInput>,
@@ -43,7 +45,7 @@ pub struct DocumentNode {
 }
 
 impl DocumentNode {
-	pub fn populate_first_network_input(&mut self, node: NodeId, offset: usize) {
+	pub fn populate_first_network_input(&mut self, source: NodeInput, offset: usize) {
 		let input = self
 			.inputs
 			.iter()
@@ -53,30 +55,60 @@ impl DocumentNode {
 			.expect("no network input");
 
 		let index = input.0;
-		self.inputs[index] = NodeInput::Node(node);
+		self.inputs[index] = source;
 	}
 
-	fn resolve_proto_nodes(&mut self) {
+	/// Ids of the nodes this node depends on, through its inputs or, once resolved, through its proto node.
+	pub fn dependencies(&self) -> impl Iterator<Item = NodeId> + '_ {
+		let proto = match &self.implementation {
+			DocumentNodeImplementation::ProtoNode(proto) => Some(proto),
+			_ => None,
+		};
+		let primary = proto.and_then(|proto| match proto.input {
+			ProtoNodeInput::Node(id) | ProtoNodeInput::Lambda(id) => Some(id),
+			ProtoNodeInput::None | ProtoNodeInput::Network => None,
+		});
+		let parameters = proto.into_iter().flat_map(|proto| match &proto.construction_args {
+			ConstructionArgs::Nodes(ids) => ids.as_slice(),
+			_ => &[][..],
+		});
+		self.inputs.iter().filter_map(NodeInput::source).chain(primary).chain(parameters.copied())
+	}
+
+	fn resolve_proto_nodes(&mut self, id: NodeId) -> NodeResult<()> {
+		if self.inputs.is_empty() {
+			return Err(NodeError::new(id, "The node has no primary input"));
+		}
 		let first = self.inputs.remove(0);
 		if let DocumentNodeImplementation::ProtoNode(proto) = &mut self.implementation {
 			match first {
 				NodeInput::Value(value) => {
 					proto.input = ProtoNodeInput::None;
 					proto.construction_args = ConstructionArgs::Value(value);
-					assert_eq!(self.inputs.len(), 0);
-					return;
+					if !self.inputs.is_empty() {
+						return Err(NodeError::new(id, "A value node can not have parameters"));
+					}
+					return Ok(());
 				}
 				NodeInput::Node(id) => proto.input = ProtoNodeInput::Node(id),
+				NodeInput::Lambda(id) => proto.input = ProtoNodeInput::Lambda(id),
 				NodeInput::Network => proto.input = ProtoNodeInput::Network,
 			}
-			assert!(!self.inputs.iter().any(|input| matches!(input, NodeInput::Network)), "recived non resolved parameter");
-			assert!(!self.inputs.iter().any(|input| matches!(input, NodeInput::Value(_))), "recieved value as parameter");
+			// Parameters are numbered from 1, as the primary input has been removed
+			for (index, input) in self.inputs.iter().enumerate() {
+				let message = match input {
+					NodeInput::Network => "The parameter is not connected",
+					NodeInput::Value(_) => "The parameter has to be resolved to a value node before compiling",
+					_ => continue,
+				};
+				return Err(NodeError::new(id, message).with_input(format!("Input {}", index + 1)));
+			}
 
 			let nodes: Vec<_> = self
 				.inputs
 				.iter()
 				.filter_map(|input| match input {
-					NodeInput::Node(id) => Some(*id),
+					NodeInput::Node(id) | NodeInput::Lambda(id) => Some(*id),
 					_ => None,
 				})
 				.collect();
@@ -86,49 +118,85 @@ impl DocumentNode {
 			}
 			self.inputs = vec![];
 		}
+		Ok(())
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum NodeInput {
 	Node(NodeId),
 	Value(Value),
 	Network,
+	/// Passes the node itself instead of its output, so the receiving node can evaluate it any number of times, such as once per element
+	Lambda(NodeId),
 }
 
 impl PartialEq for NodeInput {
 	fn eq(&self, other: &Self) -> bool {
 		match (&self, &other) {
-			(Self::Node(n1), Self::Node(n2)) => n1 == n2,
+			(Self::Node(n1), Self::Node(n2)) | (Self::Lambda(n1), Self::Lambda(n2)) => n1 == n2,
 			(Self::Value(v1), Self::Value(v2)) => v1 == v2,
 			_ => core::mem::discriminant(self) == core::mem::discriminant(other),
 		}
 	}
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DocumentNodeImplementation {
 	Network(NodeNetwork),
 	ProtoNode(ProtoNode),
+	/// An instance of a user defined node, whose contents are stored once in the `definitions` of the root network
+	Definition(DefinitionId),
 }
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct NodeNetwork {
 	pub inputs: Vec<NodeId>,
 	pub output: NodeId,
 	pub nodes: HashMap<NodeId, DocumentNode>,
+	pub definitions: HashMap<DefinitionId, NodeDefinition>,
 }
+
+/// A reusable network which can be instantiated any number of times through [`DocumentNodeImplementation::Definition`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeDefinition {
+	pub name: String,
+	/// Names of the exposed inputs, in the same order as the inputs of the network.
+	pub input_names: Vec<String>,
+	pub network: NodeNetwork,
+}
+
 pub type Value = Box<dyn ValueTrait>;
-pub trait ValueTrait: DynAny<'static> + std::fmt::Debug {}
+pub trait ValueTrait: DynAny<'static> + std::fmt::Debug {
+	fn clone_value(&self) -> Value;
+	fn type_name(&self) -> &'static str;
+	fn as_dyn_any(&self) -> &dyn DynAny<'_>;
+}
 
 pub trait IntoValue: Sized + ValueTrait + 'static {
 	fn into_any(self) -> Value {
 		Box::new(self)
 	}
 }
-impl<T: 'static + StaticType + std::fmt::Debug + PartialEq> ValueTrait for T {}
+impl<T: 'static + StaticType + std::fmt::Debug + PartialEq + Clone> ValueTrait for T {
+	fn clone_value(&self) -> Value {
+		Box::new(self.clone())
+	}
+	fn type_name(&self) -> &'static str {
+		std::any::type_name::<T>()
+	}
+	fn as_dyn_any(&self) -> &dyn DynAny<'_> {
+		self
+	}
+}
 impl<T: 'static + ValueTrait> IntoValue for T {}
 
+impl Clone for Value {
+	fn clone(&self) -> Self {
+		self.as_ref().clone_value()
+	}
+}
+
 #[repr(C)]
 struct Vtable {
 	destructor: unsafe fn(*mut ()),
@@ -156,7 +224,7 @@ impl PartialEq for Box<dyn ValueTrait> {
 	}
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub enum ConstructionArgs {
 	None,
 	#[default]
@@ -175,25 +243,36 @@ impl PartialEq for ConstructionArgs {
 	}
 }
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ProtoNode {
 	pub construction_args: ConstructionArgs,
 	pub input: ProtoNodeInput,
 	pub name: String,
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub enum ProtoNodeInput {
 	None,
 	#[default]
 	Network,
 	Node(NodeId),
+	/// The node is given the input node itself, to evaluate it with inputs of its choosing
+	Lambda(NodeId),
 }
 
 impl NodeInput {
 	fn map_ids(&mut self, f: impl Fn(NodeId) -> NodeId) {
-		if let NodeInput::Node(id) = self {
-			*self = NodeInput::Node(f(*id))
+		match self {
+			NodeInput::Node(id) | NodeInput::Lambda(id) => *id = f(*id),
+			NodeInput::Value(_) | NodeInput::Network => {}
+		}
+	}
+
+	/// The node this input depends on, either on its output or on the node itself.
+	pub fn source(&self) -> Option<NodeId> {
+		match self {
+			NodeInput::Node(id) | NodeInput::Lambda(id) => Some(*id),
+			NodeInput::Value(_) | NodeInput::Network => None,
 		}
 	}
 }
@@ -245,16 +324,17 @@ impl NodeNetwork {
 				// Connect all network inputs to either the parent network nodes, or newly created value nodes.
 				inner_network.map_ids(|inner_id| map_ids(id, inner_id));
 				let new_nodes = inner_network.nodes.keys().cloned().collect::<Vec<_>>();
-				// Copy nodes from the inner network into the parent network
+				// Copy nodes and definitions from the inner network into the parent network
 				self.nodes.extend(inner_network.nodes);
+				self.definitions.extend(inner_network.definitions);
 
 				let mut network_offsets = HashMap::new();
 				for (document_input, network_input) in node.inputs.into_iter().zip(inner_network.inputs.iter()) {
 					let offset = network_offsets.entry(network_input).or_insert(0);
 					match document_input {
-						NodeInput::Node(node) => {
+						NodeInput::Node(_) | NodeInput::Lambda(_) => {
 							let network_input = self.nodes.get_mut(network_input).unwrap();
-							network_input.populate_first_network_input(node, *offset);
+							network_input.populate_first_network_input(document_input, *offset);
 						}
 						NodeInput::Value(value) => {
 							let name = format!("Value: {:?}", value);
@@ -267,7 +347,7 @@ impl NodeNetwork {
 							assert!(!self.nodes.contains_key(&new_id));
 							self.nodes.insert(new_id, value_node);
 							let network_input = self.nodes.get_mut(network_input).unwrap();
-							network_input.populate_first_network_input(new_id, *offset);
+							network_input.populate_first_network_input(NodeInput::Node(new_id), *offset);
 						}
 						NodeInput::Network => {
 							*network_offsets.get_mut(network_input).unwrap() += 1;
@@ -286,15 +366,172 @@ impl NodeNetwork {
 			DocumentNodeImplementation::ProtoNode(proto_node) => {
 				node.implementation = DocumentNodeImplementation::ProtoNode(proto_node);
 			}
+			DocumentNodeImplementation::Definition(definition) => {
+				// Instantiate a copy of the definition's network and flatten it like any other nested network
+				let network = self.definitions.get(&definition).expect("The node references a definition which does not exist").network.clone();
+				node.implementation = DocumentNodeImplementation::Network(network);
+				self.nodes.insert(id, node);
+				return self.flatten_with_fns(id, map_ids, gen_id);
+			}
 		}
 		assert!(!self.nodes.contains_key(&id), "Trying to insert a node into the network caused an id conflict");
 		self.nodes.insert(id, node);
 	}
 
-	pub fn resolve_proto_nodes(&mut self) {
+	/// Move the given nodes into a new [`NodeDefinition`] and replace them with a single node instancing it.
+	///
+	/// Inputs coming from outside of the selection as well as constant values are exposed as inputs of the definition.
+	/// Returns the id of the new node, or `None` if the selection is empty or does not have exactly one output used outside of it.
+	pub fn collapse_nodes(&mut self, selected: &[NodeId], name: String) -> Option<NodeId> {
+		if selected.is_empty() || selected.iter().any(|id| !self.nodes.contains_key(id)) {
+			return None;
+		}
+
+		// Find the single node whose output is used by the rest of the network
+		let used_outside = |id: NodeId| {
+			self.output == id
+				|| self
+					.nodes
+					.iter()
+					.filter(|(other, _)| !selected.contains(other))
+					.any(|(_, node)| node.inputs.iter().any(|input| input.source() == Some(id)))
+		};
+		let outputs = selected.iter().copied().filter(|&id| used_outside(id)).collect::<Vec<_>>();
+		let output = match outputs.as_slice() {
+			[output] => *output,
+			_ => return None,
+		};
+
+		let node_id = generate_uuid();
+		let definition_id = generate_uuid();
+		let mut network = NodeNetwork { output, ..Default::default() };
+		let mut instance_inputs = Vec::new();
+		let mut input_names = Vec::new();
+
+		let mut sorted = selected.to_vec();
+		sorted.sort_unstable();
+		sorted.dedup();
+		for id in sorted {
+			let mut node = self.nodes.remove(&id).unwrap();
+			for (index, input) in node.inputs.iter_mut().enumerate() {
+				if matches!(input.source(), Some(source) if selected.contains(&source)) {
+					continue;
+				}
+				let exposed = std::mem::replace(input, NodeInput::Network);
+				if exposed == NodeInput::Network {
+					if let Some(network_input) = self.inputs.iter_mut().find(|network_input| **network_input == id) {
+						*network_input = node_id;
+					}
+				}
+				network.inputs.push(id);
+				instance_inputs.push(exposed);
+				input_names.push(format!("{} {}", node.name, index));
+			}
+			network.nodes.insert(id, node);
+		}
+
+		// Redirect everything which used the output of the selection to the new node
+		if self.output == output {
+			self.output = node_id;
+		}
 		for node in self.nodes.values_mut() {
-			node.resolve_proto_nodes();
+			node.inputs.iter_mut().for_each(|input| input.map_ids(|id| if id == output { node_id } else { id }));
 		}
+
+		self.definitions.insert(
+			definition_id,
+			NodeDefinition {
+				name: name.clone(),
+				input_names,
+				network,
+			},
+		);
+		self.nodes.insert(
+			node_id,
+			DocumentNode {
+				name,
+				inputs: instance_inputs,
+				implementation: DocumentNodeImplementation::Definition(definition_id),
+			},
+		);
+		Some(node_id)
+	}
+
+	/// Get the network at the given path of node ids, used to edit the contents of nested networks.
+	///
+	/// Entering a node which instances a definition yields the network of that definition, so any edits apply to all of its instances.
+	pub fn nested_network_mut(&mut self, path: &[NodeId]) -> Option<&mut NodeNetwork> {
+		// Find the last definition along the path, since definitions are stored in the root network
+		let mut network = &*self;
+		let mut start = None;
+		for (index, id) in path.iter().enumerate() {
+			network = match &network.nodes.get(id)?.implementation {
+				DocumentNodeImplementation::Network(inner) => inner,
+				DocumentNodeImplementation::Definition(definition) => {
+					start = Some((index + 1, *definition));
+					&self.definitions.get(definition)?.network
+				}
+				DocumentNodeImplementation::ProtoNode(_) => return None,
+			};
+		}
+
+		let (mut network, rest) = match start {
+			Some((index, definition)) => (&mut self.definitions.get_mut(&definition)?.network, &path[index..]),
+			None => (self, path),
+		};
+		for id in rest {
+			network = match &mut network.nodes.get_mut(id)?.implementation {
+				DocumentNodeImplementation::Network(inner) => inner,
+				_ => return None,
+			};
+		}
+		Some(network)
+	}
+
+	/// Ids of the given node and all nodes which directly or indirectly depend on its output.
+	/// These are the only nodes whose cached outputs have to be invalidated when the given node changes.
+	pub fn downstream_nodes(&self, node: NodeId) -> HashSet<NodeId> {
+		let mut downstream = HashSet::from([node]);
+		let mut stack = vec![node];
+		while let Some(current) = stack.pop() {
+			for (&id, other) in &self.nodes {
+				if other.inputs.iter().any(|input| input.source() == Some(current)) && downstream.insert(id) {
+					stack.push(id);
+				}
+			}
+		}
+		downstream
+	}
+
+	/// Group the nodes into levels which only depend on nodes of earlier levels.
+	/// The nodes within one level are independent of each other and can be evaluated concurrently.
+	///
+	/// Fails if the network contains a cycle, attributing the error to one of the nodes in it.
+	pub fn evaluation_levels(&self) -> NodeResult<Vec<Vec<NodeId>>> {
+		let mut levels: Vec<Vec<NodeId>> = Vec::new();
+		let mut evaluated = HashSet::new();
+		let mut remaining = self.nodes.keys().copied().collect::<Vec<_>>();
+		remaining.sort_unstable();
+
+		while !remaining.is_empty() {
+			let ready = |id: &NodeId| self.nodes[id].dependencies().all(|dependency| evaluated.contains(&dependency) || !self.nodes.contains_key(&dependency));
+			let (level, rest): (Vec<_>, Vec<_>) = remaining.into_iter().partition(ready);
+			if level.is_empty() {
+				return Err(NodeError::new(rest[0], "The node depends on its own output"));
+			}
+			evaluated.extend(level.iter().copied());
+			levels.push(level);
+			remaining = rest;
+		}
+		Ok(levels)
+	}
+
+	/// Turn the inputs of all proto nodes into construction arguments, failing on the first node whose inputs are invalid.
+	pub fn resolve_proto_nodes(&mut self) -> NodeResult<()> {
+		for (&id, node) in self.nodes.iter_mut() {
+			node.resolve_proto_nodes(id)?;
+		}
+		Ok(())
 	}
 }
 
@@ -347,6 +584,7 @@ mod test {
 			]
 			.into_iter()
 			.collect(),
+			..Default::default()
 		}
 	}
 
@@ -377,10 +615,31 @@ mod test {
 			]
 			.into_iter()
 			.collect(),
+			..Default::default()
 		};
 		assert_eq!(network, maped_add);
 	}
 
+	#[test]
+	fn downstream_nodes() {
+		let network = add_network();
+		assert_eq!(network.downstream_nodes(0), HashSet::from([0, 1]));
+		assert_eq!(network.downstream_nodes(1), HashSet::from([1]));
+	}
+
+	#[test]
+	fn evaluation_levels() {
+		let network = add_network();
+		assert_eq!(network.evaluation_levels(), Ok(vec![vec![0], vec![1]]));
+	}
+
+	#[test]
+	fn evaluation_levels_of_resolved_network() {
+		let mut network = flat_network();
+		network.resolve_proto_nodes().unwrap();
+		assert_eq!(network.evaluation_levels(), Ok(vec![vec![14], vec![10], vec![11], vec![1]]));
+	}
+
 	#[test]
 	fn flatten_add() {
 		let mut network = NodeNetwork {
@@ -396,6 +655,7 @@ mod test {
 			)]
 			.into_iter()
 			.collect(),
+			..Default::default()
 		};
 		network.flatten_with_fns(1, |self_id, inner_id| self_id * 10 + inner_id, gen_node_id);
 		let flat_network = flat_network();
@@ -405,6 +665,39 @@ mod test {
 		assert_eq!(flat_network, network);
 	}
 
+	#[test]
+	fn collapse_and_instance_definition() {
+		let mut network = add_network();
+		let node_id = network.collapse_nodes(&[0, 1], "Add".into()).unwrap();
+		assert_eq!(network.nodes.len(), 1);
+		assert_eq!(network.inputs, vec![node_id, node_id]);
+		assert_eq!(network.output, node_id);
+
+		let definition = match network.nodes[&node_id].implementation {
+			DocumentNodeImplementation::Definition(definition) => definition,
+			_ => panic!("the collapsed node should instance a definition"),
+		};
+		assert_eq!(network.definitions[&definition].network.inputs, vec![0, 0]);
+		assert_eq!(network.nested_network_mut(&[node_id]).unwrap().nodes.len(), 2);
+
+		// Instance the same definition a second time, fed by the first one
+		network.nodes.insert(
+			100,
+			DocumentNode {
+				name: "Add".into(),
+				inputs: vec![NodeInput::Node(node_id), NodeInput::Value(2_u32.into_any())],
+				implementation: DocumentNodeImplementation::Definition(definition),
+			},
+		);
+		network.output = 100;
+		network.flatten(100);
+		network.flatten(node_id);
+
+		// Two instances with two nodes each, their two id nodes and the value node
+		assert_eq!(network.nodes.len(), 7);
+		assert!(network.nodes.values().all(|node| matches!(node.implementation, DocumentNodeImplementation::ProtoNode(_))));
+	}
+
 	#[test]
 	fn resolve_proto_node_add() {
 		let mut d_node = DocumentNode {
@@ -413,7 +706,7 @@ mod test {
 			implementation: DocumentNodeImplementation::ProtoNode(ProtoNode::value("cons".into(), ConstructionArgs::Unresolved)),
 		};
 
-		d_node.resolve_proto_nodes();
+		d_node.resolve_proto_nodes(0).unwrap();
 		let reference = DocumentNode {
 			name: "cons".into(),
 			inputs: vec![],
@@ -483,9 +776,10 @@ mod test {
 			]
 			.into_iter()
 			.collect(),
+			..Default::default()
 		};
 		let mut resolved_network = flat_network();
-		resolved_network.resolve_proto_nodes();
+		resolved_network.resolve_proto_nodes().unwrap();
 
 		println!("{:#?}", resolved_network);
 		println!("{:#?}", construction_network);
@@ -532,6 +826,7 @@ mod test {
 			]
 			.into_iter()
 			.collect(),
+			..Default::default()
 		}
 	}
 }
@@ -0,0 +1,94 @@
+use crate::document::NodeId;
+use graphene_core::Node;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// An error raised while compiling or evaluating a node, attributed to the node so the frontend can show it on that node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeError {
+	/// Ids of the nodes leading from the root network to the failing node, through the nested networks containing it
+	pub path: Vec<NodeId>,
+	/// Name of the input which received the invalid value, if the error can be attributed to one
+	pub input: Option<String>,
+	pub message: String,
+}
+
+pub type NodeResult<T> = Result<T, NodeError>;
+
+impl NodeError {
+	pub fn new(node: NodeId, message: impl Into<String>) -> Self {
+		Self {
+			path: vec![node],
+			input: None,
+			message: message.into(),
+		}
+	}
+
+	pub fn with_input(mut self, input: impl Into<String>) -> Self {
+		self.input = Some(input.into());
+		self
+	}
+
+	/// Prefix the path with the node containing the failing node, when the error propagates out of a nested network.
+	pub fn within(mut self, node: NodeId) -> Self {
+		self.path.insert(0, node);
+		self
+	}
+
+	/// The id of the failing node within its own network.
+	pub fn node(&self) -> Option<NodeId> {
+		self.path.last().copied()
+	}
+}
+
+impl Display for NodeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let path = self.path.iter().map(ToString::to_string).collect::<Vec<_>>().join("/");
+		match &self.input {
+			Some(input) => write!(f, "Node {}, input '{}': {}", path, input, self.message),
+			None => write!(f, "Node {}: {}", path, self.message),
+		}
+	}
+}
+
+impl std::error::Error for NodeError {}
+
+/// Attributes the errors returned by a fallible node to the document node it was created for.
+pub struct NodeErrorContext<N> {
+	pub node: N,
+	pub id: NodeId,
+}
+
+impl<N> NodeErrorContext<N> {
+	pub const fn new(node: N, id: NodeId) -> Self {
+		Self { node, id }
+	}
+}
+
+impl<'n, N, I, O, E> Node<I> for &'n NodeErrorContext<N>
+where
+	&'n N: Node<I, Output = Result<O, E>>,
+	E: Display,
+{
+	type Output = NodeResult<O>;
+	fn eval(self, input: I) -> Self::Output {
+		(&self.node).eval(input).map_err(|error| NodeError::new(self.id, error.to_string()))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene_core::generic::FnNode;
+
+	#[test]
+	fn attribute_errors_to_nodes() {
+		let parse = NodeErrorContext::new(FnNode::new(|text: &str| text.parse::<u32>()), 4);
+		assert_eq!((&parse).eval("7"), Ok(7));
+
+		let error = (&parse).eval("seven").unwrap_err().with_input("Text").within(1);
+		assert_eq!(error.path, [1, 4]);
+		assert_eq!(error.node(), Some(4));
+		assert_eq!(error.to_string(), "Node 1/4, input 'Text': invalid digit found in string");
+	}
+}
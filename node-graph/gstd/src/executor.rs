@@ -0,0 +1,397 @@
+use crate::document::{ConstructionArgs, DocumentNodeImplementation, NodeId, NodeNetwork, ProtoNodeInput, Value};
+use crate::error::{NodeError, NodeResult};
+use crate::memo::MemoNode;
+use graphene_core::Node;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Evaluates the node connected to the lambda input of a proto node, with an input of the proto node's choosing in place of its primary input.
+pub type Lambda<'a, O> = &'a dyn Fn(O) -> Arc<O>;
+
+/// Implementation of a proto node, given the output of its primary input followed by the outputs of its parameters.
+///
+/// A proto node with a lambda input is given the lambda instead, followed by the outputs of its parameters.
+pub type ProtoNodeFn<O> = Arc<dyn Fn(&[Arc<O>], Option<Lambda<O>>) -> O + Send + Sync>;
+
+/// Wrap a closure as a [`ProtoNodeFn`], which lets the types of its arguments be inferred.
+pub fn proto_node_fn<O>(implementation: impl Fn(&[Arc<O>], Option<Lambda<O>>) -> O + Send + Sync + 'static) -> ProtoNodeFn<O> {
+	Arc::new(implementation)
+}
+
+/// Evaluates a registered proto node, so it can be cached by a [`MemoNode`].
+struct ProtoNodeEvaluator<O>(ProtoNodeFn<O>);
+
+impl<O> Clone for ProtoNodeEvaluator<O> {
+	fn clone(&self) -> Self {
+		Self(self.0.clone())
+	}
+}
+
+impl<O> Node<Vec<Arc<O>>> for ProtoNodeEvaluator<O> {
+	type Output = Arc<O>;
+	fn eval(self, inputs: Vec<Arc<O>>) -> Self::Output {
+		Arc::new(self.0(&inputs, None))
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+	Network,
+	Node(NodeId),
+}
+
+enum ExecutorNode<O> {
+	Value(Arc<O>),
+	/// Passes on the output of its input, like the `id` nodes left behind by flattening nested networks
+	Identity(Source),
+	Proto {
+		name: String,
+		primary: Option<Source>,
+		parameters: Vec<Source>,
+		implementation: ProtoNodeFn<O>,
+		/// Caches the output for the outputs of the inputs, which include the parameters of the node
+		memo: MemoNode<ProtoNodeEvaluator<O>, Vec<Arc<O>>>,
+	},
+	/// A proto node with a lambda input, which is not cached since its output also depends on the inputs of the lambda node
+	Lambda {
+		lambda: NodeId,
+		parameters: Vec<Source>,
+		implementation: ProtoNodeFn<O>,
+	},
+}
+
+impl<O> ExecutorNode<O> {
+	/// Take over the cache of the node this node replaces, if both are the same proto node connected to the same nodes.
+	fn keep_cache(&mut self, old: Self) {
+		if let (
+			Self::Proto { name, primary, parameters, memo, .. },
+			Self::Proto {
+				name: old_name,
+				primary: old_primary,
+				parameters: old_parameters,
+				memo: old_memo,
+				..
+			},
+		) = (self, old)
+		{
+			if *name == old_name && *primary == old_primary && *parameters == old_parameters {
+				*memo = old_memo;
+			}
+		}
+	}
+
+	/// The nodes whose outputs are needed to evaluate this node, or to evaluate it as the lambda of another node.
+	fn dependencies(&self, as_lambda: bool) -> Vec<(NodeId, bool)> {
+		let node = |source: &Source| match source {
+			Source::Network => None,
+			Source::Node(node) => Some((*node, false)),
+		};
+		match self {
+			Self::Value(_) => Vec::new(),
+			Self::Identity(input) => node(input).filter(|_| !as_lambda).into_iter().collect(),
+			Self::Proto { primary, parameters, .. } => primary.iter().filter(|_| !as_lambda).chain(parameters).filter_map(node).collect(),
+			Self::Lambda { lambda, parameters, .. } => parameters.iter().filter_map(node).chain([(*lambda, true)]).collect(),
+		}
+	}
+}
+
+/// Evaluates a flattened network with resolved proto nodes, level by level in the order of [`NodeNetwork::evaluation_levels`].
+///
+/// The nodes within a level are independent of each other, so they are evaluated concurrently on the rayon thread pool.
+/// Targets without thread support, such as wasm, evaluate them one after another.
+/// Outputs are reference counted, so the output of a node used by several branches, like a large raster image, is shared rather than cloned.
+///
+/// The output of every proto node is memoized, so evaluating the network again only evaluates the nodes whose inputs changed.
+/// Nodes which are only connected to lambda inputs are not evaluated on their own, but whenever the proto node they are connected to evaluates them.
+pub struct NetworkExecutor<O> {
+	nodes: HashMap<NodeId, ExecutorNode<O>>,
+	levels: Vec<Vec<NodeId>>,
+	output: NodeId,
+}
+
+impl<O: PartialEq + Send + Sync> NetworkExecutor<O> {
+	/// Prepare a network for evaluation, looking up the implementation of every proto node by its name in the registry.
+	///
+	/// The values of value nodes are converted with the given function, which returns `None` for values of unsupported types.
+	pub fn new(network: &NodeNetwork, registry: impl Fn(&str) -> Option<ProtoNodeFn<O>>, value: impl Fn(&Value) -> Option<O>) -> NodeResult<Self> {
+		let levels = network.evaluation_levels()?;
+		let source = |id: NodeId, input: NodeId| {
+			if network.nodes.contains_key(&input) {
+				Ok(Source::Node(input))
+			} else {
+				Err(NodeError::new(id, format!("The node is connected to the node {}, which does not exist", input)))
+			}
+		};
+
+		let mut nodes = HashMap::with_capacity(network.nodes.len());
+		for (&id, node) in &network.nodes {
+			let proto = match &node.implementation {
+				DocumentNodeImplementation::ProtoNode(proto) => proto,
+				_ => return Err(NodeError::new(id, "The network has to be flattened before it can be evaluated")),
+			};
+			let primary = match proto.input {
+				ProtoNodeInput::None => None,
+				ProtoNodeInput::Network => Some(Source::Network),
+				ProtoNodeInput::Node(input) | ProtoNodeInput::Lambda(input) => Some(source(id, input)?),
+			};
+			let parameters = match &proto.construction_args {
+				ConstructionArgs::Value(constant) => {
+					let constant = value(constant).ok_or_else(|| NodeError::new(id, format!("Values of the type {} are not supported", constant.as_ref().type_name())))?;
+					nodes.insert(id, ExecutorNode::Value(Arc::new(constant)));
+					continue;
+				}
+				ConstructionArgs::Unresolved => return Err(NodeError::new(id, "The proto node has to be resolved before it can be evaluated")),
+				ConstructionArgs::None => Vec::new(),
+				ConstructionArgs::Nodes(parameters) => parameters.iter().map(|&parameter| source(id, parameter)).collect::<NodeResult<Vec<_>>>()?,
+			};
+
+			let lambda = matches!(proto.input, ProtoNodeInput::Lambda(_));
+			let implementation = |name: &str| registry(name).ok_or_else(|| NodeError::new(id, format!("No proto node named '{}' is registered", name)));
+			let executor_node = match (proto.name.as_str(), primary) {
+				("id", Some(input)) if parameters.is_empty() && !lambda => ExecutorNode::Identity(input),
+				(name, Some(Source::Node(lambda_node))) if lambda => ExecutorNode::Lambda {
+					lambda: lambda_node,
+					parameters,
+					implementation: implementation(name)?,
+				},
+				(name, primary) => {
+					let implementation = implementation(name)?;
+					ExecutorNode::Proto {
+						name: name.to_string(),
+						primary,
+						parameters,
+						memo: MemoNode::new(ProtoNodeEvaluator(implementation.clone())),
+						implementation,
+					}
+				}
+			};
+			nodes.insert(id, executor_node);
+		}
+
+		if !nodes.contains_key(&network.output) {
+			return Err(NodeError::new(network.output, "The output node of the network does not exist"));
+		}
+
+		// Skip the nodes which only serve as lambdas, since they may not even accept the input they are connected to
+		let mut visited = HashSet::new();
+		let mut stack = vec![(network.output, false)];
+		while let Some((id, as_lambda)) = stack.pop() {
+			if visited.insert((id, as_lambda)) {
+				stack.extend(nodes[&id].dependencies(as_lambda));
+			}
+		}
+		let levels = levels
+			.into_iter()
+			.map(|level| level.into_iter().filter(|&id| visited.contains(&(id, false))).collect::<Vec<_>>())
+			.filter(|level| !level.is_empty())
+			.collect();
+
+		Ok(Self {
+			nodes,
+			levels,
+			output: network.output,
+		})
+	}
+
+	/// Prepare the changed network for evaluation like [`NetworkExecutor::new`], keeping the cached outputs of the nodes which are unchanged.
+	///
+	/// A node is unchanged if it has the same name and is connected to the same nodes, since the registry is expected to stay the same.
+	/// Nodes downstream of a changed node keep their cache as well, and are only evaluated again if their inputs turn out to be different.
+	pub fn update(&mut self, network: &NodeNetwork, registry: impl Fn(&str) -> Option<ProtoNodeFn<O>>, value: impl Fn(&Value) -> Option<O>) -> NodeResult<()> {
+		let mut updated = Self::new(network, registry, value)?;
+		for (id, node) in updated.nodes.iter_mut() {
+			if let Some(old) = self.nodes.remove(id) {
+				node.keep_cache(old);
+			}
+		}
+		*self = updated;
+		Ok(())
+	}
+
+	/// The number of evaluations of a proto node which were answered from its cache, and the number which had to evaluate it.
+	pub fn cache_statistics(&self, node: NodeId) -> Option<(u64, u64)> {
+		match self.nodes.get(&node)? {
+			ExecutorNode::Proto { memo, .. } => Some(memo.cache_statistics()),
+			_ => None,
+		}
+	}
+
+	/// Evaluate the network for the given input, returning the output of its output node.
+	pub fn execute(&self, input: O) -> Arc<O> {
+		let input = Arc::new(input);
+		let mut outputs = HashMap::with_capacity(self.nodes.len());
+		for level in &self.levels {
+			let evaluate = |&id: &NodeId| (id, self.evaluate(id, &input, &outputs));
+
+			#[cfg(not(target_arch = "wasm32"))]
+			let evaluated = {
+				use rayon::prelude::*;
+				level.par_iter().map(evaluate).collect::<Vec<_>>()
+			};
+			#[cfg(target_arch = "wasm32")]
+			let evaluated = level.iter().map(evaluate).collect::<Vec<_>>();
+
+			outputs.extend(evaluated);
+		}
+		outputs.remove(&self.output).expect("the output node is evaluated in one of the levels")
+	}
+
+	/// Evaluate a single node, whose inputs have all been evaluated in earlier levels.
+	fn evaluate(&self, id: NodeId, input: &Arc<O>, outputs: &HashMap<NodeId, Arc<O>>) -> Arc<O> {
+		let source = |source: &Source| match source {
+			Source::Network => input.clone(),
+			Source::Node(node) => outputs[node].clone(),
+		};
+		match &self.nodes[&id] {
+			ExecutorNode::Value(value) => value.clone(),
+			ExecutorNode::Identity(input) => source(input),
+			ExecutorNode::Proto { primary, parameters, memo, .. } => memo.eval(primary.iter().chain(parameters).map(source).collect()),
+			ExecutorNode::Lambda { lambda, parameters, implementation } => {
+				let lambda = |lambda_input: O| self.evaluate_lambda(*lambda, Arc::new(lambda_input), input, outputs);
+				Arc::new(implementation(&parameters.iter().map(source).collect::<Vec<_>>(), Some(&lambda)))
+			}
+		}
+	}
+
+	/// Evaluate a node connected to a lambda input with the given input in place of its primary input, which is not cached.
+	fn evaluate_lambda(&self, id: NodeId, lambda_input: Arc<O>, input: &Arc<O>, outputs: &HashMap<NodeId, Arc<O>>) -> Arc<O> {
+		let source = |source: &Source| match source {
+			Source::Network => input.clone(),
+			Source::Node(node) => outputs[node].clone(),
+		};
+		match &self.nodes[&id] {
+			ExecutorNode::Value(value) => value.clone(),
+			ExecutorNode::Identity(_) => lambda_input,
+			ExecutorNode::Proto {
+				primary, parameters, implementation, ..
+			} => {
+				let inputs = primary.iter().map(|_| lambda_input.clone()).chain(parameters.iter().map(source)).collect::<Vec<_>>();
+				Arc::new(implementation(&inputs, None))
+			}
+			ExecutorNode::Lambda { .. } => self.evaluate(id, input, outputs),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::document::{DocumentNode, IntoValue, ProtoNode};
+
+	fn proto_node(name: &str, input: ProtoNodeInput, construction_args: ConstructionArgs) -> DocumentNode {
+		DocumentNode {
+			name: name.into(),
+			inputs: vec![],
+			implementation: DocumentNodeImplementation::ProtoNode(ProtoNode {
+				name: name.into(),
+				input,
+				construction_args,
+			}),
+		}
+	}
+
+	fn registry(name: &str) -> Option<ProtoNodeFn<Vec<u32>>> {
+		Some(match name {
+			"cons" => proto_node_fn(|inputs, _| inputs.iter().flat_map(|input| input.iter().copied()).collect()),
+			"add" => proto_node_fn(|inputs, _| vec![inputs[0].iter().sum()]),
+			"double" => proto_node_fn(|inputs, _| inputs[0].iter().map(|x| x * 2).collect()),
+			"map" => proto_node_fn(|inputs, lambda| {
+				let lambda = lambda.expect("map has a lambda input");
+				inputs[0].iter().map(|&x| lambda(vec![x]).iter().sum()).collect()
+			}),
+			_ => return None,
+		})
+	}
+
+	fn value(value: &Value) -> Option<Vec<u32>> {
+		dyn_any::downcast_ref::<u32>(value.as_ref().as_dyn_any()).map(|&value| vec![value])
+	}
+
+	/// Adds the input to its double and to a constant, in two branches which are evaluated concurrently: `(x + 2) + 2x`.
+	fn branching_network() -> NodeNetwork {
+		NodeNetwork {
+			inputs: vec![1, 2],
+			output: 6,
+			nodes: [
+				(0, proto_node("value", ProtoNodeInput::None, ConstructionArgs::Value(2_u32.into_any()))),
+				(1, proto_node("cons", ProtoNodeInput::Network, ConstructionArgs::Nodes(vec![0]))),
+				(2, proto_node("double", ProtoNodeInput::Network, ConstructionArgs::None)),
+				(3, proto_node("add", ProtoNodeInput::Node(1), ConstructionArgs::None)),
+				(4, proto_node("cons", ProtoNodeInput::Node(3), ConstructionArgs::Nodes(vec![2]))),
+				(5, proto_node("add", ProtoNodeInput::Node(4), ConstructionArgs::None)),
+				(6, proto_node("id", ProtoNodeInput::Node(5), ConstructionArgs::None)),
+			]
+			.into_iter()
+			.collect(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn execute_branching_network() {
+		let network = branching_network();
+		assert_eq!(network.evaluation_levels().unwrap()[0], vec![0, 2]);
+
+		let executor = NetworkExecutor::new(&network, registry, value).unwrap();
+		assert_eq!(*executor.execute(vec![5]), vec![17]);
+		assert_eq!(*executor.execute(vec![1]), vec![5]);
+	}
+
+	#[test]
+	fn only_changed_nodes_are_evaluated_again() {
+		let mut network = branching_network();
+		let mut executor = NetworkExecutor::new(&network, registry, value).unwrap();
+		assert_eq!(*executor.execute(vec![5]), vec![17]);
+		assert_eq!(*executor.execute(vec![5]), vec![17]);
+		assert_eq!(executor.cache_statistics(5), Some((1, 1)));
+
+		// Changing the constant only affects the branch it is part of
+		network.nodes.insert(0, proto_node("value", ProtoNodeInput::None, ConstructionArgs::Value(3_u32.into_any())));
+		executor.update(&network, registry, value).unwrap();
+		assert_eq!(*executor.execute(vec![5]), vec![18]);
+		assert_eq!(executor.cache_statistics(2), Some((2, 1)));
+		assert_eq!(executor.cache_statistics(1), Some((1, 2)));
+		assert_eq!(executor.cache_statistics(5), Some((1, 2)));
+		assert_eq!(executor.cache_statistics(0), None);
+	}
+
+	#[test]
+	fn invalid_networks_are_rejected() {
+		let error = |network: &NodeNetwork| NetworkExecutor::new(network, registry, value).err().and_then(|error| error.node());
+
+		let mut network = branching_network();
+		network.nodes.insert(2, proto_node("triple", ProtoNodeInput::Network, ConstructionArgs::None));
+		assert_eq!(error(&network), Some(2));
+
+		let mut network = branching_network();
+		network.nodes.insert(0, proto_node("value", ProtoNodeInput::None, ConstructionArgs::Value(2_f64.into_any())));
+		assert_eq!(error(&network), Some(0));
+
+		let mut network = branching_network();
+		network.nodes.insert(3, proto_node("add", ProtoNodeInput::Node(7), ConstructionArgs::None));
+		assert_eq!(error(&network), Some(3));
+	}
+
+	#[test]
+	fn lambdas_are_evaluated_by_their_proto_node() {
+		// Maps every element of the input to a sum with a constant, where the constant is a parameter of the lambda node
+		let network = NodeNetwork {
+			inputs: vec![1, 3],
+			output: 2,
+			nodes: [
+				(0, proto_node("value", ProtoNodeInput::None, ConstructionArgs::Value(10_u32.into_any()))),
+				(1, proto_node("cons", ProtoNodeInput::Network, ConstructionArgs::Nodes(vec![0]))),
+				(2, proto_node("map", ProtoNodeInput::Lambda(1), ConstructionArgs::Nodes(vec![3]))),
+				(3, proto_node("id", ProtoNodeInput::Network, ConstructionArgs::None)),
+			]
+			.into_iter()
+			.collect(),
+			..Default::default()
+		};
+
+		let executor = NetworkExecutor::new(&network, registry, value).unwrap();
+		assert_eq!(*executor.execute(vec![1, 2]), vec![11, 12]);
+		// The lambda node is only evaluated by the map node, not on its own with the input of the network
+		assert_eq!(executor.cache_statistics(1), Some((0, 0)));
+	}
+}
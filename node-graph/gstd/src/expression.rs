@@ -0,0 +1,321 @@
+use graphene_core::Node;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionError {
+	UnexpectedCharacter(char),
+	UnexpectedEnd,
+	UnknownFunction(String),
+	WrongArgumentCount { function: String, expected: usize, found: usize },
+}
+
+impl Display for ExpressionError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ExpressionError::UnexpectedCharacter(c) => write!(f, "Unexpected character '{}'", c),
+			ExpressionError::UnexpectedEnd => write!(f, "Unexpected end of expression"),
+			ExpressionError::UnknownFunction(name) => write!(f, "Unknown function '{}'", name),
+			ExpressionError::WrongArgumentCount { function, expected, found } => write!(f, "'{}' takes {} arguments but {} were given", function, expected, found),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+	Add,
+	Subtract,
+	Multiply,
+	Divide,
+	Modulo,
+	Power,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+	Sin,
+	Cos,
+	Tan,
+	Sqrt,
+	Abs,
+	Floor,
+	Ceil,
+	Round,
+	Exp,
+	Ln,
+	Min,
+	Max,
+	Pow,
+}
+
+impl Function {
+	fn from_name(name: &str) -> Option<(Self, usize)> {
+		let function = match name {
+			"sin" => (Function::Sin, 1),
+			"cos" => (Function::Cos, 1),
+			"tan" => (Function::Tan, 1),
+			"sqrt" => (Function::Sqrt, 1),
+			"abs" => (Function::Abs, 1),
+			"floor" => (Function::Floor, 1),
+			"ceil" => (Function::Ceil, 1),
+			"round" => (Function::Round, 1),
+			"exp" => (Function::Exp, 1),
+			"ln" => (Function::Ln, 1),
+			"min" => (Function::Min, 2),
+			"max" => (Function::Max, 2),
+			"pow" => (Function::Pow, 2),
+			_ => return None,
+		};
+		Some(function)
+	}
+
+	fn apply(self, arguments: &[f64]) -> f64 {
+		match self {
+			Function::Sin => arguments[0].sin(),
+			Function::Cos => arguments[0].cos(),
+			Function::Tan => arguments[0].tan(),
+			Function::Sqrt => arguments[0].sqrt(),
+			Function::Abs => arguments[0].abs(),
+			Function::Floor => arguments[0].floor(),
+			Function::Ceil => arguments[0].ceil(),
+			Function::Round => arguments[0].round(),
+			Function::Exp => arguments[0].exp(),
+			Function::Ln => arguments[0].ln(),
+			Function::Min => arguments[0].min(arguments[1]),
+			Function::Max => arguments[0].max(arguments[1]),
+			Function::Pow => arguments[0].powf(arguments[1]),
+		}
+	}
+}
+
+/// A parsed expression in which all variables have been resolved to the index of their input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+	Constant(f64),
+	Variable(usize),
+	Negate(Box<Expression>),
+	Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+	Call(Function, Vec<Expression>),
+}
+
+impl Expression {
+	pub fn evaluate(&self, inputs: &[f64]) -> f64 {
+		match self {
+			Expression::Constant(value) => *value,
+			Expression::Variable(index) => inputs.get(*index).copied().unwrap_or_default(),
+			Expression::Negate(expression) => -expression.evaluate(inputs),
+			Expression::Binary(operator, left, right) => {
+				let (left, right) = (left.evaluate(inputs), right.evaluate(inputs));
+				match operator {
+					BinaryOperator::Add => left + right,
+					BinaryOperator::Subtract => left - right,
+					BinaryOperator::Multiply => left * right,
+					BinaryOperator::Divide => left / right,
+					BinaryOperator::Modulo => left % right,
+					BinaryOperator::Power => left.powf(right),
+				}
+			}
+			Expression::Call(function, arguments) => {
+				let arguments = arguments.iter().map(|argument| argument.evaluate(inputs)).collect::<Vec<_>>();
+				function.apply(&arguments)
+			}
+		}
+	}
+}
+
+/// Recursive descent parser following the usual precedence rules, with `^` binding tighter than unary minus and associating to the right.
+struct Parser<'a> {
+	chars: std::iter::Peekable<std::str::Chars<'a>>,
+	variables: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+	fn skip_whitespace(&mut self) {
+		while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+	}
+
+	fn peek(&mut self) -> Option<char> {
+		self.skip_whitespace();
+		self.chars.peek().copied()
+	}
+
+	fn expect(&mut self, expected: char) -> Result<(), ExpressionError> {
+		match self.peek() {
+			Some(c) if c == expected => {
+				self.chars.next();
+				Ok(())
+			}
+			Some(c) => Err(ExpressionError::UnexpectedCharacter(c)),
+			None => Err(ExpressionError::UnexpectedEnd),
+		}
+	}
+
+	fn sum(&mut self) -> Result<Expression, ExpressionError> {
+		let mut left = self.product()?;
+		loop {
+			let operator = match self.peek() {
+				Some('+') => BinaryOperator::Add,
+				Some('-') => BinaryOperator::Subtract,
+				_ => return Ok(left),
+			};
+			self.chars.next();
+			left = Expression::Binary(operator, Box::new(left), Box::new(self.product()?));
+		}
+	}
+
+	fn product(&mut self) -> Result<Expression, ExpressionError> {
+		let mut left = self.unary()?;
+		loop {
+			let operator = match self.peek() {
+				Some('*') => BinaryOperator::Multiply,
+				Some('/') => BinaryOperator::Divide,
+				Some('%') => BinaryOperator::Modulo,
+				_ => return Ok(left),
+			};
+			self.chars.next();
+			left = Expression::Binary(operator, Box::new(left), Box::new(self.unary()?));
+		}
+	}
+
+	fn unary(&mut self) -> Result<Expression, ExpressionError> {
+		if self.peek() == Some('-') {
+			self.chars.next();
+			return Ok(Expression::Negate(Box::new(self.unary()?)));
+		}
+		self.power()
+	}
+
+	fn power(&mut self) -> Result<Expression, ExpressionError> {
+		let base = self.atom()?;
+		if self.peek() == Some('^') {
+			self.chars.next();
+			return Ok(Expression::Binary(BinaryOperator::Power, Box::new(base), Box::new(self.unary()?)));
+		}
+		Ok(base)
+	}
+
+	fn atom(&mut self) -> Result<Expression, ExpressionError> {
+		match self.peek() {
+			Some('(') => {
+				self.chars.next();
+				let expression = self.sum()?;
+				self.expect(')')?;
+				Ok(expression)
+			}
+			Some(c) if c.is_ascii_digit() || c == '.' => {
+				let mut number = String::new();
+				while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+					number.push(c);
+				}
+				number.parse().map(Expression::Constant).map_err(|_| ExpressionError::UnexpectedCharacter('.'))
+			}
+			Some(c) if c.is_alphabetic() || c == '_' => {
+				let mut name = String::new();
+				// Dots are allowed within names so properties of other layers can be referenced, such as `circle.radius`
+				while let Some(c) = self.chars.next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '.') {
+					name.push(c);
+				}
+				if self.peek() == Some('(') {
+					return self.call(name);
+				}
+				Ok(match name.as_str() {
+					"pi" => Expression::Constant(std::f64::consts::PI),
+					"tau" => Expression::Constant(std::f64::consts::TAU),
+					"e" => Expression::Constant(std::f64::consts::E),
+					_ => Expression::Variable(self.variable_index(name)),
+				})
+			}
+			Some(c) => Err(ExpressionError::UnexpectedCharacter(c)),
+			None => Err(ExpressionError::UnexpectedEnd),
+		}
+	}
+
+	fn call(&mut self, name: String) -> Result<Expression, ExpressionError> {
+		let (function, expected) = Function::from_name(&name).ok_or_else(|| ExpressionError::UnknownFunction(name.clone()))?;
+		self.expect('(')?;
+		let mut arguments = Vec::new();
+		if self.peek() != Some(')') {
+			arguments.push(self.sum()?);
+			while self.peek() == Some(',') {
+				self.chars.next();
+				arguments.push(self.sum()?);
+			}
+		}
+		self.expect(')')?;
+		if arguments.len() != expected {
+			return Err(ExpressionError::WrongArgumentCount {
+				function: name,
+				expected,
+				found: arguments.len(),
+			});
+		}
+		Ok(Expression::Call(function, arguments))
+	}
+
+	fn variable_index(&mut self, name: String) -> usize {
+		self.variables.iter().position(|variable| *variable == name).unwrap_or_else(|| {
+			self.variables.push(name);
+			self.variables.len() - 1
+		})
+	}
+}
+
+/// Evaluates a user written math expression such as `sin(a * 3) + b / 2`.
+///
+/// The expression is parsed once on construction. Its named variables are exposed as inputs in the order of their first appearance,
+/// so the node is evaluated with a slice holding one value per entry of [`ExpressionNode::inputs`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionNode {
+	expression: Expression,
+	inputs: Vec<String>,
+}
+
+impl ExpressionNode {
+	pub fn new(source: &str) -> Result<Self, ExpressionError> {
+		let mut parser = Parser {
+			chars: source.chars().peekable(),
+			variables: Vec::new(),
+		};
+		let expression = parser.sum()?;
+		if let Some(c) = parser.peek() {
+			return Err(ExpressionError::UnexpectedCharacter(c));
+		}
+		Ok(Self { expression, inputs: parser.variables })
+	}
+
+	/// The names of the variables used by the expression, which are exposed as the parameters of the node.
+	pub fn inputs(&self) -> &[String] {
+		&self.inputs
+	}
+}
+
+impl<'n, 'i> Node<&'i [f64]> for &'n ExpressionNode {
+	type Output = f64;
+	fn eval(self, input: &'i [f64]) -> Self::Output {
+		self.expression.evaluate(input)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn evaluate_expression() {
+		let node = ExpressionNode::new("sin(a * 3) + b / 2").unwrap();
+		assert_eq!(node.inputs(), ["a", "b"]);
+		assert_eq!((&node).eval(&[0., 5.][..]), 2.5);
+
+		let node = ExpressionNode::new("-2 ^ 2 + max(x, 1) * (x - 1)").unwrap();
+		assert_eq!((&node).eval(&[3.][..]), 2.);
+
+		assert_eq!(
+			ExpressionNode::new("cos(1, 2)").unwrap_err(),
+			ExpressionError::WrongArgumentCount {
+				function: "cos".into(),
+				expected: 1,
+				found: 2
+			}
+		);
+		assert_eq!(ExpressionNode::new("(a + 1").unwrap_err(), ExpressionError::UnexpectedEnd);
+	}
+}
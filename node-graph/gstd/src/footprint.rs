@@ -0,0 +1,81 @@
+use glam::{DAffine2, DVec2, UVec2};
+use graphene_core::Node;
+
+/// Describes which part of the document is rendered and at which resolution, so raster nodes can produce exactly the pixels that are needed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Footprint {
+	/// Transformation from document space to the pixels of the rendered image
+	pub transform: DAffine2,
+	/// Size of the rendered image in pixels
+	pub resolution: UVec2,
+}
+
+impl Footprint {
+	pub fn new(transform: DAffine2, resolution: UVec2) -> Self {
+		Self { transform, resolution }
+	}
+
+	/// The size of a pixel in document space, used to avoid generating detail which would not be visible.
+	pub fn pixel_size(&self) -> f64 {
+		self.transform.matrix2.determinant().abs().sqrt().recip()
+	}
+
+	/// Document space position of the center of the given pixel.
+	pub fn pixel_center(&self, x: u32, y: u32) -> DVec2 {
+		self.transform.inverse().transform_point2(DVec2::new(x as f64 + 0.5, y as f64 + 0.5))
+	}
+
+	/// Axis aligned bounding box of the rendered area in document space.
+	pub fn visible_bounds(&self) -> [DVec2; 2] {
+		let inverse = self.transform.inverse();
+		let size = self.resolution.as_f64();
+		let corners = [DVec2::ZERO, DVec2::new(size.x, 0.), size, DVec2::new(0., size.y)].map(|corner| inverse.transform_point2(corner));
+		let min = corners.iter().fold(DVec2::splat(f64::INFINITY), |min, &corner| min.min(corner));
+		let max = corners.iter().fold(DVec2::splat(f64::NEG_INFINITY), |max, &corner| max.max(corner));
+		[min, max]
+	}
+
+	/// Whether content with the given document space bounding box is at least partially visible.
+	pub fn intersects(&self, bounds: [DVec2; 2]) -> bool {
+		let [min, max] = self.visible_bounds();
+		bounds[0].x <= max.x && bounds[1].x >= min.x && bounds[0].y <= max.y && bounds[1].y >= min.y
+	}
+}
+
+/// Skips evaluating the inner node when its content, with the given bounds in document space, lies entirely outside of the footprint.
+pub struct CullNode<N> {
+	pub node: N,
+	pub bounds: [DVec2; 2],
+}
+
+impl<'n, N> Node<Footprint> for &'n CullNode<N>
+where
+	&'n N: Node<Footprint>,
+{
+	type Output = Option<<&'n N as Node<Footprint>>::Output>;
+	fn eval(self, footprint: Footprint) -> Self::Output {
+		footprint.intersects(self.bounds).then(|| (&self.node).eval(footprint))
+	}
+}
+
+impl<N> CullNode<N> {
+	pub const fn new(node: N, bounds: [DVec2; 2]) -> Self {
+		Self { node, bounds }
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn footprint_bounds() {
+		// Viewing the document at 200% zoom, scrolled 100 document units to the right
+		let footprint = Footprint::new(DAffine2::from_scale(DVec2::splat(2.)) * DAffine2::from_translation(DVec2::new(-100., 0.)), UVec2::new(400, 200));
+		assert_eq!(footprint.pixel_size(), 0.5);
+		assert_eq!(footprint.visible_bounds(), [DVec2::new(100., 0.), DVec2::new(300., 100.)]);
+		assert_eq!(footprint.pixel_center(0, 0), DVec2::new(100.25, 0.25));
+		assert!(footprint.intersects([DVec2::new(250., 50.), DVec2::new(350., 150.)]));
+		assert!(!footprint.intersects([DVec2::new(0., 0.), DVec2::new(50., 50.)]));
+	}
+}
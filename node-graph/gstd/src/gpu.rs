@@ -0,0 +1,778 @@
+use crate::composite::CompositeNode;
+use crate::raster::{BlurNode, BlurQuality, Image, MapImageNode};
+use graphene::layers::blend_mode::BlendMode;
+use graphene_core::raster::color::Color;
+use graphene_core::Node;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const WORKGROUP_SIZE: u32 = 64;
+/// Width and height of the workgroups of shaders which run once for every pixel of a whole image, matching their `@workgroup_size(8, 8)`
+const TILE_SIZE: u32 = 8;
+const PIXEL_SIZE: u64 = std::mem::size_of::<[f32; 4]>() as u64;
+
+/// WGSL implementation of [`graphene_core::raster::GrayscaleNode`] for use with [`GpuMapImageNode`].
+pub const GRAYSCALE_SHADER: &str = "fn map(color: vec4<f32>) -> vec4<f32> {
+	let average = (color.r + color.g + color.b) / 3.0;
+	return vec4<f32>(average, average, average, color.a);
+}";
+
+/// Multiplies the color by its alpha before blurring, so transparent pixels don't bleed their color into the result.
+const PREMULTIPLY_SHADER: &str = "fn map(color: vec4<f32>) -> vec4<f32> {
+	return vec4<f32>(color.rgb * color.a, color.a);
+}";
+
+const UNPREMULTIPLY_SHADER: &str = "fn map(color: vec4<f32>) -> vec4<f32> {
+	if (color.a > 0.0) {
+		return vec4<f32>(color.rgb / color.a, color.a);
+	}
+	return vec4<f32>(vec3<f32>(0.0), color.a);
+}";
+
+/// WGSL implementation of one pass of [`BlurNode`], which convolves the rows or the columns of the image with the kernel.
+/// Pixels outside of the image repeat the closest edge pixel.
+const BLUR_SHADER: &str = "struct Parameters {
+	width: u32,
+	height: u32,
+	half_width: u32,
+	horizontal: u32,
+}
+
+@group(0) @binding(0) var<storage, read> source: array<vec4<f32>>;
+@group(0) @binding(1) var<storage, read_write> destination: array<vec4<f32>>;
+@group(0) @binding(2) var<storage, read> weights: array<f32>;
+@group(0) @binding(3) var<uniform> parameters: Parameters;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+	if (id.x >= parameters.width || id.y >= parameters.height) {
+		return;
+	}
+
+	let size = vec2<i32>(i32(parameters.width), i32(parameters.height));
+	var direction = vec2<i32>(0, 1);
+	if (parameters.horizontal != 0u) {
+		direction = vec2<i32>(1, 0);
+	}
+
+	let half_width = i32(parameters.half_width);
+	var sum = vec4<f32>(0.0);
+	for (var offset = -half_width; offset <= half_width; offset = offset + 1) {
+		let position = clamp(vec2<i32>(id.xy) + direction * offset, vec2<i32>(0), size - 1);
+		sum = sum + source[position.y * size.x + position.x] * weights[offset + half_width];
+	}
+	destination[id.y * parameters.width + id.x] = sum;
+}";
+
+/// WGSL implementation of [`CompositeNode`], which blends the foreground onto the backdrop where the mask is white.
+///
+/// The blend modes are numbered by [`blend_mode_index`].
+const COMPOSITE_SHADER: &str = "struct Parameters {
+	width: u32,
+	height: u32,
+	foreground_width: u32,
+	foreground_height: u32,
+	mask_width: u32,
+	mask_height: u32,
+	blend_mode: u32,
+	opacity: f32,
+}
+
+@group(0) @binding(0) var<storage, read_write> backdrop: array<vec4<f32>>;
+@group(0) @binding(1) var<storage, read> foreground: array<vec4<f32>>;
+@group(0) @binding(2) var<storage, read> mask: array<vec4<f32>>;
+@group(0) @binding(3) var<uniform> parameters: Parameters;
+
+fn luminosity(color: vec3<f32>) -> f32 {
+	return dot(color, vec3<f32>(0.3, 0.59, 0.11));
+}
+
+fn clip_color(color: vec3<f32>) -> vec3<f32> {
+	let color_luminosity = luminosity(color);
+	let low = min(min(color.r, color.g), color.b);
+	let high = max(max(color.r, color.g), color.b);
+	var clipped = color;
+	if (low < 0.0) {
+		clipped = color_luminosity + (clipped - color_luminosity) * color_luminosity / (color_luminosity - low);
+	}
+	if (high > 1.0) {
+		clipped = color_luminosity + (clipped - color_luminosity) * (1.0 - color_luminosity) / (high - color_luminosity);
+	}
+	return clipped;
+}
+
+fn set_luminosity(color: vec3<f32>, amount: f32) -> vec3<f32> {
+	return clip_color(color + (amount - luminosity(color)));
+}
+
+fn saturation(color: vec3<f32>) -> f32 {
+	return max(max(color.r, color.g), color.b) - min(min(color.r, color.g), color.b);
+}
+
+fn set_saturation(color: vec3<f32>, amount: f32) -> vec3<f32> {
+	let low = min(min(color.r, color.g), color.b);
+	let high = max(max(color.r, color.g), color.b);
+	if (high > low) {
+		return (color - low) * amount / (high - low);
+	}
+	return vec3<f32>(0.0);
+}
+
+fn hard_light(backdrop: f32, source: f32) -> f32 {
+	if (source <= 0.5) {
+		return backdrop * 2.0 * source;
+	}
+	let screen = 2.0 * source - 1.0;
+	return backdrop + screen - backdrop * screen;
+}
+
+fn separable(mode: u32, backdrop: f32, source: f32) -> f32 {
+	var blended = source;
+	switch (mode) {
+		case 1u: {
+			blended = backdrop * source;
+		}
+		case 2u: {
+			blended = min(backdrop, source);
+		}
+		case 3u: {
+			if (backdrop >= 1.0) {
+				blended = 1.0;
+			} else if (source <= 0.0) {
+				blended = 0.0;
+			} else {
+				blended = 1.0 - min((1.0 - backdrop) / source, 1.0);
+			}
+		}
+		case 4u: {
+			blended = backdrop + source - backdrop * source;
+		}
+		case 5u: {
+			blended = max(backdrop, source);
+		}
+		case 6u: {
+			if (backdrop <= 0.0) {
+				blended = 0.0;
+			} else if (source >= 1.0) {
+				blended = 1.0;
+			} else {
+				blended = min(backdrop / (1.0 - source), 1.0);
+			}
+		}
+		case 7u: {
+			blended = hard_light(source, backdrop);
+		}
+		case 8u: {
+			if (source <= 0.5) {
+				blended = backdrop - (1.0 - 2.0 * source) * backdrop * (1.0 - backdrop);
+			} else {
+				var d = sqrt(backdrop);
+				if (backdrop <= 0.25) {
+					d = ((16.0 * backdrop - 12.0) * backdrop + 4.0) * backdrop;
+				}
+				blended = backdrop + (2.0 * source - 1.0) * (d - backdrop);
+			}
+		}
+		case 9u: {
+			blended = hard_light(backdrop, source);
+		}
+		case 10u: {
+			blended = abs(backdrop - source);
+		}
+		case 11u: {
+			blended = backdrop + source - 2.0 * backdrop * source;
+		}
+		default: {}
+	}
+	return blended;
+}
+
+fn blend(mode: u32, backdrop: vec3<f32>, source: vec3<f32>) -> vec3<f32> {
+	var blended = vec3<f32>(separable(mode, backdrop.r, source.r), separable(mode, backdrop.g, source.g), separable(mode, backdrop.b, source.b));
+	switch (mode) {
+		case 12u: {
+			blended = set_luminosity(set_saturation(source, saturation(backdrop)), luminosity(backdrop));
+		}
+		case 13u: {
+			blended = set_luminosity(set_saturation(backdrop, saturation(source)), luminosity(backdrop));
+		}
+		case 14u: {
+			blended = set_luminosity(source, luminosity(backdrop));
+		}
+		case 15u: {
+			blended = set_luminosity(backdrop, luminosity(source));
+		}
+		default: {}
+	}
+	return blended;
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+	// Pixels outside of the foreground keep the backdrop
+	if (id.x >= parameters.width || id.y >= parameters.height || id.x >= parameters.foreground_width || id.y >= parameters.foreground_height) {
+		return;
+	}
+
+	let index = id.y * parameters.width + id.x;
+	let source = foreground[id.y * parameters.foreground_width + id.x];
+	var coverage = 0.0;
+	if (id.x < parameters.mask_width && id.y < parameters.mask_height) {
+		let mask_color = mask[id.y * parameters.mask_width + id.x];
+		coverage = luminosity(mask_color.rgb) * mask_color.a;
+	}
+	let source_alpha = source.a * coverage * parameters.opacity;
+
+	let backdrop_color = backdrop[index];
+	let backdrop_alpha = backdrop_color.a;
+	let alpha = source_alpha + backdrop_alpha * (1.0 - source_alpha);
+	if (alpha <= 0.0) {
+		backdrop[index] = vec4<f32>(0.0);
+		return;
+	}
+
+	// Where the backdrop is transparent the source color is used as is
+	let blended = blend(parameters.blend_mode, backdrop_color.rgb, source.rgb);
+	let color = (1.0 - backdrop_alpha) * source.rgb + backdrop_alpha * blended;
+	let premultiplied = source_alpha * color + backdrop_alpha * backdrop_color.rgb * (1.0 - source_alpha);
+	backdrop[index] = vec4<f32>(clamp(premultiplied / alpha, vec3<f32>(0.0), vec3<f32>(1.0)), alpha);
+}";
+
+/// The number of each blend mode in [`COMPOSITE_SHADER`].
+fn blend_mode_index(blend_mode: BlendMode) -> u32 {
+	match blend_mode {
+		BlendMode::Normal => 0,
+		BlendMode::Multiply => 1,
+		BlendMode::Darken => 2,
+		BlendMode::ColorBurn => 3,
+		BlendMode::Screen => 4,
+		BlendMode::Lighten => 5,
+		BlendMode::ColorDodge => 6,
+		BlendMode::Overlay => 7,
+		BlendMode::SoftLight => 8,
+		BlendMode::HardLight => 9,
+		BlendMode::Difference => 10,
+		BlendMode::Exclusion => 11,
+		BlendMode::Hue => 12,
+		BlendMode::Saturation => 13,
+		BlendMode::Color => 14,
+		BlendMode::Luminosity => 15,
+	}
+}
+
+/// Wraps a WGSL function with the signature `fn map(color: vec4<f32>) -> vec4<f32>` into a compute shader applying it to every pixel.
+fn compute_shader(map_function: &str) -> String {
+	format!(
+		"@group(0) @binding(0) var<storage, read_write> pixels: array<vec4<f32>>;
+
+{map_function}
+
+@compute @workgroup_size({WORKGROUP_SIZE})
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {{
+	if (id.x < arrayLength(&pixels)) {{
+		pixels[id.x] = map(pixels[id.x]);
+	}}
+}}"
+	)
+}
+
+/// An image whose pixels live in a storage buffer on the GPU, so consecutive GPU nodes don't need to download and upload it in between.
+pub struct GpuImage {
+	pub width: u32,
+	pub height: u32,
+	buffer: wgpu::Buffer,
+}
+
+/// The device used to run raster nodes as compute shaders.
+pub struct GpuContext {
+	device: wgpu::Device,
+	queue: wgpu::Queue,
+	/// Compiled shaders, keyed by their source or by the map function they apply
+	pipelines: Mutex<HashMap<&'static str, wgpu::ComputePipeline>>,
+	/// Buffers of images which are no longer used, kept around to be reused by images of the same size
+	buffer_pool: Mutex<Vec<wgpu::Buffer>>,
+}
+
+impl GpuContext {
+	/// Request a GPU device, returning `None` if no adapter is available so callers can fall back to the CPU.
+	pub fn new() -> Option<Self> {
+		let instance = wgpu::Instance::new(wgpu::Backends::all());
+		let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))?;
+		let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None)).ok()?;
+		Some(Self {
+			device,
+			queue,
+			pipelines: Mutex::new(HashMap::new()),
+			buffer_pool: Mutex::new(Vec::new()),
+		})
+	}
+
+	fn storage_buffer(&self, size: u64) -> wgpu::Buffer {
+		let mut pool = self.buffer_pool.lock().expect("buffer pool mutex poisoned");
+		match pool.iter().position(|buffer| buffer.size() == size) {
+			Some(index) => pool.swap_remove(index),
+			None => self.device.create_buffer(&wgpu::BufferDescriptor {
+				label: Some("Image"),
+				size,
+				usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+				mapped_at_creation: false,
+			}),
+		}
+	}
+
+	/// Return the buffer of an image which is no longer needed to the pool.
+	pub fn recycle(&self, image: GpuImage) {
+		self.buffer_pool.lock().expect("buffer pool mutex poisoned").push(image.buffer);
+	}
+
+	fn write_pixels(&self, buffer: &wgpu::Buffer, image: &Image) {
+		let bytes = image
+			.data
+			.iter()
+			.flat_map(|color| [color.r(), color.g(), color.b(), color.a()])
+			.flat_map(f32::to_le_bytes)
+			.collect::<Vec<_>>();
+		self.queue.write_buffer(buffer, 0, &bytes);
+	}
+
+	pub fn upload(&self, image: &Image) -> GpuImage {
+		let buffer = self.storage_buffer((image.data.len() as u64 * PIXEL_SIZE).max(PIXEL_SIZE));
+		self.write_pixels(&buffer, image);
+		GpuImage {
+			width: image.width,
+			height: image.height,
+			buffer,
+		}
+	}
+
+	/// Replace the pixels of a GPU image with those of an image of the same size.
+	pub fn overwrite(&self, target: &GpuImage, image: &Image) {
+		assert_eq!(
+			(target.width, target.height),
+			(image.width, image.height),
+			"GPU images can only be overwritten by images of the same size"
+		);
+		self.write_pixels(&target.buffer, image);
+	}
+
+	/// Whether shaders which read other pixels than their own, and so bind the whole image at once, can process an image of this size
+	/// within the device's limits on the size of a storage buffer binding and on the number of workgroups per dimension.
+	pub fn supports_whole_image(&self, image: &GpuImage) -> bool {
+		let limits = self.device.limits();
+		let size = image.width as u64 * image.height as u64 * PIXEL_SIZE;
+		let workgroups = image.width.max(image.height).div_ceil(TILE_SIZE);
+		size <= limits.max_storage_buffer_binding_size as u64 && workgroups <= limits.max_compute_workgroups_per_dimension
+	}
+
+	pub fn download(&self, image: &GpuImage) -> Image {
+		let size = image.width as u64 * image.height as u64 * PIXEL_SIZE;
+		if size == 0 {
+			return Image::new(image.width, image.height, Vec::new());
+		}
+
+		let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some("Image download"),
+			size,
+			usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		});
+		let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+		encoder.copy_buffer_to_buffer(&image.buffer, 0, &staging, 0, size);
+		self.queue.submit(Some(encoder.finish()));
+
+		let slice = staging.slice(..);
+		slice.map_async(wgpu::MapMode::Read, |_| {});
+		self.device.poll(wgpu::Maintain::Wait);
+		let data = slice
+			.get_mapped_range()
+			.chunks_exact(PIXEL_SIZE as usize)
+			.map(|pixel| {
+				let channel = |index: usize| f32::from_le_bytes(pixel[index * 4..index * 4 + 4].try_into().unwrap());
+				Color::from_rgbaf32_unchecked(channel(0), channel(1), channel(2), channel(3))
+			})
+			.collect();
+		staging.unmap();
+
+		Image::new(image.width, image.height, data)
+	}
+
+	/// Apply the WGSL `map` function to every pixel of the image in place.
+	///
+	/// Large images are processed in chunks, each bound separately, so no dispatch exceeds the device's limits on
+	/// the number of workgroups per dimension or on the size of a storage buffer binding.
+	pub fn map_image(&self, map_function: &'static str, image: &GpuImage) {
+		let pixels = image.width as u64 * image.height as u64;
+		if pixels == 0 {
+			return;
+		}
+
+		self.with_pipeline(map_function, || compute_shader(map_function), |pipeline| self.map_chunks(pipeline, image, pixels));
+	}
+
+	/// Run a closure with the compute pipeline of a shader, which is compiled the first time it is used.
+	fn with_pipeline<T>(&self, key: &'static str, source: impl FnOnce() -> String, f: impl FnOnce(&wgpu::ComputePipeline) -> T) -> T {
+		let mut pipelines = self.pipelines.lock().expect("pipeline cache mutex poisoned");
+		let pipeline = pipelines.entry(key).or_insert_with(|| {
+			let module = self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+				label: None,
+				source: wgpu::ShaderSource::Wgsl(source().into()),
+			});
+			self.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+				label: None,
+				layout: None,
+				module: &module,
+				entry_point: "main",
+			})
+		});
+		f(pipeline)
+	}
+
+	fn map_chunks(&self, pipeline: &wgpu::ComputePipeline, image: &GpuImage, pixels: u64) {
+		// Chunks are made of whole workgroups and start at offsets which satisfy the binding alignment
+		let limits = self.device.limits();
+		let granularity = WORKGROUP_SIZE as u64 * (limits.min_storage_buffer_offset_alignment as u64 / PIXEL_SIZE).max(1);
+		let max_chunk = (limits.max_storage_buffer_binding_size as u64 / PIXEL_SIZE).min(limits.max_compute_workgroups_per_dimension as u64 * WORKGROUP_SIZE as u64);
+		let chunk_pixels = (max_chunk / granularity * granularity).max(granularity);
+
+		let layout = pipeline.get_bind_group_layout(0);
+		let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+		let mut offset = 0;
+		while offset < pixels {
+			let size = chunk_pixels.min(pixels - offset);
+			let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+				label: None,
+				layout: &layout,
+				entries: &[wgpu::BindGroupEntry {
+					binding: 0,
+					resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+						buffer: &image.buffer,
+						offset: offset * PIXEL_SIZE,
+						size: wgpu::BufferSize::new(size * PIXEL_SIZE),
+					}),
+				}],
+			});
+			let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
+			pass.set_pipeline(pipeline);
+			pass.set_bind_group(0, &bind_group, &[]);
+			// The chunk size keeps the count within the dispatch limit
+			pass.dispatch_workgroups(size.div_ceil(WORKGROUP_SIZE as u64) as u32, 1, 1);
+			offset += size;
+		}
+		self.queue.submit(Some(encoder.finish()));
+	}
+
+	/// Run a shader with a workgroup size of `TILE_SIZE` by `TILE_SIZE` once for every pixel of an image of the given size.
+	///
+	/// The buffers are bound in order, followed by the parameters as a uniform buffer.
+	fn dispatch_whole_image(&self, shader: &'static str, buffers: &[&wgpu::Buffer], parameters: &[u8], width: u32, height: u32) {
+		if width == 0 || height == 0 {
+			return;
+		}
+
+		let uniform = self.device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some("Parameters"),
+			size: parameters.len() as u64,
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		});
+		self.queue.write_buffer(&uniform, 0, parameters);
+
+		self.with_pipeline(
+			shader,
+			|| shader.to_string(),
+			|pipeline| {
+				let entries = buffers
+					.iter()
+					.copied()
+					.chain(std::iter::once(&uniform))
+					.enumerate()
+					.map(|(binding, buffer)| wgpu::BindGroupEntry {
+						binding: binding as u32,
+						resource: buffer.as_entire_binding(),
+					})
+					.collect::<Vec<_>>();
+				let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+					label: None,
+					layout: &pipeline.get_bind_group_layout(0),
+					entries: &entries,
+				});
+
+				let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+				{
+					let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
+					pass.set_pipeline(pipeline);
+					pass.set_bind_group(0, &bind_group, &[]);
+					pass.dispatch_workgroups(width.div_ceil(TILE_SIZE), height.div_ceil(TILE_SIZE), 1);
+				}
+				self.queue.submit(Some(encoder.finish()));
+			},
+		);
+	}
+
+	/// Blur the image in place with the weights of a [`BlurNode`] kernel, as a horizontal and a vertical pass on premultiplied colors.
+	///
+	/// The image must be supported by [`GpuContext::supports_whole_image`].
+	pub fn blur(&self, image: &GpuImage, kernel: &[f32]) {
+		let size = (image.width as u64 * image.height as u64 * PIXEL_SIZE).max(PIXEL_SIZE);
+		let scratch = GpuImage {
+			width: image.width,
+			height: image.height,
+			buffer: self.storage_buffer(size),
+		};
+		let weights = self.device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some("Blur kernel"),
+			size: (kernel.len() * std::mem::size_of::<f32>()) as u64,
+			usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		});
+		self.queue.write_buffer(&weights, 0, &kernel.iter().copied().flat_map(f32::to_le_bytes).collect::<Vec<_>>());
+
+		self.map_image(PREMULTIPLY_SHADER, image);
+		for (horizontal, source, destination) in [(true, image, &scratch), (false, &scratch, image)] {
+			let parameters = [image.width, image.height, (kernel.len() / 2) as u32, horizontal as u32]
+				.into_iter()
+				.flat_map(u32::to_le_bytes)
+				.collect::<Vec<_>>();
+			let buffers = [&source.buffer, &destination.buffer, &weights];
+			self.dispatch_whole_image(BLUR_SHADER, &buffers, &parameters, image.width, image.height);
+		}
+		self.map_image(UNPREMULTIPLY_SHADER, image);
+
+		self.recycle(scratch);
+	}
+
+	/// Composite the foreground onto the backdrop in place where the mask is white, like [`CompositeNode`] does on the CPU.
+	///
+	/// All three images must be supported by [`GpuContext::supports_whole_image`].
+	pub fn composite(&self, backdrop: &GpuImage, foreground: &GpuImage, mask: &GpuImage, blend_mode: BlendMode, opacity: f32) {
+		let parameters = [
+			backdrop.width,
+			backdrop.height,
+			foreground.width,
+			foreground.height,
+			mask.width,
+			mask.height,
+			blend_mode_index(blend_mode),
+		]
+		.into_iter()
+		.flat_map(u32::to_le_bytes)
+		.chain(opacity.to_le_bytes())
+		.collect::<Vec<_>>();
+		let buffers = [&backdrop.buffer, &foreground.buffer, &mask.buffer];
+		self.dispatch_whole_image(COMPOSITE_SHADER, &buffers, &parameters, backdrop.width, backdrop.height);
+	}
+}
+
+/// Maps every pixel of an image with a compute shader, or with the equivalent CPU node when no GPU is available.
+pub struct GpuMapImageNode<'a, MN: Node<Color, Output = Color> + Copy> {
+	pub context: Option<&'a GpuContext>,
+	/// WGSL function with the signature `fn map(color: vec4<f32>) -> vec4<f32>`
+	pub shader: &'static str,
+	pub fallback: MapImageNode<MN>,
+}
+
+impl<'a, MN: Node<Color, Output = Color> + Copy> GpuMapImageNode<'a, MN> {
+	pub const fn new(context: Option<&'a GpuContext>, shader: &'static str, fallback: MN) -> Self {
+		Self {
+			context,
+			shader,
+			fallback: MapImageNode::new(fallback),
+		}
+	}
+}
+
+impl<'n, 'a, MN: Node<Color, Output = Color> + Copy> Node<Image> for &'n GpuMapImageNode<'a, MN> {
+	type Output = Image;
+	fn eval(self, input: Image) -> Self::Output {
+		match self.context {
+			Some(context) => {
+				let image = context.upload(&input);
+				context.map_image(self.shader, &image);
+				let output = context.download(&image);
+				context.recycle(image);
+				output
+			}
+			None => (&self.fallback).eval(input),
+		}
+	}
+}
+
+impl<'n, 'a, MN: Node<Color, Output = Color> + Copy> Node<GpuImage> for &'n GpuMapImageNode<'a, MN> {
+	type Output = GpuImage;
+	fn eval(self, input: GpuImage) -> Self::Output {
+		let context = self.context.expect("GPU images can only be processed when a GPU context is available");
+		context.map_image(self.shader, &input);
+		input
+	}
+}
+
+/// Blurs an image with compute shaders, or with [`BlurNode`] on the CPU when no GPU is available or the image is too large for it.
+pub struct GpuBlurNode<'a> {
+	pub context: Option<&'a GpuContext>,
+	pub fallback: BlurNode,
+}
+
+impl<'a> GpuBlurNode<'a> {
+	pub const fn new(context: Option<&'a GpuContext>, radius: f32, quality: BlurQuality) -> Self {
+		Self {
+			context,
+			fallback: BlurNode::new(radius, quality),
+		}
+	}
+
+	fn blur(&self, context: &GpuContext, image: GpuImage) -> GpuImage {
+		if self.fallback.radius <= 0. {
+			return image;
+		}
+
+		if context.supports_whole_image(&image) {
+			context.blur(&image, &self.fallback.kernel());
+		} else {
+			let blurred = (&self.fallback).eval(context.download(&image));
+			context.overwrite(&image, &blurred);
+		}
+		image
+	}
+}
+
+impl<'n, 'a> Node<Image> for &'n GpuBlurNode<'a> {
+	type Output = Image;
+	fn eval(self, input: Image) -> Self::Output {
+		match self.context {
+			Some(context) => {
+				let blurred = self.blur(context, context.upload(&input));
+				let output = context.download(&blurred);
+				context.recycle(blurred);
+				output
+			}
+			None => (&self.fallback).eval(input),
+		}
+	}
+}
+
+impl<'n, 'a> Node<GpuImage> for &'n GpuBlurNode<'a> {
+	type Output = GpuImage;
+	fn eval(self, input: GpuImage) -> Self::Output {
+		let context = self.context.expect("GPU images can only be processed when a GPU context is available");
+		self.blur(context, input)
+	}
+}
+
+/// Composites images with a compute shader, or with [`CompositeNode`] on the CPU when no GPU is available or an image is too large for it.
+pub struct GpuCompositeNode<'a> {
+	pub context: Option<&'a GpuContext>,
+	pub fallback: CompositeNode,
+}
+
+impl<'a> GpuCompositeNode<'a> {
+	pub const fn new(context: Option<&'a GpuContext>, blend_mode: BlendMode, opacity: f32) -> Self {
+		Self {
+			context,
+			fallback: CompositeNode::new(blend_mode, opacity),
+		}
+	}
+
+	fn composite(&self, context: &GpuContext, (foreground, background, mask): (GpuImage, GpuImage, GpuImage)) -> GpuImage {
+		if [&foreground, &background, &mask].into_iter().all(|image| context.supports_whole_image(image)) {
+			context.composite(&background, &foreground, &mask, self.fallback.blend_mode, self.fallback.opacity);
+		} else {
+			let composited = (&self.fallback).eval((context.download(&foreground), context.download(&background), context.download(&mask)));
+			context.overwrite(&background, &composited);
+		}
+		context.recycle(foreground);
+		context.recycle(mask);
+		background
+	}
+}
+
+impl<'n, 'a> Node<(Image, Image, Image)> for &'n GpuCompositeNode<'a> {
+	type Output = Image;
+	fn eval(self, (foreground, background, mask): (Image, Image, Image)) -> Self::Output {
+		match self.context {
+			Some(context) => {
+				let composited = self.composite(context, (context.upload(&foreground), context.upload(&background), context.upload(&mask)));
+				let output = context.download(&composited);
+				context.recycle(composited);
+				output
+			}
+			None => (&self.fallback).eval((foreground, background, mask)),
+		}
+	}
+}
+
+impl<'n, 'a> Node<(GpuImage, GpuImage, GpuImage)> for &'n GpuCompositeNode<'a> {
+	type Output = GpuImage;
+	fn eval(self, input: (GpuImage, GpuImage, GpuImage)) -> Self::Output {
+		let context = self.context.expect("GPU images can only be processed when a GPU context is available");
+		self.composite(context, input)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// Images with a different color in every pixel, and partial transparency.
+	fn gradient(width: u32, height: u32, seed: f32) -> Image {
+		let data = (0..width * height)
+			.map(|index| {
+				let value = |offset: f32| ((index as f32 + seed) * offset).sin().abs();
+				Color::from_rgbaf32_unchecked(value(0.37), value(0.61), value(0.83), value(0.29))
+			})
+			.collect();
+		Image::new(width, height, data)
+	}
+
+	fn assert_close(gpu: &Image, cpu: &Image) {
+		assert_eq!((gpu.width, gpu.height), (cpu.width, cpu.height));
+		for (gpu, cpu) in gpu.data.iter().zip(cpu.data.iter()) {
+			let (gpu, cpu) = (gpu.components(), cpu.components());
+			let difference = [gpu.0 - cpu.0, gpu.1 - cpu.1, gpu.2 - cpu.2, gpu.3 - cpu.3];
+			assert!(difference.iter().all(|difference| difference.abs() < 1e-4), "{gpu:?} != {cpu:?}");
+		}
+	}
+
+	#[test]
+	fn gpu_blur_matches_cpu() {
+		// Machines without a GPU only run the CPU fallback, which has its own tests
+		let Some(context) = GpuContext::new() else { return };
+
+		let image = gradient(13, 8, 0.);
+		for quality in [BlurQuality::Low, BlurQuality::High] {
+			let gpu = (&GpuBlurNode::new(Some(&context), 4., quality)).eval(image.clone());
+			let cpu = (&BlurNode::new(4., quality)).eval(image.clone());
+			assert_close(&gpu, &cpu);
+		}
+	}
+
+	#[test]
+	fn gpu_composite_matches_cpu() {
+		let Some(context) = GpuContext::new() else { return };
+
+		let (foreground, background, mask) = (gradient(9, 5, 0.), gradient(11, 7, 3.), gradient(6, 6, 7.));
+		for blend_mode in BLEND_MODES {
+			let gpu = (&GpuCompositeNode::new(Some(&context), blend_mode, 0.8)).eval((foreground.clone(), background.clone(), mask.clone()));
+			let cpu = (&CompositeNode::new(blend_mode, 0.8)).eval((foreground.clone(), background.clone(), mask.clone()));
+			assert_close(&gpu, &cpu);
+		}
+	}
+
+	const BLEND_MODES: [BlendMode; 16] = [
+		BlendMode::Normal,
+		BlendMode::Multiply,
+		BlendMode::Darken,
+		BlendMode::ColorBurn,
+		BlendMode::Screen,
+		BlendMode::Lighten,
+		BlendMode::ColorDodge,
+		BlendMode::Overlay,
+		BlendMode::SoftLight,
+		BlendMode::HardLight,
+		BlendMode::Difference,
+		BlendMode::Exclusion,
+		BlendMode::Hue,
+		BlendMode::Saturation,
+		BlendMode::Color,
+		BlendMode::Luminosity,
+	];
+}
@@ -0,0 +1,174 @@
+use crate::document::{NodeId, NodeNetwork};
+use crate::error::{NodeError, NodeResult};
+use std::collections::{HashMap, HashSet};
+
+/// The changes made to the compiled network by [`IncrementalCompiler::update`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompilationUpdate {
+	/// Top level nodes which were added or changed, and had to be compiled again
+	pub recompiled: Vec<NodeId>,
+	pub removed: Vec<NodeId>,
+	/// Compiled nodes whose cached outputs are outdated, because they or one of the nodes they depend on changed
+	pub invalidated: HashSet<NodeId>,
+	/// Nodes which could not be compiled and are missing from the compiled network until they are fixed
+	pub errors: Vec<NodeError>,
+}
+
+/// Keeps the flattened proto network of a document network up to date, compiling only the nodes which changed.
+///
+/// Every top level node is flattened on its own, which produces the same nodes as flattening the whole network
+/// because nested networks only reference the top level nodes connected to their inputs.
+/// Changing a single parameter therefore only recompiles the node owning it, and invalidates the nodes downstream of it.
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalCompiler {
+	/// The document network the compiled network was last built from
+	source: NodeNetwork,
+	compiled: NodeNetwork,
+	/// The compiled nodes produced by each top level node
+	origins: HashMap<NodeId, Vec<NodeId>>,
+}
+
+impl IncrementalCompiler {
+	pub fn new(network: NodeNetwork) -> Self {
+		let mut compiler = Self::default();
+		compiler.update(network);
+		compiler
+	}
+
+	/// The flattened network with resolved proto nodes.
+	pub fn compiled(&self) -> &NodeNetwork {
+		&self.compiled
+	}
+
+	/// Patch the compiled network to match the new document network.
+	///
+	/// Changes to the definitions or the inputs of the network affect every node using them, so they cause the whole network to be compiled again.
+	pub fn update(&mut self, network: NodeNetwork) -> CompilationUpdate {
+		let full = network.definitions != self.source.definitions || network.inputs != self.source.inputs;
+
+		let mut removed = self.source.nodes.keys().filter(|id| !network.nodes.contains_key(id)).copied().collect::<Vec<_>>();
+		removed.sort_unstable();
+		let mut recompiled = network
+			.nodes
+			.iter()
+			.filter(|(id, node)| full || self.source.nodes.get(id) != Some(node))
+			.map(|(&id, _)| id)
+			.collect::<Vec<_>>();
+		recompiled.sort_unstable();
+
+		// Outputs of nodes which are not recompiled themselves but depend on a change have to be invalidated as well
+		let mut invalidated = HashSet::new();
+		for &id in removed.iter().chain(&recompiled) {
+			for downstream in self.source.downstream_nodes(id).into_iter().chain(network.downstream_nodes(id)) {
+				invalidated.extend(self.origins.get(&downstream).into_iter().flatten().copied());
+			}
+		}
+
+		for id in removed.iter().chain(&recompiled) {
+			for compiled in self.origins.remove(id).unwrap_or_default() {
+				self.compiled.nodes.remove(&compiled);
+			}
+		}
+		if full {
+			self.compiled.inputs = network.inputs.clone();
+		}
+		self.compiled.output = network.output;
+		let mut errors = Vec::new();
+		for &id in &recompiled {
+			match self.compile_node(&network, id) {
+				Ok(compiled) => {
+					invalidated.extend(compiled.iter().copied());
+					self.origins.insert(id, compiled);
+				}
+				Err(error) => errors.push(error),
+			}
+		}
+		invalidated.retain(|id| self.compiled.nodes.contains_key(id));
+
+		self.source = network;
+		CompilationUpdate {
+			recompiled,
+			removed,
+			invalidated,
+			errors,
+		}
+	}
+
+	/// Flatten a single top level node and add the resulting proto nodes to the compiled network, returning their ids.
+	///
+	/// Errors are attributed to the top level node, with the path leading to the failing node nested within it.
+	fn compile_node(&mut self, network: &NodeNetwork, id: NodeId) -> NodeResult<Vec<NodeId>> {
+		let mut single = NodeNetwork {
+			inputs: network.inputs.clone(),
+			output: id,
+			nodes: HashMap::from([(id, network.nodes[&id].clone())]),
+			definitions: network.definitions.clone(),
+		};
+		single.flatten(id);
+		// Flattening reconnects network inputs which were passed through this node to the nested node using them
+		for (index, input) in network.inputs.iter().enumerate() {
+			if *input == id {
+				self.compiled.inputs[index] = single.inputs[index];
+			}
+		}
+		single.resolve_proto_nodes().map_err(|error| if error.node() == Some(id) { error } else { error.within(id) })?;
+
+		let ids = single.nodes.keys().copied().collect();
+		self.compiled.nodes.extend(single.nodes);
+		Ok(ids)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::document::{ConstructionArgs, DocumentNode, DocumentNodeImplementation, IntoValue, NodeInput, ProtoNode};
+
+	fn network(value: u32) -> NodeNetwork {
+		let value_node = |value: u32| DocumentNode {
+			name: "Value".into(),
+			inputs: vec![NodeInput::Value(value.into_any())],
+			implementation: DocumentNodeImplementation::ProtoNode(ProtoNode::unresolved("value".into())),
+		};
+		let identity = DocumentNode {
+			name: "Identity".into(),
+			inputs: vec![NodeInput::Node(0)],
+			implementation: DocumentNodeImplementation::ProtoNode(ProtoNode::id()),
+		};
+		NodeNetwork {
+			output: 1,
+			nodes: [(0, value_node(value)), (1, identity), (2, value_node(5))].into_iter().collect(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn recompile_changed_nodes() {
+		let mut compiler = IncrementalCompiler::new(network(1));
+		assert_eq!(compiler.compiled().nodes.len(), 3);
+
+		let update = compiler.update(network(2));
+		assert_eq!(update.recompiled, [0]);
+		assert_eq!(update.invalidated, HashSet::from([0, 1]));
+
+		let mut full = network(2);
+		full.resolve_proto_nodes().unwrap();
+		assert_eq!(compiler.compiled().nodes, full.nodes);
+		match &compiler.compiled().nodes[&0].implementation {
+			DocumentNodeImplementation::ProtoNode(proto_node) => assert_eq!(proto_node.construction_args, ConstructionArgs::Value(2_u32.into_any())),
+			_ => panic!("The value node should be resolved"),
+		}
+
+		let mut without_value = network(2);
+		without_value.nodes.remove(&2);
+		let update = compiler.update(without_value);
+		assert_eq!(update.removed, [2]);
+		assert!(update.recompiled.is_empty());
+
+		let mut broken = network(2);
+		broken.nodes.get_mut(&1).unwrap().inputs.clear();
+		let update = compiler.update(broken);
+		assert_eq!(update.errors, [NodeError::new(1, "The node has no primary input")]);
+		assert!(!compiler.compiled().nodes.contains_key(&1));
+	}
+}
@@ -0,0 +1,38 @@
+//! Nodes which receive another node as a value through [`crate::document::NodeInput::Lambda`] and evaluate it repeatedly,
+//! so per element processing can be defined by a user built sub-network.
+
+use graphene_core::Node;
+
+/// Evaluates the node once for every element of the input, giving it the element along with its index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapIndexedNode<F> {
+	pub node: F,
+}
+
+impl<F> MapIndexedNode<F> {
+	pub const fn new(node: F) -> Self {
+		Self { node }
+	}
+}
+
+impl<'n, F, T> Node<Vec<T>> for &'n MapIndexedNode<F>
+where
+	&'n F: Node<(T, usize)>,
+{
+	type Output = Vec<<&'n F as Node<(T, usize)>>::Output>;
+	fn eval(self, input: Vec<T>) -> Self::Output {
+		input.into_iter().enumerate().map(|(index, element)| (&self.node).eval((element, index))).collect()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene_core::generic::FnNode;
+
+	#[test]
+	fn evaluate_lambda_per_element() {
+		let rotate_by_index = FnNode::new(|(angle, index): (f64, usize)| angle + index as f64 * 10.);
+		assert_eq!((&MapIndexedNode::new(rotate_by_index)).eval(vec![1., 1., 5.]), [1., 11., 25.]);
+	}
+}
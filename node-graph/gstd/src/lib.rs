@@ -6,10 +6,54 @@ pub mod memo;
 
 pub mod raster;
 
+pub mod adjustments;
+
+#[cfg(feature = "gpu")]
+pub mod gpu;
+
 pub mod any;
 
 pub mod document;
 
+pub mod error;
+
+pub mod asset;
+
+pub mod types;
+
+pub mod incremental;
+
+#[cfg(feature = "memoization")]
+pub mod executor;
+
+pub mod diff;
+
+pub mod lambda;
+
+pub mod expression;
+
+pub mod constraints;
+
+pub mod noise;
+
+pub mod footprint;
+
+pub mod random;
+
+pub mod animation;
+
+pub mod vector;
+
+pub mod composite;
+
+pub mod clock;
+
+pub mod profile;
+
+pub mod progressive;
+
+pub mod script;
+
 pub use graphene_core::*;
 
 use quote::quote;
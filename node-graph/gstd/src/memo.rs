@@ -1,5 +1,7 @@
 use graphene_core::{Cache, Node};
 use once_cell::sync::OnceCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 
 /// Caches the output of a given Node and acts as a proxy
 pub struct CacheNode<CachedNode: Node<I>, I> {
@@ -27,6 +29,111 @@ impl<CachedNode: Node<I>, I> Cache for CacheNode<CachedNode, I> {
 	}
 }
 
+/// Caches the output of a given Node keyed by its input and by the parameters the inner node was constructed with
+/// The inner node is only re-evaluated when it receives a different input or its parameters change, which is checked by comparing them for equality
+pub struct MemoNode<CachedNode: Node<I>, I, P = ()> {
+	node: CachedNode,
+	parameters: P,
+	cache: Mutex<Option<(I, P, CachedNode::Output)>>,
+	hits: AtomicU64,
+	misses: AtomicU64,
+}
+impl<'n, CachedNode: Node<I> + Clone, I: PartialEq + Clone, P: PartialEq + Clone> Node<I> for &'n MemoNode<CachedNode, I, P>
+where
+	CachedNode::Output: Clone,
+{
+	type Output = CachedNode::Output;
+	fn eval(self, input: I) -> Self::Output {
+		let mut cache = self.cache.lock().expect("memo cache mutex poisoned");
+		match cache.as_ref() {
+			Some((cached_input, cached_parameters, output)) if *cached_input == input && *cached_parameters == self.parameters => {
+				self.hits.fetch_add(1, Ordering::Relaxed);
+				output.clone()
+			}
+			_ => {
+				self.misses.fetch_add(1, Ordering::Relaxed);
+				let output = self.node.clone().eval(input.clone());
+				*cache = Some((input, self.parameters.clone(), output.clone()));
+				output
+			}
+		}
+	}
+}
+
+impl<CachedNode: Node<I>, I> MemoNode<CachedNode, I> {
+	pub fn new(node: CachedNode) -> MemoNode<CachedNode, I> {
+		MemoNode::with_parameters(node, ())
+	}
+}
+
+impl<CachedNode: Node<I>, I, P> MemoNode<CachedNode, I, P> {
+	pub fn with_parameters(node: CachedNode, parameters: P) -> MemoNode<CachedNode, I, P> {
+		MemoNode {
+			node,
+			parameters,
+			cache: Mutex::new(None),
+			hits: AtomicU64::new(0),
+			misses: AtomicU64::new(0),
+		}
+	}
+
+	/// Replace the parameters the inner node was constructed with, so the cached output is only used again for the same parameters.
+	pub fn set_parameters(&mut self, parameters: P) {
+		self.parameters = parameters;
+	}
+
+	/// The number of evaluations which were answered from the cache, and the number which had to evaluate the inner node.
+	pub fn cache_statistics(&self) -> (u64, u64) {
+		(self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+	}
+}
+impl<CachedNode: Node<I>, I, P> Cache for MemoNode<CachedNode, I, P> {
+	fn clear(&mut self) {
+		self.cache = Mutex::new(None);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene_core::generic::FnNode;
+	use std::cell::Cell;
+
+	#[test]
+	fn memo_node_reevaluates_on_change() {
+		let evaluations = Cell::new(0);
+		let double = FnNode::new(|x: u32| {
+			evaluations.set(evaluations.get() + 1);
+			x * 2
+		});
+		let memo = MemoNode::new(&double);
+		assert_eq!((&memo).eval(2), 4);
+		assert_eq!((&memo).eval(2), 4);
+		assert_eq!(evaluations.get(), 1);
+		assert_eq!((&memo).eval(3), 6);
+		assert_eq!(evaluations.get(), 2);
+		assert_eq!(memo.cache_statistics(), (1, 2));
+	}
+
+	#[test]
+	fn memo_node_reevaluates_on_parameter_change() {
+		let evaluations = Cell::new(0);
+		let count = FnNode::new(|x: f64| {
+			evaluations.set(evaluations.get() + 1);
+			x
+		});
+		let mut memo = MemoNode::with_parameters(&count, 1.5);
+		assert_eq!((&memo).eval(2.), 2.);
+		assert_eq!((&memo).eval(2.), 2.);
+		assert_eq!(evaluations.get(), 1);
+
+		memo.set_parameters(2.5);
+		assert_eq!((&memo).eval(2.), 2.);
+		assert_eq!(evaluations.get(), 2);
+		assert_eq!(memo.cache_statistics(), (1, 2));
+	}
+}
+
 /*use dyn_any::{DynAny, StaticType};
 #[derive(DynAny)]
 struct Boo<'a>(&'a u8);
@@ -0,0 +1,267 @@
+use crate::footprint::Footprint;
+use crate::raster::Image;
+use graphene_core::raster::color::Color;
+use graphene_core::Node;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseType {
+	Perlin,
+	Simplex,
+	/// Cellular noise based on the distance to the closest of a set of randomly distributed feature points
+	Worley,
+}
+
+/// The area of the document which should be covered by the generated image, and the resolution it is rendered at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseRegion {
+	/// Top left corner of the region in document space
+	pub origin: (f64, f64),
+	/// Size of the region in document space
+	pub size: (f64, f64),
+	pub width: u32,
+	pub height: u32,
+}
+
+impl NoiseRegion {
+	/// The size of a single pixel in document space, used to avoid generating detail which would not be visible.
+	pub fn pixel_size(&self) -> f64 {
+		(self.size.0 / self.width.max(1) as f64).max(self.size.1 / self.height.max(1) as f64)
+	}
+}
+
+fn hash(x: i64, y: i64, seed: u32) -> u32 {
+	let mut h = (x as u32).wrapping_mul(0x27d4_eb2d) ^ (y as u32).wrapping_mul(0x1656_67b1) ^ seed.wrapping_mul(0x9e37_79b9);
+	h ^= h >> 15;
+	h = h.wrapping_mul(0x85eb_ca6b);
+	h ^= h >> 13;
+	h = h.wrapping_mul(0xc2b2_ae35);
+	h ^ (h >> 16)
+}
+
+fn gradient(x: i64, y: i64, seed: u32, dx: f64, dy: f64) -> f64 {
+	let angle = hash(x, y, seed) as f64 / u32::MAX as f64 * std::f64::consts::TAU;
+	angle.cos() * dx + angle.sin() * dy
+}
+
+fn fade(t: f64) -> f64 {
+	t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+	a + (b - a) * t
+}
+
+/// Classic gradient noise in the range -1 to 1.
+fn perlin(x: f64, y: f64, seed: u32) -> f64 {
+	let (x0, y0) = (x.floor(), y.floor());
+	let (dx, dy) = (x - x0, y - y0);
+	let (ix, iy) = (x0 as i64, y0 as i64);
+
+	let top = lerp(gradient(ix, iy, seed, dx, dy), gradient(ix + 1, iy, seed, dx - 1., dy), fade(dx));
+	let bottom = lerp(gradient(ix, iy + 1, seed, dx, dy - 1.), gradient(ix + 1, iy + 1, seed, dx - 1., dy - 1.), fade(dx));
+	lerp(top, bottom, fade(dy)) * std::f64::consts::SQRT_2
+}
+
+/// Gradient noise on a triangular grid in the range -1 to 1, which has fewer directional artifacts than Perlin noise.
+fn simplex(x: f64, y: f64, seed: u32) -> f64 {
+	const SKEW: f64 = 0.366_025_403_784_438_6; // (sqrt(3) - 1) / 2
+	const UNSKEW: f64 = 0.211_324_865_405_187_1; // (3 - sqrt(3)) / 6
+
+	let skew = (x + y) * SKEW;
+	let (i, j) = ((x + skew).floor(), (y + skew).floor());
+	let unskew = (i + j) * UNSKEW;
+	let (x0, y0) = (x - (i - unskew), y - (j - unskew));
+	let (i1, j1) = if x0 > y0 { (1., 0.) } else { (0., 1.) };
+
+	let corners = [(0., 0., x0, y0), (i1, j1, x0 - i1 + UNSKEW, y0 - j1 + UNSKEW), (1., 1., x0 - 1. + 2. * UNSKEW, y0 - 1. + 2. * UNSKEW)];
+	let sum: f64 = corners
+		.iter()
+		.map(|&(ci, cj, dx, dy)| {
+			let t = 0.5 - dx * dx - dy * dy;
+			if t < 0. {
+				0.
+			} else {
+				t.powi(4) * gradient((i + ci) as i64, (j + cj) as i64, seed, dx, dy)
+			}
+		})
+		.sum();
+	(sum * 70.).clamp(-1., 1.)
+}
+
+/// Distance to the closest feature point, with one point randomly placed in every grid cell, remapped to the range -1 to 1.
+fn worley(x: f64, y: f64, seed: u32) -> f64 {
+	let (cell_x, cell_y) = (x.floor() as i64, y.floor() as i64);
+	let mut closest = f64::MAX;
+	for offset_y in -1..=1 {
+		for offset_x in -1..=1 {
+			let (cx, cy) = (cell_x + offset_x, cell_y + offset_y);
+			let point_x = cx as f64 + hash(cx, cy, seed) as f64 / u32::MAX as f64;
+			let point_y = cy as f64 + hash(cx, cy, seed.wrapping_add(1)) as f64 / u32::MAX as f64;
+			closest = closest.min((point_x - x).hypot(point_y - y));
+		}
+	}
+	closest.min(1.) * 2. - 1.
+}
+
+/// Generates a grayscale image of procedural noise covering the region given as input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseNode {
+	pub noise_type: NoiseType,
+	pub seed: u32,
+	/// Size of the noise features in document space
+	pub scale: f64,
+	pub offset: (f64, f64),
+}
+
+impl NoiseNode {
+	pub fn new(noise_type: NoiseType, seed: u32, scale: f64) -> Self {
+		Self {
+			noise_type,
+			seed,
+			scale,
+			offset: (0., 0.),
+		}
+	}
+
+	/// Sample the noise at a point in document space, returning a value in the range -1 to 1.
+	pub fn sample(&self, x: f64, y: f64) -> f64 {
+		let scale = self.scale.max(f64::EPSILON);
+		let (x, y) = ((x + self.offset.0) / scale, (y + self.offset.1) / scale);
+		match self.noise_type {
+			NoiseType::Perlin => perlin(x, y, self.seed),
+			NoiseType::Simplex => simplex(x, y, self.seed),
+			NoiseType::Worley => worley(x, y, self.seed),
+		}
+	}
+}
+
+impl<'n> Node<NoiseRegion> for &'n NoiseNode {
+	type Output = Image;
+	fn eval(self, region: NoiseRegion) -> Self::Output {
+		render_region(region, |x, y| self.sample(x, y))
+	}
+}
+
+impl<'n> Node<Footprint> for &'n NoiseNode {
+	type Output = Image;
+	fn eval(self, footprint: Footprint) -> Self::Output {
+		render_footprint(footprint, |x, y| self.sample(x, y))
+	}
+}
+
+/// Fractal Brownian motion: sums octaves of the base noise with increasing frequency and decreasing amplitude.
+///
+/// Octaves with features smaller than a pixel of the requested region are skipped,
+/// so zooming in regenerates the noise with more detail up to the configured number of octaves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FractalNoiseNode {
+	pub noise: NoiseNode,
+	pub octaves: u32,
+	/// Frequency multiplier between successive octaves
+	pub lacunarity: f64,
+	/// Amplitude multiplier between successive octaves
+	pub gain: f64,
+}
+
+impl FractalNoiseNode {
+	pub fn new(noise: NoiseNode, octaves: u32) -> Self {
+		Self {
+			noise,
+			octaves,
+			lacunarity: 2.,
+			gain: 0.5,
+		}
+	}
+
+	/// The number of octaves which produce details larger than the given pixel size.
+	pub fn visible_octaves(&self, pixel_size: f64) -> u32 {
+		let mut scale = self.noise.scale;
+		let mut octaves = 0;
+		while octaves < self.octaves && (octaves == 0 || scale >= pixel_size) {
+			octaves += 1;
+			scale /= self.lacunarity.max(1. + f64::EPSILON);
+		}
+		octaves
+	}
+
+	pub fn sample(&self, x: f64, y: f64, octaves: u32) -> f64 {
+		let (mut noise, mut amplitude, mut total_amplitude, mut value) = (self.noise, 1., 0., 0.);
+		for octave in 0..octaves {
+			value += noise.sample(x, y) * amplitude;
+			total_amplitude += amplitude;
+			amplitude *= self.gain;
+			noise.scale /= self.lacunarity;
+			noise.seed = self.noise.seed.wrapping_add(octave + 1);
+		}
+		if total_amplitude > 0. {
+			value / total_amplitude
+		} else {
+			0.
+		}
+	}
+}
+
+impl<'n> Node<NoiseRegion> for &'n FractalNoiseNode {
+	type Output = Image;
+	fn eval(self, region: NoiseRegion) -> Self::Output {
+		let octaves = self.visible_octaves(region.pixel_size());
+		render_region(region, |x, y| self.sample(x, y, octaves))
+	}
+}
+
+impl<'n> Node<Footprint> for &'n FractalNoiseNode {
+	type Output = Image;
+	fn eval(self, footprint: Footprint) -> Self::Output {
+		let octaves = self.visible_octaves(footprint.pixel_size());
+		render_footprint(footprint, |x, y| self.sample(x, y, octaves))
+	}
+}
+
+fn render_region(region: NoiseRegion, sample: impl Fn(f64, f64) -> f64) -> Image {
+	let (step_x, step_y) = (region.size.0 / region.width.max(1) as f64, region.size.1 / region.height.max(1) as f64);
+	// Sample at the center of each pixel
+	render_pixels(region.width, region.height, |x, y| {
+		sample(region.origin.0 + (x as f64 + 0.5) * step_x, region.origin.1 + (y as f64 + 0.5) * step_y)
+	})
+}
+
+fn render_footprint(footprint: Footprint, sample: impl Fn(f64, f64) -> f64) -> Image {
+	let pixel_to_document = footprint.transform.inverse();
+	render_pixels(footprint.resolution.x, footprint.resolution.y, |x, y| {
+		let position = pixel_to_document.transform_point2((x as f64 + 0.5, y as f64 + 0.5).into());
+		sample(position.x, position.y)
+	})
+}
+
+fn render_pixels(width: u32, height: u32, sample: impl Fn(u32, u32) -> f64) -> Image {
+	let data = (0..height)
+		.flat_map(|y| (0..width).map(move |x| (x, y)))
+		.map(|(x, y)| {
+			let value = (sample(x, y) as f32 * 0.5 + 0.5).clamp(0., 1.);
+			Color::from_rgbaf32_unchecked(value, value, value, 1.)
+		})
+		.collect();
+	Image::new(width, height, data)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn fractal_noise_is_resolution_aware() {
+		let fractal = FractalNoiseNode::new(NoiseNode::new(NoiseType::Perlin, 42, 64.), 8);
+		assert_eq!(fractal.visible_octaves(16.), 3);
+		assert_eq!(fractal.visible_octaves(0.01), 8);
+
+		let region = NoiseRegion {
+			origin: (0., 0.),
+			size: (128., 128.),
+			width: 16,
+			height: 16,
+		};
+		let image = (&fractal).eval(region);
+		assert_eq!(image.data.len(), 256);
+		assert_eq!(image.data, (&fractal).eval(region).data);
+	}
+}
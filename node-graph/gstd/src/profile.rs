@@ -0,0 +1,180 @@
+use crate::clock::{Clock, SystemClock};
+use crate::document::NodeId;
+use graphene_core::Node;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Measurements collected for a single node since the profiler was last reset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeStatistics {
+	pub evaluations: u64,
+	/// Time spent evaluating the node, including the time spent evaluating its inputs
+	pub total_time: Duration,
+	pub cache_hits: u64,
+	pub cache_misses: u64,
+}
+
+impl NodeStatistics {
+	pub fn average_time(&self) -> Duration {
+		match self.evaluations {
+			0 => Duration::ZERO,
+			evaluations => Duration::from_nanos((self.total_time.as_nanos() / evaluations as u128) as u64),
+		}
+	}
+}
+
+/// Timings of a node in the form sent to the frontend, which overlays them on the node graph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeTiming {
+	pub node: NodeId,
+	pub evaluations: u64,
+	#[serde(rename = "totalMilliseconds")]
+	pub total_milliseconds: f64,
+	#[serde(rename = "averageMilliseconds")]
+	pub average_milliseconds: f64,
+	#[serde(rename = "cacheHits")]
+	pub cache_hits: u64,
+	#[serde(rename = "cacheMisses")]
+	pub cache_misses: u64,
+}
+
+/// Collects per node evaluation times and cache statistics, to find the nodes which make a document slow.
+pub struct Profiler {
+	statistics: Mutex<HashMap<NodeId, NodeStatistics>>,
+	clock: Box<dyn Clock + Send + Sync>,
+}
+
+impl Default for Profiler {
+	fn default() -> Self {
+		Self::with_clock(SystemClock::default())
+	}
+}
+
+impl Profiler {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// A profiler which measures evaluations with the given clock instead of the system clock.
+	pub fn with_clock(clock: impl Clock + Send + Sync + 'static) -> Self {
+		Self {
+			statistics: Mutex::new(HashMap::new()),
+			clock: Box::new(clock),
+		}
+	}
+
+	fn update(&self, node: NodeId, f: impl FnOnce(&mut NodeStatistics)) {
+		f(self.statistics.lock().expect("profiler mutex poisoned").entry(node).or_default());
+	}
+
+	pub fn record_evaluation(&self, node: NodeId, time: Duration) {
+		self.update(node, |statistics| {
+			statistics.evaluations += 1;
+			statistics.total_time += time;
+		});
+	}
+
+	/// Set the cache statistics of a node, as reported by its cache such as [`crate::memo::MemoNode::cache_statistics`].
+	pub fn record_cache_statistics(&self, node: NodeId, hits: u64, misses: u64) {
+		self.update(node, |statistics| {
+			statistics.cache_hits = hits;
+			statistics.cache_misses = misses;
+		});
+	}
+
+	pub fn statistics(&self, node: NodeId) -> Option<NodeStatistics> {
+		self.statistics.lock().expect("profiler mutex poisoned").get(&node).copied()
+	}
+
+	pub fn reset(&self) {
+		self.statistics.lock().expect("profiler mutex poisoned").clear();
+	}
+
+	/// The timings of all profiled nodes, starting with the node which took the longest in total.
+	pub fn report(&self) -> Vec<NodeTiming> {
+		let statistics = self.statistics.lock().expect("profiler mutex poisoned");
+		let mut report = statistics
+			.iter()
+			.map(|(&node, statistics)| NodeTiming {
+				node,
+				evaluations: statistics.evaluations,
+				total_milliseconds: statistics.total_time.as_secs_f64() * 1000.,
+				average_milliseconds: statistics.average_time().as_secs_f64() * 1000.,
+				cache_hits: statistics.cache_hits,
+				cache_misses: statistics.cache_misses,
+			})
+			.collect::<Vec<_>>();
+		report.sort_by(|a, b| b.total_milliseconds.total_cmp(&a.total_milliseconds).then(a.node.cmp(&b.node)));
+		report
+	}
+}
+
+/// Records the time taken by every evaluation of the inner node in the profiler, under the id of the document node it was created for.
+pub struct ProfileNode<'p, N> {
+	pub node: N,
+	pub id: NodeId,
+	pub profiler: &'p Profiler,
+}
+
+impl<'p, N> ProfileNode<'p, N> {
+	pub const fn new(node: N, id: NodeId, profiler: &'p Profiler) -> Self {
+		Self { node, id, profiler }
+	}
+}
+
+impl<'n, 'p, N, I> Node<I> for &'n ProfileNode<'p, N>
+where
+	&'n N: Node<I>,
+{
+	type Output = <&'n N as Node<I>>::Output;
+	fn eval(self, input: I) -> Self::Output {
+		let start = self.profiler.clock.now();
+		let output = (&self.node).eval(input);
+		self.profiler.record_evaluation(self.id, self.profiler.clock.now().saturating_sub(start));
+		output
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::clock::ManualClock;
+	use graphene_core::generic::FnNode;
+	use std::sync::Arc;
+
+	#[test]
+	fn profile_node_evaluations() {
+		let clock = Arc::new(ManualClock::new());
+		let profiler = Profiler::with_clock(clock.clone());
+		let double = FnNode::new(|x: u32| {
+			clock.advance(Duration::from_millis(x as u64));
+			x * 2
+		});
+		let node = ProfileNode::new(double, 7, &profiler);
+		assert_eq!((&node).eval(2), 4);
+		assert_eq!((&node).eval(3), 6);
+		profiler.record_cache_statistics(7, 1, 1);
+
+		let statistics = profiler.statistics(7).unwrap();
+		assert_eq!(statistics.evaluations, 2);
+		assert_eq!(statistics.total_time, Duration::from_millis(5));
+		assert_eq!(statistics.average_time(), Duration::from_micros(2500));
+		assert_eq!((statistics.cache_hits, statistics.cache_misses), (1, 1));
+		assert_eq!(profiler.report()[0].node, 7);
+
+		profiler.reset();
+		assert!(profiler.report().is_empty());
+	}
+
+	#[test]
+	fn average_time_of_many_evaluations() {
+		let statistics = NodeStatistics {
+			evaluations: u32::MAX as u64 + 1,
+			total_time: Duration::from_secs(1 << 32),
+			..Default::default()
+		};
+		assert_eq!(statistics.average_time(), Duration::from_secs(1));
+	}
+}
@@ -0,0 +1,156 @@
+use crate::clock::{Clock, SystemClock};
+use crate::footprint::Footprint;
+use crate::raster::Image;
+use glam::{DAffine2, DVec2, UVec2};
+use graphene_core::Node;
+use std::cell::Cell;
+use std::time::Duration;
+
+/// One stage of a progressive render, shown stretched over the whole footprint until the next stage replaces it.
+#[derive(Clone)]
+pub struct RenderStage {
+	pub image: Image,
+	/// The number of footprint pixels covered by each pixel of the image along either axis
+	pub downscale: u32,
+	/// Whether this is the full resolution result, after which no stages follow
+	pub is_final: bool,
+}
+
+/// Renders a raster graph in stages of increasing resolution, so a preview can be shown within the frame budget while the graph is too slow to render at full resolution every frame.
+///
+/// The render time of a stage is estimated from the earlier stages by its number of pixels.
+/// The first stage uses the finest resolution expected to fit in the budget, and every later stage doubles the resolution until it reaches the full resolution.
+pub struct ProgressiveRenderNode<N, C = SystemClock> {
+	pub node: N,
+	pub frame_budget: Duration,
+	/// Measures the render time of each stage
+	clock: C,
+	/// Estimated render time of a single pixel in seconds, or `None` until the first stage is measured
+	seconds_per_pixel: Cell<Option<f64>>,
+}
+
+impl<N> ProgressiveRenderNode<N> {
+	pub fn new(node: N, frame_budget: Duration) -> Self {
+		Self::with_clock(node, frame_budget, SystemClock::default())
+	}
+}
+
+impl<N, C> ProgressiveRenderNode<N, C> {
+	/// The coarsest preview renders one pixel for every 16 by 16 pixels of the footprint.
+	pub const MAX_DOWNSCALE: u32 = 16;
+
+	pub const fn with_clock(node: N, frame_budget: Duration, clock: C) -> Self {
+		Self {
+			node,
+			frame_budget,
+			clock,
+			seconds_per_pixel: Cell::new(None),
+		}
+	}
+
+	/// The downscale of the first stage, which is the coarsest preview if the render time of the graph isn't known yet.
+	fn initial_downscale(&self, footprint: Footprint) -> u32 {
+		let seconds_per_pixel = match self.seconds_per_pixel.get() {
+			Some(seconds_per_pixel) => seconds_per_pixel,
+			None => return Self::MAX_DOWNSCALE,
+		};
+		let pixels = footprint.resolution.x as f64 * footprint.resolution.y as f64;
+		let mut downscale = 1;
+		while downscale < Self::MAX_DOWNSCALE && seconds_per_pixel * pixels / (downscale * downscale) as f64 > self.frame_budget.as_secs_f64() {
+			downscale *= 2;
+		}
+		downscale
+	}
+
+	/// Blends the measured render time of a stage into the estimate, so it follows the graph as its parameters change.
+	fn record(&self, time: Duration, resolution: UVec2) {
+		let pixels = (resolution.x as f64 * resolution.y as f64).max(1.);
+		let measured = time.as_secs_f64() / pixels;
+		let estimate = self.seconds_per_pixel.get().map_or(measured, |estimate| (estimate + measured) / 2.);
+		self.seconds_per_pixel.set(Some(estimate));
+	}
+}
+
+/// The stages of a progressive render, each rendered when the iterator advances.
+///
+/// The caller sends every stage to the frontend and continues on a later frame, or drops the iterator to cancel the render once the parameters change again.
+pub struct RenderStages<'n, N, C = SystemClock> {
+	renderer: &'n ProgressiveRenderNode<N, C>,
+	footprint: Footprint,
+	/// The downscale of the next stage, or `None` after the full resolution stage
+	downscale: Option<u32>,
+}
+
+impl<'n, N, C: Clock> Node<Footprint> for &'n ProgressiveRenderNode<N, C>
+where
+	&'n N: Node<Footprint, Output = Image>,
+{
+	type Output = RenderStages<'n, N, C>;
+	fn eval(self, footprint: Footprint) -> Self::Output {
+		RenderStages {
+			renderer: self,
+			footprint,
+			downscale: Some(self.initial_downscale(footprint)),
+		}
+	}
+}
+
+impl<'n, N, C: Clock> Iterator for RenderStages<'n, N, C>
+where
+	&'n N: Node<Footprint, Output = Image>,
+{
+	type Item = RenderStage;
+	fn next(&mut self) -> Option<Self::Item> {
+		let downscale = self.downscale?;
+		let footprint = Footprint::new(
+			DAffine2::from_scale(DVec2::splat((downscale as f64).recip())) * self.footprint.transform,
+			(self.footprint.resolution + (downscale - 1)) / downscale,
+		);
+
+		let renderer = self.renderer;
+		let start = renderer.clock.now();
+		let image = (&renderer.node).eval(footprint);
+		renderer.record(renderer.clock.now().saturating_sub(start), footprint.resolution);
+
+		self.downscale = (downscale > 1).then_some(downscale / 2);
+		Some(RenderStage {
+			image,
+			downscale,
+			is_final: downscale == 1,
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::clock::ManualClock;
+	use graphene_core::generic::FnNode;
+	use graphene_core::raster::color::Color;
+
+	#[test]
+	fn refine_until_full_resolution() {
+		let clock = ManualClock::new();
+		let slow = FnNode::new(|footprint: Footprint| {
+			clock.advance(Duration::from_micros(footprint.resolution.x as u64 * footprint.resolution.y as u64));
+			Image::new(
+				footprint.resolution.x,
+				footprint.resolution.y,
+				vec![Color::BLACK; (footprint.resolution.x * footprint.resolution.y) as usize],
+			)
+		});
+		let renderer = ProgressiveRenderNode::with_clock(slow, Duration::from_millis(2), &clock);
+		let footprint = Footprint::new(DAffine2::IDENTITY, UVec2::new(100, 60));
+
+		// Without an estimate, the first render starts from the coarsest preview
+		let stages = (&renderer).eval(footprint).collect::<Vec<_>>();
+		assert_eq!(stages.iter().map(|stage| stage.downscale).collect::<Vec<_>>(), [16, 8, 4, 2, 1]);
+		assert_eq!((stages[0].image.width, stages[0].image.height), (7, 4));
+		assert!(stages.last().unwrap().is_final && !stages[0].is_final);
+		assert_eq!((stages[4].image.width, stages[4].image.height), (100, 60));
+
+		// The full resolution takes 6ms, so later renders start at the finest preview which fits in the budget
+		let first = (&renderer).eval(footprint).next().unwrap();
+		assert_eq!(first.downscale, 2);
+	}
+}
@@ -0,0 +1,74 @@
+use graphene_core::Node;
+use rand_chacha::{
+	rand_core::{RngCore, SeedableRng},
+	ChaCha20Rng,
+};
+
+/// Produces reproducible random numbers between `min` and `max`.
+///
+/// The value only depends on the seed of the document, the stable id of the node, and the index given as input,
+/// so scattered or jittered content stays the same across re-evaluations, saves, and reloads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RandomNode {
+	pub seed: u64,
+	pub min: f64,
+	pub max: f64,
+}
+
+impl RandomNode {
+	pub fn new(document_seed: u64, node_id: u64, min: f64, max: f64) -> Self {
+		Self {
+			seed: mix(document_seed, node_id),
+			min,
+			max,
+		}
+	}
+
+	pub fn value(&self, index: u64) -> f64 {
+		let mut rng = ChaCha20Rng::seed_from_u64(mix(self.seed, index));
+		// Use the upper 53 bits to get a uniformly distributed float in [0, 1)
+		let unit = (rng.next_u64() >> 11) as f64 / (1_u64 << 53) as f64;
+		self.min + unit * (self.max - self.min)
+	}
+}
+
+/// Combine two values with the SplitMix64 finalizer, which unlike the hasher of `HashMap` is guaranteed to never change between versions.
+fn mix(a: u64, b: u64) -> u64 {
+	let splitmix = |mut z: u64| {
+		z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^ (z >> 31)
+	};
+	splitmix(a ^ splitmix(b))
+}
+
+impl<'n> Node<u64> for &'n RandomNode {
+	type Output = f64;
+	fn eval(self, index: u64) -> Self::Output {
+		self.value(index)
+	}
+}
+
+impl<'n> Node<()> for &'n RandomNode {
+	type Output = f64;
+	fn eval(self, _: ()) -> Self::Output {
+		self.value(0)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn random_is_reproducible() {
+		let random = RandomNode::new(42, 7, -1., 1.);
+		let values = (0..16).map(|index| (&random).eval(index)).collect::<Vec<_>>();
+
+		assert_eq!(values, (0..16).map(|index| RandomNode::new(42, 7, -1., 1.).value(index)).collect::<Vec<_>>());
+		assert!(values.iter().all(|value| (-1. ..1.).contains(value)));
+		assert_ne!(values[0], values[1]);
+		assert_ne!((&random).eval(()), RandomNode::new(42, 8, -1., 1.).value(0));
+	}
+}
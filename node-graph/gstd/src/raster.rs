@@ -1,10 +1,12 @@
 use core::marker::PhantomData;
+use glam::{DAffine2, DVec2};
 use graphene_core::ops::FlatMapResultNode;
 use graphene_core::raster::color::Color;
 use graphene_core::structural::{ComposeNode, ConsNode};
 use graphene_core::{generic::FnNode, ops::MapResultNode, structural::Then, value::ValueNode, Node};
 use image::Pixel;
 use std::path::Path;
+use std::sync::Arc;
 
 pub struct MapNode<MN: Node<S>, I: IntoIterator<Item = S>, S>(pub MN, PhantomData<(S, I)>);
 
@@ -26,11 +28,7 @@ pub struct MapImageNode<MN: Node<Color, Output = Color> + Copy>(pub MN);
 impl<'n, MN: Node<Color, Output = Color> + Copy> Node<Image> for &'n MapImageNode<MN> {
 	type Output = Image;
 	fn eval(self, input: Image) -> Self::Output {
-		Image {
-			width: input.width,
-			height: input.height,
-			data: input.data.iter().map(|x| self.0.eval(*x)).collect(),
-		}
+		Image::new(input.width, input.height, input.data.iter().map(|x| self.0.eval(*x)).collect())
 	}
 }
 
@@ -40,6 +38,361 @@ impl<MN: Node<Color, Output = Color> + Copy> MapImageNode<MN> {
 	}
 }
 
+fn map_band<MN: Node<Color, Output = Color> + Copy>(node: MN, band: &mut [Color]) {
+	band.iter_mut().for_each(|color| *color = node.eval(*color));
+}
+
+/// Like [`MapImageNode`], but splits the image into bands of pixels which are processed concurrently on the rayon thread pool.
+///
+/// The image buffer is modified in place so each thread only borrows its own band instead of cloning the pixel data,
+/// unless the buffer is shared with another image in which case it is copied once.
+/// Targets without thread support, such as wasm, process the whole image at once.
+pub struct ParallelMapImageNode<MN: Node<Color, Output = Color> + Copy + Send>(pub MN);
+
+impl<'n, MN: Node<Color, Output = Color> + Copy + Send> Node<Image> for &'n ParallelMapImageNode<MN> {
+	type Output = Image;
+	fn eval(self, mut input: Image) -> Self::Output {
+		#[cfg(not(target_arch = "wasm32"))]
+		{
+			use rayon::prelude::*;
+
+			let data = input.data_mut();
+			let band_size = (data.len() / rayon::current_num_threads()).max(1);
+			data.par_chunks_mut(band_size).for_each_with(self.0, |node, band| map_band(*node, band));
+		}
+		#[cfg(target_arch = "wasm32")]
+		map_band(self.0, input.data_mut());
+
+		input
+	}
+}
+
+impl<MN: Node<Color, Output = Color> + Copy + Send> ParallelMapImageNode<MN> {
+	pub const fn new(mn: MN) -> Self {
+		ParallelMapImageNode(mn)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlurQuality {
+	Low,
+	Medium,
+	High,
+}
+
+impl BlurQuality {
+	/// How far the kernel extends, in standard deviations
+	fn kernel_extent(self) -> f32 {
+		match self {
+			BlurQuality::Low => 1.5,
+			BlurQuality::Medium => 2.,
+			BlurQuality::High => 3.,
+		}
+	}
+}
+
+/// Blurs an image with a gaussian kernel, applied as a separate horizontal and vertical pass.
+///
+/// Colors are premultiplied by their alpha while blurring so transparent pixels don't bleed their color into the result.
+/// Pixels outside of the image repeat the closest edge pixel.
+/// With the `gpu` feature, `GpuBlurNode` runs the same passes as compute shaders.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlurNode {
+	/// Radius of the blur in pixels, which corresponds to three standard deviations of the gaussian
+	pub radius: f32,
+	pub quality: BlurQuality,
+}
+
+impl BlurNode {
+	pub const fn new(radius: f32, quality: BlurQuality) -> Self {
+		Self { radius, quality }
+	}
+
+	/// The normalized weights of the pixels from `-half_width` to `half_width` around each pixel.
+	pub(crate) fn kernel(&self) -> Vec<f32> {
+		let sigma = self.radius / 3.;
+		let half_width = (sigma * self.quality.kernel_extent()).ceil() as i32;
+		let kernel = (-half_width..=half_width).map(|x| (-(x * x) as f32 / (2. * sigma * sigma)).exp()).collect::<Vec<_>>();
+		let sum: f32 = kernel.iter().sum();
+		kernel.into_iter().map(|weight| weight / sum).collect()
+	}
+}
+
+fn blur_pass(data: &[[f32; 4]], width: u32, height: u32, kernel: &[f32], horizontal: bool) -> Vec<[f32; 4]> {
+	let half_width = (kernel.len() / 2) as i64;
+	let (width, height) = (width as i64, height as i64);
+	let index = |x: i64, y: i64| (y.clamp(0, height - 1) * width + x.clamp(0, width - 1)) as usize;
+
+	(0..height)
+		.flat_map(|y| (0..width).map(move |x| (x, y)))
+		.map(|(x, y)| {
+			let mut sum = [0.; 4];
+			for (offset, weight) in (-half_width..=half_width).zip(kernel) {
+				let sample = if horizontal { data[index(x + offset, y)] } else { data[index(x, y + offset)] };
+				sum.iter_mut().zip(sample).for_each(|(sum, channel)| *sum += channel * weight);
+			}
+			sum
+		})
+		.collect()
+}
+
+impl<'n> Node<Image> for &'n BlurNode {
+	type Output = Image;
+	fn eval(self, input: Image) -> Self::Output {
+		if self.radius <= 0. || input.width == 0 || input.height == 0 {
+			return input;
+		}
+
+		let premultiplied = input
+			.data
+			.iter()
+			.map(|color| [color.r() * color.a(), color.g() * color.a(), color.b() * color.a(), color.a()])
+			.collect::<Vec<_>>();
+		let kernel = self.kernel();
+		let horizontal = blur_pass(&premultiplied, input.width, input.height, &kernel, true);
+		let blurred = blur_pass(&horizontal, input.width, input.height, &kernel, false);
+
+		let data = blurred
+			.into_iter()
+			.map(|[r, g, b, a]| {
+				let unpremultiply = if a > 0. { a.recip() } else { 0. };
+				Color::from_rgbaf32_unchecked(r * unpremultiply, g * unpremultiply, b * unpremultiply, a)
+			})
+			.collect();
+		Image::new(input.width, input.height, data)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+	Nearest,
+	Bilinear,
+	/// Catmull-Rom interpolation of the surrounding 4x4 pixels, which stays sharper than bilinear filtering when upscaling
+	Bicubic,
+}
+
+/// Determines which pixel is sampled for coordinates outside of the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeMode {
+	/// Tile the image
+	Wrap,
+	/// Repeat the closest edge pixel
+	Clamp,
+	/// Tile the image, flipping every other tile
+	Mirror,
+}
+
+impl EdgeMode {
+	/// Map a pixel coordinate onto a pixel within `0..size`.
+	fn resolve(self, coordinate: i64, size: i64) -> i64 {
+		match self {
+			EdgeMode::Wrap => coordinate.rem_euclid(size),
+			EdgeMode::Clamp => coordinate.clamp(0, size - 1),
+			EdgeMode::Mirror => {
+				let coordinate = coordinate.rem_euclid(2 * size);
+				if coordinate < size {
+					coordinate
+				} else {
+					2 * size - 1 - coordinate
+				}
+			}
+		}
+	}
+}
+
+fn cubic_weight(x: f32) -> f32 {
+	let x = x.abs();
+	if x <= 1. {
+		1.5 * x * x * x - 2.5 * x * x + 1.
+	} else if x < 2. {
+		-0.5 * x * x * x + 2.5 * x * x - 4. * x + 2.
+	} else {
+		0.
+	}
+}
+
+/// Resamples an image through an affine transform into an image of the given size.
+///
+/// Pixels are filtered with premultiplied alpha, so transparent pixels don't bleed their color into the result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleImageNode {
+	/// Transformation from the pixels of the input image to the pixels of the output image
+	pub transform: DAffine2,
+	pub width: u32,
+	pub height: u32,
+	pub filter: Filter,
+	pub edge_mode: EdgeMode,
+}
+
+impl SampleImageNode {
+	pub const fn new(transform: DAffine2, width: u32, height: u32, filter: Filter, edge_mode: EdgeMode) -> Self {
+		Self {
+			transform,
+			width,
+			height,
+			filter,
+			edge_mode,
+		}
+	}
+
+	/// Sample the image at a position in pixel space, where pixel centers lie at integer coordinates, returning a premultiplied color.
+	fn sample(&self, image: &Image, position: DVec2) -> [f32; 4] {
+		let (width, height) = (image.width as i64, image.height as i64);
+		let pixel = |x: i64, y: i64| {
+			let color = image.data[(self.edge_mode.resolve(y, height) * width + self.edge_mode.resolve(x, width)) as usize];
+			[color.r() * color.a(), color.g() * color.a(), color.b() * color.a(), color.a()]
+		};
+		let weighted_sum = |taps: &mut dyn Iterator<Item = (i64, i64, f32)>| {
+			let mut sum = [0.; 4];
+			for (x, y, weight) in taps {
+				sum.iter_mut().zip(pixel(x, y)).for_each(|(sum, channel)| *sum += channel * weight);
+			}
+			sum
+		};
+
+		let (x0, y0) = (position.x.floor() as i64, position.y.floor() as i64);
+		let (tx, ty) = ((position.x - position.x.floor()) as f32, (position.y - position.y.floor()) as f32);
+		match self.filter {
+			Filter::Nearest => pixel(position.x.round() as i64, position.y.round() as i64),
+			Filter::Bilinear => weighted_sum(&mut (0..2).flat_map(|j| (0..2).map(move |i| (x0 + i, y0 + j, (1. - (i as f32 - tx).abs()) * (1. - (j as f32 - ty).abs()))))),
+			Filter::Bicubic => weighted_sum(&mut (-1..3).flat_map(|j| (-1..3).map(move |i| (x0 + i, y0 + j, cubic_weight(i as f32 - tx) * cubic_weight(j as f32 - ty))))),
+		}
+	}
+}
+
+impl<'n> Node<Image> for &'n SampleImageNode {
+	type Output = Image;
+	fn eval(self, input: Image) -> Self::Output {
+		let transparent = Color::from_rgbaf32_unchecked(0., 0., 0., 0.);
+		if input.width == 0 || input.height == 0 || self.transform.matrix2.determinant() == 0. {
+			return Image::new(self.width, self.height, vec![transparent; self.width as usize * self.height as usize]);
+		}
+
+		let output_to_input = self.transform.inverse();
+		let data = (0..self.height)
+			.flat_map(|y| (0..self.width).map(move |x| (x, y)))
+			.map(|(x, y)| {
+				// Sample at the center of each output pixel
+				let position = output_to_input.transform_point2(DVec2::new(x as f64 + 0.5, y as f64 + 0.5)) - DVec2::splat(0.5);
+				let [r, g, b, a] = self.sample(&input, position);
+				// Bicubic filtering can overshoot the range of the surrounding pixels
+				let a = a.clamp(0., 1.);
+				let unpremultiply = if a > 0. { a.recip() } else { 0. };
+				Color::from_rgbaf32_unchecked((r * unpremultiply).clamp(0., 1.), (g * unpremultiply).clamp(0., 1.), (b * unpremultiply).clamp(0., 1.), a)
+			})
+			.collect();
+		Image::new(self.width, self.height, data)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelSpace {
+	/// Red, green, blue, and alpha
+	Rgba,
+	/// Hue, saturation, lightness, and alpha
+	Hsla,
+}
+
+fn rgb_to_hsl(r: f32, g: f32, b: f32) -> [f32; 3] {
+	let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+	let lightness = (max + min) / 2.;
+	let chroma = max - min;
+	if chroma == 0. {
+		return [0., 0., lightness];
+	}
+
+	let saturation = chroma / (1. - (2. * lightness - 1.).abs());
+	let hue = if max == r {
+		((g - b) / chroma).rem_euclid(6.)
+	} else if max == g {
+		(b - r) / chroma + 2.
+	} else {
+		(r - g) / chroma + 4.
+	};
+	[hue / 6., saturation.min(1.), lightness]
+}
+
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+	let amplitude = saturation * lightness.min(1. - lightness);
+	let channel = |offset: f32| {
+		let k = (offset + hue * 12.).rem_euclid(12.);
+		lightness - amplitude * (k - 3.).min(9. - k).clamp(-1., 1.)
+	};
+	[channel(0.), channel(8.), channel(4.)].map(|value| value.clamp(0., 1.))
+}
+
+/// Splits an image into four grayscale images, one per channel of the given channel space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitChannelsNode {
+	pub space: ChannelSpace,
+}
+
+impl SplitChannelsNode {
+	pub const fn new(space: ChannelSpace) -> Self {
+		Self { space }
+	}
+}
+
+impl<'n> Node<Image> for &'n SplitChannelsNode {
+	type Output = [Image; 4];
+	fn eval(self, input: Image) -> Self::Output {
+		let channels = input
+			.data
+			.iter()
+			.map(|color| match self.space {
+				ChannelSpace::Rgba => [color.r(), color.g(), color.b(), color.a()],
+				ChannelSpace::Hsla => {
+					let [hue, saturation, lightness] = rgb_to_hsl(color.r(), color.g(), color.b());
+					[hue, saturation, lightness, color.a()]
+				}
+			})
+			.collect::<Vec<_>>();
+		[0, 1, 2, 3].map(|channel| {
+			let data = channels
+				.iter()
+				.map(|values| Color::from_rgbaf32_unchecked(values[channel], values[channel], values[channel], 1.))
+				.collect();
+			Image::new(input.width, input.height, data)
+		})
+	}
+}
+
+/// Combines four grayscale images into the channels of a single image, the inverse of [`SplitChannelsNode`].
+///
+/// The images are expected to have the same size, the size of the first one is used for the result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergeChannelsNode {
+	pub space: ChannelSpace,
+}
+
+impl MergeChannelsNode {
+	pub const fn new(space: ChannelSpace) -> Self {
+		Self { space }
+	}
+}
+
+impl<'n> Node<[Image; 4]> for &'n MergeChannelsNode {
+	type Output = Image;
+	fn eval(self, channels: [Image; 4]) -> Self::Output {
+		let [first, second, third, alpha] = &channels;
+		let data = first
+			.data
+			.iter()
+			.zip(second.data.iter())
+			.zip(third.data.iter())
+			.zip(alpha.data.iter())
+			.map(|(((first, second), third), alpha)| {
+				let [r, g, b] = match self.space {
+					ChannelSpace::Rgba => [first.r(), second.r(), third.r()],
+					ChannelSpace::Hsla => hsl_to_rgb(first.r(), second.r(), third.r()),
+				};
+				Color::from_rgbaf32_unchecked(r, g, b, alpha.r())
+			})
+			.collect();
+		Image::new(first.width, first.height, data)
+	}
+}
+
 #[derive(Debug)]
 pub enum Error {
 	IO(std::io::Error),
@@ -86,18 +439,37 @@ impl<Reader: std::io::Read> Node<Reader> for BufferNode {
 	}
 }
 
-#[derive(Clone)]
+/// A raster image whose pixel data is reference counted, so images can be passed between nodes and cached without copying their pixels.
+///
+/// The pixel data is copied on write, when it is modified through [`Image::data_mut`] while being shared with another image.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Image {
 	pub width: u32,
 	pub height: u32,
-	pub data: Vec<Color>,
+	pub data: Arc<Vec<Color>>,
+}
+
+impl Image {
+	pub fn new(width: u32, height: u32, data: Vec<Color>) -> Self {
+		Self { width, height, data: Arc::new(data) }
+	}
+
+	/// Mutable access to the pixel data, which copies it first if it is shared with another image.
+	pub fn data_mut(&mut self) -> &mut Vec<Color> {
+		Arc::make_mut(&mut self.data)
+	}
+
+	/// Whether the pixel data is shared with another image, in which case modifying it requires a copy.
+	pub fn is_shared(&self) -> bool {
+		Arc::strong_count(&self.data) > 1
+	}
 }
 
 impl IntoIterator for Image {
 	type Item = Color;
 	type IntoIter = std::vec::IntoIter<Color>;
 	fn into_iter(self) -> Self::IntoIter {
-		self.data.into_iter()
+		Arc::try_unwrap(self.data).unwrap_or_else(|data| data.as_ref().clone()).into_iter()
 	}
 }
 
@@ -129,11 +501,7 @@ pub fn image_node<'n, P: AsRef<Path> + 'n>() -> impl Node<P, Output = Result<Ima
 				Color::from_rgbaf32(c[0], c[1], c[2], c[3]).unwrap()
 			})
 			.collect();
-		Image {
-			width: image.width(),
-			height: image.height(),
-			data,
-		}
+		Image::new(image.width(), image.height(), data)
 	});
 
 	image.then(MapResultNode::new(convert_image))
@@ -167,6 +535,70 @@ mod test {
 		assert_eq!(values[0], Color::from_rgbaf32(0.33333334, 0.33333334, 0.33333334, 1.0).unwrap());
 	}
 
+	#[test]
+	fn parallel_map_image() {
+		let image = Image::new(5, 7, (0..35).map(|i| Color::from_rgbaf32(i as f32 / 35., 0., 0., 1.).unwrap()).collect());
+		let parallel = (&ParallelMapImageNode::new(GrayscaleNode)).eval(image.clone());
+		let sequential = (&MapImageNode::new(GrayscaleNode)).eval(image);
+		assert_eq!(parallel.data, sequential.data);
+	}
+
+	#[test]
+	fn image_data_is_copied_on_write() {
+		let black = Color::from_rgbaf32(0., 0., 0., 1.).unwrap();
+		let image = Image::new(2, 1, vec![black; 2]);
+		let mut copy = image.clone();
+		assert!(image.is_shared());
+
+		copy.data_mut()[0] = Color::from_rgbaf32(1., 1., 1., 1.).unwrap();
+		assert!(!image.is_shared() && !copy.is_shared());
+		assert_eq!(image.data[0], black);
+	}
+
+	#[test]
+	fn blur_preserves_opaque_color() {
+		let red = Color::from_rgbaf32(1., 0., 0., 1.).unwrap();
+		let transparent = Color::from_rgbaf32(0., 0., 1., 0.).unwrap();
+		let image = Image::new(3, 1, vec![red, transparent, transparent]);
+		let blurred = (&BlurNode::new(3., BlurQuality::High)).eval(image);
+
+		// The transparent blue must not bleed into the red
+		assert!(blurred.data.iter().all(|color| color.b() == 0. && (color.a() == 0. || (color.r() - 1.).abs() < 1e-5)));
+		assert!(blurred.data[0].a() < 1. && blurred.data[2].a() > 0.);
+	}
+
+	#[test]
+	fn sample_image() {
+		let colors = [(1., 0., 0.), (0., 1., 0.), (0., 0., 1.), (1., 1., 1.)].map(|(r, g, b)| Color::from_rgbaf32(r, g, b, 1.).unwrap());
+		let image = Image::new(2, 2, colors.to_vec());
+
+		for filter in [Filter::Nearest, Filter::Bilinear, Filter::Bicubic] {
+			let identity = (&SampleImageNode::new(DAffine2::IDENTITY, 2, 2, filter, EdgeMode::Clamp)).eval(image.clone());
+			assert_eq!(identity.data, image.data);
+		}
+
+		let upscaled = (&SampleImageNode::new(DAffine2::from_scale(DVec2::splat(2.)), 4, 4, Filter::Nearest, EdgeMode::Clamp)).eval(image.clone());
+		assert_eq!(upscaled.data[0..4], [colors[0], colors[0], colors[1], colors[1]]);
+
+		let shifted = (&SampleImageNode::new(DAffine2::from_translation(DVec2::new(1., 0.)), 2, 2, Filter::Nearest, EdgeMode::Wrap)).eval(image);
+		assert_eq!(shifted.data[0..2], [colors[1], colors[0]]);
+	}
+
+	#[test]
+	fn split_and_merge_channels() {
+		let image = Image::new(2, 1, vec![Color::from_rgbaf32(1., 0.5, 0., 1.).unwrap(), Color::from_rgbaf32(0.2, 0.4, 0.6, 0.5).unwrap()]);
+		for space in [ChannelSpace::Rgba, ChannelSpace::Hsla] {
+			let channels = (&SplitChannelsNode::new(space)).eval(image.clone());
+			let merged = (&MergeChannelsNode::new(space)).eval(channels);
+			for (merged, original) in merged.data.iter().zip(image.data.iter()) {
+				let (merged, original) = (merged.components(), original.components());
+				assert!([merged.0 - original.0, merged.1 - original.1, merged.2 - original.2, merged.3 - original.3]
+					.iter()
+					.all(|difference| difference.abs() < 1e-5));
+			}
+		}
+	}
+
 	#[test]
 	fn load_image() {
 		let image = image_node::<&str>();
@@ -0,0 +1,361 @@
+use crate::raster::Image;
+use crate::vector::{from_bezier_subpaths, to_bezier_subpaths};
+use bezier_rs::{ManipulatorGroup, Subpath as BezierSubpath};
+use glam::DVec2;
+use graphene::layers::vector::subpath::Subpath;
+use graphene_core::raster::color::Color;
+use graphene_core::Node;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope, AST};
+use std::cell::Cell;
+use std::fmt::Display;
+use std::rc::Rc;
+
+/// Number of operations a script may perform in a single evaluation before it is aborted, so a runaway loop can not freeze the editor.
+const MAX_OPERATIONS: u64 = 10_000_000;
+/// Largest width and height of images created by scripts, so a single image of 16 byte pixels takes up at most 64 MiB of the wasm32 heap.
+const MAX_IMAGE_SIZE: i64 = 2048;
+/// Number of pixels a script may allocate in a single evaluation, for the images it creates and the shared images it copies by modifying them.
+const MAX_PIXELS: u64 = 2 * (MAX_IMAGE_SIZE * MAX_IMAGE_SIZE) as u64;
+/// Number of path anchors a script may allocate in a single evaluation, for the anchors it adds and the shared paths it copies by modifying them.
+const MAX_ANCHORS: u64 = 1 << 20;
+
+/// A value passed into or returned from a script.
+#[derive(Clone)]
+pub enum ScriptValue {
+	Number(f64),
+	Vector(Subpath),
+	Raster(Image),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptError {
+	Parse(String),
+	/// The script failed while running, or exceeded its resource limits
+	Runtime(String),
+	WrongInputCount {
+		expected: usize,
+		found: usize,
+	},
+	/// The script returned a value which is neither a number, a path, nor an image
+	UnsupportedOutput(String),
+}
+
+impl Display for ScriptError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ScriptError::Parse(error) => write!(f, "The script could not be parsed: {}", error),
+			ScriptError::Runtime(error) => write!(f, "The script failed: {}", error),
+			ScriptError::WrongInputCount { expected, found } => write!(f, "The script takes {} inputs but {} were given", expected, found),
+			ScriptError::UnsupportedOutput(type_name) => write!(f, "The script returned a value of type '{}' instead of a number, path, or image", type_name),
+		}
+	}
+}
+
+/// The pixels and anchors a script may still allocate in the current evaluation, shared by all functions of its engine.
+///
+/// Scripts copy images and paths freely, for example by pushing them into arrays, so copies share their data until one of them is modified.
+/// Modifying a shared copy then counts as allocating all of its data again.
+#[derive(Debug)]
+struct Budget {
+	pixels: Cell<u64>,
+	anchors: Cell<u64>,
+}
+
+impl Default for Budget {
+	fn default() -> Self {
+		Self {
+			pixels: Cell::new(MAX_PIXELS),
+			anchors: Cell::new(MAX_ANCHORS),
+		}
+	}
+}
+
+impl Budget {
+	fn reset(&self) {
+		self.pixels.set(MAX_PIXELS);
+		self.anchors.set(MAX_ANCHORS);
+	}
+
+	fn spend_pixels(&self, pixels: u64) -> Result<(), Box<EvalAltResult>> {
+		spend(&self.pixels, pixels, || format!("The script allocated more than {} pixels", MAX_PIXELS))
+	}
+
+	fn spend_anchors(&self, anchors: u64) -> Result<(), Box<EvalAltResult>> {
+		spend(&self.anchors, anchors, || format!("The script allocated more than {} path anchors", MAX_ANCHORS))
+	}
+}
+
+fn spend(remaining: &Cell<u64>, amount: u64, message: impl FnOnce() -> String) -> Result<(), Box<EvalAltResult>> {
+	let left = remaining.get().checked_sub(amount).ok_or_else(|| runtime_error(message()))?;
+	remaining.set(left);
+	Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+struct PathData {
+	subpaths: Vec<BezierSubpath>,
+	current: Vec<ManipulatorGroup>,
+}
+
+/// Vector path built by a script, with an API similar to a canvas. Its data is shared with its copies until it's modified.
+#[derive(Debug, Clone, Default)]
+struct ScriptPath(Rc<PathData>);
+
+impl ScriptPath {
+	fn anchor_count(&self) -> usize {
+		self.0.subpaths.iter().map(BezierSubpath::len).sum::<usize>() + self.0.current.len()
+	}
+
+	/// Mutable access to the data of the path, which copies it first if it's shared with another path.
+	fn data_mut(&mut self, budget: &Budget) -> Result<&mut PathData, Box<EvalAltResult>> {
+		if Rc::strong_count(&self.0) > 1 {
+			budget.spend_anchors(self.anchor_count() as u64)?;
+		}
+		Ok(Rc::make_mut(&mut self.0))
+	}
+
+	fn finish_subpath(&mut self, closed: bool, budget: &Budget) -> Result<(), Box<EvalAltResult>> {
+		if !self.0.current.is_empty() {
+			let data = self.data_mut(budget)?;
+			data.subpaths.push(BezierSubpath::new(std::mem::take(&mut data.current), closed));
+		}
+		Ok(())
+	}
+
+	fn push_anchor(&mut self, anchor: DVec2, in_handle: Option<DVec2>, budget: &Budget) -> Result<(), Box<EvalAltResult>> {
+		budget.spend_anchors(1)?;
+		self.data_mut(budget)?.current.push(ManipulatorGroup { anchor, in_handle, out_handle: None });
+		Ok(())
+	}
+
+	fn into_subpath(self) -> Subpath {
+		let mut data = Rc::try_unwrap(self.0).unwrap_or_else(|shared| (*shared).clone());
+		if !data.current.is_empty() {
+			data.subpaths.push(BezierSubpath::new(std::mem::take(&mut data.current), false));
+		}
+		from_bezier_subpaths(&data.subpaths)
+	}
+}
+
+fn runtime_error(message: impl Into<String>) -> Box<EvalAltResult> {
+	message.into().into()
+}
+
+/// Scripts can use integer and float literals interchangeably wherever a number is expected.
+fn number(value: &Dynamic) -> Result<f64, Box<EvalAltResult>> {
+	value
+		.as_float()
+		.or_else(|_| value.as_int().map(|value| value as f64))
+		.map_err(|type_name| runtime_error(format!("Expected a number but found '{}'", type_name)))
+}
+
+fn point(x: &Dynamic, y: &Dynamic) -> Result<DVec2, Box<EvalAltResult>> {
+	Ok(DVec2::new(number(x)?, number(y)?))
+}
+
+fn pixel_index(image: &Image, x: i64, y: i64) -> Result<usize, Box<EvalAltResult>> {
+	if x < 0 || y < 0 || x >= image.width as i64 || y >= image.height as i64 {
+		return Err(runtime_error(format!("The pixel ({}, {}) lies outside of the {}x{} image", x, y, image.width, image.height)));
+	}
+	Ok(y as usize * image.width as usize + x as usize)
+}
+
+/// Creates an engine which only exposes pure functions, so scripts can not access the file system, the network, or the document.
+/// The images and paths the functions allocate are counted against the budget, which is reset before every evaluation.
+fn engine(budget: &Rc<Budget>) -> Engine {
+	let mut engine = Engine::new();
+	engine.set_max_operations(MAX_OPERATIONS);
+	engine.set_max_expr_depths(64, 32);
+	engine.set_max_call_levels(64);
+	engine.set_max_string_size(1 << 16);
+	engine.set_max_array_size(1 << 20);
+	engine.set_max_map_size(1 << 16);
+	engine.disable_symbol("eval");
+	engine.on_print(|_| {});
+	engine.on_debug(|_, _, _| {});
+
+	engine.register_type_with_name::<ScriptPath>("Path");
+	engine.register_fn("path", ScriptPath::default);
+	let path_budget = budget.clone();
+	engine.register_fn("move_to", move |path: &mut ScriptPath, x: Dynamic, y: Dynamic| {
+		path.finish_subpath(false, &path_budget)?;
+		path.push_anchor(point(&x, &y)?, None, &path_budget)
+	});
+	let path_budget = budget.clone();
+	engine.register_fn("line_to", move |path: &mut ScriptPath, x: Dynamic, y: Dynamic| path.push_anchor(point(&x, &y)?, None, &path_budget));
+	let path_budget = budget.clone();
+	engine.register_fn("cubic_to", move |path: &mut ScriptPath, x1: Dynamic, y1: Dynamic, x2: Dynamic, y2: Dynamic, x: Dynamic, y: Dynamic| {
+		if path.0.current.is_empty() {
+			return Err(runtime_error("cubic_to requires a move_to first"));
+		}
+		let out_handle = point(&x1, &y1)?;
+		if let Some(previous) = path.data_mut(&path_budget)?.current.last_mut() {
+			previous.out_handle = Some(out_handle);
+		}
+		path.push_anchor(point(&x, &y)?, Some(point(&x2, &y2)?), &path_budget)
+	});
+	let path_budget = budget.clone();
+	engine.register_fn("close", move |path: &mut ScriptPath| path.finish_subpath(true, &path_budget));
+
+	engine.register_type_with_name::<Image>("Image");
+	let image_budget = budget.clone();
+	engine.register_fn("image", move |width: i64, height: i64| {
+		if !(1..=MAX_IMAGE_SIZE).contains(&width) || !(1..=MAX_IMAGE_SIZE).contains(&height) {
+			return Err(runtime_error(format!("Images must be between 1 and {} pixels wide and high", MAX_IMAGE_SIZE)));
+		}
+		image_budget.spend_pixels((width * height) as u64)?;
+		let data = vec![Color::from_rgbaf32_unchecked(0., 0., 0., 0.); (width * height) as usize];
+		Ok(Image::new(width as u32, height as u32, data))
+	});
+	engine.register_get("width", |image: &mut Image| image.width as i64);
+	engine.register_get("height", |image: &mut Image| image.height as i64);
+	engine.register_fn("get_pixel", |image: &mut Image, x: i64, y: i64| {
+		let color = image.data[pixel_index(image, x, y)?];
+		Ok::<_, Box<EvalAltResult>>(
+			[color.r(), color.g(), color.b(), color.a()]
+				.into_iter()
+				.map(|channel| Dynamic::from_float(channel as f64))
+				.collect::<Array>(),
+		)
+	});
+	let image_budget = budget.clone();
+	engine.register_fn("set_pixel", move |image: &mut Image, x: i64, y: i64, red: Dynamic, green: Dynamic, blue: Dynamic, alpha: Dynamic| {
+		let index = pixel_index(image, x, y)?;
+		if image.is_shared() {
+			image_budget.spend_pixels(image.data.len() as u64)?;
+		}
+		let channel = |value: &Dynamic| number(value).map(|value| value.clamp(0., 1.) as f32);
+		image.data_mut()[index] = Color::from_rgbaf32_unchecked(channel(&red)?, channel(&green)?, channel(&blue)?, channel(&alpha)?);
+		Ok::<_, Box<EvalAltResult>>(())
+	});
+
+	engine
+}
+
+/// Runs a small script written in [Rhai](https://rhai.rs), for prototyping effects without writing a node in Rust.
+///
+/// Each input is available to the script as a constant with the corresponding name: numbers as floats, vector data as a `Path`, and raster data as an `Image`.
+/// The value of the last expression of the script becomes the output of the node, which has to be a number, a `Path`, or an `Image`.
+///
+/// Paths are built with `path()`, `move_to(x, y)`, `line_to(x, y)`, `cubic_to(x1, y1, x2, y2, x, y)`, and `close()`.
+/// Images are created with `image(width, height)`, and accessed with `width`, `height`, `get_pixel(x, y)` returning `[r, g, b, a]`, and `set_pixel(x, y, r, g, b, a)`.
+///
+/// Each evaluation may allocate a limited number of pixels and path anchors in total, after which the script fails.
+pub struct ScriptNode {
+	engine: Engine,
+	ast: AST,
+	inputs: Vec<String>,
+	budget: Rc<Budget>,
+}
+
+impl ScriptNode {
+	/// Parse the script once, with the names under which the inputs of the node are exposed to it.
+	pub fn new(source: &str, inputs: Vec<String>) -> Result<Self, ScriptError> {
+		let budget = Rc::new(Budget::default());
+		let engine = engine(&budget);
+		let ast = engine.compile(source).map_err(|error| ScriptError::Parse(error.to_string()))?;
+		Ok(Self { engine, ast, inputs, budget })
+	}
+
+	pub fn inputs(&self) -> &[String] {
+		&self.inputs
+	}
+}
+
+impl<'n, 'i> Node<&'i [ScriptValue]> for &'n ScriptNode {
+	type Output = Result<ScriptValue, ScriptError>;
+	fn eval(self, input: &'i [ScriptValue]) -> Self::Output {
+		if input.len() != self.inputs.len() {
+			return Err(ScriptError::WrongInputCount {
+				expected: self.inputs.len(),
+				found: input.len(),
+			});
+		}
+
+		let mut scope = Scope::new();
+		for (name, value) in self.inputs.iter().zip(input) {
+			match value {
+				ScriptValue::Number(number) => scope.push_constant(name.as_str(), *number),
+				ScriptValue::Vector(path) => scope.push_constant(
+					name.as_str(),
+					ScriptPath(Rc::new(PathData {
+						subpaths: to_bezier_subpaths(path),
+						current: Vec::new(),
+					})),
+				),
+				ScriptValue::Raster(image) => scope.push_constant(name.as_str(), image.clone()),
+			};
+		}
+
+		self.budget.reset();
+		let output = self
+			.engine
+			.eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+			.map_err(|error| ScriptError::Runtime(error.to_string()))?;
+		if let Ok(number) = number(&output) {
+			return Ok(ScriptValue::Number(number));
+		}
+		let type_name = output.type_name().to_string();
+		if let Some(path) = output.clone().try_cast::<ScriptPath>() {
+			return Ok(ScriptValue::Vector(path.into_subpath()));
+		}
+		output.try_cast::<Image>().map(ScriptValue::Raster).ok_or(ScriptError::UnsupportedOutput(type_name))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn run_script() {
+		let node = ScriptNode::new("radius * 2 + 1", vec!["radius".into()]).unwrap();
+		assert!(matches!((&node).eval(&[ScriptValue::Number(3.)][..]), Ok(ScriptValue::Number(number)) if number == 7.));
+
+		let node = ScriptNode::new("let p = path(); p.move_to(0, 0); p.line_to(size, 0); p.line_to(size, size); p.close(); p", vec!["size".into()]).unwrap();
+		match (&node).eval(&[ScriptValue::Number(10.)][..]) {
+			Ok(ScriptValue::Vector(path)) => assert_eq!(to_bezier_subpaths(&path)[0].manipulator_groups().len(), 3),
+			_ => panic!("The script should return a path"),
+		}
+
+		let node = ScriptNode::new("let i = image(2, 2); i.set_pixel(1, 1, 1, 0, 0, 1); i", vec![]).unwrap();
+		match (&node).eval(&[][..]) {
+			Ok(ScriptValue::Raster(image)) => assert_eq!(image.data[3], Color::from_rgbaf32_unchecked(1., 0., 0., 1.)),
+			_ => panic!("The script should return an image"),
+		}
+
+		let node = ScriptNode::new("loop {}", vec![]).unwrap();
+		assert!(matches!((&node).eval(&[][..]), Err(ScriptError::Runtime(_))));
+		assert!(matches!(ScriptNode::new("let = 1", vec![]), Err(ScriptError::Parse(_))));
+	}
+
+	#[test]
+	fn allocations_share_one_budget() {
+		// Each image is within the size limit, but together they exceed the budget of the evaluation
+		let node = ScriptNode::new("let images = []; for i in 0..3 { images.push(image(2048, 2048)); } images.len()", vec![]).unwrap();
+		assert!(matches!((&node).eval(&[][..]), Err(ScriptError::Runtime(_))));
+
+		// Copies share their pixels until they are modified
+		let node = ScriptNode::new(
+			"let i = image(2048, 2048); let copies = []; for n in 0..100 { copies.push(i); } copies[0].set_pixel(0, 0, 1, 1, 1, 1); 1",
+			vec![],
+		)
+		.unwrap();
+		assert!(matches!((&node).eval(&[][..]), Ok(ScriptValue::Number(_))));
+		// Modifying each of them copies its pixels, which adds up
+		let node = ScriptNode::new(
+			"let i = image(2048, 2048); let copies = []; for n in 0..3 { copies.push(i); } for c in copies { c.set_pixel(0, 0, 1, 1, 1, 1); } 1",
+			vec![],
+		)
+		.unwrap();
+		assert!(matches!((&node).eval(&[][..]), Err(ScriptError::Runtime(_))));
+
+		let node = ScriptNode::new("let p = path(); p.move_to(0, 0); loop { p.line_to(1, 1); }", vec![]).unwrap();
+		assert!(matches!((&node).eval(&[][..]), Err(ScriptError::Runtime(error)) if error.contains("anchors")));
+
+		// The budget is restored for the next evaluation
+		let node = ScriptNode::new("image(2048, 2048).width + image(2048, 2048).width", vec![]).unwrap();
+		assert!(matches!((&node).eval(&[][..]), Ok(ScriptValue::Number(number)) if number == 4096.));
+		assert!(matches!((&node).eval(&[][..]), Ok(ScriptValue::Number(number)) if number == 4096.));
+	}
+}
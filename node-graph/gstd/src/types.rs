@@ -0,0 +1,256 @@
+use crate::document::{DocumentNodeImplementation, NodeId, NodeInput, NodeNetwork};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+	/// A concrete rust type, identified by its type name
+	Concrete(String),
+	/// A type variable which is resolved by inference
+	Generic(String),
+	Vec(Box<Type>),
+	Tuple(Vec<Type>),
+	/// A node passed as a value, which maps its input type to its output type
+	Fn(Box<Type>, Box<Type>),
+}
+
+impl Type {
+	pub fn concrete<T>() -> Self {
+		Type::Concrete(std::any::type_name::<T>().into())
+	}
+
+	pub fn generic(name: &str) -> Self {
+		Type::Generic(name.into())
+	}
+
+	fn map_generics(&self, f: &impl Fn(&str) -> Type) -> Type {
+		match self {
+			Type::Concrete(_) => self.clone(),
+			Type::Generic(name) => f(name),
+			Type::Vec(inner) => Type::Vec(Box::new(inner.map_generics(f))),
+			Type::Tuple(types) => Type::Tuple(types.iter().map(|t| t.map_generics(f)).collect()),
+			Type::Fn(input, output) => Type::Fn(Box::new(input.map_generics(f)), Box::new(output.map_generics(f))),
+		}
+	}
+
+	fn contains_generic(&self, name: &str) -> bool {
+		match self {
+			Type::Concrete(_) => false,
+			Type::Generic(other) => other == name,
+			Type::Vec(inner) => inner.contains_generic(name),
+			Type::Tuple(types) => types.iter().any(|t| t.contains_generic(name)),
+			Type::Fn(input, output) => input.contains_generic(name) || output.contains_generic(name),
+		}
+	}
+}
+
+impl Display for Type {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Type::Concrete(name) | Type::Generic(name) => write!(f, "{}", name),
+			Type::Vec(inner) => write!(f, "Vec<{}>", inner),
+			Type::Tuple(types) => write!(f, "({})", types.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")),
+			Type::Fn(input, output) => write!(f, "Fn({}) -> {}", input, output),
+		}
+	}
+}
+
+/// The type of a proto node's primary input, its parameters, and its output. Generic types are shared between them by name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeSignature {
+	pub input: Type,
+	pub parameters: Vec<Type>,
+	pub output: Type,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeError {
+	UnknownNode {
+		node: NodeId,
+		name: String,
+	},
+	/// The network has to be flattened before its types can be inferred
+	NotFlattened {
+		node: NodeId,
+	},
+	Mismatch {
+		node: NodeId,
+		input: usize,
+		expected: Type,
+		found: Type,
+	},
+}
+
+impl Display for TypeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			TypeError::UnknownNode { node, name } => write!(f, "Node {} uses the unknown implementation '{}'", node, name),
+			TypeError::NotFlattened { node } => write!(f, "Node {} contains a nested network which has to be flattened first", node),
+			TypeError::Mismatch { node, input, expected, found } => write!(f, "Input {} of node {} expects a value of type {} but was given {}", input, node, expected, found),
+		}
+	}
+}
+
+/// Substitutions of generic types found so far, used to unify types.
+#[derive(Default)]
+struct Substitutions(HashMap<String, Type>);
+
+impl Substitutions {
+	fn resolve(&self, t: &Type) -> Type {
+		t.map_generics(&|name| match self.0.get(name) {
+			Some(substitution) => self.resolve(substitution),
+			None => Type::Generic(name.into()),
+		})
+	}
+
+	fn unify(&mut self, a: &Type, b: &Type) -> bool {
+		match (self.resolve(a), self.resolve(b)) {
+			(Type::Generic(a), Type::Generic(b)) if a == b => true,
+			(Type::Generic(name), other) | (other, Type::Generic(name)) => {
+				// Refuse infinite types such as `T = Vec<T>`
+				if other.contains_generic(&name) {
+					return false;
+				}
+				self.0.insert(name, other);
+				true
+			}
+			(Type::Concrete(a), Type::Concrete(b)) => a == b,
+			(Type::Vec(a), Type::Vec(b)) => self.unify(&a, &b),
+			(Type::Tuple(a), Type::Tuple(b)) => a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| self.unify(a, b)),
+			(Type::Fn(a_input, a_output), Type::Fn(b_input, b_output)) => self.unify(&a_input, &b_input) && self.unify(&a_output, &b_output),
+			_ => false,
+		}
+	}
+}
+
+/// Resolve the concrete output type of every node of a flattened network, given the signatures of the available proto nodes.
+///
+/// The generics of each signature are instantiated separately for every node, so the same generic node can be used with different types.
+pub fn infer_types(network: &NodeNetwork, signatures: &HashMap<String, NodeSignature>) -> Result<HashMap<NodeId, Type>, TypeError> {
+	let mut ids = network.nodes.keys().copied().collect::<Vec<_>>();
+	ids.sort_unstable();
+
+	let mut instances = HashMap::new();
+	for &id in &ids {
+		let name = match &network.nodes[&id].implementation {
+			DocumentNodeImplementation::ProtoNode(proto_node) => &proto_node.name,
+			_ => return Err(TypeError::NotFlattened { node: id }),
+		};
+		let signature = signatures.get(name).ok_or_else(|| TypeError::UnknownNode { node: id, name: name.clone() })?;
+		let instantiate = |t: &Type| t.map_generics(&|generic| Type::Generic(format!("{}#{}", generic, id)));
+		let instance = NodeSignature {
+			input: instantiate(&signature.input),
+			parameters: signature.parameters.iter().map(instantiate).collect(),
+			output: instantiate(&signature.output),
+		};
+		instances.insert(id, instance);
+	}
+
+	let mut substitutions = Substitutions::default();
+	for &id in &ids {
+		let instance = &instances[&id];
+		for (index, input) in network.nodes[&id].inputs.iter().enumerate() {
+			let expected = if index == 0 { Some(&instance.input) } else { instance.parameters.get(index - 1) };
+			let found = match input {
+				NodeInput::Node(source) => instances.get(source).map(|source| source.output.clone()),
+				NodeInput::Lambda(source) => instances.get(source).map(|source| Type::Fn(Box::new(source.input.clone()), Box::new(source.output.clone()))),
+				NodeInput::Value(value) => Some(Type::Concrete(value.type_name().into())),
+				NodeInput::Network => None,
+			};
+			if let (Some(expected), Some(found)) = (expected, found) {
+				if !substitutions.unify(expected, &found) {
+					return Err(TypeError::Mismatch {
+						node: id,
+						input: index,
+						expected: substitutions.resolve(expected),
+						found: substitutions.resolve(&found),
+					});
+				}
+			}
+		}
+	}
+
+	Ok(instances.into_iter().map(|(id, instance)| (id, substitutions.resolve(&instance.output))).collect())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::document::{DocumentNode, IntoValue, ProtoNode};
+
+	fn signatures() -> HashMap<String, NodeSignature> {
+		let t = Type::generic("T");
+		[
+			(
+				"value",
+				NodeSignature {
+					input: t.clone(),
+					parameters: vec![],
+					output: t.clone(),
+				},
+			),
+			(
+				"cons",
+				NodeSignature {
+					input: t.clone(),
+					parameters: vec![Type::generic("U")],
+					output: Type::Tuple(vec![t.clone(), Type::generic("U")]),
+				},
+			),
+			(
+				"add",
+				NodeSignature {
+					input: Type::Tuple(vec![t.clone(), t.clone()]),
+					parameters: vec![],
+					output: t.clone(),
+				},
+			),
+			(
+				"sum",
+				NodeSignature {
+					input: Type::Vec(Box::new(t.clone())),
+					parameters: vec![],
+					output: t,
+				},
+			),
+		]
+		.into_iter()
+		.map(|(name, signature)| (name.to_string(), signature))
+		.collect()
+	}
+
+	fn node(name: &str, inputs: Vec<NodeInput>) -> DocumentNode {
+		DocumentNode {
+			name: name.into(),
+			inputs,
+			implementation: DocumentNodeImplementation::ProtoNode(ProtoNode::unresolved(name.into())),
+		}
+	}
+
+	#[test]
+	fn infer_generic_add() {
+		let mut network = NodeNetwork {
+			inputs: vec![0],
+			output: 2,
+			..Default::default()
+		};
+		network.nodes.insert(0, node("cons", vec![NodeInput::Network, NodeInput::Node(1)]));
+		network.nodes.insert(1, node("value", vec![NodeInput::Value(2_u32.into_any())]));
+		network.nodes.insert(2, node("add", vec![NodeInput::Node(0)]));
+
+		let types = infer_types(&network, &signatures()).unwrap();
+		assert_eq!(types[&2], Type::concrete::<u32>());
+		assert_eq!(types[&0], Type::Tuple(vec![Type::concrete::<u32>(), Type::concrete::<u32>()]));
+
+		network.nodes.insert(3, node("sum", vec![NodeInput::Node(2)]));
+		assert_eq!(
+			infer_types(&network, &signatures()),
+			Err(TypeError::Mismatch {
+				node: 3,
+				input: 0,
+				expected: Type::Vec(Box::new(Type::generic("T#3"))),
+				found: Type::concrete::<u32>(),
+			})
+		);
+	}
+}
@@ -0,0 +1,187 @@
+use crate::raster::Image;
+use bezier_rs::{Join, ManipulatorGroup, Subpath as BezierSubpath};
+use graphene::boolean_ops::{boolean_operation, BooleanOperation, BooleanOperationError};
+use graphene::image_trace::{trace_image, TraceOptions};
+use graphene::layers::shape_layer::ShapeLayer;
+use graphene::layers::style::{Fill, PathStyle};
+use graphene::layers::vector::consts::ManipulatorType;
+use graphene::layers::vector::manipulator_group::ManipulatorGroup as DocumentManipulatorGroup;
+use graphene::layers::vector::subpath::Subpath;
+use graphene_core::Node;
+
+/// Split the vector data of a document into its contours, which are separated by close path commands.
+pub fn to_bezier_subpaths(path: &Subpath) -> Vec<BezierSubpath> {
+	let mut subpaths = Vec::new();
+	let mut manipulator_groups = Vec::new();
+	for group in path.manipulator_groups().iter() {
+		let position = |manipulator_type: ManipulatorType| group.points[manipulator_type].as_ref().map(|point| point.position);
+		match position(ManipulatorType::Anchor) {
+			Some(anchor) => manipulator_groups.push(ManipulatorGroup {
+				anchor,
+				in_handle: position(ManipulatorType::InHandle),
+				out_handle: position(ManipulatorType::OutHandle),
+			}),
+			None if !manipulator_groups.is_empty() => {
+				let closed = manipulator_groups.len() > 1;
+				subpaths.push(BezierSubpath::new(std::mem::take(&mut manipulator_groups), closed));
+			}
+			None => {}
+		}
+	}
+	if !manipulator_groups.is_empty() {
+		subpaths.push(BezierSubpath::new(manipulator_groups, false));
+	}
+	subpaths
+}
+
+/// Combine contours into the vector data of a document. Only the last contour can remain open, as open contours are joined with the following one.
+pub fn from_bezier_subpaths(subpaths: &[BezierSubpath]) -> Subpath {
+	let mut path = Subpath::new();
+	for subpath in subpaths {
+		let manipulator_groups = subpath.manipulator_groups().iter();
+		path.manipulator_groups_mut()
+			.push_range(manipulator_groups.map(|group| DocumentManipulatorGroup::new_with_handles(group.anchor, group.in_handle, group.out_handle)));
+		if subpath.closed() {
+			path.manipulator_groups_mut().push(DocumentManipulatorGroup::closed());
+		}
+	}
+	path
+}
+
+fn map_subpaths(path: &Subpath, map: impl Fn(&BezierSubpath) -> BezierSubpath) -> Subpath {
+	let subpaths = to_bezier_subpaths(path).iter().map(map).collect::<Vec<_>>();
+	from_bezier_subpaths(&subpaths)
+}
+
+/// Offsets every contour of the path by a distance along its normals, connecting the offset curves with the given join.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffsetPathNode {
+	pub distance: f64,
+	pub join: Join,
+}
+
+impl<'n> Node<Subpath> for &'n OffsetPathNode {
+	type Output = Subpath;
+	fn eval(self, path: Subpath) -> Subpath {
+		map_subpaths(&path, |subpath| subpath.offset(self.distance, self.join))
+	}
+}
+
+impl OffsetPathNode {
+	pub const fn new(distance: f64, join: Join) -> Self {
+		Self { distance, join }
+	}
+}
+
+/// Removes anchors of the path as long as the shape deviates from the original by at most the tolerance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimplifyPathNode {
+	pub tolerance: f64,
+}
+
+impl<'n> Node<Subpath> for &'n SimplifyPathNode {
+	type Output = Subpath;
+	fn eval(self, path: Subpath) -> Subpath {
+		map_subpaths(&path, |subpath| subpath.simplify(self.tolerance))
+	}
+}
+
+impl SimplifyPathNode {
+	pub const fn new(tolerance: f64) -> Self {
+		Self { tolerance }
+	}
+}
+
+/// Combines two closed paths with a boolean operation, producing a single path containing all resulting shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BooleanNode {
+	pub operation: BooleanOperation,
+}
+
+impl<'n> Node<(Subpath, Subpath)> for &'n BooleanNode {
+	type Output = Result<Subpath, BooleanOperationError>;
+	fn eval(self, (alpha, beta): (Subpath, Subpath)) -> Self::Output {
+		let mut alpha = ShapeLayer::new(alpha, PathStyle::default());
+		let mut beta = ShapeLayer::new(beta, PathStyle::default());
+		let shapes = boolean_operation(self.operation, &mut alpha, &mut beta)?;
+
+		let mut path = Subpath::new();
+		for shape in shapes {
+			path.manipulator_groups_mut().push_range(shape.shape.manipulator_groups().iter().cloned());
+		}
+		Ok(path)
+	}
+}
+
+impl BooleanNode {
+	pub const fn new(operation: BooleanOperation) -> Self {
+		Self { operation }
+	}
+}
+
+/// Traces an image into filled shapes in pixel coordinates, ordered from the largest region at the bottom to the smallest on top.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceImageNode {
+	pub options: TraceOptions,
+}
+
+impl<'n> Node<Image> for &'n TraceImageNode {
+	type Output = Vec<ShapeLayer>;
+	fn eval(self, image: Image) -> Vec<ShapeLayer> {
+		let pixels = image.data.iter().flat_map(|color| color.to_rgba8()).collect::<Vec<_>>();
+		trace_image(&pixels, image.width, image.height, &self.options)
+			.into_iter()
+			.map(|shape| ShapeLayer::new(shape.path, PathStyle::new(None, Fill::Solid(shape.color))))
+			.collect()
+	}
+}
+
+impl TraceImageNode {
+	pub const fn new(options: TraceOptions) -> Self {
+		Self { options }
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use glam::DVec2;
+
+	#[test]
+	fn offset_rectangle() {
+		let rectangle = Subpath::new_rect(DVec2::new(0., 0.), DVec2::new(10., 10.));
+		assert_eq!(to_bezier_subpaths(&rectangle).len(), 1);
+		assert_eq!(from_bezier_subpaths(&to_bezier_subpaths(&rectangle)).manipulator_groups().len(), 5);
+
+		let offset = (&OffsetPathNode::new(1., Join::Miter)).eval(rectangle);
+		let simplified = to_bezier_subpaths(&(&SimplifyPathNode::new(1e-3)).eval(offset));
+		assert_eq!(simplified.len(), 1);
+		assert_eq!(simplified[0].len(), 4);
+		// The corners of the rectangle are moved outwards diagonally
+		assert!(simplified[0]
+			.manipulator_groups()
+			.iter()
+			.all(|group| (group.anchor - DVec2::splat(5.)).abs().abs_diff_eq(DVec2::splat(6.), 1e-3)));
+	}
+
+	#[test]
+	fn trace_dark_square() {
+		use graphene_core::raster::color::Color;
+
+		// A white 8x8 image with a black 4x4 square in its center
+		let data = (0..64).map(|index| {
+			if (2..6).contains(&(index % 8)) && (2..6).contains(&(index / 8)) {
+				Color::BLACK
+			} else {
+				Color::WHITE
+			}
+		});
+		let image = Image::new(8, 8, data.collect());
+
+		let shapes = (&TraceImageNode::new(TraceOptions::default())).eval(image);
+		assert_eq!(shapes.len(), 1);
+		let [min, max] = shapes[0].shape.bounding_box().unwrap();
+		assert!(min.abs_diff_eq(DVec2::splat(2.), 1e-6));
+		assert!(max.abs_diff_eq(DVec2::splat(6.), 1e-6));
+	}
+}
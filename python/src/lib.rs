@@ -0,0 +1,185 @@
+//! The `graphite` Python module, which opens, edits, and exports Graphite documents through the headless API.
+
+use graphite_api::{ApiError, Color, DVec2, Fill, Font, LayerId, Operation, PathStyle, Subpath};
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+
+/// A color given from Python as a tuple of red, green, blue, and alpha channels between 0 and 1.
+type Rgba = (f32, f32, f32, f32);
+
+fn color((red, green, blue, alpha): Rgba) -> PyResult<Color> {
+	Color::from_rgbaf32(red, green, blue, alpha).ok_or_else(|| PyValueError::new_err("The channels of a color must be between 0 and 1"))
+}
+
+fn api_error(error: ApiError) -> PyErr {
+	PyValueError::new_err(error.to_string())
+}
+
+fn io_error(action: &str, path: &str, error: std::io::Error) -> PyErr {
+	PyIOError::new_err(format!("Could not {} {}: {}", action, path, error))
+}
+
+/// A Graphite document, whose layers are identified by their paths: lists of layer IDs leading from the top of the document through the folders containing the layer.
+#[pyclass(name = "Document", unsendable)]
+struct PyDocument {
+	document: graphite_api::Document,
+}
+
+#[pymethods]
+impl PyDocument {
+	/// Creates an empty document.
+	#[new]
+	fn new() -> Self {
+		Self {
+			document: graphite_api::Document::new(),
+		}
+	}
+
+	/// Opens the `.graphite` file at the given path.
+	#[staticmethod]
+	fn open(path: &str) -> PyResult<Self> {
+		let content = std::fs::read(path).map_err(|error| io_error("read", path, error))?;
+		let document = graphite_api::Document::open(&content).map_err(api_error)?;
+		Ok(Self { document })
+	}
+
+	/// Saves the document as a `.graphite` file at the given path.
+	fn save(&self, path: &str) -> PyResult<()> {
+		std::fs::write(path, self.document.save()).map_err(|error| io_error("write", path, error))
+	}
+
+	/// The paths of all layers from the bottom to the top, with folders before their contents.
+	fn layers(&self) -> Vec<Vec<LayerId>> {
+		self.document.layers()
+	}
+
+	/// The bounding box of all artwork as its top left and bottom right corners, or `None` if the document is empty.
+	fn bounds(&self) -> Option<((f64, f64), (f64, f64))> {
+		self.document.bounds().map(|[min, max]| (min.into(), max.into()))
+	}
+
+	fn layer_name(&self, path: Vec<LayerId>) -> PyResult<Option<String>> {
+		Ok(self.document.layer(&path).map_err(api_error)?.name.clone())
+	}
+
+	fn layer_visible(&self, path: Vec<LayerId>) -> PyResult<bool> {
+		Ok(self.document.layer(&path).map_err(api_error)?.visible)
+	}
+
+	fn layer_opacity(&self, path: Vec<LayerId>) -> PyResult<f64> {
+		Ok(self.document.layer(&path).map_err(api_error)?.opacity)
+	}
+
+	/// The transform of the layer relative to its folder, as the matrix entries `[a, b, c, d, e, f]` like in SVG.
+	fn layer_transform(&self, path: Vec<LayerId>) -> PyResult<Vec<f64>> {
+		Ok(self.document.layer(&path).map_err(api_error)?.transform.to_cols_array().to_vec())
+	}
+
+	fn set_layer_name(&mut self, path: Vec<LayerId>, name: String) -> PyResult<()> {
+		self.document.apply(Operation::SetLayerName { path, name }).map_err(api_error)
+	}
+
+	fn set_layer_visible(&mut self, path: Vec<LayerId>, visible: bool) -> PyResult<()> {
+		self.document.apply(Operation::SetLayerVisibility { path, visible }).map_err(api_error)
+	}
+
+	fn set_layer_opacity(&mut self, path: Vec<LayerId>, opacity: f64) -> PyResult<()> {
+		self.document.apply(Operation::SetLayerOpacity { path, opacity }).map_err(api_error)
+	}
+
+	/// Sets the transform of the layer relative to its folder from the matrix entries `[a, b, c, d, e, f]` like in SVG.
+	fn set_layer_transform(&mut self, path: Vec<LayerId>, transform: Vec<f64>) -> PyResult<()> {
+		let transform = <[f64; 6]>::try_from(transform).map_err(|_| PyValueError::new_err("A transform has 6 entries"))?;
+		self.document.apply(Operation::SetLayerTransform { path, transform }).map_err(api_error)
+	}
+
+	/// Fills the shape or text layer with a solid color.
+	fn set_layer_fill(&mut self, path: Vec<LayerId>, fill: Rgba) -> PyResult<()> {
+		let fill = Fill::solid(color(fill)?);
+		self.document.apply(Operation::SetLayerFill { path, fill }).map_err(api_error)
+	}
+
+	/// Adds a rectangle to the top of the document, returning the path of the new layer.
+	#[args(color = "(0., 0., 0., 1.)")]
+	fn add_rectangle(&mut self, x: f64, y: f64, width: f64, height: f64, color: Rgba) -> PyResult<Vec<LayerId>> {
+		Ok(self.document.add_rectangle(DVec2::new(x, y), DVec2::new(width, height), self::color(color)?))
+	}
+
+	/// Adds an ellipse fitting the given rectangle to the top of the document, returning the path of the new layer.
+	#[args(color = "(0., 0., 0., 1.)")]
+	fn add_ellipse(&mut self, x: f64, y: f64, width: f64, height: f64, color: Rgba) -> PyResult<Vec<LayerId>> {
+		Ok(self.document.add_ellipse(DVec2::new(x, y), DVec2::new(width, height), self::color(color)?))
+	}
+
+	/// Adds a closed polygon through the given points to the top of the document, returning the path of the new layer.
+	#[args(color = "(0., 0., 0., 1.)")]
+	fn add_polygon(&mut self, points: Vec<(f64, f64)>, color: Rgba) -> PyResult<Vec<LayerId>> {
+		let subpath = Subpath::from_points(points.into_iter().map(DVec2::from), true);
+		Ok(self.document.add_shape(subpath, PathStyle::new(None, Fill::solid(self::color(color)?))))
+	}
+
+	/// Adds text with its top left corner at the given position to the top of the document, returning the path of the new layer.
+	/// The text is set in the given font if it was loaded with `load_font`, or otherwise in the default font.
+	#[args(font_family = "\"\"", font_style = "\"\"", color = "(0., 0., 0., 1.)")]
+	fn add_text(&mut self, text: &str, x: f64, y: f64, size: f64, font_family: &str, font_style: &str, color: Rgba) -> PyResult<Vec<LayerId>> {
+		let font = Font::new(font_family.into(), font_style.into());
+		Ok(self.document.add_text(text, &font, size, DVec2::new(x, y), self::color(color)?))
+	}
+
+	/// Adds an empty folder to the top of the document, returning its path.
+	fn add_folder(&mut self) -> Vec<LayerId> {
+		self.document.add_folder()
+	}
+
+	/// Loads the font file at the given path for text layers, which is also used for text in fonts that aren't loaded if it's the default.
+	#[args(default = "false")]
+	fn load_font(&mut self, font_family: &str, font_style: &str, path: &str, default: bool) -> PyResult<()> {
+		let data = std::fs::read(path).map_err(|error| io_error("read", path, error))?;
+		self.document.load_font(font_family, font_style, data, default);
+		Ok(())
+	}
+
+	/// Removes a layer, along with its contents if it's a folder.
+	fn delete_layer(&mut self, path: Vec<LayerId>) -> PyResult<()> {
+		self.document.delete_layer(&path).map_err(api_error)
+	}
+
+	fn undo(&mut self) {
+		self.document.undo();
+	}
+
+	fn redo(&mut self) {
+		self.document.redo();
+	}
+
+	/// Renders all of the artwork as an SVG document.
+	fn render_svg(&mut self) -> String {
+		self.document.render_svg()
+	}
+
+	/// Exports all of the artwork as an SVG file at the given path.
+	fn export_svg(&mut self, path: &str) -> PyResult<()> {
+		std::fs::write(path, self.document.render_svg()).map_err(|error| io_error("write", path, error))
+	}
+
+	/// Exports all of the artwork as a PNG file at the given path, with the given number of pixels per document unit.
+	#[args(scale = "1.")]
+	fn export_png(&mut self, path: &str, scale: f64) -> PyResult<()> {
+		let png = self.document.render_png(scale).map_err(api_error)?;
+		std::fs::write(path, png).map_err(|error| io_error("write", path, error))
+	}
+}
+
+/// Makes the IDs of new layers the same every time the script runs. It has to be called before the first document is created or opened.
+#[pyfunction]
+fn set_seed(seed: u64) {
+	graphite_api::set_seed(seed);
+}
+
+#[pymodule]
+fn graphite(_py: Python, module: &PyModule) -> PyResult<()> {
+	module.add_class::<PyDocument>()?;
+	module.add_function(wrap_pyfunction!(set_seed, module)?)?;
+	Ok(())
+}